@@ -1,14 +1,19 @@
+from typing import Optional
+
 import httpx
 
+from jamspy.client import signing
 from jamspy.client.models import common, http
 
 
 class Client:
-    def __init__(self, base_url: str, timeout: float = 5):
+    def __init__(self, base_url: str, timeout: float = 5, signing_key: Optional[str] = None):
         self.base_url = f'http://{base_url}'
         if base_url.startswith('http://') or base_url.startswith('https://'):
             self.base_url = base_url
         self.client = httpx.AsyncClient(timeout=timeout)
+        # hex encoded signing key of the server, the signatures of the predictions are verified if it is set
+        self.signing_key = bytes.fromhex(signing_key.strip()) if signing_key else None
 
     async def health_check(self) -> None:
         url = f'{self.base_url}/healthcheck'
@@ -25,10 +30,14 @@ class Client:
             model_name=model_name, input=model_input
         ).model_dump()
 
+        request_id = signing.new_request_id()
+
         try:
-            response = await self.client.post(url, json=request)
+            response = await self.client.post(url, json=request, headers={signing.REQUEST_ID_HEADER: request_id})
             if response.status_code != 200:
                 raise Exception(f'predict failed with {response.status_code}')
+            if self.signing_key is not None:
+                self._verify_prediction(request_id, response)
             try:
                 resp_obj = http.PredictResponse.model_validate(response.json())
                 return common.Prediction(resp_obj.output)
@@ -37,6 +46,17 @@ class Client:
         except httpx.ConnectError:
             raise ConnectionError('Could not connect to the server.')
 
+    def _verify_prediction(self, request_id: str, response: httpx.Response) -> None:
+        issued_at = response.headers.get(signing.SIGNATURE_ISSUED_AT_HEADER, '')
+        if not issued_at.isdigit() or not signing.verify_signature(
+            self.signing_key,
+            request_id,
+            int(issued_at),
+            response.content,
+            response.headers.get(signing.SIGNATURE_HEADER, ''),
+        ):
+            raise Exception('predict response signature is missing or does not match its request and body')
+
     async def add_model(self, model_name: str) -> None:
         url = f'{self.base_url}/api/models'
         request = http.AddModelsRequest(model_name=model_name).model_dump()
@@ -0,0 +1,36 @@
+import hashlib
+import hmac
+import uuid
+
+SIGNATURE_HEADER = 'x-jams-signature'
+SIGNATURE_KEY_ID_HEADER = 'x-jams-signature-key-id'
+SIGNATURE_ISSUED_AT_HEADER = 'x-jams-signature-issued-at'
+REQUEST_ID_HEADER = 'x-request-id'
+SIGNATURE_PREFIX = 'sha256='
+SIGNATURE_VERSION = 'jams-signature-v1'
+
+
+def canonical_form(request_id: str, issued_at: int, body: bytes) -> bytes:
+    """Returns the bytes which the server signs for a response: `jams-signature-v1`, the issued-at time and the request
+    ID, each followed by a newline, then the body.
+    """
+    return f'{SIGNATURE_VERSION}\n{issued_at}\n{request_id}\n'.encode() + body
+
+
+def new_request_id() -> str:
+    """Returns a new request ID, which the signature of the response is bound to."""
+    return str(uuid.uuid4())
+
+
+def verify_signature(key: bytes, request_id: str, issued_at: int, body: bytes, signature: str) -> bool:
+    """Verifies the signature of a prediction response of a J.A.M.S server configured with `[config.response_signing]`.
+
+    The signature covers the canonical form of the response, so a response only verifies for the request it answers,
+    i.e. the `x-request-id` sent with the request, and `issued_at` is the `x-jams-signature-issued-at` header. Over
+    HTTP the body is the exact bytes of the response body. The key is the signing key of the server, decoded from hex,
+    e.g. `bytes.fromhex(key)`.
+    """
+    if not signature or not signature.startswith(SIGNATURE_PREFIX):
+        return False
+    expected = hmac.new(key, canonical_form(request_id, issued_at, body), hashlib.sha256).hexdigest()
+    return hmac.compare_digest(expected, signature[len(SIGNATURE_PREFIX):].lower())
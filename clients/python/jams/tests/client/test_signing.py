@@ -0,0 +1,34 @@
+from jamspy.client import signing
+
+# the signature the server returns for the body below, the request ID `request-1` and the issued-at time 1720000000,
+# with a key of 32 0xaa bytes
+SIGNATURE = 'sha256=5a222242c55dbb537987b87283dff028f89eb3302a9a00dde06da36fd29c652a'
+BODY = b'{"output":"{\\"predictions\\":[[0.5]]}"}'
+
+
+def test_successfully_verifies_signed_response() -> None:
+    # Arrange
+    key = bytes.fromhex('aa' * 32)
+
+    # Act
+    verified = signing.verify_signature(key, 'request-1', 1720000000, BODY, SIGNATURE)
+
+    # Assert
+    assert verified
+
+
+def test_fails_to_verify_tampered_replayed_or_unsigned_response() -> None:
+    # Arrange
+    key = bytes.fromhex('aa' * 32)
+
+    # Act
+    tampered = signing.verify_signature(
+        key, 'request-1', 1720000000, b'{"output":"{\\"predictions\\":[[0.9]]}"}', SIGNATURE
+    )
+    replayed = signing.verify_signature(key, 'request-2', 1720000000, BODY, SIGNATURE)
+    unsigned = signing.verify_signature(key, 'request-1', 1720000000, BODY, '')
+
+    # Assert
+    assert not tampered
+    assert not replayed
+    assert not unsigned
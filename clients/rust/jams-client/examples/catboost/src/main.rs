@@ -1,11 +1,13 @@
 use jams_client::http::{ApiClientBuilder, Client};
-use std::fs;
+use std::{env, fs};
 
+// Override with JAMS_HTTP_URL to run against a local stack, e.g. `make e2e`
 const URL: &str = "https://jams-http.onrender.com";
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let http_client = ApiClientBuilder::new(URL.to_string()).build()?;
+    let http_client =
+        ApiClientBuilder::new(env::var("JAMS_HTTP_URL").unwrap_or(URL.to_string())).build()?;
 
     // health check
     http_client.health_check().await?;
@@ -0,0 +1,109 @@
+use jams_client::builder::{ClientBuilder, Protocol};
+use jams_client::common::Client;
+use std::{env, fs, time};
+
+const DEFAULT_HTTP_URL: &str = "0.0.0.0:3000";
+const DEFAULT_GRPC_URL: &str = "0.0.0.0:4000";
+const EXAMPLES_DIR: &str = "clients/rust/jams-client/examples";
+const READY_RETRIES: u32 = 30;
+
+/// A client example run against the stack along with the output shape it is expected to return.
+struct Case {
+    example: &'static str,
+    model_name: &'static str,
+    /// Number of values returned for each input record.
+    output_width: usize,
+}
+
+const CASES: [Case; 4] = [
+    Case {
+        example: "catboost",
+        model_name: "titanic_model",
+        output_width: 1,
+    },
+    Case {
+        example: "tensorflow",
+        model_name: "my_awesome_penguin_model",
+        output_width: 3,
+    },
+    Case {
+        example: "pytorch",
+        model_name: "my_awesome_californiahousing_model",
+        output_width: 1,
+    },
+    Case {
+        example: "lightgbm",
+        model_name: "my_awesome_reg_model",
+        output_width: 1,
+    },
+];
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let http_url = env::var("JAMS_HTTP_URL").unwrap_or(DEFAULT_HTTP_URL.to_string());
+    let grpc_url = env::var("JAMS_GRPC_URL").unwrap_or(DEFAULT_GRPC_URL.to_string());
+
+    for (protocol, url) in [(Protocol::Http, http_url), (Protocol::Grpc, grpc_url)] {
+        let client = connect(protocol, url).await?;
+        for case in CASES.iter() {
+            run_case(client.as_ref(), case).await?;
+            println!("{:?} {} ✅", protocol, case.example);
+        }
+    }
+
+    println!("all end-to-end examples passed ✅");
+    Ok(())
+}
+
+/// Builds a client and waits for the server to be healthy. The servers load all the models on
+/// startup so they might not be accepting requests as soon as the containers are up.
+async fn connect(protocol: Protocol, url: String) -> anyhow::Result<Box<dyn Client>> {
+    for _ in 0..READY_RETRIES {
+        if let Ok(client) = ClientBuilder::new(url.clone())
+            .with_protocol(protocol)
+            .build()
+            .await
+        {
+            if client.health_check().await.is_ok() {
+                return Ok(client);
+            }
+        }
+        tokio::time::sleep(time::Duration::from_secs(2)).await;
+    }
+    anyhow::bail!("{:?} server at {} is not healthy ❌", protocol, url)
+}
+
+async fn run_case(client: &dyn Client, case: &Case) -> anyhow::Result<()> {
+    let payload = fs::read_to_string(format!("{}/{}/request.json", EXAMPLES_DIR, case.example))?;
+    let input: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&payload)?;
+    let num_records = match input.values().next().and_then(|values| values.as_array()) {
+        Some(values) => values.len(),
+        None => anyhow::bail!("{} request has no features ❌", case.example),
+    };
+
+    let predictions = client
+        .predict(case.model_name.to_string(), payload)
+        .await?
+        .to_vec();
+
+    if predictions.len() != num_records {
+        anyhow::bail!(
+            "{} returned {} predictions for {} records ❌",
+            case.example,
+            predictions.len(),
+            num_records
+        )
+    }
+    if let Some(row) = predictions
+        .iter()
+        .find(|row| row.len() != case.output_width)
+    {
+        anyhow::bail!(
+            "{} returned {} values per record but expected {} ❌",
+            case.example,
+            row.len(),
+            case.output_width
+        )
+    }
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use crate::common::Client;
+use crate::tls::TlsConfig;
+use crate::{grpc, http};
+
+/// Transport protocol used to talk to the J.A.M.S server.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Protocol {
+    /// REST API served by the HTTP server (default port 3000).
+    #[default]
+    Http,
+    /// gRPC API served by the gRPC server (default port 4000). Lower latency than HTTP.
+    Grpc,
+}
+
+/// Builds a J.A.M.S client for either protocol behind the common `Client` trait.
+///
+/// # Example
+///
+/// ```no_run
+/// use jams_client::builder::{ClientBuilder, Protocol};
+///
+/// # async fn run() -> anyhow::Result<()> {
+/// let client = ClientBuilder::new("0.0.0.0:4000".to_string())
+///     .with_protocol(Protocol::Grpc)
+///     .with_timeout(2)
+///     .build()
+///     .await?;
+/// client.health_check().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ClientBuilder {
+    base_url: String,
+    protocol: Protocol,
+    timeout: Option<u64>,
+    tls: Option<TlsConfig>,
+    signing_key: Option<String>,
+}
+
+impl ClientBuilder {
+    pub fn new(base_url: String) -> ClientBuilder {
+        ClientBuilder {
+            base_url,
+            protocol: Protocol::default(),
+            timeout: None,
+            tls: None,
+            signing_key: None,
+        }
+    }
+
+    pub fn with_protocol(mut self, protocol: Protocol) -> ClientBuilder {
+        self.protocol = protocol;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: u64) -> ClientBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Verifies the certificate of the server with the given TLS settings, e.g. a private CA bundle or pinned
+    /// certificates, see `TlsConfig`. The base URL must be an `https://` URL.
+    pub fn with_tls(mut self, tls: TlsConfig) -> ClientBuilder {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Verifies the signature of every prediction response with the hex encoded signing key of the server, see
+    /// `signing::verify`. Predictions which are not signed or whose signature does not match fail.
+    pub fn with_signing_key(mut self, signing_key: String) -> ClientBuilder {
+        self.signing_key = Some(signing_key);
+        self
+    }
+
+    /// Creates the client for the selected protocol.
+    ///
+    /// # Errors
+    /// Returns an error if the underlying HTTP client cannot be created, the TLS settings or the
+    /// signing key are invalid or the gRPC server cannot be connected to.
+    pub async fn build(self) -> anyhow::Result<Box<dyn Client>> {
+        match self.protocol {
+            Protocol::Http => {
+                let mut builder = http::ApiClientBuilder::new(self.base_url);
+                if let Some(timeout) = self.timeout {
+                    builder = builder.with_timeout(timeout);
+                }
+                if let Some(tls) = self.tls {
+                    builder = builder.with_tls(tls);
+                }
+                if let Some(signing_key) = self.signing_key {
+                    builder = builder.with_signing_key(signing_key);
+                }
+                Ok(Box::new(builder.build()?))
+            }
+            Protocol::Grpc => {
+                let mut builder = grpc::ApiClientBuilder::new(self.base_url);
+                if let Some(timeout) = self.timeout {
+                    builder = builder.with_timeout(timeout);
+                }
+                if let Some(tls) = self.tls {
+                    builder = builder.with_tls(tls);
+                }
+                if let Some(signing_key) = self.signing_key {
+                    builder = builder.with_signing_key(signing_key);
+                }
+                Ok(Box::new(builder.build().await?))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_defaults_to_http_protocol() {
+        // Arrange
+        let builder = ClientBuilder::new("0.0.0.0:3000".to_string());
+
+        // Act
+        let protocol = builder.protocol;
+
+        // Assert
+        assert_eq!(protocol, Protocol::Http)
+    }
+
+    #[tokio::test]
+    async fn successfully_builds_http_client() {
+        // Arrange
+        let builder = ClientBuilder::new("0.0.0.0:3000".to_string()).with_timeout(2);
+
+        // Act
+        let client = builder.build().await;
+
+        // Assert
+        assert!(client.is_ok())
+    }
+
+    #[tokio::test]
+    async fn fails_to_build_grpc_client_when_server_is_unreachable() {
+        // Arrange
+        let builder = ClientBuilder::new("127.0.0.1:1".to_string())
+            .with_protocol(Protocol::Grpc)
+            .with_timeout(1);
+
+        // Act
+        let client = builder.build().await;
+
+        // Assert
+        assert!(client.is_err())
+    }
+
+    #[tokio::test]
+    async fn fails_to_build_client_with_tls_for_plain_http_url() {
+        // Arrange
+        let builder = ClientBuilder::new("0.0.0.0:3000".to_string()).with_tls(TlsConfig::new());
+
+        // Act
+        let client = builder.build().await;
+
+        // Assert
+        assert!(client.is_err())
+    }
+
+    #[tokio::test]
+    async fn successfully_builds_http_client_with_tls() {
+        // Arrange
+        let builder =
+            ClientBuilder::new("https://jams.internal:3000".to_string()).with_tls(TlsConfig::new());
+
+        // Act
+        let client = builder.build().await;
+
+        // Assert
+        assert!(client.is_ok())
+    }
+
+    #[tokio::test]
+    async fn fails_to_build_grpc_client_with_tls_when_server_is_unreachable() {
+        // Arrange
+        let builder = ClientBuilder::new("https://127.0.0.1:1".to_string())
+            .with_protocol(Protocol::Grpc)
+            .with_timeout(1)
+            .with_tls(TlsConfig::new());
+
+        // Act
+        let client = builder.build().await;
+
+        // Assert
+        assert!(client.is_err())
+    }
+
+    #[tokio::test]
+    async fn fails_to_build_client_with_invalid_signing_key() {
+        // Arrange
+        let builder = ClientBuilder::new("0.0.0.0:3000".to_string())
+            .with_signing_key("not a key".to_string());
+
+        // Act
+        let client = builder.build().await;
+
+        // Assert
+        assert!(client.is_err())
+    }
+
+    #[tokio::test]
+    async fn successfully_builds_http_client_with_signing_key() {
+        // Arrange
+        let builder =
+            ClientBuilder::new("0.0.0.0:3000".to_string()).with_signing_key("ab".repeat(32));
+
+        // Act
+        let client = builder.build().await;
+
+        // Assert
+        assert!(client.is_ok())
+    }
+}
@@ -1,6 +1,22 @@
+use async_trait::async_trait;
 use serde::Deserialize;
 use std::collections::HashMap;
 
+/// Operations supported by every J.A.M.S client, regardless of the transport protocol.
+///
+/// Both `http::ApiClient` and `grpc::ApiClient` implement this trait, so callers can switch
+/// protocols without changing how the client is used.
+#[async_trait]
+pub trait Client: Send + Sync {
+    async fn health_check(&self) -> anyhow::Result<()>;
+    async fn predict(&self, model_name: String, model_input: String)
+        -> anyhow::Result<Predictions>;
+    async fn add_model(&self, model_name: String) -> anyhow::Result<()>;
+    async fn update_model(&self, model_name: String) -> anyhow::Result<()>;
+    async fn delete_model(&self, model_name: String) -> anyhow::Result<()>;
+    async fn get_models(&self) -> anyhow::Result<GetModelsResponse>;
+}
+
 #[derive(Deserialize, Debug)]
 pub struct GetModelsResponse {
     /// Total number of models.
@@ -14,6 +30,36 @@ pub struct Metadata {
     pub framework: String,
     pub path: String,
     pub last_updated: String,
+    /// SHA-256 hash of the model artifact. Empty for servers which do not report it.
+    #[serde(default)]
+    pub artifact_sha256: String,
+    /// URI of the model tarball in the model store. Empty for servers which do not report it.
+    #[serde(default)]
+    pub source_uri: String,
+    /// S3 version id or blob etag of the model tarball, if known.
+    #[serde(default)]
+    pub source_version: Option<String>,
+    /// Build labels from the `labels.yaml` file in the model tarball.
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    /// New version of the model which is being loaded while the current version keeps serving, if any.
+    #[serde(default)]
+    pub pending_update: Option<PendingUpdate>,
+    /// Number of prediction requests made to the model since it was added or since the server started.
+    #[serde(default)]
+    pub request_count: u64,
+    /// Timestamp(RFC 3339) of the last prediction request made to the model, if any.
+    #[serde(default)]
+    pub last_accessed: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PendingUpdate {
+    /// S3 version id, blob etag or file modification time of the new model tarball, if known.
+    #[serde(default)]
+    pub source_version: Option<String>,
+    /// Timestamp(RFC 3339) of when the new version was detected.
+    pub detected_at: String,
 }
 
 #[derive(Deserialize, Clone, Debug)]
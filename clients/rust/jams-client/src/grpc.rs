@@ -1,42 +1,54 @@
-use crate::common::{get_url, GetModelsResponse, Metadata, Predictions};
+pub use crate::common::Client;
+use crate::common::{get_url, GetModelsResponse, Metadata, PendingUpdate, Predictions};
+use crate::signing::{decode_key, grpc_body, new_request_id, verify_response, REQUEST_ID_HEADER};
+use crate::tls::{TlsConfig, ALPN_H2};
 use async_trait::async_trait;
 use jams_proto::jams_v1::model_server_client::ModelServerClient;
 use jams_proto::jams_v1::{
-    AddModelRequest, DeleteModelRequest, PredictRequest, UpdateModelRequest,
+    AddModelRequest, DeleteModelRequest, PredictRequest, PredictResponse, UpdateModelRequest,
 };
+use rustls::pki_types::ServerName;
+use std::sync::Arc;
 use std::time;
-use tonic::transport::Channel;
-
-#[async_trait]
-pub trait Client {
-    async fn health_check(&mut self) -> anyhow::Result<()>;
-    async fn predict(
-        &mut self,
-        model_name: String,
-        model_input: String,
-    ) -> anyhow::Result<Predictions>;
-    async fn add_model(&mut self, model_name: String) -> anyhow::Result<()>;
-    async fn update_model(&mut self, model_name: String) -> anyhow::Result<()>;
-    async fn delete_model(&mut self, model_name: String) -> anyhow::Result<()>;
-    async fn get_models(&mut self) -> anyhow::Result<GetModelsResponse>;
-}
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tonic::metadata::MetadataValue;
+use tonic::transport::{Channel, Endpoint, Uri};
+use tonic::Response;
 
 pub struct ApiClient {
     client: ModelServerClient<Channel>,
     base_url: String,
     timeout: time::Duration,
+    signing_key: Option<Vec<u8>>,
 }
 
 impl ApiClient {
     pub fn builder() -> ApiClientBuilder {
         ApiClientBuilder::default()
     }
+    /// Returns a handle to the underlying tonic client.
+    ///
+    /// Tonic clients need `&mut self` to send requests but are cheap to clone as they share
+    /// the same channel, which lets `ApiClient` expose the `&self` based `Client` trait.
+    fn client(&self) -> ModelServerClient<Channel> {
+        self.client.clone()
+    }
+
+    /// Wraps the message in a `tonic::Request` with the configured timeout.
+    fn request<T>(&self, message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        request.set_timeout(self.timeout);
+        request
+    }
 }
 
 #[derive(Default)]
 pub struct ApiClientBuilder {
     base_url: String,
     timeout: time::Duration,
+    tls: Option<TlsConfig>,
+    signing_key: Option<String>,
 }
 
 impl ApiClientBuilder {
@@ -44,6 +56,8 @@ impl ApiClientBuilder {
         ApiClientBuilder {
             base_url: get_url(base_url),
             timeout: time::Duration::from_secs(5),
+            tls: None,
+            signing_key: None,
         }
     }
 
@@ -52,8 +66,32 @@ impl ApiClientBuilder {
         self
     }
 
+    /// Connects to the server over TLS and verifies its certificate with the given TLS settings, e.g. a private
+    /// CA bundle or pinned certificates. The base URL must be an `https://` URL.
+    pub fn with_tls(mut self, tls: TlsConfig) -> ApiClientBuilder {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Verifies the signature of every prediction response with the hex encoded signing key of the server, so that
+    /// predictions which were tampered with on their way, or are not signed, fail.
+    pub fn with_signing_key(mut self, signing_key: String) -> ApiClientBuilder {
+        self.signing_key = Some(signing_key);
+        self
+    }
+
     pub async fn build(self) -> anyhow::Result<ApiClient> {
-        let client = match ModelServerClient::connect(self.base_url.clone()).await {
+        let signing_key = match &self.signing_key {
+            Some(signing_key) => Some(decode_key(signing_key)?),
+            None => None,
+        };
+        let client = match &self.tls {
+            None => ModelServerClient::connect(self.base_url.clone()).await,
+            Some(tls) => connect_with_tls(self.base_url.as_str(), tls)
+                .await?
+                .map(ModelServerClient::new),
+        };
+        let client = match client {
             Ok(client) => client,
             Err(err) => {
                 anyhow::bail!("failed to create grpc client ❌: {}", err.to_string())
@@ -63,14 +101,61 @@ impl ApiClientBuilder {
             client,
             base_url: self.base_url,
             timeout: self.timeout,
+            signing_key,
         })
     }
 }
 
+/// Connects to the server over TLS with the rustls config of the TLS settings, so that the certificate of the server
+/// is verified with the CA bundles and pinned certificates of the client rather than the trust store of the OS.
+///
+/// # Errors
+/// The outer error is returned if the TLS settings or the URL are invalid, the inner error if the server cannot be
+/// connected to.
+async fn connect_with_tls(
+    base_url: &str,
+    tls: &TlsConfig,
+) -> anyhow::Result<Result<Channel, tonic::transport::Error>> {
+    if !base_url.starts_with("https://") {
+        anyhow::bail!(
+            "TLS is configured but {} is not an https:// URL ❌",
+            base_url
+        )
+    }
+    let uri: Uri = base_url.parse()?;
+    let authority = match uri.authority() {
+        Some(authority) => authority.clone(),
+        None => anyhow::bail!("{} does not have a host ❌", base_url),
+    };
+    let port = authority.port_u16().unwrap_or(443);
+    let host = authority
+        .host()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let server_name = ServerName::try_from(host.clone())?;
+    let connector = TlsConnector::from(Arc::new(tls.client_config(vec![ALPN_H2.to_vec()])?));
+
+    // the connector makes the TLS handshake, hence the endpoint itself is plain HTTP/2
+    let endpoint = Endpoint::from_shared(format!("http://{}:{}", authority.host(), port))?;
+    let channel = endpoint
+        .connect_with_connector(tower::service_fn(move |_: Uri| {
+            let connector = connector.clone();
+            let server_name = server_name.clone();
+            let address = (host.clone(), port);
+            async move {
+                let stream = TcpStream::connect(address).await?;
+                connector.connect(server_name, stream).await
+            }
+        }))
+        .await;
+    Ok(channel)
+}
+
 #[async_trait]
 impl Client for ApiClient {
-    async fn health_check(&mut self) -> anyhow::Result<()> {
-        match self.client.health_check(tonic::Request::new(())).await {
+    async fn health_check(&self) -> anyhow::Result<()> {
+        match self.client().health_check(self.request(())).await {
             Ok(_) => Ok(()),
             Err(status) => {
                 anyhow::bail!(
@@ -82,19 +167,22 @@ impl Client for ApiClient {
     }
 
     async fn predict(
-        &mut self,
+        &self,
         model_name: String,
         model_input: String,
     ) -> anyhow::Result<Predictions> {
-        match self
-            .client
-            .predict(PredictRequest {
-                model_name,
-                input: model_input,
-            })
-            .await
-        {
+        let request_id = new_request_id();
+        let mut request = self.request(PredictRequest {
+            model_name,
+            input: model_input,
+            predict_options: String::new(),
+        });
+        if let Ok(value) = MetadataValue::try_from(request_id.as_str()) {
+            request.metadata_mut().insert(REQUEST_ID_HEADER, value);
+        }
+        match self.client().predict(request).await {
             Ok(resp) => {
+                verify_prediction(self.signing_key.as_deref(), &request_id, &resp)?;
                 let inner = resp.into_inner().output;
                 match Predictions::from_bytes(inner.as_bytes()) {
                     Ok(predictions) => Ok(predictions),
@@ -112,8 +200,12 @@ impl Client for ApiClient {
         }
     }
 
-    async fn add_model(&mut self, model_name: String) -> anyhow::Result<()> {
-        match self.client.add_model(AddModelRequest { model_name }).await {
+    async fn add_model(&self, model_name: String) -> anyhow::Result<()> {
+        match self
+            .client()
+            .add_model(self.request(AddModelRequest { model_name }))
+            .await
+        {
             Ok(_) => Ok(()),
             Err(status) => {
                 anyhow::bail!("failed to add model ❌: {}", status.to_string())
@@ -121,10 +213,10 @@ impl Client for ApiClient {
         }
     }
 
-    async fn update_model(&mut self, model_name: String) -> anyhow::Result<()> {
+    async fn update_model(&self, model_name: String) -> anyhow::Result<()> {
         match self
-            .client
-            .update_model(UpdateModelRequest { model_name })
+            .client()
+            .update_model(self.request(UpdateModelRequest { model_name }))
             .await
         {
             Ok(_) => Ok(()),
@@ -134,10 +226,10 @@ impl Client for ApiClient {
         }
     }
 
-    async fn delete_model(&mut self, model_name: String) -> anyhow::Result<()> {
+    async fn delete_model(&self, model_name: String) -> anyhow::Result<()> {
         match self
-            .client
-            .delete_model(DeleteModelRequest { model_name })
+            .client()
+            .delete_model(self.request(DeleteModelRequest { model_name }))
             .await
         {
             Ok(_) => Ok(()),
@@ -147,8 +239,8 @@ impl Client for ApiClient {
         }
     }
 
-    async fn get_models(&mut self) -> anyhow::Result<GetModelsResponse> {
-        match self.client.get_models(tonic::Request::new(())).await {
+    async fn get_models(&self) -> anyhow::Result<GetModelsResponse> {
+        match self.client().get_models(self.request(())).await {
             Ok(response) => {
                 let pb_response = response.into_inner();
 
@@ -160,6 +252,19 @@ impl Client for ApiClient {
                         framework: model.framework,
                         path: model.path,
                         last_updated: model.last_updated,
+                        artifact_sha256: model.artifact_sha256,
+                        source_uri: model.source_uri,
+                        source_version: Some(model.source_version)
+                            .filter(|version| !version.is_empty()),
+                        labels: model.labels,
+                        pending_update: model.pending_update.map(|pending| PendingUpdate {
+                            source_version: Some(pending.source_version)
+                                .filter(|version| !version.is_empty()),
+                            detected_at: pending.detected_at,
+                        }),
+                        request_count: model.request_count,
+                        last_accessed: Some(model.last_accessed)
+                            .filter(|last_accessed| !last_accessed.is_empty()),
                     })
                     .collect();
 
@@ -175,9 +280,35 @@ impl Client for ApiClient {
     }
 }
 
+/// Verifies the signature of a prediction response to the request with the given ID, if a signing key is
+/// configured.
+///
+/// The signed body is `grpc_body` of the response rather than its protobuf encoding, which cannot be reproduced
+/// once the decoding dropped the fields added to `PredictResponse` by a newer server.
+fn verify_prediction(
+    key: Option<&[u8]>,
+    request_id: &str,
+    response: &Response<PredictResponse>,
+) -> anyhow::Result<()> {
+    let prediction = response.get_ref();
+    let body = grpc_body(
+        prediction.output.as_str(),
+        prediction.result_url.as_str(),
+        prediction.result_expires_at.as_str(),
+    );
+    verify_response(key, request_id, &body, |name| {
+        response
+            .metadata()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::signing::{SIGNATURE_HEADER, SIGNATURE_ISSUED_AT_HEADER};
+    use prost::Message;
     use std::env;
     fn get_url() -> String {
         let hostname = env::var("JAMS_GRPC_HOSTNAME").unwrap_or("0.0.0.0".to_string());
@@ -187,7 +318,7 @@ mod tests {
     #[tokio::test]
     async fn successfully_sends_health_check_request() {
         // Arrange
-        let mut client = ApiClientBuilder::new(get_url())
+        let client = ApiClientBuilder::new(get_url())
             .with_timeout(2)
             .build()
             .await
@@ -203,7 +334,7 @@ mod tests {
     #[tokio::test]
     async fn successfully_sends_get_model_request() {
         // Arrange
-        let mut client = ApiClientBuilder::new(get_url())
+        let client = ApiClientBuilder::new(get_url())
             .with_timeout(2)
             .build()
             .await
@@ -222,7 +353,7 @@ mod tests {
     #[tokio::test]
     async fn successfully_sends_delete_model_request() {
         // Arrange
-        let mut client = ApiClientBuilder::new(get_url())
+        let client = ApiClientBuilder::new(get_url())
             .with_timeout(2)
             .build()
             .await
@@ -245,7 +376,7 @@ mod tests {
     #[tokio::test]
     async fn successfully_sends_add_model_request() {
         // Arrange
-        let mut client = ApiClientBuilder::new(get_url())
+        let client = ApiClientBuilder::new(get_url())
             .with_timeout(2)
             .build()
             .await
@@ -268,7 +399,7 @@ mod tests {
     #[tokio::test]
     async fn successfully_sends_update_model_request() {
         // Arrange
-        let mut client = ApiClientBuilder::new(get_url())
+        let client = ApiClientBuilder::new(get_url())
             .with_timeout(2)
             .build()
             .await
@@ -284,7 +415,7 @@ mod tests {
     #[tokio::test]
     async fn successfully_sends_predict_model_request() {
         // Arrange
-        let mut client = ApiClientBuilder::new(get_url())
+        let client = ApiClientBuilder::new(get_url())
             .with_timeout(2)
             .build()
             .await
@@ -316,4 +447,38 @@ mod tests {
         // Assert
         assert!(resp.is_ok())
     }
+
+    #[test]
+    fn successfully_verifies_prediction_with_fields_added_by_a_newer_server() {
+        // Arrange
+        let key = [0xaa; 32];
+        let mut encoded = PredictResponse {
+            output: r#"{"predictions":[[0.5]]}"#.to_string(),
+            ..Default::default()
+        }
+        .encode_to_vec();
+        // a string field 4 unknown to this client, which the decoding drops
+        encoded.extend_from_slice(&[0x22, 0x01, b'x']);
+        let mut response = Response::new(PredictResponse::decode(encoded.as_slice()).unwrap());
+        response.metadata_mut().insert(
+            SIGNATURE_HEADER,
+            MetadataValue::from_static(
+                "sha256=b4d907babb16117a43f8b41982e1f305f40f4293dac3726fca7cdf4f670eaab0",
+            ),
+        );
+        response.metadata_mut().insert(
+            SIGNATURE_ISSUED_AT_HEADER,
+            MetadataValue::from_static("1720000000"),
+        );
+
+        // Act
+        let verified = verify_prediction(Some(&key), "request-1", &response);
+        let replayed = verify_prediction(Some(&key), "request-2", &response);
+        let not_verified = verify_prediction(None, "request-2", &response);
+
+        // Assert
+        assert!(verified.is_ok());
+        assert!(replayed.is_err());
+        assert!(not_verified.is_ok())
+    }
 }
@@ -1,5 +1,9 @@
+pub use crate::common::Client;
 use crate::common::{get_url, GetModelsResponse, Predictions};
+use crate::signing::{decode_key, new_request_id, verify_response, REQUEST_ID_HEADER};
+use crate::tls::TlsConfig;
 use async_trait::async_trait;
+use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
 use std::time;
 
@@ -24,21 +28,11 @@ struct UpdateModelRequest {
     model_name: String,
 }
 
-#[async_trait]
-pub trait Client {
-    async fn health_check(&self) -> anyhow::Result<()>;
-    async fn predict(&self, model_name: String, model_input: String)
-        -> anyhow::Result<Predictions>;
-    async fn add_model(&self, model_name: String) -> anyhow::Result<()>;
-    async fn update_model(&self, model_name: String) -> anyhow::Result<()>;
-    async fn delete_model(&self, model_name: String) -> anyhow::Result<()>;
-    async fn get_models(&self) -> anyhow::Result<GetModelsResponse>;
-}
-
 pub struct ApiClient {
     client: reqwest::Client,
     base_url: String,
     timeout: time::Duration,
+    signing_key: Option<Vec<u8>>,
 }
 
 impl ApiClient {
@@ -51,6 +45,8 @@ impl ApiClient {
 pub struct ApiClientBuilder {
     base_url: String,
     timeout: time::Duration,
+    tls: Option<TlsConfig>,
+    signing_key: Option<String>,
 }
 
 impl ApiClientBuilder {
@@ -58,6 +54,8 @@ impl ApiClientBuilder {
         ApiClientBuilder {
             base_url: get_url(base_url),
             timeout: time::Duration::from_secs(5),
+            tls: None,
+            signing_key: None,
         }
     }
 
@@ -66,8 +64,36 @@ impl ApiClientBuilder {
         self
     }
 
+    /// Verifies the certificate of the server with the given TLS settings, e.g. a private CA bundle or pinned
+    /// certificates, instead of the built-in roots only. The base URL must be an `https://` URL.
+    pub fn with_tls(mut self, tls: TlsConfig) -> ApiClientBuilder {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Verifies the signature of every prediction response with the hex encoded signing key of the server, so that
+    /// predictions which were tampered with on their way, or are not signed, fail.
+    pub fn with_signing_key(mut self, signing_key: String) -> ApiClientBuilder {
+        self.signing_key = Some(signing_key);
+        self
+    }
+
     pub fn build(self) -> anyhow::Result<ApiClient> {
-        let client = match reqwest::Client::builder().build() {
+        let signing_key = match &self.signing_key {
+            Some(signing_key) => Some(decode_key(signing_key)?),
+            None => None,
+        };
+        let mut builder = reqwest::Client::builder();
+        if let Some(tls) = &self.tls {
+            if !self.base_url.starts_with("https://") {
+                anyhow::bail!(
+                    "TLS is configured but {} is not an https:// URL ❌",
+                    self.base_url
+                )
+            }
+            builder = builder.use_preconfigured_tls(tls.client_config(Vec::new())?);
+        }
+        let client = match builder.build() {
             Ok(client) => client,
             Err(err) => {
                 anyhow::bail!("failed to create reqwest client: {}", err)
@@ -77,6 +103,7 @@ impl ApiClientBuilder {
             client,
             base_url: self.base_url,
             timeout: self.timeout,
+            signing_key,
         })
     }
 }
@@ -107,9 +134,11 @@ impl Client for ApiClient {
         model_input: String,
     ) -> anyhow::Result<Predictions> {
         let url = format!("{}/{}", self.base_url, "api/predict");
+        let request_id = new_request_id();
         match self
             .client
             .post(url)
+            .header(REQUEST_ID_HEADER, request_id.as_str())
             .json(&PredictRequest {
                 model_name,
                 input: model_input,
@@ -120,7 +149,10 @@ impl Client for ApiClient {
         {
             Ok(resp) => match resp.status().is_success() {
                 true => {
-                    let predictions = resp.json::<PredictResponse>().await?;
+                    let headers = resp.headers().clone();
+                    let body = resp.bytes().await?;
+                    verify_prediction(self.signing_key.as_deref(), &request_id, &headers, &body)?;
+                    let predictions = serde_json::from_slice::<PredictResponse>(&body)?;
                     match Predictions::from_bytes(predictions.output.as_ref()) {
                         Ok(predictions) => Ok(predictions),
                         Err(err) => {
@@ -228,9 +260,24 @@ impl Client for ApiClient {
     }
 }
 
+/// Verifies the signature of the body of a prediction response to the request with the given ID, if a signing key
+/// is configured.
+fn verify_prediction(
+    key: Option<&[u8]>,
+    request_id: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> anyhow::Result<()> {
+    verify_response(key, request_id, body, |name| {
+        headers.get(name).and_then(|value| value.to_str().ok())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::signing::{SIGNATURE_HEADER, SIGNATURE_ISSUED_AT_HEADER};
+    use reqwest::header::HeaderValue;
     use std::env;
     fn get_url() -> String {
         let hostname = env::var("JAMS_HTTP_HOSTNAME").unwrap_or("0.0.0.0".to_string());
@@ -363,4 +410,32 @@ mod tests {
         // Assert
         assert!(resp.is_ok())
     }
+
+    #[test]
+    fn successfully_verifies_prediction_signed_for_the_request() {
+        // Arrange
+        let key = [0xaa; 32];
+        let body = br#"{"output":"{\"predictions\":[[0.5]]}"}"#;
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            SIGNATURE_HEADER,
+            HeaderValue::from_static(
+                "sha256=5a222242c55dbb537987b87283dff028f89eb3302a9a00dde06da36fd29c652a",
+            ),
+        );
+        headers.insert(
+            SIGNATURE_ISSUED_AT_HEADER,
+            HeaderValue::from_static("1720000000"),
+        );
+
+        // Act
+        let verified = verify_prediction(Some(&key), "request-1", &headers, body);
+        let replayed = verify_prediction(Some(&key), "request-2", &headers, body);
+        let unsigned = verify_prediction(Some(&key), "request-1", &HeaderMap::new(), body);
+
+        // Assert
+        assert!(verified.is_ok());
+        assert!(replayed.is_err());
+        assert!(unsigned.is_err())
+    }
 }
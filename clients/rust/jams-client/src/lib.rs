@@ -1,4 +1,8 @@
 #![allow(dead_code)]
+pub mod builder;
 pub mod common;
 pub mod grpc;
 pub mod http;
+pub mod payload;
+pub mod signing;
+pub mod tls;
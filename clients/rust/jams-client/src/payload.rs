@@ -0,0 +1,345 @@
+use serde_json::{Map, Number, Value};
+
+/// Values of a single feature across all rows of a prediction request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FeatureValues {
+    Int(Vec<i32>),
+    Float(Vec<f64>),
+    String(Vec<String>),
+}
+
+impl FeatureValues {
+    fn len(&self) -> usize {
+        match self {
+            FeatureValues::Int(v) => v.len(),
+            FeatureValues::Float(v) => v.len(),
+            FeatureValues::String(v) => v.len(),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            FeatureValues::Int(_) => "int",
+            FeatureValues::Float(_) => "float",
+            FeatureValues::String(_) => "string",
+        }
+    }
+
+    fn push(&mut self, value: FeatureValue) -> anyhow::Result<()> {
+        match (self, value) {
+            (FeatureValues::Int(v), FeatureValue::Int(x)) => v.push(x),
+            (FeatureValues::Float(v), FeatureValue::Float(x)) => v.push(x),
+            (FeatureValues::String(v), FeatureValue::String(x)) => v.push(x),
+            (values, value) => {
+                anyhow::bail!(
+                    "expected {} value but got {} value ❌",
+                    values.kind(),
+                    value.kind()
+                )
+            }
+        }
+        Ok(())
+    }
+
+    fn into_json(self, name: &str) -> anyhow::Result<Value> {
+        let values = match self {
+            FeatureValues::Int(v) => v.into_iter().map(Value::from).collect(),
+            FeatureValues::Float(v) => v
+                .into_iter()
+                .map(|x| match Number::from_f64(x) {
+                    Some(number) => Ok(Value::Number(number)),
+                    None => anyhow::bail!(
+                        "feature '{}' contains a non finite float value '{}' ❌",
+                        name,
+                        x
+                    ),
+                })
+                .collect::<anyhow::Result<Vec<Value>>>()?,
+            FeatureValues::String(v) => v.into_iter().map(Value::from).collect(),
+        };
+        Ok(Value::Array(values))
+    }
+}
+
+/// A single feature value, used when building the payload row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FeatureValue {
+    Int(i32),
+    Float(f64),
+    String(String),
+}
+
+impl FeatureValue {
+    fn kind(&self) -> &'static str {
+        match self {
+            FeatureValue::Int(_) => "int",
+            FeatureValue::Float(_) => "float",
+            FeatureValue::String(_) => "string",
+        }
+    }
+
+    fn into_values(self) -> FeatureValues {
+        match self {
+            FeatureValue::Int(x) => FeatureValues::Int(vec![x]),
+            FeatureValue::Float(x) => FeatureValues::Float(vec![x]),
+            FeatureValue::String(x) => FeatureValues::String(vec![x]),
+        }
+    }
+}
+
+/// A single input record made of named feature values.
+#[derive(Clone, Debug, Default)]
+pub struct Row {
+    features: Vec<(String, FeatureValue)>,
+}
+
+impl Row {
+    pub fn new() -> Row {
+        Row::default()
+    }
+
+    pub fn with_int(mut self, name: &str, value: i32) -> Row {
+        self.features
+            .push((name.to_string(), FeatureValue::Int(value)));
+        self
+    }
+
+    pub fn with_float(mut self, name: &str, value: f64) -> Row {
+        self.features
+            .push((name.to_string(), FeatureValue::Float(value)));
+        self
+    }
+
+    pub fn with_string(mut self, name: &str, value: &str) -> Row {
+        self.features
+            .push((name.to_string(), FeatureValue::String(value.to_string())));
+        self
+    }
+}
+
+/// Builds the JSON model input expected by the J.A.M.S predict API.
+///
+/// The server expects a JSON object which maps every feature name to an array holding the
+/// feature value for each record, e.g. `{"age": [22.0, 38.0], "sex": ["male", "female"]}`.
+/// Features can be added a column at a time or a record at a time with `with_row`; both
+/// styles can be mixed as long as every feature ends up with the same number of values.
+///
+/// # Example
+///
+/// ```
+/// use jams_client::payload::{PayloadBuilder, Row};
+///
+/// let payload = PayloadBuilder::new()
+///     .with_row(Row::new().with_float("age", 22.0).with_string("sex", "male"))
+///     .with_row(Row::new().with_float("age", 38.0).with_string("sex", "female"))
+///     .build()
+///     .unwrap();
+/// let expected = serde_json::json!({"age": [22.0, 38.0], "sex": ["male", "female"]});
+/// assert_eq!(serde_json::from_str::<serde_json::Value>(&payload).unwrap(), expected);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PayloadBuilder {
+    features: Vec<(String, FeatureValues)>,
+    errors: Vec<String>,
+}
+
+impl PayloadBuilder {
+    pub fn new() -> PayloadBuilder {
+        PayloadBuilder::default()
+    }
+
+    /// Adds an integer feature with one value per record.
+    pub fn with_ints(self, name: &str, values: Vec<i32>) -> PayloadBuilder {
+        self.with_feature(name, FeatureValues::Int(values))
+    }
+
+    /// Adds a float feature with one value per record.
+    pub fn with_floats(self, name: &str, values: Vec<f64>) -> PayloadBuilder {
+        self.with_feature(name, FeatureValues::Float(values))
+    }
+
+    /// Adds a string (categorical) feature with one value per record.
+    pub fn with_strings(self, name: &str, values: Vec<String>) -> PayloadBuilder {
+        self.with_feature(name, FeatureValues::String(values))
+    }
+
+    /// Adds a feature with one value per record.
+    pub fn with_feature(mut self, name: &str, values: FeatureValues) -> PayloadBuilder {
+        if self.position(name).is_some() {
+            self.errors
+                .push(format!("feature '{}' is added more than once", name));
+            return self;
+        }
+        self.features.push((name.to_string(), values));
+        self
+    }
+
+    /// Appends a single record, adding each of its values to the matching feature.
+    pub fn with_row(mut self, row: Row) -> PayloadBuilder {
+        for (name, value) in row.features {
+            match self.position(&name) {
+                Some(index) => {
+                    if let Err(e) = self.features[index].1.push(value) {
+                        self.errors.push(format!("feature '{}': {}", name, e));
+                    }
+                }
+                None => self.features.push((name, value.into_values())),
+            }
+        }
+        self
+    }
+
+    /// Validates the features and serializes them into the predict API wire format.
+    ///
+    /// # Errors
+    /// Returns an error if no features were added, a feature name is empty or repeated, a
+    /// feature has no values, mixes value types, contains a non finite float or if the
+    /// features do not all have the same number of values.
+    pub fn build(self) -> anyhow::Result<String> {
+        if !self.errors.is_empty() {
+            anyhow::bail!("invalid payload ❌: {}", self.errors.join(", "))
+        }
+        if self.features.is_empty() {
+            anyhow::bail!("invalid payload ❌: no features added")
+        }
+
+        let num_rows = self.features[0].1.len();
+        let mut payload = Map::new();
+        for (name, values) in self.features {
+            if name.is_empty() {
+                anyhow::bail!("invalid payload ❌: feature name cannot be empty")
+            }
+            if values.len() == 0 {
+                anyhow::bail!("invalid payload ❌: feature '{}' has no values", name)
+            }
+            if values.len() != num_rows {
+                anyhow::bail!(
+                    "invalid payload ❌: feature '{}' has {} values but expected {}",
+                    name,
+                    values.len(),
+                    num_rows
+                )
+            }
+            let values = values.into_json(&name)?;
+            payload.insert(name, values);
+        }
+
+        Ok(Value::Object(payload).to_string())
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.features
+            .iter()
+            .position(|(feature, _)| feature == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_builds_payload_from_columns() {
+        // Arrange
+        let builder = PayloadBuilder::new()
+            .with_ints("pclass", vec![1, 3])
+            .with_floats("fare", vec![151.55, 14.4542])
+            .with_strings("sex", vec!["male".to_string(), "female".to_string()]);
+
+        // Act
+        let result = builder.build();
+
+        // Assert
+        assert!(result.is_ok());
+        let payload: Value = serde_json::from_str(&result.unwrap()).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({"pclass": [1, 3], "fare": [151.55, 14.4542], "sex": ["male", "female"]})
+        )
+    }
+
+    #[test]
+    fn successfully_builds_payload_from_rows() {
+        // Arrange
+        let builder = PayloadBuilder::new()
+            .with_row(Row::new().with_int("pclass", 1).with_float("age", 22.0))
+            .with_row(Row::new().with_int("pclass", 3).with_float("age", 23.5));
+
+        // Act
+        let result = builder.build();
+
+        // Assert
+        assert!(result.is_ok());
+        let payload: Value = serde_json::from_str(&result.unwrap()).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({"pclass": [1, 3], "age": [22.0, 23.5]})
+        )
+    }
+
+    #[test]
+    fn fails_to_build_payload_when_no_features_are_added() {
+        // Arrange
+        let builder = PayloadBuilder::new();
+
+        // Act
+        let result = builder.build();
+
+        // Assert
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn fails_to_build_payload_when_feature_lengths_differ() {
+        // Arrange
+        let builder = PayloadBuilder::new()
+            .with_ints("pclass", vec![1, 3])
+            .with_floats("fare", vec![151.55]);
+
+        // Act
+        let result = builder.build();
+
+        // Assert
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn fails_to_build_payload_when_feature_is_added_twice() {
+        // Arrange
+        let builder = PayloadBuilder::new()
+            .with_ints("pclass", vec![1])
+            .with_ints("pclass", vec![3]);
+
+        // Act
+        let result = builder.build();
+
+        // Assert
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn fails_to_build_payload_when_row_value_types_differ() {
+        // Arrange
+        let builder = PayloadBuilder::new()
+            .with_row(Row::new().with_int("pclass", 1))
+            .with_row(Row::new().with_string("pclass", "3"));
+
+        // Act
+        let result = builder.build();
+
+        // Assert
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn fails_to_build_payload_when_float_is_not_finite() {
+        // Arrange
+        let builder = PayloadBuilder::new().with_floats("fare", vec![f64::NAN]);
+
+        // Act
+        let result = builder.build();
+
+        // Assert
+        assert!(result.is_err())
+    }
+}
@@ -0,0 +1,200 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+/// Name of the HTTP header and gRPC metadata key which carries the signature of a prediction response.
+pub const SIGNATURE_HEADER: &str = "x-jams-signature";
+
+/// Name of the HTTP header and gRPC metadata key which carries the ID of the key a response was signed with.
+pub const SIGNATURE_KEY_ID_HEADER: &str = "x-jams-signature-key-id";
+
+/// Name of the HTTP header and gRPC metadata key which carries when a response was signed, in seconds since the
+/// Unix epoch.
+pub const SIGNATURE_ISSUED_AT_HEADER: &str = "x-jams-signature-issued-at";
+
+/// Name of the HTTP header and gRPC metadata key which carries the ID of a request, which the signature of its
+/// response is bound to.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Prefix of the signatures, naming the HMAC algorithm.
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+/// First line of the canonical form of a signed response, naming its version.
+const SIGNATURE_VERSION: &str = "jams-signature-v1";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Verifies the signature of a prediction response of a J.A.M.S server configured with `[config.response_signing]`,
+/// e.g. a response which was relayed by a proxy or a queue.
+///
+/// The signature covers the canonical form of the response, see `canonical_form`, so a response only verifies for
+/// the request it answers. Check `issued_at` against the age a response may have, e.g. when it was read from a
+/// queue, since a response is not signed again when it is relayed.
+///
+/// # Arguments
+/// * `key` - The signing key of the server, decoded from hex.
+/// * `request_id` - The `x-request-id` sent with the request.
+/// * `issued_at` - The value of the `x-jams-signature-issued-at` header or metadata key.
+/// * `body` - The body of the response, i.e. the exact bytes of the response body over HTTP and `grpc_body` of the
+///   `PredictResponse` over gRPC.
+/// * `signature` - The value of the `x-jams-signature` header or metadata key.
+///
+/// # Errors
+/// Returns an error if the signature is malformed or does not match the response.
+///
+/// # Example
+///
+/// ```no_run
+/// use jams_client::signing::verify;
+///
+/// # fn run(request_id: &str, issued_at: u64, body: &[u8], signature: &str) -> anyhow::Result<()> {
+/// let key = jams_client::signing::decode_key(std::fs::read_to_string("/run/secrets/signing_key")?.as_str())?;
+/// verify(&key, request_id, issued_at, body, signature)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn verify(
+    key: &[u8],
+    request_id: &str,
+    issued_at: u64,
+    body: &[u8],
+    signature: &str,
+) -> anyhow::Result<()> {
+    let mac = match signature.strip_prefix(SIGNATURE_PREFIX) {
+        Some(mac) => mac,
+        None => anyhow::bail!("response signature must start with {} ❌", SIGNATURE_PREFIX),
+    };
+    let mac = match hex::decode(mac) {
+        Ok(mac) => mac,
+        Err(err) => anyhow::bail!("response signature must be hex encoded ❌: {}", err),
+    };
+    let mut expected = match HmacSha256::new_from_slice(key) {
+        Ok(expected) => expected,
+        Err(err) => anyhow::bail!("invalid signing key ❌: {}", err),
+    };
+    expected.update(&canonical_form(request_id, issued_at, body));
+    match expected.verify_slice(&mac) {
+        Ok(()) => Ok(()),
+        Err(_) => anyhow::bail!("response signature does not match its request and body ❌"),
+    }
+}
+
+/// Returns the bytes which the server signs for a response: `jams-signature-v1`, the issued-at time and the request
+/// ID, each followed by a newline, then the body.
+pub fn canonical_form(request_id: &str, issued_at: u64, body: &[u8]) -> Vec<u8> {
+    let mut canonical =
+        format!("{}\n{}\n{}\n", SIGNATURE_VERSION, issued_at, request_id).into_bytes();
+    canonical.extend_from_slice(body);
+    canonical
+}
+
+/// Returns the signed body of a gRPC `PredictResponse`: its `result_url` and `result_expires_at`, each followed by
+/// a newline, then its `output`. Fields added to `PredictResponse` by newer servers are not signed, so they do not
+/// break the verification.
+pub fn grpc_body(output: &str, result_url: &str, result_expires_at: &str) -> Vec<u8> {
+    format!("{}\n{}\n{}", result_url, result_expires_at, output).into_bytes()
+}
+
+/// Decodes a hex encoded signing key, e.g. the contents of the key file of the server.
+///
+/// # Errors
+/// Returns an error if the key is empty or not hex encoded.
+pub fn decode_key(key: &str) -> anyhow::Result<Vec<u8>> {
+    match hex::decode(key.trim()) {
+        Ok(key) if !key.is_empty() => Ok(key),
+        Ok(_) => anyhow::bail!("signing key is empty ❌"),
+        Err(err) => anyhow::bail!("signing key must be hex encoded ❌: {}", err),
+    }
+}
+
+/// Returns a new request ID, which the signature of the response is bound to.
+pub(crate) fn new_request_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// Verifies the signature of a response to the request with the given ID if a signing key is configured, failing
+/// if the response is not signed.
+///
+/// # Arguments
+/// * `key` - The signing key, if responses are verified.
+/// * `request_id` - The `x-request-id` sent with the request.
+/// * `body` - The signed body of the response.
+/// * `lookup` - Returns the value of a header by its lowercase name, e.g. from a `HeaderMap` or a gRPC
+///   `MetadataMap`.
+pub(crate) fn verify_response<'a>(
+    key: Option<&[u8]>,
+    request_id: &str,
+    body: &[u8],
+    lookup: impl Fn(&str) -> Option<&'a str>,
+) -> anyhow::Result<()> {
+    let key = match key {
+        Some(key) => key,
+        None => return Ok(()),
+    };
+    let signature = match lookup(SIGNATURE_HEADER) {
+        Some(signature) => signature,
+        None => anyhow::bail!(
+            "response is not signed, is response signing configured on the server? ❌"
+        ),
+    };
+    let issued_at = match lookup(SIGNATURE_ISSUED_AT_HEADER).map(str::parse::<u64>) {
+        Some(Ok(issued_at)) => issued_at,
+        _ => anyhow::bail!(
+            "response signature does not have a valid {} ❌",
+            SIGNATURE_ISSUED_AT_HEADER
+        ),
+    };
+    verify(key, request_id, issued_at, body, signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the signature the server returns for the body below, the request ID `request-1` and the issued-at time
+    // 1720000000, with a key of 32 0xaa bytes
+    const SIGNATURE: &str =
+        "sha256=5a222242c55dbb537987b87283dff028f89eb3302a9a00dde06da36fd29c652a";
+
+    const BODY: &[u8] = br#"{"output":"{\"predictions\":[[0.5]]}"}"#;
+
+    #[test]
+    fn successfully_verifies_signed_response() {
+        // Arrange
+        let key = decode_key(&"aa".repeat(32)).unwrap();
+
+        // Act
+        let result = verify(&key, "request-1", 1720000000, BODY, SIGNATURE);
+
+        // Assert
+        assert!(result.is_ok())
+    }
+
+    #[test]
+    fn fails_to_verify_tampered_replayed_or_unsigned_response() {
+        // Arrange
+        let key = decode_key(&"aa".repeat(32)).unwrap();
+
+        // Act
+        let tampered = verify(
+            &key,
+            "request-1",
+            1720000000,
+            br#"{"output":"{\"predictions\":[[0.9]]}"}"#,
+            SIGNATURE,
+        );
+        let replayed = verify(&key, "request-2", 1720000000, BODY, SIGNATURE);
+        let backdated = verify(&key, "request-1", 1710000000, BODY, SIGNATURE);
+        let malformed = verify(&key, "request-1", 1720000000, BODY, "md5=abc");
+        let unsigned = verify_response(Some(&key), "request-1", BODY, |_| None);
+        let not_verified = verify_response(None, "request-1", BODY, |_| None);
+
+        // Assert
+        assert!(tampered.is_err());
+        assert!(replayed.is_err());
+        assert!(backdated.is_err());
+        assert!(malformed.is_err());
+        assert!(unsigned.is_err());
+        assert!(not_verified.is_ok())
+    }
+}
@@ -0,0 +1,141 @@
+use criterion::measurement::{Measurement, ValueFormatter};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use jams_core::model::input::{ModelInput, ModelInputBuffer};
+use jams_core::model::test_utils::utils::create_model_input_json;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Batch sizes used for every benchmark.
+const BATCH_SIZES: [usize; 4] = [1, 16, 128, 1024];
+
+/// Counts the allocations made by the benchmarks, including reallocations of growing vectors.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Measures the number of allocations made by the benchmarked code, so that criterion reports the allocations of
+/// each iteration like it reports times.
+struct Allocations;
+
+impl Measurement for Allocations {
+    type Intermediate = u64;
+    type Value = u64;
+
+    fn start(&self) -> Self::Intermediate {
+        ALLOCATIONS.load(Ordering::Relaxed)
+    }
+
+    fn end(&self, start: Self::Intermediate) -> Self::Value {
+        ALLOCATIONS.load(Ordering::Relaxed) - start
+    }
+
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        v1 + v2
+    }
+
+    fn zero(&self) -> Self::Value {
+        0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &AllocationsFormatter
+    }
+}
+
+/// Formats the number of allocations measured by `Allocations`.
+struct AllocationsFormatter;
+
+impl ValueFormatter for AllocationsFormatter {
+    fn scale_values(&self, _typical_value: f64, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical_value: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        match throughput {
+            Throughput::Elements(elements) => {
+                for value in values {
+                    *value /= *elements as f64;
+                }
+                "allocs/row"
+            }
+            _ => "allocs",
+        }
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+}
+
+/// Measures the number of allocations of a parse of a numeric payload into a new `ModelInput` and into a warm
+/// `ModelInputBuffer`, which makes none.
+fn bench_parse_allocations(c: &mut Criterion<Allocations>) {
+    let mut group = c.benchmark_group("parse_allocations");
+    for size in BATCH_SIZES {
+        let json = create_model_input_json(10, 0, size);
+        // the first parses fill the object pool and the buffer
+        ModelInput::from_str(&json).unwrap();
+        group.bench_with_input(BenchmarkId::new("from_str", size), &json, |b, json| {
+            b.iter(|| ModelInput::from_str(black_box(json)).unwrap())
+        });
+        let mut buffer = ModelInputBuffer::new();
+        buffer.parse(&json).unwrap();
+        group.bench_with_input(BenchmarkId::new("buffer", size), &json, |b, json| {
+            b.iter(|| buffer.parse(black_box(json)).unwrap().num_rows())
+        });
+    }
+    group.finish();
+}
+
+/// Measures the time taken to parse a numeric payload into a new `ModelInput` and into a reused
+/// `ModelInputBuffer`.
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_buffer");
+    for size in BATCH_SIZES {
+        let json = create_model_input_json(10, 0, size);
+        group.bench_with_input(BenchmarkId::new("from_str", size), &json, |b, json| {
+            b.iter(|| ModelInput::from_str(black_box(json)).unwrap())
+        });
+        let mut buffer = ModelInputBuffer::new();
+        group.bench_with_input(BenchmarkId::new("buffer", size), &json, |b, json| {
+            b.iter(|| buffer.parse(black_box(json)).unwrap().num_rows())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse);
+criterion_group! {
+    name = allocations;
+    config = Criterion::default().with_measurement(Allocations);
+    targets = bench_parse_allocations
+}
+criterion_main!(benches, allocations);
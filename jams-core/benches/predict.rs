@@ -0,0 +1,136 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use jams_core::model::input::ModelInput;
+use jams_core::model::predict::Predict;
+use jams_core::model::test_utils::utils::{
+    create_model_input_json, create_model_inputs_with_names,
+};
+
+/// Batch sizes used for every benchmark.
+const BATCH_SIZES: [usize; 4] = [1, 16, 128, 1024];
+
+/// Measures the time taken to parse a columnar JSON payload into a `ModelInput`.
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for size in BATCH_SIZES {
+        let json = create_model_input_json(10, 5, size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &json, |b, json| {
+            b.iter(|| ModelInput::from_str(black_box(json)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// Measures the time taken to parse a payload and make predictions using the given predictor.
+fn bench_parse_and_predict(
+    c: &mut Criterion,
+    name: &str,
+    predictor: &impl Predict,
+    num_numeric_features: usize,
+    num_string_features: usize,
+) {
+    let mut group = c.benchmark_group(name);
+    for size in BATCH_SIZES {
+        let json = create_model_input_json(num_numeric_features, num_string_features, size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &json, |b, json| {
+            b.iter(|| {
+                let input = ModelInput::from_str(black_box(json)).unwrap();
+                predictor.predict(input).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+#[cfg(feature = "catboost")]
+fn bench_catboost(c: &mut Criterion) {
+    use jams_core::model::catboost::Catboost;
+
+    let model = Catboost::load("tests/model_storage/models/catboost-titanic_model").unwrap();
+    let numeric_features: Vec<String> = vec!["age".to_string(), "fare".to_string()];
+    let string_features: Vec<String> = vec![
+        "pclass",
+        "sex",
+        "sibsp",
+        "parch",
+        "embarked",
+        "class",
+        "who",
+        "adult_male",
+        "deck",
+        "embark_town",
+        "alone",
+    ]
+    .into_iter()
+    .map(|name| name.to_string())
+    .collect();
+
+    // the input is built directly as the titanic model expects specific feature names
+    let mut group = c.benchmark_group("catboost");
+    for size in BATCH_SIZES {
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter_batched(
+                || {
+                    create_model_inputs_with_names(
+                        numeric_features.clone(),
+                        string_features.clone(),
+                        size,
+                    )
+                },
+                |input| model.predict(input).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+#[cfg(feature = "lightgbm")]
+fn bench_lightgbm(c: &mut Criterion) {
+    use jams_core::model::lightgbm::LightGBM;
+
+    let model =
+        LightGBM::load("tests/model_storage/models/lightgbm-my_awesome_reg_model.txt").unwrap();
+    bench_parse_and_predict(c, "lightgbm", &model, 28, 0);
+}
+
+#[cfg(feature = "tensorflow")]
+fn bench_tensorflow(c: &mut Criterion) {
+    use jams_core::model::tensorflow::Tensorflow;
+
+    let model =
+        Tensorflow::load("tests/model_storage/models/tensorflow-my_awesome_autompg_model").unwrap();
+
+    bench_parse_and_predict(c, "tensorflow", &model, 9, 0);
+}
+
+#[cfg(feature = "torch")]
+fn bench_torch(c: &mut Criterion) {
+    use jams_core::model::torch::Torch;
+
+    let model =
+        Torch::load("tests/model_storage/models/pytorch-my_awesome_californiahousing_model.pt")
+            .unwrap();
+    bench_parse_and_predict(c, "torch", &model, 8, 0);
+}
+
+#[cfg(not(feature = "catboost"))]
+fn bench_catboost(_: &mut Criterion) {}
+
+#[cfg(not(feature = "lightgbm"))]
+fn bench_lightgbm(_: &mut Criterion) {}
+
+#[cfg(not(feature = "tensorflow"))]
+fn bench_tensorflow(_: &mut Criterion) {}
+
+#[cfg(not(feature = "torch"))]
+fn bench_torch(_: &mut Criterion) {}
+
+criterion_group!(
+    benches,
+    bench_parse,
+    bench_catboost,
+    bench_lightgbm,
+    bench_tensorflow,
+    bench_torch
+);
+criterion_main!(benches);
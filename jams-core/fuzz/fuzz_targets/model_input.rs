@@ -0,0 +1,38 @@
+#![no_main]
+
+use jams_core::model::input::{ModelInput, MAX_FEATURES, MAX_ROWS};
+use libfuzzer_sys::fuzz_target;
+
+// Parsing arbitrary bytes must never panic. When the input is accepted, the shape of every
+// feature set has to describe the values which were parsed.
+fuzz_target!(|data: &[u8]| {
+    let Ok(json) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    if let Ok(model_input) = ModelInput::from_str(json) {
+        let float_features = &model_input.float_features;
+        let integer_features = &model_input.integer_features;
+        let string_features = &model_input.string_features;
+
+        assert_eq!(
+            float_features.shape.0 * float_features.shape.1,
+            float_features.values.as_floats().unwrap().len()
+        );
+        assert_eq!(
+            integer_features.shape.0 * integer_features.shape.1,
+            integer_features.values.as_ints().unwrap().len()
+        );
+        assert_eq!(
+            string_features.shape.0 * string_features.shape.1,
+            string_features.values.as_strings().unwrap().len()
+        );
+        assert!(
+            float_features.shape.0 + integer_features.shape.0 + string_features.shape.0
+                <= MAX_FEATURES
+        );
+        assert!(float_features.shape.1 <= MAX_ROWS);
+        assert!(integer_features.shape.1 <= MAX_ROWS);
+        assert!(string_features.shape.1 <= MAX_ROWS);
+    }
+});
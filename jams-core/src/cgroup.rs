@@ -0,0 +1,211 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Mount point of the cgroup filesystem.
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// cgroup v1 reports a memory limit close to `i64::MAX` when the memory is not limited.
+const UNLIMITED_MEMORY_BYTES: u64 = 1 << 60;
+
+/// The CPU and memory limits the cgroup of the process imposes on it, e.g. the limits of a container.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CgroupLimits {
+    /// The number of CPUs the process may use per period, e.g. `1.5` for a CPU limit of `1500m`.
+    pub cpu_quota: Option<f64>,
+    /// The memory the process may use in bytes.
+    pub memory_bytes: Option<u64>,
+}
+
+impl CgroupLimits {
+    /// Returns the limits of the cgroup of the process, read once from `/sys/fs/cgroup`.
+    ///
+    /// Both cgroup v2 and v1 are supported. The limits are `None` if they are not set or cannot be read,
+    /// e.g. on other platforms than Linux.
+    pub fn current() -> &'static CgroupLimits {
+        static LIMITS: OnceLock<CgroupLimits> = OnceLock::new();
+        LIMITS.get_or_init(|| {
+            let own_path = fs::read_to_string("/proc/self/cgroup")
+                .ok()
+                .and_then(|cgroup| unified_path(cgroup.as_str()));
+            CgroupLimits::read(Path::new(CGROUP_ROOT), own_path.as_deref())
+        })
+    }
+
+    /// Reads the limits from a cgroup filesystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The mount point of the cgroup filesystem.
+    /// * `own_path` - The path of the cgroup v2 of the process relative to the mount point, if known. The root
+    ///   of the mount is used if the cgroup of the process is not visible, e.g. in a container with its own
+    ///   cgroup namespace.
+    pub fn read(root: &Path, own_path: Option<&str>) -> CgroupLimits {
+        let mut unified_dirs: Vec<PathBuf> = Vec::new();
+        if let Some(own_path) = own_path {
+            let own_dir = root.join(own_path.trim_start_matches('/'));
+            if own_dir != root {
+                unified_dirs.push(own_dir);
+            }
+        }
+        unified_dirs.push(root.to_path_buf());
+
+        let cpu_quota = unified_dirs
+            .iter()
+            .find_map(|dir| read_file(&dir.join("cpu.max")))
+            .map(|cpu_max| parse_cpu_max(cpu_max.as_str()))
+            .unwrap_or_else(|| {
+                ["cpu,cpuacct", "cpu"].iter().find_map(|controller| {
+                    let dir = root.join(controller);
+                    let quota = read_file(&dir.join("cpu.cfs_quota_us"))?;
+                    let period = read_file(&dir.join("cpu.cfs_period_us"))?;
+                    parse_cfs_quota(quota.as_str(), period.as_str())
+                })
+            });
+
+        let memory_bytes = unified_dirs
+            .iter()
+            .find_map(|dir| read_file(&dir.join("memory.max")))
+            .or_else(|| read_file(&root.join("memory").join("memory.limit_in_bytes")))
+            .and_then(|limit| parse_memory_limit(limit.as_str()));
+
+        CgroupLimits {
+            cpu_quota,
+            memory_bytes,
+        }
+    }
+
+    /// Returns the number of CPUs the process can use, i.e. the smallest of the given CPUs, the CPUs the
+    /// process is allowed to run on and the CPU quota rounded up.
+    ///
+    /// # Arguments
+    ///
+    /// * `cpus` - The CPUs of the host, e.g. its physical cores.
+    pub fn available_cpus(&self, cpus: usize) -> usize {
+        let affinity = std::thread::available_parallelism()
+            .map(|parallelism| parallelism.get())
+            .unwrap_or(cpus);
+        self.cpus_within(cpus, affinity)
+    }
+
+    /// Returns the smallest of the given CPUs, the CPUs of the affinity of the process and the CPU quota
+    /// rounded up.
+    fn cpus_within(&self, cpus: usize, affinity: usize) -> usize {
+        let quota = self
+            .cpu_quota
+            .map(|quota| quota.ceil() as usize)
+            .unwrap_or(usize::MAX);
+        cpus.min(affinity).min(quota).max(1)
+    }
+}
+
+fn read_file(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|contents| contents.trim().to_string())
+}
+
+/// Returns the path of the cgroup v2 of the process from the contents of `/proc/self/cgroup`.
+fn unified_path(cgroup: &str) -> Option<String> {
+    cgroup
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|path| path.to_string())
+}
+
+/// Parses the `cpu.max` file of cgroup v2, e.g. `150000 100000` or `max 100000`.
+fn parse_cpu_max(cpu_max: &str) -> Option<f64> {
+    let mut fields = cpu_max.split_whitespace();
+    let quota = fields.next()?;
+    let period: f64 = fields.next().unwrap_or("100000").parse().ok()?;
+    if quota == "max" || period <= 0.0 {
+        return None;
+    }
+    let quota: f64 = quota.parse().ok()?;
+    Some(quota / period)
+}
+
+/// Parses the `cpu.cfs_quota_us` and `cpu.cfs_period_us` files of cgroup v1. A quota of `-1` is unlimited.
+fn parse_cfs_quota(quota: &str, period: &str) -> Option<f64> {
+    let quota: f64 = quota.parse().ok()?;
+    let period: f64 = period.parse().ok()?;
+    if quota <= 0.0 || period <= 0.0 {
+        return None;
+    }
+    Some(quota / period)
+}
+
+/// Parses the `memory.max` file of cgroup v2 or the `memory.limit_in_bytes` file of cgroup v1.
+fn parse_memory_limit(limit: &str) -> Option<u64> {
+    if limit == "max" {
+        return None;
+    }
+    let limit: u64 = limit.parse().ok()?;
+    (limit < UNLIMITED_MEMORY_BYTES).then_some(limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn successfully_read_cgroup_v2_limits() {
+        // Arrange
+        let root = tempfile::tempdir().unwrap();
+        write(root.path(), "cpu.max", "max 100000\n");
+        write(root.path(), "memory.max", "max\n");
+        write(root.path(), "kubepods/pod1/cpu.max", "150000 100000\n");
+        write(root.path(), "kubepods/pod1/memory.max", "536870912\n");
+
+        // Act
+        let own_cgroup = CgroupLimits::read(root.path(), Some("/kubepods/pod1"));
+        let namespaced = CgroupLimits::read(root.path(), Some("/"));
+
+        // Assert
+        assert_eq!(own_cgroup.cpu_quota, Some(1.5));
+        assert_eq!(own_cgroup.memory_bytes, Some(536870912));
+        assert_eq!(namespaced, CgroupLimits::default());
+        assert_eq!(own_cgroup.cpus_within(1, 64), 1);
+        assert_eq!(own_cgroup.cpus_within(64, 64), 2);
+        assert_eq!(own_cgroup.cpus_within(64, 1), 1);
+        assert_eq!(namespaced.cpus_within(64, 8), 8);
+    }
+
+    #[test]
+    fn successfully_read_cgroup_v1_limits() {
+        // Arrange
+        let limited = tempfile::tempdir().unwrap();
+        write(limited.path(), "cpu,cpuacct/cpu.cfs_quota_us", "50000\n");
+        write(limited.path(), "cpu,cpuacct/cpu.cfs_period_us", "100000\n");
+        write(
+            limited.path(),
+            "memory/memory.limit_in_bytes",
+            "1073741824\n",
+        );
+        let unlimited = tempfile::tempdir().unwrap();
+        write(unlimited.path(), "cpu/cpu.cfs_quota_us", "-1\n");
+        write(unlimited.path(), "cpu/cpu.cfs_period_us", "100000\n");
+        write(
+            unlimited.path(),
+            "memory/memory.limit_in_bytes",
+            "9223372036854771712\n",
+        );
+
+        // Act
+        let limited = CgroupLimits::read(limited.path(), None);
+        let unlimited = CgroupLimits::read(unlimited.path(), None);
+
+        // Assert
+        assert_eq!(limited.cpu_quota, Some(0.5));
+        assert_eq!(limited.memory_bytes, Some(1073741824));
+        assert_eq!(limited.cpus_within(8, 8), 1);
+        assert_eq!(unlimited.cpus_within(8, 4), 4);
+        assert_eq!(unlimited, CgroupLimits::default());
+    }
+}
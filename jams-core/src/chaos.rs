@@ -0,0 +1,223 @@
+use rand::Rng;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Artificial latency and random failures injected into an operation.
+///
+/// Meant for development only, to exercise timeouts, retries and circuit breakers end-to-end
+/// without real faults.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Fault {
+    /// Latency (in milliseconds) added to every operation.
+    #[serde(default)]
+    pub latency_ms: u64,
+
+    /// Upper bound (in milliseconds) of the random latency added on top of `latency_ms`.
+    #[serde(default)]
+    pub latency_jitter_ms: u64,
+
+    /// Probability between 0 and 1 that the operation fails after the latency.
+    #[serde(default)]
+    pub failure_rate: f64,
+}
+
+impl Fault {
+    /// Checks that the failure rate is a probability.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If `failure_rate` is not between 0 and 1.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(0.0..=1.0).contains(&self.failure_rate) {
+            anyhow::bail!(
+                "failure_rate must be between 0 and 1, got {} ❌",
+                self.failure_rate
+            )
+        }
+        Ok(())
+    }
+
+    /// Returns true if the fault injects any latency or failures.
+    pub fn is_enabled(&self) -> bool {
+        self.latency_ms > 0 || self.latency_jitter_ms > 0 || self.failure_rate > 0.0
+    }
+
+    /// Returns the latency to inject, which is `latency_ms` plus a random jitter between zero
+    /// and `latency_jitter_ms`.
+    pub fn next_latency(&self) -> Duration {
+        let jitter_ms = match self.latency_jitter_ms {
+            0 => 0,
+            latency_jitter_ms => rand::thread_rng().gen_range(0..=latency_jitter_ms),
+        };
+        Duration::from_millis(self.latency_ms + jitter_ms)
+    }
+
+    /// Returns true if the next operation should fail.
+    fn next_failure(&self) -> bool {
+        self.failure_rate > 0.0 && rand::thread_rng().gen_bool(self.failure_rate.min(1.0))
+    }
+
+    /// Injects the fault into an operation running on a blocking thread, e.g. a prediction.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - Describes the operation in the error message.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the operation is chosen to fail.
+    pub fn inject_blocking(&self, operation: &str) -> anyhow::Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let latency = self.next_latency();
+        if !latency.is_zero() {
+            std::thread::sleep(latency);
+        }
+        self.fail(operation)
+    }
+
+    /// Injects the fault into an async operation, e.g. a model download.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - Describes the operation in the error message.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the operation is chosen to fail.
+    pub async fn inject(&self, operation: &str) -> anyhow::Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let latency = self.next_latency();
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        self.fail(operation)
+    }
+
+    fn fail(&self, operation: &str) -> anyhow::Result<()> {
+        if self.next_failure() {
+            tracing::warn!("Injected failure into {} 💥", operation);
+            anyhow::bail!("Injected failure into {} 💥", operation)
+        }
+        Ok(())
+    }
+}
+
+/// Faults injected by the chaos developer mode.
+///
+/// ```toml
+/// [config.chaos.predict]
+/// latency_ms = 50
+/// latency_jitter_ms = 200
+/// failure_rate = 0.1
+///
+/// [config.chaos.download]
+/// failure_rate = 0.5
+/// ```
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ChaosConfig {
+    /// Fault injected into every prediction made by the manager.
+    #[serde(default)]
+    pub predict: Fault,
+
+    /// Fault injected into every model downloaded when adding, updating or polling models.
+    #[serde(default)]
+    pub download: Fault,
+}
+
+impl ChaosConfig {
+    /// Checks that the failure rates of the faults are probabilities.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If a failure rate is not between 0 and 1.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Err(e) = self.predict.validate() {
+            anyhow::bail!("Invalid chaos predict fault ❌: {}", e)
+        }
+        if let Err(e) = self.download.validate() {
+            anyhow::bail!("Invalid chaos download fault ❌: {}", e)
+        }
+        Ok(())
+    }
+
+    /// Returns true if any fault is injected.
+    pub fn is_enabled(&self) -> bool {
+        self.predict.is_enabled() || self.download.is_enabled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_inject_failures_and_latency() {
+        // Arrange
+        let fault = Fault {
+            latency_ms: 5,
+            latency_jitter_ms: 0,
+            failure_rate: 1.0,
+        };
+
+        // Act
+        let start = std::time::Instant::now();
+        let result = fault.inject_blocking("prediction");
+
+        // Assert
+        assert!(start.elapsed() >= Duration::from_millis(5));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Injected failure into prediction 💥"
+        );
+    }
+
+    #[tokio::test]
+    async fn successfully_skip_disabled_faults() {
+        // Arrange
+        let fault = Fault::default();
+
+        // Act
+        let result = fault.inject("download").await;
+
+        // Assert
+        assert!(!fault.is_enabled());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn successfully_bound_the_latency_jitter() {
+        // Arrange
+        let fault = Fault {
+            latency_ms: 10,
+            latency_jitter_ms: 20,
+            failure_rate: 0.0,
+        };
+
+        // Act
+        let latencies: Vec<Duration> = (0..100).map(|_| fault.next_latency()).collect();
+
+        // Assert
+        assert!(latencies.iter().all(|latency| (Duration::from_millis(10)
+            ..=Duration::from_millis(30))
+            .contains(latency)));
+    }
+
+    #[test]
+    fn fails_to_validate_chaos_config_when_failure_rate_is_not_a_probability() {
+        // Arrange
+        let config: ChaosConfig =
+            serde_json::from_str(r#"{"predict": {"failure_rate": 1.5}}"#).unwrap();
+
+        // Act
+        let result = config.validate();
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Environment variables which make TensorFlow, cuDNN, cuBLAS and OpenMP pick deterministic kernels and
+/// reduce in a fixed order. They are read when the libraries initialise, so they must be set before any
+/// model is loaded.
+const DETERMINISTIC_ENV_VARS: [(&str, &str); 4] = [
+    ("TF_DETERMINISTIC_OPS", "1"),
+    ("TF_CUDNN_DETERMINISTIC", "1"),
+    ("CUBLAS_WORKSPACE_CONFIG", ":4096:8"),
+    ("OMP_NUM_THREADS", "1"),
+];
+
+/// Settings of the deterministic mode, in which repeated predictions for the same input return bit-identical
+/// outputs, at the cost of throughput.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Determinism {
+    /// The seed of the random number generators of the frameworks.
+    pub seed: u64,
+}
+
+static DETERMINISM: OnceLock<Determinism> = OnceLock::new();
+
+/// Enables the deterministic mode for the whole process. It must be enabled before any model is loaded.
+///
+/// - TensorFlow and cuDNN use deterministic kernels and TensorFlow sessions run every op on a single thread.
+/// - LibTorch is seeded and runs on a single thread.
+/// - LightGBM predicts on a single thread with its `deterministic` option.
+/// - XGBoost predicts on a single thread.
+/// - CatBoost predictions are already deterministic on the CPU.
+///
+/// # Arguments
+///
+/// * `seed` - The seed of the random number generators of the frameworks.
+///
+/// # Errors
+///
+/// Returns an error if the deterministic mode was already enabled with another seed.
+pub fn enable(seed: u64) -> anyhow::Result<()> {
+    enable_in(&DETERMINISM, seed, || {
+        for (key, value) in DETERMINISTIC_ENV_VARS {
+            std::env::set_var(key, value);
+        }
+        #[cfg(feature = "torch")]
+        {
+            tch::manual_seed(seed as i64);
+            tch::set_num_threads(1);
+        }
+    })
+}
+
+/// Enables the deterministic mode in `cell`, running `configure` only the first time it is enabled.
+fn enable_in(
+    cell: &OnceLock<Determinism>,
+    seed: u64,
+    configure: impl FnOnce(),
+) -> anyhow::Result<()> {
+    let determinism = cell.get_or_init(|| {
+        configure();
+        Determinism { seed }
+    });
+    if determinism.seed != seed {
+        anyhow::bail!(
+            "Deterministic mode is already enabled with seed {} ❌",
+            determinism.seed
+        )
+    }
+    tracing::info!("Deterministic mode is enabled with seed {} 🎯", seed);
+    Ok(())
+}
+
+/// Returns the settings of the deterministic mode, or `None` if it is not enabled.
+pub fn determinism() -> Option<Determinism> {
+    DETERMINISM.get().copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_enable_deterministic_mode() {
+        // Arrange
+        let cell = OnceLock::new();
+        let configured = std::cell::Cell::new(0);
+
+        // Act
+        let enabled = enable_in(&cell, 42, || configured.set(configured.get() + 1));
+        let reenabled = enable_in(&cell, 42, || configured.set(configured.get() + 1));
+        let conflicting = enable_in(&cell, 7, || configured.set(configured.get() + 1));
+
+        // Assert
+        assert!(enabled.is_ok());
+        assert!(reenabled.is_ok());
+        assert!(conflicting.is_err());
+        assert_eq!(cell.get(), Some(&Determinism { seed: 42 }));
+        assert_eq!(configured.get(), 1);
+        assert_eq!(determinism(), None);
+    }
+}
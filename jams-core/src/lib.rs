@@ -1,7 +1,10 @@
+pub mod cgroup;
+pub mod chaos;
+pub mod determinism;
 pub mod manager;
+pub mod memory;
 pub mod model;
 pub mod model_store;
 pub mod pool;
 
-pub const FEATURE_VALUES_CAPACITY: usize = 125_000;
-pub const FEATURE_NAMES_CAPACITY: usize = 50;
+pub use jams_predictor_api::{Error, Result, FEATURE_NAMES_CAPACITY, FEATURE_VALUES_CAPACITY};
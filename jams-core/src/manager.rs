@@ -1,9 +1,48 @@
+use crate::chaos::ChaosConfig;
+use crate::memory::{artifact_size_bytes, with_budget, MemoryBudget, MEBIBYTE};
+use crate::model::bundle::ModelBundle;
+use crate::model::embedding::{EmbedOptions, Embeddings};
 use crate::model::input::ModelInput;
-use crate::model_store::storage::{Metadata, ModelName};
+use crate::model::lifecycle::DEFAULT_TEARDOWN_TIMEOUT;
+use crate::model::output::ModelOutput;
+use crate::model::predict::PredictOptions;
+use crate::model::settings::{with_settings, ModelSettingsConfig};
+use crate::model::stats::ModelStatsSnapshot;
+use crate::model::variant::{
+    LatencyFallback, LatencyFallbacks, ModelPrecision, VariantLatencies, VariantLatency,
+};
+use crate::model_store::attached::{
+    qualify, AttachedStoreInfo, AttachedStores, StoreSource, STORE_SEPARATOR,
+};
+use crate::model_store::deadline::StoreTimeouts;
+use crate::model_store::diff::{tarball_name, ArtifactSummary};
+use crate::model_store::gc::{collect_orphaned_dirs, GcReport};
+use crate::model_store::groups::DeploymentGroups;
+use crate::model_store::integrity::{verify_models, IntegrityReport};
+use crate::model_store::labels::ModelLabels;
+use crate::model_store::misses::{suggest, ModelMiss, ModelMisses};
+use crate::model_store::registry::RegistryCodec;
+use crate::model_store::skipped::{with_skipped, SkippedModel, SkippedModels};
+use crate::model_store::storage::{
+    extract_framework, schedule_teardown, teardown_model, wait_for_teardowns, Metadata, ModelName,
+    PollOptions,
+};
+use crate::model_store::supervisor::{Poller, PollerHealth, RestartPolicy, DEFAULT_STORE_NAME};
+use crate::model_store::usage::ModelUsage;
 use crate::model_store::ModelStore;
+use crate::pool::scheduler::{Priority, DEFAULT_WEIGHT};
+use crate::Error;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::time;
 
+/// The label of a model which sets its weight on the worker pool it shares with other models.
+pub const SCHEDULING_WEIGHT_LABEL: &str = "scheduling_weight";
+
 /// Manages model storage and prediction requests.
 ///
 /// The `Manager` struct is responsible for managing access to stored models and handling prediction requests.
@@ -11,8 +50,69 @@ use tokio::time;
 ///
 /// # Fields
 /// - `model_store` (Arc&ltdyn Storage&gt): A shared reference to the model storage.
+/// - `memory_budget` (Option&ltMemoryBudget&gt): An optional memory budget which is enforced when adding or updating models.
+/// - `chaos` (ChaosConfig): The faults injected into predictions and model downloads by the chaos developer mode.
+/// - `labels` (Arc&ltModelLabels&gt): The labels set on the models at runtime, which take precedence over the labels of their artifacts.
+/// - `priority_models` (Vec&ltModelName&gt): The models loaded first at startup, which must be loaded for the manager to be ready.
+/// - `remaining_models_loaded` (Arc&ltAtomicBool&gt): Whether the models deferred at startup have been loaded in the background.
+/// - `usage` (Arc&ltModelUsage&gt): The number of prediction requests made to each model and when it was last used.
+/// - `usage_state_path` (Option&ltPathBuf&gt): The file the usage is saved to, so that it carries over a restart.
+/// - `misses` (ModelMisses): The number of prediction requests made to each model name which was not loaded.
+/// - `model_suggestions` (bool): Whether the loaded model names closest to a missing model name are suggested.
+/// - `attached_stores` (AttachedStores): The model stores attached at runtime, whose models are named `<store>.<model>`.
+/// - `poll_options` (PollOptions): How the model stores attached at runtime are polled.
+/// - `restart_policy` (RestartPolicy): How the pollers of the model stores retry the polls which fail or panic.
+/// - `poller` (Option&ltPoller&gt): The supervised poller of the model store, if polling is enabled.
+/// - `store_timeouts` (StoreTimeouts): The deadlines of the calls made to the model stores attached at runtime.
+/// - `model_settings` (Option&ltArc&ltModelSettingsConfig&gt&gt): The settings passed to the init hooks of the models it loads.
+/// - `skipped` (Arc&ltSkippedModels&gt): The models which were skipped or failed to load, including the models loaded in the background.
+/// - `variant_latencies` (VariantLatencies): The time spent predicting with each precision variant of the models shipped with variants.
+/// - `latency_fallbacks` (LatencyFallbacks): The lightweight variants serving the requests whose latency budget the models exceed.
+/// - `scheduling_weights` (BTreeMap&ltModelName, f64&gt): The configured weight of each model on the worker pool it shares with other models.
 pub struct Manager {
     model_store: Arc<ModelStore>,
+    memory_budget: Option<MemoryBudget>,
+    chaos: ChaosConfig,
+    labels: Arc<ModelLabels>,
+    priority_models: Vec<ModelName>,
+    remaining_models_loaded: Arc<AtomicBool>,
+    usage: Arc<ModelUsage>,
+    usage_state_path: Option<PathBuf>,
+    misses: ModelMisses,
+    model_suggestions: bool,
+    attached_stores: AttachedStores,
+    poll_options: PollOptions,
+    restart_policy: RestartPolicy,
+    poller: Option<Poller>,
+    store_timeouts: StoreTimeouts,
+    model_settings: Option<Arc<ModelSettingsConfig>>,
+    skipped: Arc<SkippedModels>,
+    variant_latencies: VariantLatencies,
+    latency_fallbacks: LatencyFallbacks,
+    scheduling_weights: BTreeMap<ModelName, f64>,
+}
+
+/// The readiness of the manager to serve predictions.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Readiness {
+    /// Whether every priority model is loaded.
+    pub ready: bool,
+    /// The priority models which are not loaded.
+    pub pending_priority_models: Vec<ModelName>,
+    /// Whether the models deferred at startup are still being loaded in the background.
+    pub loading_remaining_models: bool,
+    /// The health of the poller of every polled model store.
+    pub pollers: Vec<PollerHealth>,
+}
+
+/// The predictions made for a request, along with the precision variant of the model which made them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServedPrediction {
+    /// The predictions formatted as a JSON string.
+    pub output: String,
+    /// The precision variant which made the predictions. `fp32` if the model artifact itself made them, otherwise
+    /// the variant selected by the request or its latency fallback.
+    pub precision: ModelPrecision,
 }
 
 impl Manager {
@@ -36,7 +136,305 @@ impl Manager {
     ///
     #[tracing::instrument(skip(self))]
     pub fn get_models(&self) -> anyhow::Result<Vec<Metadata>> {
-        self.model_store.get_models()
+        let mut models = self.model_store.get_models()?;
+        for (store_name, model_store) in self.attached_stores.stores() {
+            models.extend(model_store.get_models()?.into_iter().map(|mut metadata| {
+                metadata.name = qualify(store_name.as_str(), metadata.name.as_str());
+                metadata
+            }));
+        }
+        Ok(models
+            .into_iter()
+            .map(|metadata| self.usage.annotate(self.labels.annotate(metadata)))
+            .collect())
+    }
+
+    /// Retrieves the models which have not received any prediction request for at least `unused_for`,
+    /// e.g. to decide which models to remove from the model store.
+    ///
+    /// Usage is tracked in memory and carries over a restart only if a usage state file is configured, so models
+    /// which were never used count as unused since they were added, or since startup for the models loaded at
+    /// startup without a saved usage.
+    ///
+    /// # Arguments
+    ///
+    /// * `unused_for` - How long a model must have been left unused.
+    ///
+    /// # Errors
+    /// Returns an error if there are issues fetching the models from the store.
+    #[tracing::instrument(skip(self))]
+    pub fn get_unused_models(&self, unused_for: time::Duration) -> anyhow::Result<Vec<Metadata>> {
+        let models = self.get_models()?;
+        // forget the usage of models which were removed without going through the manager
+        self.usage.prune(|model_name| {
+            models
+                .iter()
+                .any(|metadata| metadata.name.as_str() == model_name)
+        });
+        Ok(models
+            .into_iter()
+            .filter(|metadata| self.usage.is_unused(metadata.name.as_str(), unused_for))
+            .collect())
+    }
+
+    /// Retrieves the model names which were requested most while no model with that name was loaded, most
+    /// requested first, e.g. to find the clients which call a model by a typo'd or stale name.
+    ///
+    /// Misses are tracked in memory since startup. The model names which are loaded now are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of model names returned.
+    #[tracing::instrument(skip(self))]
+    pub fn get_model_misses(&self, limit: usize) -> Vec<ModelMiss> {
+        self.misses.top(limit, |model_name| {
+            let (model_store, store_model_name) = self.store_of(model_name);
+            model_store.get_model(store_model_name).is_some()
+        })
+    }
+
+    /// Returns the number of requests made to models which were not loaded since startup.
+    pub fn model_misses_total(&self) -> u64 {
+        self.misses.total()
+    }
+
+    /// Suggests the names of the loaded models which are closest to a model name which is not loaded, closest
+    /// first, if model name suggestions are enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - The requested model name.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<ModelName>` - At most three model names, or none if suggestions are disabled or no model name is
+    ///   close enough.
+    pub fn suggest_model_names(&self, model_name: &str) -> Vec<ModelName> {
+        if !self.model_suggestions {
+            return Vec::new();
+        }
+        match self.get_models() {
+            Ok(models) => suggest(
+                model_name,
+                models.iter().map(|metadata| metadata.name.as_str()),
+            ),
+            Err(e) => {
+                tracing::warn!("Failed to suggest model names ⚠️: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Records a request made to a model which is not loaded and returns the error of the request.
+    fn model_not_found(&self, model_name: ModelName) -> anyhow::Error {
+        tracing::warn!("No model exists for model name: {} ⚠️", &model_name);
+        self.misses.record(model_name.as_str());
+        Error::ModelNotFound(model_name).into()
+    }
+
+    /// Retrieves the metadata of a single model stored in the model store.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - A `ModelName` representing the name of the model.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Metadata)` if the model is loaded.
+    /// * `None` if the model does not exist.
+    #[tracing::instrument(skip(self))]
+    pub fn get_model_metadata(&self, model_name: ModelName) -> Option<Metadata> {
+        let (model_store, store_model_name) = self.store_of(model_name.as_str());
+        model_store.get_model(store_model_name).map(|model| {
+            let mut metadata = model.info.clone();
+            metadata.name = model_name;
+            self.usage.annotate(self.labels.annotate(metadata))
+        })
+    }
+
+    /// Retrieves the bundle shipped with a model, e.g. to inspect the schema of its input.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - A `ModelName` representing the name of the model.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(ModelBundle)` if the model is loaded. The bundle is empty if the model was shipped without one.
+    /// * `None` if the model does not exist.
+    #[tracing::instrument(skip(self))]
+    pub fn get_model_bundle(&self, model_name: ModelName) -> Option<ModelBundle> {
+        let (model_store, model_name) = self.store_of(model_name.as_str());
+        model_store
+            .get_model(model_name)
+            .map(|model| model.bundle.clone())
+    }
+
+    /// Summarizes a stored version of the tarball of a model without loading it, e.g. to compare it with another
+    /// stored version of the model before promoting it.
+    ///
+    /// The tarball is the one the loaded model was unpacked from, read from the model store which serves the model.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - A `ModelName` representing the name of the model.
+    /// * `version` - The version of the tarball in the model store, i.e. its S3 or Azure version id, or its
+    ///   modification time in a local model store, or `None` for its latest version.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ArtifactSummary)` with the summary of the stored version.
+    /// * `Err(Error::ModelNotFound)` if the model does not exist.
+    /// * `Err(Error::Store)` if the version is not stored or its tarball cannot be read or unpacked.
+    #[tracing::instrument(skip(self))]
+    pub async fn get_stored_artifact_summary(
+        &self,
+        model_name: ModelName,
+        version: Option<String>,
+    ) -> anyhow::Result<ArtifactSummary> {
+        let (model_store, store_model_name) = self.store_of(model_name.as_str());
+        let model = match model_store.get_model(store_model_name) {
+            Some(model) => model,
+            None => return Err(self.model_not_found(model_name)),
+        };
+        let tarball_name = match tarball_name(model.info.source_uri.as_str()) {
+            Some(tarball_name) => tarball_name.to_string(),
+            None => {
+                return Err(Error::Store(anyhow::anyhow!(
+                    "Failed to find the stored tarball of model {} ❌",
+                    model_name
+                ))
+                .into())
+            }
+        };
+        let tarball = match model_store
+            .read_tarball(tarball_name.as_str(), version.as_deref())
+            .await
+        {
+            Ok(tarball) => tarball,
+            Err(e) => return Err(Error::Store(e).into()),
+        };
+        match tokio::task::spawn_blocking(move || {
+            ArtifactSummary::of_tarball(tarball_name.as_str(), &tarball)
+        })
+        .await
+        {
+            Ok(Ok(summary)) => Ok(summary),
+            Ok(Err(e)) => Err(Error::Store(e).into()),
+            Err(e) => Err(Error::Store(anyhow::Error::from(e)).into()),
+        }
+    }
+
+    /// Extracts the embeddings from the output of an embedding model and computes the cosine similarities
+    /// and the nearest neighbors in the embedding index shipped with the model, as requested by the options.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - A `ModelName` representing the name of the model which made the predictions.
+    /// * `output` - The output of the model.
+    /// * `options` - The similarities and nearest neighbors to compute.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Embeddings)` with the embeddings, similarities and nearest neighbors.
+    /// * `Err(anyhow::Error)` if the model does not exist or the options do not match its embeddings. An
+    ///   `Error::ModelNotFound` if the model does not exist, which is recorded as a miss.
+    #[tracing::instrument(skip(self, output))]
+    pub fn embed(
+        &self,
+        model_name: ModelName,
+        output: ModelOutput,
+        options: &EmbedOptions,
+    ) -> anyhow::Result<Embeddings> {
+        let (model_store, store_model_name) = self.store_of(model_name.as_str());
+        let model = match model_store.get_model(store_model_name) {
+            Some(model) => model,
+            None => return Err(self.model_not_found(model_name)),
+        };
+        match Embeddings::from_output(output, model.embedding_index.as_ref(), options) {
+            Ok(embeddings) => Ok(embeddings),
+            Err(e) => {
+                tracing::error!("Failed to embed: {}", e);
+                anyhow::bail!("Failed to embed: {}", e);
+            }
+        }
+    }
+
+    /// Sets and removes labels of a model at runtime.
+    ///
+    /// The labels are kept in memory across updates of the model, take precedence over the labels
+    /// from the `labels.yaml` file of the model tarball and are forgotten when the model is deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - A `ModelName` representing the name of the model.
+    /// * `labels` - The labels to set, replacing the value of existing labels with the same key.
+    /// * `remove` - The keys of the labels to remove.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Metadata)` with the labels of the model after the update.
+    /// * `Err(anyhow::Error)` if the model does not exist or a label is invalid.
+    #[tracing::instrument(skip(self))]
+    pub fn update_labels(
+        &self,
+        model_name: ModelName,
+        labels: BTreeMap<String, String>,
+        remove: &[String],
+    ) -> anyhow::Result<Metadata> {
+        let (model_store, store_model_name) = self.store_of(model_name.as_str());
+        if model_store.get_model(store_model_name).is_none() {
+            return Err(Error::ModelNotFound(model_name).into());
+        }
+        self.labels.update(model_name.clone(), labels, remove)?;
+        self.get_model_metadata(model_name.clone())
+            .ok_or_else(|| Error::ModelNotFound(model_name).into())
+    }
+
+    /// Returns the models which were skipped or failed to load since the manager was built, sorted by file name.
+    ///
+    /// The models skipped by the model store before the manager is built are included if the registry of the
+    /// manager was in scope when they were loaded, see `ManagerBuilder::with_skipped_models`.
+    pub fn skipped_models(&self) -> Vec<SkippedModel> {
+        self.skipped.models()
+    }
+
+    /// Reports whether the priority models are loaded, in which case the manager is ready to serve predictions
+    /// even though the rest of the model store may still be loading in the background.
+    ///
+    /// A manager without priority models is ready as long as the poller of its model store is healthy, as every
+    /// model is loaded before it is built. A poller which keeps failing makes the manager unready, as its models no
+    /// longer update. The pollers of the attached stores are reported but do not gate readiness, so that a flaky
+    /// secondary store does not take the manager out of rotation.
+    ///
+    /// # Returns
+    ///
+    /// * `Readiness` - The priority models which are not loaded, whether the other models are still loading and
+    ///   the health of the pollers.
+    pub fn readiness(&self) -> Readiness {
+        let pending_priority_models: Vec<ModelName> = self
+            .priority_models
+            .iter()
+            .filter(|model_name| self.model_store.get_model(model_name.to_string()).is_none())
+            .cloned()
+            .collect();
+        let default_poller_healthy = self.poller.iter().all(|poller| poller.health().healthy);
+        Readiness {
+            ready: pending_priority_models.is_empty() && default_poller_healthy,
+            pending_priority_models,
+            loading_remaining_models: !self.remaining_models_loaded.load(Ordering::Relaxed),
+            pollers: self.poller_health(),
+        }
+    }
+
+    /// Returns the health of the poller of every polled model store, starting with the model store the manager
+    /// was built with, which is named `default`.
+    pub fn poller_health(&self) -> Vec<PollerHealth> {
+        self.poller
+            .iter()
+            .map(Poller::health)
+            .chain(self.attached_stores.poller_health())
+            .collect()
     }
 
     /// Adds a new model to the model store.
@@ -49,10 +447,70 @@ impl Manager {
     /// # Returns
     ///
     /// * `Ok(())` if the model is successfully added.
-    /// * `Err(anyhow::Error)` if there is an error during the addition process.
+    /// * `Err(anyhow::Error)` if there is an error during the addition process or the model exceeds the memory budget.
+    ///
+    /// If a memory budget is configured, the model is refused before it is loaded when its artifact does not fit
+    /// within the budget. If the process goes over the budget after loading the model, the model is removed again
+    /// and an error is returned.
     #[tracing::instrument(skip(self))]
     pub async fn add_model(&self, model_name: ModelName) -> anyhow::Result<()> {
-        self.model_store.add_model(model_name).await
+        self.refuse_group_member(model_name.as_str())?;
+        let (model_store, store_model_name) = self.store_of(model_name.as_str());
+        self.chaos.download.inject("model download").await?;
+
+        let memory_budget = match self.memory_budget {
+            None => {
+                let add = with_settings(
+                    self.model_settings.clone(),
+                    model_store.add_model(store_model_name),
+                );
+                with_skipped(self.skipped.clone(), add).await?;
+                self.usage.track(strip_framework_prefix(model_name));
+                return Ok(());
+            }
+            Some(memory_budget) => memory_budget,
+        };
+
+        let add = with_settings(
+            self.model_settings.clone(),
+            model_store.add_model(store_model_name.clone()),
+        );
+        if let Err(e) =
+            with_skipped(self.skipped.clone(), with_budget(Some(memory_budget), add)).await
+        {
+            tracing::error!("Refusing to add model {} ❌: {}", model_name, e);
+            anyhow::bail!("Refusing to add model {} ❌: {}", model_name, e)
+        }
+
+        if let Err(e) = memory_budget.check(0) {
+            let loaded_model_name = strip_framework_prefix(store_model_name);
+            let artifact_size = match model_store.get_model(loaded_model_name.clone()) {
+                Some(model) => artifact_size_bytes(Path::new(model.info.path.as_str())),
+                None => 0,
+            };
+            if let Err(delete_err) = model_store.delete_model(loaded_model_name) {
+                tracing::error!(
+                    "Failed to remove model {} after exceeding memory budget ❌: {}",
+                    model_name,
+                    delete_err
+                );
+            }
+            tracing::error!(
+                "Refusing to add model {} with artifact size of {} MiB ❌: {}",
+                model_name,
+                artifact_size / MEBIBYTE,
+                e
+            );
+            anyhow::bail!(
+                "Refusing to add model {} with artifact size of {} MiB ❌: {}",
+                model_name,
+                artifact_size / MEBIBYTE,
+                e
+            )
+        }
+
+        self.usage.track(strip_framework_prefix(model_name));
+        Ok(())
     }
 
     /// Updates an existing model in the model store.
@@ -65,9 +523,35 @@ impl Manager {
     ///
     /// * `Ok(())` if the model is successfully updated.
     /// * `Err(anyhow::Error)` if there is an error during the update process or if the model does not exist.
+    ///
+    /// If a memory budget is configured, the update is refused when loading the artifact of the new version alongside
+    /// the current one would exceed the budget. Models of a deployment group are refused, as the group is deployed
+    /// together when the model store is polled.
     #[tracing::instrument(skip(self))]
     pub async fn update_model(&self, model_name: ModelName) -> anyhow::Result<()> {
-        self.model_store.update_model(model_name).await
+        self.refuse_group_member(model_name.as_str())?;
+        self.chaos.download.inject("model download").await?;
+        let (model_store, store_model_name) = self.store_of(model_name.as_str());
+
+        // the updated model is loaded before the current model is dropped, so the new artifact is checked while the
+        // current version is still resident
+        let update = with_settings(
+            self.model_settings.clone(),
+            model_store.update_model(store_model_name),
+        );
+        match with_skipped(
+            self.skipped.clone(),
+            with_budget(self.memory_budget, update),
+        )
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) if self.memory_budget.is_some() => {
+                tracing::error!("Refusing to update model {} ❌: {}", model_name, e);
+                anyhow::bail!("Refusing to update model {} ❌: {}", model_name, e)
+            }
+            Err(e) => Err(e.into()),
+        }
     }
 
     /// Deletes an existing model from the model store.
@@ -82,159 +566,1537 @@ impl Manager {
     /// * `Err(anyhow::Error)` if there is an error during the deletion process or if the model does not exist.
     #[tracing::instrument(skip(self))]
     pub fn delete_model(&self, model_name: ModelName) -> anyhow::Result<()> {
-        self.model_store.delete_model(model_name)
+        let (model_store, store_model_name) = self.store_of(model_name.as_str());
+        model_store.delete_model(store_model_name)?;
+        self.labels.clear(model_name.as_str());
+        self.usage.clear(model_name.as_str());
+        self.variant_latencies.clear(model_name.as_str());
+        Ok(())
     }
 
-    /// Predicts using the specified model and input data.
+    /// Tears down every model which is served, e.g. on shutdown once the servers have stopped, and waits for the
+    /// teardowns of the models retired before, which run in the background.
     ///
-    /// This method fetches the specified model from the storage, parses the input data,
-    /// and executes the prediction.
+    /// The models stay loaded, but must not be used once they are torn down.
+    #[tracing::instrument(skip(self))]
+    pub async fn shutdown(&self) {
+        let stores = std::iter::once(self.model_store.clone()).chain(
+            self.attached_stores
+                .stores()
+                .into_iter()
+                .map(|(_, store)| store),
+        );
+        for model_store in stores {
+            for metadata in model_store.get_models().unwrap_or_default() {
+                match model_store.get_model(metadata.name) {
+                    Some(model) if !model.is_torn_down() => {
+                        teardown_model(&model).await;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let pending = wait_for_teardowns(DEFAULT_TEARDOWN_TIMEOUT).await;
+        if pending > 0 {
+            tracing::warn!(
+                "Abandoned {} teardowns of retired models on shutdown ⚠️",
+                pending
+            );
+        }
+    }
+
+    /// Saves the usage of the models to the usage state file, if one is configured, so that it is restored on
+    /// the next startup. The usage is also saved periodically in the background, this saves the requests made
+    /// since, e.g. on shutdown.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the usage is successfully saved, or no usage state file is configured.
+    /// * `Err(anyhow::Error)` if the usage state file cannot be written.
+    #[tracing::instrument(skip(self))]
+    pub fn save_usage_state(&self) -> anyhow::Result<()> {
+        match self.usage_state_path.as_deref() {
+            Some(path) => self.usage.save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Saves the registry state of the model store so that it can be restored on the next startup.
+    /// The model stores attached at runtime are not saved, they must be attached again after a restart.
     ///
     /// # Arguments
-    /// - `model_name` (ModelName): The name of the model to use for the prediction.
-    /// - `input_json` (&str): The input data for the prediction, formatted as a JSON string.
+    ///
+    /// * `path` - The path to the state file.
+    /// * `codec` - The codec used to serialize the state.
     ///
     /// # Returns
-    /// - `Ok(())`: If the prediction was successfully made.
-    /// - `Err(anyhow::Error)`: If there was an error fetching the model, parsing the input, or making the prediction.
     ///
-    #[tracing::instrument(skip(self, input_json))]
-    pub fn predict(&self, model_name: ModelName, input_json: &str) -> anyhow::Result<String> {
-        let model = self.model_store.get_model(model_name.clone());
-        match model {
-            None => {
-                tracing::error!("No model exists for model name: {}", &model_name);
-                anyhow::bail!("No model exists for model name: {}", &model_name);
-            }
-            Some(model) => {
-                // parse input
-                match ModelInput::from_str(input_json) {
-                    Ok(input) => {
-                        // make predictions
-                        let output = match model.predictor.predict(input) {
-                            Ok(output) => output,
-                            Err(e) => {
-                                tracing::error!("Failed to make predictions: {}", e.to_string());
-                                anyhow::bail!("Failed to make predictions: {}", e.to_string());
-                            }
-                        };
+    /// * `Ok(())` if the state is successfully saved.
+    /// * `Err(anyhow::Error)` if the state cannot be captured or written.
+    #[tracing::instrument(skip(self, codec))]
+    pub fn save_registry_state(&self, path: &str, codec: &dyn RegistryCodec) -> anyhow::Result<()> {
+        self.model_store.registry_state()?.save(path, codec)
+    }
 
-                        // parse output
-                        match serde_json::to_string(&output) {
-                            Ok(json) => Ok(json),
-                            Err(e) => {
-                                tracing::error!("Failed to parse predictions: {}", e.to_string());
-                                anyhow::bail!("Failed to parse predictions: {}", e.to_string());
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to parse input: {}", e.to_string());
-                        anyhow::bail!("Failed to parse input: {}", e.to_string());
-                    }
-                }
+    /// Re-verifies the cached artifacts of the loaded models, reloading any model whose artifact is
+    /// missing or has changed since it was loaded. The models of the stores attached at runtime are not verified.
+    /// The reloads are checked against the memory budget, like the updates of models.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(IntegrityReport)` with the outcome of the verification for every loaded model.
+    /// * `Err(anyhow::Error)` if the loaded models could not be listed.
+    #[tracing::instrument(skip(self))]
+    pub async fn verify_integrity(&self) -> anyhow::Result<IntegrityReport> {
+        let verify = with_settings(
+            self.model_settings.clone(),
+            verify_models(&self.model_store),
+        );
+        with_skipped(
+            self.skipped.clone(),
+            with_budget(self.memory_budget, verify),
+        )
+        .await
+    }
+
+    /// Attaches a model store at runtime, e.g. a second bucket, and loads its models alongside the models which
+    /// are already served.
+    ///
+    /// The models of the store are named `<name>.<model_name>`, so that they cannot collide with the models of
+    /// the other stores. The store is polled for updates in the same way as the model store of the manager.
+    /// If a memory budget is configured, the models whose artifacts do not fit within the budget are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the store, which prefixes the names of its models.
+    /// * `source` - Where the store reads its models from.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<ModelName>)` with the names of the models loaded from the store.
+    /// * `Err(anyhow::Error)` if the name is invalid or taken, the store cannot be loaded, or its models exceed the
+    ///   memory budget.
+    #[tracing::instrument(skip(self))]
+    pub async fn attach_store(
+        &self,
+        name: String,
+        source: StoreSource,
+    ) -> anyhow::Result<Vec<ModelName>> {
+        AttachedStores::validate_name(name.as_str())?;
+        if self.attached_stores.contains(name.as_str()) {
+            anyhow::bail!("Model store {} is already attached ❌", name)
+        }
+        let connect = with_settings(
+            self.model_settings.clone(),
+            source.connect(self.store_timeouts),
+        );
+        let model_store = match with_skipped(
+            self.skipped.clone(),
+            with_budget(self.memory_budget, connect),
+        )
+        .await
+        {
+            Ok(model_store) => Arc::new(model_store),
+            Err(e) => {
+                tracing::error!("Failed to attach model store {} ❌: {}", name, e);
+                anyhow::bail!("Failed to attach model store {} ❌: {}", name, e)
+            }
+        };
+        if let Some(memory_budget) = self.memory_budget {
+            if let Err(e) = memory_budget.check(0) {
+                tracing::error!("Refusing to attach model store {} ❌: {}", name, e);
+                anyhow::bail!("Refusing to attach model store {} ❌: {}", name, e)
             }
         }
-    }
-}
 
-/// The `ManagerBuilder` struct is used to build a `Manager` instance with optional
-/// configurations, such as setting a polling interval for the model store.
-#[derive(Default)]
-pub struct ManagerBuilder {
-    // Note: `model_store` cannot use `#[derive(Default)]` as `Arc<dyn Storage>` doesn't have a default value.
-    model_store: Option<Arc<ModelStore>>, // Option is used to indicate it's initially None.
-    poll_interval: time::Duration,
-}
+        let poller = match self.poll_options.interval.is_zero() {
+            true => None,
+            false => Some(spawn_poller(
+                name.clone(),
+                model_store.clone(),
+                self.poll_options.clone(),
+                self.restart_policy,
+                self.memory_budget,
+                self.model_settings.clone(),
+                self.skipped.clone(),
+                self.labels.clone(),
+            )),
+        };
+        let models: Vec<ModelName> = model_store
+            .get_models()?
+            .into_iter()
+            .map(|metadata| qualify(name.as_str(), metadata.name.as_str()))
+            .collect();
+        self.attached_stores
+            .insert(name.clone(), source.clone(), model_store, poller)?;
+        for model_name in models.iter() {
+            self.usage.track(model_name.clone());
+        }
+        tracing::info!(
+            "Attached model store {} from {} with {} models ✅",
+            name,
+            source,
+            models.len()
+        );
+        Ok(models)
+    }
 
-impl ManagerBuilder {
-    /// Creates a new `ManagerBuilder` instance with a given `model_store`.
+    /// Detaches a model store which was attached at runtime. Its models stop being served, and are dropped once
+    /// the predictions in flight finish.
     ///
     /// # Arguments
-    /// - `model_store`: An `Arc` reference to a `Storage` trait object that represents
-    ///     the model storage.
+    ///
+    /// * `name` - The name of the store.
     ///
     /// # Returns
-    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
     ///
-    pub fn new(model_store: Arc<ModelStore>) -> ManagerBuilder {
-        ManagerBuilder {
-            model_store: Some(model_store),
-            poll_interval: time::Duration::from_secs(0),
+    /// * `Ok(())` if the store is detached.
+    /// * `Err(anyhow::Error)` if no store with the given name is attached.
+    #[tracing::instrument(skip(self))]
+    pub fn detach_store(&self, name: &str) -> anyhow::Result<()> {
+        let model_store = match self.attached_stores.remove(name) {
+            Some(model_store) => model_store,
+            None => anyhow::bail!("Model store {} is not attached ❌", name),
+        };
+        for metadata in model_store.get_models().unwrap_or_default() {
+            let model_name = qualify(name, metadata.name.as_str());
+            self.labels.clear(model_name.as_str());
+            self.usage.clear(model_name.as_str());
+            self.variant_latencies.clear(model_name.as_str());
+            if let Some(model) = model_store.get_model(metadata.name) {
+                schedule_teardown(model);
+            }
         }
+        tracing::info!("Detached model store {} ✅", name);
+        Ok(())
     }
 
-    /// Configures the `ManagerBuilder` to poll the model store at the specified interval.
+    /// Describes the model stores attached at runtime, sorted by name.
+    pub fn get_stores(&self) -> Vec<AttachedStoreInfo> {
+        self.attached_stores.describe()
+    }
+
+    /// Returns the time spent predicting with each precision variant of the models shipped with variants,
+    /// sorted by model name and precision, to compare the latency of the variants of a model.
+    pub fn get_variant_latencies(&self) -> Vec<VariantLatency> {
+        self.variant_latencies.snapshot()
+    }
+
+    /// Returns the latency and error statistics of every loaded model, sorted by model name.
+    ///
+    /// # Errors
+    /// Returns an error if there are issues fetching the models from the store.
+    pub fn get_model_stats(&self) -> anyhow::Result<Vec<ModelStatsSnapshot>> {
+        let mut stats: Vec<ModelStatsSnapshot> = self
+            .get_models()?
+            .iter()
+            .filter_map(|metadata| {
+                let (model_store, store_model_name) = self.store_of(metadata.name.as_str());
+                model_store
+                    .get_model(store_model_name)
+                    .map(|model| model.stats.snapshot(metadata.name.as_str()))
+            })
+            .collect();
+        stats.sort_by(|a, b| a.model_name.cmp(&b.model_name));
+        Ok(stats)
+    }
+
+    /// Returns the number of predictions routed to the lightweight variant of each model because the model
+    /// exceeded the latency budget of the requests, sorted by model name.
+    pub fn get_latency_fallbacks(&self) -> Vec<LatencyFallback> {
+        self.latency_fallbacks.snapshot()
+    }
+
+    /// Returns the priority of a prediction on the worker pool, i.e. the priority set in the prediction options
+    /// of the model, else the default priority. The priority requested in the options of the request can only
+    /// lower it, e.g. for bulk scoring, so that clients cannot move their requests ahead of the others. Unknown
+    /// models get the default priority, as they fail once they are picked up by a worker anyway.
     ///
     /// # Arguments
-    /// - `interval`: A `u64` that specifies the interval(in seconds) between each polling operation.
     ///
-    /// # Returns
-    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    /// * `model_name` - The name of the model used for the prediction.
+    /// * `options` - The prediction options sent with the request.
+    pub fn priority_of(&self, model_name: &str, options: &PredictOptions) -> Priority {
+        let (model_store, store_model_name) = self.store_of(model_name);
+        let priority = model_store
+            .get_model(store_model_name)
+            .and_then(|model| model.info.predict_options.priority)
+            .unwrap_or_default();
+        match options.priority {
+            Some(requested) if requested > priority => {
+                tracing::warn!(
+                    "Ignoring priority {} of the request to model {}, it can only lower the priority {} of the model ⚠️",
+                    requested.as_str(),
+                    model_name,
+                    priority.as_str()
+                );
+                priority
+            }
+            Some(requested) => requested,
+            None => priority,
+        }
+    }
+
+    /// Returns the weight of a model on the worker pool it shares with other models, i.e. its configured weight,
+    /// else the weight set by its `scheduling_weight` label, else `DEFAULT_WEIGHT`. Labels which are not a
+    /// positive number are ignored.
     ///
-    pub fn with_polling(mut self, interval: u64) -> ManagerBuilder {
-        self.poll_interval = time::Duration::from_secs(interval);
-        self
+    /// # Arguments
+    ///
+    /// * `model_name` - The name of the model used for the prediction.
+    pub fn scheduling_weight_of(&self, model_name: &str) -> f64 {
+        if let Some(weight) = self.scheduling_weights.get(model_name) {
+            return *weight;
+        }
+        let (model_store, store_model_name) = self.store_of(model_name);
+        model_store
+            .get_model(store_model_name)
+            .and_then(|model| {
+                self.labels
+                    .value_of(model_name, SCHEDULING_WEIGHT_LABEL, &model.info.labels)
+            })
+            .and_then(|weight| weight.parse::<f64>().ok())
+            .filter(|weight| weight.is_finite() && *weight > 0.0)
+            .unwrap_or(DEFAULT_WEIGHT)
     }
 
-    /// Builds the `Manager` instance.
+    /// Returns the name of the deployment group a model belongs to, if any. The models of a deployment group are
+    /// only deployed together, when the model store is polled.
     ///
-    /// If a polling interval is set, a background task is spawned that polls the
-    /// model store periodically to update the models.
+    /// # Arguments
     ///
-    /// # Returns
-    /// - `Ok(Manager)`: The successfully created `Manager` instance.
-    /// - `Err(anyhow::Error)`: If there was an error during model polling.
+    /// * `model_name` - The name of the model, optionally prefixed with its framework and the name of its store.
+    pub fn deployment_group_of(&self, model_name: &str) -> Option<String> {
+        let (_, store_model_name) = self.store_of(model_name);
+        self.poll_options
+            .deployment_groups
+            .group_of(strip_framework_prefix(store_model_name).as_str())
+            .map(|group| group.name.clone())
+    }
+
+    /// Refuses to add or update a model of a deployment group on its own, as its group is deployed together.
+    fn refuse_group_member(&self, model_name: &str) -> anyhow::Result<()> {
+        if let Some(group) = self.deployment_group_of(model_name) {
+            tracing::error!(
+                "Refusing to update model {} of deployment group {} on its own ❌",
+                model_name,
+                group
+            );
+            anyhow::bail!(
+                "Refusing to update model {} of deployment group {} on its own ❌, its group is deployed on the next poll",
+                model_name,
+                group
+            )
+        }
+        Ok(())
+    }
+
+    /// Returns the model store which serves a model, along with the name of the model in that store.
+    fn store_of(&self, model_name: &str) -> (Arc<ModelStore>, ModelName) {
+        match self.attached_stores.resolve(model_name) {
+            Some(resolved) => resolved,
+            None => (self.model_store.clone(), model_name.to_string()),
+        }
+    }
+
+    /// Predicts using the specified model and input data.
     ///
-    pub fn build(self) -> anyhow::Result<Manager> {
-        let model_store = self
-            .model_store
-            .ok_or_else(|| anyhow::anyhow!("Model store is required ❌"))?;
-        if !self.poll_interval.is_zero() {
+    /// This method fetches the specified model from the storage, parses the input data,
+    /// and executes the prediction.
+    ///
+    /// # Arguments
+    /// - `model_name` (ModelName): The name of the model to use for the prediction.
+    /// - `input_json` (&str): The input data for the prediction, formatted as a JSON string.
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the prediction was successfully made.
+    /// - `Err(anyhow::Error)`: If there was an error fetching the model, parsing the input, or making the prediction.
+    ///
+    #[tracing::instrument(skip(self, input_json))]
+    pub fn predict(&self, model_name: ModelName, input_json: &str) -> anyhow::Result<String> {
+        self.predict_with_options(model_name, input_json, &PredictOptions::default())
+    }
+
+    /// Predicts using the specified model, input data and prediction options.
+    ///
+    /// The options are applied on top of the default prediction options of the model, which are
+    /// read from the `predict_options.json` file in the model tarball. The output precision is applied
+    /// when serializing the predictions, after calibration.
+    ///
+    /// # Arguments
+    /// - `model_name` (ModelName): The name of the model to use for the prediction.
+    /// - `input_json` (&str): The input data for the prediction, formatted as a JSON string.
+    /// - `options` (&PredictOptions): The prediction options sent with the request.
+    ///
+    /// # Returns
+    /// - `Ok(String)`: The predictions formatted as a JSON string.
+    /// - `Err(anyhow::Error)`: If there was an error fetching the model, parsing the input, or making the prediction.
+    ///   An `Error::ModelNotFound` if the model does not exist, which is recorded as a miss.
+    ///
+    pub fn predict_with_options(
+        &self,
+        model_name: ModelName,
+        input_json: &str,
+        options: &PredictOptions,
+    ) -> anyhow::Result<String> {
+        self.predict_with_variant(model_name, input_json, options)
+            .map(|prediction| prediction.output)
+    }
+
+    /// Predicts like `predict_with_options`, and reports the precision variant of the model which made the
+    /// predictions, e.g. the lightweight variant the request fell back to because of its latency budget.
+    ///
+    /// # Arguments
+    /// - `model_name` (ModelName): The name of the model to use for the prediction.
+    /// - `input_json` (&str): The input data for the prediction, formatted as a JSON string.
+    /// - `options` (&PredictOptions): The prediction options sent with the request.
+    ///
+    /// # Returns
+    /// - `Ok(ServedPrediction)`: The predictions formatted as a JSON string and the variant which made them.
+    /// - `Err(anyhow::Error)`: If there was an error fetching the model, parsing the input, or making the prediction.
+    ///   An `Error::ModelNotFound` if the model does not exist, which is recorded as a miss, an `Error::Input` if
+    ///   the input or the requested variant is invalid for the model and an `Error::Predict` if the model failed.
+    ///
+    #[tracing::instrument(skip(self, input_json, options))]
+    pub fn predict_with_variant(
+        &self,
+        model_name: ModelName,
+        input_json: &str,
+        options: &PredictOptions,
+    ) -> anyhow::Result<ServedPrediction> {
+        let (model_store, store_model_name) = self.store_of(model_name.as_str());
+        let model = model_store.get_model(store_model_name);
+        match model {
+            None => Err(self.model_not_found(model_name)),
+            Some(model) => {
+                check_model_version(model_name.as_str(), &model.info, options)?;
+                self.usage.record(model_name.as_str());
+
+                if let Err(e) = self.chaos.predict.inject_blocking("prediction") {
+                    tracing::error!("Failed to make predictions: {}", e);
+                    anyhow::bail!("Failed to make predictions: {}", e);
+                }
+
+                // parse input
+                match ModelInput::from_str(input_json) {
+                    Ok(mut input) => {
+                        if options.parallel_batches.is_some() {
+                            tracing::warn!(
+                                "Ignoring parallel_batches of the request to model {}, it can only be set per model ⚠️",
+                                model_name
+                            );
+                        }
+
+                        // apply the vocabularies and scalers shipped in the model bundle
+                        let options = model.info.predict_options.merge(options);
+                        let unknown_features = options.unknown_features.unwrap_or_default();
+                        if let Err(e) = model.bundle.preprocess(&mut input, unknown_features) {
+                            tracing::error!("Failed to preprocess input: {}", e);
+                            return Err(
+                                Error::Input(e.context("Failed to preprocess input")).into()
+                            );
+                        }
+
+                        // select the precision variant of the model, falling back to its lightweight variant
+                        // while the model exceeds the latency budget of the request
+                        let fallback = match (options.variant, options.latency_budget_ms) {
+                            (None, Some(budget)) => self.latency_fallbacks.select(
+                                model_name.as_str(),
+                                &model.info.variants,
+                                model.stats.quantile(0.95),
+                                time::Duration::from_millis(budget),
+                            ),
+                            _ => None,
+                        };
+                        if let Some(fallback) = fallback {
+                            tracing::debug!(
+                                "Model {} exceeds the latency budget, predicting with its {} variant ⏱️",
+                                model_name,
+                                fallback
+                            );
+                        }
+                        let precision = fallback.or(options.variant).unwrap_or_default();
+                        let predictor = match model.predictor(precision) {
+                            Ok(predictor) => predictor,
+                            Err(e) => {
+                                tracing::error!("Failed to make predictions: {}", e);
+                                return Err(Error::Input(e).into());
+                            }
+                        };
+
+                        // make predictions
+                        let model_options = options.model_options();
+                        let start = Instant::now();
+                        let prediction = match options.max_batch_size {
+                            Some(max_batch_size) => predictor.predict_in_batches(
+                                input,
+                                &model_options,
+                                max_batch_size,
+                                options.parallel_batches.unwrap_or(false),
+                            ),
+                            None => predictor.predict_with_options(input, &model_options),
+                        };
+                        // the stats of the model track the latency of the model artifact, and the latencies
+                        // of its variants are compared separately
+                        let elapsed = start.elapsed();
+                        match (&prediction, precision) {
+                            (Err(_), _) => model.stats.record_error(),
+                            (Ok(_), ModelPrecision::Fp32) => model.stats.record_latency(elapsed),
+                            (Ok(_), _) => {}
+                        }
+                        if prediction.is_ok() && !model.variants.is_empty() {
+                            self.variant_latencies
+                                .record(model_name.as_str(), precision, elapsed);
+                        }
+                        let mut output = match prediction {
+                            Ok(output) => output,
+                            Err(e) => {
+                                tracing::error!("Failed to make predictions: {}", e);
+                                return Err(e.into());
+                            }
+                        };
+
+                        // add calibrated probabilities for models shipped with a calibration map
+                        if let Some(calibration) = &model.calibration {
+                            if let Err(e) = calibration.apply(&mut output) {
+                                tracing::error!("Failed to calibrate predictions: {}", e);
+                                return Err(Error::Predict(
+                                    e.context("Failed to calibrate predictions"),
+                                )
+                                .into());
+                            }
+                        }
+
+                        // add the outputs of the postprocessing program shipped with the model, if any
+                        if let Some(postprocess) = &model.postprocess {
+                            if let Err(e) = postprocess.apply(&mut output) {
+                                tracing::error!("Failed to postprocess predictions: {}", e);
+                                return Err(Error::Predict(
+                                    e.context("Failed to postprocess predictions"),
+                                )
+                                .into());
+                            }
+                        }
+
+                        // parse output, at the requested precision if any
+                        let json = match &options.precision {
+                            Some(precision) => output.to_json_with_precision(precision),
+                            None => output.to_json(),
+                        };
+                        match json {
+                            Ok(json) => Ok(ServedPrediction {
+                                output: json,
+                                precision,
+                            }),
+                            Err(e) => {
+                                tracing::error!("Failed to parse predictions: {}", e);
+                                Err(Error::Predict(e.context("Failed to parse predictions")).into())
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to parse input: {}", e);
+                        Err(Error::Input(e.context("Failed to parse input")).into())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks that a model is loaded, e.g. before its prediction is queued.
+    ///
+    /// # Arguments
+    /// - `model_name` (&str): The name of the model.
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the model is loaded.
+    /// - `Err(anyhow::Error)`: An `Error::ModelNotFound` if the model does not exist, which is recorded as a miss.
+    pub fn check_model(&self, model_name: &str) -> anyhow::Result<()> {
+        let (model_store, store_model_name) = self.store_of(model_name);
+        match model_store.get_model(store_model_name) {
+            None => Err(self.model_not_found(model_name.to_string())),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Checks that a model streams its predictions, so that a streaming request can be rejected before it is
+    /// scheduled.
+    ///
+    /// # Arguments
+    /// - `model_name` (&str): The name of the model.
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the model streams its predictions, see `Predictor::streaming`.
+    /// - `Err(anyhow::Error)`: An `Error::ModelNotFound` if the model does not exist, which is recorded as a miss,
+    ///   or an `Error::Input` if it does not stream its predictions.
+    pub fn check_streaming(&self, model_name: &str) -> anyhow::Result<()> {
+        let (model_store, store_model_name) = self.store_of(model_name);
+        match model_store.get_model(store_model_name) {
+            None => Err(self.model_not_found(model_name.to_string())),
+            Some(model) if model.predictor.streaming().is_none() => Err(Error::Input(
+                anyhow::anyhow!("Model {} does not stream its predictions ❌", model_name),
+            )
+            .into()),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Predicts using a model which yields its outputs incrementally, e.g. a sequence model, emitting each chunk
+    /// of the output as soon as it is ready.
+    ///
+    /// The default prediction options of the model, its bundle and the output precision are applied like for
+    /// `predict_with_options`. Batching and latency fallbacks do not apply to streams.
+    ///
+    /// # Arguments
+    /// - `model_name` (ModelName): The name of the model to use for the prediction.
+    /// - `input_json` (&str): The input data for the prediction, formatted as a JSON string.
+    /// - `options` (&PredictOptions): The prediction options sent with the request.
+    /// - `emit` (&mut dyn FnMut(String) -> bool): Called with each chunk of the output formatted as a JSON string.
+    ///   Returns false once nobody is listening anymore, which stops the prediction.
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the prediction was streamed, or stopped because nobody was listening anymore.
+    /// - `Err(anyhow::Error)`: If there was an error fetching the model, parsing the input, or making the prediction.
+    ///   An `Error::ModelNotFound` if the model does not exist, which is recorded as a miss. The chunks emitted
+    ///   before the error have already been delivered.
+    #[tracing::instrument(skip(self, input_json, options, emit))]
+    pub fn predict_stream(
+        &self,
+        model_name: ModelName,
+        input_json: &str,
+        options: &PredictOptions,
+        emit: &mut dyn FnMut(String) -> bool,
+    ) -> anyhow::Result<()> {
+        let (model_store, store_model_name) = self.store_of(model_name.as_str());
+        let model = match model_store.get_model(store_model_name) {
+            Some(model) => model,
+            None => return Err(self.model_not_found(model_name)),
+        };
+        check_model_version(model_name.as_str(), &model.info, options)?;
+        self.usage.record(model_name.as_str());
+
+        if let Err(e) = self.chaos.predict.inject_blocking("prediction") {
+            tracing::error!("Failed to make predictions: {}", e);
+            anyhow::bail!("Failed to make predictions: {}", e);
+        }
+
+        let mut input = match ModelInput::from_str(input_json) {
+            Ok(input) => input,
+            Err(e) => {
+                tracing::error!("Failed to parse input: {}", e);
+                return Err(Error::Input(e.context("Failed to parse input")).into());
+            }
+        };
+        let options = model.info.predict_options.merge(options);
+        let unknown_features = options.unknown_features.unwrap_or_default();
+        if let Err(e) = model.bundle.preprocess(&mut input, unknown_features) {
+            tracing::error!("Failed to preprocess input: {}", e);
+            return Err(Error::Input(e.context("Failed to preprocess input")).into());
+        }
+
+        let predictor = match model.predictor(options.variant.unwrap_or_default()) {
+            Ok(predictor) => predictor,
+            Err(e) => {
+                tracing::error!("Failed to make predictions: {}", e);
+                return Err(Error::Input(e).into());
+            }
+        };
+        let streaming = match predictor.streaming() {
+            Some(streaming) => streaming,
+            None => {
+                tracing::error!("Model {} does not stream its predictions", model_name);
+                return Err(Error::Input(anyhow::anyhow!(
+                    "Model {} does not stream its predictions ❌",
+                    model_name
+                ))
+                .into());
+            }
+        };
+
+        // serialize each chunk, at the requested precision if any
+        let mut serialization_error = None;
+        let result = streaming.predict_stream(input, &mut |output| {
+            let json = match &options.precision {
+                Some(precision) => output.to_json_with_precision(precision),
+                None => output.to_json(),
+            };
+            match json {
+                Ok(json) => emit(json),
+                Err(e) => {
+                    serialization_error = Some(e);
+                    false
+                }
+            }
+        });
+        if let Err(e) = result {
+            tracing::error!("Failed to make predictions: {}", e);
+            return Err(e.into());
+        }
+        if let Some(e) = serialization_error {
+            tracing::error!("Failed to parse predictions: {}", e);
+            return Err(Error::Predict(e.context("Failed to parse predictions")).into());
+        }
+        Ok(())
+    }
+}
+
+/// The `ManagerBuilder` struct is used to build a `Manager` instance with optional
+/// configurations, such as setting a polling interval for the model store.
+#[derive(Default)]
+pub struct ManagerBuilder {
+    // Note: `model_store` cannot use `#[derive(Default)]` as `Arc<dyn Storage>` doesn't have a default value.
+    model_store: Option<Arc<ModelStore>>, // Option is used to indicate it's initially None.
+    poll_options: PollOptions,
+    memory_budget: Option<MemoryBudget>,
+    integrity_check_interval: time::Duration,
+    temp_dir_retention: Option<time::Duration>,
+    temp_dir_gc_interval: time::Duration,
+    chaos: ChaosConfig,
+    priority_models: Vec<ModelName>,
+    store_timeouts: StoreTimeouts,
+    restart_policy: RestartPolicy,
+    latency_fallbacks: BTreeMap<ModelName, ModelPrecision>,
+    scheduling_weights: BTreeMap<ModelName, f64>,
+    model_suggestions: bool,
+    usage_state_path: Option<PathBuf>,
+    usage_save_interval: time::Duration,
+    model_settings: Option<Arc<ModelSettingsConfig>>,
+    skipped: Arc<SkippedModels>,
+}
+
+impl ManagerBuilder {
+    /// Creates a new `ManagerBuilder` instance with a given `model_store`.
+    ///
+    /// # Arguments
+    /// - `model_store`: An `Arc` reference to a `Storage` trait object that represents
+    ///     the model storage.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn new(model_store: Arc<ModelStore>) -> ManagerBuilder {
+        ManagerBuilder {
+            model_store: Some(model_store),
+            poll_options: PollOptions::default(),
+            memory_budget: None,
+            integrity_check_interval: time::Duration::from_secs(0),
+            temp_dir_retention: None,
+            temp_dir_gc_interval: time::Duration::from_secs(0),
+            chaos: ChaosConfig::default(),
+            priority_models: Vec::new(),
+            store_timeouts: StoreTimeouts::default(),
+            restart_policy: RestartPolicy::default(),
+            latency_fallbacks: BTreeMap::new(),
+            scheduling_weights: BTreeMap::new(),
+            model_suggestions: false,
+            usage_state_path: None,
+            usage_save_interval: time::Duration::from_secs(0),
+            model_settings: None,
+            skipped: Arc::default(),
+        }
+    }
+
+    /// Configures the `ManagerBuilder` to poll the model store at the specified interval.
+    ///
+    /// # Arguments
+    /// - `interval`: A `u64` that specifies the interval(in seconds) between each polling operation.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_polling(mut self, interval: u64) -> ManagerBuilder {
+        self.poll_options.interval = time::Duration::from_secs(interval);
+        self
+    }
+
+    /// Configures the `ManagerBuilder` to add a random jitter to each polling interval.
+    ///
+    /// # Arguments
+    /// - `jitter`: A `u64` that specifies the upper bound(in seconds) of the random delay added to each interval.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_poll_jitter(mut self, jitter: u64) -> ManagerBuilder {
+        self.poll_options.jitter = time::Duration::from_secs(jitter);
+        self
+    }
+
+    /// Configures the `ManagerBuilder` to limit the number of models downloaded at the same time while polling.
+    ///
+    /// # Arguments
+    /// - `limit`: A `usize` that specifies the maximum number of concurrent downloads. A value of 0 is treated as 1.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_max_concurrent_downloads(mut self, limit: usize) -> ManagerBuilder {
+        self.poll_options.max_concurrent_downloads = limit.max(1);
+        self
+    }
+
+    /// Configures how the pollers of the model stores retry the polls which fail or panic.
+    ///
+    /// # Arguments
+    /// - `restart_policy`: A `RestartPolicy` with the backoff after failed polls and the number of consecutive
+    ///   failures after which a poller is unhealthy.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_restart_policy(mut self, restart_policy: RestartPolicy) -> ManagerBuilder {
+        self.restart_policy = restart_policy;
+        self
+    }
+
+    /// Configures the `ManagerBuilder` to enforce a memory budget when adding or updating models.
+    ///
+    /// # Arguments
+    /// - `limit_mb`: A `u64` that specifies the memory budget(in MiB) for the process. A value of 0 disables the budget.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_memory_budget(mut self, limit_mb: u64) -> ManagerBuilder {
+        self.memory_budget = MemoryBudget::from_mb(limit_mb);
+        self
+    }
+
+    /// Configures the `ManagerBuilder` to periodically re-verify the cached artifacts of the loaded models.
+    ///
+    /// # Arguments
+    /// - `interval`: A `u64` that specifies the interval(in seconds) between each verification. A value of 0 disables it.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_integrity_check(mut self, interval: u64) -> ManagerBuilder {
+        self.integrity_check_interval = time::Duration::from_secs(interval);
+        self
+    }
+
+    /// Configures the `ManagerBuilder` to remove the model store directories orphaned by crashed processes.
+    ///
+    /// # Arguments
+    /// - `retention`: A `u64` that specifies how long(in seconds) a directory without an owner lock, left by a release
+    ///     without owner locks, is kept before it is removed. A value of 0 disables the collection.
+    /// - `interval`: A `u64` that specifies the interval(in seconds) between each collection after the one at startup.
+    ///     A value of 0 only collects at startup.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_temp_dir_gc(mut self, retention: u64, interval: u64) -> ManagerBuilder {
+        self.temp_dir_retention = match retention {
+            0 => None,
+            retention => Some(time::Duration::from_secs(retention)),
+        };
+        self.temp_dir_gc_interval = time::Duration::from_secs(interval);
+        self
+    }
+
+    /// Configures the `ManagerBuilder` to inject artificial latency and random failures into predictions
+    /// and model downloads. This is a developer mode for testing timeouts, retries and circuit breakers
+    /// and must not be enabled in production.
+    ///
+    /// # Arguments
+    /// - `chaos`: A `ChaosConfig` that specifies the faults injected into predictions and model downloads.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_chaos(mut self, chaos: ChaosConfig) -> ManagerBuilder {
+        self.chaos = chaos;
+        self.poll_options.download_fault = chaos.download;
+        self
+    }
+
+    /// Configures the `ManagerBuilder` with the models which the model store loaded first at startup, e.g. with
+    /// `LocalModelStore::with_priority_models`. The other models are loaded in the background once the manager is built.
+    ///
+    /// # Arguments
+    /// - `priority_models`: A `Vec<ModelName>` that specifies the models which must be loaded for the manager to be ready.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_priority_models(mut self, priority_models: Vec<ModelName>) -> ManagerBuilder {
+        self.priority_models = priority_models;
+        self
+    }
+
+    /// Configures the `ManagerBuilder` with the groups of models whose updates are swapped in together or not at all
+    /// when the model stores are polled.
+    ///
+    /// # Arguments
+    /// - `deployment_groups`: The `DeploymentGroups`, e.g. a feature encoder and the scorer consuming its outputs.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_deployment_groups(mut self, deployment_groups: DeploymentGroups) -> ManagerBuilder {
+        self.poll_options.deployment_groups = Arc::new(deployment_groups);
+        self
+    }
+
+    /// Configures the `ManagerBuilder` with the settings of the models, which are passed to the init hooks of the
+    /// models the manager loads, i.e. the models which are added, updated, polled or reloaded after a failed
+    /// integrity check, and the models of the stores attached at runtime. The models loaded by the model store before
+    /// the manager is built get the same settings if they are loaded within `with_settings`.
+    ///
+    /// # Arguments
+    /// - `model_settings`: A `ModelSettingsConfig` with the settings of each model, shared with `with_settings`.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_model_settings(
+        mut self,
+        model_settings: Arc<ModelSettingsConfig>,
+    ) -> ManagerBuilder {
+        self.model_settings = Some(model_settings);
+        self
+    }
+
+    /// Configures the `ManagerBuilder` with the registry of the models which were skipped or failed to load, so that
+    /// the models skipped by the model store before the manager is built are reported along with the models the
+    /// manager skips, if they are loaded within `with_skipped`.
+    ///
+    /// # Arguments
+    /// - `skipped`: The `SkippedModels` shared with `with_skipped`.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_skipped_models(mut self, skipped: Arc<SkippedModels>) -> ManagerBuilder {
+        self.skipped = skipped;
+        self
+    }
+
+    /// Configures the `ManagerBuilder` with the deadlines of the calls made to the model stores attached at runtime.
+    ///
+    /// # Arguments
+    /// - `timeouts`: A `StoreTimeouts` that specifies the deadlines for listing and downloading artifacts.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_store_timeouts(mut self, timeouts: StoreTimeouts) -> ManagerBuilder {
+        self.store_timeouts = timeouts;
+        self
+    }
+
+    /// Configures the `ManagerBuilder` with the lightweight variant of each model which serves the requests whose
+    /// latency budget is exceeded by the recent p95 latency of the model.
+    ///
+    /// # Arguments
+    /// - `latency_fallbacks`: A `BTreeMap` from the name of each model to the precision of its lightweight variant, e.g. `int8`.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_latency_fallbacks(
+        mut self,
+        latency_fallbacks: BTreeMap<ModelName, ModelPrecision>,
+    ) -> ManagerBuilder {
+        self.latency_fallbacks = latency_fallbacks;
+        self
+    }
+
+    /// Configures the `ManagerBuilder` with the weight of each model on the worker pool it shares with other models.
+    /// Under load, each model gets a share of the workers in proportion to its weight, so that models with many
+    /// requests cannot starve models with few of them. The weights take precedence over the `scheduling_weight`
+    /// labels of the models.
+    ///
+    /// # Arguments
+    /// - `scheduling_weights`: A `BTreeMap` from the name of each model to its weight, which must be a positive number.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_scheduling_weights(
+        mut self,
+        scheduling_weights: BTreeMap<ModelName, f64>,
+    ) -> ManagerBuilder {
+        self.scheduling_weights = scheduling_weights;
+        self
+    }
+
+    /// Configures the `ManagerBuilder` to suggest the closest loaded model names for the requests made to a model
+    /// which is not loaded, e.g. because of a typo.
+    ///
+    /// # Arguments
+    /// - `enabled`: A `bool` that specifies whether model names are suggested.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_model_suggestions(mut self, enabled: bool) -> ManagerBuilder {
+        self.model_suggestions = enabled;
+        self
+    }
+
+    /// Configures the `ManagerBuilder` to restore the usage of the models from a usage state file and to save it
+    /// back periodically, so that request counts and access times carry over a restart.
+    ///
+    /// # Arguments
+    /// - `path`: A `String` that specifies the path to the usage state file.
+    /// - `interval`: A `u64` that specifies the interval(in seconds) between each save. A value of 0 only saves the
+    ///     usage when `Manager::save_usage_state` is called, e.g. on shutdown.
+    ///
+    /// # Returns
+    /// - `ManagerBuilder`: A builder object used to configure and build a `Manager`.
+    ///
+    pub fn with_usage_state(mut self, path: String, interval: u64) -> ManagerBuilder {
+        self.usage_state_path = Some(PathBuf::from(path));
+        self.usage_save_interval = time::Duration::from_secs(interval);
+        self
+    }
+
+    /// Builds the `Manager` instance.
+    ///
+    /// If priority models are set, a background task is spawned that loads the other models of the
+    /// model store right away, regardless of the polling interval.
+    ///
+    /// If a polling interval is set, a background task is spawned that polls the
+    /// model store periodically to update the models.
+    ///
+    /// If an integrity check interval is set, a background task is spawned that re-hashes
+    /// the cached model artifacts periodically and reloads any model which was corrupted.
+    ///
+    /// If a temp directory retention is set, the model store directories orphaned by crashed processes
+    /// are removed, and a background task is spawned that removes them periodically if an interval is set.
+    ///
+    /// If a usage state file is set, the usage of the models is restored from it, and a background task is
+    /// spawned that saves the usage to it periodically if an interval is set.
+    ///
+    /// # Returns
+    /// - `Ok(Manager)`: The successfully created `Manager` instance.
+    /// - `Err(anyhow::Error)`: If there was an error during model polling, the models loaded by the
+    ///   model store already exceed the memory budget, the chaos faults are invalid, a scheduling
+    ///   weight is not a positive number or the usage state file cannot be read.
+    ///
+    pub fn build(self) -> anyhow::Result<Manager> {
+        let model_store = self
+            .model_store
+            .ok_or_else(|| anyhow::anyhow!("Model store is required ❌"))?;
+        if let Some((model_name, weight)) = self
+            .scheduling_weights
+            .iter()
+            .find(|(_, weight)| !(weight.is_finite() && **weight > 0.0))
+        {
+            tracing::error!(
+                "Scheduling weight {} of model {} must be a positive number ❌",
+                weight,
+                model_name
+            );
+            anyhow::bail!(
+                "Scheduling weight {} of model {} must be a positive number ❌",
+                weight,
+                model_name
+            )
+        }
+        if self.chaos.is_enabled() {
+            if let Err(e) = self.chaos.validate() {
+                tracing::error!("Failed to enable chaos mode ❌: {}", e);
+                anyhow::bail!("Failed to enable chaos mode ❌: {}", e)
+            }
+            tracing::warn!(
+                "Chaos mode enabled, injecting faults into predictions {:?} and model downloads {:?} 💥",
+                self.chaos.predict,
+                self.chaos.download
+            );
+        }
+        if let Some(memory_budget) = self.memory_budget {
+            if let Err(e) = memory_budget.check(0) {
+                tracing::error!(
+                    "Models loaded at startup exceed the memory budget ❌: {}",
+                    e
+                );
+                anyhow::bail!(
+                    "Models loaded at startup exceed the memory budget ❌: {}",
+                    e
+                )
+            }
+            tracing::info!(
+                "Memory budget of {} MiB enabled 🧠",
+                memory_budget.limit_bytes() / MEBIBYTE
+            );
+        }
+        // the models loaded in the background are only checked when they serve a request with a latency budget
+        let latency_fallbacks = LatencyFallbacks::new(self.latency_fallbacks);
+        if let Err(e) = latency_fallbacks.validate(&model_store.get_models()?) {
+            tracing::error!("Failed to configure latency fallbacks ❌: {}", e);
+            anyhow::bail!("Failed to configure latency fallbacks ❌: {}", e)
+        }
+        let remaining_models_loaded = Arc::new(AtomicBool::new(self.priority_models.is_empty()));
+        if !self.priority_models.is_empty() {
+            tracing::info!(
+                "Loaded {} of {} priority models, loading the remaining models in the background ⏳",
+                model_store.get_models()?.len(),
+                self.priority_models.len()
+            );
             let model_store_clone = model_store.clone();
+            let remaining_models_loaded_clone = remaining_models_loaded.clone();
+            let poll_options = PollOptions {
+                interval: time::Duration::from_secs(0),
+                jitter: time::Duration::from_secs(0),
+                ..self.poll_options.clone()
+            };
+            let memory_budget = self.memory_budget;
+            let model_settings = self.model_settings.clone();
+            let skipped = self.skipped.clone();
+            tokio::spawn(async move {
+                let poll = with_settings(model_settings, model_store_clone.poll(&poll_options));
+                match with_skipped(skipped, with_budget(memory_budget, poll)).await {
+                    Ok(_) => {
+                        log::info!("Successfully loaded the remaining models ✅");
+                    }
+                    Err(e) => {
+                        log::error!("Failed to load the remaining models ❌: {}", e);
+                    }
+                }
+                remaining_models_loaded_clone.store(true, Ordering::Relaxed);
+            });
+        }
+        let labels = Arc::new(ModelLabels::default());
+        let poller = match self.poll_options.interval.is_zero() {
+            true => None,
+            false => Some(spawn_poller(
+                DEFAULT_STORE_NAME.to_string(),
+                model_store.clone(),
+                self.poll_options.clone(),
+                self.restart_policy,
+                self.memory_budget,
+                self.model_settings.clone(),
+                self.skipped.clone(),
+                labels.clone(),
+            )),
+        };
+        if !self.integrity_check_interval.is_zero() {
+            let model_store_clone = model_store.clone();
+            let model_settings = self.model_settings.clone();
+            let skipped = self.skipped.clone();
+            let memory_budget = self.memory_budget;
             tokio::spawn(async move {
                 loop {
-                    match model_store_clone.poll(self.poll_interval).await {
-                        Ok(_) => {
-                            log::info!("Successfully polled the model store ✅");
+                    time::sleep(self.integrity_check_interval).await;
+                    let verify =
+                        with_settings(model_settings.clone(), verify_models(&model_store_clone));
+                    match with_skipped(skipped.clone(), with_budget(memory_budget, verify)).await {
+                        Ok(report) => {
+                            log::info!(
+                                "Verified integrity of {} models, reloaded {} and failed to reload {} ✅",
+                                report.verified.len(),
+                                report.reloaded.len(),
+                                report.failed.len()
+                            );
                         }
                         Err(e) => {
-                            log::error!("Failed to poll the model store ❌: {}", e);
+                            log::error!("Failed to verify the integrity of the models ❌: {}", e);
                         }
                     }
                 }
             });
         };
 
-        Ok(Manager { model_store })
+        if let Some(retention) = self.temp_dir_retention {
+            let model_store_dir = PathBuf::from(model_store.model_store_dir());
+            log_gc_report(collect_orphaned_dirs(&model_store_dir, retention));
+            if !self.temp_dir_gc_interval.is_zero() {
+                tokio::spawn(async move {
+                    loop {
+                        time::sleep(self.temp_dir_gc_interval).await;
+                        let model_store_dir = model_store_dir.clone();
+                        match tokio::task::spawn_blocking(move || {
+                            collect_orphaned_dirs(&model_store_dir, retention)
+                        })
+                        .await
+                        {
+                            Ok(result) => log_gc_report(result),
+                            Err(e) => {
+                                log::error!(
+                                    "Failed to collect orphaned model store directories ❌: {}",
+                                    e
+                                );
+                            }
+                        }
+                    }
+                });
+            }
+        };
+
+        let usage = match self.usage_state_path.as_deref() {
+            Some(path) => Arc::new(ModelUsage::load(path)?),
+            None => Arc::new(ModelUsage::default()),
+        };
+        if let Some(path) = self.usage_state_path.clone() {
+            let interval = self.usage_save_interval;
+            if !interval.is_zero() {
+                let usage = usage.clone();
+                tokio::spawn(async move {
+                    loop {
+                        time::sleep(interval).await;
+                        let usage = usage.clone();
+                        let path = path.clone();
+                        // failures to write the state file are logged when saving
+                        if let Err(e) = tokio::task::spawn_blocking(move || usage.save(&path)).await
+                        {
+                            log::error!("Failed to save the usage of the models ❌: {}", e);
+                        }
+                    }
+                });
+            }
+        }
+
+        Ok(Manager {
+            model_store,
+            memory_budget: self.memory_budget,
+            chaos: self.chaos,
+            labels,
+            priority_models: self.priority_models,
+            remaining_models_loaded,
+            usage,
+            usage_state_path: self.usage_state_path,
+            misses: ModelMisses::default(),
+            model_suggestions: self.model_suggestions,
+            attached_stores: AttachedStores::default(),
+            variant_latencies: VariantLatencies::default(),
+            latency_fallbacks,
+            scheduling_weights: self.scheduling_weights,
+            poll_options: self.poll_options,
+            restart_policy: self.restart_policy,
+            poller,
+            store_timeouts: self.store_timeouts,
+            model_settings: self.model_settings,
+            skipped: self.skipped,
+        })
+    }
+}
+
+/// Polls a model store in a supervised background task, which restarts the polls which panic and backs off
+/// after the polls which fail. After each poll, the labels of the models which the poll removed from the
+/// store are forgotten, so that they are not applied to a model added later under the same name.
+fn spawn_poller(
+    store_name: String,
+    model_store: Arc<ModelStore>,
+    poll_options: PollOptions,
+    restart_policy: RestartPolicy,
+    memory_budget: Option<MemoryBudget>,
+    model_settings: Option<Arc<ModelSettingsConfig>>,
+    skipped: Arc<SkippedModels>,
+    labels: Arc<ModelLabels>,
+) -> Poller {
+    Poller::spawn(store_name.clone(), restart_policy, move || {
+        let store_name = store_name.clone();
+        let model_store = model_store.clone();
+        let poll_options = poll_options.clone();
+        let model_settings = model_settings.clone();
+        let skipped = skipped.clone();
+        let labels = labels.clone();
+        async move {
+            let poll = with_settings(model_settings, model_store.poll(&poll_options));
+            with_skipped(skipped, with_budget(memory_budget, poll)).await?;
+            labels.prune(|model_name| {
+                match store_model_name(store_name.as_str(), model_name) {
+                    Some(store_model_name) => model_store.get_model(store_model_name).is_some(),
+                    // the model belongs to another store
+                    None => true,
+                }
+            });
+            Ok(())
+        }
+    })
+}
+
+/// Returns the name under which a model is stored in the model store with the given name, or `None` if the
+/// model belongs to another store.
+fn store_model_name(store_name: &str, model_name: &str) -> Option<ModelName> {
+    match model_name.split_once(STORE_SEPARATOR) {
+        Some((store, name)) if store == store_name => Some(name.to_string()),
+        Some(_) => None,
+        None if store_name == DEFAULT_STORE_NAME => Some(model_name.to_string()),
+        None => None,
+    }
+}
+
+/// Logs the outcome of collecting the orphaned model store directories.
+fn log_gc_report(result: anyhow::Result<GcReport>) {
+    match result {
+        Ok(report) => {
+            log::info!(
+                "Removed {} orphaned model store directories freeing {} bytes and failed to remove {} 🧹",
+                report.removed.len(),
+                report.freed_bytes,
+                report.failed.len()
+            );
+        }
+        Err(e) => {
+            log::error!(
+                "Failed to collect orphaned model store directories ❌: {}",
+                e
+            );
+        }
+    }
+}
+
+/// Strips the `<framework>-` prefix from a model name to get the name under which the model is stored.
+fn strip_framework_prefix(model_name: ModelName) -> ModelName {
+    // keep the name of the attached store which the model belongs to
+    if let Some((store_name, store_model_name)) = model_name.split_once(STORE_SEPARATOR) {
+        return qualify(
+            store_name,
+            strip_framework_prefix(store_model_name.to_string()).as_str(),
+        );
+    }
+    match extract_framework(model_name.clone()) {
+        Some(framework) => match model_name.strip_prefix(format!("{}-", framework).as_str()) {
+            Some(name) => name.to_string(),
+            None => model_name,
+        },
+        None => model_name,
+    }
+}
+
+/// Checks that the model which makes a prediction is the version pinned by the request, if any. The model is the one
+/// fetched for the prediction, so a model swapped while the request was queued is caught.
+///
+/// # Errors
+///
+/// Returns an `Error::VersionMismatch` if a version is pinned and the model is at another version.
+fn check_model_version(
+    model_name: &str,
+    model: &Metadata,
+    options: &PredictOptions,
+) -> anyhow::Result<()> {
+    match &options.model_version {
+        Some(pinned)
+            if !pinned
+                .trim()
+                .eq_ignore_ascii_case(model.artifact_sha256.as_str()) =>
+        {
+            tracing::warn!(
+                "Model {} is at version {}, not the pinned version {} ⚠️",
+                model_name,
+                model.artifact_sha256,
+                pinned
+            );
+            Err(Error::VersionMismatch(anyhow::anyhow!(
+                "model {} is at version {}, not the pinned version {} ❌",
+                model_name,
+                model.artifact_sha256,
+                pinned.trim()
+            ))
+            .into())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_store::local::filesystem::LocalModelStore;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn successfully_create_manager_with_local_model_store() {
+        let model_dir = "./tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store))).build();
+
+        // assert
+        assert!(manager.is_ok());
+    }
+
+    #[tokio::test]
+    async fn successfully_create_manager_with_local_model_store_with_polling() {
+        let model_dir = "./tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_polling(2)
+            .build();
+
+        // assert
+        assert!(manager.is_ok());
+    }
+
+    #[tokio::test]
+    async fn successfully_only_lower_priority_of_request_via_manager() {
+        // Arrange
+        let model_dir = "./tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .build()
+            .unwrap();
+        let high = PredictOptions {
+            priority: Some(Priority::High),
+            ..Default::default()
+        };
+        let low = PredictOptions {
+            priority: Some(Priority::Low),
+            ..Default::default()
+        };
+
+        // Act
+        let escalated = manager.priority_of("my_awesome_reg_model", &high);
+        let lowered = manager.priority_of("my_awesome_reg_model", &low);
+
+        // Assert
+        assert_eq!(escalated, Priority::Normal);
+        assert_eq!(lowered, Priority::Low);
+    }
+
+    #[tokio::test]
+    async fn fails_to_predict_via_manager_when_pinned_model_version_is_not_loaded() {
+        // Arrange
+        let model_dir = "./tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .build()
+            .unwrap();
+        let options = PredictOptions {
+            model_version: Some("abc123".to_string()),
+            ..Default::default()
+        };
+
+        // Act
+        let prediction =
+            manager.predict_with_options("my_awesome_reg_model".to_string(), "{}", &options);
+
+        // Assert
+        let error = prediction.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::VersionMismatch(_))
+        ));
+        assert!(error.to_string().contains("abc123"));
+    }
+
+    #[tokio::test]
+    async fn fails_to_predict_via_manager_with_input_error_when_input_cannot_be_parsed() {
+        // Arrange
+        let model_dir = "./tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .build()
+            .unwrap();
+
+        // Act
+        let prediction = manager.predict("my_awesome_reg_model".to_string(), "{\"age\": [");
+
+        // Assert
+        let error = prediction.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::Input(_))
+        ));
+        assert!(error.to_string().contains("Failed to parse input"));
+    }
+
+    #[tokio::test]
+    async fn successfully_load_remaining_models_via_manager_after_priority_models() {
+        // Arrange
+        let model_dir = "./tests/model_storage/model_store";
+        let priority_models = vec!["my_awesome_reg_model".to_string()];
+        let local_model_store =
+            LocalModelStore::with_priority_models(model_dir.to_string(), priority_models.clone())
+                .await
+                .unwrap();
+
+        // Act
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_priority_models(priority_models)
+            .build()
+            .unwrap();
+        let readiness = manager.readiness();
+        for _ in 0..100 {
+            if !manager.readiness().loading_remaining_models {
+                break;
+            }
+            time::sleep(time::Duration::from_millis(100)).await;
+        }
+
+        // Assert
+        assert!(readiness.ready);
+        assert!(readiness.pending_priority_models.is_empty());
+        assert!(!manager.readiness().loading_remaining_models);
+        assert_eq!(manager.get_models().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn fails_to_be_ready_via_manager_when_priority_model_does_not_exist() {
+        // Arrange
+        let model_dir = "./tests/model_storage/model_store";
+        let priority_models = vec!["model_which_does_not_exist".to_string()];
+        let local_model_store =
+            LocalModelStore::with_priority_models(model_dir.to_string(), priority_models.clone())
+                .await
+                .unwrap();
+
+        // Act
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_priority_models(priority_models.clone())
+            .build()
+            .unwrap();
+        let readiness = manager.readiness();
+
+        // Assert
+        assert!(!readiness.ready);
+        assert_eq!(readiness.pending_priority_models, priority_models);
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::model_store::local::filesystem::LocalModelStore;
 
     #[tokio::test]
-    async fn successfully_create_manager_with_local_model_store() {
+    async fn successfully_stay_ready_via_manager_when_poller_of_attached_store_is_unhealthy() {
+        // Arrange
         let model_dir = "./tests/model_storage/model_store";
         let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
-        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store))).build();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_polling(1)
+            .with_restart_policy(RestartPolicy {
+                initial_backoff: time::Duration::from_millis(10),
+                max_backoff: time::Duration::from_millis(10),
+                unhealthy_after: 1,
+            })
+            .build()
+            .unwrap();
+        let archive_dir = tempfile::tempdir().unwrap();
+        manager
+            .attach_store(
+                "archive".to_string(),
+                StoreSource::Local(archive_dir.path().to_str().unwrap().to_string()),
+            )
+            .await
+            .unwrap();
 
-        // assert
-        assert!(manager.is_ok());
+        // Act
+        archive_dir.close().unwrap();
+        time::sleep(time::Duration::from_millis(2500)).await;
+        let readiness = manager.readiness();
+
+        // Assert
+        assert!(readiness.ready);
+        assert!(readiness
+            .pollers
+            .iter()
+            .any(|poller| poller.store == "archive" && !poller.healthy));
     }
 
     #[tokio::test]
-    async fn successfully_create_manager_with_local_model_store_with_polling() {
+    async fn successfully_create_manager_with_local_model_store_with_integrity_check() {
         let model_dir = "./tests/model_storage/model_store";
         let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
         let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
-            .with_polling(2)
-            .build();
+            .with_integrity_check(2)
+            .build()
+            .unwrap();
+
+        // act
+        let report = manager.verify_integrity().await;
 
         // assert
-        assert!(manager.is_ok());
+        assert!(report.is_ok());
+        assert!(report.unwrap().failed.is_empty());
     }
 
     #[tokio::test]
@@ -272,6 +2134,470 @@ mod tests {
         assert!(prediction.is_err());
     }
 
+    #[tokio::test]
+    async fn successfully_make_predictions_via_manager_with_output_precision() {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .build()
+            .unwrap();
+        let options =
+            PredictOptions::parse(r#"{"precision": {"decimals": 2, "float32": true}}"#).unwrap();
+
+        // dummy input
+        let features: Vec<String> = (0..28)
+            .map(|i| {
+                format!(
+                    "\"feature_{}\": [{}, {}]",
+                    i,
+                    i as f64 * 0.37,
+                    i as f64 * 1.13
+                )
+            })
+            .collect();
+        let input = format!("{{{}}}", features.join(","));
+        let model_name: ModelName = "my_awesome_reg_model".to_string(); // lightgbm model
+
+        // assert
+        let prediction = manager
+            .predict_with_options(model_name, input.as_str(), &options)
+            .unwrap();
+        let output: crate::model::output::ModelOutput = serde_json::from_str(&prediction).unwrap();
+        assert!(output
+            .predictions
+            .values()
+            .flatten()
+            .flatten()
+            .all(|value| (value * 100.0 - (value * 100.0).round()).abs() < 1e-4));
+    }
+
+    #[tokio::test]
+    async fn successfully_make_predictions_in_batches_via_manager() {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .build()
+            .unwrap();
+
+        // dummy input with 5 rows
+        let features: Vec<String> = (0..28)
+            .map(|i| {
+                let values: Vec<String> = (0..5)
+                    .map(|row| format!("{:.2}", (i * 5 + row) as f64 * 0.37))
+                    .collect();
+                format!("\"feature_{}\": [{}]", i, values.join(","))
+            })
+            .collect();
+        let input = format!("{{{}}}", features.join(","));
+        let model_name: ModelName = "my_awesome_reg_model".to_string(); // lightgbm model
+
+        // assert
+        let unbatched = manager.predict(model_name.clone(), input.as_str()).unwrap();
+        let unbatched: crate::model::output::ModelOutput =
+            serde_json::from_str(&unbatched).unwrap();
+        let options = PredictOptions::parse(r#"{"max_batch_size": 2}"#).unwrap();
+        let batched = manager
+            .predict_with_options(model_name.clone(), input.as_str(), &options)
+            .unwrap();
+        let batched: crate::model::output::ModelOutput = serde_json::from_str(&batched).unwrap();
+        assert_eq!(batched.predictions["predictions"].len(), 5);
+        assert_eq!(
+            batched.predictions["predictions"],
+            unbatched.predictions["predictions"]
+        );
+
+        // parallel batches can only be set per model, so they are predicted with the predictor directly
+        let model = manager.model_store.get_model(model_name.clone()).unwrap();
+        let parallel = model
+            .predictor(ModelPrecision::Fp32)
+            .unwrap()
+            .predict_in_batches(
+                ModelInput::from_str(input.as_str()).unwrap(),
+                &PredictOptions::default(),
+                2,
+                true,
+            )
+            .unwrap();
+        assert_eq!(
+            parallel.predictions["predictions"],
+            unbatched.predictions["predictions"]
+        );
+    }
+
+    #[tokio::test]
+    async fn fail_to_make_predictions_via_manager_when_predict_options_are_not_supported() {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .build()
+            .unwrap();
+        let options = PredictOptions {
+            mode: Some(crate::model::predict::PredictMode::LeafIndex),
+            num_iteration: None,
+            precision: None,
+            unknown_features: None,
+            max_batch_size: None,
+            parallel_batches: None,
+            variant: None,
+            priority: None,
+            latency_budget_ms: None,
+            model_version: None,
+        };
+
+        // dummy input
+        let input = "{\"age\":[22.0]}";
+        let model_name: ModelName = "titanic_model".to_string(); // catboost model
+
+        // assert
+        let prediction = manager.predict_with_options(model_name, input, &options);
+        assert!(prediction.is_err());
+    }
+
+    #[tokio::test]
+    async fn fail_to_make_predictions_via_manager_when_model_has_no_requested_variant() {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .build()
+            .unwrap();
+        let options = PredictOptions::parse(r#"{"variant": "int8"}"#).unwrap();
+
+        // dummy input
+        let input = "{\"age\":[22.0]}";
+        let model_name: ModelName = "titanic_model".to_string(); // catboost model
+
+        // assert
+        let prediction = manager.predict_with_options(model_name, input, &options);
+        assert!(prediction
+            .unwrap_err()
+            .to_string()
+            .contains("has no int8 variant"));
+        assert!(manager.get_variant_latencies().is_empty());
+    }
+
+    #[tokio::test]
+    async fn fail_to_make_predictions_and_add_models_via_manager_when_chaos_mode_injects_failures()
+    {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let failure = crate::chaos::Fault {
+            latency_ms: 0,
+            latency_jitter_ms: 0,
+            failure_rate: 1.0,
+        };
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_chaos(ChaosConfig {
+                predict: failure,
+                download: failure,
+            })
+            .build()
+            .unwrap();
+
+        // dummy input
+        let input = "{\"age\":[22.0]}";
+        let model_name: ModelName = "titanic_model".to_string(); // catboost model
+
+        // assert
+        let prediction = manager.predict(model_name, input);
+        assert!(prediction
+            .unwrap_err()
+            .to_string()
+            .contains("Injected failure"));
+        let addition = manager.add_model("lightgbm-my_model".to_string()).await;
+        assert!(addition
+            .unwrap_err()
+            .to_string()
+            .contains("Injected failure"));
+    }
+
+    #[tokio::test]
+    async fn fail_to_create_manager_when_chaos_failure_rate_is_invalid() {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_chaos(ChaosConfig {
+                predict: crate::chaos::Fault {
+                    latency_ms: 0,
+                    latency_jitter_ms: 0,
+                    failure_rate: 2.0,
+                },
+                download: Default::default(),
+            })
+            .build();
+
+        // assert
+        assert!(manager.is_err());
+    }
+
+    #[tokio::test]
+    async fn successfully_update_labels_via_manager_with_local_model_store() {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .build()
+            .unwrap();
+        let model_name: ModelName = "my_awesome_reg_model".to_string(); // lightgbm model
+
+        // set and then remove labels
+        let labels = BTreeMap::from([
+            ("team".to_string(), "risk".to_string()),
+            ("owner".to_string(), "alice".to_string()),
+        ]);
+        manager
+            .update_labels(model_name.clone(), labels, &[])
+            .unwrap();
+        let metadata = manager
+            .update_labels(model_name.clone(), BTreeMap::new(), &["owner".to_string()])
+            .unwrap();
+
+        // assert
+        assert_eq!(metadata.labels.get("team"), Some(&"risk".to_string()));
+        assert!(!metadata.labels.contains_key("owner"));
+        let models = manager.get_models().unwrap();
+        let model = models
+            .iter()
+            .find(|model| model.name == model_name)
+            .unwrap();
+        assert_eq!(model.labels, metadata.labels);
+        assert!(matches!(
+            manager
+                .update_labels("missing_model".to_string(), BTreeMap::new(), &[])
+                .err()
+                .unwrap()
+                .downcast_ref::<Error>(),
+            Some(Error::ModelNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn successfully_resolve_scheduling_weights_via_manager_with_local_model_store() {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_scheduling_weights(BTreeMap::from([("my_awesome_reg_model".to_string(), 4.0)]))
+            .build()
+            .unwrap();
+        let labelled_model_name: ModelName = "my_awesome_penguin_model".to_string(); // tensorflow model
+
+        // set the weight of a model without a configured weight by its label
+        manager
+            .update_labels(
+                labelled_model_name.clone(),
+                BTreeMap::from([(SCHEDULING_WEIGHT_LABEL.to_string(), "2.5".to_string())]),
+                &[],
+            )
+            .unwrap();
+        let labelled_weight = manager.scheduling_weight_of(labelled_model_name.as_str());
+        manager
+            .update_labels(
+                labelled_model_name.clone(),
+                BTreeMap::from([(SCHEDULING_WEIGHT_LABEL.to_string(), "-1".to_string())]),
+                &[],
+            )
+            .unwrap();
+        let invalid_weight = manager.scheduling_weight_of(labelled_model_name.as_str());
+
+        // assert
+        assert_eq!(manager.scheduling_weight_of("my_awesome_reg_model"), 4.0);
+        assert_eq!(labelled_weight, 2.5);
+        assert_eq!(invalid_weight, DEFAULT_WEIGHT);
+        assert_eq!(
+            manager.scheduling_weight_of("missing_model"),
+            DEFAULT_WEIGHT
+        );
+    }
+
+    #[tokio::test]
+    async fn fails_to_build_manager_when_scheduling_weight_is_not_positive() {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+
+        // build the manager with a zero weight
+        let result = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_scheduling_weights(BTreeMap::from([("my_awesome_reg_model".to_string(), 0.0)]))
+            .build();
+
+        // assert
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn fails_to_build_manager_when_model_does_not_ship_its_latency_fallback() {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+
+        // build the manager with a fallback to a variant the lightgbm model is not shipped with
+        let result = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_latency_fallbacks(BTreeMap::from([(
+                "my_awesome_reg_model".to_string(),
+                ModelPrecision::Int8,
+            )]))
+            .build();
+
+        // assert
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn successfully_track_model_usage_via_manager_with_local_model_store() {
+        // Arrange
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .build()
+            .unwrap();
+        let features: Vec<String> = (0..28)
+            .map(|i| format!("\"feature_{}\": [{}]", i, i as f64 * 0.37))
+            .collect();
+        let input = format!("{{{}}}", features.join(","));
+        let model_name: ModelName = "my_awesome_reg_model".to_string(); // lightgbm model
+
+        // Act
+        for _ in 0..2 {
+            manager.predict(model_name.clone(), input.as_str()).unwrap();
+        }
+        let metadata = manager.get_model_metadata(model_name.clone()).unwrap();
+        let unused_models = manager
+            .get_unused_models(time::Duration::from_secs(3600))
+            .unwrap();
+        let all_models = manager
+            .get_unused_models(time::Duration::from_secs(0))
+            .unwrap();
+
+        // Assert
+        assert_eq!(metadata.request_count, 2);
+        assert!(metadata.last_accessed.is_some());
+        assert!(unused_models.is_empty());
+        assert_eq!(all_models.len(), manager.get_models().unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn successfully_restore_model_usage_via_manager_after_restart() {
+        // Arrange
+        let model_dir = "tests/model_storage/model_store";
+        let usage_dir = tempfile::tempdir().unwrap();
+        let usage_state_path = usage_dir.path().join("usage.json");
+        let usage_state_path = &usage_state_path;
+        let build_manager = || async move {
+            let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+            ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+                .with_usage_state(usage_state_path.to_str().unwrap().to_string(), 0)
+                .build()
+                .unwrap()
+        };
+        let features: Vec<String> = (0..28)
+            .map(|i| format!("\"feature_{}\": [{}]", i, i as f64 * 0.37))
+            .collect();
+        let input = format!("{{{}}}", features.join(","));
+        let model_name: ModelName = "my_awesome_reg_model".to_string(); // lightgbm model
+        let manager = build_manager().await;
+        manager.predict(model_name.clone(), input.as_str()).unwrap();
+        let last_accessed = manager
+            .get_model_metadata(model_name.clone())
+            .unwrap()
+            .last_accessed;
+
+        // Act
+        manager.save_usage_state().unwrap();
+        let restarted = build_manager().await;
+
+        // Assert
+        let metadata = restarted.get_model_metadata(model_name.clone()).unwrap();
+        assert_eq!(metadata.request_count, 1);
+        assert_eq!(metadata.last_accessed, last_accessed);
+    }
+
+    #[tokio::test]
+    async fn successfully_track_model_misses_via_manager_with_local_model_store() {
+        // Arrange
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_model_suggestions(true)
+            .build()
+            .unwrap();
+        let model_name: ModelName = "my_awesome_reg_modle".to_string(); // typo of the lightgbm model
+
+        // Act
+        let predictions = manager.predict(model_name.clone(), "{}");
+        let streaming = manager.check_streaming(model_name.as_str());
+        let output = ModelOutput {
+            predictions: HashMap::new(),
+            labels: HashMap::new(),
+            shapes: HashMap::new(),
+            texts: HashMap::new(),
+        };
+        let embeddings = manager.embed(model_name.clone(), output, &EmbedOptions::default());
+        let misses = manager.get_model_misses(10);
+        let suggestions = manager.suggest_model_names(model_name.as_str());
+
+        // Assert
+        assert!(matches!(
+            predictions.unwrap_err().downcast_ref::<Error>(),
+            Some(Error::ModelNotFound(_))
+        ));
+        assert!(streaming.is_err());
+        assert!(matches!(
+            embeddings.unwrap_err().downcast_ref::<Error>(),
+            Some(Error::ModelNotFound(_))
+        ));
+        assert_eq!(manager.model_misses_total(), 3);
+        assert_eq!(misses.len(), 1);
+        assert_eq!(misses[0].model_name, model_name);
+        assert_eq!(misses[0].count, 3);
+        assert_eq!(suggestions[0], "my_awesome_reg_model");
+    }
+
+    #[tokio::test]
+    async fn successfully_attach_and_detach_model_store_via_manager() {
+        // Arrange
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .build()
+            .unwrap();
+        let model_count = manager.get_models().unwrap().len();
+        let features: Vec<String> = (0..28)
+            .map(|i| format!("\"feature_{}\": [{}]", i, i as f64 * 0.37))
+            .collect();
+        let input = format!("{{{}}}", features.join(","));
+        let model_name: ModelName = "archive.my_awesome_reg_model".to_string(); // lightgbm model
+
+        // Act
+        let attached = manager
+            .attach_store(
+                "archive".to_string(),
+                StoreSource::Local(model_dir.to_string()),
+            )
+            .await
+            .unwrap();
+        let reattached = manager
+            .attach_store(
+                "archive".to_string(),
+                StoreSource::Local(model_dir.to_string()),
+            )
+            .await;
+        let prediction = manager.predict(model_name.clone(), input.as_str());
+        let metadata = manager.get_model_metadata(model_name.clone()).unwrap();
+        let stores = manager.get_stores();
+        let models = manager.get_models().unwrap();
+        let detached = manager.detach_store("archive");
+
+        // Assert
+        assert!(attached.contains(&model_name));
+        assert!(reattached.is_err());
+        assert!(prediction.is_ok());
+        assert_eq!(metadata.name, model_name);
+        assert_eq!(metadata.request_count, 1);
+        assert_eq!(stores.len(), 1);
+        assert_eq!(stores[0].url, format!("file://{}", model_dir));
+        assert_eq!(models.len(), model_count + attached.len());
+        assert!(detached.is_ok());
+        assert!(manager.predict(model_name, input.as_str()).is_err());
+        assert_eq!(manager.get_models().unwrap().len(), model_count);
+        assert!(manager.detach_store("archive").is_err());
+    }
+
     #[tokio::test]
     async fn successfully_get_models_via_manager_with_local_model_store() {
         let model_dir = "tests/model_storage/model_store";
@@ -288,6 +2614,27 @@ mod tests {
         assert_ne!(manager.get_models().unwrap().len(), 0);
     }
 
+    #[tokio::test]
+    async fn successfully_get_model_metadata_via_manager_with_local_model_store() {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .build()
+            .unwrap();
+
+        // metadata
+        let metadata = manager.get_model_metadata("titanic_model".to_string());
+        let missing = manager.get_model_metadata("model_which_does_not_exist".to_string());
+        let bundle = manager.get_model_bundle("titanic_model".to_string());
+        let missing_bundle = manager.get_model_bundle("model_which_does_not_exist".to_string());
+
+        // assert
+        assert_eq!(metadata.unwrap().name, "titanic_model");
+        assert!(missing.is_none());
+        assert!(bundle.is_some());
+        assert!(missing_bundle.is_none());
+    }
+
     #[tokio::test]
     async fn successfully_add_model_via_manager_with_local_model_store() {
         let model_dir = "tests/model_storage/model_store";
@@ -348,4 +2695,98 @@ mod tests {
             1
         )
     }
+
+    #[tokio::test]
+    async fn fails_to_build_manager_when_memory_budget_is_exceeded() {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_memory_budget(1)
+            .build();
+
+        // assert
+        assert!(manager.is_err());
+    }
+
+    #[tokio::test]
+    async fn successfully_build_manager_skipping_startup_models_which_exceed_the_memory_budget() {
+        let model_dir = "tests/model_storage/model_store";
+        let skipped = Arc::new(SkippedModels::default());
+        // a budget which leaves no room for any model on top of the memory in use
+        let memory_budget =
+            MemoryBudget::new(crate::memory::resident_memory_bytes().unwrap_or_default() + 1);
+
+        // load the model store with the budget in scope, as the server does at startup
+        let local_model_store = with_skipped(
+            skipped.clone(),
+            with_budget(
+                Some(memory_budget),
+                LocalModelStore::new(model_dir.to_string()),
+            ),
+        )
+        .await
+        .unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_skipped_models(skipped)
+            .build()
+            .unwrap();
+
+        // assert
+        assert!(manager.get_models().unwrap().is_empty());
+        let skipped_models = manager.skipped_models();
+        assert_eq!(skipped_models.len(), 4);
+        assert!(skipped_models
+            .iter()
+            .all(|model| model.reason.contains("Memory budget exceeded")));
+    }
+
+    #[tokio::test]
+    async fn successfully_add_model_via_manager_with_memory_budget() {
+        let model_dir = "tests/model_storage/model_store";
+        let local_model_store = LocalModelStore::new(model_dir.to_string()).await.unwrap();
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(local_model_store)))
+            .with_memory_budget(u64::MAX / MEBIBYTE)
+            .build()
+            .unwrap();
+        let model_name: ModelName = "catboost-titanic_model".to_string();
+
+        // delete a model to add it back
+        manager.delete_model("titanic_model".to_string()).unwrap();
+
+        // add model
+        let add = manager.add_model(model_name).await;
+
+        // assert
+        assert!(add.is_ok());
+    }
+
+    #[test]
+    fn successfully_strip_framework_prefix_from_model_name() {
+        let model_name = strip_framework_prefix("catboost-titanic_model".to_string());
+
+        // assert
+        assert_eq!(model_name, "titanic_model")
+    }
+
+    #[test]
+    fn successfully_resolve_store_model_name_of_model() {
+        // Act
+        let names = [
+            store_model_name(DEFAULT_STORE_NAME, "titanic_model"),
+            store_model_name(DEFAULT_STORE_NAME, "fraud.titanic_model"),
+            store_model_name("fraud", "fraud.titanic_model"),
+            store_model_name("fraud", "titanic_model"),
+        ];
+
+        // Assert
+        assert_eq!(
+            names,
+            [
+                Some("titanic_model".to_string()),
+                None,
+                Some("titanic_model".to_string()),
+                None
+            ]
+        );
+    }
 }
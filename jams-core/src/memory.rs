@@ -0,0 +1,240 @@
+use crate::cgroup::CgroupLimits;
+use std::fs;
+use std::future::Future;
+use std::path::Path;
+
+/// Number of bytes in a mebibyte.
+pub const MEBIBYTE: u64 = 1024 * 1024;
+
+/// Returns the resident set size (RSS) of the current process in bytes.
+///
+/// The value is read from `/proc/self/status` and is therefore only available on Linux.
+///
+/// # Returns
+/// - `Some(u64)`: The resident memory of the process in bytes.
+/// - `None`: If the resident memory could not be determined on this platform.
+pub fn resident_memory_bytes() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    // the line has the format `VmRSS:     1234 kB`
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kibibytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kibibytes * 1024)
+}
+
+/// Returns the total memory of the host in bytes.
+///
+/// The value is read from `/proc/meminfo` and is therefore only available on Linux.
+///
+/// # Returns
+/// - `Some(u64)`: The total memory of the host in bytes.
+/// - `None`: If the total memory could not be determined on this platform.
+pub fn total_memory_bytes() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    // the line has the format `MemTotal:     1234 kB`
+    let line = meminfo.lines().find(|line| line.starts_with("MemTotal:"))?;
+    let kibibytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kibibytes * 1024)
+}
+
+/// Returns the memory available to the process in bytes, i.e. the total memory of the host capped by the memory
+/// limit of its cgroup, e.g. the limit of its container.
+///
+/// # Returns
+/// - `Some(u64)`: The memory available to the process in bytes.
+/// - `None`: If neither the total memory nor a memory limit could be determined on this platform.
+pub fn available_memory_bytes() -> Option<u64> {
+    match (total_memory_bytes(), CgroupLimits::current().memory_bytes) {
+        (Some(total), Some(limit)) => Some(total.min(limit)),
+        (total, limit) => total.or(limit),
+    }
+}
+
+/// Calculates the size of a model artifact on disk in bytes.
+///
+/// If the path is a directory (for example a tensorflow saved model), the size of all the files in the
+/// directory is summed recursively.
+///
+/// # Arguments
+/// - `path`: The path to the model artifact.
+///
+/// # Returns
+/// - `u64`: The size of the artifact in bytes. Files which cannot be read are ignored.
+pub fn artifact_size_bytes(path: &Path) -> u64 {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return 0,
+    };
+
+    if metadata.is_file() {
+        return metadata.len();
+    }
+
+    match fs::read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| artifact_size_bytes(&entry.path()))
+            .sum(),
+        Err(_) => 0,
+    }
+}
+
+tokio::task_local! {
+    /// The memory budget of the `Manager` which loads models on the current task, if any.
+    static LOAD_BUDGET: MemoryBudget;
+}
+
+/// Runs a future which loads models with the memory budget in scope, so that every model it loads is checked
+/// against the budget with the size of its artifact before the model is loaded.
+///
+/// # Arguments
+/// - `budget`: The memory budget, or `None` if models are loaded without one.
+/// - `future`: The future which loads the models, e.g. adding a model to a model store.
+pub async fn with_budget<F: Future>(budget: Option<MemoryBudget>, future: F) -> F::Output {
+    match budget {
+        Some(budget) => LOAD_BUDGET.scope(budget, future).await,
+        None => future.await,
+    }
+}
+
+/// Checks the size of a model artifact against the memory budget in scope, before the model is loaded.
+///
+/// # Arguments
+/// - `path`: The path to the unpacked model artifact.
+///
+/// # Returns
+/// - `Ok(())`: If no budget is in scope or the budget allows loading the artifact.
+/// - `Err(anyhow::Error)`: If loading the artifact would exceed the budget.
+pub fn check_artifact(path: &Path) -> anyhow::Result<()> {
+    match LOAD_BUDGET.try_with(|budget| *budget) {
+        Ok(budget) => budget.check(artifact_size_bytes(path)),
+        Err(_) => Ok(()),
+    }
+}
+
+/// A memory budget for the models loaded by the `Manager`.
+///
+/// The budget is compared against the resident memory of the process. Models which push the process over
+/// the budget are refused so that the process fails loudly instead of being OOM killed.
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryBudget {
+    limit_bytes: u64,
+}
+
+impl MemoryBudget {
+    /// Creates a new memory budget with the given limit in bytes.
+    pub fn new(limit_bytes: u64) -> Self {
+        MemoryBudget { limit_bytes }
+    }
+
+    /// Creates a memory budget from a limit in MiB, or `None` if the limit is 0, i.e. the budget is disabled.
+    pub fn from_mb(limit_mb: u64) -> Option<Self> {
+        match limit_mb {
+            0 => None,
+            limit_mb => Some(MemoryBudget::new(limit_mb.saturating_mul(MEBIBYTE))),
+        }
+    }
+
+    /// Returns the limit of the budget in bytes.
+    pub fn limit_bytes(&self) -> u64 {
+        self.limit_bytes
+    }
+
+    /// Checks whether the given amount of additional memory fits within the budget.
+    ///
+    /// # Arguments
+    /// - `additional_bytes`: The amount of memory which is about to be allocated.
+    ///
+    /// If the resident memory cannot be determined, only the additional memory is checked against the budget.
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the budget allows the allocation.
+    /// - `Err(anyhow::Error)`: If the allocation would exceed the budget.
+    pub fn check(&self, additional_bytes: u64) -> anyhow::Result<()> {
+        let resident_bytes = match resident_memory_bytes() {
+            Some(resident_bytes) => resident_bytes,
+            None => {
+                tracing::warn!(
+                    "Unable to determine resident memory, checking only the requested memory against the budget ⚠️"
+                );
+                0
+            }
+        };
+
+        self.check_with_resident(resident_bytes, additional_bytes)
+    }
+
+    /// Checks whether the given amount of additional memory fits within the budget given the current resident memory.
+    fn check_with_resident(
+        &self,
+        resident_bytes: u64,
+        additional_bytes: u64,
+    ) -> anyhow::Result<()> {
+        let required_bytes = resident_bytes.saturating_add(additional_bytes);
+        if required_bytes > self.limit_bytes {
+            anyhow::bail!(
+                "Memory budget exceeded ❌: {} MiB required ({} MiB in use + {} MiB requested) but the budget is {} MiB",
+                required_bytes / MEBIBYTE,
+                resident_bytes / MEBIBYTE,
+                additional_bytes / MEBIBYTE,
+                self.limit_bytes / MEBIBYTE
+            )
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_get_resident_memory_bytes() {
+        let resident_bytes = resident_memory_bytes();
+
+        // assert
+        if cfg!(target_os = "linux") {
+            assert!(resident_bytes.unwrap() > 0)
+        }
+    }
+
+    #[test]
+    fn successfully_calculate_artifact_size_for_directory() {
+        let path = Path::new("tests/model_storage/models/tensorflow-my_awesome_autompg_model");
+        let size = artifact_size_bytes(path);
+
+        // assert
+        assert!(size > 0)
+    }
+
+    #[test]
+    fn successfully_return_zero_artifact_size_when_path_does_not_exist() {
+        let size = artifact_size_bytes(Path::new("incorrect/path"));
+
+        // assert
+        assert_eq!(size, 0)
+    }
+
+    #[test]
+    fn successfully_check_memory_budget_when_within_limit() {
+        let budget = MemoryBudget::new(100 * MEBIBYTE);
+
+        // assert
+        assert!(budget
+            .check_with_resident(50 * MEBIBYTE, 10 * MEBIBYTE)
+            .is_ok())
+    }
+
+    #[test]
+    fn fails_to_check_memory_budget_when_limit_is_exceeded() {
+        let budget = MemoryBudget::new(100 * MEBIBYTE);
+        let result = budget.check_with_resident(95 * MEBIBYTE, 10 * MEBIBYTE);
+
+        // assert
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Memory budget exceeded"))
+    }
+}
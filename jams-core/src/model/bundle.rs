@@ -0,0 +1,460 @@
+use crate::model::input::{Features, ModelInput, Values};
+use crate::model::predict::UnknownFeatures;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path};
+
+/// Name of the optional directory in a model tarball which holds the auxiliary files of the model.
+pub const BUNDLE_DIRECTORY_NAME: &str = "bundle";
+
+/// Directory inside the bundle which holds one `<feature>.txt` vocabulary file per categorical feature.
+pub const VOCABULARY_DIRECTORY_NAME: &str = "vocab";
+
+/// Name of the file inside the bundle which holds the standard scaler parameters of float features.
+pub const SCALER_FILE_NAME: &str = "scaler.json";
+
+/// Name of the file inside the bundle which holds a sample model input used to warm up the model.
+pub const WARMUP_FILE_NAME: &str = "warmup.json";
+
+/// Name of the file inside the bundle which holds the expected input features and their types.
+pub const SCHEMA_FILE_NAME: &str = "schema.json";
+
+/// Auxiliary files shipped alongside a model in the `bundle` directory of its tarball.
+///
+/// The bundle is parsed when the tarball is unpacked and recorded with the provenance of the
+/// model, so predictors can read it with `read_provenance` and the manager applies it to every
+/// request before the predictor is called. The tarball layout is
+///
+/// ```text
+/// <model_framework>-<model_name>.<ext>
+/// bundle/
+///     vocab/<feature>.txt   # one token per line, the line number is the encoded value
+///     scaler.json           # {"<feature>": {"mean": 1.5, "scale": 0.5}}
+///     warmup.json           # a model input, e.g. {"<feature>": [1.0]}
+///     schema.json           # {"<feature>": "int" | "float" | "string"}
+/// ```
+///
+/// Every file is optional and unknown files are ignored.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ModelBundle {
+    /// Vocabularies which encode categorical string features into integer features.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub vocabularies: BTreeMap<String, Vocabulary>,
+    /// Standard scalers applied to float features.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub scalers: BTreeMap<String, Scaler>,
+    /// Sample model input used to warm up the model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup_input: Option<String>,
+    /// Expected input features and their types, before any vocabulary is applied.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub schema: BTreeMap<String, FeatureType>,
+}
+
+/// Tokens of a categorical feature. A token is encoded as its index and tokens which are not in
+/// the vocabulary are encoded as the length of the vocabulary.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(from = "Vec<String>", into = "Vec<String>")]
+pub struct Vocabulary {
+    tokens: Vec<String>,
+    index: HashMap<String, i32>,
+}
+
+impl From<Vec<String>> for Vocabulary {
+    fn from(tokens: Vec<String>) -> Self {
+        let index = tokens
+            .iter()
+            .enumerate()
+            .map(|(i, token)| (token.clone(), i as i32))
+            .collect();
+        Vocabulary { tokens, index }
+    }
+}
+
+impl From<Vocabulary> for Vec<String> {
+    fn from(vocabulary: Vocabulary) -> Self {
+        vocabulary.tokens
+    }
+}
+
+impl Vocabulary {
+    /// Returns the tokens of the vocabulary in the order of their encoded values.
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    /// Encodes a token, returning the length of the vocabulary for unknown tokens.
+    pub fn encode(&self, token: &str) -> i32 {
+        self.index
+            .get(token)
+            .copied()
+            .unwrap_or(self.tokens.len() as i32)
+    }
+}
+
+/// Standard scaler parameters of a float feature, applied as `(x - mean) / scale`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Scaler {
+    pub mean: f64,
+    pub scale: f64,
+}
+
+/// Type of an input feature declared in the bundle schema.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureType {
+    Int,
+    Float,
+    String,
+}
+
+impl ModelBundle {
+    /// Returns true if the bundle has no auxiliary files.
+    pub fn is_empty(&self) -> bool {
+        self.vocabularies.is_empty()
+            && self.scalers.is_empty()
+            && self.warmup_input.is_none()
+            && self.schema.is_empty()
+    }
+
+    /// Parses a file of the bundle and adds it to the bundle.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file relative to the bundle directory.
+    /// * `contents` - The contents of the file.
+    ///
+    /// # Errors
+    /// Returns an error if a well-known file cannot be parsed or is invalid.
+    pub fn add_file(&mut self, path: &Path, contents: &str) -> anyhow::Result<()> {
+        let components: Vec<String> = path
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+
+        match components.as_slice() {
+            [name] if name == SCALER_FILE_NAME => {
+                self.scalers = match serde_json::from_str(contents) {
+                    Ok(scalers) => scalers,
+                    Err(e) => anyhow::bail!("Failed to parse {} ❌: {}", SCALER_FILE_NAME, e),
+                };
+                for (feature, scaler) in self.scalers.iter() {
+                    if !scaler.mean.is_finite() || !scaler.scale.is_finite() || scaler.scale == 0.0
+                    {
+                        anyhow::bail!(
+                            "Scaler of feature {} in {} must have a finite mean and a finite non-zero scale ❌",
+                            feature,
+                            SCALER_FILE_NAME
+                        )
+                    }
+                }
+            }
+            [name] if name == WARMUP_FILE_NAME => {
+                if let Err(e) = ModelInput::from_str(contents) {
+                    anyhow::bail!("Failed to parse {} ❌: {}", WARMUP_FILE_NAME, e)
+                }
+                self.warmup_input = Some(contents.to_string());
+            }
+            [name] if name == SCHEMA_FILE_NAME => {
+                self.schema = match serde_json::from_str(contents) {
+                    Ok(schema) => schema,
+                    Err(e) => anyhow::bail!("Failed to parse {} ❌: {}", SCHEMA_FILE_NAME, e),
+                };
+            }
+            [directory, file] if directory == VOCABULARY_DIRECTORY_NAME => {
+                let feature = match file.strip_suffix(".txt") {
+                    Some(feature) if !feature.is_empty() => feature.to_string(),
+                    _ => {
+                        tracing::warn!("Ignoring unknown bundle file {:?} ⚠️", path);
+                        return Ok(());
+                    }
+                };
+                let tokens: Vec<String> = contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|token| !token.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                self.vocabularies.insert(feature, Vocabulary::from(tokens));
+            }
+            _ => tracing::warn!("Ignoring unknown bundle file {:?} ⚠️", path),
+        }
+        Ok(())
+    }
+
+    /// Validates the input against the schema and applies the vocabularies and scalers to it.
+    ///
+    /// String features with a vocabulary are replaced by integer features holding the encoded
+    /// tokens, which are appended after the existing integer features. Float features with a
+    /// scaler are scaled in place. Features which are not in the schema are dropped or rejected
+    /// depending on `unknown_features`.
+    ///
+    /// # Arguments
+    /// * `input` - The model input to preprocess.
+    /// * `unknown_features` - What to do with the features which are not in the schema.
+    ///
+    /// # Errors
+    /// Returns an error if the input does not match the schema.
+    pub fn preprocess(
+        &self,
+        input: &mut ModelInput,
+        unknown_features: UnknownFeatures,
+    ) -> anyhow::Result<()> {
+        self.validate_schema(input, unknown_features)?;
+
+        for (feature, vocabulary) in self.vocabularies.iter() {
+            let tokens = match take_feature(&mut input.string_features, feature) {
+                Some(Values::String(tokens)) => tokens,
+                _ => continue,
+            };
+            let encoded: Vec<i32> = tokens.iter().map(|t| vocabulary.encode(t)).collect();
+            let rows = encoded.len();
+            input.integer_features.values.extend(encoded);
+            input.integer_features.names.push(feature.clone());
+            input.integer_features.shape.0 += 1;
+            input.integer_features.shape.1 = rows;
+        }
+
+        if !self.scalers.is_empty() {
+            let rows = input.float_features.shape.1;
+            let names = &input.float_features.names;
+            if let Values::Float(values) = &mut input.float_features.values {
+                for (i, name) in names.iter().enumerate() {
+                    if let Some(scaler) = self.scalers.get(name) {
+                        for value in values[i * rows..(i + 1) * rows].iter_mut() {
+                            *value = ((*value as f64 - scaler.mean) / scaler.scale) as f32;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_schema(
+        &self,
+        input: &mut ModelInput,
+        unknown_features: UnknownFeatures,
+    ) -> anyhow::Result<()> {
+        if self.schema.is_empty() {
+            return Ok(());
+        }
+
+        let mut features: HashMap<String, FeatureType> = HashMap::new();
+        for (features_of_type, feature_type) in [
+            (&input.integer_features, FeatureType::Int),
+            (&input.float_features, FeatureType::Float),
+            (&input.string_features, FeatureType::String),
+        ] {
+            for name in features_of_type.names.iter() {
+                features.insert(name.clone(), feature_type);
+            }
+        }
+
+        for (name, expected) in self.schema.iter() {
+            match features.remove(name.as_str()) {
+                Some(actual) if actual == *expected => {}
+                Some(actual) => anyhow::bail!(
+                    "Feature {} must be of type {:?} but got {:?} ❌",
+                    name,
+                    expected,
+                    actual
+                ),
+                None => anyhow::bail!("Missing feature {} ❌", name),
+            }
+        }
+        if features.is_empty() {
+            return Ok(());
+        }
+
+        let mut unknown: Vec<String> = features.into_keys().collect();
+        unknown.sort();
+        match unknown_features {
+            UnknownFeatures::Ignore => {}
+            UnknownFeatures::Warn => {
+                tracing::warn!("Ignoring unknown features {} ⚠️", unknown.join(", "))
+            }
+            UnknownFeatures::Reject => {
+                anyhow::bail!("Unknown features {} ❌", unknown.join(", "))
+            }
+        }
+        for name in unknown.iter() {
+            for features_of_type in [
+                &mut input.integer_features,
+                &mut input.float_features,
+                &mut input.string_features,
+            ] {
+                take_feature(features_of_type, name);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Removes a feature from a set of features, returning its values.
+fn take_feature(features: &mut Features, name: &str) -> Option<Values> {
+    let position = features.names.iter().position(|n| n == name)?;
+    let rows = features.shape.1;
+    let range = position * rows..(position + 1) * rows;
+    let values = match &mut features.values {
+        Values::String(v) => Values::String(v.drain(range).collect()),
+        Values::Int(v) => Values::Int(v.drain(range).collect()),
+        Values::Float(v) => Values::Float(v.drain(range).collect()),
+    };
+    features.names.remove(position);
+    features.shape.0 -= 1;
+    if features.shape.0 == 0 {
+        features.shape = (0, 0);
+    }
+    Some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle() -> ModelBundle {
+        let mut bundle = ModelBundle::default();
+        bundle
+            .add_file(Path::new("vocab/sex.txt"), "male\nfemale\n")
+            .unwrap();
+        bundle
+            .add_file(
+                Path::new(SCALER_FILE_NAME),
+                r#"{"age": {"mean": 30.0, "scale": 10.0}}"#,
+            )
+            .unwrap();
+        bundle
+            .add_file(
+                Path::new(SCHEMA_FILE_NAME),
+                r#"{"age": "float", "sex": "string", "pclass": "int"}"#,
+            )
+            .unwrap();
+        bundle
+    }
+
+    #[test]
+    fn successfully_parse_bundle_files() {
+        // Arrange
+        let mut bundle = bundle();
+
+        // Act
+        let result = bundle.add_file(Path::new(WARMUP_FILE_NAME), r#"{"age": [1.0]}"#);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(bundle.vocabularies["sex"].tokens(), ["male", "female"]);
+        assert_eq!(bundle.scalers["age"].scale, 10.0);
+        assert_eq!(bundle.schema["pclass"], FeatureType::Int);
+        assert!(bundle.warmup_input.is_some());
+    }
+
+    #[test]
+    fn successfully_preprocess_input_with_bundle() {
+        // Arrange
+        let bundle = bundle();
+        let mut input = ModelInput::from_str(
+            r#"{"pclass": [1, 3], "sex": ["female", "unknown"], "age": [40.0, 25.0]}"#,
+        )
+        .unwrap();
+
+        // Act
+        let result = bundle.preprocess(&mut input, UnknownFeatures::Ignore);
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(input.string_features.names.is_empty());
+        assert_eq!(input.integer_features.names, vec!["pclass", "sex"]);
+        assert_eq!(input.integer_features.shape, (2, 2));
+        assert_eq!(
+            input.integer_features.values.as_ints().unwrap(),
+            &vec![1, 3, 1, 2]
+        );
+        assert_eq!(
+            input.float_features.values.as_floats().unwrap(),
+            &vec![1.0, -0.5]
+        );
+    }
+
+    #[test]
+    fn successfully_round_trip_bundle_through_json() {
+        // Arrange
+        let bundle = bundle();
+
+        // Act
+        let json = serde_json::to_string(&bundle).unwrap();
+        let parsed: ModelBundle = serde_json::from_str(&json).unwrap();
+
+        // Assert
+        assert_eq!(parsed, bundle);
+        assert_eq!(parsed.vocabularies["sex"].encode("female"), 1);
+    }
+
+    #[test]
+    fn fails_to_preprocess_input_which_does_not_match_schema() {
+        // Arrange
+        let bundle = bundle();
+        let mut input =
+            ModelInput::from_str(r#"{"pclass": [1.0], "sex": ["male"], "age": [40.0]}"#).unwrap();
+
+        // Act
+        let result = bundle.preprocess(&mut input, UnknownFeatures::Ignore);
+
+        // Assert
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn successfully_drop_unknown_features_when_they_are_ignored() {
+        // Arrange
+        let bundle = bundle();
+        let mut input = ModelInput::from_str(
+            r#"{"pclass": [1], "sex": ["male"], "age": [40.0], "fare": [7.25], "cabin": ["C85"]}"#,
+        )
+        .unwrap();
+
+        // Act
+        let result = bundle.preprocess(&mut input, UnknownFeatures::Warn);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(input.float_features.names, vec!["age"]);
+        assert_eq!(input.float_features.shape, (1, 1));
+        assert!(input.string_features.names.is_empty());
+    }
+
+    #[test]
+    fn fails_to_preprocess_input_with_unknown_features_when_they_are_rejected() {
+        // Arrange
+        let bundle = bundle();
+        let mut input = ModelInput::from_str(
+            r#"{"pclass": [1], "sex": ["male"], "age": [40.0], "fare": [7.25], "cabin": ["C85"]}"#,
+        )
+        .unwrap();
+
+        // Act
+        let result = bundle.preprocess(&mut input, UnknownFeatures::Reject);
+
+        // Assert
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("cabin, fare"));
+    }
+
+    #[test]
+    fn fails_to_parse_scaler_with_zero_scale() {
+        // Arrange
+        let mut bundle = ModelBundle::default();
+
+        // Act
+        let result = bundle.add_file(
+            Path::new(SCALER_FILE_NAME),
+            r#"{"age": {"mean": 30.0, "scale": 0.0}}"#,
+        );
+
+        // Assert
+        assert!(result.is_err())
+    }
+}
@@ -0,0 +1,299 @@
+use crate::model::output::{ModelOutput, DEFAULT_OUTPUT_KEY};
+use serde::{Deserialize, Serialize};
+
+/// Name of the optional file in a model tarball which contains the calibration map of a classifier.
+pub const CALIBRATION_FILE_NAME: &str = "calibration.json";
+
+/// Key of the output which holds the calibrated probabilities.
+pub const CALIBRATED_OUTPUT_KEY: &str = "calibrated_probabilities";
+
+fn default_output() -> String {
+    DEFAULT_OUTPUT_KEY.to_string()
+}
+
+/// A calibration map shipped with a classifier, used to turn its scores into calibrated probabilities.
+///
+/// The map is read from the `calibration.json` file at the root of the model tarball.
+///
+/// # Example
+/// ```json
+/// { "method": "platt", "a": -1.7, "b": 0.3 }
+/// ```
+/// ```json
+/// { "method": "isotonic", "output": "probabilities", "x": [0.1, 0.5, 0.9], "y": [0.05, 0.4, 0.95] }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CalibrationMap {
+    /// The output of the model to calibrate. Defaults to `predictions`.
+    #[serde(default = "default_output")]
+    pub output: String,
+    /// The calibration method and its fitted parameters.
+    #[serde(flatten)]
+    pub method: Calibration,
+}
+
+/// Supported calibration methods.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Calibration {
+    /// Platt scaling, `p = 1 / (1 + exp(a * score + b))`.
+    Platt { a: f64, b: f64 },
+    /// Isotonic regression, a non-decreasing step function given by its breakpoints.
+    /// Scores between breakpoints are linearly interpolated and scores outside the breakpoints are clipped.
+    Isotonic { x: Vec<f64>, y: Vec<f64> },
+}
+
+impl CalibrationMap {
+    /// Parses and validates a calibration map.
+    ///
+    /// # Arguments
+    /// * `contents` - The contents of a `calibration.json` file.
+    ///
+    /// # Returns
+    /// * `Ok(CalibrationMap)` - If the calibration map is valid.
+    /// * `Err(anyhow::Error)` - If the file cannot be parsed or the parameters are invalid.
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let calibration: CalibrationMap = match serde_json::from_str(contents) {
+            Ok(calibration) => calibration,
+            Err(e) => anyhow::bail!("Failed to parse {} ❌: {}", CALIBRATION_FILE_NAME, e),
+        };
+        calibration.validate()?;
+        Ok(calibration)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        match &self.method {
+            Calibration::Platt { a, b } => {
+                if !a.is_finite() || !b.is_finite() {
+                    anyhow::bail!("Platt parameters must be finite numbers ❌")
+                }
+            }
+            Calibration::Isotonic { x, y } => {
+                if x.iter().chain(y.iter()).any(|value| !value.is_finite()) {
+                    anyhow::bail!("Isotonic calibration x and y values must be finite numbers ❌")
+                }
+                if x.is_empty() || x.len() != y.len() {
+                    anyhow::bail!(
+                        "Isotonic calibration requires the same non-zero number of x and y values ❌"
+                    )
+                }
+                if x.windows(2).any(|pair| pair[0] > pair[1])
+                    || y.windows(2).any(|pair| pair[0] > pair[1])
+                {
+                    anyhow::bail!("Isotonic calibration x and y values must be non-decreasing ❌")
+                }
+                if y.iter().any(|value| !(0.0..=1.0).contains(value)) {
+                    anyhow::bail!("Isotonic calibration y values must be between 0 and 1 ❌")
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Calibrates a single score.
+    ///
+    /// # Returns
+    /// * `Ok(f64)` - The calibrated probability.
+    /// * `Err(anyhow::Error)` - If the score is not a finite number, e.g. `NaN`.
+    pub fn calibrate(&self, score: f64) -> anyhow::Result<f64> {
+        if !score.is_finite() {
+            anyhow::bail!(
+                "Cannot calibrate score {}, scores must be finite numbers ❌",
+                score
+            )
+        }
+        let probability = match &self.method {
+            Calibration::Platt { a, b } => 1.0 / (1.0 + (a * score + b).exp()),
+            Calibration::Isotonic { x, y } => {
+                let last = x.len() - 1;
+                if score <= x[0] {
+                    return Ok(y[0]);
+                }
+                if score >= x[last] {
+                    return Ok(y[last]);
+                }
+                // index of the first breakpoint greater than the score
+                let upper = x.partition_point(|breakpoint| *breakpoint <= score);
+                let lower = upper - 1;
+                let width = x[upper] - x[lower];
+                if width == 0.0 {
+                    return Ok(y[upper]);
+                }
+                y[lower] + (score - x[lower]) / width * (y[upper] - y[lower])
+            }
+        };
+        Ok(probability)
+    }
+
+    /// Adds the calibrated probabilities to the output of a model as the `calibrated_probabilities` output.
+    ///
+    /// # Arguments
+    /// * `output` - The output of the model.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the output was calibrated.
+    /// * `Err(anyhow::Error)` - If the output to calibrate is missing or has a score which is not a finite number.
+    pub fn apply(&self, output: &mut ModelOutput) -> anyhow::Result<()> {
+        let scores = match output.predictions.get(self.output.as_str()) {
+            Some(scores) => scores,
+            None => {
+                tracing::error!("Model has no output named {} to calibrate", self.output);
+                anyhow::bail!("Model has no output named {} to calibrate", self.output)
+            }
+        };
+
+        let calibrated = match scores
+            .iter()
+            .map(|row| row.iter().map(|score| self.calibrate(*score)).collect())
+            .collect::<anyhow::Result<Vec<Vec<f64>>>>()
+        {
+            Ok(calibrated) => calibrated,
+            Err(e) => {
+                tracing::error!("Failed to calibrate output {} ❌: {}", self.output, e);
+                anyhow::bail!("Failed to calibrate output {} ❌: {}", self.output, e)
+            }
+        };
+        output
+            .predictions
+            .insert(CALIBRATED_OUTPUT_KEY.to_string(), calibrated);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn successfully_calibrate_scores_using_platt_scaling() {
+        // Arrange
+        let calibration =
+            CalibrationMap::parse(r#"{"method": "platt", "a": -2.0, "b": 1.0}"#).unwrap();
+
+        // Act
+        let probability = calibration.calibrate(0.5).unwrap();
+
+        // Assert
+        assert_eq!(calibration.output, DEFAULT_OUTPUT_KEY);
+        assert!((probability - 0.5).abs() < 1e-9);
+        assert!(calibration.calibrate(2.0).unwrap() > calibration.calibrate(1.0).unwrap());
+    }
+
+    #[test]
+    fn successfully_calibrate_scores_using_isotonic_regression() {
+        // Arrange
+        let calibration = CalibrationMap::parse(
+            r#"{"method": "isotonic", "x": [0.2, 0.4, 0.8], "y": [0.1, 0.3, 0.9]}"#,
+        )
+        .unwrap();
+
+        // Act + Assert
+        assert_eq!(calibration.calibrate(0.0).unwrap(), 0.1);
+        assert!((calibration.calibrate(0.3).unwrap() - 0.2).abs() < 1e-9);
+        assert!((calibration.calibrate(0.6).unwrap() - 0.6).abs() < 1e-9);
+        assert_eq!(calibration.calibrate(1.0).unwrap(), 0.9);
+    }
+
+    #[test]
+    fn fails_to_calibrate_scores_which_are_not_finite() {
+        // Arrange
+        let calibration = CalibrationMap::parse(
+            r#"{"method": "isotonic", "x": [0.2, 0.4, 0.8], "y": [0.1, 0.3, 0.9]}"#,
+        )
+        .unwrap();
+        let mut output = ModelOutput {
+            predictions: HashMap::from([(
+                DEFAULT_OUTPUT_KEY.to_string(),
+                vec![vec![0.2], vec![f64::NAN]],
+            )]),
+            labels: HashMap::new(),
+            shapes: HashMap::new(),
+            texts: HashMap::new(),
+        };
+
+        // Act
+        let result = calibration.apply(&mut output);
+
+        // Assert
+        assert!(calibration.calibrate(f64::NAN).is_err());
+        assert!(calibration.calibrate(f64::INFINITY).is_err());
+        assert!(result.is_err());
+        assert!(!output.predictions.contains_key(CALIBRATED_OUTPUT_KEY));
+    }
+
+    #[test]
+    fn successfully_add_calibrated_probabilities_to_model_output() {
+        // Arrange
+        let calibration =
+            CalibrationMap::parse(r#"{"method": "isotonic", "x": [0.0, 1.0], "y": [0.0, 0.5]}"#)
+                .unwrap();
+        let mut output = ModelOutput {
+            predictions: HashMap::from([(
+                DEFAULT_OUTPUT_KEY.to_string(),
+                vec![vec![0.2], vec![0.8]],
+            )]),
+            labels: HashMap::new(),
+            shapes: HashMap::new(),
+            texts: HashMap::new(),
+        };
+
+        // Act
+        let result = calibration.apply(&mut output);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            output.predictions.get(DEFAULT_OUTPUT_KEY).unwrap(),
+            &vec![vec![0.2], vec![0.8]]
+        );
+        assert_eq!(
+            output.predictions.get(CALIBRATED_OUTPUT_KEY).unwrap(),
+            &vec![vec![0.1], vec![0.4]]
+        );
+    }
+
+    #[test]
+    fn fails_to_parse_calibration_map_when_parameters_are_invalid() {
+        // Act
+        let unknown_method = CalibrationMap::parse(r#"{"method": "beta", "a": 1.0}"#);
+        let mismatched = CalibrationMap::parse(r#"{"method": "isotonic", "x": [0.1], "y": []}"#);
+        let decreasing =
+            CalibrationMap::parse(r#"{"method": "isotonic", "x": [0.1, 0.2], "y": [0.9, 0.1]}"#);
+        let not_finite = CalibrationMap {
+            output: DEFAULT_OUTPUT_KEY.to_string(),
+            method: Calibration::Isotonic {
+                x: vec![0.1, f64::NAN],
+                y: vec![0.1, 0.2],
+            },
+        }
+        .validate();
+
+        // Assert
+        assert!(unknown_method.is_err());
+        assert!(mismatched.is_err());
+        assert!(decreasing.is_err());
+        assert!(not_finite.is_err());
+    }
+
+    #[test]
+    fn fails_to_apply_calibration_when_output_is_missing() {
+        // Arrange
+        let calibration = CalibrationMap::parse(
+            r#"{"method": "platt", "output": "probabilities", "a": -1.0, "b": 0.0}"#,
+        )
+        .unwrap();
+        let mut output = ModelOutput {
+            predictions: HashMap::new(),
+            labels: HashMap::new(),
+            shapes: HashMap::new(),
+            texts: HashMap::new(),
+        };
+
+        // Act
+        let result = calibration.apply(&mut output);
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
@@ -1,6 +1,9 @@
 use crate::model::input::{ModelInput, Values};
-use crate::model::output::{ModelOutput, DEFAULT_OUTPUT_KEY};
+use crate::model::lifecycle::Lifecycle;
+use crate::model::output::{classifier_output, split_into_rows, ModelOutput, DEFAULT_OUTPUT_KEY};
 use crate::model::predict::Predict;
+use crate::model_store::common::read_provenance;
+use crate::{Error, Result};
 use catboost_rs;
 use std::collections::HashMap;
 
@@ -25,11 +28,10 @@ impl CatboostModelInput {
     #[tracing::instrument(skip(model_input))]
     pub fn parse(mut model_input: ModelInput) -> anyhow::Result<Self> {
         // only float features are supported, so we are converting Vec<i32> to Vec<f32>
-        // we can use .1 of any features as the length is same.
-        // only the number of features is changing
+        // all the features have the same number of rows, only the number of features is changing
         let numerical_features_shape = (
             model_input.integer_features.shape.0 + model_input.float_features.shape.0,
-            model_input.float_features.shape.1,
+            model_input.num_rows(),
         );
 
         // convert integer to float
@@ -84,11 +86,19 @@ impl CatboostModelInput {
 pub struct Catboost {
     /// The loaded Catboost model.
     model: catboost_rs::Model,
+    /// The number of values the model returns for each row, i.e. the number of classes of a multiclass
+    /// model or the number of targets of a multi-target regression model.
+    num_outputs: usize,
+    /// The class names of a multiclass model, in the order of its outputs. Empty for other models.
+    class_labels: Vec<String>,
 }
 
 impl Catboost {
     /// Loads a Catboost model from the specified path.
     ///
+    /// If a multiclass model was unpacked from a tarball with a `labels.txt` file, the file is used
+    /// as the class names of the model.
+    ///
     /// # Arguments
     ///
     /// * `path` - A string slice that holds the path to the Catboost model file (.cbm).
@@ -97,18 +107,51 @@ impl Catboost {
     ///
     /// Returns an `Err` if loading the Catboost model fails.
     #[tracing::instrument]
-    pub fn load(path: &str) -> anyhow::Result<Self> {
+    pub fn load(path: &str) -> Result<Self> {
         let model = match catboost_rs::Model::load(path) {
             Ok(model) => model,
             Err(e) => {
                 tracing::error!("Failed to load Catboost model from file {}: {}", path, e);
-                anyhow::bail!("Failed to load Catboost model from file {}: {}", path, e)
+                return Err(Error::Load(anyhow::anyhow!(
+                    "Failed to load Catboost model from file {}: {}",
+                    path,
+                    e
+                )));
             }
         };
-        Ok(Catboost { model })
+        let num_outputs = model.get_dimensions_count().max(1);
+        Ok(Catboost {
+            model,
+            num_outputs,
+            class_labels: Vec::new(),
+        }
+        .with_class_labels(read_provenance(path).class_labels))
+    }
+
+    /// Sets the class names of a multiclass model, in the order of its outputs.
+    ///
+    /// The predictions of a multiclass model with class names contain the raw formula values per class as
+    /// `logits`, the `probabilities` after applying softmax and the `predicted_label` of each row.
+    /// Class names are ignored if the model does not return one value per class.
+    pub fn with_class_labels(mut self, class_labels: Vec<String>) -> Self {
+        if class_labels.is_empty() {
+            return self;
+        }
+        if self.num_outputs < 2 || class_labels.len() != self.num_outputs {
+            tracing::warn!(
+                "Ignoring {} class labels for Catboost model with {} outputs ⚠️",
+                class_labels.len(),
+                self.num_outputs
+            );
+            return self;
+        }
+        self.class_labels = class_labels;
+        self
     }
 }
 
+impl Lifecycle for Catboost {}
+
 impl Predict for Catboost {
     /// Predicts output based on the given input using the Catboost model.
     ///
@@ -120,17 +163,42 @@ impl Predict for Catboost {
     ///
     /// Returns an `Err` if there is an issue with parsing the input or making predictions.
     #[tracing::instrument(skip(self, input))]
-    fn predict(&self, input: ModelInput) -> anyhow::Result<ModelOutput> {
-        let input = CatboostModelInput::parse(input)?;
+    fn predict(&self, input: ModelInput) -> Result<ModelOutput> {
+        let num_rows = input.num_rows();
+        let input = CatboostModelInput::parse(input).map_err(Error::Input)?;
         let preds = self
             .model
             .calc_model_prediction(input.numeric_features, input.categorical_features);
         match preds {
             Ok(preds) => {
+                // multiclass and multi-target models return one value per class or target for each row
+                if preds.len() != num_rows * self.num_outputs {
+                    tracing::error!(
+                        "Catboost model returned {} values for {} rows with {} outputs",
+                        preds.len(),
+                        num_rows,
+                        self.num_outputs
+                    );
+                    return Err(Error::Predict(anyhow::anyhow!(
+                        "Catboost model returned {} values for {} rows with {} outputs",
+                        preds.len(),
+                        num_rows,
+                        self.num_outputs
+                    )));
+                }
+                let values = split_into_rows(&preds, num_rows).map_err(Error::Predict)?;
+                if !self.class_labels.is_empty() {
+                    return classifier_output(values, &self.class_labels).map_err(Error::Predict);
+                }
+
                 let mut predictions: HashMap<String, Vec<Vec<f64>>> = HashMap::new();
-                let values: Vec<Vec<f64>> = preds.into_iter().map(|v| vec![v]).collect();
                 predictions.insert(DEFAULT_OUTPUT_KEY.to_string(), values);
-                Ok(ModelOutput { predictions })
+                Ok(ModelOutput {
+                    predictions,
+                    labels: HashMap::new(),
+                    shapes: HashMap::new(),
+                    texts: HashMap::new(),
+                })
             }
             Err(e) => {
                 tracing::error!(
@@ -138,10 +206,10 @@ impl Predict for Catboost {
                     e.to_string()
                 );
 
-                anyhow::bail!(
+                Err(Error::Predict(anyhow::anyhow!(
                     "Failed to make predictions using Catboost model: {}",
                     e.to_string()
-                )
+                )))
             }
         }
     }
@@ -149,6 +217,7 @@ impl Predict for Catboost {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::output::{PREDICTED_LABEL_KEY, PROBABILITIES_OUTPUT_KEY};
     use crate::model::test_utils;
 
     #[test]
@@ -301,43 +370,99 @@ mod tests {
         assert!(model.is_ok())
     }
 
-    // todo: investigate why multiclass classification fails for single prediction
-    // #[test]
-    // fn successfully_make_single_prediction_using_catboost_multiclass_classification_model() {
-    //     let path = "tests/model_artefacts/catboost_multiclass";
-    //     let model = Catboost::load(path).unwrap();
-    //
-    //     let model_inputs = create_model_inputs(
-    //         model.model.get_float_features_count(),
-    //         model.model.get_cat_features_count(),
-    //         1,
-    //     );
-    //
-    //     // make predictions
-    //     let output = model.predict(model_inputs);
-    //
-    //     // assert
-    //     assert!(output.is_ok());
-    //     assert_eq!(output.unwrap().predictions.len(), 1)
-    // }
-
-    // todo: investigate why multiclass classification fails for batch prediction
-    // #[test]
-    // fn successfully_make_batch_prediction_using_catboost_multiclass_classification_model() {
-    //     let path = "tests/model_artefacts/catboost_multiclass";
-    //     let model = Catboost::load(path).unwrap();
-    //
-    //     let model_inputs = create_model_inputs(
-    //         model.model.get_float_features_count(),
-    //         model.model.get_cat_features_count(),
-    //         10,
-    //     );
-    //
-    //     // make predictions
-    //     let output = model.predict(model_inputs);
-    //
-    //     // assert
-    //     assert!(output.is_ok());
-    //     assert_eq!(output.unwrap().predictions.len(), 1)
-    // }
+    #[test]
+    fn successfully_make_batch_prediction_using_catboost_multiclass_classification_model() {
+        let path = "tests/model_storage/models/catboost-my_awesome_multiclass_model";
+        let model = Catboost::load(path).unwrap();
+
+        let size = 10;
+        let model_inputs = test_utils::utils::create_model_inputs(
+            model.model.get_float_features_count(),
+            model.model.get_cat_features_count(),
+            size,
+        );
+
+        // make predictions
+        let output = model.predict(model_inputs);
+
+        // assert
+        assert!(output.is_ok());
+        let predictions = output.unwrap().predictions;
+        let predictions = predictions.get(DEFAULT_OUTPUT_KEY).unwrap();
+
+        // asserts the predictions have the shape [n_rows, n_classes]
+        assert_eq!(predictions.len(), size);
+        for row in predictions {
+            assert_eq!(row.len(), model.model.get_dimensions_count())
+        }
+    }
+
+    #[test]
+    fn successfully_make_prediction_with_class_labels_using_catboost_multiclass_classification_model(
+    ) {
+        let path = "tests/model_storage/models/catboost-my_awesome_multiclass_model";
+        let model = Catboost::load(path).unwrap();
+        let class_labels: Vec<String> = (0..model.model.get_dimensions_count())
+            .map(|i| format!("class_{}", i))
+            .collect();
+        let model = model.with_class_labels(class_labels.clone());
+
+        let size = 5;
+        let model_inputs = test_utils::utils::create_model_inputs(
+            model.model.get_float_features_count(),
+            model.model.get_cat_features_count(),
+            size,
+        );
+
+        // make predictions
+        let output = model.predict(model_inputs).unwrap();
+
+        // assert
+        let probabilities = output.predictions.get(PROBABILITIES_OUTPUT_KEY).unwrap();
+        assert_eq!(probabilities.len(), size);
+        let predicted_labels = output.labels.get(PREDICTED_LABEL_KEY).unwrap();
+        assert_eq!(predicted_labels.len(), size);
+        assert!(predicted_labels
+            .iter()
+            .all(|label| class_labels.contains(label)))
+    }
+
+    #[test]
+    fn successfully_return_one_prediction_per_row_using_catboost_regressor_model() {
+        let path = "tests/model_storage/models/catboost-my_awesome_regressor_model";
+        let model = Catboost::load(path).unwrap();
+        let strategy = test_utils::utils::model_input_strategy(
+            model.model.get_float_features_count(),
+            model.model.get_cat_features_count(),
+            true,
+        );
+
+        // asserts there is one prediction per input row for any valid input
+        let result = test_utils::utils::check_prediction_shapes(&model, strategy);
+
+        // assert the shape invariants hold for every generated input
+        assert!(result.is_ok(), "{:?}", result)
+    }
+
+    #[test]
+    fn successfully_return_one_prediction_per_row_using_catboost_binary_model() {
+        let path = "tests/model_storage/models/catboost-my_awesome_binary_model";
+        let model = Catboost::load(path).unwrap();
+        let strategy = test_utils::utils::model_input_strategy(
+            model.model.get_float_features_count(),
+            model.model.get_cat_features_count(),
+            true,
+        );
+
+        // asserts there is one prediction per input row for any valid input
+        let result = test_utils::utils::check_prediction_shapes(&model, strategy);
+
+        // assert the shape invariants hold for every generated input
+        assert!(result.is_ok(), "{:?}", result)
+    }
+
+    #[test]
+    fn successfully_match_golden_outputs_using_catboost_models() {
+        test_utils::golden::check_golden("catboost-my_awesome_regressor_model", Catboost::load);
+    }
 }
@@ -0,0 +1,219 @@
+use crate::model::output::ModelOutput;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Summary of the differences between the outputs of two versions of a model for the same input.
+///
+/// # Example
+/// ```json
+/// {
+///     "outputs": { "predictions": { "mean": 0.012, "mean_absolute": 0.02, "max_absolute": 0.08, "changed_rows": 3 } },
+///     "labels": { "predicted_label": { "changed_rows": 1 } },
+///     "mismatched": ["logits"]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct OutputComparison {
+    /// The differences of each output which both versions returned with the same shape, keyed by the name of the output.
+    pub outputs: BTreeMap<String, OutputDelta>,
+    /// The differences of each label output which both versions returned for the same rows.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, LabelDelta>,
+    /// The outputs and label outputs which only one version returned, or which have a different shape.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mismatched: Vec<String>,
+}
+
+/// Differences between the values of an output of two versions of a model.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct OutputDelta {
+    /// The mean of `candidate - baseline` over every value, i.e. how much the candidate shifts the output.
+    pub mean: f64,
+    /// The mean absolute difference over every value.
+    pub mean_absolute: f64,
+    /// The largest absolute difference of a value.
+    pub max_absolute: f64,
+    /// The number of rows with at least one different value.
+    pub changed_rows: usize,
+}
+
+/// Differences between the labels of a label output of two versions of a model.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct LabelDelta {
+    /// The number of rows with a different label.
+    pub changed_rows: usize,
+}
+
+impl OutputComparison {
+    /// Compares the outputs of two versions of a model for the same input.
+    ///
+    /// # Arguments
+    /// * `baseline` - The output of the version compared against, e.g. the version being served.
+    /// * `candidate` - The output of the other version, e.g. the version being rolled out.
+    pub fn new(baseline: &ModelOutput, candidate: &ModelOutput) -> Self {
+        let mut comparison = OutputComparison::default();
+
+        for (name, baseline_rows) in baseline.predictions.iter() {
+            match candidate.predictions.get(name) {
+                Some(candidate_rows) if same_shape(baseline_rows, candidate_rows) => {
+                    comparison.outputs.insert(
+                        name.clone(),
+                        OutputDelta::new(baseline_rows, candidate_rows),
+                    );
+                }
+                _ => comparison.mismatched.push(name.clone()),
+            }
+        }
+        for (name, baseline_labels) in baseline.labels.iter() {
+            match candidate.labels.get(name) {
+                Some(candidate_labels) if baseline_labels.len() == candidate_labels.len() => {
+                    let changed_rows = baseline_labels
+                        .iter()
+                        .zip(candidate_labels.iter())
+                        .filter(|(baseline, candidate)| baseline != candidate)
+                        .count();
+                    comparison
+                        .labels
+                        .insert(name.clone(), LabelDelta { changed_rows });
+                }
+                _ => comparison.mismatched.push(name.clone()),
+            }
+        }
+
+        // outputs which only the candidate returned
+        let only_candidate = candidate
+            .predictions
+            .keys()
+            .filter(|name| !baseline.predictions.contains_key(*name))
+            .chain(
+                candidate
+                    .labels
+                    .keys()
+                    .filter(|name| !baseline.labels.contains_key(*name)),
+            )
+            .cloned()
+            .collect::<Vec<String>>();
+        comparison.mismatched.extend(only_candidate);
+        comparison.mismatched.sort();
+        comparison
+    }
+}
+
+impl OutputDelta {
+    fn new(baseline: &[Vec<f64>], candidate: &[Vec<f64>]) -> Self {
+        let mut delta = OutputDelta::default();
+        let mut count = 0;
+        for (baseline_row, candidate_row) in baseline.iter().zip(candidate.iter()) {
+            let mut changed = false;
+            for (baseline_value, candidate_value) in baseline_row.iter().zip(candidate_row.iter()) {
+                let difference = candidate_value - baseline_value;
+                delta.mean += difference;
+                delta.mean_absolute += difference.abs();
+                delta.max_absolute = delta.max_absolute.max(difference.abs());
+                changed |= difference != 0.0;
+                count += 1;
+            }
+            if changed {
+                delta.changed_rows += 1;
+            }
+        }
+        if count > 0 {
+            delta.mean /= count as f64;
+            delta.mean_absolute /= count as f64;
+        }
+        delta
+    }
+}
+
+fn same_shape(baseline: &[Vec<f64>], candidate: &[Vec<f64>]) -> bool {
+    baseline.len() == candidate.len()
+        && baseline
+            .iter()
+            .zip(candidate.iter())
+            .all(|(baseline_row, candidate_row)| baseline_row.len() == candidate_row.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn model_output(
+        predictions: Vec<(&str, Vec<Vec<f64>>)>,
+        labels: Vec<(&str, Vec<&str>)>,
+    ) -> ModelOutput {
+        ModelOutput {
+            predictions: predictions
+                .into_iter()
+                .map(|(name, rows)| (name.to_string(), rows))
+                .collect(),
+            labels: labels
+                .into_iter()
+                .map(|(name, rows)| {
+                    (
+                        name.to_string(),
+                        rows.into_iter().map(String::from).collect(),
+                    )
+                })
+                .collect(),
+            shapes: HashMap::new(),
+            texts: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn successfully_compare_outputs_of_two_versions() {
+        // Arrange
+        let baseline = model_output(
+            vec![
+                ("predictions", vec![vec![0.2], vec![0.5], vec![0.9]]),
+                ("logits", vec![vec![1.0, 2.0]]),
+            ],
+            vec![("predicted_label", vec!["cat", "dog", "dog"])],
+        );
+        let candidate = model_output(
+            vec![
+                ("predictions", vec![vec![0.3], vec![0.5], vec![0.7]]),
+                ("logits", vec![vec![1.0, 2.0, 3.0]]),
+                ("probabilities", vec![vec![0.1]]),
+            ],
+            vec![("predicted_label", vec!["cat", "cat", "dog"])],
+        );
+
+        // Act
+        let comparison = OutputComparison::new(&baseline, &candidate);
+
+        // Assert
+        let delta = comparison.outputs.get("predictions").unwrap();
+        assert!((delta.mean - (-0.1 / 3.0)).abs() < 1e-9);
+        assert!((delta.mean_absolute - 0.1).abs() < 1e-9);
+        assert!((delta.max_absolute - 0.2).abs() < 1e-9);
+        assert_eq!(delta.changed_rows, 2);
+        assert_eq!(
+            comparison
+                .labels
+                .get("predicted_label")
+                .unwrap()
+                .changed_rows,
+            1
+        );
+        assert_eq!(comparison.mismatched, vec!["logits", "probabilities"]);
+    }
+
+    #[test]
+    fn successfully_compare_identical_outputs() {
+        // Arrange
+        let output = model_output(vec![("predictions", vec![vec![0.2], vec![0.5]])], vec![]);
+
+        // Act
+        let comparison = OutputComparison::new(&output, &output);
+
+        // Assert
+        assert_eq!(
+            comparison.outputs.get("predictions").unwrap(),
+            &OutputDelta::default()
+        );
+        assert!(comparison.labels.is_empty());
+        assert!(comparison.mismatched.is_empty());
+    }
+}
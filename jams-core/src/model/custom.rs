@@ -0,0 +1,203 @@
+use crate::model::frameworks::{ModelFramework, FRAMEWORKS};
+use crate::model::lifecycle::Lifecycle;
+use crate::model::predict::Predict;
+use crate::model::Predictor;
+use crate::{Error, Result};
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// A predictor of a framework which is not shipped with jams-core, e.g. one built with jams-predictor-sdk.
+///
+/// Every type implementing `Predict` and `Lifecycle` is a custom predictor. Predictors without hooks implement
+/// `Lifecycle` with an empty `impl` block.
+pub trait CustomPredict: Predict + Lifecycle {}
+
+impl<T: Predict + Lifecycle> CustomPredict for T {}
+
+/// Loads a predictor of a registered framework from the path to its unpacked artifact.
+type LoadCustomPredictor = Arc<dyn Fn(&str) -> Result<Box<dyn CustomPredict>> + Send + Sync>;
+
+fn loaders() -> &'static RwLock<BTreeMap<ModelFramework, LoadCustomPredictor>> {
+    static LOADERS: OnceLock<RwLock<BTreeMap<ModelFramework, LoadCustomPredictor>>> =
+        OnceLock::new();
+    LOADERS.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+/// Registers the loader of a framework which is not shipped with jams-core, so that the model stores load the
+/// `<framework>-<model_name>` models of the framework with it, like the models of the frameworks shipped with
+/// jams-core. Frameworks must be registered before the model stores are created.
+///
+/// The loader is called with the path to the unpacked artifact, i.e. the file or directory named after the
+/// tarball without its `.tar.gz` extension, or the file itself in a local model store.
+///
+/// # Arguments
+///
+/// * `framework` - The name of the framework, which prefixes the names of its models, e.g. `gbdt`.
+/// * `load` - Loads a predictor from the path to its artifact.
+///
+/// # Errors
+///
+/// * `Err(anyhow::Error)` - If the name is not a valid framework name, is the name of a framework shipped with
+///   jams-core or is already registered.
+///
+/// # Example
+/// ```ignore
+/// jams_core::model::custom::register("gbdt", |path| Gbdt::load(path))?;
+/// ```
+pub fn register<P, F>(framework: ModelFramework, load: F) -> anyhow::Result<()>
+where
+    P: Predict + Lifecycle,
+    F: Fn(&str) -> Result<P> + Send + Sync + 'static,
+{
+    if framework.is_empty()
+        || !framework
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        anyhow::bail!(
+            "Framework name {:?} must only contain lowercase letters, digits and underscores ❌",
+            framework
+        )
+    }
+    if FRAMEWORKS.contains(&framework) {
+        anyhow::bail!(
+            "Framework {} is shipped with jams-core and cannot be registered ❌",
+            framework
+        )
+    }
+
+    let mut loaders = match loaders().write() {
+        Ok(loaders) => loaders,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if loaders.contains_key(framework) {
+        anyhow::bail!("Framework {} is already registered ❌", framework)
+    }
+    let load: LoadCustomPredictor = Arc::new(move |path: &str| {
+        load(path).map(|predictor| Box::new(predictor) as Box<dyn CustomPredict>)
+    });
+    loaders.insert(framework, load);
+    tracing::info!("Registered custom framework {} 🧩", framework);
+    Ok(())
+}
+
+/// Returns the registered framework whose `<framework>-` prefix starts the given file name, if any.
+///
+/// # Arguments
+///
+/// * `file_name` - The file name of a model, e.g. `gbdt-my_model.json`.
+pub fn framework_of(file_name: &str) -> Option<ModelFramework> {
+    let loaders = match loaders().read() {
+        Ok(loaders) => loaders,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    loaders.keys().copied().find(|framework| {
+        file_name
+            .strip_prefix(*framework)
+            .is_some_and(|rest| rest.starts_with('-'))
+    })
+}
+
+/// Returns true if the given framework is registered.
+pub fn is_registered(framework: &str) -> bool {
+    let loaders = match loaders().read() {
+        Ok(loaders) => loaders,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    loaders.contains_key(framework)
+}
+
+/// Loads a predictor of a registered framework.
+///
+/// # Arguments
+///
+/// * `framework` - The registered framework.
+/// * `path` - The path to the unpacked artifact of the model.
+///
+/// # Errors
+///
+/// * `Error::Load` - If the framework is not registered or its loader fails.
+pub fn load(framework: &str, path: &str) -> Result<Predictor> {
+    // the loader is cloned so that the lock is not held while the model loads
+    let load = {
+        let loaders = match loaders().read() {
+            Ok(loaders) => loaders,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        loaders.get(framework).cloned()
+    };
+    match load {
+        Some(load) => load(path).map(Predictor::Custom),
+        None => Err(Error::Load(anyhow::anyhow!(
+            "Framework {} is not registered ❌",
+            framework
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::input::ModelInput;
+    use crate::model::output::{ModelOutput, DEFAULT_OUTPUT_KEY};
+    use std::collections::HashMap;
+
+    /// Predicts the number of rows of the input for every row.
+    struct RowCount;
+
+    impl Predict for RowCount {
+        fn predict(&self, input: ModelInput) -> Result<ModelOutput> {
+            let num_rows = input.num_rows();
+            Ok(ModelOutput {
+                predictions: HashMap::from([(
+                    DEFAULT_OUTPUT_KEY.to_string(),
+                    vec![vec![num_rows as f64]; num_rows],
+                )]),
+                labels: HashMap::new(),
+                shapes: HashMap::new(),
+                texts: HashMap::new(),
+            })
+        }
+    }
+
+    impl Lifecycle for RowCount {}
+
+    #[test]
+    fn successfully_register_and_load_custom_framework() {
+        // Arrange
+        register("row_count", |_path| Ok(RowCount)).unwrap();
+        let input = ModelInput::from_str(r#"{"feature_1": [0.5, 1.0]}"#).unwrap();
+
+        // Act
+        let framework = framework_of("row_count-my_model.json");
+        let predictor = load("row_count", "row_count-my_model.json").unwrap();
+        let output = predictor.predict(input).unwrap();
+
+        // Assert
+        assert_eq!(framework, Some("row_count"));
+        assert!(is_registered("row_count"));
+        assert_eq!(framework_of("row_counter-my_model.json"), None);
+        assert_eq!(
+            output.predictions[DEFAULT_OUTPUT_KEY],
+            vec![vec![2.0], vec![2.0]]
+        );
+    }
+
+    #[test]
+    fn fails_to_register_invalid_builtin_or_duplicate_framework() {
+        // Arrange
+        register("duplicate", |_path| Ok(RowCount)).unwrap();
+
+        // Act
+        let duplicate = register("duplicate", |_path| Ok(RowCount));
+        let builtin = register("lightgbm", |_path| Ok(RowCount));
+        let invalid = register("my-framework", |_path| Ok(RowCount));
+        let unregistered = load("unregistered", "unregistered-my_model.json");
+
+        // Assert
+        assert!(duplicate.is_err());
+        assert!(builtin.is_err());
+        assert!(invalid.is_err());
+        assert!(matches!(unregistered, Err(Error::Load(_))));
+    }
+}
@@ -0,0 +1,302 @@
+use crate::model::input::ModelInput;
+use crate::model::output::{ModelOutput, DEFAULT_OUTPUT_KEY};
+use crate::model::predict::PredictOptions;
+use crate::{Error, Result};
+use std::collections::HashMap;
+
+/// Struct representing the input data of a model which reads a single dense float tensor, e.g. a TensorFlow Lite or
+/// OpenVINO model.
+///
+/// These models read their input tensor in row-major order, whereas the features of a `ModelInput` are stored one
+/// column after another, so the features are transposed into a single buffer.
+pub struct DenseInput {
+    /// Numerical features in row-major order.
+    pub values: Vec<f32>,
+    /// The number of rows in the input.
+    pub num_rows: usize,
+}
+
+impl DenseInput {
+    /// Parses the input `ModelInput` into a `DenseInput`.
+    ///
+    /// The float features come first, followed by the integer features, as for TensorRT engines. The features of
+    /// a row are the flattened input tensor of one sample, e.g. the pixels of an image.
+    ///
+    /// # Arguments
+    ///
+    /// * `framework` - The name of the framework of the model, used in error messages.
+    /// * `model_input` - The `ModelInput` containing the input values.
+    /// * `row_len` - The number of values of one sample of the input tensor of the model.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the input is empty, contains string features or if the number of features does not
+    /// match the input tensor of the model.
+    #[tracing::instrument(skip(model_input))]
+    pub fn parse(framework: &str, model_input: ModelInput, row_len: usize) -> anyhow::Result<Self> {
+        if (model_input.integer_features.values.is_empty())
+            && (model_input.float_features.values.is_empty())
+        {
+            tracing::error!("input is empty");
+            anyhow::bail!("input is empty")
+        }
+        if !model_input.string_features.values.is_empty() {
+            tracing::error!("{} models do not support string features", framework);
+            anyhow::bail!("{} models do not support string features", framework)
+        }
+
+        let num_rows = model_input.num_rows();
+        let floats = model_input.float_features.values.into_floats().unwrap();
+        let ints = model_input.integer_features.values.into_ints().unwrap();
+        let num_features = (floats.len() + ints.len()) / num_rows.max(1);
+        if num_features != row_len {
+            tracing::error!(
+                "Expected {} features per row for the {} model, got {}",
+                row_len,
+                framework,
+                num_features
+            );
+            anyhow::bail!(
+                "Expected {} features per row for the {} model, got {}",
+                row_len,
+                framework,
+                num_features
+            )
+        }
+        let num_float_features = floats.len() / num_rows.max(1);
+
+        // transpose the columns into rows, converting the integer features to float
+        let mut values = Vec::with_capacity(num_rows * num_features);
+        for row in 0..num_rows {
+            for feature in 0..num_float_features {
+                values.push(floats[feature * num_rows + row]);
+            }
+            for feature in 0..num_features - num_float_features {
+                values.push(ints[feature * num_rows + row] as f32);
+            }
+        }
+
+        Ok(Self { values, num_rows })
+    }
+
+    /// Splits the rows into batches of the batch size of the model, returning the values and the number of rows of
+    /// each batch. Only the last batch may hold fewer rows.
+    pub fn batches<'a>(
+        &'a self,
+        input: &DenseTensor,
+        batch_size: usize,
+    ) -> impl Iterator<Item = (&'a [f32], usize)> + 'a {
+        let row_len = input.row_len;
+        self.values
+            .chunks(batch_size * row_len)
+            .map(move |batch| (batch, batch.len() / row_len))
+    }
+}
+
+/// An input or output tensor of a model with a dense float tensor per sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenseTensor {
+    pub name: String,
+    /// The number of values of one sample, i.e. the product of the dimensions after the batch dimension.
+    pub row_len: usize,
+}
+
+impl DenseTensor {
+    /// Describes a tensor of the model from its static shape, whose first dimension is the batch size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tensor is a scalar, empty or has a dynamic dimension, or if its batch size does not
+    /// match `batch_size`.
+    pub fn new(
+        framework: &str,
+        name: String,
+        shape: &[i64],
+        batch_size: usize,
+    ) -> anyhow::Result<Self> {
+        let (batch, sample) = match shape.split_first() {
+            Some(split) => split,
+            None => anyhow::bail!(
+                "Tensor {} of the {} model has no batch dimension",
+                name,
+                framework
+            ),
+        };
+        if *batch != batch_size as i64 {
+            anyhow::bail!(
+                "Tensor {} of the {} model has a batch size of {}, expected {}",
+                name,
+                framework,
+                batch,
+                batch_size
+            )
+        }
+        let row_len = sample
+            .iter()
+            .map(|dimension| usize::try_from(*dimension).unwrap_or_default())
+            .product();
+        if row_len == 0 {
+            anyhow::bail!("Tensor {} of the {} model is empty", name, framework)
+        }
+        Ok(DenseTensor { name, row_len })
+    }
+
+    /// Writes a batch into the input tensor of the model, padding it with zeros up to the batch size of the model.
+    pub fn fill(tensor: &mut [f32], batch: &[f32]) {
+        tensor[..batch.len()].copy_from_slice(batch);
+        tensor[batch.len()..].fill(0.0);
+    }
+
+    /// Appends the first `num_rows` samples of the values of this output tensor to `rows`, skipping the padding.
+    pub fn extend_rows(&self, rows: &mut Vec<Vec<f64>>, values: &[f32], num_rows: usize) {
+        rows.extend(
+            values
+                .chunks(self.row_len)
+                .take(num_rows)
+                .map(|row| row.iter().map(|x| *x as f64).collect::<Vec<f64>>()),
+        );
+    }
+}
+
+/// Checks the prediction options of a model with dense tensors, which supports neither `mode` nor `num_iteration`.
+///
+/// # Errors
+///
+/// Returns an `Error::Input` if the options are not valid or not supported.
+pub fn validate_options(framework: &str, options: &PredictOptions) -> Result<()> {
+    options.validate().map_err(Error::Input)?;
+    if options.mode.is_some() || options.num_iteration.is_some() {
+        return Err(Error::Input(anyhow::anyhow!(
+            "mode and num_iteration are not supported by {} models ❌",
+            framework
+        )));
+    }
+    Ok(())
+}
+
+/// Builds the output of a model from the rows of each of its output tensors.
+///
+/// A model with a single output tensor returns it as the `predictions` output, otherwise each output is named after
+/// its tensor.
+pub fn model_output<'a>(
+    outputs: impl IntoIterator<Item = &'a DenseTensor>,
+    rows: Vec<Vec<Vec<f64>>>,
+) -> ModelOutput {
+    let num_outputs = rows.len();
+    let mut predictions: HashMap<String, Vec<Vec<f64>>> = HashMap::new();
+    for (output, values) in outputs.into_iter().zip(rows) {
+        let output_key = match num_outputs {
+            1 => DEFAULT_OUTPUT_KEY.to_string(),
+            _ => output.name.clone(),
+        };
+        predictions.insert(output_key, values);
+    }
+    ModelOutput {
+        predictions,
+        labels: HashMap::new(),
+        shapes: HashMap::new(),
+        texts: HashMap::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_utils;
+
+    #[test]
+    fn successfully_transpose_dense_input_into_rows() {
+        let mut model_input = ModelInput::default();
+        model_input.float_features.values = crate::model::input::Values::Float(vec![
+            0.1, 0.2, // first float feature
+            1.1, 1.2, // second float feature
+        ]);
+        model_input.float_features.shape = (2, 2);
+        model_input.integer_features.values = crate::model::input::Values::Int(vec![7, 8]);
+        model_input.integer_features.shape = (1, 2);
+
+        // parse the input
+        let input = DenseInput::parse("OpenVINO", model_input, 3).unwrap();
+
+        // assert each row holds the float features followed by the integer features
+        assert_eq!(input.num_rows, 2);
+        assert_eq!(input.values, vec![0.1, 1.1, 7.0, 0.2, 1.2, 8.0]);
+    }
+
+    #[test]
+    fn fails_to_parse_dense_input_when_features_do_not_match_the_model() {
+        let model_inputs = test_utils::utils::create_model_inputs(2, 0, 3);
+
+        // parse the input
+        let input = DenseInput::parse("TensorFlow Lite", model_inputs, 96 * 96);
+
+        // assert the result is an error
+        assert!(input.is_err());
+    }
+
+    #[test]
+    fn successfully_split_dense_input_into_batches_of_the_model() {
+        let input = DenseInput {
+            values: vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+            num_rows: 3,
+        };
+        let tensor = DenseTensor::new("OpenVINO", "features".to_string(), &[2, 2], 2).unwrap();
+        let mut padded = [9.0; 4];
+
+        // split the rows and pad the last batch
+        let batches = input.batches(&tensor, 2).collect::<Vec<(&[f32], usize)>>();
+        DenseTensor::fill(&mut padded, batches[1].0);
+
+        // assert
+        assert_eq!(
+            batches,
+            vec![(&[1.0, 2.0, 3.0, 4.0][..], 2), (&[5.0, 6.0][..], 1)]
+        );
+        assert_eq!(padded, [5.0, 6.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn successfully_describe_tensors_of_a_model_with_static_shapes() {
+        // describe an image input, a mismatched output, a dynamic output and a scalar
+        let input =
+            DenseTensor::new("OpenVINO", "images".to_string(), &[8, 3, 224, 224], 8).unwrap();
+        let output = DenseTensor::new("OpenVINO", "logits".to_string(), &[4, 1000], 8);
+        let dynamic = DenseTensor::new("OpenVINO", "scores".to_string(), &[8, -1], 8);
+        let scalar = DenseTensor::new("TensorFlow Lite", "scalar".to_string(), &[], 1);
+
+        // assert
+        assert_eq!(input.row_len, 3 * 224 * 224);
+        assert!(output.is_err());
+        assert!(dynamic.is_err());
+        assert!(scalar.is_err());
+    }
+
+    #[test]
+    fn successfully_name_outputs_after_their_tensors() {
+        let single = vec![DenseTensor {
+            name: "scores".to_string(),
+            row_len: 1,
+        }];
+        let multiple = vec![
+            DenseTensor {
+                name: "boxes".to_string(),
+                row_len: 1,
+            },
+            DenseTensor {
+                name: "scores".to_string(),
+                row_len: 1,
+            },
+        ];
+
+        let single_output = model_output(&single, vec![vec![vec![0.5]]]);
+        let multiple_output = model_output(&multiple, vec![vec![vec![1.0]], vec![vec![0.5]]]);
+
+        // assert
+        assert_eq!(
+            single_output.predictions[DEFAULT_OUTPUT_KEY],
+            vec![vec![0.5]]
+        );
+        assert_eq!(multiple_output.predictions["boxes"], vec![vec![1.0]]);
+        assert_eq!(multiple_output.predictions["scores"], vec![vec![0.5]]);
+    }
+}
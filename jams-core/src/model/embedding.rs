@@ -0,0 +1,359 @@
+use crate::model::output::{ModelOutput, DEFAULT_OUTPUT_KEY};
+use serde::{Deserialize, Serialize};
+
+/// Name of the optional file in a model tarball which contains the embeddings to search for nearest neighbors.
+pub const EMBEDDING_INDEX_FILE_NAME: &str = "embedding_index.json";
+
+/// Maximum number of vectors in an embedding index. The index is searched exhaustively, so it must stay small.
+pub const MAX_INDEX_SIZE: usize = 100_000;
+
+/// Maximum number of nearest neighbors returned for each embedding.
+pub const MAX_TOP_K: usize = 1_000;
+
+fn default_output() -> String {
+    DEFAULT_OUTPUT_KEY.to_string()
+}
+
+/// A small in-memory index of embeddings shipped with an embedding model, e.g. the item tower outputs of
+/// a two-tower model, used to look up the nearest neighbors of the embeddings returned by the model.
+///
+/// The index is read from the `embedding_index.json` file at the root of the model tarball.
+///
+/// # Example
+/// ```json
+/// { "output": "embeddings", "ids": ["item_1", "item_2"], "vectors": [[0.1, 0.9], [0.7, 0.2]] }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EmbeddingIndex {
+    /// The output of the model which holds the embeddings. Defaults to `predictions`.
+    #[serde(default = "default_output")]
+    pub output: String,
+    /// The ID of each vector, returned with the nearest neighbors.
+    pub ids: Vec<String>,
+    /// The vectors of the index, all of the same dimension.
+    pub vectors: Vec<Vec<f64>>,
+}
+
+/// A vector of an embedding index which is near an embedding.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Neighbor {
+    /// The ID of the vector in the index.
+    pub id: String,
+    /// The cosine similarity between the embedding and the vector.
+    pub score: f64,
+}
+
+/// What to compute from the embeddings returned by a model.
+///
+/// # Example
+/// ```json
+/// { "output": "embeddings", "top_k": 5, "compare_to": [[0.1, 0.9]] }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EmbedOptions {
+    /// The output of the model which holds the embeddings. Defaults to the output of the embedding index
+    /// of the model, or `predictions`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Number of nearest neighbors to look up in the embedding index of the model for each embedding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<usize>,
+    /// Vectors to compare each embedding to with the cosine similarity.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compare_to: Vec<Vec<f64>>,
+}
+
+/// The embeddings returned by a model, along with the similarities and nearest neighbors requested
+/// by the `EmbedOptions`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Embeddings {
+    /// The output of the model which holds the embeddings.
+    pub output: String,
+    /// The dimension of the embeddings.
+    pub dimension: usize,
+    /// One embedding per row of the input.
+    pub embeddings: Vec<Vec<f64>>,
+    /// The cosine similarity of each embedding to each vector of `compare_to`, in the same order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub similarities: Vec<Vec<f64>>,
+    /// The `top_k` nearest neighbors of each embedding in the embedding index, most similar first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub neighbors: Vec<Vec<Neighbor>>,
+}
+
+/// Returns the cosine similarity of two vectors of the same dimension, or `0.0` if either of them is zero.
+pub fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
+    let (dot, norm_a, norm_b) = a
+        .iter()
+        .zip(b.iter())
+        .fold((0.0, 0.0, 0.0), |(dot, norm_a, norm_b), (x, y)| {
+            (dot + x * y, norm_a + x * x, norm_b + y * y)
+        });
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// Checks that vectors are finite and all have the given dimension.
+fn validate_vectors(vectors: &[Vec<f64>], dimension: usize, name: &str) -> anyhow::Result<()> {
+    for vector in vectors {
+        if vector.len() != dimension {
+            anyhow::bail!(
+                "Vectors of {} must have a dimension of {} but got {} ❌",
+                name,
+                dimension,
+                vector.len()
+            )
+        }
+        if vector.iter().any(|value| !value.is_finite()) {
+            anyhow::bail!("Vectors of {} must only contain finite numbers ❌", name)
+        }
+    }
+    Ok(())
+}
+
+impl EmbeddingIndex {
+    /// Parses and validates an embedding index.
+    ///
+    /// # Arguments
+    /// * `contents` - The contents of an `embedding_index.json` file.
+    ///
+    /// # Returns
+    /// * `Ok(EmbeddingIndex)` - If the embedding index is valid.
+    /// * `Err(anyhow::Error)` - If the file cannot be parsed or the vectors are invalid.
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let index: EmbeddingIndex = match serde_json::from_str(contents) {
+            Ok(index) => index,
+            Err(e) => anyhow::bail!("Failed to parse {} ❌: {}", EMBEDDING_INDEX_FILE_NAME, e),
+        };
+        index.validate()?;
+        Ok(index)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.vectors.is_empty() || self.ids.len() != self.vectors.len() {
+            anyhow::bail!("Embedding index requires the same non-zero number of ids and vectors ❌")
+        }
+        if self.vectors.len() > MAX_INDEX_SIZE {
+            anyhow::bail!(
+                "Embedding index must have at most {} vectors ❌",
+                MAX_INDEX_SIZE
+            )
+        }
+        if self.dimension() == 0 {
+            anyhow::bail!("Embedding index vectors must not be empty ❌")
+        }
+        validate_vectors(&self.vectors, self.dimension(), EMBEDDING_INDEX_FILE_NAME)
+    }
+
+    /// Returns the dimension of the vectors of the index.
+    pub fn dimension(&self) -> usize {
+        self.vectors.first().map(|vector| vector.len()).unwrap_or(0)
+    }
+
+    /// Returns the `top_k` vectors of the index most similar to an embedding, most similar first.
+    ///
+    /// # Arguments
+    /// * `embedding` - The embedding, of the dimension of the index.
+    /// * `top_k` - The number of neighbors to return.
+    pub fn nearest(&self, embedding: &[f64], top_k: usize) -> Vec<Neighbor> {
+        let mut scores: Vec<(usize, f64)> = self
+            .vectors
+            .iter()
+            .map(|vector| cosine_similarity(embedding, vector))
+            .enumerate()
+            .collect();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scores
+            .into_iter()
+            .take(top_k)
+            .map(|(i, score)| Neighbor {
+                id: self.ids[i].clone(),
+                score,
+            })
+            .collect()
+    }
+}
+
+impl EmbedOptions {
+    /// Validates the embed options.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(top_k) = self.top_k {
+            if top_k == 0 || top_k > MAX_TOP_K {
+                anyhow::bail!("top_k must be between 1 and {} ❌", MAX_TOP_K)
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Embeddings {
+    /// Extracts the embeddings from the output of a model and computes the similarities and nearest
+    /// neighbors requested by the options.
+    ///
+    /// # Arguments
+    /// * `output` - The output of the model.
+    /// * `index` - The embedding index shipped with the model, if any.
+    /// * `options` - The similarities and nearest neighbors to compute.
+    ///
+    /// # Returns
+    /// * `Ok(Embeddings)` - The embeddings, similarities and nearest neighbors.
+    /// * `Err(anyhow::Error)` - If the output does not exist, the vectors to compare to or the index do not have
+    ///   the dimension of the embeddings, or nearest neighbors are requested from a model without an index.
+    pub fn from_output(
+        mut output: ModelOutput,
+        index: Option<&EmbeddingIndex>,
+        options: &EmbedOptions,
+    ) -> anyhow::Result<Self> {
+        options.validate()?;
+        let output_key = match (&options.output, index) {
+            (Some(output_key), _) => output_key.clone(),
+            (None, Some(index)) => index.output.clone(),
+            (None, None) => default_output(),
+        };
+        let embeddings = match output.predictions.remove(output_key.as_str()) {
+            Some(embeddings) => embeddings,
+            None => anyhow::bail!("Model has no output named {} ❌", output_key),
+        };
+        let dimension = embeddings.first().map(|row| row.len()).unwrap_or(0);
+        validate_vectors(&embeddings, dimension, output_key.as_str())?;
+        validate_vectors(&options.compare_to, dimension, "compare_to")?;
+
+        let similarities = match options.compare_to.is_empty() {
+            true => Vec::new(),
+            false => embeddings
+                .iter()
+                .map(|embedding| {
+                    options
+                        .compare_to
+                        .iter()
+                        .map(|vector| cosine_similarity(embedding, vector))
+                        .collect()
+                })
+                .collect(),
+        };
+
+        let neighbors = match (options.top_k, index) {
+            (None, _) => Vec::new(),
+            (Some(_), None) => anyhow::bail!("Model has no embedding index to search ❌"),
+            (Some(top_k), Some(index)) => {
+                if index.dimension() != dimension {
+                    anyhow::bail!(
+                        "Embedding index has a dimension of {} but the embeddings have a dimension of {} ❌",
+                        index.dimension(),
+                        dimension
+                    )
+                }
+                embeddings
+                    .iter()
+                    .map(|embedding| index.nearest(embedding, top_k))
+                    .collect()
+            }
+        };
+
+        Ok(Embeddings {
+            output: output_key,
+            dimension,
+            embeddings,
+            similarities,
+            neighbors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn model_output(embeddings: Vec<Vec<f64>>) -> ModelOutput {
+        ModelOutput {
+            predictions: HashMap::from([("embeddings".to_string(), embeddings)]),
+            labels: HashMap::new(),
+            shapes: HashMap::new(),
+            texts: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn successfully_look_up_nearest_neighbors_and_similarities_of_embeddings() {
+        // Arrange
+        let index = EmbeddingIndex::parse(
+            r#"{"output": "embeddings", "ids": ["a", "b", "c"], "vectors": [[1.0, 0.0], [0.0, 1.0], [1.0, 1.0]]}"#,
+        )
+        .unwrap();
+        let options = EmbedOptions {
+            output: None,
+            top_k: Some(2),
+            compare_to: vec![vec![2.0, 0.0]],
+        };
+
+        // Act
+        let embeddings = Embeddings::from_output(
+            model_output(vec![vec![0.9, 0.1], vec![0.0, 3.0]]),
+            Some(&index),
+            &options,
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(embeddings.output, "embeddings");
+        assert_eq!(embeddings.dimension, 2);
+        assert_eq!(embeddings.similarities[1], vec![0.0]);
+        assert!((embeddings.similarities[0][0] - 0.9938837346736189).abs() < 1e-12);
+        let ids: Vec<Vec<&str>> = embeddings
+            .neighbors
+            .iter()
+            .map(|neighbors| neighbors.iter().map(|n| n.id.as_str()).collect())
+            .collect();
+        assert_eq!(ids, vec![vec!["a", "c"], vec!["b", "c"]]);
+    }
+
+    #[test]
+    fn fails_to_embed_when_options_do_not_match_the_embeddings() {
+        // Arrange
+        let index =
+            EmbeddingIndex::parse(r#"{"ids": ["a"], "vectors": [[1.0, 0.0, 0.0]]}"#).unwrap();
+        let output = || model_output(vec![vec![0.9, 0.1]]);
+        let top_k = EmbedOptions {
+            output: Some("embeddings".to_string()),
+            top_k: Some(1),
+            compare_to: Vec::new(),
+        };
+        let compare_to = EmbedOptions {
+            output: Some("embeddings".to_string()),
+            top_k: None,
+            compare_to: vec![vec![1.0]],
+        };
+
+        // Act
+        // the index does not name an output, so the embeddings are read from `predictions`
+        let missing_output =
+            Embeddings::from_output(output(), Some(&index), &EmbedOptions::default());
+        let without_index = Embeddings::from_output(output(), None, &top_k);
+        let wrong_index_dimension = Embeddings::from_output(output(), Some(&index), &top_k);
+        let wrong_compare_to_dimension = Embeddings::from_output(output(), None, &compare_to);
+
+        // Assert
+        assert!(missing_output.is_err());
+        assert!(without_index.is_err());
+        assert!(wrong_index_dimension.is_err());
+        assert!(wrong_compare_to_dimension.is_err());
+    }
+
+    #[test]
+    fn fails_to_parse_embedding_index_when_vectors_are_invalid() {
+        for contents in [
+            r#"{"ids": [], "vectors": []}"#,
+            r#"{"ids": ["a"], "vectors": [[1.0], [2.0]]}"#,
+            r#"{"ids": ["a", "b"], "vectors": [[1.0], [2.0, 3.0]]}"#,
+            r#"{"ids": ["a"], "vectors": [[]]}"#,
+        ] {
+            // assert result is err
+            assert!(EmbeddingIndex::parse(contents).is_err(), "{}", contents)
+        }
+    }
+}
@@ -0,0 +1,912 @@
+use crate::model::output::{ModelOutput, DEFAULT_OUTPUT_KEY};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Name of the optional file in a model tarball which contains the postprocessing program of the model.
+pub const POSTPROCESS_FILE_NAME: &str = "postprocess.expr";
+
+/// Maximum length in bytes of a postprocessing program.
+pub const MAX_PROGRAM_LENGTH: usize = 4096;
+
+/// Maximum nesting depth of an expression, which bounds the recursion of the parser and of the interpreter.
+pub const MAX_EXPRESSION_DEPTH: usize = 64;
+
+/// Variable which holds the row of the `predictions` output, unless the model has an output of the same name.
+pub const PREDICTIONS_VARIABLE: &str = "pred";
+
+/// A postprocessing program shipped with a model, which adds outputs computed from the outputs of the model,
+/// e.g. custom thresholds or transforms, without changing the code of the server.
+///
+/// The program is read from the `postprocess.expr` file at the root of the model tarball. Each statement
+/// assigns an expression to an output, which is evaluated for every row and holds one number per row. The
+/// statements are separated by new lines or `;` and run in order, so that a statement can use the outputs
+/// assigned before it. `#` starts a comment which runs to the end of the line.
+///
+/// Expressions are made of numbers, the outputs of the model, which hold the values of the current row,
+/// `pred` for the `predictions` output, indexing, e.g. `probabilities[1]`, the `+ - * / %` arithmetic, the
+/// `== != < <= > >=` comparisons and the `&& || !` logical operators, which return 1 for true and 0 for
+/// false, the `cond ? a : b` conditional and the `sigmoid`, `exp`, `log`, `abs`, `sqrt`, `floor`, `ceil`,
+/// `round`, `min`, `max`, `pow`, `clamp`, `argmax`, `sum` and `len` functions.
+///
+/// The program is sandboxed: it can only read the outputs of the model, has neither loops nor user-defined
+/// functions, and its length and nesting depth are bounded, so it always terminates in time linear in its size.
+///
+/// # Example
+/// ```text
+/// # flag the rows above the tuned threshold
+/// score = sigmoid(pred[0])
+/// out = score > 0.37 ? 1 : 0
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct PostprocessProgram {
+    source: String,
+    statements: Vec<Statement>,
+}
+
+/// Assigns the value of an expression to an output.
+#[derive(Debug, Clone, PartialEq)]
+struct Statement {
+    output: String,
+    expression: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Number(f64),
+    Variable(String),
+    Index(Box<Expression>, Box<Expression>),
+    Unary(UnaryOperator, Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    Conditional(Box<Expression>, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UnaryOperator {
+    Negate,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Sigmoid,
+    Exp,
+    Log,
+    Abs,
+    Sqrt,
+    Floor,
+    Ceil,
+    Round,
+    Min,
+    Max,
+    Pow,
+    Clamp,
+    Argmax,
+    Sum,
+    Len,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "sigmoid" => Some(Function::Sigmoid),
+            "exp" => Some(Function::Exp),
+            "log" => Some(Function::Log),
+            "abs" => Some(Function::Abs),
+            "sqrt" => Some(Function::Sqrt),
+            "floor" => Some(Function::Floor),
+            "ceil" => Some(Function::Ceil),
+            "round" => Some(Function::Round),
+            "min" => Some(Function::Min),
+            "max" => Some(Function::Max),
+            "pow" => Some(Function::Pow),
+            "clamp" => Some(Function::Clamp),
+            "argmax" => Some(Function::Argmax),
+            "sum" => Some(Function::Sum),
+            "len" => Some(Function::Len),
+            _ => None,
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Function::Min | Function::Max | Function::Pow => 2,
+            Function::Clamp => 3,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Operator(&'static str),
+    Separator,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Identifier(identifier) => write!(f, "{}", identifier),
+            Token::Operator(operator) => write!(f, "{}", operator),
+            Token::Separator => write!(f, "end of statement"),
+        }
+    }
+}
+
+/// Operators and punctuation, the two character ones first so that they are matched before their prefixes.
+const OPERATORS: [&str; 24] = [
+    "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "!", "=", "?", ":", "(",
+    ")", "[", "]", ",", ";", "\n",
+];
+
+/// Splits a program into its tokens, each with the byte offset at which it starts.
+fn tokenize(source: &str) -> anyhow::Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+    while position < source.len() {
+        let rest = &source[position..];
+        let c = rest.chars().next().unwrap_or_default();
+        if c == '#' {
+            position += rest.find('\n').unwrap_or(rest.len());
+        } else if c.is_whitespace() && c != '\n' {
+            position += c.len_utf8();
+        } else if c.is_ascii_digit()
+            || (c == '.' && rest[1..].starts_with(|c: char| c.is_ascii_digit()))
+        {
+            let length = number_length(rest);
+            match rest[..length].parse::<f64>() {
+                Ok(number) => tokens.push((Token::Number(number), position)),
+                Err(_) => anyhow::bail!(
+                    "Invalid number {} at position {}",
+                    &rest[..length],
+                    position
+                ),
+            }
+            position += length;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let length = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push((Token::Identifier(rest[..length].to_string()), position));
+            position += length;
+        } else {
+            let operator = match OPERATORS
+                .iter()
+                .find(|operator| rest.starts_with(**operator))
+            {
+                Some(operator) => *operator,
+                None => anyhow::bail!("Unexpected character {:?} at position {}", c, position),
+            };
+            match operator {
+                ";" | "\n" => tokens.push((Token::Separator, position)),
+                _ => tokens.push((Token::Operator(operator), position)),
+            }
+            position += operator.len();
+        }
+    }
+    Ok(tokens)
+}
+
+/// Returns the length of the number at the start of `source`, e.g. `3`, `0.37`, `.5` or `1e-3`.
+fn number_length(source: &str) -> usize {
+    let bytes = source.as_bytes();
+    let digits = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+    let mut length = digits(0);
+    if length < bytes.len() && bytes[length] == b'.' {
+        length = digits(length + 1);
+    }
+    if length < bytes.len() && (bytes[length] == b'e' || bytes[length] == b'E') {
+        let mut exponent = length + 1;
+        if exponent < bytes.len() && (bytes[exponent] == b'+' || bytes[exponent] == b'-') {
+            exponent += 1;
+        }
+        if exponent < bytes.len() && bytes[exponent].is_ascii_digit() {
+            length = digits(exponent);
+        }
+    }
+    length
+}
+
+/// A recursive descent parser of the tokens of a program. Every parsed expression is returned with its depth,
+/// which is bounded by `MAX_EXPRESSION_DEPTH`.
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    next: usize,
+    end: usize,
+    /// The number of expressions being parsed, i.e. the depth of the recursion of the parser.
+    nesting: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(token, _)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map(|(_, position)| *position)
+            .unwrap_or(self.end)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.next).map(|(token, _)| token.clone());
+        self.next += 1;
+        token
+    }
+
+    fn eat(&mut self, operator: &str) -> bool {
+        match self.peek() {
+            Some(Token::Operator(next)) if *next == operator => {
+                self.next += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, operator: &str) -> anyhow::Result<()> {
+        if !self.eat(operator) {
+            return Err(self.unexpected(format!("expected {}", operator).as_str()));
+        }
+        Ok(())
+    }
+
+    fn unexpected(&self, expected: &str) -> anyhow::Error {
+        match self.peek() {
+            Some(token) => anyhow::anyhow!(
+                "Unexpected {} at position {}, {}",
+                token,
+                self.position(),
+                expected
+            ),
+            None => anyhow::anyhow!("Unexpected end of program, {}", expected),
+        }
+    }
+
+    fn nest(&self, depth: usize) -> anyhow::Result<usize> {
+        if depth >= MAX_EXPRESSION_DEPTH {
+            anyhow::bail!(
+                "Expression at position {} is nested deeper than {}",
+                self.position(),
+                MAX_EXPRESSION_DEPTH
+            )
+        }
+        Ok(depth + 1)
+    }
+
+    fn parse_program(&mut self) -> anyhow::Result<Vec<Statement>> {
+        let mut statements = Vec::new();
+        loop {
+            while self.peek() == Some(&Token::Separator) {
+                self.next += 1;
+            }
+            let output = match self.advance() {
+                None => break,
+                Some(Token::Identifier(output)) => output,
+                Some(_) => {
+                    self.next -= 1;
+                    return Err(self.unexpected("expected the name of an output"));
+                }
+            };
+            self.expect("=")?;
+            let (expression, _) = self.parse_expression()?;
+            match self.peek() {
+                None | Some(Token::Separator) => statements.push(Statement { output, expression }),
+                Some(_) => return Err(self.unexpected("expected the end of the statement")),
+            }
+        }
+        if statements.is_empty() {
+            anyhow::bail!("Postprocessing program has no statement")
+        }
+        Ok(statements)
+    }
+
+    /// Parses an expression, failing before the recursion of the parser goes deeper than the maximum depth of
+    /// an expression, which it would exceed anyway.
+    fn parse_expression(&mut self) -> anyhow::Result<(Expression, usize)> {
+        self.nesting = self.nest(self.nesting)?;
+        let expression = self.parse_conditional();
+        self.nesting -= 1;
+        expression
+    }
+
+    fn parse_conditional(&mut self) -> anyhow::Result<(Expression, usize)> {
+        let (condition, depth) = self.parse_binary(0)?;
+        if !self.eat("?") {
+            return Ok((condition, depth));
+        }
+        let (then, then_depth) = self.parse_expression()?;
+        self.expect(":")?;
+        let (otherwise, otherwise_depth) = self.parse_expression()?;
+        let depth = self.nest(depth.max(then_depth).max(otherwise_depth))?;
+        Ok((
+            Expression::Conditional(Box::new(condition), Box::new(then), Box::new(otherwise)),
+            depth,
+        ))
+    }
+
+    /// Parses the binary operators of `BINARY_OPERATORS[level]` and above, which are left associative.
+    fn parse_binary(&mut self, level: usize) -> anyhow::Result<(Expression, usize)> {
+        if level == BINARY_OPERATORS.len() {
+            return self.parse_unary();
+        }
+        let (mut left, mut depth) = self.parse_binary(level + 1)?;
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Operator(next)) => BINARY_OPERATORS[level]
+                    .iter()
+                    .find(|(symbol, _)| symbol == next)
+                    .map(|(_, operator)| *operator),
+                _ => None,
+            };
+            let operator = match operator {
+                Some(operator) => operator,
+                None => return Ok((left, depth)),
+            };
+            self.next += 1;
+            let (right, right_depth) = self.parse_binary(level + 1)?;
+            depth = self.nest(depth.max(right_depth))?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_unary(&mut self) -> anyhow::Result<(Expression, usize)> {
+        let mut operators = Vec::new();
+        loop {
+            if self.eat("-") {
+                operators.push(UnaryOperator::Negate);
+            } else if self.eat("!") {
+                operators.push(UnaryOperator::Not);
+            } else {
+                break;
+            }
+        }
+        let (mut expression, mut depth) = self.parse_postfix()?;
+        for operator in operators.into_iter().rev() {
+            depth = self.nest(depth)?;
+            expression = Expression::Unary(operator, Box::new(expression));
+        }
+        Ok((expression, depth))
+    }
+
+    fn parse_postfix(&mut self) -> anyhow::Result<(Expression, usize)> {
+        let (mut expression, mut depth) = self.parse_primary()?;
+        while self.eat("[") {
+            let (index, index_depth) = self.parse_expression()?;
+            self.expect("]")?;
+            depth = self.nest(depth.max(index_depth))?;
+            expression = Expression::Index(Box::new(expression), Box::new(index));
+        }
+        Ok((expression, depth))
+    }
+
+    fn parse_primary(&mut self) -> anyhow::Result<(Expression, usize)> {
+        let position = self.position();
+        match self.advance() {
+            Some(Token::Number(number)) => Ok((Expression::Number(number), 1)),
+            Some(Token::Identifier(name)) if self.eat("(") => {
+                let function = match Function::parse(name.as_str()) {
+                    Some(function) => function,
+                    None => anyhow::bail!("Unknown function {} at position {}", name, position),
+                };
+                let mut arguments = Vec::new();
+                let mut depth = 0;
+                if !self.eat(")") {
+                    loop {
+                        let (argument, argument_depth) = self.parse_expression()?;
+                        arguments.push(argument);
+                        depth = depth.max(argument_depth);
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                if arguments.len() != function.arity() {
+                    anyhow::bail!(
+                        "Function {} at position {} takes {} arguments but got {}",
+                        name,
+                        position,
+                        function.arity(),
+                        arguments.len()
+                    )
+                }
+                Ok((Expression::Call(function, arguments), self.nest(depth)?))
+            }
+            Some(Token::Identifier(name)) => Ok((Expression::Variable(name), 1)),
+            Some(Token::Operator("(")) => {
+                let (expression, depth) = self.parse_expression()?;
+                self.expect(")")?;
+                Ok((expression, self.nest(depth)?))
+            }
+            _ => {
+                self.next -= 1;
+                Err(self.unexpected("expected a number, an output or a function"))
+            }
+        }
+    }
+}
+
+/// The binary operators by increasing precedence.
+const BINARY_OPERATORS: [&[(&str, BinaryOperator)]; 5] = [
+    &[("||", BinaryOperator::Or)],
+    &[("&&", BinaryOperator::And)],
+    &[
+        ("==", BinaryOperator::Equal),
+        ("!=", BinaryOperator::NotEqual),
+        ("<", BinaryOperator::Less),
+        ("<=", BinaryOperator::LessOrEqual),
+        (">", BinaryOperator::Greater),
+        (">=", BinaryOperator::GreaterOrEqual),
+    ],
+    &[("+", BinaryOperator::Add), ("-", BinaryOperator::Subtract)],
+    &[
+        ("*", BinaryOperator::Multiply),
+        ("/", BinaryOperator::Divide),
+        ("%", BinaryOperator::Remainder),
+    ],
+];
+
+/// The value of an expression, either a number or the row of an output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value<'a> {
+    Number(f64),
+    Row(&'a [f64]),
+}
+
+impl<'a> Value<'a> {
+    /// Returns the value as a number. A row of a single value, e.g. of a regressor, is that value.
+    fn number(self) -> anyhow::Result<f64> {
+        match self {
+            Value::Number(number) => Ok(number),
+            Value::Row([number]) => Ok(*number),
+            Value::Row(row) => anyhow::bail!(
+                "Expected a number but got a row of {} values, select one with [index]",
+                row.len()
+            ),
+        }
+    }
+
+    /// Returns the value as a row. A number is a row of a single value.
+    fn values(&self) -> &[f64] {
+        match self {
+            Value::Number(number) => std::slice::from_ref(number),
+            Value::Row(row) => row,
+        }
+    }
+}
+
+/// The variables of the row being evaluated.
+struct Scope<'a, 'b> {
+    outputs: &'a HashMap<String, Vec<Vec<f64>>>,
+    /// The outputs assigned by the statements evaluated so far, the last one first.
+    assigned: &'b [(&'b str, f64)],
+    row: usize,
+}
+
+impl<'a, 'b> Scope<'a, 'b> {
+    fn lookup(&self, name: &str) -> anyhow::Result<Value<'a>> {
+        if let Some((_, value)) = self
+            .assigned
+            .iter()
+            .rev()
+            .find(|(output, _)| *output == name)
+        {
+            return Ok(Value::Number(*value));
+        }
+        let rows = match self.outputs.get(name) {
+            Some(rows) => rows,
+            None if name == PREDICTIONS_VARIABLE => match self.outputs.get(DEFAULT_OUTPUT_KEY) {
+                Some(rows) => rows,
+                None => anyhow::bail!("Model has no output named {}", DEFAULT_OUTPUT_KEY),
+            },
+            None => anyhow::bail!("Model has no output named {}", name),
+        };
+        match rows.get(self.row) {
+            Some(row) => Ok(Value::Row(row.as_slice())),
+            None => anyhow::bail!("Output {} has no row {}", name, self.row),
+        }
+    }
+}
+
+fn truth(condition: bool) -> f64 {
+    if condition {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+impl Expression {
+    fn evaluate<'a>(&self, scope: &Scope<'a, '_>) -> anyhow::Result<Value<'a>> {
+        let value = match self {
+            Expression::Number(number) => *number,
+            Expression::Variable(name) => return scope.lookup(name.as_str()),
+            Expression::Index(row, index) => {
+                let row = row.evaluate(scope)?;
+                let index = index.evaluate(scope)?.number()?;
+                let values = row.values();
+                if index.fract() != 0.0 || index < 0.0 || index >= values.len() as f64 {
+                    anyhow::bail!(
+                        "Index {} is out of the bounds of a row of {} values",
+                        index,
+                        values.len()
+                    )
+                }
+                values[index as usize]
+            }
+            Expression::Unary(operator, operand) => {
+                let operand = operand.evaluate(scope)?.number()?;
+                match operator {
+                    UnaryOperator::Negate => -operand,
+                    UnaryOperator::Not => truth(operand == 0.0),
+                }
+            }
+            Expression::Binary(BinaryOperator::And, left, right) => truth(
+                left.evaluate(scope)?.number()? != 0.0 && right.evaluate(scope)?.number()? != 0.0,
+            ),
+            Expression::Binary(BinaryOperator::Or, left, right) => truth(
+                left.evaluate(scope)?.number()? != 0.0 || right.evaluate(scope)?.number()? != 0.0,
+            ),
+            Expression::Binary(operator, left, right) => {
+                let left = left.evaluate(scope)?.number()?;
+                let right = right.evaluate(scope)?.number()?;
+                match operator {
+                    BinaryOperator::Add => left + right,
+                    BinaryOperator::Subtract => left - right,
+                    BinaryOperator::Multiply => left * right,
+                    BinaryOperator::Divide => left / right,
+                    BinaryOperator::Remainder => left % right,
+                    BinaryOperator::Equal => truth(left == right),
+                    BinaryOperator::NotEqual => truth(left != right),
+                    BinaryOperator::Less => truth(left < right),
+                    BinaryOperator::LessOrEqual => truth(left <= right),
+                    BinaryOperator::Greater => truth(left > right),
+                    BinaryOperator::GreaterOrEqual => truth(left >= right),
+                    BinaryOperator::And | BinaryOperator::Or => unreachable!(),
+                }
+            }
+            Expression::Conditional(condition, then, otherwise) => {
+                return match condition.evaluate(scope)?.number()? != 0.0 {
+                    true => then.evaluate(scope),
+                    false => otherwise.evaluate(scope),
+                }
+            }
+            Expression::Call(function, arguments) => {
+                let mut values = Vec::with_capacity(arguments.len());
+                for argument in arguments {
+                    values.push(argument.evaluate(scope)?);
+                }
+                call(*function, &values)?
+            }
+        };
+        Ok(Value::Number(value))
+    }
+}
+
+fn call(function: Function, arguments: &[Value]) -> anyhow::Result<f64> {
+    let number = |i: usize| arguments[i].number();
+    let value = match function {
+        Function::Sigmoid => 1.0 / (1.0 + (-number(0)?).exp()),
+        Function::Exp => number(0)?.exp(),
+        Function::Log => number(0)?.ln(),
+        Function::Abs => number(0)?.abs(),
+        Function::Sqrt => number(0)?.sqrt(),
+        Function::Floor => number(0)?.floor(),
+        Function::Ceil => number(0)?.ceil(),
+        Function::Round => number(0)?.round(),
+        Function::Min => number(0)?.min(number(1)?),
+        Function::Max => number(0)?.max(number(1)?),
+        Function::Pow => number(0)?.powf(number(1)?),
+        // unlike f64::clamp, does not panic if the bounds are inverted
+        Function::Clamp => number(0)?.max(number(1)?).min(number(2)?),
+        Function::Argmax => {
+            let values = arguments[0].values();
+            let mut argmax = 0;
+            for (i, value) in values.iter().enumerate() {
+                if *value > values[argmax] || values[argmax].is_nan() {
+                    argmax = i;
+                }
+            }
+            argmax as f64
+        }
+        Function::Sum => arguments[0].values().iter().sum(),
+        Function::Len => arguments[0].values().len() as f64,
+    };
+    Ok(value)
+}
+
+impl PostprocessProgram {
+    /// Parses a postprocessing program.
+    ///
+    /// # Arguments
+    /// * `source` - The contents of a `postprocess.expr` file.
+    ///
+    /// # Returns
+    /// * `Ok(PostprocessProgram)` - If the program is valid.
+    /// * `Err(anyhow::Error)` - If the program is too long, has a syntax error, calls an unknown function or
+    ///   nests its expressions too deeply.
+    pub fn parse(source: &str) -> anyhow::Result<Self> {
+        if source.len() > MAX_PROGRAM_LENGTH {
+            anyhow::bail!(
+                "Failed to parse {} ❌: the program is longer than {} bytes",
+                POSTPROCESS_FILE_NAME,
+                MAX_PROGRAM_LENGTH
+            )
+        }
+        let statements = tokenize(source).and_then(|tokens| {
+            Parser {
+                tokens,
+                next: 0,
+                end: source.len(),
+                nesting: 0,
+            }
+            .parse_program()
+        });
+        match statements {
+            Ok(statements) => Ok(PostprocessProgram {
+                source: source.to_string(),
+                statements,
+            }),
+            Err(e) => anyhow::bail!("Failed to parse {} ❌: {}", POSTPROCESS_FILE_NAME, e),
+        }
+    }
+
+    /// Returns the names of the outputs assigned by the program, in order.
+    pub fn outputs(&self) -> Vec<&str> {
+        self.statements
+            .iter()
+            .map(|statement| statement.output.as_str())
+            .collect()
+    }
+
+    /// Runs the program on every row of the output of a model and adds the assigned outputs, with one number
+    /// per row. An assigned output replaces the output of the model with the same name.
+    ///
+    /// # Arguments
+    /// * `output` - The output of the model.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the program ran on every row.
+    /// * `Err(anyhow::Error)` - If the program reads an output which is missing, indexes out of the bounds of a
+    ///   row, uses a row as a number or computes a number which is not finite.
+    pub fn apply(&self, output: &mut ModelOutput) -> anyhow::Result<()> {
+        let num_rows = output.predictions.values().map(Vec::len).max().unwrap_or(0);
+        let mut assigned_rows = vec![Vec::with_capacity(num_rows); self.statements.len()];
+        let mut assigned = Vec::with_capacity(self.statements.len());
+        for row in 0..num_rows {
+            assigned.clear();
+            for (statement, rows) in self.statements.iter().zip(assigned_rows.iter_mut()) {
+                let scope = Scope {
+                    outputs: &output.predictions,
+                    assigned: assigned.as_slice(),
+                    row,
+                };
+                let value = match statement
+                    .expression
+                    .evaluate(&scope)
+                    .and_then(Value::number)
+                {
+                    Ok(value) if value.is_finite() => value,
+                    Ok(value) => anyhow::bail!(
+                        "Postprocessing output {} of row {} is {}, which is not a finite number",
+                        statement.output,
+                        row,
+                        value
+                    ),
+                    Err(e) => anyhow::bail!(
+                        "Failed to postprocess output {} of row {}: {}",
+                        statement.output,
+                        row,
+                        e
+                    ),
+                };
+                assigned.push((statement.output.as_str(), value));
+                rows.push(vec![value]);
+            }
+        }
+
+        for (statement, rows) in self.statements.iter().zip(assigned_rows) {
+            output.shapes.remove(statement.output.as_str());
+            output.labels.remove(statement.output.as_str());
+            output.predictions.insert(statement.output.clone(), rows);
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for PostprocessProgram {
+    type Error = anyhow::Error;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        PostprocessProgram::parse(source.as_str())
+    }
+}
+
+impl From<PostprocessProgram> for String {
+    fn from(program: PostprocessProgram) -> Self {
+        program.source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model_output(outputs: &[(&str, Vec<Vec<f64>>)]) -> ModelOutput {
+        ModelOutput {
+            predictions: outputs
+                .iter()
+                .map(|(name, rows)| (name.to_string(), rows.clone()))
+                .collect(),
+            labels: HashMap::new(),
+            shapes: HashMap::new(),
+            texts: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn successfully_apply_threshold_to_predictions() {
+        // Arrange
+        let program = PostprocessProgram::parse("out = sigmoid(pred[0]) > 0.37 ? 1 : 0").unwrap();
+        let mut output = model_output(&[("predictions", vec![vec![-2.0], vec![0.5]])]);
+
+        // Act
+        let result = program.apply(&mut output);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(program.outputs(), vec!["out"]);
+        assert_eq!(output.predictions["out"], vec![vec![0.0], vec![1.0]]);
+        assert_eq!(
+            output.predictions["predictions"],
+            vec![vec![-2.0], vec![0.5]]
+        );
+    }
+
+    #[test]
+    fn successfully_evaluate_operators_by_precedence() {
+        // Arrange
+        let program = PostprocessProgram::parse(
+            "a = 1 + 2 * 3 - 4 % 3; b = -2 - -3 * 2; c = 1 < 2 ? 3 : 4 ? 5 : 6\n\
+             d = clamp(pow(2, 10), 0, 1e2); e = !0 && 2 > 3 || 1",
+        )
+        .unwrap();
+        let mut output = model_output(&[("predictions", vec![vec![0.5]])]);
+
+        // Act
+        let result = program.apply(&mut output);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(output.predictions["a"], vec![vec![6.0]]);
+        assert_eq!(output.predictions["b"], vec![vec![4.0]]);
+        assert_eq!(output.predictions["c"], vec![vec![3.0]]);
+        assert_eq!(output.predictions["d"], vec![vec![100.0]]);
+        assert_eq!(output.predictions["e"], vec![vec![1.0]]);
+    }
+
+    #[test]
+    fn successfully_use_outputs_assigned_before() {
+        // Arrange
+        let program = PostprocessProgram::parse(
+            "# expected value of the classes
+             expected = probabilities[0] * 1 + probabilities[1] * 2; class = argmax(probabilities)
+             risky = !(expected < 1.5) && class == 1 || len(probabilities) > 2",
+        )
+        .unwrap();
+        let mut output = model_output(&[("probabilities", vec![vec![0.25, 0.75], vec![0.9, 0.1]])]);
+
+        // Act
+        let result = program.apply(&mut output);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(output.predictions["expected"], vec![vec![1.75], vec![1.1]]);
+        assert_eq!(output.predictions["class"], vec![vec![1.0], vec![0.0]]);
+        assert_eq!(output.predictions["risky"], vec![vec![1.0], vec![0.0]]);
+    }
+
+    #[test]
+    fn fails_to_parse_invalid_programs() {
+        // Arrange
+        let deeply_nested = format!(
+            "out = {}1{}",
+            "(".repeat(MAX_EXPRESSION_DEPTH),
+            ")".repeat(MAX_EXPRESSION_DEPTH)
+        );
+        let too_long = format!("out = {}", "1 + ".repeat(MAX_PROGRAM_LENGTH));
+        let programs = [
+            "",
+            "# only a comment",
+            "out = ",
+            "out = 1 +",
+            "out = (1",
+            "out = 1 2",
+            "1 = out",
+            "out = unknown(1)",
+            "out = min(1)",
+            "out = pred[0] ? 1",
+            "out = 1 $ 2",
+            deeply_nested.as_str(),
+            too_long.as_str(),
+        ];
+
+        // Act
+        let results = programs.map(PostprocessProgram::parse);
+
+        // Assert
+        for (program, result) in programs.iter().zip(results) {
+            assert!(result.is_err(), "{}", program)
+        }
+    }
+
+    #[test]
+    fn fails_to_apply_program_to_unexpected_outputs() {
+        // Arrange
+        let programs = [
+            "out = logits[0]",
+            "out = pred[2]",
+            "out = pred[0.5]",
+            "out = pred + 1",
+            "out = log(0)",
+        ];
+        let output = || model_output(&[("predictions", vec![vec![0.1, 0.9]])]);
+
+        // Act
+        let results = programs.map(|program| {
+            PostprocessProgram::parse(program)
+                .unwrap()
+                .apply(&mut output())
+        });
+
+        // Assert
+        for (program, result) in programs.iter().zip(results) {
+            assert!(result.is_err(), "{}", program)
+        }
+    }
+
+    #[test]
+    fn successfully_serialize_program_as_its_source() {
+        // Arrange
+        let source = "out = sigmoid(pred) > 0.5";
+        let program = PostprocessProgram::parse(source).unwrap();
+
+        // Act
+        let json = serde_json::to_string(&program).unwrap();
+        let deserialized: PostprocessProgram = serde_json::from_str(json.as_str()).unwrap();
+
+        // Assert
+        assert_eq!(json, serde_json::to_string(source).unwrap());
+        assert_eq!(deserialized, program);
+        assert!(serde_json::from_str::<PostprocessProgram>(r#""out = ""#).is_err());
+    }
+}
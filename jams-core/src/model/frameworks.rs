@@ -32,3 +32,43 @@ pub const LIGHTGBM: ModelFramework = "lightgbm";
 ///
 /// This constant is used to specify that a model is based on the XGBoost framework.
 pub const XGBOOST: ModelFramework = "xgboost";
+
+/// Constant representing the TensorRT inference framework.
+///
+/// This constant is used to specify that a model is a serialized TensorRT engine.
+pub const TENSORRT: ModelFramework = "tensorrt";
+
+/// Constant representing Hugging Face transformers text models.
+///
+/// This constant is used to specify that a model is a directory with a tokenizer and a TorchScript transformer.
+pub const TRANSFORMERS: ModelFramework = "transformers";
+
+/// Constant representing GGUF models run with llama.cpp.
+///
+/// This constant is used to specify that a model is a directory with a GGUF file, e.g. a small local LLM.
+pub const GGUF: ModelFramework = "gguf";
+
+/// Constant representing the TensorFlow Lite inference framework.
+///
+/// This constant is used to specify that a model is a TensorFlow Lite FlatBuffer, e.g. an edge model.
+pub const TFLITE: ModelFramework = "tflite";
+
+/// Constant representing the OpenVINO inference framework.
+///
+/// This constant is used to specify that a model is a directory with an OpenVINO IR model, e.g. for Intel CPUs.
+pub const OPENVINO: ModelFramework = "openvino";
+
+/// The frameworks shipped with jams-core. Other frameworks are registered with `model::custom::register`.
+pub const FRAMEWORKS: [ModelFramework; 11] = [
+    TENSORFLOW,
+    TORCH,
+    PYTORCH,
+    CATBOOST,
+    LIGHTGBM,
+    XGBOOST,
+    TENSORRT,
+    TRANSFORMERS,
+    GGUF,
+    TFLITE,
+    OPENVINO,
+];
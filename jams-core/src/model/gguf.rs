@@ -0,0 +1,443 @@
+use crate::model::input::ModelInput;
+use crate::model::lifecycle::Lifecycle;
+use crate::model::output::ModelOutput;
+use crate::model::predict::{Predict, PredictOptions};
+use crate::model::streaming::{DEFAULT_MAX_NEW_TOKENS, MAX_NEW_TOKENS};
+use crate::{Error, Result};
+use llama_cpp_2::context::params::LlamaContextParams;
+use llama_cpp_2::context::LlamaContext;
+use llama_cpp_2::llama_backend::LlamaBackend;
+use llama_cpp_2::llama_batch::LlamaBatch;
+use llama_cpp_2::model::params::LlamaModelParams;
+use llama_cpp_2::model::{AddBos, LlamaModel, Special};
+use llama_cpp_2::token::data_array::LlamaTokenDataArray;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Name of the GGUF file in the model directory.
+pub const MODEL_FILE_NAME: &str = "model.gguf";
+
+/// Name of the optional generation config in the model directory.
+pub const CONFIG_FILE_NAME: &str = "gguf.json";
+
+/// Default string feature holding the prompt of each row.
+pub const DEFAULT_PROMPT_FEATURE: &str = "prompt";
+
+/// Key of the output holding the text generated for each prompt.
+pub const GENERATED_TEXT_OUTPUT_KEY: &str = "generated_text";
+
+/// Key of the output holding the number of tokens generated for each prompt.
+pub const GENERATED_TOKENS_OUTPUT_KEY: &str = "generated_tokens";
+
+/// Default number of tokens of the context, i.e. of the prompt and the generated text together.
+pub const DEFAULT_CONTEXT_SIZE: u32 = 2048;
+
+/// The llama.cpp backend, which can only be initialized once per process.
+static BACKEND: OnceLock<std::result::Result<LlamaBackend, String>> = OnceLock::new();
+
+/// Returns the llama.cpp backend, initializing it on first use.
+fn backend() -> anyhow::Result<&'static LlamaBackend> {
+    match BACKEND.get_or_init(|| LlamaBackend::init().map_err(|e| e.to_string())) {
+        Ok(backend) => Ok(backend),
+        Err(e) => anyhow::bail!("Failed to initialize the llama.cpp backend: {}", e),
+    }
+}
+
+/// How a GGUF model generates text, read from the optional `gguf.json` file of the model directory.
+///
+/// # Example
+/// ```json
+/// { "context_size": 4096, "max_tokens": 256, "prompt_feature": "prompt" }
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct GgufConfig {
+    /// The number of tokens of the context. Capped by the context the model was trained with. Defaults to 2048.
+    #[serde(default = "default_context_size")]
+    context_size: u32,
+    /// The maximum number of tokens generated for a prompt. Defaults to 64.
+    #[serde(default = "default_max_tokens")]
+    max_tokens: usize,
+    /// The string feature holding the prompt of each row. Defaults to `prompt`.
+    #[serde(default = "default_prompt_feature")]
+    prompt_feature: String,
+}
+
+fn default_context_size() -> u32 {
+    DEFAULT_CONTEXT_SIZE
+}
+
+fn default_max_tokens() -> usize {
+    DEFAULT_MAX_NEW_TOKENS
+}
+
+fn default_prompt_feature() -> String {
+    DEFAULT_PROMPT_FEATURE.to_string()
+}
+
+impl Default for GgufConfig {
+    fn default() -> Self {
+        GgufConfig {
+            context_size: default_context_size(),
+            max_tokens: default_max_tokens(),
+            prompt_feature: default_prompt_feature(),
+        }
+    }
+}
+
+impl GgufConfig {
+    /// Parses the contents of a `gguf.json` file.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the contents are not a valid config or the limits are out of range.
+    fn parse(contents: &str) -> anyhow::Result<Self> {
+        let config: GgufConfig = match serde_json::from_str(contents) {
+            Ok(config) => config,
+            Err(e) => anyhow::bail!("Failed to parse {} ❌: {}", CONFIG_FILE_NAME, e),
+        };
+        if config.max_tokens == 0 || config.max_tokens > MAX_NEW_TOKENS {
+            anyhow::bail!(
+                "max_tokens in {} must be between 1 and {} ❌",
+                CONFIG_FILE_NAME,
+                MAX_NEW_TOKENS
+            )
+        }
+        if config.context_size as usize <= config.max_tokens {
+            anyhow::bail!(
+                "context_size in {} must be bigger than max_tokens, so that the prompt fits ❌",
+                CONFIG_FILE_NAME
+            )
+        }
+        Ok(config)
+    }
+
+    /// Returns the prompt of each row of the input.
+    ///
+    /// The prompt is read from the prompt feature, or from the only string feature of the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the input has numerical features or no prompt feature.
+    fn prompts<'a>(&self, model_input: &'a ModelInput) -> anyhow::Result<Vec<&'a str>> {
+        if !model_input.float_features.names.is_empty()
+            || !model_input.integer_features.names.is_empty()
+        {
+            anyhow::bail!(
+                "GGUF models only take a prompt feature, tokenization happens on the server ❌"
+            )
+        }
+        let features = &model_input.string_features;
+        let index = match features
+            .names
+            .iter()
+            .position(|name| name.as_str() == self.prompt_feature)
+        {
+            Some(index) => index,
+            None if features.names.len() == 1 => 0,
+            None => anyhow::bail!(
+                "GGUF models expect the prompt of each row in the string feature {} ❌",
+                self.prompt_feature
+            ),
+        };
+        let num_rows = features.shape.1;
+        match features.values.as_strings() {
+            Some(values) if values.len() >= (index + 1) * num_rows => Ok(values
+                [index * num_rows..(index + 1) * num_rows]
+                .iter()
+                .map(String::as_str)
+                .collect()),
+            _ => anyhow::bail!("Failed to read the prompt feature of the input ❌"),
+        }
+    }
+}
+
+/// Struct representing a local LLM in the GGUF format, run with llama.cpp.
+///
+/// The model is loaded from a directory holding a `model.gguf` file and an optional `gguf.json` generation config.
+/// Each row of the input is a prompt, sent as raw text. The most likely token is generated until the end of
+/// sequence token, `max_tokens` or the end of the context, and the generated text of each row is returned as the
+/// `generated_text` text output, with the number of generated tokens as the `generated_tokens` prediction.
+///
+/// # Fields
+/// * `model` - The llama.cpp model, whose weights are shared by the contexts of all the predictions.
+/// * `config` - How the model generates text.
+pub struct Gguf {
+    model: LlamaModel,
+    config: GgufConfig,
+}
+
+impl Gguf {
+    /// Loads a GGUF model from the specified directory.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the model directory.
+    ///
+    /// # Returns
+    /// * `Ok(Gguf)` - If the model was successfully loaded.
+    /// * `Err(Error::Load)` - If there was an error during loading.
+    #[tracing::instrument]
+    pub fn load(path: &str) -> Result<Self> {
+        match load_directory(Path::new(path)) {
+            Ok(model) => Ok(model),
+            Err(e) => {
+                tracing::error!("Failed to load GGUF model from directory {}: {}", path, e);
+                Err(Error::Load(anyhow::anyhow!(
+                    "Failed to load GGUF model from directory {}: {}",
+                    path,
+                    e
+                )))
+            }
+        }
+    }
+
+    /// Generates the text following each prompt, returning the text and the number of generated tokens.
+    fn generate(&self, prompts: Vec<&str>) -> anyhow::Result<Vec<(String, usize)>> {
+        let backend = backend()?;
+        let context_size = self.context_size();
+        let params = LlamaContextParams::default()
+            .with_n_ctx(NonZeroU32::new(context_size))
+            .with_n_batch(context_size);
+        let mut context = self.model.new_context(backend, params)?;
+
+        let mut generated = Vec::with_capacity(prompts.len());
+        for prompt in prompts {
+            context.clear_kv_cache();
+            generated.push(self.generate_one(&mut context, prompt)?);
+        }
+        Ok(generated)
+    }
+
+    /// Generates the text following a prompt greedily.
+    fn generate_one(
+        &self,
+        context: &mut LlamaContext,
+        prompt: &str,
+    ) -> anyhow::Result<(String, usize)> {
+        let context_size = self.context_size() as usize;
+        let tokens = self.model.str_to_token(prompt, AddBos::Always)?;
+        if tokens.is_empty() || tokens.len() >= context_size {
+            anyhow::bail!(
+                "The prompt must have between 1 and {} tokens, got {}",
+                context_size - 1,
+                tokens.len()
+            )
+        }
+
+        let mut batch = LlamaBatch::new(context_size, 1);
+        let last = tokens.len() - 1;
+        for (position, token) in tokens.into_iter().enumerate() {
+            batch.add(token, position as i32, &[0], position == last)?;
+        }
+        context.decode(&mut batch)?;
+
+        let mut position = batch.n_tokens();
+        let mut bytes = Vec::new();
+        let mut num_tokens = 0;
+        // stop at the end of the context, as the following tokens would not fit
+        while num_tokens < self.config.max_tokens && (position as usize) < context_size {
+            let candidates =
+                LlamaTokenDataArray::from_iter(context.candidates_ith(batch.n_tokens() - 1), false);
+            let token = context.sample_token_greedy(candidates);
+            if token == self.model.token_eos() {
+                break;
+            }
+            bytes.extend(self.model.token_to_bytes(token, Special::Tokenize)?);
+            num_tokens += 1;
+
+            batch.clear();
+            batch.add(token, position, &[0], true)?;
+            context.decode(&mut batch)?;
+            position += 1;
+        }
+        // a multibyte character can be split across tokens, so the text is decoded once generated
+        Ok((String::from_utf8_lossy(&bytes).into_owned(), num_tokens))
+    }
+
+    /// Returns the number of tokens of the context, capped by the context the model was trained with.
+    fn context_size(&self) -> u32 {
+        match self.model.n_ctx_train() {
+            0 => self.config.context_size,
+            trained => self.config.context_size.min(trained),
+        }
+    }
+}
+
+/// Reads the GGUF file and the optional generation config of a model directory.
+fn load_directory(dir: &Path) -> anyhow::Result<Gguf> {
+    let config = match std::fs::read_to_string(dir.join(CONFIG_FILE_NAME)) {
+        Ok(contents) => GgufConfig::parse(&contents)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => GgufConfig::default(),
+        Err(e) => anyhow::bail!("Failed to read {}: {}", CONFIG_FILE_NAME, e),
+    };
+    let model = LlamaModel::load_from_file(
+        backend()?,
+        dir.join(MODEL_FILE_NAME),
+        &LlamaModelParams::default(),
+    )?;
+    Ok(Gguf { model, config })
+}
+
+impl Lifecycle for Gguf {}
+
+impl Predict for Gguf {
+    /// Generates the text following the prompt of each row.
+    ///
+    /// # Arguments
+    /// * `input` - The input data for the model, with a single prompt feature.
+    ///
+    /// # Returns
+    /// * `Ok(Output)` - The generated text of each row.
+    /// * `Err(Error::Input)` - If the input has no prompt feature.
+    /// * `Err(Error::Predict)` - If there was an error during generation.
+    #[tracing::instrument(skip(self, input))]
+    fn predict(&self, input: ModelInput) -> Result<ModelOutput> {
+        self.predict_with_options(input, &PredictOptions::default())
+    }
+
+    /// Generates the text following the prompt of each row with the given prediction options.
+    ///
+    /// Neither `mode` nor `num_iteration` is supported.
+    ///
+    /// # Errors
+    /// * `Err(Error::Input)` - If the options are not supported or the input has no prompt feature.
+    /// * `Err(Error::Predict)` - If there was an error during generation.
+    #[tracing::instrument(skip(self, input))]
+    fn predict_with_options(
+        &self,
+        input: ModelInput,
+        options: &PredictOptions,
+    ) -> Result<ModelOutput> {
+        options.validate().map_err(Error::Input)?;
+        if options.mode.is_some() || options.num_iteration.is_some() {
+            return Err(Error::Input(anyhow::anyhow!(
+                "mode and num_iteration are not supported by GGUF models ❌"
+            )));
+        }
+        let prompts = self.config.prompts(&input).map_err(Error::Input)?;
+
+        match self.generate(prompts) {
+            Ok(generated) => {
+                let (texts, num_tokens): (Vec<String>, Vec<Vec<f64>>) = generated
+                    .into_iter()
+                    .map(|(text, num_tokens)| (text, vec![num_tokens as f64]))
+                    .unzip();
+                Ok(ModelOutput {
+                    predictions: HashMap::from([(
+                        GENERATED_TOKENS_OUTPUT_KEY.to_string(),
+                        num_tokens,
+                    )]),
+                    labels: HashMap::new(),
+                    shapes: HashMap::new(),
+                    texts: HashMap::from([(GENERATED_TEXT_OUTPUT_KEY.to_string(), texts)]),
+                })
+            }
+            Err(e) => {
+                tracing::error!("Failed to generate text using GGUF model: {}", e);
+                Err(Error::Predict(anyhow::anyhow!(
+                    "Failed to generate text using GGUF model: {}",
+                    e
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_utils;
+
+    #[test]
+    fn fails_to_load_gguf_model() {
+        let model_dir = "incorrect/path";
+        let model = Gguf::load(model_dir);
+
+        // assert the result is Err
+        assert!(model.is_err())
+    }
+
+    #[test]
+    fn successfully_load_and_generate_using_gguf_model() {
+        // every token of the tiny model embeds to the same vector and its output projection only favours the token
+        // ` ok`, so it generates ` ok` until `max_tokens` in its gguf.json
+        let model_dir = "tests/model_storage/models/gguf-my_awesome_tiny_llama";
+        let model = Gguf::load(model_dir).unwrap();
+        let input = ModelInput::from_str(r#"{"prompt": ["hello world", "hello"]}"#).unwrap();
+
+        let output = model.predict(input).unwrap();
+
+        // assert
+        assert_eq!(
+            output.texts[GENERATED_TEXT_OUTPUT_KEY],
+            vec![" ok ok ok ok", " ok ok ok ok"]
+        );
+        assert_eq!(
+            output.predictions[GENERATED_TOKENS_OUTPUT_KEY],
+            vec![vec![4.0], vec![4.0]]
+        );
+    }
+
+    #[test]
+    fn successfully_parse_gguf_config() {
+        let contents = r#"{"context_size": 4096, "max_tokens": 256}"#;
+
+        let config = GgufConfig::parse(contents).unwrap();
+
+        // assert the prompt feature falls back to its default
+        assert_eq!(
+            config,
+            GgufConfig {
+                context_size: 4096,
+                max_tokens: 256,
+                prompt_feature: DEFAULT_PROMPT_FEATURE.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn fails_to_parse_gguf_config_when_limits_are_out_of_range() {
+        let no_tokens = r#"{"max_tokens": 0}"#;
+        let too_many_tokens = r#"{"max_tokens": 100000, "context_size": 200000}"#;
+        let prompt_does_not_fit = r#"{"context_size": 128, "max_tokens": 128}"#;
+        let unknown_field = r#"{"temperature": 0.7}"#;
+
+        // assert
+        assert!(GgufConfig::parse(no_tokens).is_err());
+        assert!(GgufConfig::parse(too_many_tokens).is_err());
+        assert!(GgufConfig::parse(prompt_does_not_fit).is_err());
+        assert!(GgufConfig::parse(unknown_field).is_err());
+    }
+
+    #[test]
+    fn successfully_parse_prompts_from_the_only_string_feature() {
+        let model_inputs = test_utils::utils::create_model_inputs_with_names(
+            vec![],
+            vec!["question".to_string()],
+            3,
+        );
+
+        let prompts = GgufConfig::default().prompts(&model_inputs).unwrap();
+
+        // assert
+        assert_eq!(prompts.len(), 3);
+    }
+
+    #[test]
+    fn fails_to_parse_prompts_when_input_is_not_text() {
+        let config = GgufConfig::default();
+        let numerical = test_utils::utils::create_model_inputs(2, 0, 3);
+        let ambiguous = test_utils::utils::create_model_inputs_with_names(
+            vec![],
+            vec!["system".to_string(), "user".to_string()],
+            3,
+        );
+
+        // assert
+        assert!(config.prompts(&numerical).is_err());
+        assert!(config.prompts(&ambiguous).is_err());
+    }
+}
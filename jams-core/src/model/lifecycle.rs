@@ -0,0 +1,123 @@
+// the `Lifecycle` trait is defined in jams-predictor-api, so that predictors built outside of jams-core have the
+// same hooks
+use chrono::Utc;
+pub use jams_predictor_api::lifecycle::{
+    Lifecycle, DEFAULT_INIT_TIMEOUT, DEFAULT_TEARDOWN_TIMEOUT,
+};
+use serde::Serialize;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// How a lifecycle hook ended.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookState {
+    Succeeded,
+    Failed,
+    TimedOut,
+}
+
+impl HookState {
+    /// Returns the name of the state, as serialized in the metadata.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookState::Succeeded => "succeeded",
+            HookState::Failed => "failed",
+            HookState::TimedOut => "timed_out",
+        }
+    }
+}
+
+/// The status of a lifecycle hook, surfaced in the metadata of the model.
+///
+/// # Example
+/// ```json
+/// { "state": "succeeded", "duration_ms": 120, "finished_at": "2024-07-01T10:00:00+00:00" }
+/// ```
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HookStatus {
+    /// How the hook ended.
+    pub state: HookState,
+    /// The time the hook took, up to its timeout.
+    pub duration_ms: u64,
+    /// The error of the hook if it failed or timed out.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The timestamp of when the hook ended.
+    pub finished_at: String,
+}
+
+impl HookStatus {
+    /// Returns true if the hook succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.state == HookState::Succeeded
+    }
+}
+
+/// Runs a lifecycle hook, giving up once `timeout` has elapsed.
+///
+/// # Arguments
+/// * `hook` - The hook, e.g. `predictor.init(&settings)`.
+/// * `timeout` - The time allowed for the hook.
+///
+/// # Returns
+/// The status of the hook.
+pub async fn run_hook<F>(hook: F, timeout: Duration) -> HookStatus
+where
+    F: Future<Output = anyhow::Result<()>>,
+{
+    let started = Instant::now();
+    let (state, error) = match tokio::time::timeout(timeout, hook).await {
+        Ok(Ok(())) => (HookState::Succeeded, None),
+        Ok(Err(e)) => (HookState::Failed, Some(e.to_string())),
+        Err(_) => (
+            HookState::TimedOut,
+            Some(format!("Timed out after {}ms", timeout.as_millis())),
+        ),
+    };
+    HookStatus {
+        state,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error,
+        finished_at: Utc::now().to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn successfully_run_hook() {
+        // Act
+        let status = run_hook(async { Ok(()) }, Duration::from_secs(1)).await;
+
+        // Assert
+        assert!(status.is_ok());
+        assert!(status.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn fails_to_run_hook_when_it_errors_or_times_out() {
+        // Act
+        let failed = run_hook(
+            async { anyhow::bail!("tokenizer is missing") },
+            Duration::from_secs(1),
+        )
+        .await;
+        let timed_out = run_hook(
+            async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            },
+            Duration::from_millis(10),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(failed.state, HookState::Failed);
+        assert_eq!(failed.error.unwrap(), "tokenizer is missing");
+        assert_eq!(timed_out.state, HookState::TimedOut);
+        assert!(timed_out.error.is_some());
+    }
+}
@@ -1,11 +1,15 @@
+use crate::determinism::determinism;
 use crate::model::input::{ModelInput, Values};
-use crate::model::output::{ModelOutput, DEFAULT_OUTPUT_KEY};
-use crate::model::predict::Predict;
+use crate::model::lifecycle::Lifecycle;
+use crate::model::output::{
+    split_into_rows, ModelOutput, DEFAULT_OUTPUT_KEY, LEAF_INDEX_OUTPUT_KEY,
+};
+use crate::model::predict::{Predict, PredictMode, PredictOptions};
+use crate::{Error, Result};
 use lgbm;
 use lgbm::mat::MatLayouts;
 use lgbm::mat::MatLayouts::ColMajor;
-use lgbm::PredictType::RawScore;
-use lgbm::{MatBuf, Parameters};
+use lgbm::{MatBuf, Parameters, PredictType};
 use std::collections::HashMap;
 
 /// Struct representing the input data format for a LightGBM model.
@@ -15,6 +19,8 @@ use std::collections::HashMap;
 struct LightGBMModelInput {
     /// Matrix buffer containing numerical features in column-major order.
     pub matbuf: MatBuf<f32, MatLayouts>,
+    /// The number of rows in the input.
+    pub num_rows: usize,
 }
 
 impl LightGBMModelInput {
@@ -41,11 +47,10 @@ impl LightGBMModelInput {
         }
 
         // only float features are supported, so we are converting Vec<i32> to Vec<f32>
-        // we can use .1 of any features as the length is same.
-        // only the number of features is changing
+        // all the features have the same number of rows, only the number of features is changing
         let numerical_features_shape = (
             model_input.integer_features.shape.0 + model_input.float_features.shape.0,
-            model_input.float_features.shape.1,
+            model_input.num_rows(),
         );
 
         // convert integer to float
@@ -72,7 +77,10 @@ impl LightGBMModelInput {
             ColMajor,
         );
 
-        Ok(Self { matbuf })
+        Ok(Self {
+            matbuf,
+            num_rows: numerical_features_shape.1,
+        })
     }
 }
 
@@ -82,6 +90,9 @@ impl LightGBMModelInput {
 pub struct LightGBM {
     /// The LightGBM model booster.
     pub booster: lgbm::Booster,
+    /// The number of boosting iterations saved in the model file. This is the best iteration for
+    /// models saved after training with early stopping.
+    num_iterations: i32,
 }
 
 impl LightGBM {
@@ -95,18 +106,32 @@ impl LightGBM {
     ///
     /// Returns an `Err` if there is an issue loading the LightGBM model from the file.
     #[tracing::instrument]
-    pub fn load(path: &str) -> anyhow::Result<Self> {
+    pub fn load(path: &str) -> Result<Self> {
         let model = match lgbm::Booster::from_file(path.as_ref()) {
             Ok(model) => model,
             Err(e) => {
                 tracing::error!("Failed to load LightGBM model from file {}: {}", path, e);
-                anyhow::bail!("Failed to load LightGBM model from file {}: {}", path, e);
+                return Err(Error::Load(anyhow::anyhow!(
+                    "Failed to load LightGBM model from file {}: {}",
+                    path,
+                    e
+                )));
             }
         };
-        Ok(LightGBM { booster: model.0 })
+        Ok(LightGBM {
+            booster: model.0,
+            num_iterations: model.1,
+        })
+    }
+
+    /// Returns the number of boosting iterations saved in the model file.
+    pub fn num_iterations(&self) -> i32 {
+        self.num_iterations
     }
 }
 
+impl Lifecycle for LightGBM {}
+
 impl Predict for LightGBM {
     /// Performs prediction using the loaded LightGBM model.
     ///
@@ -118,22 +143,72 @@ impl Predict for LightGBM {
     ///
     /// Returns an `Err` if there is an issue parsing the input or performing the prediction.
     #[tracing::instrument(skip(self, input))]
-    fn predict(&self, input: ModelInput) -> anyhow::Result<ModelOutput> {
-        let input = LightGBMModelInput::parse(input)?;
-        let p = Parameters::new();
+    fn predict(&self, input: ModelInput) -> Result<ModelOutput> {
+        self.predict_with_options(input, &PredictOptions::default())
+    }
+
+    /// Performs prediction using the loaded LightGBM model and the given prediction options.
+    ///
+    /// The model returns raw scores unless another `mode` is set. In `leaf_index` mode the index of the
+    /// leaf each row falls into in every tree is returned as the `leaf_indices` output.
+    /// `num_iteration` limits the number of boosting iterations used for the prediction.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The `ModelInput` containing the input values for prediction.
+    /// * `options` - The prediction options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if there is an issue parsing the input or performing the prediction.
+    #[tracing::instrument(skip(self, input))]
+    fn predict_with_options(
+        &self,
+        input: ModelInput,
+        options: &PredictOptions,
+    ) -> Result<ModelOutput> {
+        options.validate().map_err(Error::Input)?;
+        let input = LightGBMModelInput::parse(input).map_err(Error::Input)?;
+        let (predict_type, output_key) = match options.mode.unwrap_or(PredictMode::RawScore) {
+            PredictMode::Normal => (PredictType::Normal, DEFAULT_OUTPUT_KEY),
+            PredictMode::RawScore => (PredictType::RawScore, DEFAULT_OUTPUT_KEY),
+            PredictMode::LeafIndex => (PredictType::LeafIndex, LEAF_INDEX_OUTPUT_KEY),
+        };
+        let num_iteration = match options.num_iteration {
+            Some(num_iteration) => {
+                Some(i32::try_from(num_iteration).map_err(|e| Error::Input(e.into()))?)
+            }
+            None => None,
+        };
+        let mut p = Parameters::new();
+        if determinism().is_some() {
+            p.push("num_threads", 1);
+            p.push("deterministic", true);
+        }
         let preds = self
             .booster
-            .predict_for_mat(input.matbuf, RawScore, 0, None, &p);
+            .predict_for_mat(input.matbuf, predict_type, 0, num_iteration, &p);
         match preds {
             Ok(preds) => {
                 let mut predictions: HashMap<String, Vec<Vec<f64>>> = HashMap::new();
-                let values: Vec<Vec<f64>> = preds.values().iter().map(|v| vec![*v]).collect();
-                predictions.insert(DEFAULT_OUTPUT_KEY.to_string(), values);
-                Ok(ModelOutput { predictions })
+                // multiclass models return one value per class for each row and
+                // leaf indices are returned for every tree of each row
+                let values =
+                    split_into_rows(preds.values(), input.num_rows).map_err(Error::Predict)?;
+                predictions.insert(output_key.to_string(), values);
+                Ok(ModelOutput {
+                    predictions,
+                    labels: HashMap::new(),
+                    shapes: HashMap::new(),
+                    texts: HashMap::new(),
+                })
             }
             Err(e) => {
                 tracing::error!("Failed to make predictions using LightGBM: {}", e);
-                anyhow::bail!("Failed to make predictions using LightGBM: {}", e)
+                Err(Error::Predict(anyhow::anyhow!(
+                    "Failed to make predictions using LightGBM: {}",
+                    e
+                )))
             }
         }
     }
@@ -153,6 +228,77 @@ mod tests {
         assert!(model.is_err())
     }
 
+    #[test]
+    fn fails_to_make_prediction_using_lightgbm_model_when_options_are_invalid() {
+        let path = "tests/model_storage/models/lightgbm-my_awesome_reg_model.txt";
+        let model = LightGBM::load(path).unwrap();
+        let model_inputs = test_utils::utils::create_model_inputs(28, 0, 1);
+        let options = PredictOptions {
+            mode: None,
+            num_iteration: Some(0),
+            precision: None,
+            unknown_features: None,
+            max_batch_size: None,
+            parallel_batches: None,
+            variant: None,
+            priority: None,
+            latency_budget_ms: None,
+            model_version: None,
+        };
+
+        // make predictions
+        let output = model.predict_with_options(model_inputs, &options);
+
+        // assert the error is reported as an input error
+        assert!(matches!(output, Err(Error::Input(_))))
+    }
+
+    #[test]
+    fn successfully_make_leaf_index_prediction_using_lightgbm_regressor_model() {
+        let path = "tests/model_storage/models/lightgbm-my_awesome_reg_model.txt";
+        let model = LightGBM::load(path).unwrap();
+
+        let size = 3;
+        let model_inputs = test_utils::utils::create_model_inputs(28, 0, size);
+        let options = PredictOptions {
+            mode: Some(PredictMode::LeafIndex),
+            num_iteration: Some(1),
+            precision: None,
+            unknown_features: None,
+            max_batch_size: None,
+            parallel_batches: None,
+            variant: None,
+            priority: None,
+            latency_budget_ms: None,
+            model_version: None,
+        };
+
+        // make predictions
+        let output = model.predict_with_options(model_inputs, &options);
+
+        // assert
+        assert!(output.is_ok());
+        let predictions = output.unwrap().predictions;
+        assert!(!predictions.contains_key(DEFAULT_OUTPUT_KEY));
+        // one leaf index per row for the single iteration
+        let leaf_indices = predictions.get(LEAF_INDEX_OUTPUT_KEY).unwrap();
+        assert_eq!(leaf_indices.len(), size);
+        for row in leaf_indices {
+            assert_eq!(row.len(), 1)
+        }
+    }
+
+    #[test]
+    fn successfully_match_golden_outputs_using_lightgbm_models() {
+        for name in [
+            "lightgbm-my_awesome_reg_model",
+            "lightgbm-my_awesome_binary_model_2",
+            "lightgbm-my_awesome_binary_model_2-normal",
+        ] {
+            test_utils::golden::check_golden(name, LightGBM::load);
+        }
+    }
+
     #[test]
     fn successfully_load_lightgbm_regressor_model() {
         let path = "tests/model_storage/models/lightgbm-my_awesome_reg_model.txt";
@@ -419,4 +565,34 @@ mod tests {
         // its own threshold.
         assert_eq!(predictions.first().unwrap().len(), 1);
     }
+
+    #[test]
+    fn successfully_return_one_prediction_per_row_using_lightgbm_regressor_model() {
+        let path = "tests/model_storage/models/lightgbm-my_awesome_reg_model.txt";
+        let model = LightGBM::load(path).unwrap();
+
+        // asserts there is one prediction per input row for any valid input
+        let result = test_utils::utils::check_prediction_shapes(
+            &model,
+            test_utils::utils::model_input_strategy(28, 0, true),
+        );
+
+        // assert the shape invariants hold for every generated input
+        assert!(result.is_ok(), "{:?}", result)
+    }
+
+    #[test]
+    fn successfully_return_one_prediction_per_row_using_lightgbm_binary_classifier_model() {
+        let path = "tests/model_storage/models/lightgbm-my_awesome_binary_model_2.txt";
+        let model = LightGBM::load(path).unwrap();
+
+        // asserts there is one prediction per input row for any valid input
+        let result = test_utils::utils::check_prediction_shapes(
+            &model,
+            test_utils::utils::model_input_strategy(2, 0, true),
+        );
+
+        // assert the shape invariants hold for every generated input
+        assert!(result.is_ok(), "{:?}", result)
+    }
 }
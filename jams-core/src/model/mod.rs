@@ -1,11 +1,24 @@
 use crate::model::catboost::Catboost;
+use crate::model::custom::CustomPredict;
+#[cfg(feature = "gguf")]
+use crate::model::gguf::Gguf;
+use crate::model::lifecycle::Lifecycle;
 use crate::model::lightgbm::LightGBM;
+#[cfg(feature = "openvino")]
+use crate::model::openvino::OpenVino;
 use crate::model::predict::Predict;
+use crate::model::settings::ModelSettings;
+use crate::model::streaming::StreamingPredict;
 use crate::model::tensorflow::Tensorflow;
+#[cfg(feature = "tensorrt")]
+use crate::model::tensorrt::TensorRT;
+#[cfg(feature = "tflite")]
+use crate::model::tflite::TfLite;
 use crate::model::torch::Torch;
-
-#[cfg(feature = "xgboost")]
+#[cfg(feature = "transformers")]
+use crate::model::transformers::Transformers;
 use crate::model::xgboost::XGBoost;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 #[cfg(feature = "catboost")]
 pub mod catboost;
@@ -19,15 +32,53 @@ pub mod tensorflow;
 #[cfg(feature = "torch")]
 pub mod torch;
 
+// TensorRT needs the CUDA toolkit and the TensorRT SDK at build time, so it is not a default feature
+#[cfg(feature = "tensorrt")]
+pub mod tensorrt;
+
+// Transformers models need the tokenizers of Hugging Face, so they are not a default feature
+#[cfg(feature = "transformers")]
+pub mod transformers;
+
+// GGUF models need llama.cpp, which is built from source, so they are not a default feature
+#[cfg(feature = "gguf")]
+pub mod gguf;
+
+// TensorFlow Lite is built from source, so it is not a default feature
+#[cfg(feature = "tflite")]
+pub mod tflite;
+
+// OpenVINO needs the OpenVINO runtime at run time, so it is not a default feature
+#[cfg(feature = "openvino")]
+pub mod openvino;
+
 #[cfg(feature = "xgboost")]
-mod xgboost;
+pub mod xgboost;
 
 // Always included modules
+pub mod bundle;
+pub mod calibration;
+pub mod comparison;
+pub mod custom;
+pub mod dense;
+pub mod embedding;
+pub mod expression;
 pub mod frameworks;
 pub mod input;
+pub mod lifecycle;
 pub mod output;
 pub mod predict;
-mod test_utils;
+pub mod requirements;
+pub mod sample;
+pub mod sessions;
+pub mod settings;
+pub mod stats;
+pub mod streaming;
+#[cfg(any(test, feature = "bench", feature = "test-utils"))]
+pub mod test_utils;
+pub mod validation;
+pub mod variant;
+pub mod versions;
 
 /// Enum representing different types of machine learning models.
 pub enum Predictor {
@@ -43,15 +94,46 @@ pub enum Predictor {
     /// Torch model predictor.
     Torch(Torch),
 
-    #[cfg(feature = "xgboost")]
     /// XGBoost model predictor.
     XGBoost(XGBoost),
+
+    /// TensorRT engine predictor, if the `tensorrt` feature is enabled.
+    #[cfg(feature = "tensorrt")]
+    TensorRT(TensorRT),
+
+    /// Hugging Face transformers text model predictor, if the `transformers` feature is enabled.
+    #[cfg(feature = "transformers")]
+    Transformers(Transformers),
+
+    /// GGUF model predictor run with llama.cpp, if the `gguf` feature is enabled.
+    #[cfg(feature = "gguf")]
+    Gguf(Gguf),
+
+    /// TensorFlow Lite model predictor, if the `tflite` feature is enabled.
+    #[cfg(feature = "tflite")]
+    TfLite(TfLite),
+    /// OpenVINO model predictor, if the `openvino` feature is enabled.
+    #[cfg(feature = "openvino")]
+    OpenVino(OpenVino),
+
+    /// Predictor of a framework registered with `custom::register`.
+    Custom(Box<dyn CustomPredict>),
 }
 impl Predictor {
+    /// Returns the predictor as a `StreamingPredict` if it can stream its outputs, i.e. Torch models shipped
+    /// with a `generation.json` file.
+    pub fn streaming(&self) -> Option<&dyn StreamingPredict> {
+        match self {
+            Predictor::Torch(predictor) if predictor.is_streaming() => Some(predictor),
+            _ => None,
+        }
+    }
+
     /// Make a prediction using the appropriate machine learning model.
     ///
     /// This function will call the `predict` method of the specific model contained
-    /// within the `Predictor` enum (Catboost, LightGBM, TensorFlow, Torch, or XGBoost).
+    /// within the `Predictor` enum (Catboost, LightGBM, TensorFlow, Torch, XGBoost, TensorRT, Transformers,
+    /// GGUF, TensorFlow Lite, OpenVINO or a custom framework).
     ///
     /// # Arguments
     ///
@@ -69,14 +151,191 @@ impl Predictor {
     /// prediction, which may be due to issues in the underlying model, data
     /// compatibility, or other errors.
     ///
-    pub fn predict(&self, input: input::ModelInput) -> anyhow::Result<output::ModelOutput> {
+    pub fn predict(&self, input: input::ModelInput) -> crate::Result<output::ModelOutput> {
         match self {
             Predictor::Catboost(predictor) => predictor.predict(input),
             Predictor::LightGBM(predictor) => predictor.predict(input),
             Predictor::Tensorflow(predictor) => predictor.predict(input),
             Predictor::Torch(predictor) => predictor.predict(input),
-            #[cfg(feature = "xgboost")]
             Predictor::XGBoost(predictor) => predictor.predict(input),
+            #[cfg(feature = "tensorrt")]
+            Predictor::TensorRT(predictor) => predictor.predict(input),
+            #[cfg(feature = "transformers")]
+            Predictor::Transformers(predictor) => predictor.predict(input),
+            #[cfg(feature = "gguf")]
+            Predictor::Gguf(predictor) => predictor.predict(input),
+            #[cfg(feature = "tflite")]
+            Predictor::TfLite(predictor) => predictor.predict(input),
+            #[cfg(feature = "openvino")]
+            Predictor::OpenVino(predictor) => predictor.predict(input),
+            Predictor::Custom(predictor) => predictor.predict(input),
+        }
+    }
+
+    /// Make a prediction using the appropriate machine learning model and prediction options.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data for the model prediction.
+    /// * `options` - The prediction options, e.g. the LightGBM prediction mode.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the model does not support the options or fails
+    /// to perform the prediction.
+    ///
+    pub fn predict_with_options(
+        &self,
+        input: input::ModelInput,
+        options: &predict::PredictOptions,
+    ) -> crate::Result<output::ModelOutput> {
+        match self {
+            Predictor::Catboost(predictor) => predictor.predict_with_options(input, options),
+            Predictor::LightGBM(predictor) => predictor.predict_with_options(input, options),
+            Predictor::Tensorflow(predictor) => predictor.predict_with_options(input, options),
+            Predictor::Torch(predictor) => predictor.predict_with_options(input, options),
+            Predictor::XGBoost(predictor) => predictor.predict_with_options(input, options),
+            #[cfg(feature = "tensorrt")]
+            Predictor::TensorRT(predictor) => predictor.predict_with_options(input, options),
+            #[cfg(feature = "transformers")]
+            Predictor::Transformers(predictor) => predictor.predict_with_options(input, options),
+            #[cfg(feature = "gguf")]
+            Predictor::Gguf(predictor) => predictor.predict_with_options(input, options),
+            #[cfg(feature = "tflite")]
+            Predictor::TfLite(predictor) => predictor.predict_with_options(input, options),
+            #[cfg(feature = "openvino")]
+            Predictor::OpenVino(predictor) => predictor.predict_with_options(input, options),
+            Predictor::Custom(predictor) => predictor.predict_with_options(input, options),
+        }
+    }
+
+    /// Make a prediction in batches of at most `max_batch_size` rows, so that big inputs do not exceed
+    /// the limits of the framework, e.g. the memory used by a TensorFlow graph for a single batch.
+    ///
+    /// The predictions of the batches are stitched back together in the order of the rows.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data for the model prediction.
+    /// * `options` - The prediction options applied by the model.
+    /// * `max_batch_size` - The maximum number of rows of each batch.
+    /// * `parallel` - Whether to predict the batches in parallel, on the rayon pool the prediction runs on, or the
+    ///   global rayon pool otherwise, so that the number of threads does not grow with the number of batches.
+    ///
+    /// # Errors
+    ///
+    /// This method will return the first error of the batches.
+    ///
+    pub fn predict_in_batches(
+        &self,
+        input: input::ModelInput,
+        options: &predict::PredictOptions,
+        max_batch_size: usize,
+        parallel: bool,
+    ) -> crate::Result<output::ModelOutput> {
+        let batches = input.split_rows(max_batch_size);
+        if batches.len() == 1 {
+            let input = batches.into_iter().next().unwrap_or_default();
+            return self.predict_with_options(input, options);
+        }
+
+        let outputs: crate::Result<Vec<output::ModelOutput>> = match parallel {
+            true => batches
+                .into_par_iter()
+                .map(|batch| self.predict_with_options(batch, options))
+                .collect(),
+            false => batches
+                .into_iter()
+                .map(|batch| self.predict_with_options(batch, options))
+                .collect(),
+        };
+        Ok(output::ModelOutput::concat(outputs?))
+    }
+}
+
+#[async_trait::async_trait]
+impl Lifecycle for Predictor {
+    async fn init(&self, settings: &ModelSettings) -> anyhow::Result<()> {
+        match self {
+            Predictor::Catboost(predictor) => predictor.init(settings).await,
+            Predictor::LightGBM(predictor) => predictor.init(settings).await,
+            Predictor::Tensorflow(predictor) => predictor.init(settings).await,
+            Predictor::Torch(predictor) => predictor.init(settings).await,
+            Predictor::XGBoost(predictor) => predictor.init(settings).await,
+            #[cfg(feature = "tensorrt")]
+            Predictor::TensorRT(predictor) => predictor.init(settings).await,
+            #[cfg(feature = "transformers")]
+            Predictor::Transformers(predictor) => predictor.init(settings).await,
+            #[cfg(feature = "gguf")]
+            Predictor::Gguf(predictor) => predictor.init(settings).await,
+            #[cfg(feature = "tflite")]
+            Predictor::TfLite(predictor) => predictor.init(settings).await,
+            #[cfg(feature = "openvino")]
+            Predictor::OpenVino(predictor) => predictor.init(settings).await,
+            Predictor::Custom(predictor) => predictor.init(settings).await,
+        }
+    }
+
+    async fn teardown(&self) -> anyhow::Result<()> {
+        match self {
+            Predictor::Catboost(predictor) => predictor.teardown().await,
+            Predictor::LightGBM(predictor) => predictor.teardown().await,
+            Predictor::Tensorflow(predictor) => predictor.teardown().await,
+            Predictor::Torch(predictor) => predictor.teardown().await,
+            Predictor::XGBoost(predictor) => predictor.teardown().await,
+            #[cfg(feature = "tensorrt")]
+            Predictor::TensorRT(predictor) => predictor.teardown().await,
+            #[cfg(feature = "transformers")]
+            Predictor::Transformers(predictor) => predictor.teardown().await,
+            #[cfg(feature = "gguf")]
+            Predictor::Gguf(predictor) => predictor.teardown().await,
+            #[cfg(feature = "tflite")]
+            Predictor::TfLite(predictor) => predictor.teardown().await,
+            #[cfg(feature = "openvino")]
+            Predictor::OpenVino(predictor) => predictor.teardown().await,
+            Predictor::Custom(predictor) => predictor.teardown().await,
+        }
+    }
+
+    fn init_timeout(&self) -> std::time::Duration {
+        match self {
+            Predictor::Catboost(predictor) => predictor.init_timeout(),
+            Predictor::LightGBM(predictor) => predictor.init_timeout(),
+            Predictor::Tensorflow(predictor) => predictor.init_timeout(),
+            Predictor::Torch(predictor) => predictor.init_timeout(),
+            Predictor::XGBoost(predictor) => predictor.init_timeout(),
+            #[cfg(feature = "tensorrt")]
+            Predictor::TensorRT(predictor) => predictor.init_timeout(),
+            #[cfg(feature = "transformers")]
+            Predictor::Transformers(predictor) => predictor.init_timeout(),
+            #[cfg(feature = "gguf")]
+            Predictor::Gguf(predictor) => predictor.init_timeout(),
+            #[cfg(feature = "tflite")]
+            Predictor::TfLite(predictor) => predictor.init_timeout(),
+            #[cfg(feature = "openvino")]
+            Predictor::OpenVino(predictor) => predictor.init_timeout(),
+            Predictor::Custom(predictor) => predictor.init_timeout(),
+        }
+    }
+
+    fn teardown_timeout(&self) -> std::time::Duration {
+        match self {
+            Predictor::Catboost(predictor) => predictor.teardown_timeout(),
+            Predictor::LightGBM(predictor) => predictor.teardown_timeout(),
+            Predictor::Tensorflow(predictor) => predictor.teardown_timeout(),
+            Predictor::Torch(predictor) => predictor.teardown_timeout(),
+            Predictor::XGBoost(predictor) => predictor.teardown_timeout(),
+            #[cfg(feature = "tensorrt")]
+            Predictor::TensorRT(predictor) => predictor.teardown_timeout(),
+            #[cfg(feature = "transformers")]
+            Predictor::Transformers(predictor) => predictor.teardown_timeout(),
+            #[cfg(feature = "gguf")]
+            Predictor::Gguf(predictor) => predictor.teardown_timeout(),
+            #[cfg(feature = "tflite")]
+            Predictor::TfLite(predictor) => predictor.teardown_timeout(),
+            #[cfg(feature = "openvino")]
+            Predictor::OpenVino(predictor) => predictor.teardown_timeout(),
+            Predictor::Custom(predictor) => predictor.teardown_timeout(),
         }
     }
 }
@@ -0,0 +1,285 @@
+use crate::model::dense::{self, DenseInput, DenseTensor};
+use crate::model::input::ModelInput;
+use crate::model::lifecycle::Lifecycle;
+use crate::model::output::ModelOutput;
+use crate::model::predict::{Predict, PredictOptions};
+use crate::model::sessions::SessionPool;
+use crate::{Error, Result};
+use openvino::{CompiledModel, Core, DeviceType, InferRequest};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Name of the topology of the model in the model directory, in the OpenVINO IR format.
+pub const MODEL_FILE_NAME: &str = "model.xml";
+
+/// Name of the weights of the model in the model directory, in the OpenVINO IR format.
+pub const WEIGHTS_FILE_NAME: &str = "model.bin";
+
+/// Name of the framework in error messages.
+const FRAMEWORK_NAME: &str = "OpenVINO";
+
+/// The model compiled for the CPU, from which an infer request is created for each worker predicting with it.
+struct Compiled(CompiledModel);
+
+// SAFETY: the compiled model is only used to create infer requests, one thread at a time, which the mutex of the
+// predictor guarantees.
+unsafe impl Send for Compiled {}
+
+/// An infer request of the compiled model, which runs one prediction at a time.
+struct Request(InferRequest);
+
+// SAFETY: an infer request can be used from any thread as long as it is used by one thread at a time, which the
+// session pool of the predictor guarantees.
+unsafe impl Send for Request {}
+
+/// Struct representing a predictor using an OpenVINO model on Intel CPUs.
+///
+/// The model is loaded from a directory holding its topology (`model.xml`) and weights (`model.bin`) in the
+/// OpenVINO intermediate representation (IR), e.g. converted with `ovc`, and compiled for the CPU. It must have
+/// static shapes, a single float input tensor and float output tensors, all with the same batch size as their first
+/// dimension. Inputs are split into batches of that size, padding the last one.
+///
+/// An infer request runs one prediction at a time, so each worker predicting with the model gets an infer request
+/// of its own from a pool, created from the compiled model.
+pub struct OpenVino {
+    // the infer requests are created from the compiled model, so they are dropped before it
+    requests: SessionPool<Request>,
+    compiled_model: Mutex<Compiled>,
+    batch_size: usize,
+    input: DenseTensor,
+    outputs: Vec<DenseTensor>,
+}
+
+impl OpenVino {
+    /// Loads an OpenVINO model from the specified directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A string slice that holds the path to the model directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the model cannot be read or compiled, or if its tensors are not supported.
+    #[tracing::instrument]
+    pub fn load(path: &str) -> Result<Self> {
+        match load_directory(Path::new(path)) {
+            Ok(predictor) => Ok(predictor),
+            Err(e) => {
+                tracing::error!(
+                    "Failed to load OpenVINO model from directory {}: {}",
+                    path,
+                    e
+                );
+                Err(Error::Load(anyhow::anyhow!(
+                    "Failed to load OpenVINO model from directory {}: {}",
+                    path,
+                    e
+                )))
+            }
+        }
+    }
+
+    /// Creates another infer request of the compiled model, for a worker which found no idle one.
+    fn create_request(&self) -> anyhow::Result<Request> {
+        let mut compiled_model = match self.compiled_model.lock() {
+            Ok(compiled_model) => compiled_model,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        Ok(Request(compiled_model.0.create_infer_request()?))
+    }
+
+    /// Runs the model on the rows of the input, one batch at a time.
+    fn infer(
+        &self,
+        request: &mut Request,
+        input: &DenseInput,
+    ) -> anyhow::Result<Vec<Vec<Vec<f64>>>> {
+        let request = &mut request.0;
+
+        let mut outputs: Vec<Vec<Vec<f64>>> =
+            vec![Vec::with_capacity(input.num_rows); self.outputs.len()];
+        for (batch, num_rows) in input.batches(&self.input, self.batch_size) {
+            // the input tensor is allocated by the infer request, so the batch is written in place
+            let mut input_tensor = request.get_tensor(self.input.name.as_str())?;
+            DenseTensor::fill(input_tensor.get_data_mut::<f32>()?, batch);
+
+            request.infer()?;
+
+            for (output, rows) in self.outputs.iter().zip(outputs.iter_mut()) {
+                let output_tensor = request.get_tensor(output.name.as_str())?;
+                output.extend_rows(rows, output_tensor.get_data::<f32>()?, num_rows);
+            }
+        }
+        Ok(outputs)
+    }
+}
+
+/// Reads and compiles the model of a model directory and describes its tensors.
+fn load_directory(dir: &Path) -> anyhow::Result<OpenVino> {
+    let model_path = dir.join(MODEL_FILE_NAME);
+    let weights_path = dir.join(WEIGHTS_FILE_NAME);
+    for path in [&model_path, &weights_path] {
+        if !path.is_file() {
+            anyhow::bail!("{} does not exist", path.display())
+        }
+    }
+    let (model_path, weights_path) = match (model_path.to_str(), weights_path.to_str()) {
+        (Some(model_path), Some(weights_path)) => (model_path, weights_path),
+        _ => anyhow::bail!("the path of the model directory is not valid UTF-8"),
+    };
+
+    let mut core = Core::new()?;
+    let model = core.read_model_from_file(model_path, weights_path)?;
+    let input_names = (0..model.get_inputs_len()?)
+        .map(|index| model.get_input_by_index(index)?.get_name())
+        .collect::<std::result::Result<Vec<String>, _>>()?;
+    let output_names = (0..model.get_outputs_len()?)
+        .map(|index| model.get_output_by_index(index)?.get_name())
+        .collect::<std::result::Result<Vec<String>, _>>()?;
+    let input_name = match input_names.as_slice() {
+        [input_name] => input_name.clone(),
+        _ => anyhow::bail!(
+            "expected a single input tensor, found {}",
+            input_names.len()
+        ),
+    };
+    if output_names.is_empty() {
+        anyhow::bail!("the model has no output tensors")
+    }
+
+    let mut compiled_model = core.compile_model(&model, DeviceType::CPU)?;
+    let mut request = compiled_model.create_infer_request()?;
+
+    // the tensors of models with static shapes are allocated by the infer request, which gives their shapes
+    let input_shape = request
+        .get_tensor(input_name.as_str())?
+        .get_shape()?
+        .get_dimensions()
+        .to_vec();
+    let batch_size = match input_shape.first() {
+        Some(batch_size) if *batch_size > 0 => *batch_size as usize,
+        _ => anyhow::bail!(
+            "the input tensor {} has no static batch size, reshape the model before converting it",
+            input_name
+        ),
+    };
+    let input = DenseTensor::new(FRAMEWORK_NAME, input_name, &input_shape, batch_size)?;
+    let mut outputs = Vec::with_capacity(output_names.len());
+    for output_name in output_names {
+        let shape = request
+            .get_tensor(output_name.as_str())?
+            .get_shape()?
+            .get_dimensions()
+            .to_vec();
+        outputs.push(DenseTensor::new(
+            FRAMEWORK_NAME,
+            output_name,
+            &shape,
+            batch_size,
+        )?);
+    }
+
+    Ok(OpenVino {
+        requests: SessionPool::new(Request(request)),
+        compiled_model: Mutex::new(Compiled(compiled_model)),
+        batch_size,
+        input,
+        outputs,
+    })
+}
+
+impl Lifecycle for OpenVino {}
+
+impl Predict for OpenVino {
+    /// Performs prediction using the loaded OpenVINO model.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The `ModelInput` containing the input values for prediction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if there is an issue parsing the input or performing the prediction.
+    #[tracing::instrument(skip(self, input))]
+    fn predict(&self, input: ModelInput) -> Result<ModelOutput> {
+        self.predict_with_options(input, &PredictOptions::default())
+    }
+
+    /// Performs prediction using the loaded OpenVINO model and the given prediction options.
+    ///
+    /// A model with a single output tensor returns it as the `predictions` output, otherwise each output
+    /// is named after its tensor. Neither `mode` nor `num_iteration` is supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The `ModelInput` containing the input values for prediction.
+    /// * `options` - The prediction options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the options are not supported, or if there is an issue parsing the input or
+    /// performing the prediction.
+    #[tracing::instrument(skip(self, input))]
+    fn predict_with_options(
+        &self,
+        input: ModelInput,
+        options: &PredictOptions,
+    ) -> Result<ModelOutput> {
+        dense::validate_options(FRAMEWORK_NAME, options)?;
+        let input =
+            DenseInput::parse(FRAMEWORK_NAME, input, self.input.row_len).map_err(Error::Input)?;
+
+        let outputs = self.requests.run(
+            || self.create_request(),
+            |request| self.infer(request, &input),
+        );
+
+        match outputs {
+            Ok(outputs) => Ok(dense::model_output(&self.outputs, outputs)),
+            Err(e) => {
+                tracing::error!("Failed to make predictions using OpenVINO: {}", e);
+                Err(Error::Predict(anyhow::anyhow!(
+                    "Failed to make predictions using OpenVINO: {}",
+                    e
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::output::DEFAULT_OUTPUT_KEY;
+
+    #[test]
+    fn fails_to_load_openvino_model() {
+        let model_dir = "incorrect/path";
+        let model = OpenVino::load(model_dir);
+
+        // assert the result is Err
+        assert!(model.is_err())
+    }
+
+    #[test]
+    fn successfully_load_and_predict_using_openvino_model() {
+        // the model adds [1, 2] to each row of its [2, 2] input, so three rows make two batches, the last padded
+        let model_dir = "tests/model_storage/models/openvino-my_awesome_shift_model";
+        let model = OpenVino::load(model_dir).unwrap();
+        let mut model_input = ModelInput::default();
+        model_input.float_features.values = crate::model::input::Values::Float(vec![
+            0.5, 2.0, 4.0, // first float feature
+            1.0, 3.0, 5.0, // second float feature
+        ]);
+        model_input.float_features.shape = (2, 3);
+
+        let output = model.predict(model_input).unwrap();
+
+        // assert
+        assert_eq!(
+            output.predictions[DEFAULT_OUTPUT_KEY],
+            vec![vec![1.5, 3.0], vec![3.0, 5.0], vec![5.0, 7.0]]
+        );
+        assert_eq!(model.requests.idle(), 1);
+    }
+}
@@ -1,14 +1,167 @@
-use serde::Serialize;
+// the output of the predictors is defined in jams-predictor-api, so that predictors built outside of jams-core
+// return the same output
+pub use jams_predictor_api::output::*;
 use std::collections::HashMap;
 
-pub const DEFAULT_OUTPUT_KEY: &str = "predictions";
+/// Applies softmax to each row of logits.
+pub(crate) fn softmax_rows(logits: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    logits
+        .iter()
+        .map(|row| {
+            // subtract the max for numerical stability
+            let max = row.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            let exps: Vec<f64> = row.iter().map(|value| (value - max).exp()).collect();
+            let sum: f64 = exps.iter().sum();
+            exps.into_iter().map(|value| value / sum).collect()
+        })
+        .collect()
+}
+
+/// Maps each row of class scores to the label of the class with the highest score.
+///
+/// # Arguments
+/// * `scores` - The class scores, one row per input row and one column per class.
+/// * `class_labels` - The label of each class, in the order of the columns.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - The label of the most likely class of each row.
+/// * `Err(anyhow::Error)` - If the number of columns does not match the number of labels.
+pub(crate) fn label_rows(
+    scores: &[Vec<f64>],
+    class_labels: &[String],
+) -> anyhow::Result<Vec<String>> {
+    scores
+        .iter()
+        .map(|row| {
+            if row.len() != class_labels.len() {
+                anyhow::bail!(
+                    "Model returned {} classes but {} class labels were provided",
+                    row.len(),
+                    class_labels.len()
+                )
+            }
+            let index = row
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(index, _)| index)
+                .unwrap_or(0);
+            Ok(class_labels[index].clone())
+        })
+        .collect()
+}
+
+/// Builds the outputs of a classifier from its logits: the raw `logits`, the `probabilities` after
+/// applying softmax and the `predicted_label` of each row.
+///
+/// # Arguments
+/// * `logits` - The raw logits, one row per input row and one column per class.
+/// * `class_labels` - The label of each class, in the order of the columns.
+///
+/// # Returns
+/// * `Ok(ModelOutput)` - The outputs of the classifier.
+/// * `Err(anyhow::Error)` - If the number of classes does not match the number of labels.
+pub(crate) fn classifier_output(
+    logits: Vec<Vec<f64>>,
+    class_labels: &[String],
+) -> anyhow::Result<ModelOutput> {
+    let probabilities = softmax_rows(&logits);
+    let predicted_labels = label_rows(&probabilities, class_labels)?;
+
+    let mut predictions = HashMap::new();
+    predictions.insert(LOGITS_OUTPUT_KEY.to_string(), logits);
+    predictions.insert(PROBABILITIES_OUTPUT_KEY.to_string(), probabilities);
+    let mut labels = HashMap::new();
+    labels.insert(PREDICTED_LABEL_KEY.to_string(), predicted_labels);
+
+    Ok(ModelOutput {
+        predictions,
+        labels,
+        shapes: HashMap::new(),
+        texts: HashMap::new(),
+    })
+}
+
+/// Splits a flat vector of predictions into one vector per row.
+///
+/// Some frameworks return the predictions of multiclass and multi-output models as a single row-major
+/// vector of `num_rows * num_outputs` values. This groups the values so that each row has all of its outputs.
+///
+/// # Arguments
+/// * `values` - The flat row-major predictions.
+/// * `num_rows` - The number of rows in the input.
+///
+/// # Returns
+/// * `Ok(Vec<Vec<f64>>)` - The predictions grouped by row.
+/// * `Err(anyhow::Error)` - If the number of values is not a multiple of the number of rows.
+pub(crate) fn split_into_rows(values: &[f64], num_rows: usize) -> anyhow::Result<Vec<Vec<f64>>> {
+    let num_outputs = values.len().checked_div(num_rows).unwrap_or(0);
+    if num_outputs == 0 || num_outputs * num_rows != values.len() {
+        tracing::error!(
+            "Unable to split {} predictions into {} rows",
+            values.len(),
+            num_rows
+        );
+        anyhow::bail!(
+            "Unable to split {} predictions into {} rows",
+            values.len(),
+            num_rows
+        )
+    }
+
+    Ok(values.chunks(num_outputs).map(|row| row.to_vec()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_split_predictions_into_rows() {
+        let values = vec![0.1, 0.2, 0.7, 0.3, 0.3, 0.4];
+
+        let rows = split_into_rows(&values, 2).unwrap();
+
+        // assert
+        assert_eq!(rows, vec![vec![0.1, 0.2, 0.7], vec![0.3, 0.3, 0.4]])
+    }
+
+    #[test]
+    fn fails_to_split_predictions_into_rows_when_shape_does_not_match() {
+        let values = vec![0.1, 0.2, 0.7];
+
+        // assert
+        assert!(split_into_rows(&values, 2).is_err());
+        assert!(split_into_rows(&values, 0).is_err());
+        assert!(split_into_rows(&[], 2).is_err())
+    }
+
+    #[test]
+    fn successfully_build_classifier_output_from_logits() {
+        let logits = vec![vec![2.0, 1.0, 0.1], vec![0.0, 0.0, 3.0]];
+        let class_labels = vec!["cat".to_string(), "dog".to_string(), "bird".to_string()];
+
+        let output = classifier_output(logits.clone(), &class_labels).unwrap();
+
+        // assert
+        let probabilities = output.predictions.get(PROBABILITIES_OUTPUT_KEY).unwrap();
+        for row in probabilities {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+        assert!(probabilities[0][0] > probabilities[0][1]);
+        assert_eq!(output.predictions.get(LOGITS_OUTPUT_KEY).unwrap(), &logits);
+        assert_eq!(
+            output.labels.get(PREDICTED_LABEL_KEY).unwrap(),
+            &vec!["cat".to_string(), "bird".to_string()]
+        )
+    }
+
+    #[test]
+    fn fails_to_build_classifier_output_when_labels_do_not_match_classes() {
+        let logits = vec![vec![2.0, 1.0, 0.1]];
+        let class_labels = vec!["cat".to_string(), "dog".to_string()];
 
-/// Struct representing the output of a prediction.
-#[derive(Debug, Serialize)]
-pub struct ModelOutput {
-    /// The predictions made by the model.
-    /// We use a hashmap because we can have models with multiple outputs
-    /// The client is responsible for selecting the correct field for their respective purpose
-    /// For the models which do not support multiple outputs, the default key will be 'predictions'
-    pub predictions: HashMap<String, Vec<Vec<f64>>>,
+        // assert
+        assert!(classifier_output(logits, &class_labels).is_err())
+    }
 }
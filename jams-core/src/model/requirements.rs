@@ -0,0 +1,230 @@
+use crate::memory::{available_memory_bytes, MEBIBYTE};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Name of the optional file in a model tarball which contains the capabilities the model requires of the host.
+pub const REQUIREMENTS_FILE_NAME: &str = "requirements.json";
+
+/// CPU features which a model can require, named as in `/proc/cpuinfo`.
+pub const SUPPORTED_CPU_FEATURES: [&str; 9] = [
+    "sse4_2",
+    "avx",
+    "avx2",
+    "fma",
+    "avx512f",
+    "avx512bw",
+    "avx512vl",
+    "avx512_vnni",
+    "neon",
+];
+
+/// Capabilities a model requires of the host, e.g. because its native library was built for a GPU or
+/// with AVX-512 instructions.
+///
+/// They are read from the `requirements.json` file at the root of the model tarball and checked before
+/// the model is loaded, so a model which cannot run on the host fails to load with a clear reason instead
+/// of crashing in native code.
+///
+/// # Example
+/// ```json
+/// { "gpu": true, "cpu_features": ["avx512f"], "min_memory_mb": 65536 }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ModelRequirements {
+    /// Whether the model requires an NVIDIA GPU. Defaults to `false`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub gpu: bool,
+    /// CPU features the model requires, e.g. `avx512f`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cpu_features: Vec<String>,
+    /// Minimum memory (in MiB) of the host.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_memory_mb: Option<u64>,
+}
+
+/// Capabilities of the host which models can require.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HostCapabilities {
+    /// Whether an NVIDIA GPU is available.
+    pub gpu: bool,
+    /// The supported CPU features which the CPU has.
+    pub cpu_features: Vec<&'static str>,
+    /// Memory available to the process in bytes, i.e. the memory of the host capped by its cgroup, if it can be determined.
+    pub memory_bytes: Option<u64>,
+}
+
+impl HostCapabilities {
+    /// Returns the capabilities of the host, which are detected once.
+    pub fn current() -> &'static HostCapabilities {
+        static CAPABILITIES: OnceLock<HostCapabilities> = OnceLock::new();
+        CAPABILITIES.get_or_init(|| HostCapabilities {
+            gpu: Path::new("/proc/driver/nvidia/version").exists()
+                || Path::new("/dev/nvidia0").exists(),
+            cpu_features: SUPPORTED_CPU_FEATURES
+                .into_iter()
+                .filter(|feature| has_cpu_feature(feature))
+                .collect(),
+            memory_bytes: available_memory_bytes(),
+        })
+    }
+}
+
+/// Returns true if the CPU has the given feature.
+#[cfg(target_arch = "x86_64")]
+fn has_cpu_feature(feature: &str) -> bool {
+    match feature {
+        "sse4_2" => std::arch::is_x86_feature_detected!("sse4.2"),
+        "avx" => std::arch::is_x86_feature_detected!("avx"),
+        "avx2" => std::arch::is_x86_feature_detected!("avx2"),
+        "fma" => std::arch::is_x86_feature_detected!("fma"),
+        "avx512f" => std::arch::is_x86_feature_detected!("avx512f"),
+        "avx512bw" => std::arch::is_x86_feature_detected!("avx512bw"),
+        "avx512vl" => std::arch::is_x86_feature_detected!("avx512vl"),
+        "avx512_vnni" => std::arch::is_x86_feature_detected!("avx512vnni"),
+        _ => false,
+    }
+}
+
+/// Returns true if the CPU has the given feature.
+#[cfg(target_arch = "aarch64")]
+fn has_cpu_feature(feature: &str) -> bool {
+    match feature {
+        "neon" => std::arch::is_aarch64_feature_detected!("neon"),
+        _ => false,
+    }
+}
+
+/// Returns true if the CPU has the given feature.
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn has_cpu_feature(_feature: &str) -> bool {
+    false
+}
+
+impl ModelRequirements {
+    /// Parses and validates model requirements.
+    ///
+    /// # Arguments
+    /// * `contents` - The contents of a `requirements.json` file.
+    ///
+    /// # Returns
+    /// * `Ok(ModelRequirements)` - If the requirements are valid.
+    /// * `Err(anyhow::Error)` - If the file cannot be parsed or requires an unknown CPU feature.
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let requirements: ModelRequirements = match serde_json::from_str(contents) {
+            Ok(requirements) => requirements,
+            Err(e) => anyhow::bail!("Failed to parse {} ❌: {}", REQUIREMENTS_FILE_NAME, e),
+        };
+        for feature in requirements.cpu_features.iter() {
+            if !SUPPORTED_CPU_FEATURES.contains(&feature.as_str()) {
+                anyhow::bail!(
+                    "Unknown CPU feature {} in {}, expected one of {} ❌",
+                    feature,
+                    REQUIREMENTS_FILE_NAME,
+                    SUPPORTED_CPU_FEATURES.join(", ")
+                )
+            }
+        }
+        Ok(requirements)
+    }
+
+    /// Returns true if the model has no requirements.
+    pub fn is_empty(&self) -> bool {
+        !self.gpu && self.cpu_features.is_empty() && self.min_memory_mb.is_none()
+    }
+
+    /// Checks that the host satisfies the requirements.
+    ///
+    /// # Arguments
+    /// * `host` - The capabilities of the host.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the host satisfies every requirement, or its memory cannot be determined.
+    /// * `Err(anyhow::Error)` - Listing the requirements which the host does not satisfy.
+    pub fn check(&self, host: &HostCapabilities) -> anyhow::Result<()> {
+        let mut unsatisfied: Vec<String> = Vec::new();
+        if self.gpu && !host.gpu {
+            unsatisfied.push("a GPU".to_string());
+        }
+        for feature in self.cpu_features.iter() {
+            if !host.cpu_features.contains(&feature.as_str()) {
+                unsatisfied.push(format!("the {} CPU feature", feature));
+            }
+        }
+        if let (Some(min_memory_mb), Some(memory_bytes)) = (self.min_memory_mb, host.memory_bytes) {
+            if memory_bytes < min_memory_mb.saturating_mul(MEBIBYTE) {
+                unsatisfied.push(format!(
+                    "{} MiB of memory but the host has {} MiB",
+                    min_memory_mb,
+                    memory_bytes / MEBIBYTE
+                ));
+            }
+        }
+        if !unsatisfied.is_empty() {
+            anyhow::bail!(
+                "Host does not satisfy the requirements of the model, it requires {} ❌",
+                unsatisfied.join(", ")
+            )
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_check_requirements_satisfied_by_host() {
+        // Arrange
+        let requirements =
+            ModelRequirements::parse(r#"{"cpu_features": ["avx2"], "min_memory_mb": 1024}"#)
+                .unwrap();
+        let host = HostCapabilities {
+            gpu: false,
+            cpu_features: vec!["avx", "avx2"],
+            memory_bytes: Some(4096 * MEBIBYTE),
+        };
+
+        // Act
+        let result = requirements.check(&host);
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(ModelRequirements::default().is_empty());
+        assert!(!requirements.is_empty());
+    }
+
+    #[test]
+    fn fails_to_check_requirements_when_host_does_not_satisfy_them() {
+        // Arrange
+        let requirements = ModelRequirements::parse(
+            r#"{"gpu": true, "cpu_features": ["avx512f"], "min_memory_mb": 65536}"#,
+        )
+        .unwrap();
+        let host = HostCapabilities {
+            gpu: false,
+            cpu_features: vec!["avx", "avx2"],
+            memory_bytes: Some(4096 * MEBIBYTE),
+        };
+
+        // Act
+        let result = requirements.check(&host);
+
+        // Assert
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("a GPU"));
+        assert!(message.contains("the avx512f CPU feature"));
+        assert!(message.contains("65536 MiB of memory but the host has 4096 MiB"));
+    }
+
+    #[test]
+    fn fails_to_parse_requirements_when_cpu_feature_is_unknown() {
+        // Act
+        let requirements = ModelRequirements::parse(r#"{"cpu_features": ["avx1024"]}"#);
+
+        // Assert
+        assert!(requirements.is_err());
+    }
+}
@@ -0,0 +1,305 @@
+use crate::model::bundle::{FeatureType, ModelBundle};
+use rand::Rng;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Maximum number of rows of a sample model input.
+pub const MAX_SAMPLE_ROWS: usize = 100;
+
+/// Range of the integer features of a sample model input.
+const INT_RANGE: (i64, i64) = (0, 10);
+
+/// Range of the float features without a scaler of a sample model input.
+const FLOAT_RANGE: (f64, f64) = (0.0, 1.0);
+
+/// How the values of a sample model input are chosen.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleStrategy {
+    /// Every value is the midpoint of the range of its feature, so the sample is the same on every call.
+    #[default]
+    Midpoint,
+    /// Every value is drawn uniformly from the range of its feature.
+    Random,
+}
+
+/// Generates an example model input from the schema in the bundle of a model, so that integrators can build
+/// their requests without reading the training code.
+///
+/// The range of a feature depends on its type:
+/// * String features with a vocabulary take one of its tokens, the middle one for the midpoint. String features
+///   without a vocabulary take the name of the feature as a placeholder.
+/// * Float features with a scaler range over one scale around the mean, i.e. the midpoint is the mean, and take
+///   the mean if the scale is 0 or not finite. Float features without a scaler, or whose mean is not finite,
+///   range from 0 to 1.
+/// * Integer features range from 0 to 10.
+///
+/// Models without a schema return their warmup input instead, if they were shipped with one, with its rows
+/// repeated or truncated to the number of rows of the sample.
+///
+/// # Arguments
+///
+/// * `bundle` - The bundle of the model.
+/// * `rows` - The number of rows of the sample, between 1 and `MAX_SAMPLE_ROWS`.
+/// * `strategy` - How the values are chosen.
+///
+/// # Returns
+///
+/// * `Ok(String)` - The sample as a columnar model input, e.g. `{"age": [5], "sex": ["male"]}`.
+/// * `Err(anyhow::Error)` - If the number of rows is out of range, the model has neither a schema nor a warmup
+///   input, or the warmup input is not a columnar model input.
+pub fn sample_input(
+    bundle: &ModelBundle,
+    rows: usize,
+    strategy: SampleStrategy,
+) -> anyhow::Result<String> {
+    if rows == 0 || rows > MAX_SAMPLE_ROWS {
+        anyhow::bail!(
+            "Number of rows of a sample must be between 1 and {} ❌",
+            MAX_SAMPLE_ROWS
+        )
+    }
+    if bundle.schema.is_empty() {
+        return match &bundle.warmup_input {
+            Some(warmup_input) => resize_rows(warmup_input.as_str(), rows),
+            None => anyhow::bail!("Model has no input schema to generate a sample from ❌"),
+        };
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut input = Map::new();
+    for (feature, feature_type) in bundle.schema.iter() {
+        let values: Vec<Value> = (0..rows)
+            .map(|_| sample_value(bundle, feature, *feature_type, strategy, &mut rng))
+            .collect();
+        input.insert(feature.clone(), Value::Array(values));
+    }
+    Ok(Value::Object(input).to_string())
+}
+
+fn sample_value(
+    bundle: &ModelBundle,
+    feature: &str,
+    feature_type: FeatureType,
+    strategy: SampleStrategy,
+    rng: &mut impl Rng,
+) -> Value {
+    match feature_type {
+        FeatureType::String => {
+            let tokens = bundle
+                .vocabularies
+                .get(feature)
+                .map(|vocabulary| vocabulary.tokens())
+                .unwrap_or_default();
+            let token = match (tokens.is_empty(), strategy) {
+                (true, _) => feature,
+                (false, SampleStrategy::Midpoint) => tokens[tokens.len() / 2].as_str(),
+                (false, SampleStrategy::Random) => tokens[rng.gen_range(0..tokens.len())].as_str(),
+            };
+            Value::from(token)
+        }
+        FeatureType::Float => {
+            let (low, high) = match bundle.scalers.get(feature) {
+                Some(scaler) if scaler.mean.is_finite() => {
+                    let scale = match scaler.scale.is_finite() {
+                        true => scaler.scale.abs(),
+                        false => 0.0,
+                    };
+                    (scaler.mean - scale, scaler.mean + scale)
+                }
+                _ => FLOAT_RANGE,
+            };
+            // an empty or unbounded range cannot be drawn from, e.g. the range of a constant feature
+            match strategy {
+                SampleStrategy::Random if low < high && (high - low).is_finite() => {
+                    Value::from(rng.gen_range(low..high))
+                }
+                _ => Value::from(low / 2.0 + high / 2.0),
+            }
+        }
+        FeatureType::Int => {
+            let (low, high) = INT_RANGE;
+            match strategy {
+                SampleStrategy::Midpoint => Value::from((low + high) / 2),
+                SampleStrategy::Random => Value::from(rng.gen_range(low..=high)),
+            }
+        }
+    }
+}
+
+/// Repeats or truncates the rows of a columnar model input, e.g. `{"age": [5, 7]}`, to the given number of rows.
+fn resize_rows(input: &str, rows: usize) -> anyhow::Result<String> {
+    let columns: Map<String, Value> = match serde_json::from_str(input) {
+        Ok(columns) => columns,
+        Err(e) => anyhow::bail!("Warmup input is not a model input ❌: {}", e),
+    };
+    let mut resized = Map::new();
+    for (feature, values) in columns {
+        let values = match values {
+            Value::Array(values) if !values.is_empty() => values,
+            _ => anyhow::bail!(
+                "Warmup input is not a model input ❌: {} is not a non-empty array",
+                feature
+            ),
+        };
+        let values = values.iter().cycle().take(rows).cloned().collect();
+        resized.insert(feature, Value::Array(values));
+    }
+    Ok(Value::Object(resized).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::bundle::{Scaler, Vocabulary};
+    use crate::model::input::ModelInput;
+    use std::collections::BTreeMap;
+
+    fn bundle() -> ModelBundle {
+        ModelBundle {
+            vocabularies: BTreeMap::from([(
+                "sex".to_string(),
+                Vocabulary::from(vec![
+                    "female".to_string(),
+                    "male".to_string(),
+                    "other".to_string(),
+                ]),
+            )]),
+            scalers: BTreeMap::from([(
+                "fare".to_string(),
+                Scaler {
+                    mean: 30.0,
+                    scale: 10.0,
+                },
+            )]),
+            schema: BTreeMap::from([
+                ("age".to_string(), FeatureType::Float),
+                ("fare".to_string(), FeatureType::Float),
+                ("pclass".to_string(), FeatureType::Int),
+                ("sex".to_string(), FeatureType::String),
+                ("embark_town".to_string(), FeatureType::String),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn successfully_generate_midpoint_sample_from_schema() {
+        // Act
+        let sample = sample_input(&bundle(), 2, SampleStrategy::Midpoint).unwrap();
+
+        // Assert
+        let sample: Value = serde_json::from_str(sample.as_str()).unwrap();
+        assert_eq!(
+            sample,
+            serde_json::json!({
+                "age": [0.5, 0.5],
+                "fare": [30.0, 30.0],
+                "pclass": [5, 5],
+                "sex": ["male", "male"],
+                "embark_town": ["embark_town", "embark_town"]
+            })
+        );
+    }
+
+    #[test]
+    fn successfully_generate_random_sample_which_matches_schema() {
+        // Arrange
+        let mut bundle = bundle();
+
+        // Act
+        let sample = sample_input(&bundle, 3, SampleStrategy::Random).unwrap();
+
+        // Assert
+        let mut input = ModelInput::from_str(sample.as_str()).unwrap();
+        assert!(bundle.preprocess(&mut input, Default::default()).is_ok());
+        let sample: Value = serde_json::from_str(sample.as_str()).unwrap();
+        for fare in sample["fare"].as_array().unwrap() {
+            assert!((20.0..=40.0).contains(&fare.as_f64().unwrap()));
+        }
+        bundle.schema.clear();
+        assert!(sample_input(&bundle, 1, SampleStrategy::Random).is_err());
+    }
+
+    #[test]
+    fn successfully_generate_sample_when_scale_is_degenerate() {
+        // Arrange
+        let mut bundle = bundle();
+        bundle.scalers.insert(
+            "age".to_string(),
+            Scaler {
+                mean: 35.0,
+                scale: 0.0,
+            },
+        );
+        bundle.scalers.insert(
+            "fare".to_string(),
+            Scaler {
+                mean: 30.0,
+                scale: f64::NAN,
+            },
+        );
+
+        // Act
+        let random = sample_input(&bundle, 2, SampleStrategy::Random).unwrap();
+        let midpoint = sample_input(&bundle, 1, SampleStrategy::Midpoint).unwrap();
+
+        // Assert
+        let random: Value = serde_json::from_str(random.as_str()).unwrap();
+        let midpoint: Value = serde_json::from_str(midpoint.as_str()).unwrap();
+        assert_eq!(random["age"], serde_json::json!([35.0, 35.0]));
+        assert_eq!(random["fare"], serde_json::json!([30.0, 30.0]));
+        assert_eq!(midpoint["fare"], serde_json::json!([30.0]));
+    }
+
+    #[test]
+    fn successfully_generate_sample_of_requested_rows_from_warmup_input() {
+        // Arrange
+        let bundle = ModelBundle {
+            warmup_input: Some(r#"{"age": [22.0, 38.0], "sex": ["male", "female"]}"#.to_string()),
+            ..Default::default()
+        };
+
+        // Act
+        let one = sample_input(&bundle, 1, SampleStrategy::Midpoint).unwrap();
+        let three = sample_input(&bundle, 3, SampleStrategy::Random).unwrap();
+
+        // Assert
+        let one: Value = serde_json::from_str(one.as_str()).unwrap();
+        let three: Value = serde_json::from_str(three.as_str()).unwrap();
+        assert_eq!(one, serde_json::json!({"age": [22.0], "sex": ["male"]}));
+        assert_eq!(
+            three,
+            serde_json::json!({"age": [22.0, 38.0, 22.0], "sex": ["male", "female", "male"]})
+        );
+    }
+
+    #[test]
+    fn successfully_generate_random_values_within_range_of_feature() {
+        // Act
+        let sample = sample_input(&bundle(), MAX_SAMPLE_ROWS, SampleStrategy::Random).unwrap();
+
+        // Assert
+        let sample: Value = serde_json::from_str(sample.as_str()).unwrap();
+        for age in sample["age"].as_array().unwrap() {
+            assert!((0.0..1.0).contains(&age.as_f64().unwrap()));
+        }
+        for pclass in sample["pclass"].as_array().unwrap() {
+            assert!((0..=10).contains(&pclass.as_i64().unwrap()));
+        }
+        for sex in sample["sex"].as_array().unwrap() {
+            assert!(["female", "male", "other"].contains(&sex.as_str().unwrap()));
+        }
+    }
+
+    #[test]
+    fn fails_to_generate_sample_when_rows_are_out_of_range() {
+        // Act
+        let none = sample_input(&bundle(), 0, SampleStrategy::Midpoint);
+        let too_many = sample_input(&bundle(), MAX_SAMPLE_ROWS + 1, SampleStrategy::Midpoint);
+
+        // Assert
+        assert!(none.is_err());
+        assert!(too_many.is_err());
+    }
+}
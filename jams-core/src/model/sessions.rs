@@ -0,0 +1,109 @@
+use std::sync::Mutex;
+
+/// A pool of the sessions of a model, e.g. the interpreters of a TensorFlow Lite model, which can only run one
+/// prediction at a time.
+///
+/// A prediction takes an idle session, or creates one if all of them are busy, and puts it back when it is done,
+/// so the pool grows to the number of workers predicting with the model concurrently instead of serializing them
+/// on a single session.
+pub struct SessionPool<T> {
+    idle: Mutex<Vec<T>>,
+}
+
+impl<T> SessionPool<T> {
+    /// Creates a pool holding the session built when the model was loaded.
+    pub fn new(session: T) -> Self {
+        SessionPool {
+            idle: Mutex::new(vec![session]),
+        }
+    }
+
+    /// Runs `f` with an idle session, or with a new session built by `create` if there is none.
+    ///
+    /// The session is put back in the pool only if `f` succeeds, since a failed prediction may leave it in an
+    /// inconsistent state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if a new session cannot be created or if `f` fails.
+    pub fn run<R>(
+        &self,
+        create: impl FnOnce() -> anyhow::Result<T>,
+        f: impl FnOnce(&mut T) -> anyhow::Result<R>,
+    ) -> anyhow::Result<R> {
+        let session = self.lock().pop();
+        let mut session = match session {
+            Some(session) => session,
+            None => create()?,
+        };
+        let result = f(&mut session)?;
+        self.lock().push(session);
+        Ok(result)
+    }
+
+    /// Returns the number of idle sessions.
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<T>> {
+        match self.idle.lock() {
+            Ok(idle) => idle,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_reuse_idle_session() {
+        // Arrange
+        let pool = SessionPool::new(1);
+
+        // Act
+        let session = pool
+            .run(|| anyhow::bail!("no session should be created"), |s| Ok(*s))
+            .unwrap();
+
+        // Assert
+        assert_eq!(session, 1);
+        assert_eq!(pool.idle(), 1);
+    }
+
+    #[test]
+    fn successfully_create_session_when_all_sessions_are_busy() {
+        // Arrange
+        let pool = SessionPool::new(1);
+
+        // Act
+        let sessions = pool
+            .run(
+                || Ok(0),
+                |outer| {
+                    let inner = pool.run(|| Ok(2), |inner| Ok(*inner))?;
+                    Ok((*outer, inner))
+                },
+            )
+            .unwrap();
+
+        // Assert
+        assert_eq!(sessions, (1, 2));
+        assert_eq!(pool.idle(), 2);
+    }
+
+    #[test]
+    fn fails_to_run_and_drops_the_session() {
+        // Arrange
+        let pool = SessionPool::new(1);
+
+        // Act
+        let result: anyhow::Result<()> = pool.run(|| Ok(0), |_| anyhow::bail!("failed"));
+
+        // Assert
+        assert!(result.is_err());
+        assert_eq!(pool.idle(), 0);
+    }
+}
@@ -0,0 +1,195 @@
+// the resolved settings are defined in jams-predictor-api, so that predictors built outside of jams-core are
+// initialized with them too
+pub use jams_predictor_api::settings::ModelSettings;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::future::Future;
+use std::sync::Arc;
+
+tokio::task_local! {
+    /// The settings of the models of the `Manager` which loads models on the current task, if any.
+    static LOAD_SETTINGS: Arc<ModelSettingsConfig>;
+}
+
+/// The settings of every model, keyed by the model name and then by the name of the setting.
+///
+/// # Example
+/// ```toml
+/// [config.model_settings.my_seq2seq_model]
+/// tokenizer_endpoint = "http://tokenizer:8080"
+/// vocab_key = { file = "/run/secrets/vocab_key" }
+/// api_token = { env = "TOKENIZER_API_TOKEN" }
+/// ```
+pub type ModelSettingsConfig = BTreeMap<String, BTreeMap<String, SettingSource>>;
+
+/// Where the value of a model setting comes from. Files and environment variables are read every time the model
+/// is loaded, so that rotated secrets are picked up by the next update of the model.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum SettingSource {
+    /// The value itself.
+    Value(String),
+    /// A file holding the value, e.g. a secret mounted into the container. A trailing newline is removed.
+    File { file: String },
+    /// An environment variable holding the value.
+    Env { env: String },
+}
+
+impl SettingSource {
+    /// Reads the value of the setting.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the file cannot be read or the environment variable is not set.
+    pub fn read(&self) -> anyhow::Result<String> {
+        match self {
+            SettingSource::Value(value) => Ok(value.clone()),
+            SettingSource::File { file } => match fs::read_to_string(file) {
+                Ok(value) => Ok(value.trim_end_matches(['\r', '\n']).to_string()),
+                Err(e) => anyhow::bail!("Failed to read {} ❌: {}", file, e),
+            },
+            SettingSource::Env { env } => match std::env::var(env) {
+                Ok(value) => Ok(value),
+                Err(e) => anyhow::bail!("Failed to read env variable {} ❌: {}", env, e),
+            },
+        }
+    }
+}
+
+/// Runs a future which loads models with the settings of the models in scope, so that the init hooks of every
+/// model it loads get the settings of the model.
+///
+/// # Arguments
+/// - `settings`: The settings of the models, or `None` if models are loaded without settings.
+/// - `future`: The future which loads the models, e.g. adding a model to a model store.
+pub async fn with_settings<F: Future>(
+    settings: Option<Arc<ModelSettingsConfig>>,
+    future: F,
+) -> F::Output {
+    match settings {
+        Some(settings) => LOAD_SETTINGS.scope(settings, future).await,
+        None => future.await,
+    }
+}
+
+/// Resolves the settings of a model from the settings in scope. A model without settings, or loaded without
+/// settings in scope, gets empty settings.
+///
+/// # Arguments
+///
+/// * `model_name` - The name of the model.
+///
+/// # Errors
+///
+/// * `Err(anyhow::Error)` - If a setting of the model cannot be read.
+pub fn resolve(model_name: &str) -> anyhow::Result<ModelSettings> {
+    match LOAD_SETTINGS.try_with(|config| resolve_from(config, model_name)) {
+        Ok(settings) => settings,
+        Err(_) => Ok(ModelSettings::default()),
+    }
+}
+
+fn resolve_from(config: &ModelSettingsConfig, model_name: &str) -> anyhow::Result<ModelSettings> {
+    let sources = match config.get(model_name) {
+        Some(sources) => sources,
+        None => return Ok(ModelSettings::default()),
+    };
+    let mut values = BTreeMap::new();
+    for (name, source) in sources {
+        match source.read() {
+            Ok(value) => {
+                values.insert(name.clone(), value);
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to resolve setting {} of model {} ❌: {}",
+                    name,
+                    model_name,
+                    e
+                );
+                anyhow::bail!(
+                    "Failed to resolve setting {} of model {} ❌: {}",
+                    name,
+                    model_name,
+                    e
+                )
+            }
+        }
+    }
+    Ok(ModelSettings::new(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_resolve_model_settings() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let secret = dir.path().join("vocab_key");
+        fs::write(&secret, "s3cr3t\n").unwrap();
+        // the tests run on several threads, so an environment variable set by cargo is read instead of setting one
+        let config: ModelSettingsConfig = serde_json::from_value(serde_json::json!({
+            "seq2seq": {
+                "tokenizer_endpoint": "http://tokenizer:8080",
+                "vocab_key": { "file": secret },
+                "api_token": { "env": "CARGO_PKG_NAME" }
+            }
+        }))
+        .unwrap();
+
+        // Act
+        let settings = resolve_from(&config, "seq2seq").unwrap();
+        let other = resolve_from(&config, "titanic").unwrap();
+
+        // Assert
+        assert_eq!(
+            settings.get("tokenizer_endpoint"),
+            Some("http://tokenizer:8080")
+        );
+        assert_eq!(settings.require("vocab_key").unwrap(), "s3cr3t");
+        assert_eq!(settings.get("api_token"), Some(env!("CARGO_PKG_NAME")));
+        assert!(settings.require("missing").is_err());
+        assert!(!format!("{:?}", settings).contains("s3cr3t"));
+        assert!(other.is_empty());
+    }
+
+    #[tokio::test]
+    async fn successfully_resolve_model_settings_only_within_their_scope() {
+        // Arrange
+        let config: ModelSettingsConfig = serde_json::from_value(serde_json::json!({
+            "xgboost": { "nthread": "4" }
+        }))
+        .unwrap();
+
+        // Act
+        let scoped = with_settings(Some(Arc::new(config)), async { resolve("xgboost") }).await;
+        let unscoped = with_settings(None, async { resolve("xgboost") }).await;
+
+        // Assert
+        assert_eq!(scoped.unwrap().get("nthread"), Some("4"));
+        assert!(unscoped.unwrap().is_empty());
+    }
+
+    #[test]
+    fn fails_to_resolve_model_settings_when_secret_is_missing() {
+        // Arrange
+        let config = ModelSettingsConfig::from([(
+            "seq2seq".to_string(),
+            BTreeMap::from([(
+                "vocab_key".to_string(),
+                SettingSource::File {
+                    file: "/does/not/exist".to_string(),
+                },
+            )]),
+        )]);
+
+        // Act
+        let settings = resolve_from(&config, "seq2seq");
+
+        // Assert
+        assert!(settings.unwrap_err().to_string().contains("vocab_key"));
+    }
+}
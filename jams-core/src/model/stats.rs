@@ -0,0 +1,317 @@
+use crate::model::variant::{MIN_RECENT_LATENCIES, RECENT_LATENCY_WINDOW};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Relative accuracy of the latency quantiles, i.e. a reported quantile is within 2% of the exact one.
+pub const RELATIVE_ACCURACY: f64 = 0.02;
+
+/// Maximum latency told apart by the sketches. Slower predictions are counted in the last bucket.
+pub const MAX_TRACKED_LATENCY: Duration = Duration::from_secs(100);
+
+/// Number of slots the recent window is split into. A slot is recycled once it is older than the window, so the
+/// window slides by `RECENT_LATENCY_WINDOW / WINDOW_SLOTS`.
+const WINDOW_SLOTS: usize = 6;
+
+/// Quantiles of the recent latency of a model reported by `ModelStats::snapshot`.
+pub const REPORTED_QUANTILES: [f64; 3] = [0.5, 0.95, 0.99];
+
+/// A DDSketch of latencies with a fixed number of atomic buckets, so that concurrent predictions record
+/// their latency without taking a lock.
+///
+/// Latencies are counted in logarithmically sized buckets of microseconds: bucket `i` holds the latencies
+/// between `gamma^(i-1)` and `gamma^i`, where `gamma = (1 + RELATIVE_ACCURACY) / (1 - RELATIVE_ACCURACY)`.
+struct LatencySketch {
+    buckets: Box<[AtomicU64]>,
+    count: AtomicU64,
+}
+
+impl LatencySketch {
+    fn new() -> Self {
+        let num_buckets = bucket_of(MAX_TRACKED_LATENCY.as_micros() as f64) + 1;
+        LatencySketch {
+            buckets: (0..num_buckets).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, elapsed: Duration) {
+        let bucket = bucket_of(elapsed.as_micros() as f64).min(self.buckets.len() - 1);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn reset(&self) {
+        for bucket in self.buckets.iter() {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.count.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Returns the bucket of a latency in microseconds. Latencies of at most a microsecond share the first bucket.
+fn bucket_of(micros: f64) -> usize {
+    if micros <= 1.0 {
+        return 0;
+    }
+    (micros.ln() / gamma().ln()).ceil() as usize
+}
+
+/// Returns the latency which represents a bucket, i.e. the value within `RELATIVE_ACCURACY` of every
+/// latency counted in it.
+fn latency_of(bucket: usize) -> Duration {
+    if bucket == 0 {
+        return Duration::from_micros(1);
+    }
+    let micros = 2.0 * gamma().powi(bucket as i32) / (gamma() + 1.0);
+    Duration::from_secs_f64(micros / 1_000_000.0)
+}
+
+fn gamma() -> f64 {
+    (1.0 + RELATIVE_ACCURACY) / (1.0 - RELATIVE_ACCURACY)
+}
+
+/// A slot of the recent window, holding the predictions made during one epoch of
+/// `RECENT_LATENCY_WINDOW / WINDOW_SLOTS`.
+struct Slot {
+    epoch: AtomicU64,
+    latencies: LatencySketch,
+    errors: AtomicU64,
+}
+
+/// Latency and error statistics of a model, recorded on the hot path of every prediction without taking a lock.
+///
+/// The totals count every prediction made since the model was loaded. The recent statistics cover the
+/// predictions of the last `RECENT_LATENCY_WINDOW`, from which the latency quantiles are estimated with a
+/// relative accuracy of `RELATIVE_ACCURACY`. They feed the metrics and the latency-budget router, see
+/// `LatencyFallbacks`.
+///
+/// A slot which has expired is reset by the first prediction of its new epoch. Predictions recorded
+/// concurrently with the reset may be lost, which is negligible for the statistics.
+pub struct ModelStats {
+    started_at: Instant,
+    slots: [Slot; WINDOW_SLOTS],
+    predictions: AtomicU64,
+    errors: AtomicU64,
+}
+
+impl Default for ModelStats {
+    fn default() -> Self {
+        ModelStats {
+            started_at: Instant::now(),
+            slots: std::array::from_fn(|_| Slot {
+                epoch: AtomicU64::new(0),
+                latencies: LatencySketch::new(),
+                errors: AtomicU64::new(0),
+            }),
+            predictions: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Statistics of a model at the time of a snapshot.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ModelStatsSnapshot {
+    /// The name of the model.
+    pub model_name: String,
+    /// The number of predictions made since the model was loaded.
+    pub predictions: u64,
+    /// The number of predictions which failed since the model was loaded.
+    pub errors: u64,
+    /// The number of predictions made in the recent window.
+    pub recent_predictions: u64,
+    /// The number of predictions which failed in the recent window.
+    pub recent_errors: u64,
+    /// The recent latency at each of `REPORTED_QUANTILES`, in seconds, if enough predictions were made in the
+    /// recent window.
+    pub latency_quantiles: Vec<(f64, f64)>,
+}
+
+impl ModelStats {
+    /// Records the latency of a successful prediction.
+    pub fn record_latency(&self, elapsed: Duration) {
+        self.predictions.fetch_add(1, Ordering::Relaxed);
+        self.current_slot().latencies.record(elapsed);
+    }
+
+    /// Records a prediction which failed.
+    pub fn record_error(&self) {
+        self.predictions.fetch_add(1, Ordering::Relaxed);
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        self.current_slot().errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the recent latency at quantile `q`, between 0 and 1, or `None` if fewer than
+    /// `MIN_RECENT_LATENCIES` successful predictions were made in the last `RECENT_LATENCY_WINDOW`.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        let epoch = self.epoch();
+        let recent: Vec<&Slot> = self.recent_slots(epoch).collect();
+        let count: u64 = recent
+            .iter()
+            .map(|slot| slot.latencies.count.load(Ordering::Relaxed))
+            .sum();
+        if count < MIN_RECENT_LATENCIES as u64 {
+            return None;
+        }
+
+        // the rank of the quantile among the recent latencies, counted from 1
+        let rank = ((q.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let num_buckets = recent[0].latencies.buckets.len();
+        let mut seen = 0;
+        for bucket in 0..num_buckets {
+            seen += recent
+                .iter()
+                .map(|slot| slot.latencies.buckets[bucket].load(Ordering::Relaxed))
+                .sum::<u64>();
+            if seen >= rank {
+                return Some(latency_of(bucket));
+            }
+        }
+        // the buckets were reset while they were read
+        None
+    }
+
+    /// Returns the statistics of the model.
+    pub fn snapshot(&self, model_name: &str) -> ModelStatsSnapshot {
+        let epoch = self.epoch();
+        let (recent_predictions, recent_errors) =
+            self.recent_slots(epoch)
+                .fold((0, 0), |(predictions, errors), slot| {
+                    let slot_errors = slot.errors.load(Ordering::Relaxed);
+                    (
+                        predictions + slot.latencies.count.load(Ordering::Relaxed) + slot_errors,
+                        errors + slot_errors,
+                    )
+                });
+        ModelStatsSnapshot {
+            model_name: model_name.to_string(),
+            predictions: self.predictions.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            recent_predictions,
+            recent_errors,
+            latency_quantiles: REPORTED_QUANTILES
+                .iter()
+                .filter_map(|q| self.quantile(*q).map(|latency| (*q, latency.as_secs_f64())))
+                .collect(),
+        }
+    }
+
+    /// Returns the current epoch, counted in slots since the stats were created. Epochs start at 1 so that
+    /// the slots which were never used are never recent.
+    fn epoch(&self) -> u64 {
+        let slot_duration = RECENT_LATENCY_WINDOW / WINDOW_SLOTS as u32;
+        (self.started_at.elapsed().as_nanos() / slot_duration.as_nanos()) as u64 + 1
+    }
+
+    /// Returns the slot of the current epoch, recycling it if it holds the predictions of an expired epoch.
+    fn current_slot(&self) -> &Slot {
+        let epoch = self.epoch();
+        let slot = &self.slots[epoch as usize % WINDOW_SLOTS];
+        let slot_epoch = slot.epoch.load(Ordering::Acquire);
+        if slot_epoch != epoch
+            && slot
+                .epoch
+                .compare_exchange(slot_epoch, epoch, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        {
+            slot.latencies.reset();
+            slot.errors.store(0, Ordering::Relaxed);
+        }
+        slot
+    }
+
+    /// Returns the slots holding the predictions of the recent window.
+    fn recent_slots(&self, epoch: u64) -> impl Iterator<Item = &Slot> {
+        self.slots.iter().filter(move |slot| {
+            let slot_epoch = slot.epoch.load(Ordering::Acquire);
+            slot_epoch != 0 && slot_epoch + (WINDOW_SLOTS as u64) > epoch
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_estimate_latency_quantiles_within_relative_accuracy() {
+        // Arrange
+        let stats = ModelStats::default();
+        for millis in 1..=100 {
+            stats.record_latency(Duration::from_millis(millis));
+        }
+        stats.record_error();
+
+        // Act
+        let p50 = stats.quantile(0.5).unwrap().as_secs_f64();
+        let p95 = stats.quantile(0.95).unwrap().as_secs_f64();
+        let snapshot = stats.snapshot("my_model");
+
+        // Assert
+        assert!((p50 - 0.050).abs() <= 0.050 * RELATIVE_ACCURACY);
+        assert!((p95 - 0.095).abs() <= 0.095 * RELATIVE_ACCURACY);
+        assert_eq!(snapshot.predictions, 101);
+        assert_eq!(snapshot.errors, 1);
+        assert_eq!(snapshot.recent_predictions, 101);
+        assert_eq!(snapshot.recent_errors, 1);
+        assert_eq!(snapshot.latency_quantiles.len(), REPORTED_QUANTILES.len());
+    }
+
+    #[test]
+    fn successfully_skip_quantiles_of_too_few_predictions() {
+        // Arrange
+        let stats = ModelStats::default();
+        for _ in 0..MIN_RECENT_LATENCIES - 1 {
+            stats.record_latency(Duration::from_millis(5));
+        }
+
+        // Act
+        let p95 = stats.quantile(0.95);
+        let snapshot = stats.snapshot("my_model");
+
+        // Assert
+        assert!(p95.is_none());
+        assert!(snapshot.latency_quantiles.is_empty());
+    }
+
+    #[test]
+    fn successfully_clamp_latencies_to_tracked_range() {
+        // Arrange
+        let stats = ModelStats::default();
+        for _ in 0..MIN_RECENT_LATENCIES {
+            stats.record_latency(Duration::from_secs(1000));
+            stats.record_latency(Duration::ZERO);
+        }
+
+        // Act
+        let max = stats.quantile(1.0).unwrap();
+        let min = stats.quantile(0.0).unwrap();
+
+        // Assert
+        assert!(max.as_secs_f64() <= MAX_TRACKED_LATENCY.as_secs_f64() * (1.0 + RELATIVE_ACCURACY));
+        assert_eq!(min, Duration::from_micros(1));
+    }
+
+    #[test]
+    fn successfully_record_concurrently() {
+        // Arrange
+        let stats = ModelStats::default();
+
+        // Act
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        stats.record_latency(Duration::from_millis(3));
+                    }
+                });
+            }
+        });
+
+        // Assert
+        assert_eq!(stats.snapshot("my_model").predictions, 4000);
+        let p99 = stats.quantile(0.99).unwrap().as_secs_f64();
+        assert!((p99 - 0.003).abs() <= 0.003 * RELATIVE_ACCURACY);
+    }
+}
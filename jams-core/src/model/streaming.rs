@@ -0,0 +1,322 @@
+use crate::model::input::ModelInput;
+use crate::model::output::ModelOutput;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Name of the optional file in a model tarball which makes the model a sequence model, whose generated tokens
+/// are streamed to the client one by one.
+pub const GENERATION_FILE_NAME: &str = "generation.json";
+
+/// Key of the output holding the id of each generated token.
+pub const TOKEN_ID_OUTPUT_KEY: &str = "token_id";
+
+/// Default maximum number of tokens generated for a prompt.
+pub const DEFAULT_MAX_NEW_TOKENS: usize = 64;
+
+/// Maximum number of tokens generated for a prompt, so that a model which never emits its end of sequence token
+/// does not hold a worker forever.
+pub const MAX_NEW_TOKENS: usize = 4096;
+
+/// Default integer feature holding the ids of the tokens of the prompt.
+pub const DEFAULT_INPUT_FEATURE: &str = "input_ids";
+
+/// How a sequence model generates its output, read from the `generation.json` file at the root of the tarball.
+///
+/// A decoder-only model is called with the ids of the tokens of the prompt, followed by the tokens generated so far,
+/// as a `[1, sequence_length]` tensor of `int64`. An encoder-decoder model, i.e. one with a `decoder_start_token_id`,
+/// encodes the prompt once and is then called with the ids of the decoder start token, followed by the tokens
+/// generated so far. Either returns the logits of the next token, as a `[1, vocab_size]` tensor or as a
+/// `[1, sequence_length, vocab_size]` tensor of which the last position is used. The most likely token is generated,
+/// until the end of sequence token or `max_new_tokens`.
+///
+/// # Example
+/// ```json
+/// { "input_feature": "input_ids", "max_new_tokens": 32, "eos_token_id": 2, "decoder_start_token_id": 0 }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct GenerationConfig {
+    /// The integer feature holding the ids of the tokens of the prompt. Defaults to `input_ids`.
+    #[serde(default = "default_input_feature")]
+    pub input_feature: String,
+    /// The maximum number of tokens generated for a prompt. Defaults to 64.
+    #[serde(default = "default_max_new_tokens")]
+    pub max_new_tokens: usize,
+    /// The id of the token which ends the sequence, which is streamed before the generation stops.
+    #[serde(default)]
+    pub eos_token_id: Option<i64>,
+    /// The id of the token the decoder of an encoder-decoder model starts from. `None` for decoder-only models.
+    #[serde(default)]
+    pub decoder_start_token_id: Option<i64>,
+}
+
+fn default_input_feature() -> String {
+    DEFAULT_INPUT_FEATURE.to_string()
+}
+
+fn default_max_new_tokens() -> usize {
+    DEFAULT_MAX_NEW_TOKENS
+}
+
+impl Default for GenerationConfig {
+    fn default() -> Self {
+        GenerationConfig {
+            input_feature: default_input_feature(),
+            max_new_tokens: default_max_new_tokens(),
+            eos_token_id: None,
+            decoder_start_token_id: None,
+        }
+    }
+}
+
+impl GenerationConfig {
+    /// Parses the contents of a `generation.json` file.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the contents are not a valid generation config.
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let config: GenerationConfig = match serde_json::from_str(contents) {
+            Ok(config) => config,
+            Err(e) => anyhow::bail!("Failed to parse {} ❌: {}", GENERATION_FILE_NAME, e),
+        };
+        if config.max_new_tokens == 0 || config.max_new_tokens > MAX_NEW_TOKENS {
+            anyhow::bail!(
+                "max_new_tokens in {} must be between 1 and {} ❌",
+                GENERATION_FILE_NAME,
+                MAX_NEW_TOKENS
+            )
+        }
+        Ok(config)
+    }
+
+    /// Returns true if the model encodes the prompt once and steps its decoder, instead of generating the tokens
+    /// following the prompt as one sequence.
+    pub fn is_encoder_decoder(&self) -> bool {
+        self.decoder_start_token_id.is_some()
+    }
+
+    /// Returns the sequence the generation starts from, i.e. the decoder start token of an encoder-decoder model
+    /// or the prompt of a decoder-only model.
+    pub fn start_sequence(&self, prompt: Vec<i64>) -> Vec<i64> {
+        match self.decoder_start_token_id {
+            Some(decoder_start_token_id) => vec![decoder_start_token_id],
+            None => prompt,
+        }
+    }
+
+    /// Returns the ids of the tokens of the prompt, i.e. the values of the input feature of the model input.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the input has no such integer feature or the prompt is empty.
+    pub fn prompt(&self, input: &ModelInput) -> anyhow::Result<Vec<i64>> {
+        let features = &input.integer_features;
+        let index = match features
+            .names
+            .iter()
+            .position(|name| name.as_str() == self.input_feature)
+        {
+            Some(index) => index,
+            None => anyhow::bail!(
+                "Sequence models expect the token ids of the prompt in the integer feature {} ❌",
+                self.input_feature
+            ),
+        };
+        let num_rows = features.shape.1;
+        let prompt: Vec<i64> = match features.values.as_ints() {
+            Some(values) => values[index * num_rows..(index + 1) * num_rows]
+                .iter()
+                .map(|value| *value as i64)
+                .collect(),
+            None => anyhow::bail!("Failed to read the token ids of the prompt ❌"),
+        };
+        if prompt.is_empty() {
+            anyhow::bail!("The prompt of a sequence model must have at least one token ❌")
+        }
+        Ok(prompt)
+    }
+
+    /// Generates the tokens following a sequence greedily, emitting each token as soon as it is generated.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - The sequence the generation starts from, see `start_sequence`.
+    /// * `next_token` - Returns the id of the most likely token following a sequence.
+    /// * `emit` - Called with the output of each generated token. Returns false once nobody is listening anymore,
+    ///   which stops the generation.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(crate::Error::Input)` - If the sequence is empty.
+    /// * `Err(crate::Error::Predict)` - If `next_token` fails.
+    pub fn generate(
+        &self,
+        mut sequence: Vec<i64>,
+        mut next_token: impl FnMut(&[i64]) -> crate::Result<i64>,
+        emit: &mut dyn FnMut(ModelOutput) -> bool,
+    ) -> crate::Result<()> {
+        if sequence.is_empty() {
+            return Err(crate::Error::Input(anyhow::anyhow!(
+                "The prompt of a sequence model must have at least one token ❌"
+            )));
+        }
+        for _ in 0..self.max_new_tokens {
+            let token = next_token(sequence.as_slice())?;
+            if !emit(token_output(token)) {
+                break;
+            }
+            if Some(token) == self.eos_token_id {
+                break;
+            }
+            sequence.push(token);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the output streamed for a generated token, i.e. `{"predictions": {"token_id": [[<id>]]}}`.
+pub fn token_output(token: i64) -> ModelOutput {
+    ModelOutput {
+        predictions: HashMap::from([(TOKEN_ID_OUTPUT_KEY.to_string(), vec![vec![token as f64]])]),
+        labels: HashMap::new(),
+        shapes: HashMap::new(),
+        texts: HashMap::new(),
+    }
+}
+
+/// Predictors which yield their outputs incrementally, e.g. the tokens generated by a sequence model, so that they
+/// can be streamed to the client instead of being returned once the whole output is ready.
+pub trait StreamingPredict: Send + Sync {
+    /// Makes a prediction, emitting each chunk of the output as soon as it is ready.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data for the model.
+    /// * `emit` - Called with each chunk of the output, in order. Returns false once nobody is listening anymore,
+    ///   in which case the predictor stops early.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is invalid for the model or a chunk cannot be predicted. The chunks emitted
+    /// before the error have already been delivered.
+    fn predict_stream(
+        &self,
+        input: ModelInput,
+        emit: &mut dyn FnMut(ModelOutput) -> bool,
+    ) -> crate::Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_generate_tokens_until_end_of_sequence() {
+        // Arrange
+        let config =
+            GenerationConfig::parse(r#"{"max_new_tokens": 10, "eos_token_id": 2}"#).unwrap();
+        let mut lengths = Vec::new();
+        let mut tokens = Vec::new();
+
+        // Act
+        let result = config.generate(
+            vec![7, 8],
+            |sequence| {
+                lengths.push(sequence.len());
+                Ok(match sequence.len() {
+                    2 => 5,
+                    3 => 6,
+                    _ => 2,
+                })
+            },
+            &mut |output| {
+                tokens.push(output.predictions[TOKEN_ID_OUTPUT_KEY][0][0]);
+                true
+            },
+        );
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(tokens, vec![5.0, 6.0, 2.0]);
+        assert_eq!(lengths, vec![2, 3, 4]);
+        assert_eq!(config.input_feature, DEFAULT_INPUT_FEATURE);
+    }
+
+    #[test]
+    fn successfully_stop_generating_tokens_when_nobody_listens() {
+        // Arrange
+        let config = GenerationConfig::default();
+        let mut calls = 0;
+
+        // Act
+        let result = config.generate(
+            vec![1],
+            |_| {
+                calls += 1;
+                Ok(3)
+            },
+            &mut |_| false,
+        );
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn successfully_start_decoder_of_encoder_decoder_model_from_its_start_token() {
+        // Arrange
+        let config = GenerationConfig::parse(
+            r#"{"max_new_tokens": 2, "eos_token_id": 1, "decoder_start_token_id": 0}"#,
+        )
+        .unwrap();
+        let decoder_only = GenerationConfig::default();
+        let mut sequences = Vec::new();
+
+        // Act
+        let result = config.generate(
+            config.start_sequence(vec![7, 8, 9]),
+            |sequence| {
+                sequences.push(sequence.to_vec());
+                Ok(4)
+            },
+            &mut |_| true,
+        );
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(config.is_encoder_decoder());
+        assert_eq!(sequences, vec![vec![0], vec![0, 4]]);
+        assert!(!decoder_only.is_encoder_decoder());
+        assert_eq!(decoder_only.start_sequence(vec![7, 8]), vec![7, 8]);
+    }
+
+    #[test]
+    fn fails_to_parse_invalid_generation_config() {
+        // Act
+        let zero = GenerationConfig::parse(r#"{"max_new_tokens": 0}"#);
+        let unknown = GenerationConfig::parse(r#"{"temperature": 0.7}"#);
+
+        // Assert
+        assert!(zero.is_err());
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn fails_to_read_prompt_without_input_feature() {
+        // Arrange
+        let config = GenerationConfig::default();
+        let input =
+            ModelInput::from_str(r#"{"input_ids": [4, 5, 6], "other": [1, 2, 3]}"#).unwrap();
+        let missing = ModelInput::from_str(r#"{"tokens": [4, 5, 6]}"#).unwrap();
+
+        // Act
+        let prompt = config.prompt(&input);
+        let missing = config.prompt(&missing);
+
+        // Assert
+        assert_eq!(prompt.unwrap(), vec![4, 5, 6]);
+        assert!(missing.is_err());
+    }
+}
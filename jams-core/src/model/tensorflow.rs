@@ -1,7 +1,9 @@
+use crate::determinism::determinism;
 use crate::model::input::{FeatureName, ModelInput};
+use crate::model::lifecycle::Lifecycle;
 use crate::model::output::ModelOutput;
 use crate::model::predict::Predict;
-use crate::FEATURE_NAMES_CAPACITY;
+use crate::{Error, Result, FEATURE_NAMES_CAPACITY};
 use std::collections::HashMap;
 use tensorflow::{
     DataType, FetchToken, Graph, Operation, SavedModelBundle, SessionOptions, SessionRunArgs,
@@ -11,6 +13,10 @@ use tensorflow::{
 // Arbitrary high number but in reality, the number is far less
 const MAX_OUTPUT_NODES_SUPPORTED: usize = 250;
 
+/// A serialized `ConfigProto` which sets `intra_op_parallelism_threads` and `inter_op_parallelism_threads`
+/// to 1, so that reductions run in a fixed order in the deterministic mode.
+const SINGLE_THREADED_CONFIG_PROTO: [u8; 4] = [0x10, 0x01, 0x28, 0x01];
+
 /// Struct representing the input tensors for a TensorFlow model.
 ///
 /// This struct encapsulates tensors grouped by their data type (`i32`, `f32`, `String`),
@@ -318,21 +324,29 @@ impl Tensorflow {
     ///
     /// # Returns
     /// * `Ok(Tensorflow)` - If the model loading was successful.
-    /// * `Err(anyhow::Error)` - If there was an error loading the model.
+    /// * `Err(Error::Load)` - If there was an error loading the model.
     #[tracing::instrument]
-    pub fn load(model_dir: &str) -> anyhow::Result<Self> {
+    pub fn load(model_dir: &str) -> Result<Self> {
         const MODEL_TAG: &str = "serve";
         let mut graph = Graph::new();
-        let bundle = match SavedModelBundle::load(
-            &SessionOptions::new(),
-            [MODEL_TAG],
-            &mut graph,
-            model_dir,
-        ) {
+        let mut options = SessionOptions::new();
+        if determinism().is_some() {
+            if let Err(e) = options.set_config(&SINGLE_THREADED_CONFIG_PROTO) {
+                tracing::error!("Failed to configure TensorFlow session: {}", e);
+                return Err(Error::Load(anyhow::anyhow!(
+                    "Failed to configure TensorFlow session: {}",
+                    e
+                )));
+            }
+        }
+        let bundle = match SavedModelBundle::load(&options, [MODEL_TAG], &mut graph, model_dir) {
             Ok(b) => b,
             Err(_) => {
                 tracing::error!("Failed to load TensorFlow model from dir: {}", model_dir);
-                anyhow::bail!("Failed to load TensorFlow model from dir: {}", model_dir);
+                return Err(Error::Load(anyhow::anyhow!(
+                    "Failed to load TensorFlow model from dir: {}",
+                    model_dir
+                )));
             }
         };
 
@@ -346,10 +360,10 @@ impl Tensorflow {
                     "Failed to get model signature for {}",
                     DEFAULT_SERVING_SIGNATURE_DEF_KEY
                 );
-                anyhow::bail!(
+                return Err(Error::Load(anyhow::anyhow!(
                     "Failed to get model signature for {}",
                     DEFAULT_SERVING_SIGNATURE_DEF_KEY
-                )
+                )));
             }
         };
 
@@ -368,6 +382,8 @@ impl Tensorflow {
     }
 }
 
+impl Lifecycle for Tensorflow {}
+
 impl Predict for Tensorflow {
     /// Performs prediction using the TensorFlow model.
     ///
@@ -376,11 +392,12 @@ impl Predict for Tensorflow {
     ///
     /// # Returns
     /// * `Ok(Output)` - If prediction was successful, containing the predicted output.
-    /// * `Err(anyhow::Error)` - If there was an error during prediction.
+    /// * `Err(Error::Predict)` - If there was an error during prediction.
     #[tracing::instrument(skip(self, input))]
-    fn predict(&self, input: ModelInput) -> anyhow::Result<ModelOutput> {
+    fn predict(&self, input: ModelInput) -> Result<ModelOutput> {
         // Parse input into TensorFlow model input format
-        let input = TensorflowModelInput::parse(input, &self.signature_def, &self.graph)?;
+        let input = TensorflowModelInput::parse(input, &self.signature_def, &self.graph)
+            .map_err(Error::Input)?;
 
         // Create session run arguments
         let mut run_args = SessionRunArgs::new();
@@ -403,7 +420,8 @@ impl Predict for Tensorflow {
         for output_def in self.signature_def.outputs() {
             let output_operation = self
                 .graph
-                .operation_by_name_required(&output_def.1.name().name)?;
+                .operation_by_name_required(&output_def.1.name().name)
+                .map_err(|e| Error::Predict(e.into()))?;
             let fetch_token = run_args.request_fetch(&output_operation, output_def.1.name().index);
             fetch_tokens.push(fetch_token);
         }
@@ -413,21 +431,34 @@ impl Predict for Tensorflow {
             Ok(_) => {}
             Err(_) => {
                 tracing::error!("Failed to execute TensorFlow graph");
-                anyhow::bail!("Failed to execute TensorFlow graph")
+                return Err(Error::Predict(anyhow::anyhow!(
+                    "Failed to execute TensorFlow graph"
+                )));
             }
         };
 
         // Retrieve and process the output tensors
         let mut predictions: HashMap<String, Vec<Vec<f64>>> = HashMap::new();
+        let mut shapes: HashMap<String, Vec<usize>> = HashMap::new();
 
         for (i, token) in fetch_tokens.into_iter().enumerate() {
-            let output: Tensor<f32> = run_args.fetch(token)?;
+            let output: Tensor<f32> = run_args
+                .fetch(token)
+                .map_err(|e| Error::Predict(e.into()))?;
 
             // model output can have a scaler or nd-array output. Currently, only 2D is supported
             if output.dims().len() > 2 {
-                anyhow::bail!("Only 2D shapes are supported in output nodes !")
+                return Err(Error::Predict(anyhow::anyhow!(
+                    "Only 2D shapes are supported in output nodes !"
+                )));
             }
 
+            // keep the shape of the tensor, so that a scalar is not mistaken for a single row
+            shapes.insert(
+                self.output_names.get(i).unwrap().to_string(),
+                output.dims().iter().map(|dim| *dim as usize).collect(),
+            );
+
             // handle non scaler output - is_empty() is true for scalar values
             if !output.dims().is_empty() {
                 let processed_output: Vec<Vec<f32>> = output
@@ -458,7 +489,9 @@ impl Predict for Tensorflow {
                 let scalar_value = match scalar_value_vec.first() {
                     None => {
                         tracing::error!("Failed to fetch scaler value from output");
-                        anyhow::bail!("Failed to fetch scaler value from output")
+                        return Err(Error::Predict(anyhow::anyhow!(
+                            "Failed to fetch scaler value from output"
+                        )));
                     }
                     Some(scaler_value) => scaler_value,
                 };
@@ -469,7 +502,12 @@ impl Predict for Tensorflow {
             }
         }
 
-        Ok(ModelOutput { predictions })
+        Ok(ModelOutput {
+            predictions,
+            labels: HashMap::new(),
+            shapes,
+            texts: HashMap::new(),
+        })
     }
 }
 
@@ -587,4 +625,43 @@ mod tests {
         // model with 3 classes
         assert_eq!(predictions.first().unwrap().len(), 3);
     }
+
+    #[test]
+    fn successfully_return_one_prediction_per_row_using_tensorflow_regression_model() {
+        let model_dir = "tests/model_storage/models/tensorflow-my_awesome_autompg_model";
+        let model = Tensorflow::load(model_dir).unwrap();
+
+        // asserts there is one prediction per input row for any valid input
+        let result = test_utils::utils::check_prediction_shapes(
+            &model,
+            test_utils::utils::model_input_strategy(9, 0, false),
+        );
+
+        // assert the shape invariants hold for every generated input
+        assert!(result.is_ok(), "{:?}", result)
+    }
+
+    #[test]
+    fn successfully_return_one_prediction_per_row_using_tensorflow_multi_class_classification_model(
+    ) {
+        let model_dir = "tests/model_storage/models/tensorflow-my_awesome_sequential_model";
+        let model = Tensorflow::load(model_dir).unwrap();
+
+        // asserts there is one prediction per input row for any valid input
+        let result = test_utils::utils::check_prediction_shapes(
+            &model,
+            test_utils::utils::model_input_strategy(6, 0, false),
+        );
+
+        // assert the shape invariants hold for every generated input
+        assert!(result.is_ok(), "{:?}", result)
+    }
+
+    #[test]
+    fn successfully_match_golden_outputs_using_tensorflow_models() {
+        test_utils::golden::check_golden(
+            "tensorflow-my_awesome_sequential_model",
+            Tensorflow::load,
+        );
+    }
 }
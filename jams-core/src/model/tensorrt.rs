@@ -0,0 +1,445 @@
+use crate::model::input::ModelInput;
+use crate::model::lifecycle::Lifecycle;
+use crate::model::output::{ModelOutput, DEFAULT_OUTPUT_KEY};
+use crate::model::predict::{Predict, PredictOptions};
+use crate::{Error, Result};
+use async_cuda::{DeviceBuffer, HostBuffer, Stream};
+use async_tensorrt::{Engine, ExecutionContext, Runtime, TensorIoMode};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+/// Struct representing the input data format for a TensorRT engine.
+///
+/// TensorRT reads its input tensor in row-major order, whereas the features of a `ModelInput` are stored one
+/// column after another, so the features are transposed into a single buffer.
+struct TensorRTModelInput {
+    /// Numerical features in row-major order.
+    pub values: Vec<f32>,
+    /// The number of rows in the input.
+    pub num_rows: usize,
+}
+
+impl TensorRTModelInput {
+    /// Parses the input `ModelInput` into a `TensorRTModelInput`.
+    ///
+    /// The float features come first, followed by the integer features, as for XGBoost models. The features of
+    /// a row are the flattened input tensor of one sample, e.g. the pixels of an image.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_input` - The `ModelInput` containing the input values.
+    /// * `row_len` - The number of values of one sample of the input tensor of the engine.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the input is empty, contains string features or if the number of features does not
+    /// match the input tensor of the engine.
+    #[tracing::instrument(skip(model_input))]
+    pub fn parse(model_input: ModelInput, row_len: usize) -> anyhow::Result<Self> {
+        if (model_input.integer_features.values.is_empty())
+            && (model_input.float_features.values.is_empty())
+        {
+            tracing::error!("input is empty");
+            anyhow::bail!("input is empty")
+        }
+        if !model_input.string_features.values.is_empty() {
+            tracing::error!("TensorRT engines do not support string features");
+            anyhow::bail!("TensorRT engines do not support string features")
+        }
+
+        let num_rows = model_input.num_rows();
+        let floats = model_input.float_features.values.into_floats().unwrap();
+        let ints = model_input.integer_features.values.into_ints().unwrap();
+        let num_features = (floats.len() + ints.len()) / num_rows.max(1);
+        if num_features != row_len {
+            tracing::error!(
+                "Expected {} features per row for the TensorRT engine, got {}",
+                row_len,
+                num_features
+            );
+            anyhow::bail!(
+                "Expected {} features per row for the TensorRT engine, got {}",
+                row_len,
+                num_features
+            )
+        }
+        let num_float_features = floats.len() / num_rows.max(1);
+
+        // transpose the columns into rows, converting the integer features to float
+        let mut values = Vec::with_capacity(num_rows * num_features);
+        for row in 0..num_rows {
+            for feature in 0..num_float_features {
+                values.push(floats[feature * num_rows + row]);
+            }
+            for feature in 0..num_features - num_float_features {
+                values.push(ints[feature * num_rows + row] as f32);
+            }
+        }
+
+        Ok(Self { values, num_rows })
+    }
+}
+
+/// An input or output tensor of a TensorRT engine.
+#[derive(Debug, Clone, PartialEq)]
+struct IoTensor {
+    name: String,
+    /// The number of values of one sample, i.e. the product of the dimensions after the batch dimension.
+    row_len: usize,
+}
+
+impl IoTensor {
+    /// Describes a tensor of the engine from its shape, whose first dimension is the batch size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tensor is a scalar or empty, or if its batch size does not match `batch_size`.
+    fn new(name: String, shape: &[usize], batch_size: usize) -> anyhow::Result<Self> {
+        let (batch, sample) = match shape.split_first() {
+            Some(split) => split,
+            None => anyhow::bail!(
+                "Tensor {} of the TensorRT engine has no batch dimension",
+                name
+            ),
+        };
+        if *batch != batch_size {
+            anyhow::bail!(
+                "Tensor {} of the TensorRT engine has a batch size of {}, expected {}",
+                name,
+                batch,
+                batch_size
+            )
+        }
+        let row_len = sample.iter().product();
+        if row_len == 0 {
+            anyhow::bail!("Tensor {} of the TensorRT engine is empty", name)
+        }
+        Ok(IoTensor { name, row_len })
+    }
+}
+
+/// The CUDA resources of an engine, which are used by one prediction at a time.
+struct Session {
+    context: ExecutionContext<'static>,
+    stream: Stream,
+}
+
+/// Struct representing a predictor using a TensorRT engine.
+///
+/// The engine is loaded from a serialized plan (`.engine`) built for the GPU of the host, e.g. by `trtexec`.
+/// It must have a single float input tensor and float output tensors, all with the same fixed batch size as
+/// their first dimension. Inputs are split into batches of that size, padding the last one, and the
+/// predictions of a model are made one batch at a time. Predictions wait for the GPU on the worker which makes
+/// them, see `wait_for`.
+pub struct TensorRT {
+    session: Mutex<Session>,
+    batch_size: usize,
+    input: IoTensor,
+    outputs: Vec<IoTensor>,
+}
+
+impl TensorRT {
+    /// Loads a TensorRT engine from the specified file path. It waits for the device, so it is meant to be called
+    /// on a blocking thread, see `wait_for`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A string slice that holds the path to the serialized engine.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the engine cannot be read or deserialized, or if its tensors are not supported.
+    #[tracing::instrument]
+    pub fn load(path: &str) -> Result<Self> {
+        match wait_for(load_engine(path)) {
+            Ok(predictor) => Ok(predictor),
+            Err(e) => {
+                tracing::error!("Failed to load TensorRT engine from file {}: {}", path, e);
+                Err(Error::Load(anyhow::anyhow!(
+                    "Failed to load TensorRT engine from file {}: {}",
+                    path,
+                    e
+                )))
+            }
+        }
+    }
+
+    /// Runs the engine on the rows of the input, one batch at a time.
+    async fn infer(
+        &self,
+        session: &mut Session,
+        input: &TensorRTModelInput,
+    ) -> anyhow::Result<Vec<Vec<Vec<f64>>>> {
+        let Session { context, stream } = session;
+
+        let mut outputs: Vec<Vec<Vec<f64>>> =
+            vec![Vec::with_capacity(input.num_rows); self.outputs.len()];
+        for batch in input.values.chunks(self.batch_size * self.input.row_len) {
+            let num_rows = batch.len() / self.input.row_len;
+            let padded = pad_batch(batch, self.batch_size * self.input.row_len);
+            let mut input_buffer = DeviceBuffer::from_slice(&padded, stream).await?;
+            let mut output_buffers = Vec::with_capacity(self.outputs.len());
+            for output in &self.outputs {
+                output_buffers
+                    .push(DeviceBuffer::<f32>::new(self.batch_size * output.row_len, stream).await);
+            }
+
+            let mut io_buffers = HashMap::with_capacity(self.outputs.len() + 1);
+            io_buffers.insert(self.input.name.as_str(), &mut input_buffer);
+            for (output, buffer) in self.outputs.iter().zip(output_buffers.iter_mut()) {
+                io_buffers.insert(output.name.as_str(), buffer);
+            }
+            context.enqueue(&mut io_buffers, stream).await?;
+
+            for ((output, buffer), rows) in self
+                .outputs
+                .iter()
+                .zip(output_buffers.iter())
+                .zip(outputs.iter_mut())
+            {
+                let mut host_buffer = HostBuffer::<f32>::new(buffer.num_elements()).await;
+                buffer.copy_to(&mut host_buffer, stream).await?;
+                rows.extend(unpad_rows(&host_buffer.to_vec(), num_rows, output.row_len));
+            }
+        }
+        Ok(outputs)
+    }
+}
+
+/// Waits for a future of the CUDA runtime on the current thread, e.g. a worker of the worker pool.
+///
+/// The futures of `async-cuda` are completed by its own runtime thread, so the current thread only waits for them.
+/// On a worker of a multi-threaded tokio runtime, the worker first hands its other tasks over to the other workers,
+/// so that they are not stalled while it waits.
+fn wait_for<F: Future>(future: F) -> F::Output {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| futures::executor::block_on(future))
+        }
+        _ => futures::executor::block_on(future),
+    }
+}
+
+/// Deserializes an engine and describes its tensors.
+async fn load_engine(path: &str) -> anyhow::Result<TensorRT> {
+    let plan = std::fs::read(path)?;
+    let runtime = Runtime::new().await;
+    let engine: Engine = runtime.deserialize_engine(&plan).await?;
+
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    for index in 0..engine.num_io_tensors() {
+        let name = engine.io_tensor_name(index);
+        let shape = engine.tensor_shape(&name);
+        match engine.tensor_io_mode(&name) {
+            TensorIoMode::Input => inputs.push((name, shape)),
+            _ => outputs.push((name, shape)),
+        }
+    }
+    let (input_name, input_shape) = match inputs.as_slice() {
+        [input] => input.clone(),
+        _ => anyhow::bail!("expected a single input tensor, found {}", inputs.len()),
+    };
+    if outputs.is_empty() {
+        anyhow::bail!("the engine has no output tensors")
+    }
+    let batch_size = input_shape.first().copied().unwrap_or_default();
+    if batch_size == 0 {
+        anyhow::bail!("the input tensor {} has no fixed batch size", input_name)
+    }
+    let input = IoTensor::new(input_name, &input_shape, batch_size)?;
+    let outputs = outputs
+        .into_iter()
+        .map(|(name, shape)| IoTensor::new(name, &shape, batch_size))
+        .collect::<anyhow::Result<Vec<IoTensor>>>()?;
+
+    let context = ExecutionContext::from_engine(engine).await?;
+    let stream = Stream::new().await?;
+    Ok(TensorRT {
+        session: Mutex::new(Session { context, stream }),
+        batch_size,
+        input,
+        outputs,
+    })
+}
+
+/// Pads the last batch of an input with zeros up to the fixed batch size of the engine.
+fn pad_batch(batch: &[f32], len: usize) -> Vec<f32> {
+    let mut padded = Vec::with_capacity(len);
+    padded.extend_from_slice(batch);
+    padded.resize(len, 0.0);
+    padded
+}
+
+/// Splits the output of a batch into rows, dropping the rows of the padding.
+fn unpad_rows(values: &[f32], num_rows: usize, row_len: usize) -> Vec<Vec<f64>> {
+    values
+        .chunks(row_len.max(1))
+        .take(num_rows)
+        .map(|row| row.iter().map(|x| *x as f64).collect())
+        .collect()
+}
+
+impl Lifecycle for TensorRT {}
+
+impl Predict for TensorRT {
+    /// Performs prediction using the loaded TensorRT engine.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The `ModelInput` containing the input values for prediction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if there is an issue parsing the input or performing the prediction.
+    #[tracing::instrument(skip(self, input))]
+    fn predict(&self, input: ModelInput) -> Result<ModelOutput> {
+        self.predict_with_options(input, &PredictOptions::default())
+    }
+
+    /// Performs prediction using the loaded TensorRT engine and the given prediction options.
+    ///
+    /// An engine with a single output tensor returns it as the `predictions` output, otherwise each output
+    /// is named after its tensor. Neither `mode` nor `num_iteration` is supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The `ModelInput` containing the input values for prediction.
+    /// * `options` - The prediction options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the options are not supported, or if there is an issue parsing the input or
+    /// performing the prediction.
+    #[tracing::instrument(skip(self, input))]
+    fn predict_with_options(
+        &self,
+        input: ModelInput,
+        options: &PredictOptions,
+    ) -> Result<ModelOutput> {
+        options.validate().map_err(Error::Input)?;
+        if options.mode.is_some() || options.num_iteration.is_some() {
+            return Err(Error::Input(anyhow::anyhow!(
+                "mode and num_iteration are not supported by TensorRT engines ❌"
+            )));
+        }
+        let input = TensorRTModelInput::parse(input, self.input.row_len).map_err(Error::Input)?;
+
+        let mut session = match self.session.lock() {
+            Ok(session) => session,
+            Err(_) => {
+                return Err(Error::Predict(anyhow::anyhow!(
+                    "Failed to make predictions using TensorRT: the session is poisoned"
+                )))
+            }
+        };
+        let outputs = wait_for(self.infer(&mut session, &input));
+        drop(session);
+
+        match outputs {
+            Ok(outputs) => {
+                let mut predictions: HashMap<String, Vec<Vec<f64>>> = HashMap::new();
+                for (output, values) in self.outputs.iter().zip(outputs) {
+                    let output_key = match self.outputs.len() {
+                        1 => DEFAULT_OUTPUT_KEY.to_string(),
+                        _ => output.name.clone(),
+                    };
+                    predictions.insert(output_key, values);
+                }
+                Ok(ModelOutput {
+                    predictions,
+                    labels: HashMap::new(),
+                    shapes: HashMap::new(),
+                    texts: HashMap::new(),
+                })
+            }
+            Err(e) => {
+                tracing::error!("Failed to make predictions using TensorRT: {}", e);
+                Err(Error::Predict(anyhow::anyhow!(
+                    "Failed to make predictions using TensorRT: {}",
+                    e
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_utils;
+
+    #[test]
+    fn fails_to_load_tensorrt_engine() {
+        let model_dir = "incorrect/path";
+        let model = TensorRT::load(model_dir);
+
+        // assert the result is Err
+        assert!(model.is_err())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn successfully_wait_for_future_on_a_worker_of_the_runtime() {
+        // wait without awaiting, as a model loaded or predicting on a worker of the runtime does
+        let value = wait_for(async { 42 });
+
+        // assert
+        assert_eq!(value, 42);
+    }
+
+    #[test]
+    fn successfully_transpose_tensorrt_model_input_into_rows() {
+        let mut model_input = ModelInput::default();
+        model_input.float_features.values = crate::model::input::Values::Float(vec![
+            0.1, 0.2, // first float feature
+            1.1, 1.2, // second float feature
+        ]);
+        model_input.float_features.shape = (2, 2);
+        model_input.integer_features.values = crate::model::input::Values::Int(vec![7, 8]);
+        model_input.integer_features.shape = (1, 2);
+
+        // parse the input
+        let input = TensorRTModelInput::parse(model_input, 3).unwrap();
+
+        // assert each row holds the float features followed by the integer features
+        assert_eq!(input.num_rows, 2);
+        assert_eq!(input.values, vec![0.1, 1.1, 7.0, 0.2, 1.2, 8.0]);
+    }
+
+    #[test]
+    fn fails_to_parse_tensorrt_model_input_when_features_do_not_match_the_engine() {
+        let model_inputs = test_utils::utils::create_model_inputs(2, 0, 3);
+
+        // parse the input
+        let input = TensorRTModelInput::parse(model_inputs, 3 * 224 * 224);
+
+        // assert the result is an error
+        assert!(input.is_err());
+    }
+
+    #[test]
+    fn successfully_describe_tensors_of_an_engine_with_fixed_batch_size() {
+        // describe an image input and a mismatched output
+        let input = IoTensor::new("images".to_string(), &[8, 3, 224, 224], 8).unwrap();
+        let output = IoTensor::new("logits".to_string(), &[4, 1000], 8);
+        let scalar = IoTensor::new("scalar".to_string(), &[], 8);
+
+        // assert
+        assert_eq!(input.row_len, 3 * 224 * 224);
+        assert!(output.is_err());
+        assert!(scalar.is_err());
+    }
+
+    #[test]
+    fn successfully_pad_and_unpad_the_last_batch() {
+        // pad 2 rows of 2 values to a batch of 4 rows
+        let padded = pad_batch(&[1.0, 2.0, 3.0, 4.0], 8);
+        let rows = unpad_rows(&padded, 2, 2);
+
+        // assert the padding is dropped from the output
+        assert_eq!(padded, vec![1.0, 2.0, 3.0, 4.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(rows, vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+    }
+}
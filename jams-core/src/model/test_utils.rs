@@ -1,9 +1,26 @@
-#[cfg(test)]
+#[cfg(any(test, feature = "bench"))]
 pub mod utils {
     use crate::model::input::ModelInput;
+    #[cfg(test)]
+    use crate::model::output::ModelOutput;
+    #[cfg(test)]
+    use crate::model::predict::Predict;
+    #[cfg(test)]
+    use proptest::collection::vec;
+    #[cfg(test)]
+    use proptest::prelude::*;
+    #[cfg(test)]
+    use proptest::test_runner::{Config, TestCaseError, TestError, TestRunner};
     use rand::Rng;
 
+    /// Maximum number of rows generated by `model_input_strategy`.
     #[cfg(test)]
+    const MAX_STRATEGY_ROWS: usize = 64;
+
+    /// Number of inputs generated by `check_prediction_shapes`.
+    #[cfg(test)]
+    const PREDICTION_SHAPE_CASES: u32 = 32;
+
     pub fn create_model_inputs(
         num_numeric_features: usize,
         num_string_features: usize,
@@ -55,7 +72,6 @@ pub mod utils {
         model_input
     }
 
-    #[cfg(test)]
     pub fn create_model_inputs_with_names(
         numeric_features_names: Vec<String>,
         string_features_names: Vec<String>,
@@ -104,4 +120,172 @@ pub mod utils {
 
         model_input
     }
+
+    /// Creates a columnar JSON payload with random values which can be parsed using `ModelInput::from_str`.
+    pub fn create_model_input_json(
+        num_numeric_features: usize,
+        num_string_features: usize,
+        size: usize,
+    ) -> String {
+        let mut rng = rand::thread_rng();
+        let mut payload = serde_json::Map::new();
+
+        for i in 0..num_string_features {
+            let values: Vec<serde_json::Value> = (0..size)
+                .map(|_| serde_json::Value::from(format!("{}", rng.gen_range(0..5))))
+                .collect();
+            payload.insert(
+                format!("string_feature_{}", i),
+                serde_json::Value::from(values),
+            );
+        }
+
+        for i in 0..num_numeric_features {
+            let values: Vec<serde_json::Value> = (0..size)
+                .map(|_| serde_json::Value::from(rng.gen::<f32>() as f64))
+                .collect();
+            payload.insert(
+                format!("numeric_feature_{}", i),
+                serde_json::Value::from(values),
+            );
+        }
+
+        serde_json::Value::Object(payload).to_string()
+    }
+
+    /// Creates a proptest strategy which generates valid `ModelInput`s with between 1 and
+    /// `MAX_STRATEGY_ROWS` rows.
+    ///
+    /// When `allow_integers` is set, each numeric feature is randomly generated as either a float
+    /// or an integer feature.
+    #[cfg(test)]
+    pub fn model_input_strategy(
+        num_numeric_features: usize,
+        num_string_features: usize,
+        allow_integers: bool,
+    ) -> impl Strategy<Value = ModelInput> {
+        (1..=MAX_STRATEGY_ROWS)
+            .prop_flat_map(move |num_rows| {
+                (
+                    vec(any::<bool>(), num_numeric_features),
+                    vec(vec(-1000.0f32..1000.0f32, num_rows), num_numeric_features),
+                    vec(
+                        vec(
+                            prop::sample::select(vec!["a", "b", "c", "d", "e"]),
+                            num_rows,
+                        ),
+                        num_string_features,
+                    ),
+                )
+            })
+            .prop_map(move |(is_integer, numeric_values, string_values)| {
+                let mut model_input = ModelInput::default();
+
+                // create numeric features
+                for (i, values) in numeric_values.into_iter().enumerate() {
+                    let num_rows = values.len();
+                    let features = if allow_integers && is_integer[i] {
+                        model_input
+                            .integer_features
+                            .values
+                            .extend(values.into_iter().map(|v| v as i32));
+                        &mut model_input.integer_features
+                    } else {
+                        model_input.float_features.values.extend(values);
+                        &mut model_input.float_features
+                    };
+                    features.names.push(format!("numeric_feature_{}", i));
+                    features.shape.0 += 1;
+                    features.shape.1 = num_rows;
+                }
+
+                // create string features
+                for (i, values) in string_values.into_iter().enumerate() {
+                    let num_rows = values.len();
+                    model_input
+                        .string_features
+                        .values
+                        .extend(values.into_iter().map(|v| v.to_string()));
+                    model_input
+                        .string_features
+                        .names
+                        .push(format!("string_feature_{}", i));
+                    model_input.string_features.shape.0 += 1;
+                    model_input.string_features.shape.1 = num_rows;
+                }
+
+                model_input
+            })
+    }
+
+    /// Predicts the inputs generated by the strategy and checks the shape of every output with
+    /// `check_output_shape`.
+    #[cfg(test)]
+    pub fn check_prediction_shapes<P: Predict>(
+        model: &P,
+        strategy: impl Strategy<Value = ModelInput>,
+    ) -> Result<(), TestError<ModelInput>> {
+        let mut runner = TestRunner::new(Config::with_cases(PREDICTION_SHAPE_CASES));
+        runner.run(&strategy, |model_inputs| {
+            let num_rows = model_inputs.num_rows();
+            let output = model
+                .predict(model_inputs)
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            check_output_shape(&output, num_rows)
+        })
+    }
+
+    /// Checks that every output has one prediction per input row and that all the rows of an
+    /// output have the same, non-zero, number of values.
+    #[cfg(test)]
+    pub fn check_output_shape(output: &ModelOutput, num_rows: usize) -> Result<(), TestCaseError> {
+        prop_assert!(!output.predictions.is_empty());
+        for (key, values) in output.predictions.iter() {
+            prop_assert_eq!(
+                values.len(),
+                num_rows,
+                "unexpected number of rows for {}",
+                key
+            );
+            let num_columns = values[0].len();
+            prop_assert!(num_columns > 0, "empty predictions for {}", key);
+            prop_assert!(
+                values.iter().all(|row| row.len() == num_columns),
+                "inconsistent number of values per row for {}",
+                key
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Golden output tests for the predictors.
+///
+/// Each fixture in `tests/model_storage/golden` pairs an input with the outputs a sample model is expected
+/// to return for it, so that framework upgrades which shift the outputs of a model are caught.
+/// Run the tests with `JAMS_UPDATE_GOLDEN=1` to record the current outputs as the expected outputs
+/// after reviewing the change.
+#[cfg(any(test, feature = "test-utils"))]
+pub mod golden {
+    use crate::model::predict::Predict;
+    // the fixtures and their checks are defined in jams-predictor-api, so that predictors built outside of
+    // jams-core are tested the same way
+    pub use jams_predictor_api::golden::{
+        check_golden_file, compare_outputs, GoldenFixture, UPDATE_GOLDEN_ENV,
+    };
+
+    /// Directory holding the golden fixtures, relative to the crate root.
+    pub const GOLDEN_DIRECTORY: &str = "tests/model_storage/golden";
+
+    /// Runs the golden fixture with the given name against the predictor loaded by `load`.
+    ///
+    /// # Panics
+    /// If the fixture cannot be read, the prediction fails or the outputs do not match.
+    pub fn check_golden<P, F>(name: &str, load: F)
+    where
+        P: Predict,
+        F: Fn(&str) -> crate::Result<P>,
+    {
+        check_golden_file(&format!("{}/{}.json", GOLDEN_DIRECTORY, name), load)
+    }
 }
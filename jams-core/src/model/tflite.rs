@@ -0,0 +1,261 @@
+use crate::model::dense::{self, DenseInput, DenseTensor};
+use crate::model::input::ModelInput;
+use crate::model::lifecycle::Lifecycle;
+use crate::model::output::ModelOutput;
+use crate::model::predict::{Predict, PredictOptions};
+use crate::model::sessions::SessionPool;
+use crate::{Error, Result};
+use tflite::context::ElementKind;
+use tflite::ops::builtin::BuiltinOpResolver;
+use tflite::{FlatBufferModel, Interpreter, InterpreterBuilder};
+
+/// Number of threads of the interpreter. Predictions already run on the worker pool, one per worker, so the
+/// interpreter does not spawn threads of its own.
+const NUM_THREADS: i32 = 1;
+
+/// Name of the framework in error messages.
+const FRAMEWORK_NAME: &str = "TensorFlow Lite";
+
+/// An input or output tensor of a TensorFlow Lite model and its index in the interpreter.
+#[derive(Debug, Clone, PartialEq)]
+struct IoTensor {
+    index: i32,
+    tensor: DenseTensor,
+}
+
+/// Struct representing a predictor using a TensorFlow Lite model.
+///
+/// The model is loaded from a FlatBuffer file (`.tflite`), e.g. exported with `tf.lite.TFLiteConverter` for edge
+/// devices, and run with the TensorFlow Lite interpreter and its builtin ops. It must have a single float input
+/// tensor and float output tensors, all with the same batch size as their first dimension. Quantized models must
+/// keep float inputs and outputs. Inputs are split into batches of that size, padding the last one.
+///
+/// An interpreter runs one prediction at a time, so each worker predicting with the model gets an interpreter of
+/// its own from a pool, built from the bytes of the model read at load.
+pub struct TfLite {
+    model: Vec<u8>,
+    interpreters: SessionPool<Interpreter<'static, BuiltinOpResolver>>,
+    batch_size: usize,
+    input: IoTensor,
+    outputs: Vec<IoTensor>,
+}
+
+impl TfLite {
+    /// Loads a TensorFlow Lite model from the specified file path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A string slice that holds the path to the `.tflite` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the model cannot be read, uses ops which are not builtin, or if its tensors are not
+    /// supported.
+    #[tracing::instrument]
+    pub fn load(path: &str) -> Result<Self> {
+        match load_model(path) {
+            Ok(predictor) => Ok(predictor),
+            Err(e) => {
+                tracing::error!(
+                    "Failed to load TensorFlow Lite model from file {}: {}",
+                    path,
+                    e
+                );
+                Err(Error::Load(anyhow::anyhow!(
+                    "Failed to load TensorFlow Lite model from file {}: {}",
+                    path,
+                    e
+                )))
+            }
+        }
+    }
+
+    /// Runs the interpreter on the rows of the input, one batch at a time.
+    fn infer(
+        &self,
+        interpreter: &mut Interpreter<'static, BuiltinOpResolver>,
+        input: &DenseInput,
+    ) -> anyhow::Result<Vec<Vec<Vec<f64>>>> {
+        let mut outputs: Vec<Vec<Vec<f64>>> =
+            vec![Vec::with_capacity(input.num_rows); self.outputs.len()];
+        for (batch, num_rows) in input.batches(&self.input.tensor, self.batch_size) {
+            let input_tensor: &mut [f32] = interpreter.tensor_data_mut(self.input.index)?;
+            DenseTensor::fill(input_tensor, batch);
+
+            interpreter.invoke()?;
+
+            for (output, rows) in self.outputs.iter().zip(outputs.iter_mut()) {
+                let values: &[f32] = interpreter.tensor_data(output.index)?;
+                output.tensor.extend_rows(rows, values, num_rows);
+            }
+        }
+        Ok(outputs)
+    }
+}
+
+/// Builds an interpreter of a model from its bytes, with its tensors allocated.
+fn build_interpreter(model: &[u8]) -> anyhow::Result<Interpreter<'static, BuiltinOpResolver>> {
+    let model = FlatBufferModel::build_from_buffer(model.to_vec())?;
+    let resolver = BuiltinOpResolver::default();
+    let mut interpreter = InterpreterBuilder::new(model, resolver)?.build()?;
+    interpreter.set_num_threads(NUM_THREADS);
+    interpreter.allocate_tensors()?;
+    Ok(interpreter)
+}
+
+/// Builds the first interpreter of a model and describes its tensors.
+fn load_model(path: &str) -> anyhow::Result<TfLite> {
+    let model = std::fs::read(path)?;
+    let interpreter = build_interpreter(&model)?;
+
+    let describe = |index: i32| -> anyhow::Result<(String, Vec<i64>)> {
+        match interpreter.tensor_info(index) {
+            Some(info) if info.element_kind == ElementKind::kTfLiteFloat32 => Ok((
+                info.name,
+                info.dims
+                    .iter()
+                    .map(|dimension| *dimension as i64)
+                    .collect(),
+            )),
+            Some(info) => anyhow::bail!(
+                "tensor {} is of type {:?}, only float tensors are supported",
+                info.name,
+                info.element_kind
+            ),
+            None => anyhow::bail!("tensor {} does not exist", index),
+        }
+    };
+    let (input_index, (input_name, input_shape)) = match interpreter.inputs() {
+        [index] => (*index, describe(*index)?),
+        inputs => anyhow::bail!("expected a single input tensor, found {}", inputs.len()),
+    };
+    if interpreter.outputs().is_empty() {
+        anyhow::bail!("the model has no output tensors")
+    }
+    let batch_size = match input_shape.first() {
+        Some(batch_size) if *batch_size > 0 => *batch_size as usize,
+        _ => anyhow::bail!("the input tensor {} has no batch size", input_name),
+    };
+    let input = IoTensor {
+        index: input_index,
+        tensor: DenseTensor::new(FRAMEWORK_NAME, input_name, &input_shape, batch_size)?,
+    };
+    let outputs = interpreter
+        .outputs()
+        .iter()
+        .map(|index| {
+            let (name, shape) = describe(*index)?;
+            Ok(IoTensor {
+                index: *index,
+                tensor: DenseTensor::new(FRAMEWORK_NAME, name, &shape, batch_size)?,
+            })
+        })
+        .collect::<anyhow::Result<Vec<IoTensor>>>()?;
+
+    Ok(TfLite {
+        model,
+        interpreters: SessionPool::new(interpreter),
+        batch_size,
+        input,
+        outputs,
+    })
+}
+
+impl Lifecycle for TfLite {}
+
+impl Predict for TfLite {
+    /// Performs prediction using the loaded TensorFlow Lite model.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The `ModelInput` containing the input values for prediction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if there is an issue parsing the input or performing the prediction.
+    #[tracing::instrument(skip(self, input))]
+    fn predict(&self, input: ModelInput) -> Result<ModelOutput> {
+        self.predict_with_options(input, &PredictOptions::default())
+    }
+
+    /// Performs prediction using the loaded TensorFlow Lite model and the given prediction options.
+    ///
+    /// A model with a single output tensor returns it as the `predictions` output, otherwise each output
+    /// is named after its tensor. Neither `mode` nor `num_iteration` is supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The `ModelInput` containing the input values for prediction.
+    /// * `options` - The prediction options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the options are not supported, or if there is an issue parsing the input or
+    /// performing the prediction.
+    #[tracing::instrument(skip(self, input))]
+    fn predict_with_options(
+        &self,
+        input: ModelInput,
+        options: &PredictOptions,
+    ) -> Result<ModelOutput> {
+        dense::validate_options(FRAMEWORK_NAME, options)?;
+        let input = DenseInput::parse(FRAMEWORK_NAME, input, self.input.tensor.row_len)
+            .map_err(Error::Input)?;
+
+        let outputs = self.interpreters.run(
+            || build_interpreter(&self.model),
+            |interpreter| self.infer(interpreter, &input),
+        );
+
+        match outputs {
+            Ok(outputs) => Ok(dense::model_output(
+                self.outputs.iter().map(|output| &output.tensor),
+                outputs,
+            )),
+            Err(e) => {
+                tracing::error!("Failed to make predictions using TensorFlow Lite: {}", e);
+                Err(Error::Predict(anyhow::anyhow!(
+                    "Failed to make predictions using TensorFlow Lite: {}",
+                    e
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::output::DEFAULT_OUTPUT_KEY;
+
+    #[test]
+    fn fails_to_load_tflite_model() {
+        let model_dir = "incorrect/path";
+        let model = TfLite::load(model_dir);
+
+        // assert the result is Err
+        assert!(model.is_err())
+    }
+
+    #[test]
+    fn successfully_load_and_predict_using_tflite_model() {
+        // the model adds [1, 2] to each row of its [2, 2] input, so three rows make two batches, the last padded
+        let model_path = "tests/model_storage/models/tflite-my_awesome_shift_model.tflite";
+        let model = TfLite::load(model_path).unwrap();
+        let mut model_input = ModelInput::default();
+        model_input.float_features.values = crate::model::input::Values::Float(vec![
+            0.5, 2.0, 4.0, // first float feature
+            1.0, 3.0, 5.0, // second float feature
+        ]);
+        model_input.float_features.shape = (2, 3);
+
+        let output = model.predict(model_input).unwrap();
+
+        // assert
+        assert_eq!(
+            output.predictions[DEFAULT_OUTPUT_KEY],
+            vec![vec![1.5, 3.0], vec![3.0, 5.0], vec![5.0, 7.0]]
+        );
+        assert_eq!(model.interpreters.idle(), 1);
+    }
+}
@@ -1,10 +1,22 @@
+use crate::model::lifecycle::Lifecycle;
 use crate::model::predict::Predict;
 use std::collections::HashMap;
 
 use crate::model::input::{ModelInput, Values};
-use crate::model::output::{ModelOutput, DEFAULT_OUTPUT_KEY};
+use crate::model::output::{classifier_output, ModelOutput, DEFAULT_OUTPUT_KEY};
+use crate::model::streaming::{GenerationConfig, StreamingPredict};
+use crate::model::variant::ModelPrecision;
+use crate::model_store::common::read_provenance;
+use crate::{Error, Result};
 use tch::CModule;
 
+/// Name of the method of an encoder-decoder model which encodes the ids of the tokens of the prompt.
+const ENCODE_METHOD_NAME: &str = "encode";
+
+/// Name of the method of an encoder-decoder model which returns the logits of the next token from the ids of the
+/// tokens decoded so far and the encoded prompt.
+const DECODE_METHOD_NAME: &str = "decode";
+
 /// Struct representing the input for a Torch model.
 ///
 /// # Fields
@@ -25,11 +37,10 @@ impl TorchModelInput {
     #[tracing::instrument(skip(model_input))]
     fn parse(mut model_input: ModelInput) -> anyhow::Result<Self> {
         // only float features are supported, so we are converting Vec<i32> to Vec<f32>
-        // we can use .1 of any features as the length is same.
-        // only the number of features is changing
+        // all the features have the same number of rows, only the number of features is changing
         let numerical_features_shape = (
             model_input.integer_features.shape.0 + model_input.float_features.shape.0,
-            model_input.float_features.shape.1,
+            model_input.num_rows(),
         );
 
         // convert integer to float
@@ -48,11 +59,13 @@ impl TorchModelInput {
             .values
             .append(&mut Values::Float(converted));
 
+        // use the fallible variant as `view` panics when the shape does not match the values
         let tensor =
-            tch::Tensor::from_slice(model_input.float_features.values.as_floats().unwrap()).view([
-                numerical_features_shape.1 as i64,
-                numerical_features_shape.0 as i64,
-            ]);
+            tch::Tensor::from_slice(model_input.float_features.values.as_floats().unwrap())
+                .f_view([
+                    numerical_features_shape.1 as i64,
+                    numerical_features_shape.0 as i64,
+                ])?;
 
         Ok(Self { tensor })
     }
@@ -62,21 +75,31 @@ impl TorchModelInput {
 ///
 /// # Fields
 /// * `model` - The compiled Torch model.
+/// * `class_labels` - The class labels of a classifier, in the order of its logits. Empty for other models.
+/// * `precision` - The precision of the model, which sets the precision of the inputs it is called with.
+/// * `generation` - How a sequence model generates its tokens. `None` for other models.
 pub struct Torch {
     model: CModule,
+    class_labels: Vec<String>,
+    precision: ModelPrecision,
+    generation: Option<GenerationConfig>,
 }
 
 impl Torch {
     /// Loads a Torch model from the specified file path.
     ///
+    /// If the model was unpacked from a tarball with a `labels.txt` file, it is served as a classifier
+    /// which returns logits. If it was unpacked from a tarball with a `generation.json` file, it is served
+    /// as a sequence model whose tokens can be streamed.
+    ///
     /// # Arguments
     /// * `path` - The file path to the Torch model.
     ///
     /// # Returns
     /// * `Ok(Torch)` - If the model was successfully loaded.
-    /// * `Err(anyhow::Error)` - If there was an error during loading.
+    /// * `Err(Error::Load)` - If there was an error during loading.
     #[tracing::instrument(skip(path))]
-    pub fn load(path: &str) -> anyhow::Result<Self> {
+    pub fn load(path: &str) -> Result<Self> {
         let model = match CModule::load(path) {
             Ok(model) => model,
             Err(e) => {
@@ -86,17 +109,134 @@ impl Torch {
                     e.to_string()
                 );
 
-                anyhow::bail!(
+                return Err(Error::Load(anyhow::anyhow!(
                     "Failed to load pytorch model from file {}: {}",
                     path,
                     e.to_string()
-                )
+                )));
             }
         };
-        Ok(Torch { model })
+        let provenance = read_provenance(path);
+        Ok(Torch {
+            model,
+            class_labels: provenance.class_labels,
+            precision: ModelPrecision::default(),
+            generation: provenance.generation,
+        })
+    }
+
+    /// Sets the class labels of a classifier which returns logits, in the order of its outputs.
+    ///
+    /// The predictions of a classifier contain the raw `logits`, the `probabilities` after applying softmax
+    /// and the `predicted_label` of each row.
+    pub fn with_class_labels(mut self, class_labels: Vec<String>) -> Self {
+        self.class_labels = class_labels;
+        self
+    }
+
+    /// Sets the precision of the model. Half precision models are called with half precision inputs,
+    /// while full precision and int8 quantized models are called with full precision inputs.
+    pub fn with_precision(mut self, precision: ModelPrecision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Sets how the model generates sequences, which makes it a sequence model whose tokens can be streamed.
+    pub fn with_generation(mut self, generation: GenerationConfig) -> Self {
+        self.generation = Some(generation);
+        self
+    }
+
+    /// Returns true if the model is a sequence model whose tokens can be streamed.
+    pub fn is_streaming(&self) -> bool {
+        self.generation.is_some()
+    }
+
+    /// Encodes the ids of the tokens of the prompt using the `encode` method of an encoder-decoder model.
+    fn encode(&self, prompt: &[i64]) -> anyhow::Result<tch::Tensor> {
+        let tokens = tch::Tensor::from_slice(prompt).f_view([1, prompt.len() as i64])?;
+        Ok(self.model.method_ts(ENCODE_METHOD_NAME, &[tokens])?)
+    }
+
+    /// Returns the id of the most likely token following a sequence. The sequence is decoded by the `decode`
+    /// method from the encoded prompt of an encoder-decoder model, or by the `forward` method otherwise.
+    fn next_token(&self, sequence: &[i64], encoded: Option<&tch::Tensor>) -> anyhow::Result<i64> {
+        let tokens = tch::Tensor::from_slice(sequence).f_view([1, sequence.len() as i64])?;
+        let logits = match encoded {
+            Some(encoded) => self
+                .model
+                .method_ts(DECODE_METHOD_NAME, &[tokens, encoded.shallow_clone()])?,
+            None => self.model.forward_ts(&[tokens])?,
+        };
+        // the logits of the next token are at the last position of outputs with one position per token
+        let logits = match logits.dim() {
+            2 => logits,
+            3 => logits.f_select(1, -1)?,
+            dim => anyhow::bail!(
+                "Expected the logits of the next token with 2 or 3 dimensions, got {} ❌",
+                dim
+            ),
+        };
+        Ok(logits.f_argmax(-1, false)?.f_int64_value(&[0])?)
     }
 }
 
+impl StreamingPredict for Torch {
+    /// Generates the tokens following the prompt of the input greedily, emitting each token as it is generated.
+    ///
+    /// The prompt of an encoder-decoder model is encoded once, and its decoder is stepped from the decoder start
+    /// token, while a decoder-only model is called with the prompt followed by the tokens generated so far.
+    ///
+    /// # Errors
+    /// * `Err(Error::Input)` - If the model is not a sequence model or the input has no prompt.
+    /// * `Err(Error::Predict)` - If a token cannot be generated.
+    #[tracing::instrument(skip(self, input, emit))]
+    fn predict_stream(
+        &self,
+        input: ModelInput,
+        emit: &mut dyn FnMut(ModelOutput) -> bool,
+    ) -> Result<()> {
+        let generation = match &self.generation {
+            Some(generation) => generation,
+            None => {
+                return Err(Error::Input(anyhow::anyhow!(
+                    "Torch model is not a sequence model, it was shipped without a generation.json file ❌"
+                )))
+            }
+        };
+        let prompt = generation.prompt(&input).map_err(Error::Input)?;
+        let encoded = match generation.is_encoder_decoder() {
+            true => match self.encode(prompt.as_slice()) {
+                Ok(encoded) => Some(encoded),
+                Err(e) => {
+                    tracing::error!("Failed to encode prompt using Torch model ❌: {}", e);
+                    return Err(Error::Predict(anyhow::anyhow!(
+                        "Failed to encode prompt using Torch model ❌: {}",
+                        e
+                    )));
+                }
+            },
+            false => None,
+        };
+        generation.generate(
+            generation.start_sequence(prompt),
+            |sequence| match self.next_token(sequence, encoded.as_ref()) {
+                Ok(token) => Ok(token),
+                Err(e) => {
+                    tracing::error!("Failed to generate token using Torch model ❌: {}", e);
+                    Err(Error::Predict(anyhow::anyhow!(
+                        "Failed to generate token using Torch model ❌: {}",
+                        e
+                    )))
+                }
+            },
+            emit,
+        )
+    }
+}
+
+impl Lifecycle for Torch {}
+
 impl Predict for Torch {
     /// Predicts the output for the given model input.
     ///
@@ -105,17 +245,41 @@ impl Predict for Torch {
     ///
     /// # Returns
     /// * `Ok(Output)` - The prediction output.
-    /// * `Err(anyhow::Error)` - If there was an error during prediction.
+    /// * `Err(Error::Predict)` - If there was an error during prediction.
     #[tracing::instrument(skip(self, input))]
-    fn predict(&self, input: ModelInput) -> anyhow::Result<ModelOutput> {
-        let input = TorchModelInput::parse(input)?;
-        let preds = self.model.forward_ts(&[input.tensor]);
+    fn predict(&self, input: ModelInput) -> Result<ModelOutput> {
+        let input = TorchModelInput::parse(input).map_err(Error::Input)?;
+        let tensor = match self.precision {
+            ModelPrecision::Fp16 => input.tensor.to_kind(tch::Kind::Half),
+            ModelPrecision::Fp32 | ModelPrecision::Int8 => input.tensor,
+        };
+        let preds = self.model.forward_ts(&[tensor]);
         match preds {
             Ok(preds) => {
+                let values: Vec<Vec<f64>> = preds
+                    .try_into()
+                    .map_err(|e| Error::Predict(anyhow::Error::from(e)))?;
+                if !self.class_labels.is_empty() {
+                    return match classifier_output(values, &self.class_labels) {
+                        Ok(output) => Ok(output),
+                        Err(e) => {
+                            tracing::error!("Failed to map Torch model logits to labels ❌: {}", e);
+                            return Err(Error::Predict(anyhow::anyhow!(
+                                "Failed to map Torch model logits to labels ❌: {}",
+                                e
+                            )));
+                        }
+                    };
+                }
+
                 let mut predictions: HashMap<String, Vec<Vec<f64>>> = HashMap::new();
-                let values: Vec<Vec<f64>> = preds.try_into()?;
                 predictions.insert(DEFAULT_OUTPUT_KEY.to_string(), values);
-                Ok(ModelOutput { predictions })
+                Ok(ModelOutput {
+                    predictions,
+                    labels: HashMap::new(),
+                    shapes: HashMap::new(),
+                    texts: HashMap::new(),
+                })
             }
             Err(e) => {
                 tracing::error!(
@@ -123,10 +287,10 @@ impl Predict for Torch {
                     e.to_string()
                 );
 
-                anyhow::bail!(
+                Err(Error::Predict(anyhow::anyhow!(
                     "Failed to make predictions using Torch model: {}",
                     e.to_string()
-                )
+                )))
             }
         }
     }
@@ -135,7 +299,10 @@ impl Predict for Torch {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::output::{LOGITS_OUTPUT_KEY, PREDICTED_LABEL_KEY, PROBABILITIES_OUTPUT_KEY};
+    use crate::model::streaming::GENERATION_FILE_NAME;
     use crate::model::test_utils;
+    use crate::model_store::common::unpack_tarball_with_provenance;
 
     #[test]
     fn fails_to_load_torch_model() {
@@ -215,4 +382,152 @@ mod tests {
         // model with 3 classes
         assert_eq!(predictions.first().unwrap().len(), 3);
     }
+
+    #[test]
+    fn successfully_make_prediction_with_labels_using_pytorch_multiclass_classification_model() {
+        let path = "tests/model_storage/models/torch-my_awesome_penguin_model.pt";
+        let class_labels = vec![
+            "Adelie".to_string(),
+            "Chinstrap".to_string(),
+            "Gentoo".to_string(),
+        ];
+        let model = Torch::load(path)
+            .unwrap()
+            .with_class_labels(class_labels.clone());
+        let size = 10;
+        let model_inputs = test_utils::utils::create_model_inputs(4, 0, size);
+
+        // make predictions
+        let output = model.predict(model_inputs).unwrap();
+
+        // assert
+        let logits = output.predictions.get(LOGITS_OUTPUT_KEY).unwrap();
+        let probabilities = output.predictions.get(PROBABILITIES_OUTPUT_KEY).unwrap();
+        let labels = output.labels.get(PREDICTED_LABEL_KEY).unwrap();
+        assert!(!output.predictions.contains_key(DEFAULT_OUTPUT_KEY));
+        assert_eq!(logits.len(), size);
+        assert_eq!(probabilities.len(), size);
+        assert_eq!(labels.len(), size);
+        for ((row, probabilities), label) in logits.iter().zip(probabilities).zip(labels) {
+            assert_eq!(row.len(), 3);
+            assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+            // the label is the class with the highest logit
+            let index = row
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(index, _)| index)
+                .unwrap();
+            assert_eq!(label, &class_labels[index]);
+        }
+    }
+
+    #[test]
+    fn fails_to_make_prediction_using_pytorch_multiclass_classification_model_when_labels_do_not_match_classes(
+    ) {
+        let path = "tests/model_storage/models/torch-my_awesome_penguin_model.pt";
+        let model = Torch::load(path)
+            .unwrap()
+            .with_class_labels(vec!["Adelie".to_string(), "Gentoo".to_string()]);
+        let model_inputs = test_utils::utils::create_model_inputs(4, 0, 10);
+
+        // make predictions
+        let output = model.predict(model_inputs);
+
+        // assert
+        assert!(output.is_err())
+    }
+
+    #[test]
+    fn successfully_return_one_prediction_per_row_using_pytorch_regression_model() {
+        let path = "tests/model_storage/models/pytorch-my_awesome_californiahousing_model.pt";
+        let model = Torch::load(path).unwrap();
+
+        // asserts there is one prediction per input row for any valid input
+        let result = test_utils::utils::check_prediction_shapes(
+            &model,
+            test_utils::utils::model_input_strategy(8, 0, true),
+        );
+
+        // assert the shape invariants hold for every generated input
+        assert!(result.is_ok(), "{:?}", result)
+    }
+
+    #[test]
+    fn successfully_return_one_prediction_per_row_using_pytorch_multiclass_classification_model() {
+        let path = "tests/model_storage/models/torch-my_awesome_penguin_model.pt";
+        let model = Torch::load(path).unwrap();
+
+        // asserts there is one prediction per input row for any valid input
+        let result = test_utils::utils::check_prediction_shapes(
+            &model,
+            test_utils::utils::model_input_strategy(4, 0, true),
+        );
+
+        // assert the shape invariants hold for every generated input
+        assert!(result.is_ok(), "{:?}", result)
+    }
+
+    #[test]
+    fn successfully_load_pytorch_sequence_model_with_generation_config() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let tarball_path = dir.path().join("pytorch-my_sequence_model.tar.gz");
+        let generation =
+            br#"{"max_new_tokens": 8, "eos_token_id": 2, "decoder_start_token_id": 0}"#;
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            std::fs::File::create(&tarball_path).unwrap(),
+            flate2::Compression::default(),
+        ));
+        builder
+            .append_path_with_name(
+                "tests/model_storage/models/torch-my_awesome_penguin_model.pt",
+                "pytorch-my_sequence_model.pt",
+            )
+            .unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(generation.len() as u64);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, GENERATION_FILE_NAME, &generation[..])
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        let out_dir = dir.path().join("out");
+        unpack_tarball_with_provenance(
+            tarball_path.to_str().unwrap(),
+            out_dir.to_str().unwrap(),
+            "s3://models/pytorch-my_sequence_model.tar.gz".to_string(),
+            None,
+        )
+        .unwrap();
+
+        // Act
+        let model = Torch::load(
+            out_dir
+                .join("pytorch-my_sequence_model.pt")
+                .to_str()
+                .unwrap(),
+        );
+        let other = Torch::load("tests/model_storage/models/torch-my_awesome_penguin_model.pt");
+
+        // Assert
+        let model = model.unwrap();
+        assert!(model.is_streaming());
+        assert!(model.generation.as_ref().unwrap().is_encoder_decoder());
+        let other = other.unwrap();
+        assert!(!other.is_streaming());
+        let input = ModelInput::from_str(r#"{"input_ids": [4, 5, 6]}"#).unwrap();
+        let result = other.predict_stream(input, &mut |_| true);
+        assert!(matches!(result, Err(Error::Input(_))));
+    }
+
+    #[test]
+    fn successfully_match_golden_outputs_using_torch_models() {
+        for name in [
+            "torch-my_awesome_penguin_model",
+            "pytorch-my_awesome_californiahousing_model",
+        ] {
+            test_utils::golden::check_golden(name, Torch::load);
+        }
+    }
 }
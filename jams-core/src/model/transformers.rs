@@ -0,0 +1,510 @@
+use crate::model::input::ModelInput;
+use crate::model::lifecycle::Lifecycle;
+use crate::model::output::{classifier_output, ModelOutput, DEFAULT_OUTPUT_KEY};
+use crate::model::predict::{Predict, PredictOptions};
+use crate::{Error, Result};
+use ort::{Session, SessionInputValue, Tensor};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use tch::{CModule, IValue, Kind};
+use tokenizers::{PaddingParams, PaddingStrategy, Tokenizer, TruncationParams};
+
+/// Name of the tokenizer file in the model directory, as saved by `tokenizer.save_pretrained`.
+pub const TOKENIZER_FILE_NAME: &str = "tokenizer.json";
+
+/// Name of the TorchScript module in the model directory.
+pub const MODULE_FILE_NAME: &str = "model.pt";
+
+/// Name of the ONNX model in the model directory, as exported with `optimum-cli export onnx`.
+pub const ONNX_FILE_NAME: &str = "model.onnx";
+
+/// Name of the optional Hugging Face model config in the model directory, as saved by `model.save_pretrained`.
+pub const CONFIG_FILE_NAME: &str = "config.json";
+
+/// Default string feature holding the text of each row.
+pub const DEFAULT_TEXT_FEATURE: &str = "text";
+
+/// Default maximum number of tokens of a text, longer texts are truncated.
+pub const DEFAULT_MAX_LENGTH: usize = 512;
+
+/// The fields read from the Hugging Face `config.json` of the model. Other fields are ignored.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+struct TransformersConfig {
+    /// The class label of each output of a sequence classification head, keyed by its index.
+    #[serde(default)]
+    id2label: BTreeMap<String, String>,
+    /// The maximum number of tokens of a sequence supported by the model.
+    #[serde(default)]
+    max_position_embeddings: Option<usize>,
+}
+
+impl TransformersConfig {
+    /// Parses the contents of a `config.json` file.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the contents are not valid JSON or `id2label` is not keyed by the indices
+    ///   `0..num_labels`, since a label without an output, or an output without a label, would shift the labels of
+    ///   the other outputs.
+    fn parse(contents: &str) -> anyhow::Result<Self> {
+        let config: TransformersConfig = match serde_json::from_str(contents) {
+            Ok(config) => config,
+            Err(e) => anyhow::bail!("Failed to parse {} ❌: {}", CONFIG_FILE_NAME, e),
+        };
+        let mut indices = Vec::with_capacity(config.id2label.len());
+        for key in config.id2label.keys() {
+            match key.parse::<usize>() {
+                Ok(index) => indices.push(index),
+                Err(_) => anyhow::bail!(
+                    "id2label in {} must be keyed by the index of each label, got {} ❌",
+                    CONFIG_FILE_NAME,
+                    key
+                ),
+            }
+        }
+        indices.sort_unstable();
+        if let Some((position, index)) = indices
+            .iter()
+            .enumerate()
+            .find(|(position, index)| position != *index)
+        {
+            anyhow::bail!(
+                "id2label in {} must have a label for each index from 0 to {}, got index {} in place of {} ❌",
+                CONFIG_FILE_NAME,
+                indices.len() - 1,
+                index,
+                position
+            )
+        }
+        Ok(config)
+    }
+
+    /// Returns the class labels in the order of the outputs of the model, or an empty vector for models
+    /// without a classification head.
+    fn class_labels(&self) -> Vec<String> {
+        let mut labels = vec![String::new(); self.id2label.len()];
+        for (index, label) in &self.id2label {
+            // the indices are checked to be 0..num_labels when parsing the config
+            if let Some(slot) = index.parse::<usize>().ok().and_then(|i| labels.get_mut(i)) {
+                *slot = label.clone();
+            }
+        }
+        labels
+    }
+
+    /// Returns the maximum number of tokens of a text.
+    fn max_length(&self) -> usize {
+        self.max_position_embeddings
+            .map(|max| max.min(DEFAULT_MAX_LENGTH))
+            .unwrap_or(DEFAULT_MAX_LENGTH)
+    }
+}
+
+/// Returns the text of each row of the input.
+///
+/// The text is read from the `text` string feature, or from the only string feature of the input.
+///
+/// # Errors
+///
+/// Returns an `Err` if the input has numerical features or no text feature.
+fn parse_texts(model_input: &ModelInput) -> anyhow::Result<Vec<&str>> {
+    if !model_input.float_features.names.is_empty()
+        || !model_input.integer_features.names.is_empty()
+    {
+        anyhow::bail!(
+            "Transformers models only take a text feature, tokenization happens on the server ❌"
+        )
+    }
+    let features = &model_input.string_features;
+    let index = match features
+        .names
+        .iter()
+        .position(|name| name.as_str() == DEFAULT_TEXT_FEATURE)
+    {
+        Some(index) => index,
+        None if features.names.len() == 1 => 0,
+        None => anyhow::bail!(
+            "Transformers models expect the text of each row in the string feature {} ❌",
+            DEFAULT_TEXT_FEATURE
+        ),
+    };
+    let num_rows = features.shape.1;
+    match features.values.as_strings() {
+        Some(values) if values.len() >= (index + 1) * num_rows => Ok(values
+            [index * num_rows..(index + 1) * num_rows]
+            .iter()
+            .map(String::as_str)
+            .collect()),
+        _ => anyhow::bail!("Failed to read the text feature of the input ❌"),
+    }
+}
+
+/// The exported network of a transformers model.
+enum Backend {
+    /// A TorchScript module, called with the `input_ids` and `attention_mask` in that order.
+    TorchScript(CModule),
+    /// An ONNX model run with ONNX Runtime, fed the `input_ids`, `attention_mask` and `token_type_ids` inputs
+    /// which it declares.
+    Onnx(Session),
+}
+
+/// Struct representing a Hugging Face transformers text model, tokenized on the server.
+///
+/// The model is loaded from a directory holding the `tokenizer.json` of the model and either a `model.onnx` ONNX
+/// model, e.g. exported with `optimum-cli export onnx`, or a `model.pt` TorchScript module, e.g. traced with
+/// `torch.jit.trace(model, (input_ids, attention_mask))` from a model loaded with `torchscript=True`. The model is
+/// called with the `input_ids` and `attention_mask` of a batch of texts as `[num_rows, sequence_length]` tensors of
+/// `int64`, and returns either the logits of a classification head as a `[num_rows, num_labels]` tensor, or the
+/// last hidden state as a `[num_rows, sequence_length, hidden_size]` tensor, which is mean pooled over the tokens
+/// of each text into an embedding. The first output of the model is used.
+///
+/// # Fields
+/// * `tokenizer` - The tokenizer, which pads each batch to its longest text.
+/// * `backend` - The ONNX model or the compiled TorchScript module.
+/// * `class_labels` - The class labels from the `id2label` of the `config.json`. Empty for other models.
+pub struct Transformers {
+    tokenizer: Tokenizer,
+    backend: Backend,
+    class_labels: Vec<String>,
+}
+
+impl Transformers {
+    /// Loads a transformers model from the specified directory.
+    ///
+    /// If the directory has a `config.json` file with an `id2label` mapping, the model is served as a
+    /// classifier which returns logits, probabilities and the predicted label of each text.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the model directory.
+    ///
+    /// # Returns
+    /// * `Ok(Transformers)` - If the model was successfully loaded.
+    /// * `Err(Error::Load)` - If there was an error during loading.
+    #[tracing::instrument]
+    pub fn load(path: &str) -> Result<Self> {
+        match load_directory(Path::new(path)) {
+            Ok(model) => Ok(model),
+            Err(e) => {
+                tracing::error!(
+                    "Failed to load transformers model from directory {}: {}",
+                    path,
+                    e
+                );
+                Err(Error::Load(anyhow::anyhow!(
+                    "Failed to load transformers model from directory {}: {}",
+                    path,
+                    e
+                )))
+            }
+        }
+    }
+
+    /// Tokenizes the texts and runs the model, returning the logits or pooled embedding of each text.
+    fn infer(&self, texts: Vec<&str>) -> anyhow::Result<Vec<Vec<f64>>> {
+        let num_rows = texts.len();
+        let encodings = match self.tokenizer.encode_batch(texts, true) {
+            Ok(encodings) => encodings,
+            Err(e) => anyhow::bail!("Failed to tokenize the texts: {}", e),
+        };
+        let sequence_length = encodings
+            .first()
+            .map(|encoding| encoding.get_ids().len())
+            .unwrap_or_default();
+
+        let mut ids = Vec::with_capacity(num_rows * sequence_length);
+        let mut mask = Vec::with_capacity(num_rows * sequence_length);
+        let mut type_ids = Vec::with_capacity(num_rows * sequence_length);
+        for encoding in &encodings {
+            ids.extend(encoding.get_ids().iter().map(|id| *id as i64));
+            mask.extend(encoding.get_attention_mask().iter().map(|m| *m as i64));
+            type_ids.extend(encoding.get_type_ids().iter().map(|id| *id as i64));
+        }
+        let shape = [num_rows as i64, sequence_length as i64];
+
+        let output = match &self.backend {
+            Backend::TorchScript(module) => {
+                let ids = tch::Tensor::from_slice(&ids).f_view(shape)?;
+                let mask = tch::Tensor::from_slice(&mask).f_view(shape)?;
+                match module.forward_is(&[IValue::Tensor(ids), IValue::Tensor(mask)])? {
+                    IValue::Tensor(tensor) => tensor,
+                    IValue::Tuple(mut values) if !values.is_empty() => {
+                        match values.swap_remove(0) {
+                            IValue::Tensor(tensor) => tensor,
+                            _ => anyhow::bail!(
+                                "Expected the first output of the model to be a tensor"
+                            ),
+                        }
+                    }
+                    _ => {
+                        anyhow::bail!("Expected the model to return a tensor or a tuple of tensors")
+                    }
+                }
+            }
+            Backend::Onnx(session) => {
+                let mut inputs: Vec<(Cow<str>, SessionInputValue)> =
+                    Vec::with_capacity(session.inputs.len());
+                for input in &session.inputs {
+                    let values = match input.name.as_str() {
+                        "input_ids" => ids.clone(),
+                        "attention_mask" => mask.clone(),
+                        "token_type_ids" => type_ids.clone(),
+                        name => anyhow::bail!(
+                            "Expected the model to take input_ids, attention_mask and token_type_ids, got {}",
+                            name
+                        ),
+                    };
+                    let tensor = Tensor::from_array((shape, values))?;
+                    inputs.push((Cow::from(input.name.as_str()), tensor.into()));
+                }
+                let outputs = session.run(inputs)?;
+                let (output_shape, values) = outputs[0].try_extract_raw_tensor::<f32>()?;
+                tch::Tensor::from_slice(values).f_view(output_shape.as_slice())?
+            }
+        };
+        let mask = tch::Tensor::from_slice(&mask).f_view(shape)?;
+        let output = match output.dim() {
+            2 => output.f_to_kind(Kind::Float)?,
+            3 => mean_pool(&output, &mask)?,
+            dim => anyhow::bail!(
+                "Expected logits with 2 dimensions or hidden states with 3 dimensions, got {}",
+                dim
+            ),
+        };
+        Ok(Vec::<Vec<f64>>::try_from(output)?)
+    }
+}
+
+/// Reads the tokenizer, the module and the optional config of a model directory.
+fn load_directory(dir: &Path) -> anyhow::Result<Transformers> {
+    let mut tokenizer = match Tokenizer::from_file(dir.join(TOKENIZER_FILE_NAME)) {
+        Ok(tokenizer) => tokenizer,
+        Err(e) => anyhow::bail!("Failed to read {}: {}", TOKENIZER_FILE_NAME, e),
+    };
+    // models exported to ONNX take precedence over TorchScript modules
+    let onnx_path = dir.join(ONNX_FILE_NAME);
+    let backend = if onnx_path.exists() {
+        Backend::Onnx(Session::builder()?.commit_from_file(onnx_path)?)
+    } else {
+        Backend::TorchScript(CModule::load(dir.join(MODULE_FILE_NAME))?)
+    };
+    let config = match std::fs::read_to_string(dir.join(CONFIG_FILE_NAME)) {
+        Ok(contents) => TransformersConfig::parse(&contents)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => TransformersConfig::default(),
+        Err(e) => anyhow::bail!("Failed to read {}: {}", CONFIG_FILE_NAME, e),
+    };
+
+    // pad each batch to its longest text and truncate texts longer than the model supports
+    tokenizer.with_padding(Some(PaddingParams {
+        strategy: PaddingStrategy::BatchLongest,
+        ..PaddingParams::default()
+    }));
+    if let Err(e) = tokenizer.with_truncation(Some(TruncationParams {
+        max_length: config.max_length(),
+        ..TruncationParams::default()
+    })) {
+        anyhow::bail!("Failed to configure the truncation of the tokenizer: {}", e)
+    }
+
+    Ok(Transformers {
+        tokenizer,
+        backend,
+        class_labels: config.class_labels(),
+    })
+}
+
+/// Averages the hidden states of the tokens of each text, ignoring the padding.
+fn mean_pool(hidden: &tch::Tensor, mask: &tch::Tensor) -> anyhow::Result<tch::Tensor> {
+    let mask = mask.f_unsqueeze(-1)?.f_to_kind(Kind::Float)?;
+    let sum = hidden
+        .f_to_kind(Kind::Float)?
+        .f_mul(&mask)?
+        .f_sum_dim_intlist(Some(&[1i64][..]), false, Kind::Float)?;
+    let count = mask
+        .f_sum_dim_intlist(Some(&[1i64][..]), false, Kind::Float)?
+        .f_clamp_min(1e-9)?;
+    Ok(sum.f_div(&count)?)
+}
+
+impl Lifecycle for Transformers {}
+
+impl Predict for Transformers {
+    /// Tokenizes the text of each row and predicts the output of the model.
+    ///
+    /// # Arguments
+    /// * `input` - The input data for the model, with a single text feature.
+    ///
+    /// # Returns
+    /// * `Ok(Output)` - The prediction output.
+    /// * `Err(Error::Input)` - If the input has no text feature.
+    /// * `Err(Error::Predict)` - If there was an error during prediction.
+    #[tracing::instrument(skip(self, input))]
+    fn predict(&self, input: ModelInput) -> Result<ModelOutput> {
+        self.predict_with_options(input, &PredictOptions::default())
+    }
+
+    /// Tokenizes the text of each row and predicts the output of the model with the given prediction options.
+    ///
+    /// Neither `mode` nor `num_iteration` is supported.
+    ///
+    /// # Errors
+    /// * `Err(Error::Input)` - If the options are not supported or the input has no text feature.
+    /// * `Err(Error::Predict)` - If there was an error during prediction.
+    #[tracing::instrument(skip(self, input))]
+    fn predict_with_options(
+        &self,
+        input: ModelInput,
+        options: &PredictOptions,
+    ) -> Result<ModelOutput> {
+        options.validate().map_err(Error::Input)?;
+        if options.mode.is_some() || options.num_iteration.is_some() {
+            return Err(Error::Input(anyhow::anyhow!(
+                "mode and num_iteration are not supported by transformers models ❌"
+            )));
+        }
+        let texts = parse_texts(&input).map_err(Error::Input)?;
+
+        match self.infer(texts) {
+            Ok(values) => {
+                if !self.class_labels.is_empty() {
+                    return match classifier_output(values, &self.class_labels) {
+                        Ok(output) => Ok(output),
+                        Err(e) => {
+                            tracing::error!(
+                                "Failed to map transformers model logits to labels ❌: {}",
+                                e
+                            );
+                            Err(Error::Predict(anyhow::anyhow!(
+                                "Failed to map transformers model logits to labels ❌: {}",
+                                e
+                            )))
+                        }
+                    };
+                }
+
+                let mut predictions: HashMap<String, Vec<Vec<f64>>> = HashMap::new();
+                predictions.insert(DEFAULT_OUTPUT_KEY.to_string(), values);
+                Ok(ModelOutput {
+                    predictions,
+                    labels: HashMap::new(),
+                    shapes: HashMap::new(),
+                    texts: HashMap::new(),
+                })
+            }
+            Err(e) => {
+                tracing::error!("Failed to make predictions using transformers model: {}", e);
+                Err(Error::Predict(anyhow::anyhow!(
+                    "Failed to make predictions using transformers model: {}",
+                    e
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::output::{LOGITS_OUTPUT_KEY, PREDICTED_LABEL_KEY};
+    use crate::model::test_utils;
+
+    #[test]
+    fn fails_to_load_transformers_model() {
+        let model_dir = "incorrect/path";
+        let model = Transformers::load(model_dir);
+
+        // assert the result is Err
+        assert!(model.is_err())
+    }
+
+    #[test]
+    fn successfully_load_and_predict_using_onnx_transformers_model() {
+        let model_dir = "tests/model_storage/models/transformers-my_awesome_length_model";
+        let model = Transformers::load(model_dir).unwrap();
+        let input =
+            ModelInput::from_str(r#"{"text": ["good movie", "a really good movie overall"]}"#)
+                .unwrap();
+
+        let output = model.predict(input).unwrap();
+
+        // assert the padding of the shorter text is masked out, the model classifies texts by their number of tokens
+        assert_eq!(
+            output.predictions[LOGITS_OUTPUT_KEY],
+            vec![vec![-1.0, 1.0], vec![2.0, -2.0]]
+        );
+        assert_eq!(output.labels[PREDICTED_LABEL_KEY], vec!["short", "long"]);
+    }
+
+    #[test]
+    fn successfully_parse_class_labels_and_max_length_from_config() {
+        let contents = r#"{
+            "architectures": ["DistilBertForSequenceClassification"],
+            "id2label": {"10": "joy", "2": "anger", "0": "sadness", "1": "fear", "3": "love", "4": "surprise", "5": "disgust", "6": "trust", "7": "anticipation", "8": "shame", "9": "guilt"},
+            "max_position_embeddings": 128
+        }"#;
+
+        let config = TransformersConfig::parse(contents).unwrap();
+
+        // assert the labels are placed by their index, not by the order of the keys, and the max length is capped
+        // by the model
+        let labels = config.class_labels();
+        assert_eq!(labels.len(), 11);
+        assert_eq!(labels[0], "sadness");
+        assert_eq!(labels[2], "anger");
+        assert_eq!(labels[10], "joy");
+        assert_eq!(config.max_length(), 128);
+        assert_eq!(
+            TransformersConfig::default().max_length(),
+            DEFAULT_MAX_LENGTH
+        );
+    }
+
+    #[test]
+    fn fails_to_parse_config_when_id2label_is_not_keyed_by_index() {
+        let contents = r#"{"id2label": {"first": "sadness"}}"#;
+
+        let config = TransformersConfig::parse(contents);
+
+        // assert
+        assert!(config.is_err())
+    }
+
+    #[test]
+    fn fails_to_parse_config_when_id2label_has_gaps() {
+        let contents = r#"{"id2label": {"10": "joy", "2": "anger", "0": "sadness"}}"#;
+
+        let config = TransformersConfig::parse(contents);
+
+        // assert
+        assert!(config.is_err())
+    }
+
+    #[test]
+    fn successfully_parse_texts_from_the_only_string_feature() {
+        let model_inputs = test_utils::utils::create_model_inputs_with_names(
+            vec![],
+            vec!["review".to_string()],
+            3,
+        );
+
+        let texts = parse_texts(&model_inputs).unwrap();
+
+        // assert
+        assert_eq!(texts.len(), 3);
+    }
+
+    #[test]
+    fn fails_to_parse_texts_when_input_is_not_text() {
+        let numerical = test_utils::utils::create_model_inputs(2, 0, 3);
+        let ambiguous = test_utils::utils::create_model_inputs_with_names(
+            vec![],
+            vec!["title".to_string(), "body".to_string()],
+            3,
+        );
+
+        // assert
+        assert!(parse_texts(&numerical).is_err());
+        assert!(parse_texts(&ambiguous).is_err());
+    }
+}
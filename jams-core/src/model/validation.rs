@@ -0,0 +1,465 @@
+use crate::model::input::ModelInput;
+use crate::model::output::DEFAULT_OUTPUT_KEY;
+use crate::model::Predictor;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Name of the optional file in a model tarball which contains a labeled validation set and the acceptance
+/// criteria the model must meet on it.
+pub const VALIDATION_FILE_NAME: &str = "validation.json";
+
+fn default_output() -> String {
+    DEFAULT_OUTPUT_KEY.to_string()
+}
+
+/// A small labeled validation set shipped with a model, along with the criteria the model must meet on it
+/// before it is promoted.
+///
+/// The set is read from the `validation.json` file at the root of the model tarball and evaluated whenever
+/// the model is loaded or updated, so that an obviously broken retrain does not go live through polling.
+///
+/// # Example
+/// ```json
+/// {
+///     "input": {"age": [22.0, 38.0, 26.0, 35.0], "fare": [7.25, 71.28, 7.92, 53.1]},
+///     "labels": [0, 1, 1, 0],
+///     "criteria": { "min_auc": 0.8, "max_drift": 0.05 }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ValidationSet {
+    /// The input of the validation set, either columnar or row-oriented as in prediction requests.
+    pub input: serde_json::Value,
+    /// The output of the model to evaluate. Defaults to `predictions`.
+    #[serde(default = "default_output")]
+    pub output: String,
+    /// The expected value of each row. Required by every criterion but `max_drift`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<f64>,
+    /// The criteria the model must meet to be promoted.
+    pub criteria: AcceptanceCriteria,
+}
+
+/// The criteria a model must meet on its validation set to be promoted. Criteria which are not set are not checked.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AcceptanceCriteria {
+    /// Minimum area under the ROC curve of a binary classifier, which scores each row with the first value
+    /// of its output. The labels must be `0` or `1`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_auc: Option<f64>,
+    /// Minimum fraction of the rows whose predicted class is their label. The predicted class is the index
+    /// of the highest value of rows with several values, and whether the value is at least 0.5 otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_accuracy: Option<f64>,
+    /// Maximum mean absolute error of a regressor, between the first value of the output of each row and its label.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_mae: Option<f64>,
+    /// Maximum mean absolute difference between the outputs of the new version and the version it replaces.
+    /// Only checked when a loaded model is updated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_drift: Option<f64>,
+}
+
+/// The metrics of a model on its validation set. Metrics of criteria which are not set are not computed.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auc: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mae: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drift: Option<f64>,
+}
+
+impl ValidationSet {
+    /// Parses and validates a validation set.
+    ///
+    /// # Arguments
+    /// * `contents` - The contents of a `validation.json` file.
+    ///
+    /// # Returns
+    /// * `Ok(ValidationSet)` - If the validation set is valid.
+    /// * `Err(anyhow::Error)` - If the file cannot be parsed, has no criteria, its input is invalid or it does not
+    ///   have one label per row.
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let validation: ValidationSet = match serde_json::from_str(contents) {
+            Ok(validation) => validation,
+            Err(e) => anyhow::bail!("Failed to parse {} ❌: {}", VALIDATION_FILE_NAME, e),
+        };
+        validation.validate()?;
+        Ok(validation)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let criteria = &self.criteria;
+        let thresholds = [
+            criteria.min_auc,
+            criteria.min_accuracy,
+            criteria.max_mae,
+            criteria.max_drift,
+        ];
+        if thresholds.iter().all(Option::is_none) {
+            anyhow::bail!(
+                "{} must set at least one acceptance criterion ❌",
+                VALIDATION_FILE_NAME
+            )
+        }
+        if thresholds
+            .iter()
+            .flatten()
+            .any(|threshold| !threshold.is_finite() || *threshold < 0.0)
+        {
+            anyhow::bail!("Acceptance criteria must be finite non-negative numbers ❌")
+        }
+        if [criteria.min_auc, criteria.min_accuracy]
+            .iter()
+            .flatten()
+            .any(|threshold| *threshold > 1.0)
+        {
+            anyhow::bail!("Minimum AUC and accuracy must be between 0 and 1 ❌")
+        }
+
+        let num_rows = self.model_input()?.num_rows();
+        if num_rows == 0 {
+            anyhow::bail!("{} must have at least one row ❌", VALIDATION_FILE_NAME)
+        }
+        let needs_labels = criteria.min_auc.is_some()
+            || criteria.min_accuracy.is_some()
+            || criteria.max_mae.is_some();
+        if needs_labels && self.labels.len() != num_rows {
+            anyhow::bail!(
+                "{} has {} labels for {} rows ❌",
+                VALIDATION_FILE_NAME,
+                self.labels.len(),
+                num_rows
+            )
+        }
+        if criteria.min_auc.is_some()
+            && self
+                .labels
+                .iter()
+                .any(|label| *label != 0.0 && *label != 1.0)
+        {
+            anyhow::bail!("Labels must be 0 or 1 to compute the AUC ❌")
+        }
+        Ok(())
+    }
+
+    fn model_input(&self) -> anyhow::Result<ModelInput> {
+        ModelInput::from_str(self.input.to_string().as_str())
+    }
+
+    /// Predicts the validation set and checks the acceptance criteria.
+    ///
+    /// # Arguments
+    /// * `predictor` - The predictor of the new version of the model.
+    /// * `current` - The predictor of the version it replaces, if the model is already loaded.
+    ///
+    /// # Returns
+    /// * `Ok(ValidationReport)` - If the model meets every criterion.
+    /// * `Err(anyhow::Error)` - If the validation set cannot be predicted or the model misses a criterion.
+    pub fn evaluate(
+        &self,
+        predictor: &Predictor,
+        current: Option<&Predictor>,
+    ) -> anyhow::Result<ValidationReport> {
+        let outputs = self.predict(predictor)?;
+        let current_outputs = match (current, self.criteria.max_drift) {
+            (Some(current), Some(_)) => Some(self.predict(current)?),
+            _ => None,
+        };
+        self.check(&outputs, current_outputs.as_deref())
+    }
+
+    fn predict(&self, predictor: &Predictor) -> anyhow::Result<Vec<Vec<f64>>> {
+        let mut output = predictor.predict(self.model_input()?)?;
+        match output.predictions.remove(self.output.as_str()) {
+            Some(rows) => Ok(rows),
+            None => anyhow::bail!("Model has no output named {} to validate ❌", self.output),
+        }
+    }
+
+    /// Computes the metrics of the outputs of the validation set and checks them against the criteria.
+    ///
+    /// # Arguments
+    /// * `outputs` - The output of each row of the validation set.
+    /// * `current` - The output of each row predicted by the version being replaced, if any.
+    ///
+    /// # Errors
+    /// Returns an error listing the criteria which are missed.
+    pub fn check(
+        &self,
+        outputs: &[Vec<f64>],
+        current: Option<&[Vec<f64>]>,
+    ) -> anyhow::Result<ValidationReport> {
+        let criteria = &self.criteria;
+        let scores: Vec<f64> = outputs
+            .iter()
+            .map(|row| row.first().copied().unwrap_or(f64::NAN))
+            .collect();
+        let mut report = ValidationReport::default();
+        let mut missed = Vec::new();
+
+        if let Some(min_auc) = criteria.min_auc {
+            match auc(&scores, &self.labels) {
+                Some(auc) if auc >= min_auc => report.auc = Some(auc),
+                Some(auc) => {
+                    missed.push(format!("AUC {:.4} is below {}", auc, min_auc));
+                    report.auc = Some(auc);
+                }
+                None => missed.push("AUC requires rows of both classes".to_string()),
+            }
+        }
+
+        if let Some(min_accuracy) = criteria.min_accuracy {
+            let correct = outputs
+                .iter()
+                .zip(self.labels.iter())
+                .filter(|(row, label)| predicted_class(row) == Some(**label))
+                .count();
+            let accuracy = correct as f64 / self.labels.len().max(1) as f64;
+            if accuracy < min_accuracy {
+                missed.push(format!(
+                    "accuracy {:.4} is below {}",
+                    accuracy, min_accuracy
+                ));
+            }
+            report.accuracy = Some(accuracy);
+        }
+
+        if let Some(max_mae) = criteria.max_mae {
+            let mae = mean_absolute_difference(
+                scores.iter().copied(),
+                self.labels.iter().copied(),
+                self.labels.len(),
+            );
+            // NaN predictions must not pass
+            if mae.is_nan() || mae > max_mae {
+                missed.push(format!(
+                    "mean absolute error {:.4} is above {}",
+                    mae, max_mae
+                ));
+            }
+            report.mae = Some(mae);
+        }
+
+        if let (Some(max_drift), Some(current)) = (criteria.max_drift, current) {
+            let shapes_match = outputs.len() == current.len()
+                && outputs
+                    .iter()
+                    .zip(current.iter())
+                    .all(|(row, current_row)| row.len() == current_row.len());
+            if shapes_match {
+                let drift = mean_absolute_difference(
+                    outputs.iter().flatten().copied(),
+                    current.iter().flatten().copied(),
+                    outputs.iter().map(Vec::len).sum(),
+                );
+                if drift.is_nan() || drift > max_drift {
+                    missed.push(format!(
+                        "drift from the current version {:.4} is above {}",
+                        drift, max_drift
+                    ));
+                }
+                report.drift = Some(drift);
+            } else {
+                missed.push(format!(
+                    "output {} has a different shape than the current version",
+                    self.output
+                ));
+            }
+        }
+
+        if !missed.is_empty() {
+            anyhow::bail!(
+                "Model missed its acceptance criteria ❌: {}",
+                missed.join(", ")
+            )
+        }
+        Ok(report)
+    }
+}
+
+/// Returns the area under the ROC curve, i.e. the probability that a positive row is scored higher than a
+/// negative row, counting ties as half. Returns `None` unless there are rows of both classes.
+fn auc(scores: &[f64], labels: &[f64]) -> Option<f64> {
+    let mut rows: Vec<(f64, bool)> = scores
+        .iter()
+        .zip(labels.iter())
+        .map(|(score, label)| (*score, *label == 1.0))
+        .collect();
+    rows.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let positives = rows.iter().filter(|(_, positive)| *positive).count() as f64;
+    let negatives = rows.len() as f64 - positives;
+    if positives == 0.0 || negatives == 0.0 {
+        return None;
+    }
+
+    // sum of the ranks of the positive rows, where tied rows share their average rank
+    let mut rank_sum = 0.0;
+    let mut start = 0;
+    while start < rows.len() {
+        let mut end = start;
+        while end + 1 < rows.len() && rows[end + 1].0 == rows[start].0 {
+            end += 1;
+        }
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+        rank_sum += rank
+            * rows[start..=end]
+                .iter()
+                .filter(|(_, positive)| *positive)
+                .count() as f64;
+        start = end + 1;
+    }
+    Some((rank_sum - positives * (positives + 1.0) / 2.0) / (positives * negatives))
+}
+
+/// Returns the class predicted by a row, see `AcceptanceCriteria::min_accuracy`.
+fn predicted_class(row: &[f64]) -> Option<f64> {
+    match row {
+        [] => None,
+        [score] => Some(if *score >= 0.5 { 1.0 } else { 0.0 }),
+        _ => row
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(class, _)| class as f64),
+    }
+}
+
+fn mean_absolute_difference(
+    values: impl Iterator<Item = f64>,
+    others: impl Iterator<Item = f64>,
+    count: usize,
+) -> f64 {
+    let total: f64 = values.zip(others).map(|(a, b)| (a - b).abs()).sum();
+    total / count.max(1) as f64
+}
+
+/// Whether a model met the acceptance criteria of its validation set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationOutcome {
+    /// The model met every criterion and was promoted.
+    Accepted,
+    /// The model missed a criterion, or its validation set could not be predicted, and was not promoted.
+    Rejected,
+}
+
+impl ValidationOutcome {
+    /// Every outcome, e.g. to export the number of validations with each one.
+    pub const ALL: [ValidationOutcome; 2] =
+        [ValidationOutcome::Accepted, ValidationOutcome::Rejected];
+
+    /// Returns the name of the outcome used in logs and metrics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationOutcome::Accepted => "accepted",
+            ValidationOutcome::Rejected => "rejected",
+        }
+    }
+
+    fn counter(&self) -> &'static AtomicU64 {
+        static ACCEPTED: AtomicU64 = AtomicU64::new(0);
+        static REJECTED: AtomicU64 = AtomicU64::new(0);
+        match self {
+            ValidationOutcome::Accepted => &ACCEPTED,
+            ValidationOutcome::Rejected => &REJECTED,
+        }
+    }
+
+    /// Records a validation with this outcome.
+    pub fn record(&self) {
+        self.counter().fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the number of validations with this outcome since startup.
+    pub fn total(&self) -> u64 {
+        self.counter().load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validation_set(criteria: &str) -> ValidationSet {
+        ValidationSet::parse(
+            format!(
+                r#"{{"input": {{"age": [22.0, 38.0, 26.0, 35.0]}}, "labels": [0, 1, 1, 0], "criteria": {}}}"#,
+                criteria
+            )
+            .as_str(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn successfully_accept_model_which_meets_its_criteria() {
+        // Arrange
+        let validation =
+            validation_set(r#"{"min_auc": 0.7, "min_accuracy": 0.75, "max_drift": 0.1}"#);
+        let outputs = vec![vec![0.1], vec![0.9], vec![0.6], vec![0.7]];
+        let current = vec![vec![0.1], vec![0.8], vec![0.6], vec![0.7]];
+
+        // Act
+        let report = validation.check(&outputs, Some(&current)).unwrap();
+
+        // Assert
+        assert_eq!(report.auc, Some(0.75));
+        assert_eq!(report.accuracy, Some(0.75));
+        assert!((report.drift.unwrap() - 0.025).abs() < 1e-9);
+        assert!(report.mae.is_none());
+    }
+
+    #[test]
+    fn fails_to_accept_model_which_misses_its_criteria() {
+        // Arrange
+        let validation = validation_set(r#"{"min_auc": 0.8, "max_mae": 0.1, "max_drift": 0.1}"#);
+        let outputs = vec![vec![0.9], vec![0.1], vec![0.2], vec![0.8]];
+        let current = vec![vec![0.1], vec![0.9], vec![0.8], vec![0.2]];
+        let reshaped = vec![vec![0.1, 0.9]; 4];
+
+        // Act
+        let missed = validation.check(&outputs, Some(&current));
+        let changed_shape = validation.check(&outputs, Some(&reshaped));
+
+        // Assert
+        let message = missed.unwrap_err().to_string();
+        assert!(message.contains("AUC 0.0000 is below 0.8"));
+        assert!(message.contains("mean absolute error"));
+        assert!(message.contains("drift from the current version"));
+        assert!(changed_shape
+            .unwrap_err()
+            .to_string()
+            .contains("different shape"));
+    }
+
+    #[test]
+    fn fails_to_parse_validation_set_when_it_is_invalid() {
+        // Act
+        let no_criteria = ValidationSet::parse(r#"{"input": {"age": [22.0]}, "criteria": {}}"#);
+        let missing_labels = ValidationSet::parse(
+            r#"{"input": {"age": [22.0, 38.0]}, "labels": [1], "criteria": {"max_mae": 1.0}}"#,
+        );
+        let non_binary_labels = ValidationSet::parse(
+            r#"{"input": {"age": [22.0, 38.0]}, "labels": [1, 2], "criteria": {"min_auc": 0.8}}"#,
+        );
+        let invalid_threshold = ValidationSet::parse(
+            r#"{"input": {"age": [22.0]}, "labels": [1], "criteria": {"min_accuracy": 1.5}}"#,
+        );
+        let drift_only =
+            ValidationSet::parse(r#"{"input": {"age": [22.0]}, "criteria": {"max_drift": 0.1}}"#);
+
+        // Assert
+        assert!(no_criteria.is_err());
+        assert!(missing_labels.is_err());
+        assert!(non_binary_labels.is_err());
+        assert!(invalid_threshold.is_err());
+        assert!(drift_only.is_ok());
+    }
+}
@@ -0,0 +1,249 @@
+use crate::model_store::storage::{Metadata, ModelName};
+use dashmap::DashMap;
+// the precisions are defined in jams-predictor-api, as they are part of the prediction options
+pub use jams_predictor_api::precision::{ModelPrecision, VARIANTS_DIRECTORY_NAME};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// How long the latencies of the model artifact are remembered to estimate its recent p95 latency.
+pub const RECENT_LATENCY_WINDOW: Duration = Duration::from_secs(60);
+
+/// Minimum number of recent latencies of a model before its p95 latency is compared to latency budgets.
+pub const MIN_RECENT_LATENCIES: usize = 10;
+
+/// Time spent predicting with a variant of a model since it was loaded or since startup.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct VariantLatency {
+    /// The name of the model.
+    pub model_name: ModelName,
+    /// The precision of the variant.
+    pub precision: ModelPrecision,
+    /// The number of predictions made with the variant.
+    pub predictions: u64,
+    /// The total time spent in the model predicting with the variant, in seconds.
+    pub seconds: f64,
+}
+
+/// Tracks the time spent predicting with each variant of each model, to compare the latency of the
+/// precision variants of a model.
+///
+/// Latencies are kept in memory only, so they start over when the server restarts.
+#[derive(Default)]
+pub struct VariantLatencies(DashMap<(ModelName, ModelPrecision), (u64, Duration)>);
+
+impl VariantLatencies {
+    /// Records the time spent predicting with a variant of a model.
+    pub fn record(&self, model_name: &str, precision: ModelPrecision, elapsed: Duration) {
+        let mut totals = self
+            .0
+            .entry((model_name.to_string(), precision))
+            .or_insert((0, Duration::ZERO));
+        totals.0 += 1;
+        totals.1 += elapsed;
+    }
+
+    /// Forgets the latencies of a model once it is deleted.
+    pub fn clear(&self, model_name: &str) {
+        self.0.retain(|(name, _), _| name != model_name);
+    }
+
+    /// Returns the latencies of every variant which made predictions, sorted by model name and precision.
+    pub fn snapshot(&self) -> Vec<VariantLatency> {
+        let mut latencies: Vec<VariantLatency> = self
+            .0
+            .iter()
+            .map(|entry| VariantLatency {
+                model_name: entry.key().0.clone(),
+                precision: entry.key().1,
+                predictions: entry.value().0,
+                seconds: entry.value().1.as_secs_f64(),
+            })
+            .collect();
+        latencies.sort_by(|a, b| {
+            (a.model_name.as_str(), a.precision).cmp(&(b.model_name.as_str(), b.precision))
+        });
+        latencies
+    }
+}
+
+/// Number of predictions served by the lightweight variant of a model because the recent p95 latency of the model
+/// artifact exceeded the latency budget of the request.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LatencyFallback {
+    /// The name of the model.
+    pub model_name: ModelName,
+    /// The precision of the lightweight variant.
+    pub precision: ModelPrecision,
+    /// The number of predictions routed to the variant.
+    pub predictions: u64,
+}
+
+/// Routes the requests with a latency budget to a lightweight variant of their model, e.g. `int8`, while the
+/// recent p95 latency of the model artifact exceeds the budget, e.g. during load spikes.
+///
+/// The p95 latency is estimated by the `ModelStats` of the model from the predictions of the model artifact made in
+/// the last `RECENT_LATENCY_WINDOW`. While requests are routed to the variant, the latencies of the model artifact
+/// expire, so requests go back to the model artifact once the window has passed.
+#[derive(Default)]
+pub struct LatencyFallbacks {
+    variants: BTreeMap<ModelName, ModelPrecision>,
+    fallbacks: DashMap<ModelName, u64>,
+}
+
+impl LatencyFallbacks {
+    /// Creates the fallbacks from the lightweight variant of each model.
+    pub fn new(variants: BTreeMap<ModelName, ModelPrecision>) -> Self {
+        LatencyFallbacks {
+            variants,
+            ..Default::default()
+        }
+    }
+
+    /// Selects the lightweight variant of a model for a request with a latency budget, and counts the fallback.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - The name of the model.
+    /// * `shipped` - The precisions of the variants the model was shipped with, see `Metadata::variants`.
+    /// * `p95` - The recent p95 latency of the model artifact, see `ModelStats::quantile`.
+    /// * `budget` - The latency budget of the request.
+    ///
+    /// # Returns
+    ///
+    /// The precision of the lightweight variant if the model ships it and the recent p95 latency of its model
+    /// artifact exceeds the budget, otherwise `None`.
+    pub fn select(
+        &self,
+        model_name: &str,
+        shipped: &[ModelPrecision],
+        p95: Option<Duration>,
+        budget: Duration,
+    ) -> Option<ModelPrecision> {
+        let variant = *self.variants.get(model_name)?;
+        if !shipped.contains(&variant) {
+            tracing::debug!(
+                "Ignoring latency budget of model {} as it does not ship its {} variant ⚠️",
+                model_name,
+                variant
+            );
+            return None;
+        }
+        match p95 {
+            Some(p95) if p95 > budget => {
+                *self.fallbacks.entry(model_name.to_string()).or_insert(0) += 1;
+                Some(variant)
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks that every loaded model with a lightweight variant ships it, so that a mistyped configuration is
+    /// caught when the models are loaded rather than silently never falling back.
+    ///
+    /// # Arguments
+    ///
+    /// * `models` - The metadata of the loaded models.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first model which does not ship its lightweight variant.
+    pub fn validate(&self, models: &[Metadata]) -> anyhow::Result<()> {
+        for model in models {
+            if let Some(variant) = self.variants.get(model.name.as_str()) {
+                if !model.variants.contains(variant) {
+                    anyhow::bail!(
+                        "Model {} has no {} variant to fall back to ❌",
+                        model.name,
+                        variant
+                    )
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of predictions routed to the lightweight variant of each model since startup, sorted by
+    /// model name. The counts are kept when a model is deleted, so that they only ever increase.
+    pub fn snapshot(&self) -> Vec<LatencyFallback> {
+        let mut fallbacks: Vec<LatencyFallback> = self
+            .fallbacks
+            .iter()
+            .filter_map(|entry| {
+                self.variants
+                    .get(entry.key())
+                    .map(|precision| LatencyFallback {
+                        model_name: entry.key().clone(),
+                        precision: *precision,
+                        predictions: *entry.value(),
+                    })
+            })
+            .collect();
+        fallbacks.sort_by(|a, b| a.model_name.cmp(&b.model_name));
+        fallbacks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_record_latency_of_each_variant() {
+        // Arrange
+        let latencies = VariantLatencies::default();
+
+        // Act
+        latencies.record("my_model", ModelPrecision::Int8, Duration::from_millis(2));
+        latencies.record("my_model", ModelPrecision::Fp32, Duration::from_millis(5));
+        latencies.record("my_model", ModelPrecision::Int8, Duration::from_millis(4));
+        latencies.record(
+            "other_model",
+            ModelPrecision::Fp32,
+            Duration::from_millis(1),
+        );
+        latencies.clear("other_model");
+        let snapshot = latencies.snapshot();
+
+        // Assert
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[0].precision, ModelPrecision::Fp32);
+        assert_eq!(snapshot[1].precision, ModelPrecision::Int8);
+        assert_eq!(snapshot[1].predictions, 2);
+        assert!((snapshot[1].seconds - 0.006).abs() < 1e-9);
+    }
+
+    #[test]
+    fn successfully_select_lightweight_variant_when_p95_exceeds_budget() {
+        // Arrange
+        let fallbacks = LatencyFallbacks::new(BTreeMap::from([(
+            "my_model".to_string(),
+            ModelPrecision::Int8,
+        )]));
+        let p95 = Some(Duration::from_millis(50));
+
+        // Act
+        let shipped = [ModelPrecision::Int8];
+        let too_few = fallbacks.select("my_model", &shipped, None, Duration::from_millis(20));
+        let over_budget = fallbacks.select("my_model", &shipped, p95, Duration::from_millis(20));
+        let within_budget = fallbacks.select("my_model", &shipped, p95, Duration::from_millis(100));
+        let without_variant =
+            fallbacks.select("other_model", &shipped, p95, Duration::from_millis(20));
+        let not_shipped = fallbacks.select("my_model", &[], p95, Duration::from_millis(20));
+
+        // Assert
+        assert!(too_few.is_none());
+        assert_eq!(over_budget, Some(ModelPrecision::Int8));
+        assert!(within_budget.is_none());
+        assert!(without_variant.is_none());
+        assert!(not_shipped.is_none());
+        assert_eq!(
+            fallbacks.snapshot(),
+            vec![LatencyFallback {
+                model_name: "my_model".to_string(),
+                precision: ModelPrecision::Int8,
+                predictions: 1,
+            }]
+        );
+    }
+}
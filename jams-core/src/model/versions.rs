@@ -0,0 +1,218 @@
+#[cfg(feature = "tensorrt")]
+use crate::model::frameworks::TENSORRT;
+use crate::model::frameworks::{ModelFramework, CATBOOST, LIGHTGBM, TENSORFLOW, TORCH, XGBOOST};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Name of the file at the root of a LibTorch distribution which holds its version, e.g. `2.2.0+cpu`.
+const LIBTORCH_VERSION_FILE_NAME: &str = "build-version";
+
+/// A native machine learning library loaded by the server.
+///
+/// Comparing these versions with the versions used for training helps to debug output discrepancies
+/// between training and serving.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct NativeLibrary {
+    /// The framework whose models are run by the library.
+    pub framework: ModelFramework,
+    /// The version of the library, if it can be detected.
+    pub version: Option<String>,
+    /// The path of the shared library loaded by the process, if it can be detected. Only detected on Linux.
+    pub path: Option<String>,
+}
+
+/// Returns the native libraries loaded by the process. They are detected once and then cached.
+///
+/// The TensorFlow version is reported by TensorFlow itself and the LibTorch version is read from
+/// the `build-version` file of the LibTorch distribution. LightGBM, CatBoost, XGBoost and TensorRT do not report their
+/// version, so it is only detected if the file name of the shared library carries it,
+/// e.g. `libcatboostmodel.so.1.2.5`.
+pub fn native_libraries() -> &'static [NativeLibrary] {
+    static LIBRARIES: OnceLock<Vec<NativeLibrary>> = OnceLock::new();
+    LIBRARIES.get_or_init(|| {
+        let loaded = loaded_libraries();
+        let find = |prefixes: &[&str]| {
+            loaded
+                .iter()
+                .find(|path| {
+                    let file_name = Path::new(path)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    prefixes.iter().any(|prefix| file_name.starts_with(prefix))
+                })
+                .cloned()
+        };
+
+        let mut libraries = Vec::new();
+        #[cfg(feature = "tensorflow")]
+        {
+            let path = find(&["libtensorflow."]);
+            let version = match tensorflow::version() {
+                Ok(version) => Some(version),
+                Err(_) => path.as_deref().and_then(version_from_file_name),
+            };
+            libraries.push(NativeLibrary {
+                framework: TENSORFLOW,
+                version,
+                path,
+            });
+        }
+        #[cfg(feature = "torch")]
+        {
+            let path = find(&["libtorch_cpu.", "libtorch."]);
+            let version = path.as_deref().and_then(libtorch_version);
+            libraries.push(NativeLibrary {
+                framework: TORCH,
+                version,
+                path,
+            });
+        }
+        #[cfg(feature = "lightgbm")]
+        {
+            let path = find(&["lib_lightgbm.", "liblightgbm."]);
+            let version = path.as_deref().and_then(version_from_file_name);
+            libraries.push(NativeLibrary {
+                framework: LIGHTGBM,
+                version,
+                path,
+            });
+        }
+        #[cfg(feature = "catboost")]
+        {
+            let path = find(&["libcatboostmodel."]);
+            let version = path.as_deref().and_then(version_from_file_name);
+            libraries.push(NativeLibrary {
+                framework: CATBOOST,
+                version,
+                path,
+            });
+        }
+        #[cfg(feature = "xgboost")]
+        {
+            let path = find(&["libxgboost."]);
+            let version = path.as_deref().and_then(version_from_file_name);
+            libraries.push(NativeLibrary {
+                framework: XGBOOST,
+                version,
+                path,
+            });
+        }
+        #[cfg(feature = "tensorrt")]
+        {
+            let path = find(&["libnvinfer."]);
+            let version = path.as_deref().and_then(version_from_file_name);
+            libraries.push(NativeLibrary {
+                framework: TENSORRT,
+                version,
+                path,
+            });
+        }
+        libraries
+    })
+}
+
+/// Logs the native libraries loaded by the process.
+pub fn log_native_libraries() {
+    for library in native_libraries() {
+        tracing::info!(
+            "Loaded {} library version {} from {} 📚",
+            library.framework,
+            library.version.as_deref().unwrap_or("unknown"),
+            library.path.as_deref().unwrap_or("unknown path")
+        );
+    }
+}
+
+/// Returns the paths of the shared libraries mapped into the process.
+#[cfg(target_os = "linux")]
+fn loaded_libraries() -> Vec<String> {
+    let maps = match std::fs::read_to_string("/proc/self/maps") {
+        Ok(maps) => maps,
+        Err(e) => {
+            tracing::warn!("Failed to read the loaded libraries ⚠️: {}", e);
+            return Vec::new();
+        }
+    };
+    let mut libraries: Vec<String> = maps
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(5))
+        .filter(|path| path.starts_with('/') && path.contains(".so"))
+        .map(str::to_string)
+        .collect();
+    libraries.dedup();
+    libraries
+}
+
+#[cfg(not(target_os = "linux"))]
+fn loaded_libraries() -> Vec<String> {
+    Vec::new()
+}
+
+/// Reads the version of a LibTorch distribution from the `build-version` file next to its `lib` directory.
+fn libtorch_version(library_path: &str) -> Option<String> {
+    let root = Path::new(library_path).parent()?.parent()?;
+    match std::fs::read_to_string(root.join(LIBTORCH_VERSION_FILE_NAME)) {
+        Ok(version) if !version.trim().is_empty() => Some(version.trim().to_string()),
+        _ => version_from_file_name(library_path),
+    }
+}
+
+/// Extracts the version from the file name of a shared library, e.g. `1.2.5` from `libcatboostmodel.so.1.2.5`.
+fn version_from_file_name(library_path: &str) -> Option<String> {
+    let file_name = Path::new(library_path).file_name()?.to_string_lossy();
+    let (_, version) = file_name.split_once(".so.")?;
+    match version.split('.').all(|part| part.parse::<u32>().is_ok()) {
+        true => Some(version.to_string()),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_detect_native_libraries() {
+        // Act
+        let libraries = native_libraries();
+
+        // Assert
+        let frameworks: Vec<ModelFramework> = libraries.iter().map(|l| l.framework).collect();
+        #[allow(unused_mut)]
+        let mut expected = vec![TENSORFLOW, TORCH, LIGHTGBM, CATBOOST, XGBOOST];
+        #[cfg(feature = "tensorrt")]
+        expected.push(TENSORRT);
+        assert_eq!(frameworks, expected);
+        assert!(libraries[0].version.is_some());
+    }
+
+    #[test]
+    fn successfully_extract_version_from_file_name() {
+        // Act
+        let versioned = version_from_file_name("/usr/lib/libcatboostmodel.so.1.2.5");
+        let unversioned = version_from_file_name("/usr/lib/lib_lightgbm.so");
+        let suffixed = version_from_file_name("/usr/lib/libtorch.so.debug");
+
+        // Assert
+        assert_eq!(versioned, Some("1.2.5".to_string()));
+        assert_eq!(unversioned, None);
+        assert_eq!(suffixed, None);
+    }
+
+    #[test]
+    fn successfully_read_libtorch_version_from_distribution() {
+        // Arrange
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("lib")).unwrap();
+        std::fs::write(root.path().join(LIBTORCH_VERSION_FILE_NAME), "2.2.0+cpu\n").unwrap();
+        let library = root.path().join("lib").join("libtorch_cpu.so");
+
+        // Act
+        let version = libtorch_version(library.to_str().unwrap());
+
+        // Assert
+        assert_eq!(version, Some("2.2.0+cpu".to_string()));
+    }
+}
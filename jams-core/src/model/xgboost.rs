@@ -1,21 +1,577 @@
+use crate::determinism::determinism;
 use crate::model::input::ModelInput;
-use crate::model::output::ModelOutput;
-use crate::model::predict::Predict;
+use crate::model::lifecycle::Lifecycle;
+use crate::model::output::{
+    split_into_rows, ModelOutput, DEFAULT_OUTPUT_KEY, LEAF_INDEX_OUTPUT_KEY,
+};
+use crate::model::predict::{Predict, PredictMode, PredictOptions};
+use crate::model::sessions::SessionPool;
+use crate::model::settings::ModelSettings;
+use crate::{Error, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::RwLock;
 
-pub struct XGBoost {}
+/// Name of the model setting with the number of threads used by each prediction of an XGBoost model.
+pub const NTHREAD_SETTING: &str = "nthread";
+
+/// Name of the model setting with the value which marks a missing feature of an XGBoost model, in addition to `NaN`.
+pub const MISSING_SETTING: &str = "missing";
+
+/// Struct representing the input data format for an XGBoost model.
+///
+/// XGBoost reads dense matrices in row-major order, whereas the features of a `ModelInput` are stored one
+/// column after another, so the features are transposed into a single buffer.
+struct XGBoostModelInput {
+    /// Numerical features in row-major order. Missing values are `f32::NAN`, which is how XGBoost marks them.
+    pub values: Vec<f32>,
+    /// The number of rows in the input.
+    pub num_rows: usize,
+}
+
+impl XGBoostModelInput {
+    /// Parses the input `ModelInput` into an `XGBoostModelInput`.
+    ///
+    /// The float features come first, followed by the integer features, as for LightGBM models.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_input` - The `ModelInput` containing the input values.
+    /// * `missing` - The value which marks a missing feature, if the model was trained with one other than `NaN`.
+    ///   The values equal to it are replaced by `NaN`.
+    /// * `values` - A buffer to transpose the features into, which is cleared first so that its allocation is
+    ///   reused across predictions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the input is empty or contains string features, which have to be
+    /// preprocessed since XGBoost models only take numerical features.
+    #[tracing::instrument(skip(model_input, values))]
+    pub fn parse(
+        model_input: ModelInput,
+        missing: Option<f32>,
+        mut values: Vec<f32>,
+    ) -> anyhow::Result<Self> {
+        if (model_input.integer_features.values.is_empty())
+            && (model_input.float_features.values.is_empty())
+        {
+            tracing::error!("input is empty");
+            anyhow::bail!("input is empty")
+        }
+        if !model_input.string_features.values.is_empty() {
+            tracing::error!("XGBoost models do not support string features");
+            anyhow::bail!("XGBoost models do not support string features")
+        }
+
+        let num_rows = model_input.num_rows();
+        let floats = model_input.float_features.values.into_floats().unwrap();
+        let ints = model_input.integer_features.values.into_ints().unwrap();
+        let num_features = (floats.len() + ints.len()) / num_rows.max(1);
+        let num_float_features = floats.len() / num_rows.max(1);
+
+        // transpose the columns into rows, converting the integer features to float
+        values.clear();
+        values.reserve(num_rows * num_features);
+        for row in 0..num_rows {
+            for feature in 0..num_float_features {
+                values.push(floats[feature * num_rows + row]);
+            }
+            for feature in 0..num_features - num_float_features {
+                values.push(ints[feature * num_rows + row] as f32);
+            }
+        }
+        if let Some(missing) = missing {
+            values
+                .iter_mut()
+                .filter(|value| **value == missing)
+                .for_each(|value| *value = f32::NAN);
+        }
+
+        Ok(Self { values, num_rows })
+    }
+}
+
+/// A booster of an XGBoost model, which holds a raw handle into the XGBoost library, with its settings and the
+/// buffer its inputs are transposed into.
+struct Session {
+    booster: xgb::Booster,
+    /// The value which marks a missing feature, from the `missing` setting of the model.
+    missing: Option<f32>,
+    /// The buffer of the last input, reused by the next prediction of the booster.
+    buffer: Vec<f32>,
+}
+
+// SAFETY: a session is only used by the worker which took it from the pool, so that the library is never called
+// for the same booster from two threads at once.
+unsafe impl Send for Session {}
+
+/// The settings applied to every booster of an XGBoost model.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct BoosterSettings {
+    /// The number of threads used by each prediction, from the `nthread` setting of the model.
+    nthread: Option<u32>,
+    /// The value which marks a missing feature, from the `missing` setting of the model.
+    missing: Option<f32>,
+}
+
+impl BoosterSettings {
+    /// Applies the settings to a booster of the model.
+    fn apply(&self, session: &mut Session) -> anyhow::Result<()> {
+        if let Some(nthread) = self.nthread {
+            if let Err(e) = session
+                .booster
+                .set_param(NTHREAD_SETTING, nthread.to_string().as_str())
+            {
+                anyhow::bail!(
+                    "Failed to set {} of XGBoost model ❌: {}",
+                    NTHREAD_SETTING,
+                    e
+                )
+            }
+        }
+        session.missing = self.missing;
+        Ok(())
+    }
+}
+
+/// Struct representing a predictor using an XGBoost model.
+///
+/// The model is loaded from the JSON (`.json`) or UBJSON (`.ubj`) format saved by `Booster.save_model`.
+/// A booster runs one prediction at a time, so each worker predicting with the model gets a booster of its own from
+/// a pool, built from the bytes of the model read at load. Each prediction runs on the number of threads set by the
+/// `nthread` setting of the model. Without it, XGBoost uses the OpenMP default, i.e. `OMP_NUM_THREADS` if it is set
+/// and all the cores otherwise. In deterministic mode every prediction runs on a single thread, whatever the
+/// `nthread` setting. Features equal to the `missing` setting of the model are treated as missing, as are `NaN`
+/// features.
+///
+/// # Example
+/// ```toml
+/// [config.model_settings.my_xgboost_model]
+/// nthread = "4"
+/// missing = "-999"
+/// ```
+pub struct XGBoost {
+    model: Vec<u8>,
+    settings: RwLock<BoosterSettings>,
+    sessions: SessionPool<Session>,
+}
 
 impl XGBoost {
-    #[allow(dead_code)]
-    pub fn load(_: &str) -> anyhow::Result<Self> {
-        todo!()
+    /// Loads an XGBoost model from the specified file path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A string slice that holds the path to the XGBoost model file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if there is an issue loading the XGBoost model from the file.
+    #[tracing::instrument]
+    pub fn load(path: &str) -> Result<Self> {
+        let model = match std::fs::read(path) {
+            Ok(model) => model,
+            Err(e) => {
+                tracing::error!("Failed to load XGBoost model from file {}: {}", path, e);
+                return Err(Error::Load(anyhow::anyhow!(
+                    "Failed to load XGBoost model from file {}: {}",
+                    path,
+                    e
+                )));
+            }
+        };
+        match build_booster(&model) {
+            Ok(booster) => Ok(XGBoost {
+                model,
+                settings: RwLock::new(BoosterSettings::default()),
+                sessions: SessionPool::new(Session {
+                    booster,
+                    missing: None,
+                    buffer: Vec::new(),
+                }),
+            }),
+            Err(e) => {
+                tracing::error!("Failed to load XGBoost model from file {}: {}", path, e);
+                Err(Error::Load(anyhow::anyhow!(
+                    "Failed to load XGBoost model from file {}: {}",
+                    path,
+                    e
+                )))
+            }
+        }
+    }
+
+    fn settings(&self) -> BoosterSettings {
+        match self.settings.read() {
+            Ok(settings) => *settings,
+            Err(poisoned) => *poisoned.into_inner(),
+        }
+    }
+
+    /// Builds a new booster of the model from its bytes, with the settings of the model applied.
+    fn session(&self) -> anyhow::Result<Session> {
+        let mut session = Session {
+            booster: build_booster(&self.model)?,
+            missing: None,
+            buffer: Vec::new(),
+        };
+        self.settings().apply(&mut session)?;
+        Ok(session)
+    }
+}
+
+/// Builds a booster of a model from its bytes.
+fn build_booster(model: &[u8]) -> anyhow::Result<xgb::Booster> {
+    match xgb::Booster::load_buffer(model) {
+        Ok(booster) => Ok(booster),
+        Err(e) => anyhow::bail!("{}", e),
+    }
+}
+
+/// Parses the `nthread` and `missing` settings of an XGBoost model.
+fn parse_settings(settings: &ModelSettings) -> anyhow::Result<(Option<u32>, Option<f32>)> {
+    let nthread = match settings.get(NTHREAD_SETTING) {
+        None => None,
+        Some(nthread) => match nthread.parse::<u32>() {
+            Ok(nthread) if nthread > 0 => Some(nthread),
+            _ => anyhow::bail!(
+                "Setting {} must be a positive integer, got {} ❌",
+                NTHREAD_SETTING,
+                nthread
+            ),
+        },
+    };
+    let missing = match settings.get(MISSING_SETTING) {
+        None => None,
+        Some(missing) => match missing.parse::<f32>() {
+            Ok(missing) if missing.is_finite() => Some(missing),
+            _ => anyhow::bail!(
+                "Setting {} must be a finite number, got {} ❌",
+                MISSING_SETTING,
+                missing
+            ),
+        },
+    };
+    Ok((nthread, missing))
+}
+
+#[async_trait]
+impl Lifecycle for XGBoost {
+    /// Applies the `nthread` and `missing` settings of the model to the booster built at load, and to every booster
+    /// built afterwards. In deterministic mode `nthread` is always 1.
+    async fn init(&self, settings: &ModelSettings) -> anyhow::Result<()> {
+        let (nthread, missing) = parse_settings(settings)?;
+        let nthread = match determinism() {
+            Some(_) => Some(1),
+            None => nthread,
+        };
+        let settings = BoosterSettings { nthread, missing };
+        match self.settings.write() {
+            Ok(mut current) => *current = settings,
+            Err(poisoned) => *poisoned.into_inner() = settings,
+        }
+        self.sessions
+            .run(|| self.session(), |session| settings.apply(session))
     }
 }
 
 impl Predict for XGBoost {
-    fn predict(&self, _: ModelInput) -> anyhow::Result<ModelOutput> {
-        todo!()
+    /// Performs prediction using the loaded XGBoost model.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The `ModelInput` containing the input values for prediction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if there is an issue parsing the input or performing the prediction.
+    #[tracing::instrument(skip(self, input))]
+    fn predict(&self, input: ModelInput) -> Result<ModelOutput> {
+        self.predict_with_options(input, &PredictOptions::default())
+    }
+
+    /// Performs prediction using the loaded XGBoost model and the given prediction options.
+    ///
+    /// The model returns the transformed predictions of its objective, e.g. probabilities for classifiers,
+    /// unless another `mode` is set. In `raw_score` mode the untransformed margins are returned, and in
+    /// `leaf_index` mode the index of the leaf each row falls into in every tree is returned as the
+    /// `leaf_indices` output. `num_iteration` is not supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The `ModelInput` containing the input values for prediction.
+    /// * `options` - The prediction options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the options are not supported, or if there is an issue parsing the input or
+    /// performing the prediction.
+    #[tracing::instrument(skip(self, input))]
+    fn predict_with_options(
+        &self,
+        input: ModelInput,
+        options: &PredictOptions,
+    ) -> Result<ModelOutput> {
+        options.validate().map_err(Error::Input)?;
+        if options.num_iteration.is_some() {
+            return Err(Error::Input(anyhow::anyhow!(
+                "num_iteration is not supported by XGBoost models ❌"
+            )));
+        }
+        let mode = options.mode.unwrap_or(PredictMode::Normal);
+        let prediction = self.sessions.run(
+            || self.session(),
+            |session| {
+                let buffer = std::mem::take(&mut session.buffer);
+                let input = match XGBoostModelInput::parse(input, session.missing, buffer) {
+                    Ok(input) => input,
+                    // an invalid input leaves the booster untouched, so it is put back in the pool
+                    Err(e) => return Ok(Err(Error::Input(e))),
+                };
+                let dmatrix =
+                    match xgb::DMatrix::from_dense(input.values.as_slice(), input.num_rows) {
+                        Ok(dmatrix) => dmatrix,
+                        Err(e) => {
+                            tracing::error!("Failed to create XGBoost DMatrix: {}", e);
+                            return Ok(Err(Error::Input(anyhow::anyhow!(
+                                "Failed to create XGBoost DMatrix: {}",
+                                e
+                            ))));
+                        }
+                    };
+
+                let preds = match mode {
+                    PredictMode::Normal => session.booster.predict(&dmatrix),
+                    PredictMode::RawScore => session.booster.predict_margin(&dmatrix),
+                    PredictMode::LeafIndex => session
+                        .booster
+                        .predict_leaf(&dmatrix)
+                        .map(|(preds, _)| preds),
+                };
+                // the DMatrix holds a copy of the input, so the buffer is given back for the next prediction
+                drop(dmatrix);
+                session.buffer = input.values;
+                match preds {
+                    Ok(preds) => Ok(Ok((preds, input.num_rows))),
+                    Err(e) => anyhow::bail!("{}", e),
+                }
+            },
+        );
+        let (preds, num_rows) = match prediction {
+            Ok(Ok(prediction)) => prediction,
+            Ok(Err(e)) => return Err(e),
+            Err(e) => {
+                tracing::error!("Failed to make predictions using XGBoost: {}", e);
+                return Err(Error::Predict(anyhow::anyhow!(
+                    "Failed to make predictions using XGBoost: {}",
+                    e
+                )));
+            }
+        };
+
+        let output_key = match mode {
+            PredictMode::LeafIndex => LEAF_INDEX_OUTPUT_KEY,
+            _ => DEFAULT_OUTPUT_KEY,
+        };
+        let preds: Vec<f64> = preds.into_iter().map(|x| x as f64).collect();
+        // multiclass models return one value per class for each row and
+        // leaf indices are returned for every tree of each row
+        let values = split_into_rows(&preds, num_rows).map_err(Error::Predict)?;
+        let mut predictions: HashMap<String, Vec<Vec<f64>>> = HashMap::new();
+        predictions.insert(output_key.to_string(), values);
+        Ok(ModelOutput {
+            predictions,
+            labels: HashMap::new(),
+            shapes: HashMap::new(),
+            texts: HashMap::new(),
+        })
     }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::model::test_utils;
+
+    #[test]
+    fn fails_to_load_xgboost_model() {
+        let model_dir = "incorrect/path";
+        let model = XGBoost::load(model_dir);
+
+        // assert the result is Ok
+        assert!(model.is_err())
+    }
+
+    #[test]
+    fn successfully_transpose_xgboost_model_input_into_rows() {
+        let mut model_input = ModelInput::default();
+        model_input.float_features.values = crate::model::input::Values::Float(vec![
+            0.1, 0.2, 0.3, // first float feature
+            1.1, 1.2, 1.3, // second float feature
+        ]);
+        model_input.float_features.shape = (2, 3);
+        model_input.integer_features.values = crate::model::input::Values::Int(vec![7, 8, 9]);
+        model_input.integer_features.shape = (1, 3);
+
+        // parse the input
+        let input = XGBoostModelInput::parse(model_input, None, Vec::new()).unwrap();
+
+        // assert each row holds the float features followed by the integer features
+        assert_eq!(input.num_rows, 3);
+        assert_eq!(
+            input.values,
+            vec![0.1, 1.1, 7.0, 0.2, 1.2, 8.0, 0.3, 1.3, 9.0]
+        );
+    }
+
+    #[test]
+    fn fails_to_parse_xgboost_model_input_when_input_is_empty() {
+        let model_inputs = test_utils::utils::create_model_inputs(28, 0, 0);
+
+        // parse the input
+        let input = XGBoostModelInput::parse(model_inputs, None, Vec::new());
+
+        // assert the result is an error
+        assert!(input.is_err());
+    }
+
+    #[test]
+    fn fails_to_parse_xgboost_model_input_with_string_features() {
+        let model_inputs = test_utils::utils::create_model_inputs(2, 1, 3);
+
+        // parse the input
+        let input = XGBoostModelInput::parse(model_inputs, None, Vec::new());
+
+        // assert the result is an error
+        assert!(input.is_err());
+    }
+
+    #[test]
+    fn successfully_replace_missing_values_of_xgboost_model_input() {
+        let mut model_input = ModelInput::default();
+        model_input.float_features.values =
+            crate::model::input::Values::Float(vec![0.1, -999.0, 0.3]);
+        model_input.float_features.shape = (1, 3);
+        model_input.integer_features.values = crate::model::input::Values::Int(vec![-999, 8, 9]);
+        model_input.integer_features.shape = (1, 3);
+
+        // parse the input into a buffer of a previous prediction
+        let input = XGBoostModelInput::parse(model_input, Some(-999.0), vec![5.0; 16]).unwrap();
+
+        // assert the values equal to the missing value are NaN and the buffer was cleared
+        assert_eq!(input.values.len(), 6);
+        let missing: Vec<bool> = input.values.iter().map(|value| value.is_nan()).collect();
+        assert_eq!(missing, vec![false, true, true, false, false, false]);
+        assert_eq!(input.values[4], 0.3);
+    }
+
+    #[test]
+    fn successfully_parse_xgboost_settings() {
+        let settings = ModelSettings::new(
+            [
+                (NTHREAD_SETTING.to_string(), "4".to_string()),
+                (MISSING_SETTING.to_string(), "-999".to_string()),
+            ]
+            .into(),
+        );
+
+        // parse the settings
+        let (nthread, missing) = parse_settings(&settings).unwrap();
+
+        // assert
+        assert_eq!(nthread, Some(4));
+        assert_eq!(missing, Some(-999.0));
+        assert_eq!(
+            parse_settings(&ModelSettings::default()).unwrap(),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn fails_to_parse_invalid_xgboost_settings() {
+        for (name, value) in [
+            (NTHREAD_SETTING, "0"),
+            (NTHREAD_SETTING, "all"),
+            (MISSING_SETTING, "NaN"),
+            (MISSING_SETTING, "none"),
+        ] {
+            let settings = ModelSettings::new([(name.to_string(), value.to_string())].into());
+
+            // assert the settings are rejected
+            assert!(parse_settings(&settings).is_err(), "{}={}", name, value)
+        }
+    }
+
+    #[tokio::test]
+    async fn successfully_load_and_predict_using_xgboost_model() {
+        let model =
+            XGBoost::load("tests/model_storage/models/xgboost-my_awesome_reg_model.json").unwrap();
+        let settings = ModelSettings::new(
+            [
+                (NTHREAD_SETTING.to_string(), "2".to_string()),
+                (MISSING_SETTING.to_string(), "-999".to_string()),
+            ]
+            .into(),
+        );
+        model.init(&settings).await.unwrap();
+        let model_inputs = test_utils::utils::create_model_inputs(2, 0, 8);
+        let num_rows = model_inputs.num_rows();
+
+        // predict twice, the second prediction reusing the booster and the buffer of the first
+        let output = model.predict(model_inputs.clone()).unwrap();
+        let raw_output = model
+            .predict_with_options(
+                model_inputs,
+                &PredictOptions {
+                    mode: Some(PredictMode::RawScore),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // assert there is a prediction for each row and the regression model has no link function
+        let predictions = output.predictions.get(DEFAULT_OUTPUT_KEY).unwrap();
+        assert_eq!(predictions.len(), num_rows);
+        assert!(predictions.iter().all(|row| row.len() == 1));
+        assert_eq!(
+            predictions,
+            raw_output.predictions.get(DEFAULT_OUTPUT_KEY).unwrap()
+        );
+        assert_eq!(model.sessions.idle(), 1);
+    }
+
+    #[tokio::test]
+    async fn successfully_apply_xgboost_settings_to_new_boosters() {
+        let model =
+            XGBoost::load("tests/model_storage/models/xgboost-my_awesome_reg_model.json").unwrap();
+        let settings = ModelSettings::new(
+            [
+                (NTHREAD_SETTING.to_string(), "2".to_string()),
+                (MISSING_SETTING.to_string(), "-999".to_string()),
+            ]
+            .into(),
+        );
+        model.init(&settings).await.unwrap();
+
+        // build a booster as when every booster of the pool is busy
+        let session = model.session().unwrap();
+
+        // assert the booster built at load and the new booster both have the settings
+        assert_eq!(session.missing, Some(-999.0));
+        assert_eq!(model.sessions.idle(), 1);
+        model
+            .sessions
+            .run(
+                || anyhow::bail!("no booster should be built"),
+                |session| {
+                    assert_eq!(session.missing, Some(-999.0));
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn successfully_match_golden_outputs_using_xgboost_models() {
+        test_utils::golden::check_golden("xgboost-my_awesome_reg_model", XGBoost::load);
+    }
+}
@@ -0,0 +1,392 @@
+#[cfg(feature = "aws")]
+use crate::model_store::aws::s3::S3ModelStore;
+#[cfg(feature = "azure")]
+use crate::model_store::azure::blob_storage::AzureBlobStorageModelStore;
+#[cfg(not(all(feature = "aws", feature = "azure")))]
+use crate::model_store::backend_disabled;
+use crate::model_store::deadline::StoreTimeouts;
+use crate::model_store::local::filesystem::LocalModelStore;
+use crate::model_store::storage::ModelName;
+use crate::model_store::supervisor::{Poller, PollerHealth, DEFAULT_STORE_NAME};
+use crate::model_store::ModelStore;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+
+/// Separates the name of an attached model store from the names of its models, e.g. `archive.my_model`.
+///
+/// Model names never contain it, as everything after the first `.` of a tarball name is dropped.
+pub const STORE_SEPARATOR: char = '.';
+
+/// Maximum length in bytes of the name of an attached model store.
+pub const MAX_STORE_NAME_LENGTH: usize = 64;
+
+/// Where an attached model store reads its models from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoreSource {
+    /// An AWS S3 bucket, `s3://<bucket>`.
+    S3(String),
+    /// A MinIO bucket, `minio://<bucket>`.
+    Minio(String),
+    /// An Azure Blob Storage container, `azure://<container>`.
+    Azure(String),
+    /// A directory on the local filesystem, `file://<dir>`.
+    Local(String),
+}
+
+impl StoreSource {
+    /// Parses the URL of a model store.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL, e.g. `s3://my-bucket`, `minio://my-bucket`, `azure://my-container` or `file:///models`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scheme is not supported or the bucket, container or directory is missing.
+    pub fn parse(url: &str) -> anyhow::Result<Self> {
+        let (scheme, location) = match url.split_once("://") {
+            Some((scheme, location)) if !location.trim_end_matches('/').is_empty() => {
+                (scheme, location)
+            }
+            _ => anyhow::bail!(
+                "Model store URL must look like <scheme>://<location> but got {} ❌",
+                url
+            ),
+        };
+        match scheme {
+            "s3" => Ok(StoreSource::S3(location.trim_end_matches('/').to_string())),
+            "minio" => Ok(StoreSource::Minio(location.trim_end_matches('/').to_string())),
+            "azure" => Ok(StoreSource::Azure(location.trim_end_matches('/').to_string())),
+            "file" => Ok(StoreSource::Local(location.to_string())),
+            _ => anyhow::bail!(
+                "Model store URL must start with s3://, minio://, azure:// or file:// but got {} ❌",
+                url
+            ),
+        }
+    }
+
+    /// Connects to the model store and loads its models.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeouts` - The deadlines of the calls made to a remote model store.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend of the model store was left out of this build, or the model store cannot
+    /// be reached or its models cannot be loaded.
+    #[cfg_attr(not(any(feature = "aws", feature = "azure")), allow(unused_variables))]
+    pub async fn connect(&self, timeouts: StoreTimeouts) -> anyhow::Result<ModelStore> {
+        match self {
+            #[cfg(feature = "aws")]
+            StoreSource::S3(bucket) => Ok(ModelStore::AWS(
+                S3ModelStore::new(bucket.clone(), None)
+                    .await?
+                    .with_timeouts(timeouts),
+            )),
+            #[cfg(feature = "aws")]
+            StoreSource::Minio(bucket) => Ok(ModelStore::AWS(
+                S3ModelStore::new(bucket.clone(), Some(true))
+                    .await?
+                    .with_timeouts(timeouts),
+            )),
+            #[cfg(feature = "azure")]
+            StoreSource::Azure(container) => Ok(ModelStore::Azure(
+                AzureBlobStorageModelStore::new(container.clone())
+                    .await?
+                    .with_timeouts(timeouts),
+            )),
+            #[cfg(not(feature = "aws"))]
+            StoreSource::S3(_) | StoreSource::Minio(_) => Err(backend_disabled(
+                format!("Model store {}", self).as_str(),
+                "aws",
+            )),
+            #[cfg(not(feature = "azure"))]
+            StoreSource::Azure(_) => Err(backend_disabled(
+                format!("Model store {}", self).as_str(),
+                "azure",
+            )),
+            StoreSource::Local(dir) => {
+                Ok(ModelStore::Local(LocalModelStore::new(dir.clone()).await?))
+            }
+        }
+    }
+}
+
+impl fmt::Display for StoreSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreSource::S3(bucket) => write!(f, "s3://{}", bucket),
+            StoreSource::Minio(bucket) => write!(f, "minio://{}", bucket),
+            StoreSource::Azure(container) => write!(f, "azure://{}", container),
+            StoreSource::Local(dir) => write!(f, "file://{}", dir),
+        }
+    }
+}
+
+/// A model store attached at runtime alongside the model store the server was started with.
+struct AttachedStore {
+    source: StoreSource,
+    model_store: Arc<ModelStore>,
+    /// Polls the model store for updates, if polling is enabled. It is stopped when the store is detached.
+    poller: Option<Poller>,
+}
+
+/// Describes a model store attached at runtime.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AttachedStoreInfo {
+    /// The name of the store, which prefixes the names of its models.
+    pub name: String,
+    /// The URL of the store, e.g. `s3://my-bucket`.
+    pub url: String,
+    /// The number of models loaded from the store.
+    pub models: usize,
+}
+
+/// The model stores attached at runtime, keyed by their name.
+///
+/// Their models are served as `<store_name>.<model_name>`, so that models with the same name in different
+/// stores do not collide with each other or with the models of the model store the server was started with.
+#[derive(Default)]
+pub struct AttachedStores(DashMap<String, AttachedStore>);
+
+impl AttachedStores {
+    /// Checks that a store name can prefix model names unambiguously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is empty, too long, contains anything but ASCII letters, digits, `_` and `-`,
+    /// or is the name reserved for the model store the server was started with.
+    pub fn validate_name(name: &str) -> anyhow::Result<()> {
+        if name.is_empty() || name.len() > MAX_STORE_NAME_LENGTH {
+            anyhow::bail!(
+                "Model store name must be between 1 and {} bytes ❌",
+                MAX_STORE_NAME_LENGTH
+            )
+        }
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            anyhow::bail!(
+                "Model store name {} must only contain ASCII letters, digits, '_' and '-' ❌",
+                name
+            )
+        }
+        if name == DEFAULT_STORE_NAME {
+            anyhow::bail!(
+                "Model store name {} is reserved for the model store the server was started with ❌",
+                name
+            )
+        }
+        Ok(())
+    }
+
+    /// Returns the health of the poller of every attached store which is polled, sorted by store name.
+    pub fn poller_health(&self) -> Vec<PollerHealth> {
+        let mut health: Vec<PollerHealth> = self
+            .0
+            .iter()
+            .filter_map(|store| store.poller.as_ref().map(Poller::health))
+            .collect();
+        health.sort_by(|a, b| a.store.cmp(&b.store));
+        health
+    }
+
+    /// Returns true if a store with the given name is attached.
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    /// Attaches a connected model store.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the store.
+    /// * `source` - Where the store reads its models from.
+    /// * `model_store` - The connected model store.
+    /// * `poller` - The poller of the store, if polling is enabled. It is stopped if the store cannot be attached.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a store with the same name is already attached.
+    pub fn insert(
+        &self,
+        name: String,
+        source: StoreSource,
+        model_store: Arc<ModelStore>,
+        poller: Option<Poller>,
+    ) -> anyhow::Result<()> {
+        let store = AttachedStore {
+            source,
+            model_store,
+            poller,
+        };
+        match self.0.entry(name) {
+            Entry::Occupied(entry) => {
+                anyhow::bail!("Model store {} is already attached ❌", entry.key())
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(store);
+                Ok(())
+            }
+        }
+    }
+
+    /// Detaches a model store, stopping its poller.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Arc<ModelStore>)` - The detached store, whose models are dropped once the in-flight predictions finish.
+    /// * `None` - If no store with the given name is attached.
+    pub fn remove(&self, name: &str) -> Option<Arc<ModelStore>> {
+        self.0
+            .remove(name)
+            .map(|(_, store)| store.model_store.clone())
+    }
+
+    /// Returns the store a model belongs to, along with the name of the model in that store.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - The name of the model, e.g. `archive.my_model`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some((Arc<ModelStore>, ModelName))` - If the model name is prefixed by the name of an attached store.
+    /// * `None` - Otherwise, i.e. the model belongs to the model store the server was started with.
+    pub fn resolve(&self, model_name: &str) -> Option<(Arc<ModelStore>, ModelName)> {
+        let (name, model_name) = model_name.split_once(STORE_SEPARATOR)?;
+        self.0
+            .get(name)
+            .map(|store| (store.model_store.clone(), model_name.to_string()))
+    }
+
+    /// Returns every attached store along with its name, sorted by name.
+    pub fn stores(&self) -> Vec<(String, Arc<ModelStore>)> {
+        let mut stores: Vec<(String, Arc<ModelStore>)> = self
+            .0
+            .iter()
+            .map(|store| (store.key().clone(), store.model_store.clone()))
+            .collect();
+        stores.sort_by(|(a, _), (b, _)| a.cmp(b));
+        stores
+    }
+
+    /// Describes every attached store, sorted by name.
+    pub fn describe(&self) -> Vec<AttachedStoreInfo> {
+        let mut stores: Vec<AttachedStoreInfo> = self
+            .0
+            .iter()
+            .map(|store| AttachedStoreInfo {
+                name: store.key().clone(),
+                url: store.source.to_string(),
+                models: store
+                    .model_store
+                    .get_models()
+                    .map(|models| models.len())
+                    .unwrap_or(0),
+            })
+            .collect();
+        stores.sort_by(|a, b| a.name.cmp(&b.name));
+        stores
+    }
+}
+
+/// Prefixes the name of a model with the name of the attached store it belongs to.
+pub fn qualify(store_name: &str, model_name: &str) -> ModelName {
+    format!("{}{}{}", store_name, STORE_SEPARATOR, model_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{with_budget, MemoryBudget};
+
+    #[test]
+    fn successfully_parse_store_source() {
+        // Act
+        let sources = [
+            "s3://my-bucket/",
+            "minio://my-bucket",
+            "azure://my-container",
+            "file:///models",
+        ]
+        .map(|url| StoreSource::parse(url).unwrap());
+
+        // Assert
+        assert_eq!(
+            sources,
+            [
+                StoreSource::S3("my-bucket".to_string()),
+                StoreSource::Minio("my-bucket".to_string()),
+                StoreSource::Azure("my-container".to_string()),
+                StoreSource::Local("/models".to_string()),
+            ]
+        );
+        assert_eq!(sources[0].to_string(), "s3://my-bucket");
+    }
+
+    #[test]
+    fn fails_to_parse_store_source_when_url_is_invalid() {
+        for url in ["gs://my-bucket", "s3://", "my-bucket"] {
+            // assert result is err
+            assert!(StoreSource::parse(url).is_err(), "{}", url)
+        }
+    }
+
+    #[test]
+    fn fails_to_validate_store_name_when_it_is_ambiguous() {
+        for name in [
+            "",
+            "my.store",
+            "my/store",
+            &"a".repeat(MAX_STORE_NAME_LENGTH + 1),
+            DEFAULT_STORE_NAME,
+        ] {
+            // assert result is err
+            assert!(AttachedStores::validate_name(name).is_err(), "{}", name)
+        }
+        assert!(AttachedStores::validate_name("archive-2024_q1").is_ok());
+    }
+
+    #[cfg(not(feature = "aws"))]
+    #[tokio::test]
+    async fn fails_to_connect_store_left_out_of_build() {
+        // Arrange
+        let source = StoreSource::parse("s3://my-bucket").unwrap();
+
+        // Act
+        let result = source.connect(StoreTimeouts::default()).await;
+
+        // Assert
+        let e = result.err().unwrap().to_string();
+        assert!(
+            e.contains("s3://my-bucket") && e.contains("aws feature"),
+            "{}",
+            e
+        );
+    }
+
+    #[tokio::test]
+    async fn successfully_skip_models_of_attached_store_which_exceed_the_memory_budget() {
+        // Arrange
+        let source = StoreSource::Local("tests/model_storage/model_store".to_string());
+
+        // Act
+        let within_budget = source.connect(StoreTimeouts::default()).await.unwrap();
+        let over_budget = with_budget(
+            Some(MemoryBudget::new(1024)),
+            source.connect(StoreTimeouts::default()),
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert!(!within_budget.get_models().unwrap().is_empty());
+        assert!(over_budget.get_models().unwrap().is_empty());
+    }
+}
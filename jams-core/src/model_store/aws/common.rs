@@ -1,11 +1,22 @@
 use aws_sdk_s3::client as s3;
+use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::operation::get_object::GetObjectError;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Seek, SeekFrom};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
 
-use crate::model_store::common::save_and_upack_tarball;
+use crate::model_store::common::{save_and_upack_tarball, save_and_upack_tarball_from};
+use crate::model_store::delta::{self, BlockManifest, DeltaCache};
+use crate::model_store::encryption;
 
 /// Downloads objects from an S3 bucket and saves them to a local directory.
 ///
 /// This function downloads objects with specified keys from the S3 bucket using an `s3::Client` instance,
 /// saves them to a temporary directory, and unpacks them into the specified output directory.
+/// The object URI and version id (or etag) are recorded as the provenance of the unpacked artifacts.
+/// If delta updates are installed, objects with a block manifest are downloaded as a delta of their
+/// previous version, falling back to a full download.
 ///
 /// # Arguments
 ///
@@ -49,6 +60,50 @@ pub async fn download_objects(
     };
 
     for object_key in object_keys {
+        if let Some(cache) = delta::installed() {
+            match download_delta(client, bucket_name.as_str(), object_key.as_str(), cache).await {
+                Ok(Some((mut data, source_version))) => {
+                    // the assembled tarball is copied into the delta cache and the model store from its file
+                    let key = object_key.clone();
+                    let temp_path = temp_path.to_string();
+                    let out_dir = out_dir.to_string();
+                    let source_uri = format!("s3://{}/{}", bucket_name, object_key);
+                    let saved = tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+                        data.seek(SeekFrom::Start(0))?;
+                        cache.write_from(key.as_str(), &mut BufReader::new(&mut data));
+                        data.seek(SeekFrom::Start(0))?;
+                        save_and_upack_tarball_from(
+                            temp_path.as_str(),
+                            key,
+                            &mut BufReader::new(&mut data),
+                            out_dir.as_str(),
+                            source_uri,
+                            source_version,
+                        )
+                    })
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|saved| saved);
+                    if let Err(e) = saved {
+                        tracing::warn!(
+                            "Failed to save artefact {} ⚠️: {}",
+                            object_key,
+                            e.to_string()
+                        )
+                    }
+                    continue;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to download artefact {} as a delta, downloading it in full ⚠️: {}",
+                        object_key,
+                        e
+                    )
+                }
+            }
+        }
+
         let response = client
             .get_object()
             .bucket(bucket_name.clone())
@@ -58,13 +113,33 @@ pub async fn download_objects(
 
         match response {
             Ok(output) => {
+                // prefer the version id and fall back to the etag for buckets without versioning
+                let source_version = output.version_id.clone().or(output.e_tag.clone());
                 match output.body.collect().await {
                     Ok(data) => {
+                        let data = data.into_bytes();
+                        if let Some(cache) = delta::installed() {
+                            let key = object_key.clone();
+                            let data = data.clone();
+                            if let Err(e) = tokio::task::spawn_blocking(move || {
+                                cache.write(key.as_str(), &data)
+                            })
+                            .await
+                            {
+                                tracing::warn!(
+                                    "Failed to cache tarball of {} ⚠️: {}",
+                                    object_key,
+                                    e
+                                )
+                            }
+                        }
                         match save_and_upack_tarball(
                             temp_path,
                             object_key.clone(),
-                            data.into_bytes(),
+                            data,
                             out_dir,
+                            format!("s3://{}/{}", bucket_name, object_key),
+                            source_version,
                         ) {
                             Ok(_) => {
                                 // Do nothing
@@ -99,3 +174,140 @@ pub async fn download_objects(
     }
     Ok(())
 }
+
+/// Downloads an object as a delta of the version kept in the delta cache, fetching only the blocks of its
+/// block manifest which the cached version does not have.
+///
+/// Neither version of the tarball is held in memory: the blocks are located in the cached version and the tarball
+/// is assembled on a blocking thread, one block at a time, and the missing ranges are streamed into a scratch file.
+///
+/// # Returns
+///
+/// * `Ok(Some((file, source_version)))` - The file of the assembled tarball and its version id (or etag).
+/// * `Ok(None)` - If the object has no block manifest or no version of it is cached, i.e. it must be
+///   downloaded in full.
+///
+/// # Errors
+///
+/// This function will return an error if the manifest or a block cannot be downloaded, the manifest does not
+/// match the size of the object, or the assembled tarball does not match the manifest.
+#[tracing::instrument(skip(client, cache))]
+async fn download_delta(
+    client: &s3::Client,
+    bucket_name: &str,
+    object_key: &str,
+    cache: &'static DeltaCache,
+) -> anyhow::Result<Option<(File, Option<String>)>> {
+    if !cache.contains(object_key) {
+        return Ok(None);
+    }
+    let manifest = match client
+        .get_object()
+        .bucket(bucket_name)
+        .key(delta::manifest_key(object_key))
+        .send()
+        .await
+    {
+        Ok(output) => BlockManifest::parse(&output.body.collect().await?.into_bytes())?,
+        Err(SdkError::ServiceError(e)) if matches!(e.err(), GetObjectError::NoSuchKey(_)) => {
+            return Ok(None)
+        }
+        Err(e) => anyhow::bail!(
+            "Failed to get block manifest of {}: {}",
+            object_key,
+            e.into_service_error()
+        ),
+    };
+
+    // the blocks are downloaded from the current version, so that an upload in between fails the checksum
+    let head = client
+        .head_object()
+        .bucket(bucket_name)
+        .key(object_key)
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e.into_service_error()))?;
+    match head
+        .content_length
+        .and_then(|content_length| u64::try_from(content_length).ok())
+    {
+        Some(content_length) => manifest.check_content_length(content_length)?,
+        None => anyhow::bail!("Failed to get the size of artefact {}", object_key),
+    }
+    let version_id = head.version_id.clone();
+    let source_version = head.version_id.or(head.e_tag);
+
+    let manifest = Arc::new(manifest);
+    let located = {
+        let manifest = manifest.clone();
+        let key = object_key.to_string();
+        tokio::task::spawn_blocking(move || -> anyhow::Result<_> {
+            let mut previous = match cache.open(key.as_str()) {
+                Some(previous) => previous,
+                None => return Ok(None),
+            };
+            let offsets = manifest.locate(&mut BufReader::new(previous.file()))?;
+            Ok(Some((previous, offsets)))
+        })
+        .await??
+    };
+    let (mut previous, offsets) = match located {
+        Some(located) => located,
+        None => return Ok(None),
+    };
+
+    let ranges = manifest.missing_ranges(&offsets);
+    let mut downloaded = tokio::fs::File::from_std(encryption::scratch_file()?);
+    let mut fetched: u64 = 0;
+    for range in ranges {
+        let mut body = client
+            .get_object()
+            .bucket(bucket_name)
+            .key(object_key)
+            .set_version_id(version_id.clone())
+            .range(format!("bytes={}-{}", range.start, range.end - 1))
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e.into_service_error()))?
+            .body;
+        let mut received: u64 = 0;
+        while let Some(chunk) = body.try_next().await? {
+            received += chunk.len() as u64;
+            downloaded.write_all(&chunk).await?;
+        }
+        if received != range.end - range.start {
+            anyhow::bail!(
+                "Downloaded {} bytes for range {:?}, expected {} ❌",
+                received,
+                range,
+                range.end - range.start
+            )
+        }
+        fetched += received;
+    }
+    downloaded.flush().await?;
+    let mut downloaded = downloaded.into_std().await;
+
+    let assembled = {
+        let manifest = manifest.clone();
+        tokio::task::spawn_blocking(move || -> anyhow::Result<File> {
+            downloaded.seek(SeekFrom::Start(0))?;
+            let mut assembled = BufWriter::new(encryption::scratch_file()?);
+            manifest.assemble(
+                &mut BufReader::new(previous.file()),
+                &offsets,
+                &mut BufReader::new(downloaded),
+                &mut assembled,
+            )?;
+            Ok(assembled.into_inner()?)
+        })
+        .await??
+    };
+    tracing::info!(
+        "Downloaded {} of {} bytes of artefact {} as a delta 🧩",
+        fetched,
+        manifest.size,
+        object_key
+    );
+    Ok(Some((assembled, source_version)))
+}
@@ -1,12 +1,14 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use crate::model_store::aws::common::download_objects;
+use crate::model_store::delta;
 use async_trait::async_trait;
 use aws_sdk_s3 as s3;
 use dashmap::DashMap;
 
 use crate::model_store::fetcher::Fetcher;
-use crate::model_store::storage::{load_models, Model, ModelName};
+use crate::model_store::storage::{is_selected, load_models, Model, ModelName};
 
 #[async_trait]
 impl Fetcher for aws_sdk_s3::client::Client {
@@ -30,6 +32,7 @@ impl Fetcher for aws_sdk_s3::client::Client {
     /// # Parameters
     /// - `artefacts_dir_name`: The S3 bucket name where model artefacts are stored.
     /// - `output_dir`: The directory where models will be downloaded and stored.
+    /// - `model_names`: The names of the models to fetch. Every model is fetched if `None`.
     ///
     /// # Returns
     /// - `Ok(DashMap<ModelName, Arc<Model>>)` containing all loaded models mapped by their names.
@@ -39,6 +42,7 @@ impl Fetcher for aws_sdk_s3::client::Client {
         &self,
         artefacts_dir_name: Option<String>,
         output_dir: String,
+        model_names: Option<Vec<ModelName>>,
     ) -> anyhow::Result<DashMap<ModelName, Arc<Model>>> {
         let s3_bucket_name = match artefacts_dir_name {
             None => {
@@ -48,7 +52,11 @@ impl Fetcher for aws_sdk_s3::client::Client {
             Some(name) => name,
         };
 
-        let keys = get_keys(self, s3_bucket_name.clone()).await?;
+        let keys = get_keys(self, s3_bucket_name.clone())
+            .await?
+            .into_iter()
+            .filter(|key| is_selected(key, model_names.as_deref()))
+            .collect();
 
         match download_objects(self, s3_bucket_name, keys, output_dir.as_str()).await {
             Ok(_) => {
@@ -109,6 +117,8 @@ async fn get_keys(client: &s3::Client, bucket_name: String) -> anyhow::Result<Ve
                             None => {
                                 tracing::warn!("Object key is empty ⚠️");
                             }
+                            // block manifests are only read by delta updates of their tarball
+                            Some(key) if delta::is_manifest(key.as_str()) => {}
                             Some(key) => {
                                 keys.push(key);
                             }
@@ -129,6 +139,56 @@ async fn get_keys(client: &s3::Client, bucket_name: String) -> anyhow::Result<Ve
     Ok(keys)
 }
 
+/// Retrieves the version of each object in an S3 bucket.
+///
+/// The version id is preferred and the etag is used for buckets without versioning, which matches
+/// the source version recorded when the object is downloaded.
+///
+/// # Arguments
+///
+/// * `client` - An `s3::Client` instance for interacting with AWS S3.
+/// * `bucket_name` - The name of the S3 bucket from which to retrieve the object versions.
+///
+/// # Returns
+///
+/// * `Result<BTreeMap<String, Option<String>>>` - The version of each object keyed by the object key.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * Object keys cannot be listed from the S3 bucket.
+/// * The metadata of an object cannot be retrieved.
+///
+#[tracing::instrument(skip(client))]
+pub async fn get_versions(
+    client: &s3::Client,
+    bucket_name: String,
+) -> anyhow::Result<BTreeMap<String, Option<String>>> {
+    let mut versions = BTreeMap::new();
+    for key in get_keys(client, bucket_name.clone()).await? {
+        match client
+            .head_object()
+            .bucket(bucket_name.clone())
+            .key(key.clone())
+            .send()
+            .await
+        {
+            Ok(output) => {
+                versions.insert(key, output.version_id.or(output.e_tag));
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to get metadata of object key: {} from S3 ❌: {}",
+                    key,
+                    e.into_service_error()
+                );
+                anyhow::bail!("Failed to get metadata of object key: {} from S3 ❌", key)
+            }
+        }
+    }
+    Ok(versions)
+}
+
 /// Fetches models from an S3 bucket, downloads them to a local directory, and loads them into memory.
 ///
 /// This function first retrieves object keys from the S3 bucket, downloads corresponding objects,
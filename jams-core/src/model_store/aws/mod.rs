@@ -1,3 +1,3 @@
 mod common;
-mod fetch;
+pub(crate) mod fetch;
 pub mod s3;
@@ -1,32 +1,48 @@
 use crate::model_store::aws::common::download_objects;
+use crate::model_store::aws::fetch::get_versions;
 use crate::model_store::common::{cleanup, DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX};
+use crate::model_store::deadline::{StoreOperation, StoreTimeouts};
+use crate::model_store::delta;
 use crate::model_store::fetcher::Fetcher;
+use crate::model_store::gc::OwnerLock;
+use crate::model_store::loaded::LoadedModels;
+use crate::model_store::registry::{restore_models, RegistryState};
 use crate::model_store::storage::{
-    append_model_format, extract_framework, load_predictor, Metadata, Model, ModelName, Storage,
+    append_model_format, extract_framework, load_predictor, prepare_model, revalidate_models,
+    schedule_teardown, Metadata, Model, ModelName, PendingUpdates, PollOptions, Storage,
 };
+use crate::{Error, Result};
 use async_trait::async_trait;
 use aws_config::meta::region::ProvideRegion;
 use aws_config::BehaviorVersion;
 use aws_sdk_s3 as s3;
 use aws_sdk_s3::config::{Credentials, Region};
 use chrono::Utc;
-use dashmap::mapref::one::Ref;
 use dashmap::DashMap;
 use std::env;
+use std::path::Path;
 use std::sync::Arc;
-use std::time::Duration;
 use uuid::Uuid;
 
 /// A struct representing a model store that interfaces with S3.
 pub struct S3ModelStore {
-    /// A thread-safe map of model names to their corresponding models.
-    pub models: Arc<DashMap<ModelName, Arc<Model>>>,
+    /// The loaded models, read from a snapshot which is replaced on every write.
+    pub models: Arc<LoadedModels>,
     /// An S3 client for interacting with the S3 service.
     client: s3::Client,
     /// The name S3 bucket where models are stored.
     bucket_name: String,
     /// Directory, which stores the model artifacts downloaded from S3
     model_store_dir: String,
+    /// Keeps the model store directory when the object is dropped so that it can be restored from the registry state
+    retain_model_dir: bool,
+    /// Lock held on the model store directory while the model store is alive, so that other processes do not
+    /// collect it as an orphaned directory
+    _owner: OwnerLock,
+    /// Models whose new version is being downloaded and loaded by the poller
+    pending_updates: Arc<PendingUpdates>,
+    /// Deadlines of the calls made to S3 while adding, updating and polling models
+    timeouts: StoreTimeouts,
 }
 
 impl S3ModelStore {
@@ -43,36 +59,44 @@ impl S3ModelStore {
     ///
     /// A result containing the newly created `S3ModelStore` or an error if the initialization fails.
     pub async fn new(bucket_name: String, use_minio: Option<bool>) -> anyhow::Result<Self> {
+        Self::create(bucket_name, use_minio, None).await
+    }
+
+    /// Creates a new instance of `S3ModelStore` which only loads the priority models.
+    ///
+    /// The other models are not downloaded, so that the priority models can serve predictions in seconds
+    /// while the rest of the model store is loaded in the background by the manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket_name` - Name of the S3 bucket where models are stored.
+    /// * `use_minio` - Whether MinIO should be used instead of AWS S3.
+    /// * `priority_models` - The names of the models to load, without the framework prefix.
+    ///
+    /// # Returns
+    ///
+    /// A result containing the newly created `S3ModelStore` or an error if the initialization fails.
+    pub async fn with_priority_models(
+        bucket_name: String,
+        use_minio: Option<bool>,
+        priority_models: Vec<ModelName>,
+    ) -> anyhow::Result<Self> {
+        Self::create(bucket_name, use_minio, Some(priority_models)).await
+    }
+
+    /// Creates a new instance of `S3ModelStore` which loads the given models, or every model if `None`.
+    async fn create(
+        bucket_name: String,
+        use_minio: Option<bool>,
+        model_names: Option<Vec<ModelName>>,
+    ) -> anyhow::Result<Self> {
         // Ensure model_dir_uri is not empty, return error if empty
         if bucket_name.is_empty() {
             tracing::error!("S3 bucket name must be specified ❌");
             anyhow::bail!("S3 bucket name must be specified ❌")
         }
 
-        // Check if minio is to be used instead of the standard AWS client.
-        let client = if use_minio.is_some() {
-            match build_minio_client().await {
-                Ok(client) => {
-                    tracing::info!("Using MinIO as model store ℹ️");
-                    client
-                }
-                Err(e) => {
-                    tracing::error!("Failed to build MinIO client ❌: {}", e.to_string());
-                    anyhow::bail!("Failed to build MinIO client ❌");
-                }
-            }
-        } else {
-            match build_s3_client(use_localstack()).await {
-                Ok(client) => {
-                    tracing::info!("Using AWS S3 as model store ℹ️");
-                    client
-                }
-                Err(e) => {
-                    tracing::error!("Failed to build S3 client ❌: {}", e.to_string());
-                    anyhow::bail!("Failed to build S3 client ❌");
-                }
-            }
-        };
+        let client = build_client(use_minio).await?;
 
         // Directory, which stores the models downloaded from S3
         let model_store_dir = format!(
@@ -81,21 +105,30 @@ impl S3ModelStore {
             DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX,
             Uuid::new_v4(),
         );
+        let owner = OwnerLock::acquire(Path::new(&model_store_dir))?;
         std::fs::create_dir(model_store_dir.clone())?;
 
         // Check if S3 is empty, if yes then return models dashmap as empty
         if client.is_empty(Some(bucket_name.clone())).await? {
             let models: DashMap<ModelName, Arc<Model>> = DashMap::new();
             Ok(Self {
-                models: Arc::new(models),
+                models: Arc::new(LoadedModels::from(models)),
                 client,
                 bucket_name,
                 model_store_dir,
+                retain_model_dir: false,
+                _owner: owner,
+                pending_updates: Arc::new(PendingUpdates::default()),
+                timeouts: StoreTimeouts::default(),
             })
         } else {
             // Fetch the models from S3
             let models = match client
-                .fetch_models(Some(bucket_name.clone()), model_store_dir.clone())
+                .fetch_models(
+                    Some(bucket_name.clone()),
+                    model_store_dir.clone(),
+                    model_names,
+                )
                 .await
             {
                 Ok(models) => {
@@ -109,13 +142,171 @@ impl S3ModelStore {
             };
 
             Ok(Self {
-                models: Arc::new(models),
+                models: Arc::new(LoadedModels::from(models)),
                 client,
                 bucket_name,
                 model_store_dir,
+                retain_model_dir: false,
+                _owner: owner,
+                pending_updates: Arc::new(PendingUpdates::default()),
+                timeouts: StoreTimeouts::default(),
             })
         }
     }
+
+    /// Creates a new instance of `S3ModelStore` which is restored from the registry state if possible.
+    ///
+    /// If the registry state is valid, the models are loaded from the directory they were downloaded
+    /// to before the last shutdown, skipping listing and downloading the models from S3. Otherwise,
+    /// the models are fetched as in `S3ModelStore::new`. The model store directory is kept when the
+    /// model store is dropped so that it can be reused.
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket_name` - Name of the S3 bucket where models are stored.
+    /// * `use_minio` - Whether MinIO should be used instead of AWS S3.
+    /// * `state` - The registry state saved by a previous run, if any.
+    ///
+    /// # Returns
+    ///
+    /// A result containing the newly created `S3ModelStore` or an error if the initialization fails.
+    pub async fn with_registry_state(
+        bucket_name: String,
+        use_minio: Option<bool>,
+        state: Option<RegistryState>,
+    ) -> anyhow::Result<Self> {
+        let source = format!("s3://{}", bucket_name);
+        let mut model_store = match restore_models(state, source.as_str()).await {
+            Some((model_store_dir, models)) => Self {
+                models: Arc::new(LoadedModels::from(models)),
+                client: build_client(use_minio).await?,
+                bucket_name,
+                _owner: OwnerLock::acquire(Path::new(&model_store_dir))?,
+                model_store_dir,
+                retain_model_dir: false,
+                pending_updates: Arc::new(PendingUpdates::default()),
+                timeouts: StoreTimeouts::default(),
+            },
+            None => Self::new(bucket_name, use_minio).await?,
+        };
+        model_store.retain_model_dir = true;
+
+        Ok(model_store)
+    }
+
+    /// Sets the deadlines of the calls made to S3 while adding, updating and polling models.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeouts` - The deadlines for listing the objects and downloading a single object.
+    pub fn with_timeouts(mut self, timeouts: StoreTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Captures the registry state of the models loaded in the model store.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the model artifacts cannot be hashed.
+    pub fn registry_state(&self) -> anyhow::Result<RegistryState> {
+        RegistryState::capture(
+            format!("s3://{}", self.bucket_name),
+            self.model_store_dir.clone(),
+            &self.models,
+        )
+    }
+
+    /// Returns the directory into which the models are downloaded.
+    pub fn model_store_dir(&self) -> &str {
+        self.model_store_dir.as_str()
+    }
+
+    /// Reads a stored version of a tarball, i.e. a version of its object in a bucket with versioning enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `tarball_name` - The key of the tarball in the bucket.
+    /// * `version` - The version id of the object, or `None` for its latest version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version of the object cannot be downloaded.
+    pub async fn read_tarball(
+        &self,
+        tarball_name: &str,
+        version: Option<&str>,
+    ) -> anyhow::Result<Vec<u8>> {
+        self.timeouts
+            .run(StoreOperation::Download, tarball_name, async {
+                let output = match self
+                    .client
+                    .get_object()
+                    .bucket(self.bucket_name.clone())
+                    .key(tarball_name)
+                    .set_version_id(version.map(str::to_string))
+                    .send()
+                    .await
+                {
+                    Ok(output) => output,
+                    Err(e) => {
+                        tracing::error!(
+                            "Failed to download version {:?} of object key: {} from S3 ❌: {}",
+                            version,
+                            tarball_name,
+                            e.into_service_error()
+                        );
+                        anyhow::bail!(
+                            "Failed to download version {:?} of object key: {} from S3 ❌",
+                            version,
+                            tarball_name
+                        )
+                    }
+                };
+                Ok(output.body.collect().await?.into_bytes().to_vec())
+            })
+            .await
+    }
+}
+
+/// Builds the client used to interact with the model store.
+///
+/// The client is configured from the environment in the same way as for the model store, so that it
+/// can be reused to write to other buckets, e.g. by the server.
+///
+/// # Parameters
+///
+/// - `use_minio`: Whether MinIO should be used instead of the standard AWS client.
+///
+/// # Returns
+///
+/// - `Result<s3::Client, anyhow::Error>`: A result containing the configured client or an error.
+///
+pub async fn build_client(use_minio: Option<bool>) -> anyhow::Result<s3::Client> {
+    // Check if minio is to be used instead of the standard AWS client.
+    if use_minio.is_some() {
+        match build_minio_client().await {
+            Ok(client) => {
+                tracing::info!("Using MinIO as model store ℹ️");
+                Ok(client)
+            }
+            Err(e) => {
+                tracing::error!("Failed to build MinIO client ❌: {}", e.to_string());
+                anyhow::bail!("Failed to build MinIO client ❌");
+            }
+        }
+    } else {
+        match build_s3_client(use_localstack()).await {
+            Ok(client) => {
+                tracing::info!("Using AWS S3 as model store ℹ️");
+                Ok(client)
+            }
+            Err(e) => {
+                tracing::error!("Failed to build S3 client ❌: {}", e.to_string());
+                anyhow::bail!("Failed to build S3 client ❌");
+            }
+        }
+    }
 }
 
 /// Asynchronously builds and returns an S3 client.
@@ -217,7 +408,9 @@ async fn build_minio_client() -> anyhow::Result<s3::Client> {
 ///
 impl Drop for S3ModelStore {
     fn drop(&mut self) {
-        cleanup(self.model_store_dir.clone())
+        if !self.retain_model_dir {
+            cleanup(self.model_store_dir.clone())
+        }
     }
 }
 
@@ -249,27 +442,35 @@ impl Storage for S3ModelStore {
     /// * The framework cannot be extracted from the model path.
     /// * The model cannot be loaded into memory.
     #[tracing::instrument(skip(self))]
-    async fn add_model(&self, model_name: ModelName) -> anyhow::Result<()> {
+    async fn add_model(&self, model_name: ModelName) -> Result<()> {
         // Prepare the S3 key from model_name
         // It is assumed that model will always be present as a .tar.gz file in S3
         // Panic otherwise
         let object_key = format!("{}.tar.gz", model_name);
 
         // Download the model
-        match download_objects(
-            &self.client,
-            self.bucket_name.clone(),
-            vec![object_key],
-            self.model_store_dir.as_str(),
-        )
-        .await
+        match self
+            .timeouts
+            .run(
+                StoreOperation::Download,
+                object_key.as_str(),
+                download_objects(
+                    &self.client,
+                    self.bucket_name.clone(),
+                    vec![object_key.clone()],
+                    self.model_store_dir.as_str(),
+                ),
+            )
+            .await
         {
             Ok(_) => {
                 tracing::info!("Downloaded object from s3 ✅");
             }
             Err(e) => {
                 tracing::error!("Failed to download object from s3 ❌: {}", e.to_string());
-                anyhow::bail!("Failed to download object from s3 ❌: {}", e.to_string());
+                return Err(Error::Store(
+                    anyhow::Error::from(e).context("Failed to download object from s3 ❌"),
+                ));
             }
         };
 
@@ -284,7 +485,9 @@ impl Storage for S3ModelStore {
         let model_framework = match extract_framework(model_name.clone()) {
             None => {
                 tracing::error!("Failed to extract framework from path");
-                anyhow::bail!("Failed to extract framework from path");
+                return Err(Error::Store(anyhow::anyhow!(
+                    "Failed to extract framework from path"
+                )));
             }
             Some(model_framework) => model_framework,
         };
@@ -303,7 +506,9 @@ impl Storage for S3ModelStore {
                     match model_name.strip_prefix(format!("{}-", model_framework).as_str()) {
                         None => {
                             tracing::error!("Failed to sanitize model name");
-                            anyhow::bail!("Failed to sanitize model name");
+                            return Err(Error::Store(anyhow::anyhow!(
+                                "Failed to sanitize model name"
+                            )));
                         }
                         Some(name) => name.to_string(),
                     };
@@ -316,12 +521,18 @@ impl Storage for S3ModelStore {
                     model_path.to_string(),
                     now.to_rfc2822(),
                 );
-                self.models.insert(sanitized_model_name, Arc::new(model));
+                let model =
+                    prepare_model(model, self.models.get(&sanitized_model_name).as_deref()).await?;
+                if let Some(replaced) = self.models.insert(sanitized_model_name, Arc::new(model)) {
+                    schedule_teardown(replaced);
+                }
                 Ok(())
             }
             Err(e) => {
                 tracing::error!("Failed to add new model: {e}");
-                anyhow::bail!("Failed to add new model: {e}")
+                Err(Error::Store(
+                    anyhow::Error::from(e).context("Failed to add new model"),
+                ))
             }
         }
     }
@@ -347,11 +558,11 @@ impl Storage for S3ModelStore {
     /// * The framework cannot be extracted from the model path.
     /// * The model cannot be loaded into memory.
     #[tracing::instrument(skip(self))]
-    async fn update_model(&self, model_name: ModelName) -> anyhow::Result<()> {
+    async fn update_model(&self, model_name: ModelName) -> Result<()> {
         // ensure the framework prefix is not passed in the model name
         if extract_framework(model_name.clone()).is_some() {
             tracing::error!("Ensure that framework is not being passed in the model name ❌. Expected <model_name> not <framework>-<model name>");
-            anyhow::bail!("Ensure that framework is not being passed in the model name ❌. Expected <model_name> not <framework>-<model name>")
+            return Err(Error::Store(anyhow::anyhow!("Ensure that framework is not being passed in the model name ❌. Expected <model_name> not <framework>-<model name>")));
         }
 
         // By calling remove on the hashmap, the object is returned on success/
@@ -362,10 +573,7 @@ impl Storage for S3ModelStore {
                     "Failed to update as the specified model {} does not exist",
                     model_name
                 );
-                anyhow::bail!(
-                    "Failed to update as the specified model {} does not exist",
-                    model_name
-                )
+                return Err(Error::ModelNotFound(model_name));
             }
             Some(model) => {
                 let (model_framework, model_path) =
@@ -375,13 +583,19 @@ impl Storage for S3ModelStore {
                 let object_key = format!("{}-{}.tar.gz", model_framework, model_name);
 
                 // Fetch the latest model from S3
-                match download_objects(
-                    &self.client,
-                    self.bucket_name.clone(),
-                    vec![object_key],
-                    self.model_store_dir.as_str(),
-                )
-                .await
+                match self
+                    .timeouts
+                    .run(
+                        StoreOperation::Download,
+                        object_key.as_str(),
+                        download_objects(
+                            &self.client,
+                            self.bucket_name.clone(),
+                            vec![object_key.clone()],
+                            self.model_store_dir.as_str(),
+                        ),
+                    )
+                    .await
                 {
                     Ok(_) => {
                         tracing::info!("Downloaded object from s3 ✅");
@@ -389,15 +603,25 @@ impl Storage for S3ModelStore {
                         match load_predictor(model_framework, model_path).await {
                             Ok(predictor) => {
                                 let now = Utc::now();
-                                let model = Model::new(
+                                let updated = Model::new(
                                     predictor,
                                     model_name.clone(),
                                     model_framework,
                                     model_path.to_string(), // todo: use S3 path here and not the local model dir path
                                     now.to_rfc2822(),
                                 );
-                                self.models.insert(model_name.clone(), Arc::new(model));
-                                Ok(())
+                                // the current version is kept if the new version fails to initialize or misses its acceptance criteria
+                                match prepare_model(updated, Some(&model.1)).await {
+                                    Ok(updated) => {
+                                        self.models.insert(model_name.clone(), Arc::new(updated));
+                                        schedule_teardown(model.1);
+                                        Ok(())
+                                    }
+                                    Err(e) => {
+                                        self.models.insert(model_name.clone(), model.1);
+                                        Err(e)
+                                    }
+                                }
                             }
                             Err(e) => {
                                 tracing::error!(
@@ -406,17 +630,18 @@ impl Storage for S3ModelStore {
                                     e
                                 );
 
-                                anyhow::bail!(
-                                    "Failed to update the specified model {}: {}",
-                                    model_name,
-                                    e
-                                )
+                                Err(Error::Store(anyhow::Error::from(e).context(format!(
+                                    "Failed to update the specified model {}",
+                                    model_name
+                                ))))
                             }
                         }
                     }
                     Err(e) => {
                         tracing::error!("Failed to download object from s3 ❌: {}", e.to_string());
-                        anyhow::bail!("Failed to download object from s3 ❌");
+                        Err(Error::Store(
+                            anyhow::Error::from(e).context("Failed to download object from s3 ❌"),
+                        ))
                     }
                 }
             }
@@ -434,11 +659,11 @@ impl Storage for S3ModelStore {
     /// # Returns
     ///
     /// This function returns an `Option`:
-    /// * `Some(Ref<ModelName, Arc<Model>>)` if the model exists.
+    /// * `Some(Arc<Model>)` if the model exists.
     /// * `None` if the model does not exist.
     ///
     #[tracing::instrument(skip(self))]
-    fn get_model(&self, model_name: ModelName) -> Option<Ref<ModelName, Arc<Model>>> {
+    fn get_model(&self, model_name: ModelName) -> Option<Arc<Model>> {
         self.models.get(model_name.as_str())
     }
 
@@ -448,14 +673,15 @@ impl Storage for S3ModelStore {
     ///
     /// # Returns
     ///
-    /// This function returns an `anyhow::Result` containing a vector of `Metadata`.
+    /// This function returns a `jams_core::Result` containing a vector of `Metadata`.
     ///
     #[tracing::instrument(skip(self))]
-    fn get_models(&self) -> anyhow::Result<Vec<Metadata>> {
+    fn get_models(&self) -> Result<Vec<Metadata>> {
         let model: Vec<Metadata> = self
             .models
-            .iter()
-            .map(|f| f.value().info.to_owned())
+            .snapshot()
+            .values()
+            .map(|model| self.pending_updates.annotate(model.info.to_owned()))
             .collect();
         Ok(model)
     }
@@ -472,63 +698,99 @@ impl Storage for S3ModelStore {
     ///
     /// This function returns an error if the specified model does not exist in the store.
     #[tracing::instrument(skip(self))]
-    fn delete_model(&self, model_name: ModelName) -> anyhow::Result<()> {
-        match self.models.remove(&model_name) {
+    fn delete_model(&self, model_name: ModelName) -> Result<()> {
+        match self.models.remove(model_name.as_str()) {
             None => {
                 tracing::error!(
                     "Failed to delete model as the specified model {} does not exist",
                     model_name
                 );
-                anyhow::bail!(
-                    "Failed to delete model as the specified model {} does not exist",
-                    model_name
-                )
+                Err(Error::ModelNotFound(model_name))
+            }
+            Some((_, model)) => {
+                schedule_teardown(model);
+                Ok(())
             }
-            Some(_) => Ok(()),
         }
     }
 
     /// Periodically polls the model store to fetch and update models.
     ///
-    /// This asynchronous function waits for the specified time interval, then fetches models from an S3
-    /// bucket using the `fetch_models` function. If new or updated models are found, they are inserted into
-    /// the in-memory model store (`self.models`). The polling process continues indefinitely with each cycle
-    /// waiting for the specified interval.
+    /// This asynchronous function waits for the specified time interval, then compares the version id
+    /// (or etag) of each object in the S3 bucket with the version of the loaded model. Only new or updated
+    /// models are downloaded and loaded. Updated models keep serving their current version, which is
+    /// reported as a pending update in the metadata, and are only swapped in the in-memory model store
+    /// (`self.models`) once the new version has loaded.
     ///
     /// # Arguments
     ///
-    /// * `interval` - A `Duration` specifying the time interval between each polling operation.
+    /// * `options` - The interval and jitter between each polling operation, and the maximum number of
+    ///   tarballs downloaded at the same time.
     ///
     /// # Returns
     ///
     /// * `Ok(())` if the models were successfully fetched and updated in the model store.
-    /// * `Err(anyhow::Error)` if there was an error during the fetch or update process, including S3 fetch failures.
+    /// * `Err(Error::Store)` if there was an error during the fetch or update process, including S3 fetch failures.
     ///
     #[tracing::instrument(skip(self))]
-    async fn poll(&self, interval: Duration) -> anyhow::Result<()> {
-        // poll every n time interval
-        tokio::time::sleep(interval).await;
+    async fn poll(&self, options: &PollOptions) -> Result<()> {
+        // wait for the interval plus a random jitter
+        tokio::time::sleep(options.next_delay()).await;
 
         tracing::info!("Polling model store ⌛");
-        let models = match self
-            .client
-            .fetch_models(Some(self.bucket_name.clone()), self.model_store_dir.clone())
+        let versions = match self
+            .timeouts
+            .run(
+                StoreOperation::List,
+                self.bucket_name.as_str(),
+                get_versions(&self.client, self.bucket_name.clone()),
+            )
             .await
         {
-            Ok(models) => {
-                tracing::info!("Successfully fetched valid models from S3 ✅");
-                models
-            }
+            Ok(versions) => versions,
             Err(e) => {
                 tracing::error!("Failed to fetch models ❌ - {}", e.to_string());
-                anyhow::bail!("Failed to fetch models ❌ - {}", e.to_string());
+                return Err(Error::Store(
+                    anyhow::Error::from(e).context("Failed to fetch models ❌"),
+                ));
             }
         };
 
-        for (model_name, model) in models {
-            self.models.insert(model_name, model);
+        // the cached tarballs of the models removed from the bucket are no longer the base of a delta update
+        if let Some(cache) = delta::installed() {
+            let object_keys: Vec<String> = versions.keys().cloned().collect();
+            if let Err(e) = tokio::task::spawn_blocking(move || {
+                cache.retain(object_keys.iter().map(String::as_str))
+            })
+            .await
+            {
+                tracing::warn!("Failed to evict the delta cache ⚠️: {}", e)
+            }
         }
 
+        revalidate_models(
+            &self.models,
+            &self.pending_updates,
+            versions,
+            self.model_store_dir.as_str(),
+            options,
+            |object_key| async move {
+                self.timeouts
+                    .run(
+                        StoreOperation::Download,
+                        object_key.as_str(),
+                        download_objects(
+                            &self.client,
+                            self.bucket_name.clone(),
+                            vec![object_key.clone()],
+                            self.model_store_dir.as_str(),
+                        ),
+                    )
+                    .await
+            },
+        )
+        .await;
+
         Ok(())
     }
 }
@@ -1,29 +1,45 @@
 use crate::model_store::azure::common::download_blob;
+use crate::model_store::azure::fetch::get_versions;
 use crate::model_store::common::{cleanup, DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX};
+use crate::model_store::deadline::{StoreOperation, StoreTimeouts};
 use crate::model_store::fetcher::Fetcher;
+use crate::model_store::gc::OwnerLock;
+use crate::model_store::loaded::LoadedModels;
+use crate::model_store::registry::{restore_models, RegistryState};
 use crate::model_store::storage::{
-    append_model_format, extract_framework, load_predictor, Metadata, Model, ModelName, Storage,
+    append_model_format, extract_framework, load_predictor, prepare_model, revalidate_models,
+    schedule_teardown, Metadata, Model, ModelName, PendingUpdates, PollOptions, Storage,
 };
+use crate::{Error, Result};
 use async_trait::async_trait;
 use azure_storage::{CloudLocation, StorageCredentials};
-use azure_storage_blobs::prelude::{BlobServiceClient, ContainerClient};
+use azure_storage_blobs::prelude::{BlobServiceClient, BlobVersioning, ContainerClient, VersionId};
 use chrono::Utc;
-use dashmap::mapref::one::Ref;
 use dashmap::DashMap;
+use futures::StreamExt;
 use std::env;
+use std::path::Path;
 use std::sync::Arc;
-use std::time::Duration;
 use uuid::Uuid;
 
 /// A struct representing a model store that interfaces with azure blob storage.
 pub struct AzureBlobStorageModelStore {
-    /// A thread-safe map of model names to their corresponding models.
-    pub models: Arc<DashMap<ModelName, Arc<Model>>>,
+    /// The loaded models, read from a snapshot which is replaced on every write.
+    pub models: Arc<LoadedModels>,
     /// Azure container client for interacting with storage container and listing blobs
     /// A new blob client will be created for each blob to download it to the local file system
     container_client: ContainerClient,
     /// Directory, which stores the model artifacts downloaded from Azure blob
     model_store_dir: String,
+    /// Keeps the model store directory when the object is dropped so that it can be restored from the registry state
+    retain_model_dir: bool,
+    /// Lock held on the model store directory while the model store is alive, so that other processes do not
+    /// collect it as an orphaned directory
+    _owner: OwnerLock,
+    /// Models whose new version is being downloaded and loaded by the poller
+    pending_updates: Arc<PendingUpdates>,
+    /// Deadlines of the calls made to Azure Blob Storage while adding, updating and polling models
+    timeouts: StoreTimeouts,
 }
 
 impl AzureBlobStorageModelStore {
@@ -50,6 +66,34 @@ impl AzureBlobStorageModelStore {
     /// * The Azure Blob Service client cannot be created.
     /// * The models cannot be fetched from Azure Blob Storage.
     pub async fn new(storage_container_name: String) -> anyhow::Result<Self> {
+        Self::create(storage_container_name, None).await
+    }
+
+    /// Creates a new instance of `AzureBlobStorageModelStore` which only loads the priority models.
+    ///
+    /// The other models are not fetched, so that the priority models can serve predictions in seconds
+    /// while the rest of the model store is loaded in the background by the manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage_container_name` - A `String` specifying the name of the Azure Blob Storage container.
+    /// * `priority_models` - The names of the models to load, without the framework prefix.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a new instance of `AzureBlobStorageModelStore` if successful, or an `anyhow::Error` if the operation fails.
+    pub async fn with_priority_models(
+        storage_container_name: String,
+        priority_models: Vec<ModelName>,
+    ) -> anyhow::Result<Self> {
+        Self::create(storage_container_name, Some(priority_models)).await
+    }
+
+    /// Creates a new instance of `AzureBlobStorageModelStore` which loads the given models, or every model if `None`.
+    async fn create(
+        storage_container_name: String,
+        model_names: Option<Vec<ModelName>>,
+    ) -> anyhow::Result<Self> {
         // Ensure model_dir_uri is not empty, return error if empty
         if storage_container_name.is_empty() {
             tracing::error!("Azure storage container name must be specified ❌");
@@ -70,6 +114,7 @@ impl AzureBlobStorageModelStore {
             DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX,
             Uuid::new_v4(),
         );
+        let owner = OwnerLock::acquire(Path::new(&model_store_dir))?;
         std::fs::create_dir(model_store_dir.clone())?;
 
         // Check if Azure blob storage is empty, if yes then return models dashmap as empty
@@ -79,14 +124,18 @@ impl AzureBlobStorageModelStore {
                     );
             let models: DashMap<ModelName, Arc<Model>> = DashMap::new();
             Ok(Self {
-                models: Arc::new(models),
+                models: Arc::new(LoadedModels::from(models)),
                 container_client,
                 model_store_dir,
+                retain_model_dir: false,
+                _owner: owner,
+                pending_updates: Arc::new(PendingUpdates::default()),
+                timeouts: StoreTimeouts::default(),
             })
         } else {
             // Fetch the models from Azure Blob Storage
             let models = match container_client
-                .fetch_models(None, model_store_dir.clone())
+                .fetch_models(None, model_store_dir.clone(), model_names)
                 .await
             {
                 Ok(models) => {
@@ -100,12 +149,141 @@ impl AzureBlobStorageModelStore {
             };
 
             Ok(Self {
-                models: Arc::new(models),
+                models: Arc::new(LoadedModels::from(models)),
                 container_client,
                 model_store_dir,
+                retain_model_dir: false,
+                _owner: owner,
+                pending_updates: Arc::new(PendingUpdates::default()),
+                timeouts: StoreTimeouts::default(),
             })
         }
     }
+
+    /// Creates a new instance of `AzureBlobStorageModelStore` which is restored from the registry state if possible.
+    ///
+    /// If the registry state is valid, the models are loaded from the directory they were downloaded
+    /// to before the last shutdown, skipping listing and downloading the models from Azure Blob Storage.
+    /// Otherwise, the models are fetched as in `AzureBlobStorageModelStore::new`. The model store
+    /// directory is kept when the model store is dropped so that it can be reused.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage_container_name` - A `String` specifying the name of the Azure Blob Storage container.
+    /// * `state` - The registry state saved by a previous run, if any.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a new instance of `AzureBlobStorageModelStore` if successful, or an `anyhow::Error` if the operation fails.
+    pub async fn with_registry_state(
+        storage_container_name: String,
+        state: Option<RegistryState>,
+    ) -> anyhow::Result<Self> {
+        let source = format!("azure://{}", storage_container_name);
+        let mut model_store = match restore_models(state, source.as_str()).await {
+            Some((model_store_dir, models)) => {
+                let container_client = match build_azure_storage_client(use_azurite()) {
+                    Ok(blob_service_client) => {
+                        blob_service_client.container_client(storage_container_name)
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to create Azure Blob Service client: {}", e);
+                        anyhow::bail!("Failed to create Azure Blob Service client: {}", e)
+                    }
+                };
+                Self {
+                    models: Arc::new(LoadedModels::from(models)),
+                    container_client,
+                    _owner: OwnerLock::acquire(Path::new(&model_store_dir))?,
+                    model_store_dir,
+                    retain_model_dir: false,
+                    pending_updates: Arc::new(PendingUpdates::default()),
+                    timeouts: StoreTimeouts::default(),
+                }
+            }
+            None => Self::new(storage_container_name).await?,
+        };
+        model_store.retain_model_dir = true;
+
+        Ok(model_store)
+    }
+
+    /// Sets the deadlines of the calls made to Azure Blob Storage while adding, updating and polling models.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeouts` - The deadlines for listing the blobs and downloading a single blob.
+    pub fn with_timeouts(mut self, timeouts: StoreTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Captures the registry state of the models loaded in the model store.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the model artifacts cannot be hashed.
+    pub fn registry_state(&self) -> anyhow::Result<RegistryState> {
+        RegistryState::capture(
+            format!("azure://{}", self.container_client.container_name()),
+            self.model_store_dir.clone(),
+            &self.models,
+        )
+    }
+
+    /// Returns the directory into which the models are downloaded.
+    pub fn model_store_dir(&self) -> &str {
+        self.model_store_dir.as_str()
+    }
+
+    /// Reads a stored version of a tarball, i.e. a version of its blob in a container with blob versioning enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `tarball_name` - The name of the blob of the tarball.
+    /// * `version` - The version id of the blob, or `None` for its current version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version of the blob cannot be downloaded.
+    pub async fn read_tarball(
+        &self,
+        tarball_name: &str,
+        version: Option<&str>,
+    ) -> anyhow::Result<Vec<u8>> {
+        self.timeouts
+            .run(StoreOperation::Download, tarball_name, async {
+                let mut get = self.container_client.blob_client(tarball_name).get();
+                if let Some(version) = version {
+                    get = get.blob_versioning(BlobVersioning::VersionId(VersionId::new(
+                        version.to_string(),
+                    )));
+                }
+                let mut data: Vec<u8> = Vec::new();
+                let mut stream = get.into_stream();
+                while let Some(response) = stream.next().await {
+                    match response {
+                        Ok(response) => data.extend(&response.data.collect().await?),
+                        Err(e) => {
+                            tracing::error!(
+                                "Failed to download version {:?} of blob {} ❌: {}",
+                                version,
+                                tarball_name,
+                                e
+                            );
+                            anyhow::bail!(
+                                "Failed to download version {:?} of blob {} ❌: {}",
+                                version,
+                                tarball_name,
+                                e
+                            )
+                        }
+                    }
+                }
+                Ok(data)
+            })
+            .await
+    }
 }
 
 /// Implements the `Drop` trait for `AzureBlobStorageModelStore`.
@@ -120,10 +298,26 @@ impl AzureBlobStorageModelStore {
 ///
 impl Drop for AzureBlobStorageModelStore {
     fn drop(&mut self) {
-        cleanup(self.model_store_dir.clone())
+        if !self.retain_model_dir {
+            cleanup(self.model_store_dir.clone())
+        }
     }
 }
 
+/// Builds a client for a container of the storage account configured by the `STORAGE_ACCOUNT` and
+/// `STORAGE_ACCESS_KEY` env variables, in the same way as for the model store.
+///
+/// # Arguments
+///
+/// * `container_name` - The name of the storage container.
+///
+/// # Errors
+///
+/// Returns an error if the storage account or access key is not set.
+pub fn build_container_client(container_name: String) -> anyhow::Result<ContainerClient> {
+    Ok(build_azure_storage_client(use_azurite())?.container_client(container_name))
+}
+
 /// Builds an Azure Blob Storage client using environment variables for credentials.
 ///
 /// This function reads the `STORAGE_ACCOUNT` and `STORAGE_ACCESS_KEY` environment variables
@@ -190,18 +384,24 @@ impl Storage for AzureBlobStorageModelStore {
     /// * The model cannot be downloaded from Azure Blob Storage.
     /// * The framework cannot be extracted from the model path.
     /// * The model cannot be loaded into memory.
-    async fn add_model(&self, model_name: ModelName) -> anyhow::Result<()> {
+    async fn add_model(&self, model_name: ModelName) -> Result<()> {
         // Prepare the blob key from model_name
         // It is assumed that model will always be present as a .tar.gz file in Azure Blob Storage
         // Panic otherwise
         let blob_name = format!("{}.tar.gz", model_name);
 
-        match download_blob(
-            &self.container_client,
-            blob_name,
-            self.model_store_dir.clone(),
-        )
-        .await
+        match self
+            .timeouts
+            .run(
+                StoreOperation::Download,
+                blob_name.as_str(),
+                download_blob(
+                    &self.container_client,
+                    blob_name.clone(),
+                    self.model_store_dir.clone(),
+                ),
+            )
+            .await
         {
             Ok(_) => {
                 tracing::info!("Downloaded blob from azure storage ✅");
@@ -211,10 +411,9 @@ impl Storage for AzureBlobStorageModelStore {
                     "Failed to download blob from azure storage ❌️: {}",
                     e.to_string()
                 );
-                anyhow::bail!(
-                    "Failed to download blob from azure storage ❌️: {}",
-                    e.to_string()
-                );
+                return Err(Error::Store(
+                    anyhow::Error::from(e).context("Failed to download blob from azure storage ❌️"),
+                ));
             }
         }
 
@@ -229,7 +428,9 @@ impl Storage for AzureBlobStorageModelStore {
         let model_framework = match extract_framework(model_name.clone()) {
             None => {
                 tracing::error!("Failed to extract framework from path");
-                anyhow::bail!("Failed to extract framework from path");
+                return Err(Error::Store(anyhow::anyhow!(
+                    "Failed to extract framework from path"
+                )));
             }
             Some(model_framework) => model_framework,
         };
@@ -248,7 +449,9 @@ impl Storage for AzureBlobStorageModelStore {
                     match model_name.strip_prefix(format!("{}-", model_framework).as_str()) {
                         None => {
                             tracing::error!("Failed to sanitize model name");
-                            anyhow::bail!("Failed to sanitize model name");
+                            return Err(Error::Store(anyhow::anyhow!(
+                                "Failed to sanitize model name"
+                            )));
                         }
                         Some(name) => name.to_string(),
                     };
@@ -261,12 +464,18 @@ impl Storage for AzureBlobStorageModelStore {
                     model_path.to_string(),
                     now.to_rfc2822(),
                 );
-                self.models.insert(sanitized_model_name, Arc::new(model));
+                let model =
+                    prepare_model(model, self.models.get(&sanitized_model_name).as_deref()).await?;
+                if let Some(replaced) = self.models.insert(sanitized_model_name, Arc::new(model)) {
+                    schedule_teardown(replaced);
+                }
                 Ok(())
             }
             Err(e) => {
                 tracing::error!("Failed to add new model: {e}");
-                anyhow::bail!("Failed to add new model: {e}")
+                Err(Error::Store(
+                    anyhow::Error::from(e).context("Failed to add new model"),
+                ))
             }
         }
     }
@@ -286,7 +495,7 @@ impl Storage for AzureBlobStorageModelStore {
     ///
     /// # Returns
     ///
-    /// A `Result` which is `Ok(())` if the operation is successful, or an `anyhow::Error` if the operation fails.
+    /// A `Result` which is `Ok(())` if the operation is successful, or an `Error::Store` if the operation fails.
     ///
     /// # Errors
     ///
@@ -294,17 +503,14 @@ impl Storage for AzureBlobStorageModelStore {
     /// * The specified model does not exist in the internal model store.
     /// * Downloading the blob from Azure Blob Storage fails.
     /// * Loading the predictor from the unpacked files fails.
-    async fn update_model(&self, model_name: ModelName) -> anyhow::Result<()> {
+    async fn update_model(&self, model_name: ModelName) -> Result<()> {
         match self.models.remove(model_name.as_str()) {
             None => {
                 tracing::error!(
                     "Failed to update as the specified model {} does not exist",
                     model_name
                 );
-                anyhow::bail!(
-                    "Failed to update as the specified model {} does not exist",
-                    model_name
-                )
+                return Err(Error::ModelNotFound(model_name));
             }
             Some(model) => {
                 let (model_framework, model_path) =
@@ -313,12 +519,18 @@ impl Storage for AzureBlobStorageModelStore {
                 // Prepare the blob name from model_name
                 let blob_name = format!("{}-{}.tar.gz", model_framework, model_name);
 
-                match download_blob(
-                    &self.container_client,
-                    blob_name,
-                    self.model_store_dir.clone(),
-                )
-                .await
+                match self
+                    .timeouts
+                    .run(
+                        StoreOperation::Download,
+                        blob_name.as_str(),
+                        download_blob(
+                            &self.container_client,
+                            blob_name.clone(),
+                            self.model_store_dir.clone(),
+                        ),
+                    )
+                    .await
                 {
                     Ok(_) => {
                         tracing::info!("Downloaded blob from azure storage ✅");
@@ -326,15 +538,25 @@ impl Storage for AzureBlobStorageModelStore {
                         match load_predictor(model_framework, model_path).await {
                             Ok(predictor) => {
                                 let now = Utc::now();
-                                let model = Model::new(
+                                let updated = Model::new(
                                     predictor,
                                     model_name.clone(),
                                     model_framework,
                                     model_path.to_string(), // todo: use Azure path here and not the local model dir path
                                     now.to_rfc2822(),
                                 );
-                                self.models.insert(model_name.clone(), Arc::new(model));
-                                Ok(())
+                                // the current version is kept if the new version fails to initialize or misses its acceptance criteria
+                                match prepare_model(updated, Some(&model.1)).await {
+                                    Ok(updated) => {
+                                        self.models.insert(model_name.clone(), Arc::new(updated));
+                                        schedule_teardown(model.1);
+                                        Ok(())
+                                    }
+                                    Err(e) => {
+                                        self.models.insert(model_name.clone(), model.1);
+                                        Err(e)
+                                    }
+                                }
                             }
                             Err(e) => {
                                 tracing::error!(
@@ -342,17 +564,18 @@ impl Storage for AzureBlobStorageModelStore {
                                     model_name,
                                     e
                                 );
-                                anyhow::bail!(
-                                    "Failed to update the specified model {}: {}",
-                                    model_name,
-                                    e
-                                )
+                                Err(Error::Store(anyhow::Error::from(e).context(format!(
+                                    "Failed to update the specified model {}",
+                                    model_name
+                                ))))
                             }
                         }
                     }
                     Err(e) => {
                         tracing::error!("Failed to download blob ❌: {}", e);
-                        anyhow::bail!("Failed to download blob ❌: {}", e)
+                        Err(Error::Store(
+                            anyhow::Error::from(e).context("Failed to download blob ❌"),
+                        ))
                     }
                 }
             }
@@ -370,10 +593,10 @@ impl Storage for AzureBlobStorageModelStore {
     /// # Returns
     ///
     /// This function returns an `Option`:
-    /// * `Some(Ref<ModelName, Arc<Model>>)` if the model exists.
+    /// * `Some(Arc<Model>)` if the model exists.
     /// * `None` if the model does not exist.
     ///
-    fn get_model(&self, model_name: ModelName) -> Option<Ref<ModelName, Arc<Model>>> {
+    fn get_model(&self, model_name: ModelName) -> Option<Arc<Model>> {
         self.models.get(model_name.as_str())
     }
 
@@ -383,13 +606,14 @@ impl Storage for AzureBlobStorageModelStore {
     ///
     /// # Returns
     ///
-    /// This function returns an `anyhow::Result` containing a vector of `Metadata`.
+    /// This function returns a `jams_core::Result` containing a vector of `Metadata`.
     ///
-    fn get_models(&self) -> anyhow::Result<Vec<Metadata>> {
+    fn get_models(&self) -> Result<Vec<Metadata>> {
         let model: Vec<Metadata> = self
             .models
-            .iter()
-            .map(|f| f.value().info.to_owned())
+            .snapshot()
+            .values()
+            .map(|model| self.pending_updates.annotate(model.info.to_owned()))
             .collect();
         Ok(model)
     }
@@ -405,62 +629,86 @@ impl Storage for AzureBlobStorageModelStore {
     /// # Errors
     ///
     /// This function returns an error if the specified model does not exist in the store.
-    fn delete_model(&self, model_name: ModelName) -> anyhow::Result<()> {
-        match self.models.remove(&model_name) {
+    fn delete_model(&self, model_name: ModelName) -> Result<()> {
+        match self.models.remove(model_name.as_str()) {
             None => {
                 tracing::error!(
                     "Failed to delete model as the specified model {} does not exist",
                     model_name
                 );
-                anyhow::bail!(
-                    "Failed to delete model as the specified model {} does not exist",
-                    model_name
-                )
+                Err(Error::ModelNotFound(model_name))
+            }
+            Some((_, model)) => {
+                schedule_teardown(model);
+                Ok(())
             }
-            Some(_) => Ok(()),
         }
     }
 
     /// Periodically polls the model store in Azure Blob Storage to fetch and update models.
     ///
-    /// This asynchronous function waits for a specified time interval, then attempts to fetch models
-    /// from the Azure Blob Storage container, and updates the internal model cache (`self.models`).
-    /// The polling interval ensures that the model store is regularly updated with new models, if available.
+    /// This asynchronous function waits for a specified time interval, then compares the etag of each blob
+    /// in the Azure Blob Storage container with the version of the loaded model. Only new or updated models
+    /// are downloaded and loaded. Updated models keep serving their current version, which is reported as a
+    /// pending update in the metadata, and are only swapped in the internal model cache (`self.models`) once
+    /// the new version has loaded.
     ///
     /// # Arguments
     ///
-    /// * `interval` - A `Duration` representing the time interval between each polling operation.
+    /// * `options` - The interval and jitter between each polling operation, and the maximum number of
+    ///   tarballs downloaded at the same time.
     ///
     /// # Returns
     ///
     /// * `Ok(())` - If the models were successfully fetched and updated in the model store.
-    /// * `Err(anyhow::Error)` - If an error occurs during the fetch or update process, such as when
+    /// * `Err(Error::Store)` - If an error occurs during the fetch or update process, such as when
     ///   the models fail to be retrieved from Azure Blob Storage.
     ///
-    async fn poll(&self, interval: Duration) -> anyhow::Result<()> {
-        // poll every n time interval
-        tokio::time::sleep(interval).await;
+    async fn poll(&self, options: &PollOptions) -> Result<()> {
+        // wait for the interval plus a random jitter
+        tokio::time::sleep(options.next_delay()).await;
 
         tracing::info!("Polling model store ⌛");
 
-        let models = match self
-            .container_client
-            .fetch_models(None, self.model_store_dir.clone())
+        let versions = match self
+            .timeouts
+            .run(
+                StoreOperation::List,
+                self.container_client.container_name(),
+                get_versions(&self.container_client),
+            )
             .await
         {
-            Ok(models) => {
-                tracing::info!("Successfully fetched valid models from Azure Blob Storage ✅");
-                models
-            }
+            Ok(versions) => versions,
             Err(e) => {
                 tracing::error!("Failed to fetch models ❌ - {}", e.to_string());
-                anyhow::bail!("Failed to fetch models ❌ - {}", e.to_string());
+                return Err(Error::Store(
+                    anyhow::Error::from(e).context("Failed to fetch models ❌"),
+                ));
             }
         };
 
-        for (model_name, model) in models {
-            self.models.insert(model_name, model);
-        }
+        revalidate_models(
+            &self.models,
+            &self.pending_updates,
+            versions,
+            self.model_store_dir.as_str(),
+            options,
+            |blob_name| async move {
+                self.timeouts
+                    .run(
+                        StoreOperation::Download,
+                        blob_name.as_str(),
+                        download_blob(
+                            &self.container_client,
+                            blob_name.clone(),
+                            self.model_store_dir.clone(),
+                        ),
+                    )
+                    .await
+            },
+        )
+        .await;
 
         Ok(())
     }
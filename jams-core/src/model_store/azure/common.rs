@@ -8,7 +8,8 @@ use futures::StreamExt;
 /// This function performs the following steps:
 /// 1. Creates a `BlobClient` for the specified blob.
 /// 2. Streams the blob data in chunks and collects it into a complete byte vector.
-/// 3. Saves the collected data to a temporary path and unpacks it into the specified model storage directory.
+/// 3. Saves the collected data to a temporary path and unpacks it into the specified model storage directory,
+///    recording the blob URI and etag as the provenance of the unpacked artifacts.
 ///
 /// # Arguments
 ///
@@ -49,15 +50,19 @@ pub async fn download_blob(
     let blob_client = client.blob_client(blob_name.clone());
     let mut blob_stream = blob_client.get().into_stream();
     let mut complete_response: Vec<u8> = vec![];
+    let mut etag: Option<String> = None;
     while let Some(value) = blob_stream.next().await {
         let data = match value {
-            Ok(response) => match response.data.collect().await {
-                Ok(data) => data.to_vec(),
-                Err(e) => {
-                    tracing::error!("Failed to convert bytes: {}", e);
-                    anyhow::bail!("Failed to convert bytes: {}", e)
+            Ok(response) => {
+                etag.get_or_insert_with(|| response.blob.properties.etag.to_string());
+                match response.data.collect().await {
+                    Ok(data) => data.to_vec(),
+                    Err(e) => {
+                        tracing::error!("Failed to convert bytes: {}", e);
+                        anyhow::bail!("Failed to convert bytes: {}", e)
+                    }
                 }
-            },
+            }
             Err(e) => {
                 tracing::error!("Failed to collect data to bytes: {}", e);
                 anyhow::bail!("Failed to collect data to bytes: {}", e)
@@ -71,6 +76,8 @@ pub async fn download_blob(
         blob_name.clone(),
         Bytes::copy_from_slice(&complete_response[..]),
         model_store_dir.as_str(),
+        format!("azure://{}/{}", client.container_name(), blob_name),
+        etag,
     ) {
         Ok(_) => {
             // Do nothing
@@ -1,10 +1,11 @@
 use crate::model_store::azure::common::download_blob;
 use crate::model_store::fetcher::Fetcher;
-use crate::model_store::storage::{load_models, Model, ModelName};
+use crate::model_store::storage::{is_selected, load_models, Model, ModelName};
 use async_trait::async_trait;
 use azure_storage_blobs::prelude::ContainerClient;
 use dashmap::DashMap;
 use futures::StreamExt;
+use std::collections::BTreeMap;
 use std::num::NonZeroU32;
 use std::sync::Arc;
 
@@ -41,6 +42,7 @@ impl Fetcher for ContainerClient {
     /// # Parameters
     /// - `artefacts_dir_name`: The storage container name  where model artefacts are stored.
     /// - `output_dir`: The directory where models will be downloaded and stored.
+    /// - `model_names`: The names of the models to fetch. Every model is fetched if `None`.
     ///
     /// # Returns
     /// - `Ok(DashMap<ModelName, Arc<Model>>)` containing all loaded models mapped by their names.
@@ -60,6 +62,7 @@ impl Fetcher for ContainerClient {
         &self,
         artefacts_dir_name: Option<String>,
         output_dir: String,
+        model_names: Option<Vec<ModelName>>,
     ) -> anyhow::Result<DashMap<ModelName, Arc<Model>>> {
         if artefacts_dir_name.is_some() {
             tracing::error!("Unexpected parameter 'artefacts_dir_name' provided ❌");
@@ -75,6 +78,9 @@ impl Fetcher for ContainerClient {
             match result {
                 Ok(result) => {
                     for blob in result.blobs.blobs() {
+                        if !is_selected(blob.name.as_str(), model_names.as_deref()) {
+                            continue;
+                        }
                         // Download blob to model_store_dir
                         match download_blob(self, blob.clone().name, output_dir.clone()).await {
                             Ok(blob) => blob,
@@ -97,3 +103,38 @@ impl Fetcher for ContainerClient {
         Ok(models)
     }
 }
+
+/// Retrieves the etag of each blob in an Azure Blob Storage container.
+///
+/// # Arguments
+///
+/// * `client` - A reference to an Azure Blob Storage `ContainerClient`.
+///
+/// # Returns
+///
+/// The etag of each blob keyed by the blob name.
+///
+/// # Errors
+///
+/// This function will return an error if listing blobs in the container fails.
+pub async fn get_versions(
+    client: &ContainerClient,
+) -> anyhow::Result<BTreeMap<String, Option<String>>> {
+    let mut versions = BTreeMap::new();
+    let max_results = NonZeroU32::new(10).unwrap();
+    let mut stream = client.list_blobs().max_results(max_results).into_stream();
+    while let Some(result) = stream.next().await {
+        match result {
+            Ok(result) => {
+                for blob in result.blobs.blobs() {
+                    versions.insert(blob.name.clone(), Some(blob.properties.etag.to_string()));
+                }
+            }
+            Err(e) => {
+                tracing::error!("Failed to list blobs ❌: {}", e);
+                anyhow::bail!("Failed to list blobs ❌: {}", e)
+            }
+        }
+    }
+    Ok(versions)
+}
@@ -1,3 +1,3 @@
 pub mod blob_storage;
 mod common;
-mod fetch;
+pub(crate) mod fetch;
@@ -0,0 +1,591 @@
+use crate::model::frameworks::ModelFramework;
+#[cfg(feature = "aws")]
+use crate::model_store::aws;
+#[cfg(feature = "azure")]
+use crate::model_store::azure;
+use crate::model_store::common::unpack_tarball_with_provenance;
+use crate::model_store::local;
+use crate::model_store::storage::{
+    accept_model, load_unpacked_model, tarball_model_name, ModelName,
+};
+#[cfg(feature = "azure")]
+use azure_storage_blobs::prelude::ContainerClient;
+#[cfg(feature = "azure")]
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The outcome of dry-run loading an artifact of a model store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtifactCheck {
+    /// Name of the artifact in the model store, i.e. the file name, the object key or the blob name.
+    pub artifact: String,
+    /// Version of the artifact in the model store, if known.
+    pub source_version: Option<String>,
+    /// Name of the model, if the artifact is named `<model_framework>-<model_name>.tar.gz`.
+    pub model_name: Option<ModelName>,
+    /// Framework of the model, if the artifact is named `<model_framework>-<model_name>.tar.gz`.
+    pub framework: Option<ModelFramework>,
+    /// SHA-256 digest of the unpacked model artifact, which is the version reported by the model metadata.
+    pub artifact_sha256: Option<String>,
+    /// SHA-256 digest of the tarball, if it was checked against a `Checksums` file.
+    pub tarball_sha256: Option<String>,
+    /// Why the model would fail to load, or `None` if it would load.
+    pub error: Option<String>,
+}
+
+impl ArtifactCheck {
+    fn new(artifact: &str, source_version: Option<String>) -> Self {
+        let names = tarball_model_name(file_name(artifact));
+        ArtifactCheck {
+            artifact: artifact.to_string(),
+            source_version,
+            model_name: names.as_ref().map(|(_, model_name)| model_name.clone()),
+            framework: names.map(|(framework, _)| framework),
+            artifact_sha256: None,
+            tarball_sha256: None,
+            error: None,
+        }
+    }
+
+    fn fail(mut self, error: impl ToString) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+
+    /// Returns true if the model would load.
+    pub fn loads(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// The outcome of dry-run loading every artifact of a model store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreCheck {
+    /// URI of the model store, e.g. `s3://bucket`.
+    pub source_uri: String,
+    /// The outcome for every artifact, ordered by the artifact name.
+    pub artifacts: Vec<ArtifactCheck>,
+}
+
+impl StoreCheck {
+    /// Returns true if every artifact of the model store would load.
+    pub fn passed(&self) -> bool {
+        self.artifacts.iter().all(ArtifactCheck::loads)
+    }
+
+    /// Returns the artifacts which would fail to load.
+    pub fn failed(&self) -> Vec<&ArtifactCheck> {
+        self.artifacts
+            .iter()
+            .filter(|artifact| !artifact.loads())
+            .collect()
+    }
+}
+
+/// The expected SHA-256 digests of the tarballs of a model store, keyed by the artifact name, in the format written
+/// by `sha256sum`.
+///
+/// # Example
+/// ```text
+/// 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08  catboost-titanic_model.tar.gz
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Checksums(BTreeMap<String, String>);
+
+impl Checksums {
+    /// Parses a checksums file, skipping empty lines and comments starting with `#`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line is not a hex encoded SHA-256 digest followed by the name of an artifact.
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let mut checksums = BTreeMap::new();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // sha256sum marks the files read in binary mode with a leading `*`
+            let (digest, artifact) = match line.split_once(char::is_whitespace) {
+                Some((digest, artifact)) => (digest, artifact.trim_start().trim_start_matches('*')),
+                None => (line, ""),
+            };
+            if digest.len() != 64
+                || !digest.chars().all(|c| c.is_ascii_hexdigit())
+                || artifact.is_empty()
+            {
+                anyhow::bail!(
+                    "Line {} of the checksums file must look like <sha256>  <artifact> ❌",
+                    index + 1
+                )
+            }
+            checksums.insert(artifact.to_string(), digest.to_ascii_lowercase());
+        }
+        Ok(Checksums(checksums))
+    }
+
+    /// Returns the expected digest of an artifact, looked up by its name and then by its file name.
+    fn get(&self, artifact: &str) -> Option<&String> {
+        self.0
+            .get(artifact)
+            .or_else(|| self.0.get(file_name(artifact)))
+    }
+}
+
+/// Checks every tarball of a local model store without loading it into a model server.
+///
+/// # Arguments
+///
+/// * `model_dir` - The directory holding the model tarballs.
+/// * `checksums` - The digests which the tarballs must match, if any.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be listed. Artifacts which would fail to load are reported in
+/// the `StoreCheck` instead.
+pub async fn check_local_store(
+    model_dir: &str,
+    checksums: Option<&Checksums>,
+) -> anyhow::Result<StoreCheck> {
+    let scratch_dir = scratch_dir()?;
+    let mut artifacts = Vec::new();
+    for (artifact, source_version) in local::filesystem::get_versions(model_dir)? {
+        let tarball_path = Path::new(model_dir).join(artifact.as_str());
+        let source_uri = match std::fs::canonicalize(&tarball_path) {
+            Ok(path) => format!("file://{}", path.display()),
+            Err(_) => format!("file://{}", tarball_path.display()),
+        };
+        let check = ArtifactCheck::new(artifact.as_str(), source_version);
+        artifacts.push(
+            check_tarball(
+                check,
+                tarball_path.as_path(),
+                source_uri,
+                &scratch_dir.path().join(artifacts.len().to_string()),
+                checksums,
+            )
+            .await,
+        );
+    }
+    Ok(report(
+        format!("file://{}", model_dir),
+        artifacts,
+        checksums,
+    ))
+}
+
+/// Checks every object of an S3 bucket, or of a MinIO bucket if `use_minio` is set, without loading it into
+/// a model server.
+///
+/// # Arguments
+///
+/// * `bucket_name` - The name of the bucket holding the model tarballs.
+/// * `use_minio` - Connects to MinIO instead of AWS S3 if set.
+/// * `checksums` - The digests which the tarballs must match, if any.
+///
+/// # Errors
+///
+/// Returns an error if the client cannot be built or the bucket cannot be listed. Artifacts which would fail
+/// to load, including those which cannot be downloaded, are reported in the `StoreCheck` instead.
+#[cfg(feature = "aws")]
+pub async fn check_s3_store(
+    bucket_name: String,
+    use_minio: Option<bool>,
+    checksums: Option<&Checksums>,
+) -> anyhow::Result<StoreCheck> {
+    let client = aws::s3::build_client(use_minio).await?;
+    let scratch_dir = scratch_dir()?;
+    let mut artifacts = Vec::new();
+    for (key, source_version) in aws::fetch::get_versions(&client, bucket_name.clone()).await? {
+        let check = ArtifactCheck::new(key.as_str(), source_version);
+        let artifact_dir = scratch_dir.path().join(artifacts.len().to_string());
+        let data = match client
+            .get_object()
+            .bucket(bucket_name.clone())
+            .key(key.clone())
+            .send()
+            .await
+        {
+            Ok(output) => match output.body.collect().await {
+                Ok(data) => data.into_bytes(),
+                Err(e) => {
+                    artifacts.push(check.fail(format!("failed to download the object: {}", e)));
+                    continue;
+                }
+            },
+            Err(e) => {
+                artifacts.push(check.fail(format!(
+                    "failed to get the object: {}",
+                    e.into_service_error()
+                )));
+                continue;
+            }
+        };
+        let source_uri = format!("s3://{}/{}", bucket_name, key);
+        artifacts.push(check_downloaded(check, &data, source_uri, &artifact_dir, checksums).await);
+    }
+    Ok(report(
+        format!("s3://{}", bucket_name),
+        artifacts,
+        checksums,
+    ))
+}
+
+/// Checks every blob of an Azure Blob Storage container without loading it into a model server.
+///
+/// # Arguments
+///
+/// * `container_name` - The name of the container holding the model tarballs.
+/// * `checksums` - The digests which the tarballs must match, if any.
+///
+/// # Errors
+///
+/// Returns an error if the client cannot be built or the container cannot be listed. Artifacts which would
+/// fail to load, including those which cannot be downloaded, are reported in the `StoreCheck` instead.
+#[cfg(feature = "azure")]
+pub async fn check_azure_store(
+    container_name: String,
+    checksums: Option<&Checksums>,
+) -> anyhow::Result<StoreCheck> {
+    let client = azure::blob_storage::build_container_client(container_name.clone())?;
+    let scratch_dir = scratch_dir()?;
+    let mut artifacts = Vec::new();
+    for (blob_name, source_version) in azure::fetch::get_versions(&client).await? {
+        let check = ArtifactCheck::new(blob_name.as_str(), source_version);
+        let artifact_dir = scratch_dir.path().join(artifacts.len().to_string());
+        let data = match read_blob(&client, blob_name.as_str()).await {
+            Ok(data) => data,
+            Err(e) => {
+                artifacts.push(check.fail(format!("failed to download the blob: {}", e)));
+                continue;
+            }
+        };
+        let source_uri = format!("azure://{}/{}", container_name, blob_name);
+        artifacts.push(check_downloaded(check, &data, source_uri, &artifact_dir, checksums).await);
+    }
+    Ok(report(
+        format!("azure://{}", container_name),
+        artifacts,
+        checksums,
+    ))
+}
+
+/// Saves a downloaded tarball into the scratch directory of the artifact before checking it.
+#[cfg(any(feature = "aws", feature = "azure"))]
+async fn check_downloaded(
+    check: ArtifactCheck,
+    data: &[u8],
+    source_uri: String,
+    artifact_dir: &Path,
+    checksums: Option<&Checksums>,
+) -> ArtifactCheck {
+    let tarball_path = artifact_dir.join(file_name(check.artifact.as_str()));
+    if let Err(e) =
+        std::fs::create_dir_all(artifact_dir).and_then(|_| std::fs::write(&tarball_path, data))
+    {
+        return check.fail(format!("failed to save the artifact: {}", e));
+    }
+    check_tarball(
+        check,
+        tarball_path.as_path(),
+        source_uri,
+        artifact_dir,
+        checksums,
+    )
+    .await
+}
+
+/// Runs the steps of loading a model from a tarball, stopping at the first one which fails:
+///
+/// 1. The tarball must be named `<model_framework>-<model_name>.tar.gz`.
+/// 2. The tarball must match its digest in the checksums file, if any.
+/// 3. The tarball must unpack, and the files describing the model, e.g. `labels.yaml` or `validation.json`,
+///    must be valid.
+/// 4. The host must satisfy the requirements of the model, and the model must load with its framework.
+/// 5. The model must meet the acceptance criteria of its validation set, if any.
+///
+/// The init hooks of the model are not run, as they may have side effects.
+async fn check_tarball(
+    mut check: ArtifactCheck,
+    tarball_path: &Path,
+    source_uri: String,
+    artifact_dir: &Path,
+    checksums: Option<&Checksums>,
+) -> ArtifactCheck {
+    let tarball_name = file_name(check.artifact.as_str()).to_string();
+    if check.model_name.is_none() || !tarball_name.ends_with(".tar.gz") {
+        return check.fail(format!(
+            "{} is not named <model_framework>-<model_name>.tar.gz",
+            tarball_name
+        ));
+    }
+    if let Some(checksums) = checksums {
+        if let Err(e) = verify_checksum(&mut check, tarball_path, checksums) {
+            return check.fail(e);
+        }
+    }
+
+    let model_store_dir = artifact_dir.join("model_store");
+    let (tarball_path, model_store_dir) = match (tarball_path.to_str(), model_store_dir.to_str()) {
+        (Some(tarball_path), Some(model_store_dir)) => {
+            (tarball_path.to_string(), model_store_dir.to_string())
+        }
+        _ => return check.fail("failed to convert the artifact path to str"),
+    };
+    let source_version = check.source_version.clone();
+    if let Err(e) = unpack_tarball_with_provenance(
+        tarball_path.as_str(),
+        model_store_dir.as_str(),
+        source_uri,
+        source_version,
+    ) {
+        return check.fail(e);
+    }
+
+    let model = match load_unpacked_model(model_store_dir.as_str(), tarball_name.as_str()).await {
+        Ok((_, model)) => model,
+        Err(e) => return check.fail(e),
+    };
+    if !model.info.artifact_sha256.is_empty() {
+        check.artifact_sha256 = Some(model.info.artifact_sha256.clone());
+    }
+    if let Err(e) = accept_model(&model, None) {
+        return check.fail(e);
+    }
+    check
+}
+
+/// Checks the SHA-256 digest of a tarball against its digest in the checksums file.
+fn verify_checksum(
+    check: &mut ArtifactCheck,
+    tarball_path: &Path,
+    checksums: &Checksums,
+) -> anyhow::Result<()> {
+    let digest = hex::encode(Sha256::digest(std::fs::read(tarball_path)?));
+    check.tarball_sha256 = Some(digest.clone());
+    match checksums.get(check.artifact.as_str()) {
+        None => anyhow::bail!("{} has no checksum in the checksums file", check.artifact),
+        Some(expected) if *expected != digest => anyhow::bail!(
+            "checksum {} does not match {} of the checksums file",
+            digest,
+            expected
+        ),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Builds the report of a model store, failing the artifacts whose model name is already taken by another
+/// artifact, as only one of them would be served, and reporting the artifacts of the checksums file which are
+/// missing from the model store.
+fn report(
+    source_uri: String,
+    mut artifacts: Vec<ArtifactCheck>,
+    checksums: Option<&Checksums>,
+) -> StoreCheck {
+    if let Some(checksums) = checksums {
+        for artifact in checksums.0.keys() {
+            let listed = artifacts.iter().any(|check| {
+                check.artifact == *artifact || file_name(check.artifact.as_str()) == artifact
+            });
+            if !listed {
+                artifacts.push(
+                    ArtifactCheck::new(artifact, None)
+                        .fail("artifact of the checksums file is missing from the model store"),
+                );
+            }
+        }
+    }
+    let mut owners: BTreeMap<ModelName, String> = BTreeMap::new();
+    for check in artifacts.iter_mut() {
+        let model_name = match &check.model_name {
+            Some(model_name) => model_name.clone(),
+            None => continue,
+        };
+        match owners.get(&model_name) {
+            Some(owner) => {
+                check.error.get_or_insert_with(|| {
+                    format!("model {} is also provided by {}", model_name, owner)
+                });
+            }
+            None => {
+                owners.insert(model_name, check.artifact.clone());
+            }
+        }
+    }
+    StoreCheck {
+        source_uri,
+        artifacts,
+    }
+}
+
+/// Reads every chunk of a blob into memory.
+#[cfg(feature = "azure")]
+async fn read_blob(client: &ContainerClient, blob_name: &str) -> anyhow::Result<Vec<u8>> {
+    let mut data: Vec<u8> = Vec::new();
+    let mut stream = client.blob_client(blob_name).get().into_stream();
+    while let Some(response) = stream.next().await {
+        data.extend(&response?.data.collect().await?);
+    }
+    Ok(data)
+}
+
+/// Returns the last component of an artifact name, e.g. the file name of a nested object key.
+fn file_name(artifact: &str) -> &str {
+    artifact.rsplit('/').next().unwrap_or(artifact)
+}
+
+/// Creates a temporary directory which holds the unpacked artifacts and is deleted once dropped.
+fn scratch_dir() -> anyhow::Result<tempfile::TempDir> {
+    match tempfile::Builder::new().prefix("store_check").tempdir() {
+        Ok(dir) => Ok(dir),
+        Err(e) => {
+            tracing::error!("Failed to create temporary directory ❌: {}", e);
+            anyhow::bail!("Failed to create temporary directory ❌: {}", e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn successfully_check_local_model_store() {
+        // Arrange
+        let model_dir = "./tests/model_storage/model_store";
+
+        // Act
+        let check = check_local_store(model_dir, None).await.unwrap();
+
+        // Assert
+        assert_eq!(check.artifacts.len(), 4);
+        let catboost = check
+            .artifacts
+            .iter()
+            .find(|artifact| artifact.artifact == "catboost-titanic_model.tar.gz")
+            .unwrap();
+        assert!(catboost.loads(), "{:?}", catboost.error);
+        assert_eq!(catboost.model_name, Some("titanic_model".to_string()));
+        assert_eq!(catboost.framework, Some("catboost"));
+        assert_eq!(catboost.artifact_sha256.as_ref().map(String::len), Some(64));
+    }
+
+    #[tokio::test]
+    async fn fails_to_check_misnamed_and_corrupt_artifacts() {
+        // Arrange
+        let model_dir = tempfile::tempdir().unwrap();
+        std::fs::write(model_dir.path().join("README.md"), "models").unwrap();
+        std::fs::write(model_dir.path().join("catboost-broken.tar.gz"), "not gzip").unwrap();
+        for tarball_name in ["catboost-titanic.tar.gz", "lightgbm-titanic.tar.gz"] {
+            std::fs::copy(
+                "./tests/model_storage/model_store/catboost-titanic_model.tar.gz",
+                model_dir.path().join(tarball_name),
+            )
+            .unwrap();
+        }
+
+        // Act
+        let check = check_local_store(model_dir.path().to_str().unwrap(), None)
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(!check.passed());
+        let errors: BTreeMap<&str, &str> = check
+            .artifacts
+            .iter()
+            .map(|artifact| {
+                (
+                    artifact.artifact.as_str(),
+                    artifact.error.as_deref().unwrap_or_default(),
+                )
+            })
+            .collect();
+        assert!(errors["README.md"].contains("is not named"));
+        assert!(errors["catboost-broken.tar.gz"].contains("Failed to unpack tarball"));
+        assert!(errors["catboost-titanic.tar.gz"].contains("catboost"));
+        assert!(errors["lightgbm-titanic.tar.gz"].contains("lightgbm"));
+        assert_eq!(check.failed().len(), 4);
+    }
+
+    #[test]
+    fn fails_to_check_artifacts_of_the_same_model() {
+        // Arrange
+        let artifacts = vec![
+            ArtifactCheck::new("catboost-titanic.tar.gz", None),
+            ArtifactCheck::new("models/lightgbm-titanic.tar.gz", None),
+            ArtifactCheck::new("catboost-penguin.tar.gz", None),
+        ];
+
+        // Act
+        let check = report("s3://models".to_string(), artifacts, None);
+
+        // Assert
+        assert_eq!(check.failed().len(), 1);
+        assert_eq!(
+            check.artifacts[1].error,
+            Some("model titanic is also provided by catboost-titanic.tar.gz".to_string())
+        );
+        assert_eq!(check.artifacts[1].framework, Some("lightgbm"));
+    }
+
+    #[tokio::test]
+    async fn fails_to_check_artifacts_which_do_not_match_their_checksums() {
+        // Arrange
+        let model_dir = tempfile::tempdir().unwrap();
+        for tarball_name in [
+            "catboost-titanic.tar.gz",
+            "catboost-penguin.tar.gz",
+            "catboost-iris.tar.gz",
+        ] {
+            std::fs::copy(
+                "./tests/model_storage/model_store/catboost-titanic_model.tar.gz",
+                model_dir.path().join(tarball_name),
+            )
+            .unwrap();
+        }
+        let digest = hex::encode(Sha256::digest(
+            std::fs::read("./tests/model_storage/model_store/catboost-titanic_model.tar.gz")
+                .unwrap(),
+        ));
+        let checksums = Checksums::parse(
+            format!(
+                "# models\n{}  catboost-titanic.tar.gz\n{} *catboost-penguin.tar.gz\n{}  catboost-lost.tar.gz\n",
+                digest,
+                "0".repeat(64),
+                digest
+            )
+            .as_str(),
+        )
+        .unwrap();
+
+        // Act
+        let check = check_local_store(model_dir.path().to_str().unwrap(), Some(&checksums))
+            .await
+            .unwrap();
+
+        // Assert
+        let errors: BTreeMap<&str, Option<&str>> = check
+            .artifacts
+            .iter()
+            .map(|artifact| (artifact.artifact.as_str(), artifact.error.as_deref()))
+            .collect();
+        assert_eq!(errors["catboost-titanic.tar.gz"], None);
+        assert!(errors["catboost-penguin.tar.gz"]
+            .unwrap()
+            .contains("does not match"));
+        assert!(errors["catboost-iris.tar.gz"]
+            .unwrap()
+            .contains("has no checksum"));
+        assert!(errors["catboost-lost.tar.gz"].unwrap().contains("missing"));
+        let titanic = check
+            .artifacts
+            .iter()
+            .find(|artifact| artifact.artifact == "catboost-titanic.tar.gz")
+            .unwrap();
+        assert_eq!(titanic.tarball_sha256.as_deref(), Some(digest.as_str()));
+        assert!(Checksums::parse("not-a-digest  catboost-titanic.tar.gz").is_err());
+    }
+}
@@ -1,11 +1,81 @@
+use crate::model::bundle::{ModelBundle, BUNDLE_DIRECTORY_NAME};
+use crate::model::calibration::{CalibrationMap, CALIBRATION_FILE_NAME};
+use crate::model::embedding::{EmbeddingIndex, EMBEDDING_INDEX_FILE_NAME};
+use crate::model::expression::{PostprocessProgram, POSTPROCESS_FILE_NAME};
+use crate::model::predict::{PredictOptions, PREDICT_OPTIONS_FILE_NAME};
+use crate::model::requirements::{ModelRequirements, REQUIREMENTS_FILE_NAME};
+use crate::model::streaming::{GenerationConfig, GENERATION_FILE_NAME};
+use crate::model::validation::{ValidationSet, VALIDATION_FILE_NAME};
+use crate::model::variant::{ModelPrecision, VARIANTS_DIRECTORY_NAME};
+use crate::model_store::encryption;
 use flate2::read::GzDecoder;
-use std::fs::{remove_dir_all, File};
-use std::io::Write;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::remove_dir_all;
+use std::io;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
 use tar::Archive;
 
 pub const DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX: &str = "model_store";
 
+/// Directory inside the model store directory which holds the provenance of the unpacked artifacts.
+pub const PROVENANCE_DIRECTORY_NAME: &str = ".provenance";
+
+/// Name of the optional file in a model tarball which contains the build labels of the model.
+pub const LABELS_FILE_NAME: &str = "labels.yaml";
+
+/// Name of the optional file in a classifier tarball which contains the class labels, one per line,
+/// in the order of the outputs of the model.
+pub const CLASS_LABELS_FILE_NAME: &str = "labels.txt";
+
+/// Describes where a model artifact came from.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Provenance {
+    /// URI of the tarball in the model store, e.g. `s3://bucket/key`.
+    pub source_uri: String,
+    /// Version of the tarball in the model store, i.e. the S3 version id or the blob etag.
+    pub source_version: Option<String>,
+    /// Build labels read from the `labels.yaml` file in the tarball.
+    pub labels: BTreeMap<String, String>,
+    /// Calibration map read from the `calibration.json` file in the tarball, if any.
+    #[serde(default)]
+    pub calibration: Option<CalibrationMap>,
+    /// Postprocessing program read from the `postprocess.expr` file in the tarball, if any.
+    #[serde(default)]
+    pub postprocess: Option<PostprocessProgram>,
+    /// Embedding index read from the `embedding_index.json` file in the tarball, if any.
+    #[serde(default)]
+    pub embedding_index: Option<EmbeddingIndex>,
+    /// Class labels read from the `labels.txt` file in the tarball, if any.
+    #[serde(default)]
+    pub class_labels: Vec<String>,
+    /// Default prediction options read from the `predict_options.json` file in the tarball, if any.
+    #[serde(default)]
+    pub predict_options: PredictOptions,
+    /// Capabilities required of the host, read from the `requirements.json` file in the tarball, if any.
+    #[serde(default)]
+    pub requirements: ModelRequirements,
+    /// Validation set and acceptance criteria read from the `validation.json` file in the tarball, if any. It is
+    /// written to its own file next to the provenance, as it is only read when the model is promoted, see
+    /// `read_validation_set`.
+    #[serde(skip)]
+    pub validation: Option<ValidationSet>,
+    /// Auxiliary files read from the `bundle` directory in the tarball, if any.
+    #[serde(default)]
+    pub bundle: ModelBundle,
+    /// Paths of the reduced precision variants unpacked from the `variants` directory in the tarball, if any.
+    #[serde(default)]
+    pub variants: BTreeMap<ModelPrecision, String>,
+    /// How the model generates sequences, read from the `generation.json` file in the tarball, if any.
+    #[serde(default)]
+    pub generation: Option<GenerationConfig>,
+    /// SHA-256 digest of the plaintext of the artifact, computed while it was unpacked, see `hash_artifact`.
+    #[serde(default)]
+    pub artifact_sha256: Option<String>,
+}
+
 /// Saves and unpacks a tarball file into a specified output directory.
 ///
 /// This function saves a tarball file received as bytes to a temporary location,
@@ -17,6 +87,8 @@ pub const DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX: &str = "model_store";
 /// * `key` - The key or name of the tarball file.
 /// * `data` - The tarball file data as bytes.
 /// * `out_dir` - The output directory where the tarball will be unpacked.
+/// * `source_uri` - The URI of the tarball in the model store.
+/// * `source_version` - The version of the tarball in the model store, if known.
 ///
 /// # Returns
 ///
@@ -34,6 +106,36 @@ pub fn save_and_upack_tarball(
     key: String,
     data: bytes::Bytes,
     out_dir: &str,
+    source_uri: String,
+    source_version: Option<String>,
+) -> anyhow::Result<()> {
+    save_and_upack_tarball_from(
+        path,
+        key,
+        &mut &data[..],
+        out_dir,
+        source_uri,
+        source_version,
+    )
+}
+
+/// Saves the tarball read from `reader` to a temporary location and unpacks it into the specified output
+/// directory, without holding the tarball in memory. See `save_and_upack_tarball`.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The tarball file cannot be saved or created.
+/// * The tarball file cannot be unpacked into the output directory.
+///
+#[tracing::instrument(skip(path, key, reader, out_dir))]
+pub fn save_and_upack_tarball_from(
+    path: &str,
+    key: String,
+    reader: &mut impl Read,
+    out_dir: &str,
+    source_uri: String,
+    source_version: Option<String>,
 ) -> anyhow::Result<()> {
     let file_path = Path::new(path).join(key);
 
@@ -48,32 +150,19 @@ pub fn save_and_upack_tarball(
         }
     }
 
-    match File::create(&file_path) {
-        Ok(mut file) => match file.write_all(&data) {
-            Ok(_) => {
-                tracing::info!("Saved file to {:?}", file_path);
-            }
-            Err(e) => {
-                tracing::error!(
-                    "Failed to save file to {:?} ⚠️: {}",
-                    file_path,
-                    e.to_string()
-                );
-                anyhow::bail!(
-                    "Failed to save file to {:?} ⚠️: {}",
-                    file_path,
-                    e.to_string()
-                )
-            }
-        },
+    // keep the downloaded tarball encrypted on disk when the artifact cache is encrypted
+    match encryption::write_cached_from(&file_path, reader) {
+        Ok(_) => {
+            tracing::info!("Saved file to {:?}", file_path);
+        }
         Err(e) => {
             tracing::error!(
-                "Failed to create file {:?} ⚠️: {}",
+                "Failed to save file to {:?} ⚠️: {}",
                 file_path,
                 e.to_string()
             );
             anyhow::bail!(
-                "Failed to create file {:?} ⚠️: {}",
+                "Failed to save file to {:?} ⚠️: {}",
                 file_path,
                 e.to_string()
             )
@@ -88,7 +177,7 @@ pub fn save_and_upack_tarball(
         Some(path) => path,
     };
 
-    match unpack_tarball(tarball_path, out_dir) {
+    match unpack_tarball_with_provenance(tarball_path, out_dir, source_uri, source_version) {
         Ok(_) => Ok(()),
         Err(e) => {
             tracing::error!("Failed to unpack ⚠️: {}", e.to_string());
@@ -100,7 +189,8 @@ pub fn save_and_upack_tarball(
 /// Unpacks a `.tar.gz` file into a specified output directory.
 ///
 /// This function opens a `.tar.gz` file located at `tarball_path`, extracts its contents,
-/// and unpacks them into the directory specified by `out_dir`.
+/// and unpacks them into the directory specified by `out_dir`. The tarball path and modification
+/// time are recorded as the source and version of the unpacked artifacts.
 ///
 /// # Arguments
 ///
@@ -119,41 +209,569 @@ pub fn save_and_upack_tarball(
 ///
 #[tracing::instrument(skip(tarball_path, out_dir))]
 pub fn unpack_tarball(tarball_path: &str, out_dir: &str) -> anyhow::Result<()> {
-    match File::open(tarball_path) {
-        Ok(tar_gz) => {
-            let tar = GzDecoder::new(tar_gz);
-            let mut archive = Archive::new(tar);
-
-            match archive.unpack(out_dir) {
-                Ok(_) => {
-                    tracing::info!(
-                        "Unpacked tarball: {:?} at location: {}",
-                        tarball_path,
-                        out_dir
-                    );
-                    Ok(())
-                }
-                Err(e) => {
-                    tracing::error!(
-                        "Failed to unpack tarball ⚠️: {:?} at location: {} - {}",
-                        tarball_path,
-                        out_dir,
-                        e.to_string()
-                    );
-                    anyhow::bail!(
-                        "Failed to unpack tarball ⚠️: {:?} at location: {} - {}",
-                        tarball_path,
-                        out_dir,
-                        e.to_string()
-                    )
-                }
-            }
-        }
+    let source_uri = match std::fs::canonicalize(tarball_path) {
+        Ok(path) => format!("file://{}", path.display()),
+        Err(_) => format!("file://{}", tarball_path),
+    };
+    unpack_tarball_with_provenance(
+        tarball_path,
+        out_dir,
+        source_uri,
+        tarball_version(tarball_path),
+    )
+}
+
+/// Returns the version of a tarball on the local filesystem, which is its modification time in
+/// nanoseconds since the Unix epoch, or `None` if the modification time cannot be read.
+pub fn tarball_version(tarball_path: &str) -> Option<String> {
+    let modified = std::fs::metadata(tarball_path).ok()?.modified().ok()?;
+    let nanos = modified
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_nanos();
+    Some(nanos.to_string())
+}
+
+/// Unpacks a `.tar.gz` file into a specified output directory and records the provenance of its artifacts.
+///
+/// The `labels.yaml` file at the root of the tarball is not unpacked. Instead, its labels are
+/// recorded in the provenance of every other top level entry of the tarball, along with the hash of
+/// its plaintext, see `hash_artifact`. The provenance is stored as JSON in the `.provenance` directory
+/// inside `out_dir`.
+///
+/// # Arguments
+///
+/// * `tarball_path` - The path to the `.tar.gz` file to unpack.
+/// * `out_dir` - The directory where the contents of the `.tar.gz` file will be unpacked.
+/// * `source_uri` - The URI of the tarball in the model store.
+/// * `source_version` - The version of the tarball in the model store, if known.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The `.tar.gz` file cannot be opened or read.
+/// * The contents of the `.tar.gz` file cannot be unpacked into the output directory.
+/// * The `labels.yaml` file is not a valid mapping.
+/// * The `calibration.json` file is not a valid calibration map.
+/// * The `postprocess.expr` file is not a valid postprocessing program.
+/// * The `embedding_index.json` file is not a valid embedding index.
+/// * The `labels.txt` file cannot be read.
+/// * The `predict_options.json` file does not contain valid prediction options.
+/// * The `requirements.json` file does not contain valid requirements.
+/// * The `validation.json` file is not a valid validation set.
+/// * The `generation.json` file is not a valid generation config.
+/// * A file in the `variants` directory is not named after a reduced precision.
+/// * The provenance cannot be written.
+///
+#[tracing::instrument(skip(tarball_path, out_dir))]
+pub fn unpack_tarball_with_provenance(
+    tarball_path: &str,
+    out_dir: &str,
+    source_uri: String,
+    source_version: Option<String>,
+) -> anyhow::Result<()> {
+    let tar_gz = match encryption::open_cached(Path::new(tarball_path)) {
+        Ok(tar_gz) => tar_gz,
         Err(e) => {
             tracing::error!("Failed to open tarball ⚠️: {}", e.to_string());
             anyhow::bail!("Failed to open tarball ⚠️: {}", e.to_string())
         }
+    };
+
+    let mut provenance = Provenance {
+        source_uri,
+        source_version,
+        ..Provenance::default()
+    };
+    let mut artifacts: BTreeMap<String, FileDigests> = BTreeMap::new();
+    // variants are kept next to the provenance, so that they are not loaded as models of their own
+    let tarball_name = Path::new(tarball_path)
+        .file_name()
+        .map(|name| {
+            name.to_string_lossy()
+                .trim_end_matches(".tar.gz")
+                .to_string()
+        })
+        .unwrap_or_default();
+    let variants_dir = Path::new(out_dir)
+        .join(PROVENANCE_DIRECTORY_NAME)
+        .join(VARIANTS_DIRECTORY_NAME)
+        .join(tarball_name);
+    if let Err(e) = unpack_entries(
+        tar_gz,
+        out_dir,
+        &variants_dir,
+        &mut provenance,
+        &mut artifacts,
+    ) {
+        tracing::error!(
+            "Failed to unpack tarball ⚠️: {:?} at location: {} - {}",
+            tarball_path,
+            out_dir,
+            e.to_string()
+        );
+        anyhow::bail!(
+            "Failed to unpack tarball ⚠️: {:?} at location: {} - {}",
+            tarball_path,
+            out_dir,
+            e.to_string()
+        )
+    }
+    tracing::info!(
+        "Unpacked tarball: {:?} at location: {}",
+        tarball_path,
+        out_dir
+    );
+
+    for (artifact, digests) in artifacts {
+        let provenance = Provenance {
+            artifact_sha256: digest_artifact(&digests),
+            ..provenance.clone()
+        };
+        write_provenance(out_dir, artifact.as_str(), &provenance)?;
+        write_validation_set(out_dir, artifact.as_str(), provenance.validation.as_ref())?;
+    }
+
+    Ok(())
+}
+
+/// The SHA-256 digests of the plaintext of the files of an artifact, keyed by their path relative to the artifact,
+/// which is empty for an artifact which is a single file.
+type FileDigests = BTreeMap<PathBuf, [u8; 32]>;
+
+/// Unpacks the entries of a tarball, collecting the files describing the model into `provenance`
+/// and the top level artifacts with the digests of their files into `artifacts`. The variants of the
+/// model are unpacked into `variants_dir`.
+///
+/// The files are encrypted as they are read from the tarball when the artifact cache is encrypted, so
+/// that their plaintext never reaches the disk.
+fn unpack_entries(
+    tar_gz: impl Read,
+    out_dir: &str,
+    variants_dir: &Path,
+    provenance: &mut Provenance,
+    artifacts: &mut BTreeMap<String, FileDigests>,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(out_dir)?;
+    let out_dir_path = std::fs::canonicalize(out_dir)?;
+    let mut archive = Archive::new(GzDecoder::new(tar_gz));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if !path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            tracing::warn!("Skipped unsafe tarball entry {:?} ⚠️", path);
+            continue;
+        }
+        let top_level = match path
+            .components()
+            .find(|c| matches!(c, Component::Normal(_)))
+        {
+            Some(Component::Normal(name)) => name.to_string_lossy().to_string(),
+            _ => continue,
+        };
+
+        if top_level == LABELS_FILE_NAME && path.components().count() == 1 {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            provenance.labels = parse_labels(contents.as_str())?;
+            continue;
+        }
+
+        if top_level == CALIBRATION_FILE_NAME && path.components().count() == 1 {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            provenance.calibration = Some(CalibrationMap::parse(contents.as_str())?);
+            continue;
+        }
+
+        if top_level == POSTPROCESS_FILE_NAME && path.components().count() == 1 {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            provenance.postprocess = Some(PostprocessProgram::parse(contents.as_str())?);
+            continue;
+        }
+
+        if top_level == EMBEDDING_INDEX_FILE_NAME && path.components().count() == 1 {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            provenance.embedding_index = Some(EmbeddingIndex::parse(contents.as_str())?);
+            continue;
+        }
+
+        if top_level == CLASS_LABELS_FILE_NAME && path.components().count() == 1 {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            provenance.class_labels = parse_class_labels(contents.as_str());
+            continue;
+        }
+
+        if top_level == PREDICT_OPTIONS_FILE_NAME && path.components().count() == 1 {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            provenance.predict_options = PredictOptions::parse(contents.as_str())?;
+            continue;
+        }
+
+        if top_level == REQUIREMENTS_FILE_NAME && path.components().count() == 1 {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            provenance.requirements = ModelRequirements::parse(contents.as_str())?;
+            continue;
+        }
+
+        if top_level == VALIDATION_FILE_NAME && path.components().count() == 1 {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            provenance.validation = Some(ValidationSet::parse(contents.as_str())?);
+            continue;
+        }
+
+        if top_level == GENERATION_FILE_NAME && path.components().count() == 1 {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            provenance.generation = Some(GenerationConfig::parse(contents.as_str())?);
+            continue;
+        }
+
+        if top_level == VARIANTS_DIRECTORY_NAME {
+            if entry.header().entry_type().is_file() {
+                let depth = path
+                    .components()
+                    .filter(|c| matches!(c, Component::Normal(_)))
+                    .count();
+                let file_name = match (depth, path.file_name()) {
+                    (2, Some(file_name)) => file_name.to_os_string(),
+                    _ => anyhow::bail!(
+                        "Model variant {:?} must be a file at the root of the {} directory ❌",
+                        path,
+                        VARIANTS_DIRECTORY_NAME
+                    ),
+                };
+                let precision = ModelPrecision::from_variant_path(&path)?;
+                std::fs::create_dir_all(variants_dir)?;
+                let variant_path = variants_dir.join(file_name);
+                write_entry(&mut entry, &variant_path)?;
+                provenance
+                    .variants
+                    .insert(precision, variant_path.to_string_lossy().to_string());
+            }
+            continue;
+        }
+
+        if top_level == BUNDLE_DIRECTORY_NAME {
+            if entry.header().entry_type().is_file() {
+                let mut contents = String::new();
+                entry.read_to_string(&mut contents)?;
+                let relative_path = path.strip_prefix(BUNDLE_DIRECTORY_NAME).unwrap_or(&path);
+                provenance
+                    .bundle
+                    .add_file(relative_path, contents.as_str())?;
+            }
+            continue;
+        }
+
+        if !artifacts.contains_key(&top_level) {
+            // an artifact which is unpacked again replaces its previous version, so that none of the files of
+            // the previous version are left behind
+            let previous = out_dir_path.join(&top_level);
+            if previous.is_dir() {
+                std::fs::remove_dir_all(&previous)?;
+            }
+            artifacts.insert(top_level.clone(), FileDigests::new());
+        }
+
+        if !entry.header().entry_type().is_file() {
+            if !entry.unpack_in(out_dir)? {
+                tracing::warn!("Skipped unsafe tarball entry {:?} ⚠️", path);
+            }
+            continue;
+        }
+
+        let file_path = path
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .fold(out_dir_path.clone(), |file_path, c| file_path.join(c));
+        let parent = match file_path.parent() {
+            Some(parent) => parent,
+            None => continue,
+        };
+        std::fs::create_dir_all(parent)?;
+        // links unpacked from the tarball must not redirect its files outside of the output directory
+        if !std::fs::canonicalize(parent)?.starts_with(&out_dir_path) {
+            tracing::warn!("Skipped unsafe tarball entry {:?} ⚠️", path);
+            continue;
+        }
+        if let Ok(metadata) = std::fs::symlink_metadata(&file_path) {
+            if metadata.file_type().is_symlink() {
+                std::fs::remove_file(&file_path)?;
+            }
+        }
+        let digest = write_entry(&mut entry, &file_path)?;
+        let relative_path = file_path
+            .strip_prefix(out_dir_path.join(&top_level))
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        if let Some(digests) = artifacts.get_mut(&top_level) {
+            digests.insert(relative_path, digest);
+        }
+    }
+    Ok(())
+}
+
+/// Writes a file of a tarball to `path`, encrypting it as it is read when the artifact cache is encrypted, and
+/// returns the SHA-256 digest of its plaintext.
+fn write_entry(entry: &mut impl Read, path: &Path) -> anyhow::Result<[u8; 32]> {
+    let mut hashing = HashingReader {
+        inner: entry,
+        hasher: Sha256::new(),
+    };
+    encryption::write_cached_from(path, &mut hashing)?;
+    Ok(hashing.hasher.finalize().into())
+}
+
+/// Hashes the bytes read from `inner`.
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let length = self.inner.read(buf)?;
+        self.hasher.update(&buf[..length]);
+        Ok(length)
+    }
+}
+
+/// Parses the build labels from the contents of a `labels.yaml` file.
+///
+/// The file must be a mapping of labels to scalar values. Numbers and booleans are converted to strings.
+pub fn parse_labels(contents: &str) -> anyhow::Result<BTreeMap<String, String>> {
+    if contents.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    let values: BTreeMap<String, serde_yaml::Value> = match serde_yaml::from_str(contents) {
+        Ok(values) => values,
+        Err(e) => anyhow::bail!("Failed to parse {} ❌: {}", LABELS_FILE_NAME, e),
+    };
+
+    let mut labels = BTreeMap::new();
+    for (key, value) in values {
+        let value = match value {
+            serde_yaml::Value::String(value) => value,
+            serde_yaml::Value::Number(value) => value.to_string(),
+            serde_yaml::Value::Bool(value) => value.to_string(),
+            _ => anyhow::bail!(
+                "Label {} in {} must be a string, number or boolean ❌",
+                key,
+                LABELS_FILE_NAME
+            ),
+        };
+        labels.insert(key, value);
+    }
+    Ok(labels)
+}
+
+/// Parses the class labels from the contents of a `labels.txt` file, skipping empty lines.
+pub fn parse_class_labels(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Writes the provenance of an unpacked artifact to the `.provenance` directory in `out_dir`.
+fn write_provenance(out_dir: &str, artifact: &str, provenance: &Provenance) -> anyhow::Result<()> {
+    let dir = Path::new(out_dir).join(PROVENANCE_DIRECTORY_NAME);
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        tracing::error!("Failed to create directory ⚠️: {}", e.to_string());
+        anyhow::bail!("Failed to create directory ⚠️: {}", e.to_string())
+    }
+
+    let path = dir.join(format!("{}.json", artifact));
+    if let Err(e) = encryption::write_cached(&path, serde_json::to_vec(provenance)?.as_slice()) {
+        tracing::error!(
+            "Failed to write provenance {:?} ⚠️: {}",
+            path,
+            e.to_string()
+        );
+        anyhow::bail!(
+            "Failed to write provenance {:?} ⚠️: {}",
+            path,
+            e.to_string()
+        )
     }
+    Ok(())
+}
+
+/// Returns the path of the file the validation set of an unpacked artifact is written to.
+fn validation_set_path(dir: &Path, artifact: &str) -> PathBuf {
+    dir.join(PROVENANCE_DIRECTORY_NAME)
+        .join(format!("{}.{}", artifact, VALIDATION_FILE_NAME))
+}
+
+/// Writes the validation set of an unpacked artifact to the `.provenance` directory in `out_dir`, or removes the
+/// validation set of a previous version of the artifact if it has none.
+fn write_validation_set(
+    out_dir: &str,
+    artifact: &str,
+    validation: Option<&ValidationSet>,
+) -> anyhow::Result<()> {
+    let path = validation_set_path(Path::new(out_dir), artifact);
+    let written = match validation {
+        Some(validation) => {
+            encryption::write_cached(&path, serde_json::to_vec(validation)?.as_slice())
+        }
+        None => match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        },
+    };
+    if let Err(e) = written {
+        tracing::error!("Failed to write validation set {:?} ⚠️: {}", path, e);
+        anyhow::bail!("Failed to write validation set {:?} ⚠️: {}", path, e)
+    }
+    Ok(())
+}
+
+/// Reads the validation set of an unpacked artifact, which is only needed when the artifact is promoted, so it
+/// is not kept with the provenance.
+///
+/// # Arguments
+///
+/// * `artifact_path` - The path to the unpacked artifact.
+///
+/// # Returns
+///
+/// * `Ok(Some(ValidationSet))` - If the artifact was shipped with a validation set.
+/// * `Ok(None)` - If it was not.
+/// * `Err(anyhow::Error)` - If the validation set cannot be read or parsed.
+pub fn read_validation_set(artifact_path: &str) -> anyhow::Result<Option<ValidationSet>> {
+    let path = Path::new(artifact_path);
+    let (dir, artifact) = match (path.parent(), path.file_name()) {
+        (Some(dir), Some(artifact)) => (dir, artifact.to_string_lossy()),
+        _ => return Ok(None),
+    };
+    let validation_path = validation_set_path(dir, artifact.as_ref());
+    let bytes = match encryption::read_cached(&validation_path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => anyhow::bail!(
+            "Failed to read validation set {:?} ❌: {}",
+            validation_path,
+            e
+        ),
+    };
+    match std::str::from_utf8(&bytes) {
+        Ok(contents) => Ok(Some(ValidationSet::parse(contents)?)),
+        Err(e) => anyhow::bail!(
+            "Failed to read validation set {:?} ❌: {}",
+            validation_path,
+            e
+        ),
+    }
+}
+
+/// Reads the provenance of an unpacked artifact.
+///
+/// # Arguments
+///
+/// * `artifact_path` - The path to the unpacked artifact.
+///
+/// # Returns
+///
+/// The provenance recorded when the artifact was unpacked, or an empty provenance if none was recorded.
+pub fn read_provenance(artifact_path: &str) -> Provenance {
+    let path = Path::new(artifact_path);
+    let (dir, artifact) = match (path.parent(), path.file_name()) {
+        (Some(dir), Some(artifact)) => (dir, artifact.to_string_lossy()),
+        _ => return Provenance::default(),
+    };
+
+    let provenance_path = dir
+        .join(PROVENANCE_DIRECTORY_NAME)
+        .join(format!("{}.json", artifact));
+    match encryption::read_cached(&provenance_path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            tracing::warn!(
+                "Ignoring invalid provenance {:?} ⚠️: {}",
+                provenance_path,
+                e
+            );
+            Provenance::default()
+        }),
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            tracing::warn!(
+                "Ignoring unreadable provenance {:?} ⚠️: {}",
+                provenance_path,
+                e
+            );
+            Provenance::default()
+        }
+        Err(_) => Provenance::default(),
+    }
+}
+
+/// Computes the SHA-256 hash of the plaintext of a model artifact, so that it is the same on every host and
+/// for every reload whether the artifact cache is encrypted or not.
+///
+/// Artifacts which are directories, like tensorflow saved models, are hashed by walking the
+/// directory in a sorted order and hashing the relative path and the SHA-256 hash of every file.
+///
+/// # Errors
+///
+/// * `Err(io::Error)` - If a file cannot be read, or it is encrypted and cannot be decrypted.
+pub fn hash_artifact(path: &Path) -> io::Result<String> {
+    if !path.is_dir() {
+        return Ok(hex::encode(hash_file(path)?));
+    }
+    let mut digests = FileDigests::new();
+    hash_dir(path, path, &mut digests)?;
+    Ok(combine_digests(&digests))
+}
+
+/// Returns the hash of an unpacked artifact from the digests of its files, as computed by `hash_artifact`, or
+/// `None` if none of its files were unpacked.
+fn digest_artifact(digests: &FileDigests) -> Option<String> {
+    match (digests.len(), digests.get(Path::new(""))) {
+        (0, _) => None,
+        (1, Some(digest)) => Some(hex::encode(digest)),
+        _ => Some(combine_digests(digests)),
+    }
+}
+
+fn combine_digests(digests: &FileDigests) -> String {
+    let mut hasher = Sha256::new();
+    for (relative_path, digest) in digests {
+        hasher.update(relative_path.to_string_lossy().as_bytes());
+        hasher.update(digest);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn hash_file(path: &Path) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut encryption::open_cached(path)?, &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
+
+fn hash_dir(root: &Path, dir: &Path, digests: &mut FileDigests) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?.path();
+        if entry.is_dir() {
+            hash_dir(root, &entry, digests)?;
+        } else {
+            let relative_path = entry.strip_prefix(root).unwrap_or(&entry).to_path_buf();
+            digests.insert(relative_path, hash_file(&entry)?);
+        }
+    }
+    Ok(())
 }
 
 #[tracing::instrument]
@@ -167,3 +785,503 @@ pub fn cleanup(dir: String) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::fs::File;
+
+    fn create_tarball(path: &Path, files: &[(&str, &str)]) {
+        let encoder = GzEncoder::new(File::create(path).unwrap(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, contents.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn successfully_unpack_tarball_with_provenance_and_labels() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("jams-provenance-{}", std::process::id()));
+        let _ = remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let tarball_path = dir.join("lightgbm-my_model.tar.gz");
+        create_tarball(
+            &tarball_path,
+            &[
+                ("lightgbm-my_model.txt", "tree"),
+                (LABELS_FILE_NAME, "git_sha: 4f2a9c1\nbuild: 42\n"),
+            ],
+        );
+        let out_dir = dir.join("out");
+
+        // Act
+        let result = unpack_tarball_with_provenance(
+            tarball_path.to_str().unwrap(),
+            out_dir.to_str().unwrap(),
+            "s3://models/lightgbm-my_model.tar.gz".to_string(),
+            Some("v1".to_string()),
+        );
+        let provenance = read_provenance(out_dir.join("lightgbm-my_model.txt").to_str().unwrap());
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(!out_dir.join(LABELS_FILE_NAME).exists());
+        assert_eq!(
+            provenance.source_uri,
+            "s3://models/lightgbm-my_model.tar.gz"
+        );
+        assert_eq!(provenance.source_version, Some("v1".to_string()));
+        assert_eq!(provenance.labels.get("git_sha").unwrap(), "4f2a9c1");
+        assert_eq!(provenance.labels.get("build").unwrap(), "42");
+        assert!(provenance.calibration.is_none());
+        let _ = remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn successfully_unpack_tarball_with_calibration_map() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("jams-calibration-{}", std::process::id()));
+        let _ = remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let tarball_path = dir.join("lightgbm-my_model.tar.gz");
+        create_tarball(
+            &tarball_path,
+            &[
+                ("lightgbm-my_model.txt", "tree"),
+                (
+                    CALIBRATION_FILE_NAME,
+                    r#"{"method": "platt", "a": -1.5, "b": 0.2}"#,
+                ),
+            ],
+        );
+        let out_dir = dir.join("out");
+
+        // Act
+        let result = unpack_tarball_with_provenance(
+            tarball_path.to_str().unwrap(),
+            out_dir.to_str().unwrap(),
+            "s3://models/lightgbm-my_model.tar.gz".to_string(),
+            None,
+        );
+        let provenance = read_provenance(out_dir.join("lightgbm-my_model.txt").to_str().unwrap());
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(!out_dir.join(CALIBRATION_FILE_NAME).exists());
+        assert_eq!(provenance.calibration.unwrap().output, "predictions");
+        let _ = remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn successfully_unpack_tarball_with_postprocessing_program() {
+        // Arrange
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let tarball_path = dir.join("lightgbm-my_model.tar.gz");
+        create_tarball(
+            &tarball_path,
+            &[
+                ("lightgbm-my_model.txt", "tree"),
+                (
+                    POSTPROCESS_FILE_NAME,
+                    "out = sigmoid(pred[0]) > 0.37 ? 1 : 0\n",
+                ),
+            ],
+        );
+        let out_dir = dir.join("out");
+
+        // Act
+        let result = unpack_tarball_with_provenance(
+            tarball_path.to_str().unwrap(),
+            out_dir.to_str().unwrap(),
+            "s3://models/lightgbm-my_model.tar.gz".to_string(),
+            None,
+        );
+        let provenance = read_provenance(out_dir.join("lightgbm-my_model.txt").to_str().unwrap());
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(!out_dir.join(POSTPROCESS_FILE_NAME).exists());
+        assert_eq!(provenance.postprocess.unwrap().outputs(), vec!["out"]);
+    }
+
+    #[test]
+    fn successfully_unpack_tarball_with_embedding_index() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("jams-embedding-index-{}", std::process::id()));
+        let _ = remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let tarball_path = dir.join("pytorch-my_model.tar.gz");
+        create_tarball(
+            &tarball_path,
+            &[
+                ("pytorch-my_model.pt", "module"),
+                (
+                    EMBEDDING_INDEX_FILE_NAME,
+                    r#"{"output": "embeddings", "ids": ["a", "b"], "vectors": [[0.1, 0.9], [0.7, 0.2]]}"#,
+                ),
+            ],
+        );
+        let out_dir = dir.join("out");
+
+        // Act
+        let result = unpack_tarball_with_provenance(
+            tarball_path.to_str().unwrap(),
+            out_dir.to_str().unwrap(),
+            "s3://models/pytorch-my_model.tar.gz".to_string(),
+            None,
+        );
+        let provenance = read_provenance(out_dir.join("pytorch-my_model.pt").to_str().unwrap());
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(!out_dir.join(EMBEDDING_INDEX_FILE_NAME).exists());
+        let index = provenance.embedding_index.unwrap();
+        assert_eq!(index.output, "embeddings");
+        assert_eq!(index.dimension(), 2);
+        let _ = remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn successfully_unpack_tarball_with_precision_variants() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("jams-variants-{}", std::process::id()));
+        let _ = remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let tarball_path = dir.join("pytorch-my_model.tar.gz");
+        create_tarball(
+            &tarball_path,
+            &[
+                ("pytorch-my_model.pt", "module"),
+                ("variants/int8.pt", "quantized module"),
+                ("variants/fp16.pt", "half module"),
+            ],
+        );
+        let out_dir = dir.join("out");
+
+        // Act
+        let result = unpack_tarball_with_provenance(
+            tarball_path.to_str().unwrap(),
+            out_dir.to_str().unwrap(),
+            "s3://models/pytorch-my_model.tar.gz".to_string(),
+            None,
+        );
+        let provenance = read_provenance(out_dir.join("pytorch-my_model.pt").to_str().unwrap());
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(!out_dir.join(VARIANTS_DIRECTORY_NAME).exists());
+        assert_eq!(provenance.variants.len(), 2);
+        let int8 = provenance.variants.get(&ModelPrecision::Int8).unwrap();
+        assert_eq!(std::fs::read_to_string(int8).unwrap(), "quantized module");
+        let _ = remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fails_to_unpack_tarball_when_variant_is_not_named_after_a_precision() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("jams-bad-variant-{}", std::process::id()));
+        let _ = remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let tarball_path = dir.join("pytorch-my_model.tar.gz");
+        create_tarball(
+            &tarball_path,
+            &[
+                ("pytorch-my_model.pt", "module"),
+                ("variants/small.pt", "quantized module"),
+            ],
+        );
+
+        // Act
+        let result = unpack_tarball_with_provenance(
+            tarball_path.to_str().unwrap(),
+            dir.join("out").to_str().unwrap(),
+            "s3://models/pytorch-my_model.tar.gz".to_string(),
+            None,
+        );
+
+        // Assert
+        assert!(result.is_err());
+        let _ = remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn successfully_unpack_tarball_with_class_labels() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("jams-class-labels-{}", std::process::id()));
+        let _ = remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let tarball_path = dir.join("pytorch-my_model.tar.gz");
+        create_tarball(
+            &tarball_path,
+            &[
+                ("pytorch-my_model.pt", "model"),
+                (CLASS_LABELS_FILE_NAME, "cat\n\ndog \nbird\n"),
+            ],
+        );
+        let out_dir = dir.join("out");
+
+        // Act
+        let result = unpack_tarball_with_provenance(
+            tarball_path.to_str().unwrap(),
+            out_dir.to_str().unwrap(),
+            "s3://models/pytorch-my_model.tar.gz".to_string(),
+            None,
+        );
+        let provenance = read_provenance(out_dir.join("pytorch-my_model.pt").to_str().unwrap());
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(!out_dir.join(CLASS_LABELS_FILE_NAME).exists());
+        assert_eq!(provenance.class_labels, vec!["cat", "dog", "bird"]);
+        let _ = remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn successfully_unpack_tarball_with_predict_options() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("jams-predict-options-{}", std::process::id()));
+        let _ = remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let tarball_path = dir.join("lightgbm-my_model.tar.gz");
+        create_tarball(
+            &tarball_path,
+            &[
+                ("lightgbm-my_model.txt", "tree"),
+                (
+                    PREDICT_OPTIONS_FILE_NAME,
+                    r#"{"mode": "normal", "num_iteration": 20}"#,
+                ),
+            ],
+        );
+        let out_dir = dir.join("out");
+
+        // Act
+        let result = unpack_tarball_with_provenance(
+            tarball_path.to_str().unwrap(),
+            out_dir.to_str().unwrap(),
+            "s3://models/lightgbm-my_model.tar.gz".to_string(),
+            None,
+        );
+        let provenance = read_provenance(out_dir.join("lightgbm-my_model.txt").to_str().unwrap());
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(!out_dir.join(PREDICT_OPTIONS_FILE_NAME).exists());
+        assert_eq!(provenance.predict_options.num_iteration, Some(20));
+        let _ = remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn successfully_unpack_tarball_with_requirements() {
+        // Arrange
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let tarball_path = dir.join("tensorflow-my_model.tar.gz");
+        create_tarball(
+            &tarball_path,
+            &[
+                ("tensorflow-my_model/saved_model.pb", "graph"),
+                (
+                    REQUIREMENTS_FILE_NAME,
+                    r#"{"gpu": true, "min_memory_mb": 65536}"#,
+                ),
+            ],
+        );
+        let out_dir = dir.join("out");
+
+        // Act
+        let result = unpack_tarball_with_provenance(
+            tarball_path.to_str().unwrap(),
+            out_dir.to_str().unwrap(),
+            "s3://models/tensorflow-my_model.tar.gz".to_string(),
+            None,
+        );
+        let provenance = read_provenance(out_dir.join("tensorflow-my_model").to_str().unwrap());
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(!out_dir.join(REQUIREMENTS_FILE_NAME).exists());
+        assert!(provenance.requirements.gpu);
+        assert_eq!(provenance.requirements.min_memory_mb, Some(65536));
+    }
+
+    #[test]
+    fn successfully_unpack_tarball_with_validation_set() {
+        // Arrange
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let tarball_path = dir.join("lightgbm-my_model.tar.gz");
+        create_tarball(
+            &tarball_path,
+            &[
+                ("lightgbm-my_model.txt", "tree"),
+                (
+                    VALIDATION_FILE_NAME,
+                    r#"{"input": {"age": [22.0, 38.0]}, "labels": [0, 1], "criteria": {"min_auc": 0.8}}"#,
+                ),
+            ],
+        );
+        let out_dir = dir.join("out");
+
+        // Act
+        let result = unpack_tarball_with_provenance(
+            tarball_path.to_str().unwrap(),
+            out_dir.to_str().unwrap(),
+            "s3://models/lightgbm-my_model.tar.gz".to_string(),
+            None,
+        );
+        let artifact_path = out_dir.join("lightgbm-my_model.txt");
+        let provenance = read_provenance(artifact_path.to_str().unwrap());
+        let validation = read_validation_set(artifact_path.to_str().unwrap()).unwrap();
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(!out_dir.join(VALIDATION_FILE_NAME).exists());
+        assert!(provenance.validation.is_none());
+        let validation = validation.unwrap();
+        assert_eq!(validation.labels, vec![0.0, 1.0]);
+        assert_eq!(validation.criteria.min_auc, Some(0.8));
+    }
+
+    #[test]
+    fn successfully_unpack_tarball_with_bundle() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("jams-bundle-{}", std::process::id()));
+        let _ = remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let tarball_path = dir.join("lightgbm-my_model.tar.gz");
+        create_tarball(
+            &tarball_path,
+            &[
+                ("lightgbm-my_model.txt", "tree"),
+                ("bundle/vocab/sex.txt", "male\nfemale\n"),
+                (
+                    "bundle/scaler.json",
+                    r#"{"age": {"mean": 30.0, "scale": 10.0}}"#,
+                ),
+                ("bundle/warmup.json", r#"{"age": [40.0], "sex": ["male"]}"#),
+                ("bundle/notes.md", "ignored"),
+            ],
+        );
+        let out_dir = dir.join("out");
+
+        // Act
+        let result = unpack_tarball_with_provenance(
+            tarball_path.to_str().unwrap(),
+            out_dir.to_str().unwrap(),
+            "s3://models/lightgbm-my_model.tar.gz".to_string(),
+            None,
+        );
+        let provenance = read_provenance(out_dir.join("lightgbm-my_model.txt").to_str().unwrap());
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(!out_dir.join(BUNDLE_DIRECTORY_NAME).exists());
+        assert_eq!(
+            provenance.bundle.vocabularies["sex"].tokens(),
+            ["male", "female"]
+        );
+        assert_eq!(provenance.bundle.scalers["age"].mean, 30.0);
+        assert!(provenance.bundle.warmup_input.is_some());
+        let _ = remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn successfully_return_empty_provenance_when_none_was_recorded() {
+        // Act
+        let provenance = read_provenance("/tmp/does_not_exist/lightgbm-my_model.txt");
+
+        // Assert
+        assert_eq!(provenance, Provenance::default());
+    }
+
+    #[test]
+    fn fails_to_parse_labels_when_value_is_not_a_scalar() {
+        // Act
+        let labels = parse_labels("git_sha:\n  - 4f2a9c1\n");
+
+        // Assert
+        assert!(labels.is_err());
+    }
+
+    #[test]
+    fn successfully_record_hash_of_unpacked_artifacts() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let tarball_path = dir.path().join("tensorflow-my_model.tar.gz");
+        create_tarball(
+            &tarball_path,
+            &[
+                ("tensorflow-my_model/saved_model.pb", "graph"),
+                ("tensorflow-my_model/variables/variables.index", "index"),
+                ("lightgbm-my_model.txt", "trees"),
+            ],
+        );
+        let out_dir = dir.path().join("out");
+        let unpack = || {
+            unpack_tarball_with_provenance(
+                tarball_path.to_str().unwrap(),
+                out_dir.to_str().unwrap(),
+                "s3://models/tensorflow-my_model.tar.gz".to_string(),
+                None,
+            )
+        };
+
+        // Act
+        unpack().unwrap();
+        std::fs::write(
+            out_dir.join("tensorflow-my_model").join("stale.pb"),
+            "stale",
+        )
+        .unwrap();
+        unpack().unwrap();
+        let saved_model = out_dir.join("tensorflow-my_model");
+        let trees = out_dir.join("lightgbm-my_model.txt");
+
+        // Assert
+        assert!(!saved_model.join("stale.pb").exists());
+        assert_eq!(
+            read_provenance(saved_model.to_str().unwrap()).artifact_sha256,
+            Some(hash_artifact(&saved_model).unwrap())
+        );
+        assert_eq!(
+            read_provenance(trees.to_str().unwrap()).artifact_sha256,
+            Some(format!("{:x}", Sha256::digest(b"trees")))
+        );
+        assert_eq!(
+            hash_artifact(&trees).unwrap(),
+            format!("{:x}", Sha256::digest(b"trees"))
+        );
+    }
+
+    #[test]
+    fn successfully_hash_artifact_directory() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("jams-hash-{}", std::process::id()));
+        let _ = remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("variables")).unwrap();
+        std::fs::write(dir.join("saved_model.pb"), "graph").unwrap();
+        std::fs::write(dir.join("variables").join("variables.index"), "index").unwrap();
+
+        // Act
+        let first = hash_artifact(&dir).unwrap();
+        std::fs::write(dir.join("saved_model.pb"), "changed graph").unwrap();
+        let second = hash_artifact(&dir).unwrap();
+
+        // Assert
+        assert_eq!(first.len(), 64);
+        assert_ne!(first, second);
+        let _ = remove_dir_all(&dir);
+    }
+}
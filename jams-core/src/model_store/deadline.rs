@@ -0,0 +1,160 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The default deadline (in seconds) for listing the artifacts of a remote model store.
+pub const DEFAULT_LIST_TIMEOUT_SECONDS: u64 = 60;
+
+/// The default deadline (in seconds) for downloading a single artifact from a remote model store.
+pub const DEFAULT_DOWNLOAD_TIMEOUT_SECONDS: u64 = 600;
+
+/// A call made to a remote model store which runs with a deadline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoreOperation {
+    /// Listing the artifacts and their versions.
+    List,
+    /// Downloading and unpacking an artifact.
+    Download,
+}
+
+impl StoreOperation {
+    /// Every operation, e.g. to export the number of timeouts of each one.
+    pub const ALL: [StoreOperation; 2] = [StoreOperation::List, StoreOperation::Download];
+
+    /// Returns the name of the operation used in logs and metrics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StoreOperation::List => "list",
+            StoreOperation::Download => "download",
+        }
+    }
+
+    fn counter(&self) -> &'static AtomicU64 {
+        static LIST_TIMEOUTS: AtomicU64 = AtomicU64::new(0);
+        static DOWNLOAD_TIMEOUTS: AtomicU64 = AtomicU64::new(0);
+        match self {
+            StoreOperation::List => &LIST_TIMEOUTS,
+            StoreOperation::Download => &DOWNLOAD_TIMEOUTS,
+        }
+    }
+
+    /// Returns the number of times the operation timed out since startup.
+    pub fn timeouts_total(&self) -> u64 {
+        self.counter().load(Ordering::Relaxed)
+    }
+}
+
+/// Deadlines of the calls made to a remote model store while adding, updating and polling models,
+/// so that a hung network call does not block the poller forever. A zero deadline disables it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StoreTimeouts {
+    /// The deadline for listing the artifacts and their versions.
+    pub list: Duration,
+    /// The deadline for downloading and unpacking a single artifact.
+    pub download: Duration,
+}
+
+impl Default for StoreTimeouts {
+    fn default() -> Self {
+        StoreTimeouts {
+            list: Duration::from_secs(DEFAULT_LIST_TIMEOUT_SECONDS),
+            download: Duration::from_secs(DEFAULT_DOWNLOAD_TIMEOUT_SECONDS),
+        }
+    }
+}
+
+impl StoreTimeouts {
+    /// Returns the deadline of the given operation.
+    pub fn get(&self, operation: StoreOperation) -> Duration {
+        match operation {
+            StoreOperation::List => self.list,
+            StoreOperation::Download => self.download,
+        }
+    }
+
+    /// Runs a call to the model store, failing it if it does not complete before the deadline of the operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - The kind of call, which selects the deadline and is counted when it times out.
+    /// * `target` - What the call is made for, e.g. the name of the artifact, used in the error.
+    /// * `future` - The call to the model store.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the call, or an error if the deadline is exceeded. The call is dropped
+    /// when it times out.
+    pub async fn run<T, F>(
+        &self,
+        operation: StoreOperation,
+        target: &str,
+        future: F,
+    ) -> anyhow::Result<T>
+    where
+        F: Future<Output = anyhow::Result<T>>,
+    {
+        let timeout = self.get(operation);
+        if timeout.is_zero() {
+            return future.await;
+        }
+        match tokio::time::timeout(timeout, future).await {
+            Ok(result) => result,
+            Err(_) => {
+                operation.counter().fetch_add(1, Ordering::Relaxed);
+                tracing::error!(
+                    "Timed out after {:?} on {} of {} ⏱️",
+                    timeout,
+                    operation.as_str(),
+                    target
+                );
+                anyhow::bail!(
+                    "Timed out after {:?} on {} of {} ⏱️",
+                    timeout,
+                    operation.as_str(),
+                    target
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn successfully_run_store_operation_before_deadline() {
+        // Arrange
+        let timeouts = StoreTimeouts::default();
+
+        // Act
+        let result = timeouts
+            .run(StoreOperation::List, "my_bucket", async { Ok(1) })
+            .await;
+
+        // Assert
+        assert_eq!(result.unwrap(), 1)
+    }
+
+    #[tokio::test]
+    async fn fails_to_run_store_operation_when_deadline_is_exceeded() {
+        // Arrange
+        let timeouts = StoreTimeouts {
+            list: Duration::from_secs(0),
+            download: Duration::from_millis(10),
+        };
+        let before = StoreOperation::Download.timeouts_total();
+
+        // Act
+        let result = timeouts
+            .run(StoreOperation::Download, "my_model.tar.gz", async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            })
+            .await;
+
+        // Assert
+        assert!(result.is_err());
+        assert_eq!(StoreOperation::Download.timeouts_total(), before + 1);
+    }
+}
@@ -0,0 +1,762 @@
+use crate::model_store::encryption::{self, OpenedArtifact};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Suffix of the block manifest published next to a tarball, e.g. `tensorflow-my_model.tar.gz.zsync.json`.
+pub const DELTA_MANIFEST_SUFFIX: &str = ".zsync.json";
+
+/// Version of the block manifest format.
+pub const DELTA_MANIFEST_VERSION: u32 = 1;
+
+/// Default size of the blocks of a manifest, i.e. 1 MiB.
+pub const DEFAULT_BLOCK_SIZE: usize = 1 << 20;
+
+/// Smallest size of the blocks of a manifest, as smaller blocks make the manifest bigger than the bytes they save.
+pub const MIN_BLOCK_SIZE: usize = 4 << 10;
+
+/// Largest size of the blocks of a manifest, i.e. 64 MiB, as every block is read into memory while a delta is
+/// located and assembled.
+pub const MAX_BLOCK_SIZE: usize = 64 << 20;
+
+/// Suffix of the tarballs kept in the cache directory.
+const TARBALL_SUFFIX: &str = ".tar.gz";
+
+/// Number of bytes read at a time from the previous version of a tarball while its blocks are located.
+const READ_CHUNK_SIZE: usize = 64 << 10;
+
+/// Directory inside the temporary directory in which the tarballs are kept by default.
+const DEFAULT_CACHE_DIR_NAME: &str = "jams-delta";
+
+/// The delta cache installed for the process, see `install`.
+static CACHE: OnceLock<DeltaCache> = OnceLock::new();
+
+/// Configuration of the delta updates of large models, which only download the blocks of a tarball which changed
+/// since the version the server already has.
+///
+/// A delta update needs the block manifest of the new tarball, published next to it by `jams store manifest`,
+/// and the previous tarball, which the server keeps in the cache directory after every download. Updates fall back
+/// to a full download whenever either is missing or the assembled tarball does not match the manifest.
+///
+/// # Example
+/// ```toml
+/// [config.delta_updates]
+/// cache_dir = "/var/cache/jams"
+/// ```
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeltaConfig {
+    /// An optional directory in which the last downloaded tarball of every model is kept. Defaults to a
+    /// `jams-delta` directory in the temporary directory.
+    pub cache_dir: Option<String>,
+}
+
+/// Keeps the last downloaded tarball of every model, which is the base of its next delta update.
+pub struct DeltaCache {
+    dir: PathBuf,
+}
+
+/// A tarball of the delta cache opened for reading. A sealed tarball is decrypted into the scratch directory of the
+/// artifact cache, which is removed when it is dropped.
+pub struct CachedTarball {
+    file: File,
+    _opened: Option<OpenedArtifact>,
+}
+
+impl CachedTarball {
+    /// Returns the plaintext of the tarball.
+    pub fn file(&mut self) -> &mut File {
+        &mut self.file
+    }
+}
+
+impl DeltaCache {
+    /// Creates the cache in the configured directory, creating the directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the directory cannot be created.
+    pub fn from_config(config: &DeltaConfig) -> anyhow::Result<Self> {
+        let dir = match &config.cache_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::temp_dir().join(DEFAULT_CACHE_DIR_NAME),
+        };
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            anyhow::bail!("Failed to create delta cache directory {:?} ❌: {}", dir, e)
+        }
+        Ok(DeltaCache { dir })
+    }
+
+    /// Returns the path at which the tarball of an object key is kept.
+    fn path(&self, object_key: &str) -> PathBuf {
+        // object keys can contain slashes, which are flattened so that every tarball sits in the cache directory
+        self.dir.join(object_key.replace('/', "_"))
+    }
+
+    /// Returns true if a tarball of the object key was downloaded before.
+    pub fn contains(&self, object_key: &str) -> bool {
+        self.path(object_key).is_file()
+    }
+
+    /// Opens the last downloaded tarball of an object key, or returns `None` if it was never downloaded. This
+    /// blocks while a sealed tarball is decrypted, so it must not run on the async runtime.
+    pub fn open(&self, object_key: &str) -> Option<CachedTarball> {
+        let path = self.path(object_key);
+        let opened = match encryption::installed() {
+            Some(cipher) if path.is_file() => match cipher.open_artifact(&path) {
+                Ok(opened) => Some(opened),
+                Err(e) => {
+                    tracing::warn!(
+                        "Ignoring unreadable cached tarball of {} ⚠️: {}",
+                        object_key,
+                        e
+                    );
+                    return None;
+                }
+            },
+            _ => None,
+        };
+        let plaintext = opened.as_ref().map(|opened| opened.path()).unwrap_or(&path);
+        match File::open(plaintext) {
+            Ok(file) => Some(CachedTarball {
+                file,
+                _opened: opened,
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => {
+                tracing::warn!(
+                    "Ignoring unreadable cached tarball of {} ⚠️: {}",
+                    object_key,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Keeps the downloaded tarball of an object key as the base of its next delta update. The tarball is
+    /// encrypted if the artifact cache is encrypted.
+    pub fn write(&self, object_key: &str, data: &[u8]) {
+        self.write_from(object_key, &mut &data[..])
+    }
+
+    /// Keeps the tarball read from `reader` as the base of the next delta update of an object key, without holding
+    /// it in memory. The tarball is encrypted if the artifact cache is encrypted.
+    pub fn write_from(&self, object_key: &str, reader: &mut impl Read) {
+        if let Err(e) = encryption::write_cached_from(&self.path(object_key), reader) {
+            tracing::warn!(
+                "Failed to cache tarball of {}, its next update downloads it in full ⚠️: {}",
+                object_key,
+                e
+            );
+        }
+    }
+
+    /// Removes the cached tarballs of the object keys which are not given, i.e. of the models which were removed
+    /// from the model store.
+    pub fn retain<'a>(&self, object_keys: impl IntoIterator<Item = &'a str>) {
+        let kept: HashSet<PathBuf> = object_keys.into_iter().map(|key| self.path(key)).collect();
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to read delta cache directory {:?} ⚠️: {}",
+                    self.dir,
+                    e
+                );
+                return;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // only tarballs are removed, in case the cache directory is shared with other files
+            if kept.contains(&path) || !path.to_string_lossy().ends_with(TARBALL_SUFFIX) {
+                continue;
+            }
+            match std::fs::remove_file(&path) {
+                Ok(_) => tracing::info!("Evicted cached tarball {:?} of a removed model 🧹", path),
+                Err(e) => tracing::warn!("Failed to evict cached tarball {:?} ⚠️: {}", path, e),
+            }
+        }
+    }
+}
+
+/// Installs the delta cache for the process, so that the model stores download the updates of the models with a
+/// block manifest as deltas. It must be installed before the model stores are created.
+///
+/// # Errors
+///
+/// * `Err(anyhow::Error)` - If a delta cache was already installed.
+pub fn install(cache: DeltaCache) -> anyhow::Result<()> {
+    match CACHE.set(cache) {
+        Ok(_) => {
+            tracing::info!("Downloading the updates of large models as deltas 🧩");
+            Ok(())
+        }
+        Err(_) => anyhow::bail!("Delta updates are already installed ❌"),
+    }
+}
+
+/// Returns the delta cache installed for the process, if any.
+pub fn installed() -> Option<&'static DeltaCache> {
+    CACHE.get()
+}
+
+/// Returns true if the object key is the block manifest of a tarball rather than a model.
+pub fn is_manifest(object_key: &str) -> bool {
+    object_key.ends_with(DELTA_MANIFEST_SUFFIX)
+}
+
+/// Returns the object key of the block manifest of a tarball.
+pub fn manifest_key(object_key: &str) -> String {
+    format!("{}{}", object_key, DELTA_MANIFEST_SUFFIX)
+}
+
+/// The checksums of one block of a tarball.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BlockChecksum {
+    /// The rolling checksum of the block, which finds candidate blocks at any offset of the previous tarball.
+    pub weak: u32,
+    /// The hex encoded SHA-256 of the block, which confirms a candidate block.
+    pub strong: String,
+}
+
+/// The block manifest of a tarball, in the spirit of zsync.
+///
+/// The tarball is split into blocks of `block_size` bytes, the last one being shorter. A server which has a
+/// previous version of the tarball finds the blocks it already has at any offset of its copy, using the rolling
+/// checksum of each block, and only downloads the other blocks with ranged requests. Tarballs compressed with
+/// `gzip --rsyncable` keep most of their blocks when a few files of the model change.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BlockManifest {
+    /// The version of the manifest format.
+    pub version: u32,
+    /// The size of the tarball in bytes.
+    pub size: u64,
+    /// The size of the blocks in bytes.
+    pub block_size: usize,
+    /// The hex encoded SHA-256 of the whole tarball.
+    pub sha256: String,
+    /// The checksums of every block, in order.
+    pub blocks: Vec<BlockChecksum>,
+}
+
+impl BlockManifest {
+    /// Builds the block manifest of a tarball.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the block size is not between the minimum and the maximum block size.
+    pub fn build(data: &[u8], block_size: usize) -> anyhow::Result<Self> {
+        check_block_size(block_size)?;
+        let blocks = data
+            .chunks(block_size)
+            .map(|block| BlockChecksum {
+                weak: RollingChecksum::new(block).value(),
+                strong: sha256(block),
+            })
+            .collect();
+        Ok(BlockManifest {
+            version: DELTA_MANIFEST_VERSION,
+            size: data.len() as u64,
+            block_size,
+            sha256: sha256(data),
+            blocks,
+        })
+    }
+
+    /// Parses a block manifest.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the manifest is invalid, has an unsupported version, its block size is out of
+    ///   bounds or its blocks do not cover the tarball.
+    pub fn parse(bytes: &[u8]) -> anyhow::Result<Self> {
+        let manifest: BlockManifest = match serde_json::from_slice(bytes) {
+            Ok(manifest) => manifest,
+            Err(e) => anyhow::bail!("Failed to parse block manifest ❌: {}", e),
+        };
+        if manifest.version != DELTA_MANIFEST_VERSION {
+            anyhow::bail!(
+                "Unsupported block manifest version {}, expected {} ❌",
+                manifest.version,
+                DELTA_MANIFEST_VERSION
+            )
+        }
+        check_block_size(manifest.block_size)?;
+        let expected_blocks = manifest.size.div_ceil(manifest.block_size as u64);
+        if manifest.blocks.len() as u64 != expected_blocks {
+            anyhow::bail!(
+                "Block manifest of {} bytes must have {} blocks, got {} ❌",
+                manifest.size,
+                expected_blocks,
+                manifest.blocks.len()
+            )
+        }
+        Ok(manifest)
+    }
+
+    /// Checks that the manifest describes a tarball of the size reported by the model store, so that a stale or
+    /// forged manifest cannot make the server read or allocate beyond the tarball.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the size of the manifest differs from the content length of the tarball.
+    pub fn check_content_length(&self, content_length: u64) -> anyhow::Result<()> {
+        if self.size != content_length {
+            anyhow::bail!(
+                "Block manifest describes a tarball of {} bytes, but the tarball has {} bytes ❌",
+                self.size,
+                content_length
+            )
+        }
+        Ok(())
+    }
+
+    /// Returns the byte range of a block in the tarball.
+    fn block_range(&self, index: usize) -> Range<u64> {
+        let start = index as u64 * self.block_size as u64;
+        start..(start + self.block_size as u64).min(self.size)
+    }
+
+    /// Finds the blocks of the tarball in a previous version of it, reading the previous version in chunks so
+    /// that it is never held in memory as a whole.
+    ///
+    /// # Returns
+    ///
+    /// The offset in `previous` of every block, or `None` for the blocks which must be downloaded.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(io::Error)` - If the previous version cannot be read.
+    pub fn locate<R: Read + Seek>(&self, previous: &mut R) -> io::Result<Vec<Option<u64>>> {
+        let mut offsets = vec![None; self.blocks.len()];
+        let previous_len = previous.seek(SeekFrom::End(0))?;
+        previous.seek(SeekFrom::Start(0))?;
+        if previous_len == 0 || self.blocks.is_empty() {
+            return Ok(offsets);
+        }
+
+        // the blocks of full size are found with the rolling checksum at any offset of the previous version
+        let block_size = self.block_size;
+        let mut candidates: HashMap<u32, Vec<usize>> = HashMap::new();
+        for (index, block) in self.blocks.iter().enumerate() {
+            let range = self.block_range(index);
+            if range.end - range.start == block_size as u64 {
+                candidates.entry(block.weak).or_default().push(index);
+            }
+        }
+        // the window is `buffer[start..start + block_size]`, at `offset` in the previous version
+        let mut buffer: Vec<u8> = Vec::with_capacity(2 * block_size + READ_CHUNK_SIZE);
+        let mut start = 0;
+        let mut offset: u64 = 0;
+        if fill(previous, &mut buffer, block_size)? {
+            let mut checksum = RollingChecksum::new(&buffer[..block_size]);
+            loop {
+                let mut matched = false;
+                if let Some(indices) = candidates.get(&checksum.value()) {
+                    let strong = sha256(&buffer[start..start + block_size]);
+                    for index in indices {
+                        if offsets[*index].is_none() && self.blocks[*index].strong == strong {
+                            offsets[*index] = Some(offset);
+                            matched = true;
+                        }
+                    }
+                }
+                // drop the bytes before the window, so that the buffer stays within a few blocks
+                if start >= block_size {
+                    buffer.drain(..start);
+                    start = 0;
+                }
+                // a matched window is skipped as a whole, as its bytes rarely start another block
+                if matched {
+                    if !fill(previous, &mut buffer, start + 2 * block_size)? {
+                        break;
+                    }
+                    start += block_size;
+                    offset += block_size as u64;
+                    checksum = RollingChecksum::new(&buffer[start..start + block_size]);
+                } else {
+                    if !fill(previous, &mut buffer, start + block_size + 1)? {
+                        break;
+                    }
+                    checksum.roll(buffer[start], buffer[start + block_size]);
+                    start += 1;
+                    offset += 1;
+                }
+            }
+        }
+
+        // the shorter last block can only be found at the end of the previous version
+        let last = self.blocks.len() - 1;
+        let last_range = self.block_range(last);
+        let last_len = last_range.end - last_range.start;
+        if last_len < block_size as u64 && previous_len >= last_len {
+            let offset = previous_len - last_len;
+            let mut block = vec![0; last_len as usize];
+            previous.seek(SeekFrom::Start(offset))?;
+            previous.read_exact(&mut block)?;
+            if sha256(&block) == self.blocks[last].strong {
+                offsets[last] = Some(offset);
+            }
+        }
+        Ok(offsets)
+    }
+
+    /// Returns the byte ranges of the tarball to download, merging the ranges of adjacent missing blocks.
+    pub fn missing_ranges(&self, offsets: &[Option<u64>]) -> Vec<Range<u64>> {
+        let mut ranges: Vec<Range<u64>> = Vec::new();
+        for (index, offset) in offsets.iter().enumerate() {
+            if offset.is_some() {
+                continue;
+            }
+            let range = self.block_range(index);
+            match ranges.last_mut() {
+                Some(last) if last.end == range.start => last.end = range.end,
+                _ => ranges.push(range),
+            }
+        }
+        ranges
+    }
+
+    /// Assembles the tarball from the blocks of the previous version and the downloaded ranges, one block at a
+    /// time.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous` - The previous version of the tarball.
+    /// * `offsets` - The offset of every block in the previous version, see `locate`.
+    /// * `downloaded` - The bytes of the ranges returned by `missing_ranges`, one after the other.
+    /// * `out` - The writer of the assembled tarball.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the downloaded bytes do not match the missing ranges, or the assembled tarball
+    ///   does not match the SHA-256 of the manifest.
+    pub fn assemble<R: Read + Seek>(
+        &self,
+        previous: &mut R,
+        offsets: &[Option<u64>],
+        downloaded: &mut impl Read,
+        out: &mut impl Write,
+    ) -> anyhow::Result<()> {
+        let mut hasher = Sha256::new();
+        let mut written: u64 = 0;
+        let mut block = vec![0; self.block_size];
+        for (index, offset) in offsets.iter().enumerate() {
+            let range = self.block_range(index);
+            let block = &mut block[..(range.end - range.start) as usize];
+            match offset {
+                Some(offset) => {
+                    previous.seek(SeekFrom::Start(*offset))?;
+                    previous.read_exact(block)?;
+                }
+                None => {
+                    if let Err(e) = downloaded.read_exact(block) {
+                        anyhow::bail!("Missing the downloaded bytes of block {} ❌: {}", index, e)
+                    }
+                }
+            }
+            hasher.update(&*block);
+            out.write_all(block)?;
+            written += block.len() as u64;
+        }
+        if downloaded.read(&mut [0; 1])? != 0 {
+            anyhow::bail!("Downloaded more bytes than the missing blocks of the tarball ❌")
+        }
+        if written != self.size || format!("{:x}", hasher.finalize()) != self.sha256 {
+            anyhow::bail!(
+                "The assembled tarball does not match the SHA-256 of its block manifest ❌"
+            )
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a block size is between the minimum and the maximum block size.
+fn check_block_size(block_size: usize) -> anyhow::Result<()> {
+    if !(MIN_BLOCK_SIZE..=MAX_BLOCK_SIZE).contains(&block_size) {
+        anyhow::bail!(
+            "Block size must be between {} and {} bytes, got {} ❌",
+            MIN_BLOCK_SIZE,
+            MAX_BLOCK_SIZE,
+            block_size
+        )
+    }
+    Ok(())
+}
+
+/// Reads from `reader` until `buffer` holds at least `len` bytes.
+///
+/// # Returns
+///
+/// True if the buffer holds `len` bytes, false if the reader ended first.
+fn fill(reader: &mut impl Read, buffer: &mut Vec<u8>, len: usize) -> io::Result<bool> {
+    if buffer.len() < len {
+        let missing = (len - buffer.len()).max(READ_CHUNK_SIZE);
+        reader.by_ref().take(missing as u64).read_to_end(buffer)?;
+    }
+    Ok(buffer.len() >= len)
+}
+
+/// The rsync rolling checksum of a window of bytes, which is updated in constant time when the window slides by
+/// one byte.
+struct RollingChecksum {
+    a: u32,
+    b: u32,
+    len: u32,
+}
+
+impl RollingChecksum {
+    fn new(window: &[u8]) -> Self {
+        let len = window.len() as u32;
+        let mut a: u32 = 0;
+        let mut b: u32 = 0;
+        for (i, byte) in window.iter().enumerate() {
+            a = a.wrapping_add(*byte as u32);
+            b = b.wrapping_add((len - i as u32).wrapping_mul(*byte as u32));
+        }
+        RollingChecksum { a, b, len }
+    }
+
+    /// Slides the window by one byte, removing `out` at its start and adding `into` at its end.
+    fn roll(&mut self, out: u8, into: u8) {
+        self.a = self.a.wrapping_sub(out as u32).wrapping_add(into as u32);
+        self.b = self
+            .b
+            .wrapping_sub(self.len.wrapping_mul(out as u32))
+            .wrapping_add(self.a);
+    }
+
+    fn value(&self) -> u32 {
+        (self.a & 0xffff) | (self.b << 16)
+    }
+}
+
+fn sha256(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Writes the block manifest of a tarball next to it, i.e. to `<tarball>.zsync.json`.
+///
+/// # Errors
+///
+/// * `Err(anyhow::Error)` - If the tarball cannot be read or the manifest cannot be written.
+pub fn write_manifest(tarball_path: &Path, block_size: usize) -> anyhow::Result<PathBuf> {
+    let data = std::fs::read(tarball_path)?;
+    let manifest = BlockManifest::build(&data, block_size)?;
+    let mut manifest_path = tarball_path.as_os_str().to_owned();
+    manifest_path.push(DELTA_MANIFEST_SUFFIX);
+    let manifest_path = PathBuf::from(manifest_path);
+    std::fs::write(&manifest_path, serde_json::to_vec(&manifest)?)?;
+    Ok(manifest_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+        // a small xorshift generator, so that the blocks do not repeat
+        let mut state = seed.max(1);
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    fn download(data: &[u8], ranges: &[Range<u64>]) -> Vec<u8> {
+        ranges
+            .iter()
+            .flat_map(|range| data[range.start as usize..range.end as usize].to_vec())
+            .collect()
+    }
+
+    fn delta(
+        manifest: &BlockManifest,
+        previous: &[u8],
+        current: &[u8],
+    ) -> (Vec<Range<u64>>, anyhow::Result<Vec<u8>>) {
+        let mut previous = io::Cursor::new(previous);
+        let offsets = manifest.locate(&mut previous).unwrap();
+        let ranges = manifest.missing_ranges(&offsets);
+        let mut assembled = Vec::new();
+        let result = manifest.assemble(
+            &mut previous,
+            &offsets,
+            &mut download(current, &ranges).as_slice(),
+            &mut assembled,
+        );
+        (ranges, result.map(|_| assembled))
+    }
+
+    #[test]
+    fn successfully_roll_checksum() {
+        let data = random_bytes(100, 7);
+        let mut checksum = RollingChecksum::new(&data[0..32]);
+
+        // slide the window to the end of the data
+        for window in data.windows(33) {
+            checksum.roll(window[0], window[32]);
+        }
+
+        // assert
+        assert_eq!(
+            checksum.value(),
+            RollingChecksum::new(&data[68..100]).value()
+        );
+    }
+
+    #[test]
+    fn successfully_download_only_the_changed_blocks() {
+        let block_size = MIN_BLOCK_SIZE;
+        let previous = random_bytes(block_size * 8 + 100, 1);
+        // insert bytes in the third block, which shifts every following block
+        let at = block_size * 2 + 10;
+        let current = [&previous[..at], &random_bytes(37, 2), &previous[at..]].concat();
+        let manifest = BlockManifest::build(&current, block_size).unwrap();
+
+        let (ranges, assembled) = delta(&manifest, &previous, &current);
+        let assembled = assembled.unwrap();
+
+        // assert only the shifted block and the last block are downloaded
+        let downloaded: u64 = ranges.iter().map(|range| range.end - range.start).sum();
+        assert!(downloaded <= (block_size * 2) as u64, "{}", downloaded);
+        assert_eq!(assembled, current);
+    }
+
+    #[test]
+    fn successfully_download_every_block_without_a_previous_version() {
+        let current = random_bytes(MIN_BLOCK_SIZE * 3, 3);
+        let manifest = BlockManifest::build(&current, MIN_BLOCK_SIZE).unwrap();
+
+        let (ranges, assembled) = delta(&manifest, &[], &current);
+        let assembled = assembled.unwrap();
+
+        // assert the adjacent blocks are merged into a single range
+        assert_eq!(ranges, vec![0..current.len() as u64]);
+        assert_eq!(assembled, current);
+    }
+
+    #[test]
+    fn fails_to_assemble_when_a_downloaded_range_is_corrupted() {
+        let previous = random_bytes(MIN_BLOCK_SIZE * 4, 4);
+        let current = random_bytes(MIN_BLOCK_SIZE * 4, 5);
+        let manifest = BlockManifest::build(&current, MIN_BLOCK_SIZE).unwrap();
+        let mut previous = io::Cursor::new(previous);
+        let offsets = manifest.locate(&mut previous).unwrap();
+        let ranges = manifest.missing_ranges(&offsets);
+        let mut downloaded = download(&current, &ranges);
+        downloaded[0] ^= 0xff;
+
+        let assembled = manifest.assemble(
+            &mut previous,
+            &offsets,
+            &mut downloaded.as_slice(),
+            &mut Vec::new(),
+        );
+        let truncated = manifest.assemble(
+            &mut previous,
+            &offsets,
+            &mut &download(&current, &ranges)[1..],
+            &mut Vec::new(),
+        );
+
+        // assert
+        assert!(assembled.is_err());
+        assert!(truncated.is_err());
+    }
+
+    #[test]
+    fn successfully_locate_blocks_beyond_the_read_buffer() {
+        // a previous version much larger than the read chunks, with the blocks of the current version at its end
+        let block_size = MIN_BLOCK_SIZE;
+        let previous = random_bytes(READ_CHUNK_SIZE * 3 + 123, 8);
+        let current = previous[previous.len() - block_size * 4 - 55..].to_vec();
+        let manifest = BlockManifest::build(&current, block_size).unwrap();
+
+        let (ranges, assembled) = delta(&manifest, &previous, &current);
+
+        // assert every block, including the shorter last one, is found in the previous version
+        assert!(ranges.is_empty(), "{:?}", ranges);
+        assert_eq!(assembled.unwrap(), current);
+    }
+
+    #[test]
+    fn fails_to_use_manifest_when_it_does_not_match_the_tarball() {
+        let manifest =
+            BlockManifest::build(&random_bytes(MIN_BLOCK_SIZE * 2, 9), MIN_BLOCK_SIZE).unwrap();
+        let mut oversized = manifest.clone();
+        oversized.block_size = MAX_BLOCK_SIZE + 1;
+
+        // assert
+        assert!(manifest
+            .check_content_length(MIN_BLOCK_SIZE as u64 * 2)
+            .is_ok());
+        assert!(manifest.check_content_length(1 << 40).is_err());
+        assert!(BlockManifest::parse(&serde_json::to_vec(&oversized).unwrap()).is_err());
+    }
+
+    #[test]
+    fn successfully_evict_tarballs_of_removed_models() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DeltaCache::from_config(&DeltaConfig {
+            cache_dir: Some(dir.path().to_str().unwrap().to_string()),
+        })
+        .unwrap();
+        cache.write("tensorflow-my_model.tar.gz", b"kept");
+        cache.write("pytorch-my_model.tar.gz", b"removed");
+        std::fs::write(dir.path().join("notes.txt"), b"not a tarball").unwrap();
+
+        // Act
+        cache.retain(["tensorflow-my_model.tar.gz"]);
+
+        // Assert
+        assert!(cache.contains("tensorflow-my_model.tar.gz"));
+        assert!(!cache.contains("pytorch-my_model.tar.gz"));
+        assert!(dir.path().join("notes.txt").exists());
+        let mut kept = Vec::new();
+        cache
+            .open("tensorflow-my_model.tar.gz")
+            .unwrap()
+            .file()
+            .read_to_end(&mut kept)
+            .unwrap();
+        assert_eq!(kept, b"kept");
+    }
+
+    #[test]
+    fn fails_to_parse_manifest_when_blocks_do_not_cover_the_tarball() {
+        let manifest =
+            BlockManifest::build(&random_bytes(MIN_BLOCK_SIZE * 2, 6), MIN_BLOCK_SIZE).unwrap();
+        let mut truncated = manifest.clone();
+        truncated.blocks.pop();
+
+        // assert
+        assert!(BlockManifest::parse(&serde_json::to_vec(&manifest).unwrap()).is_ok());
+        assert!(BlockManifest::parse(&serde_json::to_vec(&truncated).unwrap()).is_err());
+        assert!(BlockManifest::build(&[0; 16], 16).is_err());
+    }
+
+    #[test]
+    fn successfully_recognise_manifest_keys() {
+        let key = manifest_key("tensorflow-my_model.tar.gz");
+
+        // assert
+        assert_eq!(key, "tensorflow-my_model.tar.gz.zsync.json");
+        assert!(is_manifest(key.as_str()));
+        assert!(!is_manifest("tensorflow-my_model.tar.gz"));
+    }
+}
@@ -0,0 +1,413 @@
+use crate::model::bundle::FeatureType;
+use crate::model_store::common::{
+    read_provenance, unpack_tarball_with_provenance, PROVENANCE_DIRECTORY_NAME,
+};
+use crate::model_store::storage::extract_framework;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+
+/// Suffix of the build labels which record the version of a library the model was built with, e.g.
+/// `torch_version: 2.3.0`. They are diffed as library versions rather than as labels.
+pub const LIBRARY_VERSION_LABEL_SUFFIX: &str = "_version";
+
+/// Returns the name of a tarball in its model store from its source URI, i.e. the object key of
+/// `s3://<bucket>/<key>`, the blob name of `azure://<container>/<blob>` or the file name of `file://<path>`.
+/// Returns `None` if the URI is unknown.
+pub fn tarball_name(source_uri: &str) -> Option<&str> {
+    let (scheme, location) = source_uri.split_once("://")?;
+    let tarball_name = match scheme {
+        "file" => location.rsplit('/').next()?,
+        _ => location.split_once('/')?.1,
+    };
+    match tarball_name.is_empty() {
+        true => None,
+        false => Some(tarball_name),
+    }
+}
+
+/// The metadata of a stored version of a model tarball, which is compared by `ArtifactDiff`.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ArtifactSummary {
+    /// The framework of the model.
+    pub framework: String,
+    /// The SHA-256 hash of the tarball as stored in the model store.
+    pub tarball_sha256: String,
+    /// The SHA-256 hash of the plaintext of the artifact, which is the version reported by the model metadata.
+    /// Empty if it could not be hashed.
+    pub artifact_sha256: String,
+    /// The size of the tarball as stored in the model store, in bytes.
+    pub size_bytes: u64,
+    /// The input features and their types from the schema of the bundle.
+    pub schema: BTreeMap<String, FeatureType>,
+    /// The class labels from the `labels.txt` file of the tarball.
+    pub class_labels: Vec<String>,
+    /// The build labels from the `labels.yaml` file of the tarball, including the library versions.
+    pub labels: BTreeMap<String, String>,
+}
+
+impl ArtifactSummary {
+    /// Summarizes a stored version of a model tarball without loading the model, by unpacking it into a
+    /// temporary directory which is deleted afterwards.
+    ///
+    /// The checksums and the size are those of the tarball and of the plaintext of the artifact, so that they are
+    /// the same on every host, whether the artifact cache is encrypted or not. The build labels are those of the
+    /// tarball, i.e. without the labels set at runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `tarball_name` - The name of the tarball in the model store, e.g. `pytorch-my_model.tar.gz`.
+    /// * `tarball` - The contents of the tarball.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tarball is not named after a framework, cannot be unpacked or holds no artifact.
+    pub fn of_tarball(tarball_name: &str, tarball: &[u8]) -> anyhow::Result<Self> {
+        let file_name = tarball_name.rsplit('/').next().unwrap_or(tarball_name);
+        let framework = match extract_framework(file_name.to_string()) {
+            Some(framework) => framework,
+            None => anyhow::bail!(
+                "Failed to extract framework from tarball {} ❌",
+                tarball_name
+            ),
+        };
+        let scratch_dir = tempfile::Builder::new().prefix("model_diff").tempdir()?;
+        let tarball_path = scratch_dir.path().join(file_name);
+        let out_dir = scratch_dir.path().join("out");
+        fs::write(&tarball_path, tarball)?;
+        unpack_tarball_with_provenance(
+            tarball_path.to_string_lossy().as_ref(),
+            out_dir.to_string_lossy().as_ref(),
+            tarball_name.to_string(),
+            None,
+        )?;
+
+        // the artifacts are the top level entries of the tarball, next to the provenance of each of them
+        let mut artifacts: Vec<_> = fs::read_dir(&out_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|name| name != PROVENANCE_DIRECTORY_NAME)
+            })
+            .collect();
+        artifacts.sort();
+        let provenance = match artifacts.first() {
+            Some(artifact) => read_provenance(artifact.to_string_lossy().as_ref()),
+            None => anyhow::bail!("Tarball {} holds no model artifact ❌", tarball_name),
+        };
+
+        Ok(ArtifactSummary {
+            framework: framework.to_string(),
+            tarball_sha256: hex::encode(Sha256::digest(tarball)),
+            artifact_sha256: provenance.artifact_sha256.unwrap_or_default(),
+            size_bytes: tarball.len() as u64,
+            schema: provenance.bundle.schema,
+            class_labels: provenance.class_labels,
+            labels: provenance.labels,
+        })
+    }
+
+    /// Returns the library versions recorded in the build labels, keyed by library, e.g. `torch`.
+    fn library_versions(&self) -> BTreeMap<String, String> {
+        self.labels
+            .iter()
+            .filter_map(|(key, value)| {
+                key.strip_suffix(LIBRARY_VERSION_LABEL_SUFFIX)
+                    .filter(|library| !library.is_empty())
+                    .map(|library| (library.to_string(), value.clone()))
+            })
+            .collect()
+    }
+
+    /// Returns the build labels other than the library versions.
+    fn other_labels(&self) -> BTreeMap<String, String> {
+        self.labels
+            .iter()
+            .filter(|(key, _)| {
+                !key.ends_with(LIBRARY_VERSION_LABEL_SUFFIX)
+                    || key.as_str() == LIBRARY_VERSION_LABEL_SUFFIX
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+/// A value which differs between two versions of a model.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+impl<T: PartialEq + Clone> Change<T> {
+    /// Returns the change from `from` to `to`, or `None` if they are equal.
+    fn between(from: &T, to: &T) -> Option<Self> {
+        match from == to {
+            true => None,
+            false => Some(Change {
+                from: from.clone(),
+                to: to.clone(),
+            }),
+        }
+    }
+}
+
+/// The change of the size of an artifact.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SizeChange {
+    pub from: u64,
+    pub to: u64,
+    /// The growth of the artifact in bytes, negative if it shrank.
+    pub delta: i64,
+}
+
+/// The entries which were added, removed or changed between two versions of a map, e.g. the schema of a model.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MapDiff<T> {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub added: BTreeMap<String, T>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub removed: BTreeMap<String, T>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub changed: BTreeMap<String, Change<T>>,
+}
+
+impl<T: PartialEq + Clone> MapDiff<T> {
+    /// Returns the differences from `from` to `to`, or `None` if they are equal.
+    fn between(from: &BTreeMap<String, T>, to: &BTreeMap<String, T>) -> Option<Self> {
+        let diff = MapDiff {
+            added: to
+                .iter()
+                .filter(|(key, _)| !from.contains_key(key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            removed: from
+                .iter()
+                .filter(|(key, _)| !to.contains_key(key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            changed: from
+                .iter()
+                .filter_map(|(key, value)| {
+                    let change = Change::between(value, to.get(key.as_str())?)?;
+                    Some((key.clone(), change))
+                })
+                .collect(),
+        };
+        match diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty() {
+            true => None,
+            false => Some(diff),
+        }
+    }
+}
+
+/// The differences between the artifacts of two versions of a model, e.g. to review what a promotion changes.
+///
+/// Only the fields which changed are set.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ArtifactDiff {
+    /// Whether both versions are built from the same artifact, i.e. their SHA-256 hashes match.
+    pub identical: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub framework: Option<Change<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tarball_sha256: Option<Change<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifact_sha256: Option<Change<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<SizeChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<MapDiff<FeatureType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class_labels: Option<Change<Vec<String>>>,
+    /// The library versions recorded in the build labels, see `LIBRARY_VERSION_LABEL_SUFFIX`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub library_versions: Option<MapDiff<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<MapDiff<String>>,
+}
+
+impl ArtifactDiff {
+    /// Compares the artifacts of two versions of a model.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The summary of the version being replaced.
+    /// * `to` - The summary of the version replacing it.
+    pub fn between(from: &ArtifactSummary, to: &ArtifactSummary) -> Self {
+        ArtifactDiff {
+            identical: !from.artifact_sha256.is_empty()
+                && from.artifact_sha256 == to.artifact_sha256,
+            framework: Change::between(&from.framework, &to.framework),
+            tarball_sha256: Change::between(&from.tarball_sha256, &to.tarball_sha256),
+            artifact_sha256: Change::between(&from.artifact_sha256, &to.artifact_sha256),
+            size_bytes: Change::between(&from.size_bytes, &to.size_bytes).map(|change| {
+                SizeChange {
+                    from: change.from,
+                    to: change.to,
+                    delta: change.to as i64 - change.from as i64,
+                }
+            }),
+            schema: MapDiff::between(&from.schema, &to.schema),
+            class_labels: Change::between(&from.class_labels, &to.class_labels),
+            library_versions: MapDiff::between(&from.library_versions(), &to.library_versions()),
+            labels: MapDiff::between(&from.other_labels(), &to.other_labels()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(sha256: &str, size_bytes: u64, labels: &[(&str, &str)]) -> ArtifactSummary {
+        ArtifactSummary {
+            framework: "pytorch".to_string(),
+            tarball_sha256: format!("{}-tarball", sha256),
+            artifact_sha256: sha256.to_string(),
+            size_bytes,
+            schema: BTreeMap::from([
+                ("age".to_string(), FeatureType::Float),
+                ("deck".to_string(), FeatureType::String),
+            ]),
+            class_labels: vec!["cat".to_string(), "dog".to_string()],
+            labels: labels
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn successfully_diff_artifacts_of_two_versions() {
+        // Arrange
+        let from = summary(
+            "9f86d0",
+            1000,
+            &[("git_sha", "4f2a9c1"), ("torch_version", "2.2.0")],
+        );
+        let mut to = summary(
+            "60303a",
+            1200,
+            &[
+                ("git_sha", "8b1e3d7"),
+                ("torch_version", "2.3.0"),
+                ("numpy_version", "1.26.4"),
+            ],
+        );
+        to.schema.insert("age".to_string(), FeatureType::Int);
+        to.schema.remove("deck");
+        to.schema.insert("fare".to_string(), FeatureType::Float);
+        to.class_labels.push("bird".to_string());
+
+        // Act
+        let diff = ArtifactDiff::between(&from, &to);
+
+        // Assert
+        assert!(!diff.identical);
+        assert!(diff.framework.is_none());
+        assert_eq!(diff.artifact_sha256.unwrap().to, "60303a");
+        assert_eq!(diff.size_bytes.unwrap().delta, 200);
+        let schema = diff.schema.unwrap();
+        assert_eq!(schema.added["fare"], FeatureType::Float);
+        assert_eq!(schema.removed["deck"], FeatureType::String);
+        assert_eq!(
+            schema.changed["age"],
+            Change {
+                from: FeatureType::Float,
+                to: FeatureType::Int
+            }
+        );
+        assert_eq!(diff.class_labels.unwrap().to, vec!["cat", "dog", "bird"]);
+        let library_versions = diff.library_versions.unwrap();
+        assert_eq!(library_versions.added["numpy"], "1.26.4");
+        assert_eq!(library_versions.changed["torch"].from, "2.2.0");
+        let labels = diff.labels.unwrap();
+        assert_eq!(labels.changed.keys().collect::<Vec<_>>(), vec!["git_sha"]);
+    }
+
+    #[test]
+    fn successfully_find_tarball_name_from_source_uri() {
+        // Act & Assert
+        assert_eq!(
+            tarball_name("s3://models/team/pytorch-my_model.tar.gz"),
+            Some("team/pytorch-my_model.tar.gz")
+        );
+        assert_eq!(
+            tarball_name("azure://models/pytorch-my_model.tar.gz"),
+            Some("pytorch-my_model.tar.gz")
+        );
+        assert_eq!(
+            tarball_name("file:///srv/models/pytorch-my_model.tar.gz"),
+            Some("pytorch-my_model.tar.gz")
+        );
+        assert_eq!(tarball_name("s3://models"), None);
+        assert_eq!(tarball_name(""), None);
+    }
+
+    #[test]
+    fn successfully_summarize_stored_tarball() {
+        // Arrange
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        for (name, contents) in [
+            ("pytorch-my_model.pt", "model"),
+            ("labels.txt", "cat\ndog\n"),
+            ("labels.yaml", "torch_version: 2.3.0\ngit_sha: 4f2a9c1\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            builder
+                .append_data(&mut header, name, contents.as_bytes())
+                .unwrap();
+        }
+        let tarball = builder.into_inner().unwrap().finish().unwrap();
+
+        // Act
+        let summary = ArtifactSummary::of_tarball("models/pytorch-my_model.tar.gz", &tarball);
+        let unknown = ArtifactSummary::of_tarball("my_model.tar.gz", &tarball);
+
+        // Assert
+        let summary = summary.unwrap();
+        assert_eq!(summary.framework, "pytorch");
+        assert_eq!(
+            summary.tarball_sha256,
+            hex::encode(Sha256::digest(&tarball))
+        );
+        assert_eq!(summary.size_bytes, tarball.len() as u64);
+        assert_eq!(summary.class_labels, vec!["cat", "dog"]);
+        assert_eq!(summary.labels["torch_version"], "2.3.0");
+        assert!(!summary.artifact_sha256.is_empty());
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn successfully_diff_identical_artifacts() {
+        // Arrange
+        let from = summary("9f86d0", 1000, &[("torch_version", "2.3.0")]);
+
+        // Act
+        let diff = ArtifactDiff::between(&from, &from.clone());
+
+        // Assert
+        assert_eq!(
+            diff,
+            ArtifactDiff {
+                identical: true,
+                framework: None,
+                tarball_sha256: None,
+                artifact_sha256: None,
+                size_bytes: None,
+                schema: None,
+                class_labels: None,
+                library_versions: None,
+                labels: None,
+            }
+        );
+    }
+}
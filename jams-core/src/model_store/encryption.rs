@@ -0,0 +1,747 @@
+use crate::{Error, Result};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::Deserialize;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tempfile::TempDir;
+
+/// Default environment variable holding the hex encoded 256-bit key of the artifact cache.
+pub const DEFAULT_KEY_ENV: &str = "JAMS_ARTIFACT_KEY";
+
+/// Prefix of the files sealed in one piece by earlier versions, followed by the nonce and the ciphertext. They
+/// can still be opened, but every file is now sealed in chunks, see `STREAM_MAGIC`.
+pub const SEALED_MAGIC: &[u8; 8] = b"JAMSENC1";
+
+/// Prefix of every file sealed by an `ArtifactCipher`, followed by the nonce prefix and the sealed chunks.
+pub const STREAM_MAGIC: &[u8; 8] = b"JAMSENC2";
+
+/// Length of the AES-GCM nonce.
+const NONCE_LENGTH: usize = 12;
+
+/// Length of the random prefix of the nonces of a stream, which is followed by the big endian index of the
+/// chunk and a flag set for the last chunk, so that chunks can be neither reordered nor dropped.
+const NONCE_PREFIX_LENGTH: usize = 7;
+
+/// Length of the plaintext of every chunk of a stream but the last one, which is shorter.
+const CHUNK_LENGTH: usize = 64 * 1024;
+
+/// Length of the AES-GCM tag appended to every sealed chunk.
+const TAG_LENGTH: usize = 16;
+
+/// Length of the AES-256 key.
+const KEY_LENGTH: usize = 32;
+
+/// Memory backed directory in which the artifacts are decrypted while loading, if the host has one.
+const SHARED_MEMORY_DIR: &str = "/dev/shm";
+
+/// The cipher installed for the process, see `install`.
+static CIPHER: OnceLock<ArtifactCipher> = OnceLock::new();
+
+/// Configuration of the encryption of the local artifact cache, i.e. the model store directory into which
+/// the tarballs are downloaded and unpacked.
+///
+/// The key is read from an environment variable or a file, e.g. a secret which the orchestrator fetched from a
+/// KMS and mounted into the container. It must be 64 hex characters, e.g. the output of `openssl rand -hex 32`.
+///
+/// # Example
+/// ```toml
+/// [config.artifact_encryption]
+/// key_env = "JAMS_ARTIFACT_KEY"
+/// scratch_dir = "/dev/shm"
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EncryptionConfig {
+    /// The environment variable holding the key. Defaults to `JAMS_ARTIFACT_KEY`.
+    #[serde(default = "default_key_env")]
+    pub key_env: String,
+
+    /// An optional file holding the key, which takes precedence over `key_env`.
+    pub key_file: Option<String>,
+
+    /// An optional directory in which the artifacts are decrypted while they are loaded. Defaults to `/dev/shm`
+    /// if it exists, so that the plaintext never reaches the disk, and to the temporary directory otherwise.
+    pub scratch_dir: Option<String>,
+}
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        EncryptionConfig {
+            key_env: default_key_env(),
+            key_file: None,
+            scratch_dir: None,
+        }
+    }
+}
+
+fn default_key_env() -> String {
+    DEFAULT_KEY_ENV.to_string()
+}
+
+/// Encrypts the files of the local artifact cache with AES-256-GCM, and decrypts them into a short-lived scratch
+/// directory while the models are loaded, so that the model artifacts do not sit in plaintext on shared hosts.
+///
+/// Files are sealed in chunks of 64 KiB as they are written, so that neither the plaintext nor the whole
+/// ciphertext of an artifact has to be held in memory or written to disk.
+pub struct ArtifactCipher {
+    cipher: Aes256Gcm,
+    scratch_dir: PathBuf,
+}
+
+/// An artifact decrypted into the scratch directory. The plaintext is removed when it is dropped.
+pub struct OpenedArtifact {
+    _dir: TempDir,
+    path: PathBuf,
+}
+
+impl OpenedArtifact {
+    /// Returns the path to the decrypted artifact.
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+}
+
+impl ArtifactCipher {
+    /// Creates a cipher from a raw key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The 256-bit key.
+    /// * `scratch_dir` - The directory in which the artifacts are decrypted while they are loaded.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the key is not 32 bytes long.
+    pub fn new(key: &[u8], scratch_dir: PathBuf) -> anyhow::Result<Self> {
+        if key.len() != KEY_LENGTH {
+            tracing::error!("Artifact encryption key must be {} bytes ❌", KEY_LENGTH);
+            anyhow::bail!("Artifact encryption key must be {} bytes ❌", KEY_LENGTH)
+        }
+        Ok(ArtifactCipher {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+            scratch_dir,
+        })
+    }
+
+    /// Creates a cipher from the key configured by `config`.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the key cannot be read or is not 64 hex characters.
+    pub fn from_config(config: &EncryptionConfig) -> anyhow::Result<Self> {
+        let encoded = match &config.key_file {
+            Some(key_file) => fs::read_to_string(key_file).map_err(|e| {
+                tracing::error!(
+                    "Failed to read artifact encryption key from {} ❌: {}",
+                    key_file,
+                    e
+                );
+                anyhow::anyhow!(
+                    "Failed to read artifact encryption key from {} ❌: {}",
+                    key_file,
+                    e
+                )
+            })?,
+            None => std::env::var(config.key_env.as_str()).map_err(|_| {
+                tracing::error!("Artifact encryption key {} is not set ❌", config.key_env);
+                anyhow::anyhow!("Artifact encryption key {} is not set ❌", config.key_env)
+            })?,
+        };
+        let key = match hex::decode(encoded.trim()) {
+            Ok(key) => key,
+            Err(e) => {
+                tracing::error!("Artifact encryption key must be hex encoded ❌: {}", e);
+                anyhow::bail!("Artifact encryption key must be hex encoded ❌: {}", e)
+            }
+        };
+        let scratch_dir = match &config.scratch_dir {
+            Some(scratch_dir) => PathBuf::from(scratch_dir),
+            None if Path::new(SHARED_MEMORY_DIR).is_dir() => PathBuf::from(SHARED_MEMORY_DIR),
+            None => std::env::temp_dir(),
+        };
+        ArtifactCipher::new(key.as_slice(), scratch_dir)
+    }
+
+    /// Encrypts bytes, see `sealing_writer`.
+    pub fn seal(&self, plaintext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut writer = self.sealing_writer(Vec::with_capacity(plaintext.len()))?;
+        writer.write_all(plaintext)?;
+        Ok(writer.finish()?)
+    }
+
+    /// Decrypts bytes sealed by `seal`, or sealed in one piece by earlier versions.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the bytes are not sealed, were sealed with another key or were tampered with.
+    pub fn open(&self, sealed: &[u8]) -> anyhow::Result<Vec<u8>> {
+        if sealed.starts_with(STREAM_MAGIC) {
+            let mut plaintext = Vec::with_capacity(sealed.len());
+            self.opening_reader(sealed)?.read_to_end(&mut plaintext)?;
+            return Ok(plaintext);
+        }
+        if !sealed.starts_with(SEALED_MAGIC) || sealed.len() < SEALED_MAGIC.len() + NONCE_LENGTH {
+            anyhow::bail!("Artifact is not encrypted ❌")
+        }
+        let (nonce, ciphertext) = sealed[SEALED_MAGIC.len()..].split_at(NONCE_LENGTH);
+        match self.cipher.decrypt(Nonce::from_slice(nonce), ciphertext) {
+            Ok(plaintext) => Ok(plaintext),
+            Err(_) => anyhow::bail!(
+                "Failed to decrypt artifact ❌: it was encrypted with another key or was modified"
+            ),
+        }
+    }
+
+    /// Returns a writer which seals the bytes written to it into `inner` in chunks with a random nonce prefix.
+    /// The writer must be finished with `SealingWriter::finish`, which seals the last chunk.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(io::Error)` - If the header of the stream cannot be written to `inner`.
+    pub fn sealing_writer<W: Write>(&self, mut inner: W) -> io::Result<SealingWriter<'_, W>> {
+        let mut prefix = [0u8; NONCE_PREFIX_LENGTH];
+        OsRng.fill_bytes(&mut prefix);
+        inner.write_all(STREAM_MAGIC)?;
+        inner.write_all(&prefix)?;
+        Ok(SealingWriter {
+            cipher: &self.cipher,
+            inner,
+            prefix,
+            counter: 0,
+            buffer: Vec::with_capacity(CHUNK_LENGTH),
+        })
+    }
+
+    /// Returns a reader of the plaintext of `sealed`, which decrypts the chunks as they are read. Bytes which are
+    /// not sealed are read as they are.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(io::Error)` - If `sealed` cannot be read, or was sealed in one piece and cannot be decrypted. The
+    ///   chunks which cannot be decrypted fail the reads of the returned reader.
+    pub fn opening_reader<'a, R: Read + 'a>(
+        &'a self,
+        mut sealed: R,
+    ) -> io::Result<Box<dyn Read + 'a>> {
+        let magic = read_magic(&mut sealed)?;
+        if magic.as_slice() == STREAM_MAGIC {
+            let mut prefix = [0u8; NONCE_PREFIX_LENGTH];
+            sealed.read_exact(&mut prefix)?;
+            return Ok(Box::new(OpeningReader {
+                cipher: &self.cipher,
+                inner: sealed,
+                prefix,
+                counter: 0,
+                plaintext: Vec::new(),
+                position: 0,
+                done: false,
+            }));
+        }
+        if magic.as_slice() == SEALED_MAGIC {
+            // files sealed in one piece were small enough to be sealed in memory
+            let mut bytes = magic;
+            sealed.read_to_end(&mut bytes)?;
+            let plaintext = self
+                .open(bytes.as_slice())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+            return Ok(Box::new(io::Cursor::new(plaintext)));
+        }
+        Ok(Box::new(io::Cursor::new(magic).chain(sealed)))
+    }
+
+    /// Seals the bytes read from `plaintext` into the file at `path`. They are written next to it and renamed,
+    /// so that a crash never leaves a truncated artifact behind.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If `plaintext` cannot be read or the file cannot be written.
+    pub fn seal_into(&self, plaintext: &mut impl Read, path: &Path) -> anyhow::Result<()> {
+        let sealing_path = match path.file_name() {
+            Some(name) => path.with_file_name(format!("{}.sealing", name.to_string_lossy())),
+            None => anyhow::bail!("Invalid artifact path {:?} ❌", path),
+        };
+        let mut writer = self.sealing_writer(File::create(&sealing_path)?)?;
+        let written = io::copy(plaintext, &mut writer).and_then(|_| writer.finish());
+        if let Err(e) = written {
+            let _ = fs::remove_file(&sealing_path);
+            return Err(e.into());
+        }
+        fs::rename(&sealing_path, path)?;
+        Ok(())
+    }
+
+    /// Encrypts a file in place. Files which are already sealed are left as they are.
+    pub fn seal_file(&self, path: &Path) -> anyhow::Result<()> {
+        let mut file = File::open(path)?;
+        let magic = read_magic(&mut file)?;
+        if is_sealed(magic.as_slice()) {
+            return Ok(());
+        }
+        self.seal_into(&mut io::Cursor::new(magic).chain(file), path)
+    }
+
+    /// Decrypts a file or a directory of files into a new scratch directory. Files which are not sealed are
+    /// copied as they are.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the artifact cannot be read or decrypted.
+    pub fn open_artifact(&self, path: &Path) -> anyhow::Result<OpenedArtifact> {
+        let name = match path.file_name() {
+            Some(name) => name,
+            None => anyhow::bail!("Invalid artifact path {:?} ❌", path),
+        };
+        let dir = tempfile::Builder::new()
+            .prefix("jams-artifact")
+            .tempdir_in(self.scratch_dir.as_path())?;
+        let opened = dir.path().join(name);
+        self.open_into(path, opened.as_path())?;
+        Ok(OpenedArtifact {
+            _dir: dir,
+            path: opened,
+        })
+    }
+
+    fn open_into(&self, from: &Path, to: &Path) -> anyhow::Result<()> {
+        if from.is_dir() {
+            fs::create_dir_all(to)?;
+            for entry in fs::read_dir(from)? {
+                let entry = entry?;
+                self.open_into(entry.path().as_path(), to.join(entry.file_name()).as_path())?;
+            }
+            return Ok(());
+        }
+        let mut plaintext = self.opening_reader(File::open(from)?)?;
+        io::copy(&mut plaintext, &mut File::create(to)?)?;
+        Ok(())
+    }
+}
+
+/// Seals the bytes written to it in chunks, see `ArtifactCipher::sealing_writer`.
+pub struct SealingWriter<'a, W: Write> {
+    cipher: &'a Aes256Gcm,
+    inner: W,
+    prefix: [u8; NONCE_PREFIX_LENGTH],
+    counter: u32,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> SealingWriter<'_, W> {
+    /// Seals the last chunk, which is shorter than the others and may be empty, and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.seal_chunk(true)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn seal_chunk(&mut self, last: bool) -> io::Result<()> {
+        let nonce = chunk_nonce(&self.prefix, self.counter, last);
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), self.buffer.as_slice())
+            .map_err(|_| io::Error::other("Failed to encrypt artifact ❌"))?;
+        self.inner.write_all(ciphertext.as_slice())?;
+        self.buffer.clear();
+        self.counter = match self.counter.checked_add(1) {
+            Some(counter) => counter,
+            None => return Err(io::Error::other("Artifact is too large to be encrypted ❌")),
+        };
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for SealingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let length = buf.len().min(CHUNK_LENGTH - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..length]);
+        // the last chunk is always shorter than a full chunk, so that readers can tell it apart
+        if self.buffer.len() == CHUNK_LENGTH {
+            self.seal_chunk(false)?;
+        }
+        Ok(length)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decrypts the chunks of a stream as they are read, see `ArtifactCipher::opening_reader`.
+struct OpeningReader<'a, R: Read> {
+    cipher: &'a Aes256Gcm,
+    inner: R,
+    prefix: [u8; NONCE_PREFIX_LENGTH],
+    counter: u32,
+    plaintext: Vec<u8>,
+    position: usize,
+    done: bool,
+}
+
+impl<R: Read> OpeningReader<'_, R> {
+    fn open_chunk(&mut self) -> io::Result<()> {
+        let mut ciphertext = Vec::with_capacity(CHUNK_LENGTH + TAG_LENGTH);
+        self.inner
+            .by_ref()
+            .take((CHUNK_LENGTH + TAG_LENGTH) as u64)
+            .read_to_end(&mut ciphertext)?;
+        if ciphertext.len() < TAG_LENGTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Failed to decrypt artifact ❌: it was truncated",
+            ));
+        }
+        let last = ciphertext.len() < CHUNK_LENGTH + TAG_LENGTH;
+        let nonce = chunk_nonce(&self.prefix, self.counter, last);
+        self.plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Failed to decrypt artifact ❌: it was encrypted with another key or was modified",
+                )
+            })?;
+        self.position = 0;
+        self.done = last;
+        self.counter = self.counter.wrapping_add(1);
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for OpeningReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.plaintext.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.open_chunk()?;
+        }
+        let length = buf.len().min(self.plaintext.len() - self.position);
+        buf[..length].copy_from_slice(&self.plaintext[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+/// Returns the nonce of a chunk of a stream.
+fn chunk_nonce(prefix: &[u8; NONCE_PREFIX_LENGTH], counter: u32, last: bool) -> [u8; NONCE_LENGTH] {
+    let mut nonce = [0u8; NONCE_LENGTH];
+    nonce[..NONCE_PREFIX_LENGTH].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_LENGTH..NONCE_LENGTH - 1].copy_from_slice(&counter.to_be_bytes());
+    nonce[NONCE_LENGTH - 1] = last as u8;
+    nonce
+}
+
+/// Reads the bytes which would hold the magic prefix of a sealed file, or fewer if the file is shorter.
+fn read_magic(reader: &mut impl Read) -> io::Result<Vec<u8>> {
+    let mut magic = Vec::with_capacity(STREAM_MAGIC.len());
+    reader
+        .by_ref()
+        .take(STREAM_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+    Ok(magic)
+}
+
+/// Returns true if the bytes were sealed by an `ArtifactCipher`.
+pub fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.starts_with(STREAM_MAGIC) || bytes.starts_with(SEALED_MAGIC)
+}
+
+/// Installs the cipher of the local artifact cache for the process. Every model store encrypts the artifacts it
+/// unpacks from then on, so it must be installed before the model stores are created.
+///
+/// # Errors
+///
+/// * `Err(anyhow::Error)` - If a cipher was already installed.
+pub fn install(cipher: ArtifactCipher) -> anyhow::Result<()> {
+    match CIPHER.set(cipher) {
+        Ok(_) => {
+            tracing::info!("Encrypting the local artifact cache 🔐");
+            Ok(())
+        }
+        Err(_) => anyhow::bail!("Artifact encryption is already installed ❌"),
+    }
+}
+
+/// Returns the cipher installed for the process, if any.
+pub fn installed() -> Option<&'static ArtifactCipher> {
+    CIPHER.get()
+}
+
+/// Opens a file of the artifact cache for reading, decrypting it as it is read if it is sealed.
+///
+/// # Errors
+///
+/// * `Err(io::Error)` - If the file cannot be opened, or it is sealed and no cipher is installed. The chunks which
+///   cannot be decrypted fail the reads of the returned reader.
+pub fn open_cached(path: &Path) -> io::Result<Box<dyn Read>> {
+    let mut file = File::open(path)?;
+    let magic = read_magic(&mut file)?;
+    let sealed = is_sealed(magic.as_slice());
+    let file = io::Cursor::new(magic).chain(file);
+    if !sealed {
+        return Ok(Box::new(file));
+    }
+    match installed() {
+        Some(cipher) => cipher.opening_reader(file),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Artifact is encrypted but no encryption key is configured ❌",
+        )),
+    }
+}
+
+/// Reads a file of the artifact cache, decrypting it if it is sealed.
+///
+/// # Errors
+///
+/// * `Err(io::Error)` - If the file cannot be read, or it is sealed and cannot be decrypted.
+pub fn read_cached(path: &Path) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    open_cached(path)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Writes a file of the artifact cache, encrypting it if a cipher is installed.
+pub fn write_cached(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    write_cached_from(path, &mut &bytes[..])
+}
+
+/// Writes the bytes read from `reader` to a file of the artifact cache, encrypting them as they are read if a
+/// cipher is installed, so that the plaintext never reaches the disk.
+pub fn write_cached_from(path: &Path, reader: &mut impl Read) -> anyhow::Result<()> {
+    match installed() {
+        Some(cipher) => cipher.seal_into(reader, path)?,
+        None => {
+            io::copy(reader, &mut File::create(path)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Creates an anonymous temporary file for the plaintext of an artifact which is about to be written to the cache,
+/// in the scratch directory if a cipher is installed. The file is removed when it is closed.
+///
+/// # Errors
+///
+/// * `Err(io::Error)` - If the file cannot be created.
+pub fn scratch_file() -> io::Result<File> {
+    match installed() {
+        Some(cipher) => tempfile::tempfile_in(cipher.scratch_dir.as_path()),
+        None => tempfile::tempfile(),
+    }
+}
+
+/// Loads a model artifact of the cache, decrypting it into the scratch directory first if a cipher is installed.
+/// The decrypted artifact is removed as soon as `load` returns, as the frameworks read the whole model into memory.
+///
+/// # Arguments
+///
+/// * `path` - The path to the artifact in the cache.
+/// * `load` - Loads the model from the path to the plaintext artifact.
+///
+/// # Errors
+///
+/// * `Error::Load` - If the artifact cannot be decrypted or the model fails to load.
+pub fn load_decrypted<T, F>(path: &str, load: F) -> Result<T>
+where
+    F: FnOnce(&str) -> Result<T>,
+{
+    let cipher = match installed() {
+        Some(cipher) => cipher,
+        None => return load(path),
+    };
+    let opened = match cipher.open_artifact(Path::new(path)) {
+        Ok(opened) => opened,
+        Err(e) => {
+            tracing::error!("Failed to decrypt artifact {} ❌: {}", path, e);
+            return Err(Error::Load(anyhow::anyhow!(
+                "Failed to decrypt artifact {} ❌: {}",
+                path,
+                e
+            )));
+        }
+    };
+    load(opened.path().to_string_lossy().as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cipher(key: u8, scratch_dir: &Path) -> ArtifactCipher {
+        ArtifactCipher::new(&[key; KEY_LENGTH], scratch_dir.to_path_buf()).unwrap()
+    }
+
+    #[test]
+    fn successfully_seal_and_open_artifact_directory() {
+        // Arrange
+        let cache = tempfile::tempdir().unwrap();
+        let scratch = tempfile::tempdir().unwrap();
+        let artifact = cache.path().join("tensorflow-my_model");
+        fs::create_dir_all(artifact.join("variables")).unwrap();
+        fs::write(artifact.join("saved_model.pb"), b"graph").unwrap();
+        fs::write(
+            artifact.join("variables").join("variables.index"),
+            b"weights",
+        )
+        .unwrap();
+        let cipher = cipher(7, scratch.path());
+
+        // Act
+        cipher.seal_file(&artifact.join("saved_model.pb")).unwrap();
+        cipher
+            .seal_file(&artifact.join("variables").join("variables.index"))
+            .unwrap();
+        let opened = cipher.open_artifact(&artifact).unwrap();
+
+        // Assert
+        assert!(is_sealed(
+            &fs::read(artifact.join("saved_model.pb")).unwrap()
+        ));
+        assert!(opened.path().starts_with(scratch.path()));
+        assert_eq!(
+            fs::read(opened.path().join("saved_model.pb")).unwrap(),
+            b"graph"
+        );
+        assert_eq!(
+            fs::read(opened.path().join("variables").join("variables.index")).unwrap(),
+            b"weights"
+        );
+        let opened_path = opened.path().to_path_buf();
+        drop(opened);
+        assert!(!opened_path.exists());
+    }
+
+    #[test]
+    fn fails_to_open_artifact_with_another_key_or_when_modified() {
+        // Arrange
+        let scratch = tempfile::tempdir().unwrap();
+        let sealed = cipher(7, scratch.path()).seal(b"model").unwrap();
+        let mut modified = sealed.clone();
+        *modified.last_mut().unwrap() ^= 1;
+
+        // Act
+        let other_key = cipher(8, scratch.path()).open(&sealed);
+        let tampered = cipher(7, scratch.path()).open(&modified);
+        let plaintext = cipher(7, scratch.path()).open(b"model");
+
+        // Assert
+        assert!(other_key.is_err());
+        assert!(tampered.is_err());
+        assert!(plaintext.is_err());
+    }
+
+    #[test]
+    fn successfully_seal_and_open_artifact_in_chunks() {
+        // Arrange
+        let scratch = tempfile::tempdir().unwrap();
+        let cipher = cipher(7, scratch.path());
+        let plaintexts: Vec<Vec<u8>> = [0, 1, CHUNK_LENGTH - 1, CHUNK_LENGTH, 3 * CHUNK_LENGTH + 5]
+            .iter()
+            .map(|length| (0..*length).map(|i| (i % 251) as u8).collect())
+            .collect();
+
+        // Act
+        let sealed: Vec<Vec<u8>> = plaintexts
+            .iter()
+            .map(|plaintext| cipher.seal(plaintext).unwrap())
+            .collect();
+        let opened: Vec<Vec<u8>> = sealed
+            .iter()
+            .map(|sealed| cipher.open(sealed).unwrap())
+            .collect();
+
+        // Assert
+        assert!(sealed.iter().all(|sealed| sealed.starts_with(STREAM_MAGIC)));
+        assert_eq!(opened, plaintexts);
+    }
+
+    #[test]
+    fn fails_to_open_artifact_when_chunks_were_dropped_or_reordered() {
+        // Arrange
+        let scratch = tempfile::tempdir().unwrap();
+        let cipher = cipher(7, scratch.path());
+        let plaintext: Vec<u8> = (0..2 * CHUNK_LENGTH + 5).map(|i| i as u8).collect();
+        let sealed = cipher.seal(&plaintext).unwrap();
+        let header = STREAM_MAGIC.len() + NONCE_PREFIX_LENGTH;
+        let chunk = CHUNK_LENGTH + TAG_LENGTH;
+        let without_last_chunk = sealed[..header + 2 * chunk].to_vec();
+        let mut reordered = sealed[..header].to_vec();
+        reordered.extend_from_slice(&sealed[header + chunk..header + 2 * chunk]);
+        reordered.extend_from_slice(&sealed[header..header + chunk]);
+        reordered.extend_from_slice(&sealed[header + 2 * chunk..]);
+
+        // Act
+        let truncated = cipher.open(&without_last_chunk);
+        let reordered = cipher.open(&reordered);
+
+        // Assert
+        assert!(truncated.is_err());
+        assert!(reordered.is_err());
+    }
+
+    #[test]
+    fn successfully_open_artifact_sealed_in_one_piece() {
+        // Arrange
+        let scratch = tempfile::tempdir().unwrap();
+        let cipher = cipher(7, scratch.path());
+        let nonce = [3u8; NONCE_LENGTH];
+        let mut sealed = SEALED_MAGIC.to_vec();
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(
+            &cipher
+                .cipher
+                .encrypt(Nonce::from_slice(&nonce), b"model".as_slice())
+                .unwrap(),
+        );
+
+        // Act
+        let mut opened = Vec::new();
+        cipher
+            .opening_reader(sealed.as_slice())
+            .unwrap()
+            .read_to_end(&mut opened)
+            .unwrap();
+
+        // Assert
+        assert_eq!(opened, b"model");
+    }
+
+    #[test]
+    fn fails_to_create_cipher_when_key_is_invalid() {
+        // Arrange
+        let not_hex = file_with(b"not a key");
+        let hex = file_with(&[b'a'; 64]);
+
+        // Act
+        let short_key = ArtifactCipher::new(&[0; 16], std::env::temp_dir());
+        let not_hex = ArtifactCipher::from_config(&EncryptionConfig {
+            key_file: Some(not_hex.path().to_string_lossy().to_string()),
+            ..EncryptionConfig::default()
+        });
+        let hex_key = ArtifactCipher::from_config(&EncryptionConfig {
+            key_file: Some(hex.path().to_string_lossy().to_string()),
+            ..EncryptionConfig::default()
+        });
+
+        // Assert
+        assert!(short_key.is_err());
+        assert!(not_hex.is_err());
+        assert!(hex_key.is_ok());
+    }
+
+    /// Writes `contents` to a temporary file, which is removed when it is dropped.
+    fn file_with(contents: &[u8]) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(contents).unwrap();
+        file
+    }
+}
@@ -29,6 +29,8 @@ pub trait Fetcher {
     /// - `output_dir`: A `String` specifying the target directory where fetched models will be
     ///   saved. If the source is local, models will be copied here; otherwise, they will be downloaded
     ///   to this location.
+    /// - `model_names`: The names of the models to fetch, e.g. the models loaded first at startup. Every
+    ///   model is fetched if `None`.
     ///
     /// # Returns
     /// - `Ok(DashMap<ModelName, Arc<Model>>)` on success, where each `ModelName` key maps to an
@@ -38,5 +40,6 @@ pub trait Fetcher {
         &self,
         artefacts_dir_name: Option<String>,
         output_dir: String,
+        model_names: Option<Vec<ModelName>>,
     ) -> anyhow::Result<DashMap<ModelName, Arc<Model>>>;
 }
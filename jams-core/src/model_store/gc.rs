@@ -0,0 +1,371 @@
+use crate::memory::artifact_size_bytes;
+use crate::model_store::common::DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+/// The outcome of collecting the model store directories orphaned by other processes.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GcReport {
+    /// Orphaned directories which were removed.
+    pub removed: Vec<PathBuf>,
+    /// Orphaned directories which could not be removed.
+    pub failed: Vec<PathBuf>,
+    /// Total size in bytes of the removed directories.
+    pub freed_bytes: u64,
+}
+
+/// Extension of the lock file held by the process which owns a model store directory, stored next to the directory.
+const OWNER_LOCK_EXTENSION: &str = "lock";
+
+/// An exclusive lock on a model store directory, held by the process which owns the directory for as long as
+/// its model store is alive.
+///
+/// The lock is an advisory file lock on a `<directory>.lock` file next to the directory. The operating system
+/// releases it when the process exits, even if it crashes or is killed, so that `collect_orphaned_dirs` can tell
+/// the directories of live processes from the orphaned ones without relying on timestamps.
+#[derive(Debug)]
+pub struct OwnerLock {
+    model_store_dir: PathBuf,
+    path: PathBuf,
+    _file: fs::File,
+}
+
+impl OwnerLock {
+    /// Acquires the lock of a model store directory, which does not have to exist yet. The lock should be
+    /// acquired before the directory is created, so that the directory is never seen without an owner.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_store_dir` - The directory of the model store.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(OwnerLock)` - The lock, which is released when it is dropped.
+    /// * `Err(anyhow::Error)` - If the lock file cannot be created or the lock is held by another process.
+    pub fn acquire(model_store_dir: &Path) -> anyhow::Result<Self> {
+        let path = owner_lock_path(model_store_dir);
+        let file = match fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+        {
+            Ok(file) => file,
+            Err(e) => {
+                tracing::error!("Failed to create lock file {:?} ❌: {}", path, e);
+                anyhow::bail!("Failed to create lock file {:?} ❌: {}", path, e)
+            }
+        };
+        match try_lock(&file) {
+            Ok(true) => {}
+            Ok(false) => {
+                tracing::error!(
+                    "Model store directory {:?} is owned by another process ❌",
+                    model_store_dir
+                );
+                anyhow::bail!(
+                    "Model store directory {:?} is owned by another process ❌",
+                    model_store_dir
+                )
+            }
+            // the directory is then never collected, as its owner cannot be checked either
+            Err(e) => tracing::warn!("Failed to lock {:?} ⚠️: {}", path, e),
+        }
+
+        Ok(OwnerLock {
+            model_store_dir: model_store_dir.to_path_buf(),
+            path,
+            _file: file,
+        })
+    }
+}
+
+impl Drop for OwnerLock {
+    /// Removes the lock file once the directory was cleaned up. The lock file of a directory which is kept to be
+    /// restored from the registry state is left in place, so that the directory can be collected if it is not.
+    fn drop(&mut self) {
+        if !self.model_store_dir.exists() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Returns the path of the lock file of a model store directory.
+fn owner_lock_path(model_store_dir: &Path) -> PathBuf {
+    model_store_dir.with_extension(OWNER_LOCK_EXTENSION)
+}
+
+/// Tries to take an exclusive lock on a file without blocking.
+///
+/// # Returns
+///
+/// * `Ok(true)` - If the lock was taken, it is held until the file is closed.
+/// * `Ok(false)` - If the lock is held by another open file, e.g. of another process.
+/// * `Err(std::io::Error)` - If the file cannot be locked.
+#[cfg(unix)]
+fn try_lock(file: &fs::File) -> std::io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: the descriptor belongs to `file`, which outlives the call
+    match unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } {
+        0 => Ok(true),
+        _ => {
+            let e = std::io::Error::last_os_error();
+            match e.kind() {
+                std::io::ErrorKind::WouldBlock => Ok(false),
+                _ => Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn try_lock(_file: &fs::File) -> std::io::Result<bool> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "file locks are only supported on unix",
+    ))
+}
+
+/// The owner of a model store directory, as seen by another process.
+#[derive(Debug)]
+enum Owner {
+    /// A live process holds the lock of the directory.
+    Alive,
+    /// The lock file exists but is not held, so its owner exited. The probe holds the lock until it is dropped.
+    Exited(fs::File),
+    /// The directory has no lock file, e.g. because it was created by a release without owner locks.
+    Unknown,
+}
+
+/// Checks whether the owner of a model store directory is alive by trying to take its lock.
+fn probe_owner(model_store_dir: &Path) -> std::io::Result<Owner> {
+    let file = match fs::OpenOptions::new()
+        .write(true)
+        .open(owner_lock_path(model_store_dir))
+    {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Owner::Unknown),
+        Err(e) => return Err(e),
+    };
+    match try_lock(&file)? {
+        true => Ok(Owner::Exited(file)),
+        false => Ok(Owner::Alive),
+    }
+}
+
+/// Removes the model store directories left behind by processes which exited without cleaning up,
+/// e.g. because they crashed or were killed.
+///
+/// Every model store downloads or unpacks its models into a `<DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX>_<uuid>`
+/// directory which is removed when the model store is dropped, and holds an `OwnerLock` on the directory while it
+/// is alive. Sibling directories of `model_store_dir` with that name are orphaned once the lock is no longer held
+/// by any process, and are removed along with their lock file. Directories without a lock file, which were created
+/// by releases without owner locks, are only removed once they were not modified for longer than `retention`.
+///
+/// # Arguments
+///
+/// * `model_store_dir` - The directory of the running model store, which is never removed.
+/// * `retention` - How long a directory without a lock file must be left unmodified before it is removed.
+///
+/// # Returns
+///
+/// * `Ok(GcReport)` - The orphaned directories which were removed or failed to be removed.
+/// * `Err(anyhow::Error)` - If the parent directory of `model_store_dir` cannot be read.
+pub fn collect_orphaned_dirs(
+    model_store_dir: &Path,
+    retention: Duration,
+) -> anyhow::Result<GcReport> {
+    let parent = match model_store_dir.parent() {
+        Some(parent) => parent,
+        None => {
+            tracing::error!(
+                "Model store directory {:?} has no parent ❌",
+                model_store_dir
+            );
+            anyhow::bail!(
+                "Model store directory {:?} has no parent ❌",
+                model_store_dir
+            )
+        }
+    };
+    let entries = match fs::read_dir(parent) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::error!("Failed to read directory {:?} ❌: {}", parent, e);
+            anyhow::bail!("Failed to read directory {:?} ❌: {}", parent, e)
+        }
+    };
+
+    let mut report = GcReport::default();
+    let now = SystemTime::now();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.file_name() == model_store_dir.file_name() || !is_model_store_dir(&path) {
+            continue;
+        }
+
+        let owner = match probe_owner(&path) {
+            Ok(owner) => owner,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to check the owner of model store directory {:?} ⚠️: {}",
+                    path,
+                    e
+                );
+                continue;
+            }
+        };
+        match &owner {
+            Owner::Alive => continue,
+            Owner::Exited(_) => {}
+            Owner::Unknown => {
+                let modified = match entry.metadata().and_then(|metadata| metadata.modified()) {
+                    Ok(modified) => modified,
+                    Err(_) => continue,
+                };
+                if now.duration_since(modified).unwrap_or_default() < retention {
+                    continue;
+                }
+            }
+        }
+
+        let size_bytes = artifact_size_bytes(&path);
+        match fs::remove_dir_all(&path) {
+            Ok(_) => {
+                tracing::info!(
+                    "Removed orphaned model store directory {:?} freeing {} bytes 🧹",
+                    path,
+                    size_bytes
+                );
+                if let Owner::Exited(_) = owner {
+                    let _ = fs::remove_file(owner_lock_path(&path));
+                }
+                report.freed_bytes += size_bytes;
+                report.removed.push(path);
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to remove orphaned model store directory {:?} ⚠️: {}",
+                    path,
+                    e
+                );
+                report.failed.push(path);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Returns true if `path` is a directory named `<DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX>_<uuid>`.
+fn is_model_store_dir(path: &Path) -> bool {
+    let name = match path.file_name().and_then(|name| name.to_str()) {
+        Some(name) => name,
+        None => return false,
+    };
+    let is_named_like_model_store = name
+        .strip_prefix(DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX)
+        .and_then(|suffix| suffix.strip_prefix('_'))
+        .map(|uuid| Uuid::parse_str(uuid).is_ok())
+        .unwrap_or(false);
+
+    is_named_like_model_store && path.is_dir()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_model_store_dir(parent: &Path) -> PathBuf {
+        let dir = parent.join(format!(
+            "{}_{}",
+            DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX,
+            Uuid::new_v4()
+        ));
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("lightgbm-my_model.txt"), "model").unwrap();
+        dir
+    }
+
+    #[test]
+    fn successfully_remove_orphaned_model_store_directories() {
+        // Arrange
+        let parent = tempfile::tempdir().unwrap();
+        let own = create_model_store_dir(parent.path());
+        let _own_lock = OwnerLock::acquire(&own).unwrap();
+        let orphaned = create_model_store_dir(parent.path());
+        // the lock is released as if the owner crashed, but its lock file is left behind
+        drop(OwnerLock::acquire(&orphaned).unwrap());
+        let unrelated = parent.path().join("model_store_backup");
+        fs::create_dir(&unrelated).unwrap();
+
+        // Act
+        let report = collect_orphaned_dirs(&own, Duration::from_secs(3600)).unwrap();
+
+        // Assert
+        assert_eq!(report.removed, vec![orphaned.clone()]);
+        assert_eq!(report.freed_bytes, 5);
+        assert!(report.failed.is_empty());
+        assert!(own.exists());
+        assert!(!orphaned.exists());
+        assert!(!owner_lock_path(&orphaned).exists());
+        assert!(unrelated.exists());
+    }
+
+    #[test]
+    fn successfully_keep_model_store_directories_of_live_owners() {
+        // Arrange
+        let parent = tempfile::tempdir().unwrap();
+        let own = create_model_store_dir(parent.path());
+        let live = create_model_store_dir(parent.path());
+        let _live_lock = OwnerLock::acquire(&live).unwrap();
+
+        // Act
+        let report = collect_orphaned_dirs(&own, Duration::from_secs(0)).unwrap();
+
+        // Assert
+        assert_eq!(report, GcReport::default());
+        assert!(live.exists());
+        assert!(OwnerLock::acquire(&live).is_err());
+    }
+
+    #[test]
+    fn successfully_remove_model_store_directories_without_owner_lock_after_retention() {
+        // Arrange
+        let parent = tempfile::tempdir().unwrap();
+        let own = create_model_store_dir(parent.path());
+        let unowned = create_model_store_dir(parent.path());
+
+        // Act
+        let within_retention = collect_orphaned_dirs(&own, Duration::from_secs(3600)).unwrap();
+        let after_retention = collect_orphaned_dirs(&own, Duration::from_secs(0)).unwrap();
+
+        // Assert
+        assert_eq!(within_retention, GcReport::default());
+        assert_eq!(after_retention.removed, vec![unowned.clone()]);
+        assert!(!unowned.exists());
+    }
+
+    #[test]
+    fn successfully_remove_lock_file_when_model_store_directory_is_cleaned_up() {
+        // Arrange
+        let parent = tempfile::tempdir().unwrap();
+        let dir = parent.path().join(format!(
+            "{}_{}",
+            DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX,
+            Uuid::new_v4()
+        ));
+        let lock = OwnerLock::acquire(&dir).unwrap();
+        fs::create_dir(&dir).unwrap();
+
+        // Act
+        fs::remove_dir_all(&dir).unwrap();
+        drop(lock);
+
+        // Assert
+        assert!(!owner_lock_path(&dir).exists());
+    }
+}
@@ -0,0 +1,124 @@
+use crate::model_store::storage::ModelName;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A group of models which are deployed together, e.g. a feature encoder and the scorer consuming its outputs.
+///
+/// When the model store is polled, the updated models of a group are only swapped in once every one of them is
+/// loaded, and all at once. If any of them fails to download, load, initialize or meet its acceptance criteria, or
+/// the tarball of a model of the group is missing from the model store, the whole group keeps serving its current
+/// versions. Models of a group cannot be updated on their own with `Manager::update_model`.
+///
+/// # Example
+/// ```toml
+/// [[config.deployment_groups]]
+/// name = "fraud"
+/// models = ["fraud_encoder", "fraud_scorer"]
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DeploymentGroup {
+    /// Name of the group, used in the logs.
+    pub name: String,
+
+    /// Names of the models of the group.
+    pub models: Vec<ModelName>,
+}
+
+/// The deployment groups of a model store. Models outside of a group are deployed on their own.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeploymentGroups(Vec<DeploymentGroup>);
+
+impl DeploymentGroups {
+    /// Validates the deployment groups.
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The deployment groups.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If a group is configured twice, has no models or a model belongs to more than one group.
+    pub fn new(groups: Vec<DeploymentGroup>) -> anyhow::Result<Self> {
+        let mut names = HashSet::new();
+        let mut models = HashSet::new();
+        for group in groups.iter() {
+            if !names.insert(group.name.as_str()) {
+                tracing::error!("Deployment group {} is configured twice ❌", group.name);
+                anyhow::bail!("Deployment group {} is configured twice ❌", group.name)
+            }
+            if group.models.is_empty() {
+                tracing::error!("Deployment group {} has no models ❌", group.name);
+                anyhow::bail!("Deployment group {} has no models ❌", group.name)
+            }
+            for model_name in group.models.iter() {
+                if !models.insert(model_name.as_str()) {
+                    tracing::error!(
+                        "Model {} belongs to more than one deployment group ❌",
+                        model_name
+                    );
+                    anyhow::bail!(
+                        "Model {} belongs to more than one deployment group ❌",
+                        model_name
+                    )
+                }
+            }
+        }
+        Ok(DeploymentGroups(groups))
+    }
+
+    /// Returns a deployment group by its name.
+    pub fn get(&self, name: &str) -> Option<&DeploymentGroup> {
+        self.0.iter().find(|group| group.name == name)
+    }
+
+    /// Returns the deployment group of a model, if it belongs to one.
+    pub fn group_of(&self, model_name: &str) -> Option<&DeploymentGroup> {
+        self.0
+            .iter()
+            .find(|group| group.models.iter().any(|model| model == model_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(name: &str, models: &[&str]) -> DeploymentGroup {
+        DeploymentGroup {
+            name: name.to_string(),
+            models: models.iter().map(|model| model.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn successfully_find_deployment_group_of_model() {
+        // Arrange
+        let groups = DeploymentGroups::new(vec![
+            group("fraud", &["fraud_encoder", "fraud_scorer"]),
+            group("churn", &["churn_model"]),
+        ])
+        .unwrap();
+
+        // Act
+        let group = groups.group_of("fraud_scorer");
+
+        // Assert
+        assert_eq!(group.unwrap().name, "fraud");
+        assert!(groups.group_of("titanic_model").is_none());
+    }
+
+    #[test]
+    fn fails_to_create_deployment_groups_when_they_are_invalid() {
+        // Act
+        let duplicated_group =
+            DeploymentGroups::new(vec![group("fraud", &["a"]), group("fraud", &["b"])]);
+        let empty_group = DeploymentGroups::new(vec![group("fraud", &[])]);
+        let shared_model =
+            DeploymentGroups::new(vec![group("fraud", &["a"]), group("churn", &["a"])]);
+
+        // Assert
+        assert!(duplicated_group.is_err());
+        assert!(empty_group.is_err());
+        assert!(shared_model.is_err());
+    }
+}
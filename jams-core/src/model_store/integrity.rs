@@ -0,0 +1,141 @@
+use crate::model_store::common::hash_artifact;
+use crate::model_store::storage::ModelName;
+use crate::model_store::ModelStore;
+use std::path::Path;
+
+/// The outcome of re-verifying the cached artifacts of the loaded models.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct IntegrityReport {
+    /// Models whose cached artifact still matches the checksum recorded when it was loaded.
+    pub verified: Vec<ModelName>,
+    /// Models whose cached artifact was missing or changed, and which were reloaded from the model store.
+    pub reloaded: Vec<ModelName>,
+    /// Models whose cached artifact was missing or changed, and which could not be reloaded.
+    pub failed: Vec<ModelName>,
+    /// Models which were skipped because no checksum was recorded when they were loaded.
+    pub skipped: Vec<ModelName>,
+}
+
+/// Re-hashes the cached artifacts of all the loaded models and compares them with the checksums
+/// recorded when the models were loaded.
+///
+/// A model whose artifact is missing or has a different checksum, e.g. because of disk issues or
+/// manual tampering, is reloaded from the model store. Every mismatch is logged as an error so that
+/// it can be alerted on.
+///
+/// # Arguments
+///
+/// * `model_store` - The model store holding the loaded models.
+///
+/// # Returns
+///
+/// * `Ok(IntegrityReport)` - The outcome of the verification for every loaded model.
+/// * `Err(anyhow::Error)` - If the loaded models could not be listed.
+pub async fn verify_models(model_store: &ModelStore) -> anyhow::Result<IntegrityReport> {
+    let mut report = IntegrityReport::default();
+    for metadata in model_store.get_models()? {
+        if metadata.artifact_sha256.is_empty() {
+            report.skipped.push(metadata.name);
+            continue;
+        }
+
+        let path = metadata.path.clone();
+        let hash =
+            tokio::task::spawn_blocking(move || hash_artifact(Path::new(path.as_str()))).await?;
+        let reason = match hash {
+            Ok(hash) if hash == metadata.artifact_sha256 => {
+                report.verified.push(metadata.name);
+                continue;
+            }
+            Ok(hash) => format!(
+                "checksum {} does not match {} recorded at load time",
+                hash, metadata.artifact_sha256
+            ),
+            Err(e) => format!("artifact is unavailable: {}", e),
+        };
+
+        // the model may have been updated by polling while its artifact was being hashed
+        let current_sha256 = model_store
+            .get_model(metadata.name.clone())
+            .map(|model| model.info.artifact_sha256.clone());
+        if current_sha256.as_deref() != Some(metadata.artifact_sha256.as_str()) {
+            continue;
+        }
+
+        tracing::error!(
+            "Integrity check failed for model {} at {} 🚨: {}",
+            metadata.name,
+            metadata.path,
+            reason
+        );
+        match model_store.update_model(metadata.name.clone()).await {
+            Ok(_) => {
+                tracing::info!(
+                    "Reloaded model {} after failed integrity check ✅",
+                    metadata.name
+                );
+                report.reloaded.push(metadata.name);
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to reload model {} after failed integrity check 🚨: {}",
+                    metadata.name,
+                    e
+                );
+                report.failed.push(metadata.name);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_store::local::filesystem::LocalModelStore;
+    use crate::model_store::storage::Storage;
+
+    #[tokio::test]
+    async fn successfully_verify_unchanged_models() {
+        // Arrange
+        let model_store = LocalModelStore::new("tests/model_storage/model_store".to_string())
+            .await
+            .unwrap();
+        let model_store = ModelStore::Local(model_store);
+
+        // Act
+        let report = verify_models(&model_store).await.unwrap();
+
+        // Assert
+        assert!(!report.verified.is_empty());
+        assert!(report.reloaded.is_empty());
+        assert!(report.failed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn successfully_reload_model_when_cached_artifact_is_corrupted() {
+        // Arrange
+        let model_store = LocalModelStore::new("tests/model_storage/model_store".to_string())
+            .await
+            .unwrap();
+        let model_name = "my_awesome_reg_model".to_string();
+        let path = model_store
+            .get_model(model_name.clone())
+            .unwrap()
+            .info
+            .path
+            .clone();
+        std::fs::write(path, "corrupted").unwrap();
+        let model_store = ModelStore::Local(model_store);
+
+        // Act
+        let report = verify_models(&model_store).await.unwrap();
+
+        // Assert
+        assert_eq!(report.reloaded, vec![model_name.clone()]);
+        assert!(report.failed.is_empty());
+        let reloaded = verify_models(&model_store).await.unwrap();
+        assert!(reloaded.verified.contains(&model_name));
+    }
+}
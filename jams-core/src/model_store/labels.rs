@@ -0,0 +1,262 @@
+use crate::model_store::storage::{Metadata, ModelName};
+use dashmap::DashMap;
+use std::collections::BTreeMap;
+
+/// Maximum length in bytes of a label key or value.
+pub const MAX_LABEL_LENGTH: usize = 256;
+
+/// Labels set on the loaded models at runtime, e.g. through the admin API.
+///
+/// They are kept separately from the models so that they survive model updates, and take precedence
+/// over the labels from the `labels.yaml` file of the model tarball.
+#[derive(Default)]
+pub struct ModelLabels(DashMap<ModelName, BTreeMap<String, String>>);
+
+impl ModelLabels {
+    /// Sets and removes labels of a model.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - The name of the model.
+    /// * `labels` - The labels to set, replacing the value of existing labels with the same key.
+    /// * `remove` - The keys of the labels to remove.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If a label key or value is invalid.
+    pub fn update(
+        &self,
+        model_name: ModelName,
+        labels: BTreeMap<String, String>,
+        remove: &[String],
+    ) -> anyhow::Result<()> {
+        for (key, value) in labels.iter() {
+            validate_label(key, value)?;
+        }
+
+        let mut model_labels = self.0.entry(model_name).or_default();
+        for key in remove {
+            model_labels.insert(key.clone(), String::new());
+        }
+        model_labels.extend(labels);
+        Ok(())
+    }
+
+    /// Forgets the labels of a model once it is deleted.
+    pub fn clear(&self, model_name: &str) {
+        self.0.remove(model_name);
+    }
+
+    /// Forgets the labels of the models which are no longer loaded, e.g. because they were deleted from
+    /// the model store without going through the manager.
+    pub fn prune(&self, is_loaded: impl Fn(&str) -> bool) {
+        self.0.retain(|model_name, _| is_loaded(model_name));
+    }
+
+    /// Returns the value of a label of a model, i.e. the value set at runtime, else the value from the
+    /// labels of its artifact. `None` if the label is not set or was removed at runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - The name of the model.
+    /// * `key` - The key of the label.
+    /// * `artifact_labels` - The labels from the `labels.yaml` file of the model tarball.
+    pub fn value_of(
+        &self,
+        model_name: &str,
+        key: &str,
+        artifact_labels: &BTreeMap<String, String>,
+    ) -> Option<String> {
+        let runtime_value = self
+            .0
+            .get(model_name)
+            .and_then(|labels| labels.get(key).cloned());
+        match runtime_value {
+            // an empty value marks a label which was removed
+            Some(value) => (!value.is_empty()).then_some(value),
+            None => artifact_labels.get(key).cloned(),
+        }
+    }
+
+    /// Applies the labels set at runtime to the metadata of a model.
+    pub fn annotate(&self, mut metadata: Metadata) -> Metadata {
+        if let Some(labels) = self.0.get(metadata.name.as_str()) {
+            for (key, value) in labels.iter() {
+                // an empty value marks a label which was removed
+                match value.is_empty() {
+                    true => metadata.labels.remove(key),
+                    false => metadata.labels.insert(key.clone(), value.clone()),
+                };
+            }
+        }
+        metadata
+    }
+}
+
+/// Checks that a label can be set and selected, i.e. `key:value` is unambiguous in a selector.
+fn validate_label(key: &str, value: &str) -> anyhow::Result<()> {
+    if key.is_empty() || value.is_empty() {
+        anyhow::bail!("Label key and value must not be empty ❌")
+    }
+    if key.len() > MAX_LABEL_LENGTH || value.len() > MAX_LABEL_LENGTH {
+        anyhow::bail!(
+            "Label key and value must be at most {} bytes ❌",
+            MAX_LABEL_LENGTH
+        )
+    }
+    if key.contains([':', ',']) || value.contains(',') {
+        anyhow::bail!("Label key must not contain ':' or ',' and value must not contain ',' ❌")
+    }
+    Ok(())
+}
+
+/// Selects models by their labels.
+///
+/// A selector is a comma separated list of requirements which must all be met, where each requirement
+/// is either `key:value`, which requires the label to have the value, or `key`, which requires the label
+/// to be set, e.g. `team:risk,tier`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelSelector(Vec<(String, Option<String>)>);
+
+impl LabelSelector {
+    /// Parses a label selector.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - The selector, e.g. `team:risk,tier`.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If a requirement has an empty key or value.
+    pub fn parse(selector: &str) -> anyhow::Result<Self> {
+        let mut requirements = Vec::new();
+        for requirement in selector.split(',') {
+            let requirement = match requirement.split_once(':') {
+                Some((key, value)) => (key.trim(), Some(value.trim())),
+                None => (requirement.trim(), None),
+            };
+            match requirement {
+                ("", _) | (_, Some("")) => {
+                    anyhow::bail!(
+                        "Invalid label selector '{}', expected key:value or key ❌",
+                        selector
+                    )
+                }
+                (key, value) => requirements.push((key.to_string(), value.map(str::to_string))),
+            }
+        }
+        Ok(LabelSelector(requirements))
+    }
+
+    /// Returns true if the labels meet every requirement of the selector.
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        self.0
+            .iter()
+            .all(|(key, value)| match (labels.get(key), value) {
+                (Some(label), Some(value)) => label == value,
+                (Some(_), None) => true,
+                (None, _) => false,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_match_labels_with_selector() {
+        // Arrange
+        let labels = BTreeMap::from([
+            ("team".to_string(), "risk".to_string()),
+            ("tier".to_string(), "1".to_string()),
+        ]);
+
+        // Act
+        let selectors = [
+            "team:risk",
+            "team:risk,tier",
+            " tier : 1 ",
+            "team:fraud",
+            "owner",
+        ]
+        .map(|selector| LabelSelector::parse(selector).unwrap().matches(&labels));
+
+        // Assert
+        assert_eq!(selectors, [true, true, true, false, false]);
+    }
+
+    #[test]
+    fn fails_to_parse_label_selector_when_requirement_is_empty() {
+        for selector in ["", "team:", ":risk", "team:risk,"] {
+            // assert result is err
+            assert!(LabelSelector::parse(selector).is_err(), "{}", selector)
+        }
+    }
+
+    #[test]
+    fn successfully_resolve_label_value_from_runtime_and_artifact_labels() {
+        // Arrange
+        let model_labels = ModelLabels::default();
+        let artifact_labels = BTreeMap::from([
+            ("team".to_string(), "risk".to_string()),
+            ("tier".to_string(), "1".to_string()),
+        ]);
+        model_labels
+            .update(
+                "my_model".to_string(),
+                BTreeMap::from([("team".to_string(), "fraud".to_string())]),
+                &["tier".to_string()],
+            )
+            .unwrap();
+
+        // Act
+        let values = ["team", "tier", "owner"]
+            .map(|key| model_labels.value_of("my_model", key, &artifact_labels));
+
+        // Assert
+        assert_eq!(values, [Some("fraud".to_string()), None, None]);
+    }
+
+    #[test]
+    fn fails_to_update_labels_when_label_is_invalid() {
+        // Arrange
+        let model_labels = ModelLabels::default();
+
+        // Act
+        let result = model_labels.update(
+            "my_model".to_string(),
+            BTreeMap::from([("team:name".to_string(), "risk".to_string())]),
+            &[],
+        );
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn successfully_forget_labels_of_models_which_are_no_longer_loaded() {
+        // Arrange
+        let model_labels = ModelLabels::default();
+        let labels = BTreeMap::from([("team".to_string(), "risk".to_string())]);
+        for model_name in ["my_model", "deleted_model"] {
+            model_labels
+                .update(model_name.to_string(), labels.clone(), &[])
+                .unwrap();
+        }
+
+        // Act
+        model_labels.prune(|model_name| model_name == "my_model");
+
+        // Assert
+        let artifact_labels = BTreeMap::new();
+        assert_eq!(
+            model_labels.value_of("my_model", "team", &artifact_labels),
+            Some("risk".to_string())
+        );
+        assert_eq!(
+            model_labels.value_of("deleted_model", "team", &artifact_labels),
+            None
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::model_store::storage::{Model, ModelName};
+use arc_swap::ArcSwap;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// The models loaded in a model store.
+///
+/// Reads load an immutable snapshot of the models, which is replaced as a whole on every write (read-copy-update).
+/// Predictions and metadata requests therefore never wait on a lock, even while polling swaps in new versions of
+/// the models. Writes are serialized and copy the map of models, which is cheap as the models themselves are shared.
+pub struct LoadedModels {
+    snapshot: ArcSwap<HashMap<ModelName, Arc<Model>>>,
+    writer: Mutex<()>,
+}
+
+impl Default for LoadedModels {
+    fn default() -> Self {
+        LoadedModels {
+            snapshot: ArcSwap::from_pointee(HashMap::new()),
+            writer: Mutex::new(()),
+        }
+    }
+}
+
+impl From<DashMap<ModelName, Arc<Model>>> for LoadedModels {
+    fn from(models: DashMap<ModelName, Arc<Model>>) -> Self {
+        LoadedModels {
+            snapshot: ArcSwap::from_pointee(models.into_iter().collect()),
+            writer: Mutex::new(()),
+        }
+    }
+}
+
+impl LoadedModels {
+    /// Returns a model by its name.
+    pub fn get(&self, model_name: &str) -> Option<Arc<Model>> {
+        self.snapshot.load().get(model_name).cloned()
+    }
+
+    /// Returns true if a model with the name is loaded.
+    pub fn contains_key(&self, model_name: &str) -> bool {
+        self.snapshot.load().contains_key(model_name)
+    }
+
+    /// Returns the number of loaded models.
+    pub fn len(&self) -> usize {
+        self.snapshot.load().len()
+    }
+
+    /// Returns true if no model is loaded.
+    pub fn is_empty(&self) -> bool {
+        self.snapshot.load().is_empty()
+    }
+
+    /// Returns the loaded models as of now. Later writes are not reflected in the returned snapshot.
+    pub fn snapshot(&self) -> Arc<HashMap<ModelName, Arc<Model>>> {
+        self.snapshot.load_full()
+    }
+
+    /// Loads a model, replacing the model with the same name if any.
+    ///
+    /// # Returns
+    ///
+    /// The replaced model, if any.
+    pub fn insert(&self, model_name: ModelName, model: Arc<Model>) -> Option<Arc<Model>> {
+        self.update(|models| models.insert(model_name, model))
+    }
+
+    /// Loads models at once, replacing the models with the same names if any, so that readers see either none or
+    /// all of them, e.g. the models of a deployment group.
+    ///
+    /// # Returns
+    ///
+    /// The replaced models.
+    pub fn insert_all(&self, loaded: Vec<(ModelName, Arc<Model>)>) -> Vec<Arc<Model>> {
+        self.update(|models| {
+            loaded
+                .into_iter()
+                .filter_map(|(model_name, model)| models.insert(model_name, model))
+                .collect()
+        })
+    }
+
+    /// Unloads a model.
+    ///
+    /// # Returns
+    ///
+    /// The name and the unloaded model, or `None` if the model is not loaded.
+    pub fn remove(&self, model_name: &str) -> Option<(ModelName, Arc<Model>)> {
+        self.update(|models| models.remove_entry(model_name))
+    }
+
+    /// Publishes a copy of the current snapshot modified by `write`. Writers are serialized, so that no write is
+    /// lost to a concurrent one.
+    fn update<T>(&self, write: impl FnOnce(&mut HashMap<ModelName, Arc<Model>>) -> T) -> T {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let mut models = HashMap::clone(&self.snapshot.load());
+        let result = write(&mut models);
+        self.snapshot.store(Arc::new(models));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::frameworks::LIGHTGBM;
+    use crate::model::lightgbm::LightGBM;
+    use crate::model::Predictor;
+    use chrono::Utc;
+
+    fn lightgbm_model(model_name: &str) -> Arc<Model> {
+        let path = "tests/model_storage/models/lightgbm-my_awesome_reg_model.txt";
+        let predictor = LightGBM::load(path).unwrap();
+        Arc::new(Model::new(
+            Arc::new(Predictor::LightGBM(predictor)),
+            model_name.to_string(),
+            LIGHTGBM,
+            path.to_string(),
+            Utc::now().to_rfc3339(),
+        ))
+    }
+
+    #[test]
+    fn successfully_insert_models_at_once() {
+        // Arrange
+        let models = LoadedModels::default();
+        let current = lightgbm_model("fraud_encoder");
+        models.insert("fraud_encoder".to_string(), current.clone());
+        let before = models.snapshot();
+
+        // Act
+        let replaced = models.insert_all(vec![
+            ("fraud_encoder".to_string(), lightgbm_model("fraud_encoder")),
+            ("fraud_scorer".to_string(), lightgbm_model("fraud_scorer")),
+        ]);
+
+        // Assert
+        assert_eq!(replaced.len(), 1);
+        assert!(Arc::ptr_eq(&replaced[0], &current));
+        assert_eq!(before.len(), 1);
+        assert_eq!(models.len(), 2);
+        assert!(!Arc::ptr_eq(
+            &models.get("fraud_encoder").unwrap(),
+            &current
+        ));
+    }
+
+    #[test]
+    fn successfully_keep_snapshot_unchanged_while_models_are_written() {
+        // Arrange
+        let models = LoadedModels::default();
+        models.insert("my_model".to_string(), lightgbm_model("my_model"));
+
+        // Act
+        let before = models.snapshot();
+        models.insert(
+            "my_other_model".to_string(),
+            lightgbm_model("my_other_model"),
+        );
+        let removed = models.remove("my_model");
+        let missing = models.remove("my_model");
+
+        // Assert
+        assert_eq!(before.len(), 1);
+        assert!(before.contains_key("my_model"));
+        assert_eq!(removed.unwrap().0, "my_model");
+        assert!(missing.is_none());
+        assert_eq!(models.len(), 1);
+        assert!(models.get("my_other_model").is_some());
+        assert!(!models.contains_key("my_model"));
+    }
+}
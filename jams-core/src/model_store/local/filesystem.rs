@@ -1,17 +1,22 @@
 use crate::model_store::common::{
-    cleanup, unpack_tarball, DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX,
+    cleanup, tarball_version, unpack_tarball, DOWNLOADED_MODELS_DIRECTORY_NAME_PREFIX,
 };
+use crate::model_store::gc::OwnerLock;
+use crate::model_store::loaded::LoadedModels;
+use crate::model_store::registry::{restore_models, RegistryState};
 use crate::model_store::storage::{
-    append_model_format, extract_framework, load_models, load_predictor, Metadata, Model,
-    ModelName, Storage,
+    append_model_format, extract_framework, is_selected, load_models, load_predictor,
+    prepare_model, revalidate_models, schedule_teardown, Metadata, Model, ModelName,
+    PendingUpdates, PollOptions, Storage,
 };
+use crate::{Error, Result};
 use async_trait::async_trait;
 use chrono::Utc;
-use dashmap::mapref::one::Ref;
 use dashmap::DashMap;
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::Path;
 use std::sync::Arc;
-use std::time::Duration;
 use uuid::Uuid;
 
 /// A local model store that manages models stored in a specified directory.
@@ -21,15 +26,22 @@ use uuid::Uuid;
 /// Torch, CatBoost, and LightGBM.
 ///
 /// # Fields
-/// - `models` (DashMap<ModelName, Arc&ltModel&gt>): A thread-safe map of model names to their respective Model struct instances.
+/// - `models` (LoadedModels): The loaded models, read from a snapshot which is replaced on every write.
 /// - `model_dir` (String): The directory where models are stored.
 pub struct LocalModelStore {
-    /// A thread-safe map of model names to their corresponding models.
-    pub models: Arc<DashMap<ModelName, Arc<Model>>>,
+    /// The loaded models, read from a snapshot which is replaced on every write.
+    pub models: Arc<LoadedModels>,
     /// Directory on the file system containing models in .tar.gz format
     pub local_model_store_dir: String,
     /// Temporary directory in which models are stored and read from. This is cleaned up when object is dropped
     temp_model_dir: String,
+    /// Keeps the temporary directory when the object is dropped so that it can be restored from the registry state
+    retain_model_dir: bool,
+    /// Lock held on the model store directory while the model store is alive, so that other processes do not
+    /// collect it as an orphaned directory
+    _owner: OwnerLock,
+    /// Models whose new version is being unpacked and loaded by the poller
+    pending_updates: Arc<PendingUpdates>,
 }
 
 impl LocalModelStore {
@@ -45,6 +57,34 @@ impl LocalModelStore {
     /// - `Err(anyhow::Error)`: If there was an error creating the instance.
     ///
     pub async fn new(local_model_store_dir: String) -> anyhow::Result<Self> {
+        Self::create(local_model_store_dir, None).await
+    }
+
+    /// Creates a new `LocalModelStore` instance which only loads the priority models.
+    ///
+    /// The other tarballs are not unpacked, so that the priority models can serve predictions in seconds
+    /// while the rest of the model store is loaded in the background by the manager.
+    ///
+    /// # Arguments
+    /// - `local_model_store_dir` (String): The directory where models are stored.
+    /// - `priority_models` (Vec&ltModelName&gt): The names of the models to load, without the framework prefix.
+    ///
+    /// # Returns
+    /// - `Ok(LocalModelStore)`: If the instance was successfully created.
+    /// - `Err(anyhow::Error)`: If there was an error creating the instance.
+    ///
+    pub async fn with_priority_models(
+        local_model_store_dir: String,
+        priority_models: Vec<ModelName>,
+    ) -> anyhow::Result<Self> {
+        Self::create(local_model_store_dir, Some(priority_models)).await
+    }
+
+    /// Creates a new `LocalModelStore` instance which loads the given models, or every model if `None`.
+    async fn create(
+        local_model_store_dir: String,
+        model_names: Option<Vec<ModelName>>,
+    ) -> anyhow::Result<Self> {
         // Directory, which stores the unpacked
         let temp_model_dir = format!(
             "{}/{}_{}",
@@ -53,7 +93,14 @@ impl LocalModelStore {
             Uuid::new_v4(),
         );
 
-        let models = match fetch_models(local_model_store_dir.clone(), temp_model_dir.clone()).await
+        let owner = OwnerLock::acquire(Path::new(&temp_model_dir))?;
+
+        let models = match fetch_models(
+            local_model_store_dir.clone(),
+            temp_model_dir.clone(),
+            model_names,
+        )
+        .await
         {
             Ok(models) => models,
             Err(e) => {
@@ -63,11 +110,103 @@ impl LocalModelStore {
         };
 
         Ok(LocalModelStore {
-            models: Arc::new(models),
+            models: Arc::new(LoadedModels::from(models)),
             local_model_store_dir,
             temp_model_dir,
+            retain_model_dir: false,
+            _owner: owner,
+            pending_updates: Arc::new(PendingUpdates::default()),
         })
     }
+
+    /// Creates a new `LocalModelStore` instance which is restored from the registry state if possible.
+    ///
+    /// If the registry state is valid, the models are loaded from the directory in which they were
+    /// unpacked before the last shutdown. Otherwise, the models are unpacked as in `LocalModelStore::new`.
+    /// The temporary directory is kept when the model store is dropped so that it can be reused.
+    ///
+    /// # Arguments
+    ///
+    /// * `local_model_store_dir` - A `String` specifying the directory where models are stored.
+    /// * `state` - The registry state saved by a previous run, if any.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new `LocalModelStore` instance or an error if the models fail to load.
+    pub async fn with_registry_state(
+        local_model_store_dir: String,
+        state: Option<RegistryState>,
+    ) -> anyhow::Result<Self> {
+        let mut model_store = match restore_models(state, local_model_store_dir.as_str()).await {
+            Some((temp_model_dir, models)) => LocalModelStore {
+                models: Arc::new(LoadedModels::from(models)),
+                local_model_store_dir,
+                _owner: OwnerLock::acquire(Path::new(&temp_model_dir))?,
+                temp_model_dir,
+                retain_model_dir: false,
+                pending_updates: Arc::new(PendingUpdates::default()),
+            },
+            None => LocalModelStore::new(local_model_store_dir).await?,
+        };
+        model_store.retain_model_dir = true;
+
+        Ok(model_store)
+    }
+
+    /// Captures the registry state of the models loaded in the model store.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the model artifacts cannot be hashed.
+    pub fn registry_state(&self) -> anyhow::Result<RegistryState> {
+        RegistryState::capture(
+            self.local_model_store_dir.clone(),
+            self.temp_model_dir.clone(),
+            &self.models,
+        )
+    }
+
+    /// Returns the directory into which the models are unpacked.
+    pub fn model_store_dir(&self) -> &str {
+        self.temp_model_dir.as_str()
+    }
+
+    /// Reads a stored version of a tarball. A directory keeps only the latest version of each tarball, whose
+    /// version is its modification time, see `tarball_version`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tarball_name` - The file name of the tarball in the model directory.
+    /// * `version` - The version of the tarball, or `None` for its latest version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tarball cannot be read or the version is not its latest version.
+    pub async fn read_tarball(
+        &self,
+        tarball_name: &str,
+        version: Option<&str>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let tarball_path = Path::new(self.local_model_store_dir.as_str()).join(tarball_name);
+        if let Some(version) = version {
+            let latest = tarball_version(tarball_path.to_string_lossy().as_ref());
+            if latest.as_deref() != Some(version) {
+                anyhow::bail!(
+                    "Version {} of tarball {} is not stored, local model stores keep only the latest version {:?} ❌",
+                    version,
+                    tarball_name,
+                    latest
+                )
+            }
+        }
+        match tokio::fs::read(&tarball_path).await {
+            Ok(data) => Ok(data),
+            Err(e) => {
+                tracing::error!("Failed to read tarball {:?} ❌: {}", tarball_path, e);
+                anyhow::bail!("Failed to read tarball {:?} ❌: {}", tarball_path, e)
+            }
+        }
+    }
 }
 
 /// Implements the `Drop` trait for `LocalModelStore`.
@@ -82,7 +221,9 @@ impl LocalModelStore {
 ///
 impl Drop for LocalModelStore {
     fn drop(&mut self) {
-        cleanup(self.temp_model_dir.clone())
+        if !self.retain_model_dir {
+            cleanup(self.temp_model_dir.clone())
+        }
     }
 }
 
@@ -104,20 +245,23 @@ impl Storage for LocalModelStore {
     /// * The model fails to load.
     ///
     #[tracing::instrument(skip(self))]
-    async fn add_model(&self, model_name: ModelName) -> anyhow::Result<()> {
+    async fn add_model(&self, model_name: ModelName) -> Result<()> {
         let lms_model_name = format!("{}.tar.gz", model_name.clone());
 
         let local_model_store_path = format!("{}/{}", self.local_model_store_dir, lms_model_name);
         unpack_tarball(
             local_model_store_path.as_str(),
             self.temp_model_dir.as_str(),
-        )?;
+        )
+        .map_err(Error::Store)?;
 
         // Extract model framework
         let model_framework = match extract_framework(model_name.clone()) {
             None => {
                 tracing::error!("Failed to extract framework from path");
-                anyhow::bail!("Failed to extract framework from path");
+                return Err(Error::Store(anyhow::anyhow!(
+                    "Failed to extract framework from path"
+                )));
             }
             Some(model_framework) => model_framework,
         };
@@ -133,7 +277,9 @@ impl Storage for LocalModelStore {
                     match model_name.strip_prefix(format!("{}-", model_framework).as_str()) {
                         None => {
                             tracing::error!("Failed to sanitize model name");
-                            anyhow::bail!("Failed to sanitize model name");
+                            return Err(Error::Store(anyhow::anyhow!(
+                                "Failed to sanitize model name"
+                            )));
                         }
                         Some(name) => name.to_string(),
                     };
@@ -146,12 +292,18 @@ impl Storage for LocalModelStore {
                     model_path.to_string(),
                     now.to_rfc2822(),
                 );
-                self.models.insert(sanitized_model_name, Arc::new(model));
+                let model =
+                    prepare_model(model, self.models.get(&sanitized_model_name).as_deref()).await?;
+                if let Some(replaced) = self.models.insert(sanitized_model_name, Arc::new(model)) {
+                    schedule_teardown(replaced);
+                }
                 Ok(())
             }
             Err(e) => {
                 tracing::error!("Failed to add new model: {e}");
-                anyhow::bail!("Failed to add new model: {e}")
+                Err(Error::Store(
+                    anyhow::Error::from(e).context("Failed to add new model"),
+                ))
             }
         }
     }
@@ -172,7 +324,7 @@ impl Storage for LocalModelStore {
     /// * The model fails to load.
     ///
     #[tracing::instrument(skip(self))]
-    async fn update_model(&self, model_name: ModelName) -> anyhow::Result<()> {
+    async fn update_model(&self, model_name: ModelName) -> Result<()> {
         // By calling remove on the hashmap, the object is returned on success/
         // We use the returned object, in this case the model to extract the framework and model path
         match self.models.remove(model_name.as_str()) {
@@ -181,33 +333,40 @@ impl Storage for LocalModelStore {
                     "Failed to update as the specified model {} does not exist",
                     model_name
                 );
-                anyhow::bail!(
-                    "Failed to update as the specified model {} does not exist",
-                    model_name
-                )
+                return Err(Error::ModelNotFound(model_name));
             }
             Some(model) => {
                 let (model_framework, model_path) =
                     (model.1.info.framework, model.1.info.path.as_str());
 
                 // Prepare the actual name from model_name
-                let model = format!("{}-{}.tar.gz", model_framework, model_name);
+                let tarball_name = format!("{}-{}.tar.gz", model_framework, model_name);
 
                 // unpack
-                let model_tar_path = format!("{}/{}", self.local_model_store_dir, model);
+                let model_tar_path = format!("{}/{}", self.local_model_store_dir, tarball_name);
                 match unpack_tarball(model_tar_path.as_str(), self.temp_model_dir.as_str()) {
                     Ok(_) => match load_predictor(model_framework, model_path).await {
                         Ok(predictor) => {
                             let now = Utc::now();
-                            let model = Model::new(
+                            let updated = Model::new(
                                 predictor,
                                 model_name.clone(),
                                 model_framework,
                                 model_path.to_string(),
                                 now.to_rfc2822(),
                             );
-                            self.models.insert(model_name.clone(), Arc::new(model));
-                            Ok(())
+                            // the current version is kept if the new version fails to initialize or misses its acceptance criteria
+                            match prepare_model(updated, Some(&model.1)).await {
+                                Ok(updated) => {
+                                    self.models.insert(model_name.clone(), Arc::new(updated));
+                                    schedule_teardown(model.1);
+                                    Ok(())
+                                }
+                                Err(e) => {
+                                    self.models.insert(model_name.clone(), model.1);
+                                    Err(e)
+                                }
+                            }
                         }
                         Err(e) => {
                             tracing::error!(
@@ -215,16 +374,17 @@ impl Storage for LocalModelStore {
                                 model_name,
                                 e
                             );
-                            anyhow::bail!(
-                                "Failed to update the specified model {}: {}",
-                                model_name,
-                                e
-                            )
+                            Err(Error::Store(anyhow::Error::from(e).context(format!(
+                                "Failed to update the specified model {}",
+                                model_name
+                            ))))
                         }
                     },
                     Err(e) => {
                         tracing::error!("Failed to update model ❌: {}", e);
-                        anyhow::bail!("Failed to update model ❌: {}", e);
+                        Err(Error::Store(
+                            anyhow::Error::from(e).context("Failed to update model ❌"),
+                        ))
                     }
                 }
             }
@@ -242,11 +402,11 @@ impl Storage for LocalModelStore {
     /// # Returns
     ///
     /// This function returns an `Option`:
-    /// * `Some(Ref<ModelName, Arc<Model>>)` if the model exists.
+    /// * `Some(Arc<Model>)` if the model exists.
     /// * `None` if the model does not exist.
     ///
     #[tracing::instrument(skip(self))]
-    fn get_model(&self, model_name: ModelName) -> Option<Ref<ModelName, Arc<Model>>> {
+    fn get_model(&self, model_name: ModelName) -> Option<Arc<Model>> {
         self.models.get(model_name.as_str())
     }
 
@@ -256,14 +416,15 @@ impl Storage for LocalModelStore {
     ///
     /// # Returns
     ///
-    /// This function returns an `anyhow::Result` containing a vector of `Metadata`.
+    /// This function returns a `jams_core::Result` containing a vector of `Metadata`.
     ///
     #[tracing::instrument(skip(self))]
-    fn get_models(&self) -> anyhow::Result<Vec<Metadata>> {
+    fn get_models(&self) -> Result<Vec<Metadata>> {
         let model: Vec<Metadata> = self
             .models
-            .iter()
-            .map(|f| f.value().info.to_owned())
+            .snapshot()
+            .values()
+            .map(|model| self.pending_updates.annotate(model.info.to_owned()))
             .collect();
         Ok(model)
     }
@@ -280,68 +441,97 @@ impl Storage for LocalModelStore {
     ///
     /// This function returns an error if the specified model does not exist in the store.
     #[tracing::instrument(skip(self))]
-    fn delete_model(&self, model_name: ModelName) -> anyhow::Result<()> {
-        match self.models.remove(&model_name) {
+    fn delete_model(&self, model_name: ModelName) -> Result<()> {
+        match self.models.remove(model_name.as_str()) {
             None => {
                 tracing::error!(
                     "Failed to delete model as the specified model {} does not exist",
                     model_name
                 );
-                anyhow::bail!(
-                    "Failed to delete model as the specified model {} does not exist",
-                    model_name
-                )
+                Err(Error::ModelNotFound(model_name))
+            }
+            Some((_, model)) => {
+                schedule_teardown(model);
+                Ok(())
             }
-            Some(_) => Ok(()),
         }
     }
 
     /// Periodically polls the local model store to fetch and update models.
     ///
-    /// This asynchronous function waits for a specified time interval, then attempts to fetch models
-    /// from the local model store, and updates the internal model cache (`self.models`). The polling
-    /// interval ensures that the model store is regularly updated with new models, if available.
+    /// This asynchronous function waits for a specified time interval, then compares the modification
+    /// time of each tarball in the local model store with the version of the loaded model. Only new or
+    /// updated models are unpacked and loaded. Updated models keep serving their current version, which
+    /// is reported as a pending update in the metadata, and are only swapped in the internal model cache
+    /// (`self.models`) once the new version has loaded.
     ///
     /// # Arguments
     ///
-    /// * `interval` - A `Duration` representing the time interval between each polling operation.
+    /// * `options` - The interval and jitter between each polling operation, and the maximum number of
+    ///   tarballs downloaded at the same time.
     ///
     /// # Returns
     ///
     /// * `Ok(())` - If the models were successfully fetched and updated in the model store.
-    /// * `Err(anyhow::Error)` - If an error occurs during the fetch or update process, such as when
+    /// * `Err(Error::Store)` - If an error occurs during the fetch or update process, such as when
     ///   the models fail to be retrieved.
     ///
     #[tracing::instrument(skip(self))]
-    async fn poll(&self, interval: Duration) -> anyhow::Result<()> {
-        // poll every n time interval
-        tokio::time::sleep(interval).await;
+    async fn poll(&self, options: &PollOptions) -> Result<()> {
+        // wait for the interval plus a random jitter
+        tokio::time::sleep(options.next_delay()).await;
 
         tracing::info!("Polling model store ⌛");
-        let models = match fetch_models(
-            self.local_model_store_dir.clone(),
-            self.temp_model_dir.clone(),
-        )
-        .await
-        {
-            Ok(models) => {
-                tracing::info!("Successfully fetched valid models from S3 ✅");
-                models
-            }
+        let versions = match get_versions(self.local_model_store_dir.as_str()) {
+            Ok(versions) => versions,
             Err(e) => {
                 tracing::error!("Failed to fetch models ❌ - {}", e.to_string());
-                anyhow::bail!("Failed to fetch models ❌ - {}", e.to_string());
+                return Err(Error::Store(
+                    anyhow::Error::from(e).context("Failed to fetch models ❌"),
+                ));
             }
         };
 
-        for (model_name, model) in models {
-            self.models.insert(model_name, model);
-        }
+        revalidate_models(
+            &self.models,
+            &self.pending_updates,
+            versions,
+            self.temp_model_dir.as_str(),
+            options,
+            |tarball_name| async move {
+                let tarball_path = format!("{}/{}", self.local_model_store_dir, tarball_name);
+                unpack_tarball(tarball_path.as_str(), self.temp_model_dir.as_str())
+            },
+        )
+        .await;
 
         Ok(())
     }
 }
 
+/// Returns the version of each tarball in the local model store, keyed by the tarball name.
+pub(crate) fn get_versions(
+    local_model_store_dir: &str,
+) -> anyhow::Result<BTreeMap<String, Option<String>>> {
+    let mut versions = BTreeMap::new();
+    for entry in fs::read_dir(local_model_store_dir)? {
+        let path = entry?.path();
+        match (
+            path.file_name().and_then(|name| name.to_str()),
+            path.to_str(),
+        ) {
+            (Some(tarball_name), Some(tarball_path)) => {
+                versions.insert(tarball_name.to_string(), tarball_version(tarball_path));
+            }
+            _ => {
+                tracing::error!("failed to convert file path to str ❌");
+                anyhow::bail!("failed to convert file path to str ❌")
+            }
+        }
+    }
+    Ok(versions)
+}
+
 /// Fetches and loads models from the local model store directory by unpacking tarball files.
 ///
 /// This asynchronous function reads the local model store directory, unpacks model tarballs
@@ -363,6 +553,7 @@ impl Storage for LocalModelStore {
 async fn fetch_models(
     local_model_store_dir: String,
     temp_model_dir: String,
+    model_names: Option<Vec<ModelName>>,
 ) -> anyhow::Result<DashMap<ModelName, Arc<Model>>> {
     match local_model_store_dir.is_empty() {
         true => {
@@ -382,6 +573,10 @@ async fn fetch_models(
                             }
                             Some(path) => path,
                         };
+                        let tarball_name = entry.file_name().to_string_lossy().to_string();
+                        if !is_selected(tarball_name.as_str(), model_names.as_deref()) {
+                            continue;
+                        }
                         unpack_tarball(tarball_path, temp_model_dir.as_str())?
                     }
                 }
@@ -391,6 +586,11 @@ async fn fetch_models(
                 }
             }
 
+            // the directory is not created by unpacking if none of the selected models are in the model store
+            if model_names.is_some() {
+                fs::create_dir_all(temp_model_dir.as_str())?;
+            }
+
             match load_models(temp_model_dir.clone()).await {
                 Ok(models) => Ok(models),
                 Err(e) => {
@@ -405,9 +605,64 @@ async fn fetch_models(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::custom;
+    use crate::model::input::ModelInput;
+    use crate::model::lifecycle::Lifecycle;
+    use crate::model::output::ModelOutput;
+    use crate::model::predict::Predict;
     use crate::model_store::local::filesystem::LocalModelStore;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::time::Duration;
 
+    static TEARDOWNS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts its teardowns in `TEARDOWNS`.
+    struct CountsTeardowns;
+
+    impl Predict for CountsTeardowns {
+        fn predict(&self, _input: ModelInput) -> crate::Result<ModelOutput> {
+            Ok(ModelOutput {
+                predictions: Default::default(),
+                labels: Default::default(),
+                shapes: Default::default(),
+                texts: Default::default(),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Lifecycle for CountsTeardowns {
+        async fn teardown(&self) -> anyhow::Result<()> {
+            TEARDOWNS.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn successfully_tear_down_deleted_model_once_the_last_request_drops_it() {
+        // Arrange
+        custom::register("counts_teardowns", |_path| Ok(CountsTeardowns)).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("counts_teardowns-my_model.txt"), "").unwrap();
+        let local_model_store = LocalModelStore::new(dir.path().to_str().unwrap().to_string())
+            .await
+            .unwrap();
+        let in_flight = local_model_store.get_model("my_model".to_string()).unwrap();
+
+        // Act
+        local_model_store
+            .delete_model("my_model".to_string())
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let teardowns_while_in_flight = TEARDOWNS.load(Ordering::SeqCst);
+        drop(in_flight);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // Assert
+        assert_eq!(teardowns_while_in_flight, 0);
+        assert_eq!(TEARDOWNS.load(Ordering::SeqCst), 1);
+    }
+
     #[tokio::test]
     async fn successfully_load_models_from_different_frameworks_into_local_model_store() {
         let model_dir = "tests/model_storage/model_store";
@@ -420,6 +675,52 @@ mod tests {
         assert_ne!(local_model_store.unwrap().models.len(), 0);
     }
 
+    #[tokio::test]
+    async fn successfully_load_only_priority_models_into_local_model_store() {
+        let model_dir = "tests/model_storage/model_store";
+
+        // initialize and load the priority models
+        let local_model_store = LocalModelStore::with_priority_models(
+            model_dir.to_string(),
+            vec![
+                "my_awesome_reg_model".to_string(),
+                "model_which_does_not_exist".to_string(),
+            ],
+        )
+        .await
+        .unwrap();
+
+        // assert
+        assert_eq!(local_model_store.models.len(), 1);
+        assert!(local_model_store
+            .models
+            .contains_key("my_awesome_reg_model"));
+    }
+
+    #[tokio::test]
+    async fn successfully_restore_local_model_store_from_registry_state() {
+        let model_dir = "tests/model_storage/model_store";
+
+        // load models and capture the registry state before the model store is dropped
+        let local_model_store = LocalModelStore::with_registry_state(model_dir.to_string(), None)
+            .await
+            .unwrap();
+        let state = local_model_store.registry_state().unwrap();
+        let num_models = local_model_store.models.len();
+        drop(local_model_store);
+
+        // restore models from the registry state
+        let restored =
+            LocalModelStore::with_registry_state(model_dir.to_string(), Some(state.clone()))
+                .await
+                .unwrap();
+
+        // assert
+        assert_eq!(restored.temp_model_dir, state.model_store_dir);
+        assert_eq!(restored.models.len(), num_models);
+        cleanup(restored.temp_model_dir.clone());
+    }
+
     #[tokio::test]
     async fn successfully_get_model_from_local_model_store() {
         let model_dir = "tests/model_storage/model_store";
@@ -478,7 +779,7 @@ mod tests {
         let deletion = local_model_store.delete_model("model_which_does_not_exist".to_string());
 
         // assert
-        assert!(deletion.is_err());
+        assert!(matches!(deletion, Err(Error::ModelNotFound(_))));
     }
 
     #[tokio::test]
@@ -523,7 +824,7 @@ mod tests {
         let update = local_model_store.update_model(incorrect_model_name).await;
 
         // assert
-        assert!(update.is_err());
+        assert!(matches!(update, Err(Error::ModelNotFound(_))));
     }
 
     #[tokio::test]
@@ -0,0 +1,258 @@
+use crate::model_store::storage::ModelName;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Maximum number of distinct missing model names which are tracked, so that clients sending random model names
+/// cannot grow the tracker forever. Once it is reached, the least missed name makes room for each new name.
+pub const MAX_TRACKED_MISSES: usize = 1000;
+
+/// Maximum number of model names suggested for a missing model name.
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// How often a model name which is not loaded was requested.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Miss {
+    count: u64,
+    overestimate: u64,
+    last_missed: DateTime<Utc>,
+}
+
+/// A model name which was requested while no model with that name was loaded.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ModelMiss {
+    /// The requested model name.
+    pub model_name: ModelName,
+    /// Number of requests made to the model name since startup.
+    pub count: u64,
+    /// Upper bound of the requests counted in `count` which were made to other model names. It is 0 unless the
+    /// model name took the place of a less missed one once more model names were missed than are tracked.
+    pub overestimate: u64,
+    /// When the model name was last requested, in RFC 3339 format.
+    pub last_missed: String,
+}
+
+/// Tracks the requests made to models which are not loaded, which are mostly typo'd or stale model names.
+///
+/// At most `MAX_TRACKED_MISSES` model names are tracked with the space-saving algorithm: once the table is full, the
+/// least missed name is evicted for a new name, which inherits its count. A model name missed more often than the
+/// total divided by the size of the table is therefore always tracked, however many random names a scanner sends,
+/// and its count overestimates its misses by at most the inherited count.
+///
+/// Misses are kept in memory only, so they start over when the server restarts.
+#[derive(Default)]
+pub struct ModelMisses {
+    misses: Mutex<HashMap<ModelName, Miss>>,
+    total: AtomicU64,
+}
+
+impl ModelMisses {
+    /// Records a request made to a model which is not loaded.
+    pub fn record(&self, model_name: &str) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        let now = Utc::now();
+        let mut misses = match self.misses.lock() {
+            Ok(misses) => misses,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Some(miss) = misses.get_mut(model_name) {
+            miss.count += 1;
+            miss.last_missed = now;
+            return;
+        }
+
+        // the least missed name, the least recently missed among equals, makes room for the new name
+        let mut overestimate = 0;
+        if misses.len() >= MAX_TRACKED_MISSES {
+            let evicted = misses
+                .iter()
+                .min_by(|(_, a), (_, b)| {
+                    a.count
+                        .cmp(&b.count)
+                        .then_with(|| a.last_missed.cmp(&b.last_missed))
+                })
+                .map(|(model_name, _)| model_name.clone());
+            if let Some(miss) = evicted.and_then(|evicted| misses.remove(&evicted)) {
+                overestimate = miss.count;
+            }
+        }
+        misses.insert(
+            model_name.to_string(),
+            Miss {
+                count: overestimate + 1,
+                overestimate,
+                last_missed: now,
+            },
+        );
+    }
+
+    /// Returns the number of requests made to models which were not loaded since startup.
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Returns the most requested missing model names, most requested first.
+    ///
+    /// # Arguments
+    /// * `limit` - The maximum number of model names returned.
+    /// * `is_loaded` - Whether a model is loaded now, in which case its past misses are left out.
+    pub fn top(&self, limit: usize, is_loaded: impl Fn(&str) -> bool) -> Vec<ModelMiss> {
+        let tracked = match self.misses.lock() {
+            Ok(tracked) => tracked,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let mut misses: Vec<ModelMiss> = tracked
+            .iter()
+            .filter(|(model_name, _)| !is_loaded(model_name))
+            .map(|(model_name, miss)| ModelMiss {
+                model_name: model_name.clone(),
+                count: miss.count,
+                overestimate: miss.overestimate,
+                last_missed: miss.last_missed.to_rfc3339(),
+            })
+            .collect();
+        drop(tracked);
+        misses.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.model_name.cmp(&b.model_name))
+        });
+        misses.truncate(limit);
+        misses
+    }
+}
+
+/// Returns the loaded model names which are closest to a missing model name, closest first.
+///
+/// A model name is suggested when it is at most a third of the length of the missing model name away from it, or
+/// one edit for short model names, as counted by the case-insensitive Levenshtein distance.
+///
+/// # Arguments
+/// * `model_name` - The requested model name, which is not loaded.
+/// * `candidates` - The names of the loaded models.
+pub fn suggest<'a>(
+    model_name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<ModelName> {
+    let model_name = model_name.to_lowercase();
+    let max_distance = (model_name.chars().count() / 3).max(1);
+    let mut suggestions: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| {
+            (
+                edit_distance(&model_name, &candidate.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    suggestions.sort();
+    suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Returns the Levenshtein distance between two strings, i.e. the number of characters to insert, delete or
+/// substitute to turn one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_record_model_misses() {
+        // Arrange
+        let misses = ModelMisses::default();
+
+        // Act
+        misses.record("titanic_modle");
+        misses.record("titanic_modle");
+        misses.record("iris");
+        misses.record("my_model");
+
+        // Assert
+        let top = misses.top(2, |model_name| model_name == "my_model");
+        assert_eq!(misses.total(), 4);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].model_name, "titanic_modle");
+        assert_eq!(top[0].count, 2);
+        assert_eq!(top[1].model_name, "iris");
+    }
+
+    #[test]
+    fn successfully_cap_number_of_tracked_model_misses() {
+        // Arrange
+        let misses = ModelMisses::default();
+
+        // Act
+        for i in 0..MAX_TRACKED_MISSES + 10 {
+            misses.record(format!("model_{}", i).as_str());
+        }
+
+        // Assert
+        assert_eq!(misses.misses.lock().unwrap().len(), MAX_TRACKED_MISSES);
+        assert_eq!(misses.total(), (MAX_TRACKED_MISSES + 10) as u64);
+    }
+
+    #[test]
+    fn successfully_track_frequent_model_miss_after_the_table_is_full_of_random_names() {
+        // Arrange
+        let misses = ModelMisses::default();
+
+        // Act
+        for i in 0..MAX_TRACKED_MISSES * 3 {
+            misses.record(format!("random_{}", i).as_str());
+        }
+        for _ in 0..5 {
+            misses.record("titanic_modle");
+        }
+
+        // Assert the typo'd name evicts a random name and then outranks every other name
+        let top = misses.top(1, |_| false);
+        assert_eq!(top[0].model_name, "titanic_modle");
+        assert!(top[0].count >= 5);
+        assert_eq!(top[0].count - top[0].overestimate, 5);
+        assert_eq!(misses.misses.lock().unwrap().len(), MAX_TRACKED_MISSES);
+    }
+
+    #[test]
+    fn successfully_suggest_closest_model_names() {
+        // Arrange
+        let loaded = ["titanic_model", "titanic_model_v2", "iris", "penguin"];
+
+        // Act
+        let typo = suggest("Titanic_Modle", loaded);
+        let stale = suggest("titanic_model_v1", loaded);
+        let unrelated = suggest("fraud", loaded);
+
+        // Assert
+        assert_eq!(typo, vec!["titanic_model"]);
+        assert_eq!(stale, vec!["titanic_model_v2", "titanic_model"]);
+        assert!(unrelated.is_empty());
+    }
+
+    #[test]
+    fn successfully_compute_edit_distance() {
+        // assert
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "iris"), 4);
+        assert_eq!(edit_distance("iris", "iris"), 0);
+    }
+}
@@ -1,17 +1,36 @@
+#[cfg(feature = "aws")]
 use crate::model_store::aws::s3::S3ModelStore;
+#[cfg(feature = "azure")]
 use crate::model_store::azure::blob_storage::AzureBlobStorageModelStore;
 use crate::model_store::local::filesystem::LocalModelStore;
-use crate::model_store::storage::{Metadata, Model, ModelName, Storage};
-use dashmap::mapref::one::Ref;
+use crate::model_store::registry::RegistryState;
+use crate::model_store::storage::{Metadata, Model, ModelName, PollOptions, Storage};
 use std::sync::Arc;
-use std::time;
 
+pub mod attached;
+#[cfg(feature = "aws")]
 pub mod aws;
+#[cfg(feature = "azure")]
 pub mod azure;
+pub mod check;
 pub mod common;
+pub mod deadline;
+pub mod delta;
+pub mod diff;
+pub mod encryption;
 mod fetcher;
+pub mod gc;
+pub mod groups;
+pub mod integrity;
+pub mod labels;
+pub mod loaded;
 pub mod local;
+pub mod misses;
+pub mod registry;
+pub mod skipped;
 pub mod storage;
+pub mod supervisor;
+pub mod usage;
 
 /// Enum representing different types of model stores.
 ///
@@ -19,14 +38,16 @@ pub mod storage;
 /// contains a type representing the specific model store implementation.
 ///
 /// # Variants
-/// - `Azure`: Represents a model store on Azure Blob Storage.
-/// - `AWS`: Represents a model store on AWS S3.
+/// - `Azure`: Represents a model store on Azure Blob Storage, if the `azure` feature is enabled.
+/// - `AWS`: Represents a model store on AWS S3, if the `aws` feature is enabled.
 /// - `Local`: Represents a local file-based model store.
 pub enum ModelStore {
     /// Azure Blob Storage model store.
+    #[cfg(feature = "azure")]
     Azure(AzureBlobStorageModelStore),
 
     /// AWS S3 model store.
+    #[cfg(feature = "aws")]
     AWS(S3ModelStore),
 
     /// Local model store.
@@ -45,14 +66,16 @@ impl ModelStore {
     ///
     /// # Returns
     ///
-    /// This method returns an `anyhow::Result<()>` indicating success or failure.
+    /// This method returns a `jams_core::Result<()>` indicating success or failure.
     ///
     /// # Errors
     ///
     /// Returns an error if the underlying model store fails to add the model.
-    pub async fn add_model(&self, model_name: ModelName) -> anyhow::Result<()> {
+    pub async fn add_model(&self, model_name: ModelName) -> crate::Result<()> {
         match self {
+            #[cfg(feature = "azure")]
             ModelStore::Azure(azure) => azure.add_model(model_name).await,
+            #[cfg(feature = "aws")]
             ModelStore::AWS(aws) => aws.add_model(model_name).await,
             ModelStore::Local(local) => local.add_model(model_name).await,
         }
@@ -69,14 +92,16 @@ impl ModelStore {
     ///
     /// # Returns
     ///
-    /// This method returns an `anyhow::Result<()>` indicating success or failure.
+    /// This method returns a `jams_core::Result<()>` indicating success or failure.
     ///
     /// # Errors
     ///
     /// Returns an error if the underlying model store fails to update the model.
-    pub async fn update_model(&self, model_name: ModelName) -> anyhow::Result<()> {
+    pub async fn update_model(&self, model_name: ModelName) -> crate::Result<()> {
         match self {
+            #[cfg(feature = "azure")]
             ModelStore::Azure(azure) => azure.update_model(model_name).await,
+            #[cfg(feature = "aws")]
             ModelStore::AWS(aws) => aws.update_model(model_name).await,
             ModelStore::Local(local) => local.update_model(model_name).await,
         }
@@ -93,11 +118,13 @@ impl ModelStore {
     ///
     /// # Returns
     ///
-    /// This method returns an `Option<Ref<ModelName, Arc<Model>>>` where `Some(model)`
+    /// This method returns an `Option<Arc<Model>>` where `Some(model)`
     /// is returned if the model is found, or `None` if the model does not exist in the store.
-    pub fn get_model(&self, model_name: ModelName) -> Option<Ref<ModelName, Arc<Model>>> {
+    pub fn get_model(&self, model_name: ModelName) -> Option<Arc<Model>> {
         match self {
+            #[cfg(feature = "azure")]
             ModelStore::Azure(azure) => azure.get_model(model_name),
+            #[cfg(feature = "aws")]
             ModelStore::AWS(aws) => aws.get_model(model_name),
             ModelStore::Local(local) => local.get_model(model_name),
         }
@@ -112,9 +139,11 @@ impl ModelStore {
     ///
     /// This method returns a `Result<Vec<Metadata>>` containing the metadata of all models
     /// in the store. If the store fails to fetch the models, an error is returned.
-    pub fn get_models(&self) -> anyhow::Result<Vec<Metadata>> {
+    pub fn get_models(&self) -> crate::Result<Vec<Metadata>> {
         match self {
+            #[cfg(feature = "azure")]
             ModelStore::Azure(azure) => azure.get_models(),
+            #[cfg(feature = "aws")]
             ModelStore::AWS(aws) => aws.get_models(),
             ModelStore::Local(local) => local.get_models(),
         }
@@ -131,14 +160,16 @@ impl ModelStore {
     ///
     /// # Returns
     ///
-    /// This method returns an `anyhow::Result<()>` indicating success or failure.
+    /// This method returns a `jams_core::Result<()>` indicating success or failure.
     ///
     /// # Errors
     ///
     /// Returns an error if the underlying model store fails to delete the model.
-    pub fn delete_model(&self, model_name: ModelName) -> anyhow::Result<()> {
+    pub fn delete_model(&self, model_name: ModelName) -> crate::Result<()> {
         match self {
+            #[cfg(feature = "azure")]
             ModelStore::Azure(azure) => azure.delete_model(model_name),
+            #[cfg(feature = "aws")]
             ModelStore::AWS(aws) => aws.delete_model(model_name),
             ModelStore::Local(local) => local.delete_model(model_name),
         }
@@ -152,20 +183,99 @@ impl ModelStore {
     ///
     /// # Arguments
     ///
-    /// * `interval` - The duration between each poll check.
+    /// * `options` - The interval, jitter and download concurrency of the poll.
     ///
     /// # Returns
     ///
-    /// This method returns an `anyhow::Result<()>` indicating success or failure.
+    /// This method returns a `jams_core::Result<()>` indicating success or failure.
     ///
     /// # Errors
     ///
     /// Returns an error if the underlying model store fails during polling.
-    pub async fn poll(&self, interval: time::Duration) -> anyhow::Result<()> {
+    pub async fn poll(&self, options: &PollOptions) -> crate::Result<()> {
         match self {
-            ModelStore::Azure(azure) => azure.poll(interval).await,
-            ModelStore::AWS(aws) => aws.poll(interval).await,
-            ModelStore::Local(local) => local.poll(interval).await,
+            #[cfg(feature = "azure")]
+            ModelStore::Azure(azure) => azure.poll(options).await,
+            #[cfg(feature = "aws")]
+            ModelStore::AWS(aws) => aws.poll(options).await,
+            ModelStore::Local(local) => local.poll(options).await,
         }
     }
+
+    /// Captures the registry state of the models loaded in the store.
+    ///
+    /// This method calls the `registry_state` function on the underlying model store
+    /// implementation (Azure, AWS, or Local).
+    ///
+    /// # Returns
+    ///
+    /// This method returns an `anyhow::Result<RegistryState>` which can be saved and used
+    /// to restore the model store on the next startup.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the model artifacts cannot be hashed.
+    pub fn registry_state(&self) -> anyhow::Result<RegistryState> {
+        match self {
+            #[cfg(feature = "azure")]
+            ModelStore::Azure(azure) => azure.registry_state(),
+            #[cfg(feature = "aws")]
+            ModelStore::AWS(aws) => aws.registry_state(),
+            ModelStore::Local(local) => local.registry_state(),
+        }
+    }
+
+    /// Reads a stored version of a tarball from the underlying model store, e.g. to compare two versions of a
+    /// model without loading them.
+    ///
+    /// # Arguments
+    ///
+    /// * `tarball_name` - The name of the tarball in the model store, e.g. `pytorch-my_model.tar.gz`.
+    /// * `version` - The version of the tarball in the model store, i.e. its S3 or Azure version id, or its
+    ///   modification time in a local model store, or `None` for its latest version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version of the tarball is not stored or cannot be read.
+    pub async fn read_tarball(
+        &self,
+        tarball_name: &str,
+        version: Option<&str>,
+    ) -> anyhow::Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "azure")]
+            ModelStore::Azure(azure) => azure.read_tarball(tarball_name, version).await,
+            #[cfg(feature = "aws")]
+            ModelStore::AWS(aws) => aws.read_tarball(tarball_name, version).await,
+            ModelStore::Local(local) => local.read_tarball(tarball_name, version).await,
+        }
+    }
+
+    /// Returns the directory into which the underlying model store downloads or unpacks its models.
+    pub fn model_store_dir(&self) -> &str {
+        match self {
+            #[cfg(feature = "azure")]
+            ModelStore::Azure(azure) => azure.model_store_dir(),
+            #[cfg(feature = "aws")]
+            ModelStore::AWS(aws) => aws.model_store_dir(),
+            ModelStore::Local(local) => local.model_store_dir(),
+        }
+    }
+}
+
+/// Returns the error of a store whose backend was left out of this build.
+///
+/// The AWS S3, MinIO and Azure Blob Storage backends pull in the heavy cloud SDKs, hence they are compiled only
+/// with the `aws` and `azure` features, which are enabled by default.
+///
+/// # Arguments
+///
+/// * `store` - Describes the store which was requested, e.g. `The aws model store`.
+/// * `feature` - The cargo feature which compiles the backend of the store, i.e. `aws` or `azure`.
+pub fn backend_disabled(store: &str, feature: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "{} is not supported by this build. Rebuild with the {} feature enabled ❌",
+        store,
+        feature
+    )
 }
@@ -0,0 +1,364 @@
+use crate::model_store::common::{cleanup, hash_artifact};
+use crate::model_store::loaded::LoadedModels;
+use crate::model_store::storage::{
+    extract_framework, load_predictor, prepare_model, Model, ModelName,
+};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Version of the registry state format. State files written with a different version are ignored.
+pub const REGISTRY_STATE_VERSION: u32 = 1;
+
+/// A model which was loaded in the model store when the registry state was saved.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RegistryEntry {
+    /// The name of the model.
+    pub name: String,
+    /// The framework used to load the model.
+    pub framework: String,
+    /// The path to the model artifact in the local cache.
+    pub path: String,
+    /// The time at which the model was last updated in the model store.
+    pub last_updated: String,
+    /// SHA-256 hash of the model artifact, used to check that the cached artifact is unchanged.
+    pub artifact_hash: String,
+}
+
+/// A snapshot of the models loaded in a model store.
+///
+/// The snapshot is saved when the server shuts down and is used on startup to load the models
+/// straight from the local cache, skipping listing and downloading models from the model store.
+/// Any changes made to the model store in the meantime are picked up by polling.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RegistryState {
+    /// The version of the registry state format.
+    pub version: u32,
+    /// Identifies the model store the state was captured from, i.e. a bucket, container or directory.
+    pub source: String,
+    /// The local directory which caches the model artifacts.
+    pub model_store_dir: String,
+    /// The models which were loaded.
+    pub models: Vec<RegistryEntry>,
+}
+
+/// Serializes the registry state to and from bytes.
+///
+/// Implement this trait to store the registry state in a different format.
+pub trait RegistryCodec: Send + Sync {
+    /// Serializes the registry state into bytes.
+    fn encode(&self, state: &RegistryState) -> anyhow::Result<Vec<u8>>;
+
+    /// Deserializes the registry state from bytes.
+    fn decode(&self, bytes: &[u8]) -> anyhow::Result<RegistryState>;
+}
+
+/// Stores the registry state as pretty printed JSON. This is the default codec.
+pub struct JsonCodec;
+
+impl RegistryCodec for JsonCodec {
+    fn encode(&self, state: &RegistryState) -> anyhow::Result<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(state)?)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> anyhow::Result<RegistryState> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+impl RegistryState {
+    /// Captures the registry state from the models loaded in a model store.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Identifies the model store, i.e. a bucket, container or directory.
+    /// * `model_store_dir` - The local directory which caches the model artifacts.
+    /// * `models` - The models loaded in the model store.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the model artifacts cannot be hashed.
+    pub fn capture(
+        source: String,
+        model_store_dir: String,
+        models: &LoadedModels,
+    ) -> anyhow::Result<Self> {
+        let models = models.snapshot();
+        let mut entries = Vec::with_capacity(models.len());
+        for model in models.values() {
+            let info = &model.info;
+            let artifact_hash = match hash_artifact(Path::new(info.path.as_str())) {
+                Ok(hash) => hash,
+                Err(e) => {
+                    tracing::error!("Failed to hash model artifact {} ❌: {}", info.path, e);
+                    anyhow::bail!("Failed to hash model artifact {} ❌: {}", info.path, e)
+                }
+            };
+            entries.push(RegistryEntry {
+                name: info.name.clone(),
+                framework: info.framework.to_string(),
+                path: info.path.clone(),
+                last_updated: info.last_updated.clone(),
+                artifact_hash,
+            });
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(RegistryState {
+            version: REGISTRY_STATE_VERSION,
+            source,
+            model_store_dir,
+            models: entries,
+        })
+    }
+
+    /// Writes the registry state to the given path.
+    ///
+    /// The state is written to a temporary file first and then renamed, so a crash while saving
+    /// never leaves a partially written state file behind.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the state file.
+    /// * `codec` - The codec used to serialize the state.
+    pub fn save(&self, path: &str, codec: &dyn RegistryCodec) -> anyhow::Result<()> {
+        let bytes = codec.encode(self)?;
+        let temp_path = format!("{}.tmp", path);
+        if let Err(e) = fs::write(temp_path.as_str(), bytes) {
+            tracing::error!("Failed to write registry state {} ❌: {}", path, e);
+            anyhow::bail!("Failed to write registry state {} ❌: {}", path, e)
+        }
+        if let Err(e) = fs::rename(temp_path.as_str(), path) {
+            tracing::error!("Failed to write registry state {} ❌: {}", path, e);
+            anyhow::bail!("Failed to write registry state {} ❌: {}", path, e)
+        }
+
+        tracing::info!(
+            "Saved registry state with {} models to {} 📝",
+            self.models.len(),
+            path
+        );
+        Ok(())
+    }
+
+    /// Reads the registry state from the given path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the state file.
+    /// * `codec` - The codec used to deserialize the state.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(RegistryState))` - If the state file was read successfully.
+    /// * `Ok(None)` - If the state file does not exist.
+    /// * `Err(anyhow::Error)` - If the state file could not be read or deserialized.
+    pub fn load(path: &str, codec: &dyn RegistryCodec) -> anyhow::Result<Option<Self>> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                tracing::error!("Failed to read registry state {} ❌: {}", path, e);
+                anyhow::bail!("Failed to read registry state {} ❌: {}", path, e)
+            }
+        };
+
+        match codec.decode(&bytes) {
+            Ok(state) => Ok(Some(state)),
+            Err(e) => {
+                tracing::error!("Failed to decode registry state {} ❌: {}", path, e);
+                anyhow::bail!("Failed to decode registry state {} ❌: {}", path, e)
+            }
+        }
+    }
+
+    /// Loads the models recorded in the registry state from the local cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Identifies the model store which is being restored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The state was written with a different format version or for a different model store.
+    /// * Any of the cached artifacts is missing or its hash does not match.
+    /// * Any of the models fails to load.
+    pub async fn restore(&self, source: &str) -> anyhow::Result<DashMap<ModelName, Arc<Model>>> {
+        if self.version != REGISTRY_STATE_VERSION {
+            anyhow::bail!(
+                "Registry state version {} is not supported, expected {}",
+                self.version,
+                REGISTRY_STATE_VERSION
+            )
+        }
+        if self.source != source {
+            anyhow::bail!(
+                "Registry state was saved for {} but the model store is {}",
+                self.source,
+                source
+            )
+        }
+
+        let models: DashMap<ModelName, Arc<Model>> = DashMap::new();
+        for entry in &self.models {
+            let framework = match extract_framework(entry.framework.clone()) {
+                Some(framework) => framework,
+                None => anyhow::bail!(
+                    "Unknown framework {} for model {}",
+                    entry.framework,
+                    entry.name
+                ),
+            };
+            match hash_artifact(Path::new(entry.path.as_str())) {
+                Ok(hash) if hash == entry.artifact_hash => {}
+                Ok(_) => anyhow::bail!("Cached artifact for model {} has changed", entry.name),
+                Err(e) => anyhow::bail!(
+                    "Cached artifact for model {} is unavailable: {}",
+                    entry.name,
+                    e
+                ),
+            }
+
+            let predictor = load_predictor(framework, entry.path.as_str()).await?;
+            let model = Model::new(
+                predictor,
+                entry.name.clone(),
+                framework,
+                entry.path.clone(),
+                entry.last_updated.clone(),
+            );
+            let model = prepare_model(model, None).await?;
+            models.insert(entry.name.clone(), Arc::new(model));
+        }
+
+        Ok(models)
+    }
+}
+
+/// Restores the models from the registry state, if one was provided and it is still valid.
+///
+/// If the state cannot be restored, the cache directory it refers to is removed as the model
+/// store will download the models into a new directory.
+///
+/// # Returns
+///
+/// * `Some((model_store_dir, models))` - If the models were restored from the local cache.
+/// * `None` - If there was no state or it could not be restored.
+pub(crate) async fn restore_models(
+    state: Option<RegistryState>,
+    source: &str,
+) -> Option<(String, DashMap<ModelName, Arc<Model>>)> {
+    let state = state?;
+    match state.restore(source).await {
+        Ok(models) => {
+            tracing::info!(
+                "Restored {} models from registry state, skipped fetching from {} ✅",
+                models.len(),
+                source
+            );
+            Some((state.model_store_dir, models))
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Failed to restore registry state, fetching models from {} ⚠️: {}",
+                source,
+                e
+            );
+            if Path::new(state.model_store_dir.as_str()).exists() {
+                cleanup(state.model_store_dir)
+            }
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_store::common::unpack_tarball;
+    use crate::model_store::storage::load_models;
+
+    async fn load_test_models(dir: &str) -> LoadedModels {
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).unwrap();
+        unpack_tarball(
+            "tests/model_storage/model_store/lightgbm-my_awesome_reg_model.tar.gz",
+            dir,
+        )
+        .unwrap();
+        LoadedModels::from(load_models(dir.to_string()).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn successfully_saves_and_restores_registry_state() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("jams-registry-{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+        let state_path = format!("{}.json", dir);
+        let models = load_test_models(dir).await;
+        let state = RegistryState::capture("local".to_string(), dir.to_string(), &models).unwrap();
+
+        // Act
+        state.save(state_path.as_str(), &JsonCodec).unwrap();
+        let loaded = RegistryState::load(state_path.as_str(), &JsonCodec)
+            .unwrap()
+            .unwrap();
+        let restored = loaded.restore("local").await;
+
+        // Assert
+        assert_eq!(loaded, state);
+        assert!(restored.is_ok());
+        assert!(restored.unwrap().contains_key("my_awesome_reg_model"));
+        let _ = fs::remove_dir_all(dir);
+        let _ = fs::remove_file(state_path);
+    }
+
+    #[tokio::test]
+    async fn fails_to_restore_registry_state_when_artifact_has_changed() {
+        // Arrange
+        let dir =
+            std::env::temp_dir().join(format!("jams-registry-changed-{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+        let models = load_test_models(dir).await;
+        let state = RegistryState::capture("local".to_string(), dir.to_string(), &models).unwrap();
+        let path = state.models[0].path.clone();
+        fs::write(path, "not a model").unwrap();
+
+        // Act
+        let restored = state.restore("local").await;
+
+        // Assert
+        assert!(restored.is_err());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn fails_to_restore_registry_state_from_different_source() {
+        // Arrange
+        let state = RegistryState {
+            version: REGISTRY_STATE_VERSION,
+            source: "s3://bucket".to_string(),
+            model_store_dir: "/tmp/model_store".to_string(),
+            models: vec![],
+        };
+
+        // Act
+        let restored = state.restore("s3://another_bucket").await;
+
+        // Assert
+        assert!(restored.is_err());
+    }
+
+    #[test]
+    fn successfully_returns_none_when_registry_state_does_not_exist() {
+        // Act
+        let state = RegistryState::load("/tmp/does_not_exist/registry.json", &JsonCodec);
+
+        // Assert
+        assert!(state.unwrap().is_none());
+    }
+}
@@ -0,0 +1,162 @@
+use crate::Error;
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// Maximum number of distinct models whose skipped load is kept, so that a model store full of models which
+/// cannot run on the host does not grow the record forever.
+pub const MAX_SKIPPED_MODELS: usize = 1000;
+
+tokio::task_local! {
+    /// The skipped models of the `Manager` which loads models on the current task, if any.
+    static LOAD_SKIPPED: Arc<SkippedModels>;
+}
+
+/// A model which was skipped while loading a model store, so that it did not stop the other models from loading,
+/// or which failed to load.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SkippedModel {
+    /// The file or directory name of the model in the model store, e.g. `pytorch-my_model.pt`.
+    pub file_name: String,
+    /// The path the model was loaded from.
+    pub path: String,
+    /// Why the model was skipped or failed to load.
+    pub reason: String,
+    /// When the model was last skipped, in RFC 3339 format.
+    pub skipped_at: String,
+}
+
+/// The models which were skipped or failed to load by a `Manager`, including the models loaded in the background
+/// and by the polls of its model stores.
+#[derive(Debug, Default)]
+pub struct SkippedModels {
+    skipped: Mutex<BTreeMap<String, SkippedModel>>,
+}
+
+impl SkippedModels {
+    /// Records a model which was skipped or failed to load. A model which is skipped again, e.g. by every poll of
+    /// the model store, replaces its previous record.
+    ///
+    /// # Arguments
+    /// * `file_name` - The file or directory name of the model in the model store.
+    /// * `path` - The path the model was loaded from.
+    /// * `reason` - Why the model was skipped.
+    pub fn record(&self, file_name: &str, path: &str, reason: &str) {
+        let mut skipped = match self.skipped.lock() {
+            Ok(skipped) => skipped,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if skipped.len() >= MAX_SKIPPED_MODELS && !skipped.contains_key(file_name) {
+            return;
+        }
+        skipped.insert(
+            file_name.to_string(),
+            SkippedModel {
+                file_name: file_name.to_string(),
+                path: path.to_string(),
+                reason: reason.to_string(),
+                skipped_at: Utc::now().to_rfc3339(),
+            },
+        );
+    }
+
+    /// Returns the models which were skipped since the manager was built, sorted by file name.
+    pub fn models(&self) -> Vec<SkippedModel> {
+        let skipped = match self.skipped.lock() {
+            Ok(skipped) => skipped,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        skipped.values().cloned().collect()
+    }
+}
+
+/// Runs a future which loads models with the skipped models of a `Manager` in scope, so that every model it skips
+/// or fails to load is reported by the manager.
+///
+/// # Arguments
+/// - `skipped`: The skipped models of the manager.
+/// - `future`: The future which loads the models, e.g. polling a model store.
+pub async fn with_skipped<F: Future>(skipped: Arc<SkippedModels>, future: F) -> F::Output {
+    LOAD_SKIPPED.scope(skipped, future).await
+}
+
+/// Records a model which was skipped while loading a model store in the skipped models in scope. Models loaded
+/// without skipped models in scope are only logged.
+///
+/// # Arguments
+/// * `file_name` - The file or directory name of the model in the model store.
+/// * `path` - The path the model was loaded from.
+/// * `reason` - Why the model was skipped.
+pub fn record(file_name: &str, path: &str, reason: &str) {
+    let _ = LOAD_SKIPPED.try_with(|skipped| skipped.record(file_name, path, reason));
+}
+
+/// Records a model which failed to load in the skipped models in scope, and returns the error so that it is still
+/// returned by the load.
+///
+/// # Arguments
+/// * `file_name` - The file or directory name of the model in the model store.
+/// * `path` - The path the model was loaded from.
+/// * `e` - Why the model failed to load.
+pub fn record_failure(file_name: &str, path: &str, e: Error) -> Error {
+    record(file_name, path, e.to_string().as_str());
+    e
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_record_skipped_models() {
+        // Arrange
+        let skipped = SkippedModels::default();
+        let file_name = "torch-skipped_test_model.pt";
+
+        // Act
+        skipped.record(
+            file_name,
+            "/tmp/models/torch-skipped_test_model.pt",
+            "needs a GPU",
+        );
+        skipped.record(
+            file_name,
+            "/tmp/models/torch-skipped_test_model.pt",
+            "needs AVX-512",
+        );
+
+        // Assert
+        let models = skipped.models();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].reason, "needs AVX-512");
+    }
+
+    #[tokio::test]
+    async fn successfully_record_failures_only_in_the_skipped_models_in_scope() {
+        // Arrange
+        let skipped = Arc::new(SkippedModels::default());
+
+        // Act
+        let e = with_skipped(skipped.clone(), async {
+            record_failure(
+                "xgboost-broken_model.json",
+                "/tmp/models/xgboost-broken_model.json",
+                Error::Load(anyhow::anyhow!("invalid model")),
+            )
+        })
+        .await;
+        record(
+            "xgboost-unscoped_model.json",
+            "/tmp/models/xgboost-unscoped_model.json",
+            "invalid model",
+        );
+
+        // Assert
+        assert!(matches!(e, Error::Load(_)));
+        let models = skipped.models();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].file_name, "xgboost-broken_model.json");
+    }
+}
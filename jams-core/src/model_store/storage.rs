@@ -1,12 +1,43 @@
+use crate::chaos::Fault;
+use crate::memory;
 use crate::model;
-use crate::model::frameworks::{ModelFramework, CATBOOST, LIGHTGBM, PYTORCH, TENSORFLOW, TORCH};
+use crate::model::bundle::ModelBundle;
+use crate::model::calibration::CalibrationMap;
+use crate::model::custom;
+use crate::model::embedding::EmbeddingIndex;
+use crate::model::expression::PostprocessProgram;
+use crate::model::frameworks::{
+    ModelFramework, CATBOOST, FRAMEWORKS, GGUF, LIGHTGBM, OPENVINO, PYTORCH, TENSORFLOW, TENSORRT,
+    TFLITE, TORCH, TRANSFORMERS, XGBOOST,
+};
+use crate::model::input::ModelInput;
+use crate::model::lifecycle::{run_hook, HookStatus, Lifecycle, DEFAULT_TEARDOWN_TIMEOUT};
+use crate::model::predict::PredictOptions;
+use crate::model::requirements::{HostCapabilities, ModelRequirements};
+use crate::model::settings;
+use crate::model::stats::ModelStats;
+use crate::model::validation::ValidationOutcome;
+use crate::model::variant::ModelPrecision;
 use crate::model::Predictor;
+use crate::model_store::common::{
+    hash_artifact, read_provenance, read_validation_set, Provenance, PROVENANCE_DIRECTORY_NAME,
+};
+use crate::model_store::encryption::load_decrypted;
+use crate::model_store::groups::DeploymentGroups;
+use crate::model_store::loaded::LoadedModels;
+use crate::model_store::skipped;
+use crate::{Error, Result};
 use async_trait::async_trait;
 use chrono::Utc;
-use dashmap::mapref::one::Ref;
 use dashmap::DashMap;
+use futures::stream::{self, StreamExt};
+use rand::Rng;
 use serde::Serialize;
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time;
 use tokio::fs;
 
@@ -16,22 +47,74 @@ pub type ModelName = String;
 #[async_trait]
 pub trait Storage: Send + Sync + 'static {
     /// Adds a specific machine learning/deep learning model
-    async fn add_model(&self, model_name: ModelName) -> anyhow::Result<()>;
+    async fn add_model(&self, model_name: ModelName) -> Result<()>;
 
     /// Updates a specific machine learning/deep learning model based on model name
-    async fn update_model(&self, model_name: ModelName) -> anyhow::Result<()>;
+    async fn update_model(&self, model_name: ModelName) -> Result<()>;
 
     /// Retrieves a specific machine learning/deep learning model by its name.
-    fn get_model(&self, model_name: ModelName) -> Option<Ref<ModelName, Arc<Model>>>;
+    fn get_model(&self, model_name: ModelName) -> Option<Arc<Model>>;
 
     /// Retrieves metadata for models which are currently loaded in memory
-    fn get_models(&self) -> anyhow::Result<Vec<Metadata>>;
+    fn get_models(&self) -> Result<Vec<Metadata>>;
 
     /// Removes a specific machine learning/deep learning model by its name.
-    fn delete_model(&self, model_name: ModelName) -> anyhow::Result<()>;
+    fn delete_model(&self, model_name: ModelName) -> Result<()>;
 
-    /// Polls the model store at fixed intervals and updates the models
-    async fn poll(&self, interval: time::Duration) -> anyhow::Result<()>;
+    /// Waits for the next poll and then updates the models which changed in the model store
+    async fn poll(&self, options: &PollOptions) -> Result<()>;
+}
+
+/// How often `wait_for_teardowns` checks whether the teardowns running in the background have finished.
+const TEARDOWN_POLL_INTERVAL: time::Duration = time::Duration::from_millis(100);
+
+/// The number of teardowns of retired models which are running in the background.
+static PENDING_TEARDOWNS: AtomicUsize = AtomicUsize::new(0);
+
+/// A tarball which changed in the model store, with the name of its model if it can be extracted.
+type TarballUpdate = (String, Option<ModelName>);
+
+/// The default maximum number of tarballs downloaded at the same time while polling.
+pub const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 1;
+
+/// Options which control how the model store is polled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PollOptions {
+    /// The interval between each poll. A zero interval disables polling.
+    pub interval: time::Duration,
+    /// The upper bound of the random delay added to each interval, so that replicas polling the
+    /// same model store do not all hit it at the same time.
+    pub jitter: time::Duration,
+    /// The maximum number of tarballs downloaded at the same time.
+    pub max_concurrent_downloads: usize,
+    /// The fault injected into every tarball download by the chaos developer mode.
+    pub download_fault: Fault,
+    /// The groups of models whose updates are swapped in together or not at all.
+    pub deployment_groups: Arc<DeploymentGroups>,
+}
+
+impl Default for PollOptions {
+    fn default() -> Self {
+        PollOptions {
+            interval: time::Duration::from_secs(0),
+            jitter: time::Duration::from_secs(0),
+            max_concurrent_downloads: DEFAULT_MAX_CONCURRENT_DOWNLOADS,
+            download_fault: Fault::default(),
+            deployment_groups: Arc::new(DeploymentGroups::default()),
+        }
+    }
+}
+
+impl PollOptions {
+    /// Returns the delay before the next poll, which is the interval plus a random jitter
+    /// between zero and `jitter`.
+    pub fn next_delay(&self) -> time::Duration {
+        if self.jitter.is_zero() {
+            return self.interval;
+        }
+        let jitter_millis = rand::thread_rng().gen_range(0..=self.jitter.as_millis() as u64);
+        self.interval + time::Duration::from_millis(jitter_millis)
+    }
 }
 
 /// Represents a machine learning model.
@@ -50,6 +133,53 @@ pub trait Storage: Send + Sync + 'static {
 pub struct Model {
     pub predictor: Arc<Predictor>,
     pub info: Metadata,
+    /// Calibration map shipped with the model, applied to its predictions to add calibrated probabilities.
+    pub calibration: Option<CalibrationMap>,
+    /// Postprocessing program shipped with the model, run on its predictions after calibration to add outputs.
+    pub postprocess: Option<PostprocessProgram>,
+    /// Embedding index shipped with the model, searched for the nearest neighbors of its embeddings.
+    pub embedding_index: Option<EmbeddingIndex>,
+    /// Auxiliary files shipped in the `bundle` directory of the model tarball, applied to the model input before predicting.
+    pub bundle: ModelBundle,
+    /// Reduced precision variants shipped in the `variants` directory of the model tarball, keyed by their precision.
+    pub variants: BTreeMap<ModelPrecision, Arc<Predictor>>,
+    /// Latency and error statistics of the predictions made since the model was loaded.
+    pub stats: ModelStats,
+    teardown: Teardown,
+}
+
+/// The teardown of a model, run once the last reference to the model is dropped after `schedule_teardown` retired
+/// it, so that the requests still holding the model finish before its resources are released.
+#[derive(Default)]
+struct Teardown {
+    /// The runtime and predictors of the model, set when the model is retired.
+    scheduled: Mutex<Option<ScheduledTeardown>>,
+    /// Whether the teardown hooks of the model have run, so that they never run twice.
+    done: AtomicBool,
+}
+
+struct ScheduledTeardown {
+    runtime: tokio::runtime::Handle,
+    model_name: String,
+    predictors: Vec<Arc<Predictor>>,
+}
+
+impl Drop for Teardown {
+    fn drop(&mut self) {
+        let scheduled = match self.scheduled.get_mut() {
+            Ok(scheduled) => scheduled.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        };
+        let scheduled = match scheduled {
+            Some(scheduled) if !self.done.swap(true, Ordering::SeqCst) => scheduled,
+            _ => return,
+        };
+        PENDING_TEARDOWNS.fetch_add(1, Ordering::SeqCst);
+        scheduled.runtime.spawn(async move {
+            teardown_predictors(scheduled.model_name.as_str(), &scheduled.predictors).await;
+            PENDING_TEARDOWNS.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
 }
 
 /// Metadata for a machine learning model.
@@ -63,6 +193,19 @@ pub struct Model {
 /// * `framework` - The machine learning framework used to build the model (e.g., TensorFlow, PyTorch).
 /// * `path` - The filesystem path to the model's file or directory.
 /// * `last_updated` - The timestamp of when the model was last updated.
+/// * `artifact_sha256` - The SHA-256 hash of the model artifact which was loaded. Empty if it could not be hashed.
+/// * `source_uri` - The URI of the tarball in the model store. Empty if unknown.
+/// * `source_version` - The S3 version id or blob etag of the tarball in the model store, if known.
+/// * `labels` - The build labels from the `labels.yaml` file in the tarball.
+/// * `predict_options` - The default prediction options from the `predict_options.json` file in the tarball.
+/// * `best_iteration` - The number of boosting iterations saved in a LightGBM model, i.e. its best iteration if it was
+///   trained with early stopping. `None` for other models.
+/// * `requirements` - The capabilities required of the host from the `requirements.json` file in the tarball.
+/// * `variants` - The precisions of the reduced precision variants loaded from the `variants` directory in the tarball.
+/// * `pending_update` - The new version of the model which is being downloaded and loaded by the poller, if any.
+/// * `request_count` - The number of prediction requests made to the model since it was added or since startup.
+/// * `last_accessed` - The timestamp of the last prediction request made to the model, if any.
+/// * `init` - The status of the init hooks run when the model was loaded.
 ///
 #[derive(Clone, Serialize)]
 pub struct Metadata {
@@ -70,12 +213,76 @@ pub struct Metadata {
     pub framework: ModelFramework,
     pub path: String,
     pub last_updated: String,
+    pub artifact_sha256: String,
+    pub source_uri: String,
+    pub source_version: Option<String>,
+    pub labels: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "PredictOptions::is_empty")]
+    pub predict_options: PredictOptions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_iteration: Option<u32>,
+    #[serde(skip_serializing_if = "ModelRequirements::is_empty")]
+    pub requirements: ModelRequirements,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<ModelPrecision>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_update: Option<PendingUpdate>,
+    pub request_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_accessed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init: Option<HookStatus>,
+}
+
+/// A new version of a model which the poller has detected in the model store.
+///
+/// The model keeps serving its current version while the new version is downloaded and loaded,
+/// and is only swapped once the new version is ready.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct PendingUpdate {
+    /// The S3 version id, blob etag or file modification time of the new tarball, if known.
+    pub source_version: Option<String>,
+    /// The timestamp of when the new version was detected.
+    pub detected_at: String,
+}
+
+/// Tracks the models which have a pending update.
+#[derive(Default)]
+pub struct PendingUpdates(DashMap<ModelName, PendingUpdate>);
+
+impl PendingUpdates {
+    /// Marks a model as having a pending update to `source_version`.
+    pub fn begin(&self, model_name: ModelName, source_version: Option<String>) {
+        self.0.insert(
+            model_name,
+            PendingUpdate {
+                source_version,
+                detected_at: Utc::now().to_rfc3339(),
+            },
+        );
+    }
+
+    /// Clears the pending update of a model once it has been swapped or has failed to load.
+    pub fn finish(&self, model_name: &str) {
+        self.0.remove(model_name);
+    }
+
+    /// Adds the pending update of the model, if any, to its metadata.
+    pub fn annotate(&self, mut metadata: Metadata) -> Metadata {
+        metadata.pending_update = self
+            .0
+            .get(metadata.name.as_str())
+            .map(|pending| pending.clone());
+        metadata
+    }
 }
 
 impl Model {
     /// Creates a new `Model` instance.
     ///
-    /// This function initializes a new `Model` with the given predictor and metadata.
+    /// This function initializes a new `Model` with the given predictor and metadata. The provenance of the
+    /// artifact at `path` is read from the model store directory, and the artifact is hashed unless its hash
+    /// was recorded when it was unpacked.
     ///
     /// # Parameters
     ///
@@ -96,15 +303,115 @@ impl Model {
         path: String,
         last_updated: String,
     ) -> Model {
+        let provenance = read_provenance(path.as_str());
+        // the hash is recorded while the artifact is unpacked, so that encrypted artifacts are not decrypted again
+        let artifact_sha256 = match provenance.artifact_sha256.clone() {
+            Some(hash) => hash,
+            None => match hash_artifact(Path::new(path.as_str())) {
+                Ok(hash) => hash,
+                Err(e) => {
+                    tracing::warn!("Failed to hash model artifact {} ⚠️: {}", path, e);
+                    String::new()
+                }
+            },
+        };
+        let variants = load_variants(framework, &provenance);
+        let best_iteration = match predictor.as_ref() {
+            Predictor::LightGBM(model) => u32::try_from(model.num_iterations()).ok(),
+            _ => None,
+        };
+
         let info = Metadata {
             name: model_name,
             framework,
             path,
             last_updated,
+            artifact_sha256,
+            source_uri: provenance.source_uri,
+            source_version: provenance.source_version,
+            labels: provenance.labels,
+            predict_options: provenance.predict_options,
+            best_iteration,
+            requirements: provenance.requirements,
+            variants: variants.keys().copied().collect(),
+            pending_update: None,
+            request_count: 0,
+            last_accessed: None,
+            init: None,
         };
 
-        Model { predictor, info }
+        Model {
+            predictor,
+            info,
+            calibration: provenance.calibration,
+            postprocess: provenance.postprocess,
+            embedding_index: provenance.embedding_index,
+            bundle: provenance.bundle,
+            variants,
+            stats: ModelStats::default(),
+            teardown: Teardown::default(),
+        }
+    }
+
+    /// Returns true once the teardown hooks of the model have run.
+    pub fn is_torn_down(&self) -> bool {
+        self.teardown.done.load(Ordering::SeqCst)
+    }
+
+    /// Returns the predictor of the given precision variant of the model.
+    ///
+    /// # Arguments
+    ///
+    /// * `precision` - The precision of the variant. `fp32` is the model artifact itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model was not shipped with a variant of the given precision.
+    pub fn predictor(&self, precision: ModelPrecision) -> anyhow::Result<&Arc<Predictor>> {
+        if precision == ModelPrecision::Fp32 {
+            return Ok(&self.predictor);
+        }
+        match self.variants.get(&precision) {
+            Some(predictor) => Ok(predictor),
+            None => anyhow::bail!("Model {} has no {} variant ❌", self.info.name, precision),
+        }
+    }
+}
+
+/// Loads the reduced precision variants of a model from the paths recorded in its provenance.
+///
+/// Only PyTorch variants are supported. A variant which fails to load is skipped, so that the model
+/// keeps serving its other variants.
+fn load_variants(
+    framework: ModelFramework,
+    provenance: &Provenance,
+) -> BTreeMap<ModelPrecision, Arc<Predictor>> {
+    let mut variants = BTreeMap::new();
+    for (precision, path) in provenance.variants.iter() {
+        if framework != PYTORCH && framework != TORCH {
+            tracing::warn!(
+                "Skipped {} variant {} as variants are only supported by PyTorch models ⚠️",
+                precision,
+                path
+            );
+            continue;
+        }
+        match load_decrypted(path.as_str(), model::torch::Torch::load) {
+            Ok(predictor) => {
+                let mut predictor = predictor
+                    .with_class_labels(provenance.class_labels.clone())
+                    .with_precision(*precision);
+                if let Some(generation) = &provenance.generation {
+                    predictor = predictor.with_generation(generation.clone());
+                }
+                variants.insert(*precision, Arc::new(Predictor::Torch(predictor)));
+            }
+            Err(e) => {
+                tracing::error!("Failed to load {} variant {} ❌: {}", precision, path, e);
+            }
+        }
     }
+    variants
 }
 
 /// Loads models from the specified directory and returns a DashMap containing the models.
@@ -124,15 +431,15 @@ impl Model {
 /// # Returns
 ///
 /// A `Result` containing a `DashMap` with model names as keys and `Arc<Model>` as values.
-/// On failure, returns an `anyhow::Error` with details about the error.
+/// On failure, returns an `Error::Load` with details about the error.
 ///
 /// # Errors
 ///
 /// This function will return an error if it fails to read the directory, convert file paths, or
 /// load any of the models.
 #[tracing::instrument(skip(model_dir))]
-pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName, Arc<Model>>> {
-    let models: DashMap<ModelName, Arc<Model>> = DashMap::new();
+pub async fn load_models(model_dir: String) -> Result<DashMap<ModelName, Arc<Model>>> {
+    let mut loaded: Vec<(ModelName, Model)> = Vec::new();
 
     match fs::read_dir(model_dir.clone()).await {
         Ok(mut dir) => {
@@ -140,7 +447,9 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                 let file_path = match entry.path().to_str() {
                     None => {
                         tracing::error!("Failed to convert PathBuf to str ❌");
-                        anyhow::bail!("Failed to convert PathBuf to str ❌")
+                        return Err(Error::Load(anyhow::anyhow!(
+                            "Failed to convert PathBuf to str ❌"
+                        )));
                     }
                     Some(file_path) => file_path.to_string(),
                 };
@@ -148,11 +457,50 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                     Ok(file_name) => file_name,
                     Err(_) => {
                         tracing::error!("Failed to convert OsString to String ❌");
-                        anyhow::bail!("Failed to convert OsString to String ❌")
+                        return Err(Error::Load(anyhow::anyhow!(
+                            "Failed to convert OsString to String ❌"
+                        )));
                     }
                 };
 
-                if file_name.contains(TENSORFLOW) {
+                if file_name == PROVENANCE_DIRECTORY_NAME {
+                    continue;
+                }
+
+                // a model which cannot run on this host or does not fit within the memory budget must not stop the
+                // other models from loading
+                if let Err(e) = check_requirements(file_path.as_str())
+                    .and_then(|_| check_memory_budget(file_path.as_str()))
+                {
+                    tracing::error!("Skipped loading model from path: {} ❌: {}", file_path, e);
+                    skipped::record(
+                        file_name.as_str(),
+                        file_path.as_str(),
+                        e.to_string().as_str(),
+                    );
+                    continue;
+                }
+
+                // the frameworks registered by the application are matched on their prefix first, so that their
+                // models are not mistaken for the models of the frameworks shipped with jams-core
+                let framework = extract_framework(file_name.clone());
+                if let Some(framework) = custom::framework_of(file_name.as_str()) {
+                    let model_name = &file_name[framework.len() + 1..];
+                    let predictor =
+                        load_decrypted(file_path.as_str(), |path| custom::load(framework, path))
+                            .map_err(|e| skipped::record_failure(&file_name, &file_path, e))?;
+                    let now = Utc::now();
+                    let sanitised_model_name = sanitize_model_name(model_name);
+                    let model = Model::new(
+                        Arc::new(predictor),
+                        sanitised_model_name.clone(),
+                        framework,
+                        file_path.clone(),
+                        now.to_rfc3339(),
+                    );
+                    loaded.push((sanitised_model_name, model));
+                    tracing::info!("Successfully loaded model from path: {} ✅", file_path);
+                } else if framework == Some(TENSORFLOW) {
                     let prefix = format!("{}-", TENSORFLOW);
                     match file_name.to_string().strip_prefix(&prefix) {
                         None => {
@@ -161,15 +509,18 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                                 prefix,
                                 file_name
                             );
-                            anyhow::bail!(
+                            return Err(Error::Load(anyhow::anyhow!(
                                 "Failed to strip prefix {} from file name {}",
                                 prefix,
                                 file_name
-                            )
+                            )));
                         }
                         Some(model_name) => {
-                            let predictor =
-                                model::tensorflow::Tensorflow::load(file_path.as_str())?;
+                            let predictor = load_decrypted(
+                                file_path.as_str(),
+                                model::tensorflow::Tensorflow::load,
+                            )
+                            .map_err(|e| skipped::record_failure(&file_name, &file_path, e))?;
                             let now = Utc::now();
                             let sanitised_model_name = sanitize_model_name(model_name);
                             let model = Model::new(
@@ -179,11 +530,11 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                                 file_path.clone(),
                                 now.to_rfc3339(),
                             );
-                            models.insert(sanitised_model_name, Arc::new(model));
+                            loaded.push((sanitised_model_name, model));
                             tracing::info!("Successfully loaded model from path: {} ✅", file_path);
                         }
                     }
-                } else if file_name.contains(TORCH) {
+                } else if framework == Some(TORCH) || framework == Some(PYTORCH) {
                     // Torch and PyTorch are same models. PyTorch is a python wrapper around Torch
                     let prefix = format!("{}-", TORCH);
                     match file_name.to_string().strip_prefix(&prefix) {
@@ -196,14 +547,20 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                                         prefix,
                                         file_name
                                     );
-                                    anyhow::bail!(
+                                    return Err(Error::Load(anyhow::anyhow!(
                                         "Failed to strip prefix {} from file name {}",
                                         prefix,
                                         file_name
-                                    )
+                                    )));
                                 }
                                 Some(model_name) => {
-                                    let predictor = model::torch::Torch::load(file_path.as_str())?;
+                                    let predictor = load_decrypted(
+                                        file_path.as_str(),
+                                        model::torch::Torch::load,
+                                    )
+                                    .map_err(|e| {
+                                        skipped::record_failure(&file_name, &file_path, e)
+                                    })?;
                                     let now = Utc::now();
                                     let sanitised_model_name = sanitize_model_name(model_name);
                                     let model = Model::new(
@@ -213,7 +570,7 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                                         file_path.clone(),
                                         now.to_rfc3339(),
                                     );
-                                    models.insert(sanitised_model_name, Arc::new(model));
+                                    loaded.push((sanitised_model_name, model));
                                     tracing::info!(
                                         "Successfully loaded model from path: {} ✅",
                                         file_path
@@ -222,7 +579,11 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                             }
                         }
                         Some(model_name) => {
-                            let predictor = model::torch::Torch::load(file_path.as_str())?;
+                            let predictor =
+                                load_decrypted(file_path.as_str(), model::torch::Torch::load)
+                                    .map_err(|e| {
+                                        skipped::record_failure(&file_name, &file_path, e)
+                                    })?;
                             let now = Utc::now();
                             let sanitised_model_name = sanitize_model_name(model_name);
                             let model = Model::new(
@@ -232,11 +593,11 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                                 file_path.clone(),
                                 now.to_rfc2822(),
                             );
-                            models.insert(sanitised_model_name, Arc::new(model));
+                            loaded.push((sanitised_model_name, model));
                             tracing::info!("Successfully loaded model from path: {} ✅", file_path);
                         }
                     }
-                } else if file_name.contains(CATBOOST) {
+                } else if framework == Some(CATBOOST) {
                     let prefix = format!("{}-", CATBOOST);
                     match file_name.to_string().strip_prefix(&prefix) {
                         None => {
@@ -245,14 +606,18 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                                 prefix,
                                 file_name
                             );
-                            anyhow::bail!(
+                            return Err(Error::Load(anyhow::anyhow!(
                                 "Failed to strip prefix {} from file name {}",
                                 prefix,
                                 file_name
-                            )
+                            )));
                         }
                         Some(model_name) => {
-                            let predictor = model::catboost::Catboost::load(file_path.as_str())?;
+                            let predictor =
+                                load_decrypted(file_path.as_str(), model::catboost::Catboost::load)
+                                    .map_err(|e| {
+                                        skipped::record_failure(&file_name, &file_path, e)
+                                    })?;
                             let now = Utc::now();
                             let sanitised_model_name = sanitize_model_name(model_name);
                             let model = Model::new(
@@ -262,11 +627,11 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                                 file_path.clone(),
                                 now.to_rfc2822(),
                             );
-                            models.insert(sanitised_model_name, Arc::new(model));
+                            loaded.push((sanitised_model_name, model));
                             tracing::info!("Successfully loaded model from path: {} ✅", file_path);
                         }
                     }
-                } else if file_name.contains(LIGHTGBM) {
+                } else if framework == Some(LIGHTGBM) {
                     let prefix = format!("{}-", LIGHTGBM);
                     match file_name.to_string().strip_prefix(&prefix) {
                         None => {
@@ -275,14 +640,18 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                                 prefix,
                                 file_name
                             );
-                            anyhow::bail!(
+                            return Err(Error::Load(anyhow::anyhow!(
                                 "Failed to strip prefix {} from file name {}",
                                 prefix,
                                 file_name
-                            )
+                            )));
                         }
                         Some(model_name) => {
-                            let predictor = model::lightgbm::LightGBM::load(file_path.as_str())?;
+                            let predictor =
+                                load_decrypted(file_path.as_str(), model::lightgbm::LightGBM::load)
+                                    .map_err(|e| {
+                                        skipped::record_failure(&file_name, &file_path, e)
+                                    })?;
                             let now = Utc::now();
                             let sanitised_model_name = sanitize_model_name(model_name);
                             let model = Model::new(
@@ -292,7 +661,202 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                                 file_path.clone(),
                                 now.to_rfc2822(),
                             );
-                            models.insert(sanitised_model_name, Arc::new(model));
+                            loaded.push((sanitised_model_name, model));
+                            tracing::info!("Successfully loaded model from path: {} ✅", file_path);
+                        }
+                    }
+                } else if framework == Some(XGBOOST) {
+                    let prefix = format!("{}-", XGBOOST);
+                    match file_name.to_string().strip_prefix(&prefix) {
+                        None => {
+                            tracing::error!(
+                                "Failed to strip prefix {} from file name {}",
+                                prefix,
+                                file_name
+                            );
+                            return Err(Error::Load(anyhow::anyhow!(
+                                "Failed to strip prefix {} from file name {}",
+                                prefix,
+                                file_name
+                            )));
+                        }
+                        Some(model_name) => {
+                            let predictor = load_decrypted(
+                                file_path.as_str(),
+                                model::xgboost::XGBoost::load,
+                            )
+                            .map_err(|e| skipped::record_failure(&file_name, &file_path, e))?;
+                            let now = Utc::now();
+                            let sanitised_model_name = sanitize_model_name(model_name);
+                            let model = Model::new(
+                                Arc::new(Predictor::XGBoost(predictor)),
+                                sanitised_model_name.clone(),
+                                XGBOOST,
+                                file_path.clone(),
+                                now.to_rfc2822(),
+                            );
+                            loaded.push((sanitised_model_name, model));
+                            tracing::info!("Successfully loaded model from path: {} ✅", file_path);
+                        }
+                    }
+                } else if framework == Some(TENSORRT) {
+                    let prefix = format!("{}-", TENSORRT);
+                    match file_name.to_string().strip_prefix(&prefix) {
+                        None => {
+                            tracing::error!(
+                                "Failed to strip prefix {} from file name {}",
+                                prefix,
+                                file_name
+                            );
+                            return Err(Error::Load(anyhow::anyhow!(
+                                "Failed to strip prefix {} from file name {}",
+                                prefix,
+                                file_name
+                            )));
+                        }
+                        Some(model_name) => {
+                            let predictor =
+                                load_decrypted_blocking(file_path.as_str(), load_tensorrt)
+                                    .await
+                                    .map_err(|e| {
+                                        skipped::record_failure(&file_name, &file_path, e)
+                                    })?;
+                            let now = Utc::now();
+                            let sanitised_model_name = sanitize_model_name(model_name);
+                            let model = Model::new(
+                                Arc::new(predictor),
+                                sanitised_model_name.clone(),
+                                TENSORRT,
+                                file_path.clone(),
+                                now.to_rfc2822(),
+                            );
+                            loaded.push((sanitised_model_name, model));
+                            tracing::info!("Successfully loaded model from path: {} ✅", file_path);
+                        }
+                    }
+                } else if framework == Some(TRANSFORMERS) {
+                    let prefix = format!("{}-", TRANSFORMERS);
+                    match file_name.to_string().strip_prefix(&prefix) {
+                        None => {
+                            tracing::error!(
+                                "Failed to strip prefix {} from file name {}",
+                                prefix,
+                                file_name
+                            );
+                            return Err(Error::Load(anyhow::anyhow!(
+                                "Failed to strip prefix {} from file name {}",
+                                prefix,
+                                file_name
+                            )));
+                        }
+                        Some(model_name) => {
+                            let predictor = load_decrypted(file_path.as_str(), load_transformers)
+                                .map_err(|e| {
+                                skipped::record_failure(&file_name, &file_path, e)
+                            })?;
+                            let now = Utc::now();
+                            let sanitised_model_name = sanitize_model_name(model_name);
+                            let model = Model::new(
+                                Arc::new(predictor),
+                                sanitised_model_name.clone(),
+                                TRANSFORMERS,
+                                file_path.clone(),
+                                now.to_rfc2822(),
+                            );
+                            loaded.push((sanitised_model_name, model));
+                            tracing::info!("Successfully loaded model from path: {} ✅", file_path);
+                        }
+                    }
+                } else if framework == Some(GGUF) {
+                    let prefix = format!("{}-", GGUF);
+                    match file_name.to_string().strip_prefix(&prefix) {
+                        None => {
+                            tracing::error!(
+                                "Failed to strip prefix {} from file name {}",
+                                prefix,
+                                file_name
+                            );
+                            return Err(Error::Load(anyhow::anyhow!(
+                                "Failed to strip prefix {} from file name {}",
+                                prefix,
+                                file_name
+                            )));
+                        }
+                        Some(model_name) => {
+                            let predictor = load_decrypted(file_path.as_str(), load_gguf)
+                                .map_err(|e| skipped::record_failure(&file_name, &file_path, e))?;
+                            let now = Utc::now();
+                            let sanitised_model_name = sanitize_model_name(model_name);
+                            let model = Model::new(
+                                Arc::new(predictor),
+                                sanitised_model_name.clone(),
+                                GGUF,
+                                file_path.clone(),
+                                now.to_rfc2822(),
+                            );
+                            loaded.push((sanitised_model_name, model));
+                            tracing::info!("Successfully loaded model from path: {} ✅", file_path);
+                        }
+                    }
+                } else if framework == Some(TFLITE) {
+                    let prefix = format!("{}-", TFLITE);
+                    match file_name.to_string().strip_prefix(&prefix) {
+                        None => {
+                            tracing::error!(
+                                "Failed to strip prefix {} from file name {}",
+                                prefix,
+                                file_name
+                            );
+                            return Err(Error::Load(anyhow::anyhow!(
+                                "Failed to strip prefix {} from file name {}",
+                                prefix,
+                                file_name
+                            )));
+                        }
+                        Some(model_name) => {
+                            let predictor = load_decrypted(file_path.as_str(), load_tflite)
+                                .map_err(|e| skipped::record_failure(&file_name, &file_path, e))?;
+                            let now = Utc::now();
+                            let sanitised_model_name = sanitize_model_name(model_name);
+                            let model = Model::new(
+                                Arc::new(predictor),
+                                sanitised_model_name.clone(),
+                                TFLITE,
+                                file_path.clone(),
+                                now.to_rfc2822(),
+                            );
+                            loaded.push((sanitised_model_name, model));
+                            tracing::info!("Successfully loaded model from path: {} ✅", file_path);
+                        }
+                    }
+                } else if framework == Some(OPENVINO) {
+                    let prefix = format!("{}-", OPENVINO);
+                    match file_name.to_string().strip_prefix(&prefix) {
+                        None => {
+                            tracing::error!(
+                                "Failed to strip prefix {} from file name {}",
+                                prefix,
+                                file_name
+                            );
+                            return Err(Error::Load(anyhow::anyhow!(
+                                "Failed to strip prefix {} from file name {}",
+                                prefix,
+                                file_name
+                            )));
+                        }
+                        Some(model_name) => {
+                            let predictor = load_decrypted(file_path.as_str(), load_openvino)
+                                .map_err(|e| skipped::record_failure(&file_name, &file_path, e))?;
+                            let now = Utc::now();
+                            let sanitised_model_name = sanitize_model_name(model_name);
+                            let model = Model::new(
+                                Arc::new(predictor),
+                                sanitised_model_name.clone(),
+                                OPENVINO,
+                                file_path.clone(),
+                                now.to_rfc2822(),
+                            );
+                            loaded.push((sanitised_model_name, model));
                             tracing::info!("Successfully loaded model from path: {} ✅", file_path);
                         }
                     }
@@ -310,11 +874,22 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
                 model_dir,
                 e.to_string()
             );
-            anyhow::bail!(
+            return Err(Error::Load(anyhow::anyhow!(
                 "Failed to read directory {} ❌: {}",
                 model_dir,
                 e.to_string()
-            )
+            )));
+        }
+    }
+
+    // a model which fails to initialize or misses its acceptance criteria is not served, but must not stop the
+    // other models from loading
+    let models: DashMap<ModelName, Arc<Model>> = DashMap::new();
+    for (model_name, model) in loaded {
+        if let Ok(model) = prepare_model(model, None).await {
+            if let Some(replaced) = models.insert(model_name, Arc::new(model)) {
+                schedule_teardown(replaced);
+            }
         }
     }
 
@@ -322,69 +897,727 @@ pub async fn load_models(model_dir: String) -> anyhow::Result<DashMap<ModelName,
     Ok(models)
 }
 
-/// Loads a machine learning model based on the specified framework and model path.
-///
-/// # Arguments
-///
-/// * `model_framework` - An enum representing the framework of the model to be loaded.
-/// * `model_path` - A string slice that holds the path to the model file.
+/// Revalidates the loaded models against the latest versions of their tarballs in the model store.
 ///
-/// # Returns
+/// Only the tarballs whose version differs from the loaded model, or which are not loaded yet, are
+/// downloaded and loaded, with at most `options.max_concurrent_downloads` tarballs in flight at a time.
+/// Models keep serving their current version, marked with a pending update, until the new version
+/// is loaded. Models whose new version fails to download, load, initialize or meet its acceptance criteria keep serving
+/// their current version.
 ///
-/// This function returns a Result containing an `Arc<dyn Predictor>` on success, or an error of type `anyhow::Error` on failure.
+/// The updated models of a deployment group are swapped in together once all of them are loaded. If any of them
+/// fails, the versions already loaded for the group are torn down and the whole group keeps serving its current
+/// versions.
 ///
-/// # Errors
+/// # Arguments
 ///
-/// This function will return an error if:
-/// * The model framework is unsupported.
-/// * The model fails to load due to an internal error specific to the framework.
+/// * `models` - The models loaded in the model store.
+/// * `pending_updates` - The pending updates of the model store.
+/// * `versions` - The version of each tarball in the model store, keyed by the tarball name.
+/// * `model_store_dir` - The directory in which `download` unpacks the tarballs.
+/// * `options` - The poll options, which limit the number of tarballs downloaded at the same time,
+///   set the fault injected into each download and the deployment groups.
+/// * `download` - Downloads and unpacks the given tarball into `model_store_dir`.
 ///
-#[tracing::instrument]
-pub async fn load_predictor(
-    model_framework: ModelFramework,
-    model_path: &str,
-) -> anyhow::Result<Arc<Predictor>> {
-    if model_framework == TENSORFLOW {
-        match model::tensorflow::Tensorflow::load(model_path) {
-            Ok(predictor) => Ok(Arc::new(Predictor::Tensorflow(predictor))),
-            Err(e) => {
-                tracing::error!("Failed to load Tensorflow model: {}", e);
-                anyhow::bail!("Failed to load Tensorflow model: {}", e)
+pub async fn revalidate_models<F, Fut>(
+    models: &LoadedModels,
+    pending_updates: &PendingUpdates,
+    versions: BTreeMap<String, Option<String>>,
+    model_store_dir: &str,
+    options: &PollOptions,
+    download: F,
+) where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    let available: Vec<ModelName> = versions
+        .keys()
+        .filter_map(|tarball_name| {
+            tarball_model_name(tarball_name).map(|(_, model_name)| model_name)
+        })
+        .collect();
+    let updates = detect_updates(models, versions);
+    if updates.is_empty() {
+        tracing::info!("All models are up to date ✅");
+        return;
+    }
+
+    let mut model_names = Vec::with_capacity(updates.len());
+    for (tarball_name, source_version) in updates.iter() {
+        let model_name = tarball_model_name(tarball_name).map(|(_, model_name)| model_name);
+        if let Some(model_name) = &model_name {
+            if models.contains_key(model_name) {
+                tracing::info!(
+                    "Detected new version of model {}, serving the current version until it is loaded ⏳",
+                    model_name
+                );
+                pending_updates.begin(model_name.clone(), source_version.clone());
             }
         }
-    } else if (model_framework == TORCH) || (model_framework == PYTORCH) {
-        match model::torch::Torch::load(model_path) {
-            Ok(predictor) => Ok(Arc::new(Predictor::Torch(predictor))),
-            Err(e) => {
-                tracing::error!("Failed to load Torch model: {}", e);
-                anyhow::bail!("Failed to load Torch model: {}", e)
-            }
+        model_names.push(model_name);
+    }
+
+    // the updates of a deployment group are staged and swapped in together, the other updates on their own
+    let mut deployments: Vec<(Option<String>, Vec<TarballUpdate>)> = Vec::new();
+    let mut groups: BTreeMap<String, Vec<TarballUpdate>> = BTreeMap::new();
+    for ((tarball_name, _), model_name) in updates.into_iter().zip(model_names) {
+        let group = model_name
+            .as_deref()
+            .and_then(|model_name| options.deployment_groups.group_of(model_name));
+        match group {
+            Some(group) => groups
+                .entry(group.name.clone())
+                .or_default()
+                .push((tarball_name, model_name)),
+            None => deployments.push((None, vec![(tarball_name, model_name)])),
         }
-    } else if model_framework == CATBOOST {
-        match model::catboost::Catboost::load(model_path) {
-            Ok(predictor) => Ok(Arc::new(Predictor::Catboost(predictor))),
-            Err(e) => {
-                tracing::error!("Failed to load Catboost model: {}", e);
-                anyhow::bail!("Failed to load Catboost model: {}", e)
-            }
+    }
+    for (group, members) in groups {
+        // a group is only deployed once the tarballs of all of its models are in the model store
+        let missing: Vec<&ModelName> = match options.deployment_groups.get(group.as_str()) {
+            Some(group) => group
+                .models
+                .iter()
+                .filter(|model_name| !available.contains(model_name))
+                .collect(),
+            None => Vec::new(),
+        };
+        if missing.is_empty() {
+            deployments.push((Some(group), members));
+            continue;
         }
-    } else if model_framework == LIGHTGBM {
-        match model::lightgbm::LightGBM::load(model_path) {
-            Ok(predictor) => Ok(Arc::new(Predictor::LightGBM(predictor))),
-            Err(e) => {
-                tracing::error!("Failed to load LightGBM model: {}", e);
-                anyhow::bail!("Failed to load LightGBM model: {}", e)
+        tracing::warn!(
+            "Deployment group {} is incomplete as the tarballs of {:?} are missing, its current versions are kept ⚠️",
+            group,
+            missing
+        );
+        for (_, model_name) in members.iter() {
+            if let Some(model_name) = model_name {
+                pending_updates.finish(model_name.as_str());
             }
         }
-    } else {
-        tracing::error!("unsupported model framework: {}", model_framework);
-        anyhow::bail!("unsupported model framework: {}", model_framework)
     }
-}
+
+    let download = &download;
+    stream::iter(deployments)
+        .map(|(group, members)| async move {
+            let mut staged = Vec::with_capacity(members.len());
+            let mut failure = None;
+            for (tarball_name, _) in members.iter() {
+                let downloaded = match options.download_fault.inject("model download").await {
+                    Ok(_) => download(tarball_name.clone()).await,
+                    Err(e) => Err(e),
+                };
+                let loaded = match downloaded {
+                    Ok(_) => match load_unpacked_model(model_store_dir, tarball_name.as_str()).await
+                    {
+                        Ok((model_name, model)) => {
+                            prepare_model(model, models.get(&model_name).as_deref())
+                                .await
+                                .map(|model| (model_name, model))
+                        }
+                        Err(e) => Err(e),
+                    },
+                    Err(e) => Err(Error::Store(e)),
+                };
+                match loaded {
+                    Ok(model) => staged.push(model),
+                    Err(e) => {
+                        failure = Some((tarball_name, e));
+                        break;
+                    }
+                }
+            }
+
+            match failure {
+                None => {
+                    // the models of a group are swapped in at once, so that no request sees half of the group updated
+                    let model_names: Vec<ModelName> =
+                        staged.iter().map(|(model_name, _)| model_name.clone()).collect();
+                    let staged = staged
+                        .into_iter()
+                        .map(|(model_name, model)| (model_name, Arc::new(model)))
+                        .collect();
+                    for replaced in models.insert_all(staged) {
+                        schedule_teardown(replaced);
+                    }
+                    for model_name in model_names {
+                        pending_updates.finish(&model_name);
+                        tracing::info!("Swapped in new version of model {} ✅", model_name);
+                    }
+                    if let Some(group) = group {
+                        tracing::info!("Deployed deployment group {} ✅", group);
+                    }
+                }
+                Some((tarball_name, e)) => {
+                    // roll back the whole group, none of the versions loaded so far is served
+                    for (_, model) in staged.iter() {
+                        teardown_model(model).await;
+                    }
+                    for (_, model_name) in members.iter() {
+                        if let Some(model_name) = model_name {
+                            pending_updates.finish(model_name.as_str());
+                        }
+                    }
+                    match group {
+                        Some(group) => tracing::warn!(
+                            "Failed to update {}, the current versions of deployment group {} are kept ⚠️: {}",
+                            tarball_name,
+                            group,
+                            e
+                        ),
+                        None => tracing::warn!(
+                            "Failed to update {}, the current version is kept ⚠️: {}",
+                            tarball_name,
+                            e
+                        ),
+                    }
+                }
+            }
+        })
+        .buffer_unordered(options.max_concurrent_downloads.max(1))
+        .collect::<Vec<()>>()
+        .await;
+}
+
+/// Returns the tarballs whose version differs from the version of the loaded model, along with
+/// their new version. Tarballs of models which are not loaded, and tarballs without a known
+/// version which cannot be compared, are always returned.
+pub fn detect_updates(
+    models: &LoadedModels,
+    versions: BTreeMap<String, Option<String>>,
+) -> Vec<(String, Option<String>)> {
+    versions
+        .into_iter()
+        .filter(|(tarball_name, version)| {
+            let model_name = match tarball_model_name(tarball_name) {
+                Some((_, model_name)) => model_name,
+                None => return false,
+            };
+            match (models.get(&model_name), version) {
+                (Some(model), Some(version)) => model.info.source_version.as_ref() != Some(version),
+                _ => true,
+            }
+        })
+        .collect()
+}
+
+/// Returns the framework and the model name of a `<model_framework>-<model_name>.tar.gz` tarball.
+pub(crate) fn tarball_model_name(tarball_name: &str) -> Option<(ModelFramework, ModelName)> {
+    let stem = tarball_name.strip_suffix(".tar.gz").unwrap_or(tarball_name);
+    let model_framework = extract_framework(stem.to_string())?;
+    let model_name = stem.strip_prefix(format!("{}-", model_framework).as_str())?;
+    Some((model_framework, sanitize_model_name(model_name)))
+}
+
+/// Returns true if the tarball holds one of the given models, which selects every tarball if no models are given.
+///
+/// # Arguments
+///
+/// * `tarball_name` - The name of a `<model_framework>-<model_name>.tar.gz` tarball.
+/// * `model_names` - The names of the models to select, without the framework prefix.
+pub fn is_selected(tarball_name: &str, model_names: Option<&[ModelName]>) -> bool {
+    let model_names = match model_names {
+        None => return true,
+        Some(model_names) => model_names,
+    };
+    match tarball_model_name(tarball_name) {
+        Some((_, model_name)) => model_names.contains(&model_name),
+        None => false,
+    }
+}
+
+/// Loads the model which was unpacked from a `<model_framework>-<model_name>.tar.gz` tarball into `model_store_dir`.
+///
+/// # Errors
+///
+/// This function will return an error if the framework cannot be extracted from the tarball name
+/// or the model fails to load.
+pub async fn load_unpacked_model(
+    model_store_dir: &str,
+    tarball_name: &str,
+) -> Result<(ModelName, Model)> {
+    let (model_framework, model_name) = match tarball_model_name(tarball_name) {
+        Some(names) => names,
+        None => {
+            return Err(Error::Load(anyhow::anyhow!(
+                "Failed to extract framework from {} ❌",
+                tarball_name
+            )))
+        }
+    };
+    let model_path = append_model_format(
+        model_framework,
+        format!("{}/{}-{}", model_store_dir, model_framework, model_name),
+    );
+    let predictor = load_predictor(model_framework, model_path.as_str()).await?;
+    let model = Model::new(
+        predictor,
+        model_name.clone(),
+        model_framework,
+        model_path,
+        Utc::now().to_rfc3339(),
+    );
+    Ok((model_name, model))
+}
+
+/// Loads a machine learning model based on the specified framework and model path.
+///
+/// A model which fails to load is recorded in the skipped models in scope, so that it is reported by the manager.
+///
+/// # Arguments
+///
+/// * `model_framework` - An enum representing the framework of the model to be loaded.
+/// * `model_path` - A string slice that holds the path to the model file.
+///
+/// # Returns
+///
+/// This function returns a Result containing an `Arc<dyn Predictor>` on success, or an `Error::Load` on failure.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The model framework is unsupported.
+/// * The model fails to load due to an internal error specific to the framework.
+///
+#[tracing::instrument]
+pub async fn load_predictor(
+    model_framework: ModelFramework,
+    model_path: &str,
+) -> Result<Arc<Predictor>> {
+    match load_framework_predictor(model_framework, model_path).await {
+        Ok(predictor) => Ok(predictor),
+        Err(e) => {
+            let file_name = Path::new(model_path)
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .unwrap_or(model_path);
+            Err(skipped::record_failure(file_name, model_path, e))
+        }
+    }
+}
+
+/// Loads the predictor of a model of the given framework, see `load_predictor`.
+async fn load_framework_predictor(
+    model_framework: ModelFramework,
+    model_path: &str,
+) -> Result<Arc<Predictor>> {
+    check_requirements(model_path)?;
+    check_memory_budget(model_path)?;
+    if model_framework == TENSORFLOW {
+        match load_decrypted(model_path, model::tensorflow::Tensorflow::load) {
+            Ok(predictor) => Ok(Arc::new(Predictor::Tensorflow(predictor))),
+            Err(e) => {
+                tracing::error!("Failed to load Tensorflow model: {}", e);
+                Err(Error::Load(
+                    anyhow::Error::from(e).context("Failed to load Tensorflow model"),
+                ))
+            }
+        }
+    } else if (model_framework == TORCH) || (model_framework == PYTORCH) {
+        match load_decrypted(model_path, model::torch::Torch::load) {
+            Ok(predictor) => Ok(Arc::new(Predictor::Torch(predictor))),
+            Err(e) => {
+                tracing::error!("Failed to load Torch model: {}", e);
+                Err(Error::Load(
+                    anyhow::Error::from(e).context("Failed to load Torch model"),
+                ))
+            }
+        }
+    } else if model_framework == CATBOOST {
+        match load_decrypted(model_path, model::catboost::Catboost::load) {
+            Ok(predictor) => Ok(Arc::new(Predictor::Catboost(predictor))),
+            Err(e) => {
+                tracing::error!("Failed to load Catboost model: {}", e);
+                Err(Error::Load(
+                    anyhow::Error::from(e).context("Failed to load Catboost model"),
+                ))
+            }
+        }
+    } else if model_framework == LIGHTGBM {
+        match load_decrypted(model_path, model::lightgbm::LightGBM::load) {
+            Ok(predictor) => Ok(Arc::new(Predictor::LightGBM(predictor))),
+            Err(e) => {
+                tracing::error!("Failed to load LightGBM model: {}", e);
+                Err(Error::Load(
+                    anyhow::Error::from(e).context("Failed to load LightGBM model"),
+                ))
+            }
+        }
+    } else if model_framework == XGBOOST {
+        match load_decrypted(model_path, model::xgboost::XGBoost::load) {
+            Ok(predictor) => Ok(Arc::new(Predictor::XGBoost(predictor))),
+            Err(e) => {
+                tracing::error!("Failed to load XGBoost model: {}", e);
+                Err(Error::Load(
+                    anyhow::Error::from(e).context("Failed to load XGBoost model"),
+                ))
+            }
+        }
+    } else if model_framework == TENSORRT {
+        match load_decrypted_blocking(model_path, load_tensorrt).await {
+            Ok(predictor) => Ok(Arc::new(predictor)),
+            Err(e) => {
+                tracing::error!("Failed to load TensorRT engine: {}", e);
+                Err(Error::Load(
+                    anyhow::Error::from(e).context("Failed to load TensorRT engine"),
+                ))
+            }
+        }
+    } else if model_framework == TRANSFORMERS {
+        match load_decrypted(model_path, load_transformers) {
+            Ok(predictor) => Ok(Arc::new(predictor)),
+            Err(e) => {
+                tracing::error!("Failed to load transformers model: {}", e);
+                Err(Error::Load(
+                    anyhow::Error::from(e).context("Failed to load transformers model"),
+                ))
+            }
+        }
+    } else if model_framework == GGUF {
+        match load_decrypted(model_path, load_gguf) {
+            Ok(predictor) => Ok(Arc::new(predictor)),
+            Err(e) => {
+                tracing::error!("Failed to load GGUF model: {}", e);
+                Err(Error::Load(
+                    anyhow::Error::from(e).context("Failed to load GGUF model"),
+                ))
+            }
+        }
+    } else if model_framework == TFLITE {
+        match load_decrypted(model_path, load_tflite) {
+            Ok(predictor) => Ok(Arc::new(predictor)),
+            Err(e) => {
+                tracing::error!("Failed to load TensorFlow Lite model: {}", e);
+                Err(Error::Load(
+                    anyhow::Error::from(e).context("Failed to load TensorFlow Lite model"),
+                ))
+            }
+        }
+    } else if model_framework == OPENVINO {
+        match load_decrypted(model_path, load_openvino) {
+            Ok(predictor) => Ok(Arc::new(predictor)),
+            Err(e) => {
+                tracing::error!("Failed to load OpenVINO model: {}", e);
+                Err(Error::Load(
+                    anyhow::Error::from(e).context("Failed to load OpenVINO model"),
+                ))
+            }
+        }
+    } else if custom::is_registered(model_framework) {
+        match load_decrypted(model_path, |path| custom::load(model_framework, path)) {
+            Ok(predictor) => Ok(Arc::new(predictor)),
+            Err(e) => {
+                tracing::error!("Failed to load {} model: {}", model_framework, e);
+                Err(Error::Load(anyhow::Error::from(e).context(format!(
+                    "Failed to load {} model",
+                    model_framework
+                ))))
+            }
+        }
+    } else {
+        tracing::error!("unsupported model framework: {}", model_framework);
+        Err(Error::Load(anyhow::anyhow!(
+            "unsupported model framework: {}",
+            model_framework
+        )))
+    }
+}
+
+/// Checks that the host satisfies the requirements from the `requirements.json` file of the model, so that
+/// a model which cannot run on the host fails to load with a clear reason instead of crashing in native code.
+///
+/// # Arguments
+///
+/// * `model_path` - The path to the unpacked model artifact.
+///
+/// # Errors
+///
+/// Returns an `Error::Load` listing the requirements which the host does not satisfy.
+pub fn check_requirements(model_path: &str) -> Result<()> {
+    let requirements = read_provenance(model_path).requirements;
+    match requirements.check(HostCapabilities::current()) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            tracing::error!("Failed to load model {} ❌: {}", model_path, e);
+            Err(Error::Load(anyhow::anyhow!(
+                "Failed to load model {} ❌: {}",
+                model_path,
+                e
+            )))
+        }
+    }
+}
+
+/// Checks the size of the model artifact against the memory budget of the `Manager` loading it, if any, so that a
+/// model which does not fit is refused before it is loaded instead of pushing the process over the budget.
+///
+/// # Arguments
+///
+/// * `model_path` - The path to the unpacked model artifact.
+///
+/// # Errors
+///
+/// Returns an `Error::Load` if loading the artifact would exceed the memory budget.
+pub fn check_memory_budget(model_path: &str) -> Result<()> {
+    match memory::check_artifact(Path::new(model_path)) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            tracing::error!("Failed to load model {} ❌: {}", model_path, e);
+            Err(Error::Load(anyhow::anyhow!(
+                "Failed to load model {} ❌: {}",
+                model_path,
+                e
+            )))
+        }
+    }
+}
+
+/// Evaluates the validation set shipped with a model, if any, before the model is promoted, so that an obviously
+/// broken retrain does not go live. Every evaluation is logged and counted by its `ValidationOutcome`.
+///
+/// # Arguments
+///
+/// * `model` - The model to promote.
+/// * `current` - The version of the model it replaces, if the model is loaded, to check the drift of its outputs.
+///
+/// # Errors
+///
+/// Returns an `Error::Load` if the validation set cannot be predicted or the model misses its acceptance criteria.
+pub fn accept_model(model: &Model, current: Option<&Model>) -> Result<()> {
+    // the validation set is read from its file, so that it is not kept in memory while the model is served
+    let validation = match read_validation_set(model.info.path.as_str()) {
+        Ok(Some(validation)) => validation,
+        Ok(None) => return Ok(()),
+        Err(e) => {
+            ValidationOutcome::Rejected.record();
+            tracing::error!("Refused to promote model {} ❌: {}", model.info.name, e);
+            return Err(Error::Load(
+                e.context(format!("Refused to promote model {}", model.info.name)),
+            ));
+        }
+    };
+    let current = current.map(|current| current.predictor.as_ref());
+    match validation.evaluate(&model.predictor, current) {
+        Ok(report) => {
+            ValidationOutcome::Accepted.record();
+            tracing::info!(
+                "Model {} met its acceptance criteria ✅: {:?}",
+                model.info.name,
+                report
+            );
+            Ok(())
+        }
+        Err(e) => {
+            ValidationOutcome::Rejected.record();
+            tracing::error!("Refused to promote model {} ❌: {}", model.info.name, e);
+            Err(Error::Load(anyhow::anyhow!(
+                "Refused to promote model {} ❌: {}",
+                model.info.name,
+                e
+            )))
+        }
+    }
+}
+
+/// Makes a prediction with the warmup input shipped in the `bundle/warmup.json` file of a model, if any, so that the
+/// lazy allocations of the framework happen before the model serves its first request.
+///
+/// # Errors
+///
+/// Returns an `Error::Load` if the warmup input cannot be parsed or predicted.
+pub fn warm_up_model(model: &Model) -> Result<()> {
+    let warmup_input = match &model.bundle.warmup_input {
+        Some(warmup_input) => warmup_input,
+        None => return Ok(()),
+    };
+    let start = time::Instant::now();
+    let result = ModelInput::from_str(warmup_input).and_then(|mut input| {
+        let options = &model.info.predict_options;
+        model
+            .bundle
+            .preprocess(&mut input, options.unknown_features.unwrap_or_default())?;
+        model
+            .predictor
+            .predict_with_options(input, options)
+            .map_err(anyhow::Error::from)
+    });
+    match result {
+        Ok(_) => {
+            tracing::info!(
+                "Warmed up model {} in {}ms ✅",
+                model.info.name,
+                start.elapsed().as_millis()
+            );
+            Ok(())
+        }
+        Err(e) => {
+            tracing::error!("Failed to warm up model {} ❌: {}", model.info.name, e);
+            Err(Error::Load(anyhow::anyhow!(
+                "Failed to warm up model {} ❌: {}",
+                model.info.name,
+                e
+            )))
+        }
+    }
+}
+
+/// Runs the init hooks of a model, warms it up and then checks its acceptance criteria, before the model is served.
+///
+/// The model is torn down again if it is refused, so that the resources set up by its init hooks are released.
+///
+/// # Arguments
+///
+/// * `model` - The model to serve.
+/// * `current` - The version of the model it replaces, if the model is loaded.
+///
+/// # Returns
+///
+/// The model, with the status of its init hooks in its metadata.
+///
+/// # Errors
+///
+/// Returns an `Error::Load` if an init hook fails or times out, if the warmup input cannot be predicted, or if the model
+/// misses its acceptance criteria.
+pub async fn prepare_model(mut model: Model, current: Option<&Model>) -> Result<Model> {
+    // the settings are resolved on every load, so that rotated secrets are picked up by updates
+    let settings = match settings::resolve(model.info.name.as_str()) {
+        Ok(settings) => settings,
+        Err(e) => {
+            return Err(Error::Load(anyhow::anyhow!(
+                "Failed to initialize model {} ❌: {}",
+                model.info.name,
+                e
+            )))
+        }
+    };
+    let predictors: Vec<&Arc<Predictor>> = std::iter::once(&model.predictor)
+        .chain(model.variants.values())
+        .collect();
+    let status = run_hook(
+        async {
+            for predictor in predictors.iter() {
+                predictor.init(&settings).await?;
+            }
+            Ok(())
+        },
+        model.predictor.init_timeout(),
+    )
+    .await;
+
+    if let Some(e) = &status.error {
+        tracing::error!("Failed to initialize model {} ❌: {}", model.info.name, e);
+        let e = Error::Load(anyhow::anyhow!(
+            "Failed to initialize model {} ❌: {}",
+            model.info.name,
+            e
+        ));
+        teardown_model(&model).await;
+        return Err(e);
+    }
+    tracing::info!(
+        "Initialized model {} in {}ms ✅",
+        model.info.name,
+        status.duration_ms
+    );
+    model.info.init = Some(status);
+
+    if let Err(e) = warm_up_model(&model).and_then(|_| accept_model(&model, current)) {
+        teardown_model(&model).await;
+        return Err(e);
+    }
+    Ok(model)
+}
+
+/// Runs the teardown hooks of a model which is no longer served.
+///
+/// # Returns
+///
+/// The status of the teardown hooks, which is also logged.
+pub async fn teardown_model(model: &Model) -> HookStatus {
+    model.teardown.done.store(true, Ordering::SeqCst);
+    let predictors: Vec<Arc<Predictor>> = std::iter::once(&model.predictor)
+        .chain(model.variants.values())
+        .cloned()
+        .collect();
+    teardown_predictors(model.info.name.as_str(), &predictors).await
+}
+
+async fn teardown_predictors(model_name: &str, predictors: &[Arc<Predictor>]) -> HookStatus {
+    let timeout = match predictors.first() {
+        Some(predictor) => predictor.teardown_timeout(),
+        None => DEFAULT_TEARDOWN_TIMEOUT,
+    };
+    let status = run_hook(
+        async {
+            for predictor in predictors {
+                predictor.teardown().await?;
+            }
+            Ok(())
+        },
+        timeout,
+    )
+    .await;
+
+    match &status.error {
+        None => tracing::info!("Tore down model {} ✅", model_name),
+        Some(e) => tracing::error!("Failed to tear down model {} ❌: {}", model_name, e),
+    }
+    status
+}
+
+/// Retires a model which has been replaced, deleted or detached, so that it is torn down in the background once
+/// the last reference to it is dropped, i.e. once the requests still holding it have finished.
+///
+/// The model is not torn down if there is no tokio runtime to run its teardown hooks on.
+pub fn schedule_teardown(model: Arc<Model>) {
+    let runtime = match tokio::runtime::Handle::try_current() {
+        Ok(runtime) => runtime,
+        Err(_) => {
+            tracing::warn!(
+                "Skipped tearing down model {} outside of a tokio runtime ⚠️",
+                model.info.name
+            );
+            return;
+        }
+    };
+    let scheduled = ScheduledTeardown {
+        runtime,
+        model_name: model.info.name.clone(),
+        predictors: std::iter::once(&model.predictor)
+            .chain(model.variants.values())
+            .cloned()
+            .collect(),
+    };
+    match model.teardown.scheduled.lock() {
+        Ok(mut teardown) => *teardown = Some(scheduled),
+        Err(poisoned) => *poisoned.into_inner() = Some(scheduled),
+    }
+}
+
+/// Waits for the teardowns of the retired models which are running in the background, e.g. on shutdown.
+///
+/// # Arguments
+///
+/// * `timeout` - The time allowed for the teardowns, after which they are abandoned.
+///
+/// # Returns
+///
+/// The number of teardowns which were still running after the timeout.
+pub async fn wait_for_teardowns(timeout: time::Duration) -> usize {
+    let deadline = time::Instant::now() + timeout;
+    while PENDING_TEARDOWNS.load(Ordering::SeqCst) > 0 && time::Instant::now() < deadline {
+        tokio::time::sleep(TEARDOWN_POLL_INTERVAL).await;
+    }
+    PENDING_TEARDOWNS.load(Ordering::SeqCst)
+}
 
 /// Extracts the model framework from the given model path.
 ///
-/// This function checks the provided model path for the presence of specific framework identifiers and returns the corresponding `ModelFramework` enum if a match is found.
+/// The framework is the `<framework>` prefix of the `<framework>-<model_name>` file name at the end of the model path,
+/// so that a model whose name mentions another framework, e.g. `transformers-my_torch_model`, is not mistaken for a
+/// model of that framework. A bare framework name is a framework too.
+/// The frameworks registered with `model::custom::register` are matched on the `<framework>-` prefix of the file name.
 ///
 /// # Arguments
 ///
@@ -393,23 +1626,26 @@ pub async fn load_predictor(
 /// # Returns
 ///
 /// This function returns an `Option<ModelFramework>`:
-/// * `Some(ModelFramework)` if a matching framework identifier is found in the model path.
-/// * `None` if no matching framework identifier is found.
+/// * `Some(ModelFramework)` if the file name starts with a framework identifier.
+/// * `None` if the file name does not start with a framework identifier.
 ///
 pub fn extract_framework(model_path: String) -> Option<ModelFramework> {
-    if model_path.contains(TENSORFLOW) {
-        Some(TENSORFLOW)
-    } else if model_path.contains(PYTORCH) {
-        Some(PYTORCH)
-    } else if model_path.contains(TORCH) {
-        Some(TORCH)
-    } else if model_path.contains(CATBOOST) {
-        Some(CATBOOST)
-    } else if model_path.contains(LIGHTGBM) {
-        Some(LIGHTGBM)
-    } else {
-        None
+    let file_name = model_path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    if let Some(framework) = custom::framework_of(file_name) {
+        return Some(framework);
     }
+    let prefix = match file_name.split_once('-') {
+        Some((prefix, _)) => prefix,
+        None => file_name,
+    };
+    FRAMEWORKS
+        .iter()
+        .find(|framework| **framework == prefix)
+        .copied()
 }
 
 /// Removes everything after the first dot ('.') from the input string.
@@ -453,12 +1689,190 @@ pub fn append_model_format(model_framework: ModelFramework, model_path: String)
         return format!("{}.txt", model_path);
     }
 
+    if model_framework == XGBOOST {
+        return format!("{}.json", model_path);
+    }
+
+    if model_framework == TENSORRT {
+        return format!("{}.engine", model_path);
+    }
+
+    if model_framework == TFLITE {
+        return format!("{}.tflite", model_path);
+    }
+
     model_path
 }
 
+/// Loads a model on a blocking thread, for the frameworks whose loads wait on a device, e.g. TensorRT engines, so
+/// that the runtime thread keeps serving its other tasks while the model loads.
+///
+/// # Arguments
+///
+/// * `model_path` - The path to the unpacked model artifact.
+/// * `load` - The function which loads the decrypted artifact, see `load_decrypted`.
+///
+/// # Errors
+///
+/// Returns an `Err` if the model cannot be loaded or the blocking thread panicked.
+async fn load_decrypted_blocking(
+    model_path: &str,
+    load: fn(&str) -> Result<Predictor>,
+) -> Result<Predictor> {
+    let model_path = model_path.to_string();
+    match tokio::task::spawn_blocking(move || load_decrypted(model_path.as_str(), load)).await {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::error!("Failed to load model on a blocking thread ❌: {}", e);
+            Err(Error::Load(anyhow::anyhow!(
+                "Failed to load model on a blocking thread ❌: {}",
+                e
+            )))
+        }
+    }
+}
+
+/// Loads a TensorRT engine, which is only supported if jams is built with the `tensorrt` feature.
+///
+/// # Errors
+///
+/// Returns an `Err` if the engine cannot be loaded or the `tensorrt` feature is not enabled.
+#[cfg(feature = "tensorrt")]
+fn load_tensorrt(path: &str) -> Result<Predictor> {
+    model::tensorrt::TensorRT::load(path).map(Predictor::TensorRT)
+}
+
+/// Loads a TensorRT engine, which is only supported if jams is built with the `tensorrt` feature.
+///
+/// # Errors
+///
+/// Returns an `Err` if the engine cannot be loaded or the `tensorrt` feature is not enabled.
+#[cfg(not(feature = "tensorrt"))]
+fn load_tensorrt(path: &str) -> Result<Predictor> {
+    tracing::error!(
+        "Failed to load TensorRT engine from file {}: jams is built without the tensorrt feature",
+        path
+    );
+    Err(Error::Load(anyhow::anyhow!(
+        "Failed to load TensorRT engine from file {}: jams is built without the tensorrt feature",
+        path
+    )))
+}
+
+/// Loads a transformers model directory, which is only supported if jams is built with the `transformers` feature.
+///
+/// # Errors
+///
+/// Returns an `Err` if the model cannot be loaded or the `transformers` feature is not enabled.
+#[cfg(feature = "transformers")]
+fn load_transformers(path: &str) -> Result<Predictor> {
+    model::transformers::Transformers::load(path).map(Predictor::Transformers)
+}
+
+/// Loads a transformers model directory, which is only supported if jams is built with the `transformers` feature.
+///
+/// # Errors
+///
+/// Returns an `Err` if the model cannot be loaded or the `transformers` feature is not enabled.
+#[cfg(not(feature = "transformers"))]
+fn load_transformers(path: &str) -> Result<Predictor> {
+    tracing::error!(
+        "Failed to load transformers model from directory {}: jams is built without the transformers feature",
+        path
+    );
+    Err(Error::Load(anyhow::anyhow!(
+        "Failed to load transformers model from directory {}: jams is built without the transformers feature",
+        path
+    )))
+}
+
+/// Loads a GGUF model directory, which is only supported if jams is built with the `gguf` feature.
+///
+/// # Errors
+///
+/// Returns an `Err` if the model cannot be loaded or the `gguf` feature is not enabled.
+#[cfg(feature = "gguf")]
+fn load_gguf(path: &str) -> Result<Predictor> {
+    model::gguf::Gguf::load(path).map(Predictor::Gguf)
+}
+
+/// Loads a GGUF model directory, which is only supported if jams is built with the `gguf` feature.
+///
+/// # Errors
+///
+/// Returns an `Err` if the model cannot be loaded or the `gguf` feature is not enabled.
+#[cfg(not(feature = "gguf"))]
+fn load_gguf(path: &str) -> Result<Predictor> {
+    tracing::error!(
+        "Failed to load GGUF model from directory {}: jams is built without the gguf feature",
+        path
+    );
+    Err(Error::Load(anyhow::anyhow!(
+        "Failed to load GGUF model from directory {}: jams is built without the gguf feature",
+        path
+    )))
+}
+
+/// Loads a TensorFlow Lite model, which is only supported if jams is built with the `tflite` feature.
+///
+/// # Errors
+///
+/// Returns an `Err` if the model cannot be loaded or the `tflite` feature is not enabled.
+#[cfg(feature = "tflite")]
+fn load_tflite(path: &str) -> Result<Predictor> {
+    model::tflite::TfLite::load(path).map(Predictor::TfLite)
+}
+
+/// Loads a TensorFlow Lite model, which is only supported if jams is built with the `tflite` feature.
+///
+/// # Errors
+///
+/// Returns an `Err` if the model cannot be loaded or the `tflite` feature is not enabled.
+#[cfg(not(feature = "tflite"))]
+fn load_tflite(path: &str) -> Result<Predictor> {
+    tracing::error!(
+        "Failed to load TensorFlow Lite model from file {}: jams is built without the tflite feature",
+        path
+    );
+    Err(Error::Load(anyhow::anyhow!(
+        "Failed to load TensorFlow Lite model from file {}: jams is built without the tflite feature",
+        path
+    )))
+}
+
+/// Loads an OpenVINO model directory, which is only supported if jams is built with the `openvino` feature.
+///
+/// # Errors
+///
+/// Returns an `Err` if the model cannot be loaded or the `openvino` feature is not enabled.
+#[cfg(feature = "openvino")]
+fn load_openvino(path: &str) -> Result<Predictor> {
+    model::openvino::OpenVino::load(path).map(Predictor::OpenVino)
+}
+
+/// Loads an OpenVINO model directory, which is only supported if jams is built with the `openvino` feature.
+///
+/// # Errors
+///
+/// Returns an `Err` if the model cannot be loaded or the `openvino` feature is not enabled.
+#[cfg(not(feature = "openvino"))]
+fn load_openvino(path: &str) -> Result<Predictor> {
+    tracing::error!(
+        "Failed to load OpenVINO model from directory {}: jams is built without the openvino feature",
+        path
+    );
+    Err(Error::Load(anyhow::anyhow!(
+        "Failed to load OpenVINO model from directory {}: jams is built without the openvino feature",
+        path
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::memory::MemoryBudget;
+    use crate::model::validation::VALIDATION_FILE_NAME;
+    use crate::model_store::groups::DeploymentGroup;
 
     #[test]
     fn successfully_create_model_artefact() {
@@ -479,46 +1893,197 @@ mod tests {
         );
 
         // Assert
-        assert_eq!(model_name, model.info.name);
-        assert_eq!(path, model.info.path.as_str());
-        assert_eq!(framework, model.info.framework);
-        assert_eq!(timestamp.to_rfc3339(), model.info.last_updated);
+        assert_eq!(model_name, model.info.name);
+        assert_eq!(path, model.info.path.as_str());
+        assert_eq!(framework, model.info.framework);
+        assert_eq!(timestamp.to_rfc3339(), model.info.last_updated);
+    }
+
+    #[tokio::test]
+    async fn successfully_load_tensorflow_framework_model() {
+        let path = "tests/model_storage/models/tensorflow-my_awesome_penguin_model";
+        let framework = TENSORFLOW;
+
+        // Load Model
+        let model = load_predictor(framework, path).await;
+
+        // Assert
+        assert!(model.is_ok());
+    }
+
+    #[tokio::test]
+    async fn successfully_load_torch_framework_model() {
+        let path = "tests/model_storage/models/torch-my_awesome_penguin_model.pt";
+        let framework = TORCH;
+
+        // Load Model
+        let model = load_predictor(framework, path).await;
+
+        // Assert
+        assert!(model.is_ok());
+    }
+
+    #[tokio::test]
+    async fn successfully_load_pytorch_framework_model() {
+        let path = "tests/model_storage/models/pytorch-my_awesome_californiahousing_model.pt";
+        let framework = PYTORCH;
+
+        // Load Model
+        let model = load_predictor(framework, path).await;
+
+        // Assert
+        assert!(model.is_ok());
+    }
+
+    #[tokio::test]
+    async fn fails_to_load_model_when_its_artifact_exceeds_the_memory_budget() {
+        // Arrange
+        let path = "tests/model_storage/models/lightgbm-my_awesome_reg_model.txt";
+        let budget = MemoryBudget::new(1024);
+
+        // Act
+        let model = memory::with_budget(Some(budget), load_predictor(LIGHTGBM, path)).await;
+        let unbudgeted = load_predictor(LIGHTGBM, path).await;
+
+        // Assert
+        let message = model.err().unwrap().to_string();
+        assert!(message.contains("Memory budget exceeded"));
+        assert!(unbudgeted.is_ok());
+    }
+
+    #[tokio::test]
+    async fn fails_to_load_model_and_keeps_the_error_of_the_framework() {
+        // Arrange
+        let path = "tests/model_storage/models/lightgbm-does_not_exist.txt";
+
+        // Act
+        let model = load_predictor(LIGHTGBM, path).await;
+
+        // Assert
+        let source = match model.err().unwrap() {
+            Error::Load(e) => e.downcast::<Error>().ok(),
+            _ => None,
+        };
+        assert!(matches!(source, Some(Error::Load(_))));
+    }
+
+    /// Copies the binary LightGBM model into `dir` with a provenance file requiring a GPU and more memory than
+    /// any host has.
+    fn model_with_unmet_requirements(dir: &std::path::Path) -> std::path::PathBuf {
+        std::fs::create_dir_all(dir.join(PROVENANCE_DIRECTORY_NAME)).unwrap();
+        let path = dir.join("lightgbm-my_awesome_binary_model_2.txt");
+        std::fs::copy(
+            "tests/model_storage/models/lightgbm-my_awesome_binary_model_2.txt",
+            &path,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join(PROVENANCE_DIRECTORY_NAME)
+                .join("lightgbm-my_awesome_binary_model_2.txt.json"),
+            r#"{"source_uri": "", "source_version": null, "labels": {}, "requirements": {"gpu": true, "min_memory_mb": 1099511627776}}"#,
+        )
+        .unwrap();
+        path
     }
 
     #[tokio::test]
-    async fn successfully_load_tensorflow_framework_model() {
-        let path = "tests/model_storage/models/tensorflow-my_awesome_penguin_model";
-        let framework = TENSORFLOW;
+    async fn fails_to_load_model_when_host_does_not_satisfy_its_requirements() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = model_with_unmet_requirements(dir.path());
 
-        // Load Model
-        let model = load_predictor(framework, path).await;
+        // Act
+        let model = load_predictor(LIGHTGBM, path.to_str().unwrap()).await;
 
         // Assert
-        assert!(model.is_ok());
+        let message = model.err().unwrap().to_string();
+        assert!(message.contains("Host does not satisfy the requirements of the model"));
     }
 
     #[tokio::test]
-    async fn successfully_load_torch_framework_model() {
-        let path = "tests/model_storage/models/torch-my_awesome_penguin_model.pt";
-        let framework = TORCH;
+    async fn successfully_record_model_skipped_for_its_requirements() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = model_with_unmet_requirements(dir.path());
 
-        // Load Model
-        let model = load_predictor(framework, path).await;
+        let skipped = Arc::new(skipped::SkippedModels::default());
+
+        // Act
+        let models = skipped::with_skipped(
+            skipped.clone(),
+            load_models(dir.path().to_str().unwrap().to_string()),
+        )
+        .await
+        .unwrap();
 
         // Assert
-        assert!(model.is_ok());
+        assert!(models.is_empty());
+        let skipped = skipped.models().pop().unwrap();
+        assert_eq!(skipped.path, path.to_str().unwrap());
+        assert!(skipped
+            .reason
+            .contains("Host does not satisfy the requirements of the model"));
     }
 
-    #[tokio::test]
-    async fn successfully_load_pytorch_framework_model() {
-        let path = "tests/model_storage/models/pytorch-my_awesome_californiahousing_model.pt";
-        let framework = PYTORCH;
+    /// Predicts the constant read from its artifact for every row.
+    struct Constant(f64);
+
+    impl model::predict::Predict for Constant {
+        fn predict(&self, input: ModelInput) -> Result<model::output::ModelOutput> {
+            Ok(model::output::ModelOutput {
+                predictions: std::collections::HashMap::from([(
+                    model::output::DEFAULT_OUTPUT_KEY.to_string(),
+                    vec![vec![self.0]; input.num_rows()],
+                )]),
+                labels: Default::default(),
+                shapes: Default::default(),
+                texts: Default::default(),
+            })
+        }
+    }
 
-        // Load Model
-        let model = load_predictor(framework, path).await;
+    impl Lifecycle for Constant {}
+
+    fn load_constant(path: &str) -> Result<Constant> {
+        match std::fs::read_to_string(path).map(|value| value.trim().parse::<f64>()) {
+            Ok(Ok(value)) => Ok(Constant(value)),
+            _ => Err(Error::Load(anyhow::anyhow!(
+                "Failed to read constant from {}",
+                path
+            ))),
+        }
+    }
+
+    #[tokio::test]
+    async fn successfully_load_models_of_registered_framework() {
+        // Arrange
+        custom::register("constant", load_constant).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("constant-my_model.txt"), "0.5").unwrap();
+        let input = ModelInput::from_str(r#"{"feature_1": [1.0, 2.0]}"#).unwrap();
+
+        // Act
+        let models = load_models(dir.path().to_str().unwrap().to_string())
+            .await
+            .unwrap();
+        let predictor = load_predictor(
+            "constant",
+            dir.path().join("constant-my_model.txt").to_str().unwrap(),
+        )
+        .await;
 
         // Assert
-        assert!(model.is_ok());
+        let model = models.get("my_model").unwrap();
+        assert_eq!(model.info.framework, "constant");
+        assert_eq!(
+            model.predictor.predict(input).unwrap().predictions["predictions"],
+            vec![vec![0.5], vec![0.5]]
+        );
+        assert!(predictor.is_ok());
+        assert_eq!(
+            extract_framework("model/directory/constant-my_model".to_string()),
+            Some("constant")
+        );
     }
 
     #[tokio::test]
@@ -600,6 +2165,92 @@ mod tests {
         assert_eq!(result.unwrap(), CATBOOST)
     }
 
+    #[test]
+    fn successfully_extract_framework_from_path_when_xgboost_framework() {
+        let path = "model/directory/xgboost-my_model";
+
+        let result = extract_framework(path.to_string());
+
+        // assert
+        assert!(result.is_some());
+        assert_eq!(result.unwrap(), XGBOOST)
+    }
+
+    #[test]
+    fn successfully_extract_framework_from_path_when_tensorrt_framework() {
+        let path = "model/directory/tensorrt-my_model";
+
+        let result = extract_framework(path.to_string());
+
+        // assert
+        assert!(result.is_some());
+        assert_eq!(result.unwrap(), TENSORRT)
+    }
+
+    #[test]
+    fn successfully_extract_framework_from_path_when_transformers_framework() {
+        let path = "model/directory/transformers-my_model";
+
+        let result = extract_framework(path.to_string());
+
+        // assert
+        assert!(result.is_some());
+        assert_eq!(result.unwrap(), TRANSFORMERS)
+    }
+
+    #[test]
+    fn successfully_extract_framework_from_path_when_gguf_framework() {
+        let path = "model/directory/gguf-my_model";
+
+        let result = extract_framework(path.to_string());
+
+        // assert
+        assert!(result.is_some());
+        assert_eq!(result.unwrap(), GGUF)
+    }
+
+    #[test]
+    fn successfully_extract_framework_from_path_when_tflite_framework() {
+        let path = "model/directory/tflite-my_model";
+
+        let result = extract_framework(path.to_string());
+
+        // assert
+        assert!(result.is_some());
+        assert_eq!(result.unwrap(), TFLITE)
+    }
+
+    #[test]
+    fn successfully_extract_framework_from_path_when_openvino_framework() {
+        let path = "model/directory/openvino-my_model";
+
+        let result = extract_framework(path.to_string());
+
+        // assert
+        assert!(result.is_some());
+        assert_eq!(result.unwrap(), OPENVINO)
+    }
+
+    #[test]
+    fn successfully_extract_framework_from_prefix_when_model_name_contains_another_framework() {
+        let path = "model/directory/transformers-my_torch_model";
+
+        let result = extract_framework(path.to_string());
+
+        // assert
+        assert_eq!(result, Some(TRANSFORMERS))
+    }
+
+    #[test]
+    fn fails_to_extract_framework_from_path_when_framework_is_not_the_prefix() {
+        let path = "model/directory/my_pytorch-model";
+
+        let result = extract_framework(path.to_string());
+
+        // assert
+        assert!(result.is_none());
+    }
+
     #[test]
     fn fails_to_extract_framework_from_path_when_unknown_framework() {
         let path = "model/directory/fbprophet-my_model";
@@ -610,6 +2261,24 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn successfully_select_tarballs_of_the_given_models() {
+        // Arrange
+        let model_names = vec!["titanic_model".to_string()];
+
+        // Act
+        let selected = [
+            "catboost-titanic_model.tar.gz",
+            "lightgbm-my_awesome_reg_model.tar.gz",
+            "titanic_model.tar.gz",
+        ]
+        .map(|tarball_name| is_selected(tarball_name, Some(&model_names)));
+
+        // Assert
+        assert_eq!(selected, [true, false, false]);
+        assert!(is_selected("lightgbm-my_awesome_reg_model.tar.gz", None));
+    }
+
     #[test]
     fn sanitize_model_name_when_the_name_has_period() {
         let model_name = "my_torch_model.pt";
@@ -650,6 +2319,91 @@ mod tests {
         assert_eq!(result, "model/directory/my_lightgbm_model.txt")
     }
 
+    #[test]
+    fn append_model_format_when_model_framework_is_xgboost() {
+        let path = "model/directory/my_xgboost_model";
+
+        let result = append_model_format(XGBOOST, path.to_string());
+
+        // assert
+        assert_eq!(result, "model/directory/my_xgboost_model.json")
+    }
+
+    #[test]
+    fn append_model_format_when_model_framework_is_tensorrt() {
+        let path = "model/directory/my_tensorrt_model";
+
+        let result = append_model_format(TENSORRT, path.to_string());
+
+        // assert
+        assert_eq!(result, "model/directory/my_tensorrt_model.engine")
+    }
+
+    #[test]
+    fn append_model_format_when_model_framework_is_tflite() {
+        let path = "model/directory/my_tflite_model";
+
+        let result = append_model_format(TFLITE, path.to_string());
+
+        // assert
+        assert_eq!(result, "model/directory/my_tflite_model.tflite")
+    }
+
+    #[cfg(not(feature = "tensorrt"))]
+    #[test]
+    fn fails_to_load_tensorrt_engine_without_tensorrt_feature() {
+        let path = "model/directory/tensorrt-my_model.engine";
+
+        let result = load_tensorrt(path);
+
+        // assert
+        assert!(result.is_err())
+    }
+
+    #[cfg(not(feature = "transformers"))]
+    #[test]
+    fn fails_to_load_transformers_model_without_transformers_feature() {
+        let path = "model/directory/transformers-my_model";
+
+        let result = load_transformers(path);
+
+        // assert
+        assert!(result.is_err())
+    }
+
+    #[cfg(not(feature = "gguf"))]
+    #[test]
+    fn fails_to_load_gguf_model_without_gguf_feature() {
+        let path = "model/directory/gguf-my_model";
+
+        let result = load_gguf(path);
+
+        // assert
+        assert!(result.is_err())
+    }
+
+    #[cfg(not(feature = "tflite"))]
+    #[test]
+    fn fails_to_load_tflite_model_without_tflite_feature() {
+        let path = "model/directory/tflite-my_model.tflite";
+
+        let result = load_tflite(path);
+
+        // assert
+        assert!(result.is_err())
+    }
+
+    #[cfg(not(feature = "openvino"))]
+    #[test]
+    fn fails_to_load_openvino_model_without_openvino_feature() {
+        let path = "model/directory/openvino-my_model";
+
+        let result = load_openvino(path);
+
+        // assert
+        assert!(result.is_err())
+    }
+
     #[test]
     fn do_not_append_model_format_when_model_framework_not_torch_or_lightgbm() {
         let path = "model/directory/catboost-my_model";
@@ -659,4 +2413,494 @@ mod tests {
         // assert
         assert_eq!(result, "model/directory/catboost-my_model")
     }
+
+    fn lightgbm_model(model_name: &str, source_version: Option<String>) -> Arc<Model> {
+        let path = "tests/model_storage/models/lightgbm-my_awesome_reg_model.txt";
+        let predictor = model::lightgbm::LightGBM::load(path).unwrap();
+        let mut model = Model::new(
+            Arc::new(Predictor::LightGBM(predictor)),
+            model_name.to_string(),
+            LIGHTGBM,
+            path.to_string(),
+            Utc::now().to_rfc3339(),
+        );
+        model.info.source_version = source_version;
+        Arc::new(model)
+    }
+
+    #[test]
+    fn successfully_report_best_iteration_of_lightgbm_model() {
+        // Arrange
+        let path = "tests/model_storage/models/pytorch-my_awesome_californiahousing_model.pt";
+        let torch = Model::new(
+            Arc::new(Predictor::Torch(model::torch::Torch::load(path).unwrap())),
+            "my_awesome_californiahousing_model".to_string(),
+            PYTORCH,
+            path.to_string(),
+            Utc::now().to_rfc3339(),
+        );
+
+        // Act
+        let lightgbm = lightgbm_model("my_awesome_reg_model", None);
+
+        // Assert
+        assert!(lightgbm.info.best_iteration.unwrap() > 0);
+        assert_eq!(torch.info.best_iteration, None);
+    }
+
+    /// Ships a validation set with the given criteria with the model, by moving its path into `dir` and writing
+    /// the validation set next to its provenance.
+    fn with_validation_set(model: &mut Model, dir: &std::path::Path, criteria: &str) {
+        let features: serde_json::Map<String, serde_json::Value> = (0..28)
+            .map(|i| (format!("feature_{}", i), serde_json::json!([0.5, 0.25])))
+            .collect();
+        std::fs::create_dir_all(dir.join(PROVENANCE_DIRECTORY_NAME)).unwrap();
+        std::fs::write(
+            dir.join(PROVENANCE_DIRECTORY_NAME)
+                .join(format!("lightgbm-my_model.txt.{}", VALIDATION_FILE_NAME)),
+            format!(
+                r#"{{"input": {}, "labels": [-100.0, -100.0], "criteria": {}}}"#,
+                serde_json::Value::Object(features),
+                criteria
+            ),
+        )
+        .unwrap();
+        model.info.path = dir
+            .join("lightgbm-my_model.txt")
+            .to_str()
+            .unwrap()
+            .to_string();
+    }
+
+    #[test]
+    fn successfully_accept_model_which_meets_its_acceptance_criteria() {
+        // Arrange
+        let current = lightgbm_model("my_model", None);
+        let mut model = Arc::into_inner(lightgbm_model("my_model", None)).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        with_validation_set(&mut model, dir.path(), r#"{"max_drift": 0.0}"#);
+        let accepted_before = ValidationOutcome::Accepted.total();
+
+        // Act
+        let accepted = accept_model(&model, Some(&current));
+
+        // Assert
+        assert!(accepted.is_ok());
+        assert!(ValidationOutcome::Accepted.total() > accepted_before);
+    }
+
+    #[test]
+    fn fails_to_accept_model_which_misses_its_acceptance_criteria() {
+        // Arrange
+        let mut model = Arc::into_inner(lightgbm_model("my_model", None)).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        with_validation_set(&mut model, dir.path(), r#"{"max_mae": 1.0}"#);
+        let rejected_before = ValidationOutcome::Rejected.total();
+
+        // Act
+        let rejected = accept_model(&model, None);
+
+        // Assert
+        let message = rejected.unwrap_err().to_string();
+        assert!(message.contains("Refused to promote model my_model"));
+        assert!(message.contains("mean absolute error"));
+        assert!(ValidationOutcome::Rejected.total() > rejected_before);
+    }
+
+    #[test]
+    fn successfully_warm_up_model_with_its_warmup_input() {
+        // Arrange
+        let features: serde_json::Map<String, serde_json::Value> = (0..28)
+            .map(|i| (format!("feature_{}", i), serde_json::json!([0.5])))
+            .collect();
+        let mut model = Arc::into_inner(lightgbm_model("my_model", None)).unwrap();
+        model.bundle.warmup_input = Some(serde_json::Value::Object(features).to_string());
+
+        // Act
+        let warmed_up = warm_up_model(&model);
+
+        // Assert
+        assert!(warmed_up.is_ok());
+    }
+
+    #[tokio::test]
+    async fn fails_to_prepare_model_when_its_warmup_input_cannot_be_predicted() {
+        // Arrange
+        let mut model = Arc::into_inner(lightgbm_model("my_model", None)).unwrap();
+        model.bundle.warmup_input = Some(r#"{"feature_0": ["not a number"]}"#.to_string());
+
+        // Act
+        let prepared = prepare_model(model, None).await;
+
+        // Assert
+        assert!(prepared
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("Failed to warm up model my_model"));
+    }
+
+    #[tokio::test]
+    async fn successfully_prepare_model_and_record_init_status() {
+        // Arrange
+        let model = Arc::into_inner(lightgbm_model("my_model", None)).unwrap();
+
+        // Act
+        let prepared = prepare_model(model, None).await.unwrap();
+        let teardown = teardown_model(&prepared).await;
+
+        // Assert
+        let init = prepared.info.init.unwrap();
+        assert!(init.is_ok());
+        assert!(init.error.is_none());
+        assert!(teardown.is_ok());
+    }
+
+    #[tokio::test]
+    async fn fails_to_prepare_model_which_misses_its_acceptance_criteria() {
+        // Arrange
+        let mut model = Arc::into_inner(lightgbm_model("my_model", None)).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        with_validation_set(&mut model, dir.path(), r#"{"max_mae": 1.0}"#);
+
+        // Act
+        let prepared = prepare_model(model, None).await;
+
+        // Assert
+        assert!(prepared
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("Refused to promote model my_model"));
+    }
+
+    #[test]
+    fn successfully_detect_updates_of_changed_and_new_tarballs() {
+        // Arrange
+        let models = LoadedModels::default();
+        models.insert(
+            "unchanged".to_string(),
+            lightgbm_model("unchanged", Some("v1".to_string())),
+        );
+        models.insert(
+            "changed".to_string(),
+            lightgbm_model("changed", Some("v1".to_string())),
+        );
+        let versions = BTreeMap::from([
+            (
+                "lightgbm-unchanged.tar.gz".to_string(),
+                Some("v1".to_string()),
+            ),
+            (
+                "lightgbm-changed.tar.gz".to_string(),
+                Some("v2".to_string()),
+            ),
+            ("lightgbm-new.tar.gz".to_string(), None),
+            ("README.md".to_string(), Some("v1".to_string())),
+        ]);
+
+        // Act
+        let updates = detect_updates(&models, versions);
+
+        // Assert
+        assert_eq!(
+            updates,
+            vec![
+                (
+                    "lightgbm-changed.tar.gz".to_string(),
+                    Some("v2".to_string())
+                ),
+                ("lightgbm-new.tar.gz".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn successfully_annotate_metadata_with_pending_update() {
+        // Arrange
+        let pending_updates = PendingUpdates::default();
+        let model = lightgbm_model("my_model", Some("v1".to_string()));
+        pending_updates.begin("my_model".to_string(), Some("v2".to_string()));
+
+        // Act
+        let pending = pending_updates.annotate(model.info.clone());
+        pending_updates.finish("my_model");
+        let finished = pending_updates.annotate(model.info.clone());
+
+        // Assert
+        let pending_update = pending.pending_update.unwrap();
+        assert_eq!(pending_update.source_version, Some("v2".to_string()));
+        assert!(finished.pending_update.is_none());
+    }
+
+    #[tokio::test]
+    async fn successfully_swap_model_once_the_new_version_is_loaded() {
+        // Arrange
+        let model_store_dir = tempfile::tempdir().unwrap();
+        let model_store_dir = model_store_dir.path().to_str().unwrap().to_string();
+        let models = LoadedModels::default();
+        let current = lightgbm_model("my_model", Some("v1".to_string()));
+        models.insert("my_model".to_string(), current.clone());
+        let pending_updates = PendingUpdates::default();
+        let versions = BTreeMap::from([(
+            "lightgbm-my_model.tar.gz".to_string(),
+            Some("v2".to_string()),
+        )]);
+
+        // Act
+        revalidate_models(
+            &models,
+            &pending_updates,
+            versions,
+            model_store_dir.as_str(),
+            &PollOptions::default(),
+            |_| async {
+                // the current version keeps serving while the new version is downloaded
+                let serving = models.get("my_model").unwrap().clone();
+                assert!(Arc::ptr_eq(&serving, &current));
+                let metadata = pending_updates.annotate(serving.info.clone());
+                assert_eq!(
+                    metadata.pending_update.unwrap().source_version,
+                    Some("v2".to_string())
+                );
+                std::fs::copy(
+                    "tests/model_storage/models/lightgbm-my_awesome_reg_model.txt",
+                    format!("{}/lightgbm-my_model.txt", model_store_dir),
+                )?;
+                Ok(())
+            },
+        )
+        .await;
+
+        // Assert
+        let swapped = models.get("my_model").unwrap().clone();
+        assert!(!Arc::ptr_eq(&swapped, &current));
+        assert!(pending_updates
+            .annotate(swapped.info.clone())
+            .pending_update
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn successfully_keep_current_version_when_the_new_version_fails_to_load() {
+        // Arrange
+        let model_store_dir = tempfile::tempdir().unwrap();
+        let models = LoadedModels::default();
+        let current = lightgbm_model("my_model", Some("v1".to_string()));
+        models.insert("my_model".to_string(), current.clone());
+        let pending_updates = PendingUpdates::default();
+        let versions = BTreeMap::from([(
+            "lightgbm-my_model.tar.gz".to_string(),
+            Some("v2".to_string()),
+        )]);
+
+        // Act
+        revalidate_models(
+            &models,
+            &pending_updates,
+            versions,
+            model_store_dir.path().to_str().unwrap(),
+            &PollOptions::default(),
+            |_| async { Ok(()) },
+        )
+        .await;
+
+        // Assert
+        let serving = models.get("my_model").unwrap().clone();
+        assert!(Arc::ptr_eq(&serving, &current));
+        assert!(pending_updates
+            .annotate(serving.info.clone())
+            .pending_update
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn successfully_roll_back_deployment_group_when_a_member_fails_to_load() {
+        // Arrange
+        let model_store_dir = tempfile::tempdir().unwrap();
+        let model_store_dir = model_store_dir.path().to_str().unwrap().to_string();
+        let models = LoadedModels::default();
+        let encoder = lightgbm_model("fraud_encoder", Some("v1".to_string()));
+        let scorer = lightgbm_model("fraud_scorer", Some("v1".to_string()));
+        let other = lightgbm_model("my_model", Some("v1".to_string()));
+        models.insert("fraud_encoder".to_string(), encoder.clone());
+        models.insert("fraud_scorer".to_string(), scorer.clone());
+        models.insert("my_model".to_string(), other.clone());
+        let pending_updates = PendingUpdates::default();
+        let versions: BTreeMap<String, Option<String>> =
+            ["fraud_encoder", "fraud_scorer", "my_model"]
+                .into_iter()
+                .map(|model_name| {
+                    (
+                        format!("lightgbm-{}.tar.gz", model_name),
+                        Some("v2".to_string()),
+                    )
+                })
+                .collect();
+        let deployment_groups = DeploymentGroups::new(vec![DeploymentGroup {
+            name: "fraud".to_string(),
+            models: vec!["fraud_encoder".to_string(), "fraud_scorer".to_string()],
+        }])
+        .unwrap();
+
+        // Act
+        revalidate_models(
+            &models,
+            &pending_updates,
+            versions,
+            model_store_dir.as_str(),
+            &PollOptions {
+                deployment_groups: Arc::new(deployment_groups),
+                ..PollOptions::default()
+            },
+            |tarball_name| {
+                let model_store_dir = model_store_dir.clone();
+                async move {
+                    // the new version of the scorer is broken
+                    if tarball_name != "lightgbm-fraud_scorer.tar.gz" {
+                        std::fs::copy(
+                            "tests/model_storage/models/lightgbm-my_awesome_reg_model.txt",
+                            format!(
+                                "{}/{}.txt",
+                                model_store_dir,
+                                tarball_name.trim_end_matches(".tar.gz")
+                            ),
+                        )?;
+                    }
+                    Ok(())
+                }
+            },
+        )
+        .await;
+
+        // Assert
+        assert!(Arc::ptr_eq(
+            &models.get("fraud_encoder").unwrap().clone(),
+            &encoder
+        ));
+        assert!(Arc::ptr_eq(
+            &models.get("fraud_scorer").unwrap().clone(),
+            &scorer
+        ));
+        assert!(!Arc::ptr_eq(
+            &models.get("my_model").unwrap().clone(),
+            &other
+        ));
+        assert!(pending_updates
+            .annotate(encoder.info.clone())
+            .pending_update
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn fails_to_deploy_incomplete_deployment_group() {
+        // Arrange
+        let model_store_dir = tempfile::tempdir().unwrap();
+        let models = LoadedModels::default();
+        let encoder = lightgbm_model("fraud_encoder", Some("v1".to_string()));
+        models.insert("fraud_encoder".to_string(), encoder.clone());
+        let pending_updates = PendingUpdates::default();
+        // the tarball of the scorer has not been uploaded yet
+        let versions: BTreeMap<String, Option<String>> = BTreeMap::from([(
+            "lightgbm-fraud_encoder.tar.gz".to_string(),
+            Some("v2".to_string()),
+        )]);
+        let deployment_groups = DeploymentGroups::new(vec![DeploymentGroup {
+            name: "fraud".to_string(),
+            models: vec!["fraud_encoder".to_string(), "fraud_scorer".to_string()],
+        }])
+        .unwrap();
+        let downloads = AtomicUsize::new(0);
+
+        // Act
+        revalidate_models(
+            &models,
+            &pending_updates,
+            versions,
+            model_store_dir.path().to_str().unwrap(),
+            &PollOptions {
+                deployment_groups: Arc::new(deployment_groups),
+                ..PollOptions::default()
+            },
+            |_| {
+                downloads.fetch_add(1, Ordering::SeqCst);
+                async { Ok(()) }
+            },
+        )
+        .await;
+
+        // Assert
+        assert_eq!(downloads.load(Ordering::SeqCst), 0);
+        assert!(Arc::ptr_eq(
+            &models.get("fraud_encoder").unwrap().clone(),
+            &encoder
+        ));
+        assert!(pending_updates
+            .annotate(encoder.info.clone())
+            .pending_update
+            .is_none());
+    }
+
+    #[test]
+    fn successfully_add_jitter_within_bounds_to_poll_interval() {
+        // Arrange
+        let options = PollOptions {
+            interval: time::Duration::from_secs(10),
+            jitter: time::Duration::from_secs(5),
+            ..PollOptions::default()
+        };
+
+        // Act
+        let delays: Vec<time::Duration> = (0..100).map(|_| options.next_delay()).collect();
+
+        // Assert
+        for delay in delays {
+            assert!(delay >= time::Duration::from_secs(10));
+            assert!(delay <= time::Duration::from_secs(15));
+        }
+        assert_eq!(
+            PollOptions {
+                jitter: time::Duration::from_secs(0),
+                ..options
+            }
+            .next_delay(),
+            time::Duration::from_secs(10)
+        );
+    }
+
+    #[tokio::test]
+    async fn successfully_limit_concurrent_downloads_while_revalidating() {
+        // Arrange
+        let model_store_dir = tempfile::tempdir().unwrap();
+        let models = LoadedModels::default();
+        let pending_updates = PendingUpdates::default();
+        let versions: BTreeMap<String, Option<String>> = (0..6)
+            .map(|i| (format!("lightgbm-my_model_{}.tar.gz", i), None))
+            .collect();
+        let in_flight = std::sync::atomic::AtomicUsize::new(0);
+        let max_in_flight = std::sync::atomic::AtomicUsize::new(0);
+
+        // Act
+        revalidate_models(
+            &models,
+            &pending_updates,
+            versions,
+            model_store_dir.path().to_str().unwrap(),
+            &PollOptions {
+                max_concurrent_downloads: 2,
+                ..PollOptions::default()
+            },
+            |_| async {
+                let current = in_flight.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, std::sync::atomic::Ordering::SeqCst);
+                tokio::time::sleep(time::Duration::from_millis(20)).await;
+                in_flight.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(())
+            },
+        )
+        .await;
+
+        // Assert
+        assert_eq!(max_in_flight.into_inner(), 2);
+    }
 }
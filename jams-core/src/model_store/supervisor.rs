@@ -0,0 +1,262 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::{AbortHandle, JoinHandle};
+
+/// The name the model store the server was started with is reported under, e.g. in the poller health.
+pub const DEFAULT_STORE_NAME: &str = "default";
+
+/// How a `Poller` restarts the polls of a model store which fail or panic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RestartPolicy {
+    /// The delay before retrying after the first failed poll. It doubles with every consecutive failure.
+    pub initial_backoff: Duration,
+    /// The maximum delay before retrying after a failed poll.
+    pub max_backoff: Duration,
+    /// The number of consecutive failed polls after which the poller is reported as unhealthy.
+    pub unhealthy_after: u32,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(300),
+            unhealthy_after: 5,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Returns the delay before retrying after the given number of consecutive failed polls.
+    pub fn backoff(&self, consecutive_failures: u32) -> Duration {
+        if consecutive_failures == 0 {
+            return Duration::ZERO;
+        }
+        let exponent = (consecutive_failures - 1).min(31);
+        self.initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff)
+    }
+}
+
+/// The health of the poller of a model store.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct PollerHealth {
+    /// The name of the model store, `default` for the model store the server was started with.
+    pub store: String,
+    /// Whether the poller failed less than `RestartPolicy::unhealthy_after` consecutive times.
+    pub healthy: bool,
+    /// The number of polls which completed since startup.
+    pub polls: u64,
+    /// The number of polls which failed or panicked since startup.
+    pub failures: u64,
+    /// The number of polls which panicked and were restarted since startup.
+    pub restarts: u64,
+    /// The number of failed polls since the last poll which succeeded.
+    pub consecutive_failures: u32,
+    /// The timestamp of the last poll which succeeded, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success: Option<String>,
+    /// The error of the last poll which failed, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Polls a model store in a supervised background task.
+///
+/// Each poll runs in its own task, so that a poll which panics is restarted instead of silently stopping the
+/// updates of the model store. Failed polls are retried with an exponential backoff. The poller is stopped when
+/// it is dropped.
+pub struct Poller {
+    health: Arc<Mutex<PollerHealth>>,
+    supervisor: JoinHandle<()>,
+}
+
+/// Aborts the poll in flight when the supervisor is aborted.
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort()
+    }
+}
+
+impl Poller {
+    /// Starts polling a model store.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The name of the model store, which the health is reported under.
+    /// * `policy` - How failed polls are retried.
+    /// * `poll` - Runs a single poll, i.e. waits for the poll interval and then updates the models.
+    pub fn spawn<F, Fut>(store: String, policy: RestartPolicy, poll: F) -> Poller
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = crate::Result<()>> + Send + 'static,
+    {
+        let health = Arc::new(Mutex::new(PollerHealth {
+            store: store.clone(),
+            healthy: true,
+            ..PollerHealth::default()
+        }));
+        let supervised = health.clone();
+        let supervisor = tokio::spawn(async move {
+            loop {
+                let task = tokio::spawn(poll());
+                let _abort = AbortOnDrop(task.abort_handle());
+                let outcome = task.await;
+
+                let consecutive_failures = {
+                    let mut health = supervised.lock().unwrap_or_else(|e| e.into_inner());
+                    match outcome {
+                        Ok(Ok(())) => {
+                            tracing::info!("Successfully polled the model store {} ✅", store);
+                            health.polls += 1;
+                            health.consecutive_failures = 0;
+                            health.last_success = Some(Utc::now().to_rfc3339());
+                        }
+                        Ok(Err(e)) => {
+                            tracing::error!("Failed to poll the model store {} ❌: {}", store, e);
+                            health.polls += 1;
+                            health.failures += 1;
+                            health.consecutive_failures += 1;
+                            health.last_error = Some(e.to_string());
+                        }
+                        Err(e) if e.is_panic() => {
+                            tracing::error!(
+                                "Poll of the model store {} panicked, restarting it ❌",
+                                store
+                            );
+                            health.failures += 1;
+                            health.restarts += 1;
+                            health.consecutive_failures += 1;
+                            health.last_error = Some("Poll panicked".to_string());
+                        }
+                        Err(_) => return,
+                    }
+                    health.healthy = health.consecutive_failures < policy.unhealthy_after;
+                    health.consecutive_failures
+                };
+
+                if consecutive_failures > 0 {
+                    let backoff = policy.backoff(consecutive_failures);
+                    tracing::warn!(
+                        "Retrying to poll the model store {} in {:?} after {} consecutive failures ⚠️",
+                        store,
+                        backoff,
+                        consecutive_failures
+                    );
+                    tokio::time::sleep(backoff).await;
+                }
+            }
+        });
+        Poller { health, supervisor }
+    }
+
+    /// Returns the health of the poller.
+    pub fn health(&self) -> PollerHealth {
+        self.health
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        self.supervisor.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn policy() -> RestartPolicy {
+        RestartPolicy {
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+            unhealthy_after: 2,
+        }
+    }
+
+    #[test]
+    fn successfully_back_off_exponentially_up_to_the_maximum() {
+        // Arrange
+        let policy = RestartPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            unhealthy_after: 5,
+        };
+
+        // Act
+        let backoffs: Vec<Duration> = (0..5).map(|failures| policy.backoff(failures)).collect();
+
+        // Assert
+        assert_eq!(
+            backoffs,
+            vec![0, 1, 2, 4, 5]
+                .into_iter()
+                .map(Duration::from_secs)
+                .collect::<Vec<Duration>>()
+        );
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn successfully_restart_poll_which_panicked() {
+        // Arrange
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = calls.clone();
+
+        // Act
+        let poller = Poller::spawn("archive".to_string(), policy(), move || {
+            let call = counted.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match call {
+                    0 => panic!("poll panicked"),
+                    1 => Err(Error::Store(anyhow::anyhow!("bucket is unreachable"))),
+                    _ => {
+                        tokio::time::sleep(Duration::from_millis(1)).await;
+                        Ok(())
+                    }
+                }
+            }
+        });
+        while poller.health().polls < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        // Assert
+        let health = poller.health();
+        assert_eq!(health.store, "archive");
+        assert!(health.healthy);
+        assert_eq!(health.failures, 2);
+        assert_eq!(health.restarts, 1);
+        assert_eq!(health.consecutive_failures, 0);
+        assert!(health.last_success.is_some());
+        assert!(health.last_error.unwrap().contains("bucket is unreachable"));
+    }
+
+    #[tokio::test]
+    async fn fails_to_stay_healthy_when_polls_keep_failing() {
+        // Act
+        let poller = Poller::spawn("archive".to_string(), policy(), || async {
+            Err(Error::Store(anyhow::anyhow!("bucket is unreachable")))
+        });
+        while poller.health().consecutive_failures < 3 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        // Assert
+        let health = poller.health();
+        assert!(!health.healthy);
+        assert!(health.last_success.is_none());
+        assert_eq!(health.restarts, 0);
+    }
+}
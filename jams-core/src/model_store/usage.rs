@@ -0,0 +1,313 @@
+use crate::model_store::storage::{Metadata, ModelName};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Default interval in seconds between two saves of the usage to the usage state file.
+pub const DEFAULT_USAGE_SAVE_INTERVAL_SECONDS: u64 = 60;
+
+/// How often a model was used since it was added, or since startup for the models loaded at startup.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Usage {
+    request_count: u64,
+    last_accessed: Option<DateTime<Utc>>,
+    tracked_since: DateTime<Utc>,
+}
+
+/// The usage of a model as saved in the usage state file, with its times in RFC 3339 format.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct SavedUsage {
+    request_count: u64,
+    last_accessed: Option<String>,
+    tracked_since: String,
+}
+
+/// Tracks the prediction requests made to each model, to find the models which are no longer used.
+///
+/// Usage is kept in memory and starts over when the server restarts, unless it is saved to a usage state
+/// file with `save` and restored from it with `load`.
+pub struct ModelUsage {
+    models: DashMap<ModelName, Usage>,
+    started_at: DateTime<Utc>,
+}
+
+impl Default for ModelUsage {
+    fn default() -> Self {
+        ModelUsage {
+            models: DashMap::new(),
+            started_at: Utc::now(),
+        }
+    }
+}
+
+impl ModelUsage {
+    /// Restores the usage saved to the given path, so that request counts and access times carry over a restart.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the usage state file.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ModelUsage)` - The restored usage, which is empty if the state file does not exist.
+    /// * `Err(anyhow::Error)` - If the state file cannot be read or decoded.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(ModelUsage::default()),
+            Err(e) => {
+                tracing::error!("Failed to read usage state {} ❌: {}", path.display(), e);
+                anyhow::bail!("Failed to read usage state {} ❌: {}", path.display(), e)
+            }
+        };
+        let saved: BTreeMap<ModelName, SavedUsage> = match serde_json::from_slice(&bytes) {
+            Ok(saved) => saved,
+            Err(e) => {
+                tracing::error!("Failed to decode usage state {} ❌: {}", path.display(), e);
+                anyhow::bail!("Failed to decode usage state {} ❌: {}", path.display(), e)
+            }
+        };
+
+        let usage = ModelUsage::default();
+        for (model_name, saved) in saved {
+            let restored = parse_time(saved.tracked_since.as_str()).and_then(|tracked_since| {
+                let last_accessed = match saved.last_accessed.as_deref() {
+                    Some(time) => Some(parse_time(time)?),
+                    None => None,
+                };
+                Ok(Usage {
+                    request_count: saved.request_count,
+                    last_accessed,
+                    tracked_since,
+                })
+            });
+            match restored {
+                Ok(restored) => {
+                    usage.models.insert(model_name, restored);
+                }
+                Err(e) => {
+                    tracing::error!("Failed to decode usage state {} ❌: {}", path.display(), e);
+                    anyhow::bail!("Failed to decode usage state {} ❌: {}", path.display(), e)
+                }
+            }
+        }
+        tracing::info!(
+            "Restored usage of {} models from {} 📝",
+            usage.models.len(),
+            path.display()
+        );
+        Ok(usage)
+    }
+
+    /// Saves the usage to the given path, replacing the previous state file atomically so that a crash while
+    /// saving does not lose the usage saved before.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the usage state file.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the state file cannot be written.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let saved: BTreeMap<ModelName, SavedUsage> = self
+            .models
+            .iter()
+            .map(|entry| {
+                let usage = entry.value();
+                (
+                    entry.key().clone(),
+                    SavedUsage {
+                        request_count: usage.request_count,
+                        last_accessed: usage.last_accessed.map(|time| time.to_rfc3339()),
+                        tracked_since: usage.tracked_since.to_rfc3339(),
+                    },
+                )
+            })
+            .collect();
+        let bytes = serde_json::to_vec_pretty(&saved)?;
+        let temp_path = path.with_extension("tmp");
+        if let Err(e) = fs::write(&temp_path, bytes).and_then(|_| fs::rename(&temp_path, path)) {
+            tracing::error!("Failed to write usage state {} ❌: {}", path.display(), e);
+            anyhow::bail!("Failed to write usage state {} ❌: {}", path.display(), e)
+        }
+        Ok(())
+    }
+
+    /// Records a prediction request made to a loaded model.
+    pub fn record(&self, model_name: &str) {
+        let now = Utc::now();
+        match self.models.get_mut(model_name) {
+            Some(mut usage) => {
+                usage.request_count += 1;
+                usage.last_accessed = Some(now);
+            }
+            None => {
+                self.models.insert(
+                    model_name.to_string(),
+                    Usage {
+                        request_count: 1,
+                        last_accessed: Some(now),
+                        tracked_since: self.started_at,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Starts tracking a model which was added at runtime, so that it is not reported as unused since startup.
+    pub fn track(&self, model_name: ModelName) {
+        self.models.insert(
+            model_name,
+            Usage {
+                request_count: 0,
+                last_accessed: None,
+                tracked_since: Utc::now(),
+            },
+        );
+    }
+
+    /// Forgets the usage of a model once it is deleted.
+    pub fn clear(&self, model_name: &str) {
+        self.models.remove(model_name);
+    }
+
+    /// Removes the usage of the models which are no longer loaded, e.g. because they failed to reload.
+    pub fn prune(&self, is_loaded: impl Fn(&str) -> bool) {
+        self.models.retain(|model_name, _| is_loaded(model_name));
+    }
+
+    /// Adds the request count and last access time of the model to its metadata.
+    pub fn annotate(&self, mut metadata: Metadata) -> Metadata {
+        if let Some(usage) = self.models.get(metadata.name.as_str()) {
+            metadata.request_count = usage.request_count;
+            metadata.last_accessed = usage.last_accessed.map(|time| time.to_rfc3339());
+        }
+        metadata
+    }
+
+    /// Returns true if the model has not been used for at least `unused_for`.
+    ///
+    /// Models which were never used count as unused since they were added, or since startup.
+    pub fn is_unused(&self, model_name: &str, unused_for: Duration) -> bool {
+        let last_used = match self.models.get(model_name) {
+            Some(usage) => usage.last_accessed.unwrap_or(usage.tracked_since),
+            None => self.started_at,
+        };
+        let unused_since = Utc::now().signed_duration_since(last_used);
+        unused_since.to_std().unwrap_or_default() >= unused_for
+    }
+}
+
+/// Parses a time saved in RFC 3339 format.
+fn parse_time(time: &str) -> anyhow::Result<DateTime<Utc>> {
+    match DateTime::parse_from_rfc3339(time) {
+        Ok(time) => Ok(time.with_timezone(&Utc)),
+        Err(e) => anyhow::bail!("Invalid time {} ❌: {}", time, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_record_model_usage() {
+        // Arrange
+        let usage = ModelUsage::default();
+
+        // Act
+        usage.record("my_model");
+        usage.record("my_model");
+
+        // Assert
+        assert!(!usage.is_unused("my_model", Duration::from_secs(60)));
+        assert!(usage.is_unused("my_model", Duration::from_secs(0)));
+        assert_eq!(usage.models.get("my_model").unwrap().request_count, 2);
+    }
+
+    #[test]
+    fn successfully_prune_usage_of_models_which_are_not_loaded() {
+        // Arrange
+        let usage = ModelUsage::default();
+        usage.record("my_model");
+        usage.track("my_deleted_model".to_string());
+
+        // Act
+        usage.prune(|model_name| model_name == "my_model");
+
+        // Assert
+        assert!(usage.models.contains_key("my_model"));
+        assert!(!usage.models.contains_key("my_deleted_model"));
+    }
+
+    #[test]
+    fn successfully_restore_saved_model_usage() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+        let usage = ModelUsage::default();
+        usage.record("my_model");
+        usage.record("my_model");
+        usage.track("my_unused_model".to_string());
+
+        // Act
+        usage.save(&path).unwrap();
+        let restored = ModelUsage::load(&path).unwrap();
+
+        // Assert
+        assert_eq!(restored.models.len(), 2);
+        let my_model = *restored.models.get("my_model").unwrap();
+        assert_eq!(my_model.request_count, 2);
+        assert_eq!(
+            my_model.last_accessed.map(|time| time.timestamp_millis()),
+            usage
+                .models
+                .get("my_model")
+                .unwrap()
+                .last_accessed
+                .map(|time| time.timestamp_millis())
+        );
+        assert!(restored
+            .models
+            .get("my_unused_model")
+            .unwrap()
+            .last_accessed
+            .is_none());
+    }
+
+    #[test]
+    fn successfully_load_empty_usage_when_state_file_does_not_exist() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+
+        // Act
+        let usage = ModelUsage::load(&dir.path().join("usage.json")).unwrap();
+
+        // Assert
+        assert!(usage.models.is_empty());
+    }
+
+    #[test]
+    fn fails_to_load_usage_when_state_file_is_invalid() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+        std::fs::write(
+            &path,
+            r#"{"my_model": {"request_count": 1, "last_accessed": "yesterday", "tracked_since": "2024-01-01T00:00:00Z"}}"#,
+        )
+        .unwrap();
+
+        // Act
+        let usage = ModelUsage::load(&path);
+
+        // Assert
+        assert!(usage.is_err());
+    }
+}
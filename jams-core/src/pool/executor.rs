@@ -0,0 +1,325 @@
+use serde::Deserialize;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use tokio::runtime::Handle;
+use tokio::sync::Semaphore;
+
+/// A unit of work run by an `Executor`.
+pub type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Runs the jobs handed over by a `Scheduler` on its workers.
+///
+/// The scheduler decides which task runs next and keeps track of its budgets, the executor only decides
+/// where it runs. This allows the rayon threadpool to be swapped for another executor without changing
+/// how tasks are scheduled.
+pub trait Executor: Send + Sync + 'static {
+    /// Runs the job on one of the workers of the executor. Must not block the caller.
+    fn execute(&self, job: Job);
+
+    /// Returns the number of workers of the executor.
+    fn num_workers(&self) -> usize;
+}
+
+/// The executors which can run the tasks of a `Scheduler`.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutorKind {
+    /// A rayon threadpool, whose idle workers steal queued jobs from busy workers.
+    #[default]
+    Rayon,
+    /// Dedicated OS threads which take jobs from a shared queue. Suited to native calls which must not
+    /// share their threads with other work, e.g. calls which release the GIL of an embedded interpreter.
+    Threads,
+    /// The blocking threadpool of the tokio runtime, of which at most the number of workers run jobs of the
+    /// executor at a time.
+    Tokio,
+}
+
+impl ExecutorKind {
+    /// Parses the executor kind from its name, e.g. `rayon`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not one of `rayon`, `threads` or `tokio`.
+    pub fn parse(name: &str) -> anyhow::Result<Self> {
+        match name {
+            "rayon" => Ok(ExecutorKind::Rayon),
+            "threads" => Ok(ExecutorKind::Threads),
+            "tokio" => Ok(ExecutorKind::Tokio),
+            _ => anyhow::bail!(
+                "Executor {} is not supported, expected one of rayon, threads or tokio ❌",
+                name
+            ),
+        }
+    }
+
+    /// Builds an executor of this kind.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_workers` - The number of workers of the executor.
+    /// * `thread_name` - The prefix of the names of the worker threads, e.g. `jams-fraud`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Arc<dyn Executor>)` - If the executor was built.
+    /// * `Err(anyhow::Error)` - If there are no workers, the threads cannot be started or the tokio executor
+    ///   is built outside of a tokio runtime.
+    pub fn build(
+        &self,
+        num_workers: usize,
+        thread_name: &str,
+    ) -> anyhow::Result<Arc<dyn Executor>> {
+        if num_workers < 1 {
+            anyhow::bail!(
+                "At least 1 worker is required for executor {} ❌",
+                thread_name
+            )
+        }
+        let executor: Arc<dyn Executor> = match self {
+            ExecutorKind::Rayon => Arc::new(RayonExecutor::new(num_workers, thread_name)?),
+            ExecutorKind::Threads => Arc::new(ThreadExecutor::new(num_workers, thread_name)?),
+            ExecutorKind::Tokio => Arc::new(BlockingExecutor::new(num_workers)?),
+        };
+        Ok(executor)
+    }
+}
+
+/// Runs jobs on a rayon threadpool.
+pub struct RayonExecutor {
+    pool: rayon::ThreadPool,
+}
+
+impl RayonExecutor {
+    /// Builds a rayon threadpool with the given number of threads.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the threadpool cannot be built.
+    pub fn new(num_workers: usize, thread_name: &str) -> anyhow::Result<Self> {
+        let prefix = thread_name.to_string();
+        match rayon::ThreadPoolBuilder::new()
+            .num_threads(num_workers)
+            .thread_name(move |i| format!("{}-{}", prefix, i))
+            .build()
+        {
+            Ok(pool) => Ok(RayonExecutor { pool }),
+            Err(e) => {
+                tracing::error!("Failed to build rayon threadpool ❌: {}", e);
+                anyhow::bail!("Failed to build rayon threadpool ❌: {}", e)
+            }
+        }
+    }
+}
+
+impl Executor for RayonExecutor {
+    fn execute(&self, job: Job) {
+        self.pool.spawn(job)
+    }
+
+    fn num_workers(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+}
+
+/// Runs jobs on dedicated OS threads which take them from a shared queue.
+///
+/// The threads stop once the executor is dropped and the queue is drained.
+pub struct ThreadExecutor {
+    sender: Mutex<mpsc::Sender<Job>>,
+    num_workers: usize,
+}
+
+impl ThreadExecutor {
+    /// Starts the given number of threads.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a thread cannot be started.
+    pub fn new(num_workers: usize, thread_name: &str) -> anyhow::Result<Self> {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..num_workers {
+            let receiver = Arc::clone(&receiver);
+            if let Err(e) = thread::Builder::new()
+                .name(format!("{}-{}", thread_name, i))
+                .spawn(move || loop {
+                    // the lock is released as soon as a job is received
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    match job {
+                        // a panicking job must not take the thread down with it
+                        Ok(job) => {
+                            if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+                                tracing::error!("Job panicked on a dedicated worker thread ❌");
+                            }
+                        }
+                        Err(_) => return,
+                    }
+                })
+            {
+                tracing::error!("Failed to start worker thread ❌: {}", e);
+                anyhow::bail!("Failed to start worker thread ❌: {}", e)
+            }
+        }
+        Ok(ThreadExecutor {
+            sender: Mutex::new(sender),
+            num_workers,
+        })
+    }
+}
+
+impl Executor for ThreadExecutor {
+    fn execute(&self, job: Job) {
+        let sent = match self.sender.lock() {
+            Ok(sender) => sender.send(job).is_ok(),
+            Err(_) => false,
+        };
+        if !sent {
+            tracing::error!("Failed to hand over job as the worker threads have stopped ❌");
+        }
+    }
+
+    fn num_workers(&self) -> usize {
+        self.num_workers
+    }
+}
+
+/// Runs jobs on the blocking threadpool of the tokio runtime.
+///
+/// The blocking threadpool is shared by the whole runtime, so each executor holds its own permits: at most
+/// `num_workers` of its jobs run at a time, and the other jobs wait for a permit without taking a thread. The
+/// worker pools of the namespaces therefore cannot take the threads of each other.
+pub struct BlockingExecutor {
+    handle: Handle,
+    permits: Arc<Semaphore>,
+    num_workers: usize,
+}
+
+impl BlockingExecutor {
+    /// Creates an executor on the tokio runtime of the caller, which runs up to `num_workers` jobs at a time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if called outside of a tokio runtime.
+    pub fn new(num_workers: usize) -> anyhow::Result<Self> {
+        match Handle::try_current() {
+            Ok(handle) => Ok(BlockingExecutor {
+                handle,
+                permits: Arc::new(Semaphore::new(num_workers)),
+                num_workers,
+            }),
+            Err(e) => {
+                tracing::error!("Failed to get tokio runtime for executor ❌: {}", e);
+                anyhow::bail!("Failed to get tokio runtime for executor ❌: {}", e)
+            }
+        }
+    }
+}
+
+impl Executor for BlockingExecutor {
+    fn execute(&self, job: Job) {
+        let permits = Arc::clone(&self.permits);
+        let handle = self.handle.clone();
+        // the task is detached, the scheduler does not wait for it
+        self.handle.spawn(async move {
+            let permit = match permits.acquire_owned().await {
+                Ok(permit) => permit,
+                Err(_) => {
+                    tracing::error!("Failed to hand over job as the executor was closed ❌");
+                    return;
+                }
+            };
+            // the permit is held until the job has run, a panicking job releases it while unwinding
+            handle.spawn_blocking(move || {
+                let _permit = permit;
+                job()
+            });
+        });
+    }
+
+    fn num_workers(&self) -> usize {
+        self.num_workers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    // the jobs of the tokio executor are handed over by the runtime, so the tests block on a runtime with
+    // a worker to spare
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn successfully_run_jobs_on_every_executor() {
+        for kind in [
+            ExecutorKind::Rayon,
+            ExecutorKind::Threads,
+            ExecutorKind::Tokio,
+        ] {
+            // Arrange
+            let executor = kind.build(2, "jams-test").unwrap();
+            let (tx, rx) = mpsc::channel();
+
+            // Act
+            for i in 0..4 {
+                let tx = tx.clone();
+                executor.execute(Box::new(move || tx.send(i).unwrap()));
+            }
+            let mut received: Vec<i32> = (0..4)
+                .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+                .collect();
+            received.sort();
+
+            // Assert
+            assert_eq!(executor.num_workers(), 2);
+            assert_eq!(received, vec![0, 1, 2, 3]);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn successfully_bound_running_jobs_of_tokio_executor_by_its_workers() {
+        // Arrange
+        let executor = ExecutorKind::Tokio.build(2, "jams-test").unwrap();
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let (tx, rx) = mpsc::channel();
+
+        // Act
+        for _ in 0..8 {
+            let running = Arc::clone(&running);
+            let max_running = Arc::clone(&max_running);
+            let tx = tx.clone();
+            executor.execute(Box::new(move || {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(10));
+                running.fetch_sub(1, Ordering::SeqCst);
+                tx.send(()).unwrap();
+            }));
+        }
+        for _ in 0..8 {
+            rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        }
+
+        // Assert
+        assert!(max_running.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn fails_to_build_executor_when_config_is_invalid() {
+        // Act
+        let unknown = ExecutorKind::parse("gpu");
+        let no_workers = ExecutorKind::Rayon.build(0, "jams-test");
+        let outside_runtime = ExecutorKind::Tokio.build(1, "jams-test");
+
+        // Assert
+        assert!(unknown.is_err());
+        assert!(no_workers.is_err());
+        assert!(outside_runtime.is_err());
+    }
+}
@@ -1,20 +1,11 @@
 use crate::model::input::ModelInput;
-use lazy_static::lazy_static;
-use object_pool::Pool;
 use std::sync::Arc;
 use tokio::time;
 
-const MAX_POOL_SIZE: usize = 1000;
+pub mod executor;
+pub mod scheduler;
 
-lazy_static! {
-    /// A globally accessible object pool for `ModelInput` objects.
-    ///
-    /// This pool manages a collection of reusable `ModelInput` objects. The pool
-    /// is initialized with 500 objects, and additional objects can be added as needed.
-    /// It uses `lazy_static` to ensure that the pool is only created once and is accessible globally.
-    pub static ref MODEL_INPUT_POOL: Arc<Pool<ModelInput>> =
-        Arc::new(Pool::new(MAX_POOL_SIZE, ModelInput::default));
-}
+pub use jams_predictor_api::pool::{MAX_POOL_SIZE, MODEL_INPUT_POOL};
 
 /// The background worker that refills the object pool when it falls below a certain threshold.
 ///
@@ -0,0 +1,808 @@
+use crate::pool::executor::{Executor, Job};
+// the priorities are defined in jams-predictor-api, as they are part of the prediction options
+pub use jams_predictor_api::priority::Priority;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// The time a task is expected to take. Tasks over budget still run to completion, they are only counted
+/// so that an undersized worker pool or a slow model shows up in the metrics.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Budget {
+    /// The maximum time a task should wait in the queue before a worker picks it up. Unlimited if `None`.
+    pub max_wait: Option<Duration>,
+    /// The maximum time a task should take to run. Unlimited if `None`.
+    pub max_execute: Option<Duration>,
+}
+
+/// The time a queued task waits before it is promoted by one priority, so that a steady stream of tasks with a
+/// higher priority cannot starve the tasks with a lower priority.
+pub const DEFAULT_AGING: Duration = Duration::from_secs(1);
+
+/// The weight of a flow whose weight is not set, e.g. of a model without a configured scheduling weight.
+pub const DEFAULT_WEIGHT: f64 = 1.0;
+
+/// The factors the weight of a flow is multiplied by for the tasks of each priority, from the highest to the
+/// lowest, so that the flows of tasks with a higher priority get a larger share of the workers without starving
+/// the others.
+const PRIORITY_SHARES: [f64; 3] = [4.0, 2.0, 1.0];
+
+/// The time a task of a flow is expected to run for before the first task of the flow completes.
+const INITIAL_COST: Duration = Duration::from_millis(1);
+
+/// The weight of the latest task in the mean time the tasks of a flow run for.
+const COST_SMOOTHING: f64 = 0.2;
+
+/// The time a flow without queued or running tasks is kept for, after which it is evicted along with its
+/// accounting, so that the flows of models which are no longer requested do not pile up.
+pub const FLOW_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A task submitted to a `Scheduler`.
+pub struct Task {
+    job: Job,
+    priority: Priority,
+    cancellation_token: Option<CancellationToken>,
+    flow: String,
+    weight: f64,
+}
+
+impl Task {
+    /// Creates a task with the default priority which cannot be cancelled.
+    pub fn new<F>(job: F) -> Self
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        Task {
+            job: Box::new(job),
+            priority: Priority::default(),
+            cancellation_token: None,
+            flow: String::new(),
+            weight: DEFAULT_WEIGHT,
+        }
+    }
+
+    /// Sets the priority of the task.
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the token which withdraws the task from the queue once cancelled. A task which has already
+    /// started runs to completion.
+    pub fn with_cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = Some(cancellation_token);
+        self
+    }
+
+    /// Sets the flow of the task, e.g. the model it predicts with, and the weight of the flow. The workers are
+    /// shared between the flows with queued tasks in proportion to their weights, multiplied by a factor which
+    /// grows with the priority of their tasks. Weights which are not finite and positive fall back to
+    /// `DEFAULT_WEIGHT`.
+    ///
+    /// # Arguments
+    ///
+    /// * `flow` - The name of the flow.
+    /// * `weight` - The weight of the flow, which replaces its previous weight.
+    pub fn with_flow(mut self, flow: &str, weight: f64) -> Self {
+        self.flow = flow.to_string();
+        self.weight = if weight.is_finite() && weight > 0.0 {
+            weight
+        } else {
+            DEFAULT_WEIGHT
+        };
+        self
+    }
+
+    /// Returns the priority of the task.
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Wraps the job of the task, e.g. to record metrics around it, keeping its priority, cancellation token
+    /// and flow.
+    pub fn wrap<F>(self, wrapper: F) -> Self
+    where
+        F: FnOnce(Job) + Send + 'static,
+    {
+        let job = self.job;
+        Task {
+            job: Box::new(move || wrapper(job)),
+            ..self
+        }
+    }
+
+    /// Returns the weight the task is charged with, i.e. the weight of its flow multiplied by the factor of its
+    /// priority.
+    fn share(&self) -> f64 {
+        self.weight * PRIORITY_SHARES[self.priority.index()]
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancellation_token
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+    }
+}
+
+/// Accounting of the tasks of one priority.
+#[derive(Debug, Clone, Copy, Default)]
+struct Counters {
+    submitted: u64,
+    cancelled: u64,
+    completed: u64,
+    panicked: u64,
+    wait_over_budget: u64,
+    execute_over_budget: u64,
+    wait: Duration,
+    execute: Duration,
+}
+
+struct QueuedTask {
+    task: Task,
+    enqueued_at: Instant,
+}
+
+/// The queued tasks and the accounting of one flow.
+struct Flow {
+    queues: [VecDeque<QueuedTask>; 3],
+    weight: f64,
+    /// The time the workers spent on the flow divided by its weight, in seconds, including the expected time
+    /// of its running tasks.
+    virtual_time: f64,
+    /// The mean time the tasks of the flow run for, which is charged to the flow when a task starts.
+    cost: Duration,
+    completed: u64,
+    execute: Duration,
+    /// The number of tasks of the flow being run by a worker.
+    running: usize,
+    /// The time the flow last had a task queued or completed.
+    last_active: Instant,
+}
+
+impl Flow {
+    fn new() -> Self {
+        Flow {
+            queues: Default::default(),
+            weight: DEFAULT_WEIGHT,
+            virtual_time: 0.0,
+            cost: INITIAL_COST,
+            completed: 0,
+            execute: Duration::ZERO,
+            running: 0,
+            last_active: Instant::now(),
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+    }
+
+    /// Returns the index of the queue with the next task of the flow, i.e. the oldest task of the highest
+    /// priority once aged, along with the aged priority and the time the task was queued.
+    fn next(&self, now: Instant, aging: Duration) -> Option<(usize, usize, Instant)> {
+        self.queues
+            .iter()
+            .enumerate()
+            .filter_map(|(index, queue)| {
+                queue.front().map(|queued| {
+                    let waited = now.saturating_duration_since(queued.enqueued_at);
+                    (index, aged_index(index, waited, aging), queued.enqueued_at)
+                })
+            })
+            .min_by(|a, b| a.1.cmp(&b.1).then(a.2.cmp(&b.2)))
+    }
+}
+
+#[derive(Default)]
+struct State {
+    flows: HashMap<String, Flow>,
+    counters: [Counters; 3],
+    /// The virtual time of the flow which started a task last. A flow which becomes backlogged starts from it,
+    /// so that flows do not bank the time they were idle for.
+    virtual_clock: f64,
+}
+
+impl State {
+    /// Queues a task in its flow, which is created if it does not exist yet.
+    fn push(&mut self, task: Task, enqueued_at: Instant) {
+        let index = task.priority.index();
+        self.counters[index].submitted += 1;
+        let flow = self
+            .flows
+            .entry(task.flow.clone())
+            .or_insert_with(Flow::new);
+        if flow.is_idle() {
+            flow.virtual_time = flow.virtual_time.max(self.virtual_clock);
+        }
+        flow.weight = task.weight;
+        flow.last_active = enqueued_at;
+        flow.queues[index].push_back(QueuedTask { task, enqueued_at });
+    }
+
+    /// Pops the next task of the flow with the lowest virtual time among the flows with queued tasks, and
+    /// charges its expected cost divided by its share to the flow, see `Task::share`. Within the flow, the
+    /// oldest task of the highest priority runs first, and the priority of a task is raised by one for every
+    /// `aging` it waited for. Flows with the same virtual time are ordered by the priority of their next task.
+    fn pop(&mut self, aging: Duration) -> Option<(String, QueuedTask, f64)> {
+        let now = Instant::now();
+        let (name, index) = self
+            .flows
+            .iter()
+            .filter_map(|(name, flow)| {
+                flow.next(now, aging).map(|(index, aged, enqueued_at)| {
+                    (flow.virtual_time, aged, enqueued_at, index, name)
+                })
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)))
+            .map(|(_, _, _, index, name)| (name.clone(), index))?;
+
+        let flow = self.flows.get_mut(&name)?;
+        let queued = flow.queues[index].pop_front()?;
+        self.virtual_clock = self.virtual_clock.max(flow.virtual_time);
+        let charge = flow.cost.as_secs_f64() / queued.task.share();
+        flow.virtual_time += charge;
+        flow.running += 1;
+        Some((name, queued, charge))
+    }
+
+    /// Evicts the flows which had no queued or running task for `idle_timeout`.
+    fn evict_idle_flows(&mut self, idle_timeout: Duration) {
+        self.flows.retain(|_, flow| {
+            !flow.is_idle() || flow.running > 0 || flow.last_active.elapsed() < idle_timeout
+        });
+    }
+}
+
+/// Returns the index of a priority after a task with it waited for `waited`, i.e. raised by one for every
+/// `aging`. Tasks are not aged if `aging` is zero.
+fn aged_index(index: usize, waited: Duration, aging: Duration) -> usize {
+    if aging.is_zero() {
+        return index;
+    }
+    let promotions = waited.as_nanos() / aging.as_nanos();
+    index.saturating_sub(usize::try_from(promotions).unwrap_or(usize::MAX))
+}
+
+/// Accounting of the tasks of one flow of a scheduler, since the flow was created. A flow is evicted along with its
+/// accounting once it had no queued or running task for `FLOW_IDLE_TIMEOUT`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FlowStats {
+    /// The name of the scheduler.
+    pub scheduler: String,
+    /// The name of the flow, e.g. the model.
+    pub flow: String,
+    /// The weight of the flow.
+    pub weight: f64,
+    /// The number of tasks waiting for a worker.
+    pub queued: usize,
+    /// The number of tasks which ran to completion, including the ones which panicked.
+    pub completed: u64,
+    /// The total time the completed tasks ran for, in seconds.
+    pub execute_seconds: f64,
+    /// The fraction of the total time the completed tasks of the scheduler ran for which went to the flow.
+    pub share: f64,
+}
+
+/// Accounting of the tasks of one priority of a scheduler, since the scheduler was started.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SchedulerStats {
+    /// The name of the scheduler.
+    pub scheduler: String,
+    /// The priority of the tasks.
+    pub priority: Priority,
+    /// The number of tasks waiting for a worker.
+    pub queued: usize,
+    /// The number of tasks submitted.
+    pub submitted: u64,
+    /// The number of tasks withdrawn from the queue because they were cancelled.
+    pub cancelled: u64,
+    /// The number of tasks which ran to completion, including the ones which panicked.
+    pub completed: u64,
+    /// The number of tasks which panicked.
+    pub panicked: u64,
+    /// The number of tasks which waited for a worker for longer than the wait budget.
+    pub wait_over_budget: u64,
+    /// The number of tasks which ran for longer than the execute budget.
+    pub execute_over_budget: u64,
+    /// The total time the completed tasks waited for a worker, in seconds.
+    pub wait_seconds: f64,
+    /// The total time the completed tasks ran for, in seconds.
+    pub execute_seconds: f64,
+}
+
+/// Schedules tasks by priority on an `Executor` and accounts for the time they wait and run.
+///
+/// Tasks are queued by the scheduler rather than by the executor. Every submitted task hands one job over
+/// to the executor, which runs the next queued task, see below, at the time a worker becomes free.
+/// Tasks cancelled while queued are dropped without running.
+///
+/// Tasks which wait are promoted by one priority for every `aging`, see `DEFAULT_AGING`, so that tasks with a
+/// lower priority still run under a steady load of tasks of the same flow with a higher priority.
+///
+/// The workers are shared between the flows by weighted fair queuing: each flow keeps the time the workers spent
+/// on it divided by its weight, and the next task of the flow with the least of it runs next. The weight of a flow
+/// is multiplied by 4 for its `High` tasks, 2 for its `Normal` tasks and 1 for its `Low` tasks, so that the tasks
+/// with a higher priority get a larger share of the workers. A flow which submits many tasks, even with a high
+/// priority, therefore cannot starve a flow which submits few of them. Within a flow, the tasks with a higher
+/// priority run first.
+pub struct Scheduler {
+    name: String,
+    executor: Arc<dyn Executor>,
+    budget: Budget,
+    aging: Duration,
+    state: Arc<Mutex<State>>,
+}
+
+impl Scheduler {
+    /// Creates a scheduler.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the scheduler, used as the label of its metrics.
+    /// * `executor` - The executor which runs the tasks.
+    /// * `budget` - The time each task is expected to wait and run.
+    pub fn new(name: &str, executor: Arc<dyn Executor>, budget: Budget) -> Self {
+        Scheduler {
+            name: name.to_string(),
+            executor,
+            budget,
+            aging: DEFAULT_AGING,
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Sets the time a queued task waits before it is promoted by one priority. Tasks are not promoted if
+    /// it is zero, i.e. the tasks of a flow with a higher priority always run first.
+    pub fn with_aging(mut self, aging: Duration) -> Self {
+        self.aging = aging;
+        self
+    }
+
+    /// Returns the name of the scheduler.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the number of workers of the executor.
+    pub fn num_workers(&self) -> usize {
+        self.executor.num_workers()
+    }
+
+    /// Queues a task. Never blocks, the task runs once a worker is free and it is the next task of the flow
+    /// with the least time spent on it, see `Scheduler`.
+    pub fn submit(&self, task: Task) {
+        lock(&self.state).push(task, Instant::now());
+
+        let state = Arc::clone(&self.state);
+        let budget = self.budget;
+        let aging = self.aging;
+        let name = self.name.clone();
+        self.executor.execute(Box::new(move || {
+            run_next(&state, budget, aging, name.as_str())
+        }));
+    }
+
+    /// Returns the number of tasks waiting for a worker.
+    pub fn queued(&self) -> usize {
+        lock(&self.state)
+            .flows
+            .values()
+            .flat_map(|flow| flow.queues.iter().map(VecDeque::len))
+            .sum()
+    }
+
+    /// Returns the accounting of each flow, ordered by the name of the flow.
+    pub fn flow_stats(&self) -> Vec<FlowStats> {
+        let state = lock(&self.state);
+        let total: f64 = state
+            .flows
+            .values()
+            .map(|flow| flow.execute.as_secs_f64())
+            .sum();
+        let mut stats: Vec<FlowStats> = state
+            .flows
+            .iter()
+            .map(|(name, flow)| {
+                let execute_seconds = flow.execute.as_secs_f64();
+                FlowStats {
+                    scheduler: self.name.clone(),
+                    flow: name.clone(),
+                    weight: flow.weight,
+                    queued: flow.queues.iter().map(VecDeque::len).sum(),
+                    completed: flow.completed,
+                    execute_seconds,
+                    share: if total > 0.0 {
+                        execute_seconds / total
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| a.flow.cmp(&b.flow));
+        stats
+    }
+
+    /// Returns the accounting of each priority, from the highest to the lowest.
+    pub fn stats(&self) -> Vec<SchedulerStats> {
+        let state = lock(&self.state);
+        Priority::ALL
+            .iter()
+            .map(|priority| {
+                let counters = state.counters[priority.index()];
+                SchedulerStats {
+                    scheduler: self.name.clone(),
+                    priority: *priority,
+                    queued: state
+                        .flows
+                        .values()
+                        .map(|flow| flow.queues[priority.index()].len())
+                        .sum(),
+                    submitted: counters.submitted,
+                    cancelled: counters.cancelled,
+                    completed: counters.completed,
+                    panicked: counters.panicked,
+                    wait_over_budget: counters.wait_over_budget,
+                    execute_over_budget: counters.execute_over_budget,
+                    wait_seconds: counters.wait.as_secs_f64(),
+                    execute_seconds: counters.execute.as_secs_f64(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Runs the next queued task on the current worker.
+fn run_next(state: &Mutex<State>, budget: Budget, aging: Duration, name: &str) {
+    let (flow, queued, charge) = match lock(state).pop(aging) {
+        Some(next) => next,
+        None => return,
+    };
+
+    let index = queued.task.priority.index();
+    let share = queued.task.share();
+    let wait = queued.enqueued_at.elapsed();
+    if queued.task.is_cancelled() {
+        drop(queued);
+        let mut state = lock(state);
+        state.counters[index].cancelled += 1;
+        // the flow is refunded the time it was charged for the task
+        if let Some(flow) = state.flows.get_mut(&flow) {
+            flow.virtual_time -= charge;
+            flow.running -= 1;
+        }
+        state.evict_idle_flows(FLOW_IDLE_TIMEOUT);
+        return;
+    }
+
+    let start = Instant::now();
+    // a panic inside a rayon worker aborts the process, so it is caught and accounted for instead
+    let panicked = panic::catch_unwind(AssertUnwindSafe(queued.task.job)).is_err();
+    let execute = start.elapsed();
+    if panicked {
+        tracing::error!("Task panicked on scheduler {} ❌", name);
+    }
+
+    let mut state = lock(state);
+    if let Some(flow) = state.flows.get_mut(&flow) {
+        // the expected time charged when the task started is replaced by the time it actually ran for
+        flow.virtual_time += execute.as_secs_f64() / share - charge;
+        flow.cost = flow.cost.mul_f64(1.0 - COST_SMOOTHING) + execute.mul_f64(COST_SMOOTHING);
+        flow.completed += 1;
+        flow.execute += execute;
+        flow.running -= 1;
+        flow.last_active = Instant::now();
+    }
+    state.evict_idle_flows(FLOW_IDLE_TIMEOUT);
+    let counters = &mut state.counters[index];
+    counters.completed += 1;
+    counters.wait += wait;
+    counters.execute += execute;
+    if panicked {
+        counters.panicked += 1;
+    }
+    if budget.max_wait.is_some_and(|max_wait| wait > max_wait) {
+        counters.wait_over_budget += 1;
+    }
+    if budget
+        .max_execute
+        .is_some_and(|max_execute| execute > max_execute)
+    {
+        counters.execute_over_budget += 1;
+    }
+}
+
+/// Locks the state, which stays consistent even if a thread panicked while holding the lock.
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::executor::ExecutorKind;
+    use std::sync::mpsc;
+
+    fn blocked_scheduler(budget: Budget) -> (Scheduler, mpsc::Sender<()>) {
+        let executor = ExecutorKind::Threads.build(1, "jams-test").unwrap();
+        let scheduler = Scheduler::new("test", executor, budget);
+        let (block_tx, block_rx) = mpsc::channel::<()>();
+        let (started_tx, started_rx) = mpsc::channel::<()>();
+        // occupy the only worker so that the following tasks are queued
+        scheduler.submit(Task::new(move || {
+            started_tx.send(()).unwrap();
+            let _ = block_rx.recv();
+        }));
+        started_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        (scheduler, block_tx)
+    }
+
+    fn wait_for_completion(scheduler: &Scheduler, completed: u64) {
+        let start = Instant::now();
+        while scheduler
+            .stats()
+            .iter()
+            .map(|s| s.completed + s.cancelled)
+            .sum::<u64>()
+            < completed
+        {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn successfully_run_queued_tasks_by_priority() {
+        // Arrange
+        let (scheduler, block_tx) = blocked_scheduler(Budget::default());
+        let (tx, rx) = mpsc::channel();
+
+        // Act
+        for priority in [
+            Priority::Low,
+            Priority::High,
+            Priority::Normal,
+            Priority::High,
+        ] {
+            let tx = tx.clone();
+            scheduler.submit(Task::new(move || tx.send(priority).unwrap()).with_priority(priority));
+        }
+        let queued = scheduler.queued();
+        block_tx.send(()).unwrap();
+        let order: Vec<Priority> = (0..4)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+
+        // Assert
+        assert_eq!(queued, 4);
+        assert_eq!(
+            order,
+            vec![
+                Priority::High,
+                Priority::High,
+                Priority::Normal,
+                Priority::Low
+            ]
+        );
+    }
+
+    #[test]
+    fn successfully_run_low_priority_task_which_waited_before_newer_high_priority_tasks() {
+        // Arrange
+        let (scheduler, block_tx) = blocked_scheduler(Budget::default());
+        let scheduler = scheduler.with_aging(Duration::from_millis(10));
+        let (tx, rx) = mpsc::channel();
+        let low_tx = tx.clone();
+        scheduler.submit(
+            Task::new(move || low_tx.send(Priority::Low).unwrap()).with_priority(Priority::Low),
+        );
+        // the low priority task waits for two promotions
+        std::thread::sleep(Duration::from_millis(25));
+
+        // Act
+        for _ in 0..2 {
+            let tx = tx.clone();
+            scheduler.submit(
+                Task::new(move || tx.send(Priority::High).unwrap()).with_priority(Priority::High),
+            );
+        }
+        block_tx.send(()).unwrap();
+        let order: Vec<Priority> = (0..3)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+
+        // Assert
+        assert_eq!(order, vec![Priority::Low, Priority::High, Priority::High]);
+    }
+
+    #[test]
+    fn successfully_age_priority_of_waiting_tasks() {
+        // Act
+        let fresh = aged_index(Priority::Low.index(), Duration::ZERO, DEFAULT_AGING);
+        let waited = aged_index(Priority::Low.index(), DEFAULT_AGING, DEFAULT_AGING);
+        let long_waited = aged_index(Priority::Low.index(), DEFAULT_AGING * 10, DEFAULT_AGING);
+        let not_aged = aged_index(Priority::Low.index(), DEFAULT_AGING * 10, Duration::ZERO);
+
+        // Assert
+        assert_eq!(fresh, Priority::Low.index());
+        assert_eq!(waited, Priority::Normal.index());
+        assert_eq!(long_waited, Priority::High.index());
+        assert_eq!(not_aged, Priority::Low.index());
+    }
+
+    #[test]
+    fn successfully_withdraw_cancelled_tasks_and_account_for_budgets() {
+        // Arrange
+        let budget = Budget {
+            max_wait: Some(Duration::ZERO),
+            max_execute: Some(Duration::ZERO),
+        };
+        let (scheduler, block_tx) = blocked_scheduler(budget);
+        let cancellation_token = CancellationToken::new();
+        let (tx, rx) = mpsc::channel::<&str>();
+        let cancelled_tx = tx.clone();
+
+        // Act
+        scheduler.submit(
+            Task::new(move || cancelled_tx.send("cancelled").unwrap())
+                .with_cancellation_token(cancellation_token.clone()),
+        );
+        scheduler.submit(Task::new(move || {
+            std::thread::sleep(Duration::from_millis(1));
+            tx.send("completed").unwrap()
+        }));
+        scheduler.submit(Task::new(|| panic!("boom")).with_priority(Priority::Low));
+        cancellation_token.cancel();
+        block_tx.send(()).unwrap();
+        wait_for_completion(&scheduler, 4);
+        let stats = scheduler.stats();
+
+        // Assert
+        assert_eq!(rx.try_iter().collect::<Vec<&str>>(), vec!["completed"]);
+        let normal = &stats[Priority::Normal.index()];
+        assert_eq!(normal.submitted, 3);
+        assert_eq!(normal.cancelled, 1);
+        assert_eq!(normal.completed, 2);
+        assert!(normal.wait_over_budget >= 1);
+        assert_eq!(normal.execute_over_budget, 2);
+        let low = &stats[Priority::Low.index()];
+        assert_eq!(low.completed, 1);
+        assert_eq!(low.panicked, 1);
+    }
+
+    #[test]
+    fn successfully_run_tasks_of_a_low_volume_flow_while_another_flow_floods_the_queue() {
+        // Arrange
+        let (scheduler, block_tx) = blocked_scheduler(Budget::default());
+        let (tx, rx) = mpsc::channel();
+
+        // Act
+        for _ in 0..50 {
+            let tx = tx.clone();
+            scheduler.submit(Task::new(move || tx.send("bulk").unwrap()).with_flow("bulk", 1.0));
+        }
+        for _ in 0..2 {
+            let tx = tx.clone();
+            scheduler
+                .submit(Task::new(move || tx.send("critical").unwrap()).with_flow("critical", 1.0));
+        }
+        block_tx.send(()).unwrap();
+        let order: Vec<&str> = (0..52)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+
+        // Assert
+        let critical: Vec<usize> = order
+            .iter()
+            .enumerate()
+            .filter(|(_, flow)| **flow == "critical")
+            .map(|(position, _)| position)
+            .collect();
+        assert_eq!(critical.len(), 2);
+        // in the order of submission, the critical tasks would only run after all the bulk tasks
+        assert!(critical.iter().all(|position| *position < 10));
+    }
+
+    /// Pops `count` tasks from the state without running them and returns the flows they belong to.
+    fn pop_flows(state: &mut State, count: usize) -> Vec<String> {
+        (0..count)
+            .map(|_| state.pop(Duration::ZERO).unwrap().0)
+            .collect()
+    }
+
+    #[test]
+    fn successfully_share_workers_between_flows_by_weight() {
+        // Arrange
+        let mut state = State::default();
+        let start = Instant::now();
+        for position in 0..40 {
+            for (offset, (flow, weight)) in [("heavy", 3.0), ("light", 1.0)].iter().enumerate() {
+                let enqueued_at = start + Duration::from_micros(2 * position + offset as u64);
+                state.push(Task::new(|| {}).with_flow(flow, *weight), enqueued_at);
+            }
+        }
+
+        // Act
+        // while both flows are backlogged, each task is charged the same expected cost divided by its share
+        let order = pop_flows(&mut state, 40);
+
+        // Assert
+        let heavy = order.iter().filter(|flow| *flow == "heavy").count();
+        assert_eq!(heavy, 30);
+        assert_eq!(state.flows["heavy"].weight, 3.0);
+        assert_eq!(state.flows["light"].weight, 1.0);
+    }
+
+    #[test]
+    fn successfully_share_workers_between_flows_whatever_the_priorities_of_their_tasks() {
+        // Arrange
+        let mut state = State::default();
+        let start = Instant::now();
+        for position in 0..50 {
+            let task = Task::new(|| {})
+                .with_flow("bulk", 1.0)
+                .with_priority(Priority::High);
+            state.push(task, start + Duration::from_micros(position));
+        }
+        for position in 50..52 {
+            let task = Task::new(|| {}).with_flow("critical", 1.0);
+            state.push(task, start + Duration::from_micros(position));
+        }
+
+        // Act
+        let order = pop_flows(&mut state, 52);
+
+        // Assert
+        let critical: Vec<usize> = order
+            .iter()
+            .enumerate()
+            .filter(|(_, flow)| *flow == "critical")
+            .map(|(position, _)| position)
+            .collect();
+        // with strict priorities, the critical tasks would only run after all the high priority bulk tasks, instead
+        // the bulk flow gets twice the share of the critical flow
+        assert_eq!(critical, vec![1, 4]);
+    }
+
+    #[test]
+    fn successfully_evict_idle_flows() {
+        // Arrange
+        let (scheduler, block_tx) = blocked_scheduler(Budget::default());
+        scheduler.submit(Task::new(|| {}).with_flow("model", 1.0));
+        block_tx.send(()).unwrap();
+        wait_for_completion(&scheduler, 2);
+
+        // Act
+        let flows_before = scheduler.flow_stats().len();
+        lock(&scheduler.state).evict_idle_flows(Duration::ZERO);
+        let flows_after = scheduler.flow_stats().len();
+        let mut state = State::default();
+        state.push(Task::new(|| {}).with_flow("queued", 1.0), Instant::now());
+        state.evict_idle_flows(Duration::ZERO);
+
+        // Assert
+        // the flows are kept until they were idle for the timeout
+        assert_eq!(flows_before, 2);
+        assert_eq!(flows_after, 0);
+        // a flow with queued tasks is never evicted
+        assert!(state.flows.contains_key("queued"));
+    }
+
+    #[test]
+    fn successfully_fall_back_to_the_default_weight_when_the_weight_is_invalid() {
+        // Arrange
+        let weights = [0.0, -1.0, f64::NAN, f64::INFINITY];
+
+        // Act
+        let tasks: Vec<Task> = weights
+            .iter()
+            .map(|weight| Task::new(|| {}).with_flow("model", *weight))
+            .collect();
+
+        // Assert
+        assert!(tasks.iter().all(|task| task.weight == DEFAULT_WEIGHT));
+    }
+}
@@ -0,0 +1,60 @@
+// The cipher of the artifact cache is installed for the whole process, so the encrypted model stores are tested in
+// a test binary of their own.
+use jams_core::model::input::ModelInput;
+use jams_core::model_store::common::hash_artifact;
+use jams_core::model_store::encryption::{self, ArtifactCipher};
+use jams_core::model_store::local::filesystem::LocalModelStore;
+use jams_core::model_store::storage::Storage;
+use jams_predictor_api::golden::{compare_outputs, GoldenFixture};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+const MODEL_STORE_DIR: &str = "tests/model_storage/model_store";
+const MODEL_NAME: &str = "my_awesome_reg_model";
+const GOLDEN_FIXTURE: &str = "tests/model_storage/golden/lightgbm-my_awesome_reg_model.json";
+
+#[tokio::test]
+async fn successfully_load_and_predict_with_model_from_encrypted_model_store() {
+    // Arrange
+    let scratch = tempfile::tempdir().unwrap();
+    let cipher = ArtifactCipher::new(&[7; 32], scratch.path().to_path_buf()).unwrap();
+    encryption::install(cipher).unwrap();
+    let fixture: GoldenFixture =
+        serde_json::from_str(&std::fs::read_to_string(GOLDEN_FIXTURE).unwrap()).unwrap();
+    let plaintext_sha256 = format!(
+        "{:x}",
+        Sha256::digest(std::fs::read(fixture.model.as_str()).unwrap())
+    );
+    let load = || {
+        LocalModelStore::with_priority_models(
+            MODEL_STORE_DIR.to_string(),
+            vec![MODEL_NAME.to_string()],
+        )
+    };
+
+    // Act
+    let model_store = load().await.unwrap();
+    let reloaded_model_store = load().await.unwrap();
+    let model = model_store.get_model(MODEL_NAME.to_string()).unwrap();
+    let reloaded_model = reloaded_model_store
+        .get_model(MODEL_NAME.to_string())
+        .unwrap();
+    let input = ModelInput::from_str(fixture.input.to_string().as_str()).unwrap();
+    let output = model
+        .predictor
+        .predict_with_options(input, &fixture.predict_options)
+        .unwrap();
+
+    // Assert
+    let cached = std::fs::read(model.info.path.as_str()).unwrap();
+    assert!(encryption::is_sealed(cached.as_slice()));
+    assert_eq!(model.info.artifact_sha256, plaintext_sha256);
+    assert_eq!(reloaded_model.info.artifact_sha256, plaintext_sha256);
+    assert_eq!(
+        hash_artifact(Path::new(model.info.path.as_str())).unwrap(),
+        plaintext_sha256
+    );
+    assert!(compare_outputs(&output.predictions, &fixture.expected, fixture.tolerance).is_ok());
+    // the plaintext is only decrypted into the scratch directory while the model loads
+    assert_eq!(std::fs::read_dir(scratch.path()).unwrap().count(), 0);
+}
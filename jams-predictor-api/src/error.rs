@@ -0,0 +1,105 @@
+use std::fmt;
+
+/// The error returned by the public APIs of `jams-core`.
+///
+/// Each variant describes the kind of failure so that library consumers can match on it, while the
+/// wrapped `anyhow::Error` carries the details and the chain of underlying causes. The underlying error
+/// is kept as is, so it can be recovered with `anyhow::Error::downcast_ref`, and is included when the
+/// error is displayed.
+#[derive(Debug)]
+pub enum Error {
+    /// A model could not be loaded from its artifact.
+    Load(anyhow::Error),
+    /// A model failed while making predictions.
+    Predict(anyhow::Error),
+    /// The model store failed to fetch, add, update or delete models.
+    Store(anyhow::Error),
+    /// The model input or prediction options are invalid for the model.
+    Input(anyhow::Error),
+    /// The requested model is not loaded in the model store.
+    ModelNotFound(String),
+    /// The loaded model is not the version pinned by the request.
+    VersionMismatch(anyhow::Error),
+}
+
+/// A `Result` whose error is a `jams_core::Error`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Load(e) => write!(f, "load error: {:#}", e),
+            Error::Predict(e) => write!(f, "predict error: {:#}", e),
+            Error::Store(e) => write!(f, "store error: {:#}", e),
+            Error::Input(e) => write!(f, "input error: {:#}", e),
+            Error::VersionMismatch(e) => write!(f, "version mismatch: {:#}", e),
+            Error::ModelNotFound(model_name) => {
+                write!(f, "model {} does not exist ❌", model_name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Load(e)
+            | Error::Predict(e)
+            | Error::Store(e)
+            | Error::Input(e)
+            | Error::VersionMismatch(e) => Some(e.as_ref()),
+            Error::ModelNotFound(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_match_error_kind_through_anyhow() {
+        // Arrange
+        let error: anyhow::Error = Error::Input(anyhow::anyhow!("Failed to parse input ❌")).into();
+
+        // Act
+        let kind = error.downcast_ref::<Error>();
+
+        // Assert
+        assert!(matches!(kind, Some(Error::Input(_))));
+        assert_eq!(error.to_string(), "input error: Failed to parse input ❌");
+    }
+
+    #[test]
+    fn successfully_expose_the_source_of_the_error() {
+        // Arrange
+        let error = Error::Load(anyhow::anyhow!("missing file"));
+
+        // Act
+        let source = std::error::Error::source(&error);
+
+        // Assert
+        assert_eq!(source.unwrap().to_string(), "missing file");
+        assert!(std::error::Error::source(&Error::ModelNotFound("my_model".to_string())).is_none());
+    }
+
+    #[test]
+    fn successfully_downcast_the_wrapped_error() {
+        // Arrange
+        let io_error = std::io::Error::new(std::io::ErrorKind::NotFound, "missing file");
+        let error = Error::Store(anyhow::Error::from(io_error).context("Failed to add new model"));
+
+        // Act
+        let source = match &error {
+            Error::Store(e) => e.downcast_ref::<std::io::Error>(),
+            _ => None,
+        };
+
+        // Assert
+        assert_eq!(source.unwrap().kind(), std::io::ErrorKind::NotFound);
+        assert_eq!(
+            error.to_string(),
+            "store error: Failed to add new model: missing file"
+        );
+    }
+}
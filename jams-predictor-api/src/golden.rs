@@ -0,0 +1,117 @@
+use crate::input::ModelInput;
+// the outputs are compared outside of tests too, e.g. by the smoke test of jams-serve
+pub use crate::output::compare_outputs;
+use crate::predict::{Predict, PredictOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Environment variable which records the current outputs as the expected outputs when set.
+pub const UPDATE_GOLDEN_ENV: &str = "JAMS_UPDATE_GOLDEN";
+
+fn default_tolerance() -> f64 {
+    1e-6
+}
+
+/// A golden fixture.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GoldenFixture {
+    /// Path to the model artifact, relative to the crate root.
+    pub model: String,
+    /// Prediction options used for the prediction.
+    #[serde(default, skip_serializing_if = "PredictOptions::is_empty")]
+    pub predict_options: PredictOptions,
+    /// The model input.
+    pub input: serde_json::Value,
+    /// The outputs the model is expected to return, keyed by the name of the output.
+    pub expected: HashMap<String, Vec<Vec<f64>>>,
+    /// Maximum difference allowed between an output and its expected value, relative to the
+    /// expected value for values larger than 1.
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+}
+
+/// Runs the golden fixture at `path` against the predictor loaded by `load`, so that every crate can keep its
+/// fixtures in its own directory.
+///
+/// # Panics
+/// If the fixture cannot be read, the prediction fails or the outputs do not match.
+pub fn check_golden_file<P, F>(path: &str, load: F)
+where
+    P: Predict,
+    F: Fn(&str) -> crate::Result<P>,
+{
+    let contents = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("failed to read golden fixture {}: {}", path, e));
+    let mut fixture: GoldenFixture = serde_json::from_str(&contents)
+        .unwrap_or_else(|e| panic!("failed to parse golden fixture {}: {}", path, e));
+
+    let predictor = load(fixture.model.as_str())
+        .unwrap_or_else(|e| panic!("failed to load model {}: {}", fixture.model, e));
+    let input = ModelInput::from_str(fixture.input.to_string().as_str()).unwrap();
+    let output = predictor
+        .predict_with_options(input, &fixture.predict_options)
+        .unwrap_or_else(|e| panic!("failed to predict golden fixture {}: {}", path, e));
+
+    if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+        fixture.expected = output.predictions;
+        let mut contents = serde_json::to_string_pretty(&fixture).unwrap();
+        contents.push('\n');
+        std::fs::write(path, contents).unwrap();
+        return;
+    }
+
+    if let Err(mismatches) =
+        compare_outputs(&output.predictions, &fixture.expected, fixture.tolerance)
+    {
+        panic!(
+            "outputs of golden fixture {} changed, run with {}=1 to update it if this is expected:\n{}",
+            path, UPDATE_GOLDEN_ENV, mismatches
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_compare_outputs_within_tolerance() {
+        // Arrange
+        let expected = HashMap::from([("predictions".to_string(), vec![vec![0.5], vec![120.0]])]);
+        let actual = HashMap::from([(
+            "predictions".to_string(),
+            vec![vec![0.5000001], vec![120.00001]],
+        )]);
+
+        // Act
+        let result = compare_outputs(&actual, &expected, 1e-6);
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn fails_to_compare_outputs_when_values_or_shapes_differ() {
+        // Arrange
+        let expected = HashMap::from([("predictions".to_string(), vec![vec![0.5], vec![0.2]])]);
+        let shifted = HashMap::from([("predictions".to_string(), vec![vec![0.5], vec![0.21]])]);
+        let reshaped = HashMap::from([(
+            "predictions".to_string(),
+            vec![vec![0.5, 0.5], vec![0.2, 0.8]],
+        )]);
+        let renamed = HashMap::from([("logits".to_string(), vec![vec![0.5], vec![0.2]])]);
+
+        // Act
+        let shifted = compare_outputs(&shifted, &expected, 1e-6);
+        let reshaped = compare_outputs(&reshaped, &expected, 1e-6);
+        let renamed = compare_outputs(&renamed, &expected, 1e-6);
+
+        // Assert
+        assert_eq!(
+            shifted.unwrap_err(),
+            "predictions[1][0] is 0.21, expected 0.2"
+        );
+        assert!(reshaped.is_err());
+        assert!(renamed.unwrap_err().contains("missing output predictions"));
+    }
+}
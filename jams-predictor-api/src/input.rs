@@ -0,0 +1,1687 @@
+use crate::pool::MODEL_INPUT_POOL;
+use crate::{FEATURE_NAMES_CAPACITY, FEATURE_VALUES_CAPACITY};
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Formatter;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+#[cfg(feature = "fast-numbers")]
+mod fast;
+
+/// Maximum number of values accepted for a single feature when parsing a `ModelInput`.
+pub const MAX_ROWS: usize = 100_000;
+
+/// Maximum number of features accepted when parsing a `ModelInput`.
+pub const MAX_FEATURES: usize = 1024;
+
+/// Maximum length in bytes of a feature name or a string value when parsing a `ModelInput`.
+pub const MAX_STRING_LENGTH: usize = 4096;
+
+/// Type alias for the feature name, which is a string.
+pub type FeatureName = String;
+
+#[derive(Debug, Clone)]
+pub enum Values {
+    String(Vec<String>),
+    Int(Vec<i32>),
+    Float(Vec<f32>),
+}
+
+impl Values {
+    /// Consumes the `Values` enum and returns the inner `Vec<i32>` if the variant is `Values::Int`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Vec<i32>)` - if the variant is `Values::Int`.
+    /// * `None` - if the variant is not `Values::Int`.
+    ///
+    pub fn into_ints(self) -> Option<Vec<i32>> {
+        if let Values::Int(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    /// Converts the `Values` enum to a reference of the vector of `i32` values.
+    ///
+    /// This method checks if the `Values` enum is of the `Int` variant. If it is, it
+    /// returns a reference to the vector of `i32` values inside it. If the enum is not
+    /// of the `Int` variant, it returns `None`.
+    ///
+    /// # Returns
+    /// - `Some(&Vec<i32>)`: A reference to the vector of `i32` values, if the enum is of type `Int`.
+    /// - `None`: If the enum is not of type `Int`.
+    pub fn as_ints(&self) -> Option<&Vec<i32>> {
+        if let Values::Int(v) = self {
+            Some(v) // Return a reference to the vector inside `Values::Int`
+        } else {
+            None
+        }
+    }
+
+    /// Consumes the `Values` enum and returns the inner `Vec<f32>` if the variant is `Values::Float`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Vec<f32>)` - if the variant is `Values::Float`.
+    /// * `None` - if the variant is not `Values::Float`.
+    ///
+    pub fn into_floats(self) -> Option<Vec<f32>> {
+        if let Values::Float(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    /// Converts the `Values` enum to a reference of the vector of `f32` values.
+    ///
+    /// This method checks if the `Values` enum is of the `Float` variant. If it is, it
+    /// returns a reference to the vector of `f32` values inside it. If the enum is not
+    /// of the `Float` variant, it returns `None`.
+    ///
+    /// # Returns
+    /// - `Some(&Vec<f32>)`: A reference to the vector of `f32` values, if the enum is of type `Float`.
+    /// - `None`: If the enum is not of type `Float`.
+    pub fn as_floats(&self) -> Option<&Vec<f32>> {
+        if let Values::Float(v) = self {
+            Some(v) // Return a reference to the vector inside `Values::Float`
+        } else {
+            None
+        }
+    }
+
+    /// Consumes the `Values` enum and returns the inner `Vec<String>` if the variant is `Values::String`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Vec<String>)` - if the variant is `Values::String`.
+    /// * `None` - if the variant is not `Values::String`.
+    ///
+    pub fn into_strings(self) -> Option<Vec<String>> {
+        if let Values::String(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    /// Converts the `Values` enum to a reference of the vector of `String` values.
+    ///
+    /// This method checks if the `Values` enum is of the `String` variant. If it is, it
+    /// returns a reference to the vector of `String` values inside it. If the enum is not
+    /// of the `String` variant, it returns `None`.
+    ///
+    /// # Returns
+    /// - `Some(&Vec<f32>)`: A reference to the vector of `f32` values, if the enum is of type `Float`.
+    /// - `None`: If the enum is not of type `Float`.
+    pub fn as_strings(&self) -> Option<&Vec<String>> {
+        if let Values::String(v) = self {
+            Some(v) // Return a reference to the vector inside `Values::Float`
+        } else {
+            None
+        }
+    }
+
+    /// Appends the contents of another `Values` enum to the current one.
+    ///
+    /// This method allows you to append a vector of the same type (e.g., `String` to `String`, `i32` to `i32`, etc.)
+    ///
+    /// # Panics
+    /// Panics if the variant of the current `Values` enum doesn't match the variant of the other `Values` enum.
+    ///
+    pub fn append(&mut self, other: &mut Values) {
+        match (self, other) {
+            (Values::String(v1), Values::String(v2)) => v1.append(v2), // No cloning, move `v2` into `v1`
+            (Values::Int(v1), Values::Int(v2)) => v1.append(v2), // No cloning, move `v2` into `v1`
+            (Values::Float(v1), Values::Float(v2)) => v1.append(v2), // No cloning, move `v2` into `v1`
+            _ => panic!("Cannot append values of different types"), // Panics if variants are not the same
+        }
+    }
+
+    /// Clears the data inside the enum.
+    ///
+    /// This method empties the vector contained within the current variant of the `Values` enum.
+    /// It does not change the variant itself. For example:
+    /// - For `Values::String`, it clears the `Vec<String>`.
+    /// - For `Values::Int`, it clears the `Vec<i32>`.
+    /// - For `Values::Float`, it clears the `Vec<f32>`.
+    ///
+    fn clear(&mut self) {
+        match self {
+            Values::String(v) => v.clear(),
+            Values::Int(v) => v.clear(),
+            Values::Float(v) => v.clear(),
+        }
+    }
+
+    /// Shrinks the capacity of the vector to at most `capacity` values.
+    fn shrink_to(&mut self, capacity: usize) {
+        match self {
+            Values::String(v) => v.shrink_to(capacity),
+            Values::Int(v) => v.shrink_to(capacity),
+            Values::Float(v) => v.shrink_to(capacity),
+        }
+    }
+
+    /// Returns true if the vector holds no value.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Values::String(v) => v.is_empty(),
+            Values::Int(v) => v.is_empty(),
+            Values::Float(v) => v.is_empty(),
+        }
+    }
+}
+
+impl Extend<f32> for Values {
+    fn extend<T: IntoIterator<Item = f32>>(&mut self, iter: T) {
+        match self {
+            Values::Float(v) => v.extend(iter),
+            _ => panic!("Cannot extend non-Float variant with a Float iterator"),
+        }
+    }
+}
+
+impl Extend<i32> for Values {
+    fn extend<T: IntoIterator<Item = i32>>(&mut self, iter: T) {
+        match self {
+            Values::Int(v) => v.extend(iter),
+            _ => panic!("Cannot extend non-Integer variant with a Integer iterator"),
+        }
+    }
+}
+
+impl Extend<String> for Values {
+    fn extend<T: IntoIterator<Item = String>>(&mut self, iter: T) {
+        match self {
+            Values::String(v) => v.extend(iter),
+            _ => panic!("Cannot extend non-String variant with a String iterator"),
+        }
+    }
+}
+
+/// A collection of features of a specific type.
+///
+/// This struct represents a generic feature set, where each feature has a name,
+/// a collection of values, and a shape that describes the dimensions of the data.
+#[derive(Debug, Clone)]
+pub struct Features {
+    /// The names of the features.
+    pub names: Vec<FeatureName>,
+    /// The values associated with the features.
+    pub values: Values,
+    /// The shape of the feature set, represented as (rows, columns).
+    pub shape: (usize, usize),
+}
+
+impl Features {
+    /// Clears the contents of the `Features` struct.
+    ///
+    /// This method resets the `Features` struct by:
+    /// - Setting `shape` to `(0, 0)` to indicate an empty feature set.
+    /// - Calling `clear` on the `names` vector to remove all feature names.
+    /// - Calling `clear` on the `values` enum to remove all feature values.
+    ///
+    fn clear(&mut self) {
+        self.shape = (0, 0);
+        self.values.clear();
+        self.names.clear()
+    }
+
+    /// Returns the rows `start..end` of every feature.
+    fn slice_rows(&self, start: usize, end: usize) -> Features {
+        let (num_features, num_rows) = self.shape;
+        if num_features == 0 {
+            return self.clone();
+        }
+        let rows = |feature: usize| feature * num_rows + start..feature * num_rows + end;
+        let values = match &self.values {
+            Values::String(v) => Values::String(
+                (0..num_features)
+                    .flat_map(|f| v[rows(f)].to_vec())
+                    .collect(),
+            ),
+            Values::Int(v) => Values::Int(
+                (0..num_features)
+                    .flat_map(|f| v[rows(f)].to_vec())
+                    .collect(),
+            ),
+            Values::Float(v) => Values::Float(
+                (0..num_features)
+                    .flat_map(|f| v[rows(f)].to_vec())
+                    .collect(),
+            ),
+        };
+        Features {
+            names: self.names.clone(),
+            values,
+            shape: (num_features, end - start),
+        }
+    }
+}
+
+/// The input data for a machine learning model.
+///
+/// This struct contains three types of features: float, integer, and string.
+/// Each type is stored in its own `Features` structure.
+#[derive(Debug, Clone)]
+pub struct ModelInput {
+    /// The float features in the input.
+    pub float_features: Features,
+    /// The integer features in the input.
+    pub integer_features: Features,
+    /// The string features in the input.
+    pub string_features: Features,
+}
+
+impl Default for ModelInput {
+    fn default() -> Self {
+        let float_features = Features {
+            names: Vec::with_capacity(FEATURE_NAMES_CAPACITY),
+            values: Values::Float(Vec::with_capacity(FEATURE_NAMES_CAPACITY)),
+            shape: (0, 0),
+        };
+        let integer_features = Features {
+            names: Vec::with_capacity(FEATURE_NAMES_CAPACITY),
+            values: Values::Int(Vec::with_capacity(FEATURE_NAMES_CAPACITY)),
+            shape: (0, 0),
+        };
+        let string_features = Features {
+            names: Vec::with_capacity(FEATURE_NAMES_CAPACITY),
+            values: Values::String(Vec::with_capacity(FEATURE_NAMES_CAPACITY)),
+            shape: (0, 0),
+        };
+
+        Self {
+            float_features,
+            integer_features,
+            string_features,
+        }
+    }
+}
+
+impl ModelInput {
+    /// Parses a JSON string to create a `ModelInput` instance.
+    ///
+    /// Both columnar and row-oriented inputs are accepted and told apart by their top level value:
+    ///
+    /// - Columnar: a map of feature names to arrays of values, i.e. `{"f1": [1, 2], "f2": ["a", "b"]}`.
+    /// - Row-oriented: an array of records mapping feature names to values, i.e.
+    ///   `[{"f1": 1, "f2": "a"}, {"f1": 2, "f2": "b"}]`. Every record must have the same features,
+    ///   and the features are ordered as in the first record.
+    ///
+    /// The input is expected to come from untrusted clients, so it is validated while it is parsed.
+    /// Payloads with more than `MAX_FEATURES` features, more than `MAX_ROWS` values per feature,
+    /// strings longer than `MAX_STRING_LENGTH`, nested values, mixed value types, features with
+    /// different number of values or records with different features are rejected with an error.
+    ///
+    /// With the `fast-numbers` feature, columnar inputs are parsed by a scanner specialised for arrays of
+    /// numbers first, and by `serde_json` if the scanner does not support them.
+    ///
+    /// # Arguments
+    /// * `json` - The JSON string representing the model input.
+    ///
+    /// # Returns
+    /// * `Ok(ModelInput)` - If parsing was successful.
+    /// * `Err(anyhow::Error)` - If there was an error during parsing.
+    #[allow(clippy::should_implement_trait)]
+    #[tracing::instrument(skip(json))]
+    pub fn from_str(json: &str) -> anyhow::Result<ModelInput> {
+        let mut model_input = ModelInput::pooled();
+        match parse_into(json, &mut model_input, &mut Vec::new()) {
+            Ok(_) => Ok(model_input),
+            Err(e) => {
+                tracing::error!("Failed to parse json to model input: {} ❌", e.to_string());
+                anyhow::bail!("Failed to parse json to model input: {} ❌", e.to_string())
+            }
+        }
+    }
+
+    /// Returns the number of rows in the input.
+    ///
+    /// All the features in a parsed input have the same number of values, but feature types which
+    /// are not present have a shape of `(0, 0)`, so the largest row count is used.
+    pub fn num_rows(&self) -> usize {
+        self.float_features
+            .shape
+            .1
+            .max(self.integer_features.shape.1)
+            .max(self.string_features.shape.1)
+    }
+
+    /// Splits the input into inputs of at most `max_rows` rows, keeping the order of the rows.
+    ///
+    /// # Arguments
+    /// * `max_rows` - The maximum number of rows of each input. Must be at least 1.
+    ///
+    /// # Returns
+    /// The inputs, or the input itself if it has no more than `max_rows` rows.
+    pub fn split_rows(self, max_rows: usize) -> Vec<ModelInput> {
+        let num_rows = self.num_rows();
+        if max_rows == 0 || num_rows <= max_rows {
+            return vec![self];
+        }
+        (0..num_rows)
+            .step_by(max_rows)
+            .map(|start| {
+                let end = (start + max_rows).min(num_rows);
+                ModelInput {
+                    float_features: self.float_features.slice_rows(start, end),
+                    integer_features: self.integer_features.slice_rows(start, end),
+                    string_features: self.string_features.slice_rows(start, end),
+                }
+            })
+            .collect()
+    }
+
+    /// Clears the contents of the `ModelInput` struct.
+    fn clear(&mut self) {
+        self.integer_features.clear();
+        self.float_features.clear();
+        self.string_features.clear();
+    }
+
+    /// Returns the features of the given type.
+    fn features_mut(&mut self, kind: &ColumnKind) -> &mut Features {
+        match kind {
+            ColumnKind::String => &mut self.string_features,
+            ColumnKind::Int => &mut self.integer_features,
+            ColumnKind::Float => &mut self.float_features,
+        }
+    }
+
+    /// Moves the last `num_values` integer values, i.e. the values of the feature being parsed, to the float values.
+    fn move_ints_to_floats(&mut self, num_values: usize) {
+        if let (Values::Int(ints), Values::Float(floats)) = (
+            &mut self.integer_features.values,
+            &mut self.float_features.values,
+        ) {
+            let start = ints.len() - num_values;
+            floats.extend(ints.drain(start..).map(|v| v as f32));
+        }
+    }
+
+    /// Appends the values of a feature to the features of the same type.
+    fn push_column(&mut self, name: FeatureName, mut column: Values) {
+        let kind = ColumnKind::from(&column);
+        let num_rows = column.len();
+        self.features_mut(&kind).values.append(&mut column);
+        self.push_name(&kind, name, num_rows);
+    }
+
+    /// Adds the name of a feature whose values were appended to the features of the given type.
+    fn push_name(&mut self, kind: &ColumnKind, name: FeatureName, num_rows: usize) {
+        let features = self.features_mut(kind);
+        features.names.push(name);
+        features.shape.0 += 1;
+        features.shape.1 = num_rows;
+    }
+
+    /// Takes a cleared `ModelInput` from the object pool.
+    fn pooled() -> ModelInput {
+        // get the ModelInput from the pool and detach from it
+        // A background job ensures that the object pool is always filled
+        let pool = Arc::clone(&MODEL_INPUT_POOL);
+        let pool_object = pool.pull_owned(ModelInput::default);
+        let (_, mut model_input) = pool_object.detach();
+        model_input.clear();
+        model_input
+    }
+}
+
+/// Reusable buffers for the `ModelInput`s of a hot path, e.g. a request handler which parses one input per
+/// request, so that the feature vectors are not reallocated for every input.
+///
+/// The buffer holds a single `ModelInput`. Parsing or building an input clears it and writes the new
+/// features into the vectors of the previous one, which keep their capacity. The capacity kept across inputs
+/// is bounded by `FEATURE_NAMES_CAPACITY` names and `FEATURE_VALUES_CAPACITY` values per feature type, so a
+/// single huge input does not pin its memory. The strings of the feature names are reused as well, so inputs
+/// without string values are parsed without allocating once the buffer has warmed up.
+///
+/// Predictors consume their input, so an input moved out of the buffer with `take` can be given back with
+/// `recycle` once it is no longer needed. Otherwise the next input starts from the object pool.
+///
+/// # Example
+/// ```
+/// use jams_predictor_api::input::ModelInputBuffer;
+///
+/// let mut buffer = ModelInputBuffer::new();
+/// let input = buffer
+///     .float_feature("age", &[22.0, 38.0])
+///     .and_then(|buffer| buffer.string_feature("sex", &["male", "female"]))
+///     .map(|buffer| buffer.take())
+///     .unwrap();
+/// assert_eq!(input.num_rows(), 2);
+/// ```
+pub struct ModelInputBuffer {
+    input: ModelInput,
+    names: Vec<FeatureName>,
+}
+
+impl Default for ModelInputBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModelInputBuffer {
+    /// Creates an empty buffer, backed by a `ModelInput` of the object pool.
+    pub fn new() -> Self {
+        ModelInputBuffer {
+            input: ModelInput::pooled(),
+            names: Vec::with_capacity(FEATURE_NAMES_CAPACITY),
+        }
+    }
+
+    /// Returns the input which was parsed or built last.
+    pub fn input(&self) -> &ModelInput {
+        &self.input
+    }
+
+    /// Parses a JSON string into the buffer, with the same formats and limits as `ModelInput::from_str`.
+    ///
+    /// # Arguments
+    /// * `json` - The JSON string representing the model input.
+    ///
+    /// # Returns
+    /// * `Ok(&ModelInput)` - The parsed input, which can be moved out of the buffer with `take`.
+    /// * `Err(anyhow::Error)` - If there was an error during parsing. The buffer is left empty.
+    pub fn parse(&mut self, json: &str) -> anyhow::Result<&ModelInput> {
+        self.clear();
+        match parse_into(json, &mut self.input, &mut self.names) {
+            Ok(_) => Ok(&self.input),
+            Err(e) => {
+                self.clear();
+                tracing::error!("Failed to parse json to model input: {} ❌", e.to_string());
+                anyhow::bail!("Failed to parse json to model input: {} ❌", e.to_string())
+            }
+        }
+    }
+
+    /// Appends a float feature to the input being built.
+    ///
+    /// # Errors
+    /// Returns an error if the feature breaks the limits of a parsed input, or if its number of values differs
+    /// from the features appended before it.
+    pub fn float_feature(&mut self, name: &str, values: &[f32]) -> anyhow::Result<&mut Self> {
+        self.check_feature(name, values.len())?;
+        self.input
+            .float_features
+            .values
+            .extend(values.iter().copied());
+        self.input.push_name(
+            &ColumnKind::Float,
+            reuse_name(&mut self.names, name),
+            values.len(),
+        );
+        Ok(self)
+    }
+
+    /// Appends an integer feature to the input being built.
+    ///
+    /// # Errors
+    /// Returns an error if the feature breaks the limits of a parsed input, or if its number of values differs
+    /// from the features appended before it.
+    pub fn int_feature(&mut self, name: &str, values: &[i32]) -> anyhow::Result<&mut Self> {
+        self.check_feature(name, values.len())?;
+        self.input
+            .integer_features
+            .values
+            .extend(values.iter().copied());
+        self.input.push_name(
+            &ColumnKind::Int,
+            reuse_name(&mut self.names, name),
+            values.len(),
+        );
+        Ok(self)
+    }
+
+    /// Appends a string feature to the input being built.
+    ///
+    /// # Errors
+    /// Returns an error if the feature breaks the limits of a parsed input, or if its number of values differs
+    /// from the features appended before it.
+    pub fn string_feature<S: AsRef<str>>(
+        &mut self,
+        name: &str,
+        values: &[S],
+    ) -> anyhow::Result<&mut Self> {
+        self.check_feature(name, values.len())?;
+        if values.iter().any(|v| v.as_ref().len() > MAX_STRING_LENGTH) {
+            anyhow::bail!(
+                "String value of feature {} exceeds the maximum length of {} bytes ❌",
+                name,
+                MAX_STRING_LENGTH
+            )
+        }
+        self.input
+            .string_features
+            .values
+            .extend(values.iter().map(|v| v.as_ref().to_string()));
+        self.input.push_name(
+            &ColumnKind::String,
+            reuse_name(&mut self.names, name),
+            values.len(),
+        );
+        Ok(self)
+    }
+
+    /// Moves the input out of the buffer. The next input starts from `recycle`d or pooled vectors.
+    pub fn take(&mut self) -> ModelInput {
+        std::mem::replace(&mut self.input, ModelInput::pooled())
+    }
+
+    /// Gives an input back to the buffer, so that the next input reuses its vectors.
+    pub fn recycle(&mut self, input: ModelInput) {
+        self.input = input;
+        self.clear();
+    }
+
+    /// Clears the input, keeping the capacity of its vectors up to the capacity constants.
+    pub fn clear(&mut self) {
+        for features in [
+            &mut self.input.float_features,
+            &mut self.input.integer_features,
+            &mut self.input.string_features,
+        ] {
+            // keep the strings of the names for the next input
+            let spare = FEATURE_NAMES_CAPACITY.saturating_sub(self.names.len());
+            self.names.extend(features.names.drain(..).take(spare));
+            features.clear();
+            features.names.shrink_to(FEATURE_NAMES_CAPACITY);
+            features.values.shrink_to(FEATURE_VALUES_CAPACITY);
+        }
+    }
+
+    fn check_feature(&self, name: &str, num_values: usize) -> anyhow::Result<()> {
+        if name.len() > MAX_STRING_LENGTH {
+            anyhow::bail!(
+                "Feature name exceeds the maximum length of {} bytes ❌",
+                MAX_STRING_LENGTH
+            )
+        }
+        let num_features = self.input.float_features.shape.0
+            + self.input.integer_features.shape.0
+            + self.input.string_features.shape.0;
+        if num_features >= MAX_FEATURES {
+            anyhow::bail!(
+                "Too many features, at most {} features are allowed ❌",
+                MAX_FEATURES
+            )
+        }
+        if num_values == 0 {
+            anyhow::bail!("Feature {} has no values ❌", name)
+        }
+        if num_values > MAX_ROWS {
+            anyhow::bail!(
+                "Too many values, at most {} values are allowed per feature ❌",
+                MAX_ROWS
+            )
+        }
+        match self.input.num_rows() {
+            0 => Ok(()),
+            rows if rows == num_values => Ok(()),
+            rows => anyhow::bail!(
+                "Expected {} values for feature {} but found {} ❌",
+                rows,
+                name,
+                num_values
+            ),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ModelInput {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut model_input = ModelInput::pooled();
+        ModelInputSeed {
+            input: &mut model_input,
+            names: &mut Vec::new(),
+        }
+        .deserialize(deserializer)?;
+        Ok(model_input)
+    }
+}
+
+/// Parses a JSON string into a cleared `ModelInput`, reusing the given spare feature names.
+fn parse_into(
+    json: &str,
+    model_input: &mut ModelInput,
+    names: &mut Vec<FeatureName>,
+) -> Result<(), serde_json::Error> {
+    #[cfg(feature = "fast-numbers")]
+    {
+        if fast::parse_columns(json, model_input, names) {
+            return Ok(());
+        }
+        model_input.clear();
+    }
+
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    ModelInputSeed {
+        input: model_input,
+        names,
+    }
+    .deserialize(&mut deserializer)?;
+    deserializer.end()
+}
+
+/// Parses a model input into a cleared `ModelInput`, reusing the allocations of its features and the
+/// `names` of the features of previous inputs.
+struct ModelInputSeed<'a> {
+    input: &'a mut ModelInput,
+    names: &'a mut Vec<FeatureName>,
+}
+
+impl<'de> DeserializeSeed<'de> for ModelInputSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ModelInputSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a map with string keys and homogeneous arrays of integers, floats, or strings as values, or an array of such records")
+    }
+
+    fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        let model_input = self.input;
+
+        let mut num_features: usize = 0;
+        let mut num_rows: Option<usize> = None;
+
+        while let Some(key) = map.next_key_seed(NameSeed(self.names))? {
+            if key.len() > MAX_STRING_LENGTH {
+                return Err(de::Error::custom(format!(
+                    "Feature name exceeds the maximum length of {} bytes",
+                    MAX_STRING_LENGTH
+                )));
+            }
+
+            num_features += 1;
+            if num_features > MAX_FEATURES {
+                return Err(de::Error::custom(format!(
+                    "Too many features, at most {} features are allowed",
+                    MAX_FEATURES
+                )));
+            }
+
+            let (kind, arr_length) = map.next_value_seed(ColumnSeed(model_input)).map_err(|e| {
+                de::Error::custom(format!("Invalid value for key '{}': {}", key, e))
+            })?;
+
+            // every feature must have the same number of rows, otherwise the shape is invalid
+            match num_rows {
+                Some(rows) if rows != arr_length => {
+                    return Err(de::Error::custom(format!(
+                        "Expected {} values for key '{}' but found {}",
+                        rows, key, arr_length
+                    )));
+                }
+                _ => num_rows = Some(arr_length),
+            }
+
+            model_input.push_name(&kind, key, arr_length);
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut rows = RowColumns::default();
+        while seq.next_element_seed(&mut rows)?.is_some() {}
+        if rows.num_rows == 0 {
+            return Err(de::Error::custom("Empty array found"));
+        }
+
+        for (name, column) in rows.names.into_iter().zip(rows.columns) {
+            self.input.push_column(name, column);
+        }
+        Ok(())
+    }
+}
+
+/// Parses the name of a feature into one of the names of previous inputs, if any is left, instead of
+/// allocating a new string.
+struct NameSeed<'a>(&'a mut Vec<FeatureName>);
+
+impl<'de> DeserializeSeed<'de> for NameSeed<'_> {
+    type Value = FeatureName;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for NameSeed<'_> {
+    type Value = FeatureName;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(reuse_name(self.0, v))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(v)
+    }
+}
+
+/// Copies a feature name into one of the given spare names, or into a new string if none is left.
+fn reuse_name(names: &mut Vec<FeatureName>, name: &str) -> FeatureName {
+    match names.pop() {
+        Some(mut spare) => {
+            spare.clear();
+            spare.push_str(name);
+            spare
+        }
+        None => name.to_string(),
+    }
+}
+
+/// The columns of a row-oriented input, built one record at a time.
+///
+/// The first record sets the features and their types. Every other record must have exactly the
+/// same features, in any order.
+#[derive(Default)]
+struct RowColumns {
+    names: Vec<FeatureName>,
+    columns: Vec<Values>,
+    index: HashMap<FeatureName, usize>,
+    num_rows: usize,
+}
+
+impl<'de> DeserializeSeed<'de> for &mut RowColumns {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for &mut RowColumns {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a map with string keys and integers, floats, or strings as values")
+    }
+
+    fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        let row = self.num_rows;
+        if row >= MAX_ROWS {
+            return Err(de::Error::custom(format!(
+                "Too many rows, at most {} rows are allowed",
+                MAX_ROWS
+            )));
+        }
+
+        let mut num_values: usize = 0;
+        while let Some(key) = map.next_key::<String>()? {
+            if key.len() > MAX_STRING_LENGTH {
+                return Err(de::Error::custom(format!(
+                    "Feature name exceeds the maximum length of {} bytes",
+                    MAX_STRING_LENGTH
+                )));
+            }
+
+            let value = map.next_value::<Scalar>().map_err(|e| {
+                de::Error::custom(format!(
+                    "Invalid value for key '{}' in row {}: {}",
+                    key, row, e
+                ))
+            })?;
+
+            match self.index.get(&key) {
+                Some(&i) => {
+                    let column = &mut self.columns[i];
+                    if column.len() > row {
+                        return Err(de::Error::custom(format!(
+                            "Duplicate key '{}' in row {}",
+                            key, row
+                        )));
+                    }
+                    column.push_scalar::<M::Error>(value).map_err(|e| {
+                        de::Error::custom(format!(
+                            "Invalid value for key '{}' in row {}: {}",
+                            key, row, e
+                        ))
+                    })?;
+                }
+                None if row == 0 => {
+                    if self.names.len() >= MAX_FEATURES {
+                        return Err(de::Error::custom(format!(
+                            "Too many features, at most {} features are allowed",
+                            MAX_FEATURES
+                        )));
+                    }
+                    self.index.insert(key.clone(), self.names.len());
+                    self.columns.push(Values::from_scalar(value)?);
+                    self.names.push(key);
+                }
+                None => {
+                    return Err(de::Error::custom(format!(
+                        "Unexpected key '{}' in row {}, every row must have the same keys",
+                        key, row
+                    )));
+                }
+            }
+            num_values += 1;
+        }
+
+        if self.names.is_empty() {
+            return Err(de::Error::custom("Empty row found"));
+        }
+        if num_values < self.names.len() {
+            let missing = self
+                .names
+                .iter()
+                .zip(self.columns.iter())
+                .find(|(_, column)| column.len() == row)
+                .map(|(name, _)| name.as_str())
+                .unwrap_or_default();
+            return Err(de::Error::custom(format!(
+                "Missing key '{}' in row {}, every row must have the same keys",
+                missing, row
+            )));
+        }
+
+        self.num_rows += 1;
+        Ok(())
+    }
+}
+
+impl Values {
+    fn len(&self) -> usize {
+        match self {
+            Values::String(v) => v.len(),
+            Values::Int(v) => v.len(),
+            Values::Float(v) => v.len(),
+        }
+    }
+
+    /// Creates the values of a feature whose type is set by its first value.
+    fn from_scalar<E: de::Error>(value: Scalar) -> Result<Values, E> {
+        match value {
+            Scalar::String(v) => Ok(Values::String(vec![v])),
+            Scalar::Int(v) => Ok(Values::Int(vec![to_i32(v)?])),
+            Scalar::Float(v) => Ok(Values::Float(vec![to_f32(v)?])),
+        }
+    }
+
+    /// Appends a value of the same type as the values. Integers are converted when appended to floats, and
+    /// integers are converted to floats when a float is appended to them.
+    fn push_scalar<E: de::Error>(&mut self, value: Scalar) -> Result<(), E> {
+        if let (Values::Int(ints), Scalar::Float(_)) = (&*self, &value) {
+            *self = Values::Float(ints.iter().map(|v| *v as f32).collect());
+        }
+        match (self, value) {
+            (Values::String(vec), Scalar::String(v)) => vec.push(v),
+            (Values::Int(vec), Scalar::Int(v)) => vec.push(to_i32(v)?),
+            (Values::Float(vec), Scalar::Float(v)) => vec.push(to_f32(v)?),
+            (Values::Float(vec), Scalar::Int(v)) => vec.push(v as f32),
+            _ => return Err(E::custom(
+                "Mixed value types found in array, all values of a feature must have the same type",
+            )),
+        }
+        Ok(())
+    }
+}
+
+/// Appends the values of a single feature, parsed from a homogeneous JSON array, to the features of
+/// their type, without buffering them in a separate vector first.
+///
+/// The array is read one element at a time so that the limits are enforced before the values
+/// are buffered. Arrays of floats may contain integers, which are converted to floats. The type of
+/// the values and their number are returned, and the caller adds the name of the feature.
+struct ColumnSeed<'a>(&'a mut ModelInput);
+
+impl<'de> DeserializeSeed<'de> for ColumnSeed<'_> {
+    type Value = (ColumnKind, usize);
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ColumnSeed<'_> {
+    type Value = (ColumnKind, usize);
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a non-empty homogeneous array of integers, floats, or strings")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let first = match seq.next_element::<Scalar>()? {
+            Some(value) => value,
+            None => return Err(de::Error::custom("Empty array found")),
+        };
+        let mut kind = ColumnKind::from(&first);
+        self.0.features_mut(&kind).values.push_scalar(first)?;
+
+        let mut num_values: usize = 1;
+        while let Some(value) = seq.next_element::<Scalar>()? {
+            if num_values >= MAX_ROWS {
+                return Err(de::Error::custom(format!(
+                    "Too many values, at most {} values are allowed per feature",
+                    MAX_ROWS
+                )));
+            }
+
+            // an array of integers with a float in it, i.e. `[1, 1.5]`, is an array of floats
+            if let (ColumnKind::Int, Scalar::Float(_)) = (&kind, &value) {
+                self.0.move_ints_to_floats(num_values);
+                kind = ColumnKind::Float;
+            }
+            self.0.features_mut(&kind).values.push_scalar(value)?;
+            num_values += 1;
+        }
+
+        Ok((kind, num_values))
+    }
+}
+
+/// A single value inside a feature array.
+///
+/// Only scalars are accepted, which means nested arrays and maps are rejected as soon as they
+/// are encountered rather than being parsed.
+enum Scalar {
+    String(String),
+    Int(i64),
+    Float(f64),
+}
+
+impl<'de> Deserialize<'de> for Scalar {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ScalarVisitor;
+
+        impl<'de> Visitor<'de> for ScalarVisitor {
+            type Value = Scalar;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("an integer, float, or string")
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(Scalar::Int(v))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match i64::try_from(v) {
+                    Ok(v) => Ok(Scalar::Int(v)),
+                    Err(_) => Err(E::custom(format!("Integer value {} is out of range", v))),
+                }
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(Scalar::Float(v))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                if v.len() > MAX_STRING_LENGTH {
+                    return Err(E::custom(format!(
+                        "String value exceeds the maximum length of {} bytes",
+                        MAX_STRING_LENGTH
+                    )));
+                }
+                Ok(Scalar::String(v.to_owned()))
+            }
+        }
+
+        deserializer.deserialize_any(ScalarVisitor)
+    }
+}
+
+/// Computes a hash of the schema of a model input, i.e. the names of the features and the type of their values.
+///
+/// Unlike `ModelInput::from_str`, only the first value of each feature is inspected and the other values are
+/// skipped without being buffered, so this is much cheaper than parsing the input. Two inputs with the same
+/// features of the same types have the same schema hash, regardless of their values, number of rows and
+/// whether they are columnar or row-oriented. The schema of a row-oriented input is read from its first record.
+///
+/// # Arguments
+/// * `json` - The JSON string representing the model input.
+///
+/// # Returns
+/// * `Ok(u64)` - The hash of the schema.
+/// * `Err(anyhow::Error)` - If the input is not a map of feature names to arrays or an array of records.
+pub fn schema_hash(json: &str) -> anyhow::Result<u64> {
+    let schema: Schema = match serde_json::from_str(json) {
+        Ok(schema) => schema,
+        Err(e) => anyhow::bail!("Failed to read schema of model input: {} ❌", e),
+    };
+
+    let mut hasher = DefaultHasher::new();
+    schema.0.hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+/// The names of the features of a model input and the type of their values.
+struct Schema(BTreeMap<String, ColumnKind>);
+
+impl<'de> Deserialize<'de> for Schema {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SchemaVisitor;
+
+        impl<'de> Visitor<'de> for SchemaVisitor {
+            type Value = Schema;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a map of feature names to arrays, or an array of records")
+            }
+
+            fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+            where
+                M: MapAccess<'de>,
+            {
+                let mut schema = BTreeMap::new();
+                while let Some((name, kind)) = map.next_entry::<String, ColumnKind>()? {
+                    schema.insert(name, kind);
+                }
+                Ok(Schema(schema))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let record = match seq.next_element::<BTreeMap<String, Scalar>>()? {
+                    Some(record) => record,
+                    None => return Err(de::Error::custom("Empty array found")),
+                };
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(Schema(
+                    record
+                        .into_iter()
+                        .map(|(name, value)| (name, ColumnKind::from(&value)))
+                        .collect(),
+                ))
+            }
+        }
+
+        deserializer.deserialize_any(SchemaVisitor)
+    }
+}
+
+/// The type of the values of a feature, read from the first value of its array.
+#[derive(Hash)]
+enum ColumnKind {
+    String,
+    Int,
+    Float,
+}
+
+impl From<&Scalar> for ColumnKind {
+    fn from(value: &Scalar) -> Self {
+        match value {
+            Scalar::String(_) => ColumnKind::String,
+            Scalar::Int(_) => ColumnKind::Int,
+            Scalar::Float(_) => ColumnKind::Float,
+        }
+    }
+}
+
+impl From<&Values> for ColumnKind {
+    fn from(values: &Values) -> Self {
+        match values {
+            Values::String(_) => ColumnKind::String,
+            Values::Int(_) => ColumnKind::Int,
+            Values::Float(_) => ColumnKind::Float,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ColumnKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ColumnKindVisitor;
+
+        impl<'de> Visitor<'de> for ColumnKindVisitor {
+            type Value = ColumnKind;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a non-empty array of integers, floats, or strings")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let kind = match seq.next_element::<Scalar>()? {
+                    Some(value) => ColumnKind::from(&value),
+                    None => return Err(de::Error::custom("Empty array found")),
+                };
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(kind)
+            }
+        }
+
+        deserializer.deserialize_any(ColumnKindVisitor)
+    }
+}
+
+/// Converts an integer to `i32`, returning an error instead of silently truncating it.
+fn to_i32<E: de::Error>(v: i64) -> Result<i32, E> {
+    i32::try_from(v).map_err(|_| E::custom(format!("Integer value {} is out of range", v)))
+}
+
+/// Converts a float to `f32`, returning an error if the value does not fit.
+fn to_f32<E: de::Error>(v: f64) -> Result<f32, E> {
+    let converted = v as f32;
+    if converted.is_finite() {
+        Ok(converted)
+    } else {
+        Err(E::custom(format!("Float value {} is out of range", v)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    #[test]
+    fn successfully_parses_model_input_from_str() {
+        let json_data = r#"{
+        "feature_1": [42, 42],
+        "feature_2": [3.14, 3.14],
+        "feature_3": ["a", "a"]
+    }"#;
+
+        let model_input = ModelInput::from_str(json_data);
+
+        // assert result is ok
+        assert!(model_input.is_ok())
+    }
+
+    #[test]
+    fn successfully_parses_model_input_v2_from_str() {
+        let json_data = r#"{
+        "feature_1": [42, 42],
+        "feature_2": [3.14, 3.14],
+        "feature_3": ["a", "a"]
+    }"#;
+
+        let model_input = ModelInput::from_str(json_data);
+
+        // assert result is ok
+        assert!(model_input.is_ok())
+    }
+
+    #[test]
+    fn fails_to_parses_model_input_serde_json_value_due_to_empty_input() {
+        let json_data = r#"{
+        "feature_1": [],
+        "feature_2": [],
+        "feature_3": []
+    }"#;
+
+        let model_input = ModelInput::from_str(json_data);
+
+        // assert result is err
+        assert!(model_input.is_err())
+    }
+
+    #[test]
+    fn fails_to_parses_model_input_serde_json_value_due_to_value_being_not_an_array() {
+        let json_data = r#"{
+        "feature_1": {
+                "a": ""
+            },
+        "feature_2": [],
+        "feature_3": []
+    }"#;
+
+        let model_input = ModelInput::from_str(json_data);
+
+        // assert result is err
+        assert!(model_input.is_err())
+    }
+
+    #[test]
+    fn fails_to_parse_model_input_when_array_has_mixed_types() {
+        let inputs = [
+            r#"{"feature_1": ["a", 1]}"#,
+            r#"{"feature_1": [1, "a"]}"#,
+            r#"{"feature_1": [1.5, "a"]}"#,
+            r#"{"feature_1": [1, null]}"#,
+        ];
+
+        for json_data in inputs {
+            let model_input = ModelInput::from_str(json_data);
+
+            // assert result is err
+            assert!(model_input.is_err(), "{} should fail to parse", json_data)
+        }
+    }
+
+    #[test]
+    fn successfully_parses_floats_in_integer_array() {
+        let json_data =
+            r#"{"feature_1": [7, 8, 9], "feature_2": [1, 1.5, 2], "feature_3": [0.5, 1, 2]}"#;
+
+        let model_input = ModelInput::from_str(json_data).unwrap();
+
+        // assert
+        assert_eq!(model_input.integer_features.names, vec!["feature_1"]);
+        assert_eq!(
+            model_input.integer_features.values.as_ints().unwrap(),
+            &vec![7, 8, 9]
+        );
+        assert_eq!(
+            model_input.float_features.names,
+            vec!["feature_2", "feature_3"]
+        );
+        assert_eq!(
+            model_input.float_features.values.as_floats().unwrap(),
+            &vec![1.0, 1.5, 2.0, 0.5, 1.0, 2.0]
+        );
+        assert_eq!(model_input.float_features.shape, (2, 3))
+    }
+
+    #[test]
+    fn successfully_parses_integers_in_float_array() {
+        let json_data = r#"{"feature_1": [1.5, 2, 3]}"#;
+
+        let model_input = ModelInput::from_str(json_data).unwrap();
+
+        // assert
+        assert_eq!(
+            model_input.float_features.values.as_floats().unwrap(),
+            &vec![1.5, 2.0, 3.0]
+        );
+        assert_eq!(model_input.float_features.shape, (1, 3))
+    }
+
+    #[test]
+    fn successfully_parses_floats_in_integer_feature_of_row_oriented_model_input() {
+        let json_data = r#"[{"feature_1": 1, "feature_2": 7}, {"feature_1": 1.5, "feature_2": 8}]"#;
+
+        let model_input = ModelInput::from_str(json_data).unwrap();
+
+        // assert
+        assert_eq!(model_input.integer_features.names, vec!["feature_2"]);
+        assert_eq!(model_input.float_features.names, vec!["feature_1"]);
+        assert_eq!(
+            model_input.float_features.values.as_floats().unwrap(),
+            &vec![1.0, 1.5]
+        );
+        assert_eq!(model_input.num_rows(), 2)
+    }
+
+    #[test]
+    fn successfully_parses_row_oriented_model_input_from_str() {
+        let json_data = r#"[
+        {"feature_1": 42, "feature_2": 3.5, "feature_3": "a"},
+        {"feature_3": "b", "feature_1": 7, "feature_2": 2}
+    ]"#;
+
+        let model_input = ModelInput::from_str(json_data).unwrap();
+
+        // assert
+        assert_eq!(model_input.integer_features.names, vec!["feature_1"]);
+        assert_eq!(
+            model_input.integer_features.values.as_ints().unwrap(),
+            &vec![42, 7]
+        );
+        assert_eq!(
+            model_input.float_features.values.as_floats().unwrap(),
+            &vec![3.5, 2.0]
+        );
+        assert_eq!(
+            model_input.string_features.values.as_strings().unwrap(),
+            &vec!["a".to_string(), "b".to_string()]
+        );
+        assert_eq!(model_input.string_features.shape, (1, 2));
+        assert_eq!(model_input.num_rows(), 2)
+    }
+
+    #[test]
+    fn fails_to_parse_row_oriented_model_input_when_rows_are_inconsistent() {
+        let inputs = [
+            r#"[]"#,
+            r#"[{}]"#,
+            r#"[{"feature_1": 1}, {}]"#,
+            r#"[{"feature_1": 1, "feature_2": 2}, {"feature_1": 1}]"#,
+            r#"[{"feature_1": 1}, {"feature_1": 1, "feature_2": 2}]"#,
+            r#"[{"feature_1": 1}, {"feature_1": 1, "feature_1": 2}]"#,
+            r#"[{"feature_1": 1}, {"feature_1": "a"}]"#,
+            r#"[{"feature_1": [1]}]"#,
+            r#"[1, 2]"#,
+        ];
+
+        for json_data in inputs {
+            let model_input = ModelInput::from_str(json_data);
+
+            // assert result is err
+            assert!(model_input.is_err(), "{} should fail to parse", json_data)
+        }
+    }
+
+    #[test]
+    fn fails_to_parse_model_input_when_values_are_nested() {
+        let deeply_nested = format!(
+            r#"{{"feature_1": {}1{}}}"#,
+            "[".repeat(10_000),
+            "]".repeat(10_000)
+        );
+        let inputs = [
+            r#"{"feature_1": [[1, 2]]}"#.to_string(),
+            r#"{"feature_1": [{"a": 1}]}"#.to_string(),
+            deeply_nested,
+        ];
+
+        for json_data in inputs {
+            let model_input = ModelInput::from_str(json_data.as_str());
+
+            // assert result is err
+            assert!(model_input.is_err())
+        }
+    }
+
+    #[test]
+    fn fails_to_parse_model_input_when_number_is_out_of_range() {
+        let inputs = [
+            r#"{"feature_1": [2147483648]}"#,
+            r#"{"feature_1": [18446744073709551615]}"#,
+            r#"{"feature_1": [1e300]}"#,
+        ];
+
+        for json_data in inputs {
+            let model_input = ModelInput::from_str(json_data);
+
+            // assert result is err
+            assert!(model_input.is_err(), "{} should fail to parse", json_data)
+        }
+    }
+
+    #[test]
+    fn fails_to_parse_model_input_when_features_have_different_number_of_values() {
+        let json_data = r#"{
+        "feature_1": [42, 42],
+        "feature_2": [3.14],
+        "feature_3": ["a", "a"]
+    }"#;
+
+        let model_input = ModelInput::from_str(json_data);
+
+        // assert result is err
+        assert!(model_input.is_err())
+    }
+
+    #[test]
+    fn fails_to_parse_model_input_when_limits_are_exceeded() {
+        let too_many_rows = format!(
+            r#"{{"feature_1": [{}]}}"#,
+            vec!["1"; MAX_ROWS + 1].join(",")
+        );
+        let too_many_features = format!(
+            "{{{}}}",
+            (0..=MAX_FEATURES)
+                .map(|i| format!(r#""feature_{}": [1]"#, i))
+                .collect::<Vec<String>>()
+                .join(",")
+        );
+        let long_string = format!(
+            r#"{{"feature_1": ["{}"]}}"#,
+            "a".repeat(MAX_STRING_LENGTH + 1)
+        );
+        let long_name = format!(r#"{{"{}": [1]}}"#, "a".repeat(MAX_STRING_LENGTH + 1));
+
+        for json_data in [too_many_rows, too_many_features, long_string, long_name] {
+            let model_input = ModelInput::from_str(json_data.as_str());
+
+            // assert result is err
+            assert!(model_input.is_err())
+        }
+    }
+
+    #[test]
+    fn successfully_computes_same_schema_hash_for_inputs_with_same_shape() {
+        let input = r#"{"feature_1": [42, 42], "feature_2": [3.14], "feature_3": ["a"]}"#;
+        let same_shape = r#"{"feature_3": ["b", "c"], "feature_1": [7], "feature_2": [1.5, 2.5]}"#;
+        let different_type = r#"{"feature_1": ["42"], "feature_2": [3.14], "feature_3": ["a"]}"#;
+        let different_name = r#"{"feature_4": [42], "feature_2": [3.14], "feature_3": ["a"]}"#;
+
+        let hash = schema_hash(input).unwrap();
+
+        // assert
+        assert_eq!(hash, schema_hash(same_shape).unwrap());
+        assert_ne!(hash, schema_hash(different_type).unwrap());
+        assert_ne!(hash, schema_hash(different_name).unwrap());
+    }
+
+    #[test]
+    fn successfully_computes_same_schema_hash_for_columnar_and_row_oriented_inputs() {
+        let columnar =
+            r#"{"feature_1": [42, 7], "feature_2": [3.14, 1.5], "feature_3": ["a", "b"]}"#;
+        let rows = r#"[{"feature_3": "a", "feature_1": 42, "feature_2": 3.14}, {"feature_3": "b", "feature_1": 7, "feature_2": 1.5}]"#;
+
+        // assert
+        assert_eq!(schema_hash(columnar).unwrap(), schema_hash(rows).unwrap());
+    }
+
+    #[test]
+    fn fails_to_compute_schema_hash_when_input_is_not_a_map_of_arrays() {
+        for json_data in [r#"[1, 2]"#, r#"{"feature_1": 1}"#, r#"{"feature_1": []}"#] {
+            // assert result is err
+            assert!(schema_hash(json_data).is_err())
+        }
+    }
+
+    #[test]
+    fn successfully_reuses_buffers_across_parsed_inputs() {
+        // Arrange
+        let mut buffer = ModelInputBuffer::new();
+        buffer
+            .parse(r#"{"age": [22.0, 38.0, 26.0], "pclass": [3, 1, 3]}"#)
+            .unwrap();
+        let floats = buffer.input().float_features.values.as_floats().unwrap();
+        let (pointer, capacity) = (floats.as_ptr(), floats.capacity());
+
+        // Act
+        let input = buffer.parse(r#"{"age": [35.0], "pclass": [1]}"#).unwrap();
+
+        // Assert
+        let floats = input.float_features.values.as_floats().unwrap();
+        assert_eq!(floats, &vec![35.0]);
+        assert_eq!(floats.as_ptr(), pointer);
+        assert_eq!(floats.capacity(), capacity);
+        assert_eq!(input.integer_features.values.as_ints().unwrap(), &vec![1]);
+        assert_eq!(input.num_rows(), 1);
+    }
+
+    #[test]
+    fn successfully_builds_and_recycles_model_input_with_buffer() {
+        // Arrange
+        let mut buffer = ModelInputBuffer::new();
+
+        // Act
+        let input = buffer
+            .float_feature("age", &[22.0, 38.0])
+            .and_then(|buffer| buffer.int_feature("pclass", &[3, 1]))
+            .and_then(|buffer| buffer.string_feature("sex", &["male", "female"]))
+            .map(|buffer| buffer.take())
+            .unwrap();
+        let num_rows = input.num_rows();
+        let strings = input.string_features.values.as_strings().cloned();
+        let pointer = input.float_features.values.as_floats().unwrap().as_ptr();
+        buffer.recycle(input);
+        let rebuilt = buffer.float_feature("age", &[30.0, 40.0]).unwrap().input();
+
+        // Assert
+        assert_eq!(num_rows, 2);
+        assert_eq!(
+            strings,
+            Some(vec!["male".to_string(), "female".to_string()])
+        );
+        assert_eq!(
+            rebuilt.float_features.values.as_floats().unwrap().as_ptr(),
+            pointer
+        );
+        assert_eq!(rebuilt.float_features.shape, (1, 2));
+        assert!(rebuilt.integer_features.names.is_empty());
+    }
+
+    #[test]
+    fn fails_to_build_model_input_with_buffer_when_features_have_different_number_of_values() {
+        // Arrange
+        let mut buffer = ModelInputBuffer::new();
+        buffer.float_feature("age", &[22.0, 38.0]).unwrap();
+
+        // Act
+        let too_few = buffer.int_feature("pclass", &[3]).is_err();
+        let empty = buffer.string_feature::<&str>("sex", &[]).is_err();
+        let invalid = buffer
+            .parse(r#"{"age": [22.0], "pclass": [3, 1]}"#)
+            .is_err();
+
+        // Assert
+        assert!(too_few);
+        assert!(empty);
+        assert!(invalid);
+        assert_eq!(buffer.input().num_rows(), 0);
+    }
+
+    #[test]
+    fn successfully_splits_model_input_into_batches_of_rows() {
+        // Arrange
+        let input =
+            ModelInput::from_str(r#"{"age": [1.0, 2.0, 3.0, 4.0, 5.0], "sex": ["a", "b", "c", "d", "e"], "pclass": [1, 2, 3, 4, 5]}"#)
+                .unwrap();
+
+        // Act
+        let batches = input.split_rows(2);
+
+        // Assert
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(batches[2].num_rows(), 1);
+        assert_eq!(
+            batches[1].float_features.values.as_floats().unwrap(),
+            &vec![3.0, 4.0]
+        );
+        assert_eq!(
+            batches[1].string_features.values.as_strings().unwrap(),
+            &vec!["c".to_string(), "d".to_string()]
+        );
+        assert_eq!(
+            batches[2].integer_features.values.as_ints().unwrap(),
+            &vec![5]
+        );
+        assert_eq!(batches[2].integer_features.shape, (1, 1));
+    }
+
+    proptest! {
+        #[test]
+        fn successfully_parses_model_input_with_consistent_shape(
+            (num_rows, integers, floats, strings) in (1..32usize).prop_flat_map(|num_rows| (
+                Just(num_rows),
+                vec(vec(any::<i32>(), num_rows), 0..4),
+                vec(vec(-1e6f32..1e6f32, num_rows), 0..4),
+                vec(vec("[a-z]{0,8}", num_rows), 0..4),
+            ))
+        ) {
+            let mut payload = serde_json::Map::new();
+            for (i, values) in integers.iter().enumerate() {
+                payload.insert(format!("int_{}", i), serde_json::json!(values));
+            }
+            for (i, values) in floats.iter().enumerate() {
+                let values: Vec<f64> = values.iter().map(|v| *v as f64).collect();
+                payload.insert(format!("float_{}", i), serde_json::json!(values));
+            }
+            for (i, values) in strings.iter().enumerate() {
+                payload.insert(format!("string_{}", i), serde_json::json!(values));
+            }
+            let json_data = serde_json::Value::Object(payload).to_string();
+
+            let model_input = ModelInput::from_str(json_data.as_str()).unwrap();
+
+            // assert every feature type describes the values which were parsed
+            let float_features = &model_input.float_features;
+            let integer_features = &model_input.integer_features;
+            let string_features = &model_input.string_features;
+            prop_assert_eq!(integer_features.shape.0, integers.len());
+            prop_assert_eq!(float_features.shape.0, floats.len());
+            prop_assert_eq!(string_features.shape.0, strings.len());
+            prop_assert_eq!(
+                integer_features.values.as_ints().unwrap().len(),
+                integer_features.shape.0 * integer_features.shape.1
+            );
+            prop_assert_eq!(
+                float_features.values.as_floats().unwrap().len(),
+                float_features.shape.0 * float_features.shape.1
+            );
+            prop_assert_eq!(
+                string_features.values.as_strings().unwrap().len(),
+                string_features.shape.0 * string_features.shape.1
+            );
+            if !integers.is_empty() || !floats.is_empty() || !strings.is_empty() {
+                prop_assert_eq!(model_input.num_rows(), num_rows);
+            }
+        }
+    }
+}
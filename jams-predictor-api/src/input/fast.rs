@@ -0,0 +1,287 @@
+use super::{
+    reuse_name, ColumnKind, FeatureName, ModelInput, Values, MAX_FEATURES, MAX_ROWS,
+    MAX_STRING_LENGTH,
+};
+use lexical_core::{format::JSON, ParseFloatOptions, ParseIntegerOptions};
+
+const INTEGER_OPTIONS: ParseIntegerOptions = ParseIntegerOptions::new();
+
+const FLOAT_OPTIONS: ParseFloatOptions = ParseFloatOptions::new();
+
+/// Parses a columnar model input, i.e. `{"f1": [1, 2], "f2": [0.5, 1.5]}`, with a scanner specialised for flat
+/// arrays of numbers, which parses the numbers with `lexical-core` instead of `serde_json`.
+///
+/// Only the inputs which the scanner can parse exactly like `ModelInput::from_str` are supported: row-oriented
+/// inputs, strings with escape sequences, invalid inputs and inputs over the limits are not. Numbers whose type
+/// `serde_json` would change, e.g. `-0` or integers out of the range of `i64`, are not either.
+///
+/// # Arguments
+/// * `json` - The JSON string representing the model input.
+/// * `input` - A cleared `ModelInput` into which the features are parsed.
+/// * `names` - Spare feature names of previous inputs, reused for the names of the features.
+///
+/// # Returns
+/// `true` if the input was parsed. Otherwise `input` may hold some of the features and must be cleared before
+/// the input is parsed by `serde_json`, which reports why it is invalid, if it is.
+pub(super) fn parse_columns(
+    json: &str,
+    input: &mut ModelInput,
+    names: &mut Vec<FeatureName>,
+) -> bool {
+    Scanner { json, pos: 0 }.columns(input, names).is_some()
+}
+
+/// A value of a feature array.
+enum Scalar<'a> {
+    String(&'a str),
+    Int(i64),
+    Float(f64),
+}
+
+struct Scanner<'a> {
+    json: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn columns(&mut self, input: &mut ModelInput, names: &mut Vec<FeatureName>) -> Option<()> {
+        self.expect(b'{')?;
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+        } else {
+            let mut num_features: usize = 0;
+            let mut num_rows: Option<usize> = None;
+            loop {
+                let name = self.string()?;
+                num_features += 1;
+                if num_features > MAX_FEATURES {
+                    return None;
+                }
+                self.expect(b':')?;
+
+                // every feature must have the same number of rows, otherwise the shape is invalid
+                let (kind, rows) = self.column(input)?;
+                match num_rows {
+                    Some(num_rows) if num_rows != rows => return None,
+                    _ => num_rows = Some(rows),
+                }
+                input.push_name(&kind, reuse_name(names, name), rows);
+
+                match self.next()? {
+                    b',' => continue,
+                    b'}' => break,
+                    _ => return None,
+                }
+            }
+        }
+        self.skip_whitespace();
+        (self.pos == self.json.len()).then_some(())
+    }
+
+    /// Appends the values of an array to the features of their type, which is set by the first value.
+    fn column(&mut self, input: &mut ModelInput) -> Option<(ColumnKind, usize)> {
+        self.expect(b'[')?;
+        let first = self.scalar()?;
+        let kind = match first {
+            Scalar::String(_) => ColumnKind::String,
+            Scalar::Int(_) => ColumnKind::Int,
+            Scalar::Float(_) => ColumnKind::Float,
+        };
+        let values = &mut input.features_mut(&kind).values;
+        push(values, first)?;
+
+        let mut num_values: usize = 1;
+        loop {
+            match self.next()? {
+                b',' if num_values < MAX_ROWS => {
+                    push(values, self.scalar()?)?;
+                    num_values += 1;
+                }
+                b']' => return Some((kind, num_values)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn scalar(&mut self) -> Option<Scalar<'a>> {
+        match self.peek()? {
+            b'"' => self.string().map(Scalar::String),
+            b'-' | b'0'..=b'9' => self.number(),
+            _ => None,
+        }
+    }
+
+    /// Reads a string without escape sequences, which are left to `serde_json`.
+    fn string(&mut self) -> Option<&'a str> {
+        self.expect(b'"')?;
+        let start = self.pos;
+        let bytes = self.json.as_bytes();
+        while let Some(&byte) = bytes.get(self.pos) {
+            match byte {
+                b'"' => {
+                    let string = &self.json[start..self.pos];
+                    self.pos += 1;
+                    return (string.len() <= MAX_STRING_LENGTH).then_some(string);
+                }
+                b'\\' | 0x00..=0x1f => return None,
+                _ => self.pos += 1,
+            }
+        }
+        None
+    }
+
+    /// Reads a number in the JSON number format. Numbers with a fraction or an exponent are floats, like in
+    /// `serde_json`.
+    ///
+    /// The number is parsed as an integer first, which stops at the fraction or the exponent of floats, so that
+    /// every number is validated and parsed in a single pass.
+    fn number(&mut self) -> Option<Scalar<'a>> {
+        let bytes = &self.json.as_bytes()[self.pos..];
+        let (integer, length) =
+            lexical_core::parse_partial_with_options::<i64, JSON>(bytes, &INTEGER_OPTIONS).ok()?;
+        match bytes.get(length) {
+            Some(b'.' | b'e' | b'E') => {
+                let (float, length) =
+                    lexical_core::parse_partial_with_options::<f64, JSON>(bytes, &FLOAT_OPTIONS)
+                        .ok()?;
+                self.pos += length;
+                Some(Scalar::Float(float))
+            }
+            _ if length == 0 => None,
+            // serde_json parses `-0` as a float
+            _ if integer == 0 && bytes[0] == b'-' => None,
+            _ => {
+                self.pos += length;
+                Some(Scalar::Int(integer))
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let bytes = self.json.as_bytes();
+        while matches!(bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Returns the next byte after any whitespace, without consuming it.
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.json.as_bytes().get(self.pos).copied()
+    }
+
+    /// Consumes and returns the next byte after any whitespace.
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    /// Consumes the next byte after any whitespace, which must be `byte`.
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        (self.next()? == byte).then_some(())
+    }
+}
+
+/// Appends a value of the same type as the values, converting it like `serde_json` inputs are converted.
+fn push(values: &mut Values, value: Scalar) -> Option<()> {
+    match (values, value) {
+        (Values::String(vec), Scalar::String(v)) => vec.push(v.to_string()),
+        (Values::Int(vec), Scalar::Int(v)) => vec.push(i32::try_from(v).ok()?),
+        (Values::Float(vec), Scalar::Float(v)) => {
+            let converted = v as f32;
+            if !converted.is_finite() {
+                return None;
+            }
+            vec.push(converted)
+        }
+        (Values::Float(vec), Scalar::Int(v)) => vec.push(v as f32),
+        _ => return None,
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    fn parse_fast(json: &str) -> Option<ModelInput> {
+        let mut input = ModelInput::default();
+        parse_columns(json, &mut input, &mut Vec::new()).then_some(input)
+    }
+
+    #[test]
+    fn successfully_parses_numeric_columns_like_serde_json() {
+        // Arrange
+        let json = r#" { "age": [22.5, 38, -1e2], "pclass": [3, 1, 0], "sex": ["male", "female", "male"] } "#;
+
+        // Act
+        let input = parse_fast(json).unwrap();
+
+        // Assert
+        assert_eq!(
+            input.float_features.values.as_floats().unwrap(),
+            &vec![22.5, 38.0, -100.0]
+        );
+        assert_eq!(
+            input.integer_features.values.as_ints().unwrap(),
+            &vec![3, 1, 0]
+        );
+        assert_eq!(input.string_features.names, vec!["sex".to_string()]);
+        assert_eq!(input.num_rows(), 3);
+    }
+
+    #[test]
+    fn fails_to_parse_inputs_which_are_left_to_serde_json() {
+        // Arrange
+        let inputs = [
+            r#"[{"age": 22.0}]"#,
+            r#"{"name": ["a\"b"]}"#,
+            r#"{"age": [22.0, "a"]}"#,
+            r#"{"age": [1, 2.5]}"#,
+            r#"{"age": [-0]}"#,
+            r#"{"age": [9223372036854775808]}"#,
+            r#"{"age": [3000000000]}"#,
+            r#"{"age": [1e39]}"#,
+            r#"{"age": [01]}"#,
+            r#"{"age": [1.]}"#,
+            r#"{"age": []}"#,
+            r#"{"age": [1], "fare": [1, 2]}"#,
+            r#"{"age": [[1]]}"#,
+            r#"{"age": [1]} x"#,
+        ];
+
+        // Act
+        let parsed: Vec<bool> = inputs
+            .iter()
+            .map(|json| parse_fast(json).is_some())
+            .collect();
+
+        // Assert
+        assert_eq!(parsed, vec![false; inputs.len()]);
+    }
+
+    proptest! {
+        #[test]
+        fn successfully_parses_same_input_as_serde_json(
+            floats in vec(-1e6f64..1e6, 1..50),
+            ints in vec(-1_000_000i32..1_000_000, 1..50),
+        ) {
+            let num_rows = floats.len().min(ints.len());
+            let json = serde_json::json!({
+                "f": floats[..num_rows],
+                "i": ints[..num_rows],
+            })
+            .to_string();
+
+            let fast = parse_fast(json.as_str()).unwrap();
+            let expected: ModelInput = serde_json::from_str(json.as_str()).unwrap();
+
+            prop_assert_eq!(fast.float_features.values.as_floats(), expected.float_features.values.as_floats());
+            prop_assert_eq!(fast.integer_features.values.as_ints(), expected.integer_features.values.as_ints());
+            prop_assert_eq!(fast.float_features.shape, expected.float_features.shape);
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! The traits and types shared by jams-core and the predictors built outside of its tree, e.g. with
+//! jams-predictor-sdk, so that such predictors do not depend on the frameworks and model stores of jams-core.
+//!
+//! jams-core re-exports every item of this crate under its own paths, e.g. `jams_core::model::predict::Predict`.
+
+mod error;
+/// Golden output tests for the predictors.
+///
+/// A fixture pairs an input with the outputs a sample model is expected to return for it, so that framework
+/// upgrades which shift the outputs of a model are caught. Run the tests with `JAMS_UPDATE_GOLDEN=1` to record
+/// the current outputs as the expected outputs after reviewing the change.
+#[cfg(any(test, feature = "test-utils"))]
+pub mod golden;
+pub mod input;
+pub mod lifecycle;
+pub mod output;
+pub mod pool;
+pub mod precision;
+pub mod predict;
+pub mod priority;
+pub mod settings;
+
+pub use async_trait::async_trait;
+pub use error::{Error, Result};
+
+pub const FEATURE_VALUES_CAPACITY: usize = 125_000;
+pub const FEATURE_NAMES_CAPACITY: usize = 50;
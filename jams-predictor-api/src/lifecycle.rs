@@ -0,0 +1,41 @@
+use crate::settings::ModelSettings;
+use async_trait::async_trait;
+use std::time::Duration;
+
+/// The default time allowed for the init hook of a predictor.
+pub const DEFAULT_INIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The default time allowed for the teardown hook of a predictor.
+pub const DEFAULT_TEARDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Hooks run by the model store when a predictor is loaded and unloaded.
+///
+/// Predictors which depend on external resources, e.g. a tokenizer, a memory mapped embeddings file or a warm
+/// cache, set them up in `init` instead of lazily in their first prediction, and release them in `teardown`.
+/// Runtime settings and secrets of the model, e.g. the endpoint of a remote tokenizer, are passed to `init` from
+/// the model settings of the config instead of being read from process-wide environment variables.
+/// The default hooks do nothing.
+#[async_trait]
+pub trait Lifecycle: Send + Sync {
+    /// Sets up the resources of the predictor. Runs once the model is loaded and before it serves predictions,
+    /// a model whose init hook fails or times out is not served.
+    async fn init(&self, _settings: &ModelSettings) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Releases the resources of the predictor. Runs once the model has been replaced, deleted or detached and the
+    /// last request holding it has finished, or on shutdown.
+    async fn teardown(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Returns the time allowed for `init`.
+    fn init_timeout(&self) -> Duration {
+        DEFAULT_INIT_TIMEOUT
+    }
+
+    /// Returns the time allowed for `teardown`.
+    fn teardown_timeout(&self) -> Duration {
+        DEFAULT_TEARDOWN_TIMEOUT
+    }
+}
@@ -0,0 +1,467 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const DEFAULT_OUTPUT_KEY: &str = "predictions";
+
+/// Key of the output which holds the raw logits of a classifier.
+pub const LOGITS_OUTPUT_KEY: &str = "logits";
+
+/// Key of the output which holds the class probabilities of a classifier.
+pub const PROBABILITIES_OUTPUT_KEY: &str = "probabilities";
+
+/// Key of the output which holds the index of the leaf each row falls into in every tree.
+pub const LEAF_INDEX_OUTPUT_KEY: &str = "leaf_indices";
+
+/// Key of the label output which holds the most likely class of each row.
+pub const PREDICTED_LABEL_KEY: &str = "predicted_label";
+
+/// Struct representing the output of a prediction.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelOutput {
+    /// The predictions made by the model.
+    /// We use a hashmap because we can have models with multiple outputs
+    /// The client is responsible for selecting the correct field for their respective purpose
+    /// For the models which do not support multiple outputs, the default key will be 'predictions'
+    pub predictions: HashMap<String, Vec<Vec<f64>>>,
+
+    /// Named outputs with one class label per row, for classifiers shipped with a label map.
+    /// Empty for all other models.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, Vec<String>>,
+
+    /// The shape of each output, keyed by the name of the output. The first dimension is the number of rows
+    /// and the others are the dimensions of each row, e.g. `[3, 2]` for 3 rows of 2 values. Scalar outputs,
+    /// which are returned as `[[x]]`, have an empty shape.
+    ///
+    /// Predictors only set the shapes they know better than the nested predictions, see `ModelOutput::shapes`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub shapes: HashMap<String, Vec<usize>>,
+
+    /// Named outputs with the text generated for each row, for language models such as the llama.cpp ones.
+    /// Empty for all other models.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub texts: HashMap<String, Vec<String>>,
+}
+
+/// Maximum number of decimal places the predictions can be rounded to. An `f64` does not hold more.
+pub const MAX_DECIMALS: u32 = 15;
+
+/// How the fractional digits beyond `decimals` are dropped.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    /// Rounds half away from zero.
+    #[default]
+    Round,
+    /// Drops the digits, rounding towards zero.
+    Truncate,
+}
+
+/// Controls the numeric precision of the predictions in the response, which shrinks the JSON of big batches.
+///
+/// # Example
+/// ```json
+/// { "decimals": 4, "rounding": "truncate", "float32": true }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct OutputPrecision {
+    /// Number of decimal places to keep. Keeps all of them if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u32>,
+    /// How the digits beyond `decimals` are dropped. Defaults to rounding.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub rounding: Rounding,
+    /// Writes the predictions as 32-bit floats, which need at most 9 significant digits.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub float32: bool,
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+impl OutputPrecision {
+    /// Validates the output precision.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(decimals) = self.decimals {
+            if decimals > MAX_DECIMALS {
+                anyhow::bail!("decimals must be at most {} ❌", MAX_DECIMALS)
+            }
+        }
+        Ok(())
+    }
+
+    /// Rounds or truncates a value to `decimals` decimal places.
+    ///
+    /// Values which are too large to have the requested fractional digits are returned unchanged.
+    pub fn apply(&self, value: f64) -> f64 {
+        let decimals = match self.decimals {
+            Some(decimals) => decimals.min(MAX_DECIMALS),
+            None => return value,
+        };
+        let scale = 10f64.powi(decimals as i32);
+        let scaled = value * scale;
+        // beyond 2^52 an f64 has no fractional digits left to drop
+        if !scaled.is_finite() || scaled.abs() >= 4_503_599_627_370_496.0 {
+            return value;
+        }
+        match self.rounding {
+            Rounding::Round => scaled.round() / scale,
+            Rounding::Truncate => scaled.trunc() / scale,
+        }
+    }
+}
+
+/// A `ModelOutput` written with the shape of every output, including the inferred ones.
+#[derive(Serialize)]
+struct ShapedOutput<'a> {
+    predictions: &'a HashMap<String, Vec<Vec<f64>>>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    labels: &'a HashMap<String, Vec<String>>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    shapes: HashMap<String, Vec<usize>>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    texts: &'a HashMap<String, Vec<String>>,
+}
+
+/// The predictions of a `ModelOutput` written as 32-bit floats.
+#[derive(Serialize)]
+struct Float32Output<'a> {
+    predictions: HashMap<&'a str, Vec<Vec<f32>>>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    labels: &'a HashMap<String, Vec<String>>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    shapes: HashMap<String, Vec<usize>>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    texts: &'a HashMap<String, Vec<String>>,
+}
+
+impl ModelOutput {
+    /// Stitches the outputs of the batches of a split input back together, in the order of the batches.
+    ///
+    /// # Arguments
+    /// * `outputs` - The outputs of the batches, in the order of their rows.
+    pub fn concat(outputs: Vec<ModelOutput>) -> ModelOutput {
+        let mut stitched = ModelOutput {
+            predictions: HashMap::new(),
+            labels: HashMap::new(),
+            shapes: HashMap::new(),
+            texts: HashMap::new(),
+        };
+        for output in outputs {
+            for (key, rows) in output.predictions {
+                stitched.predictions.entry(key).or_default().extend(rows);
+            }
+            for (key, labels) in output.labels {
+                stitched.labels.entry(key).or_default().extend(labels);
+            }
+            for (key, texts) in output.texts {
+                stitched.texts.entry(key).or_default().extend(texts);
+            }
+        }
+        // the shapes reported for each batch no longer apply, so they are inferred from the stitched rows
+        stitched
+    }
+
+    /// Returns the shape of every output, keyed by the name of the output.
+    ///
+    /// The shapes set by the predictor are kept. The others are inferred as `[rows, values per row]` for
+    /// the predictions and `[rows]` for the labels and texts.
+    pub fn shapes(&self) -> HashMap<String, Vec<usize>> {
+        let mut shapes: HashMap<String, Vec<usize>> = self
+            .predictions
+            .iter()
+            .map(|(key, rows)| {
+                let width = rows.first().map(|row| row.len()).unwrap_or(0);
+                (key.clone(), vec![rows.len(), width])
+            })
+            .collect();
+        for (key, labels) in &self.labels {
+            shapes.insert(key.clone(), vec![labels.len()]);
+        }
+        for (key, texts) in &self.texts {
+            shapes.insert(key.clone(), vec![texts.len()]);
+        }
+        for (key, shape) in &self.shapes {
+            if self.predictions.contains_key(key) {
+                shapes.insert(key.clone(), shape.clone());
+            }
+        }
+        shapes
+    }
+
+    /// Serializes the output to JSON with the shape of every output, see `ModelOutput::shapes`.
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The output as a JSON string.
+    /// * `Err(anyhow::Error)` - If the output cannot be serialized.
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        let output = ShapedOutput {
+            predictions: &self.predictions,
+            labels: &self.labels,
+            shapes: self.shapes(),
+            texts: &self.texts,
+        };
+        Ok(serde_json::to_string(&output)?)
+    }
+
+    /// Serializes the output to JSON with the predictions written at the given precision.
+    ///
+    /// # Arguments
+    /// * `precision` - The number of decimal places and width of the floats in the JSON.
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The output as a JSON string.
+    /// * `Err(anyhow::Error)` - If the output cannot be serialized.
+    pub fn to_json_with_precision(&self, precision: &OutputPrecision) -> anyhow::Result<String> {
+        if precision.float32 {
+            let output = Float32Output {
+                predictions: self
+                    .predictions
+                    .iter()
+                    .map(|(key, rows)| {
+                        let rows = rows
+                            .iter()
+                            .map(|row| {
+                                row.iter()
+                                    .map(|value| precision.apply(*value) as f32)
+                                    .collect()
+                            })
+                            .collect();
+                        (key.as_str(), rows)
+                    })
+                    .collect(),
+                labels: &self.labels,
+                shapes: self.shapes(),
+                texts: &self.texts,
+            };
+            return Ok(serde_json::to_string(&output)?);
+        }
+
+        let output = ModelOutput {
+            predictions: self
+                .predictions
+                .iter()
+                .map(|(key, rows)| {
+                    let rows = rows
+                        .iter()
+                        .map(|row| row.iter().map(|value| precision.apply(*value)).collect())
+                        .collect();
+                    (key.clone(), rows)
+                })
+                .collect(),
+            labels: self.labels.clone(),
+            shapes: self.shapes(),
+            texts: self.texts.clone(),
+        };
+        Ok(serde_json::to_string(&output)?)
+    }
+}
+
+/// Compares the outputs of a model with the expected outputs.
+///
+/// # Returns
+/// * `Ok(())` - If both have the same outputs and shapes, and every value is within the tolerance.
+/// * `Err(String)` - A description of every mismatch otherwise.
+pub fn compare_outputs(
+    actual: &HashMap<String, Vec<Vec<f64>>>,
+    expected: &HashMap<String, Vec<Vec<f64>>>,
+    tolerance: f64,
+) -> Result<(), String> {
+    let mut mismatches: Vec<String> = Vec::new();
+    for key in actual.keys().filter(|key| !expected.contains_key(*key)) {
+        mismatches.push(format!("unexpected output {}", key));
+    }
+
+    for (key, expected_rows) in expected {
+        let actual_rows = match actual.get(key) {
+            Some(rows) => rows,
+            None => {
+                mismatches.push(format!("missing output {}", key));
+                continue;
+            }
+        };
+        if actual_rows.len() != expected_rows.len() {
+            mismatches.push(format!(
+                "{} has {} rows, expected {}",
+                key,
+                actual_rows.len(),
+                expected_rows.len()
+            ));
+            continue;
+        }
+        for (i, (actual_row, expected_row)) in actual_rows.iter().zip(expected_rows).enumerate() {
+            if actual_row.len() != expected_row.len() {
+                mismatches.push(format!(
+                    "{}[{}] has {} values, expected {}",
+                    key,
+                    i,
+                    actual_row.len(),
+                    expected_row.len()
+                ));
+                continue;
+            }
+            for (j, (a, e)) in actual_row.iter().zip(expected_row).enumerate() {
+                if (a - e).abs() > tolerance * e.abs().max(1.0) {
+                    mismatches.push(format!("{}[{}][{}] is {}, expected {}", key, i, j, a, e));
+                }
+            }
+        }
+    }
+
+    match mismatches.is_empty() {
+        true => Ok(()),
+        false => Err(mismatches.join("\n")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_infer_shapes_of_model_output() {
+        // Arrange
+        let output = ModelOutput {
+            predictions: HashMap::from([
+                ("logits".to_string(), vec![vec![0.1, 0.9], vec![0.7, 0.3]]),
+                ("score".to_string(), vec![vec![0.5]]),
+            ]),
+            labels: HashMap::from([(
+                PREDICTED_LABEL_KEY.to_string(),
+                vec!["b".to_string(), "a".to_string()],
+            )]),
+            shapes: HashMap::from([("score".to_string(), vec![])]),
+            texts: HashMap::new(),
+        };
+
+        // Act
+        let shapes = output.shapes();
+        let json: serde_json::Value = serde_json::from_str(
+            &output
+                .to_json_with_precision(&OutputPrecision::default())
+                .unwrap(),
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(shapes["logits"], vec![2, 2]);
+        assert_eq!(shapes["score"], Vec::<usize>::new());
+        assert_eq!(shapes[PREDICTED_LABEL_KEY], vec![2]);
+        assert_eq!(json["shapes"]["logits"], serde_json::json!([2, 2]));
+        assert_eq!(json["shapes"]["score"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn successfully_serialize_model_output_with_inferred_shapes() {
+        // Arrange
+        let output = ModelOutput {
+            predictions: HashMap::from([(
+                "logits".to_string(),
+                vec![vec![0.1, 0.9], vec![0.7, 0.3]],
+            )]),
+            labels: HashMap::new(),
+            shapes: HashMap::new(),
+            texts: HashMap::new(),
+        };
+
+        // Act
+        let json: serde_json::Value = serde_json::from_str(&output.to_json().unwrap()).unwrap();
+
+        // Assert
+        assert_eq!(json["shapes"]["logits"], serde_json::json!([2, 2]));
+        assert_eq!(
+            json["predictions"]["logits"],
+            serde_json::json!([[0.1, 0.9], [0.7, 0.3]])
+        );
+    }
+
+    #[test]
+    fn successfully_concat_and_serialize_texts_of_model_outputs() {
+        // Arrange
+        let output = |text: &str| ModelOutput {
+            predictions: HashMap::from([("generated_tokens".to_string(), vec![vec![3.0]])]),
+            labels: HashMap::new(),
+            shapes: HashMap::new(),
+            texts: HashMap::from([("generated_text".to_string(), vec![text.to_string()])]),
+        };
+        let float32 = OutputPrecision {
+            float32: true,
+            ..Default::default()
+        };
+
+        // Act
+        let stitched = ModelOutput::concat(vec![output("Paris"), output("Rome")]);
+        let json: serde_json::Value =
+            serde_json::from_str(&stitched.to_json_with_precision(&float32).unwrap()).unwrap();
+
+        // Assert
+        assert_eq!(
+            json["texts"]["generated_text"],
+            serde_json::json!(["Paris", "Rome"])
+        );
+        assert_eq!(json["shapes"]["generated_text"], serde_json::json!([2]));
+    }
+
+    #[test]
+    fn successfully_serialize_model_output_with_precision() {
+        // Arrange
+        let output = ModelOutput {
+            predictions: HashMap::from([(
+                DEFAULT_OUTPUT_KEY.to_string(),
+                vec![vec![0.123456789012, -2.98765, 12345678.9]],
+            )]),
+            labels: HashMap::new(),
+            shapes: HashMap::new(),
+            texts: HashMap::new(),
+        };
+        let rounded = OutputPrecision {
+            decimals: Some(3),
+            ..Default::default()
+        };
+        let truncated = OutputPrecision {
+            decimals: Some(3),
+            rounding: Rounding::Truncate,
+            float32: false,
+        };
+        let float32 = OutputPrecision {
+            float32: true,
+            ..Default::default()
+        };
+
+        // Act
+        let rounded = output.to_json_with_precision(&rounded).unwrap();
+        let truncated = output.to_json_with_precision(&truncated).unwrap();
+        let float32 = output.to_json_with_precision(&float32).unwrap();
+
+        // Assert
+        assert_eq!(
+            rounded,
+            r#"{"predictions":{"predictions":[[0.123,-2.988,12345678.9]]},"shapes":{"predictions":[1,3]}}"#
+        );
+        assert_eq!(
+            truncated,
+            r#"{"predictions":{"predictions":[[0.123,-2.987,12345678.9]]},"shapes":{"predictions":[1,3]}}"#
+        );
+        assert_eq!(
+            float32,
+            r#"{"predictions":{"predictions":[[0.12345679,-2.98765,12345679.0]]},"shapes":{"predictions":[1,3]}}"#
+        );
+    }
+
+    #[test]
+    fn fails_to_validate_output_precision_when_decimals_exceed_maximum() {
+        // Arrange
+        let precision = OutputPrecision {
+            decimals: Some(MAX_DECIMALS + 1),
+            ..Default::default()
+        };
+
+        // Act
+        let result = precision.validate();
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
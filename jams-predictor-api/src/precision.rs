@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// Name of the optional directory in a model tarball which contains the reduced precision variants of the model,
+/// e.g. `variants/int8.pt` and `variants/fp16.pt`.
+pub const VARIANTS_DIRECTORY_NAME: &str = "variants";
+
+/// Numeric precision of a variant of a model.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelPrecision {
+    /// The model artifact itself, in full precision.
+    #[default]
+    Fp32,
+    /// A variant with half precision weights, which is called with half precision inputs.
+    Fp16,
+    /// A variant with int8 quantized weights, e.g. a TorchScript module quantized with dynamic quantization,
+    /// which is called with full precision inputs.
+    Int8,
+}
+
+impl ModelPrecision {
+    /// Returns the precision as used in variant file names and metric labels.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelPrecision::Fp32 => "fp32",
+            ModelPrecision::Fp16 => "fp16",
+            ModelPrecision::Int8 => "int8",
+        }
+    }
+
+    /// Returns the precision of a variant from the name of its file in the `variants` directory,
+    /// e.g. `int8.pt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is not named after a reduced precision, i.e. `fp16` or `int8`.
+    pub fn from_variant_path(path: &Path) -> anyhow::Result<Self> {
+        let stem = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.split('.').next())
+            .unwrap_or_default();
+        match stem {
+            "fp16" => Ok(ModelPrecision::Fp16),
+            "int8" => Ok(ModelPrecision::Int8),
+            _ => anyhow::bail!(
+                "Model variant {:?} must be named fp16 or int8, e.g. {}/int8.pt ❌",
+                path,
+                VARIANTS_DIRECTORY_NAME
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ModelPrecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_parse_precision_from_variant_path() {
+        // Act
+        let int8 = ModelPrecision::from_variant_path(Path::new("variants/int8.pt"));
+        let fp16 = ModelPrecision::from_variant_path(Path::new("fp16.quantized.pt"));
+        let fp32 = ModelPrecision::from_variant_path(Path::new("variants/fp32.pt"));
+        let unknown = ModelPrecision::from_variant_path(Path::new("variants/int4.pt"));
+
+        // Assert
+        assert_eq!(int8.unwrap(), ModelPrecision::Int8);
+        assert_eq!(fp16.unwrap(), ModelPrecision::Fp16);
+        assert!(fp32.is_err());
+        assert!(unknown.is_err());
+    }
+}
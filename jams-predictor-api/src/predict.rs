@@ -0,0 +1,344 @@
+use crate::input::ModelInput;
+use crate::output::{ModelOutput, OutputPrecision};
+use crate::precision::ModelPrecision;
+use crate::priority::Priority;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// Name of the optional file in a model tarball which contains the default prediction options of the model.
+pub const PREDICT_OPTIONS_FILE_NAME: &str = "predict_options.json";
+
+/// What a model returns when making predictions. Only supported by LightGBM models.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PredictMode {
+    /// Transformed predictions, i.e. probabilities for classifiers.
+    Normal,
+    /// Raw scores before the objective transformation is applied.
+    RawScore,
+    /// The index of the leaf each row falls into in every tree, e.g. to use as an embedding.
+    LeafIndex,
+}
+
+/// What to do with the features of a request which are not in the `bundle/schema.json` of the model.
+///
+/// The policies are ordered from the most lenient to the strictest, so that a request can only make the
+/// policy of the model stricter.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownFeatures {
+    /// Drop the unknown features before calling the model.
+    #[default]
+    Ignore,
+    /// Drop the unknown features and log a warning with their names.
+    Warn,
+    /// Reject the request with an error listing the unknown features.
+    Reject,
+}
+
+/// Options which change how a model makes predictions.
+///
+/// Options can be set per model, using a `predict_options.json` file at the root of the model tarball,
+/// and per request. Options set on a request take precedence over the options of the model.
+///
+/// # Example
+/// ```json
+/// { "mode": "leaf_index", "num_iteration": 100, "precision": { "decimals": 4 } }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PredictOptions {
+    /// What the model returns. Defaults to the framework specific behaviour.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<PredictMode>,
+    /// Number of iterations to use for the prediction. Defaults to the best iteration of the model,
+    /// or all iterations if the model was trained without early stopping.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_iteration: Option<u32>,
+    /// Numeric precision of the predictions in the response. Applied by the `Manager` when serializing
+    /// the predictions, so it is supported by all models.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precision: Option<OutputPrecision>,
+    /// What to do with features which are not in the schema of the model. Applied by the `Manager`
+    /// when validating the input, so it is supported by all models with a `bundle/schema.json`. A request
+    /// can only make the policy of the model stricter, e.g. it cannot ignore features the model rejects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unknown_features: Option<UnknownFeatures>,
+    /// Maximum number of rows the model is called with. Bigger inputs are split into batches of at most
+    /// this many rows, whose predictions are stitched back together in the order of the rows. Applied by
+    /// the `Manager`, so it is supported by all models. A request can only lower the maximum of the model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_batch_size: Option<usize>,
+    /// Whether the batches of a split input are predicted in parallel. Defaults to `false`. It can only be set per
+    /// model, as it multiplies the share of the worker pool used by a request, so it is ignored on requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parallel_batches: Option<bool>,
+    /// The precision variant of the model which makes the predictions, e.g. `int8`. Defaults to `fp32`, i.e. the
+    /// model artifact itself. Applied by the `Manager`, so it is supported by all models shipped with variants.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<ModelPrecision>,
+    /// The priority of the prediction on the worker pool, e.g. `high`. Defaults to `normal`. Applied by the
+    /// server when queueing the prediction, so it is supported by all models. A request can only lower the
+    /// priority of the model, so that clients cannot move their requests ahead of the others.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    /// The latency budget of the request, in milliseconds. While the recent p95 latency of the model exceeds it,
+    /// the prediction is made with the lightweight variant configured for the model, if any. Ignored when a
+    /// `variant` is set. Applied by the `Manager`, so it is supported by all models shipped with variants.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_budget_ms: Option<u64>,
+    /// The version of the model which must make the prediction, i.e. the SHA-256 digest of its artifact as
+    /// returned by the model metadata. Applied by the `Manager` to the model which makes the prediction, so that
+    /// a model swapped while the request is queued is caught. It can only be set on requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_version: Option<String>,
+}
+
+impl PredictOptions {
+    /// Parses and validates prediction options.
+    ///
+    /// # Arguments
+    /// * `contents` - The prediction options as a JSON string.
+    ///
+    /// # Returns
+    /// * `Ok(PredictOptions)` - If the options are valid.
+    /// * `Err(anyhow::Error)` - If the options cannot be parsed or are invalid.
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let options: PredictOptions = match serde_json::from_str(contents) {
+            Ok(options) => options,
+            Err(e) => anyhow::bail!("Failed to parse prediction options ❌: {}", e),
+        };
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// Validates the prediction options.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.num_iteration == Some(0) {
+            anyhow::bail!("num_iteration must be at least 1 ❌")
+        }
+        if self.max_batch_size == Some(0) {
+            anyhow::bail!("max_batch_size must be at least 1 ❌")
+        }
+        if self.latency_budget_ms == Some(0) {
+            anyhow::bail!("latency_budget_ms must be at least 1 ❌")
+        }
+        if self
+            .model_version
+            .as_ref()
+            .is_some_and(|version| version.trim().is_empty())
+        {
+            anyhow::bail!("model_version must not be empty ❌")
+        }
+        if let Some(precision) = &self.precision {
+            precision.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Returns true if no option is set.
+    pub fn is_empty(&self) -> bool {
+        self.mode.is_none()
+            && self.num_iteration.is_none()
+            && self.precision.is_none()
+            && self.unknown_features.is_none()
+            && self.max_batch_size.is_none()
+            && self.parallel_batches.is_none()
+            && self.variant.is_none()
+            && self.priority.is_none()
+            && self.latency_budget_ms.is_none()
+            && self.model_version.is_none()
+    }
+
+    /// Returns the options applied by the model itself, i.e. without the output precision, the
+    /// unknown features policy, the batching options, the variant, the priority, the latency budget and the
+    /// pinned model version.
+    pub fn model_options(&self) -> PredictOptions {
+        PredictOptions {
+            precision: None,
+            unknown_features: None,
+            max_batch_size: None,
+            parallel_batches: None,
+            variant: None,
+            priority: None,
+            latency_budget_ms: None,
+            model_version: None,
+            ..self.clone()
+        }
+    }
+
+    /// Returns these options with the options set in `overrides` taking precedence, except for
+    /// `max_batch_size` which can only be lowered, `unknown_features` which can only be made stricter,
+    /// `parallel_batches` and `priority` which are only taken from these options and `model_version` which is only
+    /// taken from `overrides`.
+    pub fn merge(&self, overrides: &PredictOptions) -> PredictOptions {
+        let max_batch_size = match (self.max_batch_size, overrides.max_batch_size) {
+            (Some(max), Some(requested)) => Some(max.min(requested)),
+            (max, requested) => requested.or(max),
+        };
+        let unknown_features = match (self.unknown_features, overrides.unknown_features) {
+            (Some(policy), Some(requested)) => Some(policy.max(requested)),
+            (policy, requested) => requested.or(policy),
+        };
+        PredictOptions {
+            mode: overrides.mode.or(self.mode),
+            num_iteration: overrides.num_iteration.or(self.num_iteration),
+            precision: overrides.precision.or(self.precision),
+            unknown_features,
+            max_batch_size,
+            parallel_batches: self.parallel_batches,
+            variant: overrides.variant.or(self.variant),
+            priority: self.priority,
+            latency_budget_ms: overrides.latency_budget_ms.or(self.latency_budget_ms),
+            model_version: overrides.model_version.clone(),
+        }
+    }
+}
+
+/// Trait for making predictions using a model.
+pub trait Predict: Send + Sync + 'static {
+    /// Predicts the output for the given model input.
+    ///
+    /// # Arguments
+    /// * `input` - The input data for the model.
+    ///
+    /// # Returns
+    /// * `Ok(Output)` - The prediction output.
+    /// * `Err(Error::Input)` - If the input is not valid for the model.
+    /// * `Err(Error::Predict)` - If there was an error during prediction.
+    fn predict(&self, input: ModelInput) -> Result<ModelOutput>;
+
+    /// Predicts the output for the given model input using the given prediction options.
+    ///
+    /// Models which do not support any option only accept empty options.
+    ///
+    /// # Arguments
+    /// * `input` - The input data for the model.
+    /// * `options` - The prediction options.
+    ///
+    /// # Returns
+    /// * `Ok(Output)` - The prediction output.
+    /// * `Err(Error::Input)` - If the options are not supported or the input is not valid for the model.
+    /// * `Err(Error::Predict)` - If there was an error during prediction.
+    fn predict_with_options(
+        &self,
+        input: ModelInput,
+        options: &PredictOptions,
+    ) -> Result<ModelOutput> {
+        if !options.is_empty() {
+            return Err(Error::Input(anyhow::anyhow!(
+                "Prediction options are not supported by this model ❌"
+            )));
+        }
+        self.predict(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_parse_and_merge_predict_options() {
+        // Arrange
+        let model_options =
+            PredictOptions::parse(r#"{"mode": "raw_score", "num_iteration": 50}"#).unwrap();
+        let request_options = PredictOptions::parse(r#"{"mode": "leaf_index"}"#).unwrap();
+
+        // Act
+        let options = model_options.merge(&request_options);
+
+        // Assert
+        assert_eq!(options.mode, Some(PredictMode::LeafIndex));
+        assert_eq!(options.num_iteration, Some(50));
+        assert!(PredictOptions::default().is_empty());
+        assert!(PredictOptions::parse(r#"{"precision": {"float32": true}}"#)
+            .unwrap()
+            .model_options()
+            .is_empty());
+        assert!(PredictOptions::parse(r#"{"unknown_features": "reject"}"#)
+            .unwrap()
+            .model_options()
+            .is_empty());
+        let batching = PredictOptions::parse(r#"{"max_batch_size": 64}"#).unwrap();
+        assert_eq!(
+            batching
+                .merge(&PredictOptions::parse(r#"{"max_batch_size": 1000}"#).unwrap())
+                .max_batch_size,
+            Some(64)
+        );
+        assert!(batching.model_options().is_empty());
+        let parallel = PredictOptions::parse(r#"{"parallel_batches": false}"#).unwrap();
+        assert_eq!(
+            parallel
+                .merge(&PredictOptions::parse(r#"{"parallel_batches": true}"#).unwrap())
+                .parallel_batches,
+            Some(false)
+        );
+        assert_eq!(
+            PredictOptions::default()
+                .merge(&PredictOptions::parse(r#"{"parallel_batches": true}"#).unwrap())
+                .parallel_batches,
+            None
+        );
+        let variant = PredictOptions::parse(r#"{"variant": "int8"}"#).unwrap();
+        assert_eq!(
+            variant
+                .merge(&PredictOptions::parse(r#"{"variant": "fp32"}"#).unwrap())
+                .variant,
+            Some(ModelPrecision::Fp32)
+        );
+        assert!(variant.model_options().is_empty());
+        let priority = PredictOptions::parse(r#"{"priority": "low"}"#).unwrap();
+        assert_eq!(
+            priority
+                .merge(&PredictOptions::parse(r#"{"priority": "high"}"#).unwrap())
+                .priority,
+            Some(Priority::Low)
+        );
+        assert_eq!(
+            PredictOptions::default()
+                .merge(&PredictOptions::parse(r#"{"priority": "high"}"#).unwrap())
+                .priority,
+            None
+        );
+        assert!(priority.model_options().is_empty());
+    }
+
+    #[test]
+    fn successfully_merge_unknown_features_policy_only_when_stricter() {
+        // Arrange
+        let reject = PredictOptions::parse(r#"{"unknown_features": "reject"}"#).unwrap();
+        let warn = PredictOptions::parse(r#"{"unknown_features": "warn"}"#).unwrap();
+        let ignore = PredictOptions::parse(r#"{"unknown_features": "ignore"}"#).unwrap();
+
+        // Act
+        let relaxed = reject.merge(&ignore).unknown_features;
+        let tightened = warn.merge(&reject).unknown_features;
+        let unset = PredictOptions::default().merge(&warn).unknown_features;
+
+        // Assert
+        assert_eq!(relaxed, Some(UnknownFeatures::Reject));
+        assert_eq!(tightened, Some(UnknownFeatures::Reject));
+        assert_eq!(unset, Some(UnknownFeatures::Warn));
+    }
+
+    #[test]
+    fn fails_to_parse_predict_options_when_invalid() {
+        // Act
+        let unknown_mode = PredictOptions::parse(r#"{"mode": "contrib"}"#);
+        let unknown_field = PredictOptions::parse(r#"{"iterations": 10}"#);
+        let zero_iterations = PredictOptions::parse(r#"{"num_iteration": 0}"#);
+        let too_many_decimals = PredictOptions::parse(r#"{"precision": {"decimals": 20}}"#);
+        let unknown_policy = PredictOptions::parse(r#"{"unknown_features": "drop"}"#);
+        let zero_batch_size = PredictOptions::parse(r#"{"max_batch_size": 0}"#);
+
+        // Assert
+        assert!(unknown_mode.is_err());
+        assert!(unknown_field.is_err());
+        assert!(zero_iterations.is_err());
+        assert!(too_many_decimals.is_err());
+        assert!(unknown_policy.is_err());
+        assert!(zero_batch_size.is_err());
+    }
+}
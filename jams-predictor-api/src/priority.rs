@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Priority of a task. Queued tasks with a higher priority run first, tasks with the same priority run in
+/// the order they were submitted. A task is promoted by one priority for every `aging` it waits for a worker.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    /// Background work, e.g. bulk scoring, which only runs when no other task is queued.
+    Low,
+    /// Regular predictions.
+    #[default]
+    Normal,
+    /// Latency critical work, e.g. health checks or interactive predictions.
+    High,
+}
+
+impl Priority {
+    /// All priorities, from the highest to the lowest.
+    pub const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+
+    /// Returns the priority as used in metric labels.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+        }
+    }
+
+    /// Returns the position of the priority in `Priority::ALL`.
+    pub fn index(&self) -> usize {
+        match self {
+            Priority::High => 0,
+            Priority::Normal => 1,
+            Priority::Low => 2,
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The resolved settings of a model, which are passed to the init hook of its predictor.
+///
+/// The values may be secrets, hence they are never printed by `Debug`.
+#[derive(Clone, Default, PartialEq)]
+pub struct ModelSettings {
+    values: BTreeMap<String, String>,
+}
+
+impl ModelSettings {
+    /// Creates settings from resolved values.
+    pub fn new(values: BTreeMap<String, String>) -> Self {
+        ModelSettings { values }
+    }
+
+    /// Returns the value of a setting, if it is set.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Returns the value of a setting which the predictor cannot do without.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the setting is not set.
+    pub fn require(&self, name: &str) -> anyhow::Result<&str> {
+        match self.get(name) {
+            Some(value) => Ok(value),
+            None => anyhow::bail!("Model setting {} is not set ❌", name),
+        }
+    }
+
+    /// Returns the names of the settings.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+
+    /// Returns true if no setting is set.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl fmt::Debug for ModelSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.values.keys().map(|name| (name, "<redacted>")))
+            .finish()
+    }
+}
@@ -0,0 +1,274 @@
+use crate::golden::compare_outputs;
+use crate::{ModelInput, ModelOutput, Predict, PredictOptions};
+
+/// Maximum difference allowed between the outputs of the same rows predicted in different ways.
+pub const CONFORMANCE_TOLERANCE: f64 = 1e-6;
+
+/// Number of threads predicting concurrently in `check_predicts_concurrently`.
+const NUM_THREADS: usize = 4;
+
+fn parse_input(input: &str) -> ModelInput {
+    ModelInput::from_str(input).unwrap_or_else(|e| panic!("failed to parse sample input: {}", e))
+}
+
+fn predict<P: Predict>(predictor: &P, input: ModelInput) -> ModelOutput {
+    predictor
+        .predict(input)
+        .unwrap_or_else(|e| panic!("failed to predict sample input: {}", e))
+}
+
+fn assert_same_outputs(actual: &ModelOutput, expected: &ModelOutput, context: &str) {
+    if let Err(mismatches) = compare_outputs(
+        &actual.predictions,
+        &expected.predictions,
+        CONFORMANCE_TOLERANCE,
+    ) {
+        panic!("outputs differ {}:\n{}", context, mismatches)
+    }
+    if actual.labels != expected.labels {
+        panic!("labels differ {}", context)
+    }
+}
+
+/// Checks that the predictor returns at least one output with one row per row of the input.
+///
+/// # Panics
+/// If the prediction fails, there is no output or an output has a different number of rows.
+pub fn check_predicts_every_row<P: Predict>(predictor: &P, input: &str) {
+    let input = parse_input(input);
+    let num_rows = input.num_rows();
+    let output = predict(predictor, input);
+
+    if output.predictions.is_empty() && output.labels.is_empty() {
+        panic!("predictor returned no output")
+    }
+    for (key, rows) in output.predictions.iter() {
+        assert_eq!(
+            rows.len(),
+            num_rows,
+            "{} has a different number of rows than the input",
+            key
+        );
+    }
+    for (key, labels) in output.labels.iter() {
+        assert_eq!(
+            labels.len(),
+            num_rows,
+            "{} has a different number of rows than the input",
+            key
+        );
+    }
+}
+
+/// Checks that empty prediction options behave like no options, as jams-serve always passes options.
+///
+/// # Panics
+/// If the prediction with empty options fails or returns different outputs.
+pub fn check_accepts_empty_options<P: Predict>(predictor: &P, input: &str) {
+    let expected = predict(predictor, parse_input(input));
+    let output = predictor
+        .predict_with_options(parse_input(input), &PredictOptions::default())
+        .unwrap_or_else(|e| panic!("failed to predict sample input with empty options: {}", e));
+
+    assert_same_outputs(&output, &expected, "with empty options");
+}
+
+/// Checks that predicting the input row by row returns the same outputs as predicting it at once, as
+/// jams-serve splits big inputs into batches and stitches their outputs back together.
+///
+/// # Panics
+/// If the input has less than 2 rows, a prediction fails or the outputs differ.
+pub fn check_batches_are_consistent<P: Predict>(predictor: &P, input: &str) {
+    let input = parse_input(input);
+    assert!(
+        input.num_rows() >= 2,
+        "sample input must have at least 2 rows to be split into batches"
+    );
+    let expected = predict(predictor, input.clone());
+    let batches = input
+        .split_rows(1)
+        .into_iter()
+        .map(|batch| predict(predictor, batch))
+        .collect();
+    let output = ModelOutput::concat(batches);
+
+    assert_same_outputs(&output, &expected, "when predicted row by row");
+}
+
+/// Checks that the predictor can be shared by threads predicting concurrently.
+///
+/// # Panics
+/// If a prediction fails or returns different outputs than a prediction on its own.
+pub fn check_predicts_concurrently<P: Predict>(predictor: &P, input: &str) {
+    let expected = predict(predictor, parse_input(input));
+    let outputs: Vec<ModelOutput> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..NUM_THREADS)
+            .map(|_| scope.spawn(|| predict(predictor, parse_input(input))))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    for output in outputs.iter() {
+        assert_same_outputs(output, &expected, "when predicted concurrently");
+    }
+}
+
+/// Checks that the output can be serialized to JSON and back without loss, as jams-serve returns it as JSON.
+///
+/// # Panics
+/// If the prediction fails, an output holds a value which is not finite or the output changes on the way.
+pub fn check_output_serializes<P: Predict>(predictor: &P, input: &str) {
+    let output = predict(predictor, parse_input(input));
+    for (key, rows) in output.predictions.iter() {
+        assert!(
+            rows.iter().flatten().all(|value| value.is_finite()),
+            "{} holds values which are not finite",
+            key
+        );
+    }
+    let json = serde_json::to_string(&output)
+        .unwrap_or_else(|e| panic!("failed to serialize output: {}", e));
+    let deserialized: ModelOutput = serde_json::from_str(&json)
+        .unwrap_or_else(|e| panic!("failed to deserialize output: {}", e));
+
+    assert_same_outputs(&deserialized, &output, "after serialization");
+}
+
+/// Generates a `conformance` test module which checks that a predictor behaves like the predictors shipped
+/// with jams-core.
+///
+/// # Arguments
+/// * `predictor` - An expression building the predictor, evaluated once per test.
+/// * `input` - A sample JSON input of the predictor with at least 2 rows.
+///
+/// # Example
+/// ```ignore
+/// #[cfg(test)]
+/// mod tests {
+///     jams_predictor_sdk::conformance_tests!(
+///         MyPredictor::load("tests/models/my_model.bin").unwrap(),
+///         r#"{"feature_1": [0.5, 1.0], "feature_2": [0.25, 3.0]}"#
+///     );
+/// }
+/// ```
+#[macro_export]
+macro_rules! conformance_tests {
+    ($predictor:expr, $input:expr) => {
+        mod conformance {
+            use super::*;
+
+            #[test]
+            fn successfully_predict_every_row() {
+                $crate::conformance::check_predicts_every_row(&$predictor, $input);
+            }
+
+            #[test]
+            fn successfully_accept_empty_options() {
+                $crate::conformance::check_accepts_empty_options(&$predictor, $input);
+            }
+
+            #[test]
+            fn successfully_predict_consistent_batches() {
+                $crate::conformance::check_batches_are_consistent(&$predictor, $input);
+            }
+
+            #[test]
+            fn successfully_predict_concurrently() {
+                $crate::conformance::check_predicts_concurrently(&$predictor, $input);
+            }
+
+            #[test]
+            fn successfully_serialize_output() {
+                $crate::conformance::check_output_serializes(&$predictor, $input);
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golden::check_golden_file;
+    use crate::{Error, DEFAULT_OUTPUT_KEY};
+    use std::collections::HashMap;
+
+    /// Predicts the weighted sum of the float features of each row.
+    struct Linear {
+        weight: f64,
+    }
+
+    impl Linear {
+        fn load(path: &str) -> crate::Result<Self> {
+            let model: serde_json::Value = std::fs::read_to_string(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|contents| serde_json::from_str(&contents).map_err(anyhow::Error::from))
+                .map_err(Error::Load)?;
+            match model["weight"].as_f64() {
+                Some(weight) => Ok(Linear { weight }),
+                None => Err(Error::Load(anyhow::anyhow!("weight is missing"))),
+            }
+        }
+    }
+
+    impl Predict for Linear {
+        fn predict(&self, input: ModelInput) -> crate::Result<ModelOutput> {
+            let (num_features, num_rows) = input.float_features.shape;
+            let values = match input.float_features.values.as_floats() {
+                Some(values) => values,
+                None => return Err(Error::Input(anyhow::anyhow!("float features are missing"))),
+            };
+            let predictions = (0..num_rows)
+                .map(|row| {
+                    let sum: f64 = (0..num_features)
+                        .map(|feature| values[feature * num_rows + row] as f64)
+                        .sum();
+                    vec![sum * self.weight]
+                })
+                .collect();
+            Ok(ModelOutput {
+                predictions: HashMap::from([(DEFAULT_OUTPUT_KEY.to_string(), predictions)]),
+                labels: HashMap::new(),
+                shapes: HashMap::new(),
+                texts: HashMap::new(),
+            })
+        }
+    }
+
+    /// Always predicts a single row, whatever the number of rows of the input.
+    struct SingleRow;
+
+    impl Predict for SingleRow {
+        fn predict(&self, _input: ModelInput) -> crate::Result<ModelOutput> {
+            Ok(ModelOutput {
+                predictions: HashMap::from([(DEFAULT_OUTPUT_KEY.to_string(), vec![vec![1.0]])]),
+                labels: HashMap::new(),
+                shapes: HashMap::new(),
+                texts: HashMap::new(),
+            })
+        }
+    }
+
+    const SAMPLE_INPUT: &str = r#"{"feature_1": [0.5, 1.0, -2.0], "feature_2": [0.25, 3.0, 1.5]}"#;
+
+    crate::conformance_tests!(Linear { weight: 2.0 }, SAMPLE_INPUT);
+
+    #[test]
+    fn successfully_match_golden_outputs_using_custom_predictor() {
+        check_golden_file("tests/golden/linear.json", Linear::load);
+    }
+
+    #[test]
+    #[should_panic(expected = "different number of rows")]
+    fn fails_to_pass_conformance_when_rows_are_missing() {
+        check_predicts_every_row(&SingleRow, SAMPLE_INPUT);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 2 rows")]
+    fn fails_to_check_batches_when_input_has_a_single_row() {
+        check_batches_are_consistent(&Linear { weight: 2.0 }, r#"{"feature_1": [0.5]}"#);
+    }
+}
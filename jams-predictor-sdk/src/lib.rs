@@ -0,0 +1,25 @@
+//! Traits, types and test kit to build custom predictors for jams-core outside of its tree,
+//! e.g. a pure-Rust GBDT, which behave like the predictors shipped with jams-core.
+//!
+//! A predictor implements [`Predict`] and [`Lifecycle`], is tested with the golden helpers of [`golden`] and the
+//! [`conformance_tests!`] macro.
+
+pub mod conformance;
+
+pub use jams_predictor_api::async_trait;
+pub use jams_predictor_api::input::{Features, ModelInput, Values};
+pub use jams_predictor_api::lifecycle::Lifecycle;
+pub use jams_predictor_api::output::{ModelOutput, DEFAULT_OUTPUT_KEY};
+pub use jams_predictor_api::predict::{Predict, PredictOptions};
+pub use jams_predictor_api::settings::ModelSettings;
+pub use jams_predictor_api::{Error, Result};
+
+/// Helpers to compare the outputs of a predictor with the outputs recorded in a golden fixture.
+///
+/// A fixture is a JSON file with the `model` path, the `input`, the `expected` outputs and optionally the
+/// `predict_options` and `tolerance`. Run the tests with `JAMS_UPDATE_GOLDEN=1` to record the outputs.
+pub mod golden {
+    pub use jams_predictor_api::golden::{
+        check_golden_file, compare_outputs, GoldenFixture, UPDATE_GOLDEN_ENV,
+    };
+}
@@ -0,0 +1,272 @@
+use crate::common::metrics::{IN_FLIGHT_PREDICTIONS, LOAD_SHED_TOTAL, RESIDENT_MEMORY_BYTES};
+use jams_core::memory::resident_memory_bytes;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time;
+
+/// The background worker that samples the resident memory of the process.
+///
+/// This asynchronous function runs in an infinite loop and records the resident memory of the process
+/// every second. The sampled value is used for load shedding so that requests do not need to read
+/// `/proc` on the hot path.
+pub async fn resident_memory_sampler() {
+    tracing::info!("Starting resident memory sampler 🔁");
+
+    loop {
+        if let Some(resident_bytes) = resident_memory_bytes() {
+            RESIDENT_MEMORY_BYTES.set(resident_bytes as i64);
+        }
+
+        tokio::time::sleep(time::Duration::from_secs(1)).await;
+    }
+}
+
+/// Checks whether a request should be shed because the resident memory is above the watermark.
+///
+/// # Arguments
+///
+/// * `watermark_bytes` - The optional memory watermark in bytes. Shedding is disabled if `None`.
+/// * `protocol` - The protocol of the request, used for recording metrics.
+/// * `endpoint` - The endpoint of the request, used for recording metrics.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the request should be rejected.
+pub fn should_shed(
+    watermark_bytes: Option<u64>,
+    protocol: &'static str,
+    endpoint: &'static str,
+) -> bool {
+    let watermark_bytes = match watermark_bytes {
+        None => return false,
+        Some(watermark_bytes) => watermark_bytes,
+    };
+
+    let resident_bytes = RESIDENT_MEMORY_BYTES.get() as u64;
+    if resident_bytes > watermark_bytes {
+        tracing::warn!(
+            "Shedding {} {} request as resident memory of {} bytes is above the watermark of {} bytes ⚠️",
+            protocol,
+            endpoint,
+            resident_bytes,
+            watermark_bytes
+        );
+        LOAD_SHED_TOTAL
+            .with_label_values(&[protocol, endpoint, "memory"])
+            .inc();
+        return true;
+    }
+
+    false
+}
+
+/// Limits the number of predictions in flight and the time a prediction may wait for a worker, so that
+/// latency stays bounded when the offered load exceeds the capacity of the server. Predictions over
+/// either limit are rejected with a retriable status, i.e. 503 (HTTP) or UNAVAILABLE (gRPC), instead
+/// of growing the queue without bound.
+#[derive(Default)]
+pub struct InFlightLimiter {
+    max_in_flight: Option<usize>,
+    max_queue_wait: Option<Duration>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl InFlightLimiter {
+    /// Creates a limiter. Each limit is disabled if `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_in_flight` - The maximum number of predictions queued or running across all models.
+    /// * `max_queue_wait` - The maximum time a prediction may wait for a worker before it is rejected.
+    pub fn new(max_in_flight: Option<usize>, max_queue_wait: Option<Duration>) -> Self {
+        InFlightLimiter {
+            max_in_flight,
+            max_queue_wait,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Admits a prediction if fewer than the maximum number of predictions are in flight.
+    ///
+    /// # Arguments
+    ///
+    /// * `protocol` - The protocol of the request, used for recording metrics.
+    /// * `endpoint` - The endpoint of the request, used for recording metrics.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(InFlightPermit)` - If the prediction was admitted. It counts as in flight until the permit is dropped.
+    /// * `None` - If the prediction should be rejected.
+    pub fn admit(&self, protocol: &'static str, endpoint: &'static str) -> Option<InFlightPermit> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst);
+        if let Some(max_in_flight) = self.max_in_flight {
+            if in_flight >= max_in_flight {
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                tracing::warn!(
+                    "Shedding {} {} request as {} predictions are in flight ⚠️",
+                    protocol,
+                    endpoint,
+                    in_flight
+                );
+                LOAD_SHED_TOTAL
+                    .with_label_values(&[protocol, endpoint, "in_flight"])
+                    .inc();
+                return None;
+            }
+        }
+        IN_FLIGHT_PREDICTIONS.inc();
+        Some(InFlightPermit {
+            in_flight: Arc::clone(&self.in_flight),
+        })
+    }
+
+    /// Waits for the result of a queued prediction. If no worker has picked up the prediction within the
+    /// maximum queue wait, the prediction is withdrawn from the queue and rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `rx` - The receiver of the result of the prediction.
+    /// * `ticket` - The ticket which the worker claims before it runs the prediction.
+    /// * `protocol` - The protocol of the request, used for recording metrics.
+    /// * `endpoint` - The endpoint of the request, used for recording metrics.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Result<T, RecvError>)` - The result received from the worker.
+    /// * `Err(anyhow::Error)` - If the prediction waited for a worker for longer than the maximum queue wait.
+    pub async fn wait<T>(
+        &self,
+        mut rx: oneshot::Receiver<T>,
+        ticket: &QueueTicket,
+        protocol: &'static str,
+        endpoint: &'static str,
+    ) -> anyhow::Result<Result<T, oneshot::error::RecvError>> {
+        let max_queue_wait = match self.max_queue_wait {
+            Some(max_queue_wait) => max_queue_wait,
+            None => return Ok(rx.await),
+        };
+        match time::timeout(max_queue_wait, &mut rx).await {
+            Ok(result) => Ok(result),
+            // the worker is already running the prediction, so wait for it to finish
+            Err(_) if !ticket.claim() => Ok(rx.await),
+            Err(_) => {
+                tracing::warn!(
+                    "Shedding {} {} request as it waited for a worker for {:?} ⚠️",
+                    protocol,
+                    endpoint,
+                    max_queue_wait
+                );
+                LOAD_SHED_TOTAL
+                    .with_label_values(&[protocol, endpoint, "queue_timeout"])
+                    .inc();
+                anyhow::bail!("no worker was available within {:?}", max_queue_wait)
+            }
+        }
+    }
+}
+
+/// Marks a prediction as in flight until it is dropped.
+pub struct InFlightPermit {
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlightPermit {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        IN_FLIGHT_PREDICTIONS.dec();
+    }
+}
+
+/// Decides who gets a queued prediction: the worker which runs it, or the handler which withdraws it
+/// after the maximum queue wait. Whoever claims the ticket first wins.
+#[derive(Clone, Default)]
+pub struct QueueTicket(Arc<AtomicBool>);
+
+impl QueueTicket {
+    /// Claims the ticket, returning `false` if it was already claimed.
+    pub fn claim(&self) -> bool {
+        self.0
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::metrics::HTTP;
+
+    #[test]
+    fn successfully_skips_shedding_when_watermark_is_not_set() {
+        // Act
+        let shed = should_shed(None, HTTP, "predict");
+
+        // Assert
+        assert!(!shed)
+    }
+
+    #[test]
+    fn successfully_sheds_when_resident_memory_is_above_watermark() {
+        // Arrange
+        RESIDENT_MEMORY_BYTES.set(2048);
+
+        // Act
+        let shed = should_shed(Some(1024), HTTP, "predict");
+
+        // Assert
+        assert!(shed)
+    }
+
+    #[test]
+    fn fails_to_admit_prediction_when_in_flight_limit_is_reached() {
+        // Arrange
+        let limiter = InFlightLimiter::new(Some(1), None);
+
+        // Act
+        let permit = limiter.admit(HTTP, "predict");
+        let rejected = limiter.admit(HTTP, "predict");
+        drop(permit);
+        let readmitted = limiter.admit(HTTP, "predict");
+
+        // Assert
+        assert!(rejected.is_none());
+        assert!(readmitted.is_some());
+    }
+
+    #[tokio::test]
+    async fn fails_to_wait_for_prediction_when_queue_wait_is_exceeded() {
+        // Arrange
+        let limiter = InFlightLimiter::new(None, Some(Duration::from_millis(10)));
+        let ticket = QueueTicket::default();
+        let (_tx, rx) = oneshot::channel::<()>();
+
+        // Act
+        let result = limiter.wait(rx, &ticket, HTTP, "predict").await;
+
+        // Assert
+        assert!(result.is_err());
+        // the worker must skip the withdrawn prediction
+        assert!(!ticket.claim());
+    }
+
+    #[tokio::test]
+    async fn successfully_waits_for_prediction_which_a_worker_has_claimed() {
+        // Arrange
+        let limiter = InFlightLimiter::new(None, Some(Duration::from_millis(10)));
+        let ticket = QueueTicket::default();
+        let (tx, rx) = oneshot::channel();
+        assert!(ticket.claim());
+
+        // Act
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            tx.send(1).unwrap();
+        });
+        let result = limiter.wait(rx, &ticket, HTTP, "predict").await;
+
+        // Assert
+        assert_eq!(result.unwrap().unwrap(), 1)
+    }
+}
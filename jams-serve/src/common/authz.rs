@@ -0,0 +1,633 @@
+use crate::common::metrics::AUTHZ_DECISIONS_TOTAL;
+use crate::common::signing::read_hex_key;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+#[cfg(feature = "cedar")]
+use cedar_policy::{
+    Authorizer, Context, Decision, Entities, EntityId, EntityTypeName, EntityUid, PolicySet,
+    Request, RestrictedExpression,
+};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+#[cfg(feature = "cedar")]
+use std::str::FromStr;
+#[cfg(feature = "opa")]
+use std::time::Duration;
+
+/// The default time (in milliseconds) allowed for a query to the OPA server.
+pub const DEFAULT_OPA_TIMEOUT_MS: u64 = 500;
+
+/// Name of the HTTP header and gRPC metadata key which carries the bearer token of the principal.
+pub const AUTHORIZATION_HEADER: &str = "authorization";
+
+/// Environment variable holding the key of the bearer tokens if `token_key_env` is not set.
+const DEFAULT_TOKEN_KEY_ENV: &str = "JAMS_AUTHZ_TOKEN_KEY";
+
+/// The only signing algorithm accepted for the bearer tokens.
+const TOKEN_ALGORITHM: &str = "HS256";
+
+/// Namespace of the entity types of the Cedar policies, i.e. `Jams::Principal`, `Jams::Action`, `Jams::Model`
+/// and `Jams::Server`.
+#[cfg(feature = "cedar")]
+const CEDAR_NAMESPACE: &str = "Jams";
+
+/// ID of the `Jams::Server` resource of the calls which are not made to a model, e.g. listing the models.
+#[cfg(feature = "cedar")]
+const CEDAR_SERVER_ID: &str = "jams";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Configuration of the authorization of the predict and admin calls. Every call is authorized by evaluating
+/// the principal, the action, the model and the namespace of the model, either against embedded Cedar policies
+/// or by querying an OPA server. The authorization can only be configured using the config file, and the Cedar
+/// policies and the OPA server need the `cedar` and `opa` features.
+///
+/// The principal is the `sub` claim of a JWT signed with HMAC-SHA256, which is sent as a bearer token in the
+/// `authorization` header (HTTP) or metadata key (gRPC) and must carry an `exp` claim. The key is read from an
+/// environment variable or a file, shared with the issuer of the tokens and must be at least 64 hex characters.
+/// Calls without a valid token are rejected before they are authorized.
+///
+/// # Example
+/// ```toml
+/// [config.authorization]
+/// token_key_file = "/run/secrets/jams-authz-token-key"
+/// cedar_policies = "policies.cedar"
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AuthorizationConfig {
+    /// The environment variable holding the key of the bearer tokens. Defaults to `JAMS_AUTHZ_TOKEN_KEY`.
+    #[serde(default = "default_token_key_env")]
+    pub token_key_env: String,
+
+    /// An optional file holding the key of the bearer tokens, which takes precedence over `token_key_env`.
+    pub token_key_file: Option<String>,
+
+    /// An optional path to a file of Cedar policies.
+    pub cedar_policies: Option<String>,
+
+    /// An optional URL of an OPA rule returning whether a call is allowed, e.g.
+    /// `http://localhost:8181/v1/data/jams/allow`.
+    pub opa_url: Option<String>,
+
+    /// The time (in milliseconds) allowed for a query to the OPA server. Defaults to 500.
+    #[serde(default = "default_opa_timeout_ms")]
+    pub opa_timeout_ms: u64,
+}
+
+fn default_token_key_env() -> String {
+    DEFAULT_TOKEN_KEY_ENV.to_string()
+}
+
+fn default_opa_timeout_ms() -> u64 {
+    DEFAULT_OPA_TIMEOUT_MS
+}
+
+/// An action authorized by the policies.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Predict, embed, look up or compare the predictions of a model.
+    Predict,
+    /// Read the models, the attached model stores and the version of the server.
+    Read,
+    /// Add, update, delete or label models and attach or detach model stores.
+    Write,
+}
+
+impl Action {
+    /// Returns the name of the action, i.e. the ID of its `Jams::Action` entity and its OPA input.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Predict => "predict",
+            Action::Read => "read",
+            Action::Write => "write",
+        }
+    }
+}
+
+/// A call to authorize, which is the OPA input.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct AuthzRequest {
+    /// The principal making the call.
+    pub principal: String,
+    /// The action of the call.
+    pub action: Action,
+    /// The model the call is made to, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// The namespace of the model, if it belongs to one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
+/// The engine which evaluates the policies.
+pub enum PolicyEngine {
+    /// Cedar policies evaluated in process.
+    #[cfg(feature = "cedar")]
+    Cedar(PolicySet),
+    /// An OPA rule evaluated by an OPA server.
+    #[cfg(feature = "opa")]
+    Opa {
+        client: reqwest::Client,
+        url: String,
+    },
+}
+
+/// The body of the response of the OPA data API. The result is undefined if no rule matched the input.
+#[cfg(feature = "opa")]
+#[derive(Deserialize)]
+struct OpaResponse {
+    result: Option<bool>,
+}
+
+/// The header of a bearer token.
+#[derive(Deserialize)]
+struct TokenHeader {
+    alg: String,
+}
+
+/// The claims of a bearer token.
+#[derive(Deserialize)]
+struct TokenClaims {
+    /// The principal.
+    sub: String,
+    /// The time the token expires at, in seconds since the Unix epoch.
+    exp: i64,
+}
+
+/// Authenticates and authorizes the predict and admin calls made to the server.
+pub struct Authorization {
+    token_key: Vec<u8>,
+    engine: PolicyEngine,
+}
+
+impl Authorization {
+    /// Reads the key of the bearer tokens and loads the Cedar policies or builds the client of the OPA server.
+    ///
+    /// # Errors
+    /// Returns an error if the key of the bearer tokens is not set or invalid, if neither or both of
+    /// `cedar_policies` and `opa_url` are set, if the server was built without the feature of the engine, or if
+    /// the policies cannot be read or parsed.
+    pub fn new(config: AuthorizationConfig) -> anyhow::Result<Self> {
+        let token_key = read_hex_key(
+            "Bearer token key",
+            config.token_key_file.as_deref(),
+            config.token_key_env.as_str(),
+        )?;
+        let engine = match (config.cedar_policies, config.opa_url) {
+            (Some(path), None) => load_cedar_policies(path.as_str())?,
+            (None, Some(url)) => build_opa_client(url, config.opa_timeout_ms)?,
+            _ => anyhow::bail!(
+                "Authorization requires exactly one of cedar_policies and opa_url to be set ❌"
+            ),
+        };
+        Ok(Authorization::with_engine(token_key, engine))
+    }
+
+    /// Creates an authorization which verifies the bearer tokens with the given key and evaluates the given
+    /// engine.
+    pub fn with_engine(token_key: Vec<u8>, engine: PolicyEngine) -> Self {
+        Authorization { token_key, engine }
+    }
+
+    /// Authenticates a call by verifying its bearer token.
+    ///
+    /// # Arguments
+    ///
+    /// * `authorization` - The value of the `authorization` header or metadata key, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The principal, i.e. the `sub` claim of the token.
+    /// * `Err(anyhow::Error)` - If the token is missing, malformed, not signed with the key or expired.
+    pub fn authenticate(&self, authorization: Option<&str>) -> anyhow::Result<String> {
+        let token = match authorization.and_then(|value| value.strip_prefix("Bearer ")) {
+            Some(token) => token.trim(),
+            None => anyhow::bail!("Missing bearer token ❌"),
+        };
+        let result = verify_token(
+            self.token_key.as_slice(),
+            token,
+            chrono::Utc::now().timestamp(),
+        );
+        if let Err(e) = &result {
+            tracing::warn!("Rejected bearer token ⚠️: {}", e);
+        }
+        result
+    }
+
+    /// Evaluates whether a call is allowed. Every decision is counted by the `jams_authz_decisions_total` metric.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` - If the call is allowed.
+    /// * `Ok(false)` - If the call is denied.
+    /// * `Err(anyhow::Error)` - If the policies could not be evaluated, e.g. because the OPA server is unreachable.
+    pub async fn authorize(&self, request: &AuthzRequest) -> anyhow::Result<bool> {
+        let decision: anyhow::Result<bool> = match &self.engine {
+            #[cfg(feature = "cedar")]
+            PolicyEngine::Cedar(policies) => authorize_cedar(policies, request),
+            #[cfg(feature = "opa")]
+            PolicyEngine::Opa { client, url } => authorize_opa(client, url.as_str(), request).await,
+            #[cfg(not(any(feature = "cedar", feature = "opa")))]
+            _ => unreachable!("no policy engine is built"),
+        };
+        let outcome = match &decision {
+            Ok(true) => "allow",
+            Ok(false) => "deny",
+            Err(_) => "error",
+        };
+        AUTHZ_DECISIONS_TOTAL
+            .with_label_values(&[request.action.as_str(), outcome])
+            .inc();
+        match &decision {
+            Ok(true) => {}
+            Ok(false) => tracing::warn!(
+                "Denied {} of {} to {} ⚠️",
+                request.action.as_str(),
+                request.model.as_deref().unwrap_or("the server"),
+                request.principal
+            ),
+            Err(e) => tracing::error!("Failed to authorize call ❌: {}", e),
+        }
+        decision
+    }
+}
+
+/// Issues a bearer token for a principal, i.e. a JWT signed with HMAC-SHA256.
+///
+/// # Arguments
+///
+/// * `key` - The key of the bearer tokens, decoded from hex.
+/// * `principal` - The principal, which becomes the `sub` claim.
+/// * `expires_at` - The time the token expires at, in seconds since the Unix epoch.
+pub fn issue_token(key: &[u8], principal: &str, expires_at: i64) -> String {
+    let header = serde_json::json!({ "alg": TOKEN_ALGORITHM, "typ": "JWT" });
+    let claims = serde_json::json!({ "sub": principal, "exp": expires_at });
+    let header = URL_SAFE_NO_PAD.encode(header.to_string());
+    let claims = URL_SAFE_NO_PAD.encode(claims.to_string());
+    let signing_input = format!("{}.{}", header, claims);
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(signing_input.as_bytes());
+    format!(
+        "{}.{}",
+        signing_input,
+        URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes())
+    )
+}
+
+/// Verifies a bearer token and returns its principal.
+fn verify_token(key: &[u8], token: &str, now: i64) -> anyhow::Result<String> {
+    let (signing_input, signature) = match token.rsplit_once('.') {
+        Some(parts) => parts,
+        None => anyhow::bail!("Malformed bearer token ❌"),
+    };
+    let (header, claims) = match signing_input.split_once('.') {
+        Some(parts) => parts,
+        None => anyhow::bail!("Malformed bearer token ❌"),
+    };
+    let header: TokenHeader = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header)?)?;
+    if header.alg != TOKEN_ALGORITHM {
+        anyhow::bail!(
+            "Bearer token is signed with {}, expected {} ❌",
+            header.alg,
+            TOKEN_ALGORITHM
+        )
+    }
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(signing_input.as_bytes());
+    if mac
+        .verify_slice(&URL_SAFE_NO_PAD.decode(signature)?)
+        .is_err()
+    {
+        anyhow::bail!("Bearer token has an invalid signature ❌")
+    }
+    let claims: TokenClaims = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(claims)?)?;
+    if claims.exp <= now {
+        anyhow::bail!("Bearer token of {} has expired ❌", claims.sub)
+    }
+    if claims.sub.is_empty() {
+        anyhow::bail!("Bearer token has no principal ❌")
+    }
+    Ok(claims.sub)
+}
+
+/// Reads and parses the Cedar policies of a file.
+#[cfg(feature = "cedar")]
+fn load_cedar_policies(path: &str) -> anyhow::Result<PolicyEngine> {
+    let policies = std::fs::read_to_string(path).map_err(|e| {
+        tracing::error!("Failed to read Cedar policies from {} ❌: {}", path, e);
+        anyhow::anyhow!("Failed to read Cedar policies from {} ❌: {}", path, e)
+    })?;
+    tracing::info!("Calls are authorized by the Cedar policies of {} 🔐", path);
+    parse_cedar_policies(policies.as_str())
+}
+
+#[cfg(not(feature = "cedar"))]
+fn load_cedar_policies(_path: &str) -> anyhow::Result<PolicyEngine> {
+    anyhow::bail!("Cedar policies need jams-serve to be built with the cedar feature ❌")
+}
+
+/// Builds the client of the OPA server.
+#[cfg(feature = "opa")]
+fn build_opa_client(url: String, timeout_ms: u64) -> anyhow::Result<PolicyEngine> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(timeout_ms))
+        .build()?;
+    tracing::info!("Calls are authorized by the OPA rule at {} 🔐", url);
+    Ok(PolicyEngine::Opa { client, url })
+}
+
+#[cfg(not(feature = "opa"))]
+fn build_opa_client(_url: String, _timeout_ms: u64) -> anyhow::Result<PolicyEngine> {
+    anyhow::bail!("OPA rules need jams-serve to be built with the opa feature ❌")
+}
+
+/// Parses Cedar policies.
+///
+/// # Errors
+/// Returns an error if the policies are invalid.
+#[cfg(feature = "cedar")]
+pub fn parse_cedar_policies(policies: &str) -> anyhow::Result<PolicyEngine> {
+    match PolicySet::from_str(policies) {
+        Ok(policies) => Ok(PolicyEngine::Cedar(policies)),
+        Err(e) => {
+            tracing::error!("Failed to parse Cedar policies ❌: {}", e);
+            anyhow::bail!("Failed to parse Cedar policies ❌: {}", e)
+        }
+    }
+}
+
+/// Builds the UID of a `Jams::<entity_type>` entity.
+#[cfg(feature = "cedar")]
+fn cedar_uid(entity_type: &str, id: &str) -> anyhow::Result<EntityUid> {
+    let entity_type =
+        EntityTypeName::from_str(format!("{}::{}", CEDAR_NAMESPACE, entity_type).as_str())
+            .map_err(|e| anyhow::anyhow!("Invalid entity type {} ❌: {}", entity_type, e))?;
+    let id = EntityId::from_str(id)
+        .map_err(|e| anyhow::anyhow!("Invalid entity ID {} ❌: {}", id, e))?;
+    Ok(EntityUid::from_type_name_and_id(entity_type, id))
+}
+
+/// Evaluates a call against Cedar policies. The principal, action and resource are `Jams::Principal`,
+/// `Jams::Action` and `Jams::Model` entities, or `Jams::Server::"jams"` for the calls which are not made to a
+/// model. The namespace of the model is `context.namespace`, which is empty outside of a namespace.
+#[cfg(feature = "cedar")]
+fn authorize_cedar(policies: &PolicySet, request: &AuthzRequest) -> anyhow::Result<bool> {
+    let principal = cedar_uid("Principal", request.principal.as_str())?;
+    let action = cedar_uid("Action", request.action.as_str())?;
+    let resource = match &request.model {
+        Some(model) => cedar_uid("Model", model.as_str())?,
+        None => cedar_uid("Server", CEDAR_SERVER_ID)?,
+    };
+    let context = Context::from_pairs([(
+        "namespace".to_string(),
+        RestrictedExpression::new_string(request.namespace.clone().unwrap_or_default()),
+    )]);
+    let response = Authorizer::new().is_authorized(
+        &Request::new(Some(principal), Some(action), Some(resource), context),
+        policies,
+        &Entities::empty(),
+    );
+    Ok(response.decision() == Decision::Allow)
+}
+
+/// Queries an OPA rule with the call as its input. A call is denied if the rule is undefined for it.
+#[cfg(feature = "opa")]
+async fn authorize_opa(
+    client: &reqwest::Client,
+    url: &str,
+    request: &AuthzRequest,
+) -> anyhow::Result<bool> {
+    let response = client
+        .post(url)
+        .json(&serde_json::json!({ "input": request }))
+        .send()
+        .await?
+        .error_for_status()?;
+    let response: OpaResponse = response.json().await?;
+    Ok(response.result.unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "opa")]
+    use axum::routing::post;
+    #[cfg(feature = "opa")]
+    use axum::{Json, Router};
+    #[cfg(feature = "opa")]
+    use tokio::net::TcpListener;
+
+    const KEY: &[u8] = &[7; 32];
+
+    #[cfg(feature = "cedar")]
+    const POLICIES: &str = r#"
+        permit(
+            principal == Jams::Principal::"team-a",
+            action == Jams::Action::"predict",
+            resource == Jams::Model::"titanic_model"
+        );
+        permit(principal == Jams::Principal::"team-b", action, resource)
+        when { context.namespace == "fraud" };
+    "#;
+
+    #[cfg(any(feature = "cedar", feature = "opa"))]
+    fn request(principal: &str, action: Action, model: Option<&str>) -> AuthzRequest {
+        AuthzRequest {
+            principal: principal.to_string(),
+            action,
+            model: model.map(|model| model.to_string()),
+            namespace: model
+                .filter(|model| model.starts_with("fraud_"))
+                .map(|_| "fraud".to_string()),
+        }
+    }
+
+    fn config(
+        token_key_file: &tempfile::NamedTempFile,
+        cedar_policies: Option<String>,
+        opa_url: Option<String>,
+    ) -> AuthorizationConfig {
+        AuthorizationConfig {
+            token_key_env: DEFAULT_TOKEN_KEY_ENV.to_string(),
+            token_key_file: Some(token_key_file.path().to_str().unwrap().to_string()),
+            cedar_policies,
+            opa_url,
+            opa_timeout_ms: DEFAULT_OPA_TIMEOUT_MS,
+        }
+    }
+
+    fn token_key_file() -> tempfile::NamedTempFile {
+        let token_key_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(token_key_file.path(), hex::encode(KEY)).unwrap();
+        token_key_file
+    }
+
+    #[test]
+    fn successfully_verify_bearer_token() {
+        // Arrange
+        let token = issue_token(KEY, "team-a", 2000);
+
+        // Act
+        let principal = verify_token(KEY, token.as_str(), 1000);
+
+        // Assert
+        assert_eq!(principal.unwrap(), "team-a");
+    }
+
+    #[test]
+    fn fails_to_verify_forged_expired_or_malformed_bearer_token() {
+        // Arrange
+        let token = issue_token(KEY, "team-a", 2000);
+        let (_, signature) = token.rsplit_once('.').unwrap();
+        let claims = URL_SAFE_NO_PAD.encode(r#"{"sub":"team-b","exp":2000}"#);
+        let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256"}"#);
+        let forged = format!("{}.{}.{}", header, claims, signature);
+        let unsigned = format!(
+            "{}.{}.",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"none"}"#),
+            claims
+        );
+
+        // Act
+        let other_key = verify_token(&[8; 32], token.as_str(), 1000);
+        let expired = verify_token(KEY, token.as_str(), 2000);
+        let forged = verify_token(KEY, forged.as_str(), 1000);
+        let unsigned = verify_token(KEY, unsigned.as_str(), 1000);
+        let malformed = verify_token(KEY, "team-a", 1000);
+
+        // Assert
+        assert!(other_key.is_err());
+        assert!(expired.is_err());
+        assert!(forged.is_err());
+        assert!(unsigned.is_err());
+        assert!(malformed.is_err());
+    }
+
+    #[cfg(feature = "cedar")]
+    #[tokio::test]
+    async fn successfully_authorize_calls_using_cedar_policies() {
+        // Arrange
+        let authorization =
+            Authorization::with_engine(KEY.to_vec(), parse_cedar_policies(POLICIES).unwrap());
+        let token = format!("Bearer {}", issue_token(KEY, "team-a", i64::MAX));
+
+        // Act
+        let principal = authorization.authenticate(Some(token.as_str())).unwrap();
+        let anonymous = authorization.authenticate(None);
+        let claimed = authorization.authenticate(Some("team-a"));
+        let allowed = authorization
+            .authorize(&request(
+                principal.as_str(),
+                Action::Predict,
+                Some("titanic_model"),
+            ))
+            .await
+            .unwrap();
+        let other_model = authorization
+            .authorize(&request("team-a", Action::Predict, Some("churn_model")))
+            .await
+            .unwrap();
+        let other_action = authorization
+            .authorize(&request("team-a", Action::Write, Some("titanic_model")))
+            .await
+            .unwrap();
+        let namespace = authorization
+            .authorize(&request("team-b", Action::Write, Some("fraud_scorer")))
+            .await
+            .unwrap();
+        let server = authorization
+            .authorize(&request("team-b", Action::Read, None))
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(anonymous.is_err());
+        assert!(claimed.is_err());
+        assert!(allowed);
+        assert!(!other_model);
+        assert!(!other_action);
+        assert!(namespace);
+        assert!(!server);
+    }
+
+    #[test]
+    fn fails_to_create_authorization_when_it_is_invalid() {
+        // Arrange
+        let token_key_file = token_key_file();
+
+        // Act
+        let no_engine = Authorization::new(config(&token_key_file, None, None));
+        let no_token_key = Authorization::new(AuthorizationConfig {
+            token_key_env: "JAMS_AUTHZ_TOKEN_KEY_MISSING".to_string(),
+            token_key_file: None,
+            ..config(&token_key_file, None, Some("http://localhost".to_string()))
+        });
+
+        // Assert
+        assert!(no_engine.is_err());
+        assert!(no_token_key.is_err());
+    }
+
+    #[cfg(feature = "cedar")]
+    #[test]
+    fn fails_to_parse_cedar_policies_when_they_are_invalid() {
+        // Act
+        let engine = parse_cedar_policies("permit(principal, action);");
+
+        // Assert
+        assert!(engine.is_err());
+    }
+
+    #[cfg(feature = "opa")]
+    #[tokio::test]
+    async fn successfully_authorize_calls_using_opa() {
+        // Arrange
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = Router::new().route(
+            "/v1/data/jams/allow",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                // the rule is undefined for the other principals
+                match body["input"]["principal"].as_str() {
+                    Some("team-a") => Json(serde_json::json!({
+                        "result": body["input"]["model"] == "titanic_model"
+                    })),
+                    _ => Json(serde_json::json!({})),
+                }
+            }),
+        );
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+        let token_key_file = token_key_file();
+        let authorization = Authorization::new(config(
+            &token_key_file,
+            None,
+            Some(format!("http://{}/v1/data/jams/allow", addr)),
+        ))
+        .unwrap();
+
+        // Act
+        let allowed = authorization
+            .authorize(&request("team-a", Action::Predict, Some("titanic_model")))
+            .await
+            .unwrap();
+        let denied = authorization
+            .authorize(&request("team-a", Action::Predict, Some("churn_model")))
+            .await
+            .unwrap();
+        let undefined = authorization
+            .authorize(&request("team-c", Action::Predict, Some("titanic_model")))
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(allowed);
+        assert!(!denied);
+        assert!(!undefined);
+    }
+}
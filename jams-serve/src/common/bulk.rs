@@ -0,0 +1,293 @@
+use crate::common::namespace::Namespaces;
+use jams_core::manager::Manager;
+use jams_core::model_store::labels::LabelSelector;
+use jams_core::model_store::storage::ModelName;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Maximum number of bulk operations whose progress is remembered. The oldest finished operation is forgotten when
+/// it is reached, while running operations are always remembered.
+pub const MAX_BULK_OPERATIONS: usize = 100;
+
+/// The action applied to every model selected by a bulk operation.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkAction {
+    /// The models are reloaded from the model store, like `PUT /api/v1/models`.
+    Refresh,
+    /// The models are deleted, like `DELETE /api/v1/models`.
+    Delete,
+}
+
+impl BulkAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BulkAction::Refresh => "refresh",
+            BulkAction::Delete => "delete",
+        }
+    }
+}
+
+/// A model for which a bulk operation failed.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct BulkFailure {
+    /// The name of the model.
+    pub model_name: ModelName,
+    /// The reason the action failed.
+    pub error: String,
+}
+
+/// The progress of a bulk operation.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct BulkProgress {
+    /// The ID of the operation, used to poll its progress.
+    pub id: String,
+    /// The action applied to the models.
+    pub action: BulkAction,
+    /// The number of selected models.
+    pub total: usize,
+    /// The number of models the action was applied to so far.
+    pub completed: usize,
+    /// The models for which the action succeeded.
+    pub succeeded: Vec<ModelName>,
+    /// The models for which the action failed.
+    pub failed: Vec<BulkFailure>,
+    /// Whether the action was applied to every selected model.
+    pub done: bool,
+}
+
+/// Applies an action to many models in the background, e.g. to refresh or delete the models of a team during a
+/// migration, and remembers the progress of the most recent operations.
+///
+/// The models of an operation are processed one at a time, so that refreshing hundreds of models does not load
+/// all of them into memory at once.
+#[derive(Clone, Default)]
+pub struct BulkOperations {
+    operations: Arc<Mutex<VecDeque<BulkProgress>>>,
+}
+
+impl BulkOperations {
+    /// Starts applying an action to the given models in the background.
+    ///
+    /// # Arguments
+    ///
+    /// * `manager` - The manager holding the models.
+    /// * `action` - The action to apply.
+    /// * `models` - The names of the models.
+    ///
+    /// # Returns
+    ///
+    /// The initial progress of the operation, whose `id` can be passed to `progress`.
+    pub fn start(
+        &self,
+        manager: Arc<Manager>,
+        action: BulkAction,
+        models: Vec<ModelName>,
+    ) -> BulkProgress {
+        let progress = BulkProgress {
+            id: Uuid::new_v4().to_string(),
+            action,
+            total: models.len(),
+            completed: 0,
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+            done: models.is_empty(),
+        };
+        tracing::info!(
+            "Starting bulk {} of {} models 📦",
+            action.as_str(),
+            models.len()
+        );
+        self.update(|operations| {
+            if operations.len() >= MAX_BULK_OPERATIONS {
+                if let Some(i) = operations.iter().position(|progress| progress.done) {
+                    operations.remove(i);
+                }
+            }
+            operations.push_back(progress.clone());
+        });
+
+        let operations = self.clone();
+        let id = progress.id.clone();
+        tokio::spawn(async move {
+            let total = models.len();
+            for (i, model_name) in models.into_iter().enumerate() {
+                let result = match action {
+                    BulkAction::Refresh => manager.update_model(model_name.clone()).await,
+                    BulkAction::Delete => manager.delete_model(model_name.clone()),
+                };
+                if let Err(e) = &result {
+                    tracing::warn!(
+                        "Failed to {} model {} in bulk ⚠️: {}",
+                        action.as_str(),
+                        model_name,
+                        e
+                    );
+                }
+                tracing::info!(
+                    "Bulk {} progress: {}/{} models 📦",
+                    action.as_str(),
+                    i + 1,
+                    total
+                );
+                operations.record(id.as_str(), model_name, result);
+            }
+        });
+
+        progress
+    }
+
+    /// Returns the progress of an operation, or `None` if it is unknown or was forgotten.
+    pub fn progress(&self, id: &str) -> Option<BulkProgress> {
+        match self.operations.lock() {
+            Ok(operations) => operations
+                .iter()
+                .find(|progress| progress.id == id)
+                .cloned(),
+            Err(_) => None,
+        }
+    }
+
+    fn record(&self, id: &str, model_name: ModelName, result: anyhow::Result<()>) {
+        self.update(|operations| {
+            if let Some(progress) = operations.iter_mut().find(|progress| progress.id == id) {
+                match result {
+                    Ok(_) => progress.succeeded.push(model_name),
+                    Err(e) => progress.failed.push(BulkFailure {
+                        model_name,
+                        error: e.to_string(),
+                    }),
+                }
+                progress.completed += 1;
+                progress.done = progress.completed == progress.total;
+            }
+        });
+    }
+
+    fn update(&self, update: impl FnOnce(&mut VecDeque<BulkProgress>)) {
+        match self.operations.lock() {
+            Ok(mut operations) => update(&mut operations),
+            Err(e) => tracing::error!("Failed to update bulk operations ❌: {}", e),
+        }
+    }
+}
+
+/// Selects the loaded models matching a label selector and a namespace.
+///
+/// # Arguments
+///
+/// * `manager` - The manager holding the models.
+/// * `namespaces` - The namespaces of the server.
+/// * `selector` - An optional label selector which the models must match.
+/// * `namespace` - An optional namespace which the models must belong to.
+///
+/// # Returns
+///
+/// * `Ok(Vec<ModelName>)` - The names of the selected models.
+/// * `Err(anyhow::Error)` - If the models could not be listed.
+pub fn select_models(
+    manager: &Manager,
+    namespaces: &Namespaces,
+    selector: Option<&LabelSelector>,
+    namespace: Option<&str>,
+) -> anyhow::Result<Vec<ModelName>> {
+    Ok(manager
+        .get_models()?
+        .into_iter()
+        .filter(|model| match selector {
+            Some(selector) => selector.matches(&model.labels),
+            None => true,
+        })
+        .filter(|model| {
+            namespace.is_none() || namespaces.namespace_of(model.name.as_str()) == namespace
+        })
+        .map(|model| model.name)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jams_core::manager::ManagerBuilder;
+    use jams_core::model_store::local::filesystem::LocalModelStore;
+    use jams_core::model_store::ModelStore;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn successfully_report_progress_of_bulk_refresh() {
+        // Arrange
+        let model_store = LocalModelStore::new("tests/model_store".to_string())
+            .await
+            .expect("Failed to create model store ❌");
+        let manager = Arc::new(
+            ManagerBuilder::new(Arc::new(ModelStore::Local(model_store)))
+                .build()
+                .expect("Failed to initialize manager ❌"),
+        );
+        let operations = BulkOperations::default();
+
+        // Act
+        let started = operations.start(
+            manager,
+            BulkAction::Refresh,
+            vec![
+                "titanic_model".to_string(),
+                "model_which_does_not_exist".to_string(),
+            ],
+        );
+        let mut progress = operations.progress(started.id.as_str()).unwrap();
+        for _ in 0..100 {
+            if progress.done {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            progress = operations.progress(started.id.as_str()).unwrap();
+        }
+
+        // Assert
+        assert_eq!(started.total, 2);
+        assert!(!started.done);
+        assert!(progress.done);
+        assert_eq!(progress.completed, 2);
+        assert_eq!(progress.succeeded, vec!["titanic_model".to_string()]);
+        assert_eq!(progress.failed[0].model_name, "model_which_does_not_exist");
+        assert!(operations.progress("unknown").is_none());
+    }
+
+    #[tokio::test]
+    async fn successfully_remember_running_operations_when_limit_is_reached() {
+        // Arrange
+        let model_store = LocalModelStore::new("tests/model_store".to_string())
+            .await
+            .expect("Failed to create model store ❌");
+        let manager = Arc::new(
+            ManagerBuilder::new(Arc::new(ModelStore::Local(model_store)))
+                .build()
+                .expect("Failed to initialize manager ❌"),
+        );
+        let operations = BulkOperations::default();
+        let running = BulkProgress {
+            id: "running".to_string(),
+            action: BulkAction::Refresh,
+            total: 1,
+            completed: 0,
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+            done: false,
+        };
+        operations.update(|queue| queue.push_back(running.clone()));
+        let finished: Vec<BulkProgress> = (1..MAX_BULK_OPERATIONS)
+            .map(|_| operations.start(Arc::clone(&manager), BulkAction::Refresh, Vec::new()))
+            .collect();
+
+        // Act
+        let started = operations.start(manager, BulkAction::Refresh, Vec::new());
+
+        // Assert
+        assert_eq!(operations.progress("running"), Some(running));
+        assert!(operations.progress(finished[0].id.as_str()).is_none());
+        assert!(operations.progress(started.id.as_str()).is_some());
+    }
+}
@@ -0,0 +1,409 @@
+use crate::common::metrics::DEDUP_REQUESTS_TOTAL;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// The default maximum number of recent predictions remembered by the deduplicator.
+pub const DEFAULT_DEDUP_MAX_ENTRIES: usize = 10000;
+
+/// Configuration of the deduplication of prediction requests, to quantify and squash the retry storms of flaky
+/// clients. A request is a duplicate if a request for the same model with the same input and prediction options
+/// was received less than the window of the model ago. The deduplication can only be configured using the
+/// config file.
+///
+/// # Example
+/// ```toml
+/// [config.deduplication]
+/// window_ms = 1000
+/// mode = "squash"
+/// max_entries = 10000
+/// models = { titanic_model = 500, fraud_scorer = 0 }
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DedupConfig {
+    /// The window (in milliseconds) of every model which is not listed in `models`. Defaults to 0, which only
+    /// deduplicates the requests of the listed models.
+    #[serde(default)]
+    pub window_ms: u64,
+
+    /// The window (in milliseconds) of each model, overriding `window_ms`. A window of 0 disables the
+    /// deduplication of the model.
+    #[serde(default)]
+    pub models: BTreeMap<String, u64>,
+
+    /// Whether duplicates are only counted or also served the prediction of the first request.
+    #[serde(default)]
+    pub mode: DedupMode,
+
+    /// The maximum number of recent predictions remembered. The oldest is forgotten when it is reached.
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+}
+
+fn default_max_entries() -> usize {
+    DEFAULT_DEDUP_MAX_ENTRIES
+}
+
+/// What happens to the duplicates of a prediction request.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupMode {
+    /// Duplicates are predicted as usual and counted by the `jams_dedup_requests_total` metric.
+    #[default]
+    Observe,
+    /// Duplicates are served the prediction of the first request, waiting for it if it is still in flight.
+    /// If the first request fails, its duplicates are predicted as usual.
+    Squash,
+}
+
+/// Outcome of a prediction request, used as the label of the `jams_dedup_requests_total` metric.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DedupOutcome {
+    /// The first request for the model and payload in the window.
+    Unique,
+    /// A duplicate which was predicted.
+    Duplicate,
+    /// A duplicate which was served the prediction of the first request.
+    Squashed,
+}
+
+impl DedupOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DedupOutcome::Unique => "unique",
+            DedupOutcome::Duplicate => "duplicate",
+            DedupOutcome::Squashed => "squashed",
+        }
+    }
+
+    fn record(&self, model_name: &str) {
+        DEDUP_REQUESTS_TOTAL
+            .with_label_values(&[model_name, self.as_str()])
+            .inc();
+    }
+}
+
+/// The model name and the SHA-256 digest of the payload of a prediction request.
+type DedupKey = (String, [u8; 32]);
+
+/// A recent prediction request.
+struct Entry {
+    id: u64,
+    expires_at: Instant,
+    /// Holds the output of the prediction once it is served.
+    output: watch::Sender<Option<String>>,
+}
+
+#[derive(Default)]
+struct Entries {
+    requests: HashMap<DedupKey, Entry>,
+    order: VecDeque<(DedupKey, u64)>,
+}
+
+impl Entries {
+    /// Forgets the oldest requests which expired, or which do not fit in `max_entries`.
+    fn evict(&mut self, now: Instant, max_entries: usize) {
+        while let Some((key, id)) = self.order.front() {
+            let evict = match self.requests.get(key) {
+                Some(entry) if entry.id == *id => {
+                    entry.expires_at <= now || self.requests.len() >= max_entries
+                }
+                _ => true,
+            };
+            if !evict {
+                break;
+            }
+            if let Some((key, id)) = self.order.pop_front() {
+                if self.requests.get(&key).is_some_and(|entry| entry.id == id) {
+                    self.requests.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+/// Detects the duplicates of recent prediction requests, by model and hash of their payload.
+pub struct Deduplicator {
+    config: DedupConfig,
+    entries: Mutex<Entries>,
+    next_id: AtomicU64,
+}
+
+/// The result of looking up a prediction request in the deduplicator.
+pub enum Lookup<'a> {
+    /// The request must be predicted. The lease records the outcome of the prediction.
+    Predict(DedupLease<'a>),
+    /// The request is a duplicate, served the output of the prediction of the first request.
+    Duplicate(String),
+}
+
+/// Records the prediction of a request looked up in the deduplicator. A request whose lease is dropped without
+/// being completed failed, and its duplicates waiting for it are predicted as usual.
+pub struct DedupLease<'a> {
+    deduplicator: &'a Deduplicator,
+    model_name: String,
+    /// The outcome of the request, which is not counted if the requests of the model are not deduplicated.
+    outcome: Option<DedupOutcome>,
+    /// The request which this lease serves the duplicates of, if it is the first request.
+    first: Option<(DedupKey, u64)>,
+    completed: bool,
+}
+
+impl DedupLease<'_> {
+    /// Records the output of the prediction, which is served to the duplicates of the request in the squash mode.
+    pub fn complete(mut self, output: &str) {
+        self.completed = true;
+        if let Some(outcome) = self.outcome {
+            outcome.record(self.model_name.as_str());
+        }
+        if let Some((key, id)) = &self.first {
+            if self.deduplicator.config.mode == DedupMode::Squash {
+                if let Ok(entries) = self.deduplicator.entries.lock() {
+                    if let Some(entry) = entries.requests.get(key).filter(|entry| entry.id == *id) {
+                        entry.output.send_replace(Some(output.to_string()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for DedupLease<'_> {
+    fn drop(&mut self) {
+        if self.completed {
+            return;
+        }
+        // forget the failed request, which wakes up its duplicates waiting for it
+        if let Some((key, id)) = &self.first {
+            if let Ok(mut entries) = self.deduplicator.entries.lock() {
+                if entries
+                    .requests
+                    .get(key)
+                    .is_some_and(|entry| entry.id == *id)
+                {
+                    entries.requests.remove(key);
+                }
+            }
+        }
+    }
+}
+
+impl Deduplicator {
+    /// Creates a new deduplicator.
+    pub fn new(config: DedupConfig) -> Self {
+        Deduplicator {
+            config,
+            entries: Mutex::new(Entries::default()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the deduplication window of a model, which is zero if its requests are not deduplicated.
+    pub fn window_of(&self, model_name: &str) -> Duration {
+        let window_ms = self
+            .config
+            .models
+            .get(model_name)
+            .copied()
+            .unwrap_or(self.config.window_ms);
+        Duration::from_millis(window_ms)
+    }
+
+    /// Looks up a prediction request among the recent requests.
+    ///
+    /// In the squash mode, a duplicate of a request which is still in flight waits for its prediction.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - The name of the model.
+    /// * `input` - The model input as a JSON string.
+    /// * `predict_options` - The prediction options as a JSON string.
+    ///
+    /// # Returns
+    ///
+    /// * `Lookup::Predict` - If the request must be predicted.
+    /// * `Lookup::Duplicate` - If the request is a duplicate served the prediction of the first request.
+    pub async fn lookup<'a>(
+        &'a self,
+        model_name: &str,
+        input: &str,
+        predict_options: &str,
+    ) -> Lookup<'a> {
+        let mut lease = DedupLease {
+            deduplicator: self,
+            model_name: model_name.to_string(),
+            outcome: None,
+            first: None,
+            completed: false,
+        };
+        let window = self.window_of(model_name);
+        if window.is_zero() || self.config.max_entries == 0 {
+            return Lookup::Predict(lease);
+        }
+
+        let key: DedupKey = (model_name.to_string(), payload_hash(input, predict_options));
+        let now = Instant::now();
+        let mut output = {
+            let mut entries = match self.entries.lock() {
+                Ok(entries) => entries,
+                Err(_) => return Lookup::Predict(lease),
+            };
+            entries.evict(now, self.config.max_entries);
+            match entries.requests.get(&key) {
+                Some(entry) if entry.expires_at > now => entry.output.subscribe(),
+                _ => {
+                    let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                    let (output, _) = watch::channel(None);
+                    entries.requests.insert(
+                        key.clone(),
+                        Entry {
+                            id,
+                            expires_at: now + window,
+                            output,
+                        },
+                    );
+                    entries.order.push_back((key.clone(), id));
+                    lease.outcome = Some(DedupOutcome::Unique);
+                    lease.first = Some((key, id));
+                    return Lookup::Predict(lease);
+                }
+            }
+        };
+
+        lease.outcome = Some(DedupOutcome::Duplicate);
+        if self.config.mode == DedupMode::Observe {
+            return Lookup::Predict(lease);
+        }
+        let served = match output.wait_for(|output| output.is_some()).await {
+            Ok(served) => served.clone(),
+            Err(_) => None,
+        };
+        match served {
+            Some(served) => {
+                DedupOutcome::Squashed.record(model_name);
+                Lookup::Duplicate(served)
+            }
+            // the first request failed
+            None => Lookup::Predict(lease),
+        }
+    }
+}
+
+/// Returns the SHA-256 digest of the payload of a prediction request. The input is prefixed with its length, so
+/// that no two payloads are hashed from the same bytes.
+fn payload_hash(input: &str, predict_options: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update((input.len() as u64).to_le_bytes());
+    hasher.update(input.as_bytes());
+    hasher.update(predict_options.as_bytes());
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = r#"{"age": [22.0]}"#;
+
+    fn deduplicator(mode: DedupMode, window_ms: u64) -> Deduplicator {
+        Deduplicator::new(DedupConfig {
+            window_ms,
+            models: BTreeMap::from([("untracked_model".to_string(), 0)]),
+            mode,
+            max_entries: DEFAULT_DEDUP_MAX_ENTRIES,
+        })
+    }
+
+    fn requests(model_name: &str, outcome: &str) -> u64 {
+        DEDUP_REQUESTS_TOTAL
+            .with_label_values(&[model_name, outcome])
+            .get()
+    }
+
+    #[tokio::test]
+    async fn successfully_squash_duplicate_requests() {
+        // Arrange
+        let deduplicator = deduplicator(DedupMode::Squash, 60000);
+
+        // Act
+        let first = deduplicator.lookup("squash_model", INPUT, "").await;
+        let (duplicate, _) = tokio::join!(deduplicator.lookup("squash_model", INPUT, ""), async {
+            // the duplicate waits for the first request which is still in flight
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            match first {
+                Lookup::Predict(lease) => lease.complete(r#"{"predictions": [[0.5]]}"#),
+                Lookup::Duplicate(_) => panic!("first request is not a duplicate"),
+            }
+        });
+
+        // Assert
+        match duplicate {
+            Lookup::Duplicate(output) => assert_eq!(output, r#"{"predictions": [[0.5]]}"#),
+            Lookup::Predict(_) => panic!("duplicate is not squashed"),
+        }
+        assert_eq!(requests("squash_model", "unique"), 1);
+        assert_eq!(requests("squash_model", "squashed"), 1);
+    }
+
+    #[tokio::test]
+    async fn successfully_count_duplicate_requests_without_squashing_them() {
+        // Arrange
+        let deduplicator = deduplicator(DedupMode::Observe, 60000);
+
+        // Act
+        for _ in 0..3 {
+            match deduplicator.lookup("observe_model", INPUT, "").await {
+                Lookup::Predict(lease) => lease.complete("{}"),
+                Lookup::Duplicate(_) => panic!("duplicates are not squashed"),
+            }
+        }
+        let other_options = deduplicator
+            .lookup("observe_model", INPUT, r#"{"mode": "raw"}"#)
+            .await;
+        let untracked = deduplicator.lookup("untracked_model", INPUT, "").await;
+
+        // Assert
+        assert!(matches!(other_options, Lookup::Predict(_)));
+        assert!(matches!(untracked, Lookup::Predict(_)));
+        assert_eq!(requests("observe_model", "unique"), 1);
+        assert_eq!(requests("observe_model", "duplicate"), 2);
+        assert_eq!(requests("untracked_model", "unique"), 0);
+    }
+
+    #[tokio::test]
+    async fn successfully_predict_duplicate_when_first_request_fails_or_expires() {
+        // Arrange
+        let deduplicator = deduplicator(DedupMode::Squash, 20);
+
+        // Act
+        let first = deduplicator.lookup("failed_model", INPUT, "").await;
+        drop(first);
+        let retried = deduplicator.lookup("failed_model", INPUT, "").await;
+        match retried {
+            Lookup::Predict(lease) => lease.complete("{}"),
+            Lookup::Duplicate(_) => panic!("the first request failed"),
+        }
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let expired = deduplicator.lookup("failed_model", INPUT, "").await;
+
+        // Assert
+        assert!(matches!(expired, Lookup::Predict(_)));
+        assert_eq!(requests("failed_model", "squashed"), 0);
+    }
+
+    #[test]
+    fn successfully_hash_payloads_which_only_differ_in_where_the_input_ends() {
+        // Act
+        let first = payload_hash(r#"{"age": [22.0]}"#, "{}");
+        let second = payload_hash(r#"{"age": [22.0]}{"#, "}");
+
+        // Assert
+        assert_ne!(first, second);
+        assert_eq!(first, payload_hash(r#"{"age": [22.0]}"#, "{}"));
+    }
+}
@@ -0,0 +1,638 @@
+use jams_core::model::bundle::FeatureType;
+use redis::aio::ConnectionManager;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use serde_json::{Number, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Formatter;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Maximum number of looked up entities cached per model. The cache is cleared once it is full.
+const MAX_CACHED_ENTITIES: usize = 100_000;
+
+/// The default time (in milliseconds) allowed for looking up the features of a request.
+pub const DEFAULT_LOOKUP_TIMEOUT_MS: u64 = 100;
+
+/// Configuration of the feature store used to enrich requests with features looked up by entity ID.
+/// The feature store can only be configured using the config file.
+///
+/// # Example
+/// ```toml
+/// [config.feature_store]
+/// redis_url = "redis://localhost:6379"
+/// lookup_timeout_ms = 100
+///
+/// [[config.feature_store.models]]
+/// model_name = "titanic_model"
+/// entity_key = "passenger_id"
+/// key_prefix = "passenger:"
+/// features = { age = "float", deck = "string" }
+/// ttl = 60
+/// join = "left"
+/// defaults = { deck = "Unknown" }
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct FeatureStoreConfig {
+    /// URL of the Redis server, e.g. `redis://localhost:6379`.
+    pub redis_url: String,
+
+    /// The time (in milliseconds) allowed for looking up the features of a request. Lookups which take
+    /// longer fail the request as unavailable. Defaults to 100.
+    #[serde(default = "default_lookup_timeout_ms")]
+    pub lookup_timeout_ms: u64,
+
+    /// Models whose requests are enriched with features from the feature store.
+    pub models: Vec<EnrichmentConfig>,
+}
+
+fn default_lookup_timeout_ms() -> u64 {
+    DEFAULT_LOOKUP_TIMEOUT_MS
+}
+
+/// Configuration of the features looked up for the requests of a model.
+///
+/// Features of an entity are stored in a Redis hash at `<key_prefix><entity ID>` with one field per feature.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EnrichmentConfig {
+    /// Name of the model whose requests are enriched.
+    pub model_name: String,
+
+    /// Input feature holding the entity IDs. It is replaced by the looked up features before predicting.
+    pub entity_key: String,
+
+    /// Prefix of the Redis key of each entity.
+    #[serde(default)]
+    pub key_prefix: String,
+
+    /// Features to look up and their types. Redis stores every value as a string.
+    pub features: BTreeMap<String, FeatureType>,
+
+    /// An optional time (in seconds) for which looked up features are cached in memory.
+    ///
+    /// - `Some(u64)`: Entities looked up within the TTL are not fetched again.
+    /// - `None`: Features are fetched from the feature store for every request.
+    #[serde(default)]
+    pub ttl: Option<u64>,
+
+    /// How to handle entities or features which are missing from the feature store.
+    #[serde(default)]
+    pub join: JoinStrategy,
+
+    /// Values used for missing features when `join` is `left`.
+    #[serde(default)]
+    pub defaults: BTreeMap<String, Value>,
+}
+
+/// How to handle entities or features which are missing from the feature store.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinStrategy {
+    /// Reject the request if any feature of any entity is missing.
+    #[default]
+    Strict,
+    /// Use the configured default value of a missing feature, rejecting the request only if there is none.
+    Left,
+}
+
+/// Backend from which features are looked up.
+#[derive(Clone)]
+pub enum FeatureSource {
+    Redis(ConnectionManager),
+    /// Features held in memory, keyed by the Redis key of the entity and the feature name.
+    Memory(HashMap<String, HashMap<String, String>>),
+}
+
+impl FeatureSource {
+    /// Fetches the requested fields of each key within the timeout. Missing keys and fields are returned as `None`.
+    async fn fetch(
+        &self,
+        keys: &[String],
+        fields: &[String],
+        timeout: Duration,
+    ) -> anyhow::Result<Vec<Vec<Option<String>>>> {
+        match self {
+            FeatureSource::Redis(connection) => {
+                let mut pipeline = redis::pipe();
+                for key in keys {
+                    pipeline.cmd("HMGET").arg(key).arg(fields);
+                }
+                let mut connection = connection.clone();
+                match tokio::time::timeout(timeout, pipeline.query_async(&mut connection)).await {
+                    Ok(Ok(values)) => Ok(values),
+                    Ok(Err(e)) => {
+                        Err(anyhow::Error::new(e).context("Failed to fetch features from Redis ❌"))
+                    }
+                    Err(e) => Err(anyhow::Error::new(e).context(format!(
+                        "Failed to fetch features from Redis within {} ms ❌",
+                        timeout.as_millis()
+                    ))),
+                }
+            }
+            FeatureSource::Memory(entities) => Ok(keys
+                .iter()
+                .map(|key| {
+                    let entity = entities.get(key);
+                    fields
+                        .iter()
+                        .map(|field| entity.and_then(|entity| entity.get(field)).cloned())
+                        .collect()
+                })
+                .collect()),
+        }
+    }
+}
+
+/// Looked up features of an entity along with the time they were fetched at.
+type CachedEntity = (Instant, Vec<Option<String>>);
+
+/// Enriches the requests of a model with features from the feature store.
+struct Enrichment {
+    config: EnrichmentConfig,
+    fields: Vec<String>,
+    cache: Mutex<HashMap<String, CachedEntity>>,
+}
+
+/// Enriches requests with features looked up by entity ID, so clients do not have to look
+/// them up before calling the server.
+pub struct FeatureStore {
+    source: FeatureSource,
+    models: HashMap<String, Enrichment>,
+    lookup_timeout: Duration,
+}
+
+impl FeatureStore {
+    /// Connects to the Redis server of the feature store.
+    ///
+    /// # Errors
+    /// Returns an error if the configuration is invalid or Redis cannot be connected to.
+    pub async fn connect(config: FeatureStoreConfig) -> anyhow::Result<Self> {
+        if config.lookup_timeout_ms == 0 {
+            anyhow::bail!("Feature store lookup timeout must be greater than 0 ❌")
+        }
+        let client = match redis::Client::open(config.redis_url.as_str()) {
+            Ok(client) => client,
+            Err(e) => anyhow::bail!("Invalid feature store Redis URL ❌: {}", e),
+        };
+        let connection = match client.get_connection_manager().await {
+            Ok(connection) => connection,
+            Err(e) => anyhow::bail!("Failed to connect to feature store Redis ❌: {}", e),
+        };
+        tracing::info!("Connected to feature store at {} 🗃️", config.redis_url);
+        let feature_store = FeatureStore::new(FeatureSource::Redis(connection), config.models)?;
+        Ok(feature_store.with_lookup_timeout(Duration::from_millis(config.lookup_timeout_ms)))
+    }
+
+    /// Creates a feature store which looks up features from the given source.
+    ///
+    /// # Errors
+    /// Returns an error if a model is configured more than once, has no features or has defaults
+    /// which do not match the feature types.
+    pub fn new(source: FeatureSource, configs: Vec<EnrichmentConfig>) -> anyhow::Result<Self> {
+        let mut models = HashMap::new();
+        for config in configs {
+            if config.features.is_empty() {
+                anyhow::bail!(
+                    "Feature store enrichment of model {} has no features ❌",
+                    config.model_name
+                )
+            }
+            for (feature, value) in config.defaults.iter() {
+                match config.features.get(feature) {
+                    Some(feature_type) if matches_type(value, *feature_type) => {}
+                    _ => anyhow::bail!(
+                        "Default of feature {} of model {} does not match a configured feature ❌",
+                        feature,
+                        config.model_name
+                    ),
+                }
+            }
+            let model_name = config.model_name.clone();
+            let enrichment = Enrichment {
+                fields: config.features.keys().cloned().collect(),
+                config,
+                cache: Mutex::new(HashMap::new()),
+            };
+            if models.insert(model_name.clone(), enrichment).is_some() {
+                anyhow::bail!(
+                    "Feature store enrichment of model {} is configured more than once ❌",
+                    model_name
+                )
+            }
+        }
+        Ok(FeatureStore {
+            source,
+            models,
+            lookup_timeout: Duration::from_millis(DEFAULT_LOOKUP_TIMEOUT_MS),
+        })
+    }
+
+    /// Sets the time allowed for looking up the features of a request.
+    pub fn with_lookup_timeout(mut self, lookup_timeout: Duration) -> Self {
+        self.lookup_timeout = lookup_timeout;
+        self
+    }
+
+    /// Replaces the entity IDs in the model input with the features looked up from the feature store.
+    ///
+    /// The looked up features are appended after the other features of the input. Inputs of models
+    /// without an enrichment, or without the entity key, are returned unchanged. Enriched row-oriented
+    /// inputs are returned in the columnar format.
+    ///
+    /// # Arguments
+    /// * `model_name` - The name of the model the input is for.
+    /// * `input` - The model input, formatted as a JSON string.
+    ///
+    /// # Errors
+    /// Returns an error if the input cannot be parsed, the feature store cannot be reached or a feature
+    /// is missing and cannot be filled in.
+    pub async fn enrich(&self, model_name: &str, input: String) -> anyhow::Result<String> {
+        let enrichment = match self.models.get(model_name) {
+            Some(enrichment) => enrichment,
+            None => return Ok(input),
+        };
+
+        let mut columns = match serde_json::from_str::<OrderedColumns>(input.as_str()) {
+            Ok(columns) => columns.0,
+            Err(e) => anyhow::bail!("Failed to parse model input ❌: {}", e),
+        };
+        let position = match columns
+            .iter()
+            .position(|(name, _)| *name == enrichment.config.entity_key)
+        {
+            Some(position) => position,
+            None => return Ok(input),
+        };
+        let (_, entity_ids) = columns.remove(position);
+        let keys = entity_keys(&enrichment.config, entity_ids)?;
+
+        let rows = self.lookup(enrichment, &keys).await?;
+        for (i, feature) in enrichment.fields.iter().enumerate() {
+            let feature_type = enrichment.config.features[feature];
+            let mut values = Vec::with_capacity(rows.len());
+            for (key, row) in keys.iter().zip(rows.iter()) {
+                let value = match (&row[i], enrichment.config.join) {
+                    (Some(value), _) => parse_value(value, feature_type).map_err(|e| {
+                        anyhow::anyhow!("Invalid feature {} of {} ❌: {}", feature, key, e)
+                    })?,
+                    (None, JoinStrategy::Left) => match enrichment.config.defaults.get(feature) {
+                        Some(default) => default.clone(),
+                        None => anyhow::bail!("Missing feature {} of {} ❌", feature, key),
+                    },
+                    (None, JoinStrategy::Strict) => {
+                        anyhow::bail!("Missing feature {} of {} ❌", feature, key)
+                    }
+                };
+                values.push(value);
+            }
+            columns.push((feature.clone(), Value::Array(values)));
+        }
+
+        let mut json = String::from("{");
+        for (i, (name, values)) in columns.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push_str(serde_json::to_string(name)?.as_str());
+            json.push(':');
+            json.push_str(serde_json::to_string(values)?.as_str());
+        }
+        json.push('}');
+        Ok(json)
+    }
+
+    /// Looks up the features of each key, using the cached features of entities fetched within the TTL.
+    async fn lookup(
+        &self,
+        enrichment: &Enrichment,
+        keys: &[String],
+    ) -> anyhow::Result<Vec<Vec<Option<String>>>> {
+        let ttl = match enrichment.config.ttl {
+            Some(ttl) => Duration::from_secs(ttl),
+            None => {
+                return self
+                    .source
+                    .fetch(keys, &enrichment.fields, self.lookup_timeout)
+                    .await
+            }
+        };
+
+        let mut rows: Vec<Option<Vec<Option<String>>>> = vec![None; keys.len()];
+        let mut missing = Vec::new();
+        {
+            let cache = enrichment.cache.lock().unwrap_or_else(|e| e.into_inner());
+            for (i, key) in keys.iter().enumerate() {
+                match cache.get(key) {
+                    Some((fetched_at, row)) if fetched_at.elapsed() < ttl => {
+                        rows[i] = Some(row.clone())
+                    }
+                    _ => missing.push(i),
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            let missing_keys: Vec<String> = missing.iter().map(|i| keys[*i].clone()).collect();
+            let fetched = self
+                .source
+                .fetch(&missing_keys, &enrichment.fields, self.lookup_timeout)
+                .await?;
+            let mut cache = enrichment.cache.lock().unwrap_or_else(|e| e.into_inner());
+            if cache.len() + fetched.len() > MAX_CACHED_ENTITIES {
+                cache.clear();
+            }
+            let now = Instant::now();
+            for (i, row) in missing.into_iter().zip(fetched) {
+                cache.insert(keys[i].clone(), (now, row.clone()));
+                rows[i] = Some(row);
+            }
+        }
+
+        Ok(rows.into_iter().map(Option::unwrap_or_default).collect())
+    }
+}
+
+/// Returns true if enrichment failed because the feature store could not be reached in time, rather than
+/// because of an invalid request.
+pub fn is_unavailable(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<redis::RedisError>().is_some()
+        || error
+            .downcast_ref::<tokio::time::error::Elapsed>()
+            .is_some()
+}
+
+/// Returns the Redis key of each entity ID. IDs can be strings or integers.
+fn entity_keys(config: &EnrichmentConfig, entity_ids: Value) -> anyhow::Result<Vec<String>> {
+    let ids = match entity_ids {
+        Value::Array(ids) => ids,
+        _ => anyhow::bail!("Entity key {} must be an array ❌", config.entity_key),
+    };
+    ids.into_iter()
+        .map(|id| match id {
+            Value::String(id) => Ok(format!("{}{}", config.key_prefix, id)),
+            Value::Number(id) if id.is_i64() || id.is_u64() => {
+                Ok(format!("{}{}", config.key_prefix, id))
+            }
+            _ => anyhow::bail!(
+                "Entity key {} must only hold strings or integers ❌",
+                config.entity_key
+            ),
+        })
+        .collect()
+}
+
+/// Parses a feature value stored in the feature store into a JSON value of the feature type.
+fn parse_value(value: &str, feature_type: FeatureType) -> anyhow::Result<Value> {
+    match feature_type {
+        FeatureType::Int => Ok(Value::from(value.trim().parse::<i32>()?)),
+        FeatureType::Float => {
+            let number = value.trim().parse::<f64>()?;
+            match Number::from_f64(number) {
+                Some(number) => Ok(Value::Number(number)),
+                None => anyhow::bail!("{} is not a finite number", value),
+            }
+        }
+        FeatureType::String => Ok(Value::String(value.to_string())),
+    }
+}
+
+fn matches_type(value: &Value, feature_type: FeatureType) -> bool {
+    match feature_type {
+        FeatureType::Int => value.is_i64(),
+        FeatureType::Float => value.is_number(),
+        FeatureType::String => value.is_string(),
+    }
+}
+
+/// The columns of a model input in the order they were sent, which matters for models taking
+/// their features by position. Row-oriented inputs are pivoted into columns ordered as in their first row.
+struct OrderedColumns(Vec<(String, Value)>);
+
+impl<'de> Deserialize<'de> for OrderedColumns {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct OrderedColumnsVisitor;
+
+        impl<'de> Visitor<'de> for OrderedColumnsVisitor {
+            type Value = OrderedColumns;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a map of features to arrays of values, or an array of records")
+            }
+
+            fn visit_map<M>(self, map: M) -> Result<Self::Value, M::Error>
+            where
+                M: MapAccess<'de>,
+            {
+                let entries = OrderedEntries::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(OrderedColumns(entries.0))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut columns: Vec<(String, Value)> = Vec::new();
+                let mut row: usize = 0;
+                while let Some(OrderedEntries(record)) = seq.next_element()? {
+                    if row == 0 {
+                        columns = record
+                            .into_iter()
+                            .map(|(name, value)| (name, Value::Array(vec![value])))
+                            .collect();
+                    } else {
+                        if record.len() != columns.len() {
+                            return Err(de::Error::custom(format!(
+                                "Expected {} keys in row {} but found {}",
+                                columns.len(),
+                                row,
+                                record.len()
+                            )));
+                        }
+                        for (name, value) in record {
+                            match columns.iter_mut().find(|(column, _)| *column == name) {
+                                Some((_, Value::Array(values))) => values.push(value),
+                                _ => {
+                                    return Err(de::Error::custom(format!(
+                                        "Unexpected key '{}' in row {}",
+                                        name, row
+                                    )))
+                                }
+                            }
+                        }
+                    }
+                    row += 1;
+                }
+                Ok(OrderedColumns(columns))
+            }
+        }
+
+        deserializer.deserialize_any(OrderedColumnsVisitor)
+    }
+}
+
+/// The entries of a JSON map in the order they were sent.
+struct OrderedEntries(Vec<(String, Value)>);
+
+impl<'de> Deserialize<'de> for OrderedEntries {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct OrderedEntriesVisitor;
+
+        impl<'de> Visitor<'de> for OrderedEntriesVisitor {
+            type Value = OrderedEntries;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a map of features to values")
+            }
+
+            fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+            where
+                M: MapAccess<'de>,
+            {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry::<String, Value>()? {
+                    entries.push(entry);
+                }
+                Ok(OrderedEntries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(OrderedEntriesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(join: JoinStrategy) -> EnrichmentConfig {
+        EnrichmentConfig {
+            model_name: "titanic_model".to_string(),
+            entity_key: "passenger_id".to_string(),
+            key_prefix: "passenger:".to_string(),
+            features: BTreeMap::from([
+                ("age".to_string(), FeatureType::Float),
+                ("deck".to_string(), FeatureType::String),
+            ]),
+            ttl: Some(60),
+            join,
+            defaults: BTreeMap::from([("deck".to_string(), Value::from("Unknown"))]),
+        }
+    }
+
+    fn source() -> FeatureSource {
+        FeatureSource::Memory(HashMap::from([
+            (
+                "passenger:1".to_string(),
+                HashMap::from([
+                    ("age".to_string(), "22.5".to_string()),
+                    ("deck".to_string(), "C".to_string()),
+                ]),
+            ),
+            (
+                "passenger:2".to_string(),
+                HashMap::from([("age".to_string(), "38".to_string())]),
+            ),
+        ]))
+    }
+
+    #[tokio::test]
+    async fn successfully_enrich_input_with_features_from_feature_store() {
+        // Arrange
+        let store = FeatureStore::new(source(), vec![config(JoinStrategy::Left)]).unwrap();
+        let input = r#"{"pclass": ["1", "3"], "passenger_id": [1, "2"]}"#.to_string();
+
+        // Act
+        let result = store.enrich("titanic_model", input).await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            r#"{"pclass":["1","3"],"age":[22.5,38.0],"deck":["C","Unknown"]}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn successfully_enrich_row_oriented_input_with_features_from_feature_store() {
+        // Arrange
+        let store = FeatureStore::new(source(), vec![config(JoinStrategy::Left)]).unwrap();
+        let input = r#"[{"pclass": "1", "passenger_id": 1}, {"passenger_id": "2", "pclass": "3"}]"#
+            .to_string();
+
+        // Act
+        let result = store.enrich("titanic_model", input).await;
+
+        // Assert
+        assert_eq!(
+            result.unwrap(),
+            r#"{"pclass":["1","3"],"age":[22.5,38.0],"deck":["C","Unknown"]}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn successfully_return_input_of_model_without_enrichment_unchanged() {
+        // Arrange
+        let store = FeatureStore::new(source(), vec![config(JoinStrategy::Left)]).unwrap();
+        let input = r#"{"passenger_id": [1]}"#.to_string();
+
+        // Act
+        let result = store.enrich("other_model", input.clone()).await;
+
+        // Assert
+        assert_eq!(result.unwrap(), input);
+    }
+
+    #[tokio::test]
+    async fn fails_to_enrich_input_when_feature_is_missing_with_strict_join() {
+        // Arrange
+        let store = FeatureStore::new(source(), vec![config(JoinStrategy::Strict)]).unwrap();
+        let input = r#"{"passenger_id": [1, 2]}"#.to_string();
+
+        // Act
+        let result = store.enrich("titanic_model", input).await;
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn successfully_report_lookup_timeout_as_unavailable() {
+        // Arrange
+        let lookup = std::future::pending::<()>();
+
+        // Act
+        let error = match tokio::time::timeout(Duration::ZERO, lookup).await {
+            Ok(_) => panic!("Lookup should have timed out"),
+            Err(e) => anyhow::Error::new(e).context("Failed to fetch features from Redis ❌"),
+        };
+
+        // Assert
+        assert!(is_unavailable(&error));
+        assert!(!is_unavailable(&anyhow::anyhow!(
+            "Missing feature age of passenger:1 ❌"
+        )));
+    }
+
+    #[test]
+    fn fails_to_create_feature_store_when_default_does_not_match_feature_type() {
+        // Arrange
+        let mut config = config(JoinStrategy::Left);
+        config
+            .defaults
+            .insert("age".to_string(), Value::from("unknown"));
+
+        // Act
+        let result = FeatureStore::new(source(), vec![config]);
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,314 @@
+use serde::Deserialize;
+
+/// Configuration of the hardened mode, which reduces the blast radius of a malicious or compromised model.
+///
+/// TensorFlow and PyTorch models can execute native code when they are loaded or run, so a model artifact
+/// is as trusted as the code of the server itself. The hardened mode restricts what that code can do once
+/// it runs inside the server process:
+///
+/// - a seccomp filter makes the syscalls which a model server never needs fail with `EPERM`, e.g. spawning
+///   other programs (`execve`), tracing or reading the memory of other processes (`ptrace`,
+///   `process_vm_readv`), loading kernel modules, mounting filesystems and creating namespaces.
+/// - resource limits cap the open files, address space and processes of the server.
+/// - core dumps are disabled, so that model weights and request data are not written to disk on a crash.
+///
+/// The hardened mode does not stop a malicious model from reading the files, environment variables and
+/// credentials available to the server, sending data over the network or tampering with predictions.
+/// Run the server as a non-root user in a container with a read-only filesystem and a restrictive network
+/// policy for that. It is only supported on Linux x86_64 and aarch64 and is applied before any model is loaded.
+///
+/// # Example
+/// ```toml
+/// [config.hardening]
+/// seccomp = true
+/// max_open_files = 4096
+/// max_address_space_mb = 16384
+/// max_processes = 512
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HardeningConfig {
+    /// Whether to install the seccomp filter. Defaults to `true`. Programs spawned by the model stores,
+    /// e.g. an AWS `credential_process`, fail when the filter is installed.
+    #[serde(default = "default_seccomp")]
+    pub seccomp: bool,
+
+    /// An optional maximum number of open file descriptors, including sockets.
+    #[serde(default)]
+    pub max_open_files: Option<u64>,
+
+    /// An optional maximum size (in MiB) of the virtual address space of the server. TensorFlow and PyTorch
+    /// reserve much more virtual memory than they use, so this must be well above the resident memory.
+    #[serde(default)]
+    pub max_address_space_mb: Option<u64>,
+
+    /// An optional maximum number of processes and threads of the user running the server.
+    #[serde(default)]
+    pub max_processes: Option<u64>,
+}
+
+fn default_seccomp() -> bool {
+    true
+}
+
+impl HardeningConfig {
+    /// Validates the hardened mode configuration.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (name, limit) in [
+            ("max_open_files", self.max_open_files),
+            ("max_address_space_mb", self.max_address_space_mb),
+            ("max_processes", self.max_processes),
+        ] {
+            if limit == Some(0) {
+                anyhow::bail!("hardening.{} must be at least 1 ❌", name)
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the resource limits and the seccomp filter to the whole server process.
+    ///
+    /// # Errors
+    /// Returns an error if the platform is not supported or a limit or the filter cannot be applied.
+    pub fn apply(&self) -> anyhow::Result<()> {
+        self.validate()?;
+        sys::apply(self)?;
+        tracing::info!(
+            "Hardened mode is enabled with seccomp {} 🔒",
+            if self.seccomp { "on" } else { "off" }
+        );
+        Ok(())
+    }
+}
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod sys {
+    use super::HardeningConfig;
+    use libc::{
+        sock_filter, sock_fprog, BPF_ABS, BPF_JEQ, BPF_JGE, BPF_JMP, BPF_K, BPF_LD, BPF_RET, BPF_W,
+    };
+
+    /// Syscalls which fail with `EPERM` when the seccomp filter is installed.
+    const DENIED_SYSCALLS: &[libc::c_long] = &[
+        libc::SYS_execve,
+        libc::SYS_execveat,
+        libc::SYS_ptrace,
+        libc::SYS_process_vm_readv,
+        libc::SYS_process_vm_writev,
+        libc::SYS_mount,
+        libc::SYS_umount2,
+        libc::SYS_pivot_root,
+        libc::SYS_chroot,
+        libc::SYS_unshare,
+        libc::SYS_setns,
+        libc::SYS_kexec_load,
+        libc::SYS_init_module,
+        libc::SYS_finit_module,
+        libc::SYS_delete_module,
+        libc::SYS_reboot,
+        libc::SYS_swapon,
+        libc::SYS_swapoff,
+        libc::SYS_bpf,
+        libc::SYS_perf_event_open,
+        libc::SYS_keyctl,
+        libc::SYS_add_key,
+        libc::SYS_request_key,
+        libc::SYS_userfaultfd,
+    ];
+
+    #[cfg(target_env = "gnu")]
+    type Resource = libc::__rlimit_resource_t;
+    #[cfg(not(target_env = "gnu"))]
+    type Resource = libc::c_int;
+
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xC000_003E;
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xC000_00B7;
+
+    /// Syscall numbers of the x32 ABI on x86_64 have this bit set and are denied as a whole.
+    const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+    /// Offsets of the fields of `struct seccomp_data`.
+    const SYSCALL_NR_OFFSET: u32 = 0;
+    const ARCH_OFFSET: u32 = 4;
+
+    pub(super) fn apply(config: &HardeningConfig) -> anyhow::Result<()> {
+        set_limit(libc::RLIMIT_CORE, Some(0), "core dump size")?;
+        set_limit(libc::RLIMIT_NOFILE, config.max_open_files, "open files")?;
+        set_limit(
+            libc::RLIMIT_AS,
+            config.max_address_space_mb.map(|mb| mb * 1024 * 1024),
+            "address space",
+        )?;
+        set_limit(libc::RLIMIT_NPROC, config.max_processes, "processes")?;
+        if config.seccomp {
+            install_seccomp_filter()?;
+        }
+        Ok(())
+    }
+
+    fn set_limit(resource: Resource, limit: Option<u64>, name: &str) -> anyhow::Result<()> {
+        let limit = match limit {
+            Some(limit) => limit as libc::rlim_t,
+            None => return Ok(()),
+        };
+        let rlimit = libc::rlimit {
+            rlim_cur: limit,
+            rlim_max: limit,
+        };
+        // SAFETY: rlimit is a valid, initialised struct which outlives the call.
+        if unsafe { libc::setrlimit(resource, &rlimit) } != 0 {
+            let e = std::io::Error::last_os_error();
+            tracing::error!("Failed to limit {} ❌: {}", name, e);
+            anyhow::bail!("Failed to limit {} ❌: {}", name, e)
+        }
+        Ok(())
+    }
+
+    fn statement(code: u32, k: u32) -> sock_filter {
+        sock_filter {
+            code: code as u16,
+            jt: 0,
+            jf: 0,
+            k,
+        }
+    }
+
+    fn jump(code: u32, k: u32, jt: u8, jf: u8) -> sock_filter {
+        sock_filter {
+            code: code as u16,
+            jt,
+            jf,
+            k,
+        }
+    }
+
+    /// Builds a BPF program which denies the syscalls of `DENIED_SYSCALLS`, the syscalls of other
+    /// architectures and, on x86_64, the x32 ABI. Every other syscall is allowed.
+    pub(super) fn filter() -> Vec<sock_filter> {
+        let deny = libc::SECCOMP_RET_ERRNO | libc::EPERM as u32;
+        let denied = DENIED_SYSCALLS.len();
+        let mut program = vec![
+            statement(BPF_LD | BPF_W | BPF_ABS, ARCH_OFFSET),
+            jump(BPF_JMP | BPF_JEQ | BPF_K, AUDIT_ARCH, 1, 0),
+            statement(BPF_RET | BPF_K, deny),
+            statement(BPF_LD | BPF_W | BPF_ABS, SYSCALL_NR_OFFSET),
+        ];
+        // every jump lands on the final deny statement, which follows the allow statement
+        if cfg!(target_arch = "x86_64") {
+            program.push(jump(
+                BPF_JMP | BPF_JGE | BPF_K,
+                X32_SYSCALL_BIT,
+                (denied + 1) as u8,
+                0,
+            ));
+        }
+        for (i, syscall) in DENIED_SYSCALLS.iter().enumerate() {
+            program.push(jump(
+                BPF_JMP | BPF_JEQ | BPF_K,
+                *syscall as u32,
+                (denied - i) as u8,
+                0,
+            ));
+        }
+        program.push(statement(BPF_RET | BPF_K, libc::SECCOMP_RET_ALLOW));
+        program.push(statement(BPF_RET | BPF_K, deny));
+        program
+    }
+
+    fn install_seccomp_filter() -> anyhow::Result<()> {
+        let mut program = filter();
+        let prog = sock_fprog {
+            len: program.len() as u16,
+            filter: program.as_mut_ptr(),
+        };
+        // SAFETY: prctl with PR_SET_NO_NEW_PRIVS takes no pointers.
+        if unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) } != 0 {
+            let e = std::io::Error::last_os_error();
+            tracing::error!("Failed to set no_new_privs ❌: {}", e);
+            anyhow::bail!("Failed to set no_new_privs ❌: {}", e)
+        }
+        // SAFETY: prog points to a valid BPF program which outlives the call. The filter is
+        // synchronised to every thread of the process.
+        let result = unsafe {
+            libc::syscall(
+                libc::SYS_seccomp,
+                libc::SECCOMP_SET_MODE_FILTER,
+                libc::SECCOMP_FILTER_FLAG_TSYNC,
+                &prog as *const sock_fprog,
+            )
+        };
+        if result != 0 {
+            let e = std::io::Error::last_os_error();
+            tracing::error!("Failed to install seccomp filter ❌: {}", e);
+            anyhow::bail!("Failed to install seccomp filter ❌: {}", e)
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+mod sys {
+    use super::HardeningConfig;
+
+    pub(super) fn apply(_: &HardeningConfig) -> anyhow::Result<()> {
+        anyhow::bail!("Hardened mode is only supported on Linux x86_64 and aarch64 ❌")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_parse_hardening_config() {
+        // Arrange
+        let contents = "max_open_files = 4096";
+
+        // Act
+        let config: HardeningConfig = toml::from_str(contents).unwrap();
+
+        // Assert
+        assert!(config.seccomp);
+        assert_eq!(config.max_open_files, Some(4096));
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn fails_to_validate_hardening_config_with_zero_limit() {
+        // Arrange
+        let config: HardeningConfig = toml::from_str("max_processes = 0").unwrap();
+
+        // Act
+        let result = config.validate();
+
+        // Assert
+        assert!(result.is_err())
+    }
+
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    #[test]
+    fn successfully_build_seccomp_filter_which_jumps_to_the_deny_statement() {
+        // Act
+        let program = sys::filter();
+
+        // Assert
+        let deny = program.len() - 1;
+        for (i, statement) in program.iter().enumerate().skip(4) {
+            if statement.jt > 0 {
+                assert_eq!(i + 1 + statement.jt as usize, deny);
+            }
+        }
+        assert_eq!(program[deny - 1].k, libc::SECCOMP_RET_ALLOW);
+    }
+}
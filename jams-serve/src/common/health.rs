@@ -0,0 +1,371 @@
+use crate::common::metrics::SMOKE_TEST_PASSED;
+use crate::common::namespace::Namespaces;
+use crate::common::sentinel::Sentinel;
+use crate::common::worker;
+use jams_core::manager::Manager;
+use jams_core::model::predict::PredictOptions;
+use jams_core::model::sample::{sample_input, SampleStrategy};
+use jams_core::model_store::storage::Metadata;
+use jams_core::pool::scheduler::{Priority, Scheduler, Task};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+/// Time after which a deep health check is reported as unhealthy.
+pub const DEEP_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runs a canned prediction against a sentinel model to check the full prediction path, including the
+/// worker pools and the native libraries of the frameworks, rather than only the liveness of the process.
+pub struct DeepHealthCheck {
+    model_name: String,
+    input: String,
+    timeout: Duration,
+}
+
+/// The outcome of a deep health check.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DeepHealthReport {
+    /// Whether the canned prediction succeeded within the timeout.
+    pub healthy: bool,
+    /// The name of the sentinel model.
+    pub model_name: String,
+    /// Time taken by the canned prediction in milliseconds, including the time spent waiting for a worker.
+    pub latency_ms: f64,
+    /// The reason the check failed, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DeepHealthCheck {
+    /// Creates a new deep health check.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - The name of the sentinel model.
+    /// * `input` - The canned model input as a JSON string.
+    pub fn new(model_name: String, input: String) -> Self {
+        DeepHealthCheck {
+            model_name,
+            input,
+            timeout: DEEP_HEALTH_CHECK_TIMEOUT,
+        }
+    }
+
+    /// Runs the canned prediction on the worker pool the sentinel model is assigned to.
+    ///
+    /// # Arguments
+    ///
+    /// * `manager` - The manager holding the sentinel model.
+    /// * `cpu_pool` - The shared worker pool.
+    /// * `namespaces` - The namespaces, whose worker pool is used if the sentinel model belongs to one.
+    ///
+    /// # Returns
+    ///
+    /// A `DeepHealthReport` with the outcome and the latency of the prediction.
+    pub async fn run(
+        &self,
+        manager: &Arc<Manager>,
+        cpu_pool: &Scheduler,
+        namespaces: &Namespaces,
+    ) -> DeepHealthReport {
+        let start = Instant::now();
+        let (tx, rx) = oneshot::channel();
+        let cancellation_token = CancellationToken::new();
+        let _cancel_on_drop = cancellation_token.clone().drop_guard();
+
+        let weight = manager.scheduling_weight_of(self.model_name.as_str());
+        let manager = Arc::clone(manager);
+        let model_name = self.model_name.clone();
+        let input = self.input.clone();
+        let worker_cancellation_token = cancellation_token.clone();
+        // the health check must not queue behind the predictions it is checking
+        let task = Task::new(move || {
+            worker::predict_and_send(
+                manager,
+                model_name,
+                input,
+                PredictOptions::default(),
+                tx,
+                worker_cancellation_token,
+            )
+        })
+        .with_priority(Priority::High)
+        .with_flow(self.model_name.as_str(), weight)
+        .with_cancellation_token(cancellation_token.clone());
+        let result = match namespaces.spawn(cpu_pool, self.model_name.as_str(), task) {
+            Ok(_) => match tokio::time::timeout(self.timeout, rx).await {
+                Ok(Ok(Ok(_))) => Ok(()),
+                Ok(Ok(Err(e))) => Err(e.to_string()),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err(format!("timed out after {:?}", self.timeout)),
+            },
+            Err(e) => Err(e.to_string()),
+        };
+        let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        match result {
+            Ok(_) => DeepHealthReport {
+                healthy: true,
+                model_name: self.model_name.clone(),
+                latency_ms,
+                error: None,
+            },
+            Err(e) => {
+                tracing::error!(
+                    "Deep health check failed for model {} ❌: {}",
+                    self.model_name,
+                    e
+                );
+                DeepHealthReport {
+                    healthy: false,
+                    model_name: self.model_name.clone(),
+                    latency_ms,
+                    error: Some(e),
+                }
+            }
+        }
+    }
+}
+
+/// Configuration of the smoke test run at startup, which makes one prediction per framework of the loaded models
+/// to catch native libraries which are broken on the host, e.g. ABI or glibc mismatches, before traffic arrives.
+/// The smoke test can only be configured using the config file.
+///
+/// # Example
+/// ```toml
+/// [config.smoke_test]
+/// on_failure = "degrade"
+/// ```
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SmokeTestConfig {
+    /// What happens when the prediction of a framework fails.
+    #[serde(default)]
+    pub on_failure: SmokeTestFailure,
+}
+
+/// What happens when the smoke test of a framework fails.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmokeTestFailure {
+    /// The server fails to start.
+    #[default]
+    Fail,
+    /// The server starts and reports the framework as degraded in `/readyz`.
+    Degrade,
+}
+
+/// The outcome of the smoke test of a framework.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmokeTestStatus {
+    /// The sentinel prediction succeeded.
+    Passed,
+    /// The sentinel prediction failed.
+    Failed,
+    /// No sentinel model is bundled for the framework and none of its models has a `bundle/schema.json` or
+    /// `bundle/warmup.json` to build the input from.
+    Skipped,
+}
+
+/// The smoke test of a framework.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FrameworkSmokeTest {
+    /// The framework, e.g. `tensorflow`.
+    pub framework: String,
+    /// The outcome of the smoke test.
+    pub status: SmokeTestStatus,
+    /// The loaded model which made the sentinel prediction, if any. `None` if the bundled sentinel model of the
+    /// framework made it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+    /// The reason the smoke test failed, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The outcome of the smoke test run at startup.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SmokeTestReport {
+    /// The smoke test of every framework of the loaded models.
+    pub frameworks: Vec<FrameworkSmokeTest>,
+}
+
+impl SmokeTestReport {
+    /// Returns the frameworks whose smoke test failed.
+    pub fn failed_frameworks(&self) -> Vec<String> {
+        self.frameworks
+            .iter()
+            .filter(|smoke_test| smoke_test.status == SmokeTestStatus::Failed)
+            .map(|smoke_test| smoke_test.framework.clone())
+            .collect()
+    }
+}
+
+/// Runs one sentinel prediction per framework of the loaded models.
+///
+/// The built-in frameworks are checked with the sentinel model bundled for them, whose outputs must match the
+/// expected outputs, see `Sentinel`. Other frameworks are checked with an input generated from the bundle of the
+/// first of their models which has an input schema or a warmup input, see `sample_input`, on the worker pool of
+/// the model like a deep health check. Each prediction runs within `DEEP_HEALTH_CHECK_TIMEOUT`. The outcome of every framework is exported by the
+/// `jams_smoke_test_passed` metric.
+///
+/// # Arguments
+///
+/// * `manager` - The manager holding the loaded models.
+/// * `cpu_pool` - The shared worker pool.
+/// * `namespaces` - The namespaces, whose worker pools are used for the models which belong to one.
+///
+/// # Returns
+///
+/// * `Ok(SmokeTestReport)` - The outcome of every framework.
+/// * `Err(anyhow::Error)` - If the loaded models could not be listed.
+pub async fn run_smoke_test(
+    manager: &Arc<Manager>,
+    cpu_pool: &Scheduler,
+    namespaces: &Namespaces,
+) -> anyhow::Result<SmokeTestReport> {
+    let mut models_by_framework: BTreeMap<&str, Vec<Metadata>> = BTreeMap::new();
+    for model in manager.get_models()? {
+        models_by_framework
+            .entry(model.framework)
+            .or_default()
+            .push(model);
+    }
+
+    let mut report = SmokeTestReport::default();
+    for (framework, models) in models_by_framework {
+        if let Some(sentinel) = Sentinel::of(framework) {
+            let result = sentinel.check(cpu_pool, DEEP_HEALTH_CHECK_TIMEOUT).await;
+            if let Err(e) = &result {
+                tracing::error!("Smoke test of {} failed ❌: {}", framework, e);
+            }
+            SMOKE_TEST_PASSED
+                .with_label_values(&[framework])
+                .set(result.is_ok() as i64);
+            report.frameworks.push(FrameworkSmokeTest {
+                framework: framework.to_string(),
+                status: match result.is_ok() {
+                    true => SmokeTestStatus::Passed,
+                    false => SmokeTestStatus::Failed,
+                },
+                model_name: None,
+                error: result.err().map(|e| e.to_string()),
+            });
+            continue;
+        }
+        let sentinel = models.into_iter().find_map(|model| {
+            let bundle = manager.get_model_bundle(model.name.clone())?;
+            let input = sample_input(&bundle, 1, SampleStrategy::Midpoint).ok()?;
+            Some((model.name, input))
+        });
+        let smoke_test = match sentinel {
+            Some((model_name, input)) => {
+                let deep_health = DeepHealthCheck::new(model_name, input)
+                    .run(manager, cpu_pool, namespaces)
+                    .await;
+                FrameworkSmokeTest {
+                    framework: framework.to_string(),
+                    status: match deep_health.healthy {
+                        true => SmokeTestStatus::Passed,
+                        false => SmokeTestStatus::Failed,
+                    },
+                    model_name: Some(deep_health.model_name),
+                    error: deep_health.error,
+                }
+            }
+            None => {
+                tracing::warn!(
+                    "Skipping smoke test of {} as none of its models has a sample input ⚠️",
+                    framework
+                );
+                FrameworkSmokeTest {
+                    framework: framework.to_string(),
+                    status: SmokeTestStatus::Skipped,
+                    model_name: None,
+                    error: None,
+                }
+            }
+        };
+        if smoke_test.status != SmokeTestStatus::Skipped {
+            SMOKE_TEST_PASSED
+                .with_label_values(&[framework])
+                .set((smoke_test.status == SmokeTestStatus::Passed) as i64);
+        }
+        report.frameworks.push(smoke_test);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jams_core::manager::ManagerBuilder;
+    use jams_core::model_store::local::filesystem::LocalModelStore;
+    use jams_core::model_store::ModelStore;
+    use jams_core::pool::executor::ExecutorKind;
+    use jams_core::pool::scheduler::Budget;
+
+    #[tokio::test]
+    async fn fails_deep_health_check_when_sentinel_model_does_not_exist() {
+        // Arrange
+        let model_store = LocalModelStore::new("tests/model_store".to_string())
+            .await
+            .expect("Failed to create model store ❌");
+        let manager = Arc::new(
+            ManagerBuilder::new(Arc::new(ModelStore::Local(model_store)))
+                .build()
+                .expect("Failed to initialize manager ❌"),
+        );
+        let cpu_pool = Scheduler::new(
+            "shared",
+            ExecutorKind::Rayon.build(1, "jams-test").unwrap(),
+            Budget::default(),
+        );
+        let check = DeepHealthCheck::new(
+            "model_which_does_not_exist".to_string(),
+            r#"{"age": [22.0]}"#.to_string(),
+        );
+
+        // Act
+        let report = check.run(&manager, &cpu_pool, &Namespaces::default()).await;
+
+        // Assert
+        assert!(!report.healthy);
+        assert_eq!(report.model_name, "model_which_does_not_exist");
+        assert!(report.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn successfully_smoke_test_frameworks_with_bundled_sentinel_models() {
+        // Arrange
+        let model_store = LocalModelStore::new("tests/model_store".to_string())
+            .await
+            .expect("Failed to create model store ❌");
+        let manager = Arc::new(
+            ManagerBuilder::new(Arc::new(ModelStore::Local(model_store)))
+                .build()
+                .expect("Failed to initialize manager ❌"),
+        );
+        let cpu_pool = Scheduler::new(
+            "shared",
+            ExecutorKind::Rayon.build(1, "jams-test").unwrap(),
+            Budget::default(),
+        );
+
+        // Act
+        let report = run_smoke_test(&manager, &cpu_pool, &Namespaces::default())
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(!report.frameworks.is_empty());
+        assert!(report.frameworks.iter().all(|smoke_test| {
+            smoke_test.status == SmokeTestStatus::Passed && smoke_test.model_name.is_none()
+        }));
+        assert!(report.failed_frameworks().is_empty());
+    }
+}
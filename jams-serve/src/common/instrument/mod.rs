@@ -1,2 +1,3 @@
 pub mod opentelemetry;
+pub mod sampling;
 pub mod simple;
@@ -1,3 +1,4 @@
+use crate::common::instrument::sampling::LogSampler;
 use opentelemetry::{global, KeyValue};
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::propagation::TraceContextPropagator;
@@ -6,7 +7,14 @@ use opentelemetry_sdk::{runtime, trace, Resource};
 use std::env;
 use tracing_subscriber::layer::SubscriberExt;
 
-pub fn init(service_name: String, log_level: tracing::Level) -> anyhow::Result<()> {
+pub fn init(
+    service_name: String,
+    log_level: tracing::Level,
+    sampler: Option<LogSampler>,
+) -> anyhow::Result<()> {
+    if let Some(sampler) = &sampler {
+        sampler.spawn_reporter();
+    }
     global::set_text_map_propagator(TraceContextPropagator::new());
     let tracer = init_otlp_trace(service_name)?;
     // Create the OpenTelemetry tracing layer
@@ -17,7 +25,8 @@ pub fn init(service_name: String, log_level: tracing::Level) -> anyhow::Result<(
         .with_max_level(log_level)
         .pretty()
         .finish()
-        .with(telemetry);
+        .with(telemetry)
+        .with(sampler);
 
     // Set this combined subscriber as the global default
     match tracing::subscriber::set_global_default(subscriber) {
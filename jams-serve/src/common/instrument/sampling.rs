@@ -0,0 +1,302 @@
+use crate::common::metrics::LOG_EVENTS_SUPPRESSED_TOTAL;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::callsite::Identifier;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Target used for the summaries of suppressed events. Events with this target are never sampled.
+const SAMPLING_TARGET: &str = "jams_serve::log_sampling";
+
+/// Key used in the sampling limits to set the limit for modules which are not listed.
+const DEFAULT_MODULE: &str = "default";
+
+/// Number of events logged per call site per window when no limit is configured for the module.
+pub const DEFAULT_EVENTS_PER_WINDOW: u64 = 10;
+
+/// Configuration for sampling warning and error logs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingConfig {
+    /// Length of a sampling window. Suppressed events are summarised once per window.
+    pub window: Duration,
+    /// Number of events logged per call site per window for modules which are not listed in `modules`.
+    pub default_limit: u64,
+    /// Number of events logged per call site per window, keyed by module path.
+    pub modules: HashMap<String, u64>,
+}
+
+impl SamplingConfig {
+    /// Parses the sampling configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `window_secs` - Length of a sampling window in seconds.
+    /// * `limits` - Optional comma separated list of `module=limit` pairs, i.e. `jams_serve::http=5,default=20`.
+    ///   A module matches its own events and the events of its submodules, the most specific module wins.
+    ///   Use `default` to set the limit for every other module.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SamplingConfig)` - If the configuration is valid.
+    /// * `Err(anyhow::Error)` - If the window is zero or the limits cannot be parsed.
+    pub fn parse(window_secs: u64, limits: Option<&str>) -> anyhow::Result<Self> {
+        if window_secs == 0 {
+            anyhow::bail!("Log sampling window must be at least 1 second ❌")
+        }
+
+        let mut default_limit = DEFAULT_EVENTS_PER_WINDOW;
+        let mut modules = HashMap::new();
+        for pair in limits.unwrap_or_default().split(',') {
+            let pair = pair.trim();
+            if pair.is_empty() {
+                continue;
+            }
+            let (module, limit) = match pair.split_once('=') {
+                Some((module, limit)) => (module.trim(), limit.trim()),
+                None => anyhow::bail!(
+                    "Invalid log sampling limit {} ❌. Expected module=limit",
+                    pair
+                ),
+            };
+            let limit = match limit.parse::<u64>() {
+                Ok(limit) => limit,
+                Err(e) => anyhow::bail!("Invalid log sampling limit for {} ❌: {}", module, e),
+            };
+            if module == DEFAULT_MODULE {
+                default_limit = limit;
+            } else {
+                modules.insert(module.to_string(), limit);
+            }
+        }
+
+        Ok(SamplingConfig {
+            window: Duration::from_secs(window_secs),
+            default_limit,
+            modules,
+        })
+    }
+
+    /// Returns the limit for the module an event was emitted from.
+    fn limit_for(&self, target: &str) -> u64 {
+        self.modules
+            .iter()
+            .filter(|(module, _)| {
+                target == module.as_str()
+                    || (target.starts_with(module.as_str())
+                        && target[module.len()..].starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map(|(_, limit)| *limit)
+            .unwrap_or(self.default_limit)
+    }
+}
+
+/// Events from a single call site in the current window.
+struct CallSiteWindow {
+    target: String,
+    location: String,
+    message: String,
+    logged: u64,
+    suppressed: u64,
+}
+
+/// Summary of the events which were suppressed from a single call site in a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suppressed {
+    /// The module the events were emitted from.
+    pub target: String,
+    /// The file and line of the call site.
+    pub location: String,
+    /// The message of the first event in the window.
+    pub message: String,
+    /// Number of events which were logged.
+    pub logged: u64,
+    /// Number of events which were suppressed.
+    pub suppressed: u64,
+}
+
+/// A tracing layer which rate limits warning and error logs.
+///
+/// Events are grouped by call site, so the same log statement counts as the same class of error
+/// regardless of the details in the message. Only the first `limit` events from a call site are
+/// logged per window. The rest are counted and a single summary is logged at the end of the window.
+/// Info, debug and trace events are never sampled.
+#[derive(Clone)]
+pub struct LogSampler {
+    config: Arc<SamplingConfig>,
+    windows: Arc<Mutex<HashMap<Identifier, CallSiteWindow>>>,
+}
+
+impl LogSampler {
+    /// Creates a new sampler with the given configuration.
+    pub fn new(config: SamplingConfig) -> Self {
+        LogSampler {
+            config: Arc::new(config),
+            windows: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the events suppressed in the current window and starts a new window.
+    pub fn flush(&self) -> Vec<Suppressed> {
+        let windows = match self.windows.lock() {
+            Ok(mut windows) => std::mem::take(&mut *windows),
+            Err(_) => return vec![],
+        };
+
+        let mut suppressed: Vec<Suppressed> = windows
+            .into_values()
+            .filter(|window| window.suppressed > 0)
+            .map(|window| Suppressed {
+                target: window.target,
+                location: window.location,
+                message: window.message,
+                logged: window.logged,
+                suppressed: window.suppressed,
+            })
+            .collect();
+        suppressed.sort_by_key(|summary| std::cmp::Reverse(summary.suppressed));
+        suppressed
+    }
+
+    /// Spawns a task which logs a summary of the suppressed events at the end of every window.
+    pub fn spawn_reporter(&self) {
+        let sampler = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(sampler.config.window);
+            // the first tick completes immediately
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                for summary in sampler.flush() {
+                    tracing::warn!(
+                        target: SAMPLING_TARGET,
+                        "Suppressed {} of {} similar events from {} ({}) in the last {}s ⚠️: {}",
+                        summary.suppressed,
+                        summary.logged + summary.suppressed,
+                        summary.target,
+                        summary.location,
+                        sampler.config.window.as_secs(),
+                        summary.message
+                    );
+                }
+            }
+        });
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogSampler {
+    fn event_enabled(&self, event: &Event<'_>, _ctx: Context<'_, S>) -> bool {
+        let metadata = event.metadata();
+        if *metadata.level() > Level::WARN || metadata.target() == SAMPLING_TARGET {
+            return true;
+        }
+
+        let mut windows = match self.windows.lock() {
+            Ok(windows) => windows,
+            Err(_) => return true,
+        };
+        let window = windows.entry(metadata.callsite()).or_insert_with(|| {
+            let mut visitor = MessageVisitor::default();
+            event.record(&mut visitor);
+            CallSiteWindow {
+                target: metadata.target().to_string(),
+                location: format!(
+                    "{}:{}",
+                    metadata.file().unwrap_or("unknown"),
+                    metadata.line().unwrap_or(0)
+                ),
+                message: visitor.message,
+                logged: 0,
+                suppressed: 0,
+            }
+        });
+
+        if window.logged < self.config.limit_for(metadata.target()) {
+            window.logged += 1;
+            true
+        } else {
+            window.suppressed += 1;
+            LOG_EVENTS_SUPPRESSED_TOTAL
+                .with_label_values(&[metadata.target()])
+                .inc();
+            false
+        }
+    }
+}
+
+/// Records the message of an event.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Registry;
+
+    #[test]
+    fn successfully_parses_sampling_config() {
+        // Act
+        let config = SamplingConfig::parse(30, Some("jams_serve::http=5, default=20")).unwrap();
+
+        // Assert
+        assert_eq!(config.window, Duration::from_secs(30));
+        assert_eq!(config.default_limit, 20);
+        assert_eq!(config.limit_for("jams_serve::http::service"), 5);
+        assert_eq!(config.limit_for("jams_serve::http"), 5);
+        assert_eq!(config.limit_for("jams_serve::https"), 20);
+        assert_eq!(config.limit_for("jams_core::manager"), 20);
+    }
+
+    #[test]
+    fn fails_to_parse_sampling_config_when_limit_is_invalid() {
+        // Act
+        let missing_limit = SamplingConfig::parse(30, Some("jams_serve::http"));
+        let invalid_limit = SamplingConfig::parse(30, Some("jams_serve::http=many"));
+        let zero_window = SamplingConfig::parse(0, None);
+
+        // Assert
+        assert!(missing_limit.is_err());
+        assert!(invalid_limit.is_err());
+        assert!(zero_window.is_err());
+    }
+
+    #[test]
+    fn successfully_suppresses_repeated_errors_and_reports_counts() {
+        // Arrange
+        let mut config = SamplingConfig::parse(60, None).unwrap();
+        config.default_limit = 2;
+        let sampler = LogSampler::new(config);
+        let subscriber = Registry::default().with(sampler.clone());
+
+        // Act
+        tracing::subscriber::with_default(subscriber, || {
+            for i in 0..5 {
+                tracing::error!("Failed to parse input ❌: {}", i);
+            }
+            tracing::error!("Failed to predict ❌");
+            tracing::info!("Not sampled");
+        });
+        let suppressed = sampler.flush();
+
+        // Assert
+        assert_eq!(suppressed.len(), 1);
+        assert_eq!(suppressed[0].logged, 2);
+        assert_eq!(suppressed[0].suppressed, 3);
+        assert_eq!(suppressed[0].message, "Failed to parse input ❌: 0");
+        assert!(sampler.flush().is_empty());
+    }
+}
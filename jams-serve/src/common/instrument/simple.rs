@@ -1,7 +1,17 @@
-pub fn init(log_level: tracing::Level) {
+use crate::common::instrument::sampling::LogSampler;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+pub fn init(log_level: tracing::Level, sampler: Option<LogSampler>) {
+    if let Some(sampler) = &sampler {
+        sampler.spawn_reporter();
+    }
+
     tracing_subscriber::fmt()
         .with_line_number(true)
         .with_max_level(log_level)
         .pretty()
+        .finish()
+        .with(sampler)
         .init();
 }
@@ -0,0 +1,149 @@
+use crate::common::server::Config;
+use socket2::{Domain, Protocol, Socket, TcpKeepalive, Type};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+/// Accept backlog used if none is configured. This is the same backlog as `tokio::net::TcpListener::bind`.
+const DEFAULT_ACCEPT_BACKLOG: u32 = 1024;
+
+/// Socket options of the TCP listeners serving the public endpoints.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ListenerOptions {
+    /// Number of listeners bound to the same address with `SO_REUSEPORT`, each accepted by its own task,
+    /// so that the kernel spreads bursts of new connections over several accept queues.
+    pub acceptors: usize,
+    /// Maximum number of pending connections in the accept queue of each listener.
+    pub backlog: u32,
+    /// Idle time before TCP keepalive probes are sent on accepted connections. Keepalive is disabled if not set.
+    pub keepalive: Option<Duration>,
+}
+
+impl Default for ListenerOptions {
+    fn default() -> Self {
+        ListenerOptions {
+            acceptors: 1,
+            backlog: DEFAULT_ACCEPT_BACKLOG,
+            keepalive: None,
+        }
+    }
+}
+
+impl ListenerOptions {
+    /// Reads the listener options from the server configuration.
+    pub fn from_config(config: &Config) -> Self {
+        ListenerOptions {
+            acceptors: config.acceptors.unwrap_or(1),
+            backlog: config.accept_backlog.unwrap_or(DEFAULT_ACCEPT_BACKLOG),
+            keepalive: config.tcp_keepalive.map(Duration::from_secs),
+        }
+    }
+}
+
+/// Binds one TCP listener per acceptor to the address.
+///
+/// When there is more than one acceptor, every listener is bound with `SO_REUSEPORT` and the kernel
+/// balances the new connections between them. Accepted connections inherit the keepalive settings of
+/// their listener.
+///
+/// # Arguments
+///
+/// * `address` - The `host:port` address to bind to. If the port is 0, all the listeners share the
+///   port picked for the first one.
+/// * `options` - The socket options of the listeners.
+///
+/// # Errors
+///
+/// Returns an error if the address cannot be resolved, the options are invalid or a socket cannot be bound.
+pub fn bind(address: &str, options: &ListenerOptions) -> anyhow::Result<Vec<TcpListener>> {
+    if options.acceptors == 0 {
+        anyhow::bail!("The number of acceptors must be at least 1 ❌")
+    }
+    if options.acceptors > 1 && !cfg!(unix) {
+        anyhow::bail!("Multiple acceptors require SO_REUSEPORT, which is only supported on unix ❌")
+    }
+    let mut address = match address.to_socket_addrs() {
+        Ok(mut addresses) => match addresses.next() {
+            Some(address) => address,
+            None => anyhow::bail!("Failed to resolve address {} ❌", address),
+        },
+        Err(e) => anyhow::bail!("Failed to resolve address {} ❌: {}", address, e),
+    };
+
+    let mut listeners = Vec::with_capacity(options.acceptors);
+    for _ in 0..options.acceptors {
+        let listener = match bind_socket(address, options) {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!("Failed to create TCP listener on {} ❌: {}", address, e);
+                anyhow::bail!("Failed to create TCP listener on {} ❌: {}", address, e)
+            }
+        };
+        // the other acceptors must share the port picked by the kernel for the first one
+        address = listener.local_addr()?;
+        listeners.push(listener);
+    }
+    Ok(listeners)
+}
+
+fn bind_socket(address: SocketAddr, options: &ListenerOptions) -> std::io::Result<TcpListener> {
+    let socket = Socket::new(
+        Domain::for_address(address),
+        Type::STREAM,
+        Some(Protocol::TCP),
+    )?;
+    #[cfg(unix)]
+    {
+        socket.set_reuse_address(true)?;
+        if options.acceptors > 1 {
+            socket.set_reuse_port(true)?;
+        }
+    }
+    if let Some(keepalive) = options.keepalive {
+        socket.set_tcp_keepalive(&TcpKeepalive::new().with_time(keepalive))?;
+    }
+    socket.set_nonblocking(true)?;
+    socket.bind(&address.into())?;
+    socket.listen(options.backlog.min(i32::MAX as u32) as i32)?;
+    TcpListener::from_std(socket.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn successfully_bind_multiple_acceptors_to_the_same_port() {
+        // Arrange
+        let options = ListenerOptions {
+            acceptors: 3,
+            backlog: 128,
+            keepalive: Some(Duration::from_secs(30)),
+        };
+
+        // Act
+        let listeners = bind("127.0.0.1:0", &options).unwrap();
+
+        // Assert
+        let port = listeners[0].local_addr().unwrap().port();
+        assert_eq!(listeners.len(), 3);
+        assert!(listeners
+            .iter()
+            .all(|listener| listener.local_addr().unwrap().port() == port));
+    }
+
+    #[tokio::test]
+    async fn fails_to_bind_without_acceptors() {
+        // Arrange
+        let options = ListenerOptions {
+            acceptors: 0,
+            ..Default::default()
+        };
+
+        // Act
+        let result = bind("127.0.0.1:0", &options);
+
+        // Assert
+        assert!(result.is_err())
+    }
+}
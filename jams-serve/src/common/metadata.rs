@@ -0,0 +1,254 @@
+use jams_core::model::predict::PredictOptions;
+use jams_core::pool::scheduler::Priority;
+use opentelemetry::propagation::Extractor;
+use std::collections::BTreeMap;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use uuid::Uuid;
+
+/// The header pinning the version of the model which serves a prediction, i.e. the SHA-256 digest of its
+/// artifact as returned by the model metadata. Requests fail if another version of the model is loaded.
+pub const MODEL_VERSION_HEADER: &str = "x-model-version";
+
+/// The response header naming the reduced precision variant of the model which served a prediction, e.g. `int8`
+/// when the request fell back to it because of its latency budget. Absent if the model artifact served it.
+pub const MODEL_VARIANT_HEADER: &str = "x-model-variant";
+
+/// The header setting the priority of a prediction on the worker pool, i.e. `low`, `normal` or `high`. It can only
+/// lower the priority of the model, see `Manager::priority_of`.
+pub const REQUEST_PRIORITY_HEADER: &str = "x-request-priority";
+
+/// The header used to correlate a request with its response and the server logs, by both APIs. If the client does
+/// not send one, a new request ID is generated.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Maximum length of a request ID accepted from the client.
+pub const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// The W3C trace context headers, which make the spans of a request children of the span of the caller.
+pub const TRACE_CONTEXT_HEADERS: [&str; 2] = ["traceparent", "tracestate"];
+
+/// Returns the request ID sent by the client, or a new request ID if it is missing, empty or too long.
+///
+/// # Arguments
+///
+/// * `request_id` - The value of the `x-request-id` header or metadata key, if any.
+pub fn request_id(request_id: Option<&str>) -> String {
+    match request_id {
+        Some(request_id) if !request_id.is_empty() && request_id.len() <= MAX_REQUEST_ID_LENGTH => {
+            request_id.to_string()
+        }
+        _ => Uuid::new_v4().to_string(),
+    }
+}
+
+/// Request options carried by well-known HTTP headers and gRPC metadata keys instead of the prediction payload.
+/// The same names are used by both APIs:
+///
+/// - `x-model-version` pins the version of the model, see `MODEL_VERSION_HEADER`.
+/// - `x-request-priority` lowers the priority of the prediction, unless the prediction options of the payload set one.
+/// - `traceparent` and `tracestate` propagate the trace of the caller.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestMetadata {
+    /// The pinned version of the model, in lowercase.
+    pub model_version: Option<String>,
+    /// The priority of the prediction.
+    pub priority: Option<Priority>,
+    /// The trace context headers sent by the caller, keyed by their lowercase name.
+    pub trace_context: BTreeMap<String, String>,
+}
+
+impl RequestMetadata {
+    /// Parses the well-known headers of a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `lookup` - Returns the value of a header by its lowercase name, e.g. from an HTTP `HeaderMap` or a
+    ///   gRPC `MetadataMap`. Headers which are not valid UTF-8 are treated as missing.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RequestMetadata)` - The parsed headers.
+    /// * `Err(anyhow::Error)` - If a header has an invalid value.
+    pub fn parse<'a>(lookup: impl Fn(&str) -> Option<&'a str>) -> anyhow::Result<Self> {
+        let model_version = match lookup(MODEL_VERSION_HEADER).map(str::trim) {
+            Some("") => {
+                anyhow::bail!("{} must not be empty ❌", MODEL_VERSION_HEADER)
+            }
+            version => version.map(str::to_lowercase),
+        };
+
+        let priority = match lookup(REQUEST_PRIORITY_HEADER).map(str::trim) {
+            Some(value) => match Priority::ALL
+                .into_iter()
+                .find(|priority| priority.as_str().eq_ignore_ascii_case(value))
+            {
+                Some(priority) => Some(priority),
+                None => anyhow::bail!(
+                    "{} must be one of low, normal or high, got '{}' ❌",
+                    REQUEST_PRIORITY_HEADER,
+                    value
+                ),
+            },
+            None => None,
+        };
+
+        let trace_context = TRACE_CONTEXT_HEADERS
+            .iter()
+            .filter_map(|name| lookup(name).map(|value| (name.to_string(), value.to_string())))
+            .collect();
+
+        Ok(RequestMetadata {
+            model_version,
+            priority,
+            trace_context,
+        })
+    }
+
+    /// Applies the options set by the headers to the prediction options of the payload, which take precedence.
+    /// The pinned version is checked by the `Manager` against the model which makes the prediction.
+    pub fn apply(&self, options: &mut PredictOptions) {
+        if options.priority.is_none() {
+            options.priority = self.priority;
+        }
+        if options.model_version.is_none() {
+            options.model_version = self.model_version.clone();
+        }
+    }
+
+    /// Checks that the loaded version of a model is the pinned version, if any, so that a streamed prediction is
+    /// rejected before the stream starts. The `Manager` checks the version again against the model which makes
+    /// the prediction, as the model may be swapped in between.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - The name of the model.
+    /// * `loaded_version` - Returns the SHA-256 digest of the artifact of the loaded model, if the model is
+    ///   loaded. Only called when a version is pinned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a version is pinned and another version of the model is loaded. Missing models are
+    /// left to the prediction, which reports them like for any other request.
+    pub fn check_model_version(
+        &self,
+        model_name: &str,
+        loaded_version: impl FnOnce() -> Option<String>,
+    ) -> anyhow::Result<()> {
+        let pinned = match &self.model_version {
+            Some(pinned) => pinned,
+            None => return Ok(()),
+        };
+        match loaded_version() {
+            Some(loaded) if !pinned.eq_ignore_ascii_case(loaded.as_str()) => {
+                anyhow::bail!(
+                    "model {} is at version {}, not the pinned version {} ❌",
+                    model_name,
+                    loaded,
+                    pinned
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Makes a span a child of the span of the caller, if the request carries a trace context.
+    pub fn attach_trace(&self, span: &tracing::Span) {
+        if self.trace_context.is_empty() {
+            return;
+        }
+        let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&TraceContext(&self.trace_context))
+        });
+        span.set_parent(parent);
+    }
+}
+
+struct TraceContext<'a>(&'a BTreeMap<String, String>);
+
+impl Extractor for TraceContext<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(String::as_str).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_parse_request_metadata() {
+        // Arrange
+        let headers = BTreeMap::from([
+            ("x-model-version", " ABC123 "),
+            ("x-request-priority", "High"),
+            (
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            ),
+            ("authorization", "Bearer secret"),
+        ]);
+
+        // Act
+        let metadata = RequestMetadata::parse(|name| headers.get(name).copied()).unwrap();
+        let mut options = PredictOptions::default();
+        metadata.apply(&mut options);
+        let mut low = PredictOptions {
+            priority: Some(Priority::Low),
+            ..Default::default()
+        };
+        metadata.apply(&mut low);
+
+        // Assert
+        assert_eq!(metadata.model_version, Some("abc123".to_string()));
+        assert_eq!(options.priority, Some(Priority::High));
+        assert_eq!(options.model_version, Some("abc123".to_string()));
+        assert_eq!(low.priority, Some(Priority::Low));
+        assert_eq!(
+            metadata.trace_context.keys().collect::<Vec<_>>(),
+            vec!["traceparent"]
+        );
+        assert_eq!(
+            RequestMetadata::parse(|_| None).unwrap(),
+            RequestMetadata::default()
+        );
+    }
+
+    #[test]
+    fn fails_to_parse_invalid_request_metadata() {
+        // Arrange
+        let priority = BTreeMap::from([("x-request-priority", "urgent")]);
+        let version = BTreeMap::from([("x-model-version", " ")]);
+
+        // Act
+        let priority = RequestMetadata::parse(|name| priority.get(name).copied());
+        let version = RequestMetadata::parse(|name| version.get(name).copied());
+
+        // Assert
+        assert!(priority.unwrap_err().to_string().contains("urgent"));
+        assert!(version.is_err());
+    }
+
+    #[test]
+    fn successfully_check_pinned_model_version() {
+        // Arrange
+        let headers = BTreeMap::from([("x-model-version", "abc123")]);
+        let metadata = RequestMetadata::parse(|name| headers.get(name).copied()).unwrap();
+
+        // Act
+        let same = metadata.check_model_version("titanic", || Some("ABC123".to_string()));
+        let other = metadata.check_model_version("titanic", || Some("def456".to_string()));
+        let missing = metadata.check_model_version("titanic", || None);
+        let unpinned = RequestMetadata::default()
+            .check_model_version("titanic", || panic!("the version is not pinned"));
+
+        // Assert
+        assert!(same.is_ok());
+        assert!(other.unwrap_err().to_string().contains("def456"));
+        assert!(missing.is_ok());
+        assert!(unpinned.is_ok());
+    }
+}
@@ -0,0 +1,1311 @@
+use chrono::DateTime;
+use jams_core::model::stats::ModelStatsSnapshot;
+use jams_core::model::validation::ValidationOutcome;
+use jams_core::model::variant::{LatencyFallback, VariantLatency};
+use jams_core::model_store::deadline::StoreOperation;
+use jams_core::model_store::misses::ModelMiss;
+use jams_core::model_store::storage::Metadata;
+use jams_core::model_store::supervisor::PollerHealth;
+use jams_core::pool::scheduler::{FlowStats, SchedulerStats};
+use lazy_static::lazy_static;
+use prometheus::{
+    register_counter_vec_with_registry, register_gauge_vec_with_registry,
+    register_histogram_vec_with_registry, register_int_counter_vec_with_registry,
+    register_int_counter_with_registry, register_int_gauge_vec_with_registry,
+    register_int_gauge_with_registry, Counter, CounterVec, Encoder, GaugeVec, HistogramVec,
+    IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Registry, TextEncoder,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Sub;
+use std::sync::Mutex;
+
+/// Label value used for metrics recorded by the HTTP server.
+pub const HTTP: &str = "http";
+/// Label value used for metrics recorded by the gRPC server.
+pub const GRPC: &str = "grpc";
+
+/// Number of the most requested missing model names exposed by the model miss metrics, so that clients sending
+/// random model names cannot create a series per made up model name.
+pub const TOP_MODEL_MISSES: usize = 10;
+
+/// Histogram buckets (in seconds) used for measuring how long it takes to read a request body.
+const BODY_READ_SECONDS_BUCKETS: &[f64] = &[
+    0.0005, 0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Histogram buckets (in bytes) used for measuring request payload sizes. Ranges from 64B to 16MiB.
+const PAYLOAD_BYTES_BUCKETS: &[f64] = &[
+    64.0, 256.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0, 16777216.0,
+];
+
+lazy_static! {
+    /// The registry which holds all the metrics exposed by the server.
+    ///
+    /// A dedicated registry is used instead of the prometheus default registry so that only
+    /// the metrics owned by jams are exposed on the `/metrics` endpoint.
+    pub static ref REGISTRY: Registry = Registry::new_custom(Some("jams".to_string()), None)
+        .expect("Failed to create metrics registry ❌");
+
+    /// Time taken to read the request body from the client, labelled by protocol and path.
+    pub static ref REQUEST_BODY_READ_SECONDS: HistogramVec = register_histogram_vec_with_registry!(
+        "request_body_read_seconds",
+        "Time taken to read the request body from the client",
+        &["protocol", "path"],
+        BODY_READ_SECONDS_BUCKETS.to_vec(),
+        REGISTRY
+    )
+    .expect("Failed to register request_body_read_seconds metric ❌");
+
+    /// Size of the request payloads, labelled by protocol and path.
+    pub static ref REQUEST_PAYLOAD_BYTES: HistogramVec = register_histogram_vec_with_registry!(
+        "request_payload_bytes",
+        "Size of the request payload in bytes",
+        &["protocol", "path"],
+        PAYLOAD_BYTES_BUCKETS.to_vec(),
+        REGISTRY
+    )
+    .expect("Failed to register request_payload_bytes metric ❌");
+
+    /// Size of the model inputs of the prediction requests served by each model, labelled by protocol and model.
+    pub static ref MODEL_REQUEST_PAYLOAD_BYTES: HistogramVec = register_histogram_vec_with_registry!(
+        "model_request_payload_bytes",
+        "Size of the model input of the prediction requests served by the model in bytes",
+        &["protocol", "model"],
+        PAYLOAD_BYTES_BUCKETS.to_vec(),
+        REGISTRY
+    )
+    .expect("Failed to register model_request_payload_bytes metric ❌");
+
+    /// Size of the predictions returned by each model, labelled by protocol and model.
+    pub static ref MODEL_RESPONSE_PAYLOAD_BYTES: HistogramVec = register_histogram_vec_with_registry!(
+        "model_response_payload_bytes",
+        "Size of the predictions returned by the model in bytes",
+        &["protocol", "model"],
+        PAYLOAD_BYTES_BUCKETS.to_vec(),
+        REGISTRY
+    )
+    .expect("Failed to register model_response_payload_bytes metric ❌");
+
+    /// Number of requests for which the client took longer than the slow client threshold
+    /// to send the request body.
+    pub static ref SLOW_CLIENTS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "slow_clients_total",
+        "Number of requests where the client was slow to send the request body",
+        &["protocol", "path"],
+        REGISTRY
+    )
+    .expect("Failed to register slow_clients_total metric ❌");
+
+    /// Number of requests where the client went away before a response was sent.
+    pub static ref CLIENT_DISCONNECTS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "client_disconnects_total",
+        "Number of requests where the client disconnected before the response was sent",
+        &["protocol", "endpoint"],
+        REGISTRY
+    )
+    .expect("Failed to register client_disconnects_total metric ❌");
+
+    /// Number of requests which were abandoned because the deadline set by the client was exceeded.
+    pub static ref DEADLINES_EXCEEDED_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "deadlines_exceeded_total",
+        "Number of requests where the deadline set by the client was exceeded",
+        &["protocol", "endpoint"],
+        REGISTRY
+    )
+    .expect("Failed to register deadlines_exceeded_total metric ❌");
+
+    /// Number of predictions which were skipped by a worker because the caller was no longer
+    /// waiting for the result.
+    pub static ref PREDICTIONS_CANCELLED_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "predictions_cancelled_total",
+        "Number of predictions cancelled before being executed",
+        &["model_name"],
+        REGISTRY
+    )
+    .expect("Failed to register predictions_cancelled_total metric ❌");
+
+    /// Number of predictions which panicked, usually inside the native library of a framework.
+    pub static ref PREDICTION_PANICS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "prediction_panics_total",
+        "Number of predictions which panicked",
+        &["model_name"],
+        REGISTRY
+    )
+    .expect("Failed to register prediction_panics_total metric ❌");
+
+    /// The last sampled resident memory of the process.
+    pub static ref RESIDENT_MEMORY_BYTES: IntGauge = register_int_gauge_with_registry!(
+        "process_resident_memory_bytes",
+        "Resident memory of the process in bytes",
+        REGISTRY
+    )
+    .expect("Failed to register process_resident_memory_bytes metric ❌");
+
+    /// Number of predictions queued or running across all models.
+    pub static ref IN_FLIGHT_PREDICTIONS: IntGauge = register_int_gauge_with_registry!(
+        "in_flight_predictions",
+        "Number of predictions queued or running across all models",
+        REGISTRY
+    )
+    .expect("Failed to register in_flight_predictions metric ❌");
+
+    /// Number of requests which were rejected to protect the server, labelled by the reason.
+    pub static ref LOAD_SHED_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "load_shed_total",
+        "Number of requests rejected by load shedding",
+        &["protocol", "endpoint", "reason"],
+        REGISTRY
+    )
+    .expect("Failed to register load_shed_total metric ❌");
+
+    /// Number of prediction records which were dropped because the prediction log could not keep up.
+    pub static ref PREDICTION_RECORDS_DROPPED_TOTAL: IntCounter = register_int_counter_with_registry!(
+        "prediction_records_dropped_total",
+        "Number of prediction records dropped before being written to the prediction log",
+        REGISTRY
+    )
+    .expect("Failed to register prediction_records_dropped_total metric ❌");
+
+    /// Number of requests made to the deprecated unversioned API routes, labelled by path.
+    pub static ref DEPRECATED_REQUESTS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "deprecated_requests_total",
+        "Number of requests made to deprecated API routes",
+        &["path"],
+        REGISTRY
+    )
+    .expect("Failed to register deprecated_requests_total metric ❌");
+
+    /// Number of warning and error logs suppressed by log sampling, labelled by the module which emitted them.
+    pub static ref LOG_EVENTS_SUPPRESSED_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "log_events_suppressed_total",
+        "Number of log events suppressed by log sampling",
+        &["module"],
+        REGISTRY
+    )
+    .expect("Failed to register log_events_suppressed_total metric ❌");
+
+    /// Number of workers in the worker pool of each namespace.
+    pub static ref NAMESPACE_WORKERS: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "namespace_workers",
+        "Number of workers in the worker pool of the namespace",
+        &["namespace"],
+        REGISTRY
+    )
+    .expect("Failed to register namespace_workers metric ❌");
+
+    /// Number of workers of each namespace which are busy making predictions. Divide by
+    /// `namespace_workers` to get the utilization of the namespace.
+    pub static ref NAMESPACE_BUSY_WORKERS: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "namespace_busy_workers",
+        "Number of workers of the namespace which are making predictions",
+        &["namespace"],
+        REGISTRY
+    )
+    .expect("Failed to register namespace_busy_workers metric ❌");
+
+    /// Number of predictions waiting for a worker in each namespace.
+    pub static ref NAMESPACE_QUEUED_PREDICTIONS: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "namespace_queued_predictions",
+        "Number of predictions waiting for a worker in the namespace",
+        &["namespace"],
+        REGISTRY
+    )
+    .expect("Failed to register namespace_queued_predictions metric ❌");
+
+    /// Number of predictions rejected because the queue of the namespace was full.
+    pub static ref NAMESPACE_REJECTED_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "namespace_rejected_total",
+        "Number of predictions rejected because the queue of the namespace was full",
+        &["namespace"],
+        REGISTRY
+    )
+    .expect("Failed to register namespace_rejected_total metric ❌");
+
+    /// Number of lookups in the input schema cache, labelled by whether the schema was already validated.
+    pub static ref SCHEMA_CACHE_LOOKUPS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "schema_cache_lookups_total",
+        "Number of lookups in the input schema cache",
+        &["result"],
+        REGISTRY
+    )
+    .expect("Failed to register schema_cache_lookups_total metric ❌");
+
+    /// Number of served predictions of the deduplicated models by outcome, i.e. `unique` for the first request
+    /// with a payload in the window, `duplicate` for the duplicates which were predicted and `squashed` for the
+    /// duplicates which were served the prediction of the first request.
+    pub static ref DEDUP_REQUESTS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "dedup_requests_total",
+        "Number of served predictions of the deduplicated models by outcome",
+        &["model_name", "outcome"],
+        REGISTRY
+    )
+    .expect("Failed to register dedup_requests_total metric ❌");
+
+    /// Number of authorized predict and admin calls by action and decision.
+    pub static ref AUTHZ_DECISIONS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "authz_decisions_total",
+        "Number of authorized predict and admin calls by action and decision",
+        &["action", "decision"],
+        REGISTRY
+    )
+    .expect("Failed to register authz_decisions_total metric ❌");
+
+    /// Number of S3 event notifications of model artifacts applied to the models by action and outcome.
+    pub static ref S3_EVENTS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "s3_events_total",
+        "Number of S3 event notifications of model artifacts applied to the models by action and outcome",
+        &["action", "outcome"],
+        REGISTRY
+    )
+    .expect("Failed to register s3_events_total metric ❌");
+
+    /// Whether the startup smoke test of each framework passed, 1 if it did and 0 if it failed.
+    pub static ref SMOKE_TEST_PASSED: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "smoke_test_passed",
+        "Whether the startup smoke test of the framework passed",
+        &["framework"],
+        REGISTRY
+    )
+    .expect("Failed to register smoke_test_passed metric ❌");
+
+    /// Number of prediction requests made to each loaded model since it was added or since startup.
+    pub static ref MODEL_REQUESTS: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "model_requests",
+        "Number of prediction requests made to the model since it was added or since startup",
+        &["model"],
+        REGISTRY
+    )
+    .expect("Failed to register model_requests metric ❌");
+
+    /// Unix timestamp (in seconds) of the last prediction request made to each loaded model which was used.
+    pub static ref MODEL_LAST_ACCESS_TIMESTAMP_SECONDS: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "model_last_access_timestamp_seconds",
+        "Unix timestamp of the last prediction request made to the model",
+        &["model"],
+        REGISTRY
+    )
+    .expect("Failed to register model_last_access_timestamp_seconds metric ❌");
+
+    /// Number of calls to a remote model store which were abandoned because they exceeded their deadline,
+    /// labelled by the operation.
+    pub static ref STORE_TIMEOUTS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "store_timeouts_total",
+        "Number of calls to the model store which exceeded their deadline",
+        &["operation"],
+        REGISTRY
+    )
+    .expect("Failed to register store_timeouts_total metric ❌");
+
+    /// Number of prediction requests made to models which were not loaded.
+    pub static ref MODEL_MISSES_TOTAL: IntCounter = register_int_counter_with_registry!(
+        "model_misses_total",
+        "Number of prediction requests made to models which were not loaded",
+        REGISTRY
+    )
+    .expect("Failed to register model_misses_total metric ❌");
+
+    /// Number of prediction requests made to each of the most requested model names which are not loaded.
+    pub static ref MODEL_NAME_MISSES_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "model_name_misses_total",
+        "Number of prediction requests made to the model name while it was not loaded",
+        &["model"],
+        REGISTRY
+    )
+    .expect("Failed to register model_name_misses_total metric ❌");
+
+    /// Number of models evaluated against the acceptance criteria of their validation set before being promoted,
+    /// labelled by whether they were accepted or rejected.
+    pub static ref MODEL_VALIDATIONS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "model_validations_total",
+        "Number of models evaluated against the acceptance criteria of their validation set",
+        &["outcome"],
+        REGISTRY
+    )
+    .expect("Failed to register model_validations_total metric ❌");
+
+    /// Number of predictions made with each precision variant of the models shipped with variants.
+    pub static ref MODEL_VARIANT_PREDICTIONS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "model_variant_predictions_total",
+        "Number of predictions made with the precision variant of the model",
+        &["model", "precision"],
+        REGISTRY
+    )
+    .expect("Failed to register model_variant_predictions_total metric ❌");
+
+    /// Total time (in seconds) spent predicting with each precision variant of the models shipped with variants.
+    /// Divided by `model_variant_predictions_total`, it gives the mean latency of each variant.
+    pub static ref MODEL_VARIANT_PREDICT_SECONDS_TOTAL: CounterVec = register_counter_vec_with_registry!(
+        "model_variant_predict_seconds_total",
+        "Total time spent predicting with the precision variant of the model",
+        &["model", "precision"],
+        REGISTRY
+    )
+    .expect("Failed to register model_variant_predict_seconds_total metric ❌");
+
+    /// Number of predictions routed to the lightweight variant of each model because the recent p95 latency of the
+    /// model exceeded the latency budget of the request.
+    pub static ref MODEL_LATENCY_FALLBACKS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "model_latency_fallbacks_total",
+        "Number of predictions served by the lightweight variant of the model because the model exceeded the latency budget of the request",
+        &["model", "precision"],
+        REGISTRY
+    )
+    .expect("Failed to register model_latency_fallbacks_total metric ❌");
+
+    /// Number of predictions made by each model, including the predictions which failed.
+    pub static ref MODEL_PREDICTIONS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "model_predictions_total",
+        "Number of predictions made by the model",
+        &["model"],
+        REGISTRY
+    )
+    .expect("Failed to register model_predictions_total metric ❌");
+
+    /// Number of predictions of each model which failed.
+    pub static ref MODEL_PREDICTION_ERRORS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "model_prediction_errors_total",
+        "Number of predictions of the model which failed",
+        &["model"],
+        REGISTRY
+    )
+    .expect("Failed to register model_prediction_errors_total metric ❌");
+
+    /// Ratio of the predictions of each model which failed in the last minute, to track error budgets against SLOs.
+    /// Models which made no prediction in the last minute have no series.
+    pub static ref MODEL_RECENT_ERROR_RATIO: GaugeVec = register_gauge_vec_with_registry!(
+        "model_recent_error_ratio",
+        "Ratio of the predictions of the model which failed in the last minute",
+        &["model"],
+        REGISTRY
+    )
+    .expect("Failed to register model_recent_error_ratio metric ❌");
+
+    /// Latency (in seconds) of the model artifact of each model in the last minute, labelled by quantile. Estimated
+    /// by the model within 2% of the exact quantile.
+    pub static ref MODEL_RECENT_LATENCY_SECONDS: GaugeVec = register_gauge_vec_with_registry!(
+        "model_recent_latency_seconds",
+        "Latency of the model in the last minute at the quantile",
+        &["model", "quantile"],
+        REGISTRY
+    )
+    .expect("Failed to register model_recent_latency_seconds metric ❌");
+
+    /// Number of tasks of each worker pool by priority and outcome, i.e. `submitted`, `cancelled`, `completed`,
+    /// `panicked`, `wait_over_budget` and `execute_over_budget`. The shared worker pool is labelled `shared`,
+    /// the others by their namespace.
+    pub static ref SCHEDULER_TASKS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "scheduler_tasks_total",
+        "Number of tasks of the worker pool by priority and outcome",
+        &["scheduler", "priority", "outcome"],
+        REGISTRY
+    )
+    .expect("Failed to register scheduler_tasks_total metric ❌");
+
+    /// Number of tasks of each worker pool waiting for a worker, by priority.
+    pub static ref SCHEDULER_QUEUED_TASKS: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "scheduler_queued_tasks",
+        "Number of tasks of the worker pool waiting for a worker",
+        &["scheduler", "priority"],
+        REGISTRY
+    )
+    .expect("Failed to register scheduler_queued_tasks metric ❌");
+
+    /// Total time (in seconds) the completed tasks of each worker pool waited for a worker, by priority.
+    /// Divided by the `completed` tasks, it gives the mean queueing delay.
+    pub static ref SCHEDULER_WAIT_SECONDS_TOTAL: CounterVec = register_counter_vec_with_registry!(
+        "scheduler_wait_seconds_total",
+        "Total time the completed tasks of the worker pool waited for a worker",
+        &["scheduler", "priority"],
+        REGISTRY
+    )
+    .expect("Failed to register scheduler_wait_seconds_total metric ❌");
+
+    /// Total time (in seconds) the completed tasks of each worker pool ran for, by priority.
+    pub static ref SCHEDULER_EXECUTE_SECONDS_TOTAL: CounterVec = register_counter_vec_with_registry!(
+        "scheduler_execute_seconds_total",
+        "Total time the completed tasks of the worker pool ran for",
+        &["scheduler", "priority"],
+        REGISTRY
+    )
+    .expect("Failed to register scheduler_execute_seconds_total metric ❌");
+
+    /// The scheduling weight of each model on each worker pool.
+    pub static ref SCHEDULER_MODEL_WEIGHT: GaugeVec = register_gauge_vec_with_registry!(
+        "scheduler_model_weight",
+        "Scheduling weight of the model on the worker pool",
+        &["scheduler", "model"],
+        REGISTRY
+    )
+    .expect("Failed to register scheduler_model_weight metric ❌");
+
+    /// Number of tasks of each model waiting for a worker of each worker pool.
+    pub static ref SCHEDULER_MODEL_QUEUED_TASKS: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "scheduler_model_queued_tasks",
+        "Number of tasks of the model waiting for a worker of the worker pool",
+        &["scheduler", "model"],
+        REGISTRY
+    )
+    .expect("Failed to register scheduler_model_queued_tasks metric ❌");
+
+    /// Number of tasks of each model completed by each worker pool.
+    pub static ref SCHEDULER_MODEL_COMPLETED_TASKS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "scheduler_model_completed_tasks_total",
+        "Number of tasks of the model completed by the worker pool",
+        &["scheduler", "model"],
+        REGISTRY
+    )
+    .expect("Failed to register scheduler_model_completed_tasks_total metric ❌");
+
+    /// Total time (in seconds) the completed tasks of each model ran for on each worker pool.
+    pub static ref SCHEDULER_MODEL_EXECUTE_SECONDS_TOTAL: CounterVec = register_counter_vec_with_registry!(
+        "scheduler_model_execute_seconds_total",
+        "Total time the completed tasks of the model ran for on the worker pool",
+        &["scheduler", "model"],
+        REGISTRY
+    )
+    .expect("Failed to register scheduler_model_execute_seconds_total metric ❌");
+
+    /// Fraction of the time the completed tasks of each worker pool ran for which went to each model. Under
+    /// load, the shares of the models with queued tasks follow their scheduling weights.
+    pub static ref SCHEDULER_MODEL_SHARE: GaugeVec = register_gauge_vec_with_registry!(
+        "scheduler_model_share",
+        "Fraction of the time the completed tasks of the worker pool ran for which went to the model",
+        &["scheduler", "model"],
+        REGISTRY
+    )
+    .expect("Failed to register scheduler_model_share metric ❌");
+
+    /// Whether the poller of each model store is healthy (1) or failed too many times in a row (0). The model
+    /// store the server was started with is labelled `default`, the attached ones by their name.
+    pub static ref POLLER_HEALTHY: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "poller_healthy",
+        "Whether the poller of the model store is healthy",
+        &["store"],
+        REGISTRY
+    )
+    .expect("Failed to register poller_healthy metric ❌");
+
+    /// Number of polls of each model store by outcome, i.e. `succeeded` and `failed`, including the polls which panicked.
+    pub static ref POLLER_POLLS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "poller_polls_total",
+        "Number of polls of the model store since startup by outcome",
+        &["store", "outcome"],
+        REGISTRY
+    )
+    .expect("Failed to register poller_polls_total metric ❌");
+
+    /// Number of polls of each model store which panicked and were restarted.
+    pub static ref POLLER_RESTARTS_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+        "poller_restarts_total",
+        "Number of polls of the model store which panicked and were restarted since startup",
+        &["store"],
+        REGISTRY
+    )
+    .expect("Failed to register poller_restarts_total metric ❌");
+
+    /// Number of failed polls of each model store since its last poll which succeeded.
+    pub static ref POLLER_CONSECUTIVE_FAILURES: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "poller_consecutive_failures",
+        "Number of failed polls of the model store since the last poll which succeeded",
+        &["store"],
+        REGISTRY
+    )
+    .expect("Failed to register poller_consecutive_failures metric ❌");
+}
+
+/// Sets the usage metrics of the loaded models, dropping the series of the models which are no longer loaded.
+///
+/// # Arguments
+///
+/// * `models` - The metadata of the loaded models, including their usage.
+pub fn record_model_usage(models: &[Metadata]) {
+    MODEL_REQUESTS.reset();
+    MODEL_LAST_ACCESS_TIMESTAMP_SECONDS.reset();
+    for model in models {
+        MODEL_REQUESTS
+            .with_label_values(&[model.name.as_str()])
+            .set(model.request_count as i64);
+        let last_accessed = model
+            .last_accessed
+            .as_deref()
+            .and_then(|last_accessed| DateTime::parse_from_rfc3339(last_accessed).ok());
+        if let Some(last_accessed) = last_accessed {
+            MODEL_LAST_ACCESS_TIMESTAMP_SECONDS
+                .with_label_values(&[model.name.as_str()])
+                .set(last_accessed.timestamp());
+        }
+    }
+}
+
+/// The totals of the precision variants of the models as of the last scrape, i.e. their predictions and the seconds
+/// spent predicting, to advance the counters by what each variant predicted since.
+static RECORDED_VARIANT_LATENCIES: Mutex<BTreeMap<(String, String), (u64, f64)>> =
+    Mutex::new(BTreeMap::new());
+
+/// Advances the latency counters of the precision variants of the models, dropping the series of the models
+/// which are no longer loaded.
+///
+/// # Arguments
+///
+/// * `latencies` - The time spent predicting with each variant of the models shipped with variants.
+pub fn record_variant_latencies(latencies: &[VariantLatency]) {
+    let mut recorded = match RECORDED_VARIANT_LATENCIES.lock() {
+        Ok(recorded) => recorded,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let mut observed = BTreeMap::new();
+    for latency in latencies {
+        let labels = [latency.model_name.as_str(), latency.precision.as_str()];
+        let key = (labels[0].to_string(), labels[1].to_string());
+        let previous = recorded.remove(&key);
+        MODEL_VARIANT_PREDICTIONS_TOTAL
+            .with_label_values(&labels)
+            .inc_by(growth(
+                previous.map(|previous| previous.0),
+                latency.predictions,
+            ));
+        MODEL_VARIANT_PREDICT_SECONDS_TOTAL
+            .with_label_values(&labels)
+            .inc_by(growth(previous.map(|previous| previous.1), latency.seconds));
+        observed.insert(key, (latency.predictions, latency.seconds));
+    }
+    // the variants left were not reported, i.e. their models are no longer loaded
+    for (model_name, precision) in recorded.keys() {
+        let _ = MODEL_VARIANT_PREDICTIONS_TOTAL.remove_label_values(&[model_name, precision]);
+        let _ = MODEL_VARIANT_PREDICT_SECONDS_TOTAL.remove_label_values(&[model_name, precision]);
+    }
+    *recorded = observed;
+}
+
+/// The totals of the models as of the last scrape, i.e. their predictions and failed predictions since they were
+/// loaded, to advance the counters by what each model predicted since.
+static RECORDED_MODEL_STATS: Mutex<BTreeMap<String, (u64, u64)>> = Mutex::new(BTreeMap::new());
+
+/// Advances the prediction counters and sets the latency and error metrics of the models, dropping the series of
+/// the models which are no longer loaded.
+///
+/// # Arguments
+///
+/// * `stats` - The statistics of each loaded model.
+pub fn record_model_stats(stats: &[ModelStatsSnapshot]) {
+    let mut recorded = match RECORDED_MODEL_STATS.lock() {
+        Ok(recorded) => recorded,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let mut observed = BTreeMap::new();
+    MODEL_RECENT_ERROR_RATIO.reset();
+    MODEL_RECENT_LATENCY_SECONDS.reset();
+    for stats in stats {
+        let model_name = stats.model_name.as_str();
+        let previous = recorded.remove(model_name);
+        MODEL_PREDICTIONS_TOTAL
+            .with_label_values(&[model_name])
+            .inc_by(growth(
+                previous.map(|previous| previous.0),
+                stats.predictions,
+            ));
+        MODEL_PREDICTION_ERRORS_TOTAL
+            .with_label_values(&[model_name])
+            .inc_by(growth(previous.map(|previous| previous.1), stats.errors));
+        observed.insert(stats.model_name.clone(), (stats.predictions, stats.errors));
+        if stats.recent_predictions > 0 {
+            MODEL_RECENT_ERROR_RATIO
+                .with_label_values(&[model_name])
+                .set(stats.recent_errors as f64 / stats.recent_predictions as f64);
+        }
+        for (quantile, seconds) in stats.latency_quantiles.iter() {
+            MODEL_RECENT_LATENCY_SECONDS
+                .with_label_values(&[model_name, quantile.to_string().as_str()])
+                .set(*seconds);
+        }
+    }
+    // the models left were not reported, i.e. they are no longer loaded
+    for model_name in recorded.keys() {
+        let _ = MODEL_PREDICTIONS_TOTAL.remove_label_values(&[model_name]);
+        let _ = MODEL_PREDICTION_ERRORS_TOTAL.remove_label_values(&[model_name]);
+    }
+    *recorded = observed;
+}
+
+/// Returns how much a total which starts over, e.g. when its model is reloaded, grew since it was last recorded. A
+/// total below the recorded one has started over since, so all of it is new.
+fn growth<T: Copy + PartialOrd + Sub<Output = T>>(recorded: Option<T>, total: T) -> T {
+    match recorded {
+        Some(recorded) if total >= recorded => total - recorded,
+        _ => total,
+    }
+}
+
+/// Catches up the number of predictions routed to the lightweight variant of each model with the totals counted
+/// by the manager since startup.
+///
+/// # Arguments
+///
+/// * `fallbacks` - The number of predictions routed to the lightweight variant of each model.
+pub fn record_latency_fallbacks(fallbacks: &[LatencyFallback]) {
+    for fallback in fallbacks {
+        catch_up(
+            &MODEL_LATENCY_FALLBACKS_TOTAL
+                .with_label_values(&[fallback.model_name.as_str(), fallback.precision.as_str()]),
+            fallback.predictions,
+        );
+    }
+}
+
+/// Sets the queue metrics of the worker pools and catches up their task metrics with the accounting of their
+/// schedulers.
+///
+/// # Arguments
+///
+/// * `stats` - The accounting of each worker pool by priority, as tracked by its scheduler.
+pub fn record_scheduler_stats(stats: &[SchedulerStats]) {
+    for stats in stats {
+        let labels = [stats.scheduler.as_str(), stats.priority.as_str()];
+        for (outcome, count) in [
+            ("submitted", stats.submitted),
+            ("cancelled", stats.cancelled),
+            ("completed", stats.completed),
+            ("panicked", stats.panicked),
+            ("wait_over_budget", stats.wait_over_budget),
+            ("execute_over_budget", stats.execute_over_budget),
+        ] {
+            catch_up(
+                &SCHEDULER_TASKS_TOTAL.with_label_values(&[labels[0], labels[1], outcome]),
+                count,
+            );
+        }
+        SCHEDULER_QUEUED_TASKS
+            .with_label_values(&labels)
+            .set(stats.queued as i64);
+        catch_up_seconds(
+            &SCHEDULER_WAIT_SECONDS_TOTAL.with_label_values(&labels),
+            stats.wait_seconds,
+        );
+        catch_up_seconds(
+            &SCHEDULER_EXECUTE_SECONDS_TOTAL.with_label_values(&labels),
+            stats.execute_seconds,
+        );
+    }
+}
+
+/// The totals of the models of each worker pool as of the last scrape, i.e. their completed tasks and the seconds
+/// they ran for, to advance the counters by what each model completed since.
+static RECORDED_FLOW_STATS: Mutex<BTreeMap<(String, String), (u64, f64)>> =
+    Mutex::new(BTreeMap::new());
+
+/// Sets the per model metrics of worker pools from the accounting of their schedulers, dropping the series of the
+/// models whose flows were evicted for being idle.
+///
+/// # Arguments
+///
+/// * `stats` - The accounting of each model of every scheduler.
+pub fn record_scheduler_flow_stats(stats: &[FlowStats]) {
+    let mut recorded = match RECORDED_FLOW_STATS.lock() {
+        Ok(recorded) => recorded,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let mut observed = BTreeMap::new();
+    for stats in stats {
+        let labels = [stats.scheduler.as_str(), stats.flow.as_str()];
+        let key = (stats.scheduler.clone(), stats.flow.clone());
+        let previous = recorded.remove(&key);
+        SCHEDULER_MODEL_WEIGHT
+            .with_label_values(&labels)
+            .set(stats.weight);
+        SCHEDULER_MODEL_QUEUED_TASKS
+            .with_label_values(&labels)
+            .set(stats.queued as i64);
+        SCHEDULER_MODEL_COMPLETED_TASKS_TOTAL
+            .with_label_values(&labels)
+            .inc_by(growth(previous.map(|previous| previous.0), stats.completed));
+        SCHEDULER_MODEL_EXECUTE_SECONDS_TOTAL
+            .with_label_values(&labels)
+            .inc_by(growth(
+                previous.map(|previous| previous.1),
+                stats.execute_seconds,
+            ));
+        SCHEDULER_MODEL_SHARE
+            .with_label_values(&labels)
+            .set(stats.share);
+        observed.insert(key, (stats.completed, stats.execute_seconds));
+    }
+    // the flows left were not reported, i.e. they were evicted
+    for (scheduler, model_name) in recorded.keys() {
+        let labels = [scheduler.as_str(), model_name.as_str()];
+        let _ = SCHEDULER_MODEL_WEIGHT.remove_label_values(&labels);
+        let _ = SCHEDULER_MODEL_QUEUED_TASKS.remove_label_values(&labels);
+        let _ = SCHEDULER_MODEL_COMPLETED_TASKS_TOTAL.remove_label_values(&labels);
+        let _ = SCHEDULER_MODEL_EXECUTE_SECONDS_TOTAL.remove_label_values(&labels);
+        let _ = SCHEDULER_MODEL_SHARE.remove_label_values(&labels);
+    }
+    *recorded = observed;
+}
+
+/// The model stores whose pollers were recorded last, to drop the series of the stores which are detached since.
+static RECORDED_POLLER_STORES: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Sets the health metrics of the pollers of the model stores and catches up their poll counters, dropping the
+/// series of the detached stores so that a store attached again under the same name counts from zero.
+///
+/// # Arguments
+///
+/// * `pollers` - The health of the poller of every model store.
+pub fn record_poller_health(pollers: &[PollerHealth]) {
+    let mut recorded = match RECORDED_POLLER_STORES.lock() {
+        Ok(recorded) => recorded,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    for store in recorded.iter() {
+        if !pollers.iter().any(|poller| &poller.store == store) {
+            let _ = POLLER_POLLS_TOTAL.remove_label_values(&[store, "succeeded"]);
+            let _ = POLLER_POLLS_TOTAL.remove_label_values(&[store, "failed"]);
+            let _ = POLLER_RESTARTS_TOTAL.remove_label_values(&[store]);
+        }
+    }
+    *recorded = pollers.iter().map(|poller| poller.store.clone()).collect();
+
+    POLLER_HEALTHY.reset();
+    POLLER_CONSECUTIVE_FAILURES.reset();
+    for poller in pollers {
+        let store = poller.store.as_str();
+        POLLER_HEALTHY
+            .with_label_values(&[store])
+            .set(poller.healthy as i64);
+        catch_up(
+            &POLLER_POLLS_TOTAL.with_label_values(&[store, "succeeded"]),
+            poller
+                .polls
+                .saturating_sub(poller.failures - poller.restarts),
+        );
+        catch_up(
+            &POLLER_POLLS_TOTAL.with_label_values(&[store, "failed"]),
+            poller.failures,
+        );
+        catch_up(
+            &POLLER_RESTARTS_TOTAL.with_label_values(&[store]),
+            poller.restarts,
+        );
+        POLLER_CONSECUTIVE_FAILURES
+            .with_label_values(&[store])
+            .set(poller.consecutive_failures as i64);
+    }
+}
+
+/// Records the size of the model input and of the predictions of a served prediction.
+///
+/// Only served predictions are recorded, so that requests made to models which do not exist do not
+/// create a series per made up model name.
+///
+/// # Arguments
+///
+/// * `protocol` - The protocol of the request, i.e. `HTTP` or `GRPC`.
+/// * `model_name` - The name of the model which served the prediction.
+/// * `request_bytes` - The size of the model input sent by the client.
+/// * `response_bytes` - The size of the predictions returned by the model.
+pub fn record_payload_sizes(
+    protocol: &str,
+    model_name: &str,
+    request_bytes: usize,
+    response_bytes: usize,
+) {
+    MODEL_REQUEST_PAYLOAD_BYTES
+        .with_label_values(&[protocol, model_name])
+        .observe(request_bytes as f64);
+    MODEL_RESPONSE_PAYLOAD_BYTES
+        .with_label_values(&[protocol, model_name])
+        .observe(response_bytes as f64);
+}
+
+/// Catches up the store timeout metrics with the number of timeouts counted by the model store.
+pub fn record_store_timeouts() {
+    for operation in StoreOperation::ALL {
+        let counter = STORE_TIMEOUTS_TOTAL.with_label_values(&[operation.as_str()]);
+        let total = operation.timeouts_total();
+        if total > counter.get() {
+            counter.inc_by(total - counter.get());
+        }
+    }
+}
+
+/// The number of misses of the most requested missing model names as of the last scrape, to advance their counters
+/// by the misses since.
+static RECORDED_MODEL_MISSES: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+/// Advances the model miss counters of the most requested missing model names, dropping the series of the model
+/// names which are loaded now or no longer among the most requested ones, and catches up the total number of misses.
+///
+/// # Arguments
+///
+/// * `misses` - The most requested missing model names.
+/// * `total` - The number of prediction requests made to models which were not loaded since startup.
+pub fn record_model_misses(misses: &[ModelMiss], total: u64) {
+    let mut recorded = match RECORDED_MODEL_MISSES.lock() {
+        Ok(recorded) => recorded,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let mut observed = BTreeMap::new();
+    for miss in misses {
+        let previous = recorded.remove(miss.model_name.as_str());
+        MODEL_NAME_MISSES_TOTAL
+            .with_label_values(&[miss.model_name.as_str()])
+            .inc_by(growth(previous, miss.count));
+        observed.insert(miss.model_name.clone(), miss.count);
+    }
+    // the model names left were not reported, i.e. they are loaded now or no longer among the most requested ones
+    for model_name in recorded.keys() {
+        let _ = MODEL_NAME_MISSES_TOTAL.remove_label_values(&[model_name]);
+    }
+    *recorded = observed;
+    drop(recorded);
+    if total > MODEL_MISSES_TOTAL.get() {
+        MODEL_MISSES_TOTAL.inc_by(total - MODEL_MISSES_TOTAL.get());
+    }
+}
+
+/// Catches up the model validation metrics with the number of validations counted by the model store.
+pub fn record_model_validations() {
+    for outcome in ValidationOutcome::ALL {
+        let counter = MODEL_VALIDATIONS_TOTAL.with_label_values(&[outcome.as_str()]);
+        let total = outcome.total();
+        if total > counter.get() {
+            counter.inc_by(total - counter.get());
+        }
+    }
+}
+
+/// Catches up a counter with a total counted elsewhere, e.g. by a scheduler. A total below the counter, e.g. of
+/// a scheduler which was rebuilt, leaves the counter as it is, as counters never go down.
+fn catch_up(counter: &IntCounter, total: u64) {
+    let current = counter.get();
+    if total > current {
+        counter.inc_by(total - current);
+    }
+}
+
+/// Catches up a counter of seconds with a total counted elsewhere, see `catch_up`.
+fn catch_up_seconds(counter: &Counter, total: f64) {
+    let current = counter.get();
+    if total > current {
+        counter.inc_by(total - current);
+    }
+}
+
+/// Encodes all the metrics in the registry using the prometheus text exposition format.
+///
+/// # Returns
+///
+/// * `Ok(String)` - The encoded metrics.
+/// * `Err(anyhow::Error)` - If the metrics could not be encoded.
+pub fn render() -> anyhow::Result<String> {
+    let encoder = TextEncoder::new();
+    let mut buffer = vec![];
+    match encoder.encode(&REGISTRY.gather(), &mut buffer) {
+        Ok(_) => Ok(String::from_utf8(buffer)?),
+        Err(e) => {
+            tracing::error!("Failed to encode metrics ❌: {}", e);
+            anyhow::bail!("Failed to encode metrics ❌: {}", e)
+        }
+    }
+}
+
+/// A guard which records a client disconnect if it is dropped before being disarmed.
+///
+/// Both axum and tonic drop the handler future when the client disconnects. By creating the guard
+/// at the start of a handler and disarming it once the response is ready, we can detect requests
+/// which were abandoned by the client while they were still being processed.
+pub struct DisconnectGuard {
+    protocol: &'static str,
+    endpoint: &'static str,
+    armed: bool,
+}
+
+impl DisconnectGuard {
+    /// Creates a new armed guard for the given protocol and endpoint.
+    pub fn new(protocol: &'static str, endpoint: &'static str) -> Self {
+        DisconnectGuard {
+            protocol,
+            endpoint,
+            armed: true,
+        }
+    }
+
+    /// Marks the request as completed so that no disconnect is recorded when the guard is dropped.
+    pub fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for DisconnectGuard {
+    fn drop(&mut self) {
+        if self.armed {
+            tracing::warn!(
+                "Client disconnected before {} {} request completed ⚠️",
+                self.protocol,
+                self.endpoint
+            );
+            CLIENT_DISCONNECTS_TOTAL
+                .with_label_values(&[self.protocol, self.endpoint])
+                .inc();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jams_core::model::variant::ModelPrecision;
+    use jams_core::pool::scheduler::Priority;
+
+    #[test]
+    fn successfully_records_client_disconnect_when_guard_is_dropped() {
+        // Arrange
+        let before = CLIENT_DISCONNECTS_TOTAL
+            .with_label_values(&[HTTP, "test_dropped"])
+            .get();
+
+        // Act
+        let guard = DisconnectGuard::new(HTTP, "test_dropped");
+        drop(guard);
+
+        // Assert
+        let after = CLIENT_DISCONNECTS_TOTAL
+            .with_label_values(&[HTTP, "test_dropped"])
+            .get();
+        assert_eq!(after, before + 1)
+    }
+
+    #[test]
+    fn successfully_skips_client_disconnect_when_guard_is_disarmed() {
+        // Arrange
+        let before = CLIENT_DISCONNECTS_TOTAL
+            .with_label_values(&[GRPC, "test_disarmed"])
+            .get();
+
+        // Act
+        let guard = DisconnectGuard::new(GRPC, "test_disarmed");
+        guard.disarm();
+
+        // Assert
+        let after = CLIENT_DISCONNECTS_TOTAL
+            .with_label_values(&[GRPC, "test_disarmed"])
+            .get();
+        assert_eq!(after, before)
+    }
+
+    #[test]
+    fn successfully_records_payload_sizes_per_model() {
+        // Arrange
+        let before = MODEL_REQUEST_PAYLOAD_BYTES
+            .with_label_values(&[GRPC, "test_payload_model"])
+            .get_sample_count();
+
+        // Act
+        record_payload_sizes(GRPC, "test_payload_model", 2048, 64);
+
+        // Assert
+        let request = MODEL_REQUEST_PAYLOAD_BYTES.with_label_values(&[GRPC, "test_payload_model"]);
+        let response =
+            MODEL_RESPONSE_PAYLOAD_BYTES.with_label_values(&[GRPC, "test_payload_model"]);
+        assert_eq!(request.get_sample_count(), before + 1);
+        assert!(request.get_sample_sum() >= 2048.0);
+        assert!(response.get_sample_sum() >= 64.0);
+    }
+
+    #[test]
+    fn successfully_records_latency_of_model_variants() {
+        // Arrange
+        let latencies = vec![
+            VariantLatency {
+                model_name: "test_variant_model".to_string(),
+                precision: ModelPrecision::Fp32,
+                predictions: 4,
+                seconds: 0.2,
+            },
+            VariantLatency {
+                model_name: "test_variant_model".to_string(),
+                precision: ModelPrecision::Int8,
+                predictions: 2,
+                seconds: 0.05,
+            },
+        ];
+
+        let mut reloaded = latencies.clone();
+        reloaded[1].predictions = 1;
+        reloaded[1].seconds = 0.025;
+
+        // Act
+        record_variant_latencies(&latencies);
+        record_variant_latencies(&latencies);
+        let output = render().unwrap();
+        record_variant_latencies(&reloaded);
+
+        // Assert
+        assert!(output.contains(
+            "jams_model_variant_predict_seconds_total{model=\"test_variant_model\",precision=\"fp32\"} 0.2"
+        ));
+        // the counters keep growing when the totals start over with a reload of the model
+        assert_eq!(
+            MODEL_VARIANT_PREDICTIONS_TOTAL
+                .with_label_values(&["test_variant_model", "int8"])
+                .get(),
+            3
+        )
+    }
+
+    #[test]
+    fn successfully_records_model_stats() {
+        // Arrange
+        let stats = vec![ModelStatsSnapshot {
+            model_name: "test_stats_model".to_string(),
+            predictions: 40,
+            errors: 2,
+            recent_predictions: 20,
+            recent_errors: 1,
+            latency_quantiles: vec![(0.5, 0.01), (0.99, 0.25)],
+        }];
+
+        let mut more = stats.clone();
+        more[0].predictions = 50;
+        more[0].errors = 3;
+        let mut reloaded = stats.clone();
+        reloaded[0].predictions = 5;
+        reloaded[0].errors = 0;
+
+        // Act
+        record_model_stats(&stats);
+        let output = render().unwrap();
+        record_model_stats(&more);
+        record_model_stats(&reloaded);
+
+        // Assert
+        assert_eq!(
+            MODEL_PREDICTIONS_TOTAL
+                .with_label_values(&["test_stats_model"])
+                .get(),
+            55
+        );
+        assert_eq!(
+            MODEL_PREDICTION_ERRORS_TOTAL
+                .with_label_values(&["test_stats_model"])
+                .get(),
+            3
+        );
+        assert!(output.contains("jams_model_predictions_total{model=\"test_stats_model\"} 40"));
+        assert!(output.contains("jams_model_recent_error_ratio{model=\"test_stats_model\"} 0.05"));
+        assert!(output.contains(
+            "jams_model_recent_latency_seconds{model=\"test_stats_model\",quantile=\"0.99\"} 0.25"
+        ))
+    }
+
+    #[test]
+    fn successfully_records_latency_fallbacks() {
+        // Arrange
+        let fallbacks = vec![LatencyFallback {
+            model_name: "test_fallback_model".to_string(),
+            precision: ModelPrecision::Int8,
+            predictions: 3,
+        }];
+
+        // Act
+        record_latency_fallbacks(&fallbacks);
+        record_latency_fallbacks(&fallbacks);
+        let output = render().unwrap();
+
+        // Assert
+        assert!(output.contains(
+            "jams_model_latency_fallbacks_total{model=\"test_fallback_model\",precision=\"int8\"} 3"
+        ))
+    }
+
+    #[test]
+    fn successfully_records_scheduler_stats() {
+        // Arrange
+        let stats = vec![SchedulerStats {
+            scheduler: "test_scheduler".to_string(),
+            priority: Priority::High,
+            queued: 3,
+            submitted: 10,
+            cancelled: 1,
+            completed: 6,
+            panicked: 0,
+            wait_over_budget: 2,
+            execute_over_budget: 0,
+            wait_seconds: 0.5,
+            execute_seconds: 1.5,
+        }];
+
+        // Act
+        record_scheduler_stats(&stats);
+        record_scheduler_stats(&stats);
+        let output = render().unwrap();
+
+        // Assert
+        assert_eq!(
+            SCHEDULER_TASKS_TOTAL
+                .with_label_values(&["test_scheduler", "high", "wait_over_budget"])
+                .get(),
+            2
+        );
+        assert!(output.contains(
+            "jams_scheduler_execute_seconds_total{priority=\"high\",scheduler=\"test_scheduler\"} 1.5"
+        ));
+        assert!(output.contains(
+            "jams_scheduler_queued_tasks{priority=\"high\",scheduler=\"test_scheduler\"} 3"
+        ))
+    }
+
+    #[test]
+    fn successfully_records_scheduler_flow_stats() {
+        // Arrange
+        let stats = vec![
+            FlowStats {
+                scheduler: "test_flow_scheduler".to_string(),
+                flow: "critical_model".to_string(),
+                weight: 3.0,
+                queued: 1,
+                completed: 30,
+                execute_seconds: 0.75,
+                share: 0.75,
+            },
+            FlowStats {
+                scheduler: "test_flow_scheduler".to_string(),
+                flow: "bulk_model".to_string(),
+                weight: 1.0,
+                queued: 40,
+                completed: 10,
+                execute_seconds: 0.25,
+                share: 0.25,
+            },
+        ];
+
+        let mut later_stats = stats.clone();
+        later_stats[0].completed = 35;
+        later_stats[0].execute_seconds = 1.0;
+        // the flow of the bulk model was evicted for being idle
+        later_stats.truncate(1);
+
+        // Act
+        record_scheduler_flow_stats(&stats);
+        let output = render().unwrap();
+        record_scheduler_flow_stats(&later_stats);
+        let later_output = render().unwrap();
+
+        // Assert
+        assert!(output.contains(
+            "jams_scheduler_model_share{model=\"bulk_model\",scheduler=\"test_flow_scheduler\"} 0.25"
+        ));
+        assert_eq!(
+            SCHEDULER_MODEL_COMPLETED_TASKS_TOTAL
+                .with_label_values(&["test_flow_scheduler", "critical_model"])
+                .get(),
+            35
+        );
+        assert!(later_output.contains(
+            "jams_scheduler_model_execute_seconds_total{model=\"critical_model\",scheduler=\"test_flow_scheduler\"} 1"
+        ));
+        assert!(!later_output.contains("bulk_model"))
+    }
+
+    #[test]
+    fn successfully_records_poller_health() {
+        // Arrange
+        let pollers = vec![PollerHealth {
+            store: "test_poller_store".to_string(),
+            healthy: false,
+            polls: 10,
+            failures: 6,
+            restarts: 1,
+            consecutive_failures: 5,
+            last_success: None,
+            last_error: Some("bucket is unreachable".to_string()),
+        }];
+
+        // Act
+        record_poller_health(&pollers);
+        let output = render().unwrap();
+        record_poller_health(&[]);
+        let detached = render().unwrap();
+
+        // Assert
+        assert_eq!(
+            POLLER_POLLS_TOTAL
+                .with_label_values(&["test_poller_store", "succeeded"])
+                .get(),
+            5
+        );
+        assert!(output.contains("jams_poller_healthy{store=\"test_poller_store\"} 0"));
+        assert!(output.contains("jams_poller_restarts_total{store=\"test_poller_store\"} 1"));
+        assert!(!detached.contains("test_poller_store"));
+    }
+
+    #[test]
+    fn successfully_records_store_timeouts() {
+        // Act
+        record_store_timeouts();
+        let output = render().unwrap();
+
+        // Assert
+        assert_eq!(
+            STORE_TIMEOUTS_TOTAL.with_label_values(&["list"]).get(),
+            StoreOperation::List.timeouts_total()
+        );
+        assert!(output.contains("jams_store_timeouts_total{operation=\"download\"}"))
+    }
+
+    #[test]
+    fn successfully_records_model_misses() {
+        // Arrange
+        let miss = |count| ModelMiss {
+            model_name: "test_missing_model".to_string(),
+            count,
+            overestimate: 0,
+            last_missed: "2024-01-01T00:00:00+00:00".to_string(),
+        };
+
+        // Act
+        record_model_misses(&[miss(3)], 5);
+        record_model_misses(&[miss(7)], 9);
+        let output = render().unwrap();
+        record_model_misses(&[], 9);
+        let dropped = render().unwrap();
+
+        // Assert the counter advances by the misses since the last scrape and is dropped with the model name
+        assert!(MODEL_MISSES_TOTAL.get() >= 9);
+        assert!(output.contains("jams_model_name_misses_total{model=\"test_missing_model\"} 7"));
+        assert!(!dropped.contains("test_missing_model"));
+    }
+
+    #[test]
+    fn successfully_records_model_validations() {
+        // Arrange
+        ValidationOutcome::Rejected.record();
+
+        // Act
+        record_model_validations();
+        let output = render().unwrap();
+
+        // Assert
+        assert!(
+            MODEL_VALIDATIONS_TOTAL
+                .with_label_values(&["rejected"])
+                .get()
+                >= 1
+        );
+        assert!(output.contains("jams_model_validations_total{outcome=\"accepted\"}"))
+    }
+
+    #[test]
+    fn successfully_renders_metrics() {
+        // Arrange
+        REQUEST_PAYLOAD_BYTES
+            .with_label_values(&[HTTP, "/test"])
+            .observe(128.0);
+
+        // Act
+        let output = render();
+
+        // Assert
+        assert!(output.is_ok());
+        assert!(output.unwrap().contains("jams_request_payload_bytes"))
+    }
+}
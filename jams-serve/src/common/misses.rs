@@ -0,0 +1,83 @@
+use jams_core::manager::Manager;
+
+/// Returns the error message of a prediction request which named a model that is not loaded, ending with the
+/// closest loaded model names when model name suggestions are enabled.
+///
+/// The miss itself is counted by the manager, this only logs which protocol and client it came from so that the
+/// clients calling a model by a typo'd or stale name can be found in the access logs.
+///
+/// # Arguments
+///
+/// * `manager` - The manager which served the request.
+/// * `protocol` - The protocol of the request, i.e. `HTTP` or `GRPC`.
+/// * `client_id` - The ID of the client which sent the request.
+/// * `error` - The error of the request.
+///
+/// # Returns
+///
+/// * `Some(String)` - The error message, if the error is an `Error::ModelNotFound`.
+/// * `None` - If the request failed for another reason.
+pub fn model_not_found(
+    manager: &Manager,
+    protocol: &str,
+    client_id: &str,
+    error: &anyhow::Error,
+) -> Option<String> {
+    let model_name = match error.downcast_ref::<jams_core::Error>() {
+        Some(jams_core::Error::ModelNotFound(model_name)) => model_name,
+        _ => return None,
+    };
+    tracing::warn!(
+        "Model {} requested over {} by client {} is not loaded ⚠️",
+        model_name,
+        protocol,
+        client_id
+    );
+
+    let suggestions = manager.suggest_model_names(model_name.as_str());
+    match suggestions.is_empty() {
+        true => Some(format!("Failed to predict ❌: {}", error)),
+        false => Some(format!(
+            "Failed to predict ❌: {}, did you mean {}?",
+            error,
+            suggestions.join(", ")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::metrics::HTTP;
+    use jams_core::manager::ManagerBuilder;
+    use jams_core::model_store::local::filesystem::LocalModelStore;
+    use jams_core::model_store::ModelStore;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn successfully_suggest_model_names_for_missing_model() {
+        // Arrange
+        let model_store = LocalModelStore::new("tests/model_store".to_string())
+            .await
+            .expect("Failed to create model store ❌");
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(model_store)))
+            .with_model_suggestions(true)
+            .build()
+            .expect("Failed to initialize manager ❌");
+        let error = manager
+            .check_streaming("titanic_modle")
+            .expect_err("Expected the model to be missing ❌");
+
+        // Act
+        let message = model_not_found(&manager, HTTP, "test_client", &error);
+        let other = model_not_found(&manager, HTTP, "test_client", &anyhow::anyhow!("boom"));
+
+        // Assert
+        assert_eq!(
+            message.unwrap(),
+            "Failed to predict ❌: model titanic_modle does not exist ❌, did you mean titanic_model?"
+        );
+        assert!(other.is_none());
+        assert_eq!(manager.model_misses_total(), 1);
+    }
+}
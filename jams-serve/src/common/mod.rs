@@ -1,5 +1,27 @@
+pub mod admission;
+pub mod authz;
+pub mod bulk;
+pub mod dedup;
+pub mod feature_store;
+pub mod hardening;
+pub mod health;
 pub mod instrument;
+pub mod listener;
+pub mod metadata;
+pub mod metrics;
+pub mod misses;
+pub mod namespace;
+pub mod passthrough;
+pub mod recorder;
+pub mod result_store;
+pub mod s3_events;
+pub mod schema_cache;
+pub mod sentinel;
 pub mod server;
 pub mod shutdown;
+pub mod signing;
+pub mod sizing;
+pub mod startup;
 pub mod state;
+pub mod streaming;
 pub mod worker;
@@ -0,0 +1,395 @@
+use crate::common::metrics::{
+    NAMESPACE_BUSY_WORKERS, NAMESPACE_QUEUED_PREDICTIONS, NAMESPACE_REJECTED_TOTAL,
+    NAMESPACE_WORKERS,
+};
+use jams_core::pool::executor::ExecutorKind;
+use jams_core::pool::scheduler::{Budget, FlowStats, Scheduler, SchedulerStats, Task};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Configuration of a namespace, i.e. a group of models owned by one team.
+///
+/// Models are assigned to a namespace by the prefix of their name. Each namespace has its own worker pool,
+/// so heavy models in one namespace cannot starve the models of another.
+///
+/// # Example
+/// ```toml
+/// [[config.namespaces]]
+/// name = "fraud"
+/// model_prefixes = ["fraud_"]
+/// num_workers = 4
+/// max_queued = 100
+/// executor = "threads"
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct NamespaceConfig {
+    /// Name of the namespace, used as the label of the namespace metrics.
+    pub name: String,
+
+    /// Models whose name starts with any of these prefixes belong to the namespace.
+    pub model_prefixes: Vec<String>,
+
+    /// Number of threads in the worker pool of the namespace.
+    pub num_workers: usize,
+
+    /// An optional limit on the number of predictions waiting for a worker in the namespace.
+    ///
+    /// - `Some(usize)`: Predictions are rejected while the limit is reached.
+    /// - `None`: The queue is unbounded.
+    pub max_queued: Option<usize>,
+
+    /// An optional executor which runs the predictions of the namespace, i.e. `rayon`, `threads` or `tokio`.
+    ///
+    /// - `Some(ExecutorKind)`: The predictions run on the given executor.
+    /// - `None`: The predictions run on a rayon threadpool.
+    #[serde(default)]
+    pub executor: Option<ExecutorKind>,
+}
+
+/// A namespace with its own worker pool.
+struct Namespace {
+    name: String,
+    model_prefixes: Vec<String>,
+    scheduler: Scheduler,
+    max_queued: Option<usize>,
+    queued: Arc<AtomicUsize>,
+}
+
+/// The namespaces configured on the server.
+///
+/// Models which do not belong to any namespace run on the shared worker pool.
+#[derive(Default)]
+pub struct Namespaces {
+    namespaces: Vec<Namespace>,
+}
+
+impl Namespaces {
+    /// Builds a worker pool for each of the configured namespaces.
+    ///
+    /// # Arguments
+    ///
+    /// * `configs` - The namespace configurations.
+    /// * `budget` - The time each prediction is expected to wait for a worker and run.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Namespaces)` - If all the worker pools were built.
+    /// * `Err(anyhow::Error)` - If a namespace is configured twice, has no workers or its pool cannot be built.
+    pub fn new(configs: Vec<NamespaceConfig>, budget: Budget) -> anyhow::Result<Self> {
+        let mut namespaces: Vec<Namespace> = Vec::with_capacity(configs.len());
+        for config in configs {
+            if namespaces.iter().any(|n| n.name == config.name) {
+                anyhow::bail!("Namespace {} is configured more than once ❌", config.name)
+            }
+            if config.num_workers < 1 {
+                anyhow::bail!(
+                    "At least 1 worker is required for namespace {} ❌",
+                    config.name
+                )
+            }
+
+            let executor = config.executor.unwrap_or_default();
+            let pool = match executor
+                .build(config.num_workers, format!("jams-{}", config.name).as_str())
+            {
+                Ok(pool) => pool,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to build worker pool for namespace {} ❌: {}",
+                        config.name,
+                        e
+                    );
+                    anyhow::bail!(
+                        "Failed to build worker pool for namespace {} ❌: {}",
+                        config.name,
+                        e
+                    )
+                }
+            };
+
+            NAMESPACE_WORKERS
+                .with_label_values(&[config.name.as_str()])
+                .set(config.num_workers as i64);
+            tracing::info!(
+                "Namespace {} started with {} {:?} workers ⚙️",
+                config.name,
+                config.num_workers,
+                executor
+            );
+            namespaces.push(Namespace {
+                scheduler: Scheduler::new(config.name.as_str(), pool, budget),
+                name: config.name,
+                model_prefixes: config.model_prefixes,
+                max_queued: config.max_queued,
+                queued: Arc::new(AtomicUsize::new(0)),
+            });
+        }
+
+        Ok(Namespaces { namespaces })
+    }
+
+    /// Returns the namespace a model belongs to. The longest matching prefix wins.
+    fn resolve(&self, model_name: &str) -> Option<&Namespace> {
+        self.namespaces
+            .iter()
+            .filter_map(|namespace| {
+                namespace
+                    .model_prefixes
+                    .iter()
+                    .filter(|prefix| model_name.starts_with(prefix.as_str()))
+                    .map(|prefix| prefix.len())
+                    .max()
+                    .map(|len| (namespace, len))
+            })
+            .max_by_key(|(_, len)| *len)
+            .map(|(namespace, _)| namespace)
+    }
+
+    /// Returns the name of the namespace a model belongs to, if any.
+    pub fn namespace_of(&self, model_name: &str) -> Option<&str> {
+        self.resolve(model_name)
+            .map(|namespace| namespace.name.as_str())
+    }
+
+    /// Returns the accounting of the schedulers of the namespaces, by namespace and priority.
+    pub fn stats(&self) -> Vec<SchedulerStats> {
+        self.namespaces
+            .iter()
+            .flat_map(|namespace| namespace.scheduler.stats())
+            .collect()
+    }
+
+    /// Returns the accounting of the schedulers of the namespaces, by namespace and model.
+    pub fn flow_stats(&self) -> Vec<FlowStats> {
+        self.namespaces
+            .iter()
+            .flat_map(|namespace| namespace.scheduler.flow_stats())
+            .collect()
+    }
+
+    /// Runs a prediction on the worker pool of the namespace the model belongs to, or on the
+    /// shared pool if the model does not belong to any namespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `shared_pool` - The worker pool used for models which do not belong to a namespace.
+    /// * `model_name` - The name of the model used for the prediction.
+    /// * `task` - The prediction to run, along with its priority and cancellation token.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the prediction was queued.
+    /// * `Err(anyhow::Error)` - If the queue of the namespace is full.
+    pub fn spawn(
+        &self,
+        shared_pool: &Scheduler,
+        model_name: &str,
+        task: Task,
+    ) -> anyhow::Result<()> {
+        let namespace = match self.resolve(model_name) {
+            Some(namespace) => namespace,
+            None => {
+                shared_pool.submit(task);
+                return Ok(());
+            }
+        };
+
+        let queued = namespace.queued.fetch_add(1, Ordering::SeqCst);
+        if let Some(max_queued) = namespace.max_queued {
+            if queued >= max_queued {
+                namespace.queued.fetch_sub(1, Ordering::SeqCst);
+                NAMESPACE_REJECTED_TOTAL
+                    .with_label_values(&[namespace.name.as_str()])
+                    .inc();
+                tracing::warn!(
+                    "Rejected prediction for model {} as namespace {} has {} queued predictions ⚠️",
+                    model_name,
+                    namespace.name,
+                    queued
+                );
+                anyhow::bail!(
+                    "namespace {} has reached its limit of {} queued predictions",
+                    namespace.name,
+                    max_queued
+                )
+            }
+        }
+        NAMESPACE_QUEUED_PREDICTIONS
+            .with_label_values(&[namespace.name.as_str()])
+            .inc();
+
+        // the queue is released by the guard, as cancelled predictions are dropped without running
+        let guard = QueuedGuard {
+            name: namespace.name.clone(),
+            queued: Arc::clone(&namespace.queued),
+        };
+        namespace.scheduler.submit(task.wrap(move |job| {
+            let busy = NAMESPACE_BUSY_WORKERS.with_label_values(&[guard.name.as_str()]);
+            drop(guard);
+            busy.inc();
+            job();
+            busy.dec();
+        }));
+
+        Ok(())
+    }
+}
+
+/// Releases a prediction from the queue of a namespace once it is picked up by a worker or withdrawn.
+struct QueuedGuard {
+    name: String,
+    queued: Arc<AtomicUsize>,
+}
+
+impl Drop for QueuedGuard {
+    fn drop(&mut self) {
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        NAMESPACE_QUEUED_PREDICTIONS
+            .with_label_values(&[self.name.as_str()])
+            .dec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+    use tokio_util::sync::CancellationToken;
+
+    fn config(name: &str, prefixes: &[&str], max_queued: Option<usize>) -> NamespaceConfig {
+        NamespaceConfig {
+            name: name.to_string(),
+            model_prefixes: prefixes.iter().map(|p| p.to_string()).collect(),
+            num_workers: 1,
+            max_queued,
+            executor: None,
+        }
+    }
+
+    fn shared_pool() -> Scheduler {
+        let executor = ExecutorKind::Rayon.build(1, "jams-test").unwrap();
+        Scheduler::new("shared", executor, Budget::default())
+    }
+
+    fn block(namespaces: &Namespaces, model_name: &str) -> mpsc::Sender<()> {
+        let (block_tx, block_rx) = mpsc::channel::<()>();
+        let (started_tx, started_rx) = mpsc::channel::<()>();
+        namespaces
+            .spawn(
+                &shared_pool(),
+                model_name,
+                Task::new(move || {
+                    started_tx.send(()).unwrap();
+                    let _ = block_rx.recv();
+                }),
+            )
+            .unwrap();
+        started_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        block_tx
+    }
+
+    #[test]
+    fn successfully_resolves_namespace_by_longest_prefix() {
+        // Arrange
+        let namespaces = Namespaces::new(
+            vec![
+                config("fraud", &["fraud_"], None),
+                config("fraud_realtime", &["fraud_rt_"], None),
+            ],
+            Budget::default(),
+        )
+        .unwrap();
+
+        // Act + Assert
+        assert_eq!(namespaces.namespace_of("fraud_model"), Some("fraud"));
+        assert_eq!(
+            namespaces.namespace_of("fraud_rt_model"),
+            Some("fraud_realtime")
+        );
+        assert_eq!(namespaces.namespace_of("titanic_model"), None);
+    }
+
+    #[test]
+    fn fails_to_create_namespaces_when_config_is_invalid() {
+        // Arrange
+        let mut no_workers = config("fraud", &["fraud_"], None);
+        no_workers.num_workers = 0;
+
+        // Act
+        let duplicate = Namespaces::new(
+            vec![
+                config("fraud", &["fraud_"], None),
+                config("fraud", &["risk_"], None),
+            ],
+            Budget::default(),
+        );
+        let no_workers = Namespaces::new(vec![no_workers], Budget::default());
+
+        // Assert
+        assert!(duplicate.is_err());
+        assert!(no_workers.is_err());
+    }
+
+    #[test]
+    fn fails_to_spawn_when_namespace_queue_is_full() {
+        // Arrange
+        let shared_pool = shared_pool();
+        let namespaces = Namespaces::new(
+            vec![config("search", &["search_"], Some(1))],
+            Budget::default(),
+        )
+        .unwrap();
+
+        // Act
+        // occupy the only worker of the namespace
+        let block_tx = block(&namespaces, "search_model");
+        let queued = namespaces.spawn(&shared_pool, "search_model", Task::new(|| {}));
+        let rejected = namespaces.spawn(&shared_pool, "search_model", Task::new(|| {}));
+        let other = namespaces.spawn(&shared_pool, "titanic_model", Task::new(|| {}));
+        block_tx.send(()).unwrap();
+
+        // Assert
+        assert!(queued.is_ok());
+        assert!(rejected.is_err());
+        assert!(other.is_ok());
+    }
+
+    #[test]
+    fn successfully_release_queue_when_queued_prediction_is_cancelled() {
+        // Arrange
+        let shared_pool = shared_pool();
+        let mut search = config("search", &["search_"], Some(1));
+        search.executor = Some(ExecutorKind::Threads);
+        let namespaces = Namespaces::new(vec![search], Budget::default()).unwrap();
+        let cancellation_token = CancellationToken::new();
+        let (tx, rx) = mpsc::channel::<()>();
+
+        // Act
+        let block_tx = block(&namespaces, "search_model");
+        namespaces
+            .spawn(
+                &shared_pool,
+                "search_model",
+                Task::new(move || tx.send(()).unwrap())
+                    .with_cancellation_token(cancellation_token.clone()),
+            )
+            .unwrap();
+        let rejected = namespaces.spawn(&shared_pool, "search_model", Task::new(|| {}));
+        cancellation_token.cancel();
+        block_tx.send(()).unwrap();
+        // the cancelled prediction is withdrawn once the worker is free
+        let start = Instant::now();
+        while namespaces.stats().iter().map(|s| s.cancelled).sum::<u64>() < 1 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let queued = namespaces.spawn(&shared_pool, "search_model", Task::new(|| {}));
+
+        // Assert
+        assert!(rejected.is_err());
+        assert!(queued.is_ok());
+        assert!(rx.try_recv().is_err());
+    }
+}
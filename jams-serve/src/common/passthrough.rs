@@ -0,0 +1,187 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Configuration of the request headers which are passed through to the prediction log and the postprocessors,
+/// e.g. the client ID or experiment flags, so that predictions can be joined downstream without adding fields to
+/// the payload schema. The passthrough can only be configured using the config file.
+///
+/// # Example
+/// ```toml
+/// [config.passthrough]
+/// headers = ["x-client-id", "x-experiment"]
+/// ```
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PassthroughConfig {
+    /// The names of the HTTP headers or gRPC metadata keys to capture. Names are case-insensitive.
+    pub headers: Vec<String>,
+}
+
+/// The request which produced a prediction, as seen by the postprocessors.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestContext<'a> {
+    /// The name of the model which served the prediction.
+    pub model_name: &'a str,
+    /// The captured headers of the request, keyed by their lowercase name.
+    pub headers: &'a BTreeMap<String, String>,
+}
+
+/// A hook which rewrites the predictions of a model before they are returned to the client and recorded,
+/// e.g. to attach the experiment arm of the request to the output.
+pub trait Postprocessor: Send + Sync {
+    /// Postprocesses the predictions of a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The request which produced the predictions.
+    /// * `output` - The predictions returned by the model, formatted as a JSON string.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The predictions to return to the client.
+    /// * `Err(anyhow::Error)` - If the predictions could not be postprocessed. The request fails.
+    fn postprocess(&self, context: &RequestContext, output: String) -> anyhow::Result<String>;
+}
+
+/// Captures the configured headers of prediction requests and runs the postprocessors on their predictions.
+#[derive(Clone, Default)]
+pub struct Passthrough {
+    headers: Vec<String>,
+    postprocessors: Vec<Arc<dyn Postprocessor>>,
+}
+
+impl Passthrough {
+    /// Creates a passthrough of the configured headers without any postprocessor.
+    pub fn new(config: PassthroughConfig) -> Self {
+        let mut headers: Vec<String> = config
+            .headers
+            .iter()
+            .map(|name| name.trim().to_ascii_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect();
+        headers.sort();
+        headers.dedup();
+        tracing::info!("Passing through request headers {:?} 🏷️", headers);
+
+        Passthrough {
+            headers,
+            postprocessors: Vec::new(),
+        }
+    }
+
+    /// Adds a postprocessor, which runs after the postprocessors added before it.
+    pub fn with_postprocessor(mut self, postprocessor: Arc<dyn Postprocessor>) -> Self {
+        self.postprocessors.push(postprocessor);
+        self
+    }
+
+    /// Captures the configured headers of a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `lookup` - Returns the value of a header by its lowercase name, e.g. from an HTTP `HeaderMap` or a
+    ///   gRPC `MetadataMap`. Headers which are missing or not valid UTF-8 are skipped.
+    pub fn capture<'a>(
+        &self,
+        lookup: impl Fn(&str) -> Option<&'a str>,
+    ) -> BTreeMap<String, String> {
+        self.headers
+            .iter()
+            .filter_map(|name| lookup(name.as_str()).map(|value| (name.clone(), value.to_string())))
+            .collect()
+    }
+
+    /// Runs the postprocessors on the predictions of a request, in the order in which they were added.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The request which produced the predictions.
+    /// * `output` - The predictions returned by the model.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The postprocessed predictions.
+    /// * `Err(anyhow::Error)` - If one of the postprocessors failed.
+    pub fn postprocess(&self, context: &RequestContext, output: String) -> anyhow::Result<String> {
+        self.postprocessors
+            .iter()
+            .try_fold(output, |output, postprocessor| {
+                postprocessor.postprocess(context, output)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TagExperiment;
+
+    impl Postprocessor for TagExperiment {
+        fn postprocess(&self, context: &RequestContext, output: String) -> anyhow::Result<String> {
+            match context.headers.get("x-experiment") {
+                Some(arm) => Ok(format!("{{\"arm\":\"{}\",\"output\":{}}}", arm, output)),
+                None => anyhow::bail!("missing experiment arm"),
+            }
+        }
+    }
+
+    #[test]
+    fn successfully_capture_configured_headers() {
+        // Arrange
+        let passthrough = Passthrough::new(PassthroughConfig {
+            headers: vec!["X-Client-Id".to_string(), "x-experiment".to_string()],
+        });
+        let request_headers = BTreeMap::from([
+            ("x-client-id", "client-a"),
+            ("authorization", "Bearer secret"),
+        ]);
+
+        // Act
+        let captured = passthrough.capture(|name| request_headers.get(name).copied());
+
+        // Assert
+        assert_eq!(
+            captured,
+            BTreeMap::from([("x-client-id".to_string(), "client-a".to_string())])
+        );
+    }
+
+    #[test]
+    fn successfully_postprocess_predictions_with_captured_headers() {
+        // Arrange
+        let passthrough = Passthrough::new(PassthroughConfig {
+            headers: vec!["x-experiment".to_string()],
+        })
+        .with_postprocessor(Arc::new(TagExperiment));
+        let headers = BTreeMap::from([("x-experiment".to_string(), "b".to_string())]);
+        let context = RequestContext {
+            model_name: "titanic_model",
+            headers: &headers,
+        };
+
+        // Act
+        let output = passthrough.postprocess(&context, "[[0.5]]".to_string());
+
+        // Assert
+        assert_eq!(output.unwrap(), "{\"arm\":\"b\",\"output\":[[0.5]]}");
+    }
+
+    #[test]
+    fn fails_to_postprocess_predictions_when_postprocessor_fails() {
+        // Arrange
+        let passthrough = Passthrough::default().with_postprocessor(Arc::new(TagExperiment));
+        let headers = BTreeMap::new();
+        let context = RequestContext {
+            model_name: "titanic_model",
+            headers: &headers,
+        };
+
+        // Act
+        let output = passthrough.postprocess(&context, "[[0.5]]".to_string());
+
+        // Assert
+        assert!(output.is_err());
+    }
+}
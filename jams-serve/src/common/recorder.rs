@@ -0,0 +1,277 @@
+use crate::common::metrics::PREDICTION_RECORDS_DROPPED_TOTAL;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+/// Maximum number of records which can be waiting to be written to the prediction log.
+const RECORD_BUFFER_SIZE: usize = 10_000;
+
+/// Version of the format of the prediction log written by the server, see `PredictionRecord`.
+pub const CAPTURE_FORMAT_VERSION: u32 = 1;
+
+/// A prediction served by the server, stored as a single line of JSON in the prediction log.
+///
+/// The prediction log is the capture format shared by the server, which writes it, and the tools which consume it,
+/// e.g. `jams replay`. It is specified by `prediction-log.schema.json` at the root of the repository so that
+/// external tools can read it too, and evolves as follows:
+///
+/// * New fields are optional, so that records written before they were added stay valid. They do not change the
+///   `format_version`.
+/// * Readers ignore the fields they do not know, so that records written by newer servers of the same
+///   `format_version` stay readable.
+/// * Removing a field, renaming it or changing its type or meaning increments the `format_version`. Readers reject
+///   the records of a `format_version` they do not support.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PredictionRecord {
+    /// Version of the format of the record. Records written before the format was versioned are version 1.
+    #[serde(default = "unversioned_format_version")]
+    pub format_version: u32,
+    /// Time at which the prediction was served, in milliseconds since the unix epoch.
+    pub timestamp_ms: u64,
+    /// The name of the model which served the prediction.
+    pub model_name: String,
+    /// The model input as sent by the client.
+    pub input: String,
+    /// The predictions returned to the client.
+    pub output: String,
+    /// Time taken to serve the prediction in milliseconds, including the time spent waiting for a worker.
+    pub latency_ms: f64,
+    /// The request headers captured by the passthrough, keyed by their lowercase name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+fn unversioned_format_version() -> u32 {
+    1
+}
+
+impl PredictionRecord {
+    /// Creates a new record for a prediction which has just been served.
+    pub fn new(
+        model_name: String,
+        input: String,
+        output: String,
+        latency: Duration,
+        headers: BTreeMap<String, String>,
+    ) -> Self {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+
+        PredictionRecord {
+            format_version: CAPTURE_FORMAT_VERSION,
+            timestamp_ms,
+            model_name,
+            input,
+            output,
+            latency_ms: latency.as_secs_f64() * 1000.0,
+            headers,
+        }
+    }
+}
+
+/// Records served predictions to a prediction log, which can be replayed against a candidate model
+/// using `jams replay`.
+///
+/// Records are written by a background task so that request handlers never wait on disk I/O. If the
+/// writer falls behind, new records are dropped instead of slowing down predictions.
+pub struct PredictionRecorder {
+    tx: mpsc::Sender<PredictionRecord>,
+}
+
+impl PredictionRecorder {
+    /// Creates a new recorder which appends records to the file at the given path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the prediction log. The file is created if it does not exist.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PredictionRecorder)` - If the prediction log was opened successfully.
+    /// * `Err(anyhow::Error)` - If the prediction log could not be opened.
+    pub async fn new(path: &str) -> anyhow::Result<Self> {
+        let file = match OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+        {
+            Ok(file) => file,
+            Err(e) => {
+                tracing::error!("Failed to open prediction log {} ❌: {}", path, e);
+                anyhow::bail!("Failed to open prediction log {} ❌: {}", path, e)
+            }
+        };
+
+        let (tx, rx) = mpsc::channel(RECORD_BUFFER_SIZE);
+        tokio::spawn(write_records(file, rx));
+        tracing::info!("Recording predictions to {} 📝", path);
+
+        Ok(PredictionRecorder { tx })
+    }
+
+    /// Queues a record to be written to the prediction log without waiting for it to be written.
+    pub fn record(&self, record: PredictionRecord) {
+        if let Err(e) = self.tx.try_send(record) {
+            tracing::warn!("Dropped prediction record ⚠️: {}", e);
+            PREDICTION_RECORDS_DROPPED_TOTAL.inc();
+        }
+    }
+}
+
+/// Writes the queued records to the prediction log as JSON lines until the recorder is dropped.
+async fn write_records(mut file: File, mut rx: mpsc::Receiver<PredictionRecord>) {
+    while let Some(record) = rx.recv().await {
+        let mut line = match serde_json::to_vec(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::error!("Failed to serialize prediction record ❌: {}", e);
+                continue;
+            }
+        };
+        line.push(b'\n');
+
+        if let Err(e) = file.write_all(&line).await {
+            tracing::error!("Failed to write prediction record ❌: {}", e);
+            continue;
+        }
+
+        // flush once the queue is drained so that records are not held in memory
+        if rx.is_empty() {
+            if let Err(e) = file.flush().await {
+                tracing::error!("Failed to flush prediction log ❌: {}", e);
+            }
+        }
+    }
+}
+
+/// Reads all the records from a prediction log.
+///
+/// # Arguments
+///
+/// * `path` - The path to the prediction log.
+///
+/// # Returns
+///
+/// * `Ok(Vec<PredictionRecord>)` - The records in the order they were written.
+/// * `Err(anyhow::Error)` - If the file could not be read or contains an invalid record, or a record of a newer
+///   `format_version` than `CAPTURE_FORMAT_VERSION`.
+pub fn read_records(path: &str) -> anyhow::Result<Vec<PredictionRecord>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            anyhow::bail!("Failed to read prediction log {} ❌: {}", path, e)
+        }
+    };
+
+    let mut records = Vec::new();
+    for (line_number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<PredictionRecord>(line) {
+            Ok(record) if record.format_version > CAPTURE_FORMAT_VERSION => {
+                anyhow::bail!(
+                    "Failed to read prediction record on line {} ❌: format version {} is newer than the supported version {}",
+                    line_number + 1,
+                    record.format_version,
+                    CAPTURE_FORMAT_VERSION
+                )
+            }
+            Ok(record) => records.push(record),
+            Err(e) => {
+                anyhow::bail!(
+                    "Failed to parse prediction record on line {} ❌: {}",
+                    line_number + 1,
+                    e
+                )
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn successfully_records_and_reads_predictions() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recorder.jsonl");
+        let path = path.to_str().unwrap();
+        let recorder = PredictionRecorder::new(path).await.unwrap();
+        let record = PredictionRecord::new(
+            "titanic_model".to_string(),
+            "{\"age\": [22.0]}".to_string(),
+            "{\"predictions\": [[0.5]]}".to_string(),
+            Duration::from_millis(12),
+            BTreeMap::from([("x-client-id".to_string(), "client-a".to_string())]),
+        );
+
+        // Act
+        recorder.record(record.clone());
+        recorder.record(record.clone());
+
+        // the records are written in the background
+        let mut records = vec![];
+        for _ in 0..50 {
+            records = read_records(path).unwrap();
+            if records.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        // Assert
+        assert_eq!(records, vec![record.clone(), record]);
+    }
+
+    #[test]
+    fn successfully_read_unversioned_records_and_reject_newer_versions() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("versions.jsonl");
+        let newer_path = dir.path().join("newer.jsonl");
+        fs::write(
+            &path,
+            "{\"timestamp_ms\":1,\"model_name\":\"m\",\"input\":\"{}\",\"output\":\"{}\",\"latency_ms\":1.0,\"unknown\":true}\n",
+        )
+        .unwrap();
+        fs::write(
+            &newer_path,
+            "{\"format_version\":2,\"timestamp_ms\":1,\"model_name\":\"m\",\"input\":\"{}\",\"output\":\"{}\",\"latency_ms\":1.0}\n",
+        )
+        .unwrap();
+
+        // Act
+        let records = read_records(path.to_str().unwrap());
+        let newer = read_records(newer_path.to_str().unwrap());
+
+        // Assert
+        assert_eq!(records.unwrap()[0].format_version, 1);
+        assert!(newer.is_err());
+    }
+
+    #[test]
+    fn fails_to_read_records_when_log_is_invalid() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("invalid.jsonl");
+        fs::write(&path, "not a record\n").unwrap();
+
+        // Act
+        let result = read_records(path.to_str().unwrap());
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
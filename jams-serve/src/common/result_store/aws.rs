@@ -0,0 +1,70 @@
+use crate::common::result_store::RESULT_CONTENT_TYPE;
+use aws_sdk_s3 as s3;
+use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::primitives::ByteStream;
+use jams_core::model_store::aws::s3::build_client;
+use std::time::Duration;
+
+/// Writes results to an S3 or MinIO bucket and presigns the URLs to download them.
+pub struct S3Results {
+    client: s3::Client,
+    bucket: String,
+}
+
+impl S3Results {
+    /// Connects to the bucket with a client configured in the same way as the S3 client of the model store.
+    ///
+    /// # Arguments
+    /// * `use_minio` - Whether the bucket is a MinIO bucket.
+    /// * `bucket` - The name of the bucket.
+    ///
+    /// # Errors
+    /// Returns an error if the client cannot be built.
+    pub async fn connect(use_minio: Option<bool>, bucket: String) -> anyhow::Result<Self> {
+        Ok(S3Results {
+            client: build_client(use_minio).await?,
+            bucket,
+        })
+    }
+
+    /// Writes a result to the bucket and returns a presigned URL to download it.
+    ///
+    /// # Errors
+    /// Returns an error if the result cannot be written or the URL cannot be signed.
+    pub async fn store(
+        &self,
+        key: &str,
+        output: String,
+        url_expiry: Duration,
+    ) -> anyhow::Result<String> {
+        if let Err(e) = self
+            .client
+            .put_object()
+            .bucket(self.bucket.as_str())
+            .key(key)
+            .content_type(RESULT_CONTENT_TYPE)
+            .body(ByteStream::from(output.into_bytes()))
+            .send()
+            .await
+        {
+            let e = e.into_service_error();
+            tracing::error!("Failed to write result {} to S3 ❌: {}", key, e);
+            anyhow::bail!("Failed to write result {} to S3 ❌: {}", key, e)
+        }
+        let presigning_config = PresigningConfig::expires_in(url_expiry)?;
+        match self
+            .client
+            .get_object()
+            .bucket(self.bucket.as_str())
+            .key(key)
+            .presigned(presigning_config)
+            .await
+        {
+            Ok(request) => Ok(request.uri().to_string()),
+            Err(e) => {
+                tracing::error!("Failed to sign URL of result {} ❌: {}", key, e);
+                anyhow::bail!("Failed to sign URL of result {} ❌: {}", key, e)
+            }
+        }
+    }
+}
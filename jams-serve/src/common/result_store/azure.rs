@@ -0,0 +1,57 @@
+use crate::common::result_store::RESULT_CONTENT_TYPE;
+use azure_storage_blobs::prelude::{BlobSasPermissions, ContainerClient};
+use jams_core::model_store::azure::blob_storage::build_container_client;
+use std::time::Duration;
+
+/// Writes results to an Azure Blob Storage container and signs the URLs to download them.
+pub struct AzureResults(ContainerClient);
+
+impl AzureResults {
+    /// Connects to the container with a client configured in the same way as the client of the model store.
+    ///
+    /// # Errors
+    /// Returns an error if the client cannot be built.
+    pub fn connect(container: String) -> anyhow::Result<Self> {
+        Ok(AzureResults(build_container_client(container)?))
+    }
+
+    /// Writes a result to the container and returns a URL signed with a shared access signature to download it.
+    ///
+    /// # Errors
+    /// Returns an error if the result cannot be written or the URL cannot be signed.
+    pub async fn store(
+        &self,
+        key: &str,
+        output: String,
+        url_expiry: Duration,
+    ) -> anyhow::Result<String> {
+        let blob_client = self.0.blob_client(key);
+        if let Err(e) = blob_client
+            .put_block_blob(output.into_bytes())
+            .content_type(RESULT_CONTENT_TYPE)
+            .await
+        {
+            tracing::error!("Failed to write result {} to Azure ❌: {}", key, e);
+            anyhow::bail!("Failed to write result {} to Azure ❌: {}", key, e)
+        }
+        let permissions = BlobSasPermissions {
+            read: true,
+            ..Default::default()
+        };
+        let expiry = time::OffsetDateTime::now_utc() + url_expiry;
+        let signed_url = match blob_client
+            .shared_access_signature(permissions, expiry)
+            .await
+        {
+            Ok(signature) => blob_client.generate_signed_blob_url(&signature),
+            Err(e) => Err(e),
+        };
+        match signed_url {
+            Ok(url) => Ok(url.to_string()),
+            Err(e) => {
+                tracing::error!("Failed to sign URL of result {} ❌: {}", key, e);
+                anyhow::bail!("Failed to sign URL of result {} ❌: {}", key, e)
+            }
+        }
+    }
+}
@@ -0,0 +1,311 @@
+#[cfg(feature = "aws")]
+mod aws;
+#[cfg(feature = "azure")]
+mod azure;
+
+use chrono::Utc;
+use jams_core::model_store::backend_disabled;
+use serde::{Deserialize, Serialize};
+#[cfg(any(test, feature = "test-utils"))]
+use std::collections::HashMap;
+#[cfg(any(test, feature = "test-utils"))]
+use std::sync::Mutex;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// The default size (in bytes) of the predictions above which they are written to the result store.
+pub const DEFAULT_RESULT_THRESHOLD_BYTES: usize = 8 * 1024 * 1024;
+
+/// The default time (in seconds) for which the signed URL of a stored result is valid.
+pub const DEFAULT_RESULT_URL_EXPIRY_SECONDS: u64 = 3600;
+
+/// The longest time (in seconds) for which a signed URL can be valid. S3 does not sign URLs for longer.
+const MAX_RESULT_URL_EXPIRY_SECONDS: u64 = 7 * 24 * 3600;
+
+/// Content type of the stored results, which hold the predictions as returned by `/api/predict`.
+pub const RESULT_CONTENT_TYPE: &str = "application/json";
+
+/// Configuration of the result store, to which big predictions are written instead of being returned in the
+/// response. The client gets a signed URL from which it can download the predictions later, which helps the
+/// clients who cannot consume a multi-hundred-MB synchronous response. The result store can only be configured
+/// using the config file.
+///
+/// The results are not deleted by the server. Use a lifecycle rule of the bucket or container to expire them.
+///
+/// # Example
+/// ```toml
+/// [config.result_store]
+/// url = "s3://my-bucket/predictions/"
+/// threshold_bytes = 8388608
+/// url_expiry = 3600
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ResultStoreConfig {
+    /// Where the results are written, e.g. `s3://my-bucket/predictions/`, `minio://my-bucket/predictions/`
+    /// or `azure://my-container/predictions/`. The clients are configured in the same way as for the model store.
+    pub url: String,
+
+    /// The size (in bytes) of the predictions above which they are written to the result store. Defaults to 8 MiB.
+    #[serde(default = "default_threshold_bytes")]
+    pub threshold_bytes: usize,
+
+    /// The time (in seconds) for which the signed URL of a result is valid. Defaults to an hour and can be at
+    /// most 7 days.
+    #[serde(default = "default_url_expiry")]
+    pub url_expiry: u64,
+}
+
+fn default_threshold_bytes() -> usize {
+    DEFAULT_RESULT_THRESHOLD_BYTES
+}
+
+fn default_url_expiry() -> u64 {
+    DEFAULT_RESULT_URL_EXPIRY_SECONDS
+}
+
+impl ResultStoreConfig {
+    /// Validates the result store configuration.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.url_expiry == 0 || self.url_expiry > MAX_RESULT_URL_EXPIRY_SECONDS {
+            anyhow::bail!(
+                "result_store.url_expiry must be between 1 and {} seconds ❌",
+                MAX_RESULT_URL_EXPIRY_SECONDS
+            )
+        }
+        parse_url(self.url.as_str())?;
+        Ok(())
+    }
+}
+
+/// Backend to which results are written.
+pub enum ResultBackend {
+    #[cfg(feature = "aws")]
+    S3(aws::S3Results),
+    #[cfg(feature = "azure")]
+    Azure(azure::AzureResults),
+    /// Results held in memory, keyed by their key. Only built for tests.
+    #[cfg(any(test, feature = "test-utils"))]
+    Memory(Mutex<HashMap<String, String>>),
+}
+
+/// A result written to the result store, which is returned to the client instead of the predictions.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredResult {
+    /// A signed URL from which the predictions can be downloaded until `expires_at`.
+    pub url: String,
+    /// The key of the result in the bucket or container.
+    pub key: String,
+    /// The size of the predictions in bytes.
+    pub size_bytes: usize,
+    /// The RFC 3339 time after which the URL expires.
+    pub expires_at: String,
+}
+
+/// Writes predictions above a size threshold to a bucket or container and signs a URL to download them.
+pub struct ResultStore {
+    backend: ResultBackend,
+    prefix: String,
+    threshold_bytes: usize,
+    url_expiry: Duration,
+}
+
+impl ResultStore {
+    /// Connects to the bucket or container of the result store.
+    ///
+    /// # Errors
+    /// Returns an error if the configuration is invalid, the backend of the result store was left out of this
+    /// build, or the client cannot be built.
+    #[cfg_attr(not(any(feature = "aws", feature = "azure")), allow(unused_variables))]
+    pub async fn connect(config: ResultStoreConfig) -> anyhow::Result<Self> {
+        config.validate()?;
+        let (scheme, location, prefix) = parse_url(config.url.as_str())?;
+        let backend = match scheme {
+            #[cfg(feature = "aws")]
+            "s3" | "minio" => {
+                let use_minio = if scheme == "minio" { Some(true) } else { None };
+                ResultBackend::S3(aws::S3Results::connect(use_minio, location.to_string()).await?)
+            }
+            #[cfg(feature = "azure")]
+            "azure" => ResultBackend::Azure(azure::AzureResults::connect(location.to_string())?),
+            _ => {
+                let feature = if scheme == "azure" { "azure" } else { "aws" };
+                let e = backend_disabled(format!("Result store {}", config.url).as_str(), feature);
+                tracing::error!("{}", e);
+                return Err(e);
+            }
+        };
+        tracing::info!(
+            "Predictions above {} bytes are written to {} 📦",
+            config.threshold_bytes,
+            config.url
+        );
+        Ok(Self::new(
+            backend,
+            prefix.to_string(),
+            config.threshold_bytes,
+            Duration::from_secs(config.url_expiry),
+        ))
+    }
+
+    /// Creates a result store which writes the results under `prefix` in the backend.
+    pub fn new(
+        backend: ResultBackend,
+        prefix: String,
+        threshold_bytes: usize,
+        url_expiry: Duration,
+    ) -> Self {
+        let prefix = match prefix.is_empty() || prefix.ends_with('/') {
+            true => prefix,
+            false => format!("{}/", prefix),
+        };
+        ResultStore {
+            backend,
+            prefix,
+            threshold_bytes,
+            url_expiry,
+        }
+    }
+
+    /// Returns true if the predictions are too big to be returned in the response.
+    pub fn should_store(&self, output: &str) -> bool {
+        output.len() > self.threshold_bytes
+    }
+
+    /// Writes the predictions of a model to the result store and signs a URL to download them.
+    ///
+    /// # Arguments
+    /// * `model_name` - The name of the model which made the predictions, used in the key of the result.
+    /// * `output` - The predictions as a JSON string.
+    ///
+    /// # Errors
+    /// Returns an error if the result cannot be written or the URL cannot be signed.
+    #[cfg_attr(
+        not(any(feature = "aws", feature = "azure", test, feature = "test-utils")),
+        allow(unused_variables)
+    )]
+    pub async fn store(&self, model_name: &str, output: String) -> anyhow::Result<StoredResult> {
+        let key = format!("{}{}/{}.json", self.prefix, model_name, Uuid::new_v4());
+        let size_bytes = output.len();
+        let expires_at = Utc::now() + self.url_expiry;
+
+        let url = match &self.backend {
+            #[cfg(feature = "aws")]
+            ResultBackend::S3(results) => {
+                results.store(key.as_str(), output, self.url_expiry).await?
+            }
+            #[cfg(feature = "azure")]
+            ResultBackend::Azure(results) => {
+                results.store(key.as_str(), output, self.url_expiry).await?
+            }
+            #[cfg(any(test, feature = "test-utils"))]
+            ResultBackend::Memory(results) => {
+                match results.lock() {
+                    Ok(mut results) => results.insert(key.clone(), output),
+                    Err(_) => anyhow::bail!("Failed to write result {} ❌", key),
+                };
+                format!("memory://{}", key)
+            }
+            #[cfg(not(any(feature = "aws", feature = "azure", test, feature = "test-utils")))]
+            _ => unreachable!("result stores are only connected to backends of this build"),
+        };
+
+        Ok(StoredResult {
+            url,
+            key,
+            size_bytes,
+            expires_at: expires_at.to_rfc3339(),
+        })
+    }
+}
+
+/// Splits the URL of a result store into its scheme, bucket or container, and prefix.
+fn parse_url(url: &str) -> anyhow::Result<(&str, &str, &str)> {
+    let (scheme, path) = match url.split_once("://") {
+        Some((scheme, path)) if ["s3", "minio", "azure"].contains(&scheme) => (scheme, path),
+        _ => anyhow::bail!(
+            "result_store.url must start with s3://, minio:// or azure:// but got {} ❌",
+            url
+        ),
+    };
+    let (location, prefix) = path.split_once('/').unwrap_or((path, ""));
+    if location.is_empty() {
+        anyhow::bail!(
+            "result_store.url must name a bucket or container but got {} ❌",
+            url
+        )
+    }
+    Ok((scheme, location, prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_parse_result_store_config() {
+        // Arrange
+        let contents = r#"url = "azure://my-container/predictions""#;
+
+        // Act
+        let config: ResultStoreConfig = toml::from_str(contents).unwrap();
+
+        // Assert
+        assert_eq!(config.threshold_bytes, DEFAULT_RESULT_THRESHOLD_BYTES);
+        assert_eq!(config.url_expiry, DEFAULT_RESULT_URL_EXPIRY_SECONDS);
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            parse_url(config.url.as_str()).unwrap(),
+            ("azure", "my-container", "predictions")
+        );
+    }
+
+    #[test]
+    fn fails_to_validate_result_store_config_when_url_is_invalid() {
+        // Arrange
+        let config = ResultStoreConfig {
+            url: "gs://my-bucket/predictions/".to_string(),
+            threshold_bytes: DEFAULT_RESULT_THRESHOLD_BYTES,
+            url_expiry: DEFAULT_RESULT_URL_EXPIRY_SECONDS,
+        };
+
+        // Act
+        let result = config.validate();
+
+        // Assert
+        assert!(result.is_err())
+    }
+
+    #[tokio::test]
+    async fn successfully_store_result_above_threshold() {
+        // Arrange
+        let result_store = ResultStore::new(
+            ResultBackend::Memory(Mutex::new(HashMap::new())),
+            "predictions".to_string(),
+            16,
+            Duration::from_secs(60),
+        );
+        let output = r#"{"predictions":{"predictions":[[0.1],[0.2]]}}"#.to_string();
+
+        // Act
+        let should_store = result_store.should_store(output.as_str());
+        let result = result_store
+            .store("my_model", output.clone())
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(should_store);
+        assert!(!result_store.should_store("{}"));
+        assert!(result.key.starts_with("predictions/my_model/"));
+        assert_eq!(result.url, format!("memory://{}", result.key));
+        assert_eq!(result.size_bytes, output.len());
+        match &result_store.backend {
+            ResultBackend::Memory(results) => {
+                assert_eq!(results.lock().unwrap()[&result.key], output)
+            }
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use crate::common::metrics::S3_EVENTS_TOTAL;
+use crate::common::s3_events::{
+    apply, parse_notification, targets, Applied, ObjectAction, ObjectEvent, S3EventsConfig,
+    Sequencers,
+};
+use aws_config::BehaviorVersion;
+use aws_sdk_s3 as s3;
+use aws_sdk_sqs as sqs;
+use aws_sdk_sqs::error::DisplayErrorContext;
+use jams_core::manager::Manager;
+use jams_core::model_store::aws::s3::build_client;
+use jams_core::model_store::storage::ModelName;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Delay before receiving messages again after the queue could not be read, or the listener panicked.
+const RECEIVE_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Receives the S3 event notifications of the model store bucket from an SQS queue and applies them.
+pub struct S3EventListener {
+    client: sqs::Client,
+    s3_client: s3::Client,
+    config: S3EventsConfig,
+    bucket_name: String,
+    sequencers: Mutex<Sequencers>,
+}
+
+impl S3EventListener {
+    /// Creates a listener with an SQS client configured from the environment in the same way as the S3 client of
+    /// the model store, i.e. LocalStack is used if `USE_LOCALSTACK` is `true`.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The S3 event notifications configuration.
+    /// * `bucket_name` - The name of the model store bucket.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration is invalid or the bucket name is empty.
+    pub async fn connect(config: S3EventsConfig, bucket_name: String) -> anyhow::Result<Self> {
+        config.validate()?;
+        if bucket_name.is_empty() {
+            anyhow::bail!("S3 events require the name of the model store bucket ❌")
+        }
+        let mut loader = aws_config::defaults(BehaviorVersion::latest());
+        if env::var("USE_LOCALSTACK").unwrap_or_default() == "true" {
+            let hostname = env::var("LOCALSTACK_HOSTNAME").unwrap_or("localhost".to_string());
+            loader = loader.endpoint_url(format!("http://{}:4566/", hostname));
+        }
+        let client = sqs::Client::new(&loader.load().await);
+        let s3_client = build_client(None).await?;
+        tracing::info!(
+            "Listening for S3 events of bucket {} on {} 📬",
+            bucket_name,
+            config.queue_url
+        );
+        Ok(S3EventListener {
+            client,
+            s3_client,
+            config,
+            bucket_name,
+            sequencers: Mutex::new(Sequencers::default()),
+        })
+    }
+
+    /// Starts receiving and applying the notifications in a supervised background task.
+    ///
+    /// The notifications are received in a task of their own, which is restarted if it panics, so that a panic does
+    /// not silently stop applying the events of the bucket. The listener stops when the returned task is aborted.
+    pub fn spawn(self, manager: Arc<Manager>) -> JoinHandle<()> {
+        let listener = Arc::new(self);
+        tokio::spawn(async move {
+            loop {
+                let listener = listener.clone();
+                let manager = manager.clone();
+                let task = tokio::spawn(async move {
+                    loop {
+                        if let Err(e) = listener.receive(&manager).await {
+                            tracing::warn!("Failed to receive S3 events ⚠️: {}", e);
+                            tokio::time::sleep(RECEIVE_RETRY_DELAY).await;
+                        }
+                    }
+                });
+                let _abort = AbortOnDrop(task.abort_handle());
+                match task.await {
+                    Err(e) if e.is_panic() => {
+                        tracing::error!("S3 event listener panicked, restarting it ❌");
+                        tokio::time::sleep(RECEIVE_RETRY_DELAY).await;
+                    }
+                    _ => return,
+                }
+            }
+        })
+    }
+
+    /// Receives a batch of messages and applies their events.
+    ///
+    /// A message is deleted once all its events are applied, or if it is not an S3 event notification. Otherwise,
+    /// it is received again after its visibility timeout, until the redrive policy of the queue moves it aside.
+    async fn receive(&self, manager: &Manager) -> anyhow::Result<()> {
+        let output = match self
+            .client
+            .receive_message()
+            .queue_url(self.config.queue_url.as_str())
+            .max_number_of_messages(self.config.max_messages)
+            .wait_time_seconds(self.config.wait_time_seconds)
+            .send()
+            .await
+        {
+            Ok(output) => output,
+            Err(e) => anyhow::bail!("{}", DisplayErrorContext(&e)),
+        };
+
+        for message in output.messages() {
+            let events = match parse_notification(message.body().unwrap_or_default()) {
+                Ok(events) => events,
+                Err(e) => {
+                    tracing::warn!("Discarding S3 event message ⚠️: {}", e);
+                    Vec::new()
+                }
+            };
+
+            let mut applied = true;
+            for event in events {
+                let targets = targets(manager, self.bucket_name.as_str(), &event);
+                if targets.is_empty() {
+                    continue;
+                }
+                let outcome = match self.handle(manager, &event, targets).await {
+                    Ok(outcome) => outcome,
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to apply S3 event for model {} ⚠️: {}",
+                            event.model_name,
+                            e
+                        );
+                        applied = false;
+                        "failure"
+                    }
+                };
+                S3_EVENTS_TOTAL
+                    .with_label_values(&[event.action.as_str(), outcome])
+                    .inc();
+            }
+
+            if let (true, Some(receipt_handle)) = (applied, message.receipt_handle()) {
+                if let Err(e) = self
+                    .client
+                    .delete_message()
+                    .queue_url(self.config.queue_url.as_str())
+                    .receipt_handle(receipt_handle)
+                    .send()
+                    .await
+                {
+                    tracing::warn!(
+                        "Failed to delete S3 event message ⚠️: {}",
+                        DisplayErrorContext(&e)
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies an event to the models it targets, unless it is stale.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&str)` - The outcome of the event, i.e. `success`, `deferred` if the model belongs to a deployment
+    ///   group, or `stale` if a later event of the artifact was received or the removed artifact was uploaded again.
+    /// * `Err(anyhow::Error)` - If the artifact could not be checked or a model could not be added, updated or deleted.
+    async fn handle(
+        &self,
+        manager: &Manager,
+        event: &ObjectEvent,
+        targets: Vec<ModelName>,
+    ) -> anyhow::Result<&'static str> {
+        let latest = self
+            .sequencers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .observe(event);
+        // a delayed removal must not delete the artifact which was uploaded again since
+        if !latest || (event.action == ObjectAction::Removed && self.object_exists(event).await?) {
+            tracing::info!(
+                "Skipping stale S3 event: model {} was {} ⏭️",
+                event.model_name,
+                event.action.as_str()
+            );
+            return Ok("stale");
+        }
+
+        let mut outcome = "success";
+        for model_name in targets {
+            match apply(manager, event.action, model_name.as_str()).await? {
+                Applied::Done => tracing::info!(
+                    "Applied S3 event: model {} was {} ✅",
+                    model_name,
+                    event.action.as_str()
+                ),
+                Applied::Deferred => {
+                    tracing::info!(
+                        "Deferring S3 event of model {} to the next poll, as it belongs to a deployment group ⏳",
+                        model_name
+                    );
+                    outcome = "deferred";
+                }
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Checks whether the artifact of an event is in its bucket.
+    async fn object_exists(&self, event: &ObjectEvent) -> anyhow::Result<bool> {
+        match self
+            .s3_client
+            .head_object()
+            .bucket(event.bucket_name.as_str())
+            .key(event.key())
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(false),
+            Err(e) => anyhow::bail!("{}", DisplayErrorContext(&e)),
+        }
+    }
+}
+
+/// Aborts the listener task when its supervisor is aborted.
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort()
+    }
+}
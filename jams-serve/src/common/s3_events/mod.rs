@@ -0,0 +1,529 @@
+#[cfg(feature = "aws")]
+pub mod aws;
+
+use jams_core::manager::Manager;
+use jams_core::model_store::attached::{qualify, StoreSource, STORE_SEPARATOR};
+use jams_core::model_store::storage::{extract_framework, ModelName};
+use serde::Deserialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Suffix of the model artifacts in the model store.
+const ARTIFACT_SUFFIX: &str = ".tar.gz";
+
+/// Configuration of the listener which applies the S3 event notifications of the model store bucket as soon as
+/// they are delivered to an SQS queue, instead of waiting for the next poll. The S3 event notifications can only
+/// be configured using the config file and are only supported by the `aws` model store.
+///
+/// The bucket must publish its `s3:ObjectCreated:*` and `s3:ObjectRemoved:*` events to the queue, either directly
+/// or through an SNS topic. Polling can then be disabled, or kept at a long interval to catch up on missed events.
+///
+/// # Example
+/// ```toml
+/// [config.s3_events]
+/// queue_url = "https://sqs.eu-west-2.amazonaws.com/123456789012/jams-model-store"
+/// wait_time_seconds = 20
+/// max_messages = 10
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct S3EventsConfig {
+    /// URL of the SQS queue receiving the S3 event notifications.
+    pub queue_url: String,
+
+    /// How long (in seconds) a receive waits for messages, between 0 and 20. Defaults to 20, i.e. long polling.
+    #[serde(default = "default_wait_time_seconds")]
+    pub wait_time_seconds: i32,
+
+    /// Maximum number of messages received at once, between 1 and 10. Defaults to 10.
+    #[serde(default = "default_max_messages")]
+    pub max_messages: i32,
+}
+
+fn default_wait_time_seconds() -> i32 {
+    20
+}
+
+fn default_max_messages() -> i32 {
+    10
+}
+
+impl S3EventsConfig {
+    /// Validates the S3 event notifications configuration against the limits of SQS.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.queue_url.is_empty() {
+            anyhow::bail!("s3_events.queue_url must be specified ❌")
+        }
+        if !(0..=20).contains(&self.wait_time_seconds) {
+            anyhow::bail!("s3_events.wait_time_seconds must be between 0 and 20 ❌")
+        }
+        if !(1..=10).contains(&self.max_messages) {
+            anyhow::bail!("s3_events.max_messages must be between 1 and 10 ❌")
+        }
+        Ok(())
+    }
+}
+
+/// What happened to a model artifact in the model store bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectAction {
+    /// The artifact was uploaded or overwritten.
+    Created,
+    /// The artifact was deleted.
+    Removed,
+}
+
+impl ObjectAction {
+    /// Returns the name of the action as used in the labels of the metrics and in the logs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ObjectAction::Created => "created",
+            ObjectAction::Removed => "removed",
+        }
+    }
+}
+
+/// A change of a model artifact in a bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectEvent {
+    /// What happened to the artifact.
+    pub action: ObjectAction,
+    /// The name of the bucket of the artifact.
+    pub bucket_name: String,
+    /// The name of the model, prefixed with its framework, e.g. `catboost-titanic_model`.
+    pub model_name: String,
+    /// Orders the events of the same object, as SQS may deliver them out of order. Empty if S3 did not set it.
+    pub sequencer: String,
+}
+
+impl ObjectEvent {
+    /// Returns the key of the artifact in its bucket.
+    pub fn key(&self) -> String {
+        format!("{}{}", self.model_name, ARTIFACT_SUFFIX)
+    }
+}
+
+/// Parses an SQS message holding S3 event notifications.
+///
+/// # Arguments
+///
+/// * `body` - The body of the message, either an S3 event notification or an SNS notification wrapping one.
+///
+/// # Returns
+///
+/// * `Ok(Vec<ObjectEvent>)` - The changes of the model artifacts of every bucket, in the order of the records. Test
+///   events, other event types and objects which are not model artifacts are skipped.
+/// * `Err(anyhow::Error)` - If the message is not an S3 event notification.
+pub fn parse_notification(body: &str) -> anyhow::Result<Vec<ObjectEvent>> {
+    let mut notification: Value = match serde_json::from_str(body) {
+        Ok(notification) => notification,
+        Err(e) => anyhow::bail!("Failed to parse S3 event notification ❌: {}", e),
+    };
+
+    // unwrap the notifications delivered through an SNS topic
+    if notification["Type"] == "Notification" {
+        notification = match notification["Message"].as_str().map(serde_json::from_str) {
+            Some(Ok(message)) => message,
+            _ => anyhow::bail!("Failed to parse S3 event notification wrapped by SNS ❌"),
+        };
+    }
+
+    // sent by S3 when the notifications of the bucket are configured
+    if notification["Event"] == "s3:TestEvent" {
+        return Ok(Vec::new());
+    }
+
+    let records = match notification["Records"].as_array() {
+        Some(records) => records,
+        None => anyhow::bail!("S3 event notification has no Records ❌"),
+    };
+
+    Ok(records
+        .iter()
+        .filter_map(|record| {
+            let event_name = record["eventName"].as_str()?;
+            let action = if event_name.starts_with("ObjectCreated:") {
+                ObjectAction::Created
+            } else if event_name.starts_with("ObjectRemoved:") {
+                ObjectAction::Removed
+            } else {
+                return None;
+            };
+            let key = decode_key(record["s3"]["object"]["key"].as_str()?);
+            let model_name = key.strip_suffix(ARTIFACT_SUFFIX)?;
+
+            // only the artifacts at the root of the bucket are models
+            let (framework, name) = model_name.split_once('-')?;
+            if name.is_empty()
+                || model_name.contains('/')
+                || extract_framework(framework.to_string()) != Some(framework)
+            {
+                return None;
+            }
+            Some(ObjectEvent {
+                action,
+                bucket_name: record["s3"]["bucket"]["name"].as_str()?.to_string(),
+                model_name: model_name.to_string(),
+                sequencer: record["s3"]["object"]["sequencer"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+        })
+        .collect())
+}
+
+/// The latest sequencer of each artifact, to skip the events which SQS delivers after a later event of the same
+/// artifact, e.g. a delayed `ObjectRemoved` arriving after the artifact was uploaded again.
+#[derive(Debug, Default)]
+pub struct Sequencers(HashMap<(String, String), String>);
+
+impl Sequencers {
+    /// Records the sequencer of an event.
+    ///
+    /// # Returns
+    ///
+    /// * `true` - If the event is the latest event of its artifact so far, or a redelivery of it.
+    /// * `false` - If a later event of the same artifact was already received, i.e. the event is stale.
+    pub fn observe(&mut self, event: &ObjectEvent) -> bool {
+        if event.sequencer.is_empty() {
+            return true;
+        }
+        let key = (event.bucket_name.clone(), event.model_name.clone());
+        match self.0.get(&key) {
+            Some(latest) if compare_sequencers(latest, &event.sequencer) == Ordering::Greater => {
+                false
+            }
+            _ => {
+                self.0.insert(key, event.sequencer.clone());
+                true
+            }
+        }
+    }
+}
+
+/// Orders the sequencers of two events of the same object. Sequencers are hexadecimal values of varying length,
+/// which are compared once the shorter one is padded with leading zeros.
+fn compare_sequencers(a: &str, b: &str) -> Ordering {
+    let width = a.len().max(b.len());
+    format!("{:0>width$}", a).cmp(&format!("{:0>width$}", b))
+}
+
+/// Returns the names of the models an event applies to, prefixed with their framework.
+///
+/// # Arguments
+///
+/// * `manager` - The manager serving the models.
+/// * `bucket_name` - The name of the bucket of the model store the server was started with.
+/// * `event` - The change of a model artifact.
+///
+/// # Returns
+///
+/// The name of the model if the event is of the bucket of the model store the server was started with, and the name
+/// of the model qualified by the name of every attached store reading the bucket of the event, e.g.
+/// `archive.catboost-titanic_model`. Empty if the event is of a bucket which serves no model.
+pub fn targets(manager: &Manager, bucket_name: &str, event: &ObjectEvent) -> Vec<ModelName> {
+    let mut targets = Vec::new();
+    if event.bucket_name == bucket_name {
+        targets.push(event.model_name.clone());
+    }
+    let source = StoreSource::S3(event.bucket_name.clone()).to_string();
+    for store in manager.get_stores() {
+        if store.url == source {
+            targets.push(qualify(store.name.as_str(), event.model_name.as_str()));
+        }
+    }
+    targets
+}
+
+/// Decodes the object key of an S3 event, which is URL encoded with spaces as `+`.
+fn decode_key(key: &str) -> String {
+    let bytes = key.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// What applying a change of a model artifact did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Applied {
+    /// The model was added, updated or deleted, or was not loaded and deleted.
+    Done,
+    /// The model belongs to a deployment group, which is only deployed together on the next poll.
+    Deferred,
+}
+
+/// Applies a change of a model artifact to a model served by the manager.
+///
+/// A created artifact updates the model if it is loaded, or adds it otherwise. A removed artifact deletes the
+/// model if it is loaded. The models of a deployment group are left to the next poll, which deploys the group
+/// together.
+///
+/// # Arguments
+///
+/// * `manager` - The manager serving the model.
+/// * `action` - What happened to the artifact.
+/// * `model_name` - The name of the model prefixed with its framework, and the name of its store if it is attached.
+///
+/// # Errors
+///
+/// Returns an error if the model could not be added, updated or deleted.
+pub async fn apply(
+    manager: &Manager,
+    action: ObjectAction,
+    model_name: &str,
+) -> anyhow::Result<Applied> {
+    if manager.deployment_group_of(model_name).is_some() {
+        return Ok(Applied::Deferred);
+    }
+    let name = loaded_name(model_name);
+    let loaded = manager.get_model_metadata(name.clone()).is_some();
+    match (action, loaded) {
+        (ObjectAction::Created, true) => manager.update_model(name).await?,
+        (ObjectAction::Created, false) => manager.add_model(model_name.to_string()).await?,
+        (ObjectAction::Removed, true) => manager.delete_model(name)?,
+        (ObjectAction::Removed, false) => {}
+    }
+    Ok(Applied::Done)
+}
+
+/// Returns the name a model is served under, i.e. without its framework but with the name of its store, if any.
+fn loaded_name(model_name: &str) -> ModelName {
+    let (store_name, store_model_name) = match model_name.split_once(STORE_SEPARATOR) {
+        Some((store_name, store_model_name)) => (Some(store_name), store_model_name),
+        None => (None, model_name),
+    };
+    let name = match store_model_name.split_once('-') {
+        Some((_, name)) => name,
+        None => store_model_name,
+    };
+    match store_name {
+        Some(store_name) => qualify(store_name, name),
+        None => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jams_core::manager::ManagerBuilder;
+    use jams_core::model_store::groups::{DeploymentGroup, DeploymentGroups};
+    use jams_core::model_store::local::filesystem::LocalModelStore;
+    use jams_core::model_store::ModelStore;
+    use std::sync::Arc;
+
+    fn record(event_name: &str, bucket_name: &str, key: &str) -> Value {
+        serde_json::json!({
+            "eventName": event_name,
+            "s3": {"bucket": {"name": bucket_name}, "object": {"key": key, "sequencer": "0055AED6DCD90281E5"}}
+        })
+    }
+
+    fn event(action: ObjectAction, sequencer: &str) -> ObjectEvent {
+        ObjectEvent {
+            action,
+            bucket_name: "models".to_string(),
+            model_name: "catboost-titanic_model".to_string(),
+            sequencer: sequencer.to_string(),
+        }
+    }
+
+    async fn manager(deployment_groups: DeploymentGroups) -> Manager {
+        let model_store = LocalModelStore::new("tests/model_store".to_string())
+            .await
+            .expect("Failed to create model store ❌");
+        ManagerBuilder::new(Arc::new(ModelStore::Local(model_store)))
+            .with_deployment_groups(deployment_groups)
+            .build()
+            .expect("Failed to initialize manager ❌")
+    }
+
+    #[test]
+    fn successfully_parse_s3_event_notifications() {
+        // Arrange
+        let notification = serde_json::json!({"Records": [
+            record("ObjectCreated:Put", "models", "catboost-titanic_model.tar.gz"),
+            record("ObjectRemoved:Delete", "models", "pytorch-my+model%2Dv2.tar.gz"),
+            record("ObjectCreated:Put", "other", "catboost-titanic_model.tar.gz"),
+            record("ObjectCreated:Put", "models", "archive/catboost-old_model.tar.gz"),
+            record("ObjectCreated:Put", "models", "titanic_model.tar.gz"),
+            record("ObjectCreated:Put", "models", "catboost-titanic_model.json"),
+            record("ObjectRestore:Completed", "models", "catboost-titanic_model.tar.gz"),
+        ]})
+        .to_string();
+        let sns = serde_json::json!({"Type": "Notification", "Message": notification}).to_string();
+        let test_event = r#"{"Service": "Amazon S3", "Event": "s3:TestEvent", "Bucket": "models"}"#;
+
+        // Act
+        let events = parse_notification(notification.as_str()).unwrap();
+        let wrapped = parse_notification(sns.as_str()).unwrap();
+        let test_events = parse_notification(test_event).unwrap();
+
+        // Assert
+        let object_event = |action, bucket_name: &str, model_name: &str| ObjectEvent {
+            action,
+            bucket_name: bucket_name.to_string(),
+            model_name: model_name.to_string(),
+            sequencer: "0055AED6DCD90281E5".to_string(),
+        };
+        assert_eq!(
+            events,
+            vec![
+                object_event(ObjectAction::Created, "models", "catboost-titanic_model"),
+                object_event(ObjectAction::Removed, "models", "pytorch-my model-v2"),
+                object_event(ObjectAction::Created, "other", "catboost-titanic_model"),
+            ]
+        );
+        assert_eq!(events[0].key(), "catboost-titanic_model.tar.gz");
+        assert_eq!(wrapped, events);
+        assert!(test_events.is_empty());
+    }
+
+    #[test]
+    fn fails_to_parse_messages_which_are_not_s3_event_notifications() {
+        // Arrange
+        let bodies = [
+            "not json",
+            r#"{"hello": "world"}"#,
+            r#"{"Type": "Notification", "Message": "not json"}"#,
+        ];
+
+        // Act
+        let parsed: Vec<bool> = bodies
+            .iter()
+            .map(|body| parse_notification(body).is_ok())
+            .collect();
+
+        // Assert
+        assert_eq!(parsed, vec![false; bodies.len()]);
+    }
+
+    #[test]
+    fn fails_to_validate_config_outside_of_sqs_limits() {
+        // Arrange
+        let config: S3EventsConfig =
+            toml::from_str(r#"queue_url = "https://sqs.eu-west-2.amazonaws.com/1/models""#)
+                .unwrap();
+        let wait = S3EventsConfig {
+            wait_time_seconds: 21,
+            ..config.clone()
+        };
+        let messages = S3EventsConfig {
+            max_messages: 0,
+            ..config.clone()
+        };
+
+        // Act & Assert
+        assert_eq!(config.wait_time_seconds, 20);
+        assert_eq!(config.max_messages, 10);
+        assert!(config.validate().is_ok());
+        assert!(wait.validate().is_err());
+        assert!(messages.validate().is_err());
+    }
+
+    #[test]
+    fn successfully_skip_events_delivered_after_a_later_event_of_the_same_artifact() {
+        // Arrange
+        let mut sequencers = Sequencers::default();
+        let other = ObjectEvent {
+            model_name: "pytorch-my_model".to_string(),
+            ..event(ObjectAction::Removed, "0055AED6DCD9028100")
+        };
+
+        // Act
+        let created = sequencers.observe(&event(ObjectAction::Created, "0055AED6DCD90281E5"));
+        let redelivered = sequencers.observe(&event(ObjectAction::Created, "0055AED6DCD90281E5"));
+        let delayed_removal = sequencers.observe(&event(ObjectAction::Removed, "55AED6DCD9028100"));
+        let other_artifact = sequencers.observe(&other);
+        let later_removal = sequencers.observe(&event(ObjectAction::Removed, "0055AED6DCD9028200"));
+        let unordered = sequencers.observe(&event(ObjectAction::Created, ""));
+
+        // Assert
+        assert!(created);
+        assert!(redelivered);
+        assert!(!delayed_removal);
+        assert!(other_artifact);
+        assert!(later_removal);
+        assert!(unordered);
+    }
+
+    #[tokio::test]
+    async fn successfully_apply_object_events_to_the_manager() {
+        // Arrange
+        let manager = manager(DeploymentGroups::default()).await;
+        let model_name = "catboost-titanic_model";
+
+        // Act
+        let targets = targets(&manager, "models", &event(ObjectAction::Created, ""));
+        let other_bucket = ObjectEvent {
+            bucket_name: "other".to_string(),
+            ..event(ObjectAction::Created, "")
+        };
+        let other_targets = targets(&manager, "models", &other_bucket);
+        let updated = apply(&manager, ObjectAction::Created, model_name).await;
+        let removed = apply(&manager, ObjectAction::Removed, model_name).await;
+        let removed_again = apply(&manager, ObjectAction::Removed, model_name).await;
+        let is_removed = manager
+            .get_model_metadata("titanic_model".to_string())
+            .is_none();
+        let added = apply(&manager, ObjectAction::Created, model_name).await;
+
+        // Assert
+        assert_eq!(targets, vec![model_name.to_string()]);
+        assert!(other_targets.is_empty());
+        assert_eq!(updated.unwrap(), Applied::Done);
+        assert_eq!(removed.unwrap(), Applied::Done);
+        assert_eq!(removed_again.unwrap(), Applied::Done);
+        assert!(is_removed);
+        assert_eq!(added.unwrap(), Applied::Done);
+        assert!(manager
+            .get_model_metadata("titanic_model".to_string())
+            .is_some());
+        assert_eq!(
+            loaded_name("archive.catboost-titanic_model"),
+            "archive.titanic_model"
+        );
+    }
+
+    #[tokio::test]
+    async fn successfully_defer_object_events_of_deployment_groups_to_the_poll() {
+        // Arrange
+        let deployment_groups = DeploymentGroups::new(vec![DeploymentGroup {
+            name: "titanic".to_string(),
+            models: vec!["titanic_model".to_string()],
+        }])
+        .unwrap();
+        let manager = manager(deployment_groups).await;
+
+        // Act
+        let removed = apply(&manager, ObjectAction::Removed, "catboost-titanic_model").await;
+        let updated = manager.update_model("titanic_model".to_string()).await;
+
+        // Assert
+        assert_eq!(removed.unwrap(), Applied::Deferred);
+        assert!(manager
+            .get_model_metadata("titanic_model".to_string())
+            .is_some());
+        assert!(updated.is_err());
+    }
+}
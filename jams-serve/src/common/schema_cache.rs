@@ -0,0 +1,190 @@
+use crate::common::metrics::SCHEMA_CACHE_LOOKUPS_TOTAL;
+use jams_core::model::input::{schema_hash, ModelInputBuffer};
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+
+/// Header (HTTP) or metadata key (gRPC) used by clients to identify themselves.
+pub const CLIENT_ID_HEADER: &str = "x-client-id";
+
+/// Client ID used for requests which do not identify the client.
+pub const ANONYMOUS_CLIENT_ID: &str = "anonymous";
+
+/// Maximum length of a client ID. Longer client IDs are treated as anonymous.
+const MAX_CLIENT_ID_LENGTH: usize = 128;
+
+thread_local! {
+    /// The buffer into which the inputs are parsed for validation. The parsed inputs are only checked and
+    /// thrown away, so every worker thread of the runtime reuses the same feature vectors for all of them.
+    static VALIDATION_BUFFER: RefCell<ModelInputBuffer> = RefCell::new(ModelInputBuffer::new());
+}
+
+/// Validates model inputs before they are admitted to the worker pool, remembering the input schemas
+/// which were already validated for each client.
+///
+/// Full validation parses every value of the input. Clients usually send requests of the same shape,
+/// so once an input with a given schema passed validation, later inputs from the same client with the
+/// same schema only have their schema hashed. The worker still parses the whole input when predicting,
+/// so an invalid value in a cached schema is reported by the prediction instead.
+///
+/// The cache holds at most `capacity` entries. The oldest entry is evicted when it is full.
+pub struct SchemaCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    validated: HashSet<(String, u64)>,
+    order: VecDeque<(String, u64)>,
+}
+
+impl SchemaCache {
+    /// Creates a new cache which holds at most `capacity` validated schemas.
+    pub fn new(capacity: usize) -> Self {
+        SchemaCache {
+            capacity,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Validates a model input sent by a client.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_id` - The ID of the client which sent the input.
+    /// * `input` - The model input as a JSON string.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the input is valid or has the same schema as an input the client sent before.
+    /// * `Err(anyhow::Error)` - If the input is invalid.
+    pub fn validate(&self, client_id: &str, input: &str) -> anyhow::Result<()> {
+        let key = (client_id.to_string(), schema_hash(input)?);
+        if self.contains(&key) {
+            SCHEMA_CACHE_LOOKUPS_TOTAL.with_label_values(&["hit"]).inc();
+            return Ok(());
+        }
+        SCHEMA_CACHE_LOOKUPS_TOTAL
+            .with_label_values(&["miss"])
+            .inc();
+
+        VALIDATION_BUFFER.with(|buffer| buffer.borrow_mut().parse(input).map(|_| ()))?;
+        self.insert(key);
+        Ok(())
+    }
+
+    fn contains(&self, key: &(String, u64)) -> bool {
+        match self.entries.lock() {
+            Ok(entries) => entries.validated.contains(key),
+            Err(_) => false,
+        }
+    }
+
+    fn insert(&self, key: (String, u64)) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = match self.entries.lock() {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        if !entries.validated.insert(key.clone()) {
+            return;
+        }
+        entries.order.push_back(key);
+        while entries.order.len() > self.capacity {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.validated.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Returns the client ID sent by the client, or the anonymous client ID if it is missing or invalid.
+pub fn client_id(value: Option<&str>) -> &str {
+    match value {
+        Some(client_id) if !client_id.is_empty() && client_id.len() <= MAX_CLIENT_ID_LENGTH => {
+            client_id
+        }
+        _ => ANONYMOUS_CLIENT_ID,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_skips_validation_for_known_schema() {
+        // Arrange
+        let cache = SchemaCache::new(10);
+        let hits = SCHEMA_CACHE_LOOKUPS_TOTAL.with_label_values(&["hit"]).get();
+
+        // Act
+        let first = cache.validate("client_a", r#"{"age": [22.0], "sex": ["male"]}"#);
+        let second = cache.validate(
+            "client_a",
+            r#"{"age": [38.0, 26.0], "sex": ["female", "female"]}"#,
+        );
+
+        // Assert
+        assert!(first.is_ok());
+        assert!(second.is_ok());
+        assert!(SCHEMA_CACHE_LOOKUPS_TOTAL.with_label_values(&["hit"]).get() > hits);
+        assert!(cache.contains(&(
+            "client_a".to_string(),
+            schema_hash(r#"{"age": [1.0], "sex": ["a"]}"#).unwrap()
+        )));
+        assert!(!cache.contains(&(
+            "client_b".to_string(),
+            schema_hash(r#"{"age": [1.0], "sex": ["a"]}"#).unwrap()
+        )));
+    }
+
+    #[test]
+    fn fails_to_validate_invalid_input_and_does_not_cache_it() {
+        // Arrange
+        let cache = SchemaCache::new(10);
+        let input = r#"{"age": [22.0, "unknown"]}"#;
+
+        // Act
+        let result = cache.validate("client_a", input);
+
+        // Assert
+        assert!(result.is_err());
+        assert!(!cache.contains(&("client_a".to_string(), schema_hash(input).unwrap())));
+    }
+
+    #[test]
+    fn successfully_evicts_oldest_schema_when_full() {
+        // Arrange
+        let cache = SchemaCache::new(1);
+
+        // Act
+        cache.validate("client_a", r#"{"age": [22.0]}"#).unwrap();
+        cache.validate("client_a", r#"{"fare": [7.25]}"#).unwrap();
+
+        // Assert
+        assert!(!cache.contains(&(
+            "client_a".to_string(),
+            schema_hash(r#"{"age": [22.0]}"#).unwrap()
+        )));
+        assert!(cache.contains(&(
+            "client_a".to_string(),
+            schema_hash(r#"{"fare": [7.25]}"#).unwrap()
+        )));
+    }
+
+    #[test]
+    fn successfully_falls_back_to_anonymous_client_id() {
+        // Act + Assert
+        assert_eq!(client_id(Some("client_a")), "client_a");
+        assert_eq!(client_id(Some("")), ANONYMOUS_CLIENT_ID);
+        assert_eq!(client_id(None), ANONYMOUS_CLIENT_ID);
+        assert_eq!(
+            client_id(Some("a".repeat(129).as_str())),
+            ANONYMOUS_CLIENT_ID
+        );
+    }
+}
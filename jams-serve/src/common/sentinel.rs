@@ -0,0 +1,202 @@
+use jams_core::model::frameworks::{
+    ModelFramework, CATBOOST, LIGHTGBM, PYTORCH, TENSORFLOW, TORCH, XGBOOST,
+};
+use jams_core::model::input::ModelInput;
+use jams_core::model::output::{compare_outputs, ModelOutput};
+use jams_core::model_store::common::{unpack_tarball, PROVENANCE_DIRECTORY_NAME};
+use jams_core::model_store::storage::load_predictor;
+use jams_core::pool::scheduler::{Priority, Scheduler, Task};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// A tiny model of a built-in framework which is bundled with the server, along with an input and the outputs the
+/// model is expected to return for it. It checks the native library of the framework on the host independently of
+/// the models which are served, so that ABI or glibc mismatches are caught even if the served models do not ship
+/// a sample input, see `run_smoke_test`.
+pub struct Sentinel {
+    /// The framework of the model.
+    pub framework: ModelFramework,
+    /// The model as a `.tar.gz` tarball holding a single artifact named `<framework>-sentinel`.
+    tarball: &'static [u8],
+    /// The input and the expected outputs of the model as JSON, see `SentinelFixture`.
+    fixture: &'static str,
+}
+
+/// The sentinel models bundled with the server, one per built-in framework.
+pub static SENTINELS: [Sentinel; 6] = [
+    Sentinel {
+        framework: CATBOOST,
+        tarball: include_bytes!("../../sentinels/catboost.tar.gz"),
+        fixture: include_str!("../../sentinels/catboost.json"),
+    },
+    Sentinel {
+        framework: LIGHTGBM,
+        tarball: include_bytes!("../../sentinels/lightgbm.tar.gz"),
+        fixture: include_str!("../../sentinels/lightgbm.json"),
+    },
+    Sentinel {
+        framework: PYTORCH,
+        tarball: include_bytes!("../../sentinels/pytorch.tar.gz"),
+        fixture: include_str!("../../sentinels/pytorch.json"),
+    },
+    Sentinel {
+        framework: TENSORFLOW,
+        tarball: include_bytes!("../../sentinels/tensorflow.tar.gz"),
+        fixture: include_str!("../../sentinels/tensorflow.json"),
+    },
+    Sentinel {
+        framework: TORCH,
+        tarball: include_bytes!("../../sentinels/torch.tar.gz"),
+        fixture: include_str!("../../sentinels/torch.json"),
+    },
+    Sentinel {
+        framework: XGBOOST,
+        tarball: include_bytes!("../../sentinels/xgboost.tar.gz"),
+        fixture: include_str!("../../sentinels/xgboost.json"),
+    },
+];
+
+/// The input of a sentinel model and the outputs it is expected to return.
+#[derive(Deserialize)]
+struct SentinelFixture {
+    input: serde_json::Value,
+    expected: HashMap<String, Vec<Vec<f64>>>,
+    tolerance: f64,
+}
+
+impl Sentinel {
+    /// Returns the sentinel model bundled for a framework, if any.
+    pub fn of(framework: &str) -> Option<&'static Sentinel> {
+        SENTINELS
+            .iter()
+            .find(|sentinel| sentinel.framework == framework)
+    }
+
+    /// Loads the sentinel model from a temporary directory and predicts its input on the worker pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `cpu_pool` - The worker pool which makes the prediction.
+    /// * `timeout` - The time allowed for the prediction, including the time spent waiting for a worker.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(anyhow::Error)` - If the model cannot be loaded, the prediction fails, panics or times out, or the
+    ///   outputs differ from the expected outputs.
+    pub async fn check(&self, cpu_pool: &Scheduler, timeout: Duration) -> anyhow::Result<()> {
+        let fixture: SentinelFixture = serde_json::from_str(self.fixture)?;
+        let dir = tempfile::tempdir()?;
+        let tarball_path = dir
+            .path()
+            .join(format!("{}-sentinel.tar.gz", self.framework));
+        std::fs::write(&tarball_path, self.tarball)?;
+        let models_dir = dir.path().join("models");
+        unpack_tarball(
+            tarball_path.to_string_lossy().as_ref(),
+            models_dir.to_string_lossy().as_ref(),
+        )?;
+        let model_path = artifact_path(&models_dir)?;
+        let predictor = load_predictor(self.framework, model_path.as_str()).await?;
+
+        let input = ModelInput::from_str(fixture.input.to_string().as_str())?;
+        let (tx, rx) = oneshot::channel::<Result<ModelOutput, String>>();
+        // the smoke test runs before any traffic, so that it does not queue behind predictions
+        let task = Task::new(move || {
+            let output = match panic::catch_unwind(AssertUnwindSafe(|| predictor.predict(input))) {
+                Ok(Ok(output)) => Ok(output),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err("the prediction panicked".to_string()),
+            };
+            let _ = tx.send(output);
+        })
+        .with_priority(Priority::High);
+        cpu_pool.submit(task);
+
+        let output = match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(Ok(output))) => output,
+            Ok(Ok(Err(e))) => anyhow::bail!("Sentinel prediction failed ❌: {}", e),
+            Ok(Err(e)) => anyhow::bail!("Sentinel prediction failed ❌: {}", e),
+            Err(_) => anyhow::bail!("Sentinel prediction timed out after {:?} ❌", timeout),
+        };
+        if let Err(mismatches) =
+            compare_outputs(&output.predictions, &fixture.expected, fixture.tolerance)
+        {
+            anyhow::bail!(
+                "Sentinel prediction returned unexpected outputs ❌: {}",
+                mismatches
+            )
+        }
+        Ok(())
+    }
+}
+
+/// Returns the path of the artifact unpacked from the tarball of a sentinel model.
+fn artifact_path(models_dir: &Path) -> anyhow::Result<String> {
+    for entry in std::fs::read_dir(models_dir)? {
+        let entry = entry?;
+        if entry.file_name() != PROVENANCE_DIRECTORY_NAME {
+            return Ok(entry.path().to_string_lossy().to_string());
+        }
+    }
+    anyhow::bail!("Sentinel tarball has no model ❌")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jams_core::pool::executor::ExecutorKind;
+    use jams_core::pool::scheduler::Budget;
+
+    fn cpu_pool() -> Scheduler {
+        Scheduler::new(
+            "shared",
+            ExecutorKind::Rayon.build(1, "jams-test").unwrap(),
+            Budget::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn successfully_check_every_bundled_sentinel() {
+        // Arrange
+        let cpu_pool = cpu_pool();
+
+        // Act
+        let mut failures = Vec::new();
+        for sentinel in SENTINELS.iter() {
+            if let Err(e) = sentinel.check(&cpu_pool, Duration::from_secs(30)).await {
+                failures.push(format!("{}: {}", sentinel.framework, e));
+            }
+        }
+
+        // Assert
+        assert!(failures.is_empty(), "{:?}", failures);
+        assert!(Sentinel::of("lightgbm").is_some());
+        assert!(Sentinel::of("my_framework").is_none());
+    }
+
+    #[tokio::test]
+    async fn fails_to_check_sentinel_when_outputs_are_unexpected() {
+        // Arrange
+        let lightgbm = Sentinel::of(LIGHTGBM).unwrap();
+        let mut fixture: serde_json::Value = serde_json::from_str(lightgbm.fixture).unwrap();
+        fixture["expected"]["predictions"][0][0] = serde_json::json!(42.0);
+        let sentinel = Sentinel {
+            framework: LIGHTGBM,
+            tarball: lightgbm.tarball,
+            fixture: fixture.to_string().leak(),
+        };
+
+        // Act
+        let result = sentinel.check(&cpu_pool(), Duration::from_secs(30)).await;
+
+        // Assert
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("unexpected outputs"));
+    }
+}
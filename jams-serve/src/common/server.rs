@@ -1,4 +1,23 @@
+use crate::common::authz::AuthorizationConfig;
+use crate::common::dedup::DedupConfig;
+use crate::common::feature_store::FeatureStoreConfig;
+use crate::common::hardening::HardeningConfig;
+use crate::common::health::SmokeTestConfig;
+use crate::common::namespace::NamespaceConfig;
+use crate::common::passthrough::PassthroughConfig;
+use crate::common::result_store::ResultStoreConfig;
+use crate::common::s3_events::S3EventsConfig;
+use crate::common::signing::ResponseSigningConfig;
+use jams_core::chaos::ChaosConfig;
+use jams_core::model::settings::ModelSettingsConfig;
+use jams_core::model::variant::ModelPrecision;
+use jams_core::model_store::backend_disabled;
+use jams_core::model_store::delta::DeltaConfig;
+use jams_core::model_store::encryption::EncryptionConfig;
+use jams_core::model_store::groups::DeploymentGroup;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fs;
 
 // Terminal art
@@ -25,12 +44,17 @@ pub const AZURE: ModelStore = "azure";
 pub const AWS: ModelStore = "aws";
 pub const MINIO: ModelStore = "minio";
 
+/// Network interface the server binds to if `host` is not set.
+pub const DEFAULT_HOST: &str = "0.0.0.0";
+/// Network interface the admin endpoints bind to if `admin_port` is set but `admin_host` is not.
+pub const DEFAULT_ADMIN_HOST: &str = "127.0.0.1";
+
 /// Configuration for the J.A.M.S.
 ///
 /// This common struct holds various configuration options for the HTTP/gRPC server, including the model directory,
 /// port number, log level, and the number of worker threads for CPU-intensive tasks.
 /// Check https://github.com/gagansingh894/jams-rs/tree/main/build/run_config for examples
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Clone, Default)]
 pub struct Config {
     /// Protocol to use for serving = `http` ot `grpc`
     pub protocol: String,
@@ -41,6 +65,39 @@ pub struct Config {
     /// If not provided, the default port number is 3000 for HTTP and 4000 for gRPC
     pub port: Option<u16>,
 
+    /// An optional network interface the server binds to, e.g. `10.0.0.5`. Defaults to `0.0.0.0`.
+    pub host: Option<String>,
+
+    /// An optional port for the admin endpoints, i.e. model management, `/metrics` and the health checks.
+    ///
+    /// - `Some(u16)`: The admin endpoints are served over HTTP on this port and no longer on the public port,
+    ///   which only serves predictions and health checks. With the gRPC protocol the model management RPCs
+    ///   remain available on the gRPC port.
+    /// - `None`: The admin endpoints are served on the public port.
+    pub admin_port: Option<u16>,
+
+    /// An optional network interface the admin endpoints bind to. Only used if `admin_port` is set.
+    /// Defaults to `127.0.0.1`, so that the admin endpoints are only reachable from localhost.
+    pub admin_host: Option<String>,
+
+    /// An optional number of acceptors for the public port, to handle bursts of new connections.
+    ///
+    /// - `Some(usize)`: The public port is bound this many times with `SO_REUSEPORT` and every listener is
+    ///   accepted by its own task, so the kernel spreads new connections over several accept queues. Unix only.
+    /// - `None`: A single listener accepts every connection.
+    pub acceptors: Option<usize>,
+
+    /// An optional maximum number of pending connections in the accept queue of each listener of the public port.
+    /// Defaults to 1024. The kernel may cap it, e.g. at `net.core.somaxconn` on Linux.
+    pub accept_backlog: Option<u32>,
+
+    /// An optional idle time in seconds before TCP keepalive probes are sent on the connections of the public port.
+    ///
+    /// - `Some(u64)`: Keepalive is enabled, so that dead client connections are detected and closed. Must be at
+    ///   least 1.
+    /// - `None`: Keepalive is disabled.
+    pub tcp_keepalive: Option<u64>,
+
     /// Model store to use.
     /// The valid options are
     /// - `local` - filesystem.Must pass model dir
@@ -70,14 +127,374 @@ pub struct Config {
     /// Number of threads to be used in the CPU thread pool.
     ///
     /// This thread pool is different from the I/O thread pool and is used for computing CPU-intensive tasks.
-    /// This is an optional field. If not provided, the default number of worker threads is 2.
+    /// This is an optional field. If not provided, the CPUs which are not used by the tokio runtime are used.
     pub num_workers: Option<usize>,
 
+    /// An optional number of CPUs the server sizes its threads for. Runtime sizing can only be configured using
+    /// the config file.
+    ///
+    /// - `Some(f64)`: The number of CPUs, rounded up, e.g. to size for fewer CPUs than the container limit.
+    /// - `None`: The physical cores of the host, capped by the CPUs the process may run on and the CPU quota of
+    ///   its cgroup, e.g. the CPU limit of its container.
+    pub cpu_limit: Option<f64>,
+
+    /// An optional number of worker threads of the tokio runtime. Runtime sizing can only be configured using the
+    /// config file.
+    ///
+    /// - `Some(usize)`: The number of worker threads.
+    /// - `None`: Half of the CPUs, at least one.
+    pub runtime_threads: Option<usize>,
+
     /// An optional value representing the interval (in seconds) for polling the model store.
     ///
     /// - `Some(u64)`: The polling interval in seconds.
     /// - `None`: No polling interval is specified, which will disable periodic checks for model updates.
     pub poll_interval: Option<u64>,
+
+    /// An optional map from model store (`local`, `aws`, `minio` or `azure`) to its polling interval (in seconds).
+    ///
+    /// - `Some(BTreeMap<String, u64>)`: The interval of the configured model store overrides `poll_interval`.
+    /// - `None`: The model store is polled every `poll_interval`.
+    pub poll_intervals: Option<BTreeMap<String, u64>>,
+
+    /// An optional upper bound (in seconds) of the random delay added to each polling interval.
+    ///
+    /// - `Some(u64)`: Each poll waits for the interval plus a random delay of up to this many seconds, so that
+    ///   replicas do not poll the model store at the same time.
+    /// - `None`: The model store is polled at exactly the polling interval.
+    pub poll_jitter: Option<u64>,
+
+    /// An optional limit on the number of models downloaded at the same time while polling.
+    ///
+    /// - `Some(usize)`: At most this many updated models are downloaded concurrently.
+    /// - `None`: Updated models are downloaded one at a time.
+    pub max_concurrent_downloads: Option<usize>,
+
+    /// An optional deadline (in seconds) for listing the artifacts of an S3, MinIO or Azure model store while polling.
+    ///
+    /// - `Some(u64)`: A listing which takes longer fails the poll, which is retried at the next interval. Zero disables the deadline.
+    /// - `None`: The listing is abandoned after 60 seconds.
+    pub store_list_timeout: Option<u64>,
+
+    /// An optional deadline (in seconds) for downloading a single artifact from an S3, MinIO or Azure model store while
+    /// adding, updating or polling models.
+    ///
+    /// - `Some(u64)`: A download which takes longer fails, and the poller continues with the remaining artifacts. Zero
+    ///   disables the deadline.
+    /// - `None`: The download is abandoned after 600 seconds.
+    pub store_download_timeout: Option<u64>,
+
+    /// An optional retention (in seconds) for the model store directories orphaned by crashed processes.
+    ///
+    /// - `Some(u64)`: Model store directories in the same parent directory whose owner lock is no longer held by any
+    ///   process are removed at startup. Directories without an owner lock, left by older releases, are removed once
+    ///   they were not modified for longer than the retention.
+    /// - `None`: Orphaned directories are not removed.
+    pub temp_dir_retention: Option<u64>,
+
+    /// An optional interval (in seconds) for removing orphaned model store directories after startup.
+    ///
+    /// - `Some(u64)`: Orphaned directories are removed periodically. Requires `temp_dir_retention`.
+    /// - `None`: Orphaned directories are only removed at startup.
+    pub temp_dir_gc_interval: Option<u64>,
+
+    /// An optional memory budget (in MiB) for the server.
+    ///
+    /// - `Some(u64)`: Models which would push the resident memory of the server over the budget are refused.
+    ///   `0` disables the budget.
+    /// - `None`: The memory limit of the cgroup of the server is enforced, e.g. the memory limit of its container,
+    ///   so that a model which does not fit is refused instead of the server being OOM killed. No budget is
+    ///   enforced without a memory limit.
+    pub memory_budget_mb: Option<u64>,
+
+    /// An optional resident memory watermark (in MiB) above which prediction requests are shed.
+    ///
+    /// - `Some(u64)`: Prediction requests are rejected with 503 (HTTP) or UNAVAILABLE (gRPC) while the resident
+    ///   memory of the server is above the watermark.
+    /// - `None`: Load shedding is disabled.
+    pub memory_shed_watermark_mb: Option<u64>,
+
+    /// An optional flag to enable the deterministic mode, for auditing and debugging non-reproducible outputs.
+    ///
+    /// - `Some(true)`: The frameworks are configured to return bit-identical outputs for the same input, e.g. with
+    ///   deterministic TensorFlow kernels, single-threaded inference and fixed seeds, at the cost of throughput.
+    ///   Responses of `/api/v1/predict` carry the seed, the platform and the library versions.
+    /// - `Some(false)` or `None`: The frameworks run with their default settings.
+    pub deterministic: Option<bool>,
+
+    /// An optional seed of the random number generators of the frameworks in the deterministic mode.
+    ///
+    /// - `Some(u64)`: The seed to use.
+    /// - `None`: Defaults to 0.
+    pub deterministic_seed: Option<u64>,
+
+    /// An optional maximum number of predictions queued or running across all models.
+    ///
+    /// - `Some(usize)`: Prediction requests over the limit are rejected with 503 (HTTP) or UNAVAILABLE (gRPC).
+    /// - `None`: Every prediction request is queued.
+    pub max_in_flight: Option<usize>,
+
+    /// An optional maximum time (in milliseconds) a prediction may wait for a worker.
+    ///
+    /// - `Some(u64)`: Predictions which are not picked up by a worker in time are withdrawn from the queue and
+    ///   rejected with 503 (HTTP) or UNAVAILABLE (gRPC), so that clients can retry against another replica.
+    /// - `None`: Predictions wait for a worker for as long as it takes.
+    pub max_queue_wait_ms: Option<u64>,
+
+    /// An optional executor which runs the predictions on the shared worker pool, i.e. `rayon`, `threads` or `tokio`.
+    ///
+    /// - `Some(String)`: `rayon` runs them on a work-stealing threadpool, `threads` on dedicated OS threads, e.g. for
+    ///   native calls which must not share their threads, and `tokio` on the blocking threadpool of the runtime.
+    /// - `None`: Defaults to `rayon`.
+    pub executor: Option<String>,
+
+    /// An optional time (in milliseconds) a prediction is expected to wait for a worker.
+    ///
+    /// - `Some(u64)`: Predictions which waited longer are counted by the `jams_scheduler_tasks_total` metric. They still run.
+    /// - `None`: The wait time is not budgeted.
+    pub task_wait_budget_ms: Option<u64>,
+
+    /// An optional time (in milliseconds) a prediction is expected to run for once picked up by a worker.
+    ///
+    /// - `Some(u64)`: Predictions which ran longer are counted by the `jams_scheduler_tasks_total` metric.
+    /// - `None`: The run time is not budgeted.
+    pub task_execute_budget_ms: Option<u64>,
+
+    /// An optional path to a prediction log.
+    ///
+    /// - `Some(String)`: Every successful prediction is appended to the file as a line of JSON, which can be
+    ///   replayed against a candidate model using `jams replay`.
+    /// - `None`: Predictions are not recorded.
+    pub prediction_log_path: Option<String>,
+
+    /// An optional path to the registry state file.
+    ///
+    /// - `Some(String)`: The models loaded on shutdown are saved to the file along with their cached artifacts.
+    ///   On startup, the models are loaded from the cache instead of being fetched from the model store if
+    ///   the cached artifacts are unchanged. Changes made to the model store in the meantime are picked up by polling.
+    /// - `None`: Models are always fetched from the model store on startup.
+    pub registry_state_path: Option<String>,
+
+    /// An optional path to the usage state file.
+    ///
+    /// - `Some(String)`: The request count and last access time of each model are saved to the file periodically
+    ///   and on shutdown, and restored from it on startup.
+    /// - `None`: Usage is kept in memory only and starts over when the server restarts.
+    pub usage_state_path: Option<String>,
+
+    /// An optional value representing the interval (in seconds) for saving the usage to the usage state file.
+    ///
+    /// - `Some(u64)`: The usage is saved at the specified interval. A value of 0 only saves it on shutdown.
+    /// - `None`: Defaults to 60 seconds. Ignored without a usage state file.
+    pub usage_save_interval: Option<u64>,
+
+    /// An optional list of models which are loaded first at startup.
+    ///
+    /// - `Some(Vec<String>)`: The server starts serving as soon as the listed models are loaded, while the rest of
+    ///   the model store is loaded in the background. `/readyz` returns 503 until every listed model is loaded.
+    ///   Ignored if the models are restored from the registry state.
+    /// - `None`: The server starts serving once every model is loaded.
+    pub priority_models: Option<Vec<String>>,
+
+    /// An optional value representing the interval (in seconds) for re-verifying the cached model artifacts.
+    ///
+    /// - `Some(u64)`: The cached artifacts are re-hashed periodically. Models whose artifact is missing or
+    ///   has changed since it was loaded are reloaded from the model store and an error is logged.
+    /// - `None`: The cached artifacts are not re-verified.
+    pub integrity_check_interval: Option<u64>,
+
+    /// An optional window (in seconds) for sampling warning and error logs.
+    ///
+    /// - `Some(u64)`: Only the first few events from each log statement are logged per window. The rest are
+    ///   counted and summarised once at the end of the window.
+    /// - `None`: Log sampling is disabled.
+    pub log_sampling_window: Option<u64>,
+
+    /// An optional comma separated list of `module=limit` pairs which sets how many events from each log
+    /// statement of a module are logged per window, i.e. `jams_serve::http=5,default=20`.
+    ///
+    /// - `Some(String)`: The limits for the listed modules. `default` sets the limit for every other module.
+    /// - `None`: 10 events are logged per log statement per window.
+    pub log_sampling_limits: Option<String>,
+
+    /// An optional list of namespaces, each with its own worker pool and queue limit.
+    /// Namespaces can only be configured using the config file.
+    ///
+    /// - `Some(Vec<NamespaceConfig>)`: Models are assigned to a namespace by the prefix of their name and run on
+    ///   the worker pool of the namespace. Other models run on the shared worker pool.
+    /// - `None`: All models run on the shared worker pool.
+    pub namespaces: Option<Vec<NamespaceConfig>>,
+
+    /// An optional number of input schemas to remember per server for pre-admission validation.
+    ///
+    /// - `Some(usize)`: Inputs are validated before being admitted to the worker pool and rejected with 400 (HTTP)
+    ///   or INVALID_ARGUMENT (gRPC) if invalid. The schemas which passed validation are cached per client, identified
+    ///   by the `x-client-id` header, so later inputs of the same schema skip full validation.
+    /// - `None`: Inputs are only validated by the worker when predicting.
+    pub schema_cache_size: Option<usize>,
+
+    /// An optional sentinel model used by the deep health check.
+    ///
+    /// - `Some(String)`: `/healthz/deep` runs a canned prediction against the model using `deep_health_input`
+    ///   and reports its latency. Returns 503 if the prediction fails.
+    /// - `None`: The deep health check is disabled and `/healthz/deep` returns 404.
+    pub deep_health_model: Option<String>,
+
+    /// The canned model input used by the deep health check, as a JSON string. Required if `deep_health_model` is set.
+    pub deep_health_input: Option<String>,
+
+    /// An optional flag to suggest the closest loaded model names when a prediction request names a model which is
+    /// not loaded, e.g. because of a typo or a stale model name.
+    ///
+    /// - `Some(true)`: The 404 (HTTP) or NOT_FOUND (gRPC) errors end with `did you mean` and at most three model
+    ///   names.
+    /// - `Some(false)` or `None`: The errors only name the missing model. Misses are tracked either way.
+    pub model_suggestions: Option<bool>,
+
+    /// An optional feature store used to enrich requests with features looked up by entity ID.
+    /// The feature store can only be configured using the config file.
+    ///
+    /// - `Some(FeatureStoreConfig)`: Requests of the configured models which carry the entity key are enriched
+    ///   with the features stored in Redis before predicting.
+    /// - `None`: Requests must carry all the features of the model.
+    pub feature_store: Option<FeatureStoreConfig>,
+
+    /// An optional S3, MinIO or Azure location to which predictions above a size threshold are written instead of
+    /// being returned in the response of `/api/v1/predict` and the gRPC `Predict` RPC. The result store can only be
+    /// configured using the config file.
+    ///
+    /// - `Some(ResultStoreConfig)`: Clients get a signed URL from which they can download big predictions later.
+    /// - `None`: Predictions are always returned in the response.
+    pub result_store: Option<ResultStoreConfig>,
+
+    /// An optional deduplication of prediction requests, which detects the requests with the same model, input and
+    /// prediction options within a short window of each model. Deduplication can only be configured using the
+    /// config file.
+    ///
+    /// - `Some(DedupConfig)`: Duplicates are counted by the `jams_dedup_requests_total` metric and, in the squash
+    ///   mode, served the prediction of the first request.
+    /// - `None`: Every request is predicted.
+    pub deduplication: Option<DedupConfig>,
+
+    /// An optional developer mode which injects artificial latency and random failures into predictions and
+    /// model downloads, to test timeouts, retries and circuit breakers end-to-end. Must not be used in production.
+    /// The chaos mode can only be configured using the config file.
+    ///
+    /// - `Some(ChaosConfig)`: The configured faults are injected into every prediction and model download.
+    /// - `None`: No faults are injected.
+    pub chaos: Option<ChaosConfig>,
+
+    /// An optional hardened mode which applies a seccomp filter and resource limits to the server process, to
+    /// reduce the blast radius of model artifacts which execute malicious native code. Linux only.
+    /// The hardened mode can only be configured using the config file.
+    ///
+    /// - `Some(HardeningConfig)`: The filter and limits are applied before any model is loaded.
+    /// - `None`: The server runs without restrictions.
+    pub hardening: Option<HardeningConfig>,
+
+    /// An optional list of groups of models which are deployed together, e.g. a feature encoder and the scorer
+    /// consuming its outputs. Deployment groups can only be configured using the config file.
+    ///
+    /// - `Some(Vec<DeploymentGroup>)`: When the model store is polled, the updated models of a group are swapped
+    ///   in together once all of them are loaded, or the whole group keeps its current versions.
+    /// - `None`: Every model is updated on its own.
+    pub deployment_groups: Option<Vec<DeploymentGroup>>,
+
+    /// An optional authorization of the predict and admin calls, which evaluates the principal, model, namespace
+    /// and action of every call against Cedar policies or an OPA rule. Authorization can only be configured using
+    /// the config file.
+    ///
+    /// - `Some(AuthorizationConfig)`: Calls without a valid bearer token fail with 401 (HTTP) or `UNAUTHENTICATED`
+    ///   (gRPC), and calls which are denied fail with 403 (HTTP) or `PERMISSION_DENIED` (gRPC).
+    /// - `None`: Every call is allowed.
+    pub authorization: Option<AuthorizationConfig>,
+
+    /// An optional encryption of the local artifact cache, i.e. the directory into which the models are downloaded
+    /// and unpacked. Encryption can only be configured using the config file.
+    ///
+    /// - `Some(EncryptionConfig)`: The cached tarballs and artifacts are encrypted with AES-256-GCM and only
+    ///   decrypted into a scratch directory while a model is loaded.
+    /// - `None`: The artifacts are cached in plaintext.
+    pub artifact_encryption: Option<EncryptionConfig>,
+
+    /// An optional delta download of the updates of large models from the `aws` and `minio` model stores, which only
+    /// downloads the blocks of a tarball which changed since the cached previous version. Delta updates can only be
+    /// configured using the config file.
+    ///
+    /// - `Some(DeltaConfig)`: The last downloaded tarball of every model is cached, and tarballs published with a
+    ///   block manifest are updated as deltas, falling back to a full download.
+    /// - `None`: Tarballs are always downloaded in full.
+    pub delta_updates: Option<DeltaConfig>,
+
+    /// An optional list of request headers, e.g. the client ID or experiment flags, which are captured into the
+    /// prediction log and passed to the postprocessors of the predictions. The passthrough can only be configured
+    /// using the config file.
+    ///
+    /// - `Some(PassthroughConfig)`: The configured HTTP headers or gRPC metadata of every prediction request are
+    ///   captured.
+    /// - `None`: No header is captured.
+    pub passthrough: Option<PassthroughConfig>,
+
+    /// An optional smoke test run at startup, which makes one prediction per framework of the loaded models to catch
+    /// native libraries which are broken on the host before traffic arrives. The smoke test can only be configured
+    /// using the config file.
+    ///
+    /// - `Some(SmokeTestConfig)`: The server fails to start, or reports the failed frameworks in `/readyz`, if a
+    ///   prediction fails.
+    /// - `None`: No smoke test is run.
+    pub smoke_test: Option<SmokeTestConfig>,
+
+    /// An optional lightweight variant per model, e.g. `int8`, which serves the requests with a `latency_budget_ms`
+    /// while the recent p95 latency of the model exceeds their budget. The fallbacks can only be configured using
+    /// the config file.
+    ///
+    /// - `Some(BTreeMap<String, ModelPrecision>)`: The precision of the lightweight variant of each model.
+    /// - `None`: Latency budgets are ignored.
+    pub latency_fallbacks: Option<BTreeMap<String, ModelPrecision>>,
+
+    /// An optional weight per model on the worker pool it shares with other models. Under load, each model gets a
+    /// share of the workers in proportion to its weight, so that models with many requests cannot starve models with
+    /// few of them. The weights can only be configured using the config file.
+    ///
+    /// - `Some(BTreeMap<String, f64>)`: The weight of each model, which takes precedence over its `scheduling_weight`
+    ///   label.
+    /// - `None`: Models are weighted by their `scheduling_weight` label, else equally.
+    pub scheduling_weights: Option<BTreeMap<String, f64>>,
+
+    /// An optional SQS queue receiving the S3 event notifications of the model store bucket, which add, update and
+    /// delete the changed models as soon as they are delivered. The S3 events can only be configured using the
+    /// config file and are only supported by the `aws` model store.
+    ///
+    /// - `Some(S3EventsConfig)`: The changed models are applied in near real time, in addition to polling.
+    /// - `None`: Models are only updated by polling.
+    pub s3_events: Option<S3EventsConfig>,
+
+    /// An optional map from model name to its settings, e.g. the endpoint of a remote tokenizer or the decryption key
+    /// of an embedded vocabulary, which are passed to the init hook of its predictor. A setting is either a value, a
+    /// `{ file = "..." }` secret file or an `{ env = "..." }` environment variable, which are read whenever the model is
+    /// loaded. The model settings can only be configured using the config file.
+    ///
+    /// - `Some(ModelSettingsConfig)`: The settings of each model.
+    /// - `None`: The predictors get no settings.
+    pub model_settings: Option<ModelSettingsConfig>,
+
+    /// An optional signing of the prediction responses with an HMAC, so that consumers across trust boundaries can
+    /// verify that a response was not tampered with by intermediaries. The signing can only be configured using the
+    /// config file.
+    ///
+    /// - `Some(ResponseSigningConfig)`: The responses of the predict endpoints and the unary gRPC `Predict` RPC carry
+    ///   the signature of their body in the `x-jams-signature` header or metadata key.
+    /// - `None`: Responses are not signed.
+    pub response_signing: Option<ResponseSigningConfig>,
+
+    /// The SHA-256 digest of the config file, or of the flags if the server is started without a config file, which
+    /// is reported by the startup report to tell which config a server was started with. It is computed when the
+    /// config file or the flags are parsed, hence it is not part of the config file.
+    ///
+    /// - `Some(String)`: The hex encoded digest of the config file or of the flags.
+    /// - `None`: The config is built in code, e.g. by the tests.
+    #[serde(skip)]
+    pub digest: Option<String>,
 }
 
 /// Used for parsing the config TOML files
@@ -95,19 +512,29 @@ impl Config {
             }
         };
 
-        let config = match toml::from_str::<Data>(contents.as_str()) {
+        let mut config = match toml::from_str::<Data>(contents.as_str()) {
             Ok(config) => config.config,
             Err(e) => {
                 anyhow::bail!("Failed to parse config file ❌: {}", e.to_string())
             }
         };
+        config.digest = Some(digest(contents.as_str())?);
+        config.validate()?;
 
-        let protocol = config.clone().protocol;
+        Ok(config)
+    }
+
+    /// Validates the configuration, whether it was read from a config file or from the flags.
+    ///
+    /// # Errors
+    /// Returns an error if the protocol or the model store is not supported, or a setting is out of range.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let protocol = self.protocol.clone();
         if (protocol != HTTP) && (protocol != GRPC) {
             anyhow::bail!("Only following protocols are supported: {}, {}", HTTP, GRPC)
         }
 
-        let model_store = config.clone().model_store;
+        let model_store = self.model_store.clone();
         if (model_store != LOCAL)
             && (model_store != AZURE)
             && (model_store != AWS)
@@ -121,7 +548,101 @@ impl Config {
                 MINIO
             )
         }
+        check_model_store_enabled(model_store.as_str())?;
 
-        Ok(config)
+        // the kernel rejects a keepalive idle time of 0 when the listener is bound
+        if self.tcp_keepalive == Some(0) {
+            anyhow::bail!(
+                "tcp_keepalive must be at least 1 second, leave it unset to disable keepalive ❌"
+            )
+        }
+
+        if let Some(chaos) = &self.chaos {
+            chaos.validate()?;
+        }
+
+        if let Some(hardening) = &self.hardening {
+            hardening.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the hex encoded SHA-256 digest of a config file.
+///
+/// The digest is computed over the parsed config file written back with sorted keys, so that formatting, comments
+/// and the order of the keys do not change the digest of a config.
+fn digest(contents: &str) -> anyhow::Result<String> {
+    let canonical = toml::to_string(&toml::from_str::<toml::Table>(contents)?)?;
+    Ok(format!("{:x}", Sha256::digest(canonical.as_bytes())))
+}
+
+/// Returns the hex encoded SHA-256 digest of the flags a server is started with.
+///
+/// The digest is computed over the parsed values of the flags, so that the order in which they are passed and
+/// whether they are passed as flags or environment variables do not change the digest of a config.
+pub fn flags_digest(flags: &impl std::fmt::Debug) -> String {
+    format!("{:x}", Sha256::digest(format!("{:?}", flags).as_bytes()))
+}
+
+/// Checks that the backend of a model store was compiled into this build. The `aws` and `minio` model stores
+/// need the `aws` feature and the `azure` model store needs the `azure` feature, both of which are enabled by
+/// default.
+///
+/// # Errors
+///
+/// Returns an error if the model store was left out of this build.
+pub fn check_model_store_enabled(model_store: &str) -> anyhow::Result<()> {
+    let (enabled, feature) = match model_store {
+        AWS | MINIO => (cfg!(feature = "aws"), "aws"),
+        AZURE => (cfg!(feature = "azure"), "azure"),
+        _ => return Ok(()),
+    };
+    if !enabled {
+        return Err(backend_disabled(
+            format!("The {} model store", model_store).as_str(),
+            feature,
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_digest_config_regardless_of_formatting() {
+        // Arrange
+        let config = "[config]\nprotocol = \"http\"\nmodel_store = \"local\"\n";
+        let reformatted =
+            "# local server\n[config]\nmodel_store = 'local'\n\nprotocol   = \"http\"\n";
+        let changed = "[config]\nprotocol = \"grpc\"\nmodel_store = \"local\"\n";
+
+        // Act
+        let digest_of_config = digest(config).unwrap();
+
+        // Assert
+        assert_eq!(digest_of_config.len(), 64);
+        assert_eq!(digest_of_config, digest(reformatted).unwrap());
+        assert_ne!(digest_of_config, digest(changed).unwrap());
+    }
+
+    #[test]
+    fn fails_to_validate_config_with_zero_tcp_keepalive() {
+        // Arrange
+        let contents =
+            "[config]\nprotocol = \"http\"\nmodel_store = \"local\"\ntcp_keepalive = 0\n";
+        let mut config = toml::from_str::<Data>(contents).unwrap().config;
+
+        // Act
+        let zero = config.validate();
+        config.tcp_keepalive = Some(60);
+        let enabled = config.validate();
+
+        // Assert
+        assert!(zero.unwrap_err().to_string().contains("tcp_keepalive"));
+        assert!(enabled.is_ok());
     }
 }
@@ -0,0 +1,357 @@
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the HTTP header and gRPC metadata key which carries the signature of a prediction response.
+pub const SIGNATURE_HEADER: &str = "x-jams-signature";
+
+/// Name of the HTTP header and gRPC metadata key which carries the ID of the key a response was signed with.
+pub const SIGNATURE_KEY_ID_HEADER: &str = "x-jams-signature-key-id";
+
+/// Name of the HTTP header and gRPC metadata key which carries when a response was signed, in seconds since the
+/// Unix epoch.
+pub const SIGNATURE_ISSUED_AT_HEADER: &str = "x-jams-signature-issued-at";
+
+/// Prefix of the signatures, naming the HMAC algorithm.
+pub const SIGNATURE_PREFIX: &str = "sha256=";
+
+/// First line of the canonical form of a signed response, naming its version.
+pub const SIGNATURE_VERSION: &str = "jams-signature-v1";
+
+/// Environment variable holding the signing key if `key_env` is not set.
+const DEFAULT_KEY_ENV: &str = "JAMS_SIGNING_KEY";
+
+/// Minimum length of the signing key and of the key of the bearer tokens, in bytes.
+const MIN_KEY_LENGTH: usize = 32;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Configuration of the signing of prediction responses, so that consumers downstream of proxies and other trust
+/// boundaries can verify that a response was not tampered with, nor replayed for another request. The responses
+/// carry an HMAC-SHA256 of their canonical form, see `canonical_form`, which binds their body to the request ID and
+/// to the time the response was signed.
+///
+/// The key is read from an environment variable or a file, e.g. a secret mounted into the container, and shared with
+/// the consumers out of band. It must be at least 64 hex characters, e.g. the output of `openssl rand -hex 32`.
+///
+/// # Example
+/// ```toml
+/// [config.response_signing]
+/// key_file = "/run/secrets/jams-signing-key"
+/// key_id = "2024-07"
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ResponseSigningConfig {
+    /// The environment variable holding the key. Defaults to `JAMS_SIGNING_KEY`.
+    #[serde(default = "default_key_env")]
+    pub key_env: String,
+
+    /// An optional file holding the key, which takes precedence over `key_env`.
+    pub key_file: Option<String>,
+
+    /// An optional ID of the key, returned with every signature so that consumers can pick the key to verify with
+    /// while keys are rotated.
+    pub key_id: Option<String>,
+}
+
+impl Default for ResponseSigningConfig {
+    fn default() -> Self {
+        ResponseSigningConfig {
+            key_env: default_key_env(),
+            key_file: None,
+            key_id: None,
+        }
+    }
+}
+
+fn default_key_env() -> String {
+    DEFAULT_KEY_ENV.to_string()
+}
+
+/// Signs the bodies of prediction responses with HMAC-SHA256.
+#[derive(Clone)]
+pub struct ResponseSigner {
+    key: Vec<u8>,
+    key_id: Option<String>,
+}
+
+impl ResponseSigner {
+    /// Creates a signer from the key of the config.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not set, is not hex encoded or is shorter than 32 bytes.
+    pub fn new(config: ResponseSigningConfig) -> anyhow::Result<Self> {
+        let key = read_hex_key(
+            "Signing key",
+            config.key_file.as_deref(),
+            config.key_env.as_str(),
+        )?;
+        match &config.key_id {
+            Some(key_id) => tracing::info!("Signing prediction responses with key {} 🔏", key_id),
+            None => tracing::info!("Signing prediction responses 🔏"),
+        }
+
+        Ok(ResponseSigner {
+            key,
+            key_id: config.key_id,
+        })
+    }
+
+    /// Returns the signature of a response, i.e. `sha256=` followed by the hex encoded HMAC of its canonical form.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_id` - The ID of the request the response answers, returned in the `x-request-id` header.
+    /// * `issued_at` - When the response is signed, in seconds since the Unix epoch, see `issued_at`.
+    /// * `body` - The body of the response, see `canonical_form`.
+    pub fn sign(&self, request_id: &str, issued_at: u64, body: &[u8]) -> String {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(&canonical_form(request_id, issued_at, body));
+        format!(
+            "{}{}",
+            SIGNATURE_PREFIX,
+            hex::encode(mac.finalize().into_bytes())
+        )
+    }
+
+    /// Returns the ID of the key, if it is configured.
+    pub fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+}
+
+/// Returns the current time in seconds since the Unix epoch, the issued-at time of the responses signed now.
+pub fn issued_at() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Returns the bytes which are signed for a response: `jams-signature-v1`, the issued-at time and the request ID,
+/// each followed by a newline, then the body. Neither the issued-at time nor the request ID can contain a newline,
+/// since both are header values.
+///
+/// Over HTTP the body is the exact bytes of the JSON body. Over gRPC it is `grpc_body` of the `PredictResponse`.
+///
+/// # Arguments
+///
+/// * `request_id` - The ID of the request the response answers.
+/// * `issued_at` - When the response was signed, in seconds since the Unix epoch.
+/// * `body` - The body of the response.
+pub fn canonical_form(request_id: &str, issued_at: u64, body: &[u8]) -> Vec<u8> {
+    let mut canonical =
+        format!("{}\n{}\n{}\n", SIGNATURE_VERSION, issued_at, request_id).into_bytes();
+    canonical.extend_from_slice(body);
+    canonical
+}
+
+/// Returns the signed body of a gRPC `PredictResponse`: its `result_url` and `result_expires_at`, each followed by
+/// a newline, then its `output`. Unlike the protobuf encoding of the message, it does not change when fields are
+/// added to `PredictResponse`, so older clients keep verifying the responses of newer servers.
+///
+/// # Arguments
+///
+/// * `output` - The predictions of the response, empty if they were written to the result store.
+/// * `result_url` - The signed URL of the predictions in the result store, if any.
+/// * `result_expires_at` - When `result_url` expires, if any.
+pub fn grpc_body(output: &str, result_url: &str, result_expires_at: &str) -> Vec<u8> {
+    format!("{}\n{}\n{}", result_url, result_expires_at, output).into_bytes()
+}
+
+/// Reads a hex encoded key from a file or, if no file is given, from an environment variable.
+///
+/// # Arguments
+///
+/// * `name` - The name of the key in the errors, e.g. `Signing key`.
+/// * `key_file` - An optional file holding the key, which takes precedence over `key_env`.
+/// * `key_env` - The environment variable holding the key.
+///
+/// # Errors
+///
+/// Returns an error if the key is not set, is not hex encoded or is shorter than 32 bytes.
+pub(crate) fn read_hex_key(
+    name: &str,
+    key_file: Option<&str>,
+    key_env: &str,
+) -> anyhow::Result<Vec<u8>> {
+    let encoded = match key_file {
+        Some(key_file) => fs::read_to_string(key_file).map_err(|e| {
+            tracing::error!("Failed to read {} from {} ❌: {}", name, key_file, e);
+            anyhow::anyhow!("Failed to read {} from {} ❌: {}", name, key_file, e)
+        })?,
+        None => std::env::var(key_env).map_err(|_| {
+            tracing::error!("{} {} is not set ❌", name, key_env);
+            anyhow::anyhow!("{} {} is not set ❌", name, key_env)
+        })?,
+    };
+    let key = match hex::decode(encoded.trim()) {
+        Ok(key) => key,
+        Err(e) => anyhow::bail!("{} must be hex encoded ❌: {}", name, e),
+    };
+    if key.len() < MIN_KEY_LENGTH {
+        anyhow::bail!(
+            "{} must be at least {} bytes, got {} ❌",
+            name,
+            MIN_KEY_LENGTH,
+            key.len()
+        )
+    }
+    Ok(key)
+}
+
+/// Verifies the signature of a response in constant time.
+///
+/// # Arguments
+///
+/// * `key` - The signing key, decoded from hex.
+/// * `request_id` - The ID of the request the response answers.
+/// * `issued_at` - The value of the `x-jams-signature-issued-at` header or metadata key.
+/// * `body` - The body of the response, see `canonical_form`.
+/// * `signature` - The value of the `x-jams-signature` header or metadata key.
+pub fn verify(key: &[u8], request_id: &str, issued_at: u64, body: &[u8], signature: &str) -> bool {
+    let mac = match signature
+        .strip_prefix(SIGNATURE_PREFIX)
+        .and_then(|mac| hex::decode(mac).ok())
+    {
+        Some(mac) => mac,
+        None => return false,
+    };
+    match HmacSha256::new_from_slice(key) {
+        Ok(mut expected) => {
+            expected.update(&canonical_form(request_id, issued_at, body));
+            expected.verify_slice(&mac).is_ok()
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    // the signature of the body of `successfully_sign_and_verify_response_body` for the request ID `request-1`,
+    // issued at 1720000000 with a key of 32 0xaa bytes, which the clients verify too
+    const SIGNATURE: &str =
+        "sha256=5a222242c55dbb537987b87283dff028f89eb3302a9a00dde06da36fd29c652a";
+
+    fn key_file(key: &str) -> NamedTempFile {
+        let mut key_file = NamedTempFile::new().expect("Failed to create key file ❌");
+        key_file
+            .write_all(key.as_bytes())
+            .expect("Failed to write key file ❌");
+        key_file
+    }
+
+    fn signer(key_file: &NamedTempFile) -> anyhow::Result<ResponseSigner> {
+        ResponseSigner::new(ResponseSigningConfig {
+            key_file: Some(key_file.path().to_string_lossy().to_string()),
+            key_id: Some("test".to_string()),
+            ..ResponseSigningConfig::default()
+        })
+    }
+
+    #[test]
+    fn successfully_sign_and_verify_response_body() {
+        // Arrange
+        let key_file = key_file(&"a".repeat(64));
+        let signer = signer(&key_file).expect("Failed to create response signer ❌");
+        let body = br#"{"output":"{\"predictions\":[[0.5]]}"}"#;
+
+        // Act
+        let signature = signer.sign("request-1", 1720000000, body);
+
+        // Assert
+        assert_eq!(signature, SIGNATURE);
+        assert_eq!(signer.key_id(), Some("test"));
+        assert!(verify(
+            &[0xaa; 32],
+            "request-1",
+            1720000000,
+            body,
+            &signature
+        ));
+        assert!(!verify(
+            &[0xaa; 32],
+            "request-1",
+            1720000000,
+            br#"{"output":"tampered"}"#,
+            &signature
+        ));
+        assert!(!verify(
+            &[0xaa; 32],
+            "request-2",
+            1720000000,
+            body,
+            &signature
+        ));
+        assert!(!verify(
+            &[0xaa; 32],
+            "request-1",
+            1720000001,
+            body,
+            &signature
+        ));
+        assert!(!verify(
+            &[0xbb; 32],
+            "request-1",
+            1720000000,
+            body,
+            &signature
+        ));
+        assert!(!verify(
+            &[0xaa; 32],
+            "request-1",
+            1720000000,
+            body,
+            "md5=abc"
+        ));
+    }
+
+    #[test]
+    fn successfully_build_canonical_form_of_grpc_response() {
+        // Arrange
+        let output = r#"{"predictions":[[0.5]]}"#;
+
+        // Act
+        let inline = canonical_form("request-1", 1720000000, &grpc_body(output, "", ""));
+        let stored = grpc_body("", "https://results/1", "2024-07-03T10:00:00+00:00");
+
+        // Assert
+        assert_eq!(
+            inline,
+            b"jams-signature-v1\n1720000000\nrequest-1\n\n\n{\"predictions\":[[0.5]]}".to_vec()
+        );
+        assert_eq!(
+            stored,
+            b"https://results/1\n2024-07-03T10:00:00+00:00\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn fails_to_create_response_signer_with_invalid_key() {
+        // Arrange
+        let short = key_file(&"a".repeat(32));
+        let invalid = key_file("not a key");
+
+        // Act
+        let short = signer(&short);
+        let invalid = signer(&invalid);
+        let missing = ResponseSigner::new(ResponseSigningConfig {
+            key_env: "JAMS_SIGNING_KEY_MISSING".to_string(),
+            ..ResponseSigningConfig::default()
+        });
+
+        // Assert
+        assert!(short.is_err());
+        assert!(invalid.is_err());
+        assert!(missing.is_err());
+    }
+}
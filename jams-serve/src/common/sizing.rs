@@ -0,0 +1,93 @@
+use crate::common::server::Config;
+use jams_core::cgroup::CgroupLimits;
+
+/// How the threads of the server are sized for the CPUs available to it.
+///
+/// The CPUs are split in half between the tokio runtime and the worker pool computing the predictions. In a
+/// container, the CPUs are capped by its CPU limit, so that a small limit is not oversubscribed with a thread per
+/// core of the host.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuntimeSizing {
+    /// The number of CPUs the threads are sized for.
+    pub cpus: usize,
+    /// The number of worker threads of the tokio runtime.
+    pub runtime_threads: usize,
+    /// The default number of threads of the worker pool, used unless `num_workers` is configured.
+    pub worker_threads: usize,
+}
+
+impl RuntimeSizing {
+    /// Sizes the threads for the CPUs available to the process.
+    ///
+    /// # Arguments
+    ///
+    /// * `physical_cores` - The physical cores of the host.
+    /// * `config` - The server configuration, which can override the CPUs and the runtime threads.
+    pub fn detect(physical_cores: usize, config: Option<&Config>) -> Self {
+        let cpu_limit = config.and_then(|config| config.cpu_limit);
+        let runtime_threads = config.and_then(|config| config.runtime_threads);
+        let cpus = match cpu_limit {
+            Some(cpu_limit) => (cpu_limit.ceil() as usize).max(1),
+            None => CgroupLimits::current().available_cpus(physical_cores),
+        };
+        RuntimeSizing::split(cpus, runtime_threads)
+    }
+
+    /// Splits the CPUs in half between the tokio runtime and the worker pool, giving each at least one thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `cpus` - The number of CPUs.
+    /// * `runtime_threads` - Overrides the number of worker threads of the tokio runtime.
+    pub fn split(cpus: usize, runtime_threads: Option<usize>) -> Self {
+        let cpus = cpus.max(1);
+        let runtime_threads = runtime_threads.unwrap_or(cpus / 2).max(1);
+        RuntimeSizing {
+            cpus,
+            runtime_threads,
+            worker_threads: cpus.saturating_sub(runtime_threads).max(1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successfully_split_cpus_between_runtime_and_workers() {
+        // Act
+        let host = RuntimeSizing::split(16, None);
+        let small_limit = RuntimeSizing::split(1, None);
+        let odd = RuntimeSizing::split(3, None);
+        let overridden = RuntimeSizing::split(8, Some(2));
+
+        // Assert
+        assert_eq!((host.runtime_threads, host.worker_threads), (8, 8));
+        assert_eq!(
+            (small_limit.runtime_threads, small_limit.worker_threads),
+            (1, 1)
+        );
+        assert_eq!((odd.runtime_threads, odd.worker_threads), (1, 2));
+        assert_eq!(
+            (overridden.runtime_threads, overridden.worker_threads),
+            (2, 6)
+        );
+    }
+
+    #[test]
+    fn successfully_size_for_configured_cpu_limit() {
+        // Arrange
+        let config = Config {
+            cpu_limit: Some(1.5),
+            ..Default::default()
+        };
+
+        // Act
+        let sizing = RuntimeSizing::detect(64, Some(&config));
+
+        // Assert
+        assert_eq!(sizing.cpus, 2);
+        assert_eq!((sizing.runtime_threads, sizing.worker_threads), (1, 1));
+    }
+}
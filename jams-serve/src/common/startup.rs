@@ -0,0 +1,254 @@
+use jams_core::manager::Manager;
+use jams_core::model::lifecycle::HookStatus;
+use jams_core::model::versions::{native_libraries, NativeLibrary};
+use jams_core::model_store::attached::AttachedStoreInfo;
+use jams_core::model_store::skipped::SkippedModel;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Name of the model store the server was started with, as opposed to the model stores attached at runtime.
+pub const DEFAULT_STORE_NAME: &str = "default";
+
+/// A model which was loaded by the time the server started.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct StartupModel {
+    /// The name of the model.
+    pub name: String,
+    /// The framework of the model, e.g. `tensorflow`.
+    pub framework: String,
+    /// The SHA-256 digest of the model artifact.
+    pub artifact_sha256: String,
+    /// The outcome of the init hook of the model, if it has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init: Option<HookStatus>,
+}
+
+/// The time taken by the phases of the startup.
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct StartupDurations {
+    /// Time taken to load the models of the model store, in milliseconds.
+    pub model_loading_ms: u64,
+    /// Time taken by the smoke test, in milliseconds, if it is configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smoke_test_ms: Option<u64>,
+    /// Time taken by the whole startup, in milliseconds.
+    pub total_ms: u64,
+}
+
+impl StartupDurations {
+    /// Converts a duration to whole milliseconds.
+    pub fn millis(duration: Duration) -> u64 {
+        duration.as_millis() as u64
+    }
+}
+
+/// A single summary of the startup, logged once the server is ready to serve and returned by
+/// `/api/v1/startup-report` and `/api/startup-report`, so that deploy tooling can assert on it rather than grep the
+/// logs.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct StartupReport {
+    /// The version of J.A.M.S.
+    pub version: &'static str,
+    /// The protocol the server is serving, i.e. `http` or `grpc`.
+    pub protocol: String,
+    /// When the startup began, in RFC 3339 format.
+    pub started_at: String,
+    /// The time taken by the phases of the startup.
+    pub durations: StartupDurations,
+    /// The model stores the models were loaded from, starting with the model store the server was started with.
+    pub stores: Vec<AttachedStoreInfo>,
+    /// The models which were loaded, sorted by name.
+    pub models: Vec<StartupModel>,
+    /// Whether models other than the priority models were still loading in the background.
+    pub loading_remaining_models: bool,
+    /// The models which were skipped or failed to load, with the reason why, e.g. a model which cannot run on the
+    /// host, including the models loaded in the background.
+    pub failed_models: Vec<SkippedModel>,
+    /// The SHA-256 digest of the config file, or of the flags if the server was started without a config file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_digest: Option<String>,
+    /// The native libraries of the frameworks.
+    pub libraries: &'static [NativeLibrary],
+}
+
+impl StartupReport {
+    /// Builds the startup report from the models loaded by the manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `manager` - The manager holding the loaded models.
+    /// * `protocol` - The protocol the server is serving.
+    /// * `store_url` - The URL of the model store the server was started with, e.g. `s3://my-bucket`.
+    /// * `config_digest` - The digest of the config file or of the flags the server was started with.
+    /// * `started_at` - When the startup began, in RFC 3339 format.
+    /// * `durations` - The time taken by the phases of the startup.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the loaded models could not be listed.
+    pub fn new(
+        manager: &Manager,
+        protocol: String,
+        store_url: String,
+        config_digest: Option<String>,
+        started_at: String,
+        durations: StartupDurations,
+    ) -> anyhow::Result<Self> {
+        let mut models: Vec<StartupModel> = manager
+            .get_models()?
+            .into_iter()
+            .map(|metadata| StartupModel {
+                name: metadata.name,
+                framework: metadata.framework.to_string(),
+                artifact_sha256: metadata.artifact_sha256,
+                init: metadata.init,
+            })
+            .collect();
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let attached_stores = manager.get_stores();
+        let attached_models: usize = attached_stores.iter().map(|store| store.models).sum();
+        let mut stores = vec![AttachedStoreInfo {
+            name: DEFAULT_STORE_NAME.to_string(),
+            url: store_url,
+            models: models.len().saturating_sub(attached_models),
+        }];
+        stores.extend(attached_stores);
+
+        Ok(StartupReport {
+            version: env!("CARGO_PKG_VERSION"),
+            protocol,
+            started_at,
+            durations,
+            stores,
+            models,
+            loading_remaining_models: manager.readiness().loading_remaining_models,
+            failed_models: manager.skipped_models(),
+            config_digest,
+            libraries: native_libraries(),
+        })
+    }
+
+    /// Returns the report with the models which failed to load and whether the remaining models are still loading
+    /// brought up to date, so that the failures of the models loaded in the background after the startup are
+    /// reported too.
+    ///
+    /// # Arguments
+    ///
+    /// * `manager` - The manager which loaded the models.
+    pub fn refreshed(&self, manager: &Manager) -> StartupReport {
+        StartupReport {
+            loading_remaining_models: manager.readiness().loading_remaining_models,
+            failed_models: manager.skipped_models(),
+            ..self.clone()
+        }
+    }
+
+    /// Logs the startup report as a single JSON line, with a warning if any model failed to load.
+    pub fn log(&self) {
+        let report = match serde_json::to_string(self) {
+            Ok(report) => report,
+            Err(e) => {
+                tracing::error!("Failed to serialize startup report ❌: {}", e);
+                return;
+            }
+        };
+        match self.failed_models.is_empty() {
+            true => tracing::info!("Startup report 📋: {}", report),
+            false => tracing::warn!(
+                "Startup report 📋, {} model(s) failed to load ⚠️: {}",
+                self.failed_models.len(),
+                report
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jams_core::manager::ManagerBuilder;
+    use jams_core::model_store::local::filesystem::LocalModelStore;
+    use jams_core::model_store::skipped::SkippedModels;
+    use jams_core::model_store::ModelStore;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn successfully_build_startup_report() {
+        // Arrange
+        let model_store = LocalModelStore::new("tests/model_store".to_string())
+            .await
+            .expect("Failed to create model store ❌");
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(model_store)))
+            .build()
+            .expect("Failed to initialize manager ❌");
+
+        // Act
+        let report = StartupReport::new(
+            &manager,
+            "http".to_string(),
+            "file://tests/model_store".to_string(),
+            Some("digest".to_string()),
+            "2024-07-01T10:00:00+00:00".to_string(),
+            StartupDurations {
+                model_loading_ms: 120,
+                smoke_test_ms: None,
+                total_ms: 150,
+            },
+        )
+        .expect("Failed to build startup report ❌");
+
+        // Assert
+        assert!(!report.models.is_empty());
+        assert_eq!(report.stores.len(), 1);
+        assert_eq!(report.stores[0].name, DEFAULT_STORE_NAME);
+        assert_eq!(report.stores[0].models, report.models.len());
+        assert!(report
+            .models
+            .windows(2)
+            .all(|models| models[0].name <= models[1].name));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["durations"]["total_ms"], 150);
+        assert!(json["durations"].get("smoke_test_ms").is_none());
+        assert_eq!(json["config_digest"], "digest");
+    }
+
+    #[tokio::test]
+    async fn successfully_report_models_which_failed_to_load_after_the_startup() {
+        // Arrange
+        let model_store = LocalModelStore::new("tests/model_store".to_string())
+            .await
+            .expect("Failed to create model store ❌");
+        let skipped = Arc::new(SkippedModels::default());
+        let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(model_store)))
+            .with_skipped_models(skipped.clone())
+            .build()
+            .expect("Failed to initialize manager ❌");
+        let report = StartupReport::new(
+            &manager,
+            "http".to_string(),
+            "file://tests/model_store".to_string(),
+            None,
+            "2024-07-01T10:00:00+00:00".to_string(),
+            StartupDurations::default(),
+        )
+        .expect("Failed to build startup report ❌");
+
+        // Act
+        skipped.record(
+            "xgboost-background_model.json",
+            "tests/model_store/xgboost-background_model.json",
+            "Failed to load XGBoost model",
+        );
+        let refreshed = report.refreshed(&manager);
+
+        // Assert
+        assert!(report.failed_models.is_empty());
+        assert_eq!(refreshed.failed_models.len(), 1);
+        assert_eq!(
+            refreshed.failed_models[0].file_name,
+            "xgboost-background_model.json"
+        );
+        assert_eq!(refreshed.models, report.models);
+    }
+}
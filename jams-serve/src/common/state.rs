@@ -1,20 +1,86 @@
+use crate::common::admission::{resident_memory_sampler, InFlightLimiter};
+use crate::common::authz::Authorization;
+use crate::common::bulk::BulkOperations;
+use crate::common::dedup::Deduplicator;
+use crate::common::feature_store::FeatureStore;
+use crate::common::health::{run_smoke_test, DeepHealthCheck, SmokeTestFailure, SmokeTestReport};
+use crate::common::instrument::sampling::{LogSampler, SamplingConfig};
+use crate::common::namespace::Namespaces;
+use crate::common::passthrough::Passthrough;
+use crate::common::recorder::PredictionRecorder;
+use crate::common::result_store::ResultStore;
+#[cfg(feature = "aws")]
+use crate::common::s3_events::aws::S3EventListener;
+use crate::common::schema_cache::SchemaCache;
+use crate::common::signing::ResponseSigner;
+use crate::common::startup::{StartupDurations, StartupReport};
 use crate::common::{instrument, server};
+use chrono::Utc;
+use jams_core::cgroup::CgroupLimits;
 use jams_core::manager::{Manager, ManagerBuilder};
+use jams_core::memory::{with_budget, MemoryBudget, MEBIBYTE};
+use jams_core::model::settings::with_settings;
+use jams_core::model::variant::ModelPrecision;
+use jams_core::model_store::attached::StoreSource;
+#[cfg(feature = "aws")]
 use jams_core::model_store::aws::s3::S3ModelStore;
+#[cfg(feature = "azure")]
 use jams_core::model_store::azure::blob_storage::AzureBlobStorageModelStore;
+use jams_core::model_store::deadline::{
+    StoreTimeouts, DEFAULT_DOWNLOAD_TIMEOUT_SECONDS, DEFAULT_LIST_TIMEOUT_SECONDS,
+};
+use jams_core::model_store::delta::{self, DeltaCache};
+use jams_core::model_store::encryption::{self, ArtifactCipher};
+use jams_core::model_store::groups::DeploymentGroups;
 use jams_core::model_store::local::filesystem::LocalModelStore;
+use jams_core::model_store::registry::{JsonCodec, RegistryState};
+use jams_core::model_store::skipped::{with_skipped, SkippedModels};
+use jams_core::model_store::storage::DEFAULT_MAX_CONCURRENT_DOWNLOADS;
+use jams_core::model_store::usage::DEFAULT_USAGE_SAVE_INTERVAL_SECONDS;
 use jams_core::model_store::ModelStore;
+use jams_core::pool::executor::ExecutorKind;
 use jams_core::pool::object_pool_refiller;
-use rayon::{ThreadPool, ThreadPoolBuilder};
+use jams_core::pool::scheduler::{Budget, Scheduler};
 use std::env;
 use std::sync::Arc;
+use std::time::Instant;
 
 /// AppState struct holds application state.
 pub struct AppState {
     /// The manager component wrapped in an Arc (atomic reference count) for shared ownership.
     pub manager: Arc<Manager>,
-    /// A thread pool for executing CPU-bound tasks asynchronously.
-    pub cpu_pool: ThreadPool,
+    /// The shared worker pool, which schedules CPU-bound tasks by priority on its executor.
+    pub cpu_pool: Scheduler,
+    /// The resident memory (in bytes) above which prediction requests are shed. Disabled if `None`.
+    pub memory_watermark_bytes: Option<u64>,
+    /// Limits the predictions in flight and the time they wait for a worker.
+    pub in_flight_limiter: InFlightLimiter,
+    /// Records served predictions to the prediction log. Disabled if `None`.
+    pub prediction_recorder: Option<PredictionRecorder>,
+    /// Worker pools of the namespaces. Models outside of a namespace run on `cpu_pool`.
+    pub namespaces: Namespaces,
+    /// Validates inputs before they are admitted to the worker pool. Disabled if `None`.
+    pub schema_cache: Option<SchemaCache>,
+    /// Runs a canned prediction against a sentinel model for `/healthz/deep`. Disabled if `None`.
+    pub deep_health: Option<DeepHealthCheck>,
+    /// Enriches requests with features looked up by entity ID before predicting. Disabled if `None`.
+    pub feature_store: Option<FeatureStore>,
+    /// Stores predictions above a size threshold and returns a signed URL to them instead. Disabled if `None`.
+    pub result_store: Option<ResultStore>,
+    /// Counts and squashes the duplicates of recent prediction requests. Disabled if `None`.
+    pub deduplicator: Option<Deduplicator>,
+    /// Authorizes the predict and admin calls. Every call is allowed if `None`.
+    pub authorization: Option<Authorization>,
+    /// Captures request headers into the prediction log and postprocesses predictions. Disabled if `None`.
+    pub passthrough: Option<Passthrough>,
+    /// The outcome of the smoke test run at startup, reported by `/readyz`. Disabled if `None`.
+    pub smoke_test: Option<SmokeTestReport>,
+    /// Refreshes and deletes many models in the background and tracks their progress.
+    pub bulk_operations: BulkOperations,
+    /// The summary of the startup, returned by `/api/v1/startup-report`. Not reported if `None`.
+    pub startup_report: Option<StartupReport>,
+    /// Signs the bodies of the prediction responses. Responses are not signed if `None`.
+    pub response_signer: Option<ResponseSigner>,
 }
 
 /// Builds the application state from the provided configuration.
@@ -34,6 +100,14 @@ pub struct AppState {
 ///
 /// This function returns an error if:
 /// * The number of worker threads is less than 1.
+/// * The log sampling configuration is invalid.
+/// * A namespace is configured more than once or without workers.
+/// * The deep health check model is configured without an input.
+/// * The authorization policies cannot be loaded.
+/// * The artifact encryption key cannot be read.
+/// * The delta cache directory cannot be created.
+/// * The smoke test fails for a framework and is configured to fail the startup.
+/// * The backend of the model store was left out of this build, see the `aws` and `azure` features.
 /// * The S3 bucket name is not specified when `with_s3_model_store` is true.
 /// * Any failure occurs during the initialization of the thread pool, model store, or manager.
 ///
@@ -46,7 +120,17 @@ pub async fn build_app_state(
     config: server::Config,
     worker_pool_threads: usize,
 ) -> anyhow::Result<Arc<AppState>> {
-    instrument::simple::init(tracing::Level::INFO);
+    // sample warning and error logs only when a sampling window is configured
+    let log_sampler = match config.log_sampling_window {
+        Some(window_secs) => Some(LogSampler::new(SamplingConfig::parse(
+            window_secs,
+            config.log_sampling_limits.as_deref(),
+        )?)),
+        None => None,
+    };
+    instrument::simple::init(tracing::Level::INFO, log_sampler);
+    let started_at = Utc::now().to_rfc3339();
+    let startup = Instant::now();
 
     let model_dir = config.model_dir.unwrap_or_else(|| {
         // search for environment variable
@@ -56,81 +140,432 @@ pub async fn build_app_state(
     let worker_pool_threads = config.num_workers.unwrap_or(worker_pool_threads);
     let model_store = config.model_store;
 
-    // run without polling by default
-    let interval = config.poll_interval.unwrap_or(0);
+    // fail before anything is set up if the backend of the model store was left out of this build
+    server::check_model_store_enabled(model_store.as_str())?;
 
-    // initialize manager
-    let manager = if model_store == server::AWS {
-        let s3_bucket_name = config.s3_bucket_name.unwrap_or_else(|| {
-            // search for environment variable
-            env::var("S3_BUCKET_NAME").expect("S3 bucket name not specified ❌. Either set the S3_BUCKET_NAME env variable or provide the value using --s3-bucket-name flag ")
-        });
-        let model_store = S3ModelStore::new(s3_bucket_name, None)
-            .await
-            .expect("Failed to create S3 model store ❌");
-        Arc::new(
-            ManagerBuilder::new(Arc::new(ModelStore::AWS(model_store)))
-                .with_polling(interval)
-                .build()
-                .expect("Failed to initialize manager ❌"),
-        )
-    } else if model_store == server::MINIO {
-        let s3_bucket_name = config.s3_bucket_name.unwrap_or_else(|| {
-            // search for environment variable
-            env::var("S3_BUCKET_NAME").expect("S3 bucket name not specified ❌. Either set the S3_BUCKET_NAME env variable or provide the value using --s3-bucket-name flag ")
-        });
-        let model_store = S3ModelStore::new(s3_bucket_name, Some(true))
-            .await
-            .expect("Failed to create S3 model store ❌");
-        Arc::new(
-            ManagerBuilder::new(Arc::new(ModelStore::AWS(model_store)))
-                .with_polling(interval)
-                .build()
-                .expect("Failed to initialize manager ❌"),
-        )
-    } else if model_store == server::AZURE {
-        let azure_storage_container_name = config.azure_storage_container_name.unwrap_or_else(|| {
-            // search for environment variable
-            env::var("AZURE_STORAGE_CONTAINER_NAME").expect("Azure Storage container name not specified ❌. Either set the AZURE_STORAGE_CONTAINER_NAME env variable or provide the value using --azure-container-name flag ")
+    // run without polling by default, the interval of the model store takes precedence over the global interval
+    let interval = config
+        .poll_intervals
+        .as_ref()
+        .and_then(|intervals| intervals.get(model_store.as_str()).copied())
+        .or(config.poll_interval)
+        .unwrap_or(0);
+    let poll_jitter = config.poll_jitter.unwrap_or(0);
+    let max_concurrent_downloads = config
+        .max_concurrent_downloads
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_DOWNLOADS);
+
+    // abandon hung calls to remote model stores so that they do not block the poller forever
+    let store_timeouts = StoreTimeouts {
+        list: std::time::Duration::from_secs(
+            config
+                .store_list_timeout
+                .unwrap_or(DEFAULT_LIST_TIMEOUT_SECONDS),
+        ),
+        download: std::time::Duration::from_secs(
+            config
+                .store_download_timeout
+                .unwrap_or(DEFAULT_DOWNLOAD_TIMEOUT_SECONDS),
+        ),
+    };
+
+    // run without integrity checks by default
+    let integrity_check_interval = config.integrity_check_interval.unwrap_or(0);
+
+    // keep the usage in memory only by default, and save it every minute if a usage state file is configured
+    let usage_save_interval = config
+        .usage_save_interval
+        .unwrap_or(DEFAULT_USAGE_SAVE_INTERVAL_SECONDS);
+
+    // keep orphaned model store directories by default
+    let temp_dir_retention = config.temp_dir_retention.unwrap_or(0);
+    let temp_dir_gc_interval = config.temp_dir_gc_interval.unwrap_or(0);
+
+    // inject no faults by default
+    let chaos = config.chaos.unwrap_or_default();
+
+    // budget the memory limit of the container by default, so that models which do not fit are refused
+    // instead of the server being OOM killed
+    let memory_budget_mb = config
+        .memory_budget_mb
+        .unwrap_or_else(|| match CgroupLimits::current().memory_bytes {
+            Some(limit_bytes) => {
+                tracing::info!(
+                    "Budgeting the cgroup memory limit of {} MiB for the models 📦",
+                    limit_bytes / MEBIBYTE
+                );
+                limit_bytes / MEBIBYTE
+            }
+            None => 0,
         });
-        let model_store = AzureBlobStorageModelStore::new(azure_storage_container_name)
-            .await
-            .expect("Failed to create Azure model store ❌");
-        Arc::new(
-            ManagerBuilder::new(Arc::new(ModelStore::Azure(model_store)))
-                .with_polling(interval)
-                .build()
-                .expect("Failed to initialize manager ❌"),
-        )
-    } else {
-        let model_store = LocalModelStore::new(model_dir)
-            .await
-            .expect("Failed to create local model store ❌");
-        Arc::new(
-            ManagerBuilder::new(Arc::new(ModelStore::Local(model_store)))
-                .with_polling(interval)
-                .build()
-                .expect("Failed to initialize manager ❌"),
+    let memory_watermark_bytes = config
+        .memory_shed_watermark_mb
+        .map(|watermark_mb| watermark_mb * MEBIBYTE);
+
+    // admit every prediction and let it wait for a worker for as long as it takes by default
+    let in_flight_limiter = InFlightLimiter::new(
+        config.max_in_flight,
+        config
+            .max_queue_wait_ms
+            .map(std::time::Duration::from_millis),
+    );
+
+    // restore the model store from the registry state only when a state file is configured
+    let registry_state_path = config.registry_state_path;
+    let registry_state = match registry_state_path.as_deref() {
+        Some(path) => RegistryState::load(path, &JsonCodec).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring registry state ⚠️: {}", e);
+            None
+        }),
+        None => None,
+    };
+
+    // load every model before serving by default
+    let priority_models = config.priority_models.unwrap_or_default();
+
+    // update every model on its own by default
+    let deployment_groups = DeploymentGroups::new(config.deployment_groups.unwrap_or_default())?;
+
+    // route requests over their latency budget to the lightweight variant of their model
+    let latency_fallbacks = config.latency_fallbacks.unwrap_or_default();
+    if let Some((model_name, _)) = latency_fallbacks
+        .iter()
+        .find(|(_, precision)| **precision == ModelPrecision::Fp32)
+    {
+        tracing::error!(
+            "Latency fallback of model {} must be a reduced precision variant, i.e. fp16 or int8 ❌",
+            model_name
+        );
+        anyhow::bail!(
+            "Latency fallback of model {} must be a reduced precision variant, i.e. fp16 or int8",
+            model_name
         )
+    }
+
+    // apply the S3 events of the model store bucket only when a queue is configured
+    #[cfg(feature = "aws")]
+    let s3_event_listener = match config.s3_events {
+        Some(_) if model_store != server::AWS => {
+            tracing::error!("S3 events are only supported by the aws model store ❌");
+            anyhow::bail!("S3 events are only supported by the aws model store ❌")
+        }
+        Some(s3_events_config) => {
+            let bucket_name = match config
+                .s3_bucket_name
+                .clone()
+                .or_else(|| env::var("S3_BUCKET_NAME").ok())
+            {
+                Some(bucket_name) if !bucket_name.is_empty() => bucket_name,
+                _ => {
+                    tracing::error!("S3 events require the S3 bucket name to be specified ❌");
+                    anyhow::bail!("S3 events require the S3 bucket name to be specified ❌. Either set the S3_BUCKET_NAME env variable or provide the value using --s3-bucket-name flag")
+                }
+            };
+            Some(S3EventListener::connect(s3_events_config, bucket_name).await?)
+        }
+        None => None,
     };
+    #[cfg(not(feature = "aws"))]
+    if config.s3_events.is_some() {
+        tracing::error!("S3 events are only supported by the aws model store ❌");
+        anyhow::bail!("S3 events are only supported by the aws model store ❌")
+    }
 
-    // initialize threadpool for cpu intensive tasks
-    if worker_pool_threads < 1 {
-        anyhow::bail!("At least 1 worker is required for rayon threadpool")
+    // encrypt the artifact cache before the model store downloads any model into it
+    if let Some(encryption_config) = config.artifact_encryption.as_ref() {
+        encryption::install(ArtifactCipher::from_config(encryption_config)?)?;
+    }
+
+    // the previous tarballs are cached like the artifacts, hence after the encryption is installed
+    if let Some(delta_config) = config.delta_updates.as_ref() {
+        delta::install(DeltaCache::from_config(delta_config)?)?;
+    }
+
+    // the settings are resolved by the init hooks of the models, hence they are in scope of the models loaded by the
+    // model store before the manager is built too
+    let model_settings = config.model_settings.map(Arc::new);
+
+    // the models skipped by the model store before the manager is built are reported by the manager too
+    let skipped = Arc::new(SkippedModels::default());
+
+    // the models loaded by the model store before the manager is built are checked against the budget too, so that
+    // the models which do not fit are skipped instead of failing the startup
+    let memory_budget = MemoryBudget::from_mb(memory_budget_mb);
+
+    // initialize manager
+    let model_loading = Instant::now();
+    let connect = with_settings(model_settings.clone(), async {
+        match model_store.as_str() {
+            #[cfg(feature = "aws")]
+            server::AWS | server::MINIO => {
+                let use_minio = (model_store == server::MINIO).then_some(true);
+                let s3_bucket_name = config.s3_bucket_name.unwrap_or_else(|| {
+                // search for environment variable
+                env::var("S3_BUCKET_NAME").expect("S3 bucket name not specified ❌. Either set the S3_BUCKET_NAME env variable or provide the value using --s3-bucket-name flag ")
+            });
+                let store_url = match use_minio {
+                    Some(_) => StoreSource::Minio(s3_bucket_name.clone()),
+                    None => StoreSource::S3(s3_bucket_name.clone()),
+                }
+                .to_string();
+                let model_store = match registry_state_path {
+                    Some(_) => {
+                        S3ModelStore::with_registry_state(s3_bucket_name, use_minio, registry_state)
+                            .await
+                    }
+                    None if !priority_models.is_empty() => {
+                        S3ModelStore::with_priority_models(
+                            s3_bucket_name,
+                            use_minio,
+                            priority_models.clone(),
+                        )
+                        .await
+                    }
+                    None => S3ModelStore::new(s3_bucket_name, use_minio).await,
+                }
+                .expect("Failed to create S3 model store ❌")
+                .with_timeouts(store_timeouts);
+                (ModelStore::AWS(model_store), store_url)
+            }
+            #[cfg(feature = "azure")]
+            server::AZURE => {
+                let azure_storage_container_name = config.azure_storage_container_name.unwrap_or_else(|| {
+                // search for environment variable
+                env::var("AZURE_STORAGE_CONTAINER_NAME").expect("Azure Storage container name not specified ❌. Either set the AZURE_STORAGE_CONTAINER_NAME env variable or provide the value using --azure-container-name flag ")
+            });
+                let store_url =
+                    StoreSource::Azure(azure_storage_container_name.clone()).to_string();
+                let model_store = match registry_state_path {
+                    Some(_) => {
+                        AzureBlobStorageModelStore::with_registry_state(
+                            azure_storage_container_name,
+                            registry_state,
+                        )
+                        .await
+                    }
+                    None if !priority_models.is_empty() => {
+                        AzureBlobStorageModelStore::with_priority_models(
+                            azure_storage_container_name,
+                            priority_models.clone(),
+                        )
+                        .await
+                    }
+                    None => AzureBlobStorageModelStore::new(azure_storage_container_name).await,
+                }
+                .expect("Failed to create Azure model store ❌")
+                .with_timeouts(store_timeouts);
+                (ModelStore::Azure(model_store), store_url)
+            }
+            _ => {
+                let store_url = StoreSource::Local(model_dir.clone()).to_string();
+                let model_store = match registry_state_path {
+                    Some(_) => {
+                        LocalModelStore::with_registry_state(model_dir, registry_state).await
+                    }
+                    None if !priority_models.is_empty() => {
+                        LocalModelStore::with_priority_models(model_dir, priority_models.clone())
+                            .await
+                    }
+                    None => LocalModelStore::new(model_dir).await,
+                }
+                .expect("Failed to create local model store ❌");
+                (ModelStore::Local(model_store), store_url)
+            }
+        }
+    });
+    let (model_store, store_url) =
+        with_skipped(skipped.clone(), with_budget(memory_budget, connect)).await;
+    let mut manager_builder =
+        ManagerBuilder::new(Arc::new(model_store)).with_skipped_models(skipped);
+    if let Some(model_settings) = model_settings {
+        manager_builder = manager_builder.with_model_settings(model_settings);
+    }
+    if let Some(usage_state_path) = config.usage_state_path {
+        manager_builder = manager_builder.with_usage_state(usage_state_path, usage_save_interval);
     }
-    let cpu_pool = ThreadPoolBuilder::new()
-        .num_threads(worker_pool_threads)
-        .build()
-        .expect("Failed to build rayon threadpool ❌");
+    let manager = Arc::new(
+        manager_builder
+            .with_polling(interval)
+            .with_poll_jitter(poll_jitter)
+            .with_max_concurrent_downloads(max_concurrent_downloads)
+            .with_memory_budget(memory_budget_mb)
+            .with_integrity_check(integrity_check_interval)
+            .with_temp_dir_gc(temp_dir_retention, temp_dir_gc_interval)
+            .with_chaos(chaos)
+            .with_priority_models(priority_models)
+            .with_deployment_groups(deployment_groups)
+            .with_store_timeouts(store_timeouts)
+            .with_latency_fallbacks(latency_fallbacks)
+            .with_scheduling_weights(config.scheduling_weights.unwrap_or_default())
+            .with_model_suggestions(config.model_suggestions.unwrap_or(false))
+            .build()?,
+    );
+    let model_loading_ms = StartupDurations::millis(model_loading.elapsed());
+
+    // only count the predictions which are over budget, they are never rejected because of it
+    let budget = Budget {
+        max_wait: config
+            .task_wait_budget_ms
+            .map(std::time::Duration::from_millis),
+        max_execute: config
+            .task_execute_budget_ms
+            .map(std::time::Duration::from_millis),
+    };
+
+    // initialize the shared worker pool for cpu intensive tasks, running on rayon by default
+    let executor = match config.executor.as_deref() {
+        Some(executor) => ExecutorKind::parse(executor)?,
+        None => ExecutorKind::default(),
+    };
+    let cpu_pool = Scheduler::new(
+        "shared",
+        executor.build(worker_pool_threads, "jams-worker")?,
+        budget,
+    );
 
     tracing::info!(
-        "Rayon threadpool started with {} workers ⚙️",
-        worker_pool_threads
+        "Worker pool started with {} {:?} workers ⚙️",
+        worker_pool_threads,
+        executor
     );
+    let cgroup_limits = CgroupLimits::current();
+    if cgroup_limits.cpu_quota.is_some() || cgroup_limits.memory_bytes.is_some() {
+        tracing::info!(
+            "Running within a CPU quota of {:?} and a memory limit of {:?} MiB 📦",
+            cgroup_limits.cpu_quota,
+            cgroup_limits
+                .memory_bytes
+                .map(|limit_bytes| limit_bytes / MEBIBYTE)
+        );
+    }
 
     // start object pool refill worker
     tokio::spawn(object_pool_refiller());
 
+    // start resident memory sampler used for load shedding
+    tokio::spawn(resident_memory_sampler());
+
+    // start applying the S3 events once the models are loaded
+    #[cfg(feature = "aws")]
+    if let Some(s3_event_listener) = s3_event_listener {
+        s3_event_listener.spawn(manager.clone());
+    }
+
+    // record predictions only when a prediction log is configured
+    let prediction_recorder = match config.prediction_log_path {
+        Some(path) => Some(PredictionRecorder::new(path.as_str()).await?),
+        None => None,
+    };
+
+    // isolate the worker pools of the configured namespaces
+    let namespaces = Namespaces::new(config.namespaces.unwrap_or_default(), budget)?;
+
+    // validate inputs before admission only when a schema cache is configured
+    let schema_cache = config.schema_cache_size.map(SchemaCache::new);
+
+    // run the deep health check only when a sentinel model is configured
+    let deep_health = match (config.deep_health_model, config.deep_health_input) {
+        (Some(model_name), Some(input)) => Some(DeepHealthCheck::new(model_name, input)),
+        (Some(_), None) => {
+            anyhow::bail!("Deep health check requires deep_health_input to be set ❌")
+        }
+        (None, _) => None,
+    };
+
+    // enrich requests with looked up features only when a feature store is configured
+    let feature_store = match config.feature_store {
+        Some(feature_store_config) => Some(FeatureStore::connect(feature_store_config).await?),
+        None => None,
+    };
+
+    // return big predictions as a signed URL only when a result store is configured
+    let result_store = match config.result_store {
+        Some(result_store_config) => Some(ResultStore::connect(result_store_config).await?),
+        None => None,
+    };
+
+    // count and squash duplicate predictions only when deduplication is configured
+    let deduplicator = config.deduplication.map(Deduplicator::new);
+
+    // allow every call unless authorization is configured
+    let authorization = match config.authorization {
+        Some(authorization_config) => Some(Authorization::new(authorization_config)?),
+        None => None,
+    };
+
+    // make one prediction per framework only when a smoke test is configured
+    let smoke_test_started = Instant::now();
+    let smoke_test = match config.smoke_test {
+        Some(smoke_test_config) => {
+            let report = run_smoke_test(&manager, &cpu_pool, &namespaces).await?;
+            let failed_frameworks = report.failed_frameworks();
+            if !failed_frameworks.is_empty() {
+                match smoke_test_config.on_failure {
+                    SmokeTestFailure::Fail => {
+                        tracing::error!("Smoke test failed for {:?} ❌", failed_frameworks);
+                        anyhow::bail!("Smoke test failed for {:?} ❌", failed_frameworks)
+                    }
+                    SmokeTestFailure::Degrade => {
+                        tracing::warn!(
+                            "Smoke test failed for {:?}, serving them degraded ⚠️",
+                            failed_frameworks
+                        )
+                    }
+                }
+            } else {
+                tracing::info!("Smoke test passed ✅");
+            }
+            Some(report)
+        }
+        None => None,
+    };
+
+    let smoke_test_ms = smoke_test
+        .as_ref()
+        .map(|_| StartupDurations::millis(smoke_test_started.elapsed()));
+
+    // capture request headers only when a passthrough is configured
+    let passthrough = config.passthrough.map(Passthrough::new);
+
+    // sign prediction responses only when response signing is configured
+    let response_signer = match config.response_signing {
+        Some(response_signing_config) => Some(ResponseSigner::new(response_signing_config)?),
+        None => None,
+    };
+
+    // summarize the startup in a single report, so that deploy tooling does not have to grep the logs
+    let startup_report = StartupReport::new(
+        &manager,
+        config.protocol,
+        store_url,
+        config.digest,
+        started_at,
+        StartupDurations {
+            model_loading_ms,
+            smoke_test_ms,
+            total_ms: StartupDurations::millis(startup.elapsed()),
+        },
+    )?;
+    startup_report.log();
+
     // setup shared state
-    Ok(Arc::new(AppState { manager, cpu_pool }))
+    Ok(Arc::new(AppState {
+        manager,
+        cpu_pool,
+        memory_watermark_bytes,
+        in_flight_limiter,
+        prediction_recorder,
+        namespaces,
+        schema_cache,
+        deep_health,
+        feature_store,
+        result_store,
+        deduplicator,
+        authorization,
+        passthrough,
+        smoke_test,
+        bulk_operations: BulkOperations::default(),
+        startup_report: Some(startup_report),
+        response_signer,
+    }))
 }
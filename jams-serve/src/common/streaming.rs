@@ -0,0 +1,93 @@
+use crate::common::admission::InFlightPermit;
+use crate::common::state::AppState;
+use crate::common::worker;
+use jams_core::model::predict::PredictOptions;
+use jams_core::pool::scheduler::Task;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+use tokio_util::sync::{CancellationToken, DropGuard};
+use tonic::codegen::tokio_stream::wrappers::ReceiverStream;
+use tonic::codegen::tokio_stream::Stream;
+
+/// Number of chunks buffered between the worker generating a stream and the handler sending it to the client.
+/// The worker waits once the buffer is full, so that a slow client does not grow the buffer without bound.
+const STREAM_BUFFER_SIZE: usize = 16;
+
+/// The chunks of a streamed prediction, each formatted as a JSON string, in order. The stream ends after the
+/// last chunk, or after the error if the prediction failed midway.
+///
+/// The prediction counts as in flight until the stream is dropped. Dropping the stream, e.g. because the client
+/// disconnected, stops the prediction and frees the worker.
+pub struct PredictionStream {
+    chunks: ReceiverStream<anyhow::Result<String>>,
+    _permit: InFlightPermit,
+    _cancel_on_drop: DropGuard,
+}
+
+impl Stream for PredictionStream {
+    type Item = anyhow::Result<String>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.chunks).poll_next(cx)
+    }
+}
+
+/// Queues a streamed prediction on the worker pool of the model.
+///
+/// The caller is expected to have checked that the model streams its predictions, see
+/// `Manager::check_streaming`, and admitted the prediction. Streams are neither deduplicated, recorded nor
+/// written to the result store, and the maximum queue wait does not apply to them.
+///
+/// # Arguments
+///
+/// * `app_state` - The application state.
+/// * `model_name` - The name of the model to use for the prediction.
+/// * `input` - The input data for the prediction, formatted as a JSON string.
+/// * `predict_options` - The prediction options sent with the request.
+/// * `permit` - The permit of the prediction, which is held until the stream is dropped.
+///
+/// # Errors
+///
+/// * `Err(anyhow::Error)` - If the namespace of the model has reached its limit of queued predictions.
+pub fn spawn_prediction_stream(
+    app_state: &AppState,
+    model_name: String,
+    input: String,
+    predict_options: PredictOptions,
+    permit: InFlightPermit,
+) -> anyhow::Result<PredictionStream> {
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER_SIZE);
+
+    // cancel the prediction when the stream is dropped, whether or not the worker has picked it up
+    let cancellation_token = CancellationToken::new();
+    let worker_cancellation_token = cancellation_token.clone();
+
+    let manager = Arc::clone(&app_state.manager);
+    let priority = manager.priority_of(model_name.as_str(), &predict_options);
+    let weight = manager.scheduling_weight_of(model_name.as_str());
+    let worker_model_name = model_name.clone();
+    let task = Task::new(move || {
+        worker::predict_stream_and_send(
+            manager,
+            worker_model_name,
+            input,
+            predict_options,
+            tx,
+            worker_cancellation_token,
+        )
+    })
+    .with_priority(priority)
+    .with_flow(model_name.as_str(), weight)
+    .with_cancellation_token(cancellation_token.clone());
+    app_state
+        .namespaces
+        .spawn(&app_state.cpu_pool, model_name.as_str(), task)?;
+
+    Ok(PredictionStream {
+        chunks: ReceiverStream::new(rx),
+        _permit: permit,
+        _cancel_on_drop: cancellation_token.drop_guard(),
+    })
+}
@@ -1,29 +1,365 @@
-use jams_core::manager::Manager;
+use crate::common::metrics::{PREDICTIONS_CANCELLED_TOTAL, PREDICTION_PANICS_TOTAL};
+use jams_core::manager::{Manager, ServedPrediction};
+use jams_core::model::predict::PredictOptions;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::oneshot::Sender;
+use tokio_util::sync::CancellationToken;
+
+/// Time a streamed prediction waits for the client to make room for its next chunk before it is cancelled, so that
+/// a stalled client does not hold a worker forever.
+const STREAM_SEND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Interval at which a worker retries sending a chunk while the buffer of the client is full.
+const STREAM_SEND_RETRY_INTERVAL: Duration = Duration::from_millis(5);
 
 /// Asynchronously predicts an outcome using a shared manager and sends the result or error
 /// message through a channel.
 ///
 /// This function takes an Arc-wrapped `Manager`, a `PredictRequest` containing model name
-/// and input data, and a `Sender<anyhow::Result<String>>` channel for sending the prediction result.
+/// and input data, and a `Sender<anyhow::Result<ServedPrediction>>` channel for sending the prediction result.
 ///
 /// # Arguments
 ///
 /// * `manager` - An `Arc` reference to the shared `Manager` instance used for predictions.
 /// * `payload` - A `PredictRequest` containing the model name and input data for prediction.
-/// * `tx` - A `Sender<anyhow::Result<String>>` channel endpoint for sending the prediction result.
+/// * `predict_options` - The prediction options sent with the request, which override the default options of the model.
+/// * `tx` - A `Sender<anyhow::Result<ServedPrediction>>` channel endpoint for sending the prediction result, along
+///   with the precision variant of the model which made it.
+/// * `cancellation_token` - A `CancellationToken` which is cancelled when the caller no longer needs the result,
+///   for example when a gRPC deadline is exceeded.
 ///
 /// The function asynchronously sends the prediction result through the provided
 /// channel (`tx`) based on the result of the prediction operation using the shared `Manager`.
-#[tracing::instrument(skip(manager, input, tx))]
+///
+/// If the token has been cancelled or the receiving end of the channel has already been dropped, for
+/// example because the client disconnected while the task was queued, the prediction is skipped to
+/// free up the worker. None of the supported frameworks can be interrupted midway, so a prediction which
+/// has already started will always run to completion.
+///
+/// Panics raised while predicting are caught and sent back as errors with the panic message, as a panic
+/// inside the rayon threadpool would otherwise abort the whole process. Every panic is counted per model
+/// in the `prediction_panics_total` metric.
+#[tracing::instrument(skip(manager, input, predict_options, tx, cancellation_token))]
 pub fn predict_and_send(
     manager: Arc<Manager>,
     model_name: String,
     input: String,
-    tx: Sender<anyhow::Result<String>>,
+    predict_options: PredictOptions,
+    tx: Sender<anyhow::Result<ServedPrediction>>,
+    cancellation_token: CancellationToken,
 ) {
+    // the caller is no longer waiting for the result so there is no point in predicting
+    if cancellation_token.is_cancelled() || tx.is_closed() {
+        tracing::warn!(
+            "Skipping prediction for model {} as the caller has gone away ⚠️",
+            model_name
+        );
+        PREDICTIONS_CANCELLED_TOTAL
+            .with_label_values(&[model_name.as_str()])
+            .inc();
+        return;
+    }
+
+    let predictions = match panic::catch_unwind(AssertUnwindSafe(|| {
+        manager.predict_with_variant(model_name.clone(), input.as_str(), &predict_options)
+    })) {
+        Ok(predictions) => predictions,
+        Err(payload) => {
+            let message = panic_message(payload.as_ref());
+            PREDICTION_PANICS_TOTAL
+                .with_label_values(&[model_name.as_str()])
+                .inc();
+            tracing::error!(
+                "Prediction panicked for model {} ❌: {}",
+                model_name,
+                message
+            );
+            Err(anyhow::anyhow!(
+                "Prediction panicked for model {} ❌: {}",
+                model_name,
+                message
+            ))
+        }
+    };
+
     // we do not handle the result here
-    let predictions = manager.predict(model_name, input.as_str());
     let _ = tx.send(predictions);
 }
+
+/// Predicts using a model which yields its outputs incrementally and sends each chunk of the output through
+/// a channel as soon as it is ready, see `Manager::predict_stream`.
+///
+/// # Arguments
+///
+/// * `manager` - An `Arc` reference to the shared `Manager` instance used for predictions.
+/// * `model_name` - The name of the model to use for the prediction.
+/// * `input` - The input data for the prediction, formatted as a JSON string.
+/// * `predict_options` - The prediction options sent with the request, which override the default options of the model.
+/// * `tx` - A bounded `mpsc::Sender<anyhow::Result<String>>` channel endpoint for sending the chunks, followed by
+///   the error if the prediction fails midway. A slow client applies backpressure on the worker, and a client which
+///   does not make room for a chunk within 30 seconds has the prediction cancelled.
+/// * `cancellation_token` - A `CancellationToken` which is cancelled when the caller no longer needs the output.
+///
+/// Unlike `predict_and_send`, a prediction which has already started is stopped between two chunks once the
+/// token is cancelled or the receiving end of the channel is dropped, e.g. because the client disconnected.
+/// Panics are caught and sent back as errors, like for `predict_and_send`.
+#[tracing::instrument(skip(manager, input, predict_options, tx, cancellation_token))]
+pub fn predict_stream_and_send(
+    manager: Arc<Manager>,
+    model_name: String,
+    input: String,
+    predict_options: PredictOptions,
+    tx: mpsc::Sender<anyhow::Result<String>>,
+    cancellation_token: CancellationToken,
+) {
+    // the caller is no longer waiting for the output so there is no point in predicting
+    if cancellation_token.is_cancelled() || tx.is_closed() {
+        tracing::warn!(
+            "Skipping streamed prediction for model {} as the caller has gone away ⚠️",
+            model_name
+        );
+        PREDICTIONS_CANCELLED_TOTAL
+            .with_label_values(&[model_name.as_str()])
+            .inc();
+        return;
+    }
+
+    let mut emit =
+        |chunk: String| send_chunk(&tx, Ok(chunk), &cancellation_token, STREAM_SEND_TIMEOUT);
+    let result = match panic::catch_unwind(AssertUnwindSafe(|| {
+        manager.predict_stream(
+            model_name.clone(),
+            input.as_str(),
+            &predict_options,
+            &mut emit,
+        )
+    })) {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = panic_message(payload.as_ref());
+            PREDICTION_PANICS_TOTAL
+                .with_label_values(&[model_name.as_str()])
+                .inc();
+            tracing::error!(
+                "Streamed prediction panicked for model {} ❌: {}",
+                model_name,
+                message
+            );
+            Err(anyhow::anyhow!(
+                "Prediction panicked for model {} ❌: {}",
+                model_name,
+                message
+            ))
+        }
+    };
+
+    if let Err(e) = result {
+        // we do not handle the result here
+        let _ = send_chunk(&tx, Err(e), &cancellation_token, STREAM_SEND_TIMEOUT);
+    }
+}
+
+/// Sends a chunk of a streamed prediction from a worker, waiting at most `timeout` for the client to make room
+/// in the bounded channel. The token is cancelled once the timeout has elapsed, which stops the prediction.
+///
+/// # Returns
+///
+/// True if the chunk was sent, false if the token was cancelled, the receiving end was dropped or the timeout
+/// elapsed.
+fn send_chunk<T>(
+    tx: &mpsc::Sender<T>,
+    mut chunk: T,
+    cancellation_token: &CancellationToken,
+    timeout: Duration,
+) -> bool {
+    // the workers do not run on the runtime, so the channel is polled instead of awaiting a send with a timeout
+    let deadline = Instant::now() + timeout;
+    loop {
+        if cancellation_token.is_cancelled() {
+            return false;
+        }
+        match tx.try_send(chunk) {
+            Ok(()) => return true,
+            Err(TrySendError::Closed(_)) => return false,
+            Err(TrySendError::Full(returned)) => {
+                if Instant::now() >= deadline {
+                    tracing::warn!(
+                        "Cancelling streamed prediction as the client has not read a chunk for {:?} ⚠️",
+                        timeout
+                    );
+                    cancellation_token.cancel();
+                    return false;
+                }
+                chunk = returned;
+                std::thread::sleep(STREAM_SEND_RETRY_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Returns the message of a caught panic. Panics raised with `panic!` carry either a `&str` or a `String`.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+    "unknown panic".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jams_core::manager::ManagerBuilder;
+    use jams_core::model_store::local::filesystem::LocalModelStore;
+    use jams_core::model_store::ModelStore;
+    use tokio::sync::oneshot;
+
+    #[tokio::test]
+    async fn successfully_skips_prediction_when_receiver_is_dropped() {
+        // Arrange
+        let model_store = LocalModelStore::new("tests/model_store".to_string())
+            .await
+            .expect("Failed to create model store ❌");
+        let manager = Arc::new(
+            ManagerBuilder::new(Arc::new(ModelStore::Local(model_store)))
+                .build()
+                .expect("Failed to initialize manager ❌"),
+        );
+        let model_name = "cancelled_model".to_string();
+        let before = PREDICTIONS_CANCELLED_TOTAL
+            .with_label_values(&[model_name.as_str()])
+            .get();
+        let (tx, rx) = oneshot::channel();
+        drop(rx);
+
+        // Act
+        predict_and_send(
+            manager,
+            model_name.clone(),
+            "{}".to_string(),
+            PredictOptions::default(),
+            tx,
+            CancellationToken::new(),
+        );
+
+        // Assert
+        let after = PREDICTIONS_CANCELLED_TOTAL
+            .with_label_values(&[model_name.as_str()])
+            .get();
+        assert_eq!(after, before + 1)
+    }
+
+    #[tokio::test]
+    async fn successfully_reports_streamed_prediction_of_unknown_model() {
+        // Arrange
+        let model_store = LocalModelStore::new("tests/model_store".to_string())
+            .await
+            .expect("Failed to create model store ❌");
+        let manager = Arc::new(
+            ManagerBuilder::new(Arc::new(ModelStore::Local(model_store)))
+                .build()
+                .expect("Failed to initialize manager ❌"),
+        );
+        let (tx, mut rx) = mpsc::channel(1);
+
+        // Act
+        // the chunks are sent with a blocking send, which must not run on the runtime
+        tokio::task::spawn_blocking(move || {
+            predict_stream_and_send(
+                manager,
+                "unknown_streaming_model".to_string(),
+                r#"{"input_ids": [1, 2]}"#.to_string(),
+                PredictOptions::default(),
+                tx,
+                CancellationToken::new(),
+            )
+        })
+        .await
+        .expect("Failed to join worker ❌");
+
+        // Assert
+        assert!(matches!(rx.recv().await, Some(Err(_))));
+        assert!(rx.recv().await.is_none())
+    }
+
+    #[test]
+    fn successfully_cancels_streamed_prediction_when_client_stalls() {
+        // Arrange
+        let (tx, mut rx) = mpsc::channel(1);
+        let cancellation_token = CancellationToken::new();
+        let timeout = Duration::from_millis(20);
+
+        // Act
+        let first = send_chunk(&tx, 1, &cancellation_token, timeout);
+        let stalled = send_chunk(&tx, 2, &cancellation_token, timeout);
+
+        // Assert
+        assert!(first);
+        assert!(!stalled);
+        assert!(cancellation_token.is_cancelled());
+        assert_eq!(rx.try_recv().unwrap(), 1);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn successfully_extracts_panic_message() {
+        // Act
+        let static_message = panic::catch_unwind(|| panic!("index out of bounds")).unwrap_err();
+        let formatted_message =
+            panic::catch_unwind(|| panic!("failed to predict {}", "model")).unwrap_err();
+        let unknown = panic::catch_unwind(|| panic::panic_any(42)).unwrap_err();
+
+        // Assert
+        assert_eq!(
+            panic_message(static_message.as_ref()),
+            "index out of bounds"
+        );
+        assert_eq!(
+            panic_message(formatted_message.as_ref()),
+            "failed to predict model"
+        );
+        assert_eq!(panic_message(unknown.as_ref()), "unknown panic");
+    }
+
+    #[tokio::test]
+    async fn successfully_skips_prediction_when_token_is_cancelled() {
+        // Arrange
+        let model_store = LocalModelStore::new("tests/model_store".to_string())
+            .await
+            .expect("Failed to create model store ❌");
+        let manager = Arc::new(
+            ManagerBuilder::new(Arc::new(ModelStore::Local(model_store)))
+                .build()
+                .expect("Failed to initialize manager ❌"),
+        );
+        let model_name = "deadline_exceeded_model".to_string();
+        let (tx, rx) = oneshot::channel();
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
+
+        // Act
+        predict_and_send(
+            manager,
+            model_name.clone(),
+            "{}".to_string(),
+            PredictOptions::default(),
+            tx,
+            cancellation_token,
+        );
+
+        // Assert
+        // the sender is dropped without sending anything
+        assert!(rx.await.is_err());
+        let cancelled = PREDICTIONS_CANCELLED_TOTAL
+            .with_label_values(&[model_name.as_str()])
+            .get();
+        assert_eq!(cancelled, 1)
+    }
+}
@@ -1,9 +1,11 @@
+use crate::common::listener::{self, ListenerOptions};
 use crate::common::shutdown::shutdown_signal;
 use crate::common::state::AppState;
 use crate::grpc::service::JamsService;
 use jams_proto::jams_v1::model_server_server::ModelServerServer;
 use jams_proto::jams_v1::FILE_DESCRIPTOR_SET;
 use std::sync::Arc;
+use tokio::task::JoinSet;
 use tonic::codegen::tokio_stream::wrappers::TcpListenerStream;
 use tonic::transport::Server;
 
@@ -15,7 +17,9 @@ use tonic::transport::Server;
 ///
 /// # Arguments
 ///
-/// * `config` - The server configuration.
+/// * `shared_state` - The shared application state.
+/// * `address` - The `host:port` address the server is served on.
+/// * `options` - The socket options of the listeners.
 ///
 /// # Returns
 ///
@@ -28,7 +32,11 @@ use tonic::transport::Server;
 /// * The TCP listener cannot be created.
 /// * Any failure occurs during the initialization of the services or the server.
 ///
-pub async fn start(shared_state: Arc<AppState>, port: u16) -> anyhow::Result<()> {
+pub async fn start(
+    shared_state: Arc<AppState>,
+    address: String,
+    options: ListenerOptions,
+) -> anyhow::Result<()> {
     // create service
     let jams_service = JamsService::new(shared_state).expect("Failed to create J.A.M.S service ❌");
 
@@ -38,23 +46,35 @@ pub async fn start(shared_state: Arc<AppState>, port: u16) -> anyhow::Result<()>
         .build()
         .unwrap();
 
-    // run our app with hyper, listening globally on specified port
-    let address = format!("0.0.0.0:{}", port);
-    let listener = tokio::net::TcpListener::bind(address)
-        .await
-        .expect("Failed to create TCP listener ❌");
+    // run our app with hyper, listening on the specified address
+    let listeners = listener::bind(address.as_str(), &options)?;
 
     // log that the server is running
     tracing::info!(
         "{}",
-        format!("Server is running on http://0.0.0.0:{} 🚀 \n", port)
+        format!(
+            "Server is running on http://{} with {} acceptor(s) 🚀 \n",
+            address,
+            listeners.len()
+        )
     );
 
-    Server::builder()
-        .add_service(reflection_service)
-        .add_service(ModelServerServer::new(jams_service))
-        .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown_signal())
-        .await?;
+    // one accept task per listener
+    let model_server = ModelServerServer::new(jams_service);
+    let mut acceptors = JoinSet::new();
+    for listener in listeners {
+        let server = Server::builder()
+            .add_service(reflection_service.clone())
+            .add_service(model_server.clone());
+        acceptors.spawn(async move {
+            server
+                .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown_signal())
+                .await
+        });
+    }
+    while let Some(result) = acceptors.join_next().await {
+        result??;
+    }
 
     Ok(())
 }
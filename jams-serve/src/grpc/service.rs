@@ -1,16 +1,46 @@
+use crate::common::admission::{should_shed, QueueTicket};
+use crate::common::authz::{Action, AuthzRequest, AUTHORIZATION_HEADER};
+use crate::common::dedup::Lookup;
+use crate::common::feature_store;
+use crate::common::metadata::{
+    request_id, RequestMetadata, MODEL_VARIANT_HEADER, REQUEST_ID_HEADER,
+};
+use crate::common::metrics::{
+    record_payload_sizes, DisconnectGuard, DEADLINES_EXCEEDED_TOTAL, GRPC,
+};
+use crate::common::passthrough::RequestContext;
+use crate::common::recorder::PredictionRecord;
+use crate::common::schema_cache::{client_id, CLIENT_ID_HEADER};
+use crate::common::signing::{
+    grpc_body, issued_at, SIGNATURE_HEADER, SIGNATURE_ISSUED_AT_HEADER, SIGNATURE_KEY_ID_HEADER,
+};
 use crate::common::state::AppState;
-use crate::common::worker;
+use crate::common::streaming::spawn_prediction_stream;
+use crate::common::{misses, worker};
+use jams_core::manager::ServedPrediction;
+use jams_core::model::predict::PredictOptions;
+use jams_core::model::variant::ModelPrecision;
 use jams_core::model_store::storage::Metadata;
-use jams_proto::jams_v1::get_models_response::Model;
+use jams_core::pool::scheduler::Task;
+use jams_proto::jams_v1::get_models_response::{HookStatus, Model, PendingUpdate};
 use jams_proto::jams_v1::model_server_server::ModelServer;
 use jams_proto::jams_v1::{
     AddModelRequest, DeleteModelRequest, GetModelsResponse, PredictRequest, PredictResponse,
     UpdateModelRequest,
 };
+use std::collections::BTreeMap;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+use tonic::codegen::tokio_stream::{Stream, StreamExt};
+use tonic::metadata::{MetadataMap, MetadataValue};
 use tonic::{Request, Response, Status};
 
+/// The metadata key used by gRPC clients to propagate the deadline of a call.
+const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
 pub struct JamsService {
     app_state: Arc<AppState>,
 }
@@ -19,6 +49,163 @@ impl JamsService {
     pub fn new(app_state: Arc<AppState>) -> anyhow::Result<Self> {
         Ok(JamsService { app_state })
     }
+
+    /// Authorizes a call when authorization is enabled.
+    ///
+    /// # Errors
+    ///
+    /// * `Unauthenticated` - If the call has no valid bearer token.
+    /// * `PermissionDenied` - If the call is denied.
+    /// * `Unavailable` - If the policies could not be evaluated.
+    async fn authorize(
+        &self,
+        metadata: &MetadataMap,
+        model_name: Option<&str>,
+        action: Action,
+    ) -> Result<(), Status> {
+        let authorization = match &self.app_state.authorization {
+            Some(authorization) => authorization,
+            None => return Ok(()),
+        };
+        let principal = match authorization.authenticate(
+            metadata
+                .get(AUTHORIZATION_HEADER)
+                .and_then(|value| value.to_str().ok()),
+        ) {
+            Ok(principal) => principal,
+            Err(e) => {
+                return Err(Status::new(
+                    tonic::Code::Unauthenticated,
+                    format!("Failed to authenticate call ❌: {}", e),
+                ))
+            }
+        };
+        let request = AuthzRequest {
+            principal,
+            action,
+            model: model_name.map(|model_name| model_name.to_string()),
+            namespace: model_name
+                .and_then(|model_name| self.app_state.namespaces.namespace_of(model_name))
+                .map(|namespace| namespace.to_string()),
+        };
+        match authorization.authorize(&request).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Status::new(
+                tonic::Code::PermissionDenied,
+                format!(
+                    "{} is not allowed to {} {} ❌",
+                    request.principal,
+                    action.as_str(),
+                    model_name.unwrap_or("the server")
+                ),
+            )),
+            Err(e) => Err(Status::new(
+                tonic::Code::Unavailable,
+                format!("Failed to authorize call ❌: {}", e),
+            )),
+        }
+    }
+
+    /// Returns the `NotFound` status of a request naming a model which is not loaded, suggesting the closest loaded
+    /// model names when enabled, or `None` if the request failed for another reason.
+    fn model_not_found(&self, client_id: &str, error: &anyhow::Error) -> Option<Status> {
+        misses::model_not_found(&self.app_state.manager, GRPC, client_id, error)
+            .map(|error| Status::new(tonic::Code::NotFound, error))
+    }
+
+    /// Runs the postprocessors of the passthrough on the predictions of a request, if a passthrough is configured.
+    fn postprocess(
+        &self,
+        model_name: &str,
+        headers: &BTreeMap<String, String>,
+        output: String,
+    ) -> anyhow::Result<String> {
+        let passthrough = match &self.app_state.passthrough {
+            Some(passthrough) => passthrough,
+            None => return Ok(output),
+        };
+        let context = RequestContext {
+            model_name,
+            headers,
+        };
+        passthrough.postprocess(&context, output)
+    }
+
+    /// Returns the output of a prediction, or a signed URL to it if it is big enough to be written to the
+    /// result store.
+    async fn predict_response(
+        &self,
+        model_name: &str,
+        request_id: &str,
+        output: String,
+    ) -> Result<Response<PredictResponse>, Status> {
+        if let Some(result_store) = &self.app_state.result_store {
+            if result_store.should_store(output.as_str()) {
+                return match result_store.store(model_name, output).await {
+                    Ok(result) => Ok(self.sign(
+                        request_id,
+                        PredictResponse {
+                            output: String::new(),
+                            result_url: result.url,
+                            result_expires_at: result.expires_at,
+                        },
+                    )),
+                    Err(e) => Err(Status::new(
+                        tonic::Code::Internal,
+                        format!("Failed to store predictions ❌: {}", e),
+                    )),
+                };
+            }
+        }
+        Ok(self.sign(
+            request_id,
+            PredictResponse {
+                output,
+                ..Default::default()
+            },
+        ))
+    }
+
+    /// Wraps a prediction response, signing it in the `x-jams-signature` metadata key when response signing is
+    /// configured.
+    ///
+    /// The signed body is `grpc_body` of the response rather than its protobuf encoding, which older clients could
+    /// not reproduce once fields are added to `PredictResponse`. The signature is bound to the request ID, returned
+    /// in the `x-request-id` metadata key, and to the time it was signed, returned in `x-jams-signature-issued-at`.
+    fn sign(&self, request_id: &str, prediction: PredictResponse) -> Response<PredictResponse> {
+        let signer = match &self.app_state.response_signer {
+            Some(signer) => signer,
+            None => return Response::new(prediction),
+        };
+        let issued_at = issued_at();
+        let body = grpc_body(
+            prediction.output.as_str(),
+            prediction.result_url.as_str(),
+            prediction.result_expires_at.as_str(),
+        );
+        let signature = signer.sign(request_id, issued_at, &body);
+        let mut response = Response::new(prediction);
+        if let Ok(signature) = MetadataValue::try_from(signature.as_str()) {
+            response.metadata_mut().insert(SIGNATURE_HEADER, signature);
+        }
+        response
+            .metadata_mut()
+            .insert(SIGNATURE_ISSUED_AT_HEADER, MetadataValue::from(issued_at));
+        if let Ok(request_id) = MetadataValue::try_from(request_id) {
+            response
+                .metadata_mut()
+                .insert(REQUEST_ID_HEADER, request_id);
+        }
+        if let Some(key_id) = signer
+            .key_id()
+            .and_then(|key_id| MetadataValue::try_from(key_id).ok())
+        {
+            response
+                .metadata_mut()
+                .insert(SIGNATURE_KEY_ID_HEADER, key_id);
+        }
+        response
+    }
 }
 
 #[tonic::async_trait]
@@ -31,25 +218,314 @@ impl ModelServer for JamsService {
     #[tracing::instrument(skip(self, request))]
     async fn predict(
         &self,
-        request: Request<PredictRequest>,
+        mut request: Request<PredictRequest>,
     ) -> Result<Response<PredictResponse>, Status> {
+        if should_shed(self.app_state.memory_watermark_bytes, GRPC, "predict") {
+            return Err(Status::new(
+                tonic::Code::Unavailable,
+                "Failed to predict ❌: server is over its memory watermark",
+            ));
+        }
+
+        self.authorize(
+            request.metadata(),
+            Some(request.get_ref().model_name.as_str()),
+            Action::Predict,
+        )
+        .await?;
+
+        // the well-known metadata keys carry the options which are not part of the payload
+        let request_metadata = match RequestMetadata::parse(|name| {
+            request
+                .metadata()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        }) {
+            Ok(request_metadata) => request_metadata,
+            Err(e) => {
+                tracing::warn!("Rejected invalid request metadata ⚠️: {}", e);
+                return Err(Status::new(
+                    tonic::Code::InvalidArgument,
+                    format!("Failed to predict ❌: {}", e),
+                ));
+            }
+        };
+        request_metadata.attach_trace(&tracing::Span::current());
+        // the signature of the response is bound to the request ID
+        let request_id = request_id(
+            request
+                .metadata()
+                .get(REQUEST_ID_HEADER)
+                .and_then(|value| value.to_str().ok()),
+        );
+
+        // capture the configured metadata only when a passthrough is configured
+        let passthrough_headers = self
+            .app_state
+            .passthrough
+            .as_ref()
+            .map(|passthrough| {
+                passthrough.capture(|name| {
+                    request
+                        .metadata()
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                })
+            })
+            .unwrap_or_default();
+
+        // look up the features of the entities referenced by the request
+        if let Some(feature_store) = &self.app_state.feature_store {
+            let prediction_request = request.get_mut();
+            let input = std::mem::take(&mut prediction_request.input);
+            prediction_request.input = match feature_store
+                .enrich(prediction_request.model_name.as_str(), input)
+                .await
+            {
+                Ok(input) => input,
+                Err(e) => {
+                    tracing::warn!("Failed to enrich model input ⚠️: {:#}", e);
+                    let code = match feature_store::is_unavailable(&e) {
+                        true => tonic::Code::Unavailable,
+                        false => tonic::Code::InvalidArgument,
+                    };
+                    return Err(Status::new(code, format!("Failed to predict ❌: {:#}", e)));
+                }
+            };
+        }
+
+        // serve the duplicates of recent requests the prediction of the first request when deduplication is enabled,
+        // once the input is enriched so that differently enriched requests are not mistaken for duplicates
+        let dedup_lease = match &self.app_state.deduplicator {
+            Some(deduplicator) => {
+                let prediction_request = request.get_ref();
+                match deduplicator
+                    .lookup(
+                        prediction_request.model_name.as_str(),
+                        prediction_request.input.as_str(),
+                        prediction_request.predict_options.as_str(),
+                    )
+                    .await
+                {
+                    Lookup::Predict(lease) => Some(lease),
+                    Lookup::Duplicate(output) => {
+                        let model_name = prediction_request.model_name.as_str();
+                        let output = self
+                            .postprocess(model_name, &passthrough_headers, output)
+                            .map_err(postprocess_failed)?;
+                        return self
+                            .predict_response(model_name, request_id.as_str(), output)
+                            .await;
+                    }
+                }
+            }
+            None => None,
+        };
+
+        // reject models which are not loaded before they are queued, so that unknown model names never get a flow
+        if let Err(e) = self
+            .app_state
+            .manager
+            .check_model(request.get_ref().model_name.as_str())
+        {
+            let client_id = client_id(
+                request
+                    .metadata()
+                    .get(CLIENT_ID_HEADER)
+                    .and_then(|value| value.to_str().ok()),
+            );
+            return Err(self.model_not_found(client_id, &e).unwrap_or_else(|| {
+                Status::new(
+                    tonic::Code::Internal,
+                    format!("Failed to predict ❌: {}", e),
+                )
+            }));
+        }
+
+        // reject invalid inputs before they are queued when pre-admission validation is enabled
+        if let Some(schema_cache) = &self.app_state.schema_cache {
+            let client_id = client_id(
+                request
+                    .metadata()
+                    .get(CLIENT_ID_HEADER)
+                    .and_then(|value| value.to_str().ok()),
+            );
+            if let Err(e) = schema_cache.validate(client_id, request.get_ref().input.as_str()) {
+                tracing::warn!("Rejected invalid model input ⚠️: {}", e);
+                return Err(Status::new(
+                    tonic::Code::InvalidArgument,
+                    format!("Failed to predict ❌: {}", e),
+                ));
+            }
+        }
+
+        let mut predict_options =
+            match parse_predict_options(request.get_ref().predict_options.as_str()) {
+                Ok(predict_options) => predict_options,
+                Err(e) => {
+                    tracing::warn!("Rejected invalid prediction options ⚠️: {}", e);
+                    return Err(Status::new(
+                        tonic::Code::InvalidArgument,
+                        format!("Failed to predict ❌: {}", e),
+                    ));
+                }
+            };
+        request_metadata.apply(&mut predict_options);
+
+        // the permit is held until the prediction is served
+        let _permit =
+            match self.app_state.in_flight_limiter.admit(GRPC, "predict") {
+                Some(permit) => permit,
+                None => return Err(Status::new(
+                    tonic::Code::Unavailable,
+                    "Failed to predict ❌: server has reached its limit of in-flight predictions",
+                )),
+            };
+
+        let guard = DisconnectGuard::new(GRPC, "predict");
         let (tx, rx) = oneshot::channel();
 
+        // cancel the prediction if the deadline is exceeded or this handler is dropped
+        // because the client cancelled the call
+        let cancellation_token = CancellationToken::new();
+        let _cancel_on_drop = cancellation_token.clone().drop_guard();
+        let worker_cancellation_token = cancellation_token.clone();
+        let ticket = QueueTicket::default();
+        let worker_ticket = ticket.clone();
+
+        let timeout = parse_grpc_timeout(request.metadata());
+        // the client is kept to log the requests naming a model which is not loaded
+        let client = client_id(
+            request
+                .metadata()
+                .get(CLIENT_ID_HEADER)
+                .and_then(|value| value.to_str().ok()),
+        )
+        .to_string();
         let cpu_pool = &self.app_state.cpu_pool;
         let manager = Arc::clone(&self.app_state.manager);
         let prediction_request = request.into_inner();
         let model_name = prediction_request.model_name;
         let model_input = prediction_request.input;
+        let request_bytes = model_input.len();
 
-        cpu_pool.spawn(move || worker::predict_and_send(manager, model_name, model_input, tx));
+        // the request is only copied when predictions are being recorded
+        let recording = self
+            .app_state
+            .prediction_recorder
+            .as_ref()
+            .map(|recorder| (recorder, model_name.clone(), model_input.clone()));
+        let start = Instant::now();
 
-        match rx.await {
-            Ok(predictions) => match predictions {
-                Ok(output) => Ok(Response::new(PredictResponse { output })),
-                Err(e) => Err(Status::new(
-                    tonic::Code::Internal,
+        let worker_model_name = model_name.clone();
+        let priority = manager.priority_of(model_name.as_str(), &predict_options);
+        let weight = manager.scheduling_weight_of(model_name.as_str());
+        let task = Task::new(move || {
+            // the prediction was withdrawn after waiting too long for a worker
+            if !worker_ticket.claim() {
+                return;
+            }
+            worker::predict_and_send(
+                manager,
+                worker_model_name,
+                model_input,
+                predict_options,
+                tx,
+                worker_cancellation_token,
+            )
+        })
+        .with_priority(priority)
+        .with_flow(model_name.as_str(), weight)
+        .with_cancellation_token(cancellation_token.clone());
+        if let Err(e) = self
+            .app_state
+            .namespaces
+            .spawn(cpu_pool, model_name.as_str(), task)
+        {
+            guard.disarm();
+            return Err(Status::new(
+                tonic::Code::ResourceExhausted,
+                format!("Failed to predict ❌: {}", e),
+            ));
+        }
+
+        let wait = self
+            .app_state
+            .in_flight_limiter
+            .wait(rx, &ticket, GRPC, "predict");
+        let result = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, wait).await {
+                Ok(result) => result,
+                Err(_) => {
+                    guard.disarm();
+                    cancellation_token.cancel();
+                    DEADLINES_EXCEEDED_TOTAL
+                        .with_label_values(&[GRPC, "predict"])
+                        .inc();
+                    tracing::warn!("Deadline of {:?} exceeded for predict ⚠️", timeout);
+                    return Err(Status::new(
+                        tonic::Code::DeadlineExceeded,
+                        format!("Failed to predict ❌: deadline of {:?} exceeded", timeout),
+                    ));
+                }
+            },
+            None => wait.await,
+        };
+        let result = match result {
+            Ok(result) => result,
+            Err(e) => {
+                guard.disarm();
+                return Err(Status::new(
+                    tonic::Code::Unavailable,
                     format!("Failed to predict ❌: {}", e),
-                )),
+                ));
+            }
+        };
+        guard.disarm();
+
+        match result {
+            Ok(predictions) => match predictions {
+                Ok(ServedPrediction { output, precision }) => {
+                    record_payload_sizes(GRPC, model_name.as_str(), request_bytes, output.len());
+                    if let Some(lease) = dedup_lease {
+                        lease.complete(output.as_str());
+                    }
+                    let output = self
+                        .postprocess(model_name.as_str(), &passthrough_headers, output)
+                        .map_err(postprocess_failed)?;
+                    if let Some((recorder, model_name, model_input)) = recording {
+                        recorder.record(PredictionRecord::new(
+                            model_name,
+                            model_input,
+                            output.clone(),
+                            start.elapsed(),
+                            passthrough_headers,
+                        ));
+                    }
+                    let mut response = self
+                        .predict_response(model_name.as_str(), request_id.as_str(), output)
+                        .await?;
+                    // name the reduced precision variant which served the prediction, see `MODEL_VARIANT_HEADER`
+                    if precision != ModelPrecision::Fp32 {
+                        response.metadata_mut().insert(
+                            MODEL_VARIANT_HEADER,
+                            MetadataValue::from_static(precision.as_str()),
+                        );
+                    }
+                    Ok(response)
+                }
+                Err(e) => Err(self
+                    .model_not_found(client.as_str(), &e)
+                    .unwrap_or_else(|| {
+                        let code = match e.downcast_ref::<jams_core::Error>() {
+                            Some(jams_core::Error::VersionMismatch(_)) => {
+                                tonic::Code::FailedPrecondition
+                            }
+                            Some(jams_core::Error::Input(_)) => tonic::Code::InvalidArgument,
+                            _ => tonic::Code::Internal,
+                        };
+                        Status::new(code, format!("Failed to predict ❌: {}", e))
+                    })),
             },
             Err(e) => Err(Status::new(
                 tonic::Code::Internal,
@@ -58,11 +534,146 @@ impl ModelServer for JamsService {
         }
     }
 
-    #[tracing::instrument(skip(self, _request))]
+    type PredictStreamStream =
+        Pin<Box<dyn Stream<Item = Result<PredictResponse, Status>> + Send + 'static>>;
+
+    #[tracing::instrument(skip(self, request))]
+    async fn predict_stream(
+        &self,
+        request: Request<PredictRequest>,
+    ) -> Result<Response<Self::PredictStreamStream>, Status> {
+        if should_shed(
+            self.app_state.memory_watermark_bytes,
+            GRPC,
+            "predict_stream",
+        ) {
+            return Err(Status::new(
+                tonic::Code::Unavailable,
+                "Failed to predict ❌: server is over its memory watermark",
+            ));
+        }
+
+        self.authorize(
+            request.metadata(),
+            Some(request.get_ref().model_name.as_str()),
+            Action::Predict,
+        )
+        .await?;
+
+        // the well-known metadata keys carry the options which are not part of the payload
+        let request_metadata = match RequestMetadata::parse(|name| {
+            request
+                .metadata()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        }) {
+            Ok(request_metadata) => request_metadata,
+            Err(e) => {
+                tracing::warn!("Rejected invalid request metadata ⚠️: {}", e);
+                return Err(Status::new(
+                    tonic::Code::InvalidArgument,
+                    format!("Failed to predict ❌: {}", e),
+                ));
+            }
+        };
+        request_metadata.attach_trace(&tracing::Span::current());
+        let model_name = request.get_ref().model_name.as_str();
+        if let Err(e) = request_metadata.check_model_version(model_name, || {
+            self.app_state
+                .manager
+                .get_model_metadata(model_name.to_string())
+                .map(|metadata| metadata.artifact_sha256)
+        }) {
+            return Err(Status::new(
+                tonic::Code::FailedPrecondition,
+                format!("Failed to predict ❌: {}", e),
+            ));
+        }
+
+        // reject models which return their whole output at once before the prediction is queued
+        if let Err(e) = self.app_state.manager.check_streaming(model_name) {
+            let client_id = client_id(
+                request
+                    .metadata()
+                    .get(CLIENT_ID_HEADER)
+                    .and_then(|value| value.to_str().ok()),
+            );
+            return Err(self.model_not_found(client_id, &e).unwrap_or_else(|| {
+                Status::new(
+                    tonic::Code::InvalidArgument,
+                    format!("Failed to predict ❌: {}", e),
+                )
+            }));
+        }
+
+        let mut predict_options =
+            match parse_predict_options(request.get_ref().predict_options.as_str()) {
+                Ok(predict_options) => predict_options,
+                Err(e) => {
+                    tracing::warn!("Rejected invalid prediction options ⚠️: {}", e);
+                    return Err(Status::new(
+                        tonic::Code::InvalidArgument,
+                        format!("Failed to predict ❌: {}", e),
+                    ));
+                }
+            };
+        request_metadata.apply(&mut predict_options);
+
+        // the permit is held until the stream is dropped
+        let permit =
+            match self
+                .app_state
+                .in_flight_limiter
+                .admit(GRPC, "predict_stream")
+            {
+                Some(permit) => permit,
+                None => return Err(Status::new(
+                    tonic::Code::Unavailable,
+                    "Failed to predict ❌: server has reached its limit of in-flight predictions",
+                )),
+            };
+
+        let prediction_request = request.into_inner();
+        let chunks = match spawn_prediction_stream(
+            &self.app_state,
+            prediction_request.model_name,
+            prediction_request.input,
+            predict_options,
+            permit,
+        ) {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                return Err(Status::new(
+                    tonic::Code::ResourceExhausted,
+                    format!("Failed to predict ❌: {}", e),
+                ))
+            }
+        };
+
+        // an error ends the stream after the chunks which were already generated
+        let responses = chunks.map(|chunk| match chunk {
+            Ok(output) => Ok(PredictResponse {
+                output,
+                ..Default::default()
+            }),
+            Err(e) => {
+                tracing::error!("Failed to stream predictions ❌: {}", e);
+                Err(Status::new(
+                    tonic::Code::Internal,
+                    format!("Failed to predict ❌: {}", e),
+                ))
+            }
+        });
+        Ok(Response::new(Box::pin(responses)))
+    }
+
+    #[tracing::instrument(skip(self, request))]
     async fn get_models(
         &self,
-        _request: Request<()>,
+        request: Request<()>,
     ) -> Result<Response<GetModelsResponse>, Status> {
+        self.authorize(request.metadata(), None, Action::Read)
+            .await?;
         match self.app_state.manager.get_models() {
             Ok(models) => Ok(Response::new(GetModelsResponse {
                 total: models.len() as i32,
@@ -77,6 +688,12 @@ impl ModelServer for JamsService {
 
     #[tracing::instrument(skip(self, request))]
     async fn add_model(&self, request: Request<AddModelRequest>) -> Result<Response<()>, Status> {
+        self.authorize(
+            request.metadata(),
+            Some(request.get_ref().model_name.as_str()),
+            Action::Write,
+        )
+        .await?;
         let add_model_request = request.into_inner();
         match self
             .app_state
@@ -97,6 +714,12 @@ impl ModelServer for JamsService {
         &self,
         request: Request<UpdateModelRequest>,
     ) -> Result<Response<()>, Status> {
+        self.authorize(
+            request.metadata(),
+            Some(request.get_ref().model_name.as_str()),
+            Action::Write,
+        )
+        .await?;
         match self
             .app_state
             .manager
@@ -116,6 +739,12 @@ impl ModelServer for JamsService {
         &self,
         request: Request<DeleteModelRequest>,
     ) -> Result<Response<()>, Status> {
+        self.authorize(
+            request.metadata(),
+            Some(request.get_ref().model_name.as_str()),
+            Action::Write,
+        )
+        .await?;
         match self
             .app_state
             .manager
@@ -139,28 +768,130 @@ fn parse_to_proto_models(models_metadata: Vec<Metadata>) -> Vec<Model> {
             framework: data.framework.to_string(),
             path: data.path,
             last_updated: data.last_updated,
+            artifact_sha256: data.artifact_sha256,
+            source_uri: data.source_uri,
+            source_version: data.source_version.unwrap_or_default(),
+            labels: data.labels.into_iter().collect(),
+            predict_options: match data.predict_options.is_empty() {
+                true => String::new(),
+                false => serde_json::to_string(&data.predict_options).unwrap_or_default(),
+            },
+            pending_update: data.pending_update.map(|pending| PendingUpdate {
+                source_version: pending.source_version.unwrap_or_default(),
+                detected_at: pending.detected_at,
+            }),
+            request_count: data.request_count,
+            last_accessed: data.last_accessed.unwrap_or_default(),
+            requirements: match data.requirements.is_empty() {
+                true => String::new(),
+                false => serde_json::to_string(&data.requirements).unwrap_or_default(),
+            },
+            variants: data
+                .variants
+                .iter()
+                .map(|precision| precision.to_string())
+                .collect(),
+            init: data.init.map(|status| HookStatus {
+                state: status.state.as_str().to_string(),
+                duration_ms: status.duration_ms,
+                error: status.error.unwrap_or_default(),
+                finished_at: status.finished_at,
+            }),
         })
     }
 
     out
 }
 
+/// Maps the failure of a postprocessor to an `Internal` status.
+fn postprocess_failed(e: anyhow::Error) -> Status {
+    tracing::error!("Failed to postprocess predictions ❌: {}", e);
+    Status::new(
+        tonic::Code::Internal,
+        format!("Failed to postprocess predictions ❌: {}", e),
+    )
+}
+
+/// Parses the prediction options sent as a JSON string. An empty string means no options.
+fn parse_predict_options(predict_options: &str) -> anyhow::Result<PredictOptions> {
+    match predict_options.trim().is_empty() {
+        true => Ok(PredictOptions::default()),
+        false => PredictOptions::parse(predict_options),
+    }
+}
+
+/// Parses the `grpc-timeout` metadata sent by the client into a `Duration`.
+///
+/// The value is made up of at most 8 ASCII digits followed by a unit, where the unit is one of
+/// `H` (hours), `M` (minutes), `S` (seconds), `m` (milliseconds), `u` (microseconds) or `n` (nanoseconds).
+///
+/// # Arguments
+/// * `metadata` - The metadata of the incoming request.
+///
+/// # Returns
+/// * `Some(Duration)` - If a valid timeout is present.
+/// * `None` - If no timeout was sent or the value is malformed.
+pub fn parse_grpc_timeout(metadata: &MetadataMap) -> Option<Duration> {
+    let value = metadata.get(GRPC_TIMEOUT_HEADER)?.to_str().ok()?;
+    if value.len() < 2 || value.len() > 9 {
+        tracing::warn!("Ignoring malformed grpc-timeout value {} ⚠️", value);
+        return None;
+    }
+
+    let (amount, unit) = value.split_at(value.len() - 1);
+    let amount: u64 = match amount.parse() {
+        Ok(amount) => amount,
+        Err(_) => {
+            tracing::warn!("Ignoring malformed grpc-timeout value {} ⚠️", value);
+            return None;
+        }
+    };
+
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 60 * 60)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => {
+            tracing::warn!("Ignoring malformed grpc-timeout value {} ⚠️", value);
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::admission::InFlightLimiter;
+    use crate::common::bulk::BulkOperations;
+    use crate::common::namespace::Namespaces;
+    use crate::common::signing::{verify, ResponseSigner, ResponseSigningConfig};
     use chrono::Utc;
     use jams_core::manager::ManagerBuilder;
     use jams_core::model::frameworks::TENSORFLOW;
+    use jams_core::model::lifecycle::{self, HookState};
+    use jams_core::model::predict::PredictMode;
+    use jams_core::model::requirements::ModelRequirements;
+    use jams_core::model::variant::ModelPrecision;
     use jams_core::model_store::local::filesystem::LocalModelStore;
-    use jams_core::model_store::storage::Metadata;
+    use jams_core::model_store::storage::{self, Metadata};
     use jams_core::model_store::ModelStore;
-    use rayon::ThreadPoolBuilder;
+    use jams_core::pool::executor::ExecutorKind;
+    use jams_core::pool::scheduler::{Budget, Scheduler};
+    use std::collections::BTreeMap;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
 
-    async fn setup_shared_state() -> Arc<AppState> {
-        let cpu_pool = ThreadPoolBuilder::new()
-            .num_threads(1)
-            .build()
-            .expect("Failed to build rayon threadpool ❌");
+    async fn setup_shared_state(response_signer: Option<ResponseSigner>) -> Arc<AppState> {
+        let cpu_pool = Scheduler::new(
+            "shared",
+            ExecutorKind::Rayon
+                .build(1, "jams-worker")
+                .expect("Failed to build rayon threadpool ❌"),
+            Budget::default(),
+        );
 
         let model_store = LocalModelStore::new("tests/model_store".to_string())
             .await
@@ -172,13 +903,31 @@ mod tests {
                 .expect("Failed to initialize manager ❌"),
         );
 
-        Arc::new(AppState { manager, cpu_pool })
+        Arc::new(AppState {
+            manager,
+            cpu_pool,
+            memory_watermark_bytes: None,
+            in_flight_limiter: InFlightLimiter::default(),
+            prediction_recorder: None,
+            namespaces: Namespaces::default(),
+            schema_cache: None,
+            deep_health: None,
+            feature_store: None,
+            result_store: None,
+            deduplicator: None,
+            authorization: None,
+            passthrough: None,
+            smoke_test: None,
+            bulk_operations: BulkOperations::default(),
+            startup_report: None,
+            response_signer,
+        })
     }
 
     #[tokio::test]
     async fn successfully_create_jams_service() {
         // Arrange
-        let shared_state = setup_shared_state().await;
+        let shared_state = setup_shared_state(None).await;
 
         // Act
         let service = JamsService::new(shared_state);
@@ -187,6 +936,58 @@ mod tests {
         assert!(service.is_ok())
     }
 
+    #[tokio::test]
+    async fn successfully_sign_prediction_response_bound_to_the_request_id() {
+        // Arrange
+        let mut key_file = NamedTempFile::new().expect("Failed to create key file ❌");
+        key_file
+            .write_all("ab".repeat(32).as_bytes())
+            .expect("Failed to write key file ❌");
+        let response_signer = ResponseSigner::new(ResponseSigningConfig {
+            key_file: Some(key_file.path().to_string_lossy().to_string()),
+            key_id: Some("test".to_string()),
+            ..ResponseSigningConfig::default()
+        })
+        .expect("Failed to create response signer ❌");
+        let service = JamsService::new(setup_shared_state(Some(response_signer)).await)
+            .expect("Failed to create jams service ❌");
+        let output = r#"{"predictions":[[0.5]]}"#.to_string();
+
+        // Act
+        let response = service
+            .predict_response("titanic_model", "request-1", output.clone())
+            .await
+            .expect("Failed to build predict response ❌");
+
+        // Assert
+        let metadata = response.metadata();
+        let signature = metadata.get(SIGNATURE_HEADER).unwrap().to_str().unwrap();
+        let issued_at: u64 = metadata
+            .get(SIGNATURE_ISSUED_AT_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let body = grpc_body(output.as_str(), "", "");
+        assert_eq!(metadata.get(REQUEST_ID_HEADER).unwrap(), "request-1");
+        assert_eq!(metadata.get(SIGNATURE_KEY_ID_HEADER).unwrap(), "test");
+        assert!(verify(
+            &[0xab; 32],
+            "request-1",
+            issued_at,
+            &body,
+            signature
+        ));
+        assert!(!verify(
+            &[0xab; 32],
+            "request-2",
+            issued_at,
+            &body,
+            signature
+        ));
+    }
+
     #[test]
     fn successfully_parse_to_proto_models() {
         // Arrange
@@ -197,12 +998,57 @@ mod tests {
                 framework: TENSORFLOW,
                 path: "some_path_1".to_string(),
                 last_updated: now.to_rfc3339(),
+                artifact_sha256: "abc123".to_string(),
+                source_uri: "s3://models/tensorflow-my_model_1.tar.gz".to_string(),
+                source_version: Some("v1".to_string()),
+                labels: BTreeMap::from([("git_sha".to_string(), "4f2a9c1".to_string())]),
+                predict_options: PredictOptions {
+                    mode: Some(PredictMode::RawScore),
+                    num_iteration: None,
+                    precision: None,
+                    unknown_features: None,
+                    max_batch_size: None,
+                    parallel_batches: None,
+                    variant: None,
+                    priority: None,
+                    latency_budget_ms: None,
+                    model_version: None,
+                },
+                requirements: ModelRequirements {
+                    gpu: true,
+                    cpu_features: vec![],
+                    min_memory_mb: None,
+                },
+                variants: vec![ModelPrecision::Int8],
+                pending_update: None,
+                request_count: 0,
+                last_accessed: None,
+                init: None,
             },
             Metadata {
                 name: "my_model_2".to_string(),
                 framework: TENSORFLOW,
                 path: "some_path_2".to_string(),
                 last_updated: now.to_rfc3339(),
+                artifact_sha256: "def456".to_string(),
+                source_uri: "".to_string(),
+                source_version: None,
+                labels: BTreeMap::new(),
+                predict_options: PredictOptions::default(),
+                requirements: ModelRequirements::default(),
+                variants: vec![],
+                pending_update: Some(storage::PendingUpdate {
+                    source_version: Some("v2".to_string()),
+                    detected_at: now.to_rfc3339(),
+                }),
+                request_count: 3,
+                last_accessed: Some(now.to_rfc3339()),
+                init: Some(lifecycle::HookStatus {
+                    state: HookState::Succeeded,
+                    duration_ms: 120,
+                    error: None,
+                    finished_at: now.to_rfc3339(),
+                }),
             },
         ];
 
@@ -219,6 +1065,119 @@ mod tests {
                 proto_models[i].last_updated,
                 models_metadata[i].last_updated
             );
+            assert_eq!(
+                proto_models[i].artifact_sha256,
+                models_metadata[i].artifact_sha256
+            );
+            assert_eq!(proto_models[i].source_uri, models_metadata[i].source_uri);
+            assert_eq!(
+                proto_models[i].source_version,
+                models_metadata[i]
+                    .source_version
+                    .clone()
+                    .unwrap_or_default()
+            );
+            assert_eq!(
+                proto_models[i].labels.len(),
+                models_metadata[i].labels.len()
+            );
+            assert_eq!(
+                proto_models[i].request_count,
+                models_metadata[i].request_count
+            );
+        }
+        assert!(proto_models[0].last_accessed.is_empty());
+        assert_eq!(proto_models[1].last_accessed, now.to_rfc3339());
+        assert_eq!(proto_models[0].predict_options, r#"{"mode":"raw_score"}"#);
+        assert!(proto_models[1].predict_options.is_empty());
+        assert_eq!(proto_models[0].requirements, r#"{"gpu":true}"#);
+        assert!(proto_models[1].requirements.is_empty());
+        assert_eq!(proto_models[0].variants, vec!["int8".to_string()]);
+        assert!(proto_models[1].variants.is_empty());
+        assert!(proto_models[0].pending_update.is_none());
+        assert_eq!(
+            proto_models[1].pending_update,
+            Some(PendingUpdate {
+                source_version: "v2".to_string(),
+                detected_at: now.to_rfc3339(),
+            })
+        );
+        assert!(proto_models[0].init.is_none());
+        assert_eq!(
+            proto_models[1].init,
+            Some(HookStatus {
+                state: "succeeded".to_string(),
+                duration_ms: 120,
+                error: String::new(),
+                finished_at: now.to_rfc3339(),
+            })
+        );
+    }
+
+    #[test]
+    fn successfully_parse_predict_options() {
+        // Act
+        let empty = parse_predict_options("");
+        let leaf_index = parse_predict_options(r#"{"mode": "leaf_index"}"#);
+        let invalid = parse_predict_options(r#"{"mode": "embedding"}"#);
+
+        // Assert
+        assert_eq!(empty.unwrap(), PredictOptions::default());
+        assert_eq!(leaf_index.unwrap().mode, Some(PredictMode::LeafIndex));
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn successfully_parse_grpc_timeout() {
+        // Arrange
+        let mut metadata = MetadataMap::new();
+        metadata.insert(GRPC_TIMEOUT_HEADER, "250m".parse().unwrap());
+
+        // Act
+        let timeout = parse_grpc_timeout(&metadata);
+
+        // Assert
+        assert_eq!(timeout, Some(Duration::from_millis(250)))
+    }
+
+    #[test]
+    fn successfully_parse_grpc_timeout_for_all_units() {
+        let cases = vec![
+            ("1H", Duration::from_secs(3600)),
+            ("2M", Duration::from_secs(120)),
+            ("3S", Duration::from_secs(3)),
+            ("4m", Duration::from_millis(4)),
+            ("5u", Duration::from_micros(5)),
+            ("6n", Duration::from_nanos(6)),
+        ];
+
+        for (value, expected) in cases {
+            let mut metadata = MetadataMap::new();
+            metadata.insert(GRPC_TIMEOUT_HEADER, value.parse().unwrap());
+
+            assert_eq!(parse_grpc_timeout(&metadata), Some(expected))
         }
     }
+
+    #[test]
+    fn fails_to_parse_grpc_timeout_when_value_is_malformed() {
+        for value in ["", "m", "10x", "abcS", "123456789S"] {
+            let mut metadata = MetadataMap::new();
+            metadata.insert(GRPC_TIMEOUT_HEADER, value.parse().unwrap());
+
+            assert!(parse_grpc_timeout(&metadata).is_none())
+        }
+    }
+
+    #[test]
+    fn successfully_returns_none_when_grpc_timeout_is_not_set() {
+        // Arrange
+        let metadata = MetadataMap::new();
+
+        // Act
+        let timeout = parse_grpc_timeout(&metadata);
+
+        // Assert
+        assert!(timeout.is_none())
+    }
 }
@@ -0,0 +1,372 @@
+use crate::common::authz::{Action, AuthzRequest, AUTHORIZATION_HEADER};
+use crate::common::metadata::{request_id, REQUEST_ID_HEADER};
+use crate::common::metrics;
+use crate::common::signing::{
+    issued_at, SIGNATURE_HEADER, SIGNATURE_ISSUED_AT_HEADER, SIGNATURE_KEY_ID_HEADER,
+};
+use crate::common::state::AppState;
+use crate::http::service::ErrorResponse;
+use axum::body::Body;
+use axum::extract::{FromRequestParts, MatchedPath, Query, RawPathParams, Request, State};
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use http_body_util::LengthLimitError;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Maximum size of the request body which will be buffered. This matches the default limit
+/// used by axum for the `Json` extractor.
+const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Requests whose body takes longer than this to be read are logged as slow clients.
+const SLOW_CLIENT_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// Prefix of the unversioned API routes, which are deprecated in favour of the versioned routes.
+const UNVERSIONED_API_PREFIX: &str = "/api";
+
+/// Prefix of the latest version of the API routes.
+const LATEST_API_PREFIX: &str = "/api/v1";
+
+/// Middleware which tracks connection level metrics for a request.
+///
+/// The request body is read completely before the handler is invoked so that the time spent
+/// waiting for the client, as well as the size of the payload, can be recorded. Reading the body
+/// up front means slow clients are held on the async runtime and never occupy a worker slot
+/// in the rayon threadpool.
+///
+/// # Arguments
+///
+/// * `request` - The incoming request.
+/// * `next` - The next layer in the middleware stack.
+///
+/// # Returns
+///
+/// * `Response` - The response from the handler, or an error response if the body could not be read.
+pub async fn track_request_body(request: Request, next: Next) -> Response {
+    let path = match request.extensions().get::<MatchedPath>() {
+        Some(path) => path.as_str().to_string(),
+        None => request.uri().path().to_string(),
+    };
+
+    let (parts, body) = request.into_parts();
+
+    let start = Instant::now();
+    let bytes = match axum::body::to_bytes(body, MAX_BODY_SIZE).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let e = e.into_inner();
+            let status = if e.is::<LengthLimitError>() {
+                StatusCode::PAYLOAD_TOO_LARGE
+            } else {
+                // the body stream can only fail midway if the client went away
+                metrics::CLIENT_DISCONNECTS_TOTAL
+                    .with_label_values(&[metrics::HTTP, path.as_str()])
+                    .inc();
+                StatusCode::BAD_REQUEST
+            };
+            tracing::warn!(
+                "Failed to read request body for {} ⚠️: {}",
+                path,
+                e.to_string()
+            );
+            return (
+                status,
+                Json(ErrorResponse {
+                    error: format!("Failed to read request body ❌: {}", e),
+                }),
+            )
+                .into_response();
+        }
+    };
+    let elapsed = start.elapsed();
+
+    metrics::REQUEST_BODY_READ_SECONDS
+        .with_label_values(&[metrics::HTTP, path.as_str()])
+        .observe(elapsed.as_secs_f64());
+    metrics::REQUEST_PAYLOAD_BYTES
+        .with_label_values(&[metrics::HTTP, path.as_str()])
+        .observe(bytes.len() as f64);
+
+    if elapsed > SLOW_CLIENT_THRESHOLD {
+        tracing::warn!(
+            "Slow client detected for {} ⚠️: read {} bytes in {:?}",
+            path,
+            bytes.len(),
+            elapsed
+        );
+        metrics::SLOW_CLIENTS_TOTAL
+            .with_label_values(&[metrics::HTTP, path.as_str()])
+            .inc();
+    }
+
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+/// Middleware which marks the unversioned API routes as deprecated.
+///
+/// The unversioned routes keep working as before, but their responses carry a `Deprecation` header
+/// and a `Link` header pointing at the equivalent versioned route, so that clients can migrate before
+/// the unversioned routes are removed. Requests to the unversioned routes are counted to track the migration.
+///
+/// # Arguments
+///
+/// * `request` - The incoming request.
+/// * `next` - The next layer in the middleware stack.
+///
+/// # Returns
+///
+/// * `Response` - The response from the handler with the deprecation headers added.
+pub async fn deprecate_unversioned_route(request: Request, next: Next) -> Response {
+    let path = match request.extensions().get::<MatchedPath>() {
+        Some(path) => path.as_str().to_string(),
+        None => request.uri().path().to_string(),
+    };
+
+    metrics::DEPRECATED_REQUESTS_TOTAL
+        .with_label_values(&[path.as_str()])
+        .inc();
+
+    let mut response = next.run(request).await;
+    let successor = match path.strip_prefix(UNVERSIONED_API_PREFIX) {
+        Some(route) => format!("{}{}", LATEST_API_PREFIX, route),
+        None => LATEST_API_PREFIX.to_string(),
+    };
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor))
+    {
+        headers.insert(header::LINK, link);
+    }
+    response
+}
+
+/// Middleware which signs the JSON bodies of the prediction responses when response signing is configured.
+///
+/// The response body is buffered and the HMAC of its canonical form, see `signing::canonical_form`, is returned in
+/// the `x-jams-signature` header, along with the time it was signed in the `x-jams-signature-issued-at` header and
+/// the ID of the key in the `x-jams-signature-key-id` header if it is configured. The signature binds the body to
+/// the `x-request-id` of the request, which is generated if the client did not send one and returned with the
+/// response, so that a response cannot be replayed for another request. Streamed responses, e.g. the events of
+/// `/api/v1/predict/stream`, and responses without a body are not signed.
+///
+/// # Arguments
+///
+/// * `state` - The application state holding the response signer.
+/// * `request` - The incoming request.
+/// * `next` - The next layer in the middleware stack.
+///
+/// # Returns
+///
+/// * `Response` - The response from the handler with the signature headers added.
+pub async fn sign_response(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let signer = match &state.response_signer {
+        Some(signer) => signer,
+        None => return next.run(request).await,
+    };
+    // the handler answers with the same request ID as the one which is signed
+    let request_id = request_id(
+        request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
+    if let Ok(value) = HeaderValue::from_str(request_id.as_str()) {
+        request.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    // the body was already serialized into memory by the handler, hence it is not limited again
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to read response body for signing ❌: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to sign response ❌: {}", e),
+                }),
+            )
+                .into_response();
+        }
+    };
+    let issued_at = issued_at();
+    if let Ok(signature) =
+        HeaderValue::from_str(signer.sign(&request_id, issued_at, &bytes).as_str())
+    {
+        parts.headers.insert(SIGNATURE_HEADER, signature);
+    }
+    parts
+        .headers
+        .insert(SIGNATURE_ISSUED_AT_HEADER, HeaderValue::from(issued_at));
+    if let Ok(request_id) = HeaderValue::from_str(request_id.as_str()) {
+        parts.headers.insert(REQUEST_ID_HEADER, request_id);
+    }
+    if let Some(key_id) = signer
+        .key_id()
+        .and_then(|key_id| HeaderValue::from_str(key_id).ok())
+    {
+        parts.headers.insert(SIGNATURE_KEY_ID_HEADER, key_id);
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Middleware which authorizes the calls to the predict endpoints.
+///
+/// # Arguments
+///
+/// * `state` - The application state holding the authorization.
+/// * `request` - The incoming request.
+/// * `next` - The next layer in the middleware stack.
+///
+/// # Returns
+///
+/// * `Response` - The response from the handler, or an error response if the call is not allowed.
+pub async fn authorize_predict(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    authorize(state, Action::Predict, request, next).await
+}
+
+/// Middleware which authorizes the calls to the admin endpoints. `GET` calls read and every other call writes.
+///
+/// # Arguments
+///
+/// * `state` - The application state holding the authorization.
+/// * `request` - The incoming request.
+/// * `next` - The next layer in the middleware stack.
+///
+/// # Returns
+///
+/// * `Response` - The response from the handler, or an error response if the call is not allowed.
+pub async fn authorize_admin(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let action = if request.method() == Method::GET {
+        Action::Read
+    } else {
+        Action::Write
+    };
+    authorize(state, action, request, next).await
+}
+
+/// Authorizes a call, returning 401 if it has no valid bearer token, 403 if it is denied and 503 if the policies
+/// could not be evaluated.
+///
+/// The model of the call is read from the `model_name` path parameter, query parameter or JSON body field.
+/// The body has already been buffered by `track_request_body`, so it is read again from memory.
+async fn authorize(state: Arc<AppState>, action: Action, request: Request, next: Next) -> Response {
+    let authorization = match &state.authorization {
+        Some(authorization) => authorization,
+        None => return next.run(request).await,
+    };
+
+    let (mut parts, body) = request.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_BODY_SIZE).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("Failed to read request body ❌: {}", e),
+                }),
+            )
+                .into_response()
+        }
+    };
+
+    let path_model = RawPathParams::from_request_parts(&mut parts, &())
+        .await
+        .ok()
+        .and_then(|params| {
+            params
+                .iter()
+                .find(|(key, _)| *key == "model_name")
+                .map(|(_, value)| value.to_string())
+        });
+    let model = path_model
+        .or_else(|| {
+            Query::<HashMap<String, String>>::try_from_uri(&parts.uri)
+                .ok()
+                .and_then(|Query(mut query)| query.remove("model_name"))
+        })
+        .or_else(|| {
+            serde_json::from_slice::<serde_json::Value>(&bytes)
+                .ok()
+                .and_then(|body| body["model_name"].as_str().map(|name| name.to_string()))
+        });
+
+    let principal = match authorization.authenticate(
+        parts
+            .headers
+            .get(AUTHORIZATION_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    ) {
+        Ok(principal) => principal,
+        Err(e) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse {
+                    error: format!("Failed to authenticate call ❌: {}", e),
+                }),
+            )
+                .into_response()
+        }
+    };
+    let namespace = model
+        .as_deref()
+        .and_then(|model| state.namespaces.namespace_of(model))
+        .map(|namespace| namespace.to_string());
+    let authz_request = AuthzRequest {
+        principal,
+        action,
+        model,
+        namespace,
+    };
+
+    match authorization.authorize(&authz_request).await {
+        Ok(true) => {
+            next.run(Request::from_parts(parts, Body::from(bytes)))
+                .await
+        }
+        Ok(false) => (
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: format!(
+                    "{} is not allowed to {} {} ❌",
+                    authz_request.principal,
+                    action.as_str(),
+                    authz_request.model.as_deref().unwrap_or("the server")
+                ),
+            }),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: format!("Failed to authorize call ❌: {}", e),
+            }),
+        )
+            .into_response(),
+    }
+}
@@ -1,3 +1,5 @@
+pub mod middleware;
 pub mod router;
 pub mod server;
 pub mod service;
+pub mod v1;
@@ -1,42 +1,168 @@
 use crate::common::state::AppState;
+use crate::http::middleware::{
+    authorize_admin, authorize_predict, deprecate_unversioned_route, sign_response,
+    track_request_body,
+};
 use crate::http::service::{
-    add_model, delete_model, get_models, healthcheck, predict, update_model,
+    add_model, attach_store, deep_healthcheck, delete_model, delete_models, detach_store,
+    get_bulk_operation, get_model_diff, get_model_misses, get_model_sample, get_models, get_stores,
+    healthcheck, metrics, predict, readiness, refresh_models, startup_report, update_model,
+    update_model_labels, version,
 };
+use crate::http::v1;
+use axum::middleware;
 use axum::routing::{delete, get, post, put};
 use axum::Router;
 use std::sync::Arc;
 
+/// Builds the router serving every endpoint, used when the admin endpoints share the public port.
 pub fn build_router(shared_state: Arc<AppState>) -> anyhow::Result<Router> {
-    // API routes
+    Ok(health_routes()
+        .merge(predict_routes(&shared_state))
+        .merge(admin_routes(&shared_state))
+        .with_state(shared_state))
+}
+
+/// Builds the router of the public port when the admin endpoints are served on a separate port.
+/// It serves the predict and health check endpoints.
+pub fn build_public_router(shared_state: Arc<AppState>) -> anyhow::Result<Router> {
+    Ok(health_routes()
+        .merge(predict_routes(&shared_state))
+        .with_state(shared_state))
+}
+
+/// Builds the router of the admin port. It serves the model management, metrics and health check endpoints.
+pub fn build_admin_router(shared_state: Arc<AppState>) -> anyhow::Result<Router> {
+    Ok(health_routes()
+        .merge(admin_routes(&shared_state))
+        .with_state(shared_state))
+}
+
+fn health_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/healthcheck", get(healthcheck))
+        .route("/healthz/deep", get(deep_healthcheck))
+        .route("/readyz", get(readiness))
+}
+
+fn predict_routes(shared_state: &Arc<AppState>) -> Router<Arc<AppState>> {
+    // versioned API routes
+    let v1_routes = Router::new()
+        .route("/predict", post(v1::predict))
+        .route("/predict/stream", post(v1::predict_stream))
+        .route("/predict/:model_name", get(v1::predict_lookup))
+        .route("/embed", post(v1::embed))
+        .route("/compare", post(v1::compare))
+        .route_layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            authorize_predict,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            sign_response,
+        ))
+        .route_layer(middleware::from_fn(track_request_body));
+
+    // unversioned API routes, deprecated in favour of the versioned routes
     let api_routes = Router::new()
+        .route("/predict", post(predict))
+        .route("/predict/:model_name", get(v1::predict_lookup))
+        .route_layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            authorize_predict,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            sign_response,
+        ))
+        .route_layer(middleware::from_fn(track_request_body))
+        .route_layer(middleware::from_fn(deprecate_unversioned_route));
+
+    Router::new()
+        .nest("/api/v1", v1_routes)
+        .nest("/api", api_routes)
+}
+
+fn admin_routes(shared_state: &Arc<AppState>) -> Router<Arc<AppState>> {
+    // versioned API routes
+    let v1_routes = Router::new()
         .route("/models", get(get_models))
         .route("/models", post(add_model))
         .route("/models", put(update_model))
         .route("/models", delete(delete_model))
-        .route("/predict", post(predict));
+        .route("/models/labels", put(update_model_labels))
+        .route("/models/refresh", post(refresh_models))
+        .route("/models/misses", get(get_model_misses))
+        .route("/models/bulk", delete(delete_models))
+        .route("/models/operations/:operation_id", get(get_bulk_operation))
+        .route("/models/:model_name/sample", get(get_model_sample))
+        .route("/models/:model_name/diff", get(get_model_diff))
+        .route("/stores", get(get_stores))
+        .route("/stores", post(attach_store))
+        .route("/stores", delete(detach_store))
+        .route("/version", get(version))
+        .route("/startup-report", get(startup_report))
+        .route_layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            authorize_admin,
+        ))
+        .route_layer(middleware::from_fn(track_request_body));
 
-    // build router
-    Ok(Router::new()
-        .route("/healthcheck", get(healthcheck))
+    // unversioned API routes, deprecated in favour of the versioned routes
+    let api_routes = Router::new()
+        .route("/models", get(get_models))
+        .route("/models", post(add_model))
+        .route("/models", put(update_model))
+        .route("/models", delete(delete_model))
+        .route("/models/:model_name/sample", get(get_model_sample))
+        .route("/models/:model_name/diff", get(get_model_diff))
+        .route("/version", get(version))
+        .route_layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            authorize_admin,
+        ))
+        .route_layer(middleware::from_fn(track_request_body))
+        .route_layer(middleware::from_fn(deprecate_unversioned_route));
+
+    // the startup report is also served at the unversioned path deploy tooling asks for, which is not deprecated
+    // as the report is only read by tooling and has no unversioned predecessor
+    let startup_report_routes = Router::new()
+        .route("/api/startup-report", get(startup_report))
+        .route_layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            authorize_admin,
+        ))
+        .route_layer(middleware::from_fn(track_request_body));
+
+    Router::new()
+        .route("/metrics", get(metrics))
+        .merge(startup_report_routes)
+        .nest("/api/v1", v1_routes)
         .nest("/api", api_routes)
-        .with_state(shared_state))
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::common::admission::InFlightLimiter;
+    use crate::common::bulk::BulkOperations;
+    use crate::common::namespace::Namespaces;
     use crate::common::state::AppState;
-    use crate::http::router::build_router;
+    use crate::http::router::{build_admin_router, build_public_router, build_router};
     use jams_core::manager::ManagerBuilder;
     use jams_core::model_store::local::filesystem::LocalModelStore;
     use jams_core::model_store::ModelStore;
-    use rayon::ThreadPoolBuilder;
+    use jams_core::pool::executor::ExecutorKind;
+    use jams_core::pool::scheduler::{Budget, Scheduler};
     use std::sync::Arc;
 
     async fn setup_shared_state() -> Arc<AppState> {
-        let cpu_pool = ThreadPoolBuilder::new()
-            .num_threads(1)
-            .build()
-            .expect("Failed to build rayon threadpool ❌");
+        let cpu_pool = Scheduler::new(
+            "shared",
+            ExecutorKind::Rayon
+                .build(1, "jams-worker")
+                .expect("Failed to build rayon threadpool ❌"),
+            Budget::default(),
+        );
 
         let model_store = LocalModelStore::new("tests/model_store".to_string())
             .await
@@ -48,7 +174,25 @@ mod tests {
                 .expect("Failed to initialize manager ❌"),
         );
 
-        Arc::new(AppState { manager, cpu_pool })
+        Arc::new(AppState {
+            manager,
+            cpu_pool,
+            memory_watermark_bytes: None,
+            in_flight_limiter: InFlightLimiter::default(),
+            prediction_recorder: None,
+            namespaces: Namespaces::default(),
+            schema_cache: None,
+            deep_health: None,
+            feature_store: None,
+            result_store: None,
+            deduplicator: None,
+            authorization: None,
+            passthrough: None,
+            smoke_test: None,
+            bulk_operations: BulkOperations::default(),
+            startup_report: None,
+            response_signer: None,
+        })
     }
 
     #[tokio::test]
@@ -62,4 +206,18 @@ mod tests {
         // Assert
         assert!(router.is_ok())
     }
+
+    #[tokio::test]
+    async fn successfully_build_public_and_admin_routers() {
+        // Arrange
+        let shared_state = setup_shared_state().await;
+
+        // Act
+        let public_router = build_public_router(shared_state.clone());
+        let admin_router = build_admin_router(shared_state);
+
+        // Assert
+        assert!(public_router.is_ok());
+        assert!(admin_router.is_ok())
+    }
 }
@@ -1,7 +1,10 @@
+use crate::common::listener::{self, ListenerOptions};
 use crate::common::shutdown::shutdown_signal;
 use crate::common::state::AppState;
-use crate::http::router::build_router;
+use crate::http::router::{build_admin_router, build_public_router, build_router};
+use axum::Router;
 use std::sync::Arc;
+use tokio::task::JoinSet;
 
 /// Starts the HTTP server with the provided configuration.
 ///
@@ -11,7 +14,11 @@ use std::sync::Arc;
 ///
 /// # Arguments
 ///
-/// * `config` - The server configuration.
+/// * `shared_state` - The shared application state.
+/// * `address` - The `host:port` address the public endpoints are served on.
+/// * `admin_address` - An optional `host:port` address the admin endpoints, i.e. model management and
+///   metrics, are served on instead of the public address.
+/// * `options` - The socket options of the listeners of the public address.
 ///
 /// # Returns
 ///
@@ -24,30 +31,94 @@ use std::sync::Arc;
 /// * The router cannot be built.
 /// * The TCP listener cannot be created.
 /// * Any failure occurs during the initialization of the services or the server.
-pub async fn start(shared_state: Arc<AppState>, port: u16) -> anyhow::Result<()> {
-    let app = match build_router(shared_state) {
+pub async fn start(
+    shared_state: Arc<AppState>,
+    address: String,
+    admin_address: Option<String>,
+    options: ListenerOptions,
+) -> anyhow::Result<()> {
+    let admin_address = match admin_address {
+        None => {
+            let app = match build_router(shared_state) {
+                Ok(app) => app,
+                Err(_) => {
+                    anyhow::bail!("Failed to build the router ❌");
+                }
+            };
+            return serve(app, address, "Server", &options).await;
+        }
+        Some(admin_address) => admin_address,
+    };
+
+    let app = match build_public_router(shared_state.clone()) {
         Ok(app) => app,
         Err(_) => {
             anyhow::bail!("Failed to build the router ❌");
         }
     };
+    tokio::try_join!(
+        serve(app, address, "Server", &options),
+        start_admin(shared_state, admin_address)
+    )?;
+
+    Ok(())
+}
+
+/// Starts the HTTP server of the admin endpoints, i.e. model management, metrics and health checks.
+///
+/// This is used when the admin endpoints are bound to a different port or network interface than the
+/// public endpoints, e.g. to only expose them on localhost.
+///
+/// # Arguments
+///
+/// * `shared_state` - The shared application state.
+/// * `address` - The `host:port` address the admin endpoints are served on.
+///
+/// # Errors
+///
+/// This function will return an error if the router cannot be built or the TCP listener cannot be created.
+pub async fn start_admin(shared_state: Arc<AppState>, address: String) -> anyhow::Result<()> {
+    let app = match build_admin_router(shared_state) {
+        Ok(app) => app,
+        Err(_) => {
+            anyhow::bail!("Failed to build the admin router ❌");
+        }
+    };
+    serve(app, address, "Admin server", &ListenerOptions::default()).await
+}
 
-    // run our app with hyper, listening globally on specified port
-    let address = format!("0.0.0.0:{}", port);
-    let listener = tokio::net::TcpListener::bind(address)
-        .await
-        .expect("Failed to create TCP listener ❌");
+async fn serve(
+    app: Router,
+    address: String,
+    name: &str,
+    options: &ListenerOptions,
+) -> anyhow::Result<()> {
+    let listeners = listener::bind(address.as_str(), options)?;
 
     // log that the server is running
     tracing::info!(
         "{}",
-        format!("Server is running on http://0.0.0.0:{} 🚀 \n", port)
+        format!(
+            "{} is running on http://{} with {} acceptor(s) 🚀 \n",
+            name,
+            address,
+            listeners.len()
+        )
     );
 
-    // run on hyper
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    // run on hyper, with one accept task per listener
+    let mut acceptors = JoinSet::new();
+    for listener in listeners {
+        let app = app.clone();
+        acceptors.spawn(async move {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+        });
+    }
+    while let Some(result) = acceptors.join_next().await {
+        result??;
+    }
 
     Ok(())
 }
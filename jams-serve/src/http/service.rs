@@ -1,12 +1,43 @@
+use crate::common::admission::{should_shed, QueueTicket};
+use crate::common::bulk::{select_models, BulkAction, BulkProgress};
+use crate::common::dedup::Lookup;
+use crate::common::feature_store;
+use crate::common::health::DeepHealthReport;
+use crate::common::metadata::{RequestMetadata, MODEL_VARIANT_HEADER};
+use crate::common::metrics::DisconnectGuard;
+use crate::common::passthrough::RequestContext;
+use crate::common::recorder::PredictionRecord;
+use crate::common::schema_cache::{client_id, CLIENT_ID_HEADER};
+use crate::common::startup::StartupReport;
 use crate::common::state::AppState;
-use crate::common::worker;
-use axum::extract::{Query, State};
-use axum::http::StatusCode;
+use crate::common::{metrics as server_metrics, misses, worker};
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
 use axum::Json;
-use jams_core::model_store::storage::Metadata;
+use jams_core::manager::{Readiness, ServedPrediction};
+use jams_core::model::predict::PredictOptions;
+use jams_core::model::sample::{sample_input, SampleStrategy};
+use jams_core::model::variant::ModelPrecision;
+use jams_core::model::versions::{native_libraries, NativeLibrary};
+use jams_core::model_store::attached::{AttachedStoreInfo, AttachedStores, StoreSource};
+use jams_core::model_store::diff::ArtifactDiff;
+use jams_core::model_store::labels::LabelSelector;
+use jams_core::model_store::misses::ModelMiss;
+use jams_core::model_store::storage::{Metadata, ModelName};
+use jams_core::pool::scheduler::Task;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+/// Maximum length of the model name accepted in a prediction request.
+const MAX_MODEL_NAME_LENGTH: usize = 256;
+
+/// Number of seconds in a day, used to convert the `unused_for_days` query parameter.
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
 
 #[derive(Deserialize)]
 pub struct AddModelRequest {
@@ -23,6 +54,179 @@ pub struct DeleteModelRequest {
     model_name: String,
 }
 
+/// A request for refreshing many models at once. Every model is refreshed if neither `label` nor `namespace` is set.
+///
+/// # Example
+/// ```json
+/// {
+///     "label": "team:risk",
+///     "namespace": "tier1"
+/// }
+/// ```
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RefreshModelsRequest {
+    /// An optional label selector, e.g. `team:risk,tier`. Only the models with matching labels are refreshed.
+    label: Option<String>,
+    /// An optional namespace. Only the models of the namespace are refreshed.
+    namespace: Option<String>,
+}
+
+/// The query parameters of the request for deleting many models at once.
+#[derive(Deserialize)]
+pub struct DeleteModelsQuery {
+    /// The label selector of the models to delete, e.g. `team:risk,tier`.
+    label: String,
+    /// An optional namespace. Only the models of the namespace are deleted.
+    namespace: Option<String>,
+}
+
+/// A request for setting and removing labels of a model.
+///
+/// # Example
+/// ```json
+/// {
+///     "model_name": "titanic_model",
+///     "labels": { "team": "risk" },
+///     "remove": ["owner"]
+/// }
+/// ```
+#[derive(Deserialize)]
+pub struct UpdateModelLabelsRequest {
+    model_name: String,
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+    #[serde(default)]
+    remove: Vec<String>,
+}
+
+/// A request for attaching a model store at runtime.
+///
+/// # Example
+/// ```json
+/// {
+///     "name": "archive",
+///     "url": "s3://my-archive-bucket"
+/// }
+/// ```
+#[derive(Deserialize)]
+pub struct AttachStoreRequest {
+    name: String,
+    url: String,
+}
+
+/// The query parameters of the request for detaching a model store.
+#[derive(Deserialize)]
+pub struct DetachStoreQuery {
+    name: String,
+}
+
+/// Response structure for attaching a model store.
+#[derive(Serialize)]
+pub struct AttachStoreResponse {
+    /// Name of the attached store.
+    name: String,
+    /// Names of the models loaded from the store, prefixed by the name of the store.
+    models: Vec<ModelName>,
+}
+
+/// Response structure for retrieving the model stores attached at runtime.
+#[derive(Serialize)]
+pub struct GetStoresResponse {
+    /// Total number of attached stores.
+    total: i32,
+    /// The attached stores.
+    stores: Vec<AttachedStoreInfo>,
+}
+
+/// The query parameters of the request for retrieving the list of models.
+#[derive(Deserialize)]
+pub struct GetModelsQuery {
+    /// An optional label selector, e.g. `team:risk,tier`. Only the models with matching labels are returned.
+    label: Option<String>,
+    /// An optional number of days. Only the models which received no prediction request for that long are returned.
+    unused_for_days: Option<u64>,
+}
+
+/// The query parameters of the request for retrieving the most requested missing model names.
+#[derive(Deserialize)]
+pub struct GetModelMissesQuery {
+    /// An optional maximum number of model names. Default is 10.
+    limit: Option<usize>,
+}
+
+/// A model name which was requested while no model with that name was loaded, with the closest loaded model names.
+#[derive(Serialize)]
+pub struct ModelMissInfo {
+    #[serde(flatten)]
+    miss: ModelMiss,
+    /// The closest loaded model names, empty unless model name suggestions are enabled.
+    suggestions: Vec<ModelName>,
+}
+
+/// Response structure for retrieving the most requested missing model names.
+#[derive(Serialize)]
+pub struct GetModelMissesResponse {
+    /// Number of prediction requests made to models which were not loaded since startup.
+    total: u64,
+    /// The most requested missing model names, most requested first.
+    misses: Vec<ModelMissInfo>,
+}
+
+/// The query parameters of the request for generating a sample payload of a model.
+#[derive(Deserialize)]
+pub struct GetModelSampleQuery {
+    /// An optional number of rows of the sample. Default is 1.
+    rows: Option<usize>,
+    /// How the values of the sample are chosen, `midpoint` (default) or `random`.
+    #[serde(default)]
+    strategy: SampleStrategy,
+}
+
+/// Version of a diff request which stands for the latest stored version of the tarball of a model.
+pub const LATEST_STORED_VERSION: &str = "latest";
+
+/// The query parameters of the request for diffing two stored versions of a model.
+///
+/// A version is a version of the tarball of the model in its model store, i.e. its S3 or Azure version id, or its
+/// modification time in a local model store, or `latest` for its latest version.
+#[derive(Deserialize)]
+pub struct GetModelDiffQuery {
+    /// The version being replaced, e.g. `3HL4kqtJlcpXroDTDmJ.rmSpXd3dIbrHY`.
+    from: String,
+    /// The version replacing it, e.g. `latest`.
+    to: String,
+}
+
+/// Response structure for diffing two versions of a model.
+///
+/// # Example
+/// ```json
+/// {
+///     "model_name": "titanic_model",
+///     "from": "3HL4kqtJlcpXroDTDmJ.rmSpXd3dIbrHY",
+///     "to": "latest",
+///     "identical": false,
+///     "tarball_sha256": { "from": "2c26b4...", "to": "fcde2b..." },
+///     "artifact_sha256": { "from": "9f86d0...", "to": "60303a..." },
+///     "size_bytes": { "from": 104857, "to": 110592, "delta": 5735 },
+///     "schema": { "added": { "fare": "float" } },
+///     "library_versions": { "changed": { "torch": { "from": "2.2.0", "to": "2.3.0" } } }
+/// }
+/// ```
+#[derive(Serialize)]
+pub struct GetModelDiffResponse {
+    /// The name of the diffed model.
+    model_name: String,
+    /// The version being replaced.
+    from: String,
+    /// The version replacing it.
+    to: String,
+    /// The differences between the artifacts of both versions. Only the fields which changed are set.
+    #[serde(flatten)]
+    diff: ArtifactDiff,
+}
+
 /// Response structure for retrieving the list of models.
 ///
 /// Represents the JSON response structure returned by the API when
@@ -35,6 +239,34 @@ pub struct GetModelsResponse {
     models: Vec<Metadata>,
 }
 
+/// Response structure for the readiness check.
+#[derive(Serialize)]
+pub struct ReadinessResponse {
+    /// The readiness of the manager.
+    #[serde(flatten)]
+    readiness: Readiness,
+    /// The frameworks which failed the startup smoke test and are served degraded.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    degraded_frameworks: Vec<String>,
+}
+
+/// Response structure for retrieving the versions of the server and the native libraries.
+///
+/// # Example
+/// ```json
+/// {
+///     "version": "0.1.33",
+///     "libraries": [{ "framework": "tensorflow", "version": "2.15.0", "path": "/usr/local/lib/libtensorflow.so.2" }]
+/// }
+/// ```
+#[derive(Serialize)]
+pub struct VersionResponse {
+    /// Version of the server.
+    version: &'static str,
+    /// Native machine learning libraries loaded by the server.
+    libraries: &'static [NativeLibrary],
+}
+
 /// A request for making a prediction.
 ///
 /// This struct represents the data required to make a prediction using a specified model.
@@ -42,6 +274,8 @@ pub struct GetModelsResponse {
 /// # Fields
 /// - `model_name` (String): The name of the model to use for the prediction.
 /// - `input` (String): The input data for the prediction, formatted as a JSON-like string.
+/// - `predict_options` (PredictOptions): Optional prediction options which override the default options
+///   of the model, e.g. `{"mode": "leaf_index", "num_iteration": 100}`. Only supported by LightGBM models.
 ///
 /// # Example
 /// ```json
@@ -52,8 +286,10 @@ pub struct GetModelsResponse {
 /// ```
 #[derive(Deserialize, Serialize)]
 pub struct PredictRequest {
-    model_name: String,
-    input: String,
+    pub(crate) model_name: String,
+    pub(crate) input: String,
+    #[serde(default, skip_serializing_if = "PredictOptions::is_empty")]
+    pub(crate) predict_options: PredictOptions,
 }
 
 /// The response from a prediction request.
@@ -104,6 +340,174 @@ pub async fn healthcheck() -> StatusCode {
     StatusCode::OK
 }
 
+/// Deep health check endpoint handler.
+///
+/// This function handles the deep health check ("/healthz/deep") endpoint. It runs a canned prediction against
+/// the configured sentinel model to check the full prediction path, including the worker pools and the native
+/// libraries of the frameworks, and reports the latency of the prediction.
+///
+/// # Returns
+/// - `StatusCode::OK` with a `DeepHealthReport` if the prediction succeeded.
+/// - `StatusCode::SERVICE_UNAVAILABLE` with a `DeepHealthReport` if the prediction failed or timed out.
+/// - `StatusCode::NOT_FOUND` with an `ErrorResponse` if the deep health check is not configured.
+#[tracing::instrument(skip(app_state))]
+pub async fn deep_healthcheck(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<(StatusCode, Json<DeepHealthReport>), (StatusCode, Json<ErrorResponse>)> {
+    let deep_health = match &app_state.deep_health {
+        Some(deep_health) => deep_health,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "Deep health check is not configured ❌".to_string(),
+                }),
+            ))
+        }
+    };
+
+    let report = deep_health
+        .run(
+            &app_state.manager,
+            &app_state.cpu_pool,
+            &app_state.namespaces,
+        )
+        .await;
+    let status = match report.healthy {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    Ok((status, Json(report)))
+}
+
+/// Readiness check endpoint handler.
+///
+/// This function handles the readiness check ("/readyz") endpoint. The server is ready once the priority models
+/// are loaded, even though the rest of the model store may still be loading in the background, and as long as
+/// the pollers of the model stores are healthy. The frameworks which failed the startup smoke test are reported
+/// as `degraded_frameworks` without making the server unready, so that the other frameworks keep serving.
+///
+/// # Returns
+/// - `StatusCode::OK` with a `ReadinessResponse` if every priority model is loaded and every poller is healthy.
+/// - `StatusCode::SERVICE_UNAVAILABLE` with a `ReadinessResponse` listing the priority models which are not loaded
+///   and the health of the pollers.
+#[tracing::instrument(skip(app_state))]
+pub async fn readiness(
+    State(app_state): State<Arc<AppState>>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let readiness = app_state.manager.readiness();
+    let status = match readiness.ready {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    let degraded_frameworks = app_state
+        .smoke_test
+        .as_ref()
+        .map(|report| report.failed_frameworks())
+        .unwrap_or_default();
+    (
+        status,
+        Json(ReadinessResponse {
+            readiness,
+            degraded_frameworks,
+        }),
+    )
+}
+
+/// Version endpoint handler.
+///
+/// Returns the version of the server and of the TensorFlow, LibTorch, LightGBM and CatBoost libraries it
+/// loaded, so that mismatches between training and serving library versions are visible when debugging
+/// output discrepancies. Versions which cannot be detected are `null`.
+///
+/// # Returns
+/// - `StatusCode::OK` with a `VersionResponse`.
+pub async fn version() -> (StatusCode, Json<VersionResponse>) {
+    (
+        StatusCode::OK,
+        Json(VersionResponse {
+            version: env!("CARGO_PKG_VERSION"),
+            libraries: native_libraries(),
+        }),
+    )
+}
+
+/// Startup report endpoint handler.
+///
+/// Returns the summary of the startup, i.e. the models which were loaded or failed to load and why, the time taken
+/// by the startup, the model stores, the digest of the config file and the versions of the native libraries, so that
+/// deploy tooling can assert on it rather than grep the logs. The report is built once the models are loaded, only
+/// the models which failed to load and whether the remaining models are still loading are brought up to date, so
+/// that the models loaded in the background are reported too.
+///
+/// # Returns
+/// - `StatusCode::OK` with the `StartupReport`.
+/// - `StatusCode::NOT_FOUND` with an `ErrorResponse` if the server was not started with a startup report.
+#[tracing::instrument(skip(app_state))]
+pub async fn startup_report(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<(StatusCode, Json<StartupReport>), (StatusCode, Json<ErrorResponse>)> {
+    match &app_state.startup_report {
+        Some(report) => Ok((StatusCode::OK, Json(report.refreshed(&app_state.manager)))),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: "Startup report is not available ❌".to_string(),
+            }),
+        )),
+    }
+}
+
+/// Metrics endpoint handler.
+///
+/// This function handles the metrics ("/metrics") endpoint and returns all the metrics collected by the
+/// server in the prometheus text exposition format.
+///
+/// # Returns
+/// - `Ok((StatusCode, String))`: The encoded metrics with an HTTP status code `StatusCode::OK`.
+/// - `Err((StatusCode, Json<ErrorResponse>))`: An error response with `StatusCode::INTERNAL_SERVER_ERROR`
+///   if the metrics could not be encoded.
+#[tracing::instrument(skip(app_state))]
+pub async fn metrics(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<(StatusCode, String), (StatusCode, Json<ErrorResponse>)> {
+    // the usage and the stats of the models, the latencies of their variants, the latency fallbacks and the store
+    // timeouts are tracked by jams-core and only copied to the metrics when they are scraped
+    if let Ok(models) = app_state.manager.get_models() {
+        server_metrics::record_model_usage(&models);
+    }
+    if let Ok(stats) = app_state.manager.get_model_stats() {
+        server_metrics::record_model_stats(&stats);
+    }
+    server_metrics::record_variant_latencies(&app_state.manager.get_variant_latencies());
+    server_metrics::record_latency_fallbacks(&app_state.manager.get_latency_fallbacks());
+    // the worker pools are accounted for by their schedulers
+    server_metrics::record_scheduler_stats(&app_state.cpu_pool.stats());
+    server_metrics::record_scheduler_stats(&app_state.namespaces.stats());
+    // the flows of every worker pool are recorded at once, so that the series of the evicted flows are dropped
+    let mut flow_stats = app_state.cpu_pool.flow_stats();
+    flow_stats.extend(app_state.namespaces.flow_stats());
+    server_metrics::record_scheduler_flow_stats(&flow_stats);
+    server_metrics::record_store_timeouts();
+    server_metrics::record_model_misses(
+        &app_state
+            .manager
+            .get_model_misses(server_metrics::TOP_MODEL_MISSES),
+        app_state.manager.model_misses_total(),
+    );
+    server_metrics::record_model_validations();
+    server_metrics::record_poller_health(&app_state.manager.poller_health());
+    match server_metrics::render() {
+        Ok(output) => Ok((StatusCode::OK, output)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Failed to render metrics ❌: {}", e),
+            }),
+        )),
+    }
+}
+
 /// Adds a new model to the model store.
 ///
 /// This function processes the addition of a new model by interacting with the shared `Manager` in the application state.
@@ -228,6 +632,160 @@ pub async fn delete_model(
     }
 }
 
+/// Starts refreshing every model, or the models matching a label selector and a namespace, in the background.
+///
+/// The models are reloaded from the model store one at a time, like `PUT /api/v1/models`, and the progress of
+/// the operation is returned by `GET /api/v1/models/operations/:operation_id`.
+///
+/// # Arguments
+///
+/// - `State(app_state)`: The application state (`Arc<AppState>`), which contains the `Manager` responsible for
+///   handling model operations.
+/// - `body`: The optional JSON payload containing the `RefreshModelsRequest`, with the `label` selector and the
+///   `namespace` of the models to refresh. An empty body refreshes every model.
+///
+/// # Returns
+///
+/// A `Result<(StatusCode, Json<BulkProgress>), (StatusCode, Json<ErrorResponse>)>`:
+/// - On success, it returns `StatusCode::ACCEPTED` with the initial progress of the operation.
+/// - On failure, it returns:
+///   - `StatusCode::BAD_REQUEST` if the payload is malformed or the label selector is invalid.
+///   - `StatusCode::INTERNAL_SERVER_ERROR` if the models cannot be listed.
+#[tracing::instrument(skip(app_state, body))]
+pub async fn refresh_models(
+    State(app_state): State<Arc<AppState>>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<BulkProgress>), (StatusCode, Json<ErrorResponse>)> {
+    // a malformed payload is rejected rather than treated as a missing one, which would refresh every model
+    let payload = match body.is_empty() {
+        true => RefreshModelsRequest::default(),
+        false => match serde_json::from_slice::<RefreshModelsRequest>(&body) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::warn!("Rejecting bulk request ⚠️: {}", e);
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: format!("Failed to parse refresh request ❌: {}", e),
+                    }),
+                ));
+            }
+        },
+    };
+    let models = select_bulk_models(
+        &app_state,
+        payload.label.as_deref(),
+        payload.namespace.as_deref(),
+    )?;
+    let progress = app_state.bulk_operations.start(
+        Arc::clone(&app_state.manager),
+        BulkAction::Refresh,
+        models,
+    );
+    Ok((StatusCode::ACCEPTED, Json(progress)))
+}
+
+/// Starts deleting the models matching a label selector, and optionally a namespace, in the background.
+///
+/// A label selector is required so that a mistyped request cannot delete every model. The progress of the
+/// operation is returned by `GET /api/v1/models/operations/:operation_id`.
+///
+/// # Arguments
+///
+/// - `State(app_state)`: The application state (`Arc<AppState>`), which contains the `Manager` responsible for
+///   handling model operations.
+/// - `Query(query)`: The `label` selector and the optional `namespace` of the models to delete.
+///
+/// # Returns
+///
+/// A `Result<(StatusCode, Json<BulkProgress>), (StatusCode, Json<ErrorResponse>)>`:
+/// - On success, it returns `StatusCode::ACCEPTED` with the initial progress of the operation.
+/// - On failure, it returns:
+///   - `StatusCode::BAD_REQUEST` if the label selector is missing or invalid.
+///   - `StatusCode::INTERNAL_SERVER_ERROR` if the models cannot be listed.
+#[tracing::instrument(skip(app_state, query))]
+pub async fn delete_models(
+    State(app_state): State<Arc<AppState>>,
+    Query(query): Query<DeleteModelsQuery>,
+) -> Result<(StatusCode, Json<BulkProgress>), (StatusCode, Json<ErrorResponse>)> {
+    let models = select_bulk_models(
+        &app_state,
+        Some(query.label.as_str()),
+        query.namespace.as_deref(),
+    )?;
+    let progress =
+        app_state
+            .bulk_operations
+            .start(Arc::clone(&app_state.manager), BulkAction::Delete, models);
+    Ok((StatusCode::ACCEPTED, Json(progress)))
+}
+
+/// Returns the progress of a bulk refresh or delete.
+///
+/// # Arguments
+///
+/// - `State(app_state)`: The application state (`Arc<AppState>`).
+/// - `Path(operation_id)`: The ID returned when the operation was started.
+///
+/// # Returns
+///
+/// A `Result<(StatusCode, Json<BulkProgress>), (StatusCode, Json<ErrorResponse>)>`:
+/// - On success, it returns `StatusCode::OK` with the models processed so far and whether the operation is done.
+/// - On failure, it returns `StatusCode::NOT_FOUND` if the operation is unknown or was forgotten, as only the
+///   most recent operations are remembered.
+#[tracing::instrument(skip(app_state))]
+pub async fn get_bulk_operation(
+    State(app_state): State<Arc<AppState>>,
+    Path(operation_id): Path<String>,
+) -> Result<(StatusCode, Json<BulkProgress>), (StatusCode, Json<ErrorResponse>)> {
+    match app_state.bulk_operations.progress(operation_id.as_str()) {
+        Some(progress) => Ok((StatusCode::OK, Json(progress))),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Bulk operation {} not found ❌", operation_id),
+            }),
+        )),
+    }
+}
+
+/// Selects the models of a bulk operation, mapping the errors to the response of the endpoint.
+fn select_bulk_models(
+    app_state: &AppState,
+    label: Option<&str>,
+    namespace: Option<&str>,
+) -> Result<Vec<ModelName>, (StatusCode, Json<ErrorResponse>)> {
+    let selector = match label.map(LabelSelector::parse).transpose() {
+        Ok(selector) => selector,
+        Err(e) => {
+            tracing::warn!("Rejecting bulk request ⚠️: {}", e);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            ));
+        }
+    };
+    match select_models(
+        &app_state.manager,
+        &app_state.namespaces,
+        selector.as_ref(),
+        namespace,
+    ) {
+        Ok(models) => Ok(models),
+        Err(e) => {
+            tracing::error!("Failed to select models ❌: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to select models ❌: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
 /// Retrieves the list of models available in the server.
 ///
 /// This endpoint fetches the list of models and their metadata from the server.
@@ -237,6 +795,8 @@ pub async fn delete_model(
 ///
 /// - `State(app_state)`: The application state (`Arc<AppState>`), which contains the `Manager` responsible for
 ///   handling model operations.
+/// - `Query(query)`: The query parameters, with an optional `label` selector, e.g. `?label=team:risk,tier`, which
+///   only keeps the models whose labels match every requirement.
 ///
 /// # Returns
 ///
@@ -244,16 +804,44 @@ pub async fn delete_model(
 /// - On success, it returns:
 ///   - `StatusCode::OK` with a JSON response containing the total count of models and their metadata.
 /// - On failure, it returns:
+///   - `StatusCode::BAD_REQUEST` if the label selector is invalid.
 ///   - `StatusCode::INTERNAL_SERVER_ERROR` with an error message indicating the reason for the failure.
 ///
 /// # Error Handling
 /// If there is an error retrieving the models (e.g., failure to access the underlying storage or an unexpected exception),
 /// the function returns an `INTERNAL_SERVER_ERROR` status code along with a descriptive error message.
-#[tracing::instrument(skip(app_state))]
+#[tracing::instrument(skip(app_state, query))]
 pub async fn get_models(
     State(app_state): State<Arc<AppState>>,
+    Query(query): Query<GetModelsQuery>,
 ) -> Result<(StatusCode, Json<GetModelsResponse>), (StatusCode, Json<ErrorResponse>)> {
-    match app_state.manager.get_models() {
+    let selector = match query.label.as_deref().map(LabelSelector::parse).transpose() {
+        Ok(selector) => selector,
+        Err(e) => {
+            tracing::warn!("Rejecting get models request ⚠️: {}", e);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            ));
+        }
+    };
+
+    let models = match query.unused_for_days {
+        Some(days) => app_state
+            .manager
+            .get_unused_models(Duration::from_secs(days.saturating_mul(SECONDS_PER_DAY))),
+        None => app_state.manager.get_models(),
+    };
+    let models = models.map(|models| match selector {
+        Some(selector) => models
+            .into_iter()
+            .filter(|model| selector.matches(&model.labels))
+            .collect(),
+        None => models,
+    });
+    match models {
         Ok(models) => Ok((
             StatusCode::OK,
             Json(GetModelsResponse {
@@ -273,8 +861,370 @@ pub async fn get_models(
     }
 }
 
+/// Generates an example prediction request for a model from the input schema in its bundle, so that integrators
+/// can build their requests without reading the training code.
+///
+/// # Arguments
+///
+/// - `State(app_state)`: The application state (`Arc<AppState>`), which contains the `Manager` responsible for
+///   handling model operations.
+/// - `Path(model_name)`: The name of the model.
+/// - `Query(query)`: The number of `rows` of the sample and the `strategy` used to choose its values.
+///
+/// # Returns
+///
+/// A `Result<(StatusCode, Json<PredictRequest>), (StatusCode, Json<ErrorResponse>)>`:
+/// - On success, it returns `StatusCode::OK` with a request which can be sent to the predict endpoint as it is.
+/// - On failure, it returns:
+///   - `StatusCode::NOT_FOUND` if the model does not exist or has neither an input schema nor a warmup input.
+///   - `StatusCode::BAD_REQUEST` if the number of rows is out of range.
+#[tracing::instrument(skip(app_state, query))]
+pub async fn get_model_sample(
+    State(app_state): State<Arc<AppState>>,
+    Path(model_name): Path<String>,
+    Query(query): Query<GetModelSampleQuery>,
+) -> Result<(StatusCode, Json<PredictRequest>), (StatusCode, Json<ErrorResponse>)> {
+    let bundle = match app_state.manager.get_model_bundle(model_name.clone()) {
+        Some(bundle) => bundle,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!(
+                        "Failed to generate sample ❌: model {} not found",
+                        model_name
+                    ),
+                }),
+            ))
+        }
+    };
+    if bundle.schema.is_empty() && bundle.warmup_input.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!(
+                    "Failed to generate sample ❌: model {} has no input schema",
+                    model_name
+                ),
+            }),
+        ));
+    }
+
+    match sample_input(&bundle, query.rows.unwrap_or(1), query.strategy) {
+        Ok(input) => Ok((
+            StatusCode::OK,
+            Json(PredictRequest {
+                model_name,
+                input,
+                predict_options: PredictOptions::default(),
+            }),
+        )),
+        Err(e) => {
+            tracing::warn!("Rejecting sample request ⚠️: {}", e);
+            Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("Failed to generate sample ❌: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
+/// Diffs the artifacts of two stored versions of a model, so that reviewers can see what a promotion actually
+/// changes before approving it. Both versions are read from the model store which serves the model, without
+/// loading them.
+///
+/// # Arguments
+///
+/// - `State(app_state)`: The application state (`Arc<AppState>`), which contains the `Manager` responsible for
+///   handling model operations.
+/// - `Path(model_name)`: The name of the model.
+/// - `Query(query)`: The stored versions to diff, `from` and `to`.
+///
+/// # Returns
+///
+/// A `Result<(StatusCode, Json<GetModelDiffResponse>), (StatusCode, Json<ErrorResponse>)>`:
+/// - On success, it returns `StatusCode::OK` with the schema, class label, size, checksum, library version and
+///   label differences between both versions.
+/// - On failure, it returns:
+///   - `StatusCode::NOT_FOUND` if the model does not exist.
+///   - `StatusCode::BAD_REQUEST` if a version is not stored or its tarball cannot be read.
+#[tracing::instrument(skip(app_state, query))]
+pub async fn get_model_diff(
+    State(app_state): State<Arc<AppState>>,
+    Path(model_name): Path<String>,
+    Query(query): Query<GetModelDiffQuery>,
+) -> Result<(StatusCode, Json<GetModelDiffResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let mut summaries = Vec::with_capacity(2);
+    for version in [query.from.as_str(), query.to.as_str()] {
+        let stored_version = match version {
+            LATEST_STORED_VERSION => None,
+            version => Some(version.to_string()),
+        };
+        match app_state
+            .manager
+            .get_stored_artifact_summary(model_name.clone(), stored_version)
+            .await
+        {
+            Ok(summary) => summaries.push(summary),
+            Err(e) => {
+                let status = match e.downcast_ref::<jams_core::Error>() {
+                    Some(jams_core::Error::ModelNotFound(_)) => StatusCode::NOT_FOUND,
+                    _ => StatusCode::BAD_REQUEST,
+                };
+                tracing::warn!(
+                    "Failed to diff version {} of model {} ⚠️: {}",
+                    version,
+                    model_name,
+                    e
+                );
+                return Err((
+                    status,
+                    Json(ErrorResponse {
+                        error: format!(
+                            "Failed to diff version {} of model {} ❌: {}",
+                            version, model_name, e
+                        ),
+                    }),
+                ));
+            }
+        }
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(GetModelDiffResponse {
+            diff: ArtifactDiff::between(&summaries[0], &summaries[1]),
+            model_name,
+            from: query.from,
+            to: query.to,
+        }),
+    ))
+}
+
+/// Sets and removes labels of a model at runtime.
+///
+/// The labels are kept in memory, take precedence over the labels from the `labels.yaml` file of the model
+/// tarball and are forgotten when the model is deleted.
+///
+/// # Arguments
+///
+/// - `State(app_state)`: The application state (`Arc<AppState>`), which contains the `Manager` responsible for
+///   handling model operations.
+/// - `Json(payload)`: The JSON payload containing the `UpdateModelLabelsRequest`, with the `model_name`, the
+///   `labels` to set and the keys of the labels to `remove`.
+///
+/// # Returns
+///
+/// A `Result<(StatusCode, Json<Metadata>), (StatusCode, Json<ErrorResponse>)>`:
+/// - On success, it returns `StatusCode::OK` with the metadata of the model, including its labels.
+/// - On failure, it returns:
+///   - `StatusCode::NOT_FOUND` if the model does not exist.
+///   - `StatusCode::BAD_REQUEST` if a label is invalid.
+#[tracing::instrument(skip(app_state, payload))]
+pub async fn update_model_labels(
+    State(app_state): State<Arc<AppState>>,
+    Json(payload): Json<UpdateModelLabelsRequest>,
+) -> Result<(StatusCode, Json<Metadata>), (StatusCode, Json<ErrorResponse>)> {
+    match app_state.manager.update_labels(
+        payload.model_name,
+        payload.labels,
+        payload.remove.as_slice(),
+    ) {
+        Ok(metadata) => Ok((StatusCode::OK, Json(metadata))),
+        Err(e) => {
+            tracing::error!("Failed to update model labels ❌: {}", e);
+            let status = match e.downcast_ref::<jams_core::Error>() {
+                Some(jams_core::Error::ModelNotFound(_)) => StatusCode::NOT_FOUND,
+                _ => StatusCode::BAD_REQUEST,
+            };
+            Err((
+                status,
+                Json(ErrorResponse {
+                    error: format!("Failed to update model labels ❌: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
+/// Attaches a model store at runtime, e.g. a second bucket, without restarting the server.
+///
+/// The models of the store are loaded alongside the models which are already served and are named
+/// `<name>.<model_name>`. The store is polled for updates like the model store the server was started with.
+///
+/// # Arguments
+///
+/// - `State(app_state)`: The application state (`Arc<AppState>`), which contains the `Manager` responsible for
+///   handling model operations.
+/// - `Json(payload)`: The JSON payload containing the `AttachStoreRequest`, with the `name` of the store and its
+///   `url`, e.g. `s3://my-bucket`, `minio://my-bucket`, `azure://my-container` or `file:///models`.
+///
+/// # Returns
+///
+/// A `Result<(StatusCode, Json<AttachStoreResponse>), (StatusCode, Json<ErrorResponse>)>`:
+/// - On success, it returns `StatusCode::OK` with the names of the models loaded from the store.
+/// - On failure, it returns:
+///   - `StatusCode::BAD_REQUEST` if the name or the URL is invalid.
+///   - `StatusCode::CONFLICT` if a store with the same name is already attached.
+///   - `StatusCode::INTERNAL_SERVER_ERROR` if the store cannot be loaded.
+#[tracing::instrument(skip(app_state, payload))]
+pub async fn attach_store(
+    State(app_state): State<Arc<AppState>>,
+    Json(payload): Json<AttachStoreRequest>,
+) -> Result<(StatusCode, Json<AttachStoreResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let source = match AttachedStores::validate_name(payload.name.as_str())
+        .and_then(|_| StoreSource::parse(payload.url.as_str()))
+    {
+        Ok(source) => source,
+        Err(e) => {
+            tracing::warn!("Rejecting attach store request ⚠️: {}", e);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            ));
+        }
+    };
+    if app_state
+        .manager
+        .get_stores()
+        .iter()
+        .any(|store| store.name == payload.name)
+    {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: format!("Model store {} is already attached ❌", payload.name),
+            }),
+        ));
+    }
+
+    match app_state
+        .manager
+        .attach_store(payload.name.clone(), source)
+        .await
+    {
+        Ok(models) => Ok((
+            StatusCode::OK,
+            Json(AttachStoreResponse {
+                name: payload.name,
+                models,
+            }),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to attach model store ❌: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to attach model store ❌: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
+/// Detaches a model store which was attached at runtime. Its models stop being served.
+///
+/// # Arguments
+///
+/// - `State(app_state)`: The application state (`Arc<AppState>`), which contains the `Manager` responsible for
+///   handling model operations.
+/// - `Query(query)`: The query parameters, with the `name` of the store to detach.
+///
+/// # Returns
+///
+/// - `Result<StatusCode, (StatusCode, Json<ErrorResponse>)>`:
+///   - If the store is detached, it returns `StatusCode::OK`.
+///   - If no store with the given name is attached, it returns `StatusCode::NOT_FOUND`.
+#[tracing::instrument(skip(app_state, query))]
+pub async fn detach_store(
+    State(app_state): State<Arc<AppState>>,
+    Query(query): Query<DetachStoreQuery>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    match app_state.manager.detach_store(query.name.as_str()) {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => {
+            tracing::warn!("Failed to detach model store ⚠️: {}", e);
+            Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Failed to detach model store ❌: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
+/// Retrieves the model stores attached at runtime, with their URL and number of models.
+///
+/// # Arguments
+///
+/// - `State(app_state)`: The application state (`Arc<AppState>`), which contains the `Manager` responsible for
+///   handling model operations.
+///
+/// # Returns
+///
+/// - `(StatusCode, Json<GetStoresResponse>)`: `StatusCode::OK` with the attached stores, sorted by name.
+#[tracing::instrument(skip(app_state))]
+pub async fn get_stores(
+    State(app_state): State<Arc<AppState>>,
+) -> (StatusCode, Json<GetStoresResponse>) {
+    let stores = app_state.manager.get_stores();
+    (
+        StatusCode::OK,
+        Json(GetStoresResponse {
+            total: stores.len() as i32,
+            stores,
+        }),
+    )
+}
+
+/// Retrieves the model names which were requested most while no model with that name was loaded, e.g. to find the
+/// clients which call a model by a typo'd or stale name. The model names which are loaded now are left out.
+///
+/// # Arguments
+///
+/// - `State(app_state)`: The application state (`Arc<AppState>`), which contains the `Manager` tracking the misses.
+/// - `Query(query)`: The query parameters, with an optional `limit` on the number of model names.
+///
+/// # Returns
+///
+/// - `(StatusCode, Json<GetModelMissesResponse>)`: `StatusCode::OK` with the total number of misses and the most
+///   requested missing model names, with the closest loaded model names when `model_suggestions` is enabled.
+#[tracing::instrument(skip(app_state, query))]
+pub async fn get_model_misses(
+    State(app_state): State<Arc<AppState>>,
+    Query(query): Query<GetModelMissesQuery>,
+) -> (StatusCode, Json<GetModelMissesResponse>) {
+    let limit = query.limit.unwrap_or(server_metrics::TOP_MODEL_MISSES);
+    let misses = app_state
+        .manager
+        .get_model_misses(limit)
+        .into_iter()
+        .map(|miss| ModelMissInfo {
+            suggestions: app_state
+                .manager
+                .suggest_model_names(miss.model_name.as_str()),
+            miss,
+        })
+        .collect();
+    (
+        StatusCode::OK,
+        Json(GetModelMissesResponse {
+            total: app_state.manager.model_misses_total(),
+            misses,
+        }),
+    )
+}
+
 /// Prediction endpoint handler.
 ///
+/// **Deprecated**: this handler serves the unversioned `/api/predict` route. New clients should use
+/// `/api/v1/predict`, which returns the outputs in a versioned response envelope.
+///
 /// This function asynchronously processes prediction requests by utilizing a worker thread
 /// to perform the prediction. It takes a `PredictRequest` payload and leverages the shared
 /// `Manager` to make predictions on a separate thread pool (`cpu_pool`). The result is sent
@@ -283,6 +1233,8 @@ pub async fn get_models(
 /// # Arguments
 /// - `State(app_state)`: Shared state containing an `Arc<AppState>`, which holds the `Manager` responsible for
 ///   managing models and the `cpu_pool` for running blocking operations in a thread pool.
+/// - `headers`: The request headers, used to read the client ID sent in the `x-client-id` header and the
+///   well-known headers of the request, e.g. `x-model-version` and `x-request-priority`.
 /// - `Json(payload)`: The JSON payload which contains the prediction request, including the `model_name` (the name of
 ///   the model to be used) and `input` (the input data for the model in serialized form).
 ///
@@ -324,30 +1276,359 @@ pub async fn get_models(
 ///
 /// This handler ensures that any blocking operation (like model prediction) is offloaded to the `cpu_pool` to avoid
 /// blocking the main async runtime.
-#[tracing::instrument(skip(app_state, payload))]
+///
+/// If the resident memory of the server is above the configured watermark, the request is rejected with
+/// `SERVICE_UNAVAILABLE` before any work is scheduled.
+///
+/// If the client disconnects before the prediction completes, the disconnect is recorded and the
+/// prediction is skipped if it has not been picked up by a worker yet.
+///
+/// If the model belongs to a namespace whose queue is full, the request is rejected with `TOO_MANY_REQUESTS`.
+///
+/// If the `x-model-version` header pins another version of the model than the loaded one, the request is rejected
+/// with `PRECONDITION_FAILED`. See `RequestMetadata` for the other well-known headers.
+///
+/// If the model is not loaded, the request is rejected with `NOT_FOUND` and recorded as a miss. The error suggests
+/// the closest loaded model names when `model_suggestions` is enabled.
+///
+/// If a reduced precision variant of the model served the prediction, e.g. because the request fell back to it
+/// because of its latency budget, the variant is named by the `x-model-variant` response header.
+#[tracing::instrument(skip(app_state, headers, payload))]
 pub async fn predict(
     State(app_state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(payload): Json<PredictRequest>,
-) -> Result<(StatusCode, Json<PredictResponse>), (StatusCode, Json<ErrorResponse>)> {
+) -> Result<(StatusCode, HeaderMap, Json<PredictResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let client_id = client_id(
+        headers
+            .get(CLIENT_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
+    match serve_prediction(&app_state, client_id, &headers, payload).await {
+        Ok(prediction) => Ok((
+            StatusCode::OK,
+            variant_headers(prediction.variant),
+            Json(PredictResponse {
+                output: prediction.output,
+            }),
+        )),
+        Err((status, error)) => Err((status, Json(ErrorResponse { error }))),
+    }
+}
+
+/// Returns the `x-model-variant` header naming the reduced precision variant which served a prediction, if any,
+/// see `MODEL_VARIANT_HEADER`.
+pub(crate) fn variant_headers(variant: Option<ModelPrecision>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(variant) = variant {
+        headers.insert(
+            MODEL_VARIANT_HEADER,
+            HeaderValue::from_static(variant.as_str()),
+        );
+    }
+    headers
+}
+
+/// A prediction served by one of the versions of the predict endpoint.
+pub(crate) struct Prediction {
+    /// The name of the model which served the prediction.
+    pub model_name: String,
+    /// The predictions returned by the model, formatted as a JSON string.
+    pub output: String,
+    /// Time taken to serve the prediction, including the time spent waiting for a worker.
+    pub latency: Duration,
+    /// The reduced precision variant of the model which served the prediction, if any. `None` for the model
+    /// artifact and for the duplicates of a request, which are answered with its output.
+    pub variant: Option<ModelPrecision>,
+}
+
+/// Serves a prediction request. This is shared by all the versions of the predict endpoint,
+/// which only differ in the shape of their responses.
+///
+/// # Arguments
+///
+/// * `app_state` - The application state.
+/// * `client_id` - The ID of the client which sent the request, used for caching validated input schemas.
+/// * `headers` - The headers of the request. The well-known headers set the options of the request, see
+///   `RequestMetadata`, and the configured ones are passed through to the prediction log and the postprocessors.
+/// * `payload` - The prediction request.
+///
+/// # Returns
+///
+/// * `Ok(Prediction)` - If the prediction was successful.
+/// * `Err((StatusCode, String))` - The status code and error message to return to the client.
+pub(crate) async fn serve_prediction(
+    app_state: &Arc<AppState>,
+    client_id: &str,
+    headers: &HeaderMap,
+    mut payload: PredictRequest,
+) -> Result<Prediction, (StatusCode, String)> {
+    if should_shed(
+        app_state.memory_watermark_bytes,
+        server_metrics::HTTP,
+        "predict",
+    ) {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Failed to predict ❌: server is over its memory watermark".to_string(),
+        ));
+    }
+
+    // reject obviously invalid requests before they are queued on the threadpool
+    if let Err(e) = validate_predict_request(&payload) {
+        tracing::warn!("Rejected invalid predict request ⚠️: {}", e);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Failed to predict ❌: {}", e),
+        ));
+    }
+
+    // the well-known headers carry the options which are not part of the payload
+    let request_metadata = match RequestMetadata::parse(|name| {
+        headers.get(name).and_then(|value| value.to_str().ok())
+    }) {
+        Ok(request_metadata) => request_metadata,
+        Err(e) => {
+            tracing::warn!("Rejected invalid request headers ⚠️: {}", e);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Failed to predict ❌: {}", e),
+            ));
+        }
+    };
+    request_metadata.attach_trace(&tracing::Span::current());
+    request_metadata.apply(&mut payload.predict_options);
+
+    // capture the configured headers only when a passthrough is configured
+    let passthrough_headers = app_state
+        .passthrough
+        .as_ref()
+        .map(|passthrough| {
+            passthrough.capture(|name| headers.get(name).and_then(|value| value.to_str().ok()))
+        })
+        .unwrap_or_default();
+
+    // look up the features of the entities referenced by the request
+    if let Some(feature_store) = &app_state.feature_store {
+        payload.input = match feature_store
+            .enrich(payload.model_name.as_str(), payload.input)
+            .await
+        {
+            Ok(input) => input,
+            Err(e) => {
+                tracing::warn!("Failed to enrich model input ⚠️: {:#}", e);
+                let status = match feature_store::is_unavailable(&e) {
+                    true => StatusCode::SERVICE_UNAVAILABLE,
+                    false => StatusCode::BAD_REQUEST,
+                };
+                return Err((status, format!("Failed to predict ❌: {:#}", e)));
+            }
+        };
+    }
+
+    // serve the duplicates of recent requests the prediction of the first request when deduplication is enabled,
+    // once the input is enriched so that differently enriched requests are not mistaken for duplicates
+    let dedup_lease = match &app_state.deduplicator {
+        Some(deduplicator) => {
+            let lookup_start = Instant::now();
+            let predict_options =
+                serde_json::to_string(&payload.predict_options).unwrap_or_default();
+            match deduplicator
+                .lookup(
+                    payload.model_name.as_str(),
+                    payload.input.as_str(),
+                    predict_options.as_str(),
+                )
+                .await
+            {
+                Lookup::Predict(lease) => Some(lease),
+                Lookup::Duplicate(output) => {
+                    let output = postprocess(
+                        app_state,
+                        payload.model_name.as_str(),
+                        &passthrough_headers,
+                        output,
+                    )?;
+                    return Ok(Prediction {
+                        model_name: payload.model_name,
+                        output,
+                        latency: lookup_start.elapsed(),
+                        variant: None,
+                    });
+                }
+            }
+        }
+        None => None,
+    };
+
+    // reject models which are not loaded before they are queued, so that unknown model names never get a flow
+    if let Err(e) = app_state.manager.check_model(payload.model_name.as_str()) {
+        return Err(
+            match misses::model_not_found(&app_state.manager, server_metrics::HTTP, client_id, &e) {
+                Some(error) => (StatusCode::NOT_FOUND, error),
+                None => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to predict ❌: {}", e),
+                ),
+            },
+        );
+    }
+
+    // reject invalid inputs before they are queued when pre-admission validation is enabled
+    if let Some(schema_cache) = &app_state.schema_cache {
+        if let Err(e) = schema_cache.validate(client_id, payload.input.as_str()) {
+            tracing::warn!("Rejected invalid model input ⚠️: {}", e);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Failed to predict ❌: {}", e),
+            ));
+        }
+    }
+
+    // the permit is held until the prediction is served
+    let _permit = match app_state
+        .in_flight_limiter
+        .admit(server_metrics::HTTP, "predict")
+    {
+        Some(permit) => permit,
+        None => {
+            return Err((
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Failed to predict ❌: server has reached its limit of in-flight predictions"
+                    .to_string(),
+            ))
+        }
+    };
+
+    let guard = DisconnectGuard::new(server_metrics::HTTP, "predict");
     let (tx, rx) = oneshot::channel();
+    let request_bytes = payload.input.len();
+
+    // cancel the prediction if this handler is dropped before the worker picks it up
+    let cancellation_token = CancellationToken::new();
+    let _cancel_on_drop = cancellation_token.clone().drop_guard();
+    let ticket = QueueTicket::default();
+    let worker_ticket = ticket.clone();
 
     let cpu_pool = &app_state.cpu_pool;
     let manager = Arc::clone(&app_state.manager);
     let model_name = payload.model_name;
     let model_input = payload.input;
+    let predict_options = payload.predict_options;
+    let priority = manager.priority_of(model_name.as_str(), &predict_options);
+    let weight = manager.scheduling_weight_of(model_name.as_str());
+    let worker_cancellation_token = cancellation_token.clone();
 
-    cpu_pool.spawn(move || worker::predict_and_send(manager, model_name, model_input, tx));
+    // the request is only copied when predictions are being recorded
+    let recording = app_state
+        .prediction_recorder
+        .as_ref()
+        .map(|recorder| (recorder, model_input.clone()));
+    let worker_model_name = model_name.clone();
+    let start = Instant::now();
 
-    match rx.await {
+    let task = Task::new(move || {
+        // the prediction was withdrawn after waiting too long for a worker
+        if !worker_ticket.claim() {
+            return;
+        }
+        worker::predict_and_send(
+            manager,
+            worker_model_name,
+            model_input,
+            predict_options,
+            tx,
+            worker_cancellation_token,
+        )
+    })
+    .with_priority(priority)
+    .with_flow(model_name.as_str(), weight)
+    .with_cancellation_token(cancellation_token.clone());
+    if let Err(e) = app_state
+        .namespaces
+        .spawn(cpu_pool, model_name.as_str(), task)
+    {
+        guard.disarm();
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Failed to predict ❌: {}", e),
+        ));
+    }
+
+    let result = match app_state
+        .in_flight_limiter
+        .wait(rx, &ticket, server_metrics::HTTP, "predict")
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            guard.disarm();
+            return Err((
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Failed to predict ❌: {}", e),
+            ));
+        }
+    };
+    guard.disarm();
+
+    match result {
         Ok(predictions) => match predictions {
-            Ok(output) => Ok((StatusCode::OK, Json(PredictResponse { output }))),
+            Ok(ServedPrediction { output, precision }) => {
+                let latency = start.elapsed();
+                server_metrics::record_payload_sizes(
+                    server_metrics::HTTP,
+                    model_name.as_str(),
+                    request_bytes,
+                    output.len(),
+                );
+                if let Some(lease) = dedup_lease {
+                    lease.complete(output.as_str());
+                }
+                let output =
+                    postprocess(app_state, model_name.as_str(), &passthrough_headers, output)?;
+                if let Some((recorder, model_input)) = recording {
+                    recorder.record(PredictionRecord::new(
+                        model_name.clone(),
+                        model_input,
+                        output.clone(),
+                        latency,
+                        passthrough_headers,
+                    ));
+                }
+                Ok(Prediction {
+                    model_name,
+                    output,
+                    latency,
+                    variant: (precision != ModelPrecision::Fp32).then_some(precision),
+                })
+            }
             Err(e) => {
+                // requests naming a model which is not loaded are the client's fault
+                if let Some(error) =
+                    misses::model_not_found(&app_state.manager, server_metrics::HTTP, client_id, &e)
+                {
+                    return Err((StatusCode::NOT_FOUND, error));
+                }
+                match e.downcast_ref::<jams_core::Error>() {
+                    Some(jams_core::Error::VersionMismatch(_)) => {
+                        return Err((
+                            StatusCode::PRECONDITION_FAILED,
+                            format!("Failed to predict ❌: {}", e),
+                        ));
+                    }
+                    Some(jams_core::Error::Input(_)) => {
+                        tracing::warn!("Rejected invalid prediction input ⚠️: {}", e);
+                        return Err((
+                            StatusCode::BAD_REQUEST,
+                            format!("Failed to predict ❌: {}", e),
+                        ));
+                    }
+                    _ => {}
+                }
                 tracing::error!("{}", format!("Failed to predict ❌: {}", e));
                 Err((
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: format!("Failed to predict ❌: {}", e),
-                    }),
+                    format!("Failed to predict ❌: {}", e),
                 ))
             }
         },
@@ -355,10 +1636,79 @@ pub async fn predict(
             tracing::error!("{}", format!("Failed to predict ❌: {}", e));
             Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: format!("Failed to predict ❌: {}", e),
-                }),
+                format!("Failed to predict ❌: {}", e),
             ))
         }
     }
 }
+
+/// Runs the postprocessors of the passthrough on the predictions of a request, if a passthrough is configured.
+///
+/// # Arguments
+///
+/// * `app_state` - The application state.
+/// * `model_name` - The name of the model which served the predictions.
+/// * `headers` - The headers captured from the request.
+/// * `output` - The predictions returned by the model.
+///
+/// # Returns
+///
+/// * `Ok(String)` - The predictions to return to the client.
+/// * `Err((StatusCode, String))` - If a postprocessor failed.
+fn postprocess(
+    app_state: &AppState,
+    model_name: &str,
+    headers: &BTreeMap<String, String>,
+    output: String,
+) -> Result<String, (StatusCode, String)> {
+    let passthrough = match &app_state.passthrough {
+        Some(passthrough) => passthrough,
+        None => return Ok(output),
+    };
+    let context = RequestContext {
+        model_name,
+        headers,
+    };
+    match passthrough.postprocess(&context, output) {
+        Ok(output) => Ok(output),
+        Err(e) => {
+            tracing::error!("Failed to postprocess predictions ❌: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to postprocess predictions ❌: {}", e),
+            ))
+        }
+    }
+}
+
+/// Validates the fields of a prediction request.
+///
+/// The model input itself is validated when it is parsed by the worker. The size of the whole
+/// request is already limited by the `track_request_body` middleware.
+///
+/// # Arguments
+///
+/// * `payload` - The prediction request to validate.
+///
+/// # Returns
+///
+/// * `Ok(())` - If the request is valid.
+/// * `Err(String)` - A description of why the request is invalid.
+pub(crate) fn validate_predict_request(payload: &PredictRequest) -> Result<(), String> {
+    if payload.model_name.is_empty() {
+        return Err("model name must not be empty".to_string());
+    }
+    if payload.model_name.len() > MAX_MODEL_NAME_LENGTH {
+        return Err(format!(
+            "model name exceeds the maximum length of {} bytes",
+            MAX_MODEL_NAME_LENGTH
+        ));
+    }
+    if payload.input.trim().is_empty() {
+        return Err("input must not be empty".to_string());
+    }
+    if let Err(e) = payload.predict_options.validate() {
+        return Err(e.to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,1270 @@
+use crate::common::admission::should_shed;
+use crate::common::authz::AUTHORIZATION_HEADER;
+use crate::common::metadata::{self, RequestMetadata};
+use crate::common::metrics as server_metrics;
+use crate::common::misses;
+use crate::common::result_store::{ResultStore, StoredResult};
+use crate::common::schema_cache::{client_id, CLIENT_ID_HEADER};
+use crate::common::state::AppState;
+use crate::common::streaming::{spawn_prediction_stream, PredictionStream};
+use crate::http::service::{
+    serve_prediction, validate_predict_request, variant_headers, PredictRequest, Prediction,
+};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use indexmap::IndexMap;
+use jams_core::determinism::determinism;
+use jams_core::model::comparison::OutputComparison;
+use jams_core::model::embedding::{EmbedOptions, Embeddings};
+use jams_core::model::output::ModelOutput;
+use jams_core::model::predict::PredictOptions;
+use jams_core::model::variant::ModelPrecision;
+use jams_core::model::versions::native_libraries;
+use jams_core::model_store::attached::STORE_SEPARATOR;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::sync::Arc;
+use tonic::codegen::tokio_stream::StreamExt;
+
+pub use crate::common::metadata::REQUEST_ID_HEADER;
+
+/// Preference with which clients of `POST /api/v1/predict` ask for a bare response, sent in the `Prefer`
+/// header as defined by RFC 7240, e.g. `Prefer: return=minimal`.
+pub const RETURN_MINIMAL: &str = "return=minimal";
+
+/// Header in which clients send their preferences, see RFC 7240.
+pub const PREFER_HEADER: &str = "prefer";
+
+/// Header with which the server acknowledges that the response is bare.
+pub const PREFERENCE_APPLIED_HEADER: &str = "preference-applied";
+
+/// Version of `POST /api/v1/compare` which selects the model served from the model store the server was
+/// started with. Any other version is a version of the model or the name of an attached model store.
+pub const CURRENT_VERSION: &str = "current";
+
+/// Event with which `POST /api/v1/predict/stream` reports that a prediction failed midway.
+pub const STREAM_ERROR_EVENT: &str = "error";
+
+/// Maximum length of the `features` query parameter of `GET /api/v1/predict/:model_name`. Bigger inputs
+/// must be sent to `POST /api/v1/predict`.
+const MAX_LOOKUP_FEATURES_LENGTH: usize = 2048;
+
+/// Number of seconds for which caches may reuse a response of `GET /api/v1/predict/:model_name`.
+const LOOKUP_MAX_AGE_SECONDS: u64 = 60;
+
+/// The response envelope returned by `POST /api/v1/predict`.
+///
+/// # Example
+/// ```json
+/// {
+///     "model_name": "titanic_model",
+///     "model_version": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+///     "request_id": "0b6f1a5e-3b8c-4c1d-9e57-2f4a2c1f7d11",
+///     "timing": { "total_ms": 1.27 },
+///     "outputs": { "predictions": [[0.45], [0.17]] },
+///     "shapes": { "predictions": [2, 1] }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PredictResponse {
+    /// The name of the model which served the prediction.
+    pub model_name: String,
+    /// The SHA-256 of the model artifact which served the prediction. This changes whenever the model is updated.
+    pub model_version: String,
+    /// The ID of the request.
+    pub request_id: String,
+    /// Time taken to serve the prediction.
+    pub timing: Timing,
+    /// The predictions returned by the model, keyed by the name of the output. Models with a single
+    /// output use the `predictions` key.
+    pub outputs: HashMap<String, Vec<Vec<f64>>>,
+    /// The class labels returned by classifiers shipped with a label map, keyed by the name of the output.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, Vec<String>>,
+    /// The text generated for each row by language models, keyed by the name of the output.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub texts: HashMap<String, Vec<String>>,
+    /// The shape of each output in `outputs`, `labels` and `texts`, keyed by the name of the output. The first
+    /// dimension is the number of rows and the others are the dimensions of each row. Scalar outputs,
+    /// which are returned as `[[x]]`, have an empty shape.
+    #[serde(default)]
+    pub shapes: HashMap<String, Vec<usize>>,
+    /// Where the predictions can be downloaded from when they are bigger than the threshold of the result
+    /// store. `outputs`, `labels`, `texts` and `shapes` are empty in that case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<StoredResult>,
+    /// The environment which served the prediction. Only set in the deterministic mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<Environment>,
+}
+
+/// The environment which served a prediction in the deterministic mode, to reproduce it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Environment {
+    /// Version of the server.
+    pub version: String,
+    /// The seed of the random number generators of the frameworks.
+    pub seed: u64,
+    /// The operating system and the CPU architecture of the server, e.g. `linux-x86_64`.
+    pub platform: String,
+    /// Versions of the native libraries loaded by the server, keyed by framework. Versions which cannot
+    /// be detected are `null`.
+    pub libraries: BTreeMap<String, Option<String>>,
+}
+
+impl Environment {
+    /// Returns the environment of the server if the deterministic mode is enabled.
+    fn current() -> Option<Environment> {
+        determinism().map(|determinism| Environment {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            seed: determinism.seed,
+            platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+            libraries: native_libraries()
+                .iter()
+                .map(|library| (library.framework.to_string(), library.version.clone()))
+                .collect(),
+        })
+    }
+}
+
+/// The request of `POST /api/v1/embed`.
+///
+/// It is a prediction request with the options of the embedding lookup.
+///
+/// # Example
+/// ```json
+/// {
+///     "model_name": "user_tower",
+///     "input": "{\"user_id\": [\"42\"]}",
+///     "output": "embeddings",
+///     "top_k": 5,
+///     "compare_to": [[0.1, 0.9]]
+/// }
+/// ```
+#[derive(Deserialize, Serialize)]
+pub struct EmbedRequest {
+    model_name: String,
+    input: String,
+    #[serde(default, skip_serializing_if = "PredictOptions::is_empty")]
+    predict_options: PredictOptions,
+    /// The output of the model which holds the embeddings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+    /// Number of nearest neighbors to look up in the embedding index of the model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    top_k: Option<usize>,
+    /// Vectors to compare each embedding to with the cosine similarity.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    compare_to: Vec<Vec<f64>>,
+}
+
+/// The response envelope returned by `POST /api/v1/embed`.
+///
+/// # Example
+/// ```json
+/// {
+///     "model_name": "user_tower",
+///     "model_version": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+///     "request_id": "0b6f1a5e-3b8c-4c1d-9e57-2f4a2c1f7d11",
+///     "timing": { "total_ms": 1.27 },
+///     "output": "embeddings",
+///     "dimension": 2,
+///     "embeddings": [[0.12, 0.88]],
+///     "similarities": [[0.99]],
+///     "neighbors": [[{ "id": "item_1", "score": 0.98 }]]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EmbedResponse {
+    /// The name of the model which returned the embeddings.
+    pub model_name: String,
+    /// The SHA-256 of the model artifact which returned the embeddings.
+    pub model_version: String,
+    /// The ID of the request.
+    pub request_id: String,
+    /// Time taken to serve the prediction.
+    pub timing: Timing,
+    /// The embeddings, along with the requested similarities and nearest neighbors.
+    #[serde(flatten)]
+    pub embeddings: Embeddings,
+}
+
+/// The request of `POST /api/v1/compare`.
+///
+/// It is a prediction request which is served by two versions of the model. A version is either `current`,
+/// the model served from the model store the server was started with, a version of the model, i.e. the
+/// SHA-256 of its artifact or the version id of its tarball in the model store, as reported by
+/// `GET /api/models`, or the name of an attached model store which serves its own version of the model,
+/// e.g. a staging bucket. The versions of the model are looked up in every loaded model store.
+///
+/// # Example
+/// ```json
+/// {
+///     "model_name": "titanic_model",
+///     "input": "{\"age\": [22.0], \"sex\": [\"male\"]}",
+///     "baseline": "current",
+///     "candidate": "staging"
+/// }
+/// ```
+#[derive(Deserialize, Serialize)]
+pub struct CompareRequest {
+    model_name: String,
+    input: String,
+    #[serde(default, skip_serializing_if = "PredictOptions::is_empty")]
+    predict_options: PredictOptions,
+    /// The version compared against. Defaults to `current`.
+    #[serde(default = "current_version")]
+    baseline: String,
+    /// The version compared to the baseline.
+    candidate: String,
+}
+
+fn current_version() -> String {
+    CURRENT_VERSION.to_string()
+}
+
+/// The response envelope returned by `POST /api/v1/compare`.
+///
+/// # Example
+/// ```json
+/// {
+///     "model_name": "titanic_model",
+///     "request_id": "0b6f1a5e-3b8c-4c1d-9e57-2f4a2c1f7d11",
+///     "baseline": { "version": "current", "model_version": "9f86d0...", "timing": { "total_ms": 1.27 }, "outputs": { "predictions": [[0.45]] } },
+///     "candidate": { "version": "staging", "model_version": "60303a...", "timing": { "total_ms": 1.31 }, "outputs": { "predictions": [[0.41]] } },
+///     "deltas": { "outputs": { "predictions": { "mean": -0.04, "mean_absolute": 0.04, "max_absolute": 0.04, "changed_rows": 1 } } }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CompareResponse {
+    /// The name of the compared model.
+    pub model_name: String,
+    /// The ID of the request.
+    pub request_id: String,
+    /// The predictions of the baseline version.
+    pub baseline: ComparedVersion,
+    /// The predictions of the candidate version.
+    pub candidate: ComparedVersion,
+    /// The differences between the outputs of the candidate and the baseline.
+    pub deltas: OutputComparison,
+}
+
+/// The predictions of one of the versions compared by `POST /api/v1/compare`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ComparedVersion {
+    /// The version as sent in the request.
+    pub version: String,
+    /// The SHA-256 of the model artifact of the version.
+    pub model_version: String,
+    /// Time taken to serve the prediction of the version.
+    pub timing: Timing,
+    /// The predictions returned by the version, keyed by the name of the output.
+    pub outputs: HashMap<String, Vec<Vec<f64>>>,
+    /// The class labels returned by the version, if it was shipped with a label map.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, Vec<String>>,
+}
+
+/// Time taken to serve a prediction.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Timing {
+    /// Time taken to serve the prediction in milliseconds, including the time spent waiting for a worker.
+    pub total_ms: f64,
+}
+
+/// The query parameters of `GET /api/v1/predict/:model_name`.
+#[derive(Deserialize)]
+pub struct PredictLookupQuery {
+    /// The features of a single row as a JSON object of numbers and strings, e.g. `{"age": 22.0, "sex": "male"}`.
+    features: String,
+}
+
+/// The error envelope returned by the versioned endpoints.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ErrorResponse {
+    /// The ID of the request.
+    pub request_id: String,
+    /// A description of the error.
+    pub error: String,
+}
+
+/// Prediction endpoint handler for `POST /api/v1/predict`.
+///
+/// The request is the same as for the unversioned `/api/predict` endpoint, but the outputs are returned
+/// as JSON instead of a JSON encoded string, wrapped in an envelope which identifies the model version
+/// and the request. The request ID is also returned in the `x-request-id` header.
+///
+/// Latency sensitive clients can send `Prefer: return=minimal` to get the bare model output instead, e.g.
+/// `{"predictions": {"predictions": [[0.45], [0.17]]}}`. It is written as returned by the model, which
+/// saves parsing and serializing the envelope and looking up the model version. Bare responses carry a
+/// `Preference-Applied: return=minimal` header.
+///
+/// Predictions served by a reduced precision variant of the model, e.g. the lightweight variant a request fell
+/// back to because of its latency budget, name the variant in the `x-model-variant` header.
+///
+/// # Arguments
+/// - `State(app_state)`: The application state.
+/// - `headers`: The request headers, used to read the request ID, the client ID and the `Prefer` header
+///   sent by the client.
+/// - `Json(payload)`: The prediction request.
+///
+/// # Returns
+/// - `StatusCode::OK` with a `PredictResponse`, or the bare model output if requested, if the prediction
+///   was successful. If a result store is configured and the predictions are bigger than its threshold,
+///   they are written to the result store and the response carries a signed URL to download them in
+///   `result` instead, even if a bare response was requested.
+/// - `StatusCode::NOT_FOUND` with an `ErrorResponse` if the model is not loaded, suggesting the closest loaded
+///   model names when `model_suggestions` is enabled.
+/// - `StatusCode::BAD_REQUEST`, `StatusCode::SERVICE_UNAVAILABLE` or `StatusCode::INTERNAL_SERVER_ERROR`
+///   with an `ErrorResponse` otherwise.
+#[tracing::instrument(skip(app_state, headers, payload))]
+pub async fn predict(
+    State(app_state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<PredictRequest>,
+) -> Response {
+    let request_id = request_id(&headers);
+    let client_id = client_id(
+        headers
+            .get(CLIENT_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
+
+    let prediction = serve_prediction(&app_state, client_id, &headers, payload).await;
+    let variant = prediction
+        .as_ref()
+        .ok()
+        .and_then(|prediction| prediction.variant);
+    let response = match prediction {
+        Ok(prediction)
+            if prefers_minimal(&headers)
+                && !app_state.result_store.as_ref().is_some_and(|result_store| {
+                    result_store.should_store(prediction.output.as_str())
+                }) =>
+        {
+            bare_prediction_response(prediction)
+        }
+        Ok(prediction) => {
+            let model_version = app_state
+                .manager
+                .get_model_metadata(prediction.model_name.clone())
+                .map(|metadata| metadata.artifact_sha256)
+                .unwrap_or_default();
+            match &app_state.result_store {
+                Some(result_store) if result_store.should_store(prediction.output.as_str()) => {
+                    stored_prediction_response(
+                        result_store,
+                        prediction,
+                        model_version,
+                        request_id.clone(),
+                    )
+                    .await
+                }
+                _ => prediction_response(prediction, model_version, request_id.clone()),
+            }
+        }
+        Err((status, error)) => error_response(status, request_id.clone(), error),
+    };
+
+    with_request_id(with_variant(response, variant), request_id.as_str())
+}
+
+/// Prediction endpoint handler for `GET /api/v1/predict/:model_name`.
+///
+/// A cacheable alternative to `POST /api/v1/predict` for tiny single-row inputs, e.g. scoring the same
+/// reference entities over and over. The features are sent as a JSON object in the `features` query
+/// parameter, e.g. `?features={"age":22.0,"sex":"male"}`, and the response is the same envelope as for
+/// `POST /api/v1/predict`.
+///
+/// Responses carry a `Cache-Control` header and a weak `ETag` derived from the model version and the
+/// features, so that CDNs and in-cluster caches can serve repeated requests. The ETag changes whenever
+/// the model is updated. Requests with a matching `If-None-Match` header get `StatusCode::NOT_MODIFIED`
+/// without running the model.
+///
+/// # Arguments
+/// - `State(app_state)`: The application state.
+/// - `Path(model_name)`: The name of the model to use for the prediction.
+/// - `Query(query)`: The features of the row to predict.
+/// - `headers`: The request headers, used to read the request ID, the client ID and `If-None-Match`.
+///
+/// # Returns
+/// - `StatusCode::OK` with a `PredictResponse` if the prediction was successful.
+/// - `StatusCode::NOT_MODIFIED` if the client already has the prediction.
+/// - `StatusCode::NOT_FOUND` if the model does not exist.
+/// - `StatusCode::BAD_REQUEST`, `StatusCode::URI_TOO_LONG`, `StatusCode::SERVICE_UNAVAILABLE` or
+///   `StatusCode::INTERNAL_SERVER_ERROR` with an `ErrorResponse` otherwise.
+#[tracing::instrument(skip(app_state, query, headers))]
+pub async fn predict_lookup(
+    State(app_state): State<Arc<AppState>>,
+    Path(model_name): Path<String>,
+    Query(query): Query<PredictLookupQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let request_id = request_id(&headers);
+    let client_id = client_id(
+        headers
+            .get(CLIENT_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
+
+    if query.features.len() > MAX_LOOKUP_FEATURES_LENGTH {
+        let response = error_response(
+            StatusCode::URI_TOO_LONG,
+            request_id.clone(),
+            format!(
+                "Failed to predict ❌: features exceed the maximum length of {} bytes, use POST /api/v1/predict instead",
+                MAX_LOOKUP_FEATURES_LENGTH
+            ),
+        );
+        return with_request_id(response, request_id.as_str());
+    }
+
+    let input = match single_row_input(query.features.as_str()) {
+        Ok(input) => input,
+        Err(e) => {
+            tracing::warn!("Rejected invalid predict lookup ⚠️: {}", e);
+            let response = error_response(
+                StatusCode::BAD_REQUEST,
+                request_id.clone(),
+                format!("Failed to predict ❌: {}", e),
+            );
+            return with_request_id(response, request_id.as_str());
+        }
+    };
+
+    let model_version = match app_state.manager.get_model_metadata(model_name.clone()) {
+        Some(metadata) => metadata.artifact_sha256,
+        None => {
+            let response = error_response(
+                StatusCode::NOT_FOUND,
+                request_id.clone(),
+                format!(
+                    "Failed to predict ❌: no model exists for model name: {}",
+                    model_name
+                ),
+            );
+            return with_request_id(response, request_id.as_str());
+        }
+    };
+
+    let etag = lookup_etag(model_name.as_str(), model_version.as_str(), input.as_str());
+    if etag_matches(&headers, etag.as_str()) {
+        let response = with_cache_headers(
+            StatusCode::NOT_MODIFIED.into_response(),
+            etag.as_str(),
+            app_state.authorization.is_some(),
+        );
+        return with_request_id(response, request_id.as_str());
+    }
+
+    let payload = PredictRequest {
+        model_name,
+        input,
+        predict_options: PredictOptions::default(),
+    };
+    let response = match serve_prediction(&app_state, client_id, &headers, payload).await {
+        Ok(prediction) => {
+            let variant = prediction.variant;
+            with_variant(
+                with_cache_headers(
+                    prediction_response(prediction, model_version, request_id.clone()),
+                    etag.as_str(),
+                    app_state.authorization.is_some(),
+                ),
+                variant,
+            )
+        }
+        Err((status, error)) => error_response(status, request_id.clone(), error),
+    };
+
+    with_request_id(response, request_id.as_str())
+}
+
+/// Streaming prediction endpoint handler for `POST /api/v1/predict/stream`.
+///
+/// Serves models which yield their outputs incrementally, e.g. the tokens generated by a sequence model, as
+/// server-sent events. The request is the same as for `POST /api/v1/predict`. Each chunk of the output is sent
+/// as soon as it is ready in the data of a `message` event, formatted like the bare output of a model, e.g.
+/// `{"predictions": {"token_id": [[42.0]]}}`. If the prediction fails midway, an `error` event carrying an
+/// `ErrorResponse` is sent after the chunks which were already generated. The stream ends after the last event.
+///
+/// Disconnecting stops the prediction between two chunks, which frees the worker. Deduplication, prediction
+/// recording and the result store do not apply to streams.
+///
+/// # Arguments
+/// - `State(app_state)`: The application state.
+/// - `headers`: The request headers, used to read the request ID and the well-known headers sent by the client.
+/// - `Json(payload)`: The prediction request.
+///
+/// # Returns
+/// - `StatusCode::OK` with a `text/event-stream` body if the prediction was queued.
+/// - `StatusCode::NOT_FOUND` with an `ErrorResponse` if the model does not exist.
+/// - `StatusCode::BAD_REQUEST` with an `ErrorResponse` if the model does not stream its predictions or the
+///   request is invalid.
+/// - `StatusCode::PRECONDITION_FAILED`, `StatusCode::TOO_MANY_REQUESTS` or `StatusCode::SERVICE_UNAVAILABLE`
+///   with an `ErrorResponse` if the prediction could not be queued.
+#[tracing::instrument(skip(app_state, headers, payload))]
+pub async fn predict_stream(
+    State(app_state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<PredictRequest>,
+) -> Response {
+    let request_id = request_id(&headers);
+    let response = match start_prediction_stream(&app_state, &headers, payload) {
+        Ok(chunks) => {
+            let error_request_id = request_id.clone();
+            let events = chunks.map(move |chunk| {
+                let event = match chunk {
+                    Ok(chunk) => Event::default().data(chunk),
+                    Err(e) => {
+                        tracing::error!("Failed to stream predictions ❌: {}", e);
+                        let error = ErrorResponse {
+                            request_id: error_request_id.clone(),
+                            error: format!("Failed to predict ❌: {}", e),
+                        };
+                        Event::default()
+                            .event(STREAM_ERROR_EVENT)
+                            .data(serde_json::to_string(&error).unwrap_or_default())
+                    }
+                };
+                Ok::<Event, Infallible>(event)
+            });
+            Sse::new(events)
+                .keep_alive(KeepAlive::default())
+                .into_response()
+        }
+        Err((status, error)) => error_response(status, request_id.clone(), error),
+    };
+
+    with_request_id(response, request_id.as_str())
+}
+
+/// Checks and admits a streaming prediction request, then queues the prediction on the worker pool.
+fn start_prediction_stream(
+    app_state: &Arc<AppState>,
+    headers: &HeaderMap,
+    mut payload: PredictRequest,
+) -> Result<PredictionStream, (StatusCode, String)> {
+    if should_shed(
+        app_state.memory_watermark_bytes,
+        server_metrics::HTTP,
+        "predict_stream",
+    ) {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Failed to predict ❌: server is over its memory watermark".to_string(),
+        ));
+    }
+
+    if let Err(e) = validate_predict_request(&payload) {
+        tracing::warn!("Rejected invalid predict request ⚠️: {}", e);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Failed to predict ❌: {}", e),
+        ));
+    }
+
+    // the well-known headers carry the options which are not part of the payload
+    let request_metadata = match RequestMetadata::parse(|name| {
+        headers.get(name).and_then(|value| value.to_str().ok())
+    }) {
+        Ok(request_metadata) => request_metadata,
+        Err(e) => {
+            tracing::warn!("Rejected invalid request headers ⚠️: {}", e);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Failed to predict ❌: {}", e),
+            ));
+        }
+    };
+    request_metadata.attach_trace(&tracing::Span::current());
+    if let Err(e) = request_metadata.check_model_version(payload.model_name.as_str(), || {
+        app_state
+            .manager
+            .get_model_metadata(payload.model_name.clone())
+            .map(|metadata| metadata.artifact_sha256)
+    }) {
+        return Err((
+            StatusCode::PRECONDITION_FAILED,
+            format!("Failed to predict ❌: {}", e),
+        ));
+    }
+    request_metadata.apply(&mut payload.predict_options);
+
+    // reject models which return their whole output at once before the prediction is queued
+    if let Err(e) = app_state
+        .manager
+        .check_streaming(payload.model_name.as_str())
+    {
+        let client_id = client_id(
+            headers
+                .get(CLIENT_ID_HEADER)
+                .and_then(|value| value.to_str().ok()),
+        );
+        return Err(
+            match misses::model_not_found(&app_state.manager, server_metrics::HTTP, client_id, &e) {
+                Some(error) => (StatusCode::NOT_FOUND, error),
+                None => (
+                    StatusCode::BAD_REQUEST,
+                    format!("Failed to predict ❌: {}", e),
+                ),
+            },
+        );
+    }
+
+    // the permit is held until the stream is dropped
+    let permit = match app_state
+        .in_flight_limiter
+        .admit(server_metrics::HTTP, "predict_stream")
+    {
+        Some(permit) => permit,
+        None => {
+            return Err((
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Failed to predict ❌: server has reached its limit of in-flight predictions"
+                    .to_string(),
+            ))
+        }
+    };
+
+    spawn_prediction_stream(
+        app_state,
+        payload.model_name,
+        payload.input,
+        payload.predict_options,
+        permit,
+    )
+    .map_err(|e| {
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Failed to predict ❌: {}", e),
+        )
+    })
+}
+
+/// Embedding endpoint handler for `POST /api/v1/embed`.
+///
+/// Serves a prediction of an embedding model like `POST /api/v1/predict`, and returns the raw vectors of
+/// one of its outputs instead of every output. On request, the server also computes the cosine similarity
+/// of each embedding to the vectors of `compare_to` and looks up its `top_k` nearest neighbors in the
+/// embedding index shipped with the model, so that clients do not have to do the similarity math.
+///
+/// # Arguments
+/// - `State(app_state)`: The application state.
+/// - `headers`: The request headers, used to read the request ID and the client ID sent by the client.
+/// - `Json(payload)`: The embedding request.
+///
+/// # Returns
+/// - `StatusCode::OK` with an `EmbedResponse` if the embeddings were computed.
+/// - `StatusCode::BAD_REQUEST` with an `ErrorResponse` if the options do not match the embeddings, e.g. the
+///   output does not exist or the model was shipped without an embedding index.
+/// - `StatusCode::SERVICE_UNAVAILABLE` or `StatusCode::INTERNAL_SERVER_ERROR` with an `ErrorResponse` if
+///   the prediction failed.
+#[tracing::instrument(skip(app_state, headers, payload))]
+pub async fn embed(
+    State(app_state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<EmbedRequest>,
+) -> Response {
+    let request_id = request_id(&headers);
+    let client_id = client_id(
+        headers
+            .get(CLIENT_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
+
+    let options = EmbedOptions {
+        output: payload.output,
+        top_k: payload.top_k,
+        compare_to: payload.compare_to,
+    };
+    if let Err(e) = options.validate() {
+        tracing::warn!("Rejected invalid embed request ⚠️: {}", e);
+        let response = error_response(
+            StatusCode::BAD_REQUEST,
+            request_id.clone(),
+            format!("Failed to embed ❌: {}", e),
+        );
+        return with_request_id(response, request_id.as_str());
+    }
+
+    let prediction = PredictRequest {
+        model_name: payload.model_name,
+        input: payload.input,
+        predict_options: payload.predict_options,
+    };
+    let response = match serve_prediction(&app_state, client_id, &headers, prediction).await {
+        Ok(prediction) => embed_response(&app_state, prediction, options, request_id.clone()).await,
+        Err((status, error)) => error_response(status, request_id.clone(), error),
+    };
+
+    with_request_id(response, request_id.as_str())
+}
+
+/// Wraps the embeddings of a prediction into the response envelope of the embed endpoint.
+///
+/// The embeddings are normalized and searched for their nearest neighbours on the blocking pool, as the
+/// similarity search scans the whole embedding index of the model.
+async fn embed_response(
+    app_state: &Arc<AppState>,
+    prediction: Prediction,
+    options: EmbedOptions,
+    request_id: String,
+) -> Response {
+    let output = match serde_json::from_str::<ModelOutput>(prediction.output.as_str()) {
+        Ok(output) => output,
+        Err(e) => {
+            tracing::error!("Failed to parse model output ❌: {}", e);
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                request_id,
+                format!("Failed to parse model output ❌: {}", e),
+            );
+        }
+    };
+    let model_name = prediction.model_name;
+    let manager = app_state.manager.clone();
+    let embed_model_name = model_name.clone();
+    let embeddings = match tokio::task::spawn_blocking(move || {
+        manager.embed(embed_model_name, output, &options)
+    })
+    .await
+    {
+        Ok(Ok(embeddings)) => embeddings,
+        Ok(Err(e)) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                request_id,
+                format!("Failed to embed ❌: {}", e),
+            )
+        }
+        Err(e) => {
+            tracing::error!("Failed to join embedding task ❌: {}", e);
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                request_id,
+                format!("Failed to embed ❌: {}", e),
+            );
+        }
+    };
+    let model_version = app_state
+        .manager
+        .get_model_metadata(model_name.clone())
+        .map(|metadata| metadata.artifact_sha256)
+        .unwrap_or_default();
+
+    (
+        StatusCode::OK,
+        Json(EmbedResponse {
+            model_name,
+            model_version,
+            request_id,
+            timing: Timing {
+                total_ms: prediction.latency.as_secs_f64() * 1000.0,
+            },
+            embeddings,
+        }),
+    )
+        .into_response()
+}
+
+/// Converts the features of a single row, e.g. `{"age": 22.0, "sex": "male"}`, into a model input
+/// with one value per feature, e.g. `{"age":[22.0],"sex":["male"]}`.
+///
+/// The features are written in the order of the request, as the position of a feature is its column
+/// in the model input of the models which take their features by position.
+fn single_row_input(features: &str) -> Result<String, String> {
+    let features: IndexMap<String, Value> = match serde_json::from_str(features) {
+        Ok(features) => features,
+        Err(e) => return Err(format!("features must be a JSON object: {}", e)),
+    };
+    if features.is_empty() {
+        return Err("features must not be empty".to_string());
+    }
+
+    let mut input: IndexMap<String, [Value; 1]> = IndexMap::new();
+    for (name, value) in features {
+        match value {
+            Value::Number(_) | Value::String(_) => {
+                input.insert(name, [value]);
+            }
+            _ => return Err(format!("feature {} must be a number or a string", name)),
+        }
+    }
+    serde_json::to_string(&input).map_err(|e| e.to_string())
+}
+
+/// Returns a weak ETag identifying the prediction of a model version for a model input. The ETag is
+/// weak because the timing and the request ID of the response change with every request.
+fn lookup_etag(model_name: &str, model_version: &str, input: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [model_name, model_version, input] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    format!("W/\"{:x}\"", hasher.finalize())
+}
+
+/// Returns true if the `If-None-Match` header of the request matches the ETag.
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+}
+
+/// Adds the caching headers of `GET /api/v1/predict/:model_name` to the response. When authorization is configured
+/// the response depends on the caller, so only private caches may reuse it and it varies on the bearer token.
+fn with_cache_headers(mut response: Response, etag: &str, authorized: bool) -> Response {
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        headers.insert(header::ETAG, value);
+    }
+    let visibility = if authorized { "private" } else { "public" };
+    if let Ok(value) = HeaderValue::from_str(&format!(
+        "{}, max-age={}",
+        visibility, LOOKUP_MAX_AGE_SECONDS
+    )) {
+        headers.insert(header::CACHE_CONTROL, value);
+    }
+    if authorized {
+        headers.insert(header::VARY, HeaderValue::from_static(AUTHORIZATION_HEADER));
+    }
+    response
+}
+
+/// Wraps a prediction into the response envelope of the versioned predict endpoints.
+fn prediction_response(
+    prediction: Prediction,
+    model_version: String,
+    request_id: String,
+) -> Response {
+    match serde_json::from_str::<ModelOutput>(prediction.output.as_str()) {
+        Ok(output) => (
+            StatusCode::OK,
+            Json(PredictResponse {
+                model_name: prediction.model_name,
+                model_version,
+                request_id,
+                timing: Timing {
+                    total_ms: prediction.latency.as_secs_f64() * 1000.0,
+                },
+                shapes: output.shapes(),
+                outputs: output.predictions,
+                labels: output.labels,
+                texts: output.texts,
+                result: None,
+                environment: Environment::current(),
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to parse model output ❌: {}", e);
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                request_id,
+                format!("Failed to parse model output ❌: {}", e),
+            )
+        }
+    }
+}
+
+/// Writes a prediction to the result store and returns the response envelope with a signed URL to it
+/// instead of the predictions.
+async fn stored_prediction_response(
+    result_store: &ResultStore,
+    prediction: Prediction,
+    model_version: String,
+    request_id: String,
+) -> Response {
+    match result_store
+        .store(prediction.model_name.as_str(), prediction.output)
+        .await
+    {
+        Ok(result) => (
+            StatusCode::OK,
+            Json(PredictResponse {
+                model_name: prediction.model_name,
+                model_version,
+                request_id,
+                timing: Timing {
+                    total_ms: prediction.latency.as_secs_f64() * 1000.0,
+                },
+                outputs: HashMap::new(),
+                labels: HashMap::new(),
+                texts: HashMap::new(),
+                shapes: HashMap::new(),
+                result: Some(result),
+                environment: Environment::current(),
+            }),
+        )
+            .into_response(),
+        Err(e) => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            request_id,
+            format!("Failed to store predictions ❌: {}", e),
+        ),
+    }
+}
+
+/// Comparison endpoint handler for `POST /api/v1/compare`.
+///
+/// Serves the same input with two versions of a model and returns the predictions of both along with
+/// summary deltas, e.g. to check a candidate version during a rollout or to compare the version being
+/// served with the previous one during an incident. Both predictions go through the worker pool like
+/// any other prediction, concurrently.
+///
+/// # Arguments
+/// - `State(app_state)`: The application state.
+/// - `headers`: The request headers, used to read the request ID and the client ID sent by the client.
+/// - `Json(payload)`: The comparison request.
+///
+/// # Returns
+/// - `StatusCode::OK` with a `CompareResponse` if both versions served the prediction.
+/// - `StatusCode::BAD_REQUEST` with an `ErrorResponse` if a version is neither `current`, a loaded version
+///   of the model nor the name of an attached model store, or the request is invalid.
+/// - `StatusCode::SERVICE_UNAVAILABLE` or `StatusCode::INTERNAL_SERVER_ERROR` with an `ErrorResponse` if
+///   either prediction failed, or a version was unloaded while it was being compared.
+#[tracing::instrument(skip(app_state, headers, payload))]
+pub async fn compare(
+    State(app_state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<CompareRequest>,
+) -> Response {
+    let request_id = request_id(&headers);
+    let client_id = client_id(
+        headers
+            .get(CLIENT_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
+
+    let model_names = (
+        versioned_model_name(&app_state, payload.model_name.as_str(), &payload.baseline),
+        versioned_model_name(&app_state, payload.model_name.as_str(), &payload.candidate),
+    );
+    let (baseline_model_name, candidate_model_name) = match model_names {
+        (Ok(baseline), Ok(candidate)) => (baseline, candidate),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::warn!("Rejected invalid compare request ⚠️: {}", e);
+            let response = error_response(
+                StatusCode::BAD_REQUEST,
+                request_id.clone(),
+                format!("Failed to compare ❌: {}", e),
+            );
+            return with_request_id(response, request_id.as_str());
+        }
+    };
+    let request = |model_name: String| PredictRequest {
+        model_name,
+        input: payload.input.clone(),
+        predict_options: payload.predict_options.clone(),
+    };
+    let (baseline, candidate) = tokio::join!(
+        serve_prediction(
+            &app_state,
+            client_id,
+            &headers,
+            request(baseline_model_name)
+        ),
+        serve_prediction(
+            &app_state,
+            client_id,
+            &headers,
+            request(candidate_model_name)
+        )
+    );
+
+    let response = match (baseline, candidate) {
+        (Ok(baseline), Ok(candidate)) => {
+            compare_response(&app_state, payload, baseline, candidate, request_id.clone())
+        }
+        (Err((status, error)), _) | (_, Err((status, error))) => {
+            error_response(status, request_id.clone(), error)
+        }
+    };
+
+    with_request_id(response, request_id.as_str())
+}
+
+/// Returns the name under which the given version of a model is served.
+///
+/// A version is either `current`, a version of the model, i.e. the SHA-256 of its artifact or the version id
+/// of its tarball in the model store, or the name of an attached model store. Versions of the model are looked
+/// up in the model store the server was started with first and then in the attached model stores.
+fn versioned_model_name(
+    app_state: &AppState,
+    model_name: &str,
+    version: &str,
+) -> anyhow::Result<String> {
+    if version == CURRENT_VERSION {
+        return Ok(model_name.to_string());
+    }
+
+    let stores = app_state.manager.get_stores();
+    let candidates = std::iter::once(model_name.to_string()).chain(
+        stores
+            .iter()
+            .map(|store| format!("{}{}{}", store.name, STORE_SEPARATOR, model_name)),
+    );
+    for candidate in candidates {
+        let is_version = app_state
+            .manager
+            .get_model_metadata(candidate.clone())
+            .is_some_and(|metadata| {
+                (!metadata.artifact_sha256.is_empty()
+                    && metadata.artifact_sha256.eq_ignore_ascii_case(version))
+                    || metadata.source_version.as_deref() == Some(version)
+            });
+        if is_version {
+            return Ok(candidate);
+        }
+    }
+
+    match stores.iter().any(|store| store.name == version) {
+        true => Ok(format!("{}{}{}", version, STORE_SEPARATOR, model_name)),
+        false => anyhow::bail!(
+            "Unknown version {} of model {}, expected {}, the SHA-256 or store version of a loaded version of the model or the name of an attached model store",
+            version,
+            model_name,
+            CURRENT_VERSION
+        ),
+    }
+}
+
+/// Wraps the predictions of both versions and their deltas into the response envelope of the compare endpoint.
+fn compare_response(
+    app_state: &Arc<AppState>,
+    payload: CompareRequest,
+    baseline: Prediction,
+    candidate: Prediction,
+    request_id: String,
+) -> Response {
+    let outputs =
+        serde_json::from_str::<ModelOutput>(baseline.output.as_str()).and_then(|baseline_output| {
+            serde_json::from_str::<ModelOutput>(candidate.output.as_str())
+                .map(|candidate_output| (baseline_output, candidate_output))
+        });
+    let (baseline_output, candidate_output) = match outputs {
+        Ok(outputs) => outputs,
+        Err(e) => {
+            tracing::error!("Failed to parse model output ❌: {}", e);
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                request_id,
+                format!("Failed to parse model output ❌: {}", e),
+            );
+        }
+    };
+    let deltas = OutputComparison::new(&baseline_output, &candidate_output);
+    let compared_version = |version: String,
+                            prediction: Prediction,
+                            output: ModelOutput|
+     -> Result<ComparedVersion, String> {
+        let metadata = app_state
+            .manager
+            .get_model_metadata(prediction.model_name.clone())
+            .ok_or_else(|| {
+                format!(
+                    "Failed to compare ❌: model {} was unloaded while being compared",
+                    prediction.model_name
+                )
+            })?;
+        Ok(ComparedVersion {
+            version,
+            model_version: metadata.artifact_sha256,
+            timing: Timing {
+                total_ms: prediction.latency.as_secs_f64() * 1000.0,
+            },
+            outputs: output.predictions,
+            labels: output.labels,
+        })
+    };
+    let versions =
+        compared_version(payload.baseline, baseline, baseline_output).and_then(|baseline| {
+            compared_version(payload.candidate, candidate, candidate_output)
+                .map(|candidate| (baseline, candidate))
+        });
+    let (baseline, candidate) = match versions {
+        Ok(versions) => versions,
+        Err(e) => {
+            tracing::warn!("{}", e);
+            return error_response(StatusCode::SERVICE_UNAVAILABLE, request_id, e);
+        }
+    };
+
+    (
+        StatusCode::OK,
+        Json(CompareResponse {
+            model_name: payload.model_name,
+            request_id,
+            baseline,
+            candidate,
+            deltas,
+        }),
+    )
+        .into_response()
+}
+
+/// Returns the bare model output, without parsing it.
+fn bare_prediction_response(prediction: Prediction) -> Response {
+    (
+        StatusCode::OK,
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            ),
+            (
+                header::HeaderName::from_static(PREFERENCE_APPLIED_HEADER),
+                HeaderValue::from_static(RETURN_MINIMAL),
+            ),
+        ],
+        prediction.output,
+    )
+        .into_response()
+}
+
+/// Returns true if the client asked for a bare response. The `Prefer` header may carry several
+/// comma separated preferences and be sent more than once.
+fn prefers_minimal(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(PREFER_HEADER)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|preference| preference.trim().eq_ignore_ascii_case(RETURN_MINIMAL))
+}
+
+/// Reads the request ID sent by the client, or generates a new one if it is missing or invalid.
+fn request_id(headers: &HeaderMap) -> String {
+    metadata::request_id(
+        headers
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    )
+}
+
+fn error_response(status: StatusCode, request_id: String, error: String) -> Response {
+    (status, Json(ErrorResponse { request_id, error })).into_response()
+}
+
+/// Names the reduced precision variant which served the prediction in the `x-model-variant` header, if any.
+fn with_variant(mut response: Response, variant: Option<ModelPrecision>) -> Response {
+    response.headers_mut().extend(variant_headers(variant));
+    response
+}
+
+fn with_request_id(mut response: Response, request_id: &str) -> Response {
+    if let Ok(value) = HeaderValue::from_str(request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::metadata::MAX_REQUEST_ID_LENGTH;
+    use uuid::Uuid;
+
+    #[test]
+    fn successfully_uses_request_id_sent_by_client() {
+        // Arrange
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("my-request"));
+
+        // Act
+        let request_id = request_id(&headers);
+
+        // Assert
+        assert_eq!(request_id, "my-request")
+    }
+
+    #[test]
+    fn successfully_generates_request_id_when_missing_or_too_long() {
+        // Arrange
+        let mut headers = HeaderMap::new();
+        let too_long = "a".repeat(MAX_REQUEST_ID_LENGTH + 1);
+
+        // Act
+        let generated = request_id(&headers);
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_str(&too_long).unwrap());
+        let replaced = request_id(&headers);
+
+        // Assert
+        assert!(Uuid::parse_str(&generated).is_ok());
+        assert!(Uuid::parse_str(&replaced).is_ok());
+    }
+
+    #[test]
+    fn successfully_detects_minimal_return_preference() {
+        // Arrange
+        let mut minimal = HeaderMap::new();
+        minimal.append(PREFER_HEADER, HeaderValue::from_static("respond-async"));
+        minimal.append(
+            PREFER_HEADER,
+            HeaderValue::from_static("wait=5, Return=Minimal"),
+        );
+        let mut representation = HeaderMap::new();
+        representation.insert(
+            PREFER_HEADER,
+            HeaderValue::from_static("return=representation"),
+        );
+
+        // Act & Assert
+        assert!(prefers_minimal(&minimal));
+        assert!(!prefers_minimal(&representation));
+        assert!(!prefers_minimal(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn successfully_converts_features_into_a_single_row_input() {
+        // Act
+        let input = single_row_input(r#"{"sex": "male", "age": 22.0, "pclass": 1}"#).unwrap();
+
+        // Assert
+        assert_eq!(input, r#"{"sex":["male"],"age":[22.0],"pclass":[1]}"#);
+    }
+
+    #[test]
+    fn fails_to_convert_features_which_are_not_scalars() {
+        // Act
+        let nested = single_row_input(r#"{"age": [22.0]}"#);
+        let empty = single_row_input("{}");
+        let invalid = single_row_input("age=22.0");
+
+        // Assert
+        assert!(nested.is_err());
+        assert!(empty.is_err());
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn successfully_matches_etag_sent_by_client() {
+        // Arrange
+        let etag = lookup_etag("my_model", "abc123", r#"{"age":[22.0]}"#);
+        let other = lookup_etag("my_model", "def456", r#"{"age":[22.0]}"#);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_str(&format!("{}, {}", other, etag.trim_start_matches("W/")))
+                .unwrap(),
+        );
+
+        // Act
+        let matches = etag_matches(&headers, etag.as_str());
+
+        // Assert
+        assert!(matches);
+        assert!(!etag_matches(&HeaderMap::new(), etag.as_str()));
+    }
+}
@@ -2,17 +2,50 @@ pub mod common;
 pub mod grpc;
 pub mod http;
 
+use crate::common::listener::ListenerOptions;
 use crate::common::server;
 use crate::common::server::HTTP;
 use crate::common::state::build_app_state;
+use jams_core::model_store::registry::JsonCodec;
 
 pub async fn start(config: server::Config, num_physical_cores: usize) {
     // print terminal art
     println!("{}", server::ART);
 
-    // init port number
+    // init addresses
     let http_port = config.port.unwrap_or(3000);
     let grpc_port = config.port.unwrap_or(4000);
+    let host = config
+        .host
+        .clone()
+        .unwrap_or(server::DEFAULT_HOST.to_string());
+    let admin_address = config.admin_port.map(|admin_port| {
+        let admin_host = config
+            .admin_host
+            .clone()
+            .unwrap_or(server::DEFAULT_ADMIN_HOST.to_string());
+        format!("{}:{}", admin_host, admin_port)
+    });
+    let listener_options = ListenerOptions::from_config(&config);
+
+    // harden the process before any model is loaded
+    if let Some(hardening) = &config.hardening {
+        if let Err(e) = hardening.apply() {
+            tracing::error!("Failed to enable hardened mode ❌: {}", e);
+            return;
+        }
+    }
+
+    // configure the frameworks before any model is loaded
+    if config.deterministic.unwrap_or(false) {
+        if let Err(e) = jams_core::determinism::enable(config.deterministic_seed.unwrap_or(0)) {
+            tracing::error!("Failed to enable deterministic mode ❌: {}", e);
+            return;
+        }
+    }
+
+    // log the native library versions, to compare them with the versions used for training
+    jams_core::model::versions::log_native_libraries();
 
     // setup shared state
     let shared_state = match build_app_state(config.clone(), num_physical_cores).await {
@@ -26,16 +59,48 @@ pub async fn start(config: server::Config, num_physical_cores: usize) {
         }
     };
 
+    let manager = shared_state.manager.clone();
+
     if config.protocol == HTTP {
         // Start HTTP server
-        http::server::start(shared_state, http_port)
-            .await
-            .expect("Failed to start HTTP server");
+        http::server::start(
+            shared_state,
+            format!("{}:{}", host, http_port),
+            admin_address,
+            listener_options,
+        )
+        .await
+        .expect("Failed to start HTTP server");
     } else {
-        // Start gRPC server
-        grpc::server::start(shared_state, grpc_port)
-            .await
-            .expect("Failed to start gRPC server");
+        // Start gRPC server, along with the HTTP admin server if configured
+        let address = format!("{}:{}", host, grpc_port);
+        match admin_address {
+            None => grpc::server::start(shared_state, address, listener_options)
+                .await
+                .expect("Failed to start gRPC server"),
+            Some(admin_address) => {
+                tokio::try_join!(
+                    grpc::server::start(shared_state.clone(), address, listener_options),
+                    http::server::start_admin(shared_state, admin_address)
+                )
+                .expect("Failed to start gRPC server");
+            }
+        }
+    }
+
+    // run the teardown hooks of the models, as no request can use them once the servers have stopped
+    manager.shutdown().await;
+
+    // save the usage made since the last periodic save so that no request is lost over the restart
+    if let Err(e) = manager.save_usage_state() {
+        tracing::error!("Failed to save usage state ❌: {}", e);
+    }
+
+    // save the registry state after a graceful shutdown so that the next startup can skip fetching models
+    if let Some(path) = config.registry_state_path {
+        if let Err(e) = manager.save_registry_state(path.as_str(), &JsonCodec) {
+            tracing::error!("Failed to save registry state ❌: {}", e);
+        }
     }
 }
 
@@ -57,6 +122,7 @@ mod tests {
             s3_bucket_name: Some("".to_string()),
             azure_storage_container_name: Some("".to_string()),
             poll_interval: Some(0),
+            ..Default::default()
         };
 
         // Act
@@ -78,6 +144,7 @@ mod tests {
             s3_bucket_name: Some("".to_string()),
             azure_storage_container_name: Some("".to_string()),
             poll_interval: Some(0),
+            ..Default::default()
         };
 
         // Act
@@ -1,21 +1,28 @@
 use jams_core::manager::ManagerBuilder;
 use jams_core::model_store::local::filesystem::LocalModelStore;
 use jams_core::model_store::ModelStore;
+use jams_core::pool::executor::ExecutorKind;
+use jams_core::pool::scheduler::{Budget, Scheduler};
 use jams_proto::jams_v1::model_server_client::ModelServerClient;
 use jams_proto::jams_v1::model_server_server::ModelServerServer;
+use jams_serve::common::admission::InFlightLimiter;
+use jams_serve::common::bulk::BulkOperations;
+use jams_serve::common::namespace::Namespaces;
 use jams_serve::common::state::AppState;
 use jams_serve::grpc::service::JamsService;
-use rayon::ThreadPoolBuilder;
 use std::sync::Arc;
 use std::time::Duration;
 use tonic::transport::server::Router;
 use tonic::transport::{Channel, Server};
 
 async fn setup_shared_state() -> Arc<AppState> {
-    let cpu_pool = ThreadPoolBuilder::new()
-        .num_threads(1)
-        .build()
-        .expect("Failed to build rayon threadpool ❌");
+    let cpu_pool = Scheduler::new(
+        "shared",
+        ExecutorKind::Rayon
+            .build(1, "jams-worker")
+            .expect("Failed to build rayon threadpool ❌"),
+        Budget::default(),
+    );
 
     let model_store = LocalModelStore::new("tests/model_store".to_string())
         .await
@@ -27,7 +34,25 @@ async fn setup_shared_state() -> Arc<AppState> {
             .expect("Failed to initialize manager ❌"),
     );
 
-    Arc::new(AppState { manager, cpu_pool })
+    Arc::new(AppState {
+        manager,
+        cpu_pool,
+        memory_watermark_bytes: None,
+        in_flight_limiter: InFlightLimiter::default(),
+        prediction_recorder: None,
+        namespaces: Namespaces::default(),
+        schema_cache: None,
+        deep_health: None,
+        feature_store: None,
+        result_store: None,
+        deduplicator: None,
+        authorization: None,
+        passthrough: None,
+        smoke_test: None,
+        bulk_operations: BulkOperations::default(),
+        startup_report: None,
+        response_signer: None,
+    })
 }
 
 pub async fn jams_grpc_test_router() -> Router {
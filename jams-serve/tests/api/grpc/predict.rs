@@ -41,6 +41,7 @@ async fn successfully_calls_the_predict_rpc() {
         .predict(PredictRequest {
             model_name: "titanic_model".to_string(),
             input: model_input,
+            predict_options: "".to_string(),
         })
         .await;
 
@@ -78,9 +79,144 @@ async fn fails_to_call_the_predict_rpc_when_input_is_wrong() {
         .predict(PredictRequest {
             model_name: "titanic_model".to_string(),
             input: incorrect_model_input,
+            predict_options: "".to_string(),
         })
         .await;
 
     // Assert
     assert!(response.is_err());
 }
+
+#[tokio::test]
+async fn fails_to_call_the_predict_rpc_with_invalid_argument_when_input_cannot_be_parsed() {
+    // Arrange
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let test_server = jams_grpc_test_router().await;
+
+    tokio::spawn(async move {
+        test_server
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+    let mut client = grpc_client_stub(addr.to_string()).await;
+
+    // Act: Make Predictions
+    let response = client
+        .predict(PredictRequest {
+            model_name: "titanic_model".to_string(),
+            input: "{\"pclass\": [\"1\"".to_string(),
+            predict_options: "".to_string(),
+        })
+        .await;
+
+    // Assert
+    assert_eq!(response.unwrap_err().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn fails_to_call_the_predict_rpc_when_predict_options_are_invalid() {
+    // Arrange
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let test_server = jams_grpc_test_router().await;
+
+    tokio::spawn(async move {
+        test_server
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+    let mut client = grpc_client_stub(addr.to_string()).await;
+
+    // Act: Make Predictions
+    let model_input = serde_json::json!({"age": [22.0]}).to_string();
+    let response = client
+        .predict(PredictRequest {
+            model_name: "titanic_model".to_string(),
+            input: model_input,
+            predict_options: r#"{"mode": "embedding"}"#.to_string(),
+        })
+        .await;
+
+    // Assert
+    assert_eq!(response.unwrap_err().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn fails_to_call_the_predict_rpc_when_model_version_is_not_loaded() {
+    // Arrange
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let test_server = jams_grpc_test_router().await;
+
+    tokio::spawn(async move {
+        test_server
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+    let mut client = grpc_client_stub(addr.to_string()).await;
+
+    // Act: Make Predictions
+    let model_input = serde_json::json!({"age": [22.0]}).to_string();
+    let mut request = tonic::Request::new(PredictRequest {
+        model_name: "titanic_model".to_string(),
+        input: model_input,
+        predict_options: "".to_string(),
+    });
+    request
+        .metadata_mut()
+        .insert("x-model-version", "0000000".parse().unwrap());
+    let mut invalid = tonic::Request::new(request.get_ref().clone());
+    invalid
+        .metadata_mut()
+        .insert("x-request-priority", "urgent".parse().unwrap());
+    let response = client.predict(request).await;
+    let invalid = client.predict(invalid).await;
+
+    // Assert
+    assert_eq!(
+        response.unwrap_err().code(),
+        tonic::Code::FailedPrecondition
+    );
+    assert_eq!(invalid.unwrap_err().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn fails_to_call_the_predict_stream_rpc_when_model_does_not_stream_its_predictions() {
+    // Arrange
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let test_server = jams_grpc_test_router().await;
+
+    tokio::spawn(async move {
+        test_server
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+    let mut client = grpc_client_stub(addr.to_string()).await;
+
+    // Act
+    let model_input = serde_json::json!({"input_ids": [1, 2, 3]}).to_string();
+    let response = client
+        .predict_stream(PredictRequest {
+            model_name: "my_awesome_californiahousing_model".to_string(),
+            input: model_input.clone(),
+            predict_options: "".to_string(),
+        })
+        .await;
+    let missing = client
+        .predict_stream(PredictRequest {
+            model_name: "my_awesome_sequence_model".to_string(),
+            input: model_input,
+            predict_options: "".to_string(),
+        })
+        .await;
+
+    // Assert
+    assert_eq!(response.unwrap_err().code(), tonic::Code::InvalidArgument);
+    assert_eq!(missing.unwrap_err().code(), tonic::Code::NotFound);
+}
@@ -25,3 +25,116 @@ async fn successfully_calls_the_healthcheck_endpoint_and_return_200() {
     // Assert
     assert!(response.status().is_success())
 }
+
+#[tokio::test]
+async fn successfully_calls_the_deep_healthcheck_endpoint_and_return_200() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!("http://{}/healthz/deep", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["healthy"], true);
+    assert_eq!(report["model_name"], "titanic_model");
+    assert!(report["latency_ms"].as_f64().is_some());
+}
+
+#[tokio::test]
+async fn successfully_calls_the_readiness_endpoint_and_return_200() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!("http://{}/readyz", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    let readiness: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(readiness["ready"], true);
+    assert_eq!(readiness["loading_remaining_models"], false);
+    assert!(readiness["pollers"].is_array());
+}
+
+#[tokio::test]
+async fn successfully_calls_the_version_endpoint_and_return_200() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!("http://{}/api/version", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body["version"].is_string());
+    assert_eq!(body["libraries"].as_array().unwrap().len(), 4);
+    assert_eq!(body["libraries"][0]["framework"], "tensorflow");
+}
+
+#[tokio::test]
+async fn routes_the_startup_report_at_the_versioned_and_unversioned_paths() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    for path in ["api/v1/startup-report", "api/startup-report"] {
+        // Act
+        let url = format!("http://{}/{}", addr, path);
+        let response = client
+            .get(url)
+            .send()
+            .await
+            .expect("Failed to make request");
+
+        // Assert the handler answers, which has no report as the test server skips the startup
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+        assert!(!response.headers().contains_key("deprecation"));
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["error"], "Startup report is not available ❌");
+    }
+}
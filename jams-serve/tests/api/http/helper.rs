@@ -2,16 +2,35 @@ use axum::Router;
 use jams_core::manager::ManagerBuilder;
 use jams_core::model_store::local::filesystem::LocalModelStore;
 use jams_core::model_store::ModelStore;
+use jams_core::pool::executor::ExecutorKind;
+use jams_core::pool::scheduler::{Budget, Scheduler};
+use jams_serve::common::admission::InFlightLimiter;
+use jams_serve::common::authz::{parse_cedar_policies, Authorization};
+use jams_serve::common::bulk::BulkOperations;
+use jams_serve::common::health::DeepHealthCheck;
+use jams_serve::common::namespace::Namespaces;
+use jams_serve::common::passthrough::Passthrough;
+use jams_serve::common::result_store::{ResultBackend, ResultStore};
+use jams_serve::common::signing::ResponseSigner;
 use jams_serve::common::state::AppState;
-use jams_serve::http::router::build_router;
-use rayon::ThreadPoolBuilder;
-use std::sync::Arc;
+use jams_serve::http::router::{build_admin_router, build_public_router, build_router};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-async fn setup_shared_state() -> Arc<AppState> {
-    let cpu_pool = ThreadPoolBuilder::new()
-        .num_threads(1)
-        .build()
-        .expect("Failed to build rayon threadpool ❌");
+async fn setup_shared_state(
+    result_store: Option<ResultStore>,
+    authorization: Option<Authorization>,
+    passthrough: Option<Passthrough>,
+    response_signer: Option<ResponseSigner>,
+) -> Arc<AppState> {
+    let cpu_pool = Scheduler::new(
+        "shared",
+        ExecutorKind::Rayon
+            .build(1, "jams-worker")
+            .expect("Failed to build rayon threadpool ❌"),
+        Budget::default(),
+    );
 
     let model_store = LocalModelStore::new("tests/model_store".to_string())
         .await
@@ -23,12 +42,92 @@ async fn setup_shared_state() -> Arc<AppState> {
             .expect("Failed to initialize manager ❌"),
     );
 
-    Arc::new(AppState { manager, cpu_pool })
+    Arc::new(AppState {
+        manager,
+        cpu_pool,
+        memory_watermark_bytes: None,
+        in_flight_limiter: InFlightLimiter::default(),
+        prediction_recorder: None,
+        namespaces: Namespaces::default(),
+        schema_cache: None,
+        deep_health: Some(DeepHealthCheck::new(
+            "titanic_model".to_string(),
+            serde_json::json!({
+                "pclass": ["1"],
+                "sex": ["male"],
+                "age": [22.0],
+                "sibsp": ["0"],
+                "parch": ["0"],
+                "fare": [151.55],
+                "embarked": ["S"],
+                "class": ["First"],
+                "who": ["man"],
+                "adult_male": ["True"],
+                "deck": ["Unknown"],
+                "embark_town": ["Southampton"],
+                "alone": ["True"]
+            })
+            .to_string(),
+        )),
+        feature_store: None,
+        result_store,
+        deduplicator: None,
+        authorization,
+        passthrough,
+        smoke_test: None,
+        bulk_operations: BulkOperations::default(),
+        startup_report: None,
+        response_signer,
+    })
 }
 pub async fn test_router() -> Router {
     // we will not set a model for testing purpose
     // this will start the model server without any models loaded
-    let shared_state = setup_shared_state().await;
+    let shared_state = setup_shared_state(None, None, None, None).await;
+
+    build_router(shared_state).unwrap()
+}
+
+pub async fn test_router_with_result_store(threshold_bytes: usize) -> Router {
+    let result_store = ResultStore::new(
+        ResultBackend::Memory(Mutex::new(HashMap::new())),
+        "predictions".to_string(),
+        threshold_bytes,
+        Duration::from_secs(60),
+    );
+    let shared_state = setup_shared_state(Some(result_store), None, None, None).await;
+
+    build_router(shared_state).unwrap()
+}
+
+/// The key of the bearer tokens accepted by `test_router_with_cedar_policies`.
+pub const TOKEN_KEY: &[u8] = &[7; 32];
+
+pub async fn test_router_with_cedar_policies(policies: &str) -> Router {
+    let authorization =
+        Authorization::with_engine(TOKEN_KEY.to_vec(), parse_cedar_policies(policies).unwrap());
+    let shared_state = setup_shared_state(None, Some(authorization), None, None).await;
 
     build_router(shared_state).unwrap()
 }
+
+pub async fn test_router_with_passthrough(passthrough: Passthrough) -> Router {
+    let shared_state = setup_shared_state(None, None, Some(passthrough), None).await;
+
+    build_router(shared_state).unwrap()
+}
+
+pub async fn test_router_with_response_signer(response_signer: ResponseSigner) -> Router {
+    let shared_state = setup_shared_state(None, None, None, Some(response_signer)).await;
+
+    build_router(shared_state).unwrap()
+}
+
+pub async fn test_public_and_admin_routers() -> (Router, Router) {
+    let shared_state = setup_shared_state(None, None, None, None).await;
+
+    (
+        build_public_router(shared_state.clone()).unwrap(),
+        build_admin_router(shared_state).unwrap(),
+    )
+}
@@ -0,0 +1,164 @@
+use crate::http::helper::{test_public_and_admin_routers, test_router};
+use reqwest::Client;
+use tokio::net::TcpListener;
+
+#[tokio::test]
+async fn successfully_calls_the_metrics_endpoint_and_return_200() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!("http://{}/metrics", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success())
+}
+
+#[tokio::test]
+async fn successfully_records_payload_size_for_api_requests() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let models_url = format!("http://{}/api/models", addr).to_string();
+    let metrics_url = format!("http://{}/metrics", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    client
+        .get(models_url)
+        .send()
+        .await
+        .expect("Failed to make request");
+    let response = client
+        .get(metrics_url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    let body = response.text().await.unwrap();
+    assert!(body.contains("jams_request_payload_bytes"));
+    assert!(body.contains("jams_request_body_read_seconds"));
+}
+
+#[tokio::test]
+async fn fails_to_call_the_predict_endpoint_when_payload_is_too_large_and_returns_413() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!("http://{}/api/predict", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .body(vec![b' '; 3 * 1024 * 1024])
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 413)
+}
+
+#[tokio::test]
+async fn successfully_records_model_usage_when_metrics_are_scraped() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let metrics_url = format!("http://{}/metrics", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .get(metrics_url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    let body = response.text().await.unwrap();
+    assert!(body.contains("jams_model_requests{model=\"my_awesome_reg_model\"}"));
+}
+
+#[tokio::test]
+async fn successfully_serves_metrics_on_the_admin_port_only() {
+    // Arrange
+    let client = Client::new();
+    let public_listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let admin_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let public_addr = public_listener.local_addr().unwrap();
+    let admin_addr = admin_listener.local_addr().unwrap();
+    let (public_router, admin_router) = test_public_and_admin_routers().await;
+
+    tokio::spawn(async move {
+        axum::serve(public_listener, public_router).await.unwrap();
+    });
+    tokio::spawn(async move {
+        axum::serve(admin_listener, admin_router).await.unwrap();
+    });
+
+    // Act
+    let public_metrics = client
+        .get(format!("http://{}/metrics", public_addr))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let public_models = client
+        .get(format!("http://{}/api/v1/models", public_addr))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let public_health = client
+        .get(format!("http://{}/healthcheck", public_addr))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let admin_metrics = client
+        .get(format!("http://{}/metrics", admin_addr))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let admin_models = client
+        .get(format!("http://{}/api/v1/models", admin_addr))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(public_metrics.status(), reqwest::StatusCode::NOT_FOUND);
+    assert_eq!(public_models.status(), reqwest::StatusCode::NOT_FOUND);
+    assert!(public_health.status().is_success());
+    assert!(admin_metrics.status().is_success());
+    assert!(admin_models.status().is_success());
+}
@@ -1,4 +1,5 @@
 mod health_check;
 mod helper;
+mod metrics;
 mod models;
 mod predict;
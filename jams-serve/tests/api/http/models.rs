@@ -1,5 +1,9 @@
 use crate::http::helper::test_router;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use jams_core::model_store::common::tarball_version;
 use reqwest::Client;
+use std::path::Path;
 use tokio::net::TcpListener;
 
 #[tokio::test]
@@ -191,3 +195,487 @@ async fn fails_to_call_the_delete_model_endpoint_and_return_500_when_model_does_
     // Assert
     assert!(response.status().is_server_error())
 }
+
+#[tokio::test]
+async fn successfully_calls_the_update_model_labels_endpoint_and_filters_models_by_label() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let labels_url = format!("http://{}/api/v1/models/labels", addr).to_string();
+    let models_url = format!("http://{}/api/v1/models?label=team:risk", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let labels_response = client
+        .put(labels_url)
+        .json(&serde_json::json!(
+            {
+                "model_name": "my_awesome_reg_model",
+                "labels": {"team": "risk"}
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let models_response = client
+        .get(models_url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(labels_response.status().is_success());
+    let body: serde_json::Value = models_response.json().await.unwrap();
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["models"][0]["name"], "my_awesome_reg_model");
+    assert_eq!(body["models"][0]["labels"]["team"], "risk");
+}
+
+#[tokio::test]
+async fn fails_to_call_the_update_model_labels_endpoint_and_return_404_when_model_does_not_exist() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!("http://{}/api/v1/models/labels", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .put(url)
+        .json(&serde_json::json!(
+            {
+                "model_name": "model_does_not_exist",
+                "labels": {"team": "risk"}
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND)
+}
+
+#[tokio::test]
+async fn fails_to_call_the_get_models_endpoint_and_return_400_when_label_selector_is_invalid() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!("http://{}/api/v1/models?label=team:", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST)
+}
+
+#[tokio::test]
+async fn successfully_calls_the_get_models_endpoint_and_filters_models_unused_for_days() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!("http://{}/api/v1/models?unused_for_days=1", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    let body: serde_json::Value = response.json().await.unwrap();
+    // the models were loaded when the server started, so none of them can be unused for a day
+    assert_eq!(body["total"], 0);
+}
+
+#[tokio::test]
+async fn successfully_calls_the_stores_endpoints_to_attach_and_detach_a_model_store() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let stores_url = format!("http://{}/api/v1/stores", addr).to_string();
+    let detach_url = format!("http://{}/api/v1/stores?name=archive", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let attach_response = client
+        .post(stores_url.clone())
+        .json(&serde_json::json!(
+            {
+                "name": "archive",
+                "url": "file://tests/model_store"
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let stores_response = client
+        .get(stores_url)
+        .send()
+        .await
+        .expect("Failed to make request");
+    let detach_response = client
+        .delete(detach_url.clone())
+        .send()
+        .await
+        .expect("Failed to make request");
+    let detach_again_response = client
+        .delete(detach_url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(attach_response.status().is_success());
+    let body: serde_json::Value = attach_response.json().await.unwrap();
+    assert!(body["models"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("archive.my_awesome_reg_model")));
+    let body: serde_json::Value = stores_response.json().await.unwrap();
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["stores"][0]["url"], "file://tests/model_store");
+    assert!(detach_response.status().is_success());
+    assert_eq!(
+        detach_again_response.status(),
+        reqwest::StatusCode::NOT_FOUND
+    )
+}
+
+#[tokio::test]
+async fn fails_to_call_the_attach_store_endpoint_and_return_400_when_url_is_invalid() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!("http://{}/api/v1/stores", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .post(url)
+        .json(&serde_json::json!(
+            {
+                "name": "archive",
+                "url": "gs://my-bucket"
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST)
+}
+
+#[tokio::test]
+async fn fails_to_call_the_get_model_sample_endpoint_and_return_404_when_model_does_not_exist() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!(
+        "http://{}/api/v1/models/model_which_does_not_exist/sample?rows=2&strategy=random",
+        addr
+    )
+    .to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND)
+}
+
+#[tokio::test]
+async fn successfully_calls_the_get_model_diff_endpoint_and_return_200() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!(
+        "http://{}/api/models/my_awesome_reg_model/diff?from={}&to=latest",
+        addr,
+        tarball_version("tests/model_store/lightgbm-my_awesome_reg_model.tar.gz").unwrap()
+    )
+    .to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["model_name"], "my_awesome_reg_model");
+    assert_eq!(body["identical"], true);
+    assert!(body.get("artifact_sha256").is_none());
+    assert!(body.get("tarball_sha256").is_none());
+}
+
+#[tokio::test]
+async fn fails_to_call_the_get_model_diff_endpoint_and_return_400_when_version_is_not_stored() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!(
+        "http://{}/api/v1/models/my_awesome_reg_model/diff?from=version_which_is_not_stored&to=latest",
+        addr
+    )
+    .to_string();
+    let missing_url = format!(
+        "http://{}/api/v1/models/model_which_does_not_exist/diff?from=latest&to=latest",
+        addr
+    )
+    .to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to make request");
+    let missing = client
+        .get(missing_url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND)
+}
+
+#[tokio::test]
+async fn successfully_calls_the_refresh_models_endpoint_and_return_progress() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!("http://{}/api/v1/models/refresh", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .post(url)
+        .json(&serde_json::json!({ "label": "team:nobody" }))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let started: serde_json::Value = response.json().await.unwrap();
+    let progress_url = format!(
+        "http://{}/api/v1/models/operations/{}",
+        addr,
+        started["id"].as_str().unwrap()
+    );
+    let progress = client
+        .get(progress_url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(started["action"], "refresh");
+    assert_eq!(started["total"], 0);
+    assert!(progress.status().is_success());
+    let progress: serde_json::Value = progress.json().await.unwrap();
+    assert_eq!(progress["done"], true);
+}
+
+#[tokio::test]
+async fn fails_to_call_the_refresh_models_endpoint_and_return_400_when_payload_is_malformed() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!("http://{}/api/v1/models/refresh", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let unknown_field = client
+        .post(url.as_str())
+        .json(&serde_json::json!({ "labels": "team:risk" }))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let invalid_json = client
+        .post(url.as_str())
+        .header("content-type", "application/json")
+        .body("{\"label\": ")
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(unknown_field.status(), reqwest::StatusCode::BAD_REQUEST);
+    assert_eq!(invalid_json.status(), reqwest::StatusCode::BAD_REQUEST)
+}
+
+#[tokio::test]
+async fn fails_to_call_the_bulk_delete_models_endpoint_and_return_400_when_label_is_missing() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let url = format!("http://{}/api/v1/models/bulk", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .delete(url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST)
+}
+
+/// Writes a tarball of the sample lightgbm model shipped with an input schema into `dir`.
+fn write_model_with_schema(dir: &Path) {
+    let tarball = std::fs::File::create(dir.join("lightgbm-schema_model.tar.gz")).unwrap();
+    let mut builder = tar::Builder::new(GzEncoder::new(tarball, Compression::default()));
+    builder
+        .append_path_with_name(
+            "../jams-core/tests/model_storage/models/lightgbm-my_awesome_reg_model.txt",
+            "lightgbm-schema_model.txt",
+        )
+        .unwrap();
+    let schema = br#"{"feature_1": "float", "feature_2": "int"}"#;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(schema.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, "bundle/schema.json", schema.as_slice())
+        .unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+}
+
+#[tokio::test]
+async fn successfully_calls_the_get_model_sample_endpoint_and_return_200() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let model_store = tempfile::tempdir().unwrap();
+    write_model_with_schema(model_store.path());
+    let stores_url = format!("http://{}/api/v1/stores", addr);
+    let sample_url = format!("http://{}/api/v1/models/samples.schema_model/sample", addr);
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    let attach_response = client
+        .post(stores_url)
+        .json(&serde_json::json!(
+            {
+                "name": "samples",
+                "url": format!("file://{}", model_store.path().display())
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+    assert!(attach_response.status().is_success());
+
+    // Act
+    let response = client
+        .get(format!("{}?rows=2", sample_url))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let too_many_rows_response = client
+        .get(format!("{}?rows=1000", sample_url))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["model_name"], "samples.schema_model");
+    let input: serde_json::Value = serde_json::from_str(body["input"].as_str().unwrap()).unwrap();
+    assert_eq!(
+        input,
+        serde_json::json!({"feature_1": [0.5, 0.5], "feature_2": [5, 5]})
+    );
+    assert_eq!(
+        too_many_rows_response.status(),
+        reqwest::StatusCode::BAD_REQUEST
+    );
+}
@@ -1,5 +1,20 @@
-use crate::http::helper::test_router;
+use crate::http::helper::{
+    test_router, test_router_with_cedar_policies, test_router_with_passthrough,
+    test_router_with_response_signer, test_router_with_result_store, TOKEN_KEY,
+};
+use jams_serve::common::authz::issue_token;
+use jams_serve::common::metadata::REQUEST_ID_HEADER;
+use jams_serve::common::passthrough::{
+    Passthrough, PassthroughConfig, Postprocessor, RequestContext,
+};
+use jams_serve::common::signing::{
+    verify, ResponseSigner, ResponseSigningConfig, SIGNATURE_HEADER, SIGNATURE_ISSUED_AT_HEADER,
+    SIGNATURE_KEY_ID_HEADER,
+};
 use reqwest::Client;
+use std::io::Write;
+use std::sync::Arc;
+use tempfile::NamedTempFile;
 use tokio::net::TcpListener;
 
 #[tokio::test]
@@ -53,6 +68,199 @@ async fn successfully_calls_the_predict_endpoint_and_return_200() {
     assert!(response.status().is_success())
 }
 
+/// Wraps the predictions with the experiment arm of the request.
+struct TagExperiment;
+
+impl Postprocessor for TagExperiment {
+    fn postprocess(&self, context: &RequestContext, output: String) -> anyhow::Result<String> {
+        let arm = context
+            .headers
+            .get("x-experiment")
+            .map(|arm| arm.as_str())
+            .unwrap_or("control");
+        Ok(serde_json::json!({ "arm": arm, "predictions": output }).to_string())
+    }
+}
+
+#[tokio::test]
+async fn successfully_calls_the_predict_endpoint_and_return_postprocessed_output() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let passthrough = Passthrough::new(PassthroughConfig {
+        headers: vec!["X-Experiment".to_string()],
+    })
+    .with_postprocessor(Arc::new(TagExperiment));
+    let router = test_router_with_passthrough(passthrough).await;
+    let predict_url = format!("http://{}/api/predict", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act: Make Predictions
+    let model_input = serde_json::json!(
+            {
+                "pclass": ["1"],
+                "sex": ["male"],
+                "age": [22.0],
+                "sibsp": ["0"],
+                "parch": ["0"],
+                "fare": [151.55],
+                "embarked": ["S"],
+                "class": ["First"],
+                "who": ["man"],
+                "adult_male": ["True"],
+                "deck": ["Unknown"],
+                "embark_town": ["Southampton"],
+                "alone": ["True"]
+            }
+    )
+    .to_string();
+
+    let response = client
+        .post(predict_url)
+        .header("x-experiment", "candidate")
+        .json(&serde_json::json!(
+            {
+                "model_name": "titanic_model",
+                "input": model_input
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    let body: serde_json::Value = response.json().await.unwrap();
+    let output: serde_json::Value = serde_json::from_str(body["output"].as_str().unwrap()).unwrap();
+    assert_eq!(output["arm"], "candidate");
+}
+
+#[tokio::test]
+async fn successfully_calls_the_predict_endpoint_and_return_signed_response() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut key_file = NamedTempFile::new().unwrap();
+    key_file.write_all("ab".repeat(32).as_bytes()).unwrap();
+    let response_signer = ResponseSigner::new(ResponseSigningConfig {
+        key_file: Some(key_file.path().to_string_lossy().to_string()),
+        key_id: Some("test".to_string()),
+        ..ResponseSigningConfig::default()
+    })
+    .unwrap();
+    let router = test_router_with_response_signer(response_signer).await;
+    let predict_url = format!("http://{}/api/v1/predict", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act: Make Predictions
+    let model_input = serde_json::json!(
+            {
+                "pclass": ["1"],
+                "sex": ["male"],
+                "age": [22.0],
+                "sibsp": ["0"],
+                "parch": ["0"],
+                "fare": [151.55],
+                "embarked": ["S"],
+                "class": ["First"],
+                "who": ["man"],
+                "adult_male": ["True"],
+                "deck": ["Unknown"],
+                "embark_town": ["Southampton"],
+                "alone": ["True"]
+            }
+    )
+    .to_string();
+
+    let response = client
+        .post(predict_url)
+        .header(REQUEST_ID_HEADER, "request-1")
+        .json(&serde_json::json!(
+            {
+                "model_name": "titanic_model",
+                "input": model_input
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    let signature = response.headers()[SIGNATURE_HEADER]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let issued_at: u64 = response.headers()[SIGNATURE_ISSUED_AT_HEADER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(response.headers()[SIGNATURE_KEY_ID_HEADER], "test");
+    assert_eq!(response.headers()[REQUEST_ID_HEADER], "request-1");
+    let body = response.bytes().await.unwrap();
+    assert!(verify(
+        &[0xab; 32],
+        "request-1",
+        issued_at,
+        &body,
+        &signature
+    ));
+    assert!(!verify(
+        &[0xab; 32],
+        "request-1",
+        issued_at,
+        b"{}",
+        &signature
+    ));
+    // a response replayed for another request does not verify
+    assert!(!verify(
+        &[0xab; 32],
+        "request-2",
+        issued_at,
+        &body,
+        &signature
+    ));
+}
+
+#[tokio::test]
+async fn fails_to_call_the_predict_endpoint_and_return_400_when_input_cannot_be_parsed() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let predict_url = format!("http://{}/api/predict", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act: Make Predictions
+    let response = client
+        .post(predict_url)
+        .json(&serde_json::json!(
+            {
+                "model_name": "titanic_model",
+                "input": "{\"pclass\": [\"1\""
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test]
 async fn fails_to_calls_the_predict_endpoint_and_return_500_when_input_is_wrong() {
     // Arrange
@@ -95,3 +303,814 @@ async fn fails_to_calls_the_predict_endpoint_and_return_500_when_input_is_wrong(
     println!("{:?}", response);
     assert!(response.status().is_server_error())
 }
+
+#[tokio::test]
+async fn fails_to_calls_the_predict_endpoint_and_return_400_when_request_is_invalid() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let predict_url = format!("http://{}/api/predict", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let requests = [
+        serde_json::json!({"model_name": "", "input": "{\"age\": [22.0]}"}),
+        serde_json::json!({"model_name": "a".repeat(1024), "input": "{\"age\": [22.0]}"}),
+        serde_json::json!({"model_name": "titanic_model", "input": ""}),
+    ];
+
+    for request in requests {
+        // Act: Make Predictions
+        let response = client
+            .post(predict_url.as_str())
+            .json(&request)
+            .send()
+            .await
+            .expect("Failed to make request");
+
+        // Assert
+        println!("{:?}", response);
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST)
+    }
+}
+
+#[tokio::test]
+async fn successfully_calls_the_v1_predict_endpoint_and_return_response_envelope() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let predict_url = format!("http://{}/api/v1/predict", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act: Make Predictions
+    let model_input = serde_json::json!(
+            {
+                "pclass": ["1", "3"],
+                "sex": ["male", "female"],
+                "age": [22.0, 23.79929292929293],
+                "sibsp": ["0", "1", ],
+                "parch": ["0", "0"],
+                "fare": [151.55, 14.4542],
+                "embarked": ["S", "C"],
+                "class": ["First", "Third"],
+                "who": ["man", "woman"],
+                "adult_male": ["True", "False"],
+                "deck": ["Unknown", "Unknown"],
+                "embark_town": ["Southampton", "Cherbourg"],
+                "alone": ["True", "False"]
+            }
+    )
+    .to_string();
+
+    let response = client
+        .post(predict_url)
+        .header("x-request-id", "my-request")
+        .json(&serde_json::json!(
+            {
+                "model_name": "titanic_model",
+                "input": model_input
+            }
+
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    assert_eq!(response.headers()["x-request-id"], "my-request");
+    assert!(response.headers().get("deprecation").is_none());
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["model_name"], "titanic_model");
+    assert_eq!(body["request_id"], "my-request");
+    assert!(!body["model_version"].as_str().unwrap().is_empty());
+    assert!(body["timing"]["total_ms"].is_number());
+    assert!(body["outputs"]["predictions"].is_array());
+}
+
+#[tokio::test]
+async fn successfully_calls_the_v1_predict_endpoint_and_return_url_of_stored_result() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router_with_result_store(1).await;
+    let predict_url = format!("http://{}/api/v1/predict", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let features: serde_json::Map<String, serde_json::Value> = (0..28)
+        .map(|i| (format!("feature_{}", i), serde_json::json!([0.5, 0.25])))
+        .collect();
+    let model_input = serde_json::Value::Object(features).to_string();
+
+    // Act: Make Predictions
+    let response = client
+        .post(predict_url)
+        .json(&serde_json::json!(
+            {
+                "model_name": "my_awesome_reg_model",
+                "input": model_input
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["model_name"], "my_awesome_reg_model");
+    assert!(body["outputs"].as_object().unwrap().is_empty());
+    let key = body["result"]["key"].as_str().unwrap();
+    assert!(key.starts_with("predictions/my_awesome_reg_model/"));
+    assert_eq!(body["result"]["url"], format!("memory://{}", key));
+    assert!(body["result"]["size_bytes"].as_u64().unwrap() > 1);
+}
+
+#[tokio::test]
+async fn successfully_calls_the_v1_predict_endpoint_and_return_bare_output_when_preferred() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let predict_url = format!("http://{}/api/v1/predict", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let features: serde_json::Map<String, serde_json::Value> = (0..28)
+        .map(|i| (format!("feature_{}", i), serde_json::json!([0.5, 0.25])))
+        .collect();
+    let model_input = serde_json::Value::Object(features).to_string();
+
+    // Act: Make Predictions
+    let response = client
+        .post(predict_url)
+        .header("x-request-id", "my-request")
+        .header("prefer", "return=minimal")
+        .json(&serde_json::json!(
+            {
+                "model_name": "my_awesome_reg_model",
+                "input": model_input
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    assert_eq!(response.headers()["x-request-id"], "my-request");
+    assert_eq!(response.headers()["preference-applied"], "return=minimal");
+    assert_eq!(response.headers()["content-type"], "application/json");
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body.get("model_name").is_none());
+    assert!(body.get("timing").is_none());
+    assert_eq!(
+        body["predictions"]["predictions"].as_array().unwrap().len(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn fails_to_calls_the_v1_predict_endpoint_and_return_error_envelope_when_request_is_invalid()
+{
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let predict_url = format!("http://{}/api/v1/predict", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act: Make Predictions
+    let response = client
+        .post(predict_url)
+        .json(&serde_json::json!({"model_name": "", "input": "{\"age\": [22.0]}"}))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    let request_id = response.headers()["x-request-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["request_id"], request_id);
+    assert!(body["error"].is_string());
+}
+
+#[tokio::test]
+async fn successfully_marks_the_unversioned_predict_endpoint_as_deprecated() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let predict_url = format!("http://{}/api/predict", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act: Make Predictions
+    let response = client
+        .post(predict_url)
+        .json(&serde_json::json!({"model_name": "", "input": "{\"age\": [22.0]}"}))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.headers()["deprecation"], "true");
+    assert_eq!(
+        response.headers()["link"],
+        "</api/v1/predict>; rel=\"successor-version\""
+    );
+}
+
+#[tokio::test]
+async fn successfully_calls_the_predict_lookup_endpoint_with_caching_headers() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let predict_url = format!("http://{}/api/v1/predict/my_awesome_reg_model", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let features: serde_json::Map<String, serde_json::Value> = (0..28)
+        .map(|i| (format!("feature_{}", i), serde_json::json!(0.5)))
+        .collect();
+    let features = serde_json::Value::Object(features).to_string();
+
+    // Act: Make Predictions
+    let response = client
+        .get(predict_url.as_str())
+        .query(&[("features", features.as_str())])
+        .send()
+        .await
+        .expect("Failed to make request");
+    let etag = response.headers()["etag"].to_str().unwrap().to_string();
+    let cache_control = response.headers()["cache-control"].clone();
+    let vary = response.headers().get("vary").cloned();
+    let body: serde_json::Value = response.json().await.unwrap();
+
+    let cached_response = client
+        .get(predict_url.as_str())
+        .query(&[("features", features.as_str())])
+        .header("if-none-match", etag.as_str())
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(etag.starts_with("W/\""));
+    assert_eq!(cache_control, "public, max-age=60");
+    assert!(vary.is_none());
+    assert_eq!(body["model_name"], "my_awesome_reg_model");
+    assert_eq!(body["outputs"]["predictions"].as_array().unwrap().len(), 1);
+    assert_eq!(body["shapes"]["predictions"], serde_json::json!([1, 1]));
+    assert_eq!(cached_response.status(), reqwest::StatusCode::NOT_MODIFIED);
+}
+
+#[tokio::test]
+async fn successfully_calls_the_predict_lookup_endpoint_with_private_caching_headers_when_authorized(
+) {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router_with_cedar_policies(
+        r#"permit(
+            principal == Jams::Principal::"team-a",
+            action == Jams::Action::"predict",
+            resource == Jams::Model::"my_awesome_reg_model"
+        );"#,
+    )
+    .await;
+    let predict_url = format!("http://{}/api/v1/predict/my_awesome_reg_model", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let features: serde_json::Map<String, serde_json::Value> = (0..28)
+        .map(|i| (format!("feature_{}", i), serde_json::json!(0.5)))
+        .collect();
+    let features = serde_json::Value::Object(features).to_string();
+
+    // Act: Make Predictions
+    let response = client
+        .get(predict_url.as_str())
+        .query(&[("features", features.as_str())])
+        .bearer_auth(issue_token(TOKEN_KEY, "team-a", i64::MAX))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    assert_eq!(response.headers()["cache-control"], "private, max-age=60");
+    assert_eq!(response.headers()["vary"], "authorization");
+}
+
+#[tokio::test]
+async fn fails_to_call_the_predict_lookup_endpoint_and_return_404_when_model_does_not_exist() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let predict_url = format!("http://{}/api/v1/predict/unknown_model", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act: Make Predictions
+    let response = client
+        .get(predict_url)
+        .query(&[("features", "{\"age\": 22.0}")])
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    assert!(response.headers().get("etag").is_none());
+}
+
+#[tokio::test]
+async fn successfully_calls_the_embed_endpoint_and_return_similarities() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let embed_url = format!("http://{}/api/v1/embed", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let features: serde_json::Map<String, serde_json::Value> = (0..28)
+        .map(|i| (format!("feature_{}", i), serde_json::json!([0.5, 0.25])))
+        .collect();
+    let model_input = serde_json::Value::Object(features).to_string();
+
+    // Act
+    let response = client
+        .post(embed_url)
+        .header("x-request-id", "my-request")
+        .json(&serde_json::json!(
+            {
+                "model_name": "my_awesome_reg_model",
+                "input": model_input,
+                "compare_to": [[1.0], [-1.0]]
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(response.status().is_success());
+    assert_eq!(response.headers()["x-request-id"], "my-request");
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["model_name"], "my_awesome_reg_model");
+    assert_eq!(body["output"], "predictions");
+    assert_eq!(body["dimension"], 1);
+    assert_eq!(body["embeddings"].as_array().unwrap().len(), 2);
+    assert_eq!(body["similarities"][0].as_array().unwrap().len(), 2);
+    assert!(body.get("neighbors").is_none());
+}
+
+#[tokio::test]
+async fn fails_to_call_the_embed_endpoint_and_return_400_when_model_has_no_embedding_index() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let embed_url = format!("http://{}/api/v1/embed", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let features: serde_json::Map<String, serde_json::Value> = (0..28)
+        .map(|i| (format!("feature_{}", i), serde_json::json!([0.5])))
+        .collect();
+    let model_input = serde_json::Value::Object(features).to_string();
+
+    // Act
+    let response = client
+        .post(embed_url)
+        .json(&serde_json::json!(
+            {
+                "model_name": "my_awesome_reg_model",
+                "input": model_input,
+                "top_k": 3
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .contains("no embedding index"));
+}
+
+#[tokio::test]
+async fn successfully_calls_the_compare_endpoint_and_return_deltas_of_both_versions() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let stores_url = format!("http://{}/api/v1/stores", addr).to_string();
+    let compare_url = format!("http://{}/api/v1/compare", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let features: serde_json::Map<String, serde_json::Value> = (0..28)
+        .map(|i| (format!("feature_{}", i), serde_json::json!([0.5, 0.25])))
+        .collect();
+    let model_input = serde_json::Value::Object(features).to_string();
+
+    // Act
+    let attach_response = client
+        .post(stores_url)
+        .json(&serde_json::json!(
+            {
+                "name": "archive",
+                "url": "file://tests/model_store"
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let response = client
+        .post(compare_url)
+        .header("x-request-id", "my-request")
+        .json(&serde_json::json!(
+            {
+                "model_name": "my_awesome_reg_model",
+                "input": model_input,
+                "candidate": "archive"
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(attach_response.status().is_success());
+    assert!(response.status().is_success());
+    assert_eq!(response.headers()["x-request-id"], "my-request");
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["model_name"], "my_awesome_reg_model");
+    assert_eq!(body["baseline"]["version"], "current");
+    assert_eq!(body["candidate"]["version"], "archive");
+    assert_eq!(
+        body["baseline"]["model_version"],
+        body["candidate"]["model_version"]
+    );
+    assert_eq!(
+        body["baseline"]["outputs"]["predictions"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(
+        body["deltas"]["outputs"]["predictions"]["max_absolute"],
+        0.0
+    );
+    assert_eq!(body["deltas"]["outputs"]["predictions"]["changed_rows"], 0);
+}
+
+#[tokio::test]
+async fn successfully_calls_the_compare_endpoint_with_the_artifact_sha256_as_version() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let compare_url = format!("http://{}/api/v1/compare", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let features: serde_json::Map<String, serde_json::Value> = (0..28)
+        .map(|i| (format!("feature_{}", i), serde_json::json!([0.5])))
+        .collect();
+    let model_input = serde_json::Value::Object(features).to_string();
+    let current: serde_json::Value = client
+        .post(compare_url.as_str())
+        .json(&serde_json::json!(
+            {
+                "model_name": "my_awesome_reg_model",
+                "input": model_input,
+                "candidate": "current"
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request")
+        .json()
+        .await
+        .unwrap();
+    let model_version = current["baseline"]["model_version"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // Act
+    let response = client
+        .post(compare_url)
+        .json(&serde_json::json!(
+            {
+                "model_name": "my_awesome_reg_model",
+                "input": model_input,
+                "candidate": model_version
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert!(!model_version.is_empty());
+    assert!(response.status().is_success());
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["candidate"]["version"], model_version);
+    assert_eq!(body["candidate"]["model_version"], model_version);
+}
+
+#[tokio::test]
+async fn fails_to_call_the_compare_endpoint_and_return_400_when_version_is_unknown() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let compare_url = format!("http://{}/api/v1/compare", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .post(compare_url)
+        .json(&serde_json::json!(
+            {
+                "model_name": "my_awesome_reg_model",
+                "input": "{\"feature_0\": [0.5]}",
+                "candidate": "staging"
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .contains("Unknown version staging"));
+}
+
+#[tokio::test]
+async fn fails_to_call_the_v1_predict_endpoint_and_return_403_when_principal_is_not_allowed() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router_with_cedar_policies(
+        r#"permit(
+            principal == Jams::Principal::"team-a",
+            action == Jams::Action::"predict",
+            resource == Jams::Model::"my_awesome_reg_model"
+        );"#,
+    )
+    .await;
+    let predict_url = format!("http://{}/api/v1/predict", addr).to_string();
+    let models_url = format!("http://{}/api/v1/models", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let features: serde_json::Map<String, serde_json::Value> = (0..28)
+        .map(|i| (format!("feature_{}", i), serde_json::json!([0.5, 0.25])))
+        .collect();
+    let model_input = serde_json::Value::Object(features).to_string();
+    let token = |principal: &str| issue_token(TOKEN_KEY, principal, i64::MAX);
+    let predict = |principal: &'static str, model_name: &'static str| {
+        client
+            .post(predict_url.as_str())
+            .bearer_auth(token(principal))
+            .json(&serde_json::json!(
+                {
+                    "model_name": model_name,
+                    "input": model_input
+                }
+            ))
+            .send()
+    };
+
+    // Act
+    let allowed = predict("team-a", "my_awesome_reg_model").await.unwrap();
+    let other_model = predict("team-a", "titanic_model").await.unwrap();
+    let other_principal = predict("team-b", "my_awesome_reg_model").await.unwrap();
+    let models = client
+        .get(models_url.as_str())
+        .bearer_auth(token("team-a"))
+        .send()
+        .await
+        .unwrap();
+    // the principal cannot be claimed without a token signed with the key
+    let claimed = client
+        .get(models_url.as_str())
+        .header("x-client-id", "team-a")
+        .send()
+        .await
+        .unwrap();
+    let forged = client
+        .get(models_url.as_str())
+        .bearer_auth(issue_token(&[8; 32], "team-a", i64::MAX))
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(allowed.status().is_success());
+    assert_eq!(other_model.status(), 403);
+    assert_eq!(other_principal.status(), 403);
+    assert_eq!(models.status(), 403);
+    assert_eq!(claimed.status(), 401);
+    assert_eq!(forged.status(), 401);
+}
+
+#[tokio::test]
+async fn fails_to_call_the_predict_endpoint_and_return_412_when_model_version_is_not_loaded() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let predict_url = format!("http://{}/api/predict", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act: Make Predictions
+    let payload = serde_json::json!(
+        {
+            "model_name": "titanic_model",
+            "input": serde_json::json!({"age": [22.0]}).to_string()
+        }
+    );
+    let pinned = client
+        .post(predict_url.as_str())
+        .header("x-model-version", "0000000")
+        .json(&payload)
+        .send()
+        .await
+        .expect("Failed to make request");
+    let invalid = client
+        .post(predict_url.as_str())
+        .header("x-request-priority", "urgent")
+        .json(&payload)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(pinned.status(), 412);
+    assert_eq!(invalid.status(), 400);
+}
+
+#[tokio::test]
+async fn fails_to_call_the_predict_endpoint_and_return_404_when_model_does_not_exist() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let predict_url = format!("http://{}/api/v1/predict", addr).to_string();
+    let misses_url = format!("http://{}/api/v1/models/misses?limit=5", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .post(predict_url)
+        .json(&serde_json::json!(
+            {
+                "model_name": "titanic_modle",
+                "input": "{\"age\": [22.0]}"
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let misses_response = client
+        .get(misses_url)
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    assert!(misses_response.status().is_success());
+    let body: serde_json::Value = misses_response.json().await.unwrap();
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["misses"][0]["model_name"], "titanic_modle");
+    assert_eq!(body["misses"][0]["count"], 1);
+}
+
+#[tokio::test]
+async fn fails_to_call_the_predict_stream_endpoint_when_model_does_not_stream_its_predictions() {
+    // Arrange
+    let client = Client::new();
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = test_router().await;
+    let stream_url = format!("http://{}/api/v1/predict/stream", addr).to_string();
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    // Act
+    let response = client
+        .post(stream_url.as_str())
+        .json(&serde_json::json!(
+            {
+                "model_name": "my_awesome_californiahousing_model",
+                "input": "{\"input_ids\": [1, 2, 3]}"
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let missing = client
+        .post(stream_url.as_str())
+        .json(&serde_json::json!(
+            {
+                "model_name": "my_awesome_sequence_model",
+                "input": "{\"input_ids\": [1, 2, 3]}"
+            }
+        ))
+        .send()
+        .await
+        .expect("Failed to make request");
+
+    // Assert
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .contains("does not stream its predictions"));
+    assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+}
@@ -1,6 +1,7 @@
 use clap::{Args, Parser, Subcommand};
 use jams_core::model::predict::Predict;
-use jams_serve::common::server::{Config, Protocol};
+use jams_core::model_store::delta::DEFAULT_BLOCK_SIZE;
+use jams_serve::common::server::{flags_digest, Config, Protocol};
 use std::fs;
 
 /// CLI for starting an J.A.M.S
@@ -20,11 +21,27 @@ pub struct Cli {
 pub enum Commands {
     /// Start the model server with a separate rayon threadpool for computing predictions
     #[clap(name = "start")]
-    Start(StartCommands),
+    Start(Box<StartCommands>),
 
     /// Make prediction directly from CLI
     #[clap(name = "predict")]
     Predict(PredictCommands),
+
+    /// Replay recorded predictions against a candidate model and report the differences
+    #[clap(name = "replay")]
+    Replay(ReplayCommandArgs),
+
+    /// Load a local model store and explore its models from an interactive prompt
+    #[clap(name = "repl")]
+    Repl(ReplCommandArgs),
+
+    /// Score a Parquet file with a model of a local model store and write the predictions to a Parquet file
+    #[clap(name = "score")]
+    Score(ScoreCommandArgs),
+
+    /// Inspect the model store of a server without starting it
+    #[clap(name = "store")]
+    Store(StoreCommands),
 }
 
 #[derive(Parser, Debug)]
@@ -61,6 +78,23 @@ pub enum PredictSubCommands {
     Catboost(PredictCommandArgs),
     /// Make predictions using a LightGBM model
     Lightgbm(PredictCommandArgs),
+    /// Make predictions using an XGBoost model
+    Xgboost(PredictCommandArgs),
+    /// Make predictions using a TensorRT engine
+    #[cfg(feature = "tensorrt")]
+    Tensorrt(PredictCommandArgs),
+    /// Make predictions using a Hugging Face transformers text model
+    #[cfg(feature = "transformers")]
+    Transformers(PredictCommandArgs),
+    /// Generate text using a GGUF model run with llama.cpp
+    #[cfg(feature = "gguf")]
+    Gguf(PredictCommandArgs),
+    /// Make predictions using a TensorFlow Lite model
+    #[cfg(feature = "tflite")]
+    Tflite(PredictCommandArgs),
+    /// Make predictions using an OpenVINO model
+    #[cfg(feature = "openvino")]
+    Openvino(PredictCommandArgs),
 }
 
 #[derive(Args, Debug, Clone)]
@@ -77,6 +111,30 @@ pub struct StartCommandArgs {
     #[clap(long)]
     pub port: Option<u16>,
 
+    /// Network interface to bind to (default: 0.0.0.0)
+    #[clap(long)]
+    pub host: Option<String>,
+
+    /// Port number for the admin endpoints, i.e. model management, metrics and health checks. They are no longer served on the public port
+    #[clap(long)]
+    pub admin_port: Option<u16>,
+
+    /// Network interface to bind the admin endpoints to. Requires --admin-port (default: 127.0.0.1)
+    #[clap(long)]
+    pub admin_host: Option<String>,
+
+    /// Number of listeners bound to the port with SO_REUSEPORT, each accepted by its own task (default: 1)
+    #[clap(long)]
+    pub acceptors: Option<usize>,
+
+    /// Maximum number of pending connections in the accept queue of each listener (default: 1024)
+    #[clap(long)]
+    pub accept_backlog: Option<u32>,
+
+    /// Idle time in seconds before TCP keepalive probes are sent on client connections, at least 1. Keepalive is disabled if not set
+    #[clap(long)]
+    pub tcp_keepalive: Option<u64>,
+
     /// Number of threads to be used in CPU threadpool. This threadpool is different from the
     /// I/O threadpool and used for computing CPU intensive tasks (default: 2)
     #[clap(long)]
@@ -93,6 +151,114 @@ pub struct StartCommandArgs {
     /// Polling interval for model store
     #[clap(long)]
     pub poll_interval: Option<u64>,
+
+    /// Upper bound in seconds of the random delay added to each polling interval, so that replicas do not poll the model store at the same time
+    #[clap(long)]
+    pub poll_jitter: Option<u64>,
+
+    /// Maximum number of updated models downloaded at the same time while polling (default: 1)
+    #[clap(long)]
+    pub max_concurrent_downloads: Option<usize>,
+
+    /// Deadline in seconds for listing the artifacts of a remote model store while polling, 0 disables it (default: 60)
+    #[clap(long)]
+    pub store_list_timeout: Option<u64>,
+
+    /// Deadline in seconds for downloading a single artifact from a remote model store, 0 disables it (default: 600)
+    #[clap(long)]
+    pub store_download_timeout: Option<u64>,
+
+    /// Retention in seconds of the model store directories left behind by crashed processes. Directories whose owner lock is free are removed at startup, and directories without a lock once they are older
+    #[clap(long)]
+    pub temp_dir_retention: Option<u64>,
+
+    /// Interval in seconds for removing orphaned model store directories after startup. Requires --temp-dir-retention
+    #[clap(long)]
+    pub temp_dir_gc_interval: Option<u64>,
+
+    /// Memory budget in MiB. Models which would push the server over the budget are refused
+    #[clap(long)]
+    pub memory_budget_mb: Option<u64>,
+
+    /// Resident memory watermark in MiB above which prediction requests are rejected with 503/UNAVAILABLE
+    #[clap(long)]
+    pub memory_shed_watermark_mb: Option<u64>,
+
+    /// Configure the frameworks to return reproducible predictions, at the cost of throughput
+    #[clap(long)]
+    pub deterministic: Option<bool>,
+
+    /// Seed of the random number generators of the frameworks in the deterministic mode (default: 0)
+    #[clap(long)]
+    pub deterministic_seed: Option<u64>,
+
+    /// Maximum number of predictions queued or running. Predictions over the limit are rejected with 503/UNAVAILABLE
+    #[clap(long)]
+    pub max_in_flight: Option<usize>,
+
+    /// Maximum time in milliseconds a prediction waits for a worker before it is rejected with 503/UNAVAILABLE
+    #[clap(long)]
+    pub max_queue_wait_ms: Option<u64>,
+
+    /// Executor of the shared worker pool, one of rayon, threads or tokio (default: rayon)
+    #[clap(long)]
+    pub executor: Option<String>,
+
+    /// Time in milliseconds a prediction is expected to wait for a worker. Predictions over budget are counted in the metrics
+    #[clap(long)]
+    pub task_wait_budget_ms: Option<u64>,
+
+    /// Time in milliseconds a prediction is expected to run for. Predictions over budget are counted in the metrics
+    #[clap(long)]
+    pub task_execute_budget_ms: Option<u64>,
+
+    /// Path to a prediction log. Every successful prediction is appended to it and can be replayed using `jams replay`
+    #[clap(long)]
+    pub prediction_log_path: Option<String>,
+
+    /// Path to a registry state file. The loaded models are saved to it on shutdown and restored from the local cache on startup
+    #[clap(long)]
+    pub registry_state_path: Option<String>,
+
+    /// Path to a usage state file. The request count and last access time of each model are saved to it periodically and on shutdown, and restored from it on startup
+    #[clap(long)]
+    pub usage_state_path: Option<String>,
+
+    /// Interval (in seconds) for saving the usage to the usage state file. A value of 0 only saves it on shutdown. Defaults to 60
+    #[clap(long)]
+    pub usage_save_interval: Option<u64>,
+
+    /// Comma separated list of models loaded first at startup. The server starts serving once they are loaded and loads the other models in the background
+    #[clap(long, value_delimiter = ',')]
+    pub priority_models: Option<Vec<String>>,
+
+    /// Interval in seconds for re-verifying the cached model artifacts. Corrupted or changed models are reloaded from the model store
+    #[clap(long)]
+    pub integrity_check_interval: Option<u64>,
+
+    /// Number of input schemas to cache for pre-admission validation. Inputs are validated before being queued and clients identified by the x-client-id header skip full validation for schemas they sent before
+    #[clap(long)]
+    pub schema_cache_size: Option<usize>,
+
+    /// Name of the sentinel model used by the deep health check at /healthz/deep. Requires --deep-health-input
+    #[clap(long)]
+    pub deep_health_model: Option<String>,
+
+    /// Canned model input used by the deep health check, as a JSON string
+    #[clap(long)]
+    pub deep_health_input: Option<String>,
+
+    /// Suggest the closest loaded model names in the errors of prediction requests naming a model which is not loaded
+    #[clap(long)]
+    pub model_suggestions: Option<bool>,
+
+    /// Window in seconds for sampling warning and error logs. Repeated events from the same log statement are counted and summarised once per window
+    #[clap(long)]
+    pub log_sampling_window: Option<u64>,
+
+    /// Comma separated list of module=limit pairs setting how many events per log statement are logged per window, i.e. jams_serve::http=5,default=20
+    #[clap(long)]
+    pub log_sampling_limits: Option<String>,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -110,16 +276,186 @@ pub struct PredictCommandArgs {
     pub input_path: Option<String>,
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct ReplayCommandArgs {
+    /// Path to the prediction log recorded by the server
+    #[clap(long)]
+    pub log: String,
+
+    /// URL of a J.A.M.S HTTP server or path to a local model to replay the recorded requests against
+    #[clap(long)]
+    pub target: String,
+
+    /// Only replay the records of this model
+    #[clap(long)]
+    pub model_name: Option<String>,
+
+    /// Name of the model to call on the target server. Defaults to the recorded model name
+    #[clap(long)]
+    pub target_model_name: Option<String>,
+
+    /// Path to write the JSON diff report to. The report is printed if not specified
+    #[clap(long)]
+    pub report: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ReplCommandArgs {
+    /// Path to the directory containing models
+    #[clap(long)]
+    pub model_dir: String,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ScoreCommandArgs {
+    /// Path to the directory containing models
+    #[clap(long)]
+    pub model_dir: String,
+
+    /// Name of the model to score the input with
+    #[clap(long)]
+    pub model: String,
+
+    /// Path to the Parquet file to score
+    #[clap(long)]
+    pub input: String,
+
+    /// Path to write the Parquet file of predictions to
+    #[clap(long)]
+    pub output: String,
+
+    /// Number of rows read and scored at once (default: 10000)
+    #[clap(long)]
+    pub batch_size: Option<usize>,
+
+    /// Number of batches scored in parallel (default: half the number of physical cores)
+    #[clap(long)]
+    pub num_workers: Option<usize>,
+
+    /// Comma separated input columns which are not features, e.g. the IDs of the rows. They are copied to the output
+    #[clap(long, value_delimiter = ',')]
+    pub id_columns: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+pub struct StoreCommands {
+    #[clap(subcommand)]
+    pub cmd: StoreSubCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StoreSubCommands {
+    /// Dry-run loading every model of the model store and report which models would fail to load and why
+    Check(StoreCheckCommandArgs),
+    /// Generate the block manifest of a tarball, which lets the servers download its updates as deltas
+    Manifest(StoreManifestCommandArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct StoreCheckCommandArgs {
+    /// Path to the config file of the server
+    #[clap(long)]
+    pub config: String,
+
+    /// Path to a checksums file written by sha256sum, which the tarballs must match
+    #[clap(long)]
+    pub checksums: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct StoreManifestCommandArgs {
+    /// Path to the tarball, the manifest is written next to it as <tarball>.zsync.json
+    #[clap(long)]
+    pub tarball_path: String,
+
+    /// Size of the blocks in bytes. Smaller blocks download less of an update but make the manifest bigger
+    #[clap(long, default_value_t = DEFAULT_BLOCK_SIZE)]
+    pub block_size: usize,
+}
+
 pub fn parse_server_config_from_args(args: StartCommandArgs, protocol: Protocol) -> Config {
+    let digest = flags_digest(&args);
     Config {
         protocol: protocol.to_string(),
         model_store: args.model_store,
         model_dir: args.model_dir,
         port: args.port,
+        host: args.host,
+        admin_port: args.admin_port,
+        admin_host: args.admin_host,
+        acceptors: args.acceptors,
+        accept_backlog: args.accept_backlog,
+        tcp_keepalive: args.tcp_keepalive,
         num_workers: args.num_workers,
+        // runtime sizing can only be configured using the config file
+        cpu_limit: None,
+        runtime_threads: None,
         s3_bucket_name: args.s3_bucket_name,
         azure_storage_container_name: args.azure_storage_container_name,
         poll_interval: args.poll_interval,
+        // per model store polling intervals can only be configured using the config file
+        poll_intervals: None,
+        poll_jitter: args.poll_jitter,
+        max_concurrent_downloads: args.max_concurrent_downloads,
+        store_list_timeout: args.store_list_timeout,
+        store_download_timeout: args.store_download_timeout,
+        temp_dir_retention: args.temp_dir_retention,
+        temp_dir_gc_interval: args.temp_dir_gc_interval,
+        memory_budget_mb: args.memory_budget_mb,
+        memory_shed_watermark_mb: args.memory_shed_watermark_mb,
+        deterministic: args.deterministic,
+        deterministic_seed: args.deterministic_seed,
+        max_in_flight: args.max_in_flight,
+        max_queue_wait_ms: args.max_queue_wait_ms,
+        executor: args.executor,
+        task_wait_budget_ms: args.task_wait_budget_ms,
+        task_execute_budget_ms: args.task_execute_budget_ms,
+        prediction_log_path: args.prediction_log_path,
+        registry_state_path: args.registry_state_path,
+        usage_state_path: args.usage_state_path,
+        usage_save_interval: args.usage_save_interval,
+        priority_models: args.priority_models,
+        integrity_check_interval: args.integrity_check_interval,
+        log_sampling_window: args.log_sampling_window,
+        log_sampling_limits: args.log_sampling_limits,
+        // namespaces can only be configured using the config file
+        namespaces: None,
+        schema_cache_size: args.schema_cache_size,
+        deep_health_model: args.deep_health_model,
+        deep_health_input: args.deep_health_input,
+        model_suggestions: args.model_suggestions,
+        // the feature store can only be configured using the config file
+        feature_store: None,
+        // the result store can only be configured using the config file
+        result_store: None,
+        // the chaos mode can only be configured using the config file
+        chaos: None,
+        // the hardened mode can only be configured using the config file
+        hardening: None,
+        // deployment groups can only be configured using the config file
+        deployment_groups: None,
+        // deduplication can only be configured using the config file
+        deduplication: None,
+        // authorization can only be configured using the config file
+        authorization: None,
+        // artifact encryption can only be configured using the config file
+        artifact_encryption: None,
+        // delta updates can only be configured using the config file
+        delta_updates: None,
+        // header passthrough can only be configured using the config file
+        passthrough: None,
+        // smoke test can only be configured using the config file
+        smoke_test: None,
+        // latency fallbacks can only be configured using the config file
+        latency_fallbacks: None,
+        // scheduling weights can only be configured using the config file
+        scheduling_weights: None,
+        // S3 events can only be configured using the config file
+        s3_events: None,
+        // model settings can only be configured using the config file
+        model_settings: None,
+        response_signing: None,
+        digest: Some(digest),
     }
 }
 
@@ -1,36 +1,50 @@
 use crate::cli::{
     parse_server_config_from_args, predict, Commands, PredictSubCommands, StartSubCommands,
+    StoreSubCommands,
 };
 use clap::Parser;
-use jams_serve::common::server::{GRPC, HTTP};
+use jams_serve::common::server::{Config, GRPC, HTTP};
+use jams_serve::common::sizing::RuntimeSizing;
 use tokio::runtime::Builder;
 
 mod cli;
+mod repl;
+mod replay;
+mod score;
+mod store;
 
 #[cfg(not(tarpaulin_include))]
 fn main() -> anyhow::Result<()> {
-    // get physical cpu count and divide by 2.
+    let cli = cli::Cli::parse();
+
+    // the config file is parsed before the runtime is built, as it can override the runtime sizing
+    let config = match &cli.cmd {
+        Commands::Start(subcommands) => match &subcommands.file {
+            Some(file_path) => Some(Config::parse(file_path.clone())?),
+            None => None,
+        },
+        _ => None,
+    };
+
+    // split the physical cores, capped by the cpu limit of the container, in half.
     // one half is given to tokio runtime and the another to rayon thread pool.
     // the rayon threadpool worker count can also be set via config/flag
-    let physical_cores = (num_cpus::get_physical() / 2).max(1);
+    let sizing = RuntimeSizing::detect(num_cpus::get_physical(), config.as_ref());
+    let worker_threads = sizing.worker_threads;
 
     let tokio_runtime = Builder::new_multi_thread()
-        .worker_threads(physical_cores)
+        .worker_threads(sizing.runtime_threads)
         .max_blocking_threads(50)
         .enable_all()
         .build()
         .expect("Failed to create Tokio runtime");
 
-    let cli = cli::Cli::parse();
-
     match cli.cmd {
         Commands::Start(subcommands) => {
-            match subcommands.file {
-                Some(file_path) => {
-                    let config = jams_serve::common::server::Config::parse(file_path)?;
-
+            match config {
+                Some(config) => {
                     tokio_runtime.block_on(async {
-                        jams_serve::start(config, physical_cores).await;
+                        jams_serve::start(config, worker_threads).await;
                         // shutdown signal received
                         tracing::error!("Shutdown signal received ⚠️");
                     });
@@ -46,9 +60,10 @@ fn main() -> anyhow::Result<()> {
                         }
                         Some(StartSubCommands::Http(args)) => {
                             let config = parse_server_config_from_args(args, HTTP);
+                            config.validate()?;
 
                             tokio_runtime.block_on(async {
-                                jams_serve::start(config, physical_cores).await;
+                                jams_serve::start(config, worker_threads).await;
 
                                 // shutdown signal received
                                 tracing::error!("Shutdown signal received ⚠️");
@@ -58,9 +73,10 @@ fn main() -> anyhow::Result<()> {
                         }
                         Some(StartSubCommands::Grpc(args)) => {
                             let config = parse_server_config_from_args(args, GRPC);
+                            config.validate()?;
 
                             tokio_runtime.block_on(async {
-                                jams_serve::start(config, physical_cores).await;
+                                jams_serve::start(config, worker_threads).await;
 
                                 // shutdown signal received
                                 tracing::error!("Shutdown signal received ⚠️");
@@ -159,6 +175,149 @@ fn main() -> anyhow::Result<()> {
                 };
                 Ok(())
             }
+            PredictSubCommands::Xgboost(args) => {
+                match args.model_path {
+                    None => {
+                        anyhow::bail!("Model path not specified ❌")
+                    }
+                    Some(path) => match jams_core::model::xgboost::XGBoost::load(path.as_str()) {
+                        Ok(model) => match predict(model, args.input, args.input_path) {
+                            Ok(predictions) => {
+                                log::info!("✅ {:?} \n", predictions);
+                            }
+                            Err(e) => {
+                                anyhow::bail!("Failed to make predictions ❌.\n {}", e)
+                            }
+                        },
+                        Err(e) => {
+                            anyhow::bail!("Failed to load the model ❌.\n {}", e)
+                        }
+                    },
+                };
+                Ok(())
+            }
+            #[cfg(feature = "tensorrt")]
+            PredictSubCommands::Tensorrt(args) => {
+                match args.model_path {
+                    None => {
+                        anyhow::bail!("Model path not specified ❌")
+                    }
+                    Some(path) => match jams_core::model::tensorrt::TensorRT::load(path.as_str()) {
+                        Ok(model) => match predict(model, args.input, args.input_path) {
+                            Ok(predictions) => {
+                                log::info!("✅ {:?} \n", predictions);
+                            }
+                            Err(e) => {
+                                anyhow::bail!("Failed to make predictions ❌.\n {}", e)
+                            }
+                        },
+                        Err(e) => {
+                            anyhow::bail!("Failed to load the model ❌.\n {}", e)
+                        }
+                    },
+                };
+                Ok(())
+            }
+            #[cfg(feature = "transformers")]
+            PredictSubCommands::Transformers(args) => {
+                match args.model_path {
+                    None => {
+                        anyhow::bail!("Model path not specified ❌")
+                    }
+                    Some(path) => {
+                        match jams_core::model::transformers::Transformers::load(path.as_str()) {
+                            Ok(model) => match predict(model, args.input, args.input_path) {
+                                Ok(predictions) => {
+                                    log::info!("✅ {:?} \n", predictions);
+                                }
+                                Err(e) => {
+                                    anyhow::bail!("Failed to make predictions ❌.\n {}", e)
+                                }
+                            },
+                            Err(e) => {
+                                anyhow::bail!("Failed to load the model ❌.\n {}", e)
+                            }
+                        }
+                    }
+                };
+                Ok(())
+            }
+            #[cfg(feature = "gguf")]
+            PredictSubCommands::Gguf(args) => {
+                match args.model_path {
+                    None => {
+                        anyhow::bail!("Model path not specified ❌")
+                    }
+                    Some(path) => match jams_core::model::gguf::Gguf::load(path.as_str()) {
+                        Ok(model) => match predict(model, args.input, args.input_path) {
+                            Ok(predictions) => {
+                                log::info!("✅ {:?} \n", predictions);
+                            }
+                            Err(e) => {
+                                anyhow::bail!("Failed to make predictions ❌.\n {}", e)
+                            }
+                        },
+                        Err(e) => {
+                            anyhow::bail!("Failed to load the model ❌.\n {}", e)
+                        }
+                    },
+                };
+                Ok(())
+            }
+            #[cfg(feature = "tflite")]
+            PredictSubCommands::Tflite(args) => {
+                match args.model_path {
+                    None => {
+                        anyhow::bail!("Model path not specified ❌")
+                    }
+                    Some(path) => match jams_core::model::tflite::TfLite::load(path.as_str()) {
+                        Ok(model) => match predict(model, args.input, args.input_path) {
+                            Ok(predictions) => {
+                                log::info!("✅ {:?} \n", predictions);
+                            }
+                            Err(e) => {
+                                anyhow::bail!("Failed to make predictions ❌.\n {}", e)
+                            }
+                        },
+                        Err(e) => {
+                            anyhow::bail!("Failed to load the model ❌.\n {}", e)
+                        }
+                    },
+                };
+                Ok(())
+            }
+            #[cfg(feature = "openvino")]
+            PredictSubCommands::Openvino(args) => {
+                match args.model_path {
+                    None => {
+                        anyhow::bail!("Model path not specified ❌")
+                    }
+                    Some(path) => match jams_core::model::openvino::OpenVino::load(path.as_str()) {
+                        Ok(model) => match predict(model, args.input, args.input_path) {
+                            Ok(predictions) => {
+                                log::info!("✅ {:?} \n", predictions);
+                            }
+                            Err(e) => {
+                                anyhow::bail!("Failed to make predictions ❌.\n {}", e)
+                            }
+                        },
+                        Err(e) => {
+                            anyhow::bail!("Failed to load the model ❌.\n {}", e)
+                        }
+                    },
+                };
+                Ok(())
+            }
+        },
+        Commands::Replay(args) => tokio_runtime.block_on(replay::run(args)),
+        Commands::Repl(args) => {
+            let manager = tokio_runtime.block_on(repl::load(args))?;
+            repl::run(manager)
+        }
+        Commands::Score(args) => tokio_runtime.block_on(score::run(args, worker_threads)),
+        Commands::Store(subcommands) => match subcommands.cmd {
+            StoreSubCommands::Check(args) => tokio_runtime.block_on(store::check(args)),
+            StoreSubCommands::Manifest(args) => store::manifest(args),
         },
     }
 }
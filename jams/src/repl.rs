@@ -0,0 +1,384 @@
+use crate::cli::ReplCommandArgs;
+use jams_core::manager::{Manager, ManagerBuilder};
+use jams_core::model::bundle::{FeatureType, ModelBundle};
+use jams_core::model_store::local::filesystem::LocalModelStore;
+use jams_core::model_store::ModelStore;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::fs;
+use std::sync::Arc;
+
+/// Prompt shown before every command.
+const PROMPT: &str = "jams> ";
+
+/// Names of the commands of the REPL, used for tab completion.
+const COMMANDS: [&str; 5] = ["models", "schema", "predict", "help", "exit"];
+
+/// Commands whose first argument is the name of a model.
+const MODEL_COMMANDS: [&str; 2] = ["schema", "predict"];
+
+const HELP: &str = "Commands:
+  models                         List the loaded models
+  schema <model_name>            Show the input features expected by a model
+  predict <model_name> <input>   Make predictions, e.g. predict my_model {\"age\": [22.0]}
+  predict <model_name> @<path>   Make predictions using the model input stored in a JSON file
+  help                           Show this message
+  exit                           Leave the REPL";
+
+/// A command entered at the prompt.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Models,
+    Schema(String),
+    Predict { model_name: String, input: String },
+    Help,
+    Exit,
+}
+
+impl Command {
+    /// Parses a line entered at the prompt.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line entered by the user.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Command))` - If the line is a valid command.
+    /// * `Ok(None)` - If the line is empty.
+    /// * `Err(anyhow::Error)` - If the command is unknown or its arguments are missing.
+    pub fn parse(line: &str) -> anyhow::Result<Option<Command>> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+        let (command, arguments) = match line.split_once(char::is_whitespace) {
+            Some((command, arguments)) => (command, arguments.trim()),
+            None => (line, ""),
+        };
+
+        let command = match command {
+            "models" => Command::Models,
+            "schema" if !arguments.is_empty() => Command::Schema(arguments.to_string()),
+            "schema" => anyhow::bail!("Usage: schema <model_name> ❌"),
+            "predict" => match arguments.split_once(char::is_whitespace) {
+                Some((model_name, input)) => Command::Predict {
+                    model_name: model_name.to_string(),
+                    input: read_input(input.trim())?,
+                },
+                None => anyhow::bail!("Usage: predict <model_name> <input> ❌"),
+            },
+            "help" => Command::Help,
+            "exit" | "quit" => Command::Exit,
+            _ => anyhow::bail!(
+                "Unknown command {}, type help to list the commands ❌",
+                command
+            ),
+        };
+        Ok(Some(command))
+    }
+}
+
+/// Reads the model input of a `predict` command, which is either inline JSON or `@<path>` to a JSON file.
+fn read_input(input: &str) -> anyhow::Result<String> {
+    match input.strip_prefix('@') {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(input) => Ok(input),
+            Err(e) => anyhow::bail!("Failed to read model input from {} ❌: {}", path, e),
+        },
+        None => Ok(input.to_string()),
+    }
+}
+
+/// Completes the names of the commands and of the loaded models.
+pub struct ReplHelper {
+    manager: Arc<Manager>,
+}
+
+impl ReplHelper {
+    /// Returns the start of the word under the cursor and the candidates which complete it.
+    fn candidates(line: &str, pos: usize, model_names: &[String]) -> (usize, Vec<String>) {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &line[start..];
+        let previous: Vec<&str> = line[..start].split_whitespace().collect();
+
+        let candidates: Vec<String> = match previous.as_slice() {
+            [] => COMMANDS.iter().map(|command| command.to_string()).collect(),
+            [command] if MODEL_COMMANDS.contains(command) => model_names.to_vec(),
+            _ => Vec::new(),
+        };
+        let mut candidates: Vec<String> = candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .collect();
+        candidates.sort();
+        (start, candidates)
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let model_names: Vec<String> = self
+            .manager
+            .get_models()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|metadata| metadata.name)
+            .collect();
+        Ok(Self::candidates(line, pos, &model_names))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// Loads the models of a local model store without polling it for updates.
+///
+/// # Arguments
+///
+/// * `args` - The arguments of the `repl` command.
+///
+/// # Returns
+///
+/// * `Ok(Arc<Manager>)` - If the model store was loaded.
+/// * `Err(anyhow::Error)` - If the model directory could not be read.
+pub async fn load(args: ReplCommandArgs) -> anyhow::Result<Arc<Manager>> {
+    let model_store = LocalModelStore::new(args.model_dir).await?;
+    let manager = ManagerBuilder::new(Arc::new(ModelStore::Local(model_store))).build()?;
+    Ok(Arc::new(manager))
+}
+
+/// Runs the interactive prompt until the user exits or sends EOF.
+///
+/// # Arguments
+///
+/// * `manager` - The manager holding the models of the model store.
+///
+/// # Returns
+///
+/// * `Ok(())` - If the user left the REPL.
+/// * `Err(anyhow::Error)` - If the terminal could not be read.
+pub fn run(manager: Arc<Manager>) -> anyhow::Result<()> {
+    let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(ReplHelper {
+        manager: Arc::clone(&manager),
+    }));
+    println!("Type help to list the commands and press tab to complete them");
+
+    loop {
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            // ctrl-c clears the line, ctrl-d leaves the REPL
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => return Ok(()),
+            Err(e) => anyhow::bail!("Failed to read command ❌: {}", e),
+        };
+        let _ = editor.add_history_entry(line.as_str());
+
+        match Command::parse(line.as_str()) {
+            Ok(Some(Command::Exit)) => return Ok(()),
+            Ok(Some(command)) => match execute(&manager, command) {
+                Ok(output) => println!("{}", output),
+                Err(e) => println!("{}", e),
+            },
+            Ok(None) => {}
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+/// Executes a command and returns the text to print.
+fn execute(manager: &Manager, command: Command) -> anyhow::Result<String> {
+    match command {
+        Command::Models => {
+            let mut models = manager.get_models()?;
+            if models.is_empty() {
+                return Ok("No models are loaded".to_string());
+            }
+            models.sort_by(|a, b| a.name.cmp(&b.name));
+            Ok(models
+                .iter()
+                .map(|model| {
+                    format!(
+                        "{:<40} {:<12} {}",
+                        model.name,
+                        model.framework.to_string(),
+                        model.last_updated
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n"))
+        }
+        Command::Schema(model_name) => match manager.get_model_bundle(model_name.clone()) {
+            Some(bundle) => Ok(describe_schema(&model_name, &bundle)),
+            None => anyhow::bail!("No model exists for model name: {} ❌", model_name),
+        },
+        Command::Predict { model_name, input } => {
+            let output = manager.predict(model_name, input.as_str())?;
+            match serde_json::from_str::<serde_json::Value>(output.as_str()) {
+                Ok(output) => Ok(serde_json::to_string_pretty(&output)?),
+                Err(_) => Ok(output),
+            }
+        }
+        Command::Help => Ok(HELP.to_string()),
+        Command::Exit => Ok(String::new()),
+    }
+}
+
+/// Describes the input features of a model using the schema of its bundle. Models shipped without a schema
+/// fall back to the features of their warmup input.
+fn describe_schema(model_name: &str, bundle: &ModelBundle) -> String {
+    let mut features: Vec<(String, String)> = bundle
+        .schema
+        .iter()
+        .map(|(name, feature_type)| {
+            let feature_type = match feature_type {
+                FeatureType::Int => "int",
+                FeatureType::Float => "float",
+                FeatureType::String => "string",
+            };
+            (name.clone(), feature_type.to_string())
+        })
+        .collect();
+
+    if features.is_empty() {
+        let warmup_features = bundle
+            .warmup_input
+            .as_deref()
+            .and_then(|input| {
+                serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(input).ok()
+            })
+            .unwrap_or_default();
+        features = warmup_features
+            .keys()
+            .map(|name| (name.clone(), "unknown".to_string()))
+            .collect();
+    }
+    if features.is_empty() {
+        return format!(
+            "No schema or warmup input was shipped with model {}",
+            model_name
+        );
+    }
+
+    features
+        .into_iter()
+        .map(
+            |(name, feature_type)| match bundle.vocabularies.get(&name) {
+                Some(vocabulary) => format!(
+                    "{:<40} {} (vocabulary of {} tokens)",
+                    name,
+                    feature_type,
+                    vocabulary.tokens().len()
+                ),
+                None => format!("{:<40} {}", name, feature_type),
+            },
+        )
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn successfully_parse_repl_commands() {
+        // Act
+        let models = Command::parse("  models ").unwrap();
+        let schema = Command::parse("schema titanic_model").unwrap();
+        let predict = Command::parse(r#"predict titanic_model {"age": [22.0]}"#).unwrap();
+        let empty = Command::parse("").unwrap();
+
+        // Assert
+        assert_eq!(models, Some(Command::Models));
+        assert_eq!(schema, Some(Command::Schema("titanic_model".to_string())));
+        assert_eq!(
+            predict,
+            Some(Command::Predict {
+                model_name: "titanic_model".to_string(),
+                input: r#"{"age": [22.0]}"#.to_string(),
+            })
+        );
+        assert_eq!(empty, None);
+    }
+
+    #[test]
+    fn fails_to_parse_repl_command_when_arguments_are_missing() {
+        // Act
+        let schema = Command::parse("schema");
+        let predict = Command::parse("predict titanic_model");
+        let unknown = Command::parse("train titanic_model");
+
+        // Assert
+        assert!(schema.is_err());
+        assert!(predict.is_err());
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn successfully_complete_commands_and_model_names() {
+        // Arrange
+        let model_names = vec!["titanic_model".to_string(), "my_awesome_model".to_string()];
+
+        // Act
+        let commands = ReplHelper::candidates("pr", 2, &model_names);
+        let models = ReplHelper::candidates("predict ti", 10, &model_names);
+        let input = ReplHelper::candidates("predict titanic_model {", 23, &model_names);
+
+        // Assert
+        assert_eq!(commands, (0, vec!["predict".to_string()]));
+        assert_eq!(models, (8, vec!["titanic_model".to_string()]));
+        assert_eq!(input, (22, Vec::<String>::new()));
+    }
+
+    #[test]
+    fn successfully_describe_schema_of_model_bundle() {
+        // Arrange
+        let bundle = ModelBundle {
+            schema: BTreeMap::from([
+                ("age".to_string(), FeatureType::Float),
+                ("sex".to_string(), FeatureType::String),
+            ]),
+            ..Default::default()
+        };
+        let warmup_bundle = ModelBundle {
+            warmup_input: Some(r#"{"fare": [7.25]}"#.to_string()),
+            ..Default::default()
+        };
+
+        // Act
+        let schema = describe_schema("titanic_model", &bundle);
+        let warmup_schema = describe_schema("titanic_model", &warmup_bundle);
+        let missing_schema = describe_schema("titanic_model", &ModelBundle::default());
+
+        // Assert
+        assert_eq!(schema.lines().count(), 2);
+        assert!(schema.starts_with("age"));
+        assert!(schema.ends_with("string"));
+        assert!(warmup_schema.starts_with("fare"));
+        assert!(warmup_schema.ends_with("unknown"));
+        assert!(missing_schema.starts_with("No schema"));
+    }
+}
@@ -0,0 +1,578 @@
+use crate::cli::ReplayCommandArgs;
+use jams_core::model::input::ModelInput;
+use jams_core::model::output::ModelOutput;
+use jams_core::model::Predictor;
+use jams_core::model_store::storage::{extract_framework, load_predictor};
+use jams_serve::common::recorder::{read_records, PredictionRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Number of equal width bins used for computing the population stability index.
+const PSI_BINS: usize = 10;
+
+/// Small value added to empty bins so that the population stability index stays finite.
+const PSI_EPSILON: f64 = 1e-4;
+
+/// Number of rows with the largest deltas which are included in the report.
+const MAX_REPORTED_ROWS: usize = 100;
+
+/// Timeout for a single prediction request sent to a remote target.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Serialize)]
+struct PredictRequest<'a> {
+    model_name: &'a str,
+    input: &'a str,
+}
+
+#[derive(Deserialize)]
+struct PredictResponse {
+    output: String,
+}
+
+/// The target against which recorded requests are replayed.
+pub enum Target {
+    /// A J.A.M.S HTTP server.
+    Http {
+        client: reqwest::Client,
+        url: String,
+        model_name: Option<String>,
+    },
+    /// A model loaded from the local filesystem.
+    Local(Arc<Predictor>),
+}
+
+impl Target {
+    /// Creates a target from a URL of a J.A.M.S HTTP server or a path to a local model.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The URL or the path to the model. The framework of a local model is inferred from its
+    ///   path, which should follow the `<model_framework>-<model_name>` naming convention.
+    /// * `model_name` - The name of the model on the server. Defaults to the recorded model name if `None`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Target)` - If the target was created successfully.
+    /// * `Err(anyhow::Error)` - If the HTTP client could not be created or the model could not be loaded.
+    pub async fn new(target: &str, model_name: Option<String>) -> anyhow::Result<Target> {
+        if target.starts_with("http://") || target.starts_with("https://") {
+            let client = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+                Ok(client) => client,
+                Err(e) => anyhow::bail!("Failed to create HTTP client ❌: {}", e),
+            };
+            return Ok(Target::Http {
+                client,
+                url: format!("{}/api/predict", target.trim_end_matches('/')),
+                model_name,
+            });
+        }
+
+        let framework = match extract_framework(target.to_string()) {
+            Some(framework) => framework,
+            None => anyhow::bail!(
+                "Unable to infer model framework from {} ❌. The model should be named <model_framework>-<model_name>",
+                target
+            ),
+        };
+        let predictor = load_predictor(framework, target).await?;
+        Ok(Target::Local(predictor))
+    }
+
+    /// Makes a prediction for the recorded request.
+    async fn predict(&self, record: &PredictionRecord) -> anyhow::Result<ModelOutput> {
+        match self {
+            Target::Http {
+                client,
+                url,
+                model_name,
+            } => {
+                let request = PredictRequest {
+                    model_name: model_name.as_deref().unwrap_or(&record.model_name),
+                    input: &record.input,
+                };
+                let response = client.post(url).json(&request).send().await?;
+                if !response.status().is_success() {
+                    anyhow::bail!(
+                        "Failed to get predictions ❌: {}",
+                        response.text().await.unwrap_or_default()
+                    )
+                }
+                let response = response.json::<PredictResponse>().await?;
+                Ok(serde_json::from_str(&response.output)?)
+            }
+            Target::Local(predictor) => {
+                let input = ModelInput::from_str(&record.input)?;
+                Ok(predictor.predict(input)?)
+            }
+        }
+    }
+}
+
+/// Summary statistics of a set of values.
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct Distribution {
+    pub count: usize,
+    pub mean: f64,
+    pub std: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Distribution {
+    fn from_values(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return Distribution::default();
+        }
+
+        let count = values.len();
+        let mean = values.iter().sum::<f64>() / count as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count as f64;
+        Distribution {
+            count,
+            mean,
+            std: variance.sqrt(),
+            min: values.iter().cloned().fold(f64::INFINITY, f64::min),
+            max: values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+/// Comparison of the recorded and candidate predictions of a single model output.
+#[derive(Serialize, Debug)]
+pub struct OutputComparison {
+    /// The name of the output, `predictions` for models with a single output.
+    pub name: String,
+    pub recorded: Distribution,
+    pub candidate: Distribution,
+    /// Population stability index of the candidate predictions relative to the recorded predictions.
+    /// Values above 0.25 usually indicate a significant shift. `None` if either side has no predictions.
+    pub population_stability_index: Option<f64>,
+    pub mean_absolute_delta: f64,
+    pub max_absolute_delta: f64,
+    /// Number of records where the recorded and candidate predictions have a different shape.
+    pub shape_mismatches: usize,
+}
+
+/// Latency percentiles in milliseconds.
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct LatencySummary {
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl LatencySummary {
+    fn from_latencies(latencies: &[f64]) -> Self {
+        if latencies.is_empty() {
+            return LatencySummary::default();
+        }
+
+        let mut sorted = latencies.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        LatencySummary {
+            mean_ms: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p50_ms: percentile(&sorted, 0.50),
+            p95_ms: percentile(&sorted, 0.95),
+            p99_ms: percentile(&sorted, 0.99),
+        }
+    }
+}
+
+/// Comparison of the recorded serving latency and the latency of the candidate.
+///
+/// The recorded latency includes the time spent waiting for a worker on the server, so it is only
+/// directly comparable with a remote target.
+#[derive(Serialize, Debug)]
+pub struct LatencyComparison {
+    pub recorded: LatencySummary,
+    pub candidate: LatencySummary,
+}
+
+/// The difference between the recorded and candidate predictions of a single row.
+#[derive(Serialize, Debug)]
+pub struct RowDelta {
+    /// Index of the record in the prediction log.
+    pub record: usize,
+    pub output: String,
+    /// Index of the row within the record.
+    pub row: usize,
+    pub max_absolute_delta: f64,
+}
+
+/// The result of replaying a prediction log against a candidate model.
+#[derive(Serialize, Debug)]
+pub struct ReplayReport {
+    pub total_records: usize,
+    pub replayed: usize,
+    pub failed: usize,
+    pub outputs: Vec<OutputComparison>,
+    pub latency: LatencyComparison,
+    /// The rows with the largest deltas, sorted in descending order.
+    pub largest_deltas: Vec<RowDelta>,
+}
+
+/// The result of replaying a single record against the candidate.
+pub struct Replayed {
+    pub output: anyhow::Result<ModelOutput>,
+    pub latency_ms: f64,
+}
+
+/// Values collected for a single output while comparing the records.
+#[derive(Default)]
+struct OutputValues {
+    recorded: Vec<f64>,
+    candidate: Vec<f64>,
+    deltas: Vec<f64>,
+    shape_mismatches: usize,
+}
+
+/// Replays the recorded predictions from the prediction log against the target and writes the diff report.
+///
+/// # Arguments
+///
+/// * `args` - The arguments of the replay command.
+///
+/// # Returns
+///
+/// * `Ok(())` - If the replay completed. Individual predictions which fail are counted in the report.
+/// * `Err(anyhow::Error)` - If the prediction log could not be read or the target could not be created.
+pub async fn run(args: ReplayCommandArgs) -> anyhow::Result<()> {
+    let records: Vec<PredictionRecord> = read_records(args.log.as_str())?
+        .into_iter()
+        .filter(|record| match &args.model_name {
+            Some(model_name) => &record.model_name == model_name,
+            None => true,
+        })
+        .collect();
+    if records.is_empty() {
+        anyhow::bail!("No records found to replay in {} ❌", args.log)
+    }
+
+    let target = Target::new(args.target.as_str(), args.target_model_name).await?;
+    println!(
+        "🔁 Replaying {} records against {}",
+        records.len(),
+        args.target
+    );
+
+    let mut replayed = Vec::with_capacity(records.len());
+    for record in records.iter() {
+        let start = Instant::now();
+        let output = target.predict(record).await;
+        replayed.push(Replayed {
+            output,
+            latency_ms: start.elapsed().as_secs_f64() * 1000.0,
+        });
+    }
+
+    let report = compare(&records, replayed);
+    println!(
+        "✅ Replayed {} of {} records ({} failed)",
+        report.replayed, report.total_records, report.failed
+    );
+    for output in report.outputs.iter() {
+        println!(
+            "   {}: mean {:.6} -> {:.6}, mean absolute delta {:.6}, max absolute delta {:.6}, psi {}",
+            output.name,
+            output.recorded.mean,
+            output.candidate.mean,
+            output.mean_absolute_delta,
+            output.max_absolute_delta,
+            output
+                .population_stability_index
+                .map(|psi| format!("{:.4}", psi))
+                .unwrap_or_else(|| "n/a".to_string())
+        );
+    }
+    println!(
+        "   latency p50 {:.2}ms -> {:.2}ms, p99 {:.2}ms -> {:.2}ms",
+        report.latency.recorded.p50_ms,
+        report.latency.candidate.p50_ms,
+        report.latency.recorded.p99_ms,
+        report.latency.candidate.p99_ms
+    );
+
+    let json = serde_json::to_string_pretty(&report)?;
+    match args.report {
+        Some(path) => {
+            if let Err(e) = fs::write(&path, json) {
+                anyhow::bail!("Failed to write report to {} ❌: {}", path, e)
+            }
+            println!("📝 Report written to {}", path);
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+/// Compares the recorded predictions with the predictions made by the candidate.
+///
+/// # Arguments
+///
+/// * `records` - The records from the prediction log.
+/// * `replayed` - The result of replaying each record, in the same order as `records`.
+///
+/// # Returns
+///
+/// * `ReplayReport` - The differences between the recorded and candidate predictions.
+pub fn compare(records: &[PredictionRecord], replayed: Vec<Replayed>) -> ReplayReport {
+    let mut outputs: HashMap<String, OutputValues> = HashMap::new();
+    let mut row_deltas: Vec<RowDelta> = Vec::new();
+    let mut recorded_latencies = Vec::with_capacity(records.len());
+    let mut candidate_latencies = Vec::with_capacity(records.len());
+    let mut failed = 0;
+
+    for (index, (record, replayed)) in records.iter().zip(replayed).enumerate() {
+        let candidate = match replayed.output {
+            Ok(candidate) => candidate,
+            Err(e) => {
+                tracing::warn!("Failed to replay record {} ⚠️: {}", index, e);
+                failed += 1;
+                continue;
+            }
+        };
+        let recorded = match serde_json::from_str::<ModelOutput>(&record.output) {
+            Ok(recorded) => recorded,
+            Err(e) => {
+                tracing::warn!("Failed to parse recorded output {} ⚠️: {}", index, e);
+                failed += 1;
+                continue;
+            }
+        };
+        recorded_latencies.push(record.latency_ms);
+        candidate_latencies.push(replayed.latency_ms);
+
+        let names: BTreeSet<&String> = recorded
+            .predictions
+            .keys()
+            .chain(candidate.predictions.keys())
+            .collect();
+        for name in names {
+            let values = outputs.entry(name.clone()).or_default();
+            let recorded_rows = recorded.predictions.get(name);
+            let candidate_rows = candidate.predictions.get(name);
+            if let Some(rows) = recorded_rows {
+                values.recorded.extend(rows.iter().flatten());
+            }
+            if let Some(rows) = candidate_rows {
+                values.candidate.extend(rows.iter().flatten());
+            }
+
+            // deltas are only meaningful when both predictions have the same shape
+            let (recorded_rows, candidate_rows) = match (recorded_rows, candidate_rows) {
+                (Some(recorded_rows), Some(candidate_rows))
+                    if recorded_rows.len() == candidate_rows.len()
+                        && recorded_rows
+                            .iter()
+                            .zip(candidate_rows)
+                            .all(|(r, c)| r.len() == c.len()) =>
+                {
+                    (recorded_rows, candidate_rows)
+                }
+                _ => {
+                    values.shape_mismatches += 1;
+                    continue;
+                }
+            };
+
+            for (row, (recorded_row, candidate_row)) in
+                recorded_rows.iter().zip(candidate_rows).enumerate()
+            {
+                let deltas: Vec<f64> = recorded_row
+                    .iter()
+                    .zip(candidate_row)
+                    .map(|(r, c)| (r - c).abs())
+                    .collect();
+                let max_absolute_delta = deltas.iter().cloned().fold(0.0, f64::max);
+                values.deltas.extend(deltas);
+                row_deltas.push(RowDelta {
+                    record: index,
+                    output: name.clone(),
+                    row,
+                    max_absolute_delta,
+                });
+            }
+        }
+    }
+
+    let mut outputs: Vec<OutputComparison> = outputs
+        .into_iter()
+        .map(|(name, values)| OutputComparison {
+            name,
+            recorded: Distribution::from_values(&values.recorded),
+            candidate: Distribution::from_values(&values.candidate),
+            population_stability_index: population_stability_index(
+                &values.recorded,
+                &values.candidate,
+            ),
+            mean_absolute_delta: if values.deltas.is_empty() {
+                0.0
+            } else {
+                values.deltas.iter().sum::<f64>() / values.deltas.len() as f64
+            },
+            max_absolute_delta: values.deltas.iter().cloned().fold(0.0, f64::max),
+            shape_mismatches: values.shape_mismatches,
+        })
+        .collect();
+    outputs.sort_by(|a, b| a.name.cmp(&b.name));
+
+    row_deltas.sort_by(|a, b| b.max_absolute_delta.total_cmp(&a.max_absolute_delta));
+    row_deltas.truncate(MAX_REPORTED_ROWS);
+
+    ReplayReport {
+        total_records: records.len(),
+        replayed: records.len() - failed,
+        failed,
+        outputs,
+        latency: LatencyComparison {
+            recorded: LatencySummary::from_latencies(&recorded_latencies),
+            candidate: LatencySummary::from_latencies(&candidate_latencies),
+        },
+        largest_deltas: row_deltas,
+    }
+}
+
+/// Calculates the population stability index of the candidate values relative to the recorded values
+/// using equal width bins over the range of the recorded values.
+fn population_stability_index(recorded: &[f64], candidate: &[f64]) -> Option<f64> {
+    if recorded.is_empty() || candidate.is_empty() {
+        return None;
+    }
+
+    let min = recorded.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = recorded.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let width = (max - min) / PSI_BINS as f64;
+
+    let histogram = |values: &[f64]| -> Vec<f64> {
+        let mut counts = vec![0.0; PSI_BINS];
+        for value in values {
+            // values outside of the recorded range are placed in the outermost bins
+            let bin = if width > 0.0 {
+                ((value - min) / width)
+                    .floor()
+                    .clamp(0.0, (PSI_BINS - 1) as f64) as usize
+            } else {
+                0
+            };
+            counts[bin] += 1.0;
+        }
+        counts
+            .into_iter()
+            .map(|count| (count / values.len() as f64).max(PSI_EPSILON))
+            .collect()
+    };
+
+    let recorded = histogram(recorded);
+    let candidate = histogram(candidate);
+    Some(
+        recorded
+            .iter()
+            .zip(candidate.iter())
+            .map(|(r, c)| (c - r) * (c / r).ln())
+            .sum(),
+    )
+}
+
+/// Returns the value at the given quantile of the sorted values using the nearest rank method.
+fn percentile(sorted: &[f64], quantile: f64) -> f64 {
+    let rank = (quantile * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jams_serve::common::recorder::CAPTURE_FORMAT_VERSION;
+
+    fn record(output: &str, latency_ms: f64) -> PredictionRecord {
+        PredictionRecord {
+            format_version: CAPTURE_FORMAT_VERSION,
+            timestamp_ms: 0,
+            model_name: "titanic_model".to_string(),
+            input: "{}".to_string(),
+            output: output.to_string(),
+            latency_ms,
+            headers: Default::default(),
+        }
+    }
+
+    fn candidate(output: &str, latency_ms: f64) -> Replayed {
+        Replayed {
+            output: Ok(serde_json::from_str(output).unwrap()),
+            latency_ms,
+        }
+    }
+
+    #[test]
+    fn successfully_compare_recorded_and_candidate_predictions() {
+        // Arrange
+        let records = vec![
+            record(r#"{"predictions": {"predictions": [[0.5], [0.2]]}}"#, 10.0),
+            record(r#"{"predictions": {"predictions": [[0.1]]}}"#, 20.0),
+        ];
+        let replayed = vec![
+            candidate(r#"{"predictions": {"predictions": [[0.7], [0.2]]}}"#, 5.0),
+            candidate(r#"{"predictions": {"predictions": [[0.1]]}}"#, 6.0),
+        ];
+
+        // Act
+        let report = compare(&records, replayed);
+
+        // Assert
+        assert_eq!(report.replayed, 2);
+        assert_eq!(report.failed, 0);
+        assert_eq!(report.outputs.len(), 1);
+        let output = &report.outputs[0];
+        assert_eq!(output.shape_mismatches, 0);
+        assert!((output.max_absolute_delta - 0.2).abs() < 1e-9);
+        assert_eq!(report.largest_deltas[0].record, 0);
+        assert_eq!(report.largest_deltas[0].row, 0);
+        assert_eq!(report.latency.recorded.p99_ms, 20.0);
+        assert_eq!(report.latency.candidate.p99_ms, 6.0)
+    }
+
+    #[test]
+    fn successfully_count_shape_mismatches_and_failures() {
+        // Arrange
+        let records = vec![
+            record(r#"{"predictions": {"predictions": [[0.5], [0.2]]}}"#, 10.0),
+            record(r#"{"predictions": {"predictions": [[0.1]]}}"#, 20.0),
+        ];
+        let replayed = vec![
+            candidate(
+                r#"{"predictions": {"predictions": [[0.5, 0.5], [0.2, 0.8]]}}"#,
+                5.0,
+            ),
+            Replayed {
+                output: Err(anyhow::anyhow!("model not found")),
+                latency_ms: 1.0,
+            },
+        ];
+
+        // Act
+        let report = compare(&records, replayed);
+
+        // Assert
+        assert_eq!(report.replayed, 1);
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.outputs[0].shape_mismatches, 1);
+        assert!(report.largest_deltas.is_empty())
+    }
+
+    #[test]
+    fn successfully_calculate_population_stability_index() {
+        let recorded: Vec<f64> = (0..100).map(|v| v as f64 / 100.0).collect();
+        let shifted: Vec<f64> = recorded.iter().map(|v| v + 0.5).collect();
+
+        // assert
+        assert!(population_stability_index(&recorded, &recorded).unwrap() < 1e-9);
+        assert!(population_stability_index(&recorded, &shifted).unwrap() > 0.25);
+        assert!(population_stability_index(&[], &shifted).is_none())
+    }
+}
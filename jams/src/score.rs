@@ -0,0 +1,404 @@
+use crate::cli::ScoreCommandArgs;
+use arrow::array::{Array, ArrayRef, AsArray, Float64Array, RecordBatch, StringArray};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Float64Type, Int64Type, Schema};
+use jams_core::manager::{Manager, ManagerBuilder};
+use jams_core::model::output::ModelOutput;
+use jams_core::model_store::local::filesystem::LocalModelStore;
+use jams_core::model_store::ModelStore;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use serde_json::{Map, Number, Value};
+use std::fs::File;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// The default number of rows read from the input file and scored at once.
+pub const DEFAULT_SCORE_BATCH_SIZE: usize = 10_000;
+
+/// Scores a Parquet file with a model of a local model store and writes the predictions to a Parquet file.
+///
+/// The input is read in batches of rows, which are scored in parallel on a rayon threadpool and written in
+/// the order of the input, so only `num_workers` batches are held in memory at once. Every column of the
+/// input except the `id_columns` is passed to the model as a feature: integer columns as ints, floating point
+/// columns as floats and string and boolean columns as strings. Null values are not supported.
+///
+/// Each output of the model becomes a `Float64` column named after the output, or `<output>_<i>` columns for
+/// outputs with several values per row, and each label output becomes a `Utf8` column. The `id_columns` are
+/// copied from the input so that the predictions can be joined back to it.
+///
+/// # Arguments
+///
+/// * `args` - The arguments of the `score` command.
+/// * `num_workers` - The default number of batches scored in parallel.
+///
+/// # Returns
+///
+/// * `Ok(())` - If every row was scored and written.
+/// * `Err(anyhow::Error)` - If the model could not be loaded, or a batch could not be read, scored or written.
+pub async fn run(args: ScoreCommandArgs, num_workers: usize) -> anyhow::Result<()> {
+    let model_store = LocalModelStore::new(args.model_dir.clone()).await?;
+    let manager = Arc::new(ManagerBuilder::new(Arc::new(ModelStore::Local(model_store))).build()?);
+    if manager.get_model_metadata(args.model.clone()).is_none() {
+        anyhow::bail!(
+            "No model exists for model name: {} in {} ❌",
+            args.model,
+            args.model_dir
+        )
+    }
+
+    // the rayon threadpool blocks, so keep it off the tokio workers
+    match tokio::task::spawn_blocking(move || score(&manager, &args, num_workers)).await {
+        Ok(result) => result,
+        Err(e) => anyhow::bail!("Failed to score input ❌: {}", e),
+    }
+}
+
+fn score(manager: &Manager, args: &ScoreCommandArgs, num_workers: usize) -> anyhow::Result<()> {
+    let batch_size = args.batch_size.unwrap_or(DEFAULT_SCORE_BATCH_SIZE);
+    if batch_size == 0 {
+        anyhow::bail!("Batch size must be at least 1 ❌")
+    }
+    let num_workers = args.num_workers.unwrap_or(num_workers).max(1);
+    let pool = ThreadPoolBuilder::new().num_threads(num_workers).build()?;
+
+    let input = match File::open(args.input.as_str()) {
+        Ok(input) => input,
+        Err(e) => anyhow::bail!("Failed to open {} ❌: {}", args.input, e),
+    };
+    let mut reader = ParquetRecordBatchReaderBuilder::try_new(input)?
+        .with_batch_size(batch_size)
+        .build()?;
+    println!(
+        "📊 Scoring {} with model {} in batches of {} rows",
+        args.input, args.model, batch_size
+    );
+
+    let start = Instant::now();
+    let mut writer: Option<ArrowWriter<File>> = None;
+    let mut scored_rows = 0;
+    loop {
+        let mut batches = Vec::with_capacity(num_workers);
+        for batch in reader.by_ref().take(num_workers) {
+            batches.push(batch?);
+        }
+        if batches.is_empty() {
+            break;
+        }
+
+        let scored: Vec<anyhow::Result<RecordBatch>> = pool.install(|| {
+            batches
+                .par_iter()
+                .map(|batch| score_batch(manager, args.model.as_str(), batch, &args.id_columns))
+                .collect()
+        });
+        for batch in scored {
+            let batch = batch?;
+            if writer.is_none() {
+                let output = match File::create(args.output.as_str()) {
+                    Ok(output) => output,
+                    Err(e) => anyhow::bail!("Failed to create {} ❌: {}", args.output, e),
+                };
+                writer = Some(ArrowWriter::try_new(output, batch.schema(), None)?);
+            }
+            if let Some(writer) = writer.as_mut() {
+                writer.write(&batch)?;
+            }
+            scored_rows += batch.num_rows();
+        }
+    }
+
+    match writer {
+        Some(writer) => {
+            writer.close()?;
+        }
+        None => anyhow::bail!("No rows found to score in {} ❌", args.input),
+    }
+    println!(
+        "✅ Scored {} rows in {:.2}s and wrote the predictions to {}",
+        scored_rows,
+        start.elapsed().as_secs_f64(),
+        args.output
+    );
+    Ok(())
+}
+
+/// Scores a batch of rows and returns the predictions along with the ID columns of the input.
+fn score_batch(
+    manager: &Manager,
+    model_name: &str,
+    batch: &RecordBatch,
+    id_columns: &[String],
+) -> anyhow::Result<RecordBatch> {
+    let input = batch_to_model_input(batch, id_columns)?;
+    let output = manager.predict(model_name.to_string(), input.as_str())?;
+    let output: ModelOutput = serde_json::from_str(output.as_str())?;
+
+    let mut fields = Vec::new();
+    let mut columns: Vec<ArrayRef> = Vec::new();
+    for name in id_columns {
+        match batch.column_by_name(name) {
+            Some(column) => {
+                fields.push(batch.schema().field_with_name(name)?.clone());
+                columns.push(Arc::clone(column));
+            }
+            None => anyhow::bail!("ID column {} is not in the input ❌", name),
+        }
+    }
+    for (name, column) in output_columns(output, batch.num_rows())? {
+        fields.push(Field::new(name, column.data_type().clone(), false));
+        columns.push(column);
+    }
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+/// Converts a batch of rows into a columnar model input, e.g. `{"age": [22.0, 38.0], "sex": ["male", "female"]}`.
+/// The ID columns are not features and are left out.
+fn batch_to_model_input(batch: &RecordBatch, id_columns: &[String]) -> anyhow::Result<String> {
+    let schema = batch.schema();
+    let mut input = Map::with_capacity(batch.num_columns());
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        if id_columns.contains(field.name()) {
+            continue;
+        }
+        if column.null_count() > 0 {
+            anyhow::bail!("Column {} has null values ❌", field.name())
+        }
+        let values: Vec<Value> = match column.data_type() {
+            DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64 => cast(column, &DataType::Int64)?
+                .as_primitive::<Int64Type>()
+                .values()
+                .iter()
+                .map(|value| Value::from(*value))
+                .collect(),
+            DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+                let floats = cast(column, &DataType::Float64)?;
+                let mut values = Vec::with_capacity(floats.len());
+                for value in floats.as_primitive::<Float64Type>().values().iter() {
+                    match Number::from_f64(*value) {
+                        Some(value) => values.push(Value::Number(value)),
+                        None => {
+                            anyhow::bail!("Column {} has NaN or infinite values ❌", field.name())
+                        }
+                    }
+                }
+                values
+            }
+            DataType::Utf8
+            | DataType::LargeUtf8
+            | DataType::Utf8View
+            | DataType::Boolean
+            | DataType::Dictionary(_, _) => cast(column, &DataType::Utf8)?
+                .as_string::<i32>()
+                .iter()
+                .map(|value| Value::from(value.unwrap_or_default()))
+                .collect(),
+            data_type => anyhow::bail!(
+                "Column {} has unsupported type {} ❌",
+                field.name(),
+                data_type
+            ),
+        };
+        input.insert(field.name().clone(), Value::Array(values));
+    }
+    Ok(serde_json::to_string(&input)?)
+}
+
+/// Converts the output of a model into columns, sorted by name so that every batch has the same schema.
+fn output_columns(output: ModelOutput, num_rows: usize) -> anyhow::Result<Vec<(String, ArrayRef)>> {
+    let mut columns: Vec<(String, ArrayRef)> = Vec::new();
+    for (name, rows) in output.predictions {
+        if rows.len() != num_rows {
+            anyhow::bail!(
+                "Output {} has {} rows but the input has {} rows ❌",
+                name,
+                rows.len(),
+                num_rows
+            )
+        }
+        let width = rows.first().map_or(1, |row| row.len());
+        if rows.iter().any(|row| row.len() != width) {
+            anyhow::bail!("Output {} has rows of different lengths ❌", name)
+        }
+        for i in 0..width {
+            let values: Float64Array = rows.iter().map(|row| row[i]).collect::<Vec<f64>>().into();
+            let column_name = match width {
+                1 => name.clone(),
+                _ => format!("{}_{}", name, i),
+            };
+            columns.push((column_name, Arc::new(values)));
+        }
+    }
+    for (name, labels) in output.labels {
+        if labels.len() != num_rows {
+            anyhow::bail!(
+                "Output {} has {} rows but the input has {} rows ❌",
+                name,
+                labels.len(),
+                num_rows
+            )
+        }
+        columns.push((name, Arc::new(StringArray::from(labels))));
+    }
+    columns.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{BooleanArray, Int32Array};
+    use std::collections::HashMap;
+
+    #[test]
+    fn successfully_convert_batch_to_model_input() {
+        // Arrange
+        let schema = Schema::new(vec![
+            Field::new("age", DataType::Float32, false),
+            Field::new("pclass", DataType::Int32, false),
+            Field::new("alone", DataType::Boolean, false),
+            Field::new("passenger_id", DataType::Utf8, false),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(arrow::array::Float32Array::from(vec![22.5, 38.0])),
+                Arc::new(Int32Array::from(vec![1, 3])),
+                Arc::new(BooleanArray::from(vec![true, false])),
+                Arc::new(StringArray::from(vec!["p1", "p2"])),
+            ],
+        )
+        .unwrap();
+
+        // Act
+        let input = batch_to_model_input(&batch, &["passenger_id".to_string()]).unwrap();
+
+        // Assert
+        let input: Value = serde_json::from_str(input.as_str()).unwrap();
+        assert_eq!(
+            input,
+            serde_json::json!({"age": [22.5, 38.0], "pclass": [1, 3], "alone": ["true", "false"]})
+        );
+    }
+
+    #[test]
+    fn fails_to_convert_batch_to_model_input_when_column_has_nulls() {
+        // Arrange
+        let schema = Schema::new(vec![Field::new("pclass", DataType::Int32, true)]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(Int32Array::from(vec![Some(1), None]))],
+        )
+        .unwrap();
+
+        // Act
+        let input = batch_to_model_input(&batch, &[]);
+
+        // Assert
+        assert!(input.is_err())
+    }
+
+    #[test]
+    fn successfully_convert_model_output_to_columns() {
+        // Arrange
+        let output = ModelOutput {
+            predictions: HashMap::from([
+                (
+                    "probabilities".to_string(),
+                    vec![vec![0.2, 0.8], vec![0.6, 0.4]],
+                ),
+                ("predictions".to_string(), vec![vec![1.0], vec![0.0]]),
+            ]),
+            labels: HashMap::from([(
+                "predicted_label".to_string(),
+                vec!["cat".to_string(), "dog".to_string()],
+            )]),
+            shapes: HashMap::new(),
+            texts: HashMap::new(),
+        };
+
+        // Act
+        let columns = output_columns(output, 2).unwrap();
+
+        // Assert
+        let names: Vec<&str> = columns.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "predicted_label",
+                "predictions",
+                "probabilities_0",
+                "probabilities_1"
+            ]
+        );
+        assert_eq!(
+            columns[3].1.as_primitive::<Float64Type>().values().to_vec(),
+            vec![0.8, 0.4]
+        );
+    }
+
+    #[tokio::test]
+    async fn successfully_score_parquet_file_with_local_model() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("jams-score-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.parquet");
+        let output = dir.join("output.parquet");
+
+        let mut fields = vec![Field::new("id", DataType::Utf8, false)];
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(StringArray::from(vec!["a", "b", "c"]))];
+        for i in 0..28 {
+            fields.push(Field::new(
+                format!("feature_{}", i),
+                DataType::Float64,
+                false,
+            ));
+            columns.push(Arc::new(Float64Array::from(vec![0.1, 0.5, 0.9])));
+        }
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap();
+        let mut writer =
+            ArrowWriter::try_new(File::create(&input).unwrap(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let args = ScoreCommandArgs {
+            model_dir: "../jams-core/tests/model_storage/model_store".to_string(),
+            model: "my_awesome_reg_model".to_string(),
+            input: input.to_str().unwrap().to_string(),
+            output: output.to_str().unwrap().to_string(),
+            batch_size: Some(2),
+            num_workers: Some(2),
+            id_columns: vec!["id".to_string()],
+        };
+
+        // Act
+        let result = run(args, 1).await;
+
+        // Assert
+        assert!(result.is_ok());
+        let scored: Vec<RecordBatch> =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(&output).unwrap())
+                .unwrap()
+                .build()
+                .unwrap()
+                .map(|batch| batch.unwrap())
+                .collect();
+        let rows: usize = scored.iter().map(|batch| batch.num_rows()).sum();
+        assert_eq!(rows, 3);
+        assert_eq!(scored[0].schema().field(0).name(), "id");
+        assert_eq!(scored[0].schema().field(1).name(), "predictions");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
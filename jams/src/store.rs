@@ -0,0 +1,192 @@
+use crate::cli::{StoreCheckCommandArgs, StoreManifestCommandArgs};
+#[cfg(feature = "azure")]
+use jams_core::model_store::check::check_azure_store;
+#[cfg(feature = "aws")]
+use jams_core::model_store::check::check_s3_store;
+use jams_core::model_store::check::{check_local_store, Checksums, StoreCheck};
+use jams_core::model_store::delta::write_manifest;
+#[cfg(feature = "azure")]
+use jams_serve::common::server::AZURE;
+use jams_serve::common::server::{Config, LOCAL};
+#[cfg(feature = "aws")]
+use jams_serve::common::server::{AWS, MINIO};
+use std::env;
+use std::path::Path;
+
+/// Dry-runs loading every model of the model store configured in a server config file and prints which
+/// models would load and why the others would fail, without starting the server.
+///
+/// The model store is resolved like the server does, i.e. the config file takes precedence over the
+/// `MODEL_STORE_DIR`, `S3_BUCKET_NAME` and `AZURE_STORAGE_CONTAINER_NAME` environment variables. If a checksums
+/// file is given, every tarball must match its digest and every tarball of the file must be in the model store.
+///
+/// # Errors
+///
+/// Returns an error if the config file or the checksums file is invalid, the model store cannot be listed or any
+/// model would fail to load, so that it can gate uploads of models in CI.
+pub async fn check(args: StoreCheckCommandArgs) -> anyhow::Result<()> {
+    let config = Config::parse(args.config)?;
+    let checksums = match args.checksums {
+        Some(path) => match std::fs::read_to_string(path.as_str()) {
+            Ok(contents) => Some(Checksums::parse(contents.as_str())?),
+            Err(e) => anyhow::bail!("Failed to read checksums file {} ❌: {}", path, e),
+        },
+        None => None,
+    };
+    let checksums = checksums.as_ref();
+    let check = match config.model_store.as_str() {
+        LOCAL => {
+            let model_dir = setting(config.model_dir, "MODEL_STORE_DIR", "model_dir")?;
+            check_local_store(model_dir.as_str(), checksums).await?
+        }
+        #[cfg(feature = "aws")]
+        AWS => {
+            let bucket_name = setting(config.s3_bucket_name, "S3_BUCKET_NAME", "s3_bucket_name")?;
+            check_s3_store(bucket_name, None, checksums).await?
+        }
+        #[cfg(feature = "aws")]
+        MINIO => {
+            let bucket_name = setting(config.s3_bucket_name, "S3_BUCKET_NAME", "s3_bucket_name")?;
+            check_s3_store(bucket_name, Some(true), checksums).await?
+        }
+        #[cfg(feature = "azure")]
+        AZURE => {
+            let container_name = setting(
+                config.azure_storage_container_name,
+                "AZURE_STORAGE_CONTAINER_NAME",
+                "azure_storage_container_name",
+            )?;
+            check_azure_store(container_name, checksums).await?
+        }
+        model_store => anyhow::bail!("Unsupported model store {} ❌", model_store),
+    };
+
+    println!("{}", render(&check));
+    if !check.passed() {
+        anyhow::bail!(
+            "{} of {} artifacts would fail to load ❌",
+            check.failed().len(),
+            check.artifacts.len()
+        )
+    }
+    Ok(())
+}
+
+/// Writes the block manifest of a tarball next to it, to be uploaded to the model store with the tarball so that
+/// servers with delta updates only download the blocks of the tarball which changed.
+///
+/// # Errors
+///
+/// Returns an error if the tarball cannot be read, the block size is too small or the manifest cannot be written.
+pub fn manifest(args: StoreManifestCommandArgs) -> anyhow::Result<()> {
+    let manifest_path = write_manifest(Path::new(args.tarball_path.as_str()), args.block_size)?;
+    println!(
+        "🧩 Wrote block manifest of {} to {}",
+        args.tarball_path,
+        manifest_path.display()
+    );
+    Ok(())
+}
+
+/// Returns the value of a config setting, falling back to its environment variable.
+fn setting(value: Option<String>, env_var: &str, name: &str) -> anyhow::Result<String> {
+    match value.or_else(|| env::var(env_var).ok()) {
+        Some(value) if !value.is_empty() => Ok(value),
+        _ => anyhow::bail!(
+            "{} not specified ❌. Either set the {} env variable or provide the value in the config file",
+            name,
+            env_var
+        ),
+    }
+}
+
+/// Renders the outcome of every artifact of a model store, one line per artifact.
+pub fn render(check: &StoreCheck) -> String {
+    let mut lines = vec![format!(
+        "🔍 Checked {} artifacts in {}",
+        check.artifacts.len(),
+        check.source_uri
+    )];
+    for artifact in check.artifacts.iter() {
+        let line = match (&artifact.error, &artifact.model_name, artifact.framework) {
+            (Some(error), _, _) => format!("❌ {}: {}", artifact.artifact, error),
+            (None, Some(model_name), Some(framework)) => format!(
+                "✅ {}: would load {} model {} (sha256 {})",
+                artifact.artifact,
+                framework,
+                model_name,
+                artifact.artifact_sha256.as_deref().unwrap_or("n/a")
+            ),
+            (None, _, _) => format!("✅ {}: would load", artifact.artifact),
+        };
+        lines.push(line);
+    }
+    lines.push(format!(
+        "{} of {} artifacts would load",
+        check.artifacts.len() - check.failed().len(),
+        check.artifacts.len()
+    ));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jams_core::model_store::check::ArtifactCheck;
+
+    #[test]
+    fn successfully_render_store_check() {
+        // Arrange
+        let check = StoreCheck {
+            source_uri: "s3://models".to_string(),
+            artifacts: vec![
+                ArtifactCheck {
+                    artifact: "catboost-titanic.tar.gz".to_string(),
+                    source_version: Some("v1".to_string()),
+                    model_name: Some("titanic".to_string()),
+                    framework: Some("catboost"),
+                    artifact_sha256: Some("abc123".to_string()),
+                    tarball_sha256: None,
+                    error: None,
+                },
+                ArtifactCheck {
+                    artifact: "README.md".to_string(),
+                    source_version: None,
+                    model_name: None,
+                    framework: None,
+                    artifact_sha256: None,
+                    tarball_sha256: None,
+                    error: Some(
+                        "README.md is not named <model_framework>-<model_name>.tar.gz".to_string(),
+                    ),
+                },
+            ],
+        };
+
+        // Act
+        let rendered = render(&check);
+
+        // Assert
+        assert_eq!(
+            rendered,
+            "🔍 Checked 2 artifacts in s3://models
+✅ catboost-titanic.tar.gz: would load catboost model titanic (sha256 abc123)
+❌ README.md: README.md is not named <model_framework>-<model_name>.tar.gz
+1 of 2 artifacts would load"
+        );
+    }
+
+    #[test]
+    fn fails_to_resolve_missing_setting() {
+        // Arrange
+        let env_var = "JAMS_STORE_CHECK_UNSET_SETTING";
+
+        // Act
+        let configured = setting(Some("models".to_string()), env_var, "model_dir");
+        let missing = setting(None, env_var, "model_dir");
+
+        // Assert
+        assert_eq!(configured.unwrap(), "models");
+        assert!(missing.unwrap_err().to_string().contains(env_var));
+    }
+}